@@ -7,6 +7,8 @@ private let logger = Logger(subsystem: "com.jasoncavinder.Helm", category: "core
 
 extension HelmCore {
     private static let scopedUpgradePlanPhaseTimeoutSeconds: TimeInterval = 300
+    /// Returned by the remote search triggers when the manager already completed the query recently.
+    private static let remoteSearchSkippedFreshTaskId: Int64 = -2
 
     func cancelTask(_ task: TaskItem) {
         guard task.isRunning, let taskId = Int64(task.id) else { return }
@@ -122,6 +124,7 @@ extension HelmCore {
             return
         }
 
+        let idempotencyKey = UUID().uuidString
         withTimeout(
             300,
             source: "core.actions",
@@ -133,7 +136,8 @@ extension HelmCore {
                 managerId: package.managerId,
                 packageName: package.name,
                 packageTargetName: package.mutationTargetPackageName,
-                version: package.mutationVersion
+                version: package.mutationVersion,
+                idempotencyKey: idempotencyKey
             ) { completion($0) }
         }, fallback: Int64(-1)) { [weak self] taskId in
             DispatchQueue.main.async {
@@ -163,6 +167,129 @@ extension HelmCore {
         }
     }
 
+    /// Switch the package back to the version it had before its last upgrade.
+    func rollbackPackage(_ package: PackageItem) {
+        guard canRollbackPackage(package) else { return }
+        guard let service = service() else {
+            logger.error("rollbackPackage(\(package.managerId):\(package.name)) failed: service unavailable")
+            recordLastError(
+                source: "core.actions",
+                action: "rollbackPackage.service_unavailable",
+                managerId: package.managerId,
+                taskType: "upgrade"
+            )
+            return
+        }
+        service.rollbackPackage(managerId: package.managerId, packageName: package.name) { [weak self] taskId in
+            DispatchQueue.main.async {
+                guard let self = self else { return }
+                if taskId < 0 {
+                    logger.error("rollbackPackage(\(package.managerId):\(package.name)) failed")
+                    self.recordLastError(
+                        source: "core.actions",
+                        action: "rollbackPackage.queue_failed",
+                        managerId: package.managerId,
+                        taskType: "upgrade"
+                    )
+                    return
+                }
+                self.fetchTasks()
+            }
+        }
+    }
+
+    /// Start, stop or restart a Homebrew service. `action` is `start`, `stop`
+    /// or `restart`; safe mode refuses stop and restart.
+    func setServiceState(name: String, action: String) {
+        guard let service = service() else {
+            logger.error("setServiceState(\(name), \(action)) failed: service unavailable")
+            recordLastError(
+                source: "core.actions",
+                action: "setServiceState.service_unavailable",
+                managerId: "homebrew_formula",
+                taskType: "configure"
+            )
+            return
+        }
+        service.setServiceState(serviceName: name, action: action) { [weak self] taskId in
+            DispatchQueue.main.async {
+                guard let self = self else { return }
+                if taskId < 0 {
+                    logger.error("setServiceState(\(name), \(action)) failed")
+                    self.recordLastError(
+                        source: "core.actions",
+                        action: "setServiceState.queue_failed",
+                        managerId: "homebrew_formula",
+                        taskType: "configure"
+                    )
+                    return
+                }
+                self.fetchTasks()
+            }
+        }
+    }
+
+    /// Reclaim a manager's cache space (`brew cleanup`, `npm cache clean`,
+    /// `pip cache purge`, Cargo's registry downloads).
+    func runCleanup(managerId: String) {
+        guard let service = service() else {
+            logger.error("runCleanup(\(managerId)) failed: service unavailable")
+            recordLastError(
+                source: "core.actions",
+                action: "runCleanup.service_unavailable",
+                managerId: managerId,
+                taskType: "configure"
+            )
+            return
+        }
+        service.runCleanup(managerId: managerId) { [weak self] taskId in
+            DispatchQueue.main.async {
+                guard let self = self else { return }
+                if taskId < 0 {
+                    logger.error("runCleanup(\(managerId)) failed")
+                    self.recordLastError(
+                        source: "core.actions",
+                        action: "runCleanup.queue_failed",
+                        managerId: managerId,
+                        taskType: "configure"
+                    )
+                    return
+                }
+                self.fetchTasks()
+            }
+        }
+    }
+
+    /// Measure how much disk each of a manager's installed packages occupies.
+    func measurePackageSizes(managerId: String) {
+        guard let service = service() else {
+            logger.error("measurePackageSizes(\(managerId)) failed: service unavailable")
+            recordLastError(
+                source: "core.actions",
+                action: "measurePackageSizes.service_unavailable",
+                managerId: managerId,
+                taskType: "refresh"
+            )
+            return
+        }
+        service.measurePackageSizes(managerId: managerId) { [weak self] taskId in
+            DispatchQueue.main.async {
+                guard let self = self else { return }
+                if taskId < 0 {
+                    logger.error("measurePackageSizes(\(managerId)) failed")
+                    self.recordLastError(
+                        source: "core.actions",
+                        action: "measurePackageSizes.queue_failed",
+                        managerId: managerId,
+                        taskType: "refresh"
+                    )
+                    return
+                }
+                self.fetchTasks()
+            }
+        }
+    }
+
     func retryFailedUpgradePlanSteps() {
         let failedStepIds = upgradePlanSteps
             .filter { projectedUpgradePlanStatus(for: $0).lowercased() == "failed" }
@@ -395,6 +522,7 @@ extension HelmCore {
             return
         }
 
+        let idempotencyKey = UUID().uuidString
         withTimeout(
             300,
             source: "core.actions",
@@ -406,7 +534,8 @@ extension HelmCore {
                 managerId: step.managerId,
                 packageName: step.packageName,
                 packageTargetName: nil,
-                version: nil
+                version: nil,
+                idempotencyKey: idempotencyKey
             ) { completion($0) }
         }, fallback: Int64(-1)) { [weak self] taskId in
             DispatchQueue.main.async {
@@ -425,6 +554,13 @@ extension HelmCore {
                     completion?(false)
                     return
                 }
+                if let previousTaskId = self.upgradePlanTaskProjectionByStepId[step.id]?.taskId {
+                    service.recordTaskAttempt(taskId: taskId, attemptOf: Int64(previousTaskId)) { linked in
+                        if !linked {
+                            logger.warning("recordTaskAttempt(\(taskId), \(previousTaskId)) returned false")
+                        }
+                    }
+                }
                 self.upgradePlanTaskProjectionByStepId[step.id] = UpgradePlanTaskProjection(
                     stepId: step.id,
                     taskId: UInt64(taskId),
@@ -487,6 +623,7 @@ extension HelmCore {
             return
         }
 
+        let idempotencyKey = UUID().uuidString
         withTimeout(
             300,
             source: "core.actions",
@@ -498,7 +635,8 @@ extension HelmCore {
                 managerId: targetPackage.managerId,
                 packageName: targetPackage.name,
                 packageTargetName: targetPackage.mutationTargetPackageName,
-                version: targetPackage.mutationVersion
+                version: targetPackage.mutationVersion,
+                idempotencyKey: idempotencyKey
             ) { completion($0) }
         }, fallback: Int64(-1)) { [weak self] taskId in
             DispatchQueue.main.async {
@@ -523,7 +661,8 @@ extension HelmCore {
         }
     }
 
-    func uninstallPackage(_ package: PackageItem) {
+    /// `moveToTrash` sends cask app bundles to the Trash instead of deleting them.
+    func uninstallPackage(_ package: PackageItem, moveToTrash: Bool = false) {
         guard canUninstallPackage(package), !uninstallActionPackageIds.contains(package.id) else { return }
 
         DispatchQueue.main.async {
@@ -544,6 +683,7 @@ extension HelmCore {
             return
         }
 
+        let idempotencyKey = UUID().uuidString
         withTimeout(
             300,
             source: "core.actions",
@@ -555,7 +695,9 @@ extension HelmCore {
                 managerId: package.managerId,
                 packageName: package.name,
                 packageTargetName: package.mutationTargetPackageName,
-                version: package.mutationVersion
+                version: package.mutationVersion,
+                moveToTrash: moveToTrash,
+                idempotencyKey: idempotencyKey
             ) { completion($0) }
         }, fallback: Int64(-1)) { [weak self] taskId in
             DispatchQueue.main.async {
@@ -616,6 +758,46 @@ extension HelmCore {
         }
     }
 
+    func pinPackage(_ package: PackageItem, constraint: String) {
+        guard canPinPackage(package), !pinActionPackageIds.contains(package.id) else { return }
+        DispatchQueue.main.async {
+            self.pinActionPackageIds.insert(package.id)
+        }
+        guard let service = service() else {
+            logger.error("pinPackageWithConstraint(\(package.managerId):\(package.name)) failed: service unavailable")
+            recordLastError(
+                source: "core.actions",
+                action: "pinPackageWithConstraint.service_unavailable",
+                managerId: package.managerId,
+                taskType: "pin"
+            )
+            DispatchQueue.main.async {
+                self.pinActionPackageIds.remove(package.id)
+            }
+            return
+        }
+        service.pinPackageWithConstraint(
+            managerId: package.managerId,
+            packageName: package.name,
+            constraint: constraint
+        ) { [weak self] success in
+            DispatchQueue.main.async {
+                self?.pinActionPackageIds.remove(package.id)
+                if success {
+                    self?.schedulePinnedStateReconciliation()
+                } else {
+                    logger.error("pinPackageWithConstraint(\(package.managerId):\(package.name)) failed")
+                    self?.recordLastError(
+                        source: "core.actions",
+                        action: "pinPackageWithConstraint",
+                        managerId: package.managerId,
+                        taskType: "pin"
+                    )
+                }
+            }
+        }
+    }
+
     func unpinPackage(_ package: PackageItem) {
         guard canPinPackage(package), !pinActionPackageIds.contains(package.id) else { return }
         DispatchQueue.main.async {
@@ -1214,6 +1396,45 @@ extension HelmCore {
         }
     }
 
+    func setManagerProcessQos(
+        _ managerId: String,
+        qos: String?,
+        completion: ((Bool) -> Void)? = nil
+    ) {
+        if isManagerUninstalling(managerId) {
+            DispatchQueue.main.async {
+                completion?(false)
+            }
+            return
+        }
+        guard let service = service() else {
+            DispatchQueue.main.async {
+                completion?(false)
+            }
+            return
+        }
+        service.setManagerProcessQos(managerId: managerId, qos: qos) { [weak self] success in
+            guard let self else { return }
+            if !success {
+                logger.error("setManagerProcessQos(\(managerId), \(qos ?? "nil")) failed")
+                self.recordLastError(
+                    source: "core.actions",
+                    action: "setManagerProcessQos",
+                    managerId: managerId,
+                    taskType: "settings"
+                )
+                DispatchQueue.main.async {
+                    completion?(false)
+                }
+                return
+            }
+            self.fetchManagerStatus()
+            DispatchQueue.main.async {
+                completion?(true)
+            }
+        }
+    }
+
     func installManager(
         _ managerId: String,
         options: ManagerInstallActionOptions? = nil
@@ -1827,7 +2048,7 @@ extension HelmCore {
                     guard let self = self else { return }
                     if taskId >= 0 {
                         self.activeRemoteSearchTaskIds.insert(taskId)
-                    } else {
+                    } else if taskId != Self.remoteSearchSkippedFreshTaskId {
                         logger.warning("triggerRemoteSearchForManager(\(managerId)) returned error")
                         self.recordLastError(
                             source: "core.actions",
@@ -1966,7 +2187,7 @@ extension HelmCore {
                         self.descriptionLookupTaskIdsByPackage[package.id] = taskIds
                         self.activeRemoteSearchTaskIds.insert(taskId)
                         tracker.queuedTaskCount += 1
-                    } else {
+                    } else if taskId != Self.remoteSearchSkippedFreshTaskId {
                         self.recordLastError(
                             source: "core.actions",
                             action: "ensurePackageDescription.triggerRemoteSearchForManager",
@@ -352,6 +352,13 @@ extension HelmCore {
             && !isManagerUninstalling(package.managerId)
     }
 
+    func canRollbackPackage(_ package: PackageItem) -> Bool {
+        return (managerStatuses[package.managerId]?.supportsPackageRollback ?? false)
+            && !package.pinned
+            && isManagerEnabled(package.managerId)
+            && !isManagerUninstalling(package.managerId)
+    }
+
     func canInstallPackage(_ package: PackageItem, includeAlternates: Bool = true) -> Bool {
         if canInstallPackageDirect(package) {
             return true
@@ -1105,7 +1112,9 @@ extension HelmCore {
             reason = .inconsistent
         } else if latestStatus == "queued" || latestStatus == "running" {
             reason = .inProgress
-        } else if latestStatus == "failed" || latestStatus == "cancelled" {
+        } else if latestStatus == "failed" || latestStatus == "cancelled"
+            || status?.detectionOutcome == "probe_failed"
+        {
             reason = .failed
         } else if isDetected {
             reason = .detected
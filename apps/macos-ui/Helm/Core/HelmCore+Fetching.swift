@@ -146,7 +146,8 @@ extension HelmCore {
                         managerId: task.manager,
                         taskType: task.taskType,
                         labelKey: task.labelKey,
-                        labelArgs: task.labelArgs
+                        labelArgs: task.labelArgs,
+                        progress: task.progress
                     )
                 }
                 self.syncManagerOperations(from: coreTasks)
@@ -348,14 +349,14 @@ extension HelmCore {
             guard let self = self,
                   let jsonString = jsonString,
                   let data = jsonString.data(using: .utf8),
-                  let results: [CoreSearchResult] = self.decodeCorePayload(
-                    [CoreSearchResult].self,
+                  let results = self.decodeCorePayload(
+                    CoreSearchPayload.self,
                     from: data,
                     decodeContext: "fetchSearchResults",
                     source: "core.fetching",
                     action: "searchLocal.decode",
                     taskType: "search"
-                  ) else {
+                  )?.results else {
                 DispatchQueue.main.async {
                     guard let self, self.localSearchRequestGeneration == requestGeneration else { return }
                     self.searchResults = []
@@ -468,14 +469,14 @@ extension HelmCore {
             guard let self = self,
                   let jsonString = jsonString,
                   let data = jsonString.data(using: .utf8),
-                  let results: [CoreSearchResult] = self.decodeCorePayload(
-                    [CoreSearchResult].self,
+                  let results = self.decodeCorePayload(
+                    CoreSearchPayload.self,
                     from: data,
                     decodeContext: "refreshCachedAvailablePackages",
                     source: "core.fetching",
                     action: "refreshCachedAvailablePackages.decode",
                     taskType: "search"
-                  ) else { return }
+                  )?.results else { return }
 
             DispatchQueue.main.async {
                 self.rebuildPackageDescriptionSummaryIndex(from: results)
@@ -683,14 +684,14 @@ extension HelmCore {
 
             guard let jsonString,
                   let data = jsonString.data(using: .utf8),
-                  let results: [CoreSearchResult] = self.decodeCorePayload(
-                    [CoreSearchResult].self,
+                  let results = self.decodeCorePayload(
+                    CoreSearchPayload.self,
                     from: data,
                     decodeContext: "refreshPackageDescriptionSummaryFromLocalCache",
                     source: "core.fetching",
                     action: "refreshPackageDescriptionSummaryFromLocalCache.decode",
                     taskType: "search"
-                  ) else {
+                  )?.results else {
                 DispatchQueue.main.async {
                     if self.hasPackageDescriptionSummary(packageId: package.id) {
                         self.packageDescriptionUnavailableIds.remove(package.id)
@@ -940,6 +941,53 @@ extension HelmCore {
         }
     }
 
+    /// Live output chunks after `offset`; pass the returned `nextOffset` on the
+    /// next poll to tail a running task.
+    func fetchTaskOutputSince(
+        taskId: String,
+        offset: UInt64,
+        completion: @escaping (CoreTaskOutputChunks?) -> Void
+    ) {
+        guard let numericTaskId = Int64(taskId) else {
+            completion(nil)
+            return
+        }
+        guard let svc = service() else {
+            recordLastError(
+                source: "core.fetching",
+                action: "getTaskOutputSince.service_unavailable",
+                taskType: "diagnostics"
+            )
+            completion(nil)
+            return
+        }
+
+        withTimeout(
+            30,
+            source: "core.fetching",
+            action: "getTaskOutputSince",
+            taskType: "diagnostics",
+            operation: { callback in
+            svc.getTaskOutputSince(taskId: numericTaskId, offset: Int64(clamping: offset)) { callback($0) }
+        }) { [weak self] jsonString in
+            guard let self = self,
+                  let jsonString = jsonString,
+                  let data = jsonString.data(using: .utf8),
+                  let chunks: CoreTaskOutputChunks = self.decodeCorePayload(
+                    CoreTaskOutputChunks.self,
+                    from: data,
+                    decodeContext: "fetchTaskOutputSince",
+                    source: "core.fetching",
+                    action: "getTaskOutputSince.decode",
+                    taskType: "diagnostics"
+                  ) else {
+                completion(nil)
+                return
+            }
+            completion(chunks)
+        }
+    }
+
     func fetchTaskLogs(taskId: String, limit: Int, completion: @escaping ([CoreTaskLogRecord]?) -> Void) {
         guard let numericTaskId = Int64(taskId) else {
             completion(nil)
@@ -427,6 +427,41 @@ extension HelmCore {
         }
     }
 
+    // MARK: - Offline Mode
+
+    func fetchOfflineMode() {
+        service()?.getOfflineMode { [weak self] enabled in
+            DispatchQueue.main.async {
+                self?.offlineModeEnabled = enabled
+            }
+        }
+    }
+
+    func setOfflineMode(_ enabled: Bool) {
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "setOfflineMode.service_unavailable",
+                taskType: "settings"
+            )
+            return
+        }
+        service.setOfflineMode(enabled: enabled) { [weak self] success in
+            DispatchQueue.main.async {
+                if success {
+                    self?.offlineModeEnabled = enabled
+                } else {
+                    logger.error("setOfflineMode(\(enabled)) failed")
+                    self?.recordLastError(
+                        source: "core.settings",
+                        action: "setOfflineMode",
+                        taskType: "settings"
+                    )
+                }
+            }
+        }
+    }
+
     // MARK: - Keg Cleanup
 
     func fetchHomebrewKegAutoCleanup() {
@@ -742,15 +777,9 @@ extension HelmCore {
 
     // MARK: - Upgrade All
 
-    func upgradeAll(includePinned: Bool = false, allowOsUpdates: Bool = false) {
-        DispatchQueue.main.async {
-            self.upgradePlanIncludePinned = includePinned
-            self.upgradePlanAllowOsUpdates = allowOsUpdates
-            for step in self.upgradePlanSteps {
-                self.upgradePlanTaskProjectionByStepId.removeValue(forKey: step.id)
-            }
-            self.rebuildUpgradePlanFailureGroups()
-        }
+    /// Upgrade everything except OS updates, which need `upgradeAll(confirmation:)`.
+    func upgradeAll(includePinned: Bool = false) {
+        resetUpgradePlanProjection(includePinned: includePinned, allowOsUpdates: false)
         guard let service = service() else {
             recordLastError(
                 source: "core.settings",
@@ -759,9 +788,9 @@ extension HelmCore {
             )
             return
         }
-        service.upgradeAll(includePinned: includePinned, allowOsUpdates: allowOsUpdates) { success in
+        service.upgradeAll(includePinned: includePinned, allowOsUpdates: false) { success in
             if !success {
-                logger.error("upgradeAll(includePinned: \(includePinned), allowOsUpdates: \(allowOsUpdates)) failed")
+                logger.error("upgradeAll(includePinned: \(includePinned)) failed")
                 self.recordLastError(
                     source: "core.settings",
                     action: "upgradeAll",
@@ -771,6 +800,456 @@ extension HelmCore {
         }
     }
 
+    /// Per-manager outcomes of the latest refresh-all run.
+    func fetchRefreshSummary(completion: @escaping (CoreRefreshSummary?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.getRefreshSummary { [weak self] jsonString in
+            let summary = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        CoreRefreshSummary.self,
+                        from: $0,
+                        decodeContext: "getRefreshSummary",
+                        action: "getRefreshSummary.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(summary)
+            }
+        }
+    }
+
+    /// Step counts, running steps, and ETA of the latest upgrade-all batch.
+    func fetchBulkOperationProgress(completion: @escaping (CoreBulkOperationProgress?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.getBulkOperationProgress { [weak self] jsonString in
+            let progress = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        CoreBulkOperationProgress.self,
+                        from: $0,
+                        decodeContext: "getBulkOperationProgress",
+                        action: "getBulkOperationProgress.decode",
+                        taskType: "upgrade"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(progress)
+            }
+        }
+    }
+
+    /// Files an installed package owns; `limit` of 0 returns every path.
+    func fetchPackageFiles(
+        managerId: String,
+        packageName: String,
+        limit: Int64 = 0,
+        completion: @escaping (CorePackageFiles?) -> Void
+    ) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.listPackageFiles(managerId: managerId, packageName: packageName, limit: limit) { [weak self] jsonString in
+            let files = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        CorePackageFiles.self,
+                        from: $0,
+                        decodeContext: "listPackageFiles",
+                        action: "listPackageFiles.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(files)
+            }
+        }
+    }
+
+    /// Dependencies and dependents of an installed package, for uninstall warnings.
+    func fetchPackageDependencies(
+        managerId: String,
+        packageName: String,
+        completion: @escaping (CorePackageDependencies?) -> Void
+    ) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.listPackageDependencies(managerId: managerId, packageName: packageName) { [weak self] jsonString in
+            let report = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        CorePackageDependencies.self,
+                        from: $0,
+                        decodeContext: "listPackageDependencies",
+                        action: "listPackageDependencies.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(report)
+            }
+        }
+    }
+
+    /// Packages installed only as dependencies that nothing requested still needs.
+    func fetchOrphanedPackages(completion: @escaping ([CoreOrphanedPackage]?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.listOrphanedPackages { [weak self] jsonString in
+            let orphans = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreOrphanedPackage].self,
+                        from: $0,
+                        decodeContext: "listOrphanedPackages",
+                        action: "listOrphanedPackages.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(orphans)
+            }
+        }
+    }
+
+    /// Homebrew background services and their launchd state.
+    func fetchServices(completion: @escaping ([CoreService]?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.listServices { [weak self] jsonString in
+            let services = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreService].self,
+                        from: $0,
+                        decodeContext: "listServices",
+                        action: "listServices.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(services)
+            }
+        }
+    }
+
+    /// Cache space each manager's cleanup would free; nothing is removed.
+    func fetchReclaimableSpace(completion: @escaping ([CoreReclaimableSpace]?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.getReclaimableSpace { [weak self] jsonString in
+            let reclaimable = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreReclaimableSpace].self,
+                        from: $0,
+                        decodeContext: "getReclaimableSpace",
+                        action: "getReclaimableSpace.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(reclaimable)
+            }
+        }
+    }
+
+    /// Queue uninstall tasks for unpinned orphans; reports the queued tasks.
+    func cleanupOrphans(completion: @escaping ([CoreQueuedOrphanUninstall]?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.cleanupOrphans { [weak self] jsonString in
+            let queued = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreQueuedOrphanUninstall].self,
+                        from: $0,
+                        decodeContext: "cleanupOrphans",
+                        action: "cleanupOrphans.decode",
+                        taskType: "uninstall"
+                    )
+                }
+            if queued?.isEmpty == false {
+                self?.fetchTasks()
+            }
+            DispatchQueue.main.async {
+                completion(queued)
+            }
+        }
+    }
+
+    /// Description, homepage, license, size, and dependencies of a package,
+    /// served from the core's day-long metadata cache when fresh.
+    func fetchPackageInfo(
+        managerId: String,
+        packageName: String,
+        completion: @escaping (CorePackageInfo?) -> Void
+    ) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.getPackageInfo(managerId: managerId, packageName: packageName) { [weak self] jsonString in
+            let info = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        CorePackageInfo.self,
+                        from: $0,
+                        decodeContext: "getPackageInfo",
+                        action: "getPackageInfo.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(info)
+            }
+        }
+    }
+
+    /// Packages an app name, display name, or bundle id refers to, such as the
+    /// `visual-studio-code` cask for "Visual Studio Code.app".
+    func resolvePackageAlias(
+        _ name: String,
+        completion: @escaping ([CorePackageAliasMatch]) -> Void
+    ) {
+        guard let service = service() else {
+            completion([])
+            return
+        }
+        service.resolvePackageAlias(name: name) { [weak self] jsonString in
+            let matches = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CorePackageAliasMatch].self,
+                        from: $0,
+                        decodeContext: "resolvePackageAlias",
+                        action: "resolvePackageAlias.decode",
+                        taskType: "refresh"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(matches ?? [])
+            }
+        }
+    }
+
+    /// Let the core refresh the manager on screen first. `nil` clears the focus.
+    func sendViewHint(managerId: String?) {
+        var hint: [String: String] = [:]
+        hint["manager"] = managerId
+        guard let service = service(),
+              let data = try? JSONSerialization.data(withJSONObject: hint),
+              let json = String(data: data, encoding: .utf8) else {
+            return
+        }
+        service.setViewHint(json: json) { success in
+            if !success {
+                logger.warning("setViewHint failed")
+            }
+        }
+    }
+
+    /// Background refresh cadence per manager, ordered by manager id.
+    func fetchRefreshSchedules(completion: @escaping ([CoreRefreshSchedule]) -> Void) {
+        guard let service = service() else {
+            completion([])
+            return
+        }
+        service.getRefreshSchedules { [weak self] jsonString in
+            let schedules = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreRefreshSchedule].self,
+                        from: $0,
+                        decodeContext: "getRefreshSchedules",
+                        action: "getRefreshSchedules.decode",
+                        taskType: "settings"
+                    )
+                } ?? []
+            DispatchQueue.main.async {
+                completion(schedules)
+            }
+        }
+    }
+
+    /// Refresh a manager in the background every `intervalSecs` seconds; 0 turns it off.
+    func setRefreshSchedule(managerId: String, intervalSecs: Int64, completion: ((Bool) -> Void)? = nil) {
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "setRefreshSchedule.service_unavailable",
+                taskType: "settings"
+            )
+            completion?(false)
+            return
+        }
+        service.setRefreshSchedule(managerId: managerId, intervalSecs: intervalSecs) { [weak self] success in
+            DispatchQueue.main.async {
+                if !success {
+                    logger.error("setRefreshSchedule(\(managerId), \(intervalSecs)) failed")
+                    self?.recordLastError(
+                        source: "core.settings",
+                        action: "setRefreshSchedule",
+                        taskType: "settings"
+                    )
+                }
+                completion?(success)
+            }
+        }
+    }
+
+    /// Lift a manager's refresh-failure quarantine and refresh it now.
+    func retryQuarantinedManager(_ managerId: String) {
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "retryQuarantinedManager.service_unavailable",
+                managerId: managerId,
+                taskType: "refresh"
+            )
+            return
+        }
+        service.retryQuarantinedManager(managerId: managerId) { [weak self] success in
+            DispatchQueue.main.async {
+                if success {
+                    self?.fetchManagerStatus()
+                } else {
+                    logger.error("retryQuarantinedManager(\(managerId)) failed")
+                    self?.recordLastError(
+                        source: "core.settings",
+                        action: "retryQuarantinedManager",
+                        managerId: managerId,
+                        taskType: "refresh"
+                    )
+                }
+            }
+        }
+    }
+
+    /// Profiles with their own inventory database, `default` first.
+    func listProfiles(completion: @escaping ([CoreProfile]) -> Void) {
+        guard let service = service() else {
+            completion([])
+            return
+        }
+        service.listProfiles { [weak self] jsonString in
+            let profiles = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self?.decodeSettingsPayload(
+                        [CoreProfile].self,
+                        from: $0,
+                        decodeContext: "listProfiles",
+                        action: "listProfiles.decode",
+                        taskType: "settings"
+                    )
+                } ?? []
+            DispatchQueue.main.async {
+                completion(profiles)
+            }
+        }
+    }
+
+    /// Ask the core for the exact upgrade-all plan, OS updates included, and a
+    /// token that runs only that plan.
+    func requestUpgradeAllConfirmation(
+        includePinned: Bool = false,
+        completion: @escaping (CoreConfirmationToken?) -> Void
+    ) {
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "requestUpgradeAllConfirmation.service_unavailable",
+                taskType: "upgrade"
+            )
+            completion(nil)
+            return
+        }
+        service.requestUpgradeAllConfirmation(includePinned: includePinned) { [weak self] jsonString in
+            guard let self = self else { return }
+            let confirmation = jsonString
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap {
+                    self.decodeSettingsPayload(
+                        CoreConfirmationToken.self,
+                        from: $0,
+                        decodeContext: "requestUpgradeAllConfirmation",
+                        action: "requestUpgradeAllConfirmation.decode",
+                        taskType: "upgrade"
+                    )
+                }
+            DispatchQueue.main.async {
+                completion(confirmation)
+            }
+        }
+    }
+
+    /// Run the plan the user confirmed. The core refuses if the plan changed.
+    func upgradeAll(confirmation: CoreConfirmationToken) {
+        resetUpgradePlanProjection(
+            includePinned: confirmation.plan.includePinned,
+            allowOsUpdates: confirmation.plan.osUpdateCount > 0
+        )
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "upgradeAllConfirmed.service_unavailable",
+                taskType: "upgrade"
+            )
+            return
+        }
+        service.upgradeAllConfirmed(token: confirmation.token) { success in
+            if !success {
+                logger.error("upgradeAllConfirmed failed")
+                self.recordLastError(
+                    source: "core.settings",
+                    action: "upgradeAllConfirmed",
+                    taskType: "upgrade"
+                )
+            }
+        }
+    }
+
+    private func resetUpgradePlanProjection(includePinned: Bool, allowOsUpdates: Bool) {
+        DispatchQueue.main.async {
+            self.upgradePlanIncludePinned = includePinned
+            self.upgradePlanAllowOsUpdates = allowOsUpdates
+            for step in self.upgradePlanSteps {
+                self.upgradePlanTaskProjectionByStepId.removeValue(forKey: step.id)
+            }
+            self.rebuildUpgradePlanFailureGroups()
+        }
+    }
+
     func refreshUpgradePlan(includePinned: Bool = false, allowOsUpdates: Bool = false) {
         guard let service = service() else {
             recordLastError(
@@ -811,6 +1290,18 @@ extension HelmCore {
         upgradePlanTaskProjectionByStepId[step.id]?.taskId
     }
 
+    /// Estimated wall-clock time of the whole plan; managers run in parallel,
+    /// so this is the latest step finish. Nil when any step lacks an estimate.
+    var upgradePlanEstimatedDurationMs: UInt64? {
+        guard !upgradePlanSteps.isEmpty else { return nil }
+        var total: UInt64 = 0
+        for step in upgradePlanSteps {
+            guard let finish = step.estimatedFinishMs else { return nil }
+            total = max(total, finish)
+        }
+        return total
+    }
+
     func localizedUpgradePlanStatus(_ rawStatus: String) -> String {
         switch rawStatus.lowercased() {
         case "queued":
@@ -970,6 +1461,11 @@ extension HelmCore {
 
         switch managerId {
         case "homebrew_formula":
+            if taskType == "configure",
+               let serviceArg = normalizedCommandArg(task.labelArgs?["service"]),
+               let actionArg = normalizedCommandArg(task.labelArgs?["action"]) {
+                return "brew services \(actionArg) \(serviceArg)"
+            }
             return commandForHomebrewFormula(taskType: taskType, packageArg: packageArg)
         case "homebrew_cask":
             return commandForHomebrewCask(taskType: taskType, packageArg: packageArg)
@@ -1027,6 +1523,10 @@ extension HelmCore {
             return taskType == "upgrade" ? "softwareupdate --install --all" : nil
         case "mise":
             return commandForMise(taskType: taskType, packageArg: packageArg)
+        case "asdf":
+            let pluginArg = normalizedCommandArg(task.labelArgs?["plugin"])
+            guard taskType == "upgrade", let pluginArg else { return nil }
+            return "asdf install \(pluginArg) latest"
         default:
             return nil
         }
@@ -1175,6 +1675,8 @@ extension HelmCore {
             return L10n.Service.Task.Label.upgradeHomebrew.localized(with: ["package": package.name])
         case "mise":
             return L10n.Service.Task.Label.upgradeMise.localized(with: ["package": package.name])
+        case "asdf":
+            return L10n.Service.Task.Label.upgradeAsdf.localized(with: ["plugin": package.name])
         case "rustup":
             return L10n.Service.Task.Label.upgradeRustupToolchain.localized(with: ["toolchain": package.name])
         default:
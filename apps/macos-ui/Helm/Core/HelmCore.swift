@@ -208,6 +208,7 @@ struct CoreInstalledPackage: Codable {
     let installedVersion: String?
     let pinned: Bool
     let runtimeState: PackageRuntimeState?
+    let installedSizeBytes: UInt64?
 }
 
 struct CoreOutdatedPackage: Codable {
@@ -228,6 +229,9 @@ struct CoreTaskRecord: Codable {
     let label: String?
     let labelKey: String?
     let labelArgs: [String: String]?
+    let progress: Int?
+    let attemptOf: UInt64?
+    let dependsOn: [UInt64]?
 }
 
 struct CoreTaskOutputRecord: Codable {
@@ -247,6 +251,20 @@ struct CoreTaskOutputRecord: Codable {
     let stderr: String?
 }
 
+struct CoreTaskOutputChunk: Codable, Equatable {
+    let sequence: UInt64
+    let stream: String
+    let text: String
+}
+
+struct CoreTaskOutputChunks: Codable, Equatable {
+    let taskId: UInt64
+    let chunks: [CoreTaskOutputChunk]
+    let nextOffset: UInt64
+    let missedChunks: Bool
+    let finished: Bool
+}
+
 struct CoreTaskLogRecord: Codable, Identifiable {
     let id: UInt64
     let taskId: UInt64
@@ -308,6 +326,21 @@ struct CoreSearchResult: Codable {
     let version: String?
     let summary: String?
     let sourceManager: String
+    let downloads: Int64?
+    let popularityRank: Int?
+    let installed: Bool?
+    let relevance: Int?
+}
+
+struct CoreSearchCompletion: Codable {
+    let manager: String
+    let resultCount: Int
+    let completedAtUnix: Int64
+}
+
+struct CoreSearchPayload: Codable {
+    let results: [CoreSearchResult]
+    let completions: [CoreSearchCompletion]
 }
 
 struct CoreRustupToolchainDetailEntry: Codable, Hashable, Identifiable {
@@ -335,10 +368,169 @@ struct CoreUpgradePlanStep: Codable, Identifiable, Equatable {
     let reasonLabelKey: String
     let reasonLabelArgs: [String: String]
     let status: String
+    let requiresElevation: Bool?
+    let estimatedDurationMs: UInt64?
+    let estimatedFinishMs: UInt64?
 
     var id: String { stepId }
 }
 
+struct CoreConfirmedUpgrade: Codable, Equatable {
+    let manager: String
+    let packageName: String
+    let oldVersion: String?
+    let newVersion: String
+}
+
+struct CoreConfirmationPlan: Codable, Equatable {
+    let operation: String
+    let includePinned: Bool
+    let upgrades: [CoreConfirmedUpgrade]
+    let osUpdateCount: Int
+    let restartRequired: Bool
+}
+
+struct CoreServiceError: Codable, Equatable {
+    let key: String
+    let kind: String?
+    let manager: String?
+    let task: String?
+    let action: String?
+    let message: String?
+    let exitCode: Int32?
+    let stderrExcerpt: String?
+    let retryable: Bool?
+}
+
+struct CoreConfirmationToken: Codable, Equatable {
+    let token: String
+    let expiresAtUnix: Int64
+    let plan: CoreConfirmationPlan
+}
+
+struct CoreManagerRefreshResult: Codable, Equatable {
+    let managerId: String
+    let succeeded: Bool
+    let errorKey: String?
+    let errorCode: String?
+    let errorMessage: String?
+    let durationMs: UInt64
+    let finishedAtUnix: Int64
+}
+
+struct CoreRefreshSummary: Codable, Equatable {
+    let runStartedAtUnix: Int64?
+    let succeeded: Int
+    let failed: Int
+    let managers: [CoreManagerRefreshResult]
+    let meteredNetwork: Bool
+    /// Managers whose bandwidth-heavy refresh work was skipped while metered.
+    let suppressedOnMeteredNetwork: [String]
+    /// Quarantined managers the run left out until their next probe.
+    let skippedWhileQuarantined: [String]
+}
+
+struct CoreBulkOperationRunningStep: Codable, Equatable {
+    let taskId: UInt64
+    let managerId: String
+    let labelKey: String?
+    let labelArgs: [String: String]
+}
+
+struct CoreBulkOperationProgress: Codable, Equatable {
+    let startedAtUnix: Int64?
+    let totalSteps: Int
+    let completed: Int
+    let failed: Int
+    let cancelled: Int
+    let running: Int
+    let queued: Int
+    let fractionComplete: Double
+    let runningSteps: [CoreBulkOperationRunningStep]
+    let etaSeconds: UInt64?
+}
+
+struct CorePackageFiles: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+    let files: [String]
+    let total: Int
+    let truncated: Bool
+}
+
+struct CorePackageDependencies: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+    let dependencies: [String]
+    let dependents: [String]
+    let orphanedDependencies: [String]
+}
+
+struct CoreOrphanedPackage: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+    let pinned: Bool
+}
+
+struct CoreQueuedOrphanUninstall: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+    let taskId: Int64
+}
+
+struct CoreService: Codable, Equatable {
+    let name: String
+    let status: String
+    let user: String?
+    let file: String?
+    let exitCode: Int64?
+}
+
+struct CoreReclaimableSpace: Codable, Equatable {
+    let managerId: String
+    let reclaimableBytes: UInt64
+}
+
+struct CorePackageInfo: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+    let description: String?
+    let homepage: String?
+    let license: String?
+    let installedSizeBytes: Int64?
+    let dependencies: [String]
+    let aliases: [CorePackageAlias]?
+    let fetchedAtUnix: Int64
+    let stale: Bool
+}
+
+struct CorePackageAlias: Codable, Equatable {
+    /// `display_name`, `app_bundle`, or `bundle_id`.
+    let kind: String
+    let value: String
+}
+
+struct CorePackageAliasMatch: Codable, Equatable {
+    let managerId: String
+    let packageName: String
+}
+
+struct CoreRefreshSchedule: Codable, Equatable {
+    let managerId: String
+    let intervalSecs: Int64
+    let lastRefreshedUnix: Int64?
+    let nextRunUnix: Int64
+    let overdue: Bool
+}
+
+struct CoreProfile: Codable, Equatable, Identifiable {
+    let name: String
+    let databasePath: String
+    let active: Bool
+
+    var id: String { name }
+}
+
 struct UpgradePlanTaskProjection {
     let stepId: String
     let taskId: UInt64
@@ -379,6 +571,8 @@ enum KegPolicySelection {
 struct ManagerStatus: Codable {
     let managerId: String
     let detected: Bool
+    let detectionOutcome: String?
+    let detectionErrorKey: String?
     let version: String?
     let executablePath: String?
     let executablePaths: [String]?
@@ -388,6 +582,7 @@ struct ManagerStatus: Codable {
     let installMethodOptions: [ManagerInstallMethodStatus]?
     let timeoutHardSeconds: Int?
     let timeoutIdleSeconds: Int?
+    let processQos: String?
     let enabled: Bool
     let isImplemented: Bool
     let isOptional: Bool
@@ -396,6 +591,7 @@ struct ManagerStatus: Codable {
     let supportsPackageInstall: Bool
     let supportsPackageUninstall: Bool
     let supportsPackageUpgrade: Bool
+    let supportsPackageRollback: Bool?
     let packageStateIssues: [ManagerPackageStateIssue]?
     let isEligible: Bool?
     let ineligibleReasonCode: String?
@@ -417,6 +613,22 @@ struct ManagerStatus: Codable {
     let activeExplanationSecondary: String?
     let competingProvenance: String?
     let competingConfidence: Double?
+    /// Supported capabilities that need network access; the rest work offline.
+    let networkCapabilities: [String]?
+    /// Set while repeated refresh failures keep the manager out of background refreshes.
+    let quarantine: ManagerQuarantineStatus?
+    /// Manager-specific detection facts, such as yarn's `flavor` and `provider`.
+    let detectionExtras: [String: String]?
+    /// A background version probe is running; `version` is the last known value.
+    let probing: Bool?
+}
+
+struct ManagerQuarantineStatus: Codable {
+    let consecutiveFailures: Int
+    let reasonKind: String
+    let reason: String
+    let quarantinedSinceUnix: Int64
+    let nextProbeUnix: Int64
 }
 
 struct ManagerPackageStateIssue: Codable {
@@ -722,9 +934,16 @@ final class HelmCore: ObservableObject {
     @Published var packageKegPolicyOverrides: [String: HomebrewKegPolicyOverride] = [:]
     @Published var packageManagerPreferencesByFamilyKey: [String: String] = [:]
     @Published var safeModeEnabled: Bool = false
+    @Published var offlineModeEnabled: Bool = false
     @Published var lastError: String?
     @Published var lastErrorAttribution: CoreErrorAttribution?
-    @Published var selectedManagerFilter: String?
+    @Published var selectedManagerFilter: String? {
+        didSet {
+            if selectedManagerFilter != oldValue {
+                sendViewHint(managerId: selectedManagerFilter)
+            }
+        }
+    }
     @Published var hasCompletedOnboarding: Bool = UserDefaults.standard.bool(forKey: HelmCore.onboardingCompletedKey)
     @Published var acceptedLicenseTermsVersion: String? = UserDefaults.standard.string(
         forKey: HelmCore.acceptedLicenseTermsVersionKey
@@ -958,6 +1177,7 @@ final class HelmCore: ObservableObject {
             startPolling()
         }
         fetchSafeMode()
+        fetchOfflineMode()
         fetchHomebrewKegAutoCleanup()
         fetchPackageKegPolicies()
         fetchPackageManagerPreferences()
@@ -1124,6 +1344,23 @@ final class HelmCore: ObservableObject {
         }
     }
 
+    func consumeLastServiceError(_ completion: @escaping (CoreServiceError?) -> Void) {
+        guard let service = service() else {
+            completion(nil)
+            return
+        }
+        service.takeLastErrorJson { json in
+            let decoder = JSONDecoder()
+            decoder.keyDecodingStrategy = .convertFromSnakeCase
+            let error = json
+                .flatMap { $0.data(using: .utf8) }
+                .flatMap { try? decoder.decode(CoreServiceError.self, from: $0) }
+            DispatchQueue.main.async {
+                completion(error)
+            }
+        }
+    }
+
     func triggerRefresh() {
         logger.info("triggerRefresh called")
         self.lastRefreshTrigger = Date()
@@ -1182,7 +1419,8 @@ final class HelmCore: ObservableObject {
         self.lastTaskSnapshotRefreshAt = .distantPast
         self.lastFullSnapshotRefreshAt = .distantPast
 
-        service()?.triggerDetectionForManager(managerId: managerId) { success in
+        service()?.triggerDetectionForManager(managerId: managerId) { taskId in
+            let success = taskId >= 0
             if !success {
                 logger.error("triggerDetectionForManager failed (manager=\(managerId, privacy: .public))")
                 self.recordLastError(
@@ -1270,59 +1508,7 @@ final class HelmCore: ObservableObject {
         service()?.resetDatabase { [weak self] success in
             DispatchQueue.main.async {
                 if success {
-                    self?.installedPackages = []
-                    self?.outdatedPackages = []
-                    self?.activeTasks = []
-                    self?.taskTimeoutPrompts = []
-                    self?.searchResults = []
-                    self?.cachedAvailablePackages = []
-                    self?.detectedManagers = []
-                    self?.managerStatuses = [:]
-                    self?.managerOperations = [:]
-                    self?.verifyingManagerIds = []
-                    self?.packageKegPolicyOverrides = [:]
-                    self?.packageManagerPreferencesByFamilyKey = [:]
-                    self?.homebrewKegAutoCleanupEnabled = false
-                    self?.searchText = ""
-                    self?.isRefreshing = false
-                    self?.onboardingDetectionInProgress = false
-                    self?.pinActionPackageIds = []
-                    self?.upgradeActionPackageIds = []
-                    self?.installActionPackageIds = []
-                    self?.uninstallActionPackageIds = []
-                    self?.packageDescriptionLoadingIds = []
-                    self?.packageDescriptionUnavailableIds = []
-                    self?.packageDescriptionSummaryByKey = [:]
-                    self?.rustupToolchainDetailsByKey = [:]
-                    self?.rustupToolchainDetailLoadingKeys = []
-                    self?.rustupToolchainDetailUnavailableKeys = []
-                    self?.rustupToolchainActionInFlightKeys = []
-                    self?.upgradeActionTaskByPackage = [:]
-                    self?.installActionTaskByPackage = [:]
-                    self?.installActionNormalizedNameByPackageId = [:]
-                    self?.uninstallActionTaskByPackage = [:]
-                    self?.rustupToolchainActionTaskByKey = [:]
-                    self?.rustupToolchainActionPackageByKey = [:]
-                    self?.rustupToolchainActionSubmittedAtByKey = [:]
-                    self?.descriptionLookupTaskIdsByPackage = [:]
-                    self?.descriptionLookupStartedAtByPackage = [:]
-                    self?.descriptionLookupPackageById = [:]
-                    self?.activeRemoteSearchTaskIds = []
-                    self?.managerActionTaskDescriptions = [:]
-                    self?.managerActionTaskByManager = [:]
-                    self?.managerActionTaskTypes = [:]
-                    self?.managerActionTaskSubmittedAt = [:]
-                    self?.managerVerificationAnchorTaskIdByManager = [:]
-                    self?.managerVerificationStartedAtByManager = [:]
-                    self?.managerPostInstallSetupHandledTaskIds = [:]
-                    self?.localManagerActionTasks = [:]
-                    self?.localManagerActionTaskCreatedAt = [:]
-                    self?.lastObservedTaskId = 0
-                    self?.onboardingDetectionAnchorTaskId = 0
-                    self?.onboardingDetectionPendingManagers = []
-                    self?.onboardingDetectionStartedAt = nil
-                    self?.lastRefreshTrigger = nil
-                    self?.lastTaskSnapshotRefreshAt = .distantPast
+                    self?.clearDatabaseBackedState()
                     UserDefaults.standard.removeObject(forKey: Self.onboardingCompletedKey)
                     UserDefaults.standard.removeObject(forKey: Self.acceptedLicenseTermsVersionKey)
                     UserDefaults.standard.removeObject(forKey: Self.acceptedLicenseTermsAcceptedAtUnixKey)
@@ -1338,6 +1524,98 @@ final class HelmCore: ObservableObject {
         }
     }
 
+    /// Switch to another profile's inventory. Polling pauses while the core
+    /// swaps databases, and state loaded from the previous profile is dropped.
+    func switchProfile(_ name: String, completion: @escaping (Bool) -> Void) {
+        guard let service = service() else {
+            recordLastError(
+                source: "core.settings",
+                action: "switchProfile.service_unavailable",
+                taskType: "settings"
+            )
+            completion(false)
+            return
+        }
+        timer?.invalidate()
+        timer = nil
+
+        service.switchProfile(name: name) { [weak self] success in
+            DispatchQueue.main.async {
+                guard let self = self else { return }
+                if success {
+                    self.packageDescriptionRenderCache = [:]
+                    self.packageDescriptionRenderCacheOrder = []
+                    self.clearDatabaseBackedState()
+                } else {
+                    self.recordLastError(
+                        source: "core.settings",
+                        action: "switchProfile",
+                        taskType: "settings"
+                    )
+                }
+                self.startPolling()
+                self.scheduleDerivedViewStateRefresh()
+                completion(success)
+            }
+        }
+    }
+
+    private func clearDatabaseBackedState() {
+        installedPackages = []
+        outdatedPackages = []
+        activeTasks = []
+        taskTimeoutPrompts = []
+        searchResults = []
+        cachedAvailablePackages = []
+        detectedManagers = []
+        managerStatuses = [:]
+        managerOperations = [:]
+        verifyingManagerIds = []
+        packageKegPolicyOverrides = [:]
+        packageManagerPreferencesByFamilyKey = [:]
+        homebrewKegAutoCleanupEnabled = false
+        searchText = ""
+        isRefreshing = false
+        onboardingDetectionInProgress = false
+        pinActionPackageIds = []
+        upgradeActionPackageIds = []
+        installActionPackageIds = []
+        uninstallActionPackageIds = []
+        packageDescriptionLoadingIds = []
+        packageDescriptionUnavailableIds = []
+        packageDescriptionSummaryByKey = [:]
+        rustupToolchainDetailsByKey = [:]
+        rustupToolchainDetailLoadingKeys = []
+        rustupToolchainDetailUnavailableKeys = []
+        rustupToolchainActionInFlightKeys = []
+        upgradeActionTaskByPackage = [:]
+        installActionTaskByPackage = [:]
+        installActionNormalizedNameByPackageId = [:]
+        uninstallActionTaskByPackage = [:]
+        rustupToolchainActionTaskByKey = [:]
+        rustupToolchainActionPackageByKey = [:]
+        rustupToolchainActionSubmittedAtByKey = [:]
+        descriptionLookupTaskIdsByPackage = [:]
+        descriptionLookupStartedAtByPackage = [:]
+        descriptionLookupPackageById = [:]
+        activeRemoteSearchTaskIds = []
+        managerActionTaskDescriptions = [:]
+        managerActionTaskByManager = [:]
+        managerActionTaskTypes = [:]
+        managerActionTaskSubmittedAt = [:]
+        managerVerificationAnchorTaskIdByManager = [:]
+        managerVerificationStartedAtByManager = [:]
+        managerPostInstallSetupHandledTaskIds = [:]
+        localManagerActionTasks = [:]
+        localManagerActionTaskCreatedAt = [:]
+        lastObservedTaskId = 0
+        onboardingDetectionAnchorTaskId = 0
+        onboardingDetectionPendingManagers = []
+        onboardingDetectionStartedAt = nil
+        lastRefreshTrigger = nil
+        lastTaskSnapshotRefreshAt = .distantPast
+    }
+
     /// Posts a VoiceOver announcement for state changes.
     func postAccessibilityAnnouncement(_ message: String) {
         NSAccessibility.post(
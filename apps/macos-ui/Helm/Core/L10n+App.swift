@@ -85,6 +85,7 @@ extension L10n {
                 static let notInstalled = "app.managers.state.not_installed"
                 static let comingSoon = "app.managers.state.coming_soon"
                 static let metadataMismatch = "app.managers.state.metadata_mismatch"
+                static let quarantined = "app.managers.state.quarantined"
             }
             struct Label {
                 static let packageCount = "app.managers.label.package_count"
@@ -140,6 +141,7 @@ extension L10n {
                 static let install = "app.managers.action.install"
                 static let update = "app.managers.action.update"
                 static let uninstall = "app.managers.action.uninstall"
+                static let retryNow = "app.managers.action.retry_now"
             }
             struct Alert {
                 static let installTitle = "app.managers.alert.install.title"
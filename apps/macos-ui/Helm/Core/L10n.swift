@@ -45,6 +45,7 @@ struct L10n {
             static let processFailure = "service.error.process_failure"
             static let storageFailure = "service.error.storage_failure"
             static let internalError = "service.error.internal"
+            static let internalPanic = "service.error.internal_panic"
         }
         struct Task {
             struct Status {
@@ -58,9 +59,16 @@ struct L10n {
                 static let upgradeHomebrew = "service.task.label.upgrade.homebrew"
                 static let upgradeHomebrewCleanup = "service.task.label.upgrade.homebrew_cleanup"
                 static let upgradeMise = "service.task.label.upgrade.mise"
+                static let upgradeAsdf = "service.task.label.upgrade.asdf"
                 static let upgradePackage = "service.task.label.upgrade.package"
                 static let upgradeRustupToolchain = "service.task.label.upgrade.rustup_toolchain"
                 static let upgradeSoftwareUpdateAll = "service.task.label.upgrade.softwareupdate_all"
+                static let rollbackPackage = "service.task.label.rollback.package"
+                static let serviceStart = "service.task.label.service.start"
+                static let serviceStop = "service.task.label.service.stop"
+                static let serviceRestart = "service.task.label.service.restart"
+                static let cleanup = "service.task.label.cleanup"
+                static let measurePackageSizes = "service.task.label.measure_package_sizes"
                 static let pinHomebrew = "service.task.label.pin.homebrew"
                 static let unpinHomebrew = "service.task.label.unpin.homebrew"
                 static let installHomebrewFormula = "service.task.label.install.homebrew_formula"
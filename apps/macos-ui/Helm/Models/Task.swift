@@ -8,6 +8,8 @@ struct TaskItem: Identifiable {
     let taskType: String?
     let labelKey: String?
     let labelArgs: [String: String]?
+    /// Completion percentage reported by the core while the task runs.
+    var progress: Int?
 
     var isRunning: Bool {
         let s = status.lowercased()
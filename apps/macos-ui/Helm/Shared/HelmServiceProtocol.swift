@@ -3,7 +3,16 @@ import Foundation
 @objc public protocol HelmServiceProtocol {
     func listInstalledPackages(withReply reply: @escaping (String?) -> Void)
     func listOutdatedPackages(withReply reply: @escaping (String?) -> Void)
+    func listHeldBackPackages(withReply reply: @escaping (String?) -> Void)
     func getRustupToolchainDetail(toolchain: String, withReply reply: @escaping (String?) -> Void)
+    func listPackageFiles(managerId: String, packageName: String, limit: Int64, withReply reply: @escaping (String?) -> Void)
+    func listPackageDependencies(managerId: String, packageName: String, withReply reply: @escaping (String?) -> Void)
+    func getPackageInfo(managerId: String, packageName: String, withReply reply: @escaping (String?) -> Void)
+    func resolvePackageAlias(name: String, withReply reply: @escaping (String?) -> Void)
+    func listOrphanedPackages(withReply reply: @escaping (String?) -> Void)
+    func cleanupOrphans(withReply reply: @escaping (String?) -> Void)
+    func listServices(withReply reply: @escaping (String?) -> Void)
+    func getReclaimableSpace(withReply reply: @escaping (String?) -> Void)
     func addRustupComponent(toolchain: String, component: String, withReply reply: @escaping (Int64) -> Void)
     func removeRustupComponent(toolchain: String, component: String, withReply reply: @escaping (Int64) -> Void)
     func addRustupTarget(toolchain: String, target: String, withReply reply: @escaping (Int64) -> Void)
@@ -14,17 +23,24 @@ import Foundation
     func setRustupProfile(profile: String, withReply reply: @escaping (Int64) -> Void)
     func listTasks(withReply reply: @escaping (String?) -> Void)
     func getTaskOutput(taskId: Int64, withReply reply: @escaping (String?) -> Void)
+    func getTaskOutputSince(taskId: Int64, offset: Int64, withReply reply: @escaping (String?) -> Void)
     func listTaskLogs(taskId: Int64, limit: Int64, withReply reply: @escaping (String?) -> Void)
     func listTaskTimeoutPrompts(withReply reply: @escaping (String?) -> Void)
     func respondTaskTimeoutPrompt(taskId: Int64, waitForCompletion: Bool, withReply reply: @escaping (Bool) -> Void)
     func triggerRefresh(withReply reply: @escaping (Bool) -> Void)
+    func getRefreshSummary(withReply reply: @escaping (String?) -> Void)
+    func getBulkOperationProgress(withReply reply: @escaping (String?) -> Void)
     func triggerDetection(withReply reply: @escaping (Bool) -> Void)
-    func triggerDetectionForManager(managerId: String, withReply reply: @escaping (Bool) -> Void)
+    func triggerDetectionForManager(managerId: String, withReply reply: @escaping (Int64) -> Void)
+    func getManagerEnvironment(managerId: String, withReply reply: @escaping (String?) -> Void)
+    func getMetricsText(withReply reply: @escaping (String?) -> Void)
     func searchLocal(query: String, withReply reply: @escaping (String?) -> Void)
     func triggerRemoteSearch(query: String, withReply reply: @escaping (Int64) -> Void)
     func triggerRemoteSearchForManager(managerId: String, query: String, withReply reply: @escaping (Int64) -> Void)
     func cancelTask(taskId: Int64, withReply reply: @escaping (Bool) -> Void)
     func dismissTask(taskId: Int64, withReply reply: @escaping (Bool) -> Void)
+    func recordTaskAttempt(taskId: Int64, attemptOf: Int64, withReply reply: @escaping (Bool) -> Void)
+    func listTaskAttempts(taskId: Int64, withReply reply: @escaping (String?) -> Void)
     func listManagerStatus(withReply reply: @escaping (String?) -> Void)
     func doctorScan(withReply reply: @escaping (String?) -> Void)
     func getSharedOnboardingState(withReply reply: @escaping (Bool, String?) -> Void)
@@ -32,6 +48,8 @@ import Foundation
     func setSharedAcceptedLicenseTermsVersion(version: String?, withReply reply: @escaping (Bool) -> Void)
     func getSafeMode(withReply reply: @escaping (Bool) -> Void)
     func setSafeMode(enabled: Bool, withReply reply: @escaping (Bool) -> Void)
+    func getOfflineMode(withReply reply: @escaping (Bool) -> Void)
+    func setOfflineMode(enabled: Bool, withReply reply: @escaping (Bool) -> Void)
     func getHomebrewKegAutoCleanup(withReply reply: @escaping (Bool) -> Void)
     func setHomebrewKegAutoCleanup(enabled: Bool, withReply reply: @escaping (Bool) -> Void)
     func listPackageKegPolicies(withReply reply: @escaping (String?) -> Void)
@@ -41,12 +59,19 @@ import Foundation
     func setPackageManagerPreference(packageFamilyKey: String, managerId: String?, withReply reply: @escaping (Bool) -> Void)
     func previewUpgradePlan(includePinned: Bool, allowOsUpdates: Bool, withReply reply: @escaping (String?) -> Void)
     func upgradeAll(includePinned: Bool, allowOsUpdates: Bool, withReply reply: @escaping (Bool) -> Void)
-    func upgradePackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, withReply reply: @escaping (Int64) -> Void)
-    func installPackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, withReply reply: @escaping (Int64) -> Void)
-    func uninstallPackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, withReply reply: @escaping (Int64) -> Void)
+    func requestUpgradeAllConfirmation(includePinned: Bool, withReply reply: @escaping (String?) -> Void)
+    func upgradeAllConfirmed(token: String, withReply reply: @escaping (Bool) -> Void)
+    func upgradePackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, idempotencyKey: String?, withReply reply: @escaping (Int64) -> Void)
+    func rollbackPackage(managerId: String, packageName: String, withReply reply: @escaping (Int64) -> Void)
+    func setServiceState(serviceName: String, action: String, withReply reply: @escaping (Int64) -> Void)
+    func runCleanup(managerId: String, withReply reply: @escaping (Int64) -> Void)
+    func measurePackageSizes(managerId: String, withReply reply: @escaping (Int64) -> Void)
+    func installPackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, idempotencyKey: String?, withReply reply: @escaping (Int64) -> Void)
+    func uninstallPackage(managerId: String, packageName: String, packageTargetName: String?, version: String?, moveToTrash: Bool, idempotencyKey: String?, withReply reply: @escaping (Int64) -> Void)
     func previewPackageUninstall(managerId: String, packageName: String, version: String?, withReply reply: @escaping (String?) -> Void)
     func listPins(withReply reply: @escaping (String?) -> Void)
     func pinPackage(managerId: String, packageName: String, version: String?, withReply reply: @escaping (Bool) -> Void)
+    func pinPackageWithConstraint(managerId: String, packageName: String, constraint: String, withReply reply: @escaping (Bool) -> Void)
     func unpinPackage(managerId: String, packageName: String, version: String?, withReply reply: @escaping (Bool) -> Void)
     func setManagerEnabled(managerId: String, enabled: Bool, withReply reply: @escaping (Bool) -> Void)
     func setManagerSelectedExecutablePath(managerId: String, selectedPath: String?, withReply reply: @escaping (Bool) -> Void)
@@ -60,6 +85,7 @@ import Foundation
         idleTimeoutSeconds: Int64,
         withReply reply: @escaping (Bool) -> Void
     )
+    func setManagerProcessQos(managerId: String, qos: String?, withReply reply: @escaping (Bool) -> Void)
     func previewManagerUninstall(
         managerId: String,
         allowUnknownProvenance: Bool,
@@ -97,5 +123,12 @@ import Foundation
         withReply reply: @escaping (Int64) -> Void
     )
     func resetDatabase(withReply reply: @escaping (Bool) -> Void)
+    func listProfiles(withReply reply: @escaping (String?) -> Void)
+    func switchProfile(name: String, withReply reply: @escaping (Bool) -> Void)
+    func setViewHint(json: String, withReply reply: @escaping (Bool) -> Void)
+    func setRefreshSchedule(managerId: String, intervalSecs: Int64, withReply reply: @escaping (Bool) -> Void)
+    func getRefreshSchedules(withReply reply: @escaping (String?) -> Void)
+    func retryQuarantinedManager(managerId: String, withReply reply: @escaping (Bool) -> Void)
     func takeLastErrorKey(withReply reply: @escaping (String?) -> Void)
+    func takeLastErrorJson(withReply reply: @escaping (String?) -> Void)
 }
@@ -19,7 +19,14 @@ struct TaskRowView: View {
     var body: some View {
         VStack(alignment: .leading, spacing: isExpanded ? 8 : 0) {
             HStack(spacing: 8) {
-                if task.isRunning {
+                if task.isRunning, let progress = task.progress {
+                    ProgressView(value: Double(progress), total: 100)
+                        .progressViewStyle(.circular)
+                        .scaleEffect(0.6)
+                        .frame(width: 16, height: 16)
+                        .accessibilityLabel(L10n.Service.Task.Status.running.localized)
+                        .accessibilityValue("\(progress)%")
+                } else if task.isRunning {
                     ProgressView()
                         .scaleEffect(0.6)
                         .frame(width: 16, height: 16)
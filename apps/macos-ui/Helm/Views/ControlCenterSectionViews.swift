@@ -486,13 +486,14 @@ struct RedesignUpgradeSheetView: View {
     @EnvironmentObject private var context: ControlCenterContext
     @Environment(\.presentationMode) private var presentationMode
     @State private var includeOsUpdates = false
+    @State private var osConfirmation: CoreConfirmationToken?
 
     private var noOsCount: Int {
         core.upgradeAllPreviewCount(includePinned: false, allowOsUpdates: false)
     }
 
     private var withOsCount: Int {
-        core.upgradeAllPreviewCount(includePinned: false, allowOsUpdates: true)
+        osConfirmation?.plan.upgrades.count ?? 0
     }
 
     var body: some View {
@@ -503,6 +504,13 @@ struct RedesignUpgradeSheetView: View {
             if !core.safeModeEnabled {
                 Toggle(L10n.App.Updates.includeOs.localized, isOn: $includeOsUpdates)
                     .toggleStyle(.switch)
+                    .onChange(of: includeOsUpdates) { include in
+                        osConfirmation = nil
+                        guard include else { return }
+                        core.requestUpgradeAllConfirmation(includePinned: false) { confirmation in
+                            osConfirmation = confirmation
+                        }
+                    }
             }
 
             HStack {
@@ -522,7 +530,11 @@ struct RedesignUpgradeSheetView: View {
                 .buttonStyle(HelmSecondaryButtonStyle())
                 Spacer()
                 Button(L10n.App.Action.runPlan.localized) {
-                    core.upgradeAll(includePinned: false, allowOsUpdates: includeOsUpdates)
+                    if includeOsUpdates, let osConfirmation {
+                        core.upgradeAll(confirmation: osConfirmation)
+                    } else {
+                        core.upgradeAll(includePinned: false)
+                    }
                     context.dismissUpgradeSheet()
                     presentationMode.wrappedValue.dismiss()
                 }
@@ -313,6 +313,23 @@ private struct ManagerSectionRow: View {
                             .font(.caption2)
                             .foregroundColor(HelmTheme.stateAttention)
                     }
+                    if let quarantine = status?.quarantine {
+                        HStack(spacing: 6) {
+                            Text(L10n.App.Managers.State.quarantined.localized(with: [
+                                "count": quarantine.consecutiveFailures,
+                                "reason": quarantine.reason
+                            ]))
+                            .font(.caption2)
+                            .foregroundColor(HelmTheme.stateError)
+                            .lineLimit(2)
+                            Button(L10n.App.Managers.Action.retryNow.localized) {
+                                core.retryQuarantinedManager(manager.id)
+                            }
+                            .buttonStyle(.plain)
+                            .font(.caption2.weight(.semibold))
+                            .foregroundColor(HelmTheme.actionSecondaryText)
+                        }
+                    }
                 }
 
                 Spacer()
@@ -1,4 +1,5 @@
 import Foundation
+import Network
 import os.log
 
 private let logger = Logger(subsystem: "app.jasoncavinder.Helm.HelmService", category: "service")
@@ -33,6 +34,8 @@ class HelmService: NSObject, HelmServiceProtocol {
         }
     }
 
+    private let networkMonitor = NWPathMonitor()
+
     override init() {
         super.init()
 
@@ -47,6 +50,19 @@ class HelmService: NSObject, HelmServiceProtocol {
             helm_init(cPath)
         }
         logger.info("helm_init result: \(result)")
+
+        startNetworkMonitor()
+    }
+
+    /// Forward cellular, hotspot and Low Data Mode paths to the core, which
+    /// holds back bandwidth-heavy refresh work on them.
+    private func startNetworkMonitor() {
+        networkMonitor.pathUpdateHandler = { path in
+            let hint = "{\"expensive\":\(path.isExpensive),\"constrained\":\(path.isConstrained)}"
+            let accepted = hint.withCString { helm_set_network_hint($0) }
+            logger.info("helm_set_network_hint(\(hint, privacy: .public)) result: \(accepted)")
+        }
+        networkMonitor.start(queue: DispatchQueue(label: "app.jasoncavinder.Helm.HelmService.network"))
     }
 
     func listInstalledPackages(withReply reply: @escaping (String?) -> Void) {
@@ -69,6 +85,16 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(String(cString: cString))
     }
 
+    func listHeldBackPackages(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_list_held_back_packages() else {
+            logger.warning("helm_list_held_back_packages returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     func getRustupToolchainDetail(toolchain: String, withReply reply: @escaping (String?) -> Void) {
         guard let cString = toolchain.withCString({ helm_get_rustup_toolchain_detail($0) }) else {
             logger.warning("helm_get_rustup_toolchain_detail(\(toolchain, privacy: .public)) returned nil")
@@ -79,6 +105,98 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(String(cString: cString))
     }
 
+    func listPackageFiles(managerId: String, packageName: String, limit: Int64, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = managerId.withCString({ manager in
+            packageName.withCString { package in
+                helm_list_package_files(manager, package, limit)
+            }
+        }) else {
+            logger.warning("helm_list_package_files(\(managerId), \(packageName)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func listPackageDependencies(managerId: String, packageName: String, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = managerId.withCString({ manager in
+            packageName.withCString { package in
+                helm_list_package_dependencies(manager, package)
+            }
+        }) else {
+            logger.warning("helm_list_package_dependencies(\(managerId), \(packageName)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func getPackageInfo(managerId: String, packageName: String, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = managerId.withCString({ manager in
+            packageName.withCString { package in
+                helm_get_package_info(manager, package)
+            }
+        }) else {
+            logger.warning("helm_get_package_info(\(managerId), \(packageName)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func resolvePackageAlias(name: String, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = name.withCString({ helm_resolve_package_alias($0) }) else {
+            logger.warning("helm_resolve_package_alias(\(name)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func listOrphanedPackages(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_list_orphaned_packages() else {
+            logger.warning("helm_list_orphaned_packages returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func cleanupOrphans(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_cleanup_orphans() else {
+            logger.warning("helm_cleanup_orphans returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func listServices(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_list_services() else {
+            logger.warning("helm_list_services returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func getReclaimableSpace(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_reclaimable_space() else {
+            logger.warning("helm_get_reclaimable_space returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     func addRustupComponent(toolchain: String, component: String, withReply reply: @escaping (Int64) -> Void) {
         let taskId = toolchain.withCString { toolchainPtr in
             component.withCString { componentPtr in
@@ -171,6 +289,16 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(String(cString: cString))
     }
 
+    func getTaskOutputSince(taskId: Int64, offset: Int64, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_task_output_since(taskId, offset) else {
+            logger.warning("helm_get_task_output_since(\(taskId), \(offset)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     func listTaskLogs(taskId: Int64, limit: Int64, withReply reply: @escaping (String?) -> Void) {
         guard let cString = helm_list_task_logs(taskId, limit) else {
             logger.warning("helm_list_task_logs(\(taskId), \(limit)) returned nil")
@@ -205,18 +333,58 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func getRefreshSummary(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_refresh_summary() else {
+            logger.warning("helm_get_refresh_summary returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func getBulkOperationProgress(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_bulk_operation_progress() else {
+            logger.warning("helm_get_bulk_operation_progress returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     func triggerDetection(withReply reply: @escaping (Bool) -> Void) {
         let result = helm_trigger_detection()
         logger.info("helm_trigger_detection result: \(result)")
         reply(result)
     }
 
-    func triggerDetectionForManager(managerId: String, withReply reply: @escaping (Bool) -> Void) {
-        let result = managerId.withCString { manager in
+    func triggerDetectionForManager(managerId: String, withReply reply: @escaping (Int64) -> Void) {
+        let taskId = managerId.withCString { manager in
             helm_trigger_detection_for_manager(manager)
         }
-        logger.info("helm_trigger_detection_for_manager(\(managerId)) result: \(result)")
-        reply(result)
+        logger.info("helm_trigger_detection_for_manager(\(managerId)) result: \(taskId)")
+        reply(taskId)
+    }
+
+    func getManagerEnvironment(managerId: String, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = managerId.withCString({ helm_get_manager_environment($0) }) else {
+            logger.warning("helm_get_manager_environment(\(managerId)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func getMetricsText(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_metrics_text() else {
+            logger.warning("helm_get_metrics_text returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
     }
 
     func searchLocal(query: String, withReply reply: @escaping (String?) -> Void) {
@@ -257,6 +425,22 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func recordTaskAttempt(taskId: Int64, attemptOf: Int64, withReply reply: @escaping (Bool) -> Void) {
+        let result = helm_record_task_attempt(taskId, attemptOf)
+        logger.info("helm_record_task_attempt(\(taskId), \(attemptOf)) result: \(result)")
+        reply(result)
+    }
+
+    func listTaskAttempts(taskId: Int64, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_list_task_attempts(taskId) else {
+            logger.warning("helm_list_task_attempts(\(taskId)) returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     func listManagerStatus(withReply reply: @escaping (String?) -> Void) {
         guard let cString = helm_list_manager_status() else {
             logger.warning("helm_list_manager_status returned nil")
@@ -317,6 +501,17 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func getOfflineMode(withReply reply: @escaping (Bool) -> Void) {
+        let enabled = helm_get_offline_mode()
+        reply(enabled)
+    }
+
+    func setOfflineMode(enabled: Bool, withReply reply: @escaping (Bool) -> Void) {
+        let result = helm_set_offline_mode(enabled)
+        logger.info("helm_set_offline_mode(\(enabled)) result: \(result)")
+        reply(result)
+    }
+
     func getHomebrewKegAutoCleanup(withReply reply: @escaping (Bool) -> Void) {
         let enabled = helm_get_homebrew_keg_auto_cleanup()
         reply(enabled)
@@ -489,18 +684,37 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func requestUpgradeAllConfirmation(includePinned: Bool, withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_request_upgrade_all_confirmation(includePinned) else {
+            logger.warning("helm_request_upgrade_all_confirmation returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func upgradeAllConfirmed(token: String, withReply reply: @escaping (Bool) -> Void) {
+        let result = token.withCString { helm_upgrade_all_confirmed($0) }
+        logger.info("helm_upgrade_all_confirmed result: \(result)")
+        reply(result)
+    }
+
     func upgradePackage(
         managerId: String,
         packageName: String,
         packageTargetName: String?,
         version: String?,
+        idempotencyKey: String?,
         withReply reply: @escaping (Int64) -> Void
     ) {
         let taskId = managerId.withCString { manager in
             packageName.withCString { package in
                 withOptionalCString(packageTargetName) { targetPtr in
                     withOptionalCString(version) { versionPtr in
-                        helm_upgrade_package(manager, package, targetPtr, versionPtr)
+                        withOptionalCString(idempotencyKey) { keyPtr in
+                            helm_upgrade_package(manager, package, targetPtr, versionPtr, keyPtr)
+                        }
                     }
                 }
             }
@@ -511,18 +725,57 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(taskId)
     }
 
+    func rollbackPackage(managerId: String, packageName: String, withReply reply: @escaping (Int64) -> Void) {
+        let taskId = managerId.withCString { manager in
+            packageName.withCString { package in
+                helm_rollback_package(manager, package)
+            }
+        }
+        logger.info("helm_rollback_package(\(managerId), \(packageName)) result: \(taskId)")
+        reply(taskId)
+    }
+
+    func setServiceState(serviceName: String, action: String, withReply reply: @escaping (Int64) -> Void) {
+        let taskId = serviceName.withCString { service in
+            action.withCString { action in
+                helm_set_service_state(service, action)
+            }
+        }
+        logger.info("helm_set_service_state(\(serviceName), \(action)) result: \(taskId)")
+        reply(taskId)
+    }
+
+    func runCleanup(managerId: String, withReply reply: @escaping (Int64) -> Void) {
+        let taskId = managerId.withCString { manager in
+            helm_run_cleanup(manager)
+        }
+        logger.info("helm_run_cleanup(\(managerId)) result: \(taskId)")
+        reply(taskId)
+    }
+
+    func measurePackageSizes(managerId: String, withReply reply: @escaping (Int64) -> Void) {
+        let taskId = managerId.withCString { manager in
+            helm_measure_package_sizes(manager)
+        }
+        logger.info("helm_measure_package_sizes(\(managerId)) result: \(taskId)")
+        reply(taskId)
+    }
+
     func installPackage(
         managerId: String,
         packageName: String,
         packageTargetName: String?,
         version: String?,
+        idempotencyKey: String?,
         withReply reply: @escaping (Int64) -> Void
     ) {
         let taskId = managerId.withCString { manager in
             packageName.withCString { package in
                 withOptionalCString(packageTargetName) { targetPtr in
                     withOptionalCString(version) { versionPtr in
-                        helm_install_package(manager, package, targetPtr, versionPtr)
+                        withOptionalCString(idempotencyKey) { keyPtr in
+                            helm_install_package(manager, package, targetPtr, versionPtr, keyPtr)
+                        }
                     }
                 }
             }
@@ -538,19 +791,23 @@ class HelmService: NSObject, HelmServiceProtocol {
         packageName: String,
         packageTargetName: String?,
         version: String?,
+        moveToTrash: Bool,
+        idempotencyKey: String?,
         withReply reply: @escaping (Int64) -> Void
     ) {
         let taskId = managerId.withCString { manager in
             packageName.withCString { package in
                 withOptionalCString(packageTargetName) { targetPtr in
                     withOptionalCString(version) { versionPtr in
-                        helm_uninstall_package(manager, package, targetPtr, versionPtr)
+                        withOptionalCString(idempotencyKey) { keyPtr in
+                            helm_uninstall_package(manager, package, targetPtr, versionPtr, moveToTrash, keyPtr)
+                        }
                     }
                 }
             }
         }
         logger.info(
-            "helm_uninstall_package(\(managerId), \(packageName), target=\(packageTargetName ?? "-", privacy: .public), version=\(version ?? "-", privacy: .public)) result: \(taskId)"
+            "helm_uninstall_package(\(managerId), \(packageName), target=\(packageTargetName ?? "-", privacy: .public), version=\(version ?? "-", privacy: .public), trash=\(moveToTrash)) result: \(taskId)"
         )
         reply(taskId)
     }
@@ -602,6 +859,18 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func pinPackageWithConstraint(managerId: String, packageName: String, constraint: String, withReply reply: @escaping (Bool) -> Void) {
+        let result = managerId.withCString { manager in
+            packageName.withCString { package in
+                constraint.withCString { constraintPtr in
+                    helm_pin_package_with_constraint(manager, package, constraintPtr)
+                }
+            }
+        }
+        logger.info("helm_pin_package_with_constraint(\(managerId), \(packageName), \(constraint)) result: \(result)")
+        reply(result)
+    }
+
     func unpinPackage(managerId: String, packageName: String, version: String?, withReply reply: @escaping (Bool) -> Void) {
         let result: Bool
         if let version {
@@ -704,6 +973,23 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func setManagerProcessQos(managerId: String, qos: String?, withReply reply: @escaping (Bool) -> Void) {
+        let result: Bool
+        if let qos {
+            result = managerId.withCString { manager in
+                qos.withCString { qosValue in
+                    helm_set_manager_process_qos(manager, qosValue)
+                }
+            }
+        } else {
+            result = managerId.withCString { manager in
+                helm_set_manager_process_qos(manager, nil)
+            }
+        }
+        logger.info("helm_set_manager_process_qos(\(managerId), \(qos ?? "nil")) result: \(result)")
+        reply(result)
+    }
+
     func previewManagerUninstall(
         managerId: String,
         allowUnknownProvenance: Bool,
@@ -867,6 +1153,49 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(result)
     }
 
+    func listProfiles(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_list_profiles() else {
+            logger.warning("helm_list_profiles returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func switchProfile(name: String, withReply reply: @escaping (Bool) -> Void) {
+        let result = name.withCString { helm_switch_profile($0) }
+        logger.info("helm_switch_profile result: \(result)")
+        reply(result)
+    }
+
+    func setViewHint(json: String, withReply reply: @escaping (Bool) -> Void) {
+        let result = json.withCString { helm_set_view_hint($0) }
+        reply(result)
+    }
+
+    func setRefreshSchedule(managerId: String, intervalSecs: Int64, withReply reply: @escaping (Bool) -> Void) {
+        let result = managerId.withCString { helm_set_refresh_schedule($0, intervalSecs) }
+        logger.info("helm_set_refresh_schedule(\(managerId), \(intervalSecs)) result: \(result)")
+        reply(result)
+    }
+
+    func getRefreshSchedules(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_get_refresh_schedules() else {
+            logger.warning("helm_get_refresh_schedules returned nil")
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
+    func retryQuarantinedManager(managerId: String, withReply reply: @escaping (Bool) -> Void) {
+        let result = managerId.withCString { helm_retry_quarantined_manager($0) }
+        logger.info("helm_retry_quarantined_manager(\(managerId)) result: \(result)")
+        reply(result)
+    }
+
     func takeLastErrorKey(withReply reply: @escaping (String?) -> Void) {
         guard let cString = helm_take_last_error_key() else {
             reply(nil)
@@ -876,6 +1205,15 @@ class HelmService: NSObject, HelmServiceProtocol {
         reply(String(cString: cString))
     }
 
+    func takeLastErrorJson(withReply reply: @escaping (String?) -> Void) {
+        guard let cString = helm_take_last_error_json() else {
+            reply(nil)
+            return
+        }
+        defer { helm_free_string(cString) }
+        reply(String(cString: cString))
+    }
+
     private func encodeHelmCliShimInstallResponse(_ response: HelmCliShimInstallResponse) -> String? {
         let encoder = JSONEncoder()
         guard let data = try? encoder.encode(response) else {
@@ -22,6 +22,7 @@ pub(super) fn execute_command(
         }
         Command::Onboarding => cmd_onboarding(store.as_ref(), options, command_args),
         Command::SelfCmd => cmd_self(store, options, command_args),
+        Command::NotifyChanged => cmd_notify_changed(store, options, command_args),
         Command::InternalCoordinator => cmd_internal_coordinator(store, command_args),
         Command::Completion | Command::Help | Command::Version => Ok(()),
     }
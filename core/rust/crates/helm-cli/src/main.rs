@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 #[cfg(target_vendor = "apple")]
 use std::ffi::CString;
@@ -19,26 +19,31 @@ use helm_core::adapters::manager::{
 };
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, AsdfAdapter, BundlerAdapter, CargoAdapter,
-    CargoBinstallAdapter, ColimaAdapter, DetectRequest, DockerDesktopAdapter,
+    CargoBinstallAdapter, CleanupRequest, ColimaAdapter, DetectRequest, DockerDesktopAdapter,
     FirmwareUpdatesAdapter, HomebrewAdapter, HomebrewCaskAdapter, InstallRequest,
     ListInstalledRequest, ListOutdatedRequest, MacPortsAdapter, ManagerAdapter, MasAdapter,
-    MiseAdapter, NixDarwinAdapter, NpmAdapter, ParallelsDesktopAdapter, PinRequest, PipAdapter,
-    PipxAdapter, PnpmAdapter, PodmanAdapter, PoetryAdapter, ProcessAsdfSource,
-    ProcessBundlerSource, ProcessCargoBinstallSource, ProcessCargoSource, ProcessColimaSource,
-    ProcessDockerDesktopSource, ProcessFirmwareUpdatesSource, ProcessHomebrewCaskSource,
-    ProcessHomebrewSource, ProcessMacPortsSource, ProcessMasSource, ProcessMiseSource,
-    ProcessNixDarwinSource, ProcessNpmSource, ProcessParallelsDesktopSource, ProcessPipSource,
-    ProcessPipxSource, ProcessPnpmSource, ProcessPodmanSource, ProcessPoetrySource,
-    ProcessRosetta2Source, ProcessRubyGemsSource, ProcessRustupSource, ProcessSetappSource,
-    ProcessSoftwareUpdateSource, ProcessSparkleSource, ProcessXcodeCommandLineToolsSource,
-    ProcessYarnSource, Rosetta2Adapter, RubyGemsAdapter, RustupAdapter, SearchRequest,
-    SetappAdapter, SoftwareUpdateAdapter, SparkleAdapter, UninstallRequest, UnpinRequest,
-    UpgradeRequest, XcodeCommandLineToolsAdapter, YarnAdapter,
+    MeasurePackageSizesRequest, MiseAdapter, NixDarwinAdapter, NpmAdapter, ParallelsDesktopAdapter,
+    PinRequest, PipAdapter, PipxAdapter, PnpmAdapter, PodmanAdapter, PoetryAdapter,
+    ProcessAsdfSource, ProcessBundlerSource, ProcessCargoBinstallSource, ProcessCargoSource,
+    ProcessColimaSource, ProcessDockerDesktopSource, ProcessFirmwareUpdatesSource,
+    ProcessHomebrewCaskSource, ProcessHomebrewSource, ProcessMacPortsSource, ProcessMasSource,
+    ProcessMiseSource, ProcessNixDarwinSource, ProcessNpmSource, ProcessParallelsDesktopSource,
+    ProcessPipSource, ProcessPipxSource, ProcessPnpmSource, ProcessPodmanSource,
+    ProcessPoetrySource, ProcessRosetta2Source, ProcessRubyGemsSource, ProcessRustupSource,
+    ProcessSetappSource, ProcessSoftwareUpdateSource, ProcessSparkleSource,
+    ProcessXcodeCommandLineToolsSource, ProcessYarnSource, RollbackRequest, Rosetta2Adapter,
+    RubyGemsAdapter, RustupAdapter, SearchRequest, SetServiceStateRequest, SetappAdapter,
+    SoftwareUpdateAdapter, SparkleAdapter, UninstallRequest, UnpinRequest, UpgradeRequest,
+    UpgradeTarget, UpgradeTargetKind, XcodeCommandLineToolsAdapter, YarnAdapter,
     load_rustup_toolchain_detail_with_runtime,
 };
+use helm_core::base_dirs::ToolBaseDirs;
+use helm_core::diagnostics_redaction::{
+    DiagnosticsRedactor, RedactionConfig, parse_custom_patterns,
+};
 use helm_core::execution::{
-    ManagerTimeoutProfile, TaskOutputRecord, TokioProcessExecutor,
-    replace_manager_execution_preferences,
+    ManagerTimeoutProfile, ProcessQos, TaskOutputRecord, TokioProcessExecutor,
+    replace_manager_execution_preferences, set_default_process_qos,
 };
 use helm_core::managed_automation_policy::{
     ManagedAutomationPolicyMode, apply_managed_automation_policy,
@@ -47,12 +52,17 @@ use helm_core::manager_dependencies::provenance_dependency_manager;
 use helm_core::manager_instances::{install_instance_fingerprint, resolve_multi_instance_state};
 use helm_core::manager_policy::manager_enablement_eligibility;
 use helm_core::models::{
-    CachedSearchResult, Capability, DetectionInfo, HomebrewKegPolicy, InstalledPackage,
-    ManagerAuthority, ManagerId, ManagerInstallInstance, ManagerUninstallPreview, OutdatedPackage,
-    PackageRef, PackageRuntimeState, PackageUninstallPreview, PinKind, PinRecord, SearchQuery,
-    StrategyKind, TaskId, TaskLogLevel, TaskRecord, TaskStatus,
+    CachedSearchResult, Capability, DetectionInfo, DetectionOutcome, HomebrewKegPolicy,
+    InstalledPackage, ManagerAuthority, ManagerId, ManagerInstallInstance, ManagerUninstallPreview,
+    OutdatedPackage, PackageRef, PackageRuntimeState, PackageUninstallPreview, PinKind, PinRecord,
+    SearchQuery, ServiceAction, StrategyKind, TaskHistoryFilter, TaskId, TaskLogLevel, TaskRecord,
+    TaskStatus, TaskType, UninstallDisposal,
+};
+use helm_core::orchestration::{
+    AdapterRuntime, AdapterTaskTerminalState, CancellationMode, TaskViewOptions,
+    build_visible_tasks,
 };
-use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState, CancellationMode};
+use helm_core::package_key::PackageKey;
 use helm_core::persistence::{DetectionStore, PackageStore, PinStore, SearchCacheStore, TaskStore};
 use helm_core::registry;
 use helm_core::sqlite::SqliteStore;
@@ -61,6 +71,7 @@ use helm_core::uninstall_preview::{
     PackageUninstallPreviewContext, build_manager_uninstall_preview,
     build_package_uninstall_preview,
 };
+use helm_core::upgrade_target::CONFIRMED_OS_UPDATES_NAME;
 use helm_core::versioning::PackageCoordinate;
 use helm_core::versioning::package_family_preference_key;
 use semver::Version;
@@ -112,7 +123,7 @@ static CLI_NDJSON: AtomicBool = AtomicBool::new(false);
 const BASH_COMPLETION_SCRIPT: &str = r#"_helm_complete() {
     local cur
     cur="${COMP_WORDS[COMP_CWORD]}"
-    local commands="status refresh search ls packages updates tasks managers settings diagnostics doctor onboarding self completion help"
+    local commands="status refresh search ls packages updates tasks managers settings diagnostics doctor onboarding self notify-changed completion help"
     if [[ ${COMP_CWORD} -eq 1 ]]; then
         COMPREPLY=( $(compgen -W "${commands}" -- "${cur}") )
         return 0
@@ -150,6 +161,13 @@ const BASH_COMPLETION_SCRIPT: &str = r#"_helm_complete() {
                 COMPREPLY=( $(compgen -W "status check update uninstall auto-check help" -- "${cur}") )
             fi
             ;;
+        notify-changed)
+            if [[ ${COMP_CWORD} -ge 3 && "${COMP_WORDS[2]}" == "hook" ]]; then
+                COMPREPLY=( $(compgen -W "bash zsh fish" -- "${cur}") )
+            else
+                COMPREPLY=( $(compgen -W "hook help" -- "${cur}") )
+            fi
+            ;;
         completion)
             COMPREPLY=( $(compgen -W "bash zsh fish help" -- "${cur}") )
             ;;
@@ -174,6 +192,7 @@ commands=(
   doctor
   onboarding
   self
+  notify-changed
   completion
   help
 )
@@ -215,13 +234,20 @@ case $words[2] in
       _values 'subcommand' status check update uninstall auto-check help
     fi
     ;;
+  notify-changed)
+    if [[ "$words[3]" == "hook" ]]; then
+      _values 'shell' bash zsh fish
+    else
+      _values 'subcommand' hook help
+    fi
+    ;;
   completion)
     _values 'shell' bash zsh fish help
     ;;
 esac
 "#;
 const FISH_COMPLETION_SCRIPT: &str = r#"complete -c helm -f
-complete -c helm -n "__fish_use_subcommand" -a "status refresh search ls packages updates tasks managers settings diagnostics doctor onboarding self completion help"
+complete -c helm -n "__fish_use_subcommand" -a "status refresh search ls packages updates tasks managers settings diagnostics doctor onboarding self notify-changed completion help"
 complete -c helm -n "__fish_seen_subcommand_from packages" -a "list search show install uninstall upgrade pin unpin rustup keg-policy help"
 complete -c helm -n "__fish_seen_subcommand_from updates" -a "list summary preview run help"
 complete -c helm -n "__fish_seen_subcommand_from tasks" -a "list show logs output follow cancel help"
@@ -232,8 +258,96 @@ complete -c helm -n "__fish_seen_subcommand_from doctor" -a "scan repair help"
 complete -c helm -n "__fish_seen_subcommand_from onboarding" -a "status run reset help"
 complete -c helm -n "__fish_seen_subcommand_from self" -a "status check update uninstall auto-check help"
 complete -c helm -n "__fish_seen_subcommand_from auto-check" -a "status enable disable frequency help"
+complete -c helm -n "__fish_seen_subcommand_from notify-changed" -a "hook help"
+complete -c helm -n "__fish_seen_subcommand_from hook" -a "bash zsh fish"
 complete -c helm -n "__fish_seen_subcommand_from completion" -a "bash zsh fish help"
 "#;
+const POSIX_NOTIFY_CHANGED_HOOK_SCRIPT: &str = r#"# Helm shell hook: after brew, npm, cargo, or pipx changes packages, ask Helm
+# to refresh that manager right away. Source from ~/.bashrc or ~/.zshrc:
+#   eval "$(helm notify-changed hook bash)"
+_helm_notify_changed() {
+    (command helm notify-changed "$@" >/dev/null 2>&1 &)
+}
+brew() {
+    command brew "$@"
+    local helm_status=$?
+    case "$1" in
+        install|reinstall|uninstall|remove|rm|upgrade|update|link|unlink|pin|unpin|tap|untap|cleanup|autoremove)
+            _helm_notify_changed homebrew_formula homebrew_cask ;;
+    esac
+    return $helm_status
+}
+npm() {
+    command npm "$@"
+    local helm_status=$?
+    case " $* " in
+        *" -g "*|*" --global "*|*" --location=global "*)
+            _helm_notify_changed npm ;;
+    esac
+    return $helm_status
+}
+cargo() {
+    command cargo "$@"
+    local helm_status=$?
+    case "$1" in
+        install|uninstall)
+            _helm_notify_changed cargo ;;
+    esac
+    return $helm_status
+}
+pipx() {
+    command pipx "$@"
+    local helm_status=$?
+    case "$1" in
+        install|uninstall|uninstall-all|upgrade|upgrade-all|reinstall|reinstall-all|inject|uninject)
+            _helm_notify_changed pipx ;;
+    esac
+    return $helm_status
+}
+"#;
+const FISH_NOTIFY_CHANGED_HOOK_SCRIPT: &str = r#"# Helm shell hook: after brew, npm, cargo, or pipx changes packages, ask Helm
+# to refresh that manager right away. Source from ~/.config/fish/config.fish:
+#   helm notify-changed hook fish | source
+function _helm_notify_changed
+    command helm notify-changed $argv >/dev/null 2>&1 &
+    disown 2>/dev/null
+end
+function brew --wraps brew
+    command brew $argv
+    set -l helm_status $status
+    switch "$argv[1]"
+        case install reinstall uninstall remove rm upgrade update link unlink pin unpin tap untap cleanup autoremove
+            _helm_notify_changed homebrew_formula homebrew_cask
+    end
+    return $helm_status
+end
+function npm --wraps npm
+    command npm $argv
+    set -l helm_status $status
+    if contains -- -g $argv; or contains -- --global $argv; or contains -- --location=global $argv
+        _helm_notify_changed npm
+    end
+    return $helm_status
+end
+function cargo --wraps cargo
+    command cargo $argv
+    set -l helm_status $status
+    switch "$argv[1]"
+        case install uninstall
+            _helm_notify_changed cargo
+    end
+    return $helm_status
+end
+function pipx --wraps pipx
+    command pipx $argv
+    set -l helm_status $status
+    switch "$argv[1]"
+        case install uninstall uninstall-all upgrade upgrade-all reinstall reinstall-all inject uninject
+            _helm_notify_changed pipx
+    end
+    return $helm_status
+end
+"#;
 
 #[derive(Default, Debug, Clone)]
 struct GlobalOptions {
@@ -274,6 +388,7 @@ enum Command {
     Doctor,
     Onboarding,
     SelfCmd,
+    NotifyChanged,
     Completion,
     InternalCoordinator,
 }
@@ -318,6 +433,7 @@ struct CliDiagnosticsSummary {
     failure_classes: BTreeMap<String, usize>,
     failure_class_hints: BTreeMap<String, String>,
     coordinator: CliCoordinatorHealthSummary,
+    base_dirs: ToolBaseDirs,
 }
 
 #[derive(Serialize)]
@@ -341,6 +457,7 @@ struct CliTaskDiagnosticsOutput {
     cwd: Option<String>,
     program_path: Option<String>,
     path_snippet: Option<String>,
+    process_qos: Option<String>,
     started_at_unix_ms: Option<i64>,
     finished_at_unix_ms: Option<i64>,
     duration_ms: Option<u64>,
@@ -368,6 +485,8 @@ struct CliManagerStatus {
     display_name: String,
     authority: String,
     detected: bool,
+    detection_outcome: String,
+    detection_error_key: Option<String>,
     version: Option<String>,
     executable_path: Option<String>,
     enabled: bool,
@@ -378,6 +497,7 @@ struct CliManagerStatus {
     supports_package_install: bool,
     supports_package_uninstall: bool,
     supports_package_upgrade: bool,
+    supports_package_rollback: bool,
     selected_executable_path: Option<String>,
     selected_executable_differs_from_default: bool,
     executable_path_diagnostic: String,
@@ -483,6 +603,7 @@ struct ParsedManagerMutationArgs {
     install_method_override: Option<String>,
     install_options: helm_core::manager_lifecycle::ManagerInstallOptions,
     uninstall_options: helm_core::manager_lifecycle::ManagerUninstallOptions,
+    upgrade_all: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -499,6 +620,8 @@ struct UpgradeExecutionStep {
     cleanup_old_kegs: bool,
     pinned: bool,
     restart_required: bool,
+    /// Upgrade options; for the OS update step, the exact labels to install.
+    options: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -599,6 +722,10 @@ enum CoordinatorSubmitRequest {
     RustupSetProfile {
         profile: String,
     },
+    HomebrewAddTap {
+        tap: String,
+        url: Option<String>,
+    },
     Install {
         package_name: String,
         target_name: Option<String>,
@@ -608,11 +735,17 @@ enum CoordinatorSubmitRequest {
         package_name: String,
         target_name: Option<String>,
         version: Option<String>,
+        #[serde(default)]
+        disposal: UninstallDisposal,
     },
     Upgrade {
         package_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<UpgradeTargetKind>,
         target_name: Option<String>,
         version: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cleanup_old_kegs: bool,
     },
     Pin {
         package_name: String,
@@ -622,6 +755,16 @@ enum CoordinatorSubmitRequest {
         package_name: String,
         version: Option<String>,
     },
+    Rollback {
+        package_name: String,
+        version: String,
+    },
+    SetServiceState {
+        service: String,
+        action: ServiceAction,
+    },
+    Cleanup,
+    MeasurePackageSizes,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -664,6 +807,8 @@ enum CoordinatorPayload {
         action: String,
         before_version: Option<String>,
         after_version: Option<String>,
+        #[serde(default)]
+        disposal: Option<UninstallDisposal>,
     },
     Refreshed,
     InstalledPackages {
@@ -808,23 +953,28 @@ fn main() -> ExitCode {
         return ExitCode::SUCCESS;
     }
 
-    if matches!(command, Command::Completion) {
-        return cmd_completion(options.clone(), &command_args)
-            .map(|_| ExitCode::SUCCESS)
-            .unwrap_or_else(|error| {
-                let (json_emitted, normalized_error) = strip_json_error_marker(error.as_str());
-                let (marked_exit_code, normalized_error) = strip_exit_code_marker(normalized_error);
-                let exit_code =
-                    marked_exit_code.unwrap_or_else(|| exit_code_for_error(normalized_error));
-                if options.json {
-                    if !json_emitted {
-                        emit_cli_error_json("helm.cli.v1.error", normalized_error, exit_code);
-                    }
-                } else if !json_emitted {
-                    eprintln!("helm: {normalized_error}");
+    if matches!(command, Command::Completion)
+        || is_notify_changed_hook_request(command, &command_args)
+    {
+        let result = if matches!(command, Command::Completion) {
+            cmd_completion(options.clone(), &command_args)
+        } else {
+            cmd_notify_changed_hook(options.clone(), &command_args[1..])
+        };
+        return result.map(|_| ExitCode::SUCCESS).unwrap_or_else(|error| {
+            let (json_emitted, normalized_error) = strip_json_error_marker(error.as_str());
+            let (marked_exit_code, normalized_error) = strip_exit_code_marker(normalized_error);
+            let exit_code =
+                marked_exit_code.unwrap_or_else(|| exit_code_for_error(normalized_error));
+            if options.json {
+                if !json_emitted {
+                    emit_cli_error_json("helm.cli.v1.error", normalized_error, exit_code);
                 }
-                ExitCode::from(exit_code)
-            });
+            } else if !json_emitted {
+                eprintln!("helm: {normalized_error}");
+            }
+            ExitCode::from(exit_code)
+        });
     }
 
     if let Some(help_path) = extract_help_path(&command_args) {
@@ -1201,6 +1351,7 @@ fn parse_top_level_command(raw: &str) -> Option<Command> {
         "doctor" => Some(Command::Doctor),
         "onboarding" => Some(Command::Onboarding),
         "self" => Some(Command::SelfCmd),
+        "notify-changed" => Some(Command::NotifyChanged),
         "completion" => Some(Command::Completion),
         "__coordinator__" => Some(Command::InternalCoordinator),
         _ => None,
@@ -1289,6 +1440,7 @@ fn command_label(command: Command) -> &'static str {
         Command::Doctor => "doctor",
         Command::Onboarding => "onboarding",
         Command::SelfCmd => "self",
+        Command::NotifyChanged => "notify-changed",
         Command::Completion => "completion",
         Command::InternalCoordinator => "__coordinator__",
     }
@@ -1309,6 +1461,7 @@ fn emit_help_json_payload(command: Option<Command>, path: &[String], resolved: b
         "doctor",
         "onboarding",
         "self",
+        "notify-changed",
         "completion",
         "help",
     ];
@@ -1490,6 +1643,7 @@ fn command_help_topic_exists(command: Command, path: &[String]) -> bool {
             }
             false
         }
+        Command::NotifyChanged => path.is_empty() || (path.len() == 1 && path[0] == "hook"),
         Command::Completion => path.is_empty(),
         Command::Help | Command::Version | Command::InternalCoordinator => false,
     }
@@ -2810,11 +2964,14 @@ fn cmd_packages_mutation(
             package_name: parsed.package_name.clone(),
             target_name: None,
             version: parsed.version.clone(),
+            disposal: parsed.disposal,
         }),
         "upgrade" => Some(CoordinatorSubmitRequest::Upgrade {
             package_name: Some(parsed.package_name.clone()),
+            target: Some(UpgradeTargetKind::Package),
             target_name: None,
             version: parsed.version.clone(),
+            cleanup_old_kegs: false,
         }),
         "pin" if supports_native_pin => Some(CoordinatorSubmitRequest::Pin {
             package_name: parsed.package_name.clone(),
@@ -2850,20 +3007,35 @@ fn cmd_packages_mutation(
         } else {
             PinKind::Virtual
         };
+        let pinned_version = if pin_kind == PinKind::Native {
+            None
+        } else if parsed.version.is_none() && parsed.manager == ManagerId::HomebrewCask {
+            // Casks have no native pin; hold the installed version so the pin
+            // lapses once the cask updates itself.
+            store
+                .list_installed()
+                .map_err(|error| format!("failed to read installed packages: {error}"))?
+                .into_iter()
+                .find(|installed| installed.package == package)
+                .and_then(|installed| installed.installed_version)
+        } else {
+            parsed.version.clone()
+        };
         store
             .upsert_pin(&PinRecord {
                 package: package.clone(),
                 kind: pin_kind,
-                pinned_version: if pin_kind == PinKind::Native {
-                    None
-                } else {
-                    parsed.version.clone()
-                },
+                pinned_version: pinned_version.clone(),
+                version_constraint: None,
                 created_at: SystemTime::now(),
             })
             .map_err(|error| format!("failed to persist pin record: {error}"))?;
         store
-            .set_snapshot_pinned(&package, parsed.version.as_deref(), true)
+            .set_snapshot_pinned(
+                &package,
+                pinned_version.as_deref().or(parsed.version.as_deref()),
+                true,
+            )
             .map_err(|error| format!("failed to mark package pinned in snapshot: {error}"))?;
     }
 
@@ -2943,6 +3115,7 @@ fn cmd_packages_mutation(
                 action,
                 before_version,
                 after_version,
+                disposal,
             }) => {
                 if options.json {
                     emit_json_payload(
@@ -2954,6 +3127,7 @@ fn cmd_packages_mutation(
                             "action": action,
                             "before_version": before_version,
                             "after_version": after_version,
+                            "disposal": disposal,
                             "uninstall_preview": package_uninstall_preview
                         }),
                     );
@@ -2962,6 +3136,9 @@ fn cmd_packages_mutation(
                         "Package {} {} via manager '{}' (task #{})",
                         package_name, subcommand, manager_id, task_id
                     );
+                    if disposal == Some(UninstallDisposal::Trash) {
+                        println!("  app bundles moved to Trash");
+                    }
                     if let Some(preview) = package_uninstall_preview.as_ref() {
                         println!(
                             "  blast_radius_score: {} (requires_confirmation={})",
@@ -3545,14 +3722,7 @@ fn cmd_updates_run(
 
     let mut results: Vec<CliUpgradeRunStepResult> = Vec::with_capacity(steps.len());
     for step in &steps {
-        let request = AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(PackageRef {
-                manager: step.manager,
-                name: upgrade_request_name(step),
-            }),
-            target_name: None,
-            version: None,
-        });
+        let request = upgrade_request_for_step(step);
         let response = tokio_runtime.block_on(submit_request_wait(&runtime, step.manager, request));
         match response {
             Ok((task_id, _)) => results.push(CliUpgradeRunStepResult {
@@ -3658,7 +3828,11 @@ fn cmd_tasks_list(
 ) -> Result<(), String> {
     let parsed = parse_tasks_list_args(command_args)?;
     let enabled_map = manager_enabled_map(store)?;
-    let mut tasks = list_tasks_for_enabled(store, &enabled_map)?;
+    let mut tasks = if parsed.all {
+        list_tasks_for_enabled(store, &enabled_map)?
+    } else {
+        list_visible_tasks_for_enabled(store, &enabled_map)?
+    };
     if let Some(status_filter) = parsed.status_filter.as_deref() {
         tasks.retain(|task| task.status == status_filter);
     }
@@ -4042,6 +4216,10 @@ fn cmd_managers(
                 println!("  authority: {}", row.authority);
                 println!("  enabled: {}", row.enabled);
                 println!("  detected: {}", row.detected);
+                println!("  detection_outcome: {}", row.detection_outcome);
+                if let Some(error_key) = row.detection_error_key.as_deref() {
+                    println!("  detection_error_key: {error_key}");
+                }
                 println!("  version: {}", row.version.as_deref().unwrap_or("-"));
                 println!(
                     "  executable_path: {}",
@@ -4247,7 +4425,9 @@ fn cmd_managers_list(store: &SqliteStore, options: GlobalOptions) -> Result<(),
         } else {
             "disabled"
         };
-        let detected = if manager.detected {
+        let detected = if manager.detection_outcome == "probe_failed" {
+            "probe-failed"
+        } else if manager.detected {
             "detected"
         } else {
             "not-detected"
@@ -4467,6 +4647,10 @@ fn cmd_managers_mutation(
         }
 
         (plan.target_manager, plan.request, Some(plan.preview))
+    } else if parsed.upgrade_all {
+        let (target_manager, request) =
+            build_manager_update_then_upgrade_all_request(store.as_ref(), manager)?;
+        (target_manager, request, None)
     } else {
         let (target_manager, request) = build_manager_mutation_request_with_options(
             store.as_ref(),
@@ -4528,6 +4712,7 @@ fn cmd_managers_mutation(
             action,
             before_version,
             after_version,
+            ..
         }) => {
             if options.json {
                 emit_json_payload(
@@ -8146,6 +8331,133 @@ fn cmd_doctor_repair(
     }
 }
 
+fn is_notify_changed_hook_request(command: Command, command_args: &[String]) -> bool {
+    matches!(command, Command::NotifyChanged)
+        && command_args.first().map(String::as_str) == Some("hook")
+        && !command_args.iter().skip(1).any(|arg| is_help_token(arg))
+}
+
+fn notify_changed_hook_script(shell: &str) -> Option<&'static str> {
+    match shell {
+        "bash" | "zsh" => Some(POSIX_NOTIFY_CHANGED_HOOK_SCRIPT),
+        "fish" => Some(FISH_NOTIFY_CHANGED_HOOK_SCRIPT),
+        _ => None,
+    }
+}
+
+fn cmd_notify_changed_hook(options: GlobalOptions, command_args: &[String]) -> Result<(), String> {
+    if command_args.len() != 1 {
+        return Err(
+            "notify-changed hook requires exactly one shell argument: bash, zsh, or fish"
+                .to_string(),
+        );
+    }
+    let shell = command_args[0].as_str();
+    let script = notify_changed_hook_script(shell)
+        .ok_or_else(|| format!("unsupported hook shell '{shell}' (expected: bash, zsh, fish)"))?;
+    if options.json {
+        emit_json_payload(
+            "helm.cli.v1.notify_changed.hook",
+            json!({
+                "shell": shell,
+                "script": script
+            }),
+        );
+    } else {
+        print!("{script}");
+    }
+    Ok(())
+}
+
+fn cmd_notify_changed(
+    store: Arc<SqliteStore>,
+    options: GlobalOptions,
+    command_args: &[String],
+) -> Result<(), String> {
+    if command_args.is_empty() {
+        return Err("notify-changed requires at least one manager id".to_string());
+    }
+    let mut managers = Vec::new();
+    for raw in command_args {
+        let manager = parse_manager_id(raw)?;
+        if !managers.contains(&manager) {
+            managers.push(manager);
+        }
+    }
+
+    let enabled_map = manager_enabled_map(store.as_ref())?;
+    // A queued refresh has not listed packages yet, so it will see the change.
+    let queued_refreshes: HashSet<ManagerId> = store
+        .list_recent_tasks(TASK_FETCH_LIMIT)
+        .map_err(|error| format!("failed to list recent tasks: {error}"))?
+        .into_iter()
+        .filter(|task| task.task_type == TaskType::Refresh && task.status == TaskStatus::Queued)
+        .map(|task| task.manager)
+        .collect();
+
+    let mut submitted = Vec::new();
+    let mut skipped = Vec::new();
+    for manager in managers {
+        if !enabled_map.get(&manager).copied().unwrap_or(false) {
+            skipped.push((manager, "disabled"));
+            continue;
+        }
+        if queued_refreshes.contains(&manager) {
+            skipped.push((manager, "refresh_queued"));
+            continue;
+        }
+        let response = coordinator_start_workflow(
+            store.as_ref(),
+            CoordinatorWorkflowRequest::RefreshManager {
+                manager_id: manager.as_str().to_string(),
+            },
+            ExecutionMode::Detach,
+        )?;
+        let job_id = response
+            .job_id
+            .ok_or_else(|| "coordinator workflow response missing job id".to_string())?;
+        submitted.push((manager, job_id));
+    }
+
+    if options.json {
+        emit_json_payload(
+            "helm.cli.v1.notify_changed",
+            json!({
+                "submitted": submitted
+                    .iter()
+                    .map(|(manager, job_id)| json!({
+                        "manager_id": manager.as_str(),
+                        "job_id": job_id
+                    }))
+                    .collect::<Vec<_>>(),
+                "skipped": skipped
+                    .iter()
+                    .map(|(manager, reason)| json!({
+                        "manager_id": manager.as_str(),
+                        "reason": reason
+                    }))
+                    .collect::<Vec<_>>()
+            }),
+        );
+    } else {
+        for (manager, job_id) in &submitted {
+            println!(
+                "Refresh workflow submitted for '{}' (job {}).",
+                manager.as_str(),
+                job_id
+            );
+        }
+        for (manager, reason) in &skipped {
+            let reason = match *reason {
+                "disabled" => "manager is disabled",
+                _ => "a refresh is already queued",
+            };
+            println!("Skipped '{}': {reason}.", manager.as_str());
+        }
+    }
+    Ok(())
+}
+
 fn cmd_completion(options: GlobalOptions, command_args: &[String]) -> Result<(), String> {
     if command_args.is_empty() || is_help_token(&command_args[0]) {
         if options.json {
@@ -9228,14 +9540,7 @@ fn run_coordinator_workflow(
                 manager_filter,
             )?;
             let failures = count_upgrade_step_failures(&steps, |step| {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: step.manager,
-                        name: upgrade_request_name(step),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
+                let request = upgrade_request_for_step(step);
                 tokio_runtime
                     .block_on(submit_request_wait(&runtime, step.manager, request))
                     .map(|_| ())
@@ -9361,6 +9666,13 @@ fn coordinator_submit_request_to_adapter(
                 operation: PackageDetailOperation::SetProfile { profile },
             })
         }
+        CoordinatorSubmitRequest::HomebrewAddTap { tap, url } => {
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+                manager,
+                package: None,
+                operation: PackageDetailOperation::AddTap { tap, url },
+            })
+        }
         CoordinatorSubmitRequest::Install {
             package_name,
             target_name,
@@ -9372,11 +9684,13 @@ fn coordinator_submit_request_to_adapter(
             },
             target_name,
             version,
+            options: Vec::new(),
         }),
         CoordinatorSubmitRequest::Uninstall {
             package_name,
             target_name,
             version,
+            disposal,
         } => AdapterRequest::Uninstall(UninstallRequest {
             package: PackageRef {
                 manager,
@@ -9384,15 +9698,20 @@ fn coordinator_submit_request_to_adapter(
             },
             target_name,
             version,
+            disposal,
         }),
         CoordinatorSubmitRequest::Upgrade {
             package_name,
+            target,
             target_name,
             version,
+            cleanup_old_kegs,
         } => AdapterRequest::Upgrade(UpgradeRequest {
-            package: package_name.map(|name| PackageRef { manager, name }),
+            target: UpgradeTarget::from_wire(manager, target, package_name),
             target_name,
             version,
+            options: Vec::new(),
+            cleanup_old_kegs,
         }),
         CoordinatorSubmitRequest::Pin {
             package_name,
@@ -9413,6 +9732,23 @@ fn coordinator_submit_request_to_adapter(
                 name: package_name,
             },
         }),
+        CoordinatorSubmitRequest::Rollback {
+            package_name,
+            version,
+        } => AdapterRequest::Rollback(RollbackRequest {
+            package: PackageRef {
+                manager,
+                name: package_name,
+            },
+            version,
+        }),
+        CoordinatorSubmitRequest::SetServiceState { service, action } => {
+            AdapterRequest::SetServiceState(SetServiceStateRequest { service, action })
+        }
+        CoordinatorSubmitRequest::Cleanup => AdapterRequest::Cleanup(CleanupRequest),
+        CoordinatorSubmitRequest::MeasurePackageSizes => {
+            AdapterRequest::MeasurePackageSizes(MeasurePackageSizesRequest)
+        }
     }
 }
 
@@ -9494,6 +9830,20 @@ fn adapter_request_to_coordinator_submit(
             PackageDetailOperation::SetProfile { profile } => {
                 Ok(CoordinatorSubmitRequest::RustupSetProfile { profile })
             }
+            PackageDetailOperation::AddChild {
+                kind: PackageDetailChildKind::Tap,
+                value,
+            } => Ok(CoordinatorSubmitRequest::HomebrewAddTap {
+                tap: value,
+                url: None,
+            }),
+            PackageDetailOperation::AddTap { tap, url } => {
+                Ok(CoordinatorSubmitRequest::HomebrewAddTap { tap, url })
+            }
+            PackageDetailOperation::RemoveChild {
+                kind: PackageDetailChildKind::Tap,
+                ..
+            } => Err("coordinator submit request does not support removing taps".to_string()),
         },
         AdapterRequest::Install(install) => Ok(CoordinatorSubmitRequest::Install {
             package_name: install.package.name,
@@ -9504,12 +9854,18 @@ fn adapter_request_to_coordinator_submit(
             package_name: uninstall.package.name,
             target_name: uninstall.target_name,
             version: uninstall.version,
+            disposal: uninstall.disposal,
         }),
-        AdapterRequest::Upgrade(upgrade) => Ok(CoordinatorSubmitRequest::Upgrade {
-            package_name: upgrade.package.map(|package| package.name),
-            target_name: upgrade.target_name,
-            version: upgrade.version,
-        }),
+        AdapterRequest::Upgrade(upgrade) => {
+            let (target, package_name) = upgrade.target.to_wire();
+            Ok(CoordinatorSubmitRequest::Upgrade {
+                package_name,
+                target: Some(target),
+                target_name: upgrade.target_name,
+                version: upgrade.version,
+                cleanup_old_kegs: upgrade.cleanup_old_kegs,
+            })
+        }
         AdapterRequest::Pin(pin) => Ok(CoordinatorSubmitRequest::Pin {
             package_name: pin.package.name,
             version: pin.version,
@@ -9518,6 +9874,16 @@ fn adapter_request_to_coordinator_submit(
             package_name: unpin.package.name,
             version: None,
         }),
+        AdapterRequest::Rollback(rollback) => Ok(CoordinatorSubmitRequest::Rollback {
+            package_name: rollback.package.name,
+            version: rollback.version,
+        }),
+        AdapterRequest::SetServiceState(request) => Ok(CoordinatorSubmitRequest::SetServiceState {
+            service: request.service,
+            action: request.action,
+        }),
+        AdapterRequest::Cleanup(_) => Ok(CoordinatorSubmitRequest::Cleanup),
+        AdapterRequest::MeasurePackageSizes(_) => Ok(CoordinatorSubmitRequest::MeasurePackageSizes),
         unsupported => Err(format!(
             "coordinator submit request does not support adapter action '{:?}'",
             unsupported.action()
@@ -9539,7 +9905,16 @@ fn adapter_response_to_coordinator_payload(response: AdapterResponse) -> Coordin
         AdapterResponse::SnapshotSync {
             installed: _,
             outdated: _,
+            stage_failures: _,
         } => CoordinatorPayload::Refreshed,
+        AdapterResponse::SelfVersion(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::PackageFiles(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::Dependencies(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::RequestedPackages(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::PackageInfo(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::Services(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::Cleanup(_) => CoordinatorPayload::Refreshed,
+        AdapterResponse::PackageSizes(_) => CoordinatorPayload::Refreshed,
         AdapterResponse::InstalledPackages(packages) => CoordinatorPayload::InstalledPackages {
             count: packages.len(),
         },
@@ -9555,6 +9930,7 @@ fn adapter_response_to_coordinator_payload(response: AdapterResponse) -> Coordin
             action: format!("{:?}", mutation.action).to_lowercase(),
             before_version: mutation.before_version,
             after_version: mutation.after_version,
+            disposal: mutation.disposal,
         },
     }
 }
@@ -9669,6 +10045,33 @@ fn cmd_diagnostics_summary(store: &SqliteStore, options: GlobalOptions) -> Resul
             summary.coordinator.stale_reasons.join(", ")
         );
     }
+    let base_dirs = &summary.base_dirs;
+    println!("  base_dirs:");
+    for (label, path) in [
+        ("home", &base_dirs.home),
+        ("xdg_data_home", &base_dirs.xdg_data_home),
+        ("xdg_config_home", &base_dirs.xdg_config_home),
+        ("xdg_cache_home", &base_dirs.xdg_cache_home),
+        ("local_bin_dir", &base_dirs.local_bin_dir),
+        ("cargo_home", &base_dirs.cargo_home),
+        ("rustup_home", &base_dirs.rustup_home),
+        ("asdf_dir", &base_dirs.asdf_dir),
+        ("asdf_data_dir", &base_dirs.asdf_data_dir),
+        ("mise_data_dir", &base_dirs.mise_data_dir),
+        ("mise_config_dir", &base_dirs.mise_config_dir),
+        ("mise_cache_dir", &base_dirs.mise_cache_dir),
+        ("rtx_data_dir", &base_dirs.rtx_data_dir),
+        ("pipx_home", &base_dirs.pipx_home),
+        ("pipx_bin_dir", &base_dirs.pipx_bin_dir),
+        ("npm_prefix", &base_dirs.npm_prefix),
+    ] {
+        println!(
+            "    {label}: {}",
+            path.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
     Ok(())
 }
 
@@ -9750,6 +10153,10 @@ fn cmd_diagnostics_task(
             "  path_snippet: {}",
             output_payload.path_snippet.as_deref().unwrap_or("-")
         );
+        println!(
+            "  process_qos: {}",
+            output_payload.process_qos.as_deref().unwrap_or("-")
+        );
         println!(
             "  started_at_unix_ms: {}",
             output_payload
@@ -9925,39 +10332,7 @@ fn cmd_diagnostics_export(
     command_args: &[String],
 ) -> Result<(), String> {
     let export_path = parse_diagnostics_export_path(command_args)?;
-    let summary = build_diagnostics_summary(store)?;
-    let managers = list_managers(store)?;
-    let tasks = store
-        .list_recent_tasks(TASK_FETCH_LIMIT)
-        .map_err(|error| format!("failed to list recent tasks: {error}"))?
-        .into_iter()
-        .map(task_to_cli_task)
-        .collect::<Vec<_>>();
-
-    let failed_task_logs = tasks
-        .iter()
-        .filter(|task| task.status == "failed")
-        .take(25)
-        .map(|task| {
-            let logs = store
-                .list_task_logs(TaskId(task.id), 128)
-                .map_err(|error| format!("failed to list task logs: {error}"))?
-                .into_iter()
-                .map(task_log_to_cli_record)
-                .collect::<Vec<_>>();
-            Ok(json!({
-                "taskId": task.id,
-                "logs": logs
-            }))
-        })
-        .collect::<Result<Vec<_>, String>>()?;
-
-    let data = json!({
-        "summary": summary,
-        "managers": managers,
-        "tasks": tasks,
-        "failedTaskLogs": failed_task_logs
-    });
+    let data = build_diagnostics_export_data(store)?;
 
     let payload = json!({
         "schema": "helm.cli.v1.diagnostics.export",
@@ -9977,11 +10352,13 @@ fn cmd_diagnostics_export(
                 "helm.cli.v1.diagnostics.export.write",
                 json!({
                     "path": path,
-                    "written": true
+                    "written": true,
+                    "redaction": data["redaction"]
                 }),
             );
         } else {
             println!("Diagnostics export written to '{}'.", path);
+            println!("{}", diagnostics_redaction_summary_line(&data["redaction"]));
         }
         return Ok(());
     }
@@ -9997,6 +10374,106 @@ fn cmd_diagnostics_export(
     Ok(())
 }
 
+/// Diagnostics bundle contents, redacted for sharing. Recent failed task
+/// logs and archived outputs are included; `redaction` reports what was
+/// replaced.
+fn build_diagnostics_export_data(store: &SqliteStore) -> Result<serde_json::Value, String> {
+    let summary = build_diagnostics_summary(store)?;
+    let managers = list_managers(store)?;
+    let tasks = store
+        .list_recent_tasks(TASK_FETCH_LIMIT)
+        .map_err(|error| format!("failed to list recent tasks: {error}"))?
+        .into_iter()
+        .map(task_to_cli_task)
+        .collect::<Vec<_>>();
+
+    let failed_task_logs = tasks
+        .iter()
+        .filter(|task| task.status == "failed")
+        .take(25)
+        .map(|task| {
+            let logs = store
+                .list_task_logs(TaskId(task.id), 128)
+                .map_err(|error| format!("failed to list task logs: {error}"))?
+                .into_iter()
+                .map(task_log_to_cli_record)
+                .collect::<Vec<_>>();
+            Ok(json!({
+                "taskId": task.id,
+                "logs": logs
+            }))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let failed_task_outputs = store
+        .list_task_history(
+            &TaskHistoryFilter {
+                status: Some(TaskStatus::Failed),
+                ..TaskHistoryFilter::default()
+            },
+            0,
+            25,
+        )
+        .map_err(|error| format!("failed to list task history: {error}"))?
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "taskId": entry.task_id.0,
+                "manager": entry.manager.as_str(),
+                "exitCode": entry.exit_code,
+                "errorCode": entry.error_code,
+                "errorMessage": entry.error_message,
+                "stdoutTail": entry.stdout_tail,
+                "stderrTail": entry.stderr_tail
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let custom_patterns = store
+        .diagnostics_redaction_patterns()
+        .map_err(|error| format!("failed to read diagnostics redaction patterns: {error}"))?;
+    let mut redactor = DiagnosticsRedactor::new(RedactionConfig::current_user(
+        parse_custom_patterns(custom_patterns.as_deref()),
+    ));
+    let mut data = json!({
+        "summary": summary,
+        "managers": managers,
+        "tasks": tasks,
+        "failedTaskLogs": failed_task_logs,
+        "failedTaskOutputs": failed_task_outputs
+    });
+    redactor.redact_json(&mut data);
+    data["redaction"] = serde_json::to_value(redactor.report())
+        .map_err(|error| format!("failed to serialize redaction report: {error}"))?;
+    Ok(data)
+}
+
+fn diagnostics_redaction_summary_line(report: &serde_json::Value) -> String {
+    let rules = report["rules"]
+        .as_array()
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|entry| {
+                    Some(format!(
+                        "{} {}",
+                        entry["rule"].as_str()?,
+                        entry["count"].as_u64()?
+                    ))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if rules.is_empty() {
+        return "Nothing was redacted.".to_string();
+    }
+    format!(
+        "Redacted {} value(s): {}.",
+        report["totalReplacements"].as_u64().unwrap_or(0),
+        rules.join(", ")
+    )
+}
+
 fn parse_diagnostics_export_path(command_args: &[String]) -> Result<Option<String>, String> {
     let mut path: Option<String> = None;
     let mut index = 0usize;
@@ -10027,6 +10504,7 @@ fn task_output_to_cli_diagnostics(output: Option<&TaskOutputRecord>) -> CliTaskD
         cwd: output.and_then(|entry| entry.cwd.clone()),
         program_path: output.and_then(|entry| entry.program_path.clone()),
         path_snippet: output.and_then(|entry| entry.path_snippet.clone()),
+        process_qos: output.and_then(|entry| entry.process_qos.clone()),
         started_at_unix_ms: output.and_then(|entry| entry.started_at_unix_ms),
         finished_at_unix_ms: output.and_then(|entry| entry.finished_at_unix_ms),
         duration_ms: output.and_then(|entry| entry.duration_ms),
@@ -10306,6 +10784,7 @@ fn build_diagnostics_summary(store: &SqliteStore) -> Result<CliDiagnosticsSummar
         failure_classes,
         failure_class_hints,
         coordinator: build_coordinator_health_summary(),
+        base_dirs: ToolBaseDirs::from_env(),
     })
 }
 
@@ -10722,6 +11201,7 @@ struct ParsedPackageMutationArgs {
     version: Option<String>,
     preview: bool,
     yes: bool,
+    disposal: UninstallDisposal,
 }
 
 fn parse_package_show_args(command_args: &[String]) -> Result<ParsedPackageShowArgs, String> {
@@ -10780,6 +11260,7 @@ fn parse_package_mutation_args(
     let uninstall_command = subcommand == "uninstall";
     let mut preview = false;
     let mut yes = false;
+    let mut disposal = UninstallDisposal::Delete;
 
     let mut index = 1usize;
     while index < command_args.len() {
@@ -10823,6 +11304,10 @@ fn parse_package_mutation_args(
                 yes = true;
                 index += 1;
             }
+            "--trash" if uninstall_command => {
+                disposal = UninstallDisposal::Trash;
+                index += 1;
+            }
             other => {
                 return Err(format!("unsupported package mutation argument '{other}'"));
             }
@@ -10831,6 +11316,12 @@ fn parse_package_mutation_args(
 
     let manager = manager
         .ok_or_else(|| "package mutation requires --manager <id> or name@manager".to_string())?;
+    if !disposal.supported_by(manager) {
+        return Err(format!(
+            "manager '{}' does not support uninstall --trash",
+            manager.as_str()
+        ));
+    }
 
     if allow_version
         && let Some((coordinate_package_name, coordinate_version)) =
@@ -10854,6 +11345,7 @@ fn parse_package_mutation_args(
         version,
         preview,
         yes,
+        disposal,
     })
 }
 
@@ -10968,6 +11460,13 @@ fn cli_tokio_runtime() -> Result<&'static tokio::runtime::Runtime, String> {
 
 fn build_adapter_runtime(store: Arc<SqliteStore>) -> Result<AdapterRuntime, String> {
     sync_manager_executable_overrides(store.as_ref())?;
+    if let Some(root) = helm_core::execution::task_temp_dir_store::default_temp_root() {
+        helm_core::execution::task_temp_dir_store::set_temp_root(root);
+    }
+    helm_engine::registry_clients::install_registry_clients();
+    helm_core::adapters::npm_registry::set_npm_registry_outdated_checks(
+        store.npm_registry_outdated_checks().unwrap_or(false),
+    );
 
     let executor = Arc::new(TokioProcessExecutor);
     let adapters: Vec<Arc<dyn ManagerAdapter>> = vec![
@@ -11040,8 +11539,19 @@ fn build_adapter_runtime(store: Arc<SqliteStore>) -> Result<AdapterRuntime, Stri
         )),
     ];
 
-    AdapterRuntime::with_all_stores(adapters, store.clone(), store.clone(), store.clone(), store)
-        .map_err(format_core_error)
+    AdapterRuntime::with_all_stores(
+        adapters,
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .map(|runtime| {
+        runtime
+            .with_dependency_store(store.clone())
+            .with_pin_store(store)
+    })
+    .map_err(format_core_error)
 }
 
 fn sync_manager_executable_overrides(store: &SqliteStore) -> Result<(), String> {
@@ -11060,6 +11570,7 @@ fn sync_manager_executable_overrides(store: &SqliteStore) -> Result<(), String>
 
     let mut executable_overrides: HashMap<ManagerId, PathBuf> = HashMap::new();
     let mut timeout_profiles: HashMap<ManagerId, ManagerTimeoutProfile> = HashMap::new();
+    let mut process_qos: HashMap<ManagerId, ProcessQos> = HashMap::new();
     for manager in ManagerId::ALL {
         let preferred = preferences
             .get(&manager)
@@ -11092,8 +11603,22 @@ fn sync_manager_executable_overrides(store: &SqliteStore) -> Result<(), String>
         if profile.hard_timeout.is_some() || profile.idle_timeout.is_some() {
             timeout_profiles.insert(manager, profile);
         }
+        if let Some(qos) = preferences
+            .get(&manager)
+            .and_then(|preference| preference.process_qos.as_deref())
+            .and_then(|value| value.parse::<ProcessQos>().ok())
+        {
+            process_qos.insert(manager, qos);
+        }
     }
-    replace_manager_execution_preferences(executable_overrides, timeout_profiles);
+    replace_manager_execution_preferences(executable_overrides, timeout_profiles, process_qos);
+    set_default_process_qos(
+        store
+            .process_qos()
+            .ok()
+            .and_then(|value| value.parse::<ProcessQos>().ok())
+            .unwrap_or_default(),
+    );
     Ok(())
 }
 
@@ -11716,6 +12241,27 @@ fn list_tasks_for_enabled(
         .collect())
 }
 
+/// Enabled-manager tasks shaped like the app's task list, per the
+/// `task_view_*` settings.
+fn list_visible_tasks_for_enabled(
+    store: &SqliteStore,
+    enabled_map: &HashMap<ManagerId, bool>,
+) -> Result<Vec<CliTaskRecord>, String> {
+    let tasks = store
+        .list_recent_tasks(TASK_FETCH_LIMIT)
+        .map_err(|error| format!("failed to list tasks: {error}"))?;
+    let view_options = TaskViewOptions::from_store(store)
+        .map_err(|error| format!("failed to read task view settings: {error}"))?;
+    let tasks = tasks
+        .into_iter()
+        .filter(|task| enabled_map.get(&task.manager).copied().unwrap_or(true))
+        .collect();
+    Ok(build_visible_tasks(tasks, &view_options)
+        .into_iter()
+        .map(task_to_cli_task)
+        .collect())
+}
+
 fn task_to_cli_task(task: TaskRecord) -> CliTaskRecord {
     let status = match task.status {
         TaskStatus::Queued => "queued",
@@ -11987,6 +12533,12 @@ fn list_managers(store: &SqliteStore) -> Result<Vec<CliManagerStatus>, String> {
         .list_install_instances(None)
         .map_err(|error| format!("failed to list manager install instances: {error}"))?;
 
+    let detection_outcomes: HashMap<ManagerId, DetectionOutcome> = store
+        .list_detection_outcomes()
+        .map_err(|error| format!("failed to list detection outcomes: {error}"))?
+        .into_iter()
+        .collect();
+
     let detection_map: HashMap<ManagerId, helm_core::models::DetectionInfo> =
         detections.into_iter().collect();
     let preference_map: HashMap<ManagerId, helm_core::persistence::ManagerPreference> = preferences
@@ -12073,6 +12625,16 @@ fn list_managers(store: &SqliteStore) -> Result<Vec<CliManagerStatus>, String> {
                 .map(std::path::Path::new),
         );
         let enabled = configured_enabled && eligibility.is_eligible;
+        let detected = detection.map(|info| info.installed).unwrap_or(false);
+        let detection_outcome =
+            detection_outcomes
+                .get(&descriptor.id)
+                .cloned()
+                .unwrap_or(if detected {
+                    DetectionOutcome::Installed
+                } else {
+                    DetectionOutcome::NotFound
+                });
 
         rows.push(CliManagerStatus {
             manager_id: descriptor.id.as_str().to_string(),
@@ -12084,7 +12646,9 @@ fn list_managers(store: &SqliteStore) -> Result<Vec<CliManagerStatus>, String> {
                 ManagerAuthority::DetectionOnly => "detection_only",
             }
             .to_string(),
-            detected: detection.map(|info| info.installed).unwrap_or(false),
+            detected,
+            detection_outcome: detection_outcome.as_str().to_string(),
+            detection_error_key: detection_outcome.error_key().map(str::to_string),
             version: detection.and_then(|info| info.version.clone()),
             executable_path: detection
                 .and_then(|info| info.executable_path.as_ref())
@@ -12100,6 +12664,7 @@ fn list_managers(store: &SqliteStore) -> Result<Vec<CliManagerStatus>, String> {
             supports_package_install: descriptor.capabilities.contains(&Capability::Install),
             supports_package_uninstall: descriptor.capabilities.contains(&Capability::Uninstall),
             supports_package_upgrade: descriptor.capabilities.contains(&Capability::Upgrade),
+            supports_package_rollback: descriptor.capabilities.contains(&Capability::Rollback),
             selected_executable_path,
             selected_executable_differs_from_default,
             executable_path_diagnostic,
@@ -12531,13 +13096,6 @@ fn normalize_path_string(path: &std::path::Path) -> Option<String> {
     }
 }
 
-fn absolute_env_path(key: &str) -> Option<std::path::PathBuf> {
-    std::env::var_os(key)
-        .filter(|value| !value.is_empty())
-        .map(std::path::PathBuf::from)
-        .filter(|path| path.is_absolute() && !path.as_os_str().is_empty())
-}
-
 fn manager_additional_bin_roots() -> Vec<std::path::PathBuf> {
     manager_additional_bin_roots_for_home(std::env::var_os("HOME").map(std::path::PathBuf::from))
 }
@@ -12557,26 +13115,11 @@ fn manager_additional_bin_roots_for_home(
         std::path::PathBuf::from("/nix/var/nix/profiles/default/bin"),
     ];
 
-    if let Some(cargo_home) = absolute_env_path("CARGO_HOME") {
-        roots.push(cargo_home.join("bin"));
-    }
-    if let Some(home) = home {
-        roots.push(home.join(".local/bin"));
-        roots.push(home.join(".cargo/bin"));
-        roots.push(home.join(".asdf/bin"));
-        roots.push(home.join(".asdf/shims"));
-        roots.push(home.join(".local/share/mise/shims"));
-        roots.push(home.join(".local/share/rtx/shims"));
+    let base_dirs = ToolBaseDirs::resolve(home, |key| std::env::var_os(key));
+    roots.extend(base_dirs.tool_bin_roots());
+    if let Some(home) = &base_dirs.home {
         roots.push(home.join(".nix-profile/bin"));
     }
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
 
     roots
 }
@@ -12632,18 +13175,10 @@ fn manager_versioned_install_roots_for_home(
             | ManagerId::Bundler
             | ManagerId::Cargo
             | ManagerId::CargoBinstall
-    ) && let Some(home) = home
-    {
-        roots.push(home.join(".asdf/installs"));
-        roots.push(home.join(".local/share/mise/installs"));
-        roots.push(home.join(".local/share/rtx/installs"));
-    }
-
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("installs"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("installs"));
+    ) {
+        roots.extend(
+            ToolBaseDirs::resolve(home, |key| std::env::var_os(key)).tool_version_install_roots(),
+        );
     }
 
     roots
@@ -12853,14 +13388,6 @@ fn manager_selected_install_method(store: &SqliteStore, manager: ManagerId) -> O
     normalize_install_method(manager, preference.selected_install_method)
 }
 
-fn encode_homebrew_upgrade_target(package_name: &str, cleanup_old_kegs: bool) -> String {
-    if cleanup_old_kegs {
-        format!("{package_name}@@helm.cleanup")
-    } else {
-        package_name.to_string()
-    }
-}
-
 fn effective_homebrew_keg_policy(store: &SqliteStore, package_name: &str) -> HomebrewKegPolicy {
     let package = PackageRef {
         manager: ManagerId::HomebrewFormula,
@@ -12876,6 +13403,22 @@ fn effective_homebrew_keg_policy(store: &SqliteStore, package_name: &str) -> Hom
         .unwrap_or(HomebrewKegPolicy::Keep)
 }
 
+/// Install options saved for a package that still apply when upgrading it.
+fn persisted_upgrade_options(
+    store: &SqliteStore,
+    manager: ManagerId,
+    package_name: &str,
+) -> Vec<String> {
+    let package = PackageRef {
+        manager,
+        name: package_name.to_string(),
+    };
+    store
+        .package_install_options(&package)
+        .map(|options| helm_core::install_options::upgrade_options_from_install(manager, &options))
+        .unwrap_or_default()
+}
+
 fn homebrew_dependency_available(store: &SqliteStore) -> bool {
     let mut detected_path: Option<PathBuf> = None;
     if let Ok(detections) = store.list_detections()
@@ -13155,6 +13698,24 @@ fn build_manager_mutation_request(
     )
 }
 
+fn build_manager_update_then_upgrade_all_request(
+    store: &SqliteStore,
+    manager: ManagerId,
+) -> Result<(ManagerId, AdapterRequest), String> {
+    let active_instance = active_manager_install_instance(store, manager)?;
+    let update_plan =
+        helm_core::manager_lifecycle::plan_manager_update(manager, active_instance.as_ref())
+            .map_err(|error| manager_update_plan_error_message(manager, error))?;
+    let request = helm_core::manager_lifecycle::build_update_then_upgrade_all_request(&update_plan)
+        .ok_or_else(|| {
+            format!(
+                "manager '{}' cannot upgrade all packages after updating itself",
+                manager.as_str(),
+            )
+        })?;
+    Ok((update_plan.target_manager, request))
+}
+
 fn build_manager_mutation_request_with_options(
     store: &SqliteStore,
     manager: ManagerId,
@@ -13186,18 +13747,17 @@ fn build_manager_mutation_request_with_options(
 
             let request = match &update_plan.target {
                 helm_core::manager_lifecycle::ManagerUpdateTarget::ManagerSelf => {
-                    helm_core::manager_lifecycle::build_update_request(&update_plan, None)
+                    helm_core::manager_lifecycle::build_update_request(&update_plan, None, false)
                 }
                 helm_core::manager_lifecycle::ManagerUpdateTarget::HomebrewFormula {
                     formula_name,
                 } => {
                     let policy = effective_homebrew_keg_policy(store, formula_name);
                     let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
-                    let target_name =
-                        encode_homebrew_upgrade_target(formula_name, cleanup_old_kegs);
                     helm_core::manager_lifecycle::build_update_request(
                         &update_plan,
-                        Some(target_name),
+                        Some(formula_name.clone()),
+                        cleanup_old_kegs,
                     )
                 }
             }
@@ -13220,6 +13780,7 @@ fn build_manager_mutation_request_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             _ => {
@@ -13233,6 +13794,7 @@ fn build_manager_mutation_request_with_options(
                             },
                             target_name: None,
                             version: None,
+                            disposal: Default::default(),
                         }),
                     )
                 } else {
@@ -13418,6 +13980,7 @@ fn parse_manager_mutation_args(
     let mut preview = false;
     let mut yes = false;
     let mut allow_unknown_provenance = false;
+    let mut upgrade_all = false;
     let mut install_method_raw: Option<String> = None;
     let mut rustup_install_source: Option<helm_core::manager_lifecycle::RustupInstallSource> = None;
     let mut rustup_binary_path: Option<String> = None;
@@ -13433,6 +13996,7 @@ fn parse_manager_mutation_args(
 
     let uninstall_command = subcommand == "uninstall";
     let install_command = subcommand == "install";
+    let update_command = subcommand == "update";
     let mut index = 0usize;
     while index < command_args.len() {
         match command_args[index].as_str() {
@@ -13448,6 +14012,10 @@ fn parse_manager_mutation_args(
                 allow_unknown_provenance = true;
                 index += 1;
             }
+            "--upgrade-all" if update_command => {
+                upgrade_all = true;
+                index += 1;
+            }
             "--homebrew-cleanup-mode" if uninstall_command => {
                 if index + 1 >= command_args.len() {
                     return Err(
@@ -13589,6 +14157,12 @@ fn parse_manager_mutation_args(
                         flag
                     ));
                 }
+                if update_command {
+                    return Err(format!(
+                        "unsupported managers update argument '{}'; supported: <manager-id>, --upgrade-all",
+                        flag
+                    ));
+                }
                 return Err(format!(
                     "unsupported managers {} argument '{}'; expected exactly one <manager-id>",
                     subcommand, flag
@@ -13609,6 +14183,12 @@ fn parse_manager_mutation_args(
 
     let manager = manager
         .ok_or_else(|| format!("managers {} requires exactly one manager id", subcommand))?;
+    if upgrade_all && manager != ManagerId::HomebrewFormula {
+        return Err(
+            "managers update --upgrade-all is only supported for manager 'homebrew_formula'"
+                .to_string(),
+        );
+    }
     let install_method_override = install_method_raw
         .map(|raw| parse_selected_install_method_arg(manager, raw.as_str()))
         .transpose()?
@@ -13699,6 +14279,7 @@ fn parse_manager_mutation_args(
             mise_cleanup_mode,
             mise_config_removal,
             remove_helm_managed_shell_setup: None,
+            confirm_manager_data_removal: false,
         }
     } else if uninstall_command {
         helm_core::manager_lifecycle::ManagerUninstallOptions {
@@ -13717,6 +14298,7 @@ fn parse_manager_mutation_args(
         install_method_override,
         install_options,
         uninstall_options,
+        upgrade_all,
     })
 }
 
@@ -14142,6 +14724,7 @@ struct ParsedListLimitArgs {
 struct ParsedTasksListArgs {
     limit: Option<usize>,
     status_filter: Option<String>,
+    all: bool,
 }
 
 fn parse_list_limit_args(
@@ -14183,6 +14766,7 @@ fn parse_list_limit_args(
 fn parse_tasks_list_args(command_args: &[String]) -> Result<ParsedTasksListArgs, String> {
     let mut limit: Option<usize> = None;
     let mut status_filter: Option<String> = None;
+    let mut all = false;
     let mut index = 0usize;
     while index < command_args.len() {
         match command_args[index].as_str() {
@@ -14222,9 +14806,13 @@ fn parse_tasks_list_args(command_args: &[String]) -> Result<ParsedTasksListArgs,
                 status_filter = Some(normalized);
                 index += 2;
             }
+            "--all" => {
+                all = true;
+                index += 1;
+            }
             other => {
                 return Err(format!(
-                    "unsupported tasks list argument '{}'; supported: --limit <n>, --status <state>",
+                    "unsupported tasks list argument '{}'; supported: --limit <n>, --status <state>, --all",
                     other
                 ));
             }
@@ -14234,6 +14822,7 @@ fn parse_tasks_list_args(command_args: &[String]) -> Result<ParsedTasksListArgs,
     Ok(ParsedTasksListArgs {
         limit,
         status_filter,
+        all,
     })
 }
 
@@ -14436,15 +15025,18 @@ fn manager_authority_key(id: ManagerId) -> &'static str {
 }
 
 fn upgrade_plan_step_id(manager: ManagerId, package_name: &str) -> String {
-    format!("{}:{}", manager.as_str(), package_name)
+    helm_core::upgrade_target::upgrade_plan_step_id(manager, package_name)
 }
 
-fn upgrade_request_name(step: &UpgradeExecutionStep) -> String {
-    if step.manager == ManagerId::HomebrewFormula && step.cleanup_old_kegs {
-        encode_homebrew_upgrade_target(&step.package_name, true)
-    } else {
-        step.package_name.clone()
-    }
+fn upgrade_request_for_step(step: &UpgradeExecutionStep) -> AdapterRequest {
+    AdapterRequest::Upgrade(UpgradeRequest {
+        // Plan steps keep manager-wide targets as reserved package names.
+        target: UpgradeTarget::from_wire(step.manager, None, Some(step.package_name.clone())),
+        target_name: None,
+        version: None,
+        options: step.options.clone(),
+        cleanup_old_kegs: step.manager == ManagerId::HomebrewFormula && step.cleanup_old_kegs,
+    })
 }
 
 fn manager_operation_failure_error(operation: &str, failures: usize) -> Option<String> {
@@ -14504,15 +15096,15 @@ fn collect_upgrade_execution_steps(
 ) -> Result<Vec<UpgradeExecutionStep>, String> {
     let enabled_map = manager_enabled_map(store)?;
     let outdated = list_outdated_for_enabled(store, &enabled_map)?;
-    let pinned_keys: std::collections::HashSet<String> = store
+    let pinned_keys: std::collections::HashSet<PackageKey> = store
         .list_pins()
         .map_err(|error| format!("failed to list pin records: {error}"))?
         .into_iter()
-        .map(|pin| format!("{}:{}", pin.package.manager.as_str(), pin.package.name))
+        .map(|pin| pin.package.key())
         .collect();
 
     let mut manager_steps: HashMap<ManagerId, Vec<UpgradeExecutionStep>> = HashMap::new();
-    let mut seen_steps: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut seen_steps: std::collections::HashSet<PackageKey> = std::collections::HashSet::new();
 
     for package in outdated {
         let manager = package.package.manager;
@@ -14525,7 +15117,7 @@ fn collect_upgrade_execution_steps(
             continue;
         }
 
-        let package_key = format!("{}:{}", manager.as_str(), package.package.name);
+        let package_key = package.package.key();
         if !include_pinned && (package.pinned || pinned_keys.contains(&package_key)) {
             continue;
         }
@@ -14534,13 +15126,20 @@ fn collect_upgrade_execution_steps(
             if !allow_os_updates || runtime.is_safe_mode() {
                 continue;
             }
-            "__confirm_os_updates__".to_string()
+            CONFIRMED_OS_UPDATES_NAME.to_string()
         } else {
             package.package.name.clone()
         };
 
-        let dedupe_key = format!("{}:{}", manager.as_str(), step_name);
-        if !seen_steps.insert(dedupe_key) {
+        if !seen_steps.insert(PackageKey::new(manager, &step_name)) {
+            if manager == ManagerId::SoftwareUpdate
+                && let Some(step) = manager_steps
+                    .get_mut(&manager)
+                    .and_then(|steps| steps.iter_mut().find(|step| step.package_name == step_name))
+            {
+                step.options.push(package.package.name.clone());
+                step.restart_required |= package.restart_required;
+            }
             continue;
         }
 
@@ -14556,6 +15155,11 @@ fn collect_upgrade_execution_steps(
                 cleanup_old_kegs,
                 pinned: package.pinned || pinned_keys.contains(&package_key),
                 restart_required: package.restart_required,
+                options: if manager == ManagerId::SoftwareUpdate {
+                    vec![package.package.name.clone()]
+                } else {
+                    persisted_upgrade_options(store, manager, &package.package.name)
+                },
             });
     }
 
@@ -14691,6 +15295,7 @@ fn print_command_help_topic(command: Command, path: &[String]) -> bool {
         Command::Doctor => print_doctor_help_topic(path),
         Command::Onboarding => print_onboarding_help_topic(path),
         Command::SelfCmd => print_self_help_topic(path),
+        Command::NotifyChanged => print_notify_changed_help_topic(path),
         Command::Completion => print_completion_help_topic(path),
         Command::InternalCoordinator | Command::Help | Command::Version => false,
     }
@@ -15156,6 +15761,14 @@ fn print_diagnostics_help_topic(path: &[String]) -> bool {
     }
 }
 
+fn print_notify_changed_help_topic(path: &[String]) -> bool {
+    if path.is_empty() || (path.len() == 1 && path[0] == "hook") {
+        print_notify_changed_help();
+        return true;
+    }
+    false
+}
+
 fn print_completion_help_topic(path: &[String]) -> bool {
     if path.is_empty() {
         print_completion_help();
@@ -15241,6 +15854,8 @@ fn print_help() {
     println!("                         Inspect/run/reset CLI first-run onboarding state");
     println!("  self [status|check|update|uninstall|auto-check]");
     println!("                         Helm self-update/uninstall namespace");
+    println!("  notify-changed <manager-id>... | hook [bash|zsh|fish]");
+    println!("                         Refresh managers changed outside Helm; print shell hook");
     println!("  completion [bash|zsh|fish]");
     println!("                         Generate shell completion scripts");
     println!("  help                   Show this help");
@@ -15349,7 +15964,9 @@ fn print_packages_install_help() {
 
 fn print_packages_uninstall_help() {
     println!("USAGE:");
-    println!("  helm packages uninstall <name|name@manager> --manager <id> [--preview] [--yes]");
+    println!(
+        "  helm packages uninstall <name|name@manager> --manager <id> [--preview] [--yes] [--trash]"
+    );
     println!();
     println!("DESCRIPTION:");
     println!(
@@ -15543,7 +16160,7 @@ fn print_updates_run_help() {
 fn print_tasks_help() {
     println!("USAGE:");
     println!(
-        "  helm tasks list [--limit <n>] [--status queued|running|completed|failed|cancelled]"
+        "  helm tasks list [--limit <n>] [--status queued|running|completed|failed|cancelled] [--all]"
     );
     println!("  helm tasks show <task-id>");
     println!(
@@ -15564,11 +16181,12 @@ fn print_tasks_help() {
 fn print_tasks_list_help() {
     println!("USAGE:");
     println!(
-        "  helm tasks list [--limit <n>] [--status queued|running|completed|failed|cancelled]"
+        "  helm tasks list [--limit <n>] [--status queued|running|completed|failed|cancelled] [--all]"
     );
     println!();
     println!("DESCRIPTION:");
-    println!("  List recent tasks for enabled managers.");
+    println!("  List recent tasks for enabled managers, shaped like the app's task list");
+    println!("  (task_view_* settings); --all lists every stored task instead.");
     println!("  Use --limit to cap returned rows and --status to filter by lifecycle state.");
 }
 
@@ -15708,10 +16326,13 @@ fn print_managers_install_help() {
 
 fn print_managers_update_help() {
     println!("USAGE:");
-    println!("  helm managers update <manager-id>");
+    println!("  helm managers update <manager-id> [--upgrade-all]");
     println!();
     println!("DESCRIPTION:");
     println!("  Update a supported manager via manager-specific routing.");
+    println!(
+        "  homebrew_formula-only: --upgrade-all runs 'brew upgrade' after a successful 'brew update'."
+    );
 }
 
 fn print_managers_uninstall_help() {
@@ -16152,6 +16773,21 @@ fn print_self_auto_check_frequency_help() {
     println!("  Set auto-check cadence in minutes.");
 }
 
+fn print_notify_changed_help() {
+    println!("USAGE:");
+    println!("  helm notify-changed <manager-id>...");
+    println!("  helm notify-changed hook <bash|zsh|fish>");
+    println!();
+    println!("DESCRIPTION:");
+    println!("  Queue a background refresh for managers you just ran outside Helm.");
+    println!("  Disabled managers, and managers with a refresh already queued, are skipped.");
+    println!();
+    println!("  'hook' prints a shell snippet that wraps brew, npm, cargo, and pipx so");
+    println!("  their package changes call notify-changed automatically:");
+    println!("    eval \"$(helm notify-changed hook zsh)\"");
+    println!("    helm notify-changed hook fish | source");
+}
+
 fn print_completion_help() {
     println!("USAGE:");
     println!("  helm completion <bash|zsh|fish>");
@@ -16163,32 +16799,34 @@ fn print_completion_help() {
 #[cfg(test)]
 mod tests {
     use super::{
-        CLI_LICENSE_TERMS_VERSION, Command, CoordinatorClientTransport, ExecutionMode,
-        GlobalOptions, HomebrewKegPolicy, InstallChannel, ManagerId, RustupPackagesCommand,
-        SelfUpdateErrorKind, UpdatePolicy, UpgradeExecutionStep,
+        CLI_LICENSE_TERMS_VERSION, CONFIRMED_OS_UPDATES_NAME, Command, CoordinatorClientTransport,
+        ExecutionMode, GlobalOptions, HomebrewKegPolicy, InstallChannel, ManagerId,
+        RustupPackagesCommand, SelfUpdateErrorKind, UpdatePolicy, UpgradeExecutionStep,
         acquire_coordinator_bootstrap_lock, apply_manager_enablement_self_heal,
         build_json_payload_lines, classify_failure_class, cmd_updates_run,
         command_bypasses_cli_onboarding, command_help_topic_exists,
         coordinator_transport_for_cancel, coordinator_transport_for_submit,
         coordinator_transport_for_workflow, count_upgrade_step_failures,
-        ensure_cli_onboarding_completed, exit_code_for_error, failure_class_hint, list_managers,
-        manager_operation_failure_error, mark_exit_code, parse_args, parse_args_with_tty,
+        ensure_cli_onboarding_completed, exit_code_for_error, failure_class_hint,
+        is_notify_changed_hook_request, list_managers, manager_operation_failure_error,
+        mark_exit_code, notify_changed_hook_script, parse_args, parse_args_with_tty,
         parse_homebrew_keg_policy_arg, parse_manager_id, parse_manager_mutation_args,
         parse_package_mutation_args, parse_package_selector, parse_package_show_args,
         parse_packages_rustup_args, parse_search_args, parse_structured_terminal_error_message,
-        parse_updates_run_preview_args, provenance_can_self_update, raw_args_request_json,
-        raw_args_request_ndjson, read_update_bytes_with_limit, remove_install_marker_if_channel,
-        resolve_redirect_url, resolve_update_redirect_target,
+        parse_updates_run_preview_args, persisted_upgrade_options, provenance_can_self_update,
+        raw_args_request_json, raw_args_request_ndjson, read_update_bytes_with_limit,
+        remove_install_marker_if_channel, resolve_redirect_url, resolve_update_redirect_target,
         selected_executable_differs_from_default, self_uninstall_recommended_action,
-        should_launch_coordinator_on_demand, strip_exit_code_marker, upgrade_request_name,
+        should_launch_coordinator_on_demand, strip_exit_code_marker, upgrade_request_for_step,
     };
     use helm_core::execution::TaskOutputRecord;
     use helm_core::models::{
         AutomationLevel, DetectionInfo, InstallInstanceIdentityKind, InstallProvenance,
-        ManagerInstallInstance, StrategyKind,
+        ManagerInstallInstance, PackageRef, StrategyKind, UninstallDisposal,
     };
     use helm_core::persistence::DetectionStore;
     use helm_core::sqlite::SqliteStore;
+    use helm_core::upgrade_target::UpgradeTarget;
     use serde_json::json;
     use std::fs;
     use std::io::Cursor;
@@ -16423,6 +17061,7 @@ mod tests {
                 cleanup_old_kegs: false,
                 pinned: false,
                 restart_required: false,
+                options: Vec::new(),
             },
             UpgradeExecutionStep {
                 manager: ManagerId::Pnpm,
@@ -16430,6 +17069,7 @@ mod tests {
                 cleanup_old_kegs: false,
                 pinned: false,
                 restart_required: false,
+                options: Vec::new(),
             },
             UpgradeExecutionStep {
                 manager: ManagerId::Yarn,
@@ -16437,6 +17077,7 @@ mod tests {
                 cleanup_old_kegs: false,
                 pinned: false,
                 restart_required: false,
+                options: Vec::new(),
             },
         ];
 
@@ -16462,13 +17103,14 @@ mod tests {
     }
 
     #[test]
-    fn upgrade_request_name_encodes_homebrew_cleanup_targets() {
+    fn upgrade_request_for_step_sets_homebrew_cleanup_flag() {
         let homebrew_step = UpgradeExecutionStep {
             manager: ManagerId::HomebrewFormula,
             package_name: "wget".to_string(),
             cleanup_old_kegs: true,
             pinned: false,
             restart_required: false,
+            options: Vec::new(),
         };
         let npm_step = UpgradeExecutionStep {
             manager: ManagerId::Npm,
@@ -16476,13 +17118,52 @@ mod tests {
             cleanup_old_kegs: true,
             pinned: false,
             restart_required: false,
+            options: Vec::new(),
         };
 
+        let helm_core::adapters::AdapterRequest::Upgrade(homebrew_request) =
+            upgrade_request_for_step(&homebrew_step)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert_eq!(
+            homebrew_request
+                .target
+                .package()
+                .map(|package| package.name.as_str()),
+            Some("wget")
+        );
+        assert!(homebrew_request.cleanup_old_kegs);
+
+        let helm_core::adapters::AdapterRequest::Upgrade(npm_request) =
+            upgrade_request_for_step(&npm_step)
+        else {
+            panic!("expected upgrade request");
+        };
         assert_eq!(
-            upgrade_request_name(&homebrew_step),
-            "wget@@helm.cleanup".to_string()
+            npm_request
+                .target
+                .package()
+                .map(|package| package.name.as_str()),
+            Some("eslint")
         );
-        assert_eq!(upgrade_request_name(&npm_step), "eslint".to_string());
+        assert!(!npm_request.cleanup_old_kegs);
+
+        let os_step = UpgradeExecutionStep {
+            manager: ManagerId::SoftwareUpdate,
+            package_name: CONFIRMED_OS_UPDATES_NAME.to_string(),
+            cleanup_old_kegs: false,
+            pinned: false,
+            restart_required: true,
+            options: vec!["Safari 18.3.1-18.3.1".to_string()],
+        };
+        let helm_core::adapters::AdapterRequest::Upgrade(os_request) =
+            upgrade_request_for_step(&os_step)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert_eq!(os_request.target, UpgradeTarget::OsUpdatesConfirmed);
+        assert_eq!(os_request.options, vec!["Safari 18.3.1-18.3.1".to_string()]);
     }
 
     #[test]
@@ -17123,12 +17804,49 @@ mod tests {
             Command::Onboarding,
             &["status".to_string()]
         ));
+        assert!(command_help_topic_exists(
+            Command::NotifyChanged,
+            &["hook".to_string()]
+        ));
         assert!(!command_help_topic_exists(
             Command::Updates,
             &["unknown".to_string()]
         ));
     }
 
+    #[test]
+    fn notify_changed_hooks_only_name_known_manager_ids() {
+        for shell in ["bash", "zsh", "fish"] {
+            let script = notify_changed_hook_script(shell).expect("hook script for shell");
+            let notified: Vec<&str> = script
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("_helm_notify_changed "))
+                .flat_map(str::split_whitespace)
+                .filter(|word| *word != ";;")
+                .collect();
+            assert!(notified.contains(&"npm"), "{shell} hook should notify npm");
+            for raw in notified {
+                assert!(
+                    parse_manager_id(raw).is_ok(),
+                    "{shell} hook notifies unknown manager '{raw}'"
+                );
+            }
+        }
+        assert!(notify_changed_hook_script("powershell").is_none());
+        assert!(is_notify_changed_hook_request(
+            Command::NotifyChanged,
+            &["hook".to_string(), "zsh".to_string()]
+        ));
+        assert!(!is_notify_changed_hook_request(
+            Command::NotifyChanged,
+            &["hook".to_string(), "--help".to_string()]
+        ));
+        assert!(!is_notify_changed_hook_request(
+            Command::NotifyChanged,
+            &["npm".to_string()]
+        ));
+    }
+
     #[test]
     fn list_managers_includes_active_install_instance_metadata() {
         let db_path = temp_db_path("manager-instance-metadata");
@@ -17833,6 +18551,24 @@ mod tests {
         assert!(error.contains("only supported for manager 'mise'"));
     }
 
+    #[test]
+    fn parse_manager_mutation_args_update_accepts_upgrade_all_for_homebrew_only() {
+        let parsed = parse_manager_mutation_args(
+            "update",
+            &["homebrew_formula".to_string(), "--upgrade-all".to_string()],
+        )
+        .expect("homebrew update --upgrade-all should parse");
+        assert_eq!(parsed.manager, ManagerId::HomebrewFormula);
+        assert!(parsed.upgrade_all);
+
+        let error = parse_manager_mutation_args(
+            "update",
+            &["rustup".to_string(), "--upgrade-all".to_string()],
+        )
+        .expect_err("non-homebrew managers should reject --upgrade-all");
+        assert!(error.contains("only supported for manager 'homebrew_formula'"));
+    }
+
     #[test]
     fn parse_manager_mutation_args_uninstall_rejects_invalid_mise_cleanup_mode() {
         let error = parse_manager_mutation_args(
@@ -17897,6 +18633,25 @@ mod tests {
         assert_eq!(parsed.version, None);
     }
 
+    #[test]
+    fn parse_package_mutation_args_uninstall_trash_requires_cask_manager() {
+        let parsed = parse_package_mutation_args(
+            "uninstall",
+            &["firefox@homebrew_cask".to_string(), "--trash".to_string()],
+            false,
+        )
+        .expect("cask uninstall --trash should parse");
+        assert_eq!(parsed.disposal, UninstallDisposal::Trash);
+
+        let error = parse_package_mutation_args(
+            "uninstall",
+            &["git@homebrew_formula".to_string(), "--trash".to_string()],
+            false,
+        )
+        .expect_err("formula uninstall --trash should be rejected");
+        assert!(error.contains("--trash"));
+    }
+
     #[test]
     fn parse_package_mutation_args_upgrade_rejects_uninstall_only_flags() {
         let error = parse_package_mutation_args(
@@ -18547,11 +19302,7 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade
-                    .package
-                    .expect("homebrew self update package should exist");
-                assert_eq!(package.manager, ManagerId::HomebrewFormula);
-                assert_eq!(package.name, "__self__");
+                assert_eq!(upgrade.target, super::UpgradeTarget::SelfUpdate);
             }
             other => panic!("unexpected request: {other:?}"),
         }
@@ -18614,7 +19365,11 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade.package.expect("homebrew node package should exist");
+                let package = upgrade
+                    .target
+                    .package()
+                    .cloned()
+                    .expect("homebrew node package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("node"));
             }
@@ -18708,7 +19463,9 @@ mod tests {
             match request {
                 super::AdapterRequest::Upgrade(upgrade) => {
                     let package = upgrade
-                        .package
+                        .target
+                        .package()
+                        .cloned()
                         .expect("homebrew parent formula package should exist");
                     assert_eq!(package.manager, ManagerId::HomebrewFormula);
                     assert!(
@@ -19151,7 +19908,11 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade.package.expect("homebrew asdf package should exist");
+                let package = upgrade
+                    .target
+                    .package()
+                    .cloned()
+                    .expect("homebrew asdf package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("asdf"));
             }
@@ -19264,7 +20025,11 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade.package.expect("homebrew mise package should exist");
+                let package = upgrade
+                    .target
+                    .package()
+                    .cloned()
+                    .expect("homebrew mise package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("mise"));
             }
@@ -19375,7 +20140,11 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade.package.expect("homebrew mas package should exist");
+                let package = upgrade
+                    .target
+                    .package()
+                    .cloned()
+                    .expect("homebrew mas package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("mas"));
             }
@@ -19440,7 +20209,11 @@ mod tests {
         assert_eq!(target_manager, ManagerId::HomebrewFormula);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade.package.expect("homebrew pnpm package should exist");
+                let package = upgrade
+                    .target
+                    .package()
+                    .cloned()
+                    .expect("homebrew pnpm package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("pnpm"));
             }
@@ -19574,7 +20347,11 @@ mod tests {
             assert_eq!(target_manager, ManagerId::HomebrewFormula);
             match request {
                 super::AdapterRequest::Upgrade(upgrade) => {
-                    let package = upgrade.package.expect("homebrew package should exist");
+                    let package = upgrade
+                        .target
+                        .package()
+                        .cloned()
+                        .expect("homebrew package should exist");
                     assert_eq!(package.manager, ManagerId::HomebrewFormula);
                     assert!(
                         package.name.starts_with(formula_name),
@@ -19784,11 +20561,7 @@ mod tests {
         assert_eq!(target_manager, ManagerId::Rustup);
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
-                let package = upgrade
-                    .package
-                    .expect("rustup self update package should exist");
-                assert_eq!(package.manager, ManagerId::Rustup);
-                assert_eq!(package.name, "__self__");
+                assert_eq!(upgrade.target, super::UpgradeTarget::SelfUpdate);
             }
             other => panic!("unexpected request: {other:?}"),
         }
@@ -19912,7 +20685,9 @@ mod tests {
         match request {
             super::AdapterRequest::Upgrade(upgrade) => {
                 let package = upgrade
-                    .package
+                    .target
+                    .package()
+                    .cloned()
                     .expect("homebrew rustup package should exist");
                 assert_eq!(package.manager, ManagerId::HomebrewFormula);
                 assert!(package.name.contains("rustup"));
@@ -22614,6 +23389,32 @@ mod tests {
         assert!(error.contains("helm managers list"));
     }
 
+    #[test]
+    fn updates_run_steps_replay_saved_install_options() {
+        let db_path = temp_db_path("updates-run-install-options");
+        let store = SqliteStore::new(&db_path);
+        store
+            .migrate_to_latest()
+            .expect("store migration should succeed");
+        store
+            .set_package_install_options(
+                &PackageRef {
+                    manager: ManagerId::HomebrewCask,
+                    name: "firefox".to_string(),
+                },
+                &["--adopt".to_string(), "--no-quarantine".to_string()],
+            )
+            .expect("install options should persist");
+
+        assert_eq!(
+            persisted_upgrade_options(&store, ManagerId::HomebrewCask, "firefox"),
+            vec!["--no-quarantine".to_string()]
+        );
+        assert!(persisted_upgrade_options(&store, ManagerId::HomebrewCask, "zoom").is_empty());
+
+        let _ = fs::remove_file(db_path);
+    }
+
     #[test]
     fn updates_run_requires_yes_message_includes_preview_hint() {
         let db_path = temp_db_path("updates-run-yes-hint");
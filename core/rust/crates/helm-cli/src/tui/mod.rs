@@ -9,6 +9,7 @@ use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
+use helm_core::adapters::UpgradeTargetKind;
 use helm_core::persistence::{DetectionStore, PackageStore, PinStore, TaskStore};
 use helm_core::versioning::package_family_preference_key;
 use ratatui::Terminal;
@@ -24,9 +25,9 @@ use super::{
     CachedSearchResult, Capability, CliDiagnosticsSummary, CliManagerInstallInstance,
     CliManagerStatus, CliTaskLogRecord, CliTaskRecord, CoordinatorSubmitRequest,
     CoordinatorWorkflowRequest, ExecutionMode, InstalledPackage, ManagerId, OutdatedPackage,
-    PackageRef, PinKind, PinRecord, SELF_UPDATE_ALLOW_ROOT_ENV, SqliteStore, TASK_FETCH_LIMIT,
-    TaskId, acknowledge_manager_multi_instance_state, adapter_request_to_coordinator_submit,
-    build_diagnostics_summary, build_manager_mutation_request,
+    PackageRef, PinKind, PinRecord, SELF_UPDATE_ALLOW_ROOT_ENV, SqliteStore, TaskId,
+    acknowledge_manager_multi_instance_state, adapter_request_to_coordinator_submit,
+    build_diagnostics_export_data, build_diagnostics_summary, build_manager_mutation_request,
     build_manager_uninstall_plan_with_options, build_package_uninstall_preview_for_package,
     cancel_inflight_tasks_for_manager, channel_managed_check_status,
     clear_manager_multi_instance_ack, coordinator_cancel_task, coordinator_start_workflow,
@@ -39,10 +40,11 @@ use super::{
     provenance_can_self_update, provenance_recommended_action, registry,
     resolve_install_method_override_for_tui, search_local_for_enabled,
     set_manager_active_install_instance, set_manager_priority_rank, task_log_to_cli_record,
-    task_to_cli_task, write_setting,
+    write_setting,
 };
 use helm_core::models::HomebrewKegPolicy;
 use helm_core::models::PackageRuntimeState;
+use helm_core::models::UninstallDisposal;
 
 const SPLASH_AUTO_DISMISS_MS: Option<u64> = Some(900);
 const DATA_REFRESH_INTERVAL_MS: u64 = 1200;
@@ -119,6 +121,7 @@ fn mise_uninstall_options_full_cleanup(
         ),
         mise_config_removal: Some(config_removal),
         remove_helm_managed_shell_setup: None,
+        confirm_manager_data_removal: false,
     }
 }
 
@@ -661,6 +664,7 @@ impl AppState {
                     last_heartbeat_unix: None,
                     stale_reasons: Vec::new(),
                 },
+                base_dirs: Default::default(),
             },
             settings: SettingsSnapshot::default(),
             self_update: SelfUpdateSnapshot::default(),
@@ -2436,8 +2440,10 @@ fn execute_confirmed_action(store: &SqliteStore, action: ConfirmAction) -> Resul
                 manager,
                 CoordinatorSubmitRequest::Upgrade {
                     package_name: Some(package_name.clone()),
+                    target: Some(UpgradeTargetKind::Package),
                     target_name: None,
                     version: package_version.clone(),
+                    cleanup_old_kegs: false,
                 },
                 ExecutionMode::Wait,
             )?;
@@ -2461,6 +2467,7 @@ fn execute_confirmed_action(store: &SqliteStore, action: ConfirmAction) -> Resul
                     package_name: package_name.clone(),
                     target_name: None,
                     version: package_version.clone(),
+                    disposal: UninstallDisposal::Delete,
                 },
                 ExecutionMode::Wait,
             )?;
@@ -2546,6 +2553,7 @@ fn execute_confirmed_action(store: &SqliteStore, action: ConfirmAction) -> Resul
                         package: package.clone(),
                         kind: PinKind::Virtual,
                         pinned_version: version.clone(),
+                        version_constraint: None,
                         created_at: SystemTime::now(),
                     })
                     .map_err(|error| format!("failed to persist pin record: {error}"))?;
@@ -3156,43 +3164,13 @@ fn apply_self_update(force: bool) -> Result<String, String> {
 }
 
 fn diagnostics_export_payload(store: &SqliteStore) -> Result<serde_json::Value, String> {
-    let summary = build_diagnostics_summary(store)?;
-    let managers = list_managers(store)?;
-    let tasks = store
-        .list_recent_tasks(TASK_FETCH_LIMIT)
-        .map_err(|error| format!("failed to list recent tasks: {error}"))?
-        .into_iter()
-        .map(task_to_cli_task)
-        .collect::<Vec<_>>();
-
-    let failed_task_logs = tasks
-        .iter()
-        .filter(|task| task.status == "failed")
-        .take(25)
-        .map(|task| {
-            let logs = store
-                .list_task_logs(TaskId(task.id), 128)
-                .map_err(|error| format!("failed to list task logs: {error}"))?
-                .into_iter()
-                .map(task_log_to_cli_record)
-                .collect::<Vec<_>>();
-            Ok(json!({
-                "taskId": task.id,
-                "logs": logs
-            }))
-        })
-        .collect::<Result<Vec<_>, String>>()?;
+    let data = build_diagnostics_export_data(store)?;
 
     Ok(json!({
         "schema": "helm.cli.v1.diagnostics.export",
         "schema_version": 1,
         "generated_at": chrono_like_unix_now(),
-        "data": {
-            "summary": summary,
-            "managers": managers,
-            "tasks": tasks,
-            "failedTaskLogs": failed_task_logs
-        }
+        "data": data
     }))
 }
 
@@ -4838,6 +4816,7 @@ mod tests {
                 installed_version: Some("2026.1.4".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             }])
             .expect("failed to seed installed package");
 
@@ -11,6 +11,7 @@ use crate::models::{
     ManagerId, OutdatedPackage, PackageCandidate, PackageRef, PackageRuntimeState, SearchQuery,
     TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 use crate::versioning::PackageCoordinate;
 
 const ASDF_CAPABILITIES: &[Capability] = &[
@@ -132,6 +133,7 @@ impl<S: AsdfSource> AsdfAdapter<S> {
                     installed_version: Some(version),
                     pinned: false,
                     runtime_state,
+                    sizes: Default::default(),
                 });
             }
         }
@@ -201,6 +203,7 @@ impl<S: AsdfSource> AsdfAdapter<S> {
                 pinned: false,
                 restart_required: false,
                 runtime_state: representative.runtime_state.clone(),
+                sizes: Default::default(),
             });
         }
 
@@ -376,6 +379,7 @@ impl<S: AsdfSource> AsdfAdapter<S> {
             action: ManagerAction::Upgrade,
             before_version: Some(target.version),
             after_version: Some(latest_version),
+            disposal: None,
         })
     }
 
@@ -434,14 +438,12 @@ impl<S: AsdfSource> AsdfAdapter<S> {
         }
 
         Ok(crate::adapters::MutationResult {
-            package: PackageRef {
-                manager: ManagerId::Asdf,
-                name: "__all__".to_string(),
-            },
+            package: UpgradeTarget::All.result_package(ManagerId::Asdf),
             package_identifier: None,
             action: ManagerAction::Upgrade,
             before_version: None,
             after_version: None,
+            disposal: None,
         })
     }
 }
@@ -476,15 +478,13 @@ impl<S: AsdfSource> ManagerAdapter for AsdfAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = self.load_installed_packages()?;
-                let outdated = self.load_outdated_packages()?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self.load_installed_packages();
+                let outdated = self.load_outdated_packages();
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let packages = self.load_installed_packages()?;
@@ -509,6 +509,7 @@ impl<S: AsdfSource> ManagerAdapter for AsdfAdapter<S> {
                         action: ManagerAction::Install,
                         before_version: None,
                         after_version: None,
+                        disposal: None,
                     }));
                 }
 
@@ -562,6 +563,7 @@ impl<S: AsdfSource> ManagerAdapter for AsdfAdapter<S> {
                     action: ManagerAction::Install,
                     before_version: None,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -597,6 +599,7 @@ impl<S: AsdfSource> ManagerAdapter for AsdfAdapter<S> {
                         action: ManagerAction::Uninstall,
                         before_version: None,
                         after_version: None,
+                        disposal: None,
                     }));
                 }
 
@@ -625,32 +628,33 @@ impl<S: AsdfSource> ManagerAdapter for AsdfAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(target.version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Asdf,
-                    name: "__all__".to_string(),
-                });
-
-                if package.name == "__self__" {
-                    let _ = self.source.self_update()?;
-                    return Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                        package,
-                        package_identifier: None,
-                        action: ManagerAction::Upgrade,
-                        before_version: None,
-                        after_version: None,
-                    }));
-                }
-
-                let result = if package.name == "__all__" {
-                    self.upgrade_all_plugins()?
-                } else {
-                    self.upgrade_single_plugin(
+                let result = match &upgrade_request.target {
+                    UpgradeTarget::SelfUpdate => {
+                        let _ = self.source.self_update()?;
+                        crate::adapters::MutationResult {
+                            package: upgrade_request.target.result_package(ManagerId::Asdf),
+                            package_identifier: None,
+                            action: ManagerAction::Upgrade,
+                            before_version: None,
+                            after_version: None,
+                            disposal: None,
+                        }
+                    }
+                    UpgradeTarget::All => self.upgrade_all_plugins()?,
+                    UpgradeTarget::Package(package) => self.upgrade_single_plugin(
                         package.name.as_str(),
                         upgrade_request.version.as_deref(),
-                    )?
+                    )?,
+                    target => {
+                        return Err(crate::adapters::unsupported_upgrade_target_error(
+                            ManagerId::Asdf,
+                            target,
+                        ));
+                    }
                 };
                 Ok(AdapterResponse::Mutation(result))
             }
@@ -1132,6 +1136,7 @@ fn parse_asdf_search(output: &str, query: &SearchQuery) -> Vec<CachedSearchResul
             source_manager: ManagerId::Asdf,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -1338,6 +1343,7 @@ mod tests {
     use crate::models::{
         CoreError, CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskType,
     };
+    use crate::upgrade_target::UpgradeTarget;
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/asdf/version.txt");
     const CURRENT_FIXTURE: &str = include_str!("../../tests/fixtures/asdf/current.txt");
@@ -1571,6 +1577,7 @@ python 3.11.9 /Users/test/.tool-versions
                 },
                 target_name: None,
                 version: Some("3.12.2".to_string()),
+                options: Vec::new(),
             }))
             .unwrap();
 
@@ -1603,6 +1610,7 @@ python 3.11.9 /Users/test/.tool-versions
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }))
             .unwrap();
 
@@ -1634,6 +1642,7 @@ python 3.11.9 /Users/test/.tool-versions
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }))
             .expect_err("ambiguous uninstall should fail");
         assert!(error.message.contains("multiple installed versions"));
@@ -1651,12 +1660,14 @@ python 3.11.9 /Users/test/.tool-versions
         let adapter = AsdfAdapter::new(source.clone());
         let response = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Asdf,
                     name: "python".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
 
@@ -1716,6 +1727,7 @@ python 3.11.9 /Users/test/.tool-versions
                 },
                 target_name: None,
                 version: Some("scriptInstaller:officialDownload".to_string()),
+                options: Vec::new(),
             }))
             .expect("manager install should succeed");
         assert!(matches!(install_response, AdapterResponse::Mutation(_)));
@@ -1728,18 +1740,18 @@ python 3.11.9 /Users/test/.tool-versions
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }))
             .expect("manager uninstall should succeed");
         assert!(matches!(uninstall_response, AdapterResponse::Mutation(_)));
 
         let upgrade_response = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Asdf,
-                    name: "__self__".to_string(),
-                }),
+                target: UpgradeTarget::SelfUpdate,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect("manager update should succeed");
         assert!(matches!(upgrade_response, AdapterResponse::Mutation(_)));
@@ -8,6 +8,7 @@ use crate::models::{
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
     ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 
 const BUNDLER_CAPABILITIES: &[Capability] = &[
     Capability::Detect,
@@ -90,15 +91,19 @@ impl<S: BundlerSource> ManagerAdapter for BundlerAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_bundler_list_installed(&self.source.list_installed()?)?;
-                let outdated = parse_bundler_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_bundler_list_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_bundler_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -136,6 +141,7 @@ impl<S: BundlerSource> ManagerAdapter for BundlerAdapter<S> {
                         &after_versions,
                         install_request.version.as_deref(),
                     ),
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -154,13 +160,24 @@ impl<S: BundlerSource> ManagerAdapter for BundlerAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Bundler,
-                    name: BUNDLER_PACKAGE_NAME.to_string(),
-                });
+                // Bundler's only package is bundler itself.
+                let package = match upgrade_request.target {
+                    UpgradeTarget::Package(package) => package,
+                    UpgradeTarget::All | UpgradeTarget::SelfUpdate => PackageRef {
+                        manager: ManagerId::Bundler,
+                        name: BUNDLER_PACKAGE_NAME.to_string(),
+                    },
+                    other => {
+                        return Err(crate::adapters::unsupported_upgrade_target_error(
+                            ManagerId::Bundler,
+                            &other,
+                        ));
+                    }
+                };
                 validate_bundler_package_name(ManagerAction::Upgrade, package.name.as_str())?;
                 let outdated = find_bundler_outdated_entry(&self.source)?;
                 let _ = self.source.upgrade()?;
@@ -175,6 +192,7 @@ impl<S: BundlerSource> ManagerAdapter for BundlerAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -336,6 +354,7 @@ fn parse_bundler_list_installed(output: &str) -> AdapterResult<Vec<InstalledPack
                 installed_version: Some(version),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -552,6 +571,7 @@ fn parse_bundler_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
                 pinned: false,
                 restart_required: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -592,6 +612,7 @@ fn parse_bundler_search(
             source_manager: ManagerId::Bundler,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         })
         .collect())
 }
@@ -754,6 +775,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert_eq!(installed.expect("installed snapshot").len(), 2);
                 assert_eq!(outdated.expect("outdated snapshot").len(), 1);
@@ -797,6 +819,7 @@ mod tests {
                 package: package.clone(),
                 target_name: None,
                 version: Some("2.5.23".to_string()),
+                options: Vec::new(),
             }))
             .expect("install response");
 
@@ -821,6 +844,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("non-bundler package should be rejected");
 
@@ -836,9 +860,11 @@ mod tests {
 
         let response = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: None,
+                target: UpgradeTarget::All,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect("upgrade response");
 
@@ -860,9 +886,11 @@ mod tests {
 
         let error = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: None,
+                target: UpgradeTarget::All,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect_err("upgrade should fail when bundler remains outdated");
 
@@ -882,6 +910,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("2.5.22".to_string()),
+                disposal: Default::default(),
             }))
             .expect("uninstall response");
 
@@ -911,6 +940,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }))
             .expect_err("uninstall should fail without an exact version");
 
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use serde::Deserialize;
@@ -7,8 +7,9 @@ use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, M
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
-    InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
+    InstalledPackage, InstalledPackageSize, ManagerAction, ManagerAuthority, ManagerCategory,
+    ManagerDescriptor, ManagerId, OutdatedPackage, PackageCandidate, PackageMetadata, PackageRef,
+    SearchQuery, TaskId, TaskType,
 };
 
 const CARGO_CAPABILITIES: &[Capability] = &[
@@ -20,6 +21,9 @@ const CARGO_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::PackageInfo,
+    Capability::Cleanup,
+    Capability::PackageSizes,
 ];
 
 const CARGO_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -50,6 +54,13 @@ pub trait CargoSource: Send + Sync {
     fn install(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn uninstall(&self, name: &str) -> AdapterResult<String>;
     fn upgrade(&self, name: Option<&str>) -> AdapterResult<String>;
+    fn info(&self, name: &str) -> AdapterResult<String>;
+    /// Downloaded `.crate` archives and their unpacked sources under
+    /// `CARGO_HOME/registry`; cargo fetches both again on demand.
+    fn registry_cache_dirs(&self) -> Vec<PathBuf>;
+    fn clean_registry_cache(&self) -> AdapterResult<()>;
+    /// `CARGO_HOME/bin`, where `cargo install` puts binaries.
+    fn bin_dir(&self) -> Option<PathBuf>;
 }
 
 pub struct CargoAdapter<S: CargoSource> {
@@ -92,15 +103,19 @@ impl<S: CargoSource> ManagerAdapter for CargoAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_cargo_installed(&self.source.list_installed()?)?;
-                let outdated = parse_cargo_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_cargo_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_cargo_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -114,7 +129,12 @@ impl<S: CargoSource> ManagerAdapter for CargoAdapter<S> {
             }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search(search_request.query.text.as_str())?;
-                let results = parse_cargo_search(&raw, &search_request.query)?;
+                let mut results = parse_cargo_search(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::Cargo,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -140,6 +160,7 @@ impl<S: CargoSource> ManagerAdapter for CargoAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -157,43 +178,95 @@ impl<S: CargoSource> ManagerAdapter for CargoAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Cargo,
-                    name: "__all__".to_string(),
-                });
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Cargo,
+                    &upgrade_request.target,
+                )?;
 
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Cargo,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Cargo,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_cargo_outdated_entry(&self.source, name))
                     .transpose()?
                     .flatten();
-                let _ = self.source.upgrade(target_name)?;
-                if let Some(name) = target_name {
-                    ensure_cargo_no_longer_outdated(&self.source, name)?;
+                match (target_name, upgrade_request.version.as_deref()) {
+                    // A range pin locks the reinstall to its version requirement,
+                    // so the crate may legitimately stay listed as outdated.
+                    (Some(name), Some(requirement)) => {
+                        let _ = self.source.install(name, Some(requirement))?;
+                    }
+                    (Some(name), None) => {
+                        let _ = self.source.upgrade(Some(name))?;
+                        ensure_cargo_no_longer_outdated(&self.source, name)?;
+                    }
+                    (None, _) if !upgrade_request.options.is_empty() => {
+                        crate::adapters::upgrade_outdated_except_held(
+                            &parse_cargo_outdated(&self.source.list_outdated()?)?,
+                            &upgrade_request.options,
+                            |name| self.source.upgrade(Some(name)),
+                        )?;
+                    }
+                    (None, _) => {
+                        let _ = self.source.upgrade(None)?;
+                    }
                 }
 
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Cargo),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|entry| entry.installed_version.clone()),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
+            AdapterRequest::PackageInfo(info_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::Cargo,
+                    ManagerAction::PackageInfo,
+                    info_request.package.name.as_str(),
+                )?;
+                let raw = self.source.info(&info_request.package.name)?;
+                Ok(AdapterResponse::PackageInfo(parse_cargo_info(&raw)?))
+            }
+            AdapterRequest::MeasurePackageSizes(_) => {
+                let Some(bin_dir) = self.source.bin_dir() else {
+                    return Ok(AdapterResponse::PackageSizes(Vec::new()));
+                };
+                let raw = self.source.list_installed()?;
+                Ok(AdapterResponse::PackageSizes(
+                    parse_cargo_installed_binaries(&raw)
+                        .into_iter()
+                        .filter_map(|(package, binaries)| {
+                            let bytes = cargo_binaries_size(&bin_dir, &binaries)?;
+                            Some(InstalledPackageSize { package, bytes })
+                        })
+                        .collect(),
+                ))
+            }
+            AdapterRequest::PreviewCleanup(_) | AdapterRequest::Cleanup(_) => {
+                let dry_run = matches!(request, AdapterRequest::PreviewCleanup(_));
+                crate::adapters::cache_directory_cleanup(
+                    dry_run,
+                    &self.source.registry_cache_dirs(),
+                    || self.source.clean_registry_cache(),
+                )
+            }
             _ => Err(CoreError {
                 manager: Some(ManagerId::Cargo),
                 task: None,
@@ -293,6 +366,20 @@ pub fn cargo_upgrade_request(task_id: Option<TaskId>, crate_name: &str) -> Proce
     )
 }
 
+/// `cargo info` (Cargo 1.82+) reads crates.io metadata for the latest
+/// release, so it works for crates that are not installed.
+pub fn cargo_info_request(task_id: Option<TaskId>, crate_name: &str) -> ProcessSpawnRequest {
+    cargo_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PackageInfo,
+        CommandSpec::new(CARGO_COMMAND)
+            .args(["info", "--color", "never"])
+            .arg(crate_name),
+        SEARCH_TIMEOUT,
+    )
+}
+
 fn cargo_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
@@ -359,6 +446,7 @@ pub(crate) fn parse_cargo_installed(output: &str) -> AdapterResult<Vec<Installed
             installed_version: Some(version.to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -366,6 +454,44 @@ pub(crate) fn parse_cargo_installed(output: &str) -> AdapterResult<Vec<Installed
     Ok(packages)
 }
 
+/// Each crate in `cargo install --list` with the binaries listed under it.
+fn parse_cargo_installed_binaries(output: &str) -> Vec<(PackageRef, Vec<String>)> {
+    let mut crates: Vec<(PackageRef, Vec<String>)> = Vec::new();
+    for line in output.lines() {
+        if line.starts_with(char::is_whitespace) {
+            let binary = line.trim();
+            if let Some((_, binaries)) = crates.last_mut()
+                && !binary.is_empty()
+                && !binary.contains('/')
+            {
+                binaries.push(binary.to_string());
+            }
+            continue;
+        }
+        let Some((name, _)) = line.trim().split_once(" v") else {
+            continue;
+        };
+        crates.push((
+            PackageRef {
+                manager: ManagerId::Cargo,
+                name: name.trim().to_string(),
+            },
+            Vec::new(),
+        ));
+    }
+    crates
+}
+
+/// Total size of the binaries present in `bin_dir`, or `None` if none are.
+fn cargo_binaries_size(bin_dir: &Path, binaries: &[String]) -> Option<u64> {
+    binaries
+        .iter()
+        .filter_map(|binary| std::fs::metadata(bin_dir.join(binary)).ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .reduce(u64::saturating_add)
+}
+
 pub(crate) fn parse_cargo_search_version(output: &str, crate_name: &str) -> Option<String> {
     let mut exact_match: Option<String> = None;
 
@@ -464,6 +590,7 @@ pub(crate) fn parse_cargo_search(
             source_manager: ManagerId::Cargo,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -560,6 +687,7 @@ pub(crate) fn parse_cargo_outdated(output: &str) -> AdapterResult<Vec<OutdatedPa
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -567,6 +695,63 @@ pub(crate) fn parse_cargo_outdated(output: &str) -> AdapterResult<Vec<OutdatedPa
     Ok(packages)
 }
 
+/// `cargo info` prints a `name #keywords` header, the description (possibly
+/// wrapped over several lines), then `key: value` fields and indented
+/// sections. Only the `dependencies:` section holds runtime dependencies;
+/// entries read `name@req`, with a `+` marker when a default feature enables
+/// an optional one. crates.io reports no installed size.
+fn parse_cargo_info(output: &str) -> AdapterResult<PackageMetadata> {
+    let mut lines = output.lines().skip_while(|line| line.trim().is_empty());
+    if lines
+        .next()
+        .and_then(|header| header.split_whitespace().next())
+        .is_none()
+    {
+        return Err(parse_error("cargo info output is empty"));
+    }
+
+    let mut metadata = PackageMetadata::default();
+    let mut description = Vec::new();
+    let mut in_fields = false;
+    let mut section = None;
+    for line in lines {
+        // `version:` is always the first field, so a colon in the description
+        // is not mistaken for one.
+        in_fields |= line.starts_with("version:");
+        if !in_fields {
+            if !line.trim().is_empty() {
+                description.push(line.trim());
+            }
+            continue;
+        }
+        if let Some(entry) = line.strip_prefix(' ') {
+            if section == Some("dependencies") {
+                let entry = entry.trim().trim_start_matches('+');
+                if let Some(name) = entry.split(['@', ' ']).next().filter(|n| !n.is_empty()) {
+                    metadata.dependencies.push(name.to_string());
+                }
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        section = value.is_empty().then_some(key);
+        let value = (!value.is_empty()).then(|| value.to_string());
+        match key {
+            "license" => metadata.license = value,
+            "homepage" => metadata.homepage = value,
+            _ => {}
+        }
+    }
+    if !in_fields {
+        return Err(parse_error("cargo info output is missing crate fields"));
+    }
+    metadata.description = (!description.is_empty()).then(|| description.join(" "));
+    Ok(metadata)
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::Cargo),
@@ -585,23 +770,24 @@ mod tests {
 
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
-        ListOutdatedRequest, ManagerAdapter,
+        ListOutdatedRequest, ManagerAdapter, MeasurePackageSizesRequest,
     };
     use crate::models::{
         CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType,
     };
 
     use super::{
-        CargoAdapter, CargoDetectOutput, CargoSource, cargo_detect_request, cargo_install_request,
-        cargo_list_installed_request, cargo_search_request, cargo_uninstall_request,
-        cargo_upgrade_request, parse_cargo_installed, parse_cargo_outdated, parse_cargo_search,
-        parse_cargo_search_version, parse_cargo_version,
+        CargoAdapter, CargoDetectOutput, CargoSource, cargo_detect_request, cargo_info_request,
+        cargo_install_request, cargo_list_installed_request, cargo_search_request,
+        cargo_uninstall_request, cargo_upgrade_request, parse_cargo_info, parse_cargo_installed,
+        parse_cargo_outdated, parse_cargo_search, parse_cargo_search_version, parse_cargo_version,
     };
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/cargo/version.txt");
     const INSTALLED_FIXTURE: &str = include_str!("../../tests/fixtures/cargo/install_list.txt");
     const SEARCH_FIXTURE: &str = include_str!("../../tests/fixtures/cargo/search.txt");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/cargo/outdated.json");
+    const INFO_FIXTURE: &str = include_str!("../../tests/fixtures/cargo/info.txt");
 
     #[test]
     fn parses_cargo_version_from_fixture() {
@@ -640,6 +826,32 @@ mod tests {
         assert!(parse_cargo_search_version(SEARCH_FIXTURE, "missing").is_none());
     }
 
+    #[test]
+    fn parses_info_from_fixture() {
+        let metadata = parse_cargo_info(INFO_FIXTURE).unwrap();
+        assert!(metadata.description.as_deref().is_some_and(|description| {
+            description.starts_with("ripgrep is a line-oriented")
+                && description.ends_with("macOS and Linux.")
+        }));
+        assert_eq!(metadata.license.as_deref(), Some("Unlicense OR MIT"));
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://github.com/BurntSushi/ripgrep")
+        );
+        assert_eq!(metadata.installed_size_bytes, None);
+        assert_eq!(metadata.dependencies.len(), 10);
+        assert_eq!(metadata.dependencies[0], "anyhow");
+        assert_eq!(metadata.dependencies[9], "jemallocator");
+        assert!(parse_cargo_info("").is_err());
+
+        let request = cargo_info_request(None, "ripgrep");
+        assert_eq!(request.action, ManagerAction::PackageInfo);
+        assert_eq!(
+            request.command.args,
+            vec!["info", "--color", "never", "ripgrep"]
+        );
+    }
+
     #[test]
     fn parses_outdated_from_fixture() {
         let outdated = parse_cargo_outdated(OUTDATED_FIXTURE).unwrap();
@@ -692,6 +904,7 @@ mod tests {
         installed_result: AdapterResult<String>,
         outdated_result: AdapterResult<String>,
         search_result: AdapterResult<String>,
+        bin_dir: Option<PathBuf>,
     }
 
     impl StubCargoSource {
@@ -705,6 +918,7 @@ mod tests {
                 installed_result: Ok(INSTALLED_FIXTURE.to_string()),
                 outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
                 search_result: Ok(SEARCH_FIXTURE.to_string()),
+                bin_dir: None,
             }
         }
     }
@@ -738,6 +952,51 @@ mod tests {
         fn upgrade(&self, _name: Option<&str>) -> AdapterResult<String> {
             Ok(String::new())
         }
+
+        fn info(&self, _name: &str) -> AdapterResult<String> {
+            Ok(INFO_FIXTURE.to_string())
+        }
+
+        fn registry_cache_dirs(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+
+        fn clean_registry_cache(&self) -> AdapterResult<()> {
+            Ok(())
+        }
+
+        fn bin_dir(&self) -> Option<PathBuf> {
+            self.bin_dir.clone()
+        }
+    }
+
+    #[test]
+    fn measures_installed_binaries() {
+        let bin_dir = std::env::temp_dir().join(format!("helm-cargo-bin-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&bin_dir);
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::fs::write(bin_dir.join("rg"), vec![0_u8; 4096]).unwrap();
+        std::fs::write(bin_dir.join("bat"), vec![0_u8; 1024]).unwrap();
+
+        let adapter = CargoAdapter::new(StubCargoSource {
+            bin_dir: Some(bin_dir.clone()),
+            ..StubCargoSource::success()
+        });
+        let response = adapter
+            .execute(AdapterRequest::MeasurePackageSizes(
+                MeasurePackageSizesRequest,
+            ))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&bin_dir);
+
+        let AdapterResponse::PackageSizes(sizes) = response else {
+            panic!("expected package sizes");
+        };
+        let sizes: Vec<(&str, u64)> = sizes
+            .iter()
+            .map(|size| (size.package.name.as_str(), size.bytes))
+            .collect();
+        assert_eq!(sizes, vec![("ripgrep", 4096), ("bat", 1024)]);
     }
 
     #[test]
@@ -781,6 +1040,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("14.1.1".to_string()),
+                options: Vec::new(),
             }))
             .unwrap()
         {
@@ -9,8 +9,7 @@ use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, M
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, Capability, CoreError, CoreErrorKind, DetectionInfo, ManagerAction,
-    ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId, PackageRef, SearchQuery,
-    TaskId, TaskType,
+    ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId, SearchQuery, TaskId, TaskType,
 };
 
 const CARGO_BINSTALL_CAPABILITIES: &[Capability] = &[
@@ -96,16 +95,14 @@ impl<S: CargoBinstallSource> ManagerAdapter for CargoBinstallAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
                 let tracked = self.source.tracked_package_names()?;
-                let installed = tracked_cargo_binstall_installed_packages(&self.source, &tracked)?;
-                let outdated = tracked_cargo_binstall_outdated_packages(&self.source, &tracked)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = tracked_cargo_binstall_installed_packages(&self.source, &tracked);
+                let outdated = tracked_cargo_binstall_outdated_packages(&self.source, &tracked);
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let tracked = self.source.tracked_package_names()?;
@@ -124,6 +121,11 @@ impl<S: CargoBinstallSource> ManagerAdapter for CargoBinstallAdapter<S> {
                     result.result.package.manager = ManagerId::CargoBinstall;
                     result.source_manager = ManagerId::CargoBinstall;
                 }
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::CargoBinstall,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -156,6 +158,7 @@ impl<S: CargoBinstallSource> ManagerAdapter for CargoBinstallAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -175,23 +178,25 @@ impl<S: CargoBinstallSource> ManagerAdapter for CargoBinstallAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::CargoBinstall,
-                    name: "__all__".to_string(),
-                });
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::CargoBinstall,
+                    &upgrade_request.target,
+                )?;
 
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::CargoBinstall,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::CargoBinstall,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_cargo_binstall_outdated_entry(&self.source, name))
@@ -203,13 +208,16 @@ impl<S: CargoBinstallSource> ManagerAdapter for CargoBinstallAdapter<S> {
                 }
 
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request
+                        .target
+                        .result_package(ManagerId::CargoBinstall),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|entry| entry.installed_version.clone()),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -645,6 +653,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("14.1.1".to_string()),
+                options: Vec::new(),
             }))
             .unwrap()
         {
@@ -8,6 +8,7 @@ use crate::adapters::cargo_binstall::{
     cargo_binstall_search_request, cargo_binstall_search_single_request,
     cargo_binstall_uninstall_request, cargo_binstall_upgrade_request,
 };
+use crate::adapters::cargo_index::{crates_index_client, resolve_latest_from_index};
 use crate::adapters::cargo_outdated::synthesize_outdated_payload_for_packages;
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
@@ -125,10 +126,19 @@ impl CargoBinstallSource for ProcessCargoBinstallSource {
             .filter(|package| tracked.contains(&package.package.name))
             .collect();
 
+        let index_client = crates_index_client();
         synthesize_outdated_payload_for_packages(
             ManagerId::CargoBinstall,
             installed,
-            |crate_name| {
+            |crate_name, installed_version| {
+                if let Some(client) = &index_client {
+                    return resolve_latest_from_index(
+                        client.as_ref(),
+                        ManagerId::CargoBinstall,
+                        crate_name,
+                        installed_version,
+                    );
+                }
                 let request =
                     self.configure_request(cargo_binstall_search_single_request(None, crate_name));
                 let search_output = run_and_collect_stdout(self.executor.as_ref(), request)?;
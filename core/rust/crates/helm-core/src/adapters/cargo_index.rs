@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::Deserialize;
+
+use crate::adapters::manager::AdapterResult;
+use crate::models::{
+    CoreError, CoreErrorKind, InstalledPackage, ManagerAction, ManagerId, PackageRef, TaskType,
+};
+
+static CRATES_INDEX_CLIENT: OnceLock<RwLock<Option<Arc<dyn CratesIndexClient>>>> = OnceLock::new();
+
+/// Sources `cargo install` records for crates.io in `.crates2.json`.
+const CRATES_IO_SOURCES: &[&str] = &[
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+/// Host-provided reader for the crates.io sparse index
+/// (`https://index.crates.io/`).
+pub trait CratesIndexClient: Send + Sync {
+    /// Fetch the index file at `path`, relative to the index root. `Ok(None)`
+    /// means the index has no such crate.
+    fn fetch_index_file(&self, path: &str) -> Result<Option<String>, String>;
+}
+
+/// Install the process-wide index client. Without one, cargo outdated checks
+/// fall back to one `cargo search` per installed crate.
+pub fn install_crates_index_client(client: Arc<dyn CratesIndexClient>) {
+    let slot = CRATES_INDEX_CLIENT.get_or_init(|| RwLock::new(None));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = Some(client);
+}
+
+pub fn crates_index_client() -> Option<Arc<dyn CratesIndexClient>> {
+    let slot = CRATES_INDEX_CLIENT.get()?;
+    slot.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Index file path of `crate_name` per the registry index layout.
+pub(crate) fn sparse_index_path(crate_name: &str) -> String {
+    let name = crate_name.to_ascii_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+#[derive(Deserialize)]
+struct Crates2File {
+    #[serde(default)]
+    installs: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+/// crates.io installs recorded in cargo's `.crates2.json`, sorted by name.
+/// Git, path, and alternate-registry installs are left out since the
+/// crates.io index says nothing about them.
+pub(crate) fn parse_crates2_installs(
+    manager: ManagerId,
+    raw: &str,
+) -> AdapterResult<Vec<InstalledPackage>> {
+    let file: Crates2File = serde_json::from_str(raw).map_err(|error| CoreError {
+        manager: Some(manager),
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::ListOutdated),
+        kind: CoreErrorKind::ParseFailure,
+        message: format!("failed to parse .crates2.json: {error}"),
+    })?;
+
+    let mut packages = Vec::new();
+    for key in file.installs.keys() {
+        // Example: "ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)"
+        let mut parts = key.splitn(3, ' ');
+        let (Some(name), Some(version), Some(source)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let source = source.trim_start_matches('(').trim_end_matches(')');
+        if !CRATES_IO_SOURCES.contains(&source) {
+            continue;
+        }
+        packages.push(InstalledPackage {
+            package: PackageRef {
+                manager,
+                name: name.to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some(version.to_string()),
+            pinned: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        });
+    }
+
+    packages.sort_by(|a, b| a.package.name.cmp(&b.package.name));
+    Ok(packages)
+}
+
+/// Read `.crates2.json` under `cargo_home`; `None` when it is missing or
+/// unreadable, so callers can fall back to `cargo install --list`.
+pub(crate) fn read_crates2_installs(
+    manager: ManagerId,
+    cargo_home: &Path,
+) -> Option<Vec<InstalledPackage>> {
+    let raw = std::fs::read_to_string(cargo_home.join(".crates2.json")).ok()?;
+    parse_crates2_installs(manager, &raw).ok()
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Newest non-yanked version in a sparse index file that is newer than
+/// `installed_version`. Pre-releases are only candidates when the installed
+/// version is itself a pre-release, matching how cargo selects versions.
+pub(crate) fn latest_index_version(index_file: &str, installed_version: &str) -> Option<String> {
+    let installed = SemVer::parse(installed_version)?;
+    index_file
+        .lines()
+        .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+        .filter(|entry| !entry.yanked)
+        .filter_map(|entry| Some((SemVer::parse(&entry.vers)?, entry.vers)))
+        .filter(|(version, _)| version.pre.is_empty() || !installed.pre.is_empty())
+        .filter(|(version, _)| version.cmp(&installed) == Ordering::Greater)
+        .max_by(|(left, _), (right, _)| left.cmp(right))
+        .map(|(_, raw)| raw)
+}
+
+pub(crate) fn resolve_latest_from_index(
+    client: &dyn CratesIndexClient,
+    manager: ManagerId,
+    crate_name: &str,
+    installed_version: &str,
+) -> AdapterResult<Option<String>> {
+    let index_file = client
+        .fetch_index_file(&sparse_index_path(crate_name))
+        .map_err(|error| CoreError {
+            manager: Some(manager),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListOutdated),
+            kind: CoreErrorKind::ProcessFailure,
+            message: format!("crates.io index lookup for '{crate_name}' failed: {error}"),
+        })?;
+    Ok(index_file.and_then(|index_file| latest_index_version(&index_file, installed_version)))
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct SemVer {
+    release: [u64; 3],
+    pre: Vec<String>,
+}
+
+impl SemVer {
+    fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let raw = raw.split_once('+').map_or(raw, |(version, _)| version);
+        let (release, pre) = match raw.split_once('-') {
+            Some((release, pre)) => (release, pre.split('.').map(str::to_string).collect()),
+            None => (raw, Vec::new()),
+        };
+        let mut parts = release.split('.').map(|part| part.parse::<u64>().ok());
+        let release = [parts.next()??, parts.next()??, parts.next()??];
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { release, pre })
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.release.cmp(&other.release).then_with(|| {
+            // A release outranks its pre-releases; identifiers then compare
+            // numerically when both are numeric, numeric before alphanumeric.
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (left, right) in self.pre.iter().zip(&other.pre) {
+                        let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+                            (Ok(left), Ok(right)) => left.cmp(&right),
+                            (Ok(_), Err(_)) => Ordering::Less,
+                            (Err(_), Ok(_)) => Ordering::Greater,
+                            (Err(_), Err(_)) => left.cmp(right),
+                        };
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            }
+        })
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{latest_index_version, parse_crates2_installs, sparse_index_path};
+    use crate::models::ManagerId;
+
+    const INDEX_FILE: &str = concat!(
+        "{\"name\":\"ripgrep\",\"vers\":\"13.0.0\",\"yanked\":false}\n",
+        "{\"name\":\"ripgrep\",\"vers\":\"14.1.1\",\"yanked\":false}\n",
+        "{\"name\":\"ripgrep\",\"vers\":\"14.2.0\",\"yanked\":true}\n",
+        "{\"name\":\"ripgrep\",\"vers\":\"15.0.0-beta.2\",\"yanked\":false}\n",
+        "{\"name\":\"ripgrep\",\"vers\":\"15.0.0-beta.10\",\"yanked\":false}\n",
+    );
+
+    #[test]
+    fn index_paths_follow_registry_layout() {
+        assert_eq!(sparse_index_path("a"), "1/a");
+        assert_eq!(sparse_index_path("cc"), "2/cc");
+        assert_eq!(sparse_index_path("bat"), "3/b/bat");
+        assert_eq!(sparse_index_path("Ripgrep"), "ri/pg/ripgrep");
+    }
+
+    #[test]
+    fn latest_version_skips_yanked_and_unrequested_pre_releases() {
+        assert_eq!(
+            latest_index_version(INDEX_FILE, "13.0.0").as_deref(),
+            Some("14.1.1")
+        );
+        assert_eq!(latest_index_version(INDEX_FILE, "14.1.1"), None);
+        assert_eq!(
+            latest_index_version(INDEX_FILE, "15.0.0-beta.1").as_deref(),
+            Some("15.0.0-beta.10")
+        );
+        assert_eq!(latest_index_version(INDEX_FILE, "not-a-version"), None);
+    }
+
+    #[test]
+    fn crates2_installs_keep_only_crates_io_sources() {
+        let raw = r#"{"installs":{
+            "ripgrep 14.1.1 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["rg"]},
+            "bat 0.24.0 (sparse+https://index.crates.io/)":{"bins":["bat"]},
+            "helix-term 24.7.0 (git+https://github.com/helix-editor/helix#abc)":{"bins":["hx"]},
+            "local 0.1.0 (path+file:///src/local)":{"bins":["local"]}
+        }}"#;
+
+        let installs = parse_crates2_installs(ManagerId::Cargo, raw).expect("valid file");
+        let installs = installs
+            .iter()
+            .map(|package| {
+                (
+                    package.package.name.as_str(),
+                    package.installed_version.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            installs,
+            vec![("bat", Some("0.24.0")), ("ripgrep", Some("14.1.1"))]
+        );
+    }
+}
@@ -19,7 +19,7 @@ pub(crate) fn synthesize_outdated_payload<F>(
     resolve_latest: F,
 ) -> AdapterResult<String>
 where
-    F: FnMut(&str) -> AdapterResult<Option<String>>,
+    F: FnMut(&str, &str) -> AdapterResult<Option<String>>,
 {
     let installed = parse_cargo_installed(installed_raw).map_err(|mut error| {
         error.manager = Some(manager);
@@ -35,7 +35,7 @@ pub(crate) fn synthesize_outdated_payload_for_packages<F>(
     mut resolve_latest: F,
 ) -> AdapterResult<String>
 where
-    F: FnMut(&str) -> AdapterResult<Option<String>>,
+    F: FnMut(&str, &str) -> AdapterResult<Option<String>>,
 {
     let mut seen = BTreeSet::new();
     let mut outdated = Vec::new();
@@ -50,7 +50,7 @@ where
             continue;
         };
 
-        let Some(latest) = resolve_latest(&name)? else {
+        let Some(latest) = resolve_latest(&name, &installed_version)? else {
             continue;
         };
 
@@ -1,17 +1,24 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::adapters::cargo::{
-    CargoDetectOutput, CargoSource, cargo_detect_request, cargo_install_request,
-    cargo_list_installed_request, cargo_search_request, cargo_search_single_request,
-    cargo_uninstall_request, cargo_upgrade_request, parse_cargo_outdated,
-    parse_cargo_search_version,
+    CargoDetectOutput, CargoSource, cargo_detect_request, cargo_info_request,
+    cargo_install_request, cargo_list_installed_request, cargo_search_request,
+    cargo_search_single_request, cargo_uninstall_request, cargo_upgrade_request,
+    parse_cargo_installed, parse_cargo_outdated, parse_cargo_search_version,
+};
+use crate::adapters::cargo_index::{
+    crates_index_client, read_crates2_installs, resolve_latest_from_index,
+};
+use crate::adapters::cargo_outdated::{
+    synthesize_outdated_payload, synthesize_outdated_payload_for_packages,
 };
-use crate::adapters::cargo_outdated::synthesize_outdated_payload;
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
+use crate::base_dirs::ToolBaseDirs;
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
-use crate::models::{ManagerId, SearchQuery};
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, SearchQuery, TaskType};
 
 pub struct ProcessCargoSource {
     executor: Arc<dyn ProcessExecutor>,
@@ -86,9 +93,31 @@ impl CargoSource for ProcessCargoSource {
     }
 
     fn list_outdated(&self) -> AdapterResult<String> {
+        if let Some(client) = crates_index_client() {
+            let installed = match ToolBaseDirs::from_env()
+                .cargo_home
+                .and_then(|cargo_home| read_crates2_installs(ManagerId::Cargo, &cargo_home))
+            {
+                Some(installed) => installed,
+                None => parse_cargo_installed(&self.list_installed()?)?,
+            };
+            return synthesize_outdated_payload_for_packages(
+                ManagerId::Cargo,
+                installed,
+                |crate_name, installed_version| {
+                    resolve_latest_from_index(
+                        client.as_ref(),
+                        ManagerId::Cargo,
+                        crate_name,
+                        installed_version,
+                    )
+                },
+            );
+        }
+
         let installed_raw = self.list_installed()?;
         // cargo has no built-in global outdated list command for installed binaries.
-        synthesize_outdated_payload(ManagerId::Cargo, &installed_raw, |crate_name| {
+        synthesize_outdated_payload(ManagerId::Cargo, &installed_raw, |crate_name, _| {
             let request = self.configure_request(cargo_search_single_request(None, crate_name));
             let search_output = run_and_collect_stdout(self.executor.as_ref(), request)?;
             Ok(parse_cargo_search_version(&search_output, crate_name))
@@ -130,4 +159,48 @@ impl CargoSource for ProcessCargoSource {
 
         Ok(String::new())
     }
+
+    fn info(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(cargo_info_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn registry_cache_dirs(&self) -> Vec<PathBuf> {
+        ToolBaseDirs::from_env()
+            .cargo_home
+            .map(|cargo_home| {
+                vec![
+                    cargo_home.join("registry/cache"),
+                    cargo_home.join("registry/src"),
+                ]
+            })
+            .unwrap_or_default()
+    }
+
+    fn clean_registry_cache(&self) -> AdapterResult<()> {
+        // cargo has no cache command of its own; removing these directories is
+        // what `cargo cache --autoclean` does.
+        for dir in self.registry_cache_dirs() {
+            match std::fs::remove_dir_all(&dir) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    return Err(CoreError {
+                        manager: Some(ManagerId::Cargo),
+                        task: Some(TaskType::Configure),
+                        action: Some(ManagerAction::Cleanup),
+                        kind: CoreErrorKind::ProcessFailure,
+                        message: format!("failed to remove '{}': {error}", dir.display()),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn bin_dir(&self) -> Option<PathBuf> {
+        ToolBaseDirs::from_env()
+            .cargo_home
+            .map(|cargo_home| cargo_home.join("bin"))
+    }
 }
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use crate::adapters::process_utils::run_and_collect_stdout;
+use crate::base_dirs::ToolBaseDirs;
 use crate::execution::{CommandSpec, ProcessExecutor, ProcessSpawnRequest};
 use crate::models::{ManagerAction, ManagerId, TaskType};
 
@@ -111,13 +112,6 @@ fn manager_additional_bin_roots() -> Vec<PathBuf> {
     manager_additional_bin_roots_for_home(std::env::var_os("HOME").map(PathBuf::from))
 }
 
-fn absolute_env_path(key: &str) -> Option<PathBuf> {
-    std::env::var_os(key)
-        .filter(|value| !value.is_empty())
-        .map(PathBuf::from)
-        .filter(|path| path.is_absolute() && !path.as_os_str().is_empty())
-}
-
 fn manager_additional_bin_roots_for_home(home: Option<PathBuf>) -> Vec<PathBuf> {
     let mut roots = vec![
         PathBuf::from("/opt/homebrew/bin"),
@@ -131,26 +125,11 @@ fn manager_additional_bin_roots_for_home(home: Option<PathBuf>) -> Vec<PathBuf>
         PathBuf::from("/nix/var/nix/profiles/default/bin"),
     ];
 
-    if let Some(cargo_home) = absolute_env_path("CARGO_HOME") {
-        roots.push(cargo_home.join("bin"));
-    }
-    if let Some(home) = home {
-        roots.push(home.join(".local/bin"));
-        roots.push(home.join(".cargo/bin"));
-        roots.push(home.join(".asdf/bin"));
-        roots.push(home.join(".asdf/shims"));
-        roots.push(home.join(".local/share/mise/shims"));
-        roots.push(home.join(".local/share/rtx/shims"));
+    let base_dirs = ToolBaseDirs::resolve(home, |key| std::env::var_os(key));
+    roots.extend(base_dirs.tool_bin_roots());
+    if let Some(home) = &base_dirs.home {
         roots.push(home.join(".nix-profile/bin"));
     }
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
 
     roots
 }
@@ -211,19 +190,10 @@ fn manager_versioned_install_roots_for_home(
         roots.push(PathBuf::from("/usr/local/Cellar"));
     }
 
-    if uses_tool_version_installs(manager)
-        && let Some(home) = home
-    {
-        roots.push(home.join(".asdf/installs"));
-        roots.push(home.join(".local/share/mise/installs"));
-        roots.push(home.join(".local/share/rtx/installs"));
-    }
-
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("installs"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("installs"));
+    if uses_tool_version_installs(manager) {
+        roots.extend(
+            ToolBaseDirs::resolve(home, |key| std::env::var_os(key)).tool_version_install_roots(),
+        );
     }
 
     roots
@@ -85,6 +85,7 @@ impl<S: DockerDesktopSource> ManagerAdapter for DockerDesktopAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
@@ -103,8 +104,10 @@ impl<S: DockerDesktopSource> ManagerAdapter for DockerDesktopAdapter<S> {
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]),
                     outdated: Some(outdated),
+                    stage_failures: Vec::new(),
                 })
             }
             AdapterRequest::ListInstalled(_) => {
@@ -121,6 +124,7 @@ impl<S: DockerDesktopSource> ManagerAdapter for DockerDesktopAdapter<S> {
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]
                 } else {
                     Vec::new()
@@ -303,6 +307,7 @@ fn parse_docker_desktop_outdated(output: &str) -> AdapterResult<Vec<OutdatedPack
                 pinned: false,
                 restart_required: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
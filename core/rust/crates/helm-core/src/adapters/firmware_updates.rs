@@ -2,11 +2,12 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::softwareupdate::parse_softwareupdate_size;
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, Capability, CoreError, CoreErrorKind, DetectionInfo, ManagerAction,
     ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId, OutdatedPackage, PackageRef,
-    TaskId, TaskType,
+    PackageSizes, TaskId, TaskType,
 };
 
 const FIRMWARE_UPDATES_CAPABILITIES: &[Capability] = &[
@@ -77,6 +78,7 @@ impl<S: FirmwareUpdatesSource> ManagerAdapter for FirmwareUpdatesAdapter<S> {
                 Ok(AdapterResponse::SnapshotSync {
                     installed: None,
                     outdated: Some(outdated),
+                    stage_failures: Vec::new(),
                 })
             }
             AdapterRequest::ListOutdated(_) => {
@@ -183,6 +185,7 @@ fn parse_firmware_updates_list(output: &str) -> AdapterResult<Vec<OutdatedPackag
     let mut current_title: Option<String> = None;
     let mut current_version: Option<String> = None;
     let mut current_restart_required = false;
+    let mut current_download_size: Option<u64> = None;
 
     for line in output.lines() {
         let trimmed = line.trim();
@@ -194,11 +197,13 @@ fn parse_firmware_updates_list(output: &str) -> AdapterResult<Vec<OutdatedPackag
                 current_title.take(),
                 current_version.take(),
                 current_restart_required,
+                current_download_size.take(),
             );
             current_label = Some(rest.trim().to_string());
             current_title = None;
             current_version = None;
             current_restart_required = false;
+            current_download_size = None;
             continue;
         }
 
@@ -211,6 +216,7 @@ fn parse_firmware_updates_list(output: &str) -> AdapterResult<Vec<OutdatedPackag
                     match key {
                         "Title" => current_title = Some(value.to_string()),
                         "Version" => current_version = Some(value.to_string()),
+                        "Size" => current_download_size = parse_softwareupdate_size(value),
                         "Action" if value.eq_ignore_ascii_case("restart") => {
                             current_restart_required = true;
                         }
@@ -227,6 +233,7 @@ fn parse_firmware_updates_list(output: &str) -> AdapterResult<Vec<OutdatedPackag
         current_title.take(),
         current_version.take(),
         current_restart_required,
+        current_download_size.take(),
     );
 
     Ok(packages)
@@ -238,6 +245,7 @@ fn flush_firmware_update_block(
     title: Option<String>,
     version: Option<String>,
     restart_required: bool,
+    download_size_bytes: Option<u64>,
 ) {
     let Some(label) = label else {
         return;
@@ -274,6 +282,10 @@ fn flush_firmware_update_block(
         pinned: false,
         restart_required,
         runtime_state: Default::default(),
+        sizes: PackageSizes {
+            install_size_bytes: None,
+            download_size_bytes,
+        },
     });
 }
 
@@ -366,6 +378,7 @@ mod tests {
         let AdapterResponse::SnapshotSync {
             installed,
             outdated,
+            ..
         } = response
         else {
             panic!("expected snapshot sync response");
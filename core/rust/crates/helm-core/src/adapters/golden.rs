@@ -0,0 +1,107 @@
+//! Golden tests for adapter parsers against captured tool output.
+//!
+//! Each manager keeps one directory per tool version under
+//! `tests/fixtures/<manager>/versions/<tool>-<major>/`, holding raw output
+//! captured with the `helm-capture-fixtures` binary next to `*.golden` files
+//! with the expected parse. Set `HELM_UPDATE_GOLDEN=1` to rewrite the golden
+//! files from the current parsers, then review the diff. See
+//! `tests/fixtures/README.md`.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::models::{InstalledPackage, OutdatedPackage};
+
+const UPDATE_ENV: &str = "HELM_UPDATE_GOLDEN";
+
+fn versions_dir(manager_dir: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(manager_dir)
+        .join("versions")
+}
+
+/// Raw captured output for one tool version.
+pub(crate) fn fixture(manager_dir: &str, case: &str, file: &str) -> String {
+    let path = versions_dir(manager_dir).join(case).join(file);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("missing fixture {}: {error}", path.display()))
+}
+
+/// Fails when a captured version directory has no row in the test's case
+/// table, so a newly captured tool version cannot go untested.
+pub(crate) fn assert_cases_cover_fixtures<T>(manager_dir: &str, cases: &[(&str, T)]) {
+    let mut on_disk: Vec<String> = std::fs::read_dir(versions_dir(manager_dir))
+        .expect("fixture versions directory")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    on_disk.sort();
+    let mut listed: Vec<String> = cases.iter().map(|(case, _)| case.to_string()).collect();
+    listed.sort();
+    assert_eq!(
+        listed, on_disk,
+        "golden case table for {manager_dir} is out of sync with tests/fixtures/{manager_dir}/versions"
+    );
+}
+
+/// Compare `actual` with the golden file, or rewrite it when
+/// `HELM_UPDATE_GOLDEN` is set.
+pub(crate) fn assert_golden(manager_dir: &str, case: &str, file: &str, actual: &str) {
+    let path = versions_dir(manager_dir).join(case).join(file);
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::write(&path, actual)
+            .unwrap_or_else(|error| panic!("failed to write {}: {error}", path.display()));
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!(
+            "missing golden file {} ({error}); rerun with {UPDATE_ENV}=1 to create it",
+            path.display()
+        )
+    });
+    assert_eq!(
+        actual,
+        expected,
+        "{} no longer matches; rerun with {UPDATE_ENV}=1 and review the diff",
+        path.display()
+    );
+}
+
+/// One line per package: `name installed_version [pinned]`.
+pub(crate) fn render_installed(packages: &[InstalledPackage]) -> String {
+    let mut rendered = String::new();
+    for package in packages {
+        let _ = write!(
+            rendered,
+            "{} {}",
+            package.package.name,
+            package.installed_version.as_deref().unwrap_or("-")
+        );
+        if package.pinned {
+            rendered.push_str(" pinned");
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// One line per package: `name installed_version -> candidate_version [pinned]`.
+pub(crate) fn render_outdated(packages: &[OutdatedPackage]) -> String {
+    let mut rendered = String::new();
+    for package in packages {
+        let _ = write!(
+            rendered,
+            "{} {} -> {}",
+            package.package.name,
+            package.installed_version.as_deref().unwrap_or("-"),
+            package.candidate_version
+        );
+        if package.pinned {
+            rendered.push_str(" pinned");
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
@@ -5,7 +5,14 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
-use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::homebrew_cleanup::parse_homebrew_cleanup;
+use crate::adapters::homebrew_services::{parse_homebrew_services, validate_service_name};
+use crate::adapters::manager::{
+    AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter, PackageDetailChildKind,
+    PackageDetailOperation, PackageDetailRequest, measure_package_directories,
+};
+use crate::adapters::process_utils::last_percent;
+use crate::base_dirs::ToolBaseDirs;
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::manager_lifecycle::{
     HomebrewUninstallCleanupMode, parse_homebrew_manager_uninstall_package_name,
@@ -13,8 +20,10 @@ use crate::manager_lifecycle::{
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
+    ManagerId, OutdatedPackage, PackageCandidate, PackageDependency, PackageFiles, PackageMetadata,
+    PackageRef, SearchQuery, ServiceAction, TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 
 const HOMEBREW_READ_CAPABILITIES: &[Capability] = &[
     Capability::Detect,
@@ -27,6 +36,14 @@ const HOMEBREW_READ_CAPABILITIES: &[Capability] = &[
     Capability::Upgrade,
     Capability::Pin,
     Capability::Unpin,
+    Capability::ListFiles,
+    Capability::ListDependencies,
+    Capability::ListRequested,
+    Capability::Rollback,
+    Capability::PackageInfo,
+    Capability::Services,
+    Capability::Cleanup,
+    Capability::PackageSizes,
 ];
 
 const HOMEBREW_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -37,10 +54,11 @@ const HOMEBREW_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
     capabilities: HOMEBREW_READ_CAPABILITIES,
 };
 
-const HOMEBREW_COMMAND: &str = "brew";
-const HOMEBREW_CLEANUP_MARKER: &str = "@@helm.cleanup";
+pub(crate) const HOMEBREW_COMMAND: &str = "brew";
+#[cfg(test)]
+const HOMEBREW_CLEANUP_MARKER: &str = crate::upgrade_target::LEGACY_HOMEBREW_CLEANUP_SUFFIX;
 const DETECT_TIMEOUT: Duration = Duration::from_secs(10);
-const LIST_TIMEOUT: Duration = Duration::from_secs(120);
+pub(crate) const LIST_TIMEOUT: Duration = Duration::from_secs(120);
 const SEARCH_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -60,19 +78,55 @@ pub trait HomebrewSource: Send + Sync {
 
     fn list_outdated_formulae(&self) -> AdapterResult<String>;
 
+    /// Outdated listing for an explicit refresh, where Homebrew may auto-update first.
+    fn refresh_outdated_formulae(&self) -> AdapterResult<String> {
+        self.list_outdated_formulae()
+    }
+
     fn search_formulae(&self, query: &SearchQuery) -> AdapterResult<String>;
 
-    fn install_formula(&self, name: &str) -> AdapterResult<String>;
+    fn install_formula(&self, name: &str, options: &[String]) -> AdapterResult<String>;
 
     fn uninstall_formula(&self, name: &str) -> AdapterResult<String>;
 
-    fn upgrade_formula(&self, name: Option<&str>) -> AdapterResult<String>;
+    fn update_homebrew(&self) -> AdapterResult<String>;
+
+    fn upgrade_formula(&self, name: Option<&str>, options: &[String]) -> AdapterResult<String>;
 
     fn cleanup_formula(&self, name: &str) -> AdapterResult<String>;
 
     fn pin_formula(&self, name: &str) -> AdapterResult<String>;
 
     fn unpin_formula(&self, name: &str) -> AdapterResult<String>;
+
+    fn list_formula_files(&self, name: &str) -> AdapterResult<String>;
+
+    fn list_formula_dependencies(&self) -> AdapterResult<String>;
+
+    fn list_requested_formulae(&self) -> AdapterResult<String>;
+
+    /// `brew info --json=v2` output for one formula.
+    fn formula_info(&self, name: &str) -> AdapterResult<String>;
+
+    /// Make the `version` keg still in the Cellar the linked one again.
+    fn switch_formula_keg(&self, name: &str, version: &str) -> AdapterResult<String>;
+
+    /// `brew tap` output: one installed `user/repo` tap per line.
+    fn list_taps(&self) -> AdapterResult<String>;
+
+    /// `brew tap user/repo [url]`.
+    fn add_tap(&self, tap: &str, url: Option<&str>) -> AdapterResult<String>;
+
+    /// `brew services list --json` output.
+    fn list_services(&self) -> AdapterResult<String>;
+
+    fn set_service_state(&self, name: &str, action: ServiceAction) -> AdapterResult<String>;
+
+    /// `brew cleanup` output; with `dry_run`, what it would remove.
+    fn cleanup(&self, dry_run: bool) -> AdapterResult<String>;
+
+    /// `brew --cellar`, the directory holding every formula's kegs.
+    fn cellar(&self) -> AdapterResult<String>;
 }
 
 pub struct HomebrewAdapter<S: HomebrewSource> {
@@ -83,6 +137,27 @@ impl<S: HomebrewSource> HomebrewAdapter<S> {
     pub fn new(source: S) -> Self {
         Self { source }
     }
+
+    fn add_tap(&self, tap: String, url: Option<String>) -> AdapterResult<AdapterResponse> {
+        validate_homebrew_tap_name(&tap)?;
+        if let Some(url) = url.as_deref() {
+            validate_homebrew_tap_url(url)?;
+        }
+        if !homebrew_tap_is_installed(&self.source.list_taps()?, &tap) {
+            self.source.add_tap(&tap, url.as_deref())?;
+        }
+        Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: tap,
+            },
+            package_identifier: None,
+            action: ManagerAction::Configure,
+            before_version: None,
+            after_version: None,
+            disposal: None,
+        }))
+    }
 }
 
 impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
@@ -111,15 +186,19 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_installed_formulae(&self.source.list_installed_formulae()?)?;
-                let outdated = parse_outdated_formulae(&self.source.list_outdated_formulae()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed_formulae()
+                    .and_then(|raw| parse_installed_formulae(&raw));
+                let outdated = self
+                    .source
+                    .refresh_outdated_formulae()
+                    .and_then(|raw| parse_outdated_formulae(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed_formulae()?;
@@ -133,7 +212,12 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
             }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search_formulae(&search_request.query)?;
-                let results = parse_search_formulae(&raw, &search_request.query)?;
+                let mut results = parse_search_formulae(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::HomebrewFormula,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -142,25 +226,42 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
                     install_request.version.as_deref(),
                     ManagerAction::Install,
                 )?;
-                let before_version = resolve_homebrew_formula_version(
-                    &self.source,
-                    install_request.package.name.as_str(),
+                let target =
+                    parse_homebrew_formula_install_target(install_request.package.name.as_str())?;
+                let options = crate::install_options::validate_install_options(
+                    ManagerId::HomebrewFormula,
+                    &install_request.options,
                 )?;
-                if let Err(error) = self.source.install_formula(&install_request.package.name)
+                if let HomebrewFormulaInstallTarget::TapQualified { tap, .. } = &target
+                    && !homebrew_tap_is_installed(&self.source.list_taps()?, tap)
+                {
+                    self.source.add_tap(tap, None)?;
+                    crate::execution::record_task_log_note(&format!(
+                        "added Homebrew tap '{tap}' before installing '{}'",
+                        install_request.package.name
+                    ));
+                }
+                let before_version =
+                    resolve_homebrew_formula_version(&self.source, target.installed_name())?;
+                if let Err(error) = self
+                    .source
+                    .install_formula(&install_request.package.name, &options)
                     && !is_homebrew_already_installed_error(&error)
                 {
                     return Err(error);
                 }
-                let after_version = resolve_homebrew_formula_version(
-                    &self.source,
-                    install_request.package.name.as_str(),
-                )?;
+                let after_version =
+                    resolve_homebrew_formula_version(&self.source, target.installed_name())?;
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package: install_request.package,
+                    package: PackageRef {
+                        manager: ManagerId::HomebrewFormula,
+                        name: target.installed_name().to_string(),
+                    },
                     package_identifier: None,
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -227,43 +328,72 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let requested_package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::HomebrewFormula,
-                    name: "__all__".to_string(),
-                });
+                let target_name = match &upgrade_request.target {
+                    UpgradeTarget::Package(package) => Some(package.name.as_str()),
+                    UpgradeTarget::All
+                    | UpgradeTarget::SelfUpdate
+                    | UpgradeTarget::SelfUpdateThenAll => None,
+                    target => {
+                        return Err(crate::adapters::unsupported_upgrade_target_error(
+                            ManagerId::HomebrewFormula,
+                            target,
+                        ));
+                    }
+                };
                 validate_homebrew_formula_upgrade_target(
-                    requested_package.name.as_str(),
+                    target_name,
                     upgrade_request.version.as_deref(),
                 )?;
-                let (target_name, cleanup_after_upgrade) =
-                    split_upgrade_target(requested_package.name.as_str());
-                let targeted_outdated = if target_name != "__all__" && target_name != "__self__" {
-                    find_outdated_homebrew_formula(&self.source, target_name)?
-                } else {
-                    None
-                };
-                let _ = self.source.upgrade_formula(Some(target_name))?;
-                if target_name != "__all__" && target_name != "__self__" {
-                    ensure_formula_no_longer_outdated(&self.source, target_name)?;
+                let options = crate::install_options::validate_upgrade_options(
+                    ManagerId::HomebrewFormula,
+                    &upgrade_request.options,
+                )?;
+
+                if upgrade_request.target.updates_manager() {
+                    // A failed `brew update` aborts the flow so `brew upgrade`
+                    // never runs against stale metadata.
+                    let _ = self.source.update_homebrew()?;
                 }
-                if cleanup_after_upgrade && target_name != "__all__" && target_name != "__self__" {
+                let Some(target_name) = target_name else {
+                    if !matches!(upgrade_request.target, UpgradeTarget::SelfUpdate) {
+                        let _ = self.source.upgrade_formula(None, &options)?;
+                    }
+                    return Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
+                        package: upgrade_request
+                            .target
+                            .result_package(ManagerId::HomebrewFormula),
+                        package_identifier: None,
+                        action: ManagerAction::Upgrade,
+                        before_version: None,
+                        after_version: None,
+                        disposal: None,
+                    }));
+                };
+
+                let (target_name, legacy_cleanup) = split_upgrade_target(target_name);
+                let cleanup_after_upgrade = upgrade_request.cleanup_old_kegs || legacy_cleanup;
+                let targeted_outdated = find_outdated_homebrew_formula(&self.source, target_name)?;
+                let _ = self.source.upgrade_formula(Some(target_name), &options)?;
+                ensure_formula_no_longer_outdated(&self.source, target_name)?;
+                if cleanup_after_upgrade {
                     let _ = self.source.cleanup_formula(target_name)?;
                 }
-                let package = PackageRef {
-                    manager: requested_package.manager,
-                    name: target_name.to_string(),
-                };
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: PackageRef {
+                        manager: ManagerId::HomebrewFormula,
+                        name: target_name.to_string(),
+                    },
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|item| item.installed_version.clone()),
                     after_version: targeted_outdated.map(|item| item.candidate_version),
+                    disposal: None,
                 }))
             }
             AdapterRequest::Pin(pin_request) => {
@@ -279,6 +409,7 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
                     action: ManagerAction::Pin,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Unpin(unpin_request) => {
@@ -294,10 +425,130 @@ impl<S: HomebrewSource> ManagerAdapter for HomebrewAdapter<S> {
                     action: ManagerAction::Unpin,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
+                }))
+            }
+            AdapterRequest::ListFiles(list_files_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::HomebrewFormula,
+                    ManagerAction::ListFiles,
+                    list_files_request.package.name.as_str(),
+                )?;
+                let raw = self
+                    .source
+                    .list_formula_files(&list_files_request.package.name)?;
+                Ok(AdapterResponse::PackageFiles(PackageFiles::from_listing(
+                    parse_formula_files(&raw),
+                    list_files_request.limit,
+                )))
+            }
+            AdapterRequest::ListDependencies(_) => {
+                let raw = self.source.list_formula_dependencies()?;
+                Ok(AdapterResponse::Dependencies(parse_formula_dependencies(
+                    &raw,
+                )))
+            }
+            AdapterRequest::PackageInfo(info_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::HomebrewFormula,
+                    ManagerAction::PackageInfo,
+                    info_request.package.name.as_str(),
+                )?;
+                let raw = self.source.formula_info(&info_request.package.name)?;
+                Ok(AdapterResponse::PackageInfo(parse_formula_info(&raw)?))
+            }
+            AdapterRequest::ListServices(_) => {
+                let raw = self.source.list_services()?;
+                Ok(AdapterResponse::Services(parse_homebrew_services(&raw)?))
+            }
+            AdapterRequest::SetServiceState(service_request) => {
+                let name = service_request.service.trim();
+                validate_service_name(name)?;
+                self.source
+                    .set_service_state(name, service_request.action)?;
+                let raw = self.source.list_services()?;
+                Ok(AdapterResponse::Services(parse_homebrew_services(&raw)?))
+            }
+            AdapterRequest::PreviewCleanup(_) => {
+                let raw = self.source.cleanup(true)?;
+                Ok(AdapterResponse::Cleanup(parse_homebrew_cleanup(&raw)))
+            }
+            AdapterRequest::Cleanup(_) => {
+                let raw = self.source.cleanup(false)?;
+                Ok(AdapterResponse::Cleanup(parse_homebrew_cleanup(&raw)))
+            }
+            AdapterRequest::MeasurePackageSizes(_) => {
+                let installed = parse_installed_formulae(&self.source.list_installed_formulae()?)?;
+                let cellar = parse_homebrew_cellar(&self.source.cellar()?)?;
+                // Every keg of a formula counts, not just the linked one.
+                Ok(AdapterResponse::PackageSizes(measure_package_directories(
+                    installed.into_iter().map(|installed| {
+                        let kegs = cellar.join(&installed.package.name);
+                        (installed.package, vec![kegs])
+                    }),
+                )))
+            }
+            AdapterRequest::ListRequested(_) => {
+                let raw = self.source.list_requested_formulae()?;
+                Ok(AdapterResponse::RequestedPackages(
+                    parse_requested_formulae(&raw)?,
+                ))
+            }
+            AdapterRequest::Rollback(rollback_request) => {
+                validate_homebrew_formula_target(
+                    rollback_request.package.name.as_str(),
+                    None,
+                    ManagerAction::Rollback,
+                )?;
+                let version = rollback_request.version.trim();
+                if version.is_empty() || version.contains('/') || version.starts_with('.') {
+                    return Err(CoreError {
+                        manager: Some(ManagerId::HomebrewFormula),
+                        task: None,
+                        action: Some(ManagerAction::Rollback),
+                        kind: CoreErrorKind::InvalidInput,
+                        message: format!("invalid rollback version '{version}'"),
+                    });
+                }
+                let _ = self
+                    .source
+                    .switch_formula_keg(&rollback_request.package.name, version)?;
+                Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
+                    package: rollback_request.package,
+                    package_identifier: None,
+                    action: ManagerAction::Rollback,
+                    before_version: None,
+                    after_version: Some(version.to_string()),
+                    disposal: None,
                 }))
             }
-            AdapterRequest::ConfigurePackageDetail(_) => unreachable!(
-                "unsupported package detail request should have been rejected by ensure_request_supported"
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+                operation:
+                    PackageDetailOperation::AddChild {
+                        kind: PackageDetailChildKind::Tap,
+                        value: tap,
+                    }
+                    | PackageDetailOperation::AddTap { tap, url: None },
+                ..
+            }) => self.add_tap(tap, None),
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+                operation:
+                    PackageDetailOperation::AddTap {
+                        tap,
+                        url: Some(url),
+                    },
+                ..
+            }) => self.add_tap(tap, Some(url)),
+            AdapterRequest::ConfigurePackageDetail(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewFormula),
+                task: Some(TaskType::Configure),
+                action: Some(ManagerAction::Configure),
+                kind: CoreErrorKind::InvalidInput,
+                message: "homebrew package detail configuration only supports adding a tap"
+                    .to_string(),
+            }),
+            AdapterRequest::SelfVersionCheck(_) => unreachable!(
+                "unsupported self version check should have been rejected by ensure_request_supported"
             ),
         }
     }
@@ -343,6 +594,16 @@ pub fn homebrew_list_outdated_request(task_id: Option<TaskId>) -> ProcessSpawnRe
     )
 }
 
+pub fn homebrew_refresh_outdated_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::Refresh,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["outdated", "--formula", "--json=v2"]),
+        LIST_TIMEOUT,
+    )
+}
+
 pub fn homebrew_catalog_formulae_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
     homebrew_request(
         task_id,
@@ -379,12 +640,47 @@ pub fn homebrew_search_local_request(
     homebrew_search_formulae_request(task_id, query)
 }
 
-pub fn homebrew_install_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+pub fn homebrew_install_request(
+    task_id: Option<TaskId>,
+    name: &str,
+    options: &[String],
+) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Install,
+        ManagerAction::Install,
+        CommandSpec::new(HOMEBREW_COMMAND)
+            .arg("install")
+            .args(options.iter().cloned())
+            .arg(name),
+        LIFECYCLE_TIMEOUT,
+    )
+    .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
+    .progress_parser(homebrew_download_progress)
+}
+
+pub fn homebrew_list_taps_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Install,
+        ManagerAction::Install,
+        CommandSpec::new(HOMEBREW_COMMAND).arg("tap"),
+        LIST_TIMEOUT,
+    )
+}
+
+pub fn homebrew_tap_request(
+    task_id: Option<TaskId>,
+    tap: &str,
+    url: Option<&str>,
+) -> ProcessSpawnRequest {
     homebrew_request(
         task_id,
         TaskType::Install,
         ManagerAction::Install,
-        CommandSpec::new(HOMEBREW_COMMAND).args(["install", name]),
+        CommandSpec::new(HOMEBREW_COMMAND)
+            .args(["tap", tap])
+            .args(url),
         LIFECYCLE_TIMEOUT,
     )
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
@@ -401,13 +697,28 @@ pub fn homebrew_uninstall_request(task_id: Option<TaskId>, name: &str) -> Proces
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
 }
 
-pub fn homebrew_upgrade_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
-    let command = if name == "__self__" {
-        CommandSpec::new(HOMEBREW_COMMAND).arg("update")
-    } else if name == "__all__" {
-        CommandSpec::new(HOMEBREW_COMMAND).arg("upgrade")
-    } else {
-        CommandSpec::new(HOMEBREW_COMMAND).args(["upgrade", name])
+pub fn homebrew_update_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Upgrade,
+        ManagerAction::Upgrade,
+        CommandSpec::new(HOMEBREW_COMMAND).arg("update"),
+        LIFECYCLE_TIMEOUT,
+    )
+    .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
+}
+
+pub fn homebrew_upgrade_request(
+    task_id: Option<TaskId>,
+    name: Option<&str>,
+    options: &[String],
+) -> ProcessSpawnRequest {
+    let command = match name {
+        Some(name) => CommandSpec::new(HOMEBREW_COMMAND)
+            .arg("upgrade")
+            .args(options.iter().cloned())
+            .arg(name),
+        None => CommandSpec::new(HOMEBREW_COMMAND).arg("upgrade"),
     };
     homebrew_request(
         task_id,
@@ -417,6 +728,16 @@ pub fn homebrew_upgrade_request(task_id: Option<TaskId>, name: &str) -> ProcessS
         LIFECYCLE_TIMEOUT,
     )
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
+    .progress_parser(homebrew_download_progress)
+}
+
+/// Homebrew downloads through curl's `--progress-bar`, which redraws a
+/// `#####     45.2%` bar.
+pub(crate) fn homebrew_download_progress(line: &str) -> Option<u8> {
+    if !line.starts_with('#') {
+        return None;
+    }
+    last_percent(line)
 }
 
 pub fn homebrew_cleanup_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
@@ -431,11 +752,7 @@ pub fn homebrew_cleanup_request(task_id: Option<TaskId>, name: &str) -> ProcessS
 }
 
 fn split_upgrade_target(name: &str) -> (&str, bool) {
-    if let Some(stripped) = name.strip_suffix(HOMEBREW_CLEANUP_MARKER) {
-        (stripped, true)
-    } else {
-        (name, false)
-    }
+    crate::upgrade_target::decode_legacy_upgrade_target(name)
 }
 
 fn validate_homebrew_formula_target(
@@ -458,11 +775,149 @@ fn validate_homebrew_formula_target(
     Ok(())
 }
 
+/// What a formula install names: a formula from core or an installed tap, a
+/// tap-qualified `user/tap/formula`, or a direct `https://…/formula.rb` URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HomebrewFormulaInstallTarget {
+    Name(String),
+    TapQualified { tap: String, formula: String },
+    Url { url: String, formula: String },
+}
+
+impl HomebrewFormulaInstallTarget {
+    /// The name Homebrew lists the formula under once it is installed.
+    pub fn installed_name(&self) -> &str {
+        match self {
+            Self::Name(formula)
+            | Self::TapQualified { formula, .. }
+            | Self::Url { formula, .. } => formula,
+        }
+    }
+}
+
+pub fn parse_homebrew_formula_install_target(
+    name: &str,
+) -> AdapterResult<HomebrewFormulaInstallTarget> {
+    let name = name.trim();
+    let invalid = |message: String| CoreError {
+        manager: Some(ManagerId::HomebrewFormula),
+        task: None,
+        action: Some(ManagerAction::Install),
+        kind: CoreErrorKind::InvalidInput,
+        message,
+    };
+
+    if name.contains("://") {
+        let Some(path) = name.strip_prefix("https://") else {
+            return Err(invalid(format!(
+                "homebrew formula URL '{name}' must use https"
+            )));
+        };
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        let formula = file_name.strip_suffix(".rb").unwrap_or_default();
+        if !path.contains('/')
+            || name
+                .chars()
+                .any(|ch| ch.is_control() || ch == '"' || ch == '\\')
+            || !is_valid_homebrew_formula_segment(formula)
+        {
+            return Err(invalid(format!(
+                "homebrew formula URL '{name}' must point to a '<formula>.rb' file"
+            )));
+        }
+        return Ok(HomebrewFormulaInstallTarget::Url {
+            url: name.to_string(),
+            formula: formula.to_string(),
+        });
+    }
+
+    let segments: Vec<&str> = name.split('/').collect();
+    match segments.as_slice() {
+        [formula] if is_valid_homebrew_formula_segment(formula) => {
+            Ok(HomebrewFormulaInstallTarget::Name(formula.to_string()))
+        }
+        [user, repo, formula]
+            if is_valid_homebrew_tap_segment(user)
+                && is_valid_homebrew_tap_segment(repo)
+                && is_valid_homebrew_formula_segment(formula) =>
+        {
+            Ok(HomebrewFormulaInstallTarget::TapQualified {
+                tap: format!("{user}/{repo}"),
+                formula: formula.to_string(),
+            })
+        }
+        _ => Err(invalid(format!(
+            "'{name}' is not a homebrew formula; expected 'formula', 'user/tap/formula', or an https formula URL"
+        ))),
+    }
+}
+
+fn is_valid_homebrew_formula_segment(segment: &str) -> bool {
+    segment
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric())
+        && segment
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '@' | '+' | '-' | '.' | '_'))
+}
+
+fn is_valid_homebrew_tap_segment(segment: &str) -> bool {
+    segment
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric())
+        && segment
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '.' | '_'))
+}
+
+fn validate_homebrew_tap_name(tap: &str) -> AdapterResult<()> {
+    match tap.split('/').collect::<Vec<_>>().as_slice() {
+        [user, repo]
+            if is_valid_homebrew_tap_segment(user) && is_valid_homebrew_tap_segment(repo) =>
+        {
+            Ok(())
+        }
+        _ => Err(CoreError {
+            manager: Some(ManagerId::HomebrewFormula),
+            task: Some(TaskType::Configure),
+            action: Some(ManagerAction::Configure),
+            kind: CoreErrorKind::InvalidInput,
+            message: format!("'{tap}' is not a homebrew tap; expected 'user/repo'"),
+        }),
+    }
+}
+
+/// Tap remotes must be URLs git can clone; anything that could be read as a
+/// `brew tap` flag is rejected.
+fn validate_homebrew_tap_url(url: &str) -> AdapterResult<()> {
+    let looks_like_remote = ["https://", "http://", "ssh://", "git://", "file://", "git@"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix));
+    if looks_like_remote && !url.chars().any(char::is_whitespace) {
+        return Ok(());
+    }
+    Err(CoreError {
+        manager: Some(ManagerId::HomebrewFormula),
+        task: Some(TaskType::Configure),
+        action: Some(ManagerAction::Configure),
+        kind: CoreErrorKind::InvalidInput,
+        message: format!("'{url}' is not a git remote URL for a homebrew tap"),
+    })
+}
+
+fn homebrew_tap_is_installed(tap_output: &str, tap: &str) -> bool {
+    tap_output
+        .lines()
+        .any(|line| line.trim().eq_ignore_ascii_case(tap))
+}
+
 fn validate_homebrew_formula_upgrade_target(
-    package_name: &str,
+    package_name: Option<&str>,
     version: Option<&str>,
 ) -> AdapterResult<()> {
-    if package_name != "__all__" && package_name != "__self__" {
+    if let Some(package_name) = package_name {
         let (target_name, _) = split_upgrade_target(package_name);
         validate_homebrew_formula_target(target_name, version, ManagerAction::Upgrade)?;
     } else if version.is_some() {
@@ -560,7 +1015,97 @@ pub fn homebrew_unpin_request(task_id: Option<TaskId>, name: &str) -> ProcessSpa
     )
 }
 
-fn homebrew_request(
+/// `brew --cellar` or `brew --prefix`, printing the Homebrew root a rollback
+/// resolves kegs and opt links against.
+pub fn homebrew_location_request(task_id: Option<TaskId>, flag: &str) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Upgrade,
+        ManagerAction::Rollback,
+        CommandSpec::new(HOMEBREW_COMMAND).arg(flag),
+        DETECT_TIMEOUT,
+    )
+}
+
+pub fn homebrew_cellar_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::MeasurePackageSizes,
+        CommandSpec::new(HOMEBREW_COMMAND).arg("--cellar"),
+        DETECT_TIMEOUT,
+    )
+}
+
+pub fn homebrew_unlink_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Upgrade,
+        ManagerAction::Rollback,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["unlink", name]),
+        PIN_TIMEOUT,
+    )
+}
+
+/// `brew link` links the keg `opt/<name>` points at, so relinking after the
+/// opt link moves switches the active version.
+pub fn homebrew_link_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Upgrade,
+        ManagerAction::Rollback,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["link", "--overwrite", name]),
+        PIN_TIMEOUT,
+    )
+}
+
+pub fn homebrew_list_files_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListFiles,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["list", "--formula", "--verbose", name]),
+        LIST_TIMEOUT,
+    )
+}
+
+/// Recursive dependencies of every installed formula, one `name: deps...`
+/// line per formula; `brew deps` has no JSON output.
+pub fn homebrew_list_dependencies_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListDependencies,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["deps", "--installed", "--formula"]),
+        LIST_TIMEOUT,
+    )
+}
+
+/// Installed formulae with their install receipts, read for
+/// `installed_on_request`.
+pub fn homebrew_list_requested_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListRequested,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["info", "--formula", "--json=v2", "--installed"]),
+        LIST_TIMEOUT,
+    )
+}
+
+/// Metadata for one formula, installed or not; `--json=v2` carries the
+/// description, homepage, license, and direct dependencies.
+pub fn homebrew_info_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PackageInfo,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["info", "--formula", "--json=v2", name]),
+        SEARCH_TIMEOUT,
+    )
+}
+
+pub(crate) fn homebrew_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
     action: ManagerAction,
@@ -706,6 +1251,7 @@ fn parse_installed_formulae(output: &str) -> AdapterResult<Vec<InstalledPackage>
             installed_version: Some(installed_version),
             pinned: formula.pinned,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -713,18 +1259,103 @@ fn parse_installed_formulae(output: &str) -> AdapterResult<Vec<InstalledPackage>
     Ok(parsed)
 }
 
-fn parse_outdated_formulae(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
+/// `brew list --verbose` prints one absolute path per line.
+fn parse_formula_files(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// `brew deps --installed` prints `formula: dep dep ...`; formulae without
+/// dependencies print `formula:` and contribute no edges.
+fn parse_formula_dependencies(output: &str) -> Vec<PackageDependency> {
+    let mut edges = Vec::new();
+    for line in output.lines() {
+        let Some((formula, dependencies)) = line.split_once(':') else {
+            continue;
+        };
+        let formula = formula.trim();
+        if formula.is_empty() || formula.contains(char::is_whitespace) {
+            continue;
+        }
+        for dependency in dependencies.split_whitespace() {
+            edges.push(PackageDependency {
+                package: PackageRef {
+                    manager: ManagerId::HomebrewFormula,
+                    name: formula.to_string(),
+                },
+                dependency: dependency.to_string(),
+            });
+        }
+    }
+    edges
+}
+
+/// Formulae with any keg installed on request. Receipts written before
+/// Homebrew tracked the flag omit it; those count as requested so they are
+/// never reported as orphans.
+fn parse_requested_formulae(output: &str) -> AdapterResult<Vec<String>> {
     let trimmed = output.trim();
     if trimmed.is_empty() || trimmed == "{}" {
         return Ok(Vec::new());
     }
 
-    let payload: HomebrewFormulaOutdatedEnvelope =
+    let payload: HomebrewFormulaInstalledEnvelope =
         serde_json::from_str(trimmed).map_err(|error| {
-            parse_error(&format!("invalid Homebrew formula outdated JSON: {error}"))
+            parse_error(&format!("invalid Homebrew formula installed JSON: {error}"))
         })?;
 
-    let mut parsed = Vec::new();
+    Ok(payload
+        .formulae
+        .into_iter()
+        .filter(|formula| {
+            formula
+                .installed
+                .iter()
+                .any(|installed| installed.installed_on_request != Some(false))
+        })
+        .filter_map(|formula| normalize_optional_text(Some(formula.name)))
+        .collect())
+}
+
+/// Homebrew does not report keg sizes in `brew info --json=v2`, so
+/// `installed_size_bytes` stays unset.
+fn parse_formula_info(output: &str) -> AdapterResult<PackageMetadata> {
+    let payload: HomebrewFormulaInfoEnvelope = serde_json::from_str(output.trim())
+        .map_err(|error| parse_error(&format!("invalid Homebrew formula info JSON: {error}")))?;
+    let Some(formula) = payload.formulae.into_iter().next() else {
+        return Err(parse_error("Homebrew formula info JSON listed no formula"));
+    };
+
+    Ok(PackageMetadata {
+        description: normalize_optional_text(formula.desc),
+        homepage: normalize_optional_text(formula.homepage),
+        license: normalize_optional_text(formula.license),
+        installed_size_bytes: None,
+        dependencies: formula
+            .dependencies
+            .into_iter()
+            .filter_map(|dependency| normalize_optional_text(Some(dependency)))
+            .collect(),
+        aliases: Vec::new(),
+    })
+}
+
+fn parse_outdated_formulae(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() || trimmed == "{}" {
+        return Ok(Vec::new());
+    }
+
+    let payload: HomebrewFormulaOutdatedEnvelope =
+        serde_json::from_str(trimmed).map_err(|error| {
+            parse_error(&format!("invalid Homebrew formula outdated JSON: {error}"))
+        })?;
+
+    let mut parsed = Vec::new();
     for formula in payload.formulae {
         let Some(name) = normalize_optional_text(Some(formula.name)) else {
             continue;
@@ -753,6 +1384,7 @@ fn parse_outdated_formulae(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
             pinned: formula.pinned,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -814,6 +1446,7 @@ fn parse_search_formulae(
                 source_manager: ManagerId::HomebrewFormula,
                 originating_query: query.text.clone(),
                 cached_at: query.issued_at,
+                popularity: Default::default(),
             });
         }
     }
@@ -878,6 +1511,26 @@ struct HomebrewFormulaInstalledEntry {
 struct HomebrewFormulaInstalledVersion {
     #[serde(default)]
     version: Option<String>,
+    #[serde(default)]
+    installed_on_request: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HomebrewFormulaInfoEnvelope {
+    #[serde(default)]
+    formulae: Vec<HomebrewFormulaInfoEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HomebrewFormulaInfoEntry {
+    #[serde(default)]
+    desc: Option<String>,
+    #[serde(default)]
+    homepage: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -990,11 +1643,13 @@ fn cleanup_rustup_artifacts() -> AdapterResult<String> {
 }
 
 fn cleanup_mise_artifacts() -> AdapterResult<String> {
-    let home = std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("~"));
-    let state_dir = home.join(".local/share/mise");
-    let cache_dir = home.join(".cache/mise");
+    let base_dirs = ToolBaseDirs::from_env();
+    let state_dir = base_dirs
+        .mise_data_dir
+        .unwrap_or_else(|| PathBuf::from("~/.local/share/mise"));
+    let cache_dir = base_dirs
+        .mise_cache_dir
+        .unwrap_or_else(|| PathBuf::from("~/.cache/mise"));
 
     let removed_state = remove_directory_if_exists(state_dir.as_path())?;
     let removed_cache = remove_directory_if_exists(cache_dir.as_path())?;
@@ -1051,25 +1706,26 @@ fn remove_file_if_exists(path: &std::path::Path) -> AdapterResult<bool> {
 }
 
 fn resolve_cargo_home() -> PathBuf {
-    if let Some(raw) = std::env::var_os("CARGO_HOME").filter(|value| !value.is_empty()) {
-        return PathBuf::from(raw);
-    }
-    std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .map(|home| home.join(".cargo"))
+    ToolBaseDirs::from_env()
+        .cargo_home
         .unwrap_or_else(|| PathBuf::from("~/.cargo"))
 }
 
 fn resolve_rustup_home() -> PathBuf {
-    if let Some(raw) = std::env::var_os("RUSTUP_HOME").filter(|value| !value.is_empty()) {
-        return PathBuf::from(raw);
-    }
-    std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .map(|home| home.join(".rustup"))
+    ToolBaseDirs::from_env()
+        .rustup_home
         .unwrap_or_else(|| PathBuf::from("~/.rustup"))
 }
 
+fn parse_homebrew_cellar(output: &str) -> AdapterResult<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .ok_or_else(|| parse_error("brew --cellar did not print a Cellar path"))
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::HomebrewFormula),
@@ -1083,29 +1739,59 @@ fn parse_error(message: &str) -> CoreError {
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
-    use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
     use std::time::UNIX_EPOCH;
 
+    use crate::adapters::golden;
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
         ListOutdatedRequest, ManagerAdapter, SearchRequest,
     };
-    use crate::models::{CoreError, CoreErrorKind, ManagerAction, SearchQuery, TaskId, TaskType};
+    use crate::models::{
+        CoreError, CoreErrorKind, ManagerAction, SearchQuery, ServiceAction, TaskId, TaskType,
+    };
+    use crate::upgrade_target::UpgradeTarget;
 
     use super::{
-        HomebrewAdapter, HomebrewDetectOutput, HomebrewSource, homebrew_catalog_formulae_request,
-        homebrew_cleanup_request, homebrew_detect_request, homebrew_install_request,
-        homebrew_list_installed_request, homebrew_list_outdated_request, homebrew_pin_request,
+        HomebrewAdapter, HomebrewDetectOutput, HomebrewFormulaInstallTarget, HomebrewSource,
+        homebrew_catalog_formulae_request, homebrew_cleanup_request, homebrew_detect_request,
+        homebrew_download_progress, homebrew_info_request, homebrew_install_request,
+        homebrew_list_dependencies_request, homebrew_list_files_request,
+        homebrew_list_installed_request, homebrew_list_outdated_request,
+        homebrew_list_requested_request, homebrew_pin_request, homebrew_refresh_outdated_request,
         homebrew_search_formulae_request, homebrew_search_local_request,
-        homebrew_uninstall_request, homebrew_unpin_request, homebrew_upgrade_request,
-        parse_homebrew_version, parse_installed_formulae, parse_outdated_formulae,
-        parse_search_formulae,
+        homebrew_uninstall_request, homebrew_unpin_request, homebrew_update_request,
+        homebrew_upgrade_request, parse_homebrew_formula_install_target, parse_homebrew_version,
+        parse_installed_formulae, parse_outdated_formulae, parse_search_formulae,
     };
 
+    #[test]
+    fn download_progress_reads_curl_progress_bars_only() {
+        assert_eq!(
+            homebrew_download_progress("################                  23.4%"),
+            Some(23)
+        );
+        assert_eq!(
+            homebrew_download_progress("==> Downloading https://ghcr.io/v2/homebrew/core/wget"),
+            None
+        );
+        assert_eq!(homebrew_download_progress("Already downloaded: 100%"), None);
+        assert!(
+            homebrew_upgrade_request(None, Some("wget"), &[])
+                .progress_parser
+                .is_some()
+        );
+    }
+
     const INSTALLED_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/installed.json");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/outdated.json");
     const SEARCH_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/search_local.txt");
+    const SERVICES_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/services_list.json");
+    const CLEANUP_DRY_RUN_FIXTURE: &str =
+        include_str!("../../tests/fixtures/homebrew/cleanup_dry_run.txt");
+    const FORMULA_INFO_FIXTURE: &str =
+        include_str!("../../tests/fixtures/homebrew/formula_info.json");
     const SEARCH_DESC_FIXTURE: &str = "==> Formulae\nripgrep: Recursively search directories for a regex pattern\nripgrep-all: Search all the things\n==> Casks\nripper: should be ignored\n";
 
     #[test]
@@ -1171,6 +1857,44 @@ mod tests {
         assert!(parsed[1].pinned);
     }
 
+    /// `(fixture version directory, version reported by brew)`.
+    const GOLDEN_CASES: &[(&str, &str)] = &[("brew-4", "4.4.31"), ("brew-5", "5.0.14")];
+
+    #[test]
+    fn golden_parses_across_brew_versions() {
+        golden::assert_cases_cover_fixtures("homebrew", GOLDEN_CASES);
+        for (case, version) in GOLDEN_CASES {
+            for file in ["version.txt", "config.txt"] {
+                let output = golden::fixture("homebrew", case, file);
+                assert_eq!(
+                    parse_homebrew_version(&output).as_deref(),
+                    Some(*version),
+                    "{case}/{file}"
+                );
+            }
+
+            let installed =
+                parse_installed_formulae(&golden::fixture("homebrew", case, "installed.json"))
+                    .unwrap();
+            golden::assert_golden(
+                "homebrew",
+                case,
+                "installed.golden",
+                &golden::render_installed(&installed),
+            );
+
+            let outdated =
+                parse_outdated_formulae(&golden::fixture("homebrew", case, "outdated.json"))
+                    .unwrap();
+            golden::assert_golden(
+                "homebrew",
+                case,
+                "outdated.golden",
+                &golden::render_outdated(&outdated),
+            );
+        }
+    }
+
     #[test]
     fn skips_outdated_entries_when_current_version_matches_installed_version() {
         let parsed = parse_outdated_formulae(OUTDATED_FIXTURE).unwrap();
@@ -1281,11 +2005,161 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
     }
 
+    #[test]
+    fn install_targets_accept_tap_qualified_names_and_formula_urls() {
+        assert_eq!(
+            parse_homebrew_formula_install_target("python@3.12").unwrap(),
+            HomebrewFormulaInstallTarget::Name("python@3.12".to_string())
+        );
+        assert_eq!(
+            parse_homebrew_formula_install_target("hashicorp/tap/terraform").unwrap(),
+            HomebrewFormulaInstallTarget::TapQualified {
+                tap: "hashicorp/tap".to_string(),
+                formula: "terraform".to_string(),
+            }
+        );
+        let url = "https://raw.githubusercontent.com/acme/homebrew-tools/main/Formula/widget.rb";
+        assert_eq!(
+            parse_homebrew_formula_install_target(url).unwrap(),
+            HomebrewFormulaInstallTarget::Url {
+                url: url.to_string(),
+                formula: "widget".to_string(),
+            }
+        );
+
+        for rejected in [
+            "hashicorp/tap",
+            "a/b/c/d",
+            "../tap/formula",
+            "user/tap/",
+            "git;rm",
+            "http://example.com/widget.rb",
+            "https://example.com/widget.tar.gz",
+            "file:///tmp/widget.rb",
+        ] {
+            let error = parse_homebrew_formula_install_target(rejected).unwrap_err();
+            assert_eq!(error.kind, CoreErrorKind::InvalidInput, "{rejected}");
+        }
+    }
+
+    #[test]
+    fn tap_qualified_install_adds_a_missing_tap_first() {
+        let source = FixtureSource::default();
+        let calls = source.mutation_calls.clone();
+        let adapter = HomebrewAdapter::new(source);
+        let install = |name: &str| {
+            adapter.execute(AdapterRequest::Install(crate::adapters::InstallRequest {
+                package: crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: name.to_string(),
+                },
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+            }))
+        };
+
+        let result = install("acme/tools/widget").unwrap();
+        let AdapterResponse::Mutation(mutation) = result else {
+            panic!("expected mutation response");
+        };
+        assert_eq!(mutation.package.name, "widget");
+        assert_eq!(
+            calls.lock().unwrap().clone(),
+            vec!["tap:acme/tools", "install:acme/tools/widget"]
+        );
+
+        calls.lock().unwrap().clear();
+        install("hashicorp/tap/terraform").unwrap();
+        assert_eq!(
+            calls.lock().unwrap().clone(),
+            vec!["install:hashicorp/tap/terraform"]
+        );
+    }
+
+    #[test]
+    fn tap_configuration_adds_only_missing_taps() {
+        let source = FixtureSource::default();
+        let calls = source.mutation_calls.clone();
+        let adapter = HomebrewAdapter::new(source);
+        let add_tap = |tap: &str| {
+            adapter.execute(AdapterRequest::ConfigurePackageDetail(
+                crate::adapters::manager::PackageDetailRequest {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    package: None,
+                    operation: crate::adapters::manager::PackageDetailOperation::AddChild {
+                        kind: crate::adapters::manager::PackageDetailChildKind::Tap,
+                        value: tap.to_string(),
+                    },
+                },
+            ))
+        };
+
+        add_tap("acme/tools").unwrap();
+        add_tap("hashicorp/tap").unwrap();
+        assert_eq!(calls.lock().unwrap().clone(), vec!["tap:acme/tools"]);
+
+        let error = add_tap("acme/tools/widget").unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn tap_configuration_passes_custom_remote_urls() {
+        let source = FixtureSource::default();
+        let calls = source.mutation_calls.clone();
+        let adapter = HomebrewAdapter::new(source);
+        let add_tap = |tap: &str, url: &str| {
+            adapter.execute(AdapterRequest::ConfigurePackageDetail(
+                crate::adapters::manager::PackageDetailRequest {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    package: None,
+                    operation: crate::adapters::manager::PackageDetailOperation::AddTap {
+                        tap: tap.to_string(),
+                        url: Some(url.to_string()),
+                    },
+                },
+            ))
+        };
+
+        add_tap(
+            "acme/private",
+            "https://git.example.com/acme/homebrew-private.git",
+        )
+        .unwrap();
+        assert_eq!(
+            calls.lock().unwrap().clone(),
+            vec!["tap:acme/private https://git.example.com/acme/homebrew-private.git"]
+        );
+
+        let error = add_tap("acme/other", "--force").unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn adapter_rejects_install_options_outside_allowlist() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let error = adapter
+            .execute(AdapterRequest::Install(crate::adapters::InstallRequest {
+                package: crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: "ripgrep".to_string(),
+                },
+                target_name: None,
+                version: None,
+                options: vec!["--cc=clang".to_string()],
+            }))
+            .unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+    }
+
     #[test]
     fn adapter_treats_already_installed_as_success_for_install() {
         let source = FixtureSource::with_install_error("Error: mas 1.0.0 is already installed");
@@ -1299,6 +2173,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -1318,6 +2193,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .unwrap();
@@ -1339,6 +2215,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .unwrap();
@@ -1360,6 +2237,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .expect_err("non-idempotent uninstall error should be returned");
@@ -1374,12 +2252,14 @@ mod tests {
 
         let result = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
                     manager: crate::models::ManagerId::HomebrewFormula,
                     name: "mise".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -1418,6 +2298,272 @@ mod tests {
         assert!(matches!(result, AdapterResponse::Mutation(_)));
     }
 
+    #[test]
+    fn adapter_rolls_back_to_the_requested_keg() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        let result = adapter
+            .execute(AdapterRequest::Rollback(crate::adapters::RollbackRequest {
+                package: crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: "git".to_string(),
+                },
+                version: "2.44.0".to_string(),
+            }))
+            .unwrap();
+        let AdapterResponse::Mutation(mutation) = result else {
+            panic!("expected mutation response");
+        };
+        assert_eq!(mutation.action, ManagerAction::Rollback);
+        assert_eq!(mutation.after_version.as_deref(), Some("2.44.0"));
+        assert_eq!(source.mutation_calls(), vec!["switch:git@2.44.0"]);
+
+        let error = adapter
+            .execute(AdapterRequest::Rollback(crate::adapters::RollbackRequest {
+                package: crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: "git".to_string(),
+                },
+                version: "../2.44.0".to_string(),
+            }))
+            .expect_err("path-like versions should be rejected");
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn adapter_lists_formula_files_up_to_the_limit() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let result = adapter
+            .execute(AdapterRequest::ListFiles(
+                crate::adapters::ListFilesRequest {
+                    package: crate::models::PackageRef {
+                        manager: crate::models::ManagerId::HomebrewFormula,
+                        name: "git".to_string(),
+                    },
+                    limit: Some(2),
+                },
+            ))
+            .unwrap();
+        let AdapterResponse::PackageFiles(files) = result else {
+            panic!("expected package files");
+        };
+        assert_eq!(
+            files.files,
+            vec![
+                "/opt/homebrew/Cellar/git/2.45.1/bin/git".to_string(),
+                "/opt/homebrew/Cellar/git/2.45.1/bin/git-shell".to_string(),
+            ]
+        );
+        assert_eq!(files.total, 3);
+        assert!(files.truncated());
+
+        let request = homebrew_list_files_request(None, "git");
+        assert_eq!(request.action, ManagerAction::ListFiles);
+        assert_eq!(
+            request.command.args,
+            vec!["list", "--formula", "--verbose", "git"]
+        );
+    }
+
+    #[test]
+    fn adapter_lists_the_installed_dependency_graph() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let result = adapter
+            .execute(AdapterRequest::ListDependencies(
+                crate::adapters::ListDependenciesRequest,
+            ))
+            .unwrap();
+        let AdapterResponse::Dependencies(edges) = result else {
+            panic!("expected dependencies");
+        };
+        let pairs: Vec<(&str, &str)> = edges
+            .iter()
+            .map(|edge| (edge.package.name.as_str(), edge.dependency.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("git", "gettext"),
+                ("git", "pcre2"),
+                ("wget", "libidn2"),
+                ("wget", "openssl@3"),
+            ]
+        );
+
+        let request = homebrew_list_dependencies_request(None);
+        assert_eq!(request.action, ManagerAction::ListDependencies);
+        assert_eq!(
+            request.command.args,
+            vec!["deps", "--installed", "--formula"]
+        );
+    }
+
+    #[test]
+    fn adapter_changes_service_state_and_reports_services() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        let result = adapter
+            .execute(AdapterRequest::SetServiceState(
+                crate::adapters::SetServiceStateRequest {
+                    service: "postgresql@16".to_string(),
+                    action: ServiceAction::Restart,
+                },
+            ))
+            .unwrap();
+        let AdapterResponse::Services(services) = result else {
+            panic!("expected services response");
+        };
+        assert_eq!(services.len(), 3);
+        assert_eq!(
+            source.mutation_calls(),
+            vec!["services:restart:postgresql@16"]
+        );
+
+        let error = adapter
+            .execute(AdapterRequest::SetServiceState(
+                crate::adapters::SetServiceStateRequest {
+                    service: "--all".to_string(),
+                    action: ServiceAction::Stop,
+                },
+            ))
+            .unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert_eq!(source.mutation_calls().len(), 1);
+    }
+
+    #[test]
+    fn adapter_previews_and_runs_cleanup() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        let preview = adapter
+            .execute(AdapterRequest::PreviewCleanup(
+                crate::adapters::PreviewCleanupRequest,
+            ))
+            .unwrap();
+        assert_eq!(
+            preview,
+            AdapterResponse::Cleanup(crate::models::CleanupReport {
+                bytes: 1_288_490_189
+            })
+        );
+        assert!(source.mutation_calls().is_empty());
+
+        let cleanup = adapter
+            .execute(AdapterRequest::Cleanup(crate::adapters::CleanupRequest))
+            .unwrap();
+        assert_eq!(
+            cleanup,
+            AdapterResponse::Cleanup(crate::models::CleanupReport { bytes: 327_680_000 })
+        );
+        assert_eq!(source.mutation_calls(), vec!["cleanup"]);
+    }
+
+    #[test]
+    fn adapter_measures_every_keg_of_installed_formulae() {
+        let cellar = std::env::temp_dir().join(format!("helm-cellar-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cellar);
+        std::fs::create_dir_all(cellar.join("openssl@3/3.3.0/lib")).unwrap();
+        std::fs::create_dir_all(cellar.join("openssl@3/3.3.1/lib")).unwrap();
+        std::fs::write(
+            cellar.join("openssl@3/3.3.0/lib/libssl.a"),
+            vec![0_u8; 1024],
+        )
+        .unwrap();
+        std::fs::write(
+            cellar.join("openssl@3/3.3.1/lib/libssl.a"),
+            vec![0_u8; 2048],
+        )
+        .unwrap();
+
+        let adapter = HomebrewAdapter::new(FixtureSource {
+            cellar: format!("{}\n", cellar.display()),
+            ..FixtureSource::default()
+        });
+        let response = adapter
+            .execute(AdapterRequest::MeasurePackageSizes(
+                crate::adapters::MeasurePackageSizesRequest,
+            ))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&cellar);
+
+        // Formulae without a keg directory are left out.
+        assert_eq!(
+            response,
+            AdapterResponse::PackageSizes(vec![crate::models::InstalledPackageSize {
+                package: crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: "openssl@3".to_string(),
+                },
+                bytes: 3072,
+            }])
+        );
+    }
+
+    #[test]
+    fn adapter_reports_formula_metadata() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let result = adapter
+            .execute(AdapterRequest::PackageInfo(
+                crate::adapters::PackageInfoRequest {
+                    package: crate::models::PackageRef {
+                        manager: crate::models::ManagerId::HomebrewFormula,
+                        name: "wget".to_string(),
+                    },
+                },
+            ))
+            .unwrap();
+        let AdapterResponse::PackageInfo(metadata) = result else {
+            panic!("expected package info");
+        };
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Internet file retriever")
+        );
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://www.gnu.org/software/wget/")
+        );
+        assert_eq!(metadata.license.as_deref(), Some("GPL-3.0-or-later"));
+        assert_eq!(metadata.installed_size_bytes, None);
+        assert_eq!(metadata.dependencies, vec!["libidn2", "openssl@3"]);
+
+        let request = homebrew_info_request(None, "wget");
+        assert_eq!(request.action, ManagerAction::PackageInfo);
+        assert_eq!(
+            request.command.args,
+            vec!["info", "--formula", "--json=v2", "wget"]
+        );
+    }
+
+    #[test]
+    fn adapter_lists_formulae_installed_on_request() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let result = adapter
+            .execute(AdapterRequest::ListRequested(
+                crate::adapters::ListRequestedRequest,
+            ))
+            .unwrap();
+        let AdapterResponse::RequestedPackages(names) = result else {
+            panic!("expected requested packages");
+        };
+        // Receipts without the flag count as requested.
+        assert_eq!(names, vec!["git", "legacy"]);
+
+        let request = homebrew_list_requested_request(None);
+        assert_eq!(request.action, ManagerAction::ListRequested);
+        assert_eq!(
+            request.command.args,
+            vec!["info", "--formula", "--json=v2", "--installed"]
+        );
+    }
+
     #[test]
     fn detect_command_plan_uses_structured_homebrew_args() {
         let request = homebrew_detect_request(Some(TaskId(11)));
@@ -1456,6 +2602,12 @@ mod tests {
         );
         assert_eq!(outdated.action, ManagerAction::ListOutdated);
         assert_eq!(outdated.task_type, TaskType::Refresh);
+
+        // Explicit refreshes run the same listing under the Refresh action so
+        // the Homebrew env policy lets brew auto-update first.
+        let refresh = homebrew_refresh_outdated_request(None);
+        assert_eq!(refresh.command.args, outdated.command.args);
+        assert_eq!(refresh.action, ManagerAction::Refresh);
     }
 
     #[test]
@@ -1502,18 +2654,33 @@ mod tests {
 
     #[test]
     fn upgrade_command_plan_is_structured_for_self_and_formula_targets() {
-        let self_update = homebrew_upgrade_request(None, "__self__");
+        let self_update = homebrew_update_request(None);
         assert_eq!(self_update.command.args, vec!["update".to_string()]);
         assert_eq!(self_update.action, ManagerAction::Upgrade);
         assert_eq!(self_update.task_type, TaskType::Upgrade);
 
-        let formula_upgrade = homebrew_upgrade_request(Some(TaskId(7)), "mise");
+        let formula_upgrade = homebrew_upgrade_request(Some(TaskId(7)), Some("mise"), &[]);
         assert_eq!(
             formula_upgrade.command.args,
             vec!["upgrade".to_string(), "mise".to_string()]
         );
         assert_eq!(formula_upgrade.task_id, Some(TaskId(7)));
 
+        let options = vec!["--build-from-source".to_string()];
+        let source_upgrade = homebrew_upgrade_request(None, Some("mise"), &options);
+        assert_eq!(
+            source_upgrade.command.args,
+            vec!["upgrade", "--build-from-source", "mise"]
+        );
+        let all_upgrade = homebrew_upgrade_request(None, None, &options);
+        assert_eq!(all_upgrade.command.args, vec!["upgrade"]);
+
+        let head_install = homebrew_install_request(None, "neovim", &["--HEAD".to_string()]);
+        assert_eq!(
+            head_install.command.args,
+            vec!["install", "--HEAD", "neovim"]
+        );
+
         let cleanup = homebrew_cleanup_request(None, "sevenzip");
         assert_eq!(
             cleanup.command.args,
@@ -1525,7 +2692,7 @@ mod tests {
 
     #[test]
     fn lifecycle_command_plans_include_extended_hard_and_idle_timeouts() {
-        let install = homebrew_install_request(None, "rustup");
+        let install = homebrew_install_request(None, "rustup", &[]);
         assert_eq!(install.timeout, Some(super::LIFECYCLE_TIMEOUT));
         assert_eq!(install.idle_timeout, Some(super::LIFECYCLE_IDLE_TIMEOUT));
 
@@ -1533,7 +2700,7 @@ mod tests {
         assert_eq!(uninstall.timeout, Some(super::LIFECYCLE_TIMEOUT));
         assert_eq!(uninstall.idle_timeout, Some(super::LIFECYCLE_IDLE_TIMEOUT));
 
-        let upgrade = homebrew_upgrade_request(None, "rustup");
+        let upgrade = homebrew_upgrade_request(None, Some("rustup"), &[]);
         assert_eq!(upgrade.timeout, Some(super::LIFECYCLE_TIMEOUT));
         assert_eq!(upgrade.idle_timeout, Some(super::LIFECYCLE_IDLE_TIMEOUT));
 
@@ -1560,12 +2727,14 @@ mod tests {
 
         let result = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
                     manager: crate::models::ManagerId::HomebrewFormula,
                     name: format!("sevenzip{}", super::HOMEBREW_CLEANUP_MARKER),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         match result {
@@ -1576,6 +2745,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn adapter_upgrade_keeps_versioned_formula_name_with_structured_cleanup() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source);
+
+        let result = adapter
+            .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
+                    manager: crate::models::ManagerId::HomebrewFormula,
+                    name: "openssl@1.1".to_string(),
+                }),
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: true,
+            }))
+            .unwrap();
+        match result {
+            AdapterResponse::Mutation(mutation) => {
+                assert_eq!(mutation.package.name, "openssl@1.1");
+            }
+            _ => panic!("expected mutation response"),
+        }
+    }
+
     #[test]
     fn split_upgrade_target_plain_name() {
         let (name, cleanup) = super::split_upgrade_target("sevenzip");
@@ -1605,6 +2799,84 @@ mod tests {
         assert!(cleanup);
     }
 
+    #[test]
+    fn adapter_self_update_then_all_runs_update_before_upgrade() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        let response = adapter
+            .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
+                target: UpgradeTarget::SelfUpdateThenAll,
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            }))
+            .expect("update then upgrade should succeed");
+
+        let AdapterResponse::Mutation(result) = response else {
+            panic!("expected mutation response");
+        };
+        assert_eq!(result.package.name, "__self__");
+        assert_eq!(source.mutation_calls(), vec!["update", "upgrade:all"]);
+    }
+
+    #[test]
+    fn adapter_self_update_then_all_skips_upgrade_when_update_fails() {
+        let source = FixtureSource::with_update_error("fatal: could not read from remote");
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        let error = adapter
+            .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
+                target: UpgradeTarget::SelfUpdateThenAll,
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            }))
+            .expect_err("failed update should abort the flow");
+
+        assert_eq!(error.kind, CoreErrorKind::ProcessFailure);
+        assert_eq!(source.mutation_calls(), vec!["update"]);
+    }
+
+    #[test]
+    fn adapter_self_update_and_all_targets_run_a_single_command() {
+        let source = FixtureSource::default();
+        let adapter = HomebrewAdapter::new(source.clone());
+
+        for target in [UpgradeTarget::SelfUpdate, UpgradeTarget::All] {
+            adapter
+                .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
+                    target,
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }))
+                .expect("manager-wide upgrade should succeed");
+        }
+
+        assert_eq!(source.mutation_calls(), vec!["update", "upgrade:all"]);
+    }
+
+    #[test]
+    fn adapter_rejects_os_update_target() {
+        let adapter = HomebrewAdapter::new(FixtureSource::default());
+
+        let error = adapter
+            .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
+                target: UpgradeTarget::OsUpdatesConfirmed,
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            }))
+            .expect_err("os update target is not a homebrew target");
+
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+    }
+
     #[test]
     fn adapter_upgrade_fails_when_formula_still_outdated_after_upgrade() {
         let source = FixtureSource::with_outdated_output(
@@ -1614,12 +2886,14 @@ mod tests {
 
         let error = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
                     manager: crate::models::ManagerId::HomebrewFormula,
                     name: "gdu".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap_err();
 
@@ -1649,7 +2923,10 @@ mod tests {
         detect_calls: Arc<AtomicUsize>,
         install_error: Option<String>,
         uninstall_error: Option<String>,
+        update_error: Option<String>,
         outdated_output: String,
+        cellar: String,
+        mutation_calls: Arc<Mutex<Vec<String>>>,
     }
 
     impl FixtureSource {
@@ -1658,7 +2935,10 @@ mod tests {
                 detect_calls: Arc::new(AtomicUsize::new(0)),
                 install_error: Some(message.to_string()),
                 uninstall_error: None,
+                update_error: None,
                 outdated_output: OUTDATED_FIXTURE.to_string(),
+                cellar: "/nonexistent/helm-cellar\n".to_string(),
+                mutation_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -1667,16 +2947,33 @@ mod tests {
                 detect_calls: Arc::new(AtomicUsize::new(0)),
                 install_error: None,
                 uninstall_error: Some(message.to_string()),
+                update_error: None,
                 outdated_output: OUTDATED_FIXTURE.to_string(),
+                cellar: "/nonexistent/helm-cellar\n".to_string(),
+                mutation_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
+        fn with_update_error(message: &str) -> Self {
+            Self {
+                update_error: Some(message.to_string()),
+                ..Self::default()
+            }
+        }
+
+        fn mutation_calls(&self) -> Vec<String> {
+            self.mutation_calls.lock().unwrap().clone()
+        }
+
         fn with_outdated_output(output: &str) -> Self {
             Self {
                 detect_calls: Arc::new(AtomicUsize::new(0)),
                 install_error: None,
                 uninstall_error: None,
+                update_error: None,
                 outdated_output: output.to_string(),
+                cellar: "/nonexistent/helm-cellar\n".to_string(),
+                mutation_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
     }
@@ -1687,7 +2984,10 @@ mod tests {
                 detect_calls: Arc::new(AtomicUsize::new(0)),
                 install_error: None,
                 uninstall_error: None,
+                update_error: None,
                 outdated_output: OUTDATED_FIXTURE.to_string(),
+                cellar: "/nonexistent/helm-cellar\n".to_string(),
+                mutation_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
     }
@@ -1717,7 +3017,11 @@ mod tests {
             }
         }
 
-        fn install_formula(&self, _name: &str) -> AdapterResult<String> {
+        fn install_formula(&self, name: &str, _options: &[String]) -> AdapterResult<String> {
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push(format!("install:{name}"));
             if let Some(message) = &self.install_error {
                 return Err(CoreError {
                     manager: Some(crate::models::ManagerId::HomebrewFormula),
@@ -1743,7 +3047,32 @@ mod tests {
             Ok(String::new())
         }
 
-        fn upgrade_formula(&self, _name: Option<&str>) -> AdapterResult<String> {
+        fn update_homebrew(&self) -> AdapterResult<String> {
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push("update".to_string());
+            if let Some(message) = &self.update_error {
+                return Err(CoreError {
+                    manager: Some(crate::models::ManagerId::HomebrewFormula),
+                    task: Some(TaskType::Upgrade),
+                    action: Some(ManagerAction::Upgrade),
+                    kind: CoreErrorKind::ProcessFailure,
+                    message: message.clone(),
+                });
+            }
+            Ok(String::new())
+        }
+
+        fn upgrade_formula(
+            &self,
+            name: Option<&str>,
+            _options: &[String],
+        ) -> AdapterResult<String> {
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push(format!("upgrade:{}", name.unwrap_or("all")));
             Ok(String::new())
         }
 
@@ -1758,5 +3087,74 @@ mod tests {
         fn unpin_formula(&self, _name: &str) -> AdapterResult<String> {
             Ok(String::new())
         }
+
+        fn list_formula_files(&self, _name: &str) -> AdapterResult<String> {
+            Ok("/opt/homebrew/Cellar/git/2.45.1/bin/git\n/opt/homebrew/Cellar/git/2.45.1/bin/git-shell\n/opt/homebrew/Cellar/git/2.45.1/share/man/man1/git.1\n".to_string())
+        }
+
+        fn list_formula_dependencies(&self) -> AdapterResult<String> {
+            Ok("git: gettext pcre2\nripgrep:\nwget: libidn2 openssl@3\n".to_string())
+        }
+
+        fn list_requested_formulae(&self) -> AdapterResult<String> {
+            Ok(r#"{"formulae":[
+                {"name":"git","installed":[{"version":"2.45.1","installed_on_request":true}]},
+                {"name":"pcre2","installed":[{"version":"10.44","installed_on_request":false}]},
+                {"name":"legacy","installed":[{"version":"1.0"}]}
+            ],"casks":[]}"#
+                .to_string())
+        }
+
+        fn formula_info(&self, _name: &str) -> AdapterResult<String> {
+            Ok(FORMULA_INFO_FIXTURE.to_string())
+        }
+
+        fn switch_formula_keg(&self, name: &str, version: &str) -> AdapterResult<String> {
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push(format!("switch:{name}@{version}"));
+            Ok(String::new())
+        }
+
+        fn list_taps(&self) -> AdapterResult<String> {
+            Ok("homebrew/core\nHashicorp/Tap\n".to_string())
+        }
+
+        fn add_tap(&self, tap: &str, url: Option<&str>) -> AdapterResult<String> {
+            let call = match url {
+                Some(url) => format!("tap:{tap} {url}"),
+                None => format!("tap:{tap}"),
+            };
+            self.mutation_calls.lock().unwrap().push(call);
+            Ok(String::new())
+        }
+
+        fn list_services(&self) -> AdapterResult<String> {
+            Ok(SERVICES_FIXTURE.to_string())
+        }
+
+        fn set_service_state(&self, name: &str, action: ServiceAction) -> AdapterResult<String> {
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push(format!("services:{}:{name}", action.as_str()));
+            Ok(String::new())
+        }
+
+        fn cleanup(&self, dry_run: bool) -> AdapterResult<String> {
+            if dry_run {
+                return Ok(CLEANUP_DRY_RUN_FIXTURE.to_string());
+            }
+            self.mutation_calls
+                .lock()
+                .unwrap()
+                .push("cleanup".to_string());
+            Ok("==> This operation has freed approximately 312.5MB of disk space.\n".to_string())
+        }
+
+        fn cellar(&self) -> AdapterResult<String> {
+            Ok(self.cellar.clone())
+        }
     }
 }
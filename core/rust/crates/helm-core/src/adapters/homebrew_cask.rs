@@ -1,15 +1,17 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use serde_json::Value;
 
-use crate::adapters::homebrew::parse_homebrew_version;
+use crate::adapters::homebrew::{homebrew_download_progress, parse_homebrew_version};
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
+    ManagerId, OutdatedPackage, PackageAlias, PackageAliasKind, PackageCandidate, PackageMetadata,
+    PackageRef, SearchQuery, TaskId, TaskType, UninstallDisposal,
 };
 
 const HOMEBREW_CASK_CAPABILITIES: &[Capability] = &[
@@ -21,6 +23,7 @@ const HOMEBREW_CASK_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::PackageInfo,
 ];
 
 const HOMEBREW_CASK_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -37,6 +40,21 @@ const LIST_TIMEOUT: Duration = Duration::from_secs(180);
 const SEARCH_TIMEOUT: Duration = Duration::from_secs(60);
 const LIFECYCLE_TIMEOUT: Duration = Duration::from_secs(4 * 60 * 60);
 const LIFECYCLE_IDLE_TIMEOUT: Duration = Duration::from_secs(45 * 60);
+const TRASH_TIMEOUT: Duration = Duration::from_secs(60);
+const OSASCRIPT_COMMAND: &str = "/usr/bin/osascript";
+const CASK_APP_DIR: &str = "/Applications";
+
+static EXCLUDE_AUTO_UPDATING_CASKS: AtomicBool = AtomicBool::new(true);
+
+/// Set whether outdated listings skip casks declaring `auto_updates true`,
+/// which update themselves outside Homebrew.
+pub fn set_exclude_auto_updating_casks(exclude: bool) {
+    EXCLUDE_AUTO_UPDATING_CASKS.store(exclude, Ordering::SeqCst);
+}
+
+pub fn exclude_auto_updating_casks() -> bool {
+    EXCLUDE_AUTO_UPDATING_CASKS.load(Ordering::SeqCst)
+}
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct HomebrewCaskDetectOutput {
@@ -48,10 +66,18 @@ pub trait HomebrewCaskSource: Send + Sync {
     fn detect(&self) -> AdapterResult<HomebrewCaskDetectOutput>;
     fn list_installed_casks(&self) -> AdapterResult<String>;
     fn list_outdated_casks(&self) -> AdapterResult<String>;
+    /// Outdated listing for an explicit refresh, where Homebrew may auto-update first.
+    fn refresh_outdated_casks(&self) -> AdapterResult<String> {
+        self.list_outdated_casks()
+    }
     fn search_casks(&self, query: &SearchQuery) -> AdapterResult<String>;
-    fn install_cask(&self, name: &str) -> AdapterResult<String>;
+    fn install_cask(&self, name: &str, options: &[String]) -> AdapterResult<String>;
     fn uninstall_cask(&self, name: &str) -> AdapterResult<String>;
-    fn upgrade_cask(&self, name: Option<&str>) -> AdapterResult<String>;
+    /// Move an installed app bundle to the user's Trash.
+    fn trash_app(&self, path: &Path) -> AdapterResult<()>;
+    fn upgrade_cask(&self, name: Option<&str>, options: &[String]) -> AdapterResult<String>;
+    /// `brew info --cask --json=v2` output for one cask.
+    fn cask_info(&self, name: &str) -> AdapterResult<String>;
 }
 
 pub struct HomebrewCaskAdapter<S: HomebrewCaskSource> {
@@ -94,16 +120,19 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed =
-                    parse_homebrew_cask_installed(&self.source.list_installed_casks()?)?;
-                let outdated = parse_homebrew_cask_outdated(&self.source.list_outdated_casks()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed_casks()
+                    .and_then(|raw| parse_homebrew_cask_installed(&raw));
+                let outdated = self
+                    .source
+                    .refresh_outdated_casks()
+                    .and_then(|raw| parse_homebrew_cask_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed_casks()?;
@@ -117,7 +146,12 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
             }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search_casks(&search_request.query)?;
-                let results = parse_homebrew_cask_search(&raw, &search_request.query)?;
+                let mut results = parse_homebrew_cask_search(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::HomebrewCask,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -126,11 +160,17 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                     install_request.version.as_deref(),
                     ManagerAction::Install,
                 )?;
+                let options = crate::install_options::validate_install_options(
+                    ManagerId::HomebrewCask,
+                    &install_request.options,
+                )?;
                 let before_version = resolve_homebrew_cask_version(
                     &self.source,
                     install_request.package.name.as_str(),
                 )?;
-                if let Err(error) = self.source.install_cask(&install_request.package.name)
+                if let Err(error) = self
+                    .source
+                    .install_cask(&install_request.package.name, &options)
                     && !is_homebrew_cask_already_installed_error(&error)
                 {
                     return Err(error);
@@ -145,6 +185,7 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -157,6 +198,17 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                     &self.source,
                     uninstall_request.package.name.as_str(),
                 )?;
+                if uninstall_request.disposal == UninstallDisposal::Trash {
+                    // Trash the bundles first; the uninstall that follows then
+                    // only clears Homebrew's record of the cask.
+                    let installed = self.source.list_installed_casks()?;
+                    for app in parse_homebrew_cask_app_bundles(
+                        &installed,
+                        uninstall_request.package.name.as_str(),
+                    )? {
+                        self.source.trash_app(&app)?;
+                    }
+                }
                 if let Err(error) = self.source.uninstall_cask(&uninstall_request.package.name)
                     && !is_homebrew_cask_already_absent_error(&error)
                 {
@@ -168,39 +220,43 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version,
                     after_version: None,
+                    disposal: Some(uninstall_request.disposal),
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let requested_package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::HomebrewCask,
-                    name: "__all__".to_string(),
-                });
+                let requested_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::HomebrewCask,
+                    &upgrade_request.target,
+                )?;
+                let target_name = requested_package.map(|package| package.name.as_str());
                 validate_homebrew_cask_upgrade_target(
-                    requested_package.name.as_str(),
+                    target_name,
                     upgrade_request.version.as_deref(),
                 )?;
-                let target_name = if requested_package.name == "__all__" {
-                    None
-                } else {
-                    Some(requested_package.name.as_str())
-                };
+                let options = crate::install_options::validate_upgrade_options(
+                    ManagerId::HomebrewCask,
+                    &upgrade_request.options,
+                )?;
                 let targeted_outdated = if let Some(target_name) = target_name {
                     find_outdated_homebrew_cask(&self.source, target_name)?
                 } else {
                     None
                 };
-                let _ = self.source.upgrade_cask(target_name)?;
+                let _ = self.source.upgrade_cask(target_name, &options)?;
                 if let Some(target_name) = target_name {
                     ensure_cask_no_longer_outdated(&self.source, target_name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package: requested_package,
+                    package: upgrade_request
+                        .target
+                        .result_package(ManagerId::HomebrewCask),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|item| item.installed_version.clone()),
                     after_version: targeted_outdated.map(|item| item.candidate_version),
+                    disposal: None,
                 }))
             }
             AdapterRequest::ConfigurePackageDetail(_) => unreachable!(
@@ -213,6 +269,75 @@ impl<S: HomebrewCaskSource> ManagerAdapter for HomebrewCaskAdapter<S> {
                 kind: CoreErrorKind::UnsupportedCapability,
                 message: "homebrew cask does not support native pinning".to_string(),
             }),
+            AdapterRequest::SelfVersionCheck(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not report a self version".to_string(),
+            }),
+            AdapterRequest::ListFiles(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not list owned files".to_string(),
+            }),
+            AdapterRequest::ListDependencies(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not report dependencies".to_string(),
+            }),
+            AdapterRequest::ListRequested(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not report requested packages".to_string(),
+            }),
+            AdapterRequest::Rollback(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not keep prior versions to roll back to".to_string(),
+            }),
+            AdapterRequest::PackageInfo(info_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::HomebrewCask,
+                    ManagerAction::PackageInfo,
+                    info_request.package.name.as_str(),
+                )?;
+                let raw = self.source.cask_info(&info_request.package.name)?;
+                Ok(AdapterResponse::PackageInfo(parse_homebrew_cask_info(
+                    &raw,
+                )?))
+            }
+            AdapterRequest::ListServices(_) | AdapterRequest::SetServiceState(_) => {
+                Err(CoreError {
+                    manager: Some(ManagerId::HomebrewCask),
+                    task: None,
+                    action: Some(request.action()),
+                    kind: CoreErrorKind::UnsupportedCapability,
+                    message: "homebrew cask does not manage services".to_string(),
+                })
+            }
+            AdapterRequest::PreviewCleanup(_) | AdapterRequest::Cleanup(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "`brew cleanup` runs through the Homebrew formula manager".to_string(),
+            }),
+            AdapterRequest::MeasurePackageSizes(_) => Err(CoreError {
+                manager: Some(ManagerId::HomebrewCask),
+                task: None,
+                action: Some(request.action()),
+                kind: CoreErrorKind::UnsupportedCapability,
+                message: "homebrew cask does not measure package sizes".to_string(),
+            }),
         }
     }
 }
@@ -287,6 +412,16 @@ pub fn homebrew_cask_search_request(
     )
 }
 
+pub fn homebrew_cask_info_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    homebrew_cask_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PackageInfo,
+        CommandSpec::new(BREW_COMMAND).args(["info", "--cask", "--json=v2", name]),
+        SEARCH_TIMEOUT,
+    )
+}
+
 pub fn homebrew_cask_detect_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
     homebrew_cask_request(
         task_id,
@@ -317,25 +452,60 @@ pub fn homebrew_cask_list_installed_request(task_id: Option<TaskId>) -> ProcessS
     )
 }
 
-pub fn homebrew_cask_list_outdated_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+/// `brew outdated --cask` skips `auto_updates true` casks unless asked for them,
+/// so including them only needs `--greedy-auto-updates`.
+pub fn homebrew_cask_list_outdated_request(
+    task_id: Option<TaskId>,
+    include_auto_updates: bool,
+) -> ProcessSpawnRequest {
     homebrew_cask_request(
         task_id,
         TaskType::Refresh,
         ManagerAction::ListOutdated,
-        CommandSpec::new(BREW_COMMAND).args(["outdated", "--cask", "--json=v2"]),
+        homebrew_cask_outdated_command(include_auto_updates),
         LIST_TIMEOUT,
     )
 }
 
-pub fn homebrew_cask_install_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+pub fn homebrew_cask_refresh_outdated_request(
+    task_id: Option<TaskId>,
+    include_auto_updates: bool,
+) -> ProcessSpawnRequest {
+    homebrew_cask_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::Refresh,
+        homebrew_cask_outdated_command(include_auto_updates),
+        LIST_TIMEOUT,
+    )
+}
+
+fn homebrew_cask_outdated_command(include_auto_updates: bool) -> CommandSpec {
+    let command = CommandSpec::new(BREW_COMMAND).args(["outdated", "--cask", "--json=v2"]);
+    if include_auto_updates {
+        command.arg("--greedy-auto-updates")
+    } else {
+        command
+    }
+}
+
+pub fn homebrew_cask_install_request(
+    task_id: Option<TaskId>,
+    name: &str,
+    options: &[String],
+) -> ProcessSpawnRequest {
     homebrew_cask_request(
         task_id,
         TaskType::Install,
         ManagerAction::Install,
-        CommandSpec::new(BREW_COMMAND).args(["install", "--cask", name]),
+        CommandSpec::new(BREW_COMMAND)
+            .args(["install", "--cask"])
+            .args(options.iter().cloned())
+            .arg(name),
         LIFECYCLE_TIMEOUT,
     )
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
+    .progress_parser(homebrew_download_progress)
 }
 
 pub fn homebrew_cask_uninstall_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
@@ -349,12 +519,40 @@ pub fn homebrew_cask_uninstall_request(task_id: Option<TaskId>, name: &str) -> P
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
 }
 
+/// Ask Finder to move `path` to the Trash, so the bundle can be put back.
+/// The path is passed as a script argument rather than spliced into the script.
+pub fn homebrew_cask_trash_app_request(
+    task_id: Option<TaskId>,
+    path: &Path,
+) -> ProcessSpawnRequest {
+    homebrew_cask_request(
+        task_id,
+        TaskType::Uninstall,
+        ManagerAction::Uninstall,
+        CommandSpec::new(OSASCRIPT_COMMAND)
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "tell application \"Finder\" to delete (POSIX file (item 1 of argv) as alias)",
+                "-e",
+                "end run",
+            ])
+            .arg(path.to_string_lossy().into_owned()),
+        TRASH_TIMEOUT,
+    )
+}
+
 pub fn homebrew_cask_upgrade_request(
     task_id: Option<TaskId>,
     name: Option<&str>,
+    options: &[String],
 ) -> ProcessSpawnRequest {
     let command = if let Some(name) = name {
-        CommandSpec::new(BREW_COMMAND).args(["upgrade", "--cask", name])
+        CommandSpec::new(BREW_COMMAND)
+            .args(["upgrade", "--cask"])
+            .args(options.iter().cloned())
+            .arg(name)
     } else {
         CommandSpec::new(BREW_COMMAND).args(["upgrade", "--cask"])
     };
@@ -366,6 +564,7 @@ pub fn homebrew_cask_upgrade_request(
         LIFECYCLE_TIMEOUT,
     )
     .idle_timeout(LIFECYCLE_IDLE_TIMEOUT)
+    .progress_parser(homebrew_download_progress)
 }
 
 fn homebrew_cask_request(
@@ -431,6 +630,7 @@ fn parse_homebrew_cask_installed(output: &str) -> AdapterResult<Vec<InstalledPac
             installed_version: Some(installed_version),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -438,6 +638,146 @@ fn parse_homebrew_cask_installed(output: &str) -> AdapterResult<Vec<InstalledPac
     Ok(packages)
 }
 
+/// App bundle paths a cask installed, read from the `app` artifacts in
+/// `brew info --cask --json=v2` output. Relative targets resolve against
+/// `/Applications`, Homebrew's default app directory.
+fn parse_homebrew_cask_app_bundles(output: &str, cask_name: &str) -> AdapterResult<Vec<PathBuf>> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let payload: Value = serde_json::from_str(trimmed)
+        .map_err(|error| parse_error(&format!("invalid homebrew cask installed JSON: {error}")))?;
+
+    let Some(cask) = payload
+        .get("casks")
+        .and_then(Value::as_array)
+        .and_then(|casks| {
+            casks
+                .iter()
+                .find(|cask| cask_identifier(cask).as_deref() == Some(cask_name))
+        })
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut bundles = Vec::new();
+    let artifacts = cask.get("artifacts").and_then(Value::as_array);
+    for app in artifacts
+        .into_iter()
+        .flatten()
+        .filter_map(|artifact| artifact.get("app").and_then(Value::as_array))
+    {
+        // An app artifact is `[source]` or `[source, {"target": name}]`.
+        let target = app
+            .iter()
+            .find_map(|entry| entry.get("target").and_then(Value::as_str))
+            .or_else(|| app.first().and_then(Value::as_str));
+        let Some(target) = target.map(str::trim).filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        let target = Path::new(target);
+        let path = if target.is_absolute() {
+            target.to_path_buf()
+        } else {
+            Path::new(CASK_APP_DIR).join(target)
+        };
+        if !bundles.contains(&path) {
+            bundles.push(path);
+        }
+    }
+    Ok(bundles)
+}
+
+/// Description, homepage, and aliases for one cask from `brew info --cask
+/// --json=v2`. Aliases are the cask's display names, the app bundles it
+/// installs, and the bundle ids its `uninstall` stanza quits, which is how
+/// Homebrew's cask API names the apps.
+fn parse_homebrew_cask_info(output: &str) -> AdapterResult<PackageMetadata> {
+    let payload: Value = serde_json::from_str(output.trim())
+        .map_err(|error| parse_error(&format!("invalid homebrew cask info JSON: {error}")))?;
+    let Some(cask) = payload
+        .get("casks")
+        .and_then(Value::as_array)
+        .and_then(|casks| casks.first())
+    else {
+        return Err(parse_error("homebrew cask info JSON listed no cask"));
+    };
+
+    let text = |key: &str| {
+        cask.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let strings = |value: &Value| -> Vec<String> {
+        match value {
+            Value::String(value) => vec![value.clone()],
+            Value::Array(values) => values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+
+    let mut aliases = Vec::new();
+    let mut push = |kind: PackageAliasKind, value: &str| {
+        let value = value.trim();
+        let alias = PackageAlias {
+            kind,
+            value: value.to_string(),
+        };
+        if !value.is_empty() && !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    };
+    for name in cask.get("name").map(strings).unwrap_or_default() {
+        push(PackageAliasKind::DisplayName, &name);
+    }
+    for artifact in cask
+        .get("artifacts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let Some(app) = artifact.get("app").and_then(Value::as_array) {
+            let target = app
+                .iter()
+                .find_map(|entry| entry.get("target").and_then(Value::as_str))
+                .or_else(|| app.first().and_then(Value::as_str));
+            if let Some(file_name) = target
+                .and_then(|target| Path::new(target).file_name())
+                .and_then(|name| name.to_str())
+            {
+                push(PackageAliasKind::AppBundle, file_name);
+            }
+        }
+        for stanza in artifact
+            .get("uninstall")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            for bundle_id in stanza.get("quit").map(strings).unwrap_or_default() {
+                push(PackageAliasKind::BundleId, &bundle_id);
+            }
+        }
+    }
+
+    Ok(PackageMetadata {
+        description: text("desc"),
+        homepage: text("homepage"),
+        license: None,
+        installed_size_bytes: None,
+        dependencies: Vec::new(),
+        aliases,
+    })
+}
+
 fn parse_homebrew_cask_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
     let trimmed = output.trim();
     if trimmed.is_empty() || trimmed == "{}" {
@@ -497,6 +837,7 @@ fn parse_homebrew_cask_outdated(output: &str) -> AdapterResult<Vec<OutdatedPacka
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -554,6 +895,7 @@ fn parse_homebrew_cask_search(
                 source_manager: ManagerId::HomebrewCask,
                 originating_query: query.text.clone(),
                 cached_at: query.issued_at,
+                popularity: Default::default(),
             });
         }
     }
@@ -654,10 +996,10 @@ fn validate_homebrew_cask_target(
 }
 
 fn validate_homebrew_cask_upgrade_target(
-    package_name: &str,
+    package_name: Option<&str>,
     version: Option<&str>,
 ) -> AdapterResult<()> {
-    if package_name != "__all__" {
+    if let Some(package_name) = package_name {
         validate_homebrew_cask_target(package_name, version, ManagerAction::Upgrade)?;
     } else if version.is_some() {
         return Err(CoreError {
@@ -735,27 +1077,33 @@ fn parse_error(message: &str) -> CoreError {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
 
     use crate::adapters::homebrew_cask::{
         HomebrewCaskAdapter, HomebrewCaskDetectOutput, HomebrewCaskSource,
         homebrew_cask_catalog_request, homebrew_cask_detect_request, homebrew_cask_install_request,
         homebrew_cask_list_installed_request, homebrew_cask_list_outdated_request,
-        homebrew_cask_search_request, homebrew_cask_uninstall_request,
-        homebrew_cask_upgrade_request, parse_homebrew_cask_installed, parse_homebrew_cask_outdated,
-        parse_homebrew_cask_search,
+        homebrew_cask_refresh_outdated_request, homebrew_cask_search_request,
+        homebrew_cask_trash_app_request, homebrew_cask_uninstall_request,
+        homebrew_cask_upgrade_request, parse_homebrew_cask_app_bundles, parse_homebrew_cask_info,
+        parse_homebrew_cask_installed, parse_homebrew_cask_outdated, parse_homebrew_cask_search,
     };
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, InstallRequest,
         ListInstalledRequest, ListOutdatedRequest, ManagerAdapter, SearchRequest,
     };
-    use crate::models::{ManagerAction, ManagerId, PackageRef, SearchQuery, TaskType};
+    use crate::models::{
+        ManagerAction, ManagerId, PackageAliasKind, PackageRef, SearchQuery, TaskType,
+        UninstallDisposal,
+    };
     use std::time::UNIX_EPOCH;
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew_cask/version.txt");
     const INSTALLED_FIXTURE: &str =
         include_str!("../../tests/fixtures/homebrew_cask/installed.json");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew_cask/outdated.json");
+    const INFO_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew_cask/info.json");
     const SEARCH_FIXTURE: &str = "Warning: Use `--eval-all` to search 1 additional cask in third party taps.\n==> Casks\nfont-ia-writer-mono: (iA Writer Mono) [no description]\niterm2: (iTerm2) Terminal emulator as alternative to Apple's Terminal app\niterm2@beta: (iTerm2) Terminal emulator as alternative to Apple's Terminal app\n";
 
     #[test]
@@ -781,6 +1129,37 @@ mod tests {
         assert_eq!(packages[0].candidate_version, "134.0.6998.89");
     }
 
+    #[test]
+    fn parses_cask_info_with_display_names_apps_and_bundle_ids() {
+        let metadata = parse_homebrew_cask_info(INFO_FIXTURE).unwrap();
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Open-source code editor")
+        );
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://code.visualstudio.com/")
+        );
+        let aliases = metadata
+            .aliases
+            .iter()
+            .map(|alias| (alias.kind, alias.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            aliases,
+            vec![
+                (
+                    PackageAliasKind::DisplayName,
+                    "Microsoft Visual Studio Code"
+                ),
+                (PackageAliasKind::DisplayName, "VS Code"),
+                (PackageAliasKind::AppBundle, "Visual Studio Code.app"),
+                (PackageAliasKind::BundleId, "com.microsoft.VSCode"),
+            ]
+        );
+        assert!(parse_homebrew_cask_info(r#"{"casks":[]}"#).is_err());
+    }
+
     #[test]
     fn parses_cask_search_results_with_descriptions() {
         let query = SearchQuery {
@@ -816,7 +1195,7 @@ mod tests {
             vec!["info", "--cask", "--json=v2", "--installed"]
         );
 
-        let outdated = homebrew_cask_list_outdated_request(None);
+        let outdated = homebrew_cask_list_outdated_request(None, false);
         assert_eq!(outdated.task_type, TaskType::Refresh);
         assert_eq!(outdated.action, ManagerAction::ListOutdated);
         assert_eq!(
@@ -824,6 +1203,13 @@ mod tests {
             vec!["outdated", "--cask", "--json=v2"]
         );
 
+        let greedy = homebrew_cask_refresh_outdated_request(None, true);
+        assert_eq!(greedy.action, ManagerAction::Refresh);
+        assert_eq!(
+            greedy.command.args,
+            vec!["outdated", "--cask", "--json=v2", "--greedy-auto-updates"]
+        );
+
         let search = homebrew_cask_search_request(
             None,
             &SearchQuery {
@@ -842,7 +1228,7 @@ mod tests {
         assert_eq!(catalog.task_type, TaskType::CatalogSync);
         assert_eq!(catalog.command.args, vec!["casks"]);
 
-        let install = homebrew_cask_install_request(None, "iterm2");
+        let install = homebrew_cask_install_request(None, "iterm2", &[]);
         assert_eq!(install.task_type, TaskType::Install);
         assert_eq!(install.command.args, vec!["install", "--cask", "iterm2"]);
 
@@ -853,9 +1239,21 @@ mod tests {
             vec!["uninstall", "--cask", "iterm2"]
         );
 
-        let upgrade = homebrew_cask_upgrade_request(None, Some("iterm2"));
+        let upgrade = homebrew_cask_upgrade_request(None, Some("iterm2"), &[]);
         assert_eq!(upgrade.task_type, TaskType::Upgrade);
         assert_eq!(upgrade.command.args, vec!["upgrade", "--cask", "iterm2"]);
+
+        let options = vec!["--no-quarantine".to_string()];
+        let install = homebrew_cask_install_request(None, "iterm2", &options);
+        assert_eq!(
+            install.command.args,
+            vec!["install", "--cask", "--no-quarantine", "iterm2"]
+        );
+        let upgrade = homebrew_cask_upgrade_request(None, Some("iterm2"), &options);
+        assert_eq!(
+            upgrade.command.args,
+            vec!["upgrade", "--cask", "--no-quarantine", "iterm2"]
+        );
     }
 
     #[test]
@@ -868,6 +1266,7 @@ mod tests {
             list_installed_result: Ok(INSTALLED_FIXTURE.to_string()),
             list_outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
             search_result: Ok(SEARCH_FIXTURE.to_string()),
+            trashed: Default::default(),
         };
         let adapter = HomebrewCaskAdapter::new(source);
 
@@ -919,6 +1318,7 @@ mod tests {
             list_installed_result: Ok(INSTALLED_FIXTURE.to_string()),
             list_outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
             search_result: Ok(SEARCH_FIXTURE.to_string()),
+            trashed: Default::default(),
         };
         let adapter = HomebrewCaskAdapter::new(source);
         let install = adapter.execute(AdapterRequest::Install(InstallRequest {
@@ -928,6 +1328,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            options: Vec::new(),
         }));
         assert!(matches!(install, Ok(AdapterResponse::Mutation(_))));
 
@@ -939,26 +1340,92 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             },
         ));
         assert!(matches!(uninstall, Ok(AdapterResponse::Mutation(_))));
 
         let upgrade = adapter.execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-            package: Some(PackageRef {
+            target: crate::adapters::UpgradeTarget::Package(PackageRef {
                 manager: ManagerId::HomebrewCask,
                 name: "raycast".to_string(),
             }),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }));
         assert!(matches!(upgrade, Ok(AdapterResponse::Mutation(_))));
     }
 
+    #[test]
+    fn parses_app_bundles_from_cask_artifacts() {
+        assert_eq!(
+            parse_homebrew_cask_app_bundles(INSTALLED_FIXTURE, "google-chrome").unwrap(),
+            vec![PathBuf::from("/Applications/Google Chrome.app")]
+        );
+        assert!(
+            parse_homebrew_cask_app_bundles(INSTALLED_FIXTURE, "zoom")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn trash_uninstall_moves_app_bundles_and_records_disposal() {
+        let source = FixtureSource {
+            detect_result: Ok(HomebrewCaskDetectOutput {
+                executable_path: Some(PathBuf::from("/opt/homebrew/bin/brew")),
+                version_output: VERSION_FIXTURE.to_string(),
+            }),
+            list_installed_result: Ok(INSTALLED_FIXTURE.to_string()),
+            list_outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
+            search_result: Ok(SEARCH_FIXTURE.to_string()),
+            trashed: Default::default(),
+        };
+        let adapter = HomebrewCaskAdapter::new(source);
+        let response = adapter
+            .execute(AdapterRequest::Uninstall(
+                crate::adapters::UninstallRequest {
+                    package: PackageRef {
+                        manager: ManagerId::HomebrewCask,
+                        name: "iterm2".to_string(),
+                    },
+                    target_name: None,
+                    version: None,
+                    disposal: UninstallDisposal::Trash,
+                },
+            ))
+            .unwrap();
+        let AdapterResponse::Mutation(mutation) = response else {
+            panic!("expected mutation response");
+        };
+        assert_eq!(mutation.disposal, Some(UninstallDisposal::Trash));
+        assert_eq!(
+            *adapter.source.trashed.lock().unwrap(),
+            vec![PathBuf::from("/Applications/iTerm2.app")]
+        );
+    }
+
+    #[test]
+    fn trash_app_request_passes_path_as_script_argument() {
+        let request =
+            homebrew_cask_trash_app_request(None, Path::new("/Applications/Google Chrome.app"));
+        assert_eq!(request.command.program, PathBuf::from("/usr/bin/osascript"));
+        assert_eq!(
+            request.command.args.last().map(String::as_str),
+            Some("/Applications/Google Chrome.app")
+        );
+        let script_args = &request.command.args[..request.command.args.len() - 1];
+        assert!(script_args.iter().all(|arg| !arg.contains("Chrome")));
+    }
+
     struct FixtureSource {
         detect_result: AdapterResult<HomebrewCaskDetectOutput>,
         list_installed_result: AdapterResult<String>,
         list_outdated_result: AdapterResult<String>,
         search_result: AdapterResult<String>,
+        trashed: Mutex<Vec<PathBuf>>,
     }
 
     impl HomebrewCaskSource for FixtureSource {
@@ -982,7 +1449,7 @@ mod tests {
             }
         }
 
-        fn install_cask(&self, _name: &str) -> AdapterResult<String> {
+        fn install_cask(&self, _name: &str, _options: &[String]) -> AdapterResult<String> {
             Ok(String::new())
         }
 
@@ -990,8 +1457,17 @@ mod tests {
             Ok(String::new())
         }
 
-        fn upgrade_cask(&self, _name: Option<&str>) -> AdapterResult<String> {
+        fn trash_app(&self, path: &Path) -> AdapterResult<()> {
+            self.trashed.lock().unwrap().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn upgrade_cask(&self, _name: Option<&str>, _options: &[String]) -> AdapterResult<String> {
             Ok(String::new())
         }
+
+        fn cask_info(&self, _name: &str) -> AdapterResult<String> {
+            Ok(INFO_FIXTURE.to_string())
+        }
     }
 }
@@ -1,13 +1,17 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::homebrew::parse_homebrew_version;
 use crate::adapters::homebrew_cask::{
-    HomebrewCaskDetectOutput, HomebrewCaskSource, homebrew_cask_config_request,
-    homebrew_cask_detect_request, homebrew_cask_install_request,
-    homebrew_cask_list_installed_request, homebrew_cask_list_outdated_request,
-    homebrew_cask_search_request, homebrew_cask_uninstall_request, homebrew_cask_upgrade_request,
+    HomebrewCaskDetectOutput, HomebrewCaskSource, exclude_auto_updating_casks,
+    homebrew_cask_config_request, homebrew_cask_detect_request, homebrew_cask_info_request,
+    homebrew_cask_install_request, homebrew_cask_list_installed_request,
+    homebrew_cask_list_outdated_request, homebrew_cask_refresh_outdated_request,
+    homebrew_cask_search_request, homebrew_cask_trash_app_request, homebrew_cask_uninstall_request,
+    homebrew_cask_upgrade_request,
 };
+use crate::adapters::homebrew_env::{homebrew_env_overrides, homebrew_env_vars};
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
@@ -27,12 +31,11 @@ impl ProcessHomebrewCaskSource {
         let path = std::env::var("PATH").unwrap_or_default();
         let new_path = format!("/opt/homebrew/bin:/usr/local/bin:{path}");
 
-        request.command = request
-            .command
-            .env("PATH", new_path)
-            .env("HOMEBREW_NO_AUTO_UPDATE", "1")
-            .env("HOMEBREW_NO_INSTALL_CLEANUP", "1")
-            .env("HOMEBREW_NO_ENV_HINTS", "1");
+        request.command = request.command.env("PATH", new_path);
+        let overrides = homebrew_env_overrides(ManagerId::HomebrewCask);
+        for (key, value) in homebrew_env_vars(request.action, &overrides) {
+            request.command = request.command.env(key, value);
+        }
         request
     }
 }
@@ -81,7 +84,18 @@ impl HomebrewCaskSource for ProcessHomebrewCaskSource {
     }
 
     fn list_outdated_casks(&self) -> AdapterResult<String> {
-        let request = self.configure_request(homebrew_cask_list_outdated_request(None));
+        let request = self.configure_request(homebrew_cask_list_outdated_request(
+            None,
+            !exclude_auto_updating_casks(),
+        ));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn refresh_outdated_casks(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_cask_refresh_outdated_request(
+            None,
+            !exclude_auto_updating_casks(),
+        ));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
@@ -90,8 +104,8 @@ impl HomebrewCaskSource for ProcessHomebrewCaskSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
-    fn install_cask(&self, name: &str) -> AdapterResult<String> {
-        let request = self.configure_request(homebrew_cask_install_request(None, name));
+    fn install_cask(&self, name: &str, options: &[String]) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_cask_install_request(None, name, options));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
@@ -100,8 +114,18 @@ impl HomebrewCaskSource for ProcessHomebrewCaskSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
-    fn upgrade_cask(&self, name: Option<&str>) -> AdapterResult<String> {
-        let request = self.configure_request(homebrew_cask_upgrade_request(None, name));
+    fn trash_app(&self, path: &Path) -> AdapterResult<()> {
+        let request = homebrew_cask_trash_app_request(None, path);
+        run_and_collect_stdout(self.executor.as_ref(), request).map(|_| ())
+    }
+
+    fn upgrade_cask(&self, name: Option<&str>, options: &[String]) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_cask_upgrade_request(None, name, options));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn cask_info(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_cask_info_request(None, name));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 }
@@ -0,0 +1,122 @@
+//! `brew cleanup`: stale downloads in the Homebrew cache and kegs older than
+//! the linked version.
+
+use std::time::Duration;
+
+use crate::adapters::homebrew::{HOMEBREW_COMMAND, LIST_TIMEOUT, homebrew_request};
+use crate::execution::{CommandSpec, ProcessSpawnRequest};
+use crate::models::{CleanupReport, ManagerAction, TaskId, TaskType};
+
+/// Removing old kegs and a large download cache can take minutes.
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub fn homebrew_disk_cleanup_request(
+    task_id: Option<TaskId>,
+    dry_run: bool,
+) -> ProcessSpawnRequest {
+    if dry_run {
+        homebrew_request(
+            task_id,
+            TaskType::Refresh,
+            ManagerAction::PreviewCleanup,
+            CommandSpec::new(HOMEBREW_COMMAND).args(["cleanup", "--dry-run"]),
+            LIST_TIMEOUT,
+        )
+    } else {
+        homebrew_request(
+            task_id,
+            TaskType::Configure,
+            ManagerAction::Cleanup,
+            CommandSpec::new(HOMEBREW_COMMAND).arg("cleanup"),
+            CLEANUP_TIMEOUT,
+        )
+    }
+}
+
+/// Read the bytes from `brew cleanup` output. Homebrew ends with a summary
+/// ("This operation would free approximately 1.2GB of disk space.") only
+/// when something was removable; without one, the per-file sizes are summed.
+pub(crate) fn parse_homebrew_cleanup(output: &str) -> CleanupReport {
+    let summary = output.lines().rev().find_map(|line| {
+        let (_, rest) = line.split_once(" approximately ")?;
+        let (size, _) = rest.split_once(" of disk space")?;
+        parse_homebrew_size(size)
+    });
+    let bytes = summary.unwrap_or_else(|| {
+        output
+            .lines()
+            .filter(|line| line.starts_with("Would remove: ") || line.starts_with("Removing: "))
+            .filter_map(|line| {
+                let (_, size) = line.trim_end().strip_suffix(')')?.rsplit_once(" (")?;
+                parse_homebrew_size(size)
+            })
+            .fold(0_u64, u64::saturating_add)
+    });
+    CleanupReport { bytes }
+}
+
+/// Parse Homebrew's `disk_usage_readable` sizes: `312B`, `45.1KB`, `1.2GB`,
+/// in powers of 1024. Item lines may add a file count (`12 files, 3.4MB`).
+fn parse_homebrew_size(raw: &str) -> Option<u64> {
+    let size = raw.rsplit(", ").next()?.trim();
+    let (number, multiplier) = if let Some(number) = size.strip_suffix("GB") {
+        (number, 1024_f64 * 1024.0 * 1024.0)
+    } else if let Some(number) = size.strip_suffix("MB") {
+        (number, 1024_f64 * 1024.0)
+    } else if let Some(number) = size.strip_suffix("KB") {
+        (number, 1024_f64)
+    } else {
+        (size.strip_suffix('B')?, 1_f64)
+    };
+    let value: f64 = number.trim().parse().ok()?;
+    (value >= 0.0).then(|| (value * multiplier).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{ManagerAction, TaskType};
+
+    use super::{homebrew_disk_cleanup_request, parse_homebrew_cleanup, parse_homebrew_size};
+
+    const DRY_RUN_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/cleanup_dry_run.txt");
+
+    #[test]
+    fn parses_dry_run_summary() {
+        let report = parse_homebrew_cleanup(DRY_RUN_FIXTURE);
+        assert_eq!(report.bytes, 1_288_490_189);
+    }
+
+    #[test]
+    fn sums_item_sizes_without_a_summary() {
+        let output = "Removing: /opt/homebrew/Cellar/wget/1.21.3... (91 files, 4.1MB)\n\
+                      Removing: /Users/jason/Library/Caches/Homebrew/wget--1.21.3.bottle.tar.gz... (1.5MB)\n";
+        let report = parse_homebrew_cleanup(output);
+        assert_eq!(report.bytes, 4_299_162 + 1_572_864);
+    }
+
+    #[test]
+    fn nothing_to_clean_is_zero_bytes() {
+        assert_eq!(parse_homebrew_cleanup("").bytes, 0);
+    }
+
+    #[test]
+    fn parses_homebrew_size_units() {
+        assert_eq!(parse_homebrew_size("312B"), Some(312));
+        assert_eq!(parse_homebrew_size("2.0KB"), Some(2048));
+        assert_eq!(parse_homebrew_size("12 files, 3.0MB"), Some(3_145_728));
+        assert_eq!(parse_homebrew_size("lots"), None);
+    }
+
+    #[test]
+    fn cleanup_requests_use_dry_run_only_for_previews() {
+        let preview = homebrew_disk_cleanup_request(None, true);
+        assert_eq!(preview.action, ManagerAction::PreviewCleanup);
+        assert_eq!(preview.task_type, TaskType::Refresh);
+        assert_eq!(preview.command.args, vec!["cleanup", "--dry-run"]);
+
+        let cleanup = homebrew_disk_cleanup_request(None, false);
+        assert_eq!(cleanup.action, ManagerAction::Cleanup);
+        assert_eq!(cleanup.task_type, TaskType::Configure);
+        assert_eq!(cleanup.command.args, vec!["cleanup"]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId};
+use crate::persistence::ManagerEnvSetting;
+
+static HOMEBREW_ENV_OVERRIDES: OnceLock<RwLock<HashMap<ManagerId, HomebrewEnvOverrides>>> =
+    OnceLock::new();
+
+/// User-overridable Homebrew environment settings, stored as manager env settings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum HomebrewEnvSetting {
+    /// Let `brew` run its own `brew update` before commands that trigger it.
+    AutoUpdate,
+    /// Send Homebrew analytics.
+    Analytics,
+    /// Emit ANSI color in Homebrew output.
+    Color,
+}
+
+impl HomebrewEnvSetting {
+    pub const ALL: [HomebrewEnvSetting; 3] = [
+        HomebrewEnvSetting::AutoUpdate,
+        HomebrewEnvSetting::Analytics,
+        HomebrewEnvSetting::Color,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HomebrewEnvSetting::AutoUpdate => "auto_update",
+            HomebrewEnvSetting::Analytics => "analytics",
+            HomebrewEnvSetting::Color => "color",
+        }
+    }
+}
+
+impl FromStr for HomebrewEnvSetting {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        HomebrewEnvSetting::ALL
+            .into_iter()
+            .find(|setting| setting.as_str() == value.trim())
+            .ok_or_else(|| CoreError {
+                manager: None,
+                task: None,
+                action: None,
+                kind: CoreErrorKind::InvalidInput,
+                message: format!("unknown homebrew env setting '{}'", value.trim()),
+            })
+    }
+}
+
+/// Per-setting overrides; `None` keeps Helm's default for the running action.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HomebrewEnvOverrides {
+    pub auto_update: Option<bool>,
+    pub analytics: Option<bool>,
+    pub color: Option<bool>,
+}
+
+impl HomebrewEnvOverrides {
+    pub fn get(&self, setting: HomebrewEnvSetting) -> Option<bool> {
+        match setting {
+            HomebrewEnvSetting::AutoUpdate => self.auto_update,
+            HomebrewEnvSetting::Analytics => self.analytics,
+            HomebrewEnvSetting::Color => self.color,
+        }
+    }
+
+    pub fn set(&mut self, setting: HomebrewEnvSetting, value: Option<bool>) {
+        match setting {
+            HomebrewEnvSetting::AutoUpdate => self.auto_update = value,
+            HomebrewEnvSetting::Analytics => self.analytics = value,
+            HomebrewEnvSetting::Color => self.color = value,
+        }
+    }
+
+    /// Build overrides for `manager` from stored settings, ignoring unknown
+    /// keys and values other than `true`/`false`.
+    pub fn from_settings(manager: ManagerId, settings: &[ManagerEnvSetting]) -> Self {
+        let mut overrides = Self::default();
+        for setting in settings.iter().filter(|setting| setting.manager == manager) {
+            let Ok(key) = setting.key.parse::<HomebrewEnvSetting>() else {
+                continue;
+            };
+            if let Ok(value) = setting.value.trim().parse::<bool>() {
+                overrides.set(key, Some(value));
+            }
+        }
+        overrides
+    }
+}
+
+/// Homebrew environment for a command running `action`.
+///
+/// Auto-update defaults to on only for explicit refreshes, so listings,
+/// searches, and mutations never pay for an implicit `brew update`, and is off
+/// for those too while the network is metered. Analytics
+/// and color default to off; env hints and install-time cleanup are always
+/// disabled because Helm manages keg cleanup itself.
+pub fn homebrew_env_vars(
+    action: ManagerAction,
+    overrides: &HomebrewEnvOverrides,
+) -> Vec<(&'static str, &'static str)> {
+    let mut vars = vec![
+        ("HOMEBREW_NO_INSTALL_CLEANUP", "1"),
+        ("HOMEBREW_NO_ENV_HINTS", "1"),
+    ];
+    if !overrides.auto_update.unwrap_or(
+        action == ManagerAction::Refresh && !crate::network_policy::suppress_heavy_network(),
+    ) {
+        vars.push(("HOMEBREW_NO_AUTO_UPDATE", "1"));
+    }
+    if !overrides.analytics.unwrap_or(false) {
+        vars.push(("HOMEBREW_NO_ANALYTICS", "1"));
+    }
+    if !overrides.color.unwrap_or(false) {
+        vars.push(("HOMEBREW_NO_COLOR", "1"));
+    }
+    vars
+}
+
+pub fn set_homebrew_env_overrides(manager: ManagerId, overrides: HomebrewEnvOverrides) {
+    let slot = HOMEBREW_ENV_OVERRIDES.get_or_init(|| RwLock::new(HashMap::new()));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if overrides == HomebrewEnvOverrides::default() {
+        guard.remove(&manager);
+    } else {
+        guard.insert(manager, overrides);
+    }
+}
+
+pub fn homebrew_env_overrides(manager: ManagerId) -> HomebrewEnvOverrides {
+    HOMEBREW_ENV_OVERRIDES
+        .get()
+        .and_then(|slot| {
+            slot.read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .get(&manager)
+                .copied()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(vars: &[(&str, &str)], key: &str) -> bool {
+        vars.iter().any(|(name, _)| *name == key)
+    }
+
+    #[test]
+    fn defaults_disable_auto_update_except_for_explicit_refresh() {
+        let defaults = HomebrewEnvOverrides::default();
+        let listing = homebrew_env_vars(ManagerAction::ListOutdated, &defaults);
+        assert!(has(&listing, "HOMEBREW_NO_AUTO_UPDATE"));
+        assert!(has(&listing, "HOMEBREW_NO_ANALYTICS"));
+        assert!(has(&listing, "HOMEBREW_NO_COLOR"));
+        assert!(has(&listing, "HOMEBREW_NO_ENV_HINTS"));
+
+        let refresh = homebrew_env_vars(ManagerAction::Refresh, &defaults);
+        assert!(!has(&refresh, "HOMEBREW_NO_AUTO_UPDATE"));
+        assert!(has(&refresh, "HOMEBREW_NO_ANALYTICS"));
+    }
+
+    #[test]
+    fn user_overrides_replace_action_defaults() {
+        let overrides = HomebrewEnvOverrides {
+            auto_update: Some(false),
+            analytics: Some(true),
+            color: Some(true),
+        };
+        let refresh = homebrew_env_vars(ManagerAction::Refresh, &overrides);
+        assert!(has(&refresh, "HOMEBREW_NO_AUTO_UPDATE"));
+        assert!(!has(&refresh, "HOMEBREW_NO_ANALYTICS"));
+        assert!(!has(&refresh, "HOMEBREW_NO_COLOR"));
+
+        let install = homebrew_env_vars(
+            ManagerAction::Install,
+            &HomebrewEnvOverrides {
+                auto_update: Some(true),
+                ..HomebrewEnvOverrides::default()
+            },
+        );
+        assert!(!has(&install, "HOMEBREW_NO_AUTO_UPDATE"));
+    }
+
+    #[test]
+    fn overrides_parse_from_stored_settings_for_one_manager() {
+        let setting = |manager, key: &str, value: &str| ManagerEnvSetting {
+            manager,
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let settings = [
+            setting(ManagerId::HomebrewFormula, "auto_update", "true"),
+            setting(ManagerId::HomebrewFormula, "color", "maybe"),
+            setting(ManagerId::HomebrewFormula, "unknown", "true"),
+            setting(ManagerId::HomebrewCask, "analytics", "true"),
+        ];
+        assert_eq!(
+            HomebrewEnvOverrides::from_settings(ManagerId::HomebrewFormula, &settings),
+            HomebrewEnvOverrides {
+                auto_update: Some(true),
+                ..HomebrewEnvOverrides::default()
+            }
+        );
+    }
+}
@@ -1,17 +1,29 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::homebrew::{
-    HomebrewDetectOutput, HomebrewSource, homebrew_cleanup_request, homebrew_config_request,
-    homebrew_detect_request, homebrew_install_request, homebrew_list_installed_request,
-    homebrew_list_outdated_request, homebrew_pin_request, homebrew_search_formulae_request,
-    homebrew_uninstall_request, homebrew_unpin_request, homebrew_upgrade_request,
+    HomebrewDetectOutput, HomebrewSource, homebrew_cellar_request, homebrew_cleanup_request,
+    homebrew_config_request, homebrew_detect_request, homebrew_info_request,
+    homebrew_install_request, homebrew_link_request, homebrew_list_dependencies_request,
+    homebrew_list_files_request, homebrew_list_installed_request, homebrew_list_outdated_request,
+    homebrew_list_requested_request, homebrew_list_taps_request, homebrew_location_request,
+    homebrew_pin_request, homebrew_refresh_outdated_request, homebrew_search_formulae_request,
+    homebrew_tap_request, homebrew_uninstall_request, homebrew_unlink_request,
+    homebrew_unpin_request, homebrew_update_request, homebrew_upgrade_request,
     parse_homebrew_version,
 };
+use crate::adapters::homebrew_cleanup::homebrew_disk_cleanup_request;
+use crate::adapters::homebrew_env::{homebrew_env_overrides, homebrew_env_vars};
+use crate::adapters::homebrew_services::{
+    homebrew_services_action_request, homebrew_services_list_request,
+};
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
-use crate::models::{ManagerId, SearchQuery};
+use crate::models::{
+    CoreError, CoreErrorKind, ManagerAction, ManagerId, SearchQuery, ServiceAction, TaskType,
+};
 
 pub struct ProcessHomebrewSource {
     executor: Arc<dyn ProcessExecutor>,
@@ -71,13 +83,18 @@ impl HomebrewSource for ProcessHomebrewSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
+    fn refresh_outdated_formulae(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_refresh_outdated_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
     fn search_formulae(&self, query: &SearchQuery) -> AdapterResult<String> {
         let request = self.configure_request(homebrew_search_formulae_request(None, query));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
-    fn install_formula(&self, name: &str) -> AdapterResult<String> {
-        let request = self.configure_request(homebrew_install_request(None, name));
+    fn install_formula(&self, name: &str, options: &[String]) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_install_request(None, name, options));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
@@ -86,9 +103,13 @@ impl HomebrewSource for ProcessHomebrewSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
-    fn upgrade_formula(&self, name: Option<&str>) -> AdapterResult<String> {
-        let target = name.unwrap_or("__all__");
-        let request = self.configure_request(homebrew_upgrade_request(None, target));
+    fn update_homebrew(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_update_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn upgrade_formula(&self, name: Option<&str>, options: &[String]) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_upgrade_request(None, name, options));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
@@ -106,20 +127,143 @@ impl HomebrewSource for ProcessHomebrewSource {
         let request = self.configure_request(homebrew_unpin_request(None, name));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
+
+    fn list_formula_files(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_list_files_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn list_formula_dependencies(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_list_dependencies_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn list_requested_formulae(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_list_requested_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn formula_info(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_info_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    // `brew switch` is gone, so point `opt/<name>` at the prior keg and let
+    // `brew link` follow it.
+    fn switch_formula_keg(&self, name: &str, version: &str) -> AdapterResult<String> {
+        let cellar = self.homebrew_location("--cellar")?;
+        let prefix = self.homebrew_location("--prefix")?;
+        let keg = cellar.join(name).join(version);
+        if !keg.is_dir() {
+            return Err(rollback_error(
+                CoreErrorKind::NotInstalled,
+                format!(
+                    "no {name} {version} keg in {}; it may have been removed by cleanup",
+                    cellar.display()
+                ),
+            ));
+        }
+
+        let unlink_request = self.configure_request(homebrew_unlink_request(None, name));
+        let mut output = run_and_collect_stdout(self.executor.as_ref(), unlink_request)?;
+        replace_opt_link(&prefix.join("opt").join(name), &keg)?;
+        let link_request = self.configure_request(homebrew_link_request(None, name));
+        output.push_str(&run_and_collect_stdout(
+            self.executor.as_ref(),
+            link_request,
+        )?);
+        Ok(output)
+    }
+
+    fn list_taps(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_list_taps_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn add_tap(&self, tap: &str, url: Option<&str>) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_tap_request(None, tap, url));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn list_services(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_services_list_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn set_service_state(&self, name: &str, action: ServiceAction) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_services_action_request(None, name, action));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn cleanup(&self, dry_run: bool) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_disk_cleanup_request(None, dry_run));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn cellar(&self) -> AdapterResult<String> {
+        let request = self.configure_request(homebrew_cellar_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
 }
 
 impl ProcessHomebrewSource {
+    fn homebrew_location(&self, flag: &str) -> AdapterResult<PathBuf> {
+        let request = self.configure_request(homebrew_location_request(None, flag));
+        let output = run_and_collect_stdout(self.executor.as_ref(), request)?;
+        let location = output.trim();
+        if location.is_empty() {
+            return Err(rollback_error(
+                CoreErrorKind::ParseFailure,
+                format!("brew {flag} printed no path"),
+            ));
+        }
+        Ok(PathBuf::from(location))
+    }
+
     fn configure_request(&self, mut request: ProcessSpawnRequest) -> ProcessSpawnRequest {
         // XPC services have a stripped down PATH. We must explicitly add Homebrew paths.
         let path = std::env::var("PATH").unwrap_or_default();
         let new_path = format!("/opt/homebrew/bin:/usr/local/bin:{path}");
 
-        request.command = request
-            .command
-            .env("PATH", new_path)
-            .env("HOMEBREW_NO_AUTO_UPDATE", "1")
-            .env("HOMEBREW_NO_INSTALL_CLEANUP", "1")
-            .env("HOMEBREW_NO_ENV_HINTS", "1");
+        request.command = request.command.env("PATH", new_path);
+        let overrides = homebrew_env_overrides(ManagerId::HomebrewFormula);
+        for (key, value) in homebrew_env_vars(request.action, &overrides) {
+            request.command = request.command.env(key, value);
+        }
         request
     }
 }
+
+/// Swap the opt link in one rename so a failed rollback never leaves
+/// `opt/<name>` missing.
+fn replace_opt_link(opt_link: &Path, keg: &Path) -> AdapterResult<()> {
+    let link_name = opt_link
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staged = opt_link.with_file_name(format!(".{link_name}.helm-rollback"));
+    let _ = std::fs::remove_file(&staged);
+    std::os::unix::fs::symlink(keg, &staged)
+        .and_then(|()| std::fs::rename(&staged, opt_link))
+        .map_err(|error| {
+            let _ = std::fs::remove_file(&staged);
+            rollback_error(
+                CoreErrorKind::ProcessFailure,
+                format!(
+                    "failed to point {} at {}: {error}",
+                    opt_link.display(),
+                    keg.display()
+                ),
+            )
+        })
+}
+
+fn rollback_error(kind: CoreErrorKind, message: String) -> CoreError {
+    CoreError {
+        manager: Some(ManagerId::HomebrewFormula),
+        task: Some(TaskType::Upgrade),
+        action: Some(ManagerAction::Rollback),
+        kind,
+        message,
+    }
+}
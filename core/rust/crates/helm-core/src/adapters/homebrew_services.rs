@@ -0,0 +1,189 @@
+//! `brew services`: the launchd-backed background services some formulae
+//! ship (postgres, redis, unbound, ...).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::adapters::homebrew::{HOMEBREW_COMMAND, LIST_TIMEOUT, homebrew_request};
+use crate::adapters::manager::AdapterResult;
+use crate::execution::{CommandSpec, ProcessSpawnRequest};
+use crate::models::{
+    CoreError, CoreErrorKind, ManagerAction, ManagerId, ServiceAction, ServiceRecord, TaskId,
+    TaskType,
+};
+
+/// launchd waits for a stopping service to exit, which can take a while for
+/// databases flushing to disk.
+const SERVICE_STATE_TIMEOUT: Duration = Duration::from_secs(180);
+
+pub fn homebrew_services_list_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListServices,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["services", "list", "--json"]),
+        LIST_TIMEOUT,
+    )
+}
+
+pub fn homebrew_services_action_request(
+    task_id: Option<TaskId>,
+    name: &str,
+    action: ServiceAction,
+) -> ProcessSpawnRequest {
+    homebrew_request(
+        task_id,
+        TaskType::Configure,
+        ManagerAction::SetServiceState,
+        CommandSpec::new(HOMEBREW_COMMAND).args(["services", action.as_str(), name]),
+        SERVICE_STATE_TIMEOUT,
+    )
+}
+
+pub(crate) fn parse_homebrew_services(output: &str) -> AdapterResult<Vec<ServiceRecord>> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let entries: Vec<HomebrewServiceEntry> = serde_json::from_str(trimmed).map_err(|error| {
+        services_error(
+            CoreErrorKind::ParseFailure,
+            ManagerAction::ListServices,
+            format!("invalid Homebrew services JSON: {error}"),
+        )
+    })?;
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let name = entry.name.trim().to_string();
+            if name.is_empty() {
+                return None;
+            }
+            Some(ServiceRecord {
+                name,
+                status: entry.status.unwrap_or_else(|| "unknown".to_string()),
+                user: entry.user.filter(|user| !user.trim().is_empty()),
+                file: entry.file,
+                exit_code: entry.exit_code,
+            })
+        })
+        .collect())
+}
+
+/// Service names are formula names; reject anything `brew services` would
+/// read as a flag or a path.
+pub(crate) fn validate_service_name(name: &str) -> AdapterResult<()> {
+    crate::adapters::validate_package_identifier(
+        ManagerId::HomebrewFormula,
+        ManagerAction::SetServiceState,
+        name,
+    )?;
+    if name.starts_with('-') || name.contains('/') {
+        return Err(services_error(
+            CoreErrorKind::InvalidInput,
+            ManagerAction::SetServiceState,
+            format!("invalid Homebrew service name '{name}'"),
+        ));
+    }
+    Ok(())
+}
+
+fn services_error(kind: CoreErrorKind, action: ManagerAction, message: String) -> CoreError {
+    CoreError {
+        manager: Some(ManagerId::HomebrewFormula),
+        task: None,
+        action: Some(action),
+        kind,
+        message,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HomebrewServiceEntry {
+    name: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    file: Option<PathBuf>,
+    #[serde(default)]
+    exit_code: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::models::{CoreErrorKind, ManagerAction, ServiceAction, TaskType};
+
+    use super::{
+        homebrew_services_action_request, homebrew_services_list_request, parse_homebrew_services,
+        validate_service_name,
+    };
+
+    const SERVICES_FIXTURE: &str = include_str!("../../tests/fixtures/homebrew/services_list.json");
+
+    #[test]
+    fn parses_services_list_fixture() {
+        let services = parse_homebrew_services(SERVICES_FIXTURE).unwrap();
+        assert_eq!(services.len(), 3);
+
+        assert_eq!(services[0].name, "postgresql@16");
+        assert_eq!(services[0].status, "started");
+        assert_eq!(services[0].user.as_deref(), Some("jason"));
+        assert_eq!(
+            services[0].file,
+            Some(PathBuf::from(
+                "/Users/jason/Library/LaunchAgents/homebrew.mxcl.postgresql@16.plist"
+            ))
+        );
+        assert_eq!(services[0].exit_code, Some(0));
+
+        assert_eq!(services[1].name, "redis");
+        assert_eq!(services[1].status, "none");
+        assert_eq!(services[1].user, None);
+        assert_eq!(services[1].file, None);
+
+        assert_eq!(services[2].status, "error");
+        assert_eq!(services[2].exit_code, Some(78));
+    }
+
+    #[test]
+    fn empty_services_output_is_an_empty_list() {
+        assert!(parse_homebrew_services("").unwrap().is_empty());
+        assert!(parse_homebrew_services("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_services_output_is_a_parse_failure() {
+        let error = parse_homebrew_services("Error: unknown command").unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::ParseFailure);
+        assert_eq!(error.action, Some(ManagerAction::ListServices));
+    }
+
+    #[test]
+    fn service_requests_use_brew_services_subcommands() {
+        let list = homebrew_services_list_request(None);
+        assert_eq!(list.action, ManagerAction::ListServices);
+        assert_eq!(list.task_type, TaskType::Refresh);
+        assert_eq!(list.command.args, vec!["services", "list", "--json"]);
+
+        let restart = homebrew_services_action_request(None, "redis", ServiceAction::Restart);
+        assert_eq!(restart.action, ManagerAction::SetServiceState);
+        assert_eq!(restart.task_type, TaskType::Configure);
+        assert_eq!(restart.command.args, vec!["services", "restart", "redis"]);
+    }
+
+    #[test]
+    fn service_names_reject_flags_and_paths() {
+        assert!(validate_service_name("postgresql@16").is_ok());
+        assert!(validate_service_name("--all").is_err());
+        assert!(validate_service_name("../redis").is_err());
+        assert!(validate_service_name(" ").is_err());
+    }
+}
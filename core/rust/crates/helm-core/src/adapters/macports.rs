@@ -8,8 +8,8 @@ use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, PackageRuntimeState, SearchQuery,
-    TaskId, TaskType,
+    ManagerId, OutdatedPackage, PackageCandidate, PackageFiles, PackageRef, PackageRuntimeState,
+    SearchQuery, TaskId, TaskType,
 };
 
 const MACPORTS_CAPABILITIES: &[Capability] = &[
@@ -21,6 +21,7 @@ const MACPORTS_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::ListFiles,
 ];
 
 const MACPORTS_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -92,6 +93,7 @@ pub trait MacPortsSource: Send + Sync {
         version: Option<&str>,
         variants: &[String],
     ) -> AdapterResult<String>;
+    fn contents(&self, port_name: &str) -> AdapterResult<String>;
 }
 
 pub struct MacPortsAdapter<S: MacPortsSource> {
@@ -328,15 +330,13 @@ impl<S: MacPortsSource> ManagerAdapter for MacPortsAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = self.load_installed_packages()?;
-                let outdated = self.load_outdated_packages()?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self.load_installed_packages();
+                let outdated = self.load_outdated_packages();
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let packages = self.load_installed_packages()?;
@@ -390,6 +390,7 @@ impl<S: MacPortsSource> ManagerAdapter for MacPortsAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -424,23 +425,25 @@ impl<S: MacPortsSource> ManagerAdapter for MacPortsAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(target.version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::MacPorts,
-                    name: "__all__".to_string(),
-                });
-                if package.name == "__all__" {
+                let Some(package) = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::MacPorts,
+                    &upgrade_request.target,
+                )?
+                else {
                     let _ = self.source.upgrade(None, None, &[])?;
                     return Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                        package,
+                        package: upgrade_request.target.result_package(ManagerId::MacPorts),
                         package_identifier: None,
                         action: ManagerAction::Upgrade,
                         before_version: None,
                         after_version: None,
+                        disposal: None,
                     }));
-                }
+                };
 
                 let target = self.resolve_installed_target(
                     upgrade_request
@@ -478,8 +481,21 @@ impl<S: MacPortsSource> ManagerAdapter for MacPortsAdapter<S> {
                     action: ManagerAction::Upgrade,
                     before_version: Some(target.version),
                     after_version: candidate_version,
+                    disposal: None,
                 }))
             }
+            AdapterRequest::ListFiles(list_files_request) => {
+                let target = parse_macports_requested_target(
+                    list_files_request.package.name.as_str(),
+                    None,
+                    ManagerAction::ListFiles,
+                )?;
+                let raw = self.source.contents(&target.base_name)?;
+                Ok(AdapterResponse::PackageFiles(PackageFiles::from_listing(
+                    parse_macports_contents(&raw),
+                    list_files_request.limit,
+                )))
+            }
             _ => Err(CoreError {
                 manager: Some(ManagerId::MacPorts),
                 task: None,
@@ -588,6 +604,16 @@ pub fn macports_upgrade_request(
     .requires_elevation(true)
 }
 
+pub fn macports_contents_request(task_id: Option<TaskId>, port_name: &str) -> ProcessSpawnRequest {
+    macports_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListFiles,
+        CommandSpec::new(PORT_COMMAND).args(["contents", port_name]),
+        LIST_TIMEOUT,
+    )
+}
+
 fn macports_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
@@ -683,6 +709,7 @@ fn parse_macports_installed(output: &str) -> AdapterResult<Vec<InstalledPackage>
             installed_version: Some(entry.version),
             pinned: false,
             runtime_state: entry.runtime_state,
+            sizes: Default::default(),
         });
     }
 
@@ -723,6 +750,7 @@ fn parse_macports_outdated(output: &str) -> Vec<OutdatedPackage> {
             pinned: false,
             restart_required: false,
             runtime_state: installed_entry.runtime_state,
+            sizes: Default::default(),
         });
     }
 
@@ -796,6 +824,7 @@ fn parse_macports_search(output: &str, query: &SearchQuery) -> Vec<CachedSearchR
             source_manager: ManagerId::MacPorts,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
         pending_summary = Some(results.len() - 1);
     }
@@ -842,6 +871,17 @@ fn parse_macports_entry(line: &str) -> Option<ParsedMacPortsEntry> {
     })
 }
 
+/// `port contents` prints a `Port <name> contains:` header followed by one
+/// indented absolute path per line.
+fn parse_macports_contents(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .map(str::to_string)
+        .collect()
+}
+
 fn parse_macports_requested_target(
     raw_package_name: &str,
     explicit_version: Option<&str>,
@@ -1061,14 +1101,24 @@ fn versionish_segments(version: &str) -> Vec<&str> {
 fn task_type_for_action(action: ManagerAction) -> TaskType {
     match action {
         ManagerAction::Detect => TaskType::Detection,
-        ManagerAction::Refresh | ManagerAction::ListInstalled | ManagerAction::ListOutdated => {
-            TaskType::Refresh
-        }
+        ManagerAction::Refresh
+        | ManagerAction::ListInstalled
+        | ManagerAction::ListOutdated
+        | ManagerAction::SelfVersionCheck
+        | ManagerAction::ListFiles
+        | ManagerAction::ListDependencies
+        | ManagerAction::ListRequested
+        | ManagerAction::PackageInfo
+        | ManagerAction::ListServices
+        | ManagerAction::PreviewCleanup
+        | ManagerAction::MeasurePackageSizes => TaskType::Refresh,
         ManagerAction::Search => TaskType::Search,
         ManagerAction::Install => TaskType::Install,
         ManagerAction::Uninstall => TaskType::Uninstall,
-        ManagerAction::Upgrade => TaskType::Upgrade,
-        ManagerAction::Configure => TaskType::Configure,
+        ManagerAction::Upgrade | ManagerAction::Rollback => TaskType::Upgrade,
+        ManagerAction::Configure | ManagerAction::SetServiceState | ManagerAction::Cleanup => {
+            TaskType::Configure
+        }
         ManagerAction::Pin => TaskType::Pin,
         ManagerAction::Unpin => TaskType::Unpin,
     }
@@ -1081,11 +1131,11 @@ mod tests {
     use std::time::UNIX_EPOCH;
 
     use crate::adapters::macports::{
-        MacPortsAdapter, MacPortsDetectOutput, MacPortsSource, macports_detect_request,
-        macports_install_request, macports_list_installed_request, macports_list_outdated_request,
-        macports_search_request, macports_uninstall_request, macports_upgrade_request,
-        parse_macports_installed, parse_macports_outdated, parse_macports_requested_target,
-        parse_macports_search, parse_macports_version,
+        MacPortsAdapter, MacPortsDetectOutput, MacPortsSource, macports_contents_request,
+        macports_detect_request, macports_install_request, macports_list_installed_request,
+        macports_list_outdated_request, macports_search_request, macports_uninstall_request,
+        macports_upgrade_request, parse_macports_installed, parse_macports_outdated,
+        parse_macports_requested_target, parse_macports_search, parse_macports_version,
     };
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, InstallRequest,
@@ -1093,6 +1143,7 @@ mod tests {
         UpgradeRequest,
     };
     use crate::models::{ManagerAction, ManagerId, PackageRef, SearchQuery, TaskType};
+    use crate::upgrade_target::UpgradeTarget;
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/macports/version.txt");
     const INSTALLED_FIXTURE: &str = include_str!("../../tests/fixtures/macports/installed.txt");
@@ -1293,6 +1344,30 @@ mod tests {
             panic!("expected search response");
         };
         assert_eq!(results.len(), 1);
+
+        let files = adapter
+            .execute(AdapterRequest::ListFiles(
+                crate::adapters::ListFilesRequest {
+                    package: PackageRef {
+                        manager: ManagerId::MacPorts,
+                        name: "git@2.49.0_0+credential_osxkeychain".to_string(),
+                    },
+                    limit: None,
+                },
+            ))
+            .unwrap();
+        let AdapterResponse::PackageFiles(files) = files else {
+            panic!("expected package files response");
+        };
+        assert_eq!(
+            files.files,
+            vec!["/opt/local/bin/git", "/opt/local/share/man/man1/git.1.gz"]
+        );
+        assert!(!files.truncated());
+        assert_eq!(
+            macports_contents_request(None, "git").command.args,
+            vec!["contents", "git"]
+        );
     }
 
     #[test]
@@ -1319,6 +1394,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("2.49.0_0".to_string()),
+                disposal: Default::default(),
             }))
             .expect_err("ambiguous uninstall should fail");
         assert!(
@@ -1356,6 +1432,7 @@ mod tests {
                 },
                 target_name: Some("git+credential_osxkeychain".to_string()),
                 version: Some("2.49.0_0".to_string()),
+                options: Vec::new(),
             }))
             .unwrap();
         let AdapterResponse::Mutation(install) = install else {
@@ -1372,6 +1449,7 @@ mod tests {
                 },
                 target_name: Some("git+credential_osxkeychain".to_string()),
                 version: Some("2.49.0_0".to_string()),
+                disposal: Default::default(),
             }))
             .unwrap();
         let AdapterResponse::Mutation(uninstall) = uninstall else {
@@ -1382,12 +1460,14 @@ mod tests {
 
         let upgrade = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::MacPorts,
                     name: "git+credential_osxkeychain".to_string(),
                 }),
                 target_name: Some("git+credential_osxkeychain".to_string()),
                 version: Some("2.49.0_0".to_string()),
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         let AdapterResponse::Mutation(upgrade) = upgrade else {
@@ -1454,5 +1534,12 @@ mod tests {
         ) -> AdapterResult<String> {
             self.upgrade_result.clone()
         }
+
+        fn contents(&self, _port_name: &str) -> AdapterResult<String> {
+            Ok(
+                "Port git contains:\n  /opt/local/bin/git\n  /opt/local/share/man/man1/git.1.gz\n"
+                    .to_string(),
+            )
+        }
     }
 }
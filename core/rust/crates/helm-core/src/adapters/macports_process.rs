@@ -2,9 +2,9 @@ use std::sync::Arc;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::macports::{
-    MacPortsDetectOutput, MacPortsSource, macports_detect_request, macports_install_request,
-    macports_list_installed_request, macports_list_outdated_request, macports_search_request,
-    macports_uninstall_request, macports_upgrade_request,
+    MacPortsDetectOutput, MacPortsSource, macports_contents_request, macports_detect_request,
+    macports_install_request, macports_list_installed_request, macports_list_outdated_request,
+    macports_search_request, macports_uninstall_request, macports_upgrade_request,
 };
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
@@ -160,4 +160,9 @@ impl MacPortsSource for ProcessMacPortsSource {
             self.configure_request(macports_upgrade_request(None, port_name, version, variants));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
+
+    fn contents(&self, port_name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(macports_contents_request(None, port_name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
 }
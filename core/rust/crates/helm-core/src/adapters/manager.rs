@@ -1,8 +1,12 @@
 use crate::models::{
-    ActionSafety, CachedSearchResult, CoreError, CoreErrorKind, DetectionInfo, InstalledPackage,
-    ManagerAction, ManagerDescriptor, ManagerId, OutdatedPackage, PackageRef, SearchQuery,
+    ActionSafety, CachedSearchResult, CleanupReport, CoreError, CoreErrorKind, DetectionInfo,
+    InstalledPackage, InstalledPackageSize, ManagerAction, ManagerDescriptor, ManagerId,
+    ManagerSelfVersion, OutdatedPackage, PackageDependency, PackageFiles, PackageMetadata,
+    PackageRef, SearchQuery, ServiceAction, ServiceRecord, UninstallDisposal,
 };
+use crate::upgrade_target::UpgradeTarget;
 use std::path::PathBuf;
+use std::time::Duration;
 
 pub type AdapterResult<T> = Result<T, CoreError>;
 
@@ -18,6 +22,54 @@ pub struct ListInstalledRequest;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ListOutdatedRequest;
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelfVersionCheckRequest;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListFilesRequest {
+    pub package: PackageRef,
+    /// Upper bound on returned paths; the response still reports the full count.
+    pub limit: Option<usize>,
+}
+
+/// Request the manager's whole installed dependency graph.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListDependenciesRequest;
+
+/// Request the installed packages the user asked for, as opposed to ones
+/// pulled in as dependencies.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListRequestedRequest;
+
+/// Request descriptive metadata for one package, installed or not.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackageInfoRequest {
+    pub package: PackageRef,
+}
+
+/// Request the background services the manager's packages provide.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListServicesRequest;
+
+/// Start, stop, or restart one background service by name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetServiceStateRequest {
+    pub service: String,
+    pub action: ServiceAction,
+}
+
+/// Measure the disk space a cleanup would reclaim, without removing anything.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreviewCleanupRequest;
+
+/// Remove the manager's caches and stale downloads.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CleanupRequest;
+
+/// Measure the disk space each installed package occupies.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeasurePackageSizesRequest;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SearchRequest {
     pub query: SearchQuery,
@@ -28,6 +80,8 @@ pub struct InstallRequest {
     pub package: PackageRef,
     pub target_name: Option<String>,
     pub version: Option<String>,
+    /// Manager-specific install flags, validated against `install_options` allowlists.
+    pub options: Vec<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -35,13 +89,24 @@ pub struct UninstallRequest {
     pub package: PackageRef,
     pub target_name: Option<String>,
     pub version: Option<String>,
+    /// Whether app bundles are deleted or moved to the Trash; see
+    /// `UninstallDisposal::supported_by`.
+    pub disposal: UninstallDisposal,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UpgradeRequest {
-    pub package: Option<PackageRef>,
+    pub target: UpgradeTarget,
     pub target_name: Option<String>,
     pub version: Option<String>,
+    /// Install flags carried forward from the original install (see `install_options`).
+    /// For confirmed `softwareupdate` upgrades, the update labels held back by pins;
+    /// for manager-wide npm, pipx, and cargo upgrades, the package names held
+    /// back by virtual pins (see `pin_enforcement`).
+    pub options: Vec<String>,
+    /// Remove superseded versions after a successful targeted upgrade
+    /// (Homebrew formula keg cleanup); ignored by other managers.
+    pub cleanup_old_kegs: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -55,10 +120,20 @@ pub struct UnpinRequest {
     pub package: PackageRef,
 }
 
+/// Switch an installed package back to `version`, the version it had before
+/// its last upgrade.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RollbackRequest {
+    pub package: PackageRef,
+    pub version: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PackageDetailChildKind {
     Component,
     Target,
+    /// A Homebrew tap (`user/repo`), added before installing from it.
+    Tap,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -71,6 +146,12 @@ pub enum PackageDetailOperation {
         kind: PackageDetailChildKind,
         value: String,
     },
+    /// Add a Homebrew tap, cloned from `url` instead of its default GitHub
+    /// repository when one is given.
+    AddTap {
+        tap: String,
+        url: Option<String>,
+    },
     SetDefault,
     SetPathOverride {
         path: PathBuf,
@@ -103,6 +184,17 @@ pub enum AdapterRequest {
     ConfigurePackageDetail(PackageDetailRequest),
     Pin(PinRequest),
     Unpin(UnpinRequest),
+    SelfVersionCheck(SelfVersionCheckRequest),
+    ListFiles(ListFilesRequest),
+    ListDependencies(ListDependenciesRequest),
+    ListRequested(ListRequestedRequest),
+    Rollback(RollbackRequest),
+    PackageInfo(PackageInfoRequest),
+    ListServices(ListServicesRequest),
+    SetServiceState(SetServiceStateRequest),
+    PreviewCleanup(PreviewCleanupRequest),
+    Cleanup(CleanupRequest),
+    MeasurePackageSizes(MeasurePackageSizesRequest),
 }
 
 impl AdapterRequest {
@@ -119,8 +211,102 @@ impl AdapterRequest {
             Self::ConfigurePackageDetail(_) => ManagerAction::Configure,
             Self::Pin(_) => ManagerAction::Pin,
             Self::Unpin(_) => ManagerAction::Unpin,
+            Self::SelfVersionCheck(_) => ManagerAction::SelfVersionCheck,
+            Self::ListFiles(_) => ManagerAction::ListFiles,
+            Self::ListDependencies(_) => ManagerAction::ListDependencies,
+            Self::ListRequested(_) => ManagerAction::ListRequested,
+            Self::Rollback(_) => ManagerAction::Rollback,
+            Self::PackageInfo(_) => ManagerAction::PackageInfo,
+            Self::ListServices(_) => ManagerAction::ListServices,
+            Self::SetServiceState(_) => ManagerAction::SetServiceState,
+            Self::PreviewCleanup(_) => ManagerAction::PreviewCleanup,
+            Self::Cleanup(_) => ManagerAction::Cleanup,
+            Self::MeasurePackageSizes(_) => ManagerAction::MeasurePackageSizes,
         }
     }
+
+    /// Whether this request reaches `manager`'s registry, per the registry's
+    /// declared network capabilities. Offline mode refuses these.
+    pub fn requires_network(&self, manager: ManagerId) -> bool {
+        crate::reachability::action_requires_network(manager, self.action())
+    }
+}
+
+/// Resolve the target of an adapter that upgrades one package or every
+/// outdated package; `None` means every package. Other targets are rejected.
+pub fn package_or_all_upgrade_target(
+    manager: ManagerId,
+    target: &UpgradeTarget,
+) -> AdapterResult<Option<&PackageRef>> {
+    match target {
+        UpgradeTarget::Package(package) => Ok(Some(package)),
+        UpgradeTarget::All => Ok(None),
+        other => Err(unsupported_upgrade_target_error(manager, other)),
+    }
+}
+
+/// Upgrade `outdated` packages one at a time, skipping the names in `held`.
+/// Manager-wide upgrades use this instead of the manager's own upgrade-all
+/// when their `options` carry a virtual-pin hold list.
+pub fn upgrade_outdated_except_held(
+    outdated: &[OutdatedPackage],
+    held: &[String],
+    mut upgrade: impl FnMut(&str) -> AdapterResult<String>,
+) -> AdapterResult<()> {
+    for package in outdated
+        .iter()
+        .filter(|package| !held.contains(&package.package.name))
+    {
+        upgrade(&package.package.name)?;
+    }
+    Ok(())
+}
+
+/// Report a cleanup for a manager whose reclaimable space is a cache
+/// directory: a preview measures `roots`; a cleanup runs `clean` and reports
+/// how much the roots shrank.
+pub fn cache_directory_cleanup(
+    dry_run: bool,
+    roots: &[PathBuf],
+    clean: impl FnOnce() -> AdapterResult<()>,
+) -> AdapterResult<AdapterResponse> {
+    let before = crate::manager_stats::directory_size_bytes(roots).unwrap_or(0);
+    if dry_run {
+        return Ok(AdapterResponse::Cleanup(CleanupReport { bytes: before }));
+    }
+    clean()?;
+    let after = crate::manager_stats::directory_size_bytes(roots).unwrap_or(0);
+    Ok(AdapterResponse::Cleanup(CleanupReport {
+        bytes: before.saturating_sub(after),
+    }))
+}
+
+/// Size each package by the directories it occupies. Packages whose
+/// directories are all missing are left out rather than reported as empty.
+pub fn measure_package_directories(
+    packages: impl IntoIterator<Item = (PackageRef, Vec<PathBuf>)>,
+) -> Vec<InstalledPackageSize> {
+    packages
+        .into_iter()
+        .filter_map(|(package, roots)| {
+            crate::manager_stats::directory_size_bytes(&roots)
+                .map(|bytes| InstalledPackageSize { package, bytes })
+        })
+        .collect()
+}
+
+pub fn unsupported_upgrade_target_error(manager: ManagerId, target: &UpgradeTarget) -> CoreError {
+    CoreError {
+        manager: Some(manager),
+        task: None,
+        action: Some(ManagerAction::Upgrade),
+        kind: CoreErrorKind::InvalidInput,
+        message: format!(
+            "{} does not support '{}' upgrades",
+            manager.as_str(),
+            target.kind().as_str()
+        ),
+    }
 }
 
 pub fn ensure_action_supported(
@@ -148,7 +334,23 @@ pub fn ensure_request_supported(
     descriptor: &ManagerDescriptor,
     request: &AdapterRequest,
 ) -> AdapterResult<()> {
-    ensure_action_supported(descriptor, request.action())
+    ensure_action_supported(descriptor, request.action())?;
+    if let AdapterRequest::Uninstall(uninstall) = request
+        && !uninstall.disposal.supported_by(descriptor.id)
+    {
+        return Err(CoreError {
+            manager: Some(descriptor.id),
+            task: None,
+            action: Some(ManagerAction::Uninstall),
+            kind: CoreErrorKind::UnsupportedCapability,
+            message: format!(
+                "manager '{}' does not support '{}' uninstall disposal",
+                descriptor.display_name,
+                uninstall.disposal.as_str()
+            ),
+        });
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -158,6 +360,8 @@ pub struct MutationResult {
     pub action: ManagerAction,
     pub before_version: Option<String>,
     pub after_version: Option<String>,
+    /// Disposal an uninstall actually applied; `None` for other actions.
+    pub disposal: Option<UninstallDisposal>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -169,9 +373,25 @@ pub enum AdapterResponse {
     SnapshotSync {
         installed: Option<Vec<InstalledPackage>>,
         outdated: Option<Vec<OutdatedPackage>>,
+        /// Stages that failed while the rest of the refresh succeeded, each
+        /// tagged with the stage's action (`ListInstalled` or `ListOutdated`).
+        /// The matching snapshot is left as it was.
+        stage_failures: Vec<CoreError>,
     },
     SearchResults(Vec<CachedSearchResult>),
     Mutation(MutationResult),
+    SelfVersion(ManagerSelfVersion),
+    PackageFiles(PackageFiles),
+    Dependencies(Vec<PackageDependency>),
+    /// Names of installed packages installed on request.
+    RequestedPackages(Vec<String>),
+    PackageInfo(PackageMetadata),
+    /// Every service the manager knows about, after any requested state change.
+    Services(Vec<ServiceRecord>),
+    /// Bytes a `PreviewCleanup` would free, or a `Cleanup` freed.
+    Cleanup(CleanupReport),
+    /// Disk space each installed package occupies.
+    PackageSizes(Vec<InstalledPackageSize>),
 }
 
 pub trait ManagerAdapter: Send + Sync {
@@ -179,6 +399,12 @@ pub trait ManagerAdapter: Send + Sync {
 
     fn action_safety(&self, action: ManagerAction) -> ActionSafety;
 
+    /// Rough wall-clock duration of `action` for one package, before any
+    /// refinement from this machine's task history.
+    fn estimated_duration(&self, action: ManagerAction) -> Option<Duration> {
+        crate::duration_estimates::default_action_duration(self.descriptor().id, action)
+    }
+
     fn execute(&self, request: AdapterRequest) -> AdapterResult<AdapterResponse>;
 }
 
@@ -187,5 +413,15 @@ pub fn execute_with_capability_check(
     request: AdapterRequest,
 ) -> AdapterResult<AdapterResponse> {
     ensure_request_supported(adapter.descriptor(), &request)?;
-    adapter.execute(request)
+    let disposal = match &request {
+        AdapterRequest::Uninstall(uninstall) => Some(uninstall.disposal),
+        _ => None,
+    };
+    let mut response = adapter.execute(request)?;
+    if let AdapterResponse::Mutation(mutation) = &mut response
+        && mutation.disposal.is_none()
+    {
+        mutation.disposal = disposal;
+    }
+    Ok(response)
 }
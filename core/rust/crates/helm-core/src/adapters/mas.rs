@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::process_utils::last_percent;
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
@@ -29,7 +30,6 @@ const MAS_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
 };
 
 const MAS_COMMAND: &str = "mas";
-const ALL_PACKAGES_TARGET: &str = "__all__";
 const DETECT_TIMEOUT: Duration = Duration::from_secs(10);
 const LIST_TIMEOUT: Duration = Duration::from_secs(60);
 const SEARCH_TIMEOUT: Duration = Duration::from_secs(120);
@@ -91,15 +91,19 @@ impl<S: MasSource> ManagerAdapter for MasAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_mas_list(&self.source.list_installed()?)?;
-                let outdated = parse_mas_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_mas_list(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_mas_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -142,6 +146,7 @@ impl<S: MasSource> ManagerAdapter for MasAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -174,6 +179,7 @@ impl<S: MasSource> ManagerAdapter for MasAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
@@ -181,20 +187,18 @@ impl<S: MasSource> ManagerAdapter for MasAdapter<S> {
                     ManagerAction::Upgrade,
                     upgrade_request.version.as_deref(),
                 )?;
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Mas,
-                    name: ALL_PACKAGES_TARGET.to_string(),
-                });
-                let target = if package.name == ALL_PACKAGES_TARGET {
-                    None
-                } else {
-                    Some(upgrade_request.target_name.clone().unwrap_or(
+                let target = match crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Mas,
+                    &upgrade_request.target,
+                )? {
+                    Some(package) => Some(upgrade_request.target_name.clone().unwrap_or(
                         resolve_mas_installed_target(
                             &self.source,
                             package.name.as_str(),
                             ManagerAction::Upgrade,
                         )?,
-                    ))
+                    )),
+                    None => None,
                 };
                 let targeted_outdated = target
                     .as_deref()
@@ -206,13 +210,14 @@ impl<S: MasSource> ManagerAdapter for MasAdapter<S> {
                     ensure_mas_no_longer_outdated(&self.source, app_id)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Mas),
                     package_identifier: target,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|entry| entry.installed_version.clone()),
                     after_version: targeted_outdated.and_then(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -274,6 +279,7 @@ pub fn mas_install_request(task_id: Option<TaskId>, app_id: &str) -> ProcessSpaw
         CommandSpec::new(MAS_COMMAND).args(["install", app_id]),
         MUTATION_TIMEOUT,
     )
+    .progress_parser(mas_download_progress)
 }
 
 pub fn mas_get_request(task_id: Option<TaskId>, app_id: &str) -> ProcessSpawnRequest {
@@ -284,6 +290,7 @@ pub fn mas_get_request(task_id: Option<TaskId>, app_id: &str) -> ProcessSpawnReq
         CommandSpec::new(MAS_COMMAND).args(["get", app_id]),
         MUTATION_TIMEOUT,
     )
+    .progress_parser(mas_download_progress)
 }
 
 pub fn mas_uninstall_request(task_id: Option<TaskId>, app_id: &str) -> ProcessSpawnRequest {
@@ -309,6 +316,36 @@ pub fn mas_upgrade_request(task_id: Option<TaskId>, app_id: Option<&str>) -> Pro
         command,
         MUTATION_TIMEOUT,
     )
+    .progress_parser(mas_download_progress)
+}
+
+/// mas draws a `[#####-----] 45.0% Downloading` bar per app.
+fn mas_download_progress(line: &str) -> Option<u8> {
+    if !line.starts_with('[') {
+        return None;
+    }
+    last_percent(line)
+}
+
+/// Whether mas output says no Apple Account is signed in to the App Store,
+/// which blocks every install and upgrade.
+pub(crate) fn mentions_mas_not_signed_in(text: &str) -> bool {
+    let normalized = text.to_ascii_lowercase();
+    normalized.contains("not signed in")
+        || normalized.contains("no apple account")
+        || normalized.contains("sign in to the app store")
+}
+
+/// Replace an opaque process failure caused by a missing App Store sign-in
+/// with an actionable message; other errors pass through unchanged.
+pub(crate) fn map_mas_sign_in_error(error: CoreError) -> CoreError {
+    if error.kind != CoreErrorKind::ProcessFailure || !mentions_mas_not_signed_in(&error.message) {
+        return error;
+    }
+    CoreError {
+        message: "mas requires an Apple Account signed in to the App Store; open the App Store, sign in, then retry".to_string(),
+        ..error
+    }
 }
 
 fn mas_request(
@@ -361,6 +398,7 @@ fn parse_mas_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -388,6 +426,7 @@ fn parse_mas_search(output: &str, query: &SearchQuery) -> AdapterResult<Vec<Cach
             source_manager: ManagerId::Mas,
             originating_query: normalized_query.to_string(),
             cached_at: SystemTime::now(),
+            popularity: Default::default(),
         })
         .collect())
 }
@@ -434,6 +473,7 @@ fn installed_package_from_entry(entry: MasEntry) -> AdapterResult<InstalledPacka
         installed_version: entry.installed_version.or(entry.candidate_version),
         pinned: false,
         runtime_state: Default::default(),
+        sizes: Default::default(),
     })
 }
 
@@ -630,15 +670,32 @@ mod tests {
         UpgradeRequest,
     };
     use crate::models::{
-        CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType,
+        CoreError, CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId,
+        TaskType,
     };
+    use crate::upgrade_target::UpgradeTarget;
 
     use super::{
-        MasAdapter, MasDetectOutput, MasSource, mas_detect_request, mas_get_request,
-        mas_install_request, mas_search_request, mas_uninstall_request, mas_upgrade_request,
-        parse_mas_list, parse_mas_outdated, parse_mas_search, parse_mas_version,
+        MasAdapter, MasDetectOutput, MasSource, map_mas_sign_in_error, mas_detect_request,
+        mas_download_progress, mas_get_request, mas_install_request, mas_search_request,
+        mas_uninstall_request, mas_upgrade_request, parse_mas_list, parse_mas_outdated,
+        parse_mas_search, parse_mas_version,
     };
 
+    #[test]
+    fn download_progress_reads_mas_progress_bars() {
+        assert_eq!(
+            mas_download_progress("[##########----------] 50.0% Downloading"),
+            Some(50)
+        );
+        assert_eq!(mas_download_progress("==> Downloading Xcode"), None);
+        assert!(
+            mas_upgrade_request(None, Some("497799835"))
+                .progress_parser
+                .is_some()
+        );
+    }
+
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/mas/version.txt");
     const LIST_FIXTURE: &str = include_str!("../../tests/fixtures/mas/list.txt");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/mas/outdated.txt");
@@ -647,6 +704,29 @@ mod tests {
     const SEARCH_AMBIGUOUS_FIXTURE: &str =
         "100000001 Sample App (1.0)\n100000002 Sample App (2.0)\n";
 
+    #[test]
+    fn sign_in_failures_map_to_an_actionable_error() {
+        let error = map_mas_sign_in_error(CoreError {
+            manager: Some(ManagerId::Mas),
+            task: Some(TaskType::Upgrade),
+            action: Some(ManagerAction::Upgrade),
+            kind: CoreErrorKind::ProcessFailure,
+            message: "process exited with code 1: Error: Not signed in".to_string(),
+        });
+        assert_eq!(error.kind, CoreErrorKind::ProcessFailure);
+        assert_eq!(error.action, Some(ManagerAction::Upgrade));
+        assert!(error.message.contains("sign in"));
+
+        let timeout = CoreError {
+            manager: Some(ManagerId::Mas),
+            task: Some(TaskType::Upgrade),
+            action: Some(ManagerAction::Upgrade),
+            kind: CoreErrorKind::Timeout,
+            message: "timed out".to_string(),
+        };
+        assert_eq!(map_mas_sign_in_error(timeout.clone()), timeout);
+    }
+
     #[test]
     fn parses_mas_version_from_output() {
         let version = parse_mas_version("1.8.7\n");
@@ -754,6 +834,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            options: Vec::new(),
         }));
 
         assert!(matches!(result, Ok(AdapterResponse::Mutation(_))));
@@ -776,6 +857,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap_err();
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
@@ -793,6 +875,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }));
 
         assert!(matches!(result, Ok(AdapterResponse::Mutation(_))));
@@ -805,20 +888,24 @@ mod tests {
         let adapter = MasAdapter::new(source.clone());
 
         let all = adapter.execute(AdapterRequest::Upgrade(UpgradeRequest {
-            package: None,
+            target: UpgradeTarget::All,
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }));
         assert!(matches!(all, Ok(AdapterResponse::Mutation(_))));
         assert_eq!(source.upgraded_target(), Some("__all__".to_string()));
 
         let targeted = adapter.execute(AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(PackageRef {
+            target: UpgradeTarget::Package(PackageRef {
                 manager: ManagerId::Mas,
                 name: "Xcode".to_string(),
             }),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }));
         assert!(matches!(targeted, Ok(AdapterResponse::Mutation(_))));
         assert_eq!(source.upgraded_target(), Some("497799835".to_string()));
@@ -837,6 +924,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("16.2".to_string()),
+                options: Vec::new(),
             }))
             .unwrap_err();
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
@@ -3,9 +3,9 @@ use std::sync::Arc;
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::mas::{
-    MasDetectOutput, MasSource, mas_detect_request, mas_get_request, mas_install_request,
-    mas_list_installed_request, mas_list_outdated_request, mas_search_request,
-    mas_uninstall_request, mas_upgrade_request,
+    MasDetectOutput, MasSource, map_mas_sign_in_error, mas_detect_request, mas_get_request,
+    mas_install_request, mas_list_installed_request, mas_list_outdated_request, mas_search_request,
+    mas_uninstall_request, mas_upgrade_request, mentions_mas_not_signed_in,
 };
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
@@ -83,6 +83,10 @@ impl MasSource for ProcessMasSource {
         let install_request = self.configure_request(mas_install_request(None, app_id));
         match run_and_collect_stdout(self.executor.as_ref(), install_request) {
             Ok(output) => Ok(output),
+            // `mas get` needs the same sign-in, so there is nothing to fall back to.
+            Err(install_error) if mentions_mas_not_signed_in(&install_error.message) => {
+                Err(map_mas_sign_in_error(install_error))
+            }
             Err(install_error) => {
                 let get_request = self.configure_request(mas_get_request(None, app_id));
                 match run_and_collect_stdout(self.executor.as_ref(), get_request) {
@@ -109,6 +113,6 @@ impl MasSource for ProcessMasSource {
 
     fn upgrade(&self, app_id: Option<&str>) -> AdapterResult<String> {
         let request = self.configure_request(mas_upgrade_request(None, app_id));
-        run_and_collect_stdout(self.executor.as_ref(), request)
+        run_and_collect_stdout(self.executor.as_ref(), request).map_err(map_mas_sign_in_error)
     }
 }
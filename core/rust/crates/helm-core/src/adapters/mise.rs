@@ -7,7 +7,7 @@ use serde::Deserialize;
 use serde_json::Value;
 
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
-use crate::execution::{CommandSpec, ProcessSpawnRequest};
+use crate::execution::{CommandSpec, ProcessSpawnRequest, ScriptInstallerStep};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
@@ -74,7 +74,7 @@ pub trait MiseSource: Send + Sync {
     fn install_tool(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn uninstall_tool(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn self_uninstall(&self, mode: MiseUninstallMode) -> AdapterResult<String>;
-    fn upgrade_tool(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
+    fn upgrade_tool(&self, name: Option<&str>, version: Option<&str>) -> AdapterResult<String>;
 }
 
 pub struct MiseAdapter<S: MiseSource> {
@@ -235,17 +235,22 @@ impl<S: MiseSource> ManagerAdapter for MiseAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = self.load_installed_packages()?;
-                let raw = self.source.list_outdated()?;
-                let mut outdated = parse_mise_outdated(&raw)?;
-                hydrate_mise_outdated_runtime_state(&mut outdated, installed.as_slice());
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self.load_installed_packages();
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_mise_outdated(&raw))
+                    .map(|mut outdated| {
+                        if let Ok(installed) = installed.as_ref() {
+                            hydrate_mise_outdated_runtime_state(&mut outdated, installed);
+                        }
+                        outdated
+                    });
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -277,6 +282,7 @@ impl<S: MiseSource> ManagerAdapter for MiseAdapter<S> {
                         action: ManagerAction::Install,
                         before_version: None,
                         after_version: None,
+                        disposal: None,
                     }));
                 }
 
@@ -302,6 +308,7 @@ impl<S: MiseSource> ManagerAdapter for MiseAdapter<S> {
                     action: ManagerAction::Install,
                     before_version: None,
                     after_version: requested_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -346,6 +353,7 @@ impl<S: MiseSource> ManagerAdapter for MiseAdapter<S> {
                         action: ManagerAction::Uninstall,
                         before_version: None,
                         after_version: None,
+                        disposal: None,
                     }));
                 }
 
@@ -385,34 +393,41 @@ impl<S: MiseSource> ManagerAdapter for MiseAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(target.version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Mise,
-                    name: "__all__".to_string(),
-                });
-                let (tool_name, requested_version) = if package.name == "__all__" {
-                    ("__all__".to_string(), None)
-                } else {
-                    parse_package_target(
-                        package.name.as_str(),
-                        upgrade_request.version.as_deref(),
-                        ManagerAction::Upgrade,
-                    )?
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Mise,
+                    &upgrade_request.target,
+                )?;
+                let (tool_name, requested_version) = match target_package {
+                    Some(package) => {
+                        let (tool_name, requested_version) = parse_package_target(
+                            package.name.as_str(),
+                            upgrade_request.version.as_deref(),
+                            ManagerAction::Upgrade,
+                        )?;
+                        (Some(tool_name), requested_version)
+                    }
+                    None => (None, None),
                 };
                 let _ = self
                     .source
-                    .upgrade_tool(tool_name.as_str(), requested_version.as_deref())?;
+                    .upgrade_tool(tool_name.as_deref(), requested_version.as_deref())?;
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package: PackageRef {
-                        manager: package.manager,
-                        name: tool_name,
+                    package: match tool_name {
+                        Some(name) => PackageRef {
+                            manager: ManagerId::Mise,
+                            name,
+                        },
+                        None => upgrade_request.target.result_package(ManagerId::Mise),
                     },
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -476,11 +491,10 @@ pub fn mise_registry_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
     )
 }
 
-pub fn mise_upgrade_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
-    let command = if name == "__all__" {
-        CommandSpec::new(MISE_COMMAND).arg("upgrade")
-    } else {
-        CommandSpec::new(MISE_COMMAND).args(["upgrade", name])
+pub fn mise_upgrade_request(task_id: Option<TaskId>, name: Option<&str>) -> ProcessSpawnRequest {
+    let command = match name {
+        Some(name) => CommandSpec::new(MISE_COMMAND).args(["upgrade", name]),
+        None => CommandSpec::new(MISE_COMMAND).arg("upgrade"),
     };
     mise_request(
         task_id,
@@ -491,6 +505,12 @@ pub fn mise_upgrade_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawn
     )
 }
 
+/// SHA-256 of the `https://mise.run` install script this adapter is reviewed
+/// against. The downloaded script must match it before Helm runs it, so this
+/// is bumped together with any upstream change to the script.
+pub const MISE_INSTALL_SCRIPT_SHA256: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
 pub fn mise_download_install_script_request(
     task_id: Option<TaskId>,
     output_script: &str,
@@ -502,6 +522,7 @@ pub fn mise_download_install_script_request(
         CommandSpec::new("curl").args(["-fsSL", "https://mise.run", "-o", output_script]),
         INSTALL_TIMEOUT,
     )
+    .script_installer(ScriptInstallerStep::Download)
 }
 
 pub fn mise_run_downloaded_install_script_request(
@@ -785,6 +806,7 @@ fn parse_mise_installed_with_home(
                     entry.source.as_ref(),
                     home_dir,
                 ),
+                sizes: Default::default(),
             });
         }
     }
@@ -880,6 +902,7 @@ fn parse_mise_outdated(json: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         })
         .collect();
 
@@ -923,16 +946,25 @@ fn hydrate_mise_outdated_runtime_state(
 fn task_type_for_action(action: ManagerAction) -> TaskType {
     match action {
         ManagerAction::Detect => TaskType::Detection,
-        ManagerAction::Refresh | ManagerAction::ListInstalled | ManagerAction::ListOutdated => {
-            TaskType::Refresh
-        }
+        ManagerAction::Refresh
+        | ManagerAction::ListInstalled
+        | ManagerAction::ListOutdated
+        | ManagerAction::SelfVersionCheck
+        | ManagerAction::ListFiles
+        | ManagerAction::ListDependencies
+        | ManagerAction::ListRequested
+        | ManagerAction::PackageInfo
+        | ManagerAction::ListServices
+        | ManagerAction::PreviewCleanup
+        | ManagerAction::MeasurePackageSizes => TaskType::Refresh,
         ManagerAction::Search => TaskType::Search,
         ManagerAction::Install => TaskType::Install,
         ManagerAction::Uninstall => TaskType::Uninstall,
-        ManagerAction::Upgrade => TaskType::Upgrade,
+        ManagerAction::Upgrade | ManagerAction::Rollback => TaskType::Upgrade,
         ManagerAction::Pin => TaskType::Pin,
         ManagerAction::Unpin => TaskType::Unpin,
         ManagerAction::Configure => TaskType::Refresh,
+        ManagerAction::SetServiceState | ManagerAction::Cleanup => TaskType::Configure,
     }
 }
 
@@ -1261,6 +1293,7 @@ fn filter_mise_remote_packages(
             source_manager: ManagerId::Mise,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -1308,6 +1341,7 @@ mod tests {
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
         ListOutdatedRequest, ManagerAdapter, SearchRequest,
     };
+    use crate::execution::ScriptInstallerStep;
     use crate::models::{CoreErrorKind, ManagerAction, ManagerId, SearchQuery, TaskId, TaskType};
 
     use super::{
@@ -1730,6 +1764,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("scriptInstaller:officialDownload".to_string()),
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -1758,6 +1793,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap();
         let AdapterResponse::Mutation(mutation) = result else {
@@ -1803,6 +1839,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .unwrap();
@@ -1823,6 +1860,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .expect("tool uninstall should succeed");
@@ -1854,12 +1892,14 @@ mod tests {
 
         let result = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
                     manager: ManagerId::Mise,
                     name: "node".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -1918,7 +1958,7 @@ mod tests {
 
     #[test]
     fn upgrade_command_spec_uses_structured_args() {
-        let package_upgrade = mise_upgrade_request(Some(TaskId(99)), "node");
+        let package_upgrade = mise_upgrade_request(Some(TaskId(99)), Some("node"));
         assert_eq!(package_upgrade.task_id, Some(TaskId(99)));
         assert_eq!(
             package_upgrade.command.args,
@@ -1927,7 +1967,7 @@ mod tests {
         assert_eq!(package_upgrade.task_type, TaskType::Upgrade);
         assert_eq!(package_upgrade.action, ManagerAction::Upgrade);
 
-        let all_upgrade = mise_upgrade_request(None, "__all__");
+        let all_upgrade = mise_upgrade_request(None, None);
         assert_eq!(all_upgrade.command.args, vec!["upgrade".to_string()]);
         assert_eq!(all_upgrade.task_type, TaskType::Upgrade);
         assert_eq!(all_upgrade.action, ManagerAction::Upgrade);
@@ -1947,6 +1987,10 @@ mod tests {
                 "/tmp/mise.sh".to_string()
             ]
         );
+        assert_eq!(
+            download.script_installer,
+            Some(ScriptInstallerStep::Download)
+        );
 
         let run = mise_run_downloaded_install_script_request(None, "/tmp/mise.sh");
         assert_eq!(run.task_type, TaskType::Install);
@@ -2140,7 +2184,11 @@ mod tests {
             Ok(String::new())
         }
 
-        fn upgrade_tool(&self, _name: &str, _version: Option<&str>) -> AdapterResult<String> {
+        fn upgrade_tool(
+            &self,
+            _name: Option<&str>,
+            _version: Option<&str>,
+        ) -> AdapterResult<String> {
             Ok(String::new())
         }
     }
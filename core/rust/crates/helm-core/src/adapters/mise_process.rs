@@ -6,15 +6,17 @@ use std::time::{Duration, Instant};
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::mise::{
-    MiseDetectOutput, MiseInstallSource, MiseRegistryPackage, MiseRemotePackage, MiseSource,
-    MiseUninstallMode, mise_detect_request, mise_download_install_script_request,
-    mise_implode_request, mise_install_tool_request, mise_list_installed_request,
-    mise_list_outdated_request, mise_list_remote_request, mise_registry_request,
-    mise_run_downloaded_install_script_request, mise_uninstall_tool_request, mise_upgrade_request,
-    parse_mise_registry_catalog, parse_mise_remote_catalog,
+    MISE_INSTALL_SCRIPT_SHA256, MiseDetectOutput, MiseInstallSource, MiseRegistryPackage,
+    MiseRemotePackage, MiseSource, MiseUninstallMode, mise_detect_request,
+    mise_download_install_script_request, mise_implode_request, mise_install_tool_request,
+    mise_list_installed_request, mise_list_outdated_request, mise_list_remote_request,
+    mise_registry_request, mise_run_downloaded_install_script_request, mise_uninstall_tool_request,
+    mise_upgrade_request, parse_mise_registry_catalog, parse_mise_remote_catalog,
 };
-use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
-use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
+use crate::adapters::process_utils::{
+    run_and_collect_stdout, run_and_collect_version_output, run_script_installer_and_collect_stdout,
+};
+use crate::execution::{ProcessExecutor, ProcessSpawnRequest, ScriptInstallerStep};
 use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskType};
 
 pub struct ProcessMiseSource {
@@ -125,7 +127,9 @@ impl ProcessMiseSource {
     fn install_self_via_official_download(&self) -> AdapterResult<String> {
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let temp_dir = std::env::temp_dir();
+        // Outside a task (direct CLI calls) there is no scratch dir to clean up after us.
+        let temp_dir =
+            crate::task_context::current_task_temp_dir().unwrap_or_else(|_| std::env::temp_dir());
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|duration| duration.as_millis())
@@ -147,7 +151,14 @@ impl ProcessMiseSource {
                 None,
                 &script_path_string,
             ));
-            run_and_collect_stdout(self.executor.as_ref(), run_request)
+            run_script_installer_and_collect_stdout(
+                self.executor.as_ref(),
+                run_request,
+                ScriptInstallerStep::Run {
+                    script_path: script_path.clone(),
+                    sha256: MISE_INSTALL_SCRIPT_SHA256.to_string(),
+                },
+            )
         };
 
         let _ = std::fs::remove_file(&script_path);
@@ -492,12 +503,15 @@ impl MiseSource for ProcessMiseSource {
         }
     }
 
-    fn upgrade_tool(&self, name: &str, version: Option<&str>) -> AdapterResult<String> {
-        let target = match version.map(str::trim).filter(|value| !value.is_empty()) {
-            Some(version) if name != "__all__" => format!("{name}@{version}"),
-            _ => name.to_string(),
-        };
-        let request = self.configure_request(mise_upgrade_request(None, target.as_str()));
+    fn upgrade_tool(&self, name: Option<&str>, version: Option<&str>) -> AdapterResult<String> {
+        let target =
+            name.map(
+                |name| match version.map(str::trim).filter(|value| !value.is_empty()) {
+                    Some(version) => format!("{name}@{version}"),
+                    None => name.to_string(),
+                },
+            );
+        let request = self.configure_request(mise_upgrade_request(None, target.as_deref()));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 }
@@ -5,6 +5,7 @@ pub mod bundler_process;
 pub mod cargo;
 pub mod cargo_binstall;
 pub mod cargo_binstall_process;
+pub mod cargo_index;
 pub(crate) mod cargo_outdated;
 pub mod cargo_process;
 pub mod colima;
@@ -14,10 +15,15 @@ pub mod docker_desktop;
 pub mod docker_desktop_process;
 pub mod firmware_updates;
 pub mod firmware_updates_process;
+#[cfg(test)]
+pub(crate) mod golden;
 pub mod homebrew;
 pub mod homebrew_cask;
 pub mod homebrew_cask_process;
+pub mod homebrew_cleanup;
+pub mod homebrew_env;
 pub mod homebrew_process;
+pub mod homebrew_services;
 pub mod macports;
 pub mod macports_process;
 pub mod manager;
@@ -29,6 +35,7 @@ pub mod nix_darwin;
 pub mod nix_darwin_process;
 pub mod npm;
 pub mod npm_process;
+pub mod npm_registry;
 pub mod parallels_desktop;
 pub mod parallels_desktop_process;
 pub mod pip;
@@ -42,6 +49,7 @@ pub mod podman_process;
 pub mod poetry;
 pub mod poetry_process;
 pub(crate) mod process_utils;
+pub mod pypi;
 pub mod rosetta2;
 pub mod rosetta2_process;
 pub mod rubygems;
@@ -59,6 +67,7 @@ pub mod xcode_command_line_tools_process;
 pub mod yarn;
 pub mod yarn_process;
 
+pub use crate::upgrade_target::{UpgradeTarget, UpgradeTargetKind};
 pub use asdf::{
     AsdfAdapter, AsdfSource, asdf_add_plugin_request, asdf_detect_request, asdf_install_request,
     asdf_latest_request, asdf_list_current_request, asdf_list_installed_versions_request,
@@ -105,7 +114,7 @@ pub use homebrew::{
 };
 pub use homebrew_cask::{
     HomebrewCaskAdapter, HomebrewCaskSource, homebrew_cask_catalog_request,
-    homebrew_cask_detect_request, homebrew_cask_install_request,
+    homebrew_cask_detect_request, homebrew_cask_info_request, homebrew_cask_install_request,
     homebrew_cask_list_installed_request, homebrew_cask_list_outdated_request,
     homebrew_cask_search_request, homebrew_cask_uninstall_request, homebrew_cask_upgrade_request,
 };
@@ -118,10 +127,15 @@ pub use macports::{
 };
 pub use macports_process::ProcessMacPortsSource;
 pub use manager::{
-    AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, InstallRequest,
-    ListInstalledRequest, ListOutdatedRequest, ManagerAdapter, MutationResult, PinRequest,
-    RefreshRequest, SearchRequest, UninstallRequest, UnpinRequest, UpgradeRequest,
+    AdapterRequest, AdapterResponse, AdapterResult, CleanupRequest, DetectRequest, InstallRequest,
+    ListDependenciesRequest, ListFilesRequest, ListInstalledRequest, ListOutdatedRequest,
+    ListRequestedRequest, ListServicesRequest, ManagerAdapter, MeasurePackageSizesRequest,
+    MutationResult, PackageInfoRequest, PinRequest, PreviewCleanupRequest, RefreshRequest,
+    RollbackRequest, SearchRequest, SelfVersionCheckRequest, SetServiceStateRequest,
+    UninstallRequest, UnpinRequest, UpgradeRequest, cache_directory_cleanup,
     ensure_action_supported, ensure_request_supported, execute_with_capability_check,
+    measure_package_directories, package_or_all_upgrade_target, unsupported_upgrade_target_error,
+    upgrade_outdated_except_held,
 };
 pub use mas::{
     MasAdapter, MasSource, mas_detect_request, mas_get_request, mas_install_request,
@@ -194,7 +208,7 @@ pub use setapp::{SetappAdapter, SetappSource, setapp_detect_request};
 pub use setapp_process::ProcessSetappSource;
 pub use softwareupdate::{
     SoftwareUpdateAdapter, SoftwareUpdateSource, softwareupdate_detect_request,
-    softwareupdate_list_request, softwareupdate_upgrade_request,
+    softwareupdate_install_labels_request, softwareupdate_list_request,
 };
 pub use softwareupdate_process::ProcessSoftwareUpdateSource;
 pub use sparkle::{SparkleAdapter, SparkleSource, sparkle_detect_request};
@@ -206,12 +220,69 @@ pub use xcode_command_line_tools::{
 };
 pub use xcode_command_line_tools_process::ProcessXcodeCommandLineToolsSource;
 pub use yarn::{
-    YarnAdapter, YarnSource, yarn_detect_request, yarn_install_request,
+    YarnAdapter, YarnSource, yarn_detect_request, yarn_detection_extras, yarn_install_request,
     yarn_list_installed_request, yarn_list_outdated_request, yarn_search_request,
     yarn_uninstall_request, yarn_upgrade_request,
 };
 pub use yarn_process::ProcessYarnSource;
 
+/// Manager-specific facts derived from a detection (for example the active
+/// yarn flavor), reported alongside manager status. Empty for most managers.
+pub fn detection_extras(
+    manager: crate::models::ManagerId,
+    info: &crate::models::DetectionInfo,
+) -> std::collections::BTreeMap<String, String> {
+    match manager {
+        crate::models::ManagerId::Yarn => yarn_detection_extras(info),
+        _ => std::collections::BTreeMap::new(),
+    }
+}
+
+/// Combine the list-installed and list-outdated stages of a refresh. A failed
+/// stage leaves its snapshot untouched and is reported in `stage_failures`;
+/// the refresh itself fails only when both stages do.
+pub(crate) fn snapshot_sync_from_stages(
+    installed: crate::adapters::manager::AdapterResult<Vec<crate::models::InstalledPackage>>,
+    outdated: crate::adapters::manager::AdapterResult<Vec<crate::models::OutdatedPackage>>,
+) -> crate::adapters::manager::AdapterResult<AdapterResponse> {
+    match (installed, outdated) {
+        (Err(error), Err(_)) => Err(error),
+        (installed, outdated) => {
+            let mut stage_failures = Vec::new();
+            let installed = installed
+                .map_err(|error| {
+                    stage_failures.push(snapshot_stage_failure(
+                        error,
+                        crate::models::ManagerAction::ListInstalled,
+                    ));
+                })
+                .ok();
+            let outdated = outdated
+                .map_err(|error| {
+                    stage_failures.push(snapshot_stage_failure(
+                        error,
+                        crate::models::ManagerAction::ListOutdated,
+                    ));
+                })
+                .ok();
+            Ok(AdapterResponse::SnapshotSync {
+                installed,
+                outdated,
+                stage_failures,
+            })
+        }
+    }
+}
+
+/// Tag a failed refresh stage with the action it stood for.
+pub(crate) fn snapshot_stage_failure(
+    mut error: crate::models::CoreError,
+    stage: crate::models::ManagerAction,
+) -> crate::models::CoreError {
+    error.action = Some(stage);
+    error
+}
+
 pub(crate) fn validate_package_identifier(
     manager: crate::models::ManagerId,
     action: crate::models::ManagerAction,
@@ -80,6 +80,7 @@ impl<S: NixDarwinSource> ManagerAdapter for NixDarwinAdapter<S> {
                 Ok(AdapterResponse::SnapshotSync {
                     installed: Some(Vec::new()),
                     outdated: Some(Vec::new()),
+                    stage_failures: Vec::new(),
                 })
             }
             _ => Err(CoreError {
@@ -241,6 +242,7 @@ fn parse_nix_darwin_installed(output: &str) -> Vec<InstalledPackage> {
             installed_version: version,
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -283,6 +285,7 @@ fn parse_nix_darwin_outdated(output: &str) -> Vec<OutdatedPackage> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -328,6 +331,7 @@ fn parse_nix_darwin_search(output: &str, query: &SearchQuery) -> Vec<CachedSearc
             source_manager: ManagerId::NixDarwin,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -465,6 +469,7 @@ mod tests {
         let AdapterResponse::SnapshotSync {
             installed,
             outdated,
+            ..
         } = refresh
         else {
             panic!("expected snapshot sync response");
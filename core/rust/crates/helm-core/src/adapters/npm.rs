@@ -1,16 +1,19 @@
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::manager::{
+    AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter, measure_package_directories,
+};
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
+    ManagerId, OutdatedPackage, PackageCandidate, PackageFiles, PackageMetadata, PackageRef,
+    SearchQuery, TaskId, TaskType,
 };
 
 const NPM_CAPABILITIES: &[Capability] = &[
@@ -22,6 +25,10 @@ const NPM_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::ListFiles,
+    Capability::PackageInfo,
+    Capability::Cleanup,
+    Capability::PackageSizes,
 ];
 
 const NPM_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -52,6 +59,13 @@ pub trait NpmSource: Send + Sync {
     fn install_global(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn uninstall_global(&self, name: &str) -> AdapterResult<String>;
     fn upgrade_global(&self, name: Option<&str>) -> AdapterResult<String>;
+    fn global_root(&self) -> AdapterResult<String>;
+    /// `npm view <name> --json` registry metadata for the latest version.
+    fn view(&self, name: &str) -> AdapterResult<String>;
+    /// `npm config get cache`.
+    fn cache_dir(&self) -> AdapterResult<String>;
+    /// `npm cache clean --force`.
+    fn clean_cache(&self) -> AdapterResult<String>;
 }
 
 pub struct NpmAdapter<S: NpmSource> {
@@ -94,15 +108,19 @@ impl<S: NpmSource> ManagerAdapter for NpmAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_npm_list_installed(&self.source.list_installed_global()?)?;
-                let outdated = parse_npm_outdated(&self.source.list_outdated_global()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed_global()
+                    .and_then(|raw| parse_npm_list_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated_global()
+                    .and_then(|raw| parse_npm_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed_global()?;
@@ -116,7 +134,12 @@ impl<S: NpmSource> ManagerAdapter for NpmAdapter<S> {
             }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search(search_request.query.text.as_str())?;
-                let results = parse_npm_search(&raw, &search_request.query)?;
+                let mut results = parse_npm_search(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::Npm,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -147,6 +170,7 @@ impl<S: NpmSource> ManagerAdapter for NpmAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -168,33 +192,43 @@ impl<S: NpmSource> ManagerAdapter for NpmAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Npm,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Npm,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Npm,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Npm,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_npm_outdated_entry(&self.source, name))
                     .transpose()?
                     .flatten();
-                let _ = self.source.upgrade_global(target_name)?;
+                if target_name.is_none() && !upgrade_request.options.is_empty() {
+                    crate::adapters::upgrade_outdated_except_held(
+                        &parse_npm_outdated(&self.source.list_outdated_global()?)?,
+                        &upgrade_request.options,
+                        |name| self.source.upgrade_global(Some(name)),
+                    )?;
+                } else {
+                    let _ = self.source.upgrade_global(target_name)?;
+                }
                 if let Some(name) = target_name {
                     ensure_npm_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Npm),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: upgrade_request.version.or_else(|| {
@@ -203,8 +237,44 @@ impl<S: NpmSource> ManagerAdapter for NpmAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
+            AdapterRequest::ListFiles(list_files_request) => {
+                // npm keeps no file manifest; a global package owns its directory.
+                let root = parse_npm_global_root(&self.source.global_root()?)?;
+                let package_dir = npm_package_dir(&root, &list_files_request.package.name)?;
+                Ok(AdapterResponse::PackageFiles(PackageFiles::from_listing(
+                    collect_npm_package_files(&package_dir)?,
+                    list_files_request.limit,
+                )))
+            }
+            AdapterRequest::MeasurePackageSizes(_) => {
+                let root = parse_npm_global_root(&self.source.global_root()?)?;
+                let installed = parse_npm_list_installed(&self.source.list_installed_global()?)?;
+                Ok(AdapterResponse::PackageSizes(measure_package_directories(
+                    installed.into_iter().filter_map(|installed| {
+                        let package_dir = npm_package_dir(&root, &installed.package.name).ok()?;
+                        Some((installed.package, vec![package_dir]))
+                    }),
+                )))
+            }
+            AdapterRequest::PackageInfo(info_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::Npm,
+                    ManagerAction::PackageInfo,
+                    info_request.package.name.as_str(),
+                )?;
+                let raw = self.source.view(&info_request.package.name)?;
+                Ok(AdapterResponse::PackageInfo(parse_npm_view(&raw)?))
+            }
+            AdapterRequest::PreviewCleanup(_) | AdapterRequest::Cleanup(_) => {
+                let dry_run = matches!(request, AdapterRequest::PreviewCleanup(_));
+                let roots = [npm_cache_content_dir(&self.source.cache_dir()?)?];
+                crate::adapters::cache_directory_cleanup(dry_run, &roots, || {
+                    self.source.clean_cache().map(|_| ())
+                })
+            }
             _ => Err(CoreError {
                 manager: Some(ManagerId::Npm),
                 task: None,
@@ -305,6 +375,57 @@ pub fn npm_upgrade_request(task_id: Option<TaskId>, name: Option<&str>) -> Proce
     )
 }
 
+pub fn npm_global_root_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    npm_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListFiles,
+        CommandSpec::new(NPM_COMMAND).args(["root", "-g"]),
+        DETECT_TIMEOUT,
+    )
+}
+
+pub fn npm_view_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    npm_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PackageInfo,
+        CommandSpec::new(NPM_COMMAND).args(["view", name, "--json"]),
+        SEARCH_TIMEOUT,
+    )
+}
+
+pub fn npm_cache_dir_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    npm_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PreviewCleanup,
+        CommandSpec::new(NPM_COMMAND).args(["config", "get", "cache"]),
+        DETECT_TIMEOUT,
+    )
+}
+
+pub fn npm_cache_clean_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    npm_request(
+        task_id,
+        TaskType::Configure,
+        ManagerAction::Cleanup,
+        CommandSpec::new(NPM_COMMAND).args(["cache", "clean", "--force"]),
+        MUTATION_TIMEOUT,
+    )
+}
+
+/// `npm ping`, used to warm npm's runtime and registry lookup at launch.
+pub fn npm_ping_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    npm_request(
+        task_id,
+        TaskType::Detection,
+        ManagerAction::Detect,
+        CommandSpec::new(NPM_COMMAND).arg("ping"),
+        SEARCH_TIMEOUT,
+    )
+}
+
 fn npm_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
@@ -365,6 +486,7 @@ fn parse_npm_list_installed(output: &str) -> AdapterResult<Vec<InstalledPackage>
             installed_version: Some(version),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         })
         .collect())
 }
@@ -467,6 +589,7 @@ fn parse_npm_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -474,6 +597,63 @@ fn parse_npm_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
     Ok(packages)
 }
 
+#[derive(Debug, Deserialize)]
+struct NpmViewEntry {
+    description: Option<String>,
+    homepage: Option<String>,
+    #[serde(default)]
+    license: Option<Value>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Value>,
+    dist: Option<NpmViewDist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmViewDist {
+    #[serde(rename = "unpackedSize")]
+    unpacked_size: Option<u64>,
+}
+
+/// `npm view --json` prints one object, or an array of objects when the
+/// spec matched several versions; the last entry is the newest. Older
+/// packages spell the license as `{ "type": ... }`.
+fn parse_npm_view(output: &str) -> AdapterResult<PackageMetadata> {
+    let value: Value = serde_json::from_str(output.trim())
+        .map_err(|e| parse_error(&format!("invalid npm view JSON: {e}")))?;
+    let value = match value {
+        Value::Array(entries) => entries
+            .into_iter()
+            .next_back()
+            .ok_or_else(|| parse_error("npm view JSON listed no versions"))?,
+        other => other,
+    };
+    let entry: NpmViewEntry = serde_json::from_value(value)
+        .map_err(|e| parse_error(&format!("invalid npm view JSON: {e}")))?;
+
+    let license = match entry.license {
+        Some(Value::String(license)) => Some(license),
+        Some(Value::Object(license)) => license
+            .get("type")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    };
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(PackageMetadata {
+        description: non_empty(entry.description),
+        homepage: non_empty(entry.homepage),
+        license: non_empty(license),
+        installed_size_bytes: entry.dist.and_then(|dist| dist.unpacked_size),
+        dependencies: entry.dependencies.into_keys().collect(),
+        aliases: Vec::new(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 struct NpmSearchEntry {
     name: Option<String>,
@@ -532,12 +712,101 @@ fn parse_npm_search(output: &str, query: &SearchQuery) -> AdapterResult<Vec<Cach
             source_manager: ManagerId::Npm,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
     Ok(results)
 }
 
+fn parse_npm_global_root(output: &str) -> AdapterResult<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .ok_or_else(|| parse_error("npm root -g did not print a global node_modules path"))
+}
+
+/// npm keeps downloaded tarballs in the content-addressed `_cacache` under
+/// its cache directory; `npm cache clean` empties only that.
+fn npm_cache_content_dir(output: &str) -> AdapterResult<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('/'))
+        .map(|cache| PathBuf::from(cache).join("_cacache"))
+        .ok_or_else(|| parse_error("npm config get cache did not print a cache path"))
+}
+
+/// Resolve `name` (`pkg` or `@scope/pkg`) under the global `node_modules`,
+/// refusing anything that would step outside it.
+fn npm_package_dir(root: &Path, name: &str) -> AdapterResult<PathBuf> {
+    crate::adapters::validate_package_identifier(ManagerId::Npm, ManagerAction::ListFiles, name)?;
+    let name = name.trim();
+    let segments = name.split('/').collect::<Vec<_>>();
+    let well_formed = match segments.as_slice() {
+        [package] => !package.starts_with('@'),
+        [scope, package] => scope.len() > 1 && scope.starts_with('@') && !package.is_empty(),
+        _ => false,
+    } && segments
+        .iter()
+        .all(|segment| !segment.is_empty() && *segment != "." && *segment != "..");
+    if !well_formed {
+        return Err(CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListFiles),
+            kind: CoreErrorKind::InvalidInput,
+            message: format!("'{name}' is not an npm package name"),
+        });
+    }
+    Ok(root.join(name))
+}
+
+/// Every file under the package directory, sorted by path. Symlinks are
+/// listed but not followed.
+fn collect_npm_package_files(package_dir: &Path) -> AdapterResult<Vec<String>> {
+    let io_error = |error: std::io::Error| CoreError {
+        manager: Some(ManagerId::Npm),
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::ListFiles),
+        kind: CoreErrorKind::ProcessFailure,
+        message: format!(
+            "failed to read npm package directory '{}': {error}",
+            package_dir.display()
+        ),
+    };
+    if !package_dir.is_dir() {
+        return Err(CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListFiles),
+            kind: CoreErrorKind::NotInstalled,
+            message: format!(
+                "npm package directory '{}' does not exist",
+                package_dir.display()
+            ),
+        });
+    }
+
+    let mut files = Vec::new();
+    let mut pending = vec![package_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let path = entry.path();
+            if entry.file_type().map_err(io_error)?.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::Npm),
@@ -551,31 +820,74 @@ fn parse_error(message: &str) -> CoreError {
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
-    use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
         ManagerAdapter, SearchRequest,
     };
     use crate::models::{
-        CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType,
+        CoreError, CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId,
+        TaskType,
     };
 
     use super::{
         NpmAdapter, NpmDetectOutput, NpmSource, npm_detect_request, npm_install_request,
         npm_list_installed_request, npm_list_outdated_request, npm_search_request,
-        npm_uninstall_request, npm_upgrade_request, parse_npm_list_installed, parse_npm_outdated,
-        parse_npm_search, parse_npm_version,
+        npm_uninstall_request, npm_upgrade_request, npm_view_request, parse_npm_list_installed,
+        parse_npm_outdated, parse_npm_search, parse_npm_version, parse_npm_view,
+    };
+    use crate::adapters::golden;
+    use crate::adapters::{
+        CleanupRequest, ListFilesRequest, MeasurePackageSizesRequest, PreviewCleanupRequest,
+        UpgradeRequest,
     };
+    use crate::upgrade_target::UpgradeTarget;
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/npm/version.txt");
     const LIST_FIXTURE: &str = include_str!("../../tests/fixtures/npm/list_global.json");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/npm/outdated_global.json");
     const SEARCH_FIXTURE: &str = include_str!("../../tests/fixtures/npm/search_array.json");
+    const VIEW_FIXTURE: &str = include_str!("../../tests/fixtures/npm/view.json");
     const SEARCH_NDJSON_FIXTURE: &str =
         include_str!("../../tests/fixtures/npm/search_ndjson.jsonl");
 
+    /// `(fixture version directory, version reported by npm)`.
+    const GOLDEN_CASES: &[(&str, &str)] = &[("npm-9", "9.8.1"), ("npm-10", "10.9.2")];
+
+    #[test]
+    fn golden_parses_across_npm_versions() {
+        golden::assert_cases_cover_fixtures("npm", GOLDEN_CASES);
+        for (case, version) in GOLDEN_CASES {
+            let output = golden::fixture("npm", case, "version.txt");
+            assert_eq!(
+                parse_npm_version(&output).as_deref(),
+                Some(*version),
+                "{case}"
+            );
+
+            let installed =
+                parse_npm_list_installed(&golden::fixture("npm", case, "list_global.json"))
+                    .unwrap();
+            golden::assert_golden(
+                "npm",
+                case,
+                "installed.golden",
+                &golden::render_installed(&installed),
+            );
+
+            let outdated =
+                parse_npm_outdated(&golden::fixture("npm", case, "outdated_global.json")).unwrap();
+            golden::assert_golden(
+                "npm",
+                case,
+                "outdated.golden",
+                &golden::render_outdated(&outdated),
+            );
+        }
+    }
+
     #[test]
     fn parses_npm_version_from_fixture() {
         let version = parse_npm_version(VERSION_FIXTURE);
@@ -614,6 +926,30 @@ mod tests {
         assert_eq!(results[0].source_manager, ManagerId::Npm);
     }
 
+    #[test]
+    fn parses_package_metadata_from_view_fixture() {
+        let metadata = parse_npm_view(VIEW_FIXTURE).unwrap();
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://www.typescriptlang.org/")
+        );
+        assert_eq!(metadata.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(metadata.installed_size_bytes, Some(22_946_702));
+        assert!(metadata.dependencies.is_empty());
+
+        let legacy = parse_npm_view(
+            r#"[{"description":"old"},{"description":"new","license":{"type":"MIT"},"dependencies":{"b":"^1","a":"^2"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.description.as_deref(), Some("new"));
+        assert_eq!(legacy.license.as_deref(), Some("MIT"));
+        assert_eq!(legacy.dependencies, vec!["a", "b"]);
+
+        let request = npm_view_request(None, "typescript");
+        assert_eq!(request.action, ManagerAction::PackageInfo);
+        assert_eq!(request.command.args, vec!["view", "typescript", "--json"]);
+    }
+
     #[test]
     fn parses_search_results_from_ndjson_fixture() {
         let query = SearchQuery {
@@ -671,6 +1007,32 @@ mod tests {
         assert_eq!(upgrade_all.command.args, vec!["update", "-g"]);
     }
 
+    #[test]
+    fn upgrade_all_skips_packages_on_the_hold_list() {
+        let source = StubNpmSource::success();
+        let upgrade_calls = source.upgrade_calls.clone();
+        let adapter = NpmAdapter::new(source);
+
+        let upgrade_all = |options: Vec<String>| {
+            adapter
+                .execute(AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::All,
+                    target_name: None,
+                    version: None,
+                    options,
+                    cleanup_old_kegs: false,
+                }))
+                .unwrap();
+            std::mem::take(&mut *upgrade_calls.lock().unwrap())
+        };
+
+        assert_eq!(upgrade_all(Vec::new()), vec![None]);
+        assert_eq!(
+            upgrade_all(vec!["typescript".to_string()]),
+            vec![Some("npm-check-updates".to_string())]
+        );
+    }
+
     #[derive(Clone)]
     struct StubNpmSource {
         detect_calls: Arc<AtomicUsize>,
@@ -678,6 +1040,9 @@ mod tests {
         list_installed_result: AdapterResult<String>,
         list_outdated_result: AdapterResult<String>,
         search_result: AdapterResult<String>,
+        global_root_result: AdapterResult<String>,
+        cache_dir_result: AdapterResult<String>,
+        upgrade_calls: Arc<Mutex<Vec<Option<String>>>>,
     }
 
     impl StubNpmSource {
@@ -691,6 +1056,9 @@ mod tests {
                 list_installed_result: Ok(LIST_FIXTURE.to_string()),
                 list_outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
                 search_result: Ok(SEARCH_FIXTURE.to_string()),
+                global_root_result: Ok("/opt/homebrew/lib/node_modules\n".to_string()),
+                cache_dir_result: Ok("/nonexistent/helm-npm-cache\n".to_string()),
+                upgrade_calls: Arc::new(Mutex::new(Vec::new())),
             }
         }
     }
@@ -721,7 +1089,29 @@ mod tests {
             Ok(String::new())
         }
 
-        fn upgrade_global(&self, _name: Option<&str>) -> AdapterResult<String> {
+        fn upgrade_global(&self, name: Option<&str>) -> AdapterResult<String> {
+            self.upgrade_calls
+                .lock()
+                .unwrap()
+                .push(name.map(str::to_string));
+            Ok(String::new())
+        }
+
+        fn global_root(&self) -> AdapterResult<String> {
+            self.global_root_result.clone()
+        }
+
+        fn view(&self, _name: &str) -> AdapterResult<String> {
+            Ok(VIEW_FIXTURE.to_string())
+        }
+
+        fn cache_dir(&self) -> AdapterResult<String> {
+            self.cache_dir_result.clone()
+        }
+
+        fn clean_cache(&self) -> AdapterResult<String> {
+            let cache = self.cache_dir_result.clone()?;
+            let _ = std::fs::remove_dir_all(PathBuf::from(cache.trim()).join("_cacache"));
             Ok(String::new())
         }
     }
@@ -758,6 +1148,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert_eq!(installed.expect("installed snapshot").len(), 3);
                 assert_eq!(outdated.expect("outdated snapshot").len(), 2);
@@ -766,6 +1157,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn refresh_reports_failed_outdated_stage_without_failing() {
+        let adapter = NpmAdapter::new(StubNpmSource {
+            list_outdated_result: Err(CoreError {
+                manager: Some(ManagerId::Npm),
+                task: Some(TaskType::Refresh),
+                action: Some(ManagerAction::Refresh),
+                kind: CoreErrorKind::Timeout,
+                message: "npm outdated timed out".to_string(),
+            }),
+            ..StubNpmSource::success()
+        });
+
+        let response = adapter
+            .execute(AdapterRequest::Refresh(crate::adapters::RefreshRequest))
+            .expect("refresh should succeed with a partial snapshot");
+
+        let AdapterResponse::SnapshotSync {
+            installed,
+            outdated,
+            stage_failures,
+        } = response
+        else {
+            panic!("expected snapshot sync response");
+        };
+        assert_eq!(installed.expect("installed snapshot").len(), 3);
+        assert!(outdated.is_none());
+        assert_eq!(stage_failures.len(), 1);
+        assert_eq!(stage_failures[0].action, Some(ManagerAction::ListOutdated));
+        assert_eq!(stage_failures[0].kind, CoreErrorKind::Timeout);
+    }
+
+    #[test]
+    fn refresh_fails_when_every_stage_fails() {
+        let failure = CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::Refresh),
+            kind: CoreErrorKind::ProcessFailure,
+            message: "npm exited with status 1".to_string(),
+        };
+        let adapter = NpmAdapter::new(StubNpmSource {
+            list_installed_result: Err(failure.clone()),
+            list_outdated_result: Err(failure),
+            ..StubNpmSource::success()
+        });
+
+        let error = adapter
+            .execute(AdapterRequest::Refresh(crate::adapters::RefreshRequest))
+            .expect_err("refresh should fail");
+        assert_eq!(error.kind, CoreErrorKind::ProcessFailure);
+    }
+
     #[test]
     fn refresh_clears_snapshots_when_npm_is_not_usable() {
         let adapter = NpmAdapter::new(StubNpmSource {
@@ -777,6 +1221,9 @@ mod tests {
             list_installed_result: Ok(LIST_FIXTURE.to_string()),
             list_outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
             search_result: Ok(SEARCH_FIXTURE.to_string()),
+            global_root_result: Ok(String::new()),
+            cache_dir_result: Ok(String::new()),
+            upgrade_calls: Arc::new(Mutex::new(Vec::new())),
         });
 
         let response = adapter
@@ -787,6 +1234,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert!(installed.expect("installed snapshot").is_empty());
                 assert!(outdated.expect("outdated snapshot").is_empty());
@@ -846,6 +1294,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("5.7.2".to_string()),
+                options: Vec::new(),
             }))
             .expect("install should succeed");
 
@@ -870,11 +1319,115 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
     }
 
+    #[test]
+    fn cleanup_measures_and_empties_the_cacache() {
+        let cache = std::env::temp_dir().join(format!("helm-npm-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cache);
+        std::fs::create_dir_all(cache.join("_cacache/content-v2")).unwrap();
+        std::fs::create_dir_all(cache.join("_logs")).unwrap();
+        std::fs::write(cache.join("_cacache/content-v2/blob"), vec![0_u8; 4096]).unwrap();
+        std::fs::write(cache.join("_logs/debug.log"), vec![0_u8; 512]).unwrap();
+
+        let adapter = NpmAdapter::new(StubNpmSource {
+            cache_dir_result: Ok(format!("{}\n", cache.display())),
+            ..StubNpmSource::success()
+        });
+        let preview = adapter
+            .execute(AdapterRequest::PreviewCleanup(PreviewCleanupRequest))
+            .unwrap();
+        let cleanup = adapter
+            .execute(AdapterRequest::Cleanup(CleanupRequest))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&cache);
+
+        let AdapterResponse::Cleanup(preview) = preview else {
+            panic!("expected cleanup report");
+        };
+        let AdapterResponse::Cleanup(cleanup) = cleanup else {
+            panic!("expected cleanup report");
+        };
+        assert_eq!(preview.bytes, 4096);
+        assert_eq!(cleanup.bytes, preview.bytes);
+    }
+
+    #[test]
+    fn measures_global_module_directories() {
+        let root = std::env::temp_dir().join(format!("helm-npm-sizes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("typescript/lib")).unwrap();
+        std::fs::write(root.join("typescript/lib/tsc.js"), vec![0_u8; 8192]).unwrap();
+        std::fs::write(root.join("typescript/package.json"), vec![0_u8; 512]).unwrap();
+
+        let adapter = NpmAdapter::new(StubNpmSource {
+            global_root_result: Ok(format!("{}\n", root.display())),
+            ..StubNpmSource::success()
+        });
+        let response = adapter
+            .execute(AdapterRequest::MeasurePackageSizes(
+                MeasurePackageSizesRequest,
+            ))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let AdapterResponse::PackageSizes(sizes) = response else {
+            panic!("expected package sizes");
+        };
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].package.name, "typescript");
+        assert_eq!(sizes[0].bytes, 8704);
+    }
+
+    #[test]
+    fn list_files_walks_the_global_package_directory() {
+        let root = std::env::temp_dir().join(format!("helm-npm-files-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let package_dir = root.join("@scope/tool");
+        std::fs::create_dir_all(package_dir.join("bin")).unwrap();
+        std::fs::write(package_dir.join("package.json"), "{}").unwrap();
+        std::fs::write(package_dir.join("bin/tool.js"), "").unwrap();
+
+        let adapter = NpmAdapter::new(StubNpmSource {
+            global_root_result: Ok(format!("{}\n", root.display())),
+            ..StubNpmSource::success()
+        });
+        let list_files = |name: &str| {
+            adapter.execute(AdapterRequest::ListFiles(ListFilesRequest {
+                package: PackageRef {
+                    manager: ManagerId::Npm,
+                    name: name.to_string(),
+                },
+                limit: None,
+            }))
+        };
+
+        let AdapterResponse::PackageFiles(files) = list_files("@scope/tool").unwrap() else {
+            panic!("expected package files");
+        };
+        assert_eq!(
+            files.files,
+            vec![
+                package_dir.join("bin/tool.js").display().to_string(),
+                package_dir.join("package.json").display().to_string(),
+            ]
+        );
+        assert_eq!(
+            list_files("missing").unwrap_err().kind,
+            CoreErrorKind::NotInstalled
+        );
+        assert_eq!(
+            list_files("../escape").unwrap_err().kind,
+            CoreErrorKind::InvalidInput
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     #[test]
     fn parse_errors_are_structured() {
         let error = parse_npm_list_installed("{not json").expect_err("expected parse failure");
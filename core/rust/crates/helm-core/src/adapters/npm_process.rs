@@ -1,13 +1,20 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::npm::{
-    NpmDetectOutput, NpmSource, npm_detect_request, npm_install_request,
-    npm_list_installed_request, npm_list_outdated_request, npm_search_request,
-    npm_uninstall_request, npm_upgrade_request,
+    NpmDetectOutput, NpmSource, npm_cache_clean_request, npm_cache_dir_request, npm_detect_request,
+    npm_global_root_request, npm_install_request, npm_list_installed_request,
+    npm_list_outdated_request, npm_search_request, npm_uninstall_request, npm_upgrade_request,
+    npm_view_request,
+};
+use crate::adapters::npm_registry::{
+    DistTagsCache, npm_global_root, npm_registry_client, npm_registry_outdated_checks,
+    read_global_packages, registry_outdated_payload, uses_default_registry,
 };
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
+use crate::base_dirs::ToolBaseDirs;
 use crate::execution::{
     ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest, spawn_validated,
 };
@@ -47,6 +54,38 @@ impl ProcessNpmSource {
         request
     }
 
+    /// Outdated globals computed from `node_modules` and the registry, or
+    /// `None` when the npm CLI has to answer instead.
+    fn registry_outdated_payload(&self) -> Option<String> {
+        let client = npm_registry_client()?;
+        let base_dirs = ToolBaseDirs::from_env();
+        if !uses_default_registry(&base_dirs) {
+            return None;
+        }
+        let npm = which_executable(
+            self.executor.as_ref(),
+            "npm",
+            &["/opt/homebrew/bin", "/usr/local/bin"],
+            ManagerId::Npm,
+        )?;
+        let packages = read_global_packages(&npm_global_root(&base_dirs, &npm)?);
+        if packages.is_empty() {
+            return None;
+        }
+        match registry_outdated_payload(
+            client.as_ref(),
+            DistTagsCache::shared(),
+            &packages,
+            Instant::now(),
+        ) {
+            Ok(payload) => Some(payload),
+            Err(error) => {
+                tracing::warn!(%error, "npm registry outdated check failed; using npm outdated");
+                None
+            }
+        }
+    }
+
     fn run_and_collect_stdout_accepting(
         &self,
         request: ProcessSpawnRequest,
@@ -159,6 +198,12 @@ impl NpmSource for ProcessNpmSource {
     }
 
     fn list_outdated_global(&self) -> AdapterResult<String> {
+        if npm_registry_outdated_checks()
+            && let Some(payload) = self.registry_outdated_payload()
+        {
+            return Ok(payload);
+        }
+
         // npm uses exit code 1 to indicate outdated packages were found.
         let request = self.configure_request(npm_list_outdated_request(None));
         self.run_and_collect_stdout_accepting(request, &[1], false)
@@ -188,6 +233,26 @@ impl NpmSource for ProcessNpmSource {
         let request = self.configure_request(npm_upgrade_request(None, name));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
+
+    fn global_root(&self) -> AdapterResult<String> {
+        let request = self.configure_request(npm_global_root_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn view(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(npm_view_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn cache_dir(&self) -> AdapterResult<String> {
+        let request = self.configure_request(npm_cache_dir_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn clean_cache(&self) -> AdapterResult<String> {
+        let request = self.configure_request(npm_cache_clean_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
 }
 
 #[cfg(test)]
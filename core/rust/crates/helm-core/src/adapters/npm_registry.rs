@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::base_dirs::ToolBaseDirs;
+
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+const DIST_TAGS_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Registry lookups in flight at once.
+const REGISTRY_LOOKUP_BATCH_SIZE: usize = 8;
+
+static NPM_REGISTRY_OUTDATED_CHECKS: AtomicBool = AtomicBool::new(false);
+static NPM_REGISTRY_CLIENT: OnceLock<RwLock<Option<Arc<dyn NpmRegistryClient>>>> = OnceLock::new();
+static DIST_TAGS_CACHE: OnceLock<DistTagsCache> = OnceLock::new();
+
+/// Result of a conditional dist-tags request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NpmRegistryResponse {
+    Modified { body: String, etag: Option<String> },
+    NotModified,
+    NotFound,
+}
+
+/// Host-provided reader for npm registry dist-tags
+/// (`<registry>/-/package/<name>/dist-tags`).
+pub trait NpmRegistryClient: Send + Sync {
+    /// Fetch dist-tags for `package_name`, sending `If-None-Match` when an
+    /// `etag` from an earlier response is given.
+    fn fetch_dist_tags(
+        &self,
+        package_name: &str,
+        etag: Option<&str>,
+    ) -> Result<NpmRegistryResponse, String>;
+}
+
+/// Install the process-wide registry client used when registry outdated
+/// checks are enabled.
+pub fn install_npm_registry_client(client: Arc<dyn NpmRegistryClient>) {
+    let slot = NPM_REGISTRY_CLIENT.get_or_init(|| RwLock::new(None));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = Some(client);
+}
+
+pub fn npm_registry_client() -> Option<Arc<dyn NpmRegistryClient>> {
+    let slot = NPM_REGISTRY_CLIENT.get()?;
+    slot.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Set whether global outdated checks read `node_modules` and query the
+/// registry instead of running `npm outdated -g`.
+pub fn set_npm_registry_outdated_checks(enabled: bool) {
+    NPM_REGISTRY_OUTDATED_CHECKS.store(enabled, Ordering::SeqCst);
+}
+
+pub fn npm_registry_outdated_checks() -> bool {
+    NPM_REGISTRY_OUTDATED_CHECKS.load(Ordering::SeqCst)
+}
+
+/// Whether npm resolves packages from the public registry only. A registry
+/// override in the environment or `~/.npmrc` (including scoped registries)
+/// may need credentials, so those setups stay on the npm CLI.
+pub(crate) fn uses_default_registry(base_dirs: &ToolBaseDirs) -> bool {
+    for key in ["NPM_CONFIG_REGISTRY", "npm_config_registry"] {
+        if let Some(registry) = std::env::var_os(key)
+            && registry.to_string_lossy().trim_end_matches('/')
+                != DEFAULT_REGISTRY.trim_end_matches('/')
+        {
+            return false;
+        }
+    }
+    let Some(npmrc) = base_dirs
+        .home
+        .as_ref()
+        .and_then(|home| std::fs::read_to_string(home.join(".npmrc")).ok())
+    else {
+        return true;
+    };
+    !npmrc_overrides_registry(&npmrc)
+}
+
+fn npmrc_overrides_registry(npmrc: &str) -> bool {
+    npmrc.lines().any(|line| {
+        let line = line.trim();
+        !line.starts_with(['#', ';'])
+            && line
+                .split_once('=')
+                .is_some_and(|(key, _)| key.trim().ends_with("registry"))
+    })
+}
+
+/// Global `node_modules` of the npm at `npm_executable`, honoring a
+/// configured prefix.
+pub(crate) fn npm_global_root(base_dirs: &ToolBaseDirs, npm_executable: &Path) -> Option<PathBuf> {
+    let prefix = match &base_dirs.npm_prefix {
+        Some(prefix) => prefix.clone(),
+        // `<prefix>/bin/npm` for Homebrew, nvm, volta, and the official installer.
+        None => npm_executable.parent()?.parent()?.to_path_buf(),
+    };
+    Some(prefix.join("lib/node_modules"))
+}
+
+/// `(name, version)` of every package in a global `node_modules`, sorted by
+/// name. Linked packages point at local checkouts and are skipped.
+pub(crate) fn read_global_packages(root: &Path) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return packages;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            let Ok(scoped) = std::fs::read_dir(entry.path()) else {
+                continue;
+            };
+            for scoped_entry in scoped.flatten() {
+                push_package(&mut packages, &scoped_entry);
+            }
+        } else {
+            push_package(&mut packages, &entry);
+        }
+    }
+    packages.sort();
+    packages
+}
+
+fn push_package(packages: &mut Vec<(String, String)>, entry: &std::fs::DirEntry) {
+    if entry
+        .file_type()
+        .is_ok_and(|file_type| file_type.is_symlink())
+    {
+        return;
+    }
+    let Ok(raw) = std::fs::read_to_string(entry.path().join("package.json")) else {
+        return;
+    };
+    let Ok(manifest) = serde_json::from_str::<Value>(&raw) else {
+        return;
+    };
+    if let (Some(name), Some(version)) = (
+        manifest.get("name").and_then(Value::as_str),
+        manifest.get("version").and_then(Value::as_str),
+    ) {
+        packages.push((name.to_string(), version.to_string()));
+    }
+}
+
+struct CachedDistTags {
+    etag: Option<String>,
+    latest: Option<String>,
+    checked_at: Instant,
+}
+
+/// `latest` dist-tags by package. Fresh entries skip the network; stale
+/// ones are revalidated with their etag.
+#[derive(Default)]
+pub(crate) struct DistTagsCache {
+    entries: Mutex<HashMap<String, CachedDistTags>>,
+}
+
+impl DistTagsCache {
+    pub(crate) fn shared() -> &'static DistTagsCache {
+        DIST_TAGS_CACHE.get_or_init(DistTagsCache::default)
+    }
+
+    fn latest(
+        &self,
+        client: &dyn NpmRegistryClient,
+        package_name: &str,
+        now: Instant,
+    ) -> Result<Option<String>, String> {
+        let etag = {
+            let entries = self.lock();
+            match entries.get(package_name) {
+                Some(cached) if now.duration_since(cached.checked_at) < DIST_TAGS_CACHE_TTL => {
+                    return Ok(cached.latest.clone());
+                }
+                Some(cached) => cached.etag.clone(),
+                None => None,
+            }
+        };
+
+        // Fetch without holding the lock; lookups in a batch run concurrently.
+        let (etag, latest) = match client.fetch_dist_tags(package_name, etag.as_deref())? {
+            NpmRegistryResponse::Modified { body, etag } => {
+                let tags = serde_json::from_str::<Value>(&body).map_err(|error| {
+                    format!("invalid dist-tags JSON for '{package_name}': {error}")
+                })?;
+                let latest = tags
+                    .get("latest")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                (etag, latest)
+            }
+            NpmRegistryResponse::NotModified => {
+                let entries = self.lock();
+                let Some(cached) = entries.get(package_name) else {
+                    return Err(format!(
+                        "registry answered not modified for uncached '{package_name}'"
+                    ));
+                };
+                (cached.etag.clone(), cached.latest.clone())
+            }
+            NpmRegistryResponse::NotFound => (None, None),
+        };
+        self.lock().insert(
+            package_name.to_string(),
+            CachedDistTags {
+                etag,
+                latest: latest.clone(),
+                checked_at: now,
+            },
+        );
+        Ok(latest)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedDistTags>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Serialize)]
+struct OutdatedEntry {
+    current: String,
+    wanted: String,
+    latest: String,
+}
+
+/// Outdated packages among `packages`, encoded like `npm outdated -g --json`
+/// so the CLI parser applies unchanged. Packages missing from the registry
+/// are skipped; any other lookup failure fails the whole check.
+pub(crate) fn registry_outdated_payload(
+    client: &dyn NpmRegistryClient,
+    cache: &DistTagsCache,
+    packages: &[(String, String)],
+    now: Instant,
+) -> Result<String, String> {
+    let mut outdated = BTreeMap::new();
+    for batch in packages.chunks(REGISTRY_LOOKUP_BATCH_SIZE) {
+        let results = std::thread::scope(|scope| {
+            let lookups = batch
+                .iter()
+                .map(|(name, _)| scope.spawn(move || cache.latest(client, name, now)))
+                .collect::<Vec<_>>();
+            lookups
+                .into_iter()
+                .map(|lookup| {
+                    lookup
+                        .join()
+                        .unwrap_or_else(|_| Err("registry lookup panicked".to_string()))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for ((name, current), latest) in batch.iter().zip(results) {
+            let Some(latest) = latest? else {
+                continue;
+            };
+            if &latest != current {
+                outdated.insert(
+                    name.clone(),
+                    OutdatedEntry {
+                        current: current.clone(),
+                        wanted: latest.clone(),
+                        latest,
+                    },
+                );
+            }
+        }
+    }
+    serde_json::to_string(&outdated)
+        .map_err(|error| format!("failed to encode outdated payload: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    use super::{
+        DistTagsCache, NpmRegistryClient, NpmRegistryResponse, npmrc_overrides_registry,
+        read_global_packages, registry_outdated_payload,
+    };
+
+    #[derive(Default)]
+    struct FakeRegistry {
+        latest: HashMap<&'static str, &'static str>,
+        requests: Mutex<Vec<(String, Option<String>)>>,
+    }
+
+    impl NpmRegistryClient for FakeRegistry {
+        fn fetch_dist_tags(
+            &self,
+            package_name: &str,
+            etag: Option<&str>,
+        ) -> Result<NpmRegistryResponse, String> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((package_name.to_string(), etag.map(str::to_string)));
+            if etag.is_some() {
+                return Ok(NpmRegistryResponse::NotModified);
+            }
+            Ok(match self.latest.get(package_name) {
+                Some(latest) => NpmRegistryResponse::Modified {
+                    body: format!("{{\"latest\":\"{latest}\",\"next\":\"99.0.0-rc.1\"}}"),
+                    etag: Some(format!("W/\"{package_name}\"")),
+                },
+                None => NpmRegistryResponse::NotFound,
+            })
+        }
+    }
+
+    fn packages() -> Vec<(String, String)> {
+        [
+            ("@angular/cli", "17.0.0"),
+            ("private-tool", "1.0.0"),
+            ("typescript", "5.4.5"),
+        ]
+        .map(|(name, version)| (name.to_string(), version.to_string()))
+        .to_vec()
+    }
+
+    #[test]
+    fn outdated_payload_matches_npm_outdated_shape_and_revalidates_with_etags() {
+        let registry = FakeRegistry {
+            latest: HashMap::from([("@angular/cli", "18.1.0"), ("typescript", "5.4.5")]),
+            ..FakeRegistry::default()
+        };
+        let cache = DistTagsCache::default();
+        let start = Instant::now();
+
+        let payload =
+            registry_outdated_payload(&registry, &cache, &packages(), start).expect("payload");
+        assert_eq!(
+            payload,
+            r#"{"@angular/cli":{"current":"17.0.0","wanted":"18.1.0","latest":"18.1.0"}}"#
+        );
+        assert_eq!(registry.requests.lock().unwrap().len(), 3);
+
+        registry_outdated_payload(&registry, &cache, &packages(), start).expect("cached");
+        assert_eq!(registry.requests.lock().unwrap().len(), 3);
+
+        let later = start + Duration::from_secs(11 * 60);
+        let payload =
+            registry_outdated_payload(&registry, &cache, &packages(), later).expect("revalidated");
+        assert!(payload.contains("\"latest\":\"18.1.0\""));
+        let requests = registry.requests.lock().unwrap();
+        assert!(requests.contains(&(
+            "@angular/cli".to_string(),
+            Some("W/\"@angular/cli\"".to_string())
+        )));
+    }
+
+    #[test]
+    fn global_packages_include_scoped_and_skip_linked() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock should be after epoch")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("helm-npm-global-{nanos}"));
+        for (dir, manifest) in [
+            ("typescript", r#"{"name":"typescript","version":"5.4.5"}"#),
+            (
+                "@angular/cli",
+                r#"{"name":"@angular/cli","version":"17.0.0"}"#,
+            ),
+            ("broken", "not json"),
+        ] {
+            std::fs::create_dir_all(root.join(dir)).expect("create package dir");
+            std::fs::write(root.join(dir).join("package.json"), manifest).expect("write");
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("typescript"), root.join("linked")).expect("symlink");
+
+        let packages = read_global_packages(&root);
+        assert_eq!(
+            packages,
+            vec![
+                ("@angular/cli".to_string(), "17.0.0".to_string()),
+                ("typescript".to_string(), "5.4.5".to_string()),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn npmrc_registry_overrides_are_detected() {
+        assert!(npmrc_overrides_registry(
+            "registry=https://npm.example.com/\n"
+        ));
+        assert!(npmrc_overrides_registry(
+            "@corp:registry = https://npm.corp.example/\n"
+        ));
+        assert!(!npmrc_overrides_registry(
+            "; registry=https://npm.example.com/\nfund=false\n"
+        ));
+    }
+}
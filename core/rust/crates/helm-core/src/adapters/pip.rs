@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
 
 use serde::Deserialize;
@@ -8,8 +8,10 @@ use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, SearchQuery, TaskId, TaskType,
+    ManagerId, OutdatedPackage, PackageCandidate, PackageFiles, PackageMetadata, PackageRef,
+    SearchQuery, TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 
 const PIP_CAPABILITIES: &[Capability] = &[
     Capability::Detect,
@@ -20,6 +22,9 @@ const PIP_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::ListFiles,
+    Capability::PackageInfo,
+    Capability::Cleanup,
 ];
 
 const PIP_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -49,6 +54,12 @@ pub trait PipSource: Send + Sync {
     fn install(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn uninstall(&self, name: &str) -> AdapterResult<String>;
     fn upgrade(&self, name: Option<&str>) -> AdapterResult<String>;
+    fn show_files(&self, name: &str) -> AdapterResult<String>;
+    fn show(&self, name: &str) -> AdapterResult<String>;
+    /// `pip cache dir`.
+    fn cache_dir(&self) -> AdapterResult<String>;
+    /// `pip cache purge`.
+    fn purge_cache(&self) -> AdapterResult<String>;
 }
 
 pub struct PipAdapter<S: PipSource> {
@@ -91,15 +102,19 @@ impl<S: PipSource> ManagerAdapter for PipAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_pip_list(&self.source.list_installed()?)?;
-                let outdated = parse_pip_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_pip_list(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_pip_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -146,6 +161,7 @@ impl<S: PipSource> ManagerAdapter for PipAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -165,23 +181,30 @@ impl<S: PipSource> ManagerAdapter for PipAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Pip,
-                    name: "__self__".to_string(),
-                });
-
-                let target_name = if package.name == "__self__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Pip,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                // pip has no upgrade-all; a manager-wide target upgrades pip itself.
+                let (package, target_name) = match &upgrade_request.target {
+                    UpgradeTarget::Package(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Pip,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        (package.clone(), Some(package.name.as_str()))
+                    }
+                    UpgradeTarget::SelfUpdate | UpgradeTarget::All => (
+                        UpgradeTarget::SelfUpdate.result_package(ManagerId::Pip),
+                        None,
+                    ),
+                    other => {
+                        return Err(crate::adapters::unsupported_upgrade_target_error(
+                            ManagerId::Pip,
+                            other,
+                        ));
+                    }
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_pip_outdated_entry(&self.source, name))
@@ -202,8 +225,37 @@ impl<S: PipSource> ManagerAdapter for PipAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
+            AdapterRequest::ListFiles(list_files_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::Pip,
+                    ManagerAction::ListFiles,
+                    list_files_request.package.name.as_str(),
+                )?;
+                let raw = self.source.show_files(&list_files_request.package.name)?;
+                Ok(AdapterResponse::PackageFiles(PackageFiles::from_listing(
+                    parse_pip_show_files(&raw)?,
+                    list_files_request.limit,
+                )))
+            }
+            AdapterRequest::PackageInfo(info_request) => {
+                crate::adapters::validate_package_identifier(
+                    ManagerId::Pip,
+                    ManagerAction::PackageInfo,
+                    info_request.package.name.as_str(),
+                )?;
+                let raw = self.source.show(&info_request.package.name)?;
+                Ok(AdapterResponse::PackageInfo(parse_pip_show(&raw)?))
+            }
+            AdapterRequest::PreviewCleanup(_) | AdapterRequest::Cleanup(_) => {
+                let dry_run = matches!(request, AdapterRequest::PreviewCleanup(_));
+                let roots = [parse_pip_cache_dir(&self.source.cache_dir()?)?];
+                crate::adapters::cache_directory_cleanup(dry_run, &roots, || {
+                    self.source.purge_cache().map(|_| ())
+                })
+            }
             _ => Err(CoreError {
                 manager: Some(ManagerId::Pip),
                 task: None,
@@ -215,6 +267,46 @@ impl<S: PipSource> ManagerAdapter for PipAdapter<S> {
     }
 }
 
+/// Whether `name` is an interpreter executable pip can run through:
+/// `python`, `python3`, or a versioned `python3.N`.
+pub fn is_python_interpreter_name(name: &str) -> bool {
+    match name.strip_prefix("python") {
+        Some("" | "3") => true,
+        Some(rest) => rest.strip_prefix("3.").is_some_and(|minor| {
+            !minor.is_empty() && minor.bytes().all(|byte| byte.is_ascii_digit())
+        }),
+        None => false,
+    }
+}
+
+/// Python interpreters directly inside `dirs`, in directory order and sorted
+/// by name within each directory, without duplicate paths.
+pub fn discover_python_interpreters(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut interpreters = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut found = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(is_python_interpreter_name)
+                    && path.is_file()
+            })
+            .collect::<Vec<_>>();
+        found.sort();
+        for path in found {
+            if !interpreters.contains(&path) {
+                interpreters.push(path);
+            }
+        }
+    }
+    interpreters
+}
+
 pub fn pip_detect_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
     pip_request(
         task_id,
@@ -345,6 +437,60 @@ pub fn pip_upgrade_request(task_id: Option<TaskId>, name: Option<&str>) -> Proce
     )
 }
 
+pub fn pip_show_files_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    pip_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::ListFiles,
+        CommandSpec::new(PYTHON_COMMAND).args([
+            "-m",
+            "pip",
+            "show",
+            "--files",
+            "--disable-pip-version-check",
+            name,
+        ]),
+        LIST_TIMEOUT,
+    )
+}
+
+/// `pip show` only reports installed distributions.
+pub fn pip_show_request(task_id: Option<TaskId>, name: &str) -> ProcessSpawnRequest {
+    pip_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PackageInfo,
+        CommandSpec::new(PYTHON_COMMAND).args([
+            "-m",
+            "pip",
+            "show",
+            "--disable-pip-version-check",
+            name,
+        ]),
+        LIST_TIMEOUT,
+    )
+}
+
+pub fn pip_cache_dir_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    pip_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::PreviewCleanup,
+        CommandSpec::new(PYTHON_COMMAND).args(["-m", "pip", "cache", "dir"]),
+        DETECT_TIMEOUT,
+    )
+}
+
+pub fn pip_cache_purge_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    pip_request(
+        task_id,
+        TaskType::Configure,
+        ManagerAction::Cleanup,
+        CommandSpec::new(PYTHON_COMMAND).args(["-m", "pip", "cache", "purge"]),
+        MUTATION_TIMEOUT,
+    )
+}
+
 fn pip_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
@@ -413,6 +559,7 @@ fn parse_pip_list(output: &str) -> AdapterResult<Vec<InstalledPackage>> {
                 },
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             })
         })
         .collect();
@@ -504,6 +651,7 @@ fn parse_pip_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
                 pinned: false,
                 restart_required: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             })
         })
         .collect();
@@ -550,6 +698,7 @@ fn parse_pip_local_search(
                 source_manager: ManagerId::Pip,
                 originating_query: query.text.clone(),
                 cached_at: query.issued_at,
+                popularity: Default::default(),
             })
         })
         .collect();
@@ -558,6 +707,87 @@ fn parse_pip_local_search(
     Ok(results)
 }
 
+/// `pip show --files` lists the package's RECORD entries under `Files:`,
+/// relative to its `Location:`; entries may climb out of site-packages
+/// (console scripts land in `../../../bin`).
+fn parse_pip_show_files(output: &str) -> AdapterResult<Vec<String>> {
+    let location = output
+        .lines()
+        .find_map(|line| line.strip_prefix("Location:"))
+        .map(str::trim)
+        .filter(|location| !location.is_empty())
+        .ok_or_else(|| parse_error("pip show output is missing the package location"))?;
+
+    Ok(output
+        .lines()
+        .skip_while(|line| !line.starts_with("Files:"))
+        .skip(1)
+        .take_while(|line| line.starts_with(' '))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with("Cannot locate"))
+        .map(|entry| resolve_record_entry(location, entry))
+        .collect())
+}
+
+/// `pip show` prints RFC 822-style `Key: value` headers. Newer metadata
+/// carries an SPDX `License-Expression`, preferred over the free-form
+/// `License`; pip reports no installed size.
+fn parse_pip_show(output: &str) -> AdapterResult<PackageMetadata> {
+    let field = |key: &str| {
+        output
+            .lines()
+            .take_while(|line| !line.starts_with("Files:"))
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if field("Name").is_none() {
+        return Err(parse_error("pip show output is missing the package name"));
+    }
+
+    Ok(PackageMetadata {
+        description: field("Summary"),
+        homepage: field("Home-page"),
+        license: field("License-Expression").or_else(|| field("License")),
+        installed_size_bytes: None,
+        dependencies: field("Requires")
+            .map(|requires| {
+                requires
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        aliases: Vec::new(),
+    })
+}
+
+fn resolve_record_entry(location: &str, entry: &str) -> String {
+    let mut resolved = PathBuf::new();
+    for component in Path::new(location).join(entry).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+fn parse_pip_cache_dir(output: &str) -> AdapterResult<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .ok_or_else(|| parse_error("pip cache dir did not print a cache path"))
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::Pip),
@@ -574,6 +804,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    use crate::adapters::golden;
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
         ListOutdatedRequest, ManagerAdapter,
@@ -582,14 +813,50 @@ mod tests {
 
     use super::{
         PipAdapter, PipDetectOutput, PipSource, parse_pip_list, parse_pip_local_search,
-        parse_pip_outdated, parse_pip_version, pip_detect_request, pip_install_request,
-        pip_list_outdated_request, pip_list_request, pip_search_request, pip_uninstall_request,
+        parse_pip_outdated, parse_pip_show, parse_pip_show_files, parse_pip_version,
+        pip_detect_request, pip_install_request, pip_list_outdated_request, pip_list_request,
+        pip_search_request, pip_show_files_request, pip_show_request, pip_uninstall_request,
         pip_upgrade_request,
     };
+    use super::{discover_python_interpreters, is_python_interpreter_name};
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/pip/version.txt");
     const LIST_FIXTURE: &str = include_str!("../../tests/fixtures/pip/list.json");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/pip/outdated.json");
+    const SHOW_FILES_FIXTURE: &str = include_str!("../../tests/fixtures/pip/show_files.txt");
+
+    /// `(fixture version directory, version reported by pip)`.
+    const GOLDEN_CASES: &[(&str, &str)] = &[("pip-23", "23.2.1"), ("pip-24", "24.3.1")];
+
+    #[test]
+    fn golden_parses_across_pip_versions() {
+        golden::assert_cases_cover_fixtures("pip", GOLDEN_CASES);
+        for (case, version) in GOLDEN_CASES {
+            let output = golden::fixture("pip", case, "version.txt");
+            assert_eq!(
+                parse_pip_version(&output).as_deref(),
+                Some(*version),
+                "{case}"
+            );
+
+            let installed = parse_pip_list(&golden::fixture("pip", case, "list.json")).unwrap();
+            golden::assert_golden(
+                "pip",
+                case,
+                "installed.golden",
+                &golden::render_installed(&installed),
+            );
+
+            let outdated =
+                parse_pip_outdated(&golden::fixture("pip", case, "outdated.json")).unwrap();
+            golden::assert_golden(
+                "pip",
+                case,
+                "outdated.golden",
+                &golden::render_outdated(&outdated),
+            );
+        }
+    }
 
     #[test]
     fn parses_pip_version_from_fixture() {
@@ -628,6 +895,69 @@ mod tests {
         assert_eq!(results[0].result.package.name, "pip");
     }
 
+    #[test]
+    fn parses_record_files_relative_to_the_package_location() {
+        let files = parse_pip_show_files(SHOW_FILES_FIXTURE).unwrap();
+        assert_eq!(files.len(), 6);
+        assert_eq!(files[0], "/opt/homebrew/bin/black");
+        assert_eq!(
+            files[4],
+            "/opt/homebrew/lib/python3.12/site-packages/black/__init__.py"
+        );
+
+        let missing_record = "Name: six\nLocation: /usr/lib/python3/dist-packages\nFiles:\nCannot locate RECORD or installed-files.txt\n";
+        assert!(parse_pip_show_files(missing_record).unwrap().is_empty());
+        assert!(parse_pip_show_files("Name: six\n").is_err());
+
+        let request = pip_show_files_request(None, "black");
+        assert_eq!(request.action, ManagerAction::ListFiles);
+        assert_eq!(
+            request.command.args,
+            vec![
+                "-m",
+                "pip",
+                "show",
+                "--files",
+                "--disable-pip-version-check",
+                "black"
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_package_metadata_from_show_output() {
+        let metadata = parse_pip_show(SHOW_FILES_FIXTURE).unwrap();
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("The uncompromising code formatter.")
+        );
+        assert_eq!(metadata.homepage, None);
+        assert_eq!(metadata.license.as_deref(), Some("MIT"));
+        assert_eq!(
+            metadata.dependencies,
+            vec![
+                "click",
+                "mypy-extensions",
+                "packaging",
+                "pathspec",
+                "platformdirs"
+            ]
+        );
+
+        let expression =
+            parse_pip_show("Name: rich\nLicense: MIT License\nLicense-Expression: MIT\n").unwrap();
+        assert_eq!(expression.license.as_deref(), Some("MIT"));
+        assert!(expression.dependencies.is_empty());
+        assert!(parse_pip_show("WARNING: Package(s) not found: nope\n").is_err());
+
+        let request = pip_show_request(None, "black");
+        assert_eq!(request.action, ManagerAction::PackageInfo);
+        assert_eq!(
+            request.command.args,
+            vec!["-m", "pip", "show", "--disable-pip-version-check", "black"]
+        );
+    }
+
     #[test]
     fn request_builders_use_structured_python_args() {
         let detect = pip_detect_request(Some(TaskId(33)));
@@ -777,6 +1107,22 @@ mod tests {
         fn upgrade(&self, _name: Option<&str>) -> AdapterResult<String> {
             Ok(String::new())
         }
+
+        fn show_files(&self, _name: &str) -> AdapterResult<String> {
+            Ok(SHOW_FILES_FIXTURE.to_string())
+        }
+
+        fn show(&self, _name: &str) -> AdapterResult<String> {
+            Ok(SHOW_FILES_FIXTURE.to_string())
+        }
+
+        fn cache_dir(&self) -> AdapterResult<String> {
+            Ok("/nonexistent/helm-pip-cache\n".to_string())
+        }
+
+        fn purge_cache(&self) -> AdapterResult<String> {
+            Ok("Files removed: 0\n".to_string())
+        }
     }
 
     #[test]
@@ -820,6 +1166,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("24.10.0".to_string()),
+                options: Vec::new(),
             }))
             .unwrap()
         {
@@ -843,11 +1190,43 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
     }
 
+    #[test]
+    fn recognizes_plain_and_versioned_python_interpreters() {
+        for name in ["python", "python3", "python3.9", "python3.12"] {
+            assert!(is_python_interpreter_name(name), "{name}");
+        }
+        for name in [
+            "python3.",
+            "python3.12-config",
+            "python2.7",
+            "pip3",
+            "pythonw",
+        ] {
+            assert!(!is_python_interpreter_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn discovers_versioned_interpreters_in_search_dirs() {
+        let dir =
+            std::env::temp_dir().join(format!("helm-pip-interpreters-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["python3.12", "python3", "python3.12-config", "pip3"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let found = discover_python_interpreters(&[dir.clone(), dir.clone()]);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(found, vec![dir.join("python3"), dir.join("python3.12")]);
+    }
+
     #[test]
     fn invalid_json_produces_parse_failure() {
         let error = parse_pip_list("{not-json").expect_err("expected parse failure");
@@ -1,10 +1,12 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
 use crate::adapters::pip::{
-    PipDetectOutput, PipSource, pip_detect_request, pip_install_request, pip_list_outdated_request,
-    pip_list_request, pip_uninstall_request, pip_upgrade_request,
+    PipDetectOutput, PipSource, pip_cache_dir_request, pip_cache_purge_request, pip_detect_request,
+    pip_install_request, pip_list_outdated_request, pip_list_request, pip_show_files_request,
+    pip_show_request, pip_uninstall_request, pip_upgrade_request,
 };
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
@@ -12,14 +14,39 @@ use crate::models::ManagerId;
 
 pub struct ProcessPipSource {
     executor: Arc<dyn ProcessExecutor>,
+    interpreter: Option<PathBuf>,
 }
 
 impl ProcessPipSource {
     pub fn new(executor: Arc<dyn ProcessExecutor>) -> Self {
-        Self { executor }
+        Self {
+            executor,
+            interpreter: None,
+        }
+    }
+
+    /// Run pip through `interpreter` instead of the first `python3` on the
+    /// search path, to read one specific Python's packages.
+    pub fn with_interpreter(mut self, interpreter: PathBuf) -> Self {
+        self.interpreter = Some(interpreter);
+        self
+    }
+
+    fn python_executable(&self) -> Option<PathBuf> {
+        self.interpreter.clone().or_else(|| {
+            which_executable(
+                self.executor.as_ref(),
+                "python3",
+                &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"],
+                ManagerId::Pip,
+            )
+        })
     }
 
-    fn configure_request(&self, mut request: ProcessSpawnRequest) -> ProcessSpawnRequest {
+    pub(crate) fn configure_request(
+        &self,
+        mut request: ProcessSpawnRequest,
+    ) -> ProcessSpawnRequest {
         let path = std::env::var("PATH").unwrap_or_default();
         let new_path = format!("/opt/homebrew/bin:/usr/local/bin:/usr/bin:{path}");
         request.command = request
@@ -29,12 +56,7 @@ impl ProcessPipSource {
             .env("PIP_NO_INPUT", "1");
 
         if request.command.program.to_str() == Some("python3")
-            && let Some(exe) = which_executable(
-                self.executor.as_ref(),
-                "python3",
-                &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"],
-                ManagerId::Pip,
-            )
+            && let Some(exe) = self.python_executable()
         {
             request.command.program = exe;
         }
@@ -45,12 +67,7 @@ impl ProcessPipSource {
 
 impl PipSource for ProcessPipSource {
     fn detect(&self) -> AdapterResult<PipDetectOutput> {
-        let executable_path = which_executable(
-            self.executor.as_ref(),
-            "python3",
-            &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"],
-            ManagerId::Pip,
-        );
+        let executable_path = self.python_executable();
 
         let request = self.configure_request(pip_detect_request(None));
         let version_output = run_and_collect_version_output(self.executor.as_ref(), request);
@@ -85,4 +102,24 @@ impl PipSource for ProcessPipSource {
         let request = self.configure_request(pip_upgrade_request(None, name));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
+
+    fn show_files(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(pip_show_files_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn show(&self, name: &str) -> AdapterResult<String> {
+        let request = self.configure_request(pip_show_request(None, name));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn cache_dir(&self) -> AdapterResult<String> {
+        let request = self.configure_request(pip_cache_dir_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
+
+    fn purge_cache(&self) -> AdapterResult<String> {
+        let request = self.configure_request(pip_cache_purge_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
 }
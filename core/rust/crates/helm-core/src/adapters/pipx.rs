@@ -4,7 +4,9 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
-use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::manager::{
+    AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter, measure_package_directories,
+};
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
@@ -21,6 +23,7 @@ const PIPX_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::PackageSizes,
 ];
 
 const PIPX_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -48,9 +51,14 @@ pub trait PipxSource: Send + Sync {
     fn list_installed(&self) -> AdapterResult<String>;
     fn list_outdated(&self) -> AdapterResult<String>;
     fn search(&self, query: &str) -> AdapterResult<String>;
+    /// PyPI JSON API document for `project`, or `None` when PyPI has no such
+    /// project or no PyPI client is installed.
+    fn search_pypi(&self, project: &str) -> AdapterResult<Option<String>>;
     fn install(&self, name: &str, version: Option<&str>) -> AdapterResult<String>;
     fn uninstall(&self, name: &str) -> AdapterResult<String>;
     fn upgrade(&self, name: Option<&str>) -> AdapterResult<String>;
+    /// `pipx environment --value PIPX_LOCAL_VENVS`.
+    fn venvs_dir(&self) -> AdapterResult<String>;
 }
 
 pub struct PipxAdapter<S: PipxSource> {
@@ -93,15 +101,19 @@ impl<S: PipxSource> ManagerAdapter for PipxAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_pipx_list(&self.source.list_installed()?)?;
-                let outdated = parse_pipx_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_pipx_list(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_pipx_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -113,9 +125,33 @@ impl<S: PipxSource> ManagerAdapter for PipxAdapter<S> {
                 let packages = parse_pipx_outdated(&raw)?;
                 Ok(AdapterResponse::OutdatedPackages(packages))
             }
+            AdapterRequest::MeasurePackageSizes(_) => {
+                let venvs = parse_pipx_venvs_dir(&self.source.venvs_dir()?)?;
+                let installed = parse_pipx_list(&self.source.list_installed()?)?;
+                // Installed packages are named after their venv directory.
+                Ok(AdapterResponse::PackageSizes(measure_package_directories(
+                    installed
+                        .into_iter()
+                        .filter(|installed| is_venv_dir_name(&installed.package.name))
+                        .map(|installed| {
+                            let venv = venvs.join(&installed.package.name);
+                            (installed.package, vec![venv])
+                        }),
+                )))
+            }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search(search_request.query.text.as_str())?;
-                let results = parse_pipx_local_search(&raw, &search_request.query)?;
+                let mut results = parse_pipx_local_search(&raw, &search_request.query)?;
+                if let Some(project) = pypi_project_name(&search_request.query.text)
+                    && let Some(raw) = self.source.search_pypi(&project)?
+                    && let Some(remote) = parse_pypi_project(&raw, &search_request.query)?
+                {
+                    let remote_key = normalize_pypi_project_name(&remote.result.package.name);
+                    results.retain(|local| {
+                        normalize_pypi_project_name(&local.result.package.name) != remote_key
+                    });
+                    results.insert(0, remote);
+                }
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -148,6 +184,7 @@ impl<S: PipxSource> ManagerAdapter for PipxAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -173,33 +210,43 @@ impl<S: PipxSource> ManagerAdapter for PipxAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Pipx,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Pipx,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Pipx,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Pipx,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_pipx_outdated_entry(&self.source, name))
                     .transpose()?
                     .flatten();
-                let _ = self.source.upgrade(target_name)?;
+                if target_name.is_none() && !upgrade_request.options.is_empty() {
+                    crate::adapters::upgrade_outdated_except_held(
+                        &parse_pipx_outdated(&self.source.list_outdated()?)?,
+                        &upgrade_request.options,
+                        |name| self.source.upgrade(Some(name)),
+                    )?;
+                } else {
+                    let _ = self.source.upgrade(target_name)?;
+                }
                 if let Some(name) = target_name {
                     ensure_pipx_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Pipx),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: upgrade_request.version.or_else(|| {
@@ -208,6 +255,7 @@ impl<S: PipxSource> ManagerAdapter for PipxAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -308,6 +356,16 @@ pub fn pipx_upgrade_request(task_id: Option<TaskId>, name: Option<&str>) -> Proc
     )
 }
 
+pub fn pipx_venvs_dir_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+    pipx_request(
+        task_id,
+        TaskType::Refresh,
+        ManagerAction::MeasurePackageSizes,
+        CommandSpec::new(PIPX_COMMAND).args(["environment", "--value", "PIPX_LOCAL_VENVS"]),
+        DETECT_TIMEOUT,
+    )
+}
+
 fn pipx_request(
     task_id: Option<TaskId>,
     task_type: TaskType,
@@ -424,6 +482,7 @@ fn parse_pipx_list(output: &str) -> AdapterResult<Vec<InstalledPackage>> {
             installed_version,
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -463,6 +522,7 @@ fn parse_pipx_local_search(
                 source_manager: ManagerId::Pipx,
                 originating_query: query.text.clone(),
                 cached_at: query.issued_at,
+                popularity: Default::default(),
             })
         })
         .collect::<Vec<_>>();
@@ -471,6 +531,91 @@ fn parse_pipx_local_search(
     Ok(results)
 }
 
+/// Returns the PEP 503 normalized project name for a search query, or `None`
+/// when the query cannot name a PyPI project.
+fn pypi_project_name(query: &str) -> Option<String> {
+    let trimmed = query.trim();
+    if trimmed.is_empty()
+        || !trimmed.starts_with(|c: char| c.is_ascii_alphanumeric())
+        || !trimmed.ends_with(|c: char| c.is_ascii_alphanumeric())
+        || !trimmed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return None;
+    }
+    Some(normalize_pypi_project_name(trimmed))
+}
+
+fn normalize_pypi_project_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut previous_separator = false;
+    for c in name.trim().chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !previous_separator {
+                normalized.push('-');
+            }
+            previous_separator = true;
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+            previous_separator = false;
+        }
+    }
+    normalized
+}
+
+#[derive(Debug, Deserialize)]
+struct PypiProjectRoot {
+    info: Option<PypiProjectInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PypiProjectInfo {
+    name: Option<String>,
+    version: Option<String>,
+    summary: Option<String>,
+}
+
+fn parse_pypi_project(
+    output: &str,
+    query: &SearchQuery,
+) -> AdapterResult<Option<CachedSearchResult>> {
+    let root: PypiProjectRoot = serde_json::from_str(output)
+        .map_err(|e| parse_error(&format!("invalid PyPI project JSON: {e}")))?;
+    let Some(info) = root.info else {
+        return Ok(None);
+    };
+    let Some(name) = info
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(Some(CachedSearchResult {
+        result: PackageCandidate {
+            package: PackageRef {
+                manager: ManagerId::Pipx,
+                name: name.to_string(),
+            },
+            package_identifier: None,
+            version: non_empty(info.version),
+            summary: non_empty(info.summary),
+        },
+        source_manager: ManagerId::Pipx,
+        originating_query: query.text.clone(),
+        cached_at: query.issued_at,
+        popularity: Default::default(),
+    }))
+}
+
 fn ensure_pipx_no_longer_outdated<S: PipxSource>(
     source: &S,
     package_name: &str,
@@ -612,6 +757,7 @@ fn parse_pipx_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -619,6 +765,19 @@ fn parse_pipx_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
     Ok(packages)
 }
 
+fn parse_pipx_venvs_dir(output: &str) -> AdapterResult<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .ok_or_else(|| parse_error("pipx environment did not print a venvs path"))
+}
+
+fn is_venv_dir_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains('/')
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::Pipx),
@@ -637,7 +796,7 @@ mod tests {
 
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
-        ListOutdatedRequest, ManagerAdapter, SearchRequest,
+        ListOutdatedRequest, ManagerAdapter, MeasurePackageSizesRequest, SearchRequest,
     };
     use crate::models::{
         CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType,
@@ -645,14 +804,16 @@ mod tests {
 
     use super::{
         PipxAdapter, PipxDetectOutput, PipxSource, parse_pipx_list, parse_pipx_local_search,
-        parse_pipx_outdated, parse_pipx_version, pipx_detect_request, pipx_install_request,
-        pipx_list_outdated_request, pipx_list_request, pipx_search_request, pipx_uninstall_request,
-        pipx_upgrade_request,
+        parse_pipx_outdated, parse_pipx_version, parse_pypi_project, pipx_detect_request,
+        pipx_install_request, pipx_list_outdated_request, pipx_list_request, pipx_search_request,
+        pipx_uninstall_request, pipx_upgrade_request, pypi_project_name,
     };
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/pipx/version.txt");
     const LIST_FIXTURE: &str = include_str!("../../tests/fixtures/pipx/list_global.json");
     const OUTDATED_FIXTURE: &str = include_str!("../../tests/fixtures/pipx/list_outdated.json");
+    const PYPI_PROJECT_FIXTURE: &str = include_str!("../../tests/fixtures/pipx/pypi_project.json");
+    const PYPI_NOT_FOUND_FIXTURE: &str = r#"{"message": "Not Found"}"#;
     const SUFFIXED_VENV_FIXTURE: &str = r#"{
   "pipx_spec_version": "0.1",
   "venvs": {
@@ -723,6 +884,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_pypi_project_lookup() {
+        let query = SearchQuery {
+            text: "HTTPie".to_string(),
+            issued_at: std::time::SystemTime::UNIX_EPOCH,
+        };
+
+        let result = parse_pypi_project(PYPI_PROJECT_FIXTURE, &query)
+            .unwrap()
+            .expect("project should resolve");
+        assert_eq!(result.result.package.manager, ManagerId::Pipx);
+        assert_eq!(result.result.package.name, "httpie");
+        assert_eq!(result.result.version.as_deref(), Some("3.2.4"));
+        assert!(
+            result
+                .result
+                .summary
+                .as_deref()
+                .is_some_and(|summary| summary.starts_with("HTTPie"))
+        );
+        assert_eq!(result.originating_query, "HTTPie");
+
+        assert!(
+            parse_pypi_project(PYPI_NOT_FOUND_FIXTURE, &query)
+                .unwrap()
+                .is_none()
+        );
+        let error = parse_pypi_project("<html>", &query).unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::ParseFailure);
+    }
+
+    #[test]
+    fn pypi_project_name_normalizes_queries_and_rejects_free_text() {
+        assert_eq!(pypi_project_name(" HTTPie ").as_deref(), Some("httpie"));
+        assert_eq!(
+            pypi_project_name("Foo__Bar.baz").as_deref(),
+            Some("foo-bar-baz")
+        );
+        assert_eq!(pypi_project_name("http client"), None);
+        assert_eq!(pypi_project_name("-leading"), None);
+        assert_eq!(pypi_project_name("../etc"), None);
+        assert_eq!(pypi_project_name(""), None);
+    }
+
     #[test]
     fn request_builders_use_expected_commands() {
         let detect = pipx_detect_request(Some(TaskId(12)));
@@ -768,6 +973,8 @@ mod tests {
         detect_result: AdapterResult<PipxDetectOutput>,
         list_result: AdapterResult<String>,
         outdated_result: AdapterResult<String>,
+        pypi_result: AdapterResult<Option<String>>,
+        venvs_dir_result: AdapterResult<String>,
     }
 
     impl StubPipxSource {
@@ -780,6 +987,8 @@ mod tests {
                 }),
                 list_result: Ok(LIST_FIXTURE.to_string()),
                 outdated_result: Ok(OUTDATED_FIXTURE.to_string()),
+                pypi_result: Ok(None),
+                venvs_dir_result: Ok("/nonexistent/helm-pipx-venvs\n".to_string()),
             }
         }
     }
@@ -802,6 +1011,10 @@ mod tests {
             self.list_result.clone()
         }
 
+        fn search_pypi(&self, _project: &str) -> AdapterResult<Option<String>> {
+            self.pypi_result.clone()
+        }
+
         fn install(&self, _name: &str, _version: Option<&str>) -> AdapterResult<String> {
             Ok(String::new())
         }
@@ -813,6 +1026,36 @@ mod tests {
         fn upgrade(&self, _name: Option<&str>) -> AdapterResult<String> {
             Ok(String::new())
         }
+
+        fn venvs_dir(&self) -> AdapterResult<String> {
+            self.venvs_dir_result.clone()
+        }
+    }
+
+    #[test]
+    fn measures_each_package_venv() {
+        let venvs = std::env::temp_dir().join(format!("helm-pipx-venvs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&venvs);
+        std::fs::create_dir_all(venvs.join("black/bin")).unwrap();
+        std::fs::write(venvs.join("black/bin/black"), vec![0_u8; 2048]).unwrap();
+
+        let adapter = PipxAdapter::new(StubPipxSource {
+            venvs_dir_result: Ok(format!("{}\n", venvs.display())),
+            ..StubPipxSource::success()
+        });
+        let response = adapter
+            .execute(AdapterRequest::MeasurePackageSizes(
+                MeasurePackageSizesRequest,
+            ))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&venvs);
+
+        let AdapterResponse::PackageSizes(sizes) = response else {
+            panic!("expected package sizes");
+        };
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].package.name, "black");
+        assert_eq!(sizes[0].bytes, 2048);
     }
 
     #[test]
@@ -879,6 +1122,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn execute_search_prefers_pypi_match_over_local_entry() {
+        let mut source = StubPipxSource::success();
+        source.pypi_result = Ok(Some(PYPI_PROJECT_FIXTURE.to_string()));
+        let adapter = PipxAdapter::new(source);
+
+        let response = adapter
+            .execute(AdapterRequest::Search(SearchRequest {
+                query: SearchQuery {
+                    text: "httpie".to_string(),
+                    issued_at: std::time::SystemTime::UNIX_EPOCH,
+                },
+            }))
+            .expect("search should succeed");
+
+        match response {
+            AdapterResponse::SearchResults(results) => {
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].result.package.name, "httpie");
+                assert_eq!(results[0].result.version.as_deref(), Some("3.2.4"));
+                assert!(results[0].result.summary.is_some());
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+    }
+
     #[test]
     fn install_returns_mutation_response() {
         let adapter = PipxAdapter::new(StubPipxSource::success());
@@ -891,6 +1160,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("24.10.0".to_string()),
+                options: Vec::new(),
             }))
             .expect("install should succeed");
 
@@ -5,11 +5,12 @@ use crate::adapters::manager::AdapterResult;
 use crate::adapters::pipx::{
     PipxDetectOutput, PipxSource, pipx_detect_request, pipx_install_request,
     pipx_list_outdated_request, pipx_list_request, pipx_search_request, pipx_uninstall_request,
-    pipx_upgrade_request,
+    pipx_upgrade_request, pipx_venvs_dir_request,
 };
 use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
+use crate::adapters::pypi::pypi_client;
 use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
-use crate::models::{ManagerId, SearchQuery};
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, SearchQuery, TaskType};
 
 pub struct ProcessPipxSource {
     executor: Arc<dyn ProcessExecutor>,
@@ -84,6 +85,21 @@ impl PipxSource for ProcessPipxSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
+    fn search_pypi(&self, project: &str) -> AdapterResult<Option<String>> {
+        let Some(client) = pypi_client() else {
+            return Ok(None);
+        };
+        client
+            .fetch_project_json(project)
+            .map_err(|error| CoreError {
+                manager: Some(ManagerId::Pipx),
+                task: Some(TaskType::Search),
+                action: Some(ManagerAction::Search),
+                kind: CoreErrorKind::ProcessFailure,
+                message: format!("PyPI lookup for '{project}' failed: {error}"),
+            })
+    }
+
     fn install(&self, name: &str, version: Option<&str>) -> AdapterResult<String> {
         let request = self.configure_request(pipx_install_request(None, name, version));
         run_and_collect_stdout(self.executor.as_ref(), request)
@@ -98,4 +114,9 @@ impl PipxSource for ProcessPipxSource {
         let request = self.configure_request(pipx_upgrade_request(None, name));
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
+
+    fn venvs_dir(&self) -> AdapterResult<String> {
+        let request = self.configure_request(pipx_venvs_dir_request(None));
+        run_and_collect_stdout(self.executor.as_ref(), request)
+    }
 }
@@ -94,15 +94,19 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_pnpm_list_installed(&self.source.list_installed_global()?)?;
-                let outdated = parse_pnpm_outdated(&self.source.list_outdated_global()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed_global()
+                    .and_then(|raw| parse_pnpm_list_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated_global()
+                    .and_then(|raw| parse_pnpm_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed_global()?;
@@ -116,7 +120,12 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
             }
             AdapterRequest::Search(search_request) => {
                 let raw = self.source.search(search_request.query.text.as_str())?;
-                let results = parse_pnpm_search(&raw, &search_request.query)?;
+                let mut results = parse_pnpm_search(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::Pnpm,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -147,6 +156,7 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -168,22 +178,24 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Pnpm,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Pnpm,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Pnpm,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Pnpm,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_pnpm_outdated_entry(&self.source, name))
@@ -194,7 +206,7 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
                     ensure_pnpm_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Pnpm),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: upgrade_request.version.or_else(|| {
@@ -203,6 +215,7 @@ impl<S: PnpmSource> ManagerAdapter for PnpmAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -372,6 +385,7 @@ fn parse_pnpm_list_installed(output: &str) -> AdapterResult<Vec<InstalledPackage
             installed_version: Some(version),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         })
         .collect())
 }
@@ -481,6 +495,7 @@ fn parse_pnpm_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -546,6 +561,7 @@ fn parse_pnpm_search(output: &str, query: &SearchQuery) -> AdapterResult<Vec<Cac
             source_manager: ManagerId::Pnpm,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -772,6 +788,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert_eq!(installed.expect("installed snapshot").len(), 3);
                 assert_eq!(outdated.expect("outdated snapshot").len(), 2);
@@ -801,6 +818,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert!(installed.expect("installed snapshot").is_empty());
                 assert!(outdated.expect("outdated snapshot").is_empty());
@@ -860,6 +878,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("5.7.2".to_string()),
+                options: Vec::new(),
             }))
             .expect("install should succeed");
 
@@ -884,6 +903,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
@@ -85,6 +85,7 @@ impl<S: PodmanSource> ManagerAdapter for PodmanAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
@@ -103,8 +104,10 @@ impl<S: PodmanSource> ManagerAdapter for PodmanAdapter<S> {
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]),
                     outdated: Some(outdated),
+                    stage_failures: Vec::new(),
                 })
             }
             AdapterRequest::ListInstalled(_) => {
@@ -121,6 +124,7 @@ impl<S: PodmanSource> ManagerAdapter for PodmanAdapter<S> {
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]
                 } else {
                     Vec::new()
@@ -293,6 +297,7 @@ fn parse_podman_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
                     .unwrap_or(false),
                 restart_required: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -88,16 +88,19 @@ impl<S: PoetrySource> ManagerAdapter for PoetryAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_poetry_plugins_installed(&self.source.list_plugins()?)?;
-                let outdated =
-                    parse_poetry_plugins_outdated(&self.source.list_outdated_plugins()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_plugins()
+                    .and_then(|raw| parse_poetry_plugins_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated_plugins()
+                    .and_then(|raw| parse_poetry_plugins_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_plugins()?;
@@ -142,6 +145,7 @@ impl<S: PoetrySource> ManagerAdapter for PoetryAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -163,22 +167,24 @@ impl<S: PoetrySource> ManagerAdapter for PoetryAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Poetry,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Poetry,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Poetry,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Poetry,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_poetry_outdated_plugin(&self.source, name))
@@ -189,13 +195,14 @@ impl<S: PoetrySource> ManagerAdapter for PoetryAdapter<S> {
                     ensure_poetry_plugin_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Poetry),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: targeted_outdated
                         .as_ref()
                         .and_then(|entry| entry.installed_version.clone()),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -376,6 +383,7 @@ fn parse_poetry_plugins_installed(output: &str) -> AdapterResult<Vec<InstalledPa
                 installed_version: version,
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -495,6 +503,7 @@ fn parse_poetry_plugins_outdated(output: &str) -> AdapterResult<Vec<OutdatedPack
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -537,6 +546,7 @@ fn parse_poetry_plugins_search(
             source_manager: ManagerId::Poetry,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -756,6 +766,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
@@ -1,25 +1,36 @@
 use crate::adapters::manager::AdapterResult;
 use crate::execution::{
-    ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest, spawn_validated,
+    ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest, ScriptInstallerStep,
+    spawn_validated,
 };
 use crate::models::{CoreError, CoreErrorKind};
 
 /// Run a process and return stdout, falling back to stderr if stdout is empty.
 /// Used for version detection where some tools output to stderr.
 /// Returns empty string only when spawn/wait fails or no textual output is produced.
+/// Spawn/wait failures other than a missing program are recorded as detection
+/// probe failures for the current task.
 pub(crate) fn run_and_collect_version_output(
     executor: &dyn ProcessExecutor,
     request: ProcessSpawnRequest,
 ) -> String {
     let process = match spawn_validated(executor, request) {
         Ok(p) => p,
-        Err(_) => return String::new(),
+        Err(error) => {
+            if !is_missing_program_spawn_failure(&error) {
+                crate::execution::record_detection_probe_failure(error.kind);
+            }
+            return String::new();
+        }
     };
 
     let handle = tokio::runtime::Handle::current();
     let output: ProcessOutput = match handle.block_on(process.wait()) {
         Ok(o) => o,
-        Err(_) => return String::new(),
+        Err(error) => {
+            crate::execution::record_detection_probe_failure(error.kind);
+            return String::new();
+        }
     };
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -35,6 +46,16 @@ pub(crate) fn run_and_collect_version_output(
     String::new()
 }
 
+/// Whether `error` is a spawn failure because the program does not exist.
+pub(crate) fn is_missing_program_spawn_failure(error: &CoreError) -> bool {
+    if error.kind != CoreErrorKind::ProcessFailure {
+        return false;
+    }
+    let message = error.message.to_ascii_lowercase();
+    message.contains("failed to spawn process")
+        && (message.contains("no such file or directory") || message.contains("os error 2"))
+}
+
 pub(crate) fn run_and_collect_stdout(
     executor: &dyn ProcessExecutor,
     request: ProcessSpawnRequest,
@@ -70,6 +91,27 @@ pub(crate) fn run_and_collect_stdout(
     }
 }
 
+/// Run a script-installer step; the command policy limits the spawn to the
+/// program `step` names and, for downloaded scripts, to their pinned digest.
+pub(crate) fn run_script_installer_and_collect_stdout(
+    executor: &dyn ProcessExecutor,
+    request: ProcessSpawnRequest,
+    step: ScriptInstallerStep,
+) -> AdapterResult<String> {
+    run_and_collect_stdout(executor, request.script_installer(step))
+}
+
+/// The last `NN%` or `NN.N%` figure in a progress line, truncated to a whole
+/// percentage. Figures above 100 are ignored.
+pub(crate) fn last_percent(line: &str) -> Option<u8> {
+    let end = line.rfind('%')?;
+    let start = line[..end]
+        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map_or(0, |index| index + 1);
+    let value: f64 = line[start..end].parse().ok()?;
+    (0.0..=100.0).contains(&value).then_some(value as u8)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -81,7 +123,17 @@ mod tests {
     };
     use crate::models::{CoreErrorKind, ManagerAction, ManagerId, TaskType};
 
-    use super::run_and_collect_stdout;
+    use super::{last_percent, run_and_collect_stdout};
+
+    #[test]
+    fn last_percent_reads_the_final_figure_in_a_line() {
+        assert_eq!(last_percent("######          45.2%"), Some(45));
+        assert_eq!(last_percent("Downloading: 12% of 100%"), Some(100));
+        assert_eq!(last_percent("[####------] 7.0% Downloading"), Some(7));
+        assert_eq!(last_percent("==> Pouring ripgrep"), None);
+        assert_eq!(last_percent("progress: %"), None);
+        assert_eq!(last_percent("150%"), None);
+    }
 
     #[derive(Clone)]
     struct StaticExecutor {
@@ -0,0 +1,28 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+static PYPI_CLIENT: OnceLock<RwLock<Option<Arc<dyn PypiClient>>>> = OnceLock::new();
+
+/// Host-provided reader for PyPI's JSON API
+/// (`https://pypi.org/pypi/<project>/json`).
+pub trait PypiClient: Send + Sync {
+    /// Fetch the JSON document for `project`. `Ok(None)` means PyPI has no
+    /// such project.
+    fn fetch_project_json(&self, project: &str) -> Result<Option<String>, String>;
+}
+
+/// Install the process-wide PyPI client. Without one, pipx search only
+/// matches installed packages.
+pub fn install_pypi_client(client: Arc<dyn PypiClient>) {
+    let slot = PYPI_CLIENT.get_or_init(|| RwLock::new(None));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = Some(client);
+}
+
+pub fn pypi_client() -> Option<Arc<dyn PypiClient>> {
+    let slot = PYPI_CLIENT.get()?;
+    slot.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
@@ -107,6 +107,7 @@ impl<S: Rosetta2Source> ManagerAdapter for Rosetta2Adapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -256,6 +257,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            options: Vec::new(),
         }));
 
         if host_is_apple_silicon() {
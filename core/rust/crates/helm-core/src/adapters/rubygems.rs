@@ -90,15 +90,19 @@ impl<S: RubyGemsSource> ManagerAdapter for RubyGemsAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_rubygems_list_installed(&self.source.list_installed()?)?;
-                let outdated = parse_rubygems_outdated(&self.source.list_outdated()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed()
+                    .and_then(|raw| parse_rubygems_list_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated()
+                    .and_then(|raw| parse_rubygems_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 let raw = self.source.list_installed()?;
@@ -148,6 +152,7 @@ impl<S: RubyGemsSource> ManagerAdapter for RubyGemsAdapter<S> {
                         &after_versions,
                         install_request.version.as_deref(),
                     ),
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -177,26 +182,28 @@ impl<S: RubyGemsSource> ManagerAdapter for RubyGemsAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::RubyGems,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    let requested_target = upgrade_request
-                        .target_name
-                        .as_deref()
-                        .unwrap_or(package.name.as_str());
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::RubyGems,
-                        ManagerAction::Upgrade,
-                        requested_target,
-                    )?;
-                    Some(requested_target)
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::RubyGems,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        let requested_target = upgrade_request
+                            .target_name
+                            .as_deref()
+                            .unwrap_or(package.name.as_str());
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::RubyGems,
+                            ManagerAction::Upgrade,
+                            requested_target,
+                        )?;
+                        Some(requested_target)
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_rubygems_outdated_entry(&self.source, name))
@@ -207,9 +214,12 @@ impl<S: RubyGemsSource> ManagerAdapter for RubyGemsAdapter<S> {
                     ensure_gem_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package: PackageRef {
-                        manager: package.manager,
-                        name: target_name.unwrap_or(package.name.as_str()).to_string(),
+                    package: match target_name {
+                        Some(name) => PackageRef {
+                            manager: ManagerId::RubyGems,
+                            name: name.to_string(),
+                        },
+                        None => upgrade_request.target.result_package(ManagerId::RubyGems),
                     },
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
@@ -219,6 +229,7 @@ impl<S: RubyGemsSource> ManagerAdapter for RubyGemsAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -552,6 +563,7 @@ fn parse_rubygems_list_installed(output: &str) -> AdapterResult<Vec<InstalledPac
                 installed_version: Some(installed_version),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -611,6 +623,7 @@ fn parse_rubygems_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         });
     }
 
@@ -683,6 +696,7 @@ fn parse_rubygems_search(
             source_manager: ManagerId::RubyGems,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -993,6 +1007,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("1.72.0".to_string()),
+                options: Vec::new(),
             }))
             .expect("install should succeed");
 
@@ -1020,6 +1035,7 @@ mod tests {
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .expect_err("expected ambiguous uninstall to fail");
@@ -1043,6 +1059,7 @@ mod tests {
                     },
                     target_name: None,
                     version: Some("1.72.0".to_string()),
+                    disposal: Default::default(),
                 },
             ))
             .expect("exact uninstall should succeed");
@@ -1073,6 +1090,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
@@ -1096,12 +1114,14 @@ mod tests {
 
         let response = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::RubyGems,
                     name: "rake".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect("upgrade should succeed when gem is no longer outdated");
 
@@ -1123,12 +1143,14 @@ mod tests {
 
         let error = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::RubyGems,
                     name: "rake".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect_err("upgrade should fail when gem remains outdated");
 
@@ -1143,9 +1165,11 @@ mod tests {
 
         let response = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: None,
+                target: crate::adapters::UpgradeTarget::All,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .expect("upgrade all should succeed without post-validation");
 
@@ -6,13 +6,14 @@ use crate::adapters::manager::{
     AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter, PackageDetailChildKind,
     PackageDetailOperation, PackageDetailRequest,
 };
-use crate::execution::{CommandSpec, ProcessSpawnRequest};
+use crate::execution::{CommandSpec, ProcessSpawnRequest, ScriptInstallerStep};
 use crate::models::{
     ActionSafety, CachedSearchResult, Capability, CoreError, CoreErrorKind, DetectionInfo,
     InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, OutdatedPackage, PackageCandidate, PackageRef, PackageRuntimeState, SearchQuery,
-    TaskId, TaskType,
+    ManagerId, ManagerSelfVersion, OutdatedPackage, PackageCandidate, PackageRef,
+    PackageRuntimeState, SearchQuery, TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 use serde::Serialize;
 
 const RUSTUP_CAPABILITIES: &[Capability] = &[
@@ -24,6 +25,7 @@ const RUSTUP_CAPABILITIES: &[Capability] = &[
     Capability::Install,
     Capability::Uninstall,
     Capability::Upgrade,
+    Capability::SelfVersionCheck,
 ];
 
 const RUSTUP_DESCRIPTOR: ManagerDescriptor = ManagerDescriptor {
@@ -142,6 +144,7 @@ impl<S: RustupSource> ManagerAdapter for RustupAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
                 sync_package_state_after_configuration(&self.source)
@@ -168,6 +171,10 @@ impl<S: RustupSource> ManagerAdapter for RustupAdapter<S> {
                 }
                 Ok(AdapterResponse::OutdatedPackages(packages))
             }
+            AdapterRequest::SelfVersionCheck(_) => {
+                let raw = self.source.check()?;
+                Ok(AdapterResponse::SelfVersion(parse_rustup_self_check(&raw)))
+            }
             AdapterRequest::Install(install_request) => {
                 if install_request.package.name == "__self__" {
                     let install_source = parse_install_source(install_request.version.as_deref())?;
@@ -184,6 +191,7 @@ impl<S: RustupSource> ManagerAdapter for RustupAdapter<S> {
                     action: ManagerAction::Install,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -239,24 +247,34 @@ impl<S: RustupSource> ManagerAdapter for RustupAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Rustup,
-                    name: "__self__".to_string(),
-                });
-                if package.name == "__self__" {
-                    let _ = self.source.self_update()?;
-                } else {
-                    let _ = self.source.update_toolchain(&package.name)?;
-                }
+                let package = match &upgrade_request.target {
+                    UpgradeTarget::Package(package) => {
+                        let _ = self.source.update_toolchain(&package.name)?;
+                        package.clone()
+                    }
+                    // An untargeted rustup upgrade has always meant a rustup self-update.
+                    UpgradeTarget::SelfUpdate | UpgradeTarget::All => {
+                        let _ = self.source.self_update()?;
+                        UpgradeTarget::SelfUpdate.result_package(ManagerId::Rustup)
+                    }
+                    target => {
+                        return Err(crate::adapters::unsupported_upgrade_target_error(
+                            ManagerId::Rustup,
+                            target,
+                        ));
+                    }
+                };
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
                     package,
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
@@ -359,6 +377,22 @@ impl<S: RustupSource> ManagerAdapter for RustupAdapter<S> {
                         let _ = self.source.set_profile(profile.as_str())?;
                         sync_package_state_after_configuration(&self.source)
                     }
+                    PackageDetailOperation::AddChild {
+                        kind: PackageDetailChildKind::Tap,
+                        ..
+                    }
+                    | PackageDetailOperation::RemoveChild {
+                        kind: PackageDetailChildKind::Tap,
+                        ..
+                    }
+                    | PackageDetailOperation::AddTap { .. } => Err(CoreError {
+                        manager: Some(ManagerId::Rustup),
+                        task: Some(TaskType::Configure),
+                        action: Some(ManagerAction::Configure),
+                        kind: CoreErrorKind::InvalidInput,
+                        message: "rustup package detail configuration does not manage taps"
+                            .to_string(),
+                    }),
                 }
             }
             _ => Err(CoreError {
@@ -521,6 +555,12 @@ pub fn rustup_init_install_request(
     .idle_timeout(INSTALL_IDLE_TIMEOUT)
 }
 
+/// SHA-256 of the `https://sh.rustup.rs` install script this adapter is
+/// reviewed against. The downloaded script must match it before Helm runs it,
+/// so this is bumped together with any upstream change to the script.
+pub const RUSTUP_INIT_SCRIPT_SHA256: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
 pub fn rustup_download_install_script_request(
     task_id: Option<TaskId>,
     output_script: &str,
@@ -540,6 +580,7 @@ pub fn rustup_download_install_script_request(
         ]),
         INSTALL_TIMEOUT,
     )
+    .script_installer(ScriptInstallerStep::Download)
 }
 
 pub fn rustup_run_downloaded_install_script_request(
@@ -1142,6 +1183,7 @@ fn resolve_rustup_search_default_host<S: RustupSource>(source: &S) -> Option<Str
 fn sync_package_state_after_configuration<S: RustupSource>(
     source: &S,
 ) -> AdapterResult<AdapterResponse> {
+    let mut stage_failures = Vec::new();
     let installed = match source.toolchain_list() {
         Ok(raw) => {
             let mut packages = parse_toolchain_list(&raw)?;
@@ -1154,6 +1196,10 @@ fn sync_package_state_after_configuration<S: RustupSource>(
                 error = %error.message,
                 "rustup package-state sync skipped installed snapshot refresh after configuration"
             );
+            stage_failures.push(crate::adapters::snapshot_stage_failure(
+                error,
+                ManagerAction::ListInstalled,
+            ));
             None
         }
     };
@@ -1177,6 +1223,10 @@ fn sync_package_state_after_configuration<S: RustupSource>(
                 error = %error.message,
                 "rustup package-state sync skipped outdated snapshot refresh after configuration"
             );
+            stage_failures.push(crate::adapters::snapshot_stage_failure(
+                error,
+                ManagerAction::ListOutdated,
+            ));
             None
         }
     };
@@ -1184,6 +1234,7 @@ fn sync_package_state_after_configuration<S: RustupSource>(
     Ok(AdapterResponse::SnapshotSync {
         installed,
         outdated,
+        stage_failures,
     })
 }
 
@@ -1443,6 +1494,7 @@ fn parse_toolchain_list(output: &str) -> AdapterResult<Vec<InstalledPackage>> {
             installed_version: derive_toolchain_version_from_name(name),
             pinned: false,
             runtime_state,
+            sizes: Default::default(),
         });
     }
 
@@ -1593,6 +1645,7 @@ fn build_rustup_search_results(
             source_manager: ManagerId::Rustup,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -1658,12 +1711,44 @@ fn parse_rustup_check(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
             pinned: false,
             restart_required: false,
             runtime_state: PackageRuntimeState::default(),
+            sizes: Default::default(),
         });
     }
 
     Ok(packages)
 }
 
+/// Parse rustup's own line from `rustup check`, either
+/// `rustup - Update available : 1.27.0 -> 1.28.2` or `rustup - Up to date : 1.28.2`.
+fn parse_rustup_self_check(output: &str) -> ManagerSelfVersion {
+    for line in output.lines().map(str::trim) {
+        let Some(status) = line.strip_prefix("rustup - ") else {
+            continue;
+        };
+        if let Some(update) = status.strip_prefix("Update available : ")
+            && let Some((installed, latest)) = update.split_once(" -> ")
+        {
+            return ManagerSelfVersion {
+                installed_version: normalize_self_version(installed),
+                latest_version: normalize_self_version(latest),
+            };
+        }
+        if let Some(current) = status.strip_prefix("Up to date : ") {
+            let version = normalize_self_version(current);
+            return ManagerSelfVersion {
+                installed_version: version.clone(),
+                latest_version: version,
+            };
+        }
+    }
+    ManagerSelfVersion::default()
+}
+
+fn normalize_self_version(raw: &str) -> Option<String> {
+    let version = raw.trim();
+    (!version.is_empty()).then(|| version.to_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -1676,7 +1761,10 @@ mod tests {
         ListOutdatedRequest, ManagerAdapter, PackageDetailChildKind, PackageDetailOperation,
         PackageDetailRequest, SearchRequest,
     };
-    use crate::models::{ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType};
+    use crate::execution::ScriptInstallerStep;
+    use crate::models::{
+        ManagerAction, ManagerId, ManagerSelfVersion, PackageRef, SearchQuery, TaskId, TaskType,
+    };
 
     use super::{
         INSTALL_IDLE_TIMEOUT, INSTALL_TIMEOUT, RustupAdapter, RustupDetectOutput,
@@ -1684,9 +1772,9 @@ mod tests {
         UNINSTALL_TIMEOUT, UPGRADE_IDLE_TIMEOUT, UPGRADE_TIMEOUT, build_rustup_search_results,
         infer_default_host_from_installed_toolchains, parse_install_source, parse_rustup_check,
         parse_rustup_component_list, parse_rustup_default_host, parse_rustup_runtime_state,
-        parse_rustup_target_list, parse_rustup_version, parse_toolchain_list,
-        parse_toolchain_version_output, rustup_check_request, rustup_detect_request,
-        rustup_download_install_script_request, rustup_init_install_request,
+        parse_rustup_self_check, parse_rustup_target_list, parse_rustup_version,
+        parse_toolchain_list, parse_toolchain_version_output, rustup_check_request,
+        rustup_detect_request, rustup_download_install_script_request, rustup_init_install_request,
         rustup_run_downloaded_install_script_request, rustup_self_uninstall_request,
         rustup_self_update_request, rustup_show_request, rustup_toolchain_detail,
         rustup_toolchain_install_request, rustup_toolchain_list_request,
@@ -1974,6 +2062,22 @@ wasm32-unknown-unknown\n";
         assert!(packages.is_empty());
     }
 
+    #[test]
+    fn parses_rustup_self_version_from_check_output() {
+        let up_to_date = parse_rustup_self_check(CHECK_FIXTURE);
+        assert_eq!(up_to_date.installed_version.as_deref(), Some("1.28.2"));
+        assert!(!up_to_date.update_available());
+
+        let outdated = parse_rustup_self_check(
+            "stable-x86_64-apple-darwin - Up to date : 1.93.0\nrustup - Update available : 1.27.1 -> 1.28.2\n",
+        );
+        assert_eq!(outdated.installed_version.as_deref(), Some("1.27.1"));
+        assert_eq!(outdated.latest_version.as_deref(), Some("1.28.2"));
+        assert!(outdated.update_available());
+
+        assert_eq!(parse_rustup_self_check(""), ManagerSelfVersion::default());
+    }
+
     #[test]
     fn parses_empty_check_output() {
         let packages = parse_rustup_check("").unwrap();
@@ -2074,6 +2178,7 @@ wasm32-unknown-unknown\n";
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -2094,6 +2199,7 @@ wasm32-unknown-unknown\n";
                 },
                 target_name: None,
                 version: Some("existingBinaryPath:/tmp/rustup-init".to_string()),
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -2115,6 +2221,7 @@ wasm32-unknown-unknown\n";
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -2138,6 +2245,7 @@ wasm32-unknown-unknown\n";
                 },
                 target_name: None,
                 version: Some("1.93.0".to_string()),
+                options: Vec::new(),
             }))
             .expect_err("toolchain install with --version should fail");
         assert_eq!(error.kind, crate::models::CoreErrorKind::InvalidInput);
@@ -2158,6 +2266,7 @@ wasm32-unknown-unknown\n";
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .unwrap();
@@ -2181,6 +2290,7 @@ wasm32-unknown-unknown\n";
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 },
             ))
             .unwrap();
@@ -2199,12 +2309,11 @@ wasm32-unknown-unknown\n";
 
         let result = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
-                    manager: ManagerId::Rustup,
-                    name: "__self__".to_string(),
-                }),
+                target: crate::adapters::UpgradeTarget::SelfUpdate,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -2217,12 +2326,14 @@ wasm32-unknown-unknown\n";
 
         let result = adapter
             .execute(AdapterRequest::Upgrade(crate::adapters::UpgradeRequest {
-                package: Some(crate::models::PackageRef {
+                target: crate::adapters::UpgradeTarget::Package(crate::models::PackageRef {
                     manager: ManagerId::Rustup,
                     name: "stable-x86_64-apple-darwin".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
@@ -2253,7 +2364,8 @@ wasm32-unknown-unknown\n";
             add_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2276,7 +2388,8 @@ wasm32-unknown-unknown\n";
             remove_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2314,7 +2427,8 @@ wasm32-unknown-unknown\n";
             add_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2337,7 +2451,8 @@ wasm32-unknown-unknown\n";
             remove_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2372,7 +2487,8 @@ wasm32-unknown-unknown\n";
             default_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2394,7 +2510,8 @@ wasm32-unknown-unknown\n";
             override_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2416,7 +2533,8 @@ wasm32-unknown-unknown\n";
             unset_override_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2435,7 +2553,8 @@ wasm32-unknown-unknown\n";
             profile_result,
             AdapterResponse::SnapshotSync {
                 installed: _,
-                outdated: _
+                outdated: _,
+                ..
             }
         ));
 
@@ -2603,6 +2722,10 @@ wasm32-unknown-unknown\n";
             ]
         );
         assert_eq!(request.timeout, Some(INSTALL_TIMEOUT));
+        assert_eq!(
+            request.script_installer,
+            Some(ScriptInstallerStep::Download)
+        );
     }
 
     #[test]
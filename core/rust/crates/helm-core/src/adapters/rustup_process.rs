@@ -5,21 +5,25 @@ use std::time::Duration;
 
 use crate::adapters::detect_utils::which_executable;
 use crate::adapters::manager::AdapterResult;
-use crate::adapters::process_utils::{run_and_collect_stdout, run_and_collect_version_output};
+use crate::adapters::process_utils::{
+    is_missing_program_spawn_failure, run_and_collect_stdout, run_and_collect_version_output,
+    run_script_installer_and_collect_stdout,
+};
 use crate::adapters::rustup::{
-    RustupDetectOutput, RustupInstallSource, RustupSource, RustupToolchainDetail,
-    rustup_add_component_request, rustup_add_target_request, rustup_check_request,
-    rustup_component_list_request, rustup_detect_request, rustup_download_install_script_request,
-    rustup_init_install_request, rustup_override_list_request, rustup_remove_component_request,
-    rustup_remove_target_request, rustup_run_downloaded_install_script_request,
-    rustup_self_uninstall_request, rustup_self_update_request, rustup_set_default_request,
-    rustup_set_override_request, rustup_set_profile_request, rustup_show_profile_request,
-    rustup_show_request, rustup_target_list_request, rustup_toolchain_detail,
-    rustup_toolchain_install_request, rustup_toolchain_list_request,
-    rustup_toolchain_uninstall_request, rustup_toolchain_update_request,
-    rustup_toolchain_version_request, rustup_unset_override_request,
+    RUSTUP_INIT_SCRIPT_SHA256, RustupDetectOutput, RustupInstallSource, RustupSource,
+    RustupToolchainDetail, rustup_add_component_request, rustup_add_target_request,
+    rustup_check_request, rustup_component_list_request, rustup_detect_request,
+    rustup_download_install_script_request, rustup_init_install_request,
+    rustup_override_list_request, rustup_remove_component_request, rustup_remove_target_request,
+    rustup_run_downloaded_install_script_request, rustup_self_uninstall_request,
+    rustup_self_update_request, rustup_set_default_request, rustup_set_override_request,
+    rustup_set_profile_request, rustup_show_profile_request, rustup_show_request,
+    rustup_target_list_request, rustup_toolchain_detail, rustup_toolchain_install_request,
+    rustup_toolchain_list_request, rustup_toolchain_uninstall_request,
+    rustup_toolchain_update_request, rustup_toolchain_version_request,
+    rustup_unset_override_request,
 };
-use crate::execution::{ProcessExecutor, ProcessSpawnRequest};
+use crate::execution::{ProcessExecutor, ProcessSpawnRequest, ScriptInstallerStep};
 use crate::models::{CoreError, CoreErrorKind, ManagerId};
 
 pub struct ProcessRustupSource {
@@ -179,7 +183,10 @@ impl RustupSource for ProcessRustupSource {
             RustupInstallSource::OfficialDownload => self.install_self_via_official_download(),
             RustupInstallSource::ExistingBinaryPath(path) => {
                 let request = self.configure_request(rustup_init_install_request(None, path));
-                run_and_collect_stdout(self.executor.as_ref(), request)
+                let step = ScriptInstallerStep::RunExisting {
+                    program: request.command.program.clone(),
+                };
+                run_script_installer_and_collect_stdout(self.executor.as_ref(), request, step)
             }
         }
     }
@@ -311,7 +318,9 @@ impl ProcessRustupSource {
     fn install_self_via_official_download(&self) -> AdapterResult<String> {
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let temp_dir = std::env::temp_dir();
+        // Outside a task (direct CLI calls) there is no scratch dir to clean up after us.
+        let temp_dir =
+            crate::task_context::current_task_temp_dir().unwrap_or_else(|_| std::env::temp_dir());
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|duration| duration.as_millis())
@@ -332,7 +341,14 @@ impl ProcessRustupSource {
             let install_request = self.configure_request(
                 rustup_run_downloaded_install_script_request(None, &script_path_string),
             );
-            run_and_collect_stdout(self.executor.as_ref(), install_request)
+            run_script_installer_and_collect_stdout(
+                self.executor.as_ref(),
+                install_request,
+                ScriptInstallerStep::Run {
+                    script_path: script_path.clone(),
+                    sha256: RUSTUP_INIT_SCRIPT_SHA256.to_string(),
+                },
+            )
         };
 
         let _ = std::fs::remove_file(&script_path);
@@ -349,15 +365,6 @@ fn is_rustup_home_not_empty_failure(error: &CoreError) -> bool {
         && (message.contains("directory not empty") || message.contains("os error 66"))
 }
 
-fn is_missing_program_spawn_failure(error: &CoreError) -> bool {
-    if error.kind != CoreErrorKind::ProcessFailure {
-        return false;
-    }
-    let message = error.message.to_ascii_lowercase();
-    message.contains("failed to spawn process")
-        && (message.contains("no such file or directory") || message.contains("os error 2"))
-}
-
 fn resolve_rustup_home_path() -> Option<PathBuf> {
     if let Ok(custom) = std::env::var("RUSTUP_HOME") {
         let trimmed = custom.trim();
@@ -2,12 +2,14 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::process_utils::last_percent;
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, Capability, CoreError, CoreErrorKind, DetectionInfo, ManagerAction,
     ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId, OutdatedPackage, PackageRef,
     TaskId, TaskType,
 };
+use crate::upgrade_target::UpgradeTarget;
 
 const SOFTWAREUPDATE_READ_CAPABILITIES: &[Capability] = &[
     Capability::Detect,
@@ -39,7 +41,7 @@ pub struct SoftwareUpdateDetectOutput {
 pub trait SoftwareUpdateSource: Send + Sync {
     fn detect(&self) -> AdapterResult<SoftwareUpdateDetectOutput>;
     fn list_available(&self) -> AdapterResult<String>;
-    fn install_all_updates(&self) -> AdapterResult<String>;
+    fn install_updates(&self, labels: &[String]) -> AdapterResult<String>;
 }
 
 pub struct SoftwareUpdateAdapter<S: SoftwareUpdateSource> {
@@ -82,6 +84,7 @@ impl<S: SoftwareUpdateSource> ManagerAdapter for SoftwareUpdateAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: None,
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
@@ -89,6 +92,7 @@ impl<S: SoftwareUpdateSource> ManagerAdapter for SoftwareUpdateAdapter<S> {
                 Ok(AdapterResponse::SnapshotSync {
                     installed: None,
                     outdated: Some(outdated),
+                    stage_failures: Vec::new(),
                 })
             }
             AdapterRequest::ListOutdated(_) => {
@@ -97,11 +101,7 @@ impl<S: SoftwareUpdateSource> ManagerAdapter for SoftwareUpdateAdapter<S> {
                 Ok(AdapterResponse::OutdatedPackages(packages))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::SoftwareUpdate,
-                    name: "__all__".to_string(),
-                });
-                if package.name != "__confirm_os_updates__" {
+                if !matches!(upgrade_request.target, UpgradeTarget::OsUpdatesConfirmed) {
                     return Err(CoreError {
                         manager: Some(ManagerId::SoftwareUpdate),
                         task: None,
@@ -111,13 +111,28 @@ impl<S: SoftwareUpdateSource> ManagerAdapter for SoftwareUpdateAdapter<S> {
                             .to_string(),
                     });
                 }
-                let _ = self.source.install_all_updates()?;
+                // `options` lists the exact labels the user confirmed. Updates
+                // that appeared since are left for the next confirmation.
+                if upgrade_request.options.is_empty() {
+                    return Err(CoreError {
+                        manager: Some(ManagerId::SoftwareUpdate),
+                        task: None,
+                        action: Some(ManagerAction::Upgrade),
+                        kind: CoreErrorKind::InvalidInput,
+                        message: "softwareupdate upgrades require the confirmed update labels"
+                            .to_string(),
+                    });
+                }
+                let _ = self.source.install_updates(&upgrade_request.options)?;
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request
+                        .target
+                        .result_package(ManagerId::SoftwareUpdate),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: None,
                     after_version: None,
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -152,15 +167,30 @@ pub fn softwareupdate_list_request(task_id: Option<TaskId>) -> ProcessSpawnReque
     )
 }
 
-pub fn softwareupdate_upgrade_request(task_id: Option<TaskId>) -> ProcessSpawnRequest {
+/// Install only the given update labels, leaving every other pending update alone.
+pub fn softwareupdate_install_labels_request(
+    task_id: Option<TaskId>,
+    labels: &[String],
+) -> ProcessSpawnRequest {
     softwareupdate_request(
         task_id,
         TaskType::Upgrade,
         ManagerAction::Upgrade,
-        CommandSpec::new(SOFTWAREUPDATE_COMMAND).args(["-i", "-a"]),
+        CommandSpec::new(SOFTWAREUPDATE_COMMAND)
+            .arg("-i")
+            .args(labels.iter().cloned()),
         UPGRADE_TIMEOUT,
     )
     .requires_elevation(true)
+    .progress_parser(softwareupdate_download_progress)
+}
+
+/// `softwareupdate -i` reports `Downloading: 42.50%` while fetching updates.
+fn softwareupdate_download_progress(line: &str) -> Option<u8> {
+    if !line.starts_with("Download") {
+        return None;
+    }
+    last_percent(line)
 }
 
 fn softwareupdate_request(
@@ -206,6 +236,7 @@ fn parse_softwareupdate_list(output: &str) -> AdapterResult<Vec<OutdatedPackage>
     let mut current_label: Option<String> = None;
     let mut current_version: Option<String> = None;
     let mut current_restart_required = false;
+    let mut current_download_size: Option<u64> = None;
 
     for line in output.lines() {
         let trimmed = line.trim();
@@ -218,10 +249,12 @@ fn parse_softwareupdate_list(output: &str) -> AdapterResult<Vec<OutdatedPackage>
                 current_label.take(),
                 current_version.take(),
                 current_restart_required,
+                current_download_size.take(),
             )?;
             current_label = Some(rest.trim().to_owned());
             current_version = None;
             current_restart_required = false;
+            current_download_size = None;
             continue;
         }
 
@@ -236,6 +269,7 @@ fn parse_softwareupdate_list(output: &str) -> AdapterResult<Vec<OutdatedPackage>
                     let value = value.trim();
                     match key {
                         "Version" => current_version = Some(value.to_owned()),
+                        "Size" => current_download_size = parse_softwareupdate_size(value),
                         "Action" if value.eq_ignore_ascii_case("restart") => {
                             current_restart_required = true;
                         }
@@ -252,6 +286,7 @@ fn parse_softwareupdate_list(output: &str) -> AdapterResult<Vec<OutdatedPackage>
         current_label.take(),
         current_version.take(),
         current_restart_required,
+        current_download_size.take(),
     )?;
 
     Ok(packages)
@@ -262,6 +297,7 @@ fn flush_softwareupdate_block(
     label: Option<String>,
     version: Option<String>,
     restart_required: bool,
+    download_size_bytes: Option<u64>,
 ) -> AdapterResult<()> {
     let Some(label) = label else {
         return Ok(());
@@ -273,7 +309,9 @@ fn flush_softwareupdate_block(
         )));
     };
 
-    packages.push(build_outdated_package(&label, &version, restart_required));
+    let mut package = build_outdated_package(&label, &version, restart_required);
+    package.sizes.download_size_bytes = download_size_bytes;
+    packages.push(package);
     Ok(())
 }
 
@@ -293,9 +331,28 @@ fn build_outdated_package(
         pinned: false,
         restart_required,
         runtime_state: Default::default(),
+        sizes: Default::default(),
     }
 }
 
+/// Parse a `softwareupdate --list` size field such as `1803133KiB` into bytes.
+pub(crate) fn parse_softwareupdate_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let amount: u64 = digits.parse().ok()?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return None,
+    };
+    amount.checked_mul(multiplier)
+}
+
 fn parse_error(message: &str) -> CoreError {
     CoreError {
         manager: Some(ManagerId::SoftwareUpdate),
@@ -309,21 +366,44 @@ fn parse_error(message: &str) -> CoreError {
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
-    use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     use crate::adapters::manager::{
         AdapterRequest, AdapterResponse, AdapterResult, DetectRequest, ListInstalledRequest,
         ListOutdatedRequest, ManagerAdapter, UpgradeRequest,
     };
     use crate::models::{CoreErrorKind, ManagerAction, ManagerId, TaskId, TaskType};
+    use crate::upgrade_target::UpgradeTarget;
 
     use super::{
         SoftwareUpdateAdapter, SoftwareUpdateDetectOutput, SoftwareUpdateSource,
-        parse_softwareupdate_list, parse_softwareupdate_version, softwareupdate_detect_request,
-        softwareupdate_list_request, softwareupdate_upgrade_request,
+        parse_softwareupdate_list, parse_softwareupdate_size, parse_softwareupdate_version,
+        softwareupdate_detect_request, softwareupdate_download_progress,
+        softwareupdate_install_labels_request, softwareupdate_list_request,
     };
 
+    #[test]
+    fn download_progress_reads_downloading_lines() {
+        assert_eq!(
+            softwareupdate_download_progress("Downloading: 42.50%"),
+            Some(42)
+        );
+        assert_eq!(
+            softwareupdate_download_progress("Downloaded: 100.00%"),
+            Some(100)
+        );
+        assert_eq!(
+            softwareupdate_download_progress("Software Update found the following: 3%"),
+            None
+        );
+        assert!(
+            softwareupdate_install_labels_request(None, &["Safari 18.3.1-18.3.1".to_string()])
+                .progress_parser
+                .is_some()
+        );
+    }
+
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/softwareupdate/version.txt");
     const LIST_AVAILABLE_FIXTURE: &str =
         include_str!("../../tests/fixtures/softwareupdate/list_available.txt");
@@ -370,6 +450,21 @@ mod tests {
         assert!(!packages[1].restart_required);
     }
 
+    #[test]
+    fn parses_download_sizes_from_list_available_fixture() {
+        let packages = parse_softwareupdate_list(LIST_AVAILABLE_FIXTURE).unwrap();
+        assert_eq!(
+            packages[0].sizes.download_size_bytes,
+            Some(1_803_133 * 1024)
+        );
+        assert_eq!(packages[1].sizes.download_size_bytes, Some(198_524 * 1024));
+        assert!(packages[0].sizes.install_size_bytes.is_none());
+
+        assert_eq!(parse_softwareupdate_size("2MiB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_softwareupdate_size("512"), Some(512));
+        assert_eq!(parse_softwareupdate_size("unknown"), None);
+    }
+
     #[test]
     fn parses_empty_list_available() {
         let packages = parse_softwareupdate_list(LIST_AVAILABLE_EMPTY_FIXTURE).unwrap();
@@ -405,33 +500,44 @@ mod tests {
 
         let error = adapter
             .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(crate::models::PackageRef {
-                    manager: ManagerId::SoftwareUpdate,
-                    name: "__all__".to_string(),
-                }),
+                target: UpgradeTarget::All,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }))
             .unwrap_err();
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
     }
 
     #[test]
-    fn adapter_executes_confirmed_upgrade_request() {
+    fn confirmed_upgrade_installs_only_the_confirmed_labels() {
         let source = FixtureSource::default();
+        let installed_labels = source.installed_labels.clone();
         let adapter = SoftwareUpdateAdapter::new(source);
 
-        let result = adapter
-            .execute(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(crate::models::PackageRef {
-                    manager: ManagerId::SoftwareUpdate,
-                    name: "__confirm_os_updates__".to_string(),
-                }),
+        let upgrade_confirming = |labels: &[&str]| {
+            adapter.execute(AdapterRequest::Upgrade(UpgradeRequest {
+                target: UpgradeTarget::OsUpdatesConfirmed,
                 target_name: None,
                 version: None,
+                options: labels.iter().map(|label| label.to_string()).collect(),
+                cleanup_old_kegs: false,
             }))
-            .unwrap();
+        };
+
+        let result = upgrade_confirming(&["Safari 18.3.1-18.3.1"]).unwrap();
         assert!(matches!(result, AdapterResponse::Mutation(_)));
+        // The fixture also lists a macOS update; it was not confirmed, so it is
+        // not installed.
+        assert_eq!(
+            *installed_labels.lock().unwrap(),
+            vec![vec!["Safari 18.3.1-18.3.1".to_string()]]
+        );
+
+        let error = upgrade_confirming(&[]).unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert_eq!(installed_labels.lock().unwrap().len(), 1);
     }
 
     #[test]
@@ -458,6 +564,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .unwrap_err();
         assert_eq!(error.kind, CoreErrorKind::UnsupportedCapability);
@@ -489,14 +596,17 @@ mod tests {
 
     #[test]
     fn upgrade_command_spec_uses_structured_args_and_elevation() {
-        let request = softwareupdate_upgrade_request(Some(TaskId(100)));
+        let request = softwareupdate_install_labels_request(
+            Some(TaskId(100)),
+            &["Safari 18.3.1-18.3.1".to_string()],
+        );
         assert_eq!(
             request.command.program,
             PathBuf::from("/usr/sbin/softwareupdate")
         );
         assert_eq!(
             request.command.args,
-            vec!["-i".to_string(), "-a".to_string()]
+            vec!["-i".to_string(), "Safari 18.3.1-18.3.1".to_string()]
         );
         assert_eq!(request.action, ManagerAction::Upgrade);
         assert_eq!(request.task_type, TaskType::Upgrade);
@@ -507,6 +617,7 @@ mod tests {
     #[derive(Default, Clone)]
     struct FixtureSource {
         detect_calls: Arc<AtomicUsize>,
+        installed_labels: Arc<Mutex<Vec<Vec<String>>>>,
     }
 
     impl SoftwareUpdateSource for FixtureSource {
@@ -522,7 +633,8 @@ mod tests {
             Ok(LIST_AVAILABLE_FIXTURE.to_string())
         }
 
-        fn install_all_updates(&self) -> AdapterResult<String> {
+        fn install_updates(&self, labels: &[String]) -> AdapterResult<String> {
+            self.installed_labels.lock().unwrap().push(labels.to_vec());
             Ok(String::new())
         }
     }
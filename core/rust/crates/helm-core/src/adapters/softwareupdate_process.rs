@@ -5,7 +5,7 @@ use crate::adapters::manager::AdapterResult;
 use crate::adapters::process_utils::run_and_collect_stdout;
 use crate::adapters::softwareupdate::{
     SoftwareUpdateDetectOutput, SoftwareUpdateSource, softwareupdate_detect_request,
-    softwareupdate_list_request, softwareupdate_upgrade_request,
+    softwareupdate_install_labels_request, softwareupdate_list_request,
 };
 use crate::execution::ProcessExecutor;
 
@@ -45,8 +45,8 @@ impl SoftwareUpdateSource for ProcessSoftwareUpdateSource {
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 
-    fn install_all_updates(&self) -> AdapterResult<String> {
-        let request = softwareupdate_upgrade_request(None);
+    fn install_updates(&self, labels: &[String]) -> AdapterResult<String> {
+        let request = softwareupdate_install_labels_request(None, labels);
         run_and_collect_stdout(self.executor.as_ref(), request)
     }
 }
@@ -2,11 +2,12 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use crate::adapters::manager::{AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter};
+use crate::adapters::softwareupdate::parse_softwareupdate_size;
 use crate::execution::{CommandSpec, ProcessSpawnRequest};
 use crate::models::{
     ActionSafety, Capability, CoreError, CoreErrorKind, DetectionInfo, InstalledPackage,
     ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId,
-    OutdatedPackage, PackageRef, TaskId, TaskType,
+    OutdatedPackage, PackageRef, PackageSizes, TaskId, TaskType,
 };
 
 const XCODE_CLT_CAPABILITIES: &[Capability] = &[
@@ -85,6 +86,7 @@ impl<S: XcodeCommandLineToolsSource> ManagerAdapter for XcodeCommandLineToolsAda
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
@@ -99,8 +101,10 @@ impl<S: XcodeCommandLineToolsSource> ManagerAdapter for XcodeCommandLineToolsAda
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]),
                     outdated: Some(outdated),
+                    stage_failures: Vec::new(),
                 })
             }
             AdapterRequest::ListInstalled(_) => {
@@ -117,6 +121,7 @@ impl<S: XcodeCommandLineToolsSource> ManagerAdapter for XcodeCommandLineToolsAda
                         installed_version: version,
                         pinned: false,
                         runtime_state: Default::default(),
+                        sizes: Default::default(),
                     }]
                 } else {
                     Vec::new()
@@ -129,7 +134,7 @@ impl<S: XcodeCommandLineToolsSource> ManagerAdapter for XcodeCommandLineToolsAda
                 Ok(AdapterResponse::OutdatedPackages(packages))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
-                let package = upgrade_request.package.ok_or(CoreError {
+                let package = upgrade_request.target.package().cloned().ok_or(CoreError {
                     manager: Some(ManagerId::XcodeCommandLineTools),
                     task: None,
                     action: Some(ManagerAction::Upgrade),
@@ -163,6 +168,7 @@ impl<S: XcodeCommandLineToolsSource> ManagerAdapter for XcodeCommandLineToolsAda
                             .and_then(|item| item.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|item| item.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -245,6 +251,7 @@ fn parse_xcode_clt_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
     let mut current_label: Option<String> = None;
     let mut current_title: Option<String> = None;
     let mut current_version: Option<String> = None;
+    let mut current_download_size: Option<u64> = None;
 
     for line in output.lines() {
         let trimmed = line.trim();
@@ -255,10 +262,12 @@ fn parse_xcode_clt_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
                 current_label.take(),
                 current_title.take(),
                 current_version.take(),
+                current_download_size.take(),
             );
             current_label = Some(rest.trim().to_string());
             current_title = None;
             current_version = None;
+            current_download_size = None;
             continue;
         }
 
@@ -271,6 +280,7 @@ fn parse_xcode_clt_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
                     match key {
                         "Title" => current_title = Some(value.to_string()),
                         "Version" => current_version = Some(value.to_string()),
+                        "Size" => current_download_size = parse_softwareupdate_size(value),
                         _ => {}
                     }
                 }
@@ -283,6 +293,7 @@ fn parse_xcode_clt_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>>
         current_label.take(),
         current_title.take(),
         current_version.take(),
+        current_download_size.take(),
     );
 
     Ok(packages)
@@ -293,6 +304,7 @@ fn flush_xcode_clt_update(
     label: Option<String>,
     title: Option<String>,
     version: Option<String>,
+    download_size_bytes: Option<u64>,
 ) {
     let Some(label) = label else {
         return;
@@ -326,6 +338,10 @@ fn flush_xcode_clt_update(
         pinned: false,
         restart_required: false,
         runtime_state: Default::default(),
+        sizes: PackageSizes {
+            install_size_bytes: None,
+            download_size_bytes,
+        },
     });
 }
 
@@ -410,6 +426,7 @@ mod tests {
             "Command Line Tools for Xcode-16.3"
         );
         assert_eq!(packages[0].candidate_version, "16.3");
+        assert_eq!(packages[0].sizes.download_size_bytes, Some(76_000 * 1024));
     }
 
     #[test]
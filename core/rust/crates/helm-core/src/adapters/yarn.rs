@@ -1,5 +1,5 @@
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use serde::Deserialize;
@@ -113,15 +113,19 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
                     return Ok(AdapterResponse::SnapshotSync {
                         installed: Some(Vec::new()),
                         outdated: Some(Vec::new()),
+                        stage_failures: Vec::new(),
                     });
                 }
 
-                let installed = parse_yarn_list_installed(&self.source.list_installed_global()?)?;
-                let outdated = parse_yarn_outdated(&self.source.list_outdated_global()?)?;
-                Ok(AdapterResponse::SnapshotSync {
-                    installed: Some(installed),
-                    outdated: Some(outdated),
-                })
+                let installed = self
+                    .source
+                    .list_installed_global()
+                    .and_then(|raw| parse_yarn_list_installed(&raw));
+                let outdated = self
+                    .source
+                    .list_outdated_global()
+                    .and_then(|raw| parse_yarn_outdated(&raw));
+                crate::adapters::snapshot_sync_from_stages(installed, outdated)
             }
             AdapterRequest::ListInstalled(_) => {
                 self.ensure_classic_global_support(ManagerAction::ListInstalled)?;
@@ -138,7 +142,12 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
             AdapterRequest::Search(search_request) => {
                 self.ensure_classic_global_support(ManagerAction::Search)?;
                 let raw = self.source.search(search_request.query.text.as_str())?;
-                let results = parse_yarn_search(&raw, &search_request.query)?;
+                let mut results = parse_yarn_search(&raw, &search_request.query)?;
+                crate::search_popularity::annotate_search_popularity(
+                    ManagerId::Yarn,
+                    &search_request.query.text,
+                    &mut results,
+                );
                 Ok(AdapterResponse::SearchResults(results))
             }
             AdapterRequest::Install(install_request) => {
@@ -170,6 +179,7 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
                     action: ManagerAction::Install,
                     before_version,
                     after_version,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Uninstall(uninstall_request) => {
@@ -192,23 +202,25 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
                     action: ManagerAction::Uninstall,
                     before_version: Some(before_version),
                     after_version: None,
+                    disposal: None,
                 }))
             }
             AdapterRequest::Upgrade(upgrade_request) => {
                 self.ensure_classic_global_support(ManagerAction::Upgrade)?;
-                let package = upgrade_request.package.unwrap_or(PackageRef {
-                    manager: ManagerId::Yarn,
-                    name: "__all__".to_string(),
-                });
-                let target_name = if package.name == "__all__" {
-                    None
-                } else {
-                    crate::adapters::validate_package_identifier(
-                        ManagerId::Yarn,
-                        ManagerAction::Upgrade,
-                        package.name.as_str(),
-                    )?;
-                    Some(package.name.as_str())
+                let target_package = crate::adapters::package_or_all_upgrade_target(
+                    ManagerId::Yarn,
+                    &upgrade_request.target,
+                )?;
+                let target_name = match target_package {
+                    Some(package) => {
+                        crate::adapters::validate_package_identifier(
+                            ManagerId::Yarn,
+                            ManagerAction::Upgrade,
+                            package.name.as_str(),
+                        )?;
+                        Some(package.name.as_str())
+                    }
+                    None => None,
                 };
                 let targeted_outdated = target_name
                     .map(|name| find_yarn_outdated_entry(&self.source, name))
@@ -219,7 +231,7 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
                     ensure_yarn_no_longer_outdated(&self.source, name)?;
                 }
                 Ok(AdapterResponse::Mutation(crate::adapters::MutationResult {
-                    package,
+                    package: upgrade_request.target.result_package(ManagerId::Yarn),
                     package_identifier: None,
                     action: ManagerAction::Upgrade,
                     before_version: upgrade_request.version.or_else(|| {
@@ -228,6 +240,7 @@ impl<S: YarnSource> ManagerAdapter for YarnAdapter<S> {
                             .and_then(|entry| entry.installed_version.clone())
                     }),
                     after_version: targeted_outdated.map(|entry| entry.candidate_version),
+                    disposal: None,
                 }))
             }
             _ => Err(CoreError {
@@ -348,15 +361,13 @@ fn yarn_request(
 }
 
 fn parse_yarn_version(output: &str) -> Option<String> {
-    let line = output
-        .lines()
-        .map(str::trim)
-        .find(|line| !line.is_empty())?;
-    let version = line.split_whitespace().next()?.trim();
-    if version.is_empty() || !version.starts_with(|c: char| c.is_ascii_digit()) {
-        return None;
-    }
-    Some(version.to_owned())
+    // Corepack-managed yarn may print download notices before the version.
+    output.lines().map(str::trim).find_map(|line| {
+        let version = line.split_whitespace().next()?.trim();
+        version
+            .starts_with(|c: char| c.is_ascii_digit())
+            .then(|| version.to_owned())
+    })
 }
 
 fn parse_yarn_flavor(version: Option<&str>) -> Option<YarnFlavor> {
@@ -369,8 +380,36 @@ fn parse_yarn_flavor(version: Option<&str>) -> Option<YarnFlavor> {
     }
 }
 
+/// Detection extras for yarn: the active flavor (`classic` or `berry`) and
+/// whether the executable is a Corepack shim.
+pub fn yarn_detection_extras(info: &DetectionInfo) -> BTreeMap<String, String> {
+    let mut extras = BTreeMap::new();
+    if let Some(flavor) = parse_yarn_flavor(info.version.as_deref()) {
+        let flavor = match flavor {
+            YarnFlavor::Classic => "classic",
+            YarnFlavor::Berry => "berry",
+        };
+        extras.insert("flavor".to_string(), flavor.to_string());
+    }
+    if let Some(path) = info.executable_path.as_deref() {
+        let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if is_corepack_shim(&resolved) {
+            extras.insert("provider".to_string(), "corepack".to_string());
+        }
+    }
+    extras
+}
+
+fn is_corepack_shim(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == "corepack")
+}
+
 fn yarn_global_scope_error(version: Option<&str>, action: ManagerAction) -> CoreError {
     let detail = match version {
+        Some(version) if parse_yarn_flavor(Some(version)) == Some(YarnFlavor::Berry) => format!(
+            "Yarn {version} (Berry) has no global packages; Helm supports Yarn Classic global packages only. Run one-off tools with `yarn dlx` or install them with npm"
+        ),
         Some(version) => format!(
             "Helm currently supports Yarn Classic global packages only; detected Yarn {version}"
         ),
@@ -464,6 +503,7 @@ fn parse_yarn_list_installed(output: &str) -> AdapterResult<Vec<InstalledPackage
             installed_version: Some(version),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         })
         .collect())
 }
@@ -566,6 +606,7 @@ fn parse_yarn_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
                 pinned: false,
                 restart_required: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             });
         }
     }
@@ -631,6 +672,7 @@ fn parse_yarn_outdated(output: &str) -> AdapterResult<Vec<OutdatedPackage>> {
                     pinned: false,
                     restart_required: false,
                     runtime_state: Default::default(),
+                    sizes: Default::default(),
                 });
             }
         }
@@ -702,6 +744,7 @@ fn parse_yarn_search(output: &str, query: &SearchQuery) -> AdapterResult<Vec<Cac
             source_manager: ManagerId::Yarn,
             originating_query: query.text.clone(),
             cached_at: query.issued_at,
+            popularity: Default::default(),
         });
     }
 
@@ -729,15 +772,16 @@ mod tests {
         ManagerAdapter, SearchRequest,
     };
     use crate::models::{
-        CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId, TaskType,
+        CoreErrorKind, DetectionInfo, ManagerAction, ManagerId, PackageRef, SearchQuery, TaskId,
+        TaskType,
     };
 
     use super::{
         YarnAdapter, YarnDetectOutput, YarnFlavor, YarnSource, parse_yarn_flavor,
         parse_yarn_list_installed, parse_yarn_outdated, parse_yarn_search, parse_yarn_version,
-        yarn_detect_request, yarn_install_request, yarn_list_installed_request,
-        yarn_list_outdated_request, yarn_search_request, yarn_uninstall_request,
-        yarn_upgrade_request,
+        yarn_detect_request, yarn_detection_extras, yarn_install_request,
+        yarn_list_installed_request, yarn_list_outdated_request, yarn_search_request,
+        yarn_uninstall_request, yarn_upgrade_request,
     };
 
     const VERSION_FIXTURE: &str = include_str!("../../tests/fixtures/yarn/version.txt");
@@ -945,6 +989,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert_eq!(installed.expect("installed snapshot").len(), 3);
                 assert_eq!(outdated.expect("outdated snapshot").len(), 2);
@@ -974,6 +1019,7 @@ mod tests {
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 assert!(installed.expect("installed snapshot").is_empty());
                 assert!(outdated.expect("outdated snapshot").is_empty());
@@ -1006,6 +1052,38 @@ mod tests {
 
         assert_eq!(error.kind, CoreErrorKind::UnsupportedCapability);
         assert!(error.message.contains("Yarn Classic"));
+        assert!(error.message.contains("yarn dlx"));
+    }
+
+    #[test]
+    fn detection_extras_report_flavor_and_corepack_provider() {
+        let extras = yarn_detection_extras(&DetectionInfo {
+            installed: true,
+            executable_path: Some(PathBuf::from(
+                "/usr/local/lib/node_modules/corepack/shims/yarn",
+            )),
+            version: Some("4.6.1".to_string()),
+        });
+        assert_eq!(extras.get("flavor").map(String::as_str), Some("berry"));
+        assert_eq!(extras.get("provider").map(String::as_str), Some("corepack"));
+
+        let extras = yarn_detection_extras(&DetectionInfo {
+            installed: true,
+            executable_path: Some(PathBuf::from("/opt/homebrew/bin/yarn")),
+            version: Some("1.22.22".to_string()),
+        });
+        assert_eq!(extras.get("flavor").map(String::as_str), Some("classic"));
+        assert!(!extras.contains_key("provider"));
+    }
+
+    #[test]
+    fn version_parsing_skips_corepack_download_notices() {
+        assert_eq!(
+            parse_yarn_version(
+                "! Corepack is about to download https://repo.yarnpkg.com/4.6.1/packages/yarnpkg-cli/bin/yarn.js\n4.6.1\n"
+            ),
+            Some("4.6.1".to_string())
+        );
     }
 
     #[test]
@@ -1059,6 +1137,7 @@ mod tests {
                 },
                 target_name: None,
                 version: Some("5.7.2".to_string()),
+                options: Vec::new(),
             }))
             .expect("install should succeed");
 
@@ -1083,6 +1162,7 @@ mod tests {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }))
             .expect_err("expected invalid input");
         assert_eq!(error.kind, CoreErrorKind::InvalidInput);
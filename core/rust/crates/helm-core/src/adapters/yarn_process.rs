@@ -31,7 +31,8 @@ impl ProcessYarnSource {
             .env("PATH", new_path)
             .env("YARN_CONFIG_UPDATE_NOTIFIER", "false")
             .env("YARN_CONFIG_FUND", "false")
-            .env("YARN_CONFIG_AUDIT", "false");
+            .env("YARN_CONFIG_AUDIT", "false")
+            .env("COREPACK_ENABLE_DOWNLOAD_PROMPT", "0");
 
         if request.command.program.to_str() == Some("yarn")
             && let Some(exe) = which_executable(
@@ -0,0 +1,216 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// Data, config, and bin directories of the tools Helm discovers, resolved
+/// from each tool's own override variable before falling back to its default
+/// under `HOME`. Relative or empty overrides are ignored, as the tools
+/// themselves would misbehave with them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolBaseDirs {
+    pub home: Option<PathBuf>,
+    /// `XDG_DATA_HOME`, else `~/.local/share`.
+    pub xdg_data_home: Option<PathBuf>,
+    /// `XDG_CONFIG_HOME`, else `~/.config`.
+    pub xdg_config_home: Option<PathBuf>,
+    /// `XDG_CACHE_HOME`, else `~/.cache`.
+    pub xdg_cache_home: Option<PathBuf>,
+    /// `XDG_BIN_HOME`, else `~/.local/bin`.
+    pub local_bin_dir: Option<PathBuf>,
+    /// `CARGO_HOME`, else `~/.cargo`.
+    pub cargo_home: Option<PathBuf>,
+    /// `RUSTUP_HOME`, else `~/.rustup`.
+    pub rustup_home: Option<PathBuf>,
+    /// asdf checkout (`ASDF_DIR`), else `~/.asdf`.
+    pub asdf_dir: Option<PathBuf>,
+    /// asdf plugins, installs, and shims (`ASDF_DATA_DIR`), else `~/.asdf`.
+    pub asdf_data_dir: Option<PathBuf>,
+    /// `MISE_DATA_DIR`, else `$XDG_DATA_HOME/mise`.
+    pub mise_data_dir: Option<PathBuf>,
+    /// `MISE_CONFIG_DIR`, else `$XDG_CONFIG_HOME/mise`.
+    pub mise_config_dir: Option<PathBuf>,
+    /// `MISE_CACHE_DIR`, else `$XDG_CACHE_HOME/mise`.
+    pub mise_cache_dir: Option<PathBuf>,
+    /// Legacy rtx data (`RTX_DATA_DIR`), else `$XDG_DATA_HOME/rtx`.
+    pub rtx_data_dir: Option<PathBuf>,
+    /// `PIPX_HOME`, else `~/.local/pipx`.
+    pub pipx_home: Option<PathBuf>,
+    /// `PIPX_BIN_DIR`, else the local bin dir.
+    pub pipx_bin_dir: Option<PathBuf>,
+    /// `NPM_CONFIG_PREFIX`; npm otherwise derives its prefix from node.
+    pub npm_prefix: Option<PathBuf>,
+}
+
+impl ToolBaseDirs {
+    pub fn from_env() -> Self {
+        Self::resolve(std::env::var_os("HOME").map(PathBuf::from), |key| {
+            std::env::var_os(key)
+        })
+    }
+
+    /// Resolve against `home` and the variables `env` returns.
+    pub fn resolve(home: Option<PathBuf>, env: impl Fn(&str) -> Option<OsString>) -> Self {
+        let var = |key: &str| {
+            env(key)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let under_home = |relative: &str| home.as_ref().map(|home| home.join(relative));
+
+        let xdg_data_home = var("XDG_DATA_HOME").or_else(|| under_home(".local/share"));
+        let xdg_config_home = var("XDG_CONFIG_HOME").or_else(|| under_home(".config"));
+        let xdg_cache_home = var("XDG_CACHE_HOME").or_else(|| under_home(".cache"));
+        let local_bin_dir = var("XDG_BIN_HOME").or_else(|| under_home(".local/bin"));
+        let in_xdg = |base: &Option<PathBuf>, name: &str| base.as_ref().map(|base| base.join(name));
+
+        Self {
+            cargo_home: var("CARGO_HOME").or_else(|| under_home(".cargo")),
+            rustup_home: var("RUSTUP_HOME").or_else(|| under_home(".rustup")),
+            asdf_dir: var("ASDF_DIR").or_else(|| under_home(".asdf")),
+            asdf_data_dir: var("ASDF_DATA_DIR").or_else(|| under_home(".asdf")),
+            mise_data_dir: var("MISE_DATA_DIR").or_else(|| in_xdg(&xdg_data_home, "mise")),
+            mise_config_dir: var("MISE_CONFIG_DIR").or_else(|| in_xdg(&xdg_config_home, "mise")),
+            mise_cache_dir: var("MISE_CACHE_DIR").or_else(|| in_xdg(&xdg_cache_home, "mise")),
+            rtx_data_dir: var("RTX_DATA_DIR").or_else(|| in_xdg(&xdg_data_home, "rtx")),
+            pipx_home: var("PIPX_HOME").or_else(|| under_home(".local/pipx")),
+            pipx_bin_dir: var("PIPX_BIN_DIR").or_else(|| local_bin_dir.clone()),
+            npm_prefix: var("NPM_CONFIG_PREFIX").or_else(|| var("npm_config_prefix")),
+            home,
+            xdg_data_home,
+            xdg_config_home,
+            xdg_cache_home,
+            local_bin_dir,
+        }
+    }
+
+    /// Per-user bin and shim directories tools install executables into.
+    pub fn tool_bin_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        push_root(&mut roots, self.local_bin_dir.clone());
+        push_root(&mut roots, self.pipx_bin_dir.clone());
+        push_root(&mut roots, join(&self.cargo_home, "bin"));
+        for asdf_root in [&self.asdf_dir, &self.asdf_data_dir] {
+            push_root(&mut roots, join(asdf_root, "bin"));
+            push_root(&mut roots, join(asdf_root, "shims"));
+        }
+        push_root(&mut roots, join(&self.mise_data_dir, "shims"));
+        push_root(&mut roots, join(&self.rtx_data_dir, "shims"));
+        push_root(&mut roots, join(&self.npm_prefix, "bin"));
+        roots
+    }
+
+    /// Roots laid out as `<root>/<tool>/<version>/bin` by version managers.
+    pub fn tool_version_install_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        push_root(&mut roots, join(&self.asdf_data_dir, "installs"));
+        push_root(&mut roots, join(&self.asdf_dir, "installs"));
+        push_root(&mut roots, join(&self.mise_data_dir, "installs"));
+        push_root(&mut roots, join(&self.rtx_data_dir, "installs"));
+        roots
+    }
+
+    /// asdf checkout and data directories, deduplicated.
+    pub fn asdf_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        push_root(&mut roots, self.asdf_dir.clone());
+        push_root(&mut roots, self.asdf_data_dir.clone());
+        roots
+    }
+}
+
+fn join(base: &Option<PathBuf>, relative: &str) -> Option<PathBuf> {
+    base.as_ref().map(|base| base.join(relative))
+}
+
+fn push_root(roots: &mut Vec<PathBuf>, root: Option<PathBuf>) {
+    if let Some(root) = root
+        && !roots.contains(&root)
+    {
+        roots.push(root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    use super::ToolBaseDirs;
+
+    fn resolve(vars: &[(&str, &str)]) -> ToolBaseDirs {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), OsString::from(value)))
+            .collect::<HashMap<_, _>>();
+        ToolBaseDirs::resolve(Some(PathBuf::from("/Users/test")), |key| {
+            vars.get(key).cloned()
+        })
+    }
+
+    #[test]
+    fn defaults_live_under_home() {
+        let dirs = resolve(&[]);
+        assert_eq!(dirs.cargo_home, Some(PathBuf::from("/Users/test/.cargo")));
+        assert_eq!(
+            dirs.mise_data_dir,
+            Some(PathBuf::from("/Users/test/.local/share/mise"))
+        );
+        assert_eq!(dirs.npm_prefix, None);
+        assert_eq!(
+            dirs.tool_bin_roots(),
+            [
+                "/Users/test/.local/bin",
+                "/Users/test/.cargo/bin",
+                "/Users/test/.asdf/bin",
+                "/Users/test/.asdf/shims",
+                "/Users/test/.local/share/mise/shims",
+                "/Users/test/.local/share/rtx/shims",
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(
+            dirs.tool_version_install_roots(),
+            [
+                "/Users/test/.asdf/installs",
+                "/Users/test/.local/share/mise/installs",
+                "/Users/test/.local/share/rtx/installs",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_relative_values_are_ignored() {
+        let dirs = resolve(&[
+            ("CARGO_HOME", "/opt/cargo"),
+            ("RUSTUP_HOME", "relative/rustup"),
+            ("ASDF_DATA_DIR", "/data/asdf"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("MISE_DATA_DIR", "/data/mise"),
+            ("PIPX_BIN_DIR", "/opt/pipx/bin"),
+            ("NPM_CONFIG_PREFIX", "/opt/npm"),
+        ]);
+        assert_eq!(dirs.rustup_home, Some(PathBuf::from("/Users/test/.rustup")));
+        assert_eq!(dirs.rtx_data_dir, Some(PathBuf::from("/xdg/data/rtx")));
+
+        let bin_roots = dirs.tool_bin_roots();
+        for expected in [
+            "/opt/pipx/bin",
+            "/opt/cargo/bin",
+            "/data/asdf/shims",
+            "/data/mise/shims",
+            "/opt/npm/bin",
+        ] {
+            assert!(bin_roots.contains(&PathBuf::from(expected)), "{expected}");
+        }
+        assert!(!bin_roots.contains(&PathBuf::from("/Users/test/.cargo/bin")));
+        assert!(!bin_roots.contains(&PathBuf::from("/Users/test/.local/share/mise/shims")));
+        assert!(
+            dirs.tool_version_install_roots()
+                .contains(&PathBuf::from("/data/asdf/installs"))
+        );
+    }
+}
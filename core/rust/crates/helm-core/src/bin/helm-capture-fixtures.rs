@@ -0,0 +1,173 @@
+//! Capture a manager's raw command output as parser fixtures.
+//!
+//! ```text
+//! cargo run -p helm-core --bin helm-capture-fixtures -- <homebrew|npm|pip> [--fixtures-dir DIR]
+//! ```
+//!
+//! Runs the same commands the adapter runs and writes their stdout to
+//! `<fixtures-dir>/<manager>/versions/<tool>-<major>/`, replacing the home
+//! directory with `/Users/dev`. See `tests/fixtures/README.md` for the rest of
+//! the process.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+use helm_core::adapters::homebrew::{
+    homebrew_config_request, homebrew_detect_request, homebrew_list_installed_request,
+    homebrew_list_outdated_request,
+};
+use helm_core::adapters::npm::{
+    npm_detect_request, npm_list_installed_request, npm_list_outdated_request,
+};
+use helm_core::adapters::pip::{pip_detect_request, pip_list_outdated_request, pip_list_request};
+use helm_core::execution::ProcessSpawnRequest;
+
+const USAGE: &str = "usage: helm-capture-fixtures <homebrew|npm|pip> [--fixtures-dir DIR]";
+const SCRUBBED_HOME: &str = "/Users/dev";
+
+struct CapturePlan {
+    manager_dir: &'static str,
+    tool: &'static str,
+    version: ProcessSpawnRequest,
+    /// `(fixture file name, request)`, written after `version.txt`.
+    outputs: Vec<(&'static str, ProcessSpawnRequest)>,
+}
+
+fn capture_plan(manager: &str) -> Option<CapturePlan> {
+    match manager {
+        "homebrew" => Some(CapturePlan {
+            manager_dir: "homebrew",
+            tool: "brew",
+            version: homebrew_detect_request(None),
+            outputs: vec![
+                ("config.txt", homebrew_config_request(None)),
+                ("installed.json", homebrew_list_installed_request(None)),
+                ("outdated.json", homebrew_list_outdated_request(None)),
+            ],
+        }),
+        "npm" => Some(CapturePlan {
+            manager_dir: "npm",
+            tool: "npm",
+            version: npm_detect_request(None),
+            outputs: vec![
+                ("list_global.json", npm_list_installed_request(None)),
+                ("outdated_global.json", npm_list_outdated_request(None)),
+            ],
+        }),
+        "pip" => Some(CapturePlan {
+            manager_dir: "pip",
+            tool: "pip",
+            version: pip_detect_request(None),
+            outputs: vec![
+                ("list.json", pip_list_request(None)),
+                ("outdated.json", pip_list_outdated_request(None)),
+            ],
+        }),
+        _ => None,
+    }
+}
+
+/// Run `request` and return its stdout. A non-zero exit is only a warning:
+/// `npm outdated` exits 1 whenever something is outdated.
+fn run(request: &ProcessSpawnRequest) -> Result<String, String> {
+    let spec = &request.command;
+    let mut command = Command::new(&spec.program);
+    command.args(&spec.args).envs(&spec.env);
+    if let Some(working_dir) = &spec.working_dir {
+        command.current_dir(working_dir);
+    }
+    let output = command
+        .output()
+        .map_err(|error| format!("failed to run {}: {error}", spec.program.display()))?;
+    if !output.status.success() {
+        eprintln!(
+            "warning: {} {} exited with {}",
+            spec.program.display(),
+            spec.args.join(" "),
+            output.status
+        );
+    }
+    String::from_utf8(output.stdout).map_err(|_| {
+        format!(
+            "{} {} printed non-UTF-8 output",
+            spec.program.display(),
+            spec.args.join(" ")
+        )
+    })
+}
+
+/// Major version from the first numeric token, e.g. `pip 24.3.1 from ...` -> `24`.
+fn major_version(version_output: &str) -> Option<&str> {
+    let token = version_output
+        .split_whitespace()
+        .find(|token| token.starts_with(|ch: char| ch.is_ascii_digit()))?;
+    token.split('.').next()
+}
+
+fn scrub(output: &str, home: Option<&str>) -> String {
+    match home {
+        Some(home) if !home.is_empty() && home != "/" => output.replace(home, SCRUBBED_HOME),
+        _ => output.to_string(),
+    }
+}
+
+fn capture(plan: &CapturePlan, fixtures_dir: &Path) -> Result<PathBuf, String> {
+    let home = std::env::var("HOME").ok();
+    let version_output = run(&plan.version)?;
+    let major = major_version(&version_output)
+        .ok_or_else(|| format!("could not find a version in {version_output:?}"))?;
+    let case_dir = fixtures_dir
+        .join(plan.manager_dir)
+        .join("versions")
+        .join(format!("{}-{major}", plan.tool));
+    std::fs::create_dir_all(&case_dir)
+        .map_err(|error| format!("failed to create {}: {error}", case_dir.display()))?;
+
+    let mut files = vec![("version.txt", version_output)];
+    for (file, request) in &plan.outputs {
+        files.push((file, run(request)?));
+    }
+    for (file, output) in files {
+        let path = case_dir.join(file);
+        std::fs::write(&path, scrub(&output, home.as_deref()))
+            .map_err(|error| format!("failed to write {}: {error}", path.display()))?;
+        println!("wrote {}", path.display());
+    }
+    Ok(case_dir)
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(plan) = args.next().as_deref().and_then(capture_plan) else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    let mut fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--fixtures-dir", Some(dir)) => fixtures_dir = PathBuf::from(dir),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    match capture(&plan, &fixtures_dir) {
+        Ok(case_dir) => {
+            println!(
+                "\nReview {} for anything private, add its directory name to the \
+                 GOLDEN_CASES table in src/adapters/{}.rs, then run\n  \
+                 HELM_UPDATE_GOLDEN=1 cargo test -p helm-core golden_parses\n\
+                 and review the generated .golden files.",
+                case_dir.display(),
+                plan.manager_dir
+            );
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
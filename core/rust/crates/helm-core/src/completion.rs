@@ -0,0 +1,81 @@
+//! Word lists for shell completion: manager ids, installed package names per
+//! manager, and task types. Built from the cached package snapshot so a
+//! completion script can ask on every keystroke without touching any manager.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::models::{InstalledPackage, ManagerId, TaskType};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct CompletionData {
+    /// Enabled managers, in registry order.
+    pub managers: Vec<ManagerId>,
+    /// Sorted, deduplicated package names keyed by manager id. Managers
+    /// themselves (`__self__` rows) are left out.
+    pub packages: BTreeMap<&'static str, Vec<String>>,
+    pub task_types: Vec<TaskType>,
+}
+
+pub fn build_completion_data(
+    installed: &[InstalledPackage],
+    is_enabled: impl Fn(ManagerId) -> bool,
+) -> CompletionData {
+    let managers: Vec<ManagerId> = ManagerId::ALL
+        .into_iter()
+        .filter(|manager| is_enabled(*manager))
+        .collect();
+
+    let mut packages: BTreeMap<&'static str, Vec<String>> = BTreeMap::new();
+    for package in installed
+        .iter()
+        .map(|package| &package.package)
+        .filter(|package| package.is_user_visible_package() && managers.contains(&package.manager))
+    {
+        packages
+            .entry(package.manager.as_str())
+            .or_default()
+            .push(package.name.clone());
+    }
+    for names in packages.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+
+    CompletionData {
+        managers,
+        packages,
+        task_types: TaskType::ALL.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_completion_data;
+    use crate::models::ManagerId;
+    use crate::test_fixtures::installed;
+
+    #[test]
+    fn completion_data_lists_enabled_managers_and_their_packages() {
+        let data = build_completion_data(
+            &[
+                installed(ManagerId::Npm, "typescript", None),
+                installed(ManagerId::Npm, "eslint", None),
+                installed(ManagerId::Npm, "eslint", None),
+                installed(ManagerId::Npm, "__self__", None),
+                installed(ManagerId::Cargo, "ripgrep", None),
+            ],
+            |manager| manager != ManagerId::Cargo,
+        );
+
+        assert!(data.managers.contains(&ManagerId::Npm));
+        assert!(!data.managers.contains(&ManagerId::Cargo));
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            json["packages"],
+            serde_json::json!({ "npm": ["eslint", "typescript"] })
+        );
+        assert_eq!(json["task_types"][0], "detection");
+    }
+}
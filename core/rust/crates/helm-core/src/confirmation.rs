@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::models::ManagerId;
+use crate::upgrade_simulation::UpgradeSimulation;
+
+/// How long an issued token stays redeemable.
+pub const CONFIRMATION_TOKEN_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationOperation {
+    UpgradeAll,
+}
+
+/// One package change a confirmed plan will make.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmedUpgrade {
+    pub manager: ManagerId,
+    pub package_name: String,
+    pub old_version: Option<String>,
+    pub new_version: String,
+}
+
+/// Exactly what a destructive operation will do, as shown to the user before
+/// they confirm it. Two plans are the same confirmation only if every field
+/// matches.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationPlan {
+    pub operation: ConfirmationOperation,
+    pub include_pinned: bool,
+    pub upgrades: Vec<ConfirmedUpgrade>,
+    /// OS updates among `upgrades`; zero means the plan never runs
+    /// `softwareupdate`.
+    pub os_update_count: usize,
+    pub restart_required: bool,
+}
+
+impl ConfirmationPlan {
+    pub fn upgrade_all(include_pinned: bool, simulation: &UpgradeSimulation) -> Self {
+        let upgrades = simulation
+            .upgrades
+            .iter()
+            .map(|upgrade| ConfirmedUpgrade {
+                manager: upgrade.manager,
+                package_name: upgrade.package_name.clone(),
+                old_version: upgrade.old_version.clone(),
+                new_version: upgrade.new_version.clone(),
+            })
+            .collect::<Vec<_>>();
+        Self {
+            operation: ConfirmationOperation::UpgradeAll,
+            include_pinned,
+            os_update_count: upgrades
+                .iter()
+                .filter(|upgrade| upgrade.manager == ManagerId::SoftwareUpdate)
+                .count(),
+            upgrades,
+            restart_required: simulation.restart_required,
+        }
+    }
+}
+
+/// A token the mutating call must present, with the plan it vouches for.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationToken {
+    pub token: String,
+    pub expires_at_unix: i64,
+    pub plan: ConfirmationPlan,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum ConfirmationError {
+    #[error("confirmation token is unknown or was already used")]
+    Unknown,
+    #[error("confirmation token expired")]
+    Expired,
+    #[error("plan changed since it was confirmed")]
+    PlanChanged,
+}
+
+struct IssuedConfirmation {
+    plan: ConfirmationPlan,
+    expires_at: SystemTime,
+}
+
+/// A spent, unexpired token whose plan has yet to be checked against the
+/// plan the core would run now.
+#[derive(Debug)]
+pub struct RedeemedConfirmation {
+    plan: ConfirmationPlan,
+}
+
+impl RedeemedConfirmation {
+    pub fn plan(&self) -> &ConfirmationPlan {
+        &self.plan
+    }
+
+    /// Accept `current_plan`, recomputed from the confirmed plan's
+    /// parameters, only if it is the plan the user confirmed.
+    pub fn verify(
+        self,
+        current_plan: &ConfirmationPlan,
+    ) -> Result<ConfirmationPlan, ConfirmationError> {
+        if *current_plan == self.plan {
+            Ok(self.plan)
+        } else {
+            Err(ConfirmationError::PlanChanged)
+        }
+    }
+}
+
+/// Single-use confirmation tokens for destructive operations.
+///
+/// The core issues a token for the plan it would run now; the mutating call
+/// redeems it, and the core recomputes the plan from the confirmed parameters
+/// and refuses to act when it no longer matches what the user saw.
+pub struct ConfirmationTokens {
+    ttl: Duration,
+    issued: Mutex<HashMap<String, IssuedConfirmation>>,
+    sequence: AtomicU64,
+    seed: RandomState,
+}
+
+impl Default for ConfirmationTokens {
+    fn default() -> Self {
+        Self::new(CONFIRMATION_TOKEN_TTL)
+    }
+}
+
+impl ConfirmationTokens {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            issued: Mutex::new(HashMap::new()),
+            sequence: AtomicU64::new(0),
+            seed: RandomState::new(),
+        }
+    }
+
+    pub fn issue(&self, plan: ConfirmationPlan, now: SystemTime) -> ConfirmationToken {
+        let token = self.next_token(now);
+        let expires_at = now + self.ttl;
+
+        let mut issued = self
+            .issued
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        issued.retain(|_, entry| entry.expires_at > now);
+        issued.insert(
+            token.clone(),
+            IssuedConfirmation {
+                plan: plan.clone(),
+                expires_at,
+            },
+        );
+
+        ConfirmationToken {
+            token,
+            expires_at_unix: expires_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0),
+            plan,
+        }
+    }
+
+    /// Consume `token`. A token is spent even when a later
+    /// [`RedeemedConfirmation::verify`] fails, so a changed plan must be
+    /// confirmed again.
+    pub fn redeem(
+        &self,
+        token: &str,
+        now: SystemTime,
+    ) -> Result<RedeemedConfirmation, ConfirmationError> {
+        let entry = self
+            .issued
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(token)
+            .ok_or(ConfirmationError::Unknown)?;
+        if now >= entry.expires_at {
+            return Err(ConfirmationError::Expired);
+        }
+        Ok(RedeemedConfirmation { plan: entry.plan })
+    }
+
+    fn next_token(&self, now: SystemTime) -> String {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let nanos = now
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or(0);
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.hash_one(sequence).to_le_bytes());
+        hasher.update(sequence.to_le_bytes());
+        hasher.update(nanos.to_le_bytes());
+        hasher.finalize()[..16]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{ConfirmationError, ConfirmationPlan, ConfirmationTokens};
+    use crate::models::ManagerId;
+    use crate::upgrade_simulation::{SimulatedUpgrade, UpgradeSimulation};
+
+    fn simulation(os_update_version: &str) -> UpgradeSimulation {
+        let upgrade = |manager, name: &str, new_version: &str, restart_required| SimulatedUpgrade {
+            manager,
+            package_name: name.to_string(),
+            old_version: Some("1.0".to_string()),
+            new_version: new_version.to_string(),
+            pinned: false,
+            restart_required,
+            download_size_bytes: None,
+        };
+        UpgradeSimulation {
+            upgrades: vec![
+                upgrade(ManagerId::HomebrewFormula, "git", "2.0", false),
+                upgrade(
+                    ManagerId::SoftwareUpdate,
+                    "macOS Sequoia",
+                    os_update_version,
+                    true,
+                ),
+            ],
+            restart_required: true,
+            ..UpgradeSimulation::default()
+        }
+    }
+
+    #[test]
+    fn issued_plan_describes_os_updates_and_restart() {
+        let tokens = ConfirmationTokens::default();
+        let issued = tokens.issue(
+            ConfirmationPlan::upgrade_all(false, &simulation("15.1")),
+            UNIX_EPOCH,
+        );
+
+        assert_eq!(issued.token.len(), 32);
+        assert_eq!(issued.expires_at_unix, 300);
+        assert_eq!(issued.plan.os_update_count, 1);
+        assert!(issued.plan.restart_required);
+    }
+
+    #[test]
+    fn tokens_redeem_once_for_an_unchanged_plan() {
+        let tokens = ConfirmationTokens::default();
+        let plan = ConfirmationPlan::upgrade_all(false, &simulation("15.1"));
+        let issued = tokens.issue(plan.clone(), UNIX_EPOCH);
+
+        let redeemed = tokens
+            .redeem(&issued.token, UNIX_EPOCH)
+            .expect("issued token");
+        let current =
+            ConfirmationPlan::upgrade_all(redeemed.plan().include_pinned, &simulation("15.1"));
+        assert_eq!(redeemed.verify(&current), Ok(plan));
+        assert_eq!(
+            tokens.redeem(&issued.token, UNIX_EPOCH).unwrap_err(),
+            ConfirmationError::Unknown
+        );
+    }
+
+    #[test]
+    fn changed_or_expired_plans_are_refused() {
+        let tokens = ConfirmationTokens::new(Duration::from_secs(60));
+        let plan = ConfirmationPlan::upgrade_all(false, &simulation("15.1"));
+
+        let issued = tokens.issue(plan.clone(), UNIX_EPOCH);
+        let redeemed = tokens
+            .redeem(&issued.token, UNIX_EPOCH)
+            .expect("issued token");
+        assert_eq!(
+            redeemed.verify(&ConfirmationPlan::upgrade_all(false, &simulation("15.2"))),
+            Err(ConfirmationError::PlanChanged)
+        );
+
+        let issued = tokens.issue(plan, UNIX_EPOCH);
+        assert_eq!(
+            tokens
+                .redeem(&issued.token, UNIX_EPOCH + Duration::from_secs(60))
+                .unwrap_err(),
+            ConfirmationError::Expired
+        );
+    }
+}
@@ -0,0 +1,752 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+const USER_PLACEHOLDER: &str = "[user]";
+const HOSTNAME_PLACEHOLDER: &str = "[hostname]";
+const EMAIL_PLACEHOLDER: &str = "[email]";
+/// Usernames and hostnames shorter than this are too likely to collide with
+/// ordinary words to be replaced wherever they appear.
+const MIN_IDENTITY_REPLACEMENT_LEN: usize = 3;
+/// Generic secrets are long runs of mixed-case letters and digits; shorter
+/// runs are mostly identifiers and versions.
+const MIN_GENERIC_TOKEN_LEN: usize = 32;
+const ALLOWED_ENV_KEYS: &[&str] = &[
+    "PATH", "PWD", "SHELL", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "TMPDIR", "TMP", "TEMP",
+];
+const KNOWN_TOKEN_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "ghr_",
+    "github_pat_",
+    "glpat-",
+    "npm_",
+    "xoxb-",
+    "xoxp-",
+    "sk-",
+    "AKIA",
+    "ASIA",
+];
+const GIT_REMOTE_SCHEMES: &[&str] = &["git", "ssh", "git+ssh", "http", "https"];
+const TOKEN_WRAPPER_CHARS: &[char] = &[
+    '"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', ',', ';',
+];
+
+/// Category of value a diagnostics redaction pass replaced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionRule {
+    /// A pattern configured in the `diagnostics_redaction_patterns` setting.
+    CustomPattern,
+    /// `Authorization:` and `Proxy-Authorization:` header lines.
+    AuthorizationHeader,
+    /// `KEY=value` assignments outside the diagnostics allowlist.
+    EnvAssignment,
+    /// `key=value` or `key:value` pairs whose key names a secret.
+    SecretValue,
+    /// Known token formats and long mixed-case alphanumeric runs.
+    Token,
+    /// `user:password@` credentials embedded in URLs.
+    UrlCredentials,
+    /// Repository paths of git remotes, which name private repositories.
+    GitRemote,
+    Email,
+    /// The current user's home directory, replaced with `~`.
+    HomeDirectory,
+    /// Other users' home directories under `/Users` or `/home`.
+    UserPath,
+    Username,
+    Hostname,
+}
+
+impl RedactionRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RedactionRule::CustomPattern => "custom_pattern",
+            RedactionRule::AuthorizationHeader => "authorization_header",
+            RedactionRule::EnvAssignment => "env_assignment",
+            RedactionRule::SecretValue => "secret_value",
+            RedactionRule::Token => "token",
+            RedactionRule::UrlCredentials => "url_credentials",
+            RedactionRule::GitRemote => "git_remote",
+            RedactionRule::Email => "email",
+            RedactionRule::HomeDirectory => "home_directory",
+            RedactionRule::UserPath => "user_path",
+            RedactionRule::Username => "username",
+            RedactionRule::Hostname => "hostname",
+        }
+    }
+}
+
+/// Machine-specific values a redaction pass substitutes, plus literal
+/// patterns the user configured.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RedactionConfig {
+    pub home_dir: Option<String>,
+    pub username: Option<String>,
+    pub hostname: Option<String>,
+    pub custom_patterns: Vec<String>,
+}
+
+impl RedactionConfig {
+    /// Config for the user and host this process runs as.
+    pub fn current_user(custom_patterns: Vec<String>) -> Self {
+        let home_dir = std::env::var("HOME")
+            .ok()
+            .map(|home| home.trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+        let username = std::env::var("USER")
+            .or_else(|_| std::env::var("LOGNAME"))
+            .ok()
+            .or_else(|| {
+                home_dir.as_deref().and_then(|home| {
+                    Path::new(home)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+            })
+            .filter(|name| !name.trim().is_empty());
+        Self {
+            home_dir,
+            username,
+            hostname: current_hostname(),
+            custom_patterns,
+        }
+    }
+}
+
+/// Split the `diagnostics_redaction_patterns` setting into literal patterns.
+/// Patterns are separated by commas or newlines.
+pub fn parse_custom_patterns(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRuleCount {
+    pub rule: RedactionRule,
+    pub count: usize,
+}
+
+/// What a redaction pass replaced, by rule, without the replaced values.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionReport {
+    pub total_replacements: usize,
+    pub rules: Vec<RedactionRuleCount>,
+}
+
+/// Redaction pass for diagnostics exports. One redactor is meant to cover a
+/// whole bundle so its report describes everything that was exported.
+#[derive(Clone, Debug)]
+pub struct DiagnosticsRedactor {
+    config: RedactionConfig,
+    counts: BTreeMap<RedactionRule, usize>,
+}
+
+impl DiagnosticsRedactor {
+    pub fn new(config: RedactionConfig) -> Self {
+        Self {
+            config,
+            counts: BTreeMap::new(),
+        }
+    }
+
+    pub fn redact_text(&mut self, value: &str) -> String {
+        let mut text = value.to_string();
+        for pattern in self.config.custom_patterns.clone() {
+            text = self.replace_literal(
+                RedactionRule::CustomPattern,
+                &text,
+                &pattern,
+                REDACTED_PLACEHOLDER,
+                false,
+            );
+        }
+
+        let mut lines = Vec::new();
+        for line in text.split('\n') {
+            match redact_auth_header_line(line) {
+                Some(redacted) => {
+                    self.record(RedactionRule::AuthorizationHeader, 1);
+                    lines.push(redacted);
+                }
+                None => lines.push(self.redact_tokens(line)),
+            }
+        }
+        text = lines.join("\n");
+
+        if let Some(home) = self.config.home_dir.clone() {
+            text = self.replace_literal(RedactionRule::HomeDirectory, &text, &home, "~", true);
+        }
+        text = self.redact_user_paths(&text);
+        if let Some(hostname) = self.config.hostname.clone() {
+            let short = hostname.split('.').next().unwrap_or_default().to_string();
+            text = self.replace_literal(
+                RedactionRule::Hostname,
+                &text,
+                &hostname,
+                HOSTNAME_PLACEHOLDER,
+                true,
+            );
+            if short != hostname {
+                text = self.replace_literal(
+                    RedactionRule::Hostname,
+                    &text,
+                    &short,
+                    HOSTNAME_PLACEHOLDER,
+                    true,
+                );
+            }
+        }
+        if let Some(username) = self.config.username.clone() {
+            text = self.replace_literal(
+                RedactionRule::Username,
+                &text,
+                &username,
+                USER_PLACEHOLDER,
+                true,
+            );
+        }
+        text
+    }
+
+    pub fn redact_optional(&mut self, value: Option<String>) -> Option<String> {
+        value.map(|text| self.redact_text(&text))
+    }
+
+    /// Redact every string in `value` in place; object keys are kept.
+    pub fn redact_json(&mut self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.redact_text(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            Value::Object(entries) => entries
+                .values_mut()
+                .for_each(|entry| self.redact_json(entry)),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    pub fn report(&self) -> RedactionReport {
+        RedactionReport {
+            total_replacements: self.counts.values().sum(),
+            rules: self
+                .counts
+                .iter()
+                .map(|(rule, count)| RedactionRuleCount {
+                    rule: *rule,
+                    count: *count,
+                })
+                .collect(),
+        }
+    }
+
+    fn record(&mut self, rule: RedactionRule, count: usize) {
+        if count > 0 {
+            *self.counts.entry(rule).or_default() += count;
+        }
+    }
+
+    fn redact_tokens(&mut self, line: &str) -> String {
+        let mut rendered = String::with_capacity(line.len());
+        let mut token = String::new();
+        for character in line.chars() {
+            if character.is_whitespace() {
+                if !token.is_empty() {
+                    rendered.push_str(&self.redact_token(&token));
+                    token.clear();
+                }
+                rendered.push(character);
+                continue;
+            }
+            token.push(character);
+        }
+        if !token.is_empty() {
+            rendered.push_str(&self.redact_token(&token));
+        }
+        rendered
+    }
+
+    fn redact_token(&mut self, token: &str) -> String {
+        let core = token.trim_matches(TOKEN_WRAPPER_CHARS);
+        if core.is_empty() {
+            return token.to_string();
+        }
+        let start = token.find(core).unwrap_or(0);
+        let (prefix, suffix) = (&token[..start], &token[start + core.len()..]);
+
+        let redacted = if let Some(redacted) = redact_env_assignment(core) {
+            (redacted != core).then_some((RedactionRule::EnvAssignment, redacted))
+        } else if let Some(redacted) = redact_sensitive_pair(core) {
+            Some((RedactionRule::SecretValue, redacted))
+        } else if let Some(redacted) = redact_url(core, &mut self.counts) {
+            Some(redacted)
+        } else if let Some(redacted) = redact_scp_remote(core) {
+            Some((RedactionRule::GitRemote, redacted))
+        } else if looks_like_email(core) {
+            Some((RedactionRule::Email, EMAIL_PLACEHOLDER.to_string()))
+        } else if looks_like_token(core) {
+            Some((RedactionRule::Token, REDACTED_PLACEHOLDER.to_string()))
+        } else {
+            None
+        };
+
+        match redacted {
+            Some((rule, redacted)) => {
+                self.record(rule, 1);
+                format!("{prefix}{redacted}{suffix}")
+            }
+            None => token.to_string(),
+        }
+    }
+
+    fn redact_user_paths(&mut self, text: &str) -> String {
+        let mut rendered = text.to_string();
+        for root in ["/Users/", "/home/"] {
+            let mut output = String::with_capacity(rendered.len());
+            let mut rest = rendered.as_str();
+            let mut count = 0;
+            while let Some(index) = rest.find(root) {
+                let after = &rest[index + root.len()..];
+                let segment_len = after
+                    .find(|character: char| character == '/' || !is_path_char(character))
+                    .unwrap_or(after.len());
+                let segment = &after[..segment_len];
+                output.push_str(&rest[..index + root.len()]);
+                if segment.is_empty() || segment == "Shared" || segment == USER_PLACEHOLDER {
+                    output.push_str(segment);
+                } else {
+                    output.push_str(USER_PLACEHOLDER);
+                    count += 1;
+                }
+                rest = &after[segment_len..];
+            }
+            output.push_str(rest);
+            self.record(RedactionRule::UserPath, count);
+            rendered = output;
+        }
+        rendered
+    }
+
+    /// Replace `target` with `replacement`. With `bounded`, only occurrences
+    /// not embedded in a longer name or path segment are replaced.
+    fn replace_literal(
+        &mut self,
+        rule: RedactionRule,
+        text: &str,
+        target: &str,
+        replacement: &str,
+        bounded: bool,
+    ) -> String {
+        if target.is_empty()
+            || (bounded
+                && rule != RedactionRule::HomeDirectory
+                && target.chars().count() < MIN_IDENTITY_REPLACEMENT_LEN)
+        {
+            return text.to_string();
+        }
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        let mut count = 0;
+        while let Some(found) = text[cursor..].find(target) {
+            let start = cursor + found;
+            let end = start + target.len();
+            let standalone = !bounded
+                || !(text[..start].chars().next_back().is_some_and(is_name_char)
+                    || continues_name(&text[end..]));
+            output.push_str(&text[cursor..start]);
+            if standalone {
+                output.push_str(replacement);
+                count += 1;
+            } else {
+                output.push_str(target);
+            }
+            cursor = end;
+        }
+        output.push_str(&text[cursor..]);
+        self.record(rule, count);
+        output
+    }
+}
+
+/// Mask credentials in `value` without touching machine identity: the
+/// redaction applied to task output and logs shown outside an export.
+pub fn redact_secrets(value: &str) -> String {
+    let line_redacted = value
+        .lines()
+        .map(|line| redact_auth_header_line(line).unwrap_or_else(|| line.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut rendered = String::with_capacity(line_redacted.len());
+    let mut token = String::new();
+    for character in line_redacted.chars() {
+        if character.is_whitespace() {
+            if !token.is_empty() {
+                rendered.push_str(&redact_secret_token(&token));
+                token.clear();
+            }
+            rendered.push(character);
+            continue;
+        }
+        token.push(character);
+    }
+    if !token.is_empty() {
+        rendered.push_str(&redact_secret_token(&token));
+    }
+    if value.ends_with('\n') && !rendered.ends_with('\n') {
+        rendered.push('\n');
+    }
+    rendered
+}
+
+fn redact_secret_token(token: &str) -> String {
+    redact_env_assignment(token)
+        .or_else(|| redact_sensitive_pair(token))
+        .unwrap_or_else(|| token.to_string())
+}
+
+#[cfg(unix)]
+fn current_hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its full length and gethostname writes
+    // at most that many bytes.
+    let status =
+        unsafe { libc::gethostname(buffer.as_mut_ptr().cast::<libc::c_char>(), buffer.len()) };
+    if status != 0 {
+        return None;
+    }
+    let len = buffer
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(buffer.len());
+    let hostname = String::from_utf8_lossy(&buffer[..len]).trim().to_string();
+    (!hostname.is_empty()).then_some(hostname)
+}
+
+#[cfg(not(unix))]
+fn current_hostname() -> Option<String> {
+    None
+}
+
+fn is_name_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_' || character == '-'
+}
+
+/// Whether `rest` carries on the name just matched, as in `alice2` or
+/// `alice.old`; a trailing period ends the name.
+fn continues_name(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    match chars.next() {
+        Some('.') => chars.next().is_some_and(char::is_alphanumeric),
+        Some(character) => is_name_char(character),
+        None => false,
+    }
+}
+
+fn is_path_char(character: char) -> bool {
+    is_name_char(character) || character == '.' || character == '@' || character == '+'
+}
+
+fn looks_like_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if !(first.is_ascii_alphabetic() || first == '_') {
+        return false;
+    }
+    chars.all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+fn normalize_sensitive_key(raw: &str) -> Option<String> {
+    let trimmed = raw.trim_matches(|character: char| {
+        !(character.is_ascii_alphanumeric() || character == '_' || character == '-')
+    });
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(trimmed.to_ascii_lowercase().replace('-', "_"))
+}
+
+fn is_sensitive_key_name(key: &str) -> bool {
+    key.contains("token")
+        || key.contains("secret")
+        || key.contains("password")
+        || key.contains("passwd")
+        || key.contains("api_key")
+        || key.contains("apikey")
+        || key.contains("auth")
+        || key.contains("cookie")
+        || key.contains("session")
+        || key.contains("license_key")
+        || key.contains("private_key")
+}
+
+/// `Some` for any `KEY=value` assignment: unchanged when the key is
+/// allowlisted, with the value masked otherwise.
+fn redact_env_assignment(token: &str) -> Option<String> {
+    let (key, value) = token.split_once('=')?;
+    if value.is_empty() || !looks_like_env_key(key) {
+        return None;
+    }
+    if ALLOWED_ENV_KEYS
+        .iter()
+        .any(|allowed| key.eq_ignore_ascii_case(allowed))
+    {
+        return Some(token.to_string());
+    }
+    Some(format!("{key}={REDACTED_PLACEHOLDER}"))
+}
+
+fn redact_sensitive_pair(token: &str) -> Option<String> {
+    let (key, delimiter, value) = if let Some((key, value)) = token.split_once('=') {
+        (key, '=', value)
+    } else if let Some((key, value)) = token.split_once(':') {
+        (key, ':', value)
+    } else {
+        return None;
+    };
+    if value.is_empty() || value.starts_with("//") {
+        return None;
+    }
+    let normalized = normalize_sensitive_key(key)?;
+    if !is_sensitive_key_name(normalized.as_str()) {
+        return None;
+    }
+    Some(format!("{key}{delimiter}{REDACTED_PLACEHOLDER}"))
+}
+
+fn redact_auth_header_line(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let lowercase = trimmed.to_ascii_lowercase();
+    if !(lowercase.starts_with("authorization:") || lowercase.starts_with("proxy-authorization:")) {
+        return None;
+    }
+    let indent_len = line.len().saturating_sub(trimmed.len());
+    let indent = &line[..indent_len];
+    let header_name = trimmed
+        .split_once(':')
+        .map(|(name, _)| name)
+        .unwrap_or("Authorization");
+    Some(format!("{indent}{header_name}: {REDACTED_PLACEHOLDER}"))
+}
+
+/// Strip URL credentials and git repository paths. Credentials are counted
+/// here so a URL with both reports both rules.
+fn redact_url(
+    token: &str,
+    counts: &mut BTreeMap<RedactionRule, usize>,
+) -> Option<(RedactionRule, String)> {
+    let (scheme, rest) = token.split_once("://")?;
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+')
+    {
+        return None;
+    }
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let (authority, had_credentials) = match authority.rsplit_once('@') {
+        Some((userinfo, host)) if !userinfo.is_empty() => {
+            (format!("{REDACTED_PLACEHOLDER}@{host}"), true)
+        }
+        _ => (authority.to_string(), false),
+    };
+    let git_path = path.trim_end_matches('/');
+    let is_git_remote = GIT_REMOTE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+        && git_path.ends_with(".git")
+        && git_path.len() > "/.git".len();
+
+    match (had_credentials, is_git_remote) {
+        (false, false) => None,
+        (true, false) => Some((
+            RedactionRule::UrlCredentials,
+            format!("{scheme}://{authority}{path}"),
+        )),
+        (had_credentials, true) => {
+            if had_credentials {
+                *counts.entry(RedactionRule::UrlCredentials).or_default() += 1;
+            }
+            Some((
+                RedactionRule::GitRemote,
+                format!("{scheme}://{authority}/{REDACTED_PLACEHOLDER}.git"),
+            ))
+        }
+    }
+}
+
+/// scp-style remotes such as `git@example.com:team/repo.git`.
+fn redact_scp_remote(token: &str) -> Option<String> {
+    let (user_host, path) = token.split_once(':')?;
+    let (user, host) = user_host.split_once('@')?;
+    if user.is_empty()
+        || !host.contains('.')
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        || path.is_empty()
+        || path.starts_with('/')
+        || !path.contains('/')
+    {
+        return None;
+    }
+    Some(format!("{user}@{host}:{REDACTED_PLACEHOLDER}"))
+}
+
+fn looks_like_email(token: &str) -> bool {
+    let Some((local, domain)) = token.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c))
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        && domain
+            .rsplit('.')
+            .next()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+fn looks_like_token(token: &str) -> bool {
+    let body_ok = |body: &str| {
+        body.len() >= 16
+            && body
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if KNOWN_TOKEN_PREFIXES
+        .iter()
+        .any(|prefix| token.strip_prefix(prefix).is_some_and(body_ok))
+    {
+        return true;
+    }
+    token.len() >= MIN_GENERIC_TOKEN_LEN
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+/=".contains(c))
+        && token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_digit())
+        && !token.contains('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(custom_patterns: &[&str]) -> DiagnosticsRedactor {
+        DiagnosticsRedactor::new(RedactionConfig {
+            home_dir: Some("/Users/alice".to_string()),
+            username: Some("alice".to_string()),
+            hostname: Some("alices-mbp.local".to_string()),
+            custom_patterns: custom_patterns.iter().map(|p| p.to_string()).collect(),
+        })
+    }
+
+    fn count(report: &RedactionReport, rule: RedactionRule) -> usize {
+        report
+            .rules
+            .iter()
+            .find(|entry| entry.rule == rule)
+            .map_or(0, |entry| entry.count)
+    }
+
+    #[test]
+    fn machine_identity_is_substituted() {
+        let mut redactor = redactor(&[]);
+        let redacted = redactor.redact_text(
+            "cd /Users/alice/src && ls /Users/bob/Library\n\
+             alice logged in on alices-mbp (alices-mbp.local)\n\
+             /Users/alice2 /Users/Shared/cache",
+        );
+        assert_eq!(
+            redacted,
+            "cd ~/src && ls /Users/[user]/Library\n\
+             [user] logged in on [hostname] ([hostname])\n\
+             /Users/[user] /Users/Shared/cache"
+        );
+        let report = redactor.report();
+        assert_eq!(count(&report, RedactionRule::HomeDirectory), 1);
+        assert_eq!(count(&report, RedactionRule::UserPath), 2);
+        assert_eq!(count(&report, RedactionRule::Hostname), 2);
+        assert_eq!(count(&report, RedactionRule::Username), 1);
+    }
+
+    #[test]
+    fn secrets_tokens_and_remotes_are_masked() {
+        let mut redactor = redactor(&[]);
+        let redacted = redactor.redact_text(
+            "Authorization: Bearer abc\n\
+             NPM_TOKEN=xyz PATH=/usr/bin password:hunter2\n\
+             push ghp_0123456789abcdefABCDEF to git@github.example.com:acme/secret.git\n\
+             clone https://bob:pw@git.example.com/acme/secret.git from 'https://github.com/Homebrew/brew'\n\
+             mail me@example.org, sha 4f2a9c0e8b7d6a5f4e3d2c1b0a9f8e7d",
+        );
+        assert_eq!(
+            redacted,
+            "Authorization: [REDACTED]\n\
+             NPM_TOKEN=[REDACTED] PATH=/usr/bin password:[REDACTED]\n\
+             push [REDACTED] to git@github.example.com:[REDACTED]\n\
+             clone https://[REDACTED]@git.example.com/[REDACTED].git from 'https://github.com/Homebrew/brew'\n\
+             mail [email], sha 4f2a9c0e8b7d6a5f4e3d2c1b0a9f8e7d"
+        );
+        let report = redactor.report();
+        assert_eq!(count(&report, RedactionRule::AuthorizationHeader), 1);
+        assert_eq!(count(&report, RedactionRule::EnvAssignment), 1);
+        assert_eq!(count(&report, RedactionRule::SecretValue), 1);
+        assert_eq!(count(&report, RedactionRule::Token), 1);
+        assert_eq!(count(&report, RedactionRule::GitRemote), 2);
+        assert_eq!(count(&report, RedactionRule::UrlCredentials), 1);
+        assert_eq!(count(&report, RedactionRule::Email), 1);
+        assert_eq!(report.total_replacements, 8);
+    }
+
+    #[test]
+    fn custom_patterns_and_json_values_are_redacted() {
+        assert_eq!(
+            parse_custom_patterns(Some(" acme-internal ,\nproject-x,, ")),
+            vec!["acme-internal".to_string(), "project-x".to_string()]
+        );
+        let mut redactor = redactor(&["acme-internal"]);
+        let mut payload = serde_json::json!({
+            "/Users/alice": ["tap acme-internal/tools", 3],
+            "path": "/Users/alice/.cargo/bin"
+        });
+        redactor.redact_json(&mut payload);
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "/Users/alice": ["tap [REDACTED]/tools", 3],
+                "path": "~/.cargo/bin"
+            })
+        );
+        assert_eq!(count(&redactor.report(), RedactionRule::CustomPattern), 1);
+    }
+
+    #[test]
+    fn redact_secrets_leaves_machine_identity_alone() {
+        assert_eq!(
+            redact_secrets("API_TOKEN=abc HOME_DIR /Users/alice TMPDIR=/tmp\n"),
+            "API_TOKEN=[REDACTED] HOME_DIR /Users/alice TMPDIR=/tmp\n"
+        );
+    }
+}
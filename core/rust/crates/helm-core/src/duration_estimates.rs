@@ -0,0 +1,204 @@
+//! Rough wall-clock estimates for manager actions, for plan previews.
+//!
+//! Adapters declare a static heuristic per action
+//! ([`ManagerAdapter::estimated_duration`](crate::adapters::ManagerAdapter::estimated_duration));
+//! durations of completed tasks archived on this machine pull the estimate
+//! towards what the action actually takes here as samples accumulate.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::models::{ManagerAction, ManagerId, TaskHistoryRecord, TaskStatus, TaskType};
+
+/// Weight of an adapter's declared estimate, in archived samples. With fewer
+/// samples than this the heuristic still dominates.
+const DECLARED_ESTIMATE_WEIGHT: u64 = 3;
+
+/// Static per-action heuristic used by adapters that do not declare their own.
+/// `None` for actions Helm never estimates.
+pub fn default_action_duration(manager: ManagerId, action: ManagerAction) -> Option<Duration> {
+    let seconds = match action {
+        ManagerAction::Install | ManagerAction::Upgrade => match manager {
+            ManagerId::SoftwareUpdate => 20 * 60,
+            ManagerId::MacPorts => 180,
+            ManagerId::Cargo => 120,
+            ManagerId::Asdf | ManagerId::Mise => 90,
+            ManagerId::HomebrewFormula | ManagerId::Rustup | ManagerId::Mas => 60,
+            ManagerId::HomebrewCask => 45,
+            ManagerId::RubyGems | ManagerId::Bundler => 20,
+            _ => 15,
+        },
+        ManagerAction::Uninstall => match manager {
+            ManagerId::HomebrewCask | ManagerId::MacPorts | ManagerId::Rustup => 20,
+            _ => 10,
+        },
+        ManagerAction::Refresh => match manager {
+            ManagerId::SoftwareUpdate | ManagerId::MacPorts => 60,
+            ManagerId::HomebrewFormula | ManagerId::HomebrewCask => 20,
+            _ => 10,
+        },
+        ManagerAction::Configure | ManagerAction::Pin | ManagerAction::Unpin => 5,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+/// Task type archived for `action`, for the actions that are estimated.
+fn history_task_type(action: ManagerAction) -> Option<TaskType> {
+    match action {
+        ManagerAction::Install => Some(TaskType::Install),
+        ManagerAction::Uninstall => Some(TaskType::Uninstall),
+        ManagerAction::Upgrade => Some(TaskType::Upgrade),
+        ManagerAction::Refresh => Some(TaskType::Refresh),
+        ManagerAction::Configure => Some(TaskType::Configure),
+        ManagerAction::Pin => Some(TaskType::Pin),
+        ManagerAction::Unpin => Some(TaskType::Unpin),
+        _ => None,
+    }
+}
+
+/// Average durations of completed tasks per (manager, task type), learned
+/// from the task history archive.
+#[derive(Clone, Debug, Default)]
+pub struct DurationEstimator {
+    learned: HashMap<(ManagerId, TaskType), (u64, u64)>,
+}
+
+impl DurationEstimator {
+    pub fn from_history(history: &[TaskHistoryRecord]) -> Self {
+        let mut learned: HashMap<(ManagerId, TaskType), (u64, u64)> = HashMap::new();
+        for entry in history {
+            if entry.status != TaskStatus::Completed {
+                continue;
+            }
+            let Some(duration_ms) = entry.duration_ms else {
+                continue;
+            };
+            let totals = learned.entry((entry.manager, entry.task_type)).or_default();
+            totals.0 = totals.0.saturating_add(duration_ms);
+            totals.1 += 1;
+        }
+        Self { learned }
+    }
+
+    /// Estimate `action` on `manager`, blending the adapter's `declared`
+    /// heuristic with the learned average weighted by its sample count.
+    /// Falls back to whichever of the two is known.
+    pub fn estimate(
+        &self,
+        manager: ManagerId,
+        action: ManagerAction,
+        declared: Option<Duration>,
+    ) -> Option<Duration> {
+        let learned = history_task_type(action)
+            .and_then(|task_type| self.learned.get(&(manager, task_type)).copied())
+            .filter(|(_, samples)| *samples > 0);
+        let estimate_ms = match (declared, learned) {
+            (None, None) => return None,
+            (Some(declared), None) => declared.as_millis() as u64,
+            (None, Some((total, samples))) => total / samples,
+            (Some(declared), Some((total, samples))) => {
+                let declared_ms = declared.as_millis() as u64;
+                declared_ms
+                    .saturating_mul(DECLARED_ESTIMATE_WEIGHT)
+                    .saturating_add(total)
+                    / (DECLARED_ESTIMATE_WEIGHT + samples)
+            }
+        };
+        Some(Duration::from_millis(estimate_ms))
+    }
+}
+
+/// Offset from the start of a plan at which each step should finish. Managers
+/// run their steps one at a time, in order, but in parallel with each other,
+/// so the plan takes as long as its latest finish offset.
+pub fn plan_finish_offsets(steps: &[(ManagerId, Option<Duration>)]) -> Vec<Option<Duration>> {
+    let mut elapsed: HashMap<ManagerId, Option<Duration>> = HashMap::new();
+    steps
+        .iter()
+        .map(|(manager, duration)| {
+            let lane = elapsed.entry(*manager).or_insert(Some(Duration::ZERO));
+            *lane = lane.zip(*duration).map(|(lane, duration)| lane + duration);
+            *lane
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{DurationEstimator, default_action_duration, plan_finish_offsets};
+    use crate::models::{
+        ManagerAction, ManagerId, TaskHistoryRecord, TaskId, TaskStatus, TaskType,
+    };
+
+    fn archived(manager: ManagerId, status: TaskStatus, duration_ms: u64) -> TaskHistoryRecord {
+        TaskHistoryRecord {
+            task_id: TaskId(duration_ms),
+            manager,
+            task_type: TaskType::Upgrade,
+            status,
+            exit_code: None,
+            error_code: None,
+            error_message: None,
+            duration_ms: Some(duration_ms),
+            stdout_tail: None,
+            stderr_tail: None,
+            finished_at: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn learned_durations_refine_declared_estimates() {
+        let declared = default_action_duration(ManagerId::Npm, ManagerAction::Upgrade);
+        assert_eq!(declared, Some(Duration::from_secs(15)));
+        assert_eq!(
+            default_action_duration(ManagerId::Npm, ManagerAction::Search),
+            None
+        );
+
+        let estimator = DurationEstimator::from_history(&[
+            archived(ManagerId::Npm, TaskStatus::Completed, 35_000),
+            archived(ManagerId::Npm, TaskStatus::Failed, 900_000),
+        ]);
+        // (3 * 15s + 35s) / 4
+        assert_eq!(
+            estimator.estimate(ManagerId::Npm, ManagerAction::Upgrade, declared),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            estimator.estimate(ManagerId::Npm, ManagerAction::Upgrade, None),
+            Some(Duration::from_secs(35))
+        );
+        assert_eq!(
+            estimator.estimate(ManagerId::Pip, ManagerAction::Upgrade, declared),
+            declared
+        );
+        assert_eq!(
+            estimator.estimate(ManagerId::Pip, ManagerAction::Upgrade, None),
+            None
+        );
+    }
+
+    #[test]
+    fn plan_finish_offsets_run_managers_in_parallel() {
+        let offsets = plan_finish_offsets(&[
+            (ManagerId::Npm, Some(Duration::from_secs(10))),
+            (ManagerId::Pip, Some(Duration::from_secs(30))),
+            (ManagerId::Npm, Some(Duration::from_secs(5))),
+            (ManagerId::Cargo, None),
+            (ManagerId::Cargo, Some(Duration::from_secs(5))),
+        ]);
+        assert_eq!(
+            offsets,
+            vec![
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(30)),
+                Some(Duration::from_secs(15)),
+                None,
+                None,
+            ]
+        );
+    }
+}
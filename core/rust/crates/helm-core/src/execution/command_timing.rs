@@ -0,0 +1,298 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::models::{ManagerAction, ManagerId, TaskId};
+
+/// Inclusive upper bounds of the duration buckets; a final overflow bucket
+/// counts everything slower than the last bound.
+pub const COMMAND_DURATION_BUCKETS_MS: [u64; 10] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 300_000,
+];
+/// Samples kept per (manager, action); older samples roll off.
+const ROLLING_SAMPLE_LIMIT: usize = 256;
+/// Commands kept in the daily slow-command trace.
+pub const SLOW_COMMAND_TRACE_LIMIT: usize = 10;
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+static SLOW_COMMAND_TRACING: AtomicBool = AtomicBool::new(false);
+static COMMAND_TIMINGS: OnceLock<Mutex<CommandTimings>> = OnceLock::new();
+
+/// Turn the daily slow-command trace on or off. Histograms are always kept;
+/// the trace is opt-in because it retains full command lines.
+pub fn set_slow_command_tracing_enabled(enabled: bool) {
+    SLOW_COMMAND_TRACING.store(enabled, Ordering::SeqCst);
+    if !enabled {
+        lock_timings().slow_commands.clear();
+    }
+}
+
+pub fn slow_command_tracing_enabled() -> bool {
+    SLOW_COMMAND_TRACING.load(Ordering::SeqCst)
+}
+
+/// One finished (or timed out) process.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandTimingSample {
+    pub manager: ManagerId,
+    pub action: ManagerAction,
+    pub task_id: Option<TaskId>,
+    pub command: String,
+    pub started_at: SystemTime,
+    pub duration: Duration,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandTimingHistogram {
+    pub manager_id: ManagerId,
+    pub action: &'static str,
+    pub sample_count: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    /// One count per entry in `COMMAND_DURATION_BUCKETS_MS`, plus overflow.
+    pub bucket_counts: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowCommandTrace {
+    pub manager_id: ManagerId,
+    pub action: &'static str,
+    pub task_id: Option<u64>,
+    pub command: String,
+    pub started_at_unix_ms: i64,
+    pub duration_ms: u64,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandTimingSnapshot {
+    pub bucket_upper_bounds_ms: Vec<u64>,
+    pub histograms: Vec<CommandTimingHistogram>,
+    pub slow_command_tracing_enabled: bool,
+    /// Slowest commands started on the current UTC day, slowest first.
+    pub slow_commands: Vec<SlowCommandTrace>,
+}
+
+/// Record a finished process into the rolling histograms and, when tracing is
+/// enabled, today's slow-command trace.
+pub fn record_command_timing(sample: CommandTimingSample) {
+    let trace = slow_command_tracing_enabled();
+    lock_timings().record(sample, trace);
+}
+
+pub fn command_timing_snapshot() -> CommandTimingSnapshot {
+    let mut snapshot = lock_timings().snapshot();
+    snapshot.slow_command_tracing_enabled = slow_command_tracing_enabled();
+    snapshot
+}
+
+fn lock_timings() -> std::sync::MutexGuard<'static, CommandTimings> {
+    COMMAND_TIMINGS
+        .get_or_init(|| Mutex::new(CommandTimings::default()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[derive(Default)]
+struct CommandTimings {
+    samples: HashMap<(ManagerId, ManagerAction), VecDeque<u64>>,
+    slow_command_day: Option<i64>,
+    slow_commands: Vec<SlowCommandTrace>,
+}
+
+impl CommandTimings {
+    fn record(&mut self, sample: CommandTimingSample, trace: bool) {
+        let duration_ms = u64::try_from(sample.duration.as_millis()).unwrap_or(u64::MAX);
+        let window = self
+            .samples
+            .entry((sample.manager, sample.action))
+            .or_default();
+        if window.len() == ROLLING_SAMPLE_LIMIT {
+            window.pop_front();
+        }
+        window.push_back(duration_ms);
+
+        if !trace {
+            return;
+        }
+        let started_at_unix_ms = sample
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX));
+        let day = started_at_unix_ms.div_euclid(MILLIS_PER_DAY);
+        if self.slow_command_day != Some(day) {
+            self.slow_command_day = Some(day);
+            self.slow_commands.clear();
+        }
+        let is_slow_enough = self.slow_commands.len() < SLOW_COMMAND_TRACE_LIMIT
+            || self
+                .slow_commands
+                .last()
+                .is_some_and(|slowest_kept| duration_ms > slowest_kept.duration_ms);
+        if !is_slow_enough {
+            return;
+        }
+        tracing::info!(
+            manager = sample.manager.as_str(),
+            action = sample.action.as_str(),
+            duration_ms,
+            command = %sample.command,
+            "slow command traced"
+        );
+        let position = self
+            .slow_commands
+            .partition_point(|kept| kept.duration_ms >= duration_ms);
+        self.slow_commands.insert(
+            position,
+            SlowCommandTrace {
+                manager_id: sample.manager,
+                action: sample.action.as_str(),
+                task_id: sample.task_id.map(|task_id| task_id.0),
+                command: sample.command,
+                started_at_unix_ms,
+                duration_ms,
+                exit_code: sample.exit_code,
+                timed_out: sample.timed_out,
+            },
+        );
+        self.slow_commands.truncate(SLOW_COMMAND_TRACE_LIMIT);
+    }
+
+    fn snapshot(&self) -> CommandTimingSnapshot {
+        let mut histograms = self
+            .samples
+            .iter()
+            .map(|((manager, action), window)| histogram(*manager, *action, window))
+            .collect::<Vec<_>>();
+        histograms.sort_by(|a, b| {
+            (a.manager_id.as_str(), a.action).cmp(&(b.manager_id.as_str(), b.action))
+        });
+        CommandTimingSnapshot {
+            bucket_upper_bounds_ms: COMMAND_DURATION_BUCKETS_MS.to_vec(),
+            histograms,
+            slow_command_tracing_enabled: false,
+            slow_commands: self.slow_commands.clone(),
+        }
+    }
+}
+
+fn histogram(
+    manager: ManagerId,
+    action: ManagerAction,
+    window: &VecDeque<u64>,
+) -> CommandTimingHistogram {
+    let mut bucket_counts = vec![0_u64; COMMAND_DURATION_BUCKETS_MS.len() + 1];
+    for duration_ms in window {
+        let bucket = COMMAND_DURATION_BUCKETS_MS.partition_point(|bound| bound < duration_ms);
+        bucket_counts[bucket] += 1;
+    }
+    let mut sorted = window.iter().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    CommandTimingHistogram {
+        manager_id: manager,
+        action: action.as_str(),
+        sample_count: sorted.len() as u64,
+        total_ms: sorted
+            .iter()
+            .fold(0_u64, |total, ms| total.saturating_add(*ms)),
+        max_ms: sorted.last().copied().unwrap_or(0),
+        p50_ms: percentile(&sorted, 50),
+        p95_ms: percentile(&sorted, 95),
+        bucket_counts,
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{
+        COMMAND_DURATION_BUCKETS_MS, CommandTimingSample, CommandTimings, SLOW_COMMAND_TRACE_LIMIT,
+    };
+    use crate::models::{ManagerAction, ManagerId};
+
+    fn sample(manager: ManagerId, duration_ms: u64, started_at_secs: u64) -> CommandTimingSample {
+        CommandTimingSample {
+            manager,
+            action: ManagerAction::Refresh,
+            task_id: None,
+            command: format!("{} outdated", manager.as_str()),
+            started_at: UNIX_EPOCH + Duration::from_secs(started_at_secs),
+            duration: Duration::from_millis(duration_ms),
+            exit_code: Some(0),
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn histogram_buckets_durations_and_reports_percentiles() {
+        let mut timings = CommandTimings::default();
+        for duration_ms in [50, 100, 101, 900, 400_000] {
+            timings.record(sample(ManagerId::Npm, duration_ms, 0), false);
+        }
+
+        let snapshot = timings.snapshot();
+        assert_eq!(snapshot.histograms.len(), 1);
+        let histogram = &snapshot.histograms[0];
+        assert_eq!(histogram.action, "refresh");
+        assert_eq!(histogram.sample_count, 5);
+        assert_eq!(histogram.max_ms, 400_000);
+        assert_eq!(histogram.p50_ms, 101);
+        assert_eq!(histogram.p95_ms, 400_000);
+        assert_eq!(
+            histogram.bucket_counts.len(),
+            COMMAND_DURATION_BUCKETS_MS.len() + 1
+        );
+        assert_eq!(histogram.bucket_counts[0], 2);
+        assert_eq!(histogram.bucket_counts[1], 1);
+        assert_eq!(histogram.bucket_counts[3], 1);
+        assert_eq!(
+            histogram.bucket_counts[COMMAND_DURATION_BUCKETS_MS.len()],
+            1
+        );
+        assert!(snapshot.slow_commands.is_empty());
+    }
+
+    #[test]
+    fn slow_command_trace_keeps_slowest_per_day() {
+        let mut timings = CommandTimings::default();
+        for duration_ms in 1..=(SLOW_COMMAND_TRACE_LIMIT as u64 + 5) {
+            timings.record(sample(ManagerId::Pip, duration_ms * 10, 60), true);
+        }
+        let slow = timings.snapshot().slow_commands;
+        assert_eq!(slow.len(), SLOW_COMMAND_TRACE_LIMIT);
+        assert_eq!(
+            slow[0].duration_ms,
+            (SLOW_COMMAND_TRACE_LIMIT as u64 + 5) * 10
+        );
+        assert!(
+            slow.windows(2)
+                .all(|pair| pair[0].duration_ms >= pair[1].duration_ms)
+        );
+
+        timings.record(sample(ManagerId::Pip, 5, 86_400 + 60), true);
+        let next_day = timings.snapshot().slow_commands;
+        assert_eq!(next_day.len(), 1);
+        assert_eq!(next_day[0].duration_ms, 5);
+    }
+}
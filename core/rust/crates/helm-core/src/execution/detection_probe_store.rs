@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::models::{CoreErrorKind, TaskId};
+
+const MAX_PROBE_FAILURE_RECORDS: usize = 512;
+
+static PROBE_FAILURES: OnceLock<Mutex<BTreeMap<u64, CoreErrorKind>>> = OnceLock::new();
+
+fn probe_failures() -> &'static Mutex<BTreeMap<u64, CoreErrorKind>> {
+    PROBE_FAILURES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Remember that a version probe for `task_id` errored. The first failure wins
+/// so a later fallback probe cannot mask the original cause.
+pub fn record(task_id: TaskId, kind: CoreErrorKind) {
+    if let Ok(mut failures) = probe_failures().lock() {
+        if !failures.contains_key(&task_id.0) && failures.len() >= MAX_PROBE_FAILURE_RECORDS {
+            let oldest = failures.keys().next().copied();
+            if let Some(oldest) = oldest {
+                failures.remove(&oldest);
+            }
+        }
+        failures.entry(task_id.0).or_insert(kind);
+    }
+}
+
+pub fn take(task_id: TaskId) -> Option<CoreErrorKind> {
+    probe_failures()
+        .lock()
+        .ok()
+        .and_then(|mut failures| failures.remove(&task_id.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, take};
+    use crate::models::{CoreErrorKind, TaskId};
+
+    #[test]
+    fn first_recorded_failure_is_taken_once() {
+        let task_id = TaskId(92001);
+        record(task_id, CoreErrorKind::Timeout);
+        record(task_id, CoreErrorKind::ProcessFailure);
+        assert_eq!(take(task_id), Some(CoreErrorKind::Timeout));
+        assert_eq!(take(task_id), None);
+    }
+}
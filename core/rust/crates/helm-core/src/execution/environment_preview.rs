@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::execution::task_output_store::{redact_sensitive_env_value, redact_sensitive_text};
+use crate::execution::{
+    ExecutionResult, ProcessExecutor, ProcessSpawnRequest, RunningProcess,
+    manager_selected_executable,
+};
+use crate::models::{CoreError, CoreErrorKind, ManagerId};
+
+/// Executable lookups run before a source settles on its real command.
+const WHICH_PROGRAM: &str = "/usr/bin/which";
+
+/// Executor that records the first manager command it is asked to spawn and
+/// fails every spawn, so a source can be driven without running anything.
+///
+/// `/usr/bin/which` lookups are refused without being recorded; sources then
+/// fall back to filesystem discovery and go on to their real command.
+#[derive(Default)]
+pub struct EnvironmentPreviewExecutor {
+    captured: Mutex<Option<ProcessSpawnRequest>>,
+}
+
+impl EnvironmentPreviewExecutor {
+    pub fn take_captured(&self) -> Option<ProcessSpawnRequest> {
+        self.captured
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    }
+}
+
+impl ProcessExecutor for EnvironmentPreviewExecutor {
+    fn spawn(&self, request: ProcessSpawnRequest) -> ExecutionResult<Box<dyn RunningProcess>> {
+        let error = CoreError {
+            manager: Some(request.manager),
+            task: Some(request.task_type),
+            action: Some(request.action),
+            kind: CoreErrorKind::ProcessFailure,
+            message: "environment preview does not run commands".to_string(),
+        };
+        if request.command.program != Path::new(WHICH_PROGRAM) {
+            self.captured
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .get_or_insert(request);
+        }
+        Err(error)
+    }
+}
+
+/// PATH, environment, and program a manager's commands run with, with
+/// sensitive values redacted.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagerEnvironment {
+    pub manager_id: ManagerId,
+    /// Executable chosen in manager settings, if any.
+    pub selected_executable_path: Option<String>,
+    /// Program the previewed command resolves to after overrides and PATH lookup.
+    pub program_path: Option<String>,
+    pub args: Vec<String>,
+    /// PATH the command runs with; the inherited PATH when the source sets none.
+    pub path: Option<String>,
+    /// PATH Helm itself was launched with.
+    pub inherited_path: Option<String>,
+    /// Variables the source sets on top of the inherited environment, except PATH.
+    pub env_overrides: BTreeMap<String, String>,
+}
+
+/// Describe the environment of `captured`, the command a manager's source
+/// tried to spawn (already passed through `spawn_validated`). `None` means the
+/// source found nothing to run, so only settings and inherited state are shown.
+pub fn manager_environment(
+    manager: ManagerId,
+    captured: Option<ProcessSpawnRequest>,
+) -> ManagerEnvironment {
+    let inherited_path = std::env::var("PATH").ok();
+    let selected_executable_path =
+        manager_selected_executable(manager).map(|path| path.to_string_lossy().to_string());
+    let Some(request) = captured else {
+        return ManagerEnvironment {
+            manager_id: manager,
+            selected_executable_path,
+            program_path: None,
+            args: Vec::new(),
+            path: inherited_path.clone(),
+            inherited_path,
+            env_overrides: BTreeMap::new(),
+        };
+    };
+
+    let mut env = request.command.env;
+    let path = env.remove("PATH").or_else(|| inherited_path.clone());
+    ManagerEnvironment {
+        manager_id: manager,
+        selected_executable_path,
+        program_path: Some(request.command.program.to_string_lossy().to_string()),
+        args: request
+            .command
+            .args
+            .iter()
+            .map(|arg| redact_sensitive_text(arg))
+            .collect(),
+        path,
+        inherited_path,
+        env_overrides: env
+            .into_iter()
+            .map(|(key, value)| {
+                let value = redact_sensitive_env_value(&key, &value);
+                (key, value)
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvironmentPreviewExecutor, manager_environment};
+    use crate::execution::{CommandSpec, ProcessExecutor, ProcessSpawnRequest};
+    use crate::models::{ManagerAction, ManagerId, TaskType};
+
+    fn request(command: CommandSpec) -> ProcessSpawnRequest {
+        ProcessSpawnRequest::new(
+            ManagerId::HomebrewFormula,
+            TaskType::Detection,
+            ManagerAction::Detect,
+            command,
+        )
+    }
+
+    #[test]
+    fn preview_executor_records_first_non_which_command() {
+        let executor = EnvironmentPreviewExecutor::default();
+        let which = request(CommandSpec::new("/usr/bin/which").arg("brew"));
+        let version = request(CommandSpec::new("/opt/homebrew/bin/brew").arg("--version"));
+        let config = request(CommandSpec::new("/opt/homebrew/bin/brew").arg("config"));
+
+        assert!(executor.spawn(which).is_err());
+        assert!(executor.spawn(version.clone()).is_err());
+        assert!(executor.spawn(config).is_err());
+        assert_eq!(executor.take_captured(), Some(version));
+        assert_eq!(executor.take_captured(), None);
+    }
+
+    #[test]
+    fn environment_splits_path_and_redacts_sensitive_overrides() {
+        let captured = request(
+            CommandSpec::new("/opt/homebrew/bin/brew")
+                .arg("--version")
+                .env("PATH", "/opt/homebrew/bin:/usr/bin")
+                .env("HOMEBREW_GITHUB_API_TOKEN", "ghp_secret")
+                .env("HOMEBREW_NO_AUTO_UPDATE", "1"),
+        );
+
+        let environment = manager_environment(ManagerId::HomebrewFormula, Some(captured));
+        assert_eq!(
+            environment.program_path.as_deref(),
+            Some("/opt/homebrew/bin/brew")
+        );
+        assert_eq!(environment.args, vec!["--version".to_string()]);
+        assert_eq!(
+            environment.path.as_deref(),
+            Some("/opt/homebrew/bin:/usr/bin")
+        );
+        assert!(!environment.env_overrides.contains_key("PATH"));
+        assert_eq!(
+            environment.env_overrides["HOMEBREW_NO_AUTO_UPDATE"],
+            "1".to_string()
+        );
+        assert_ne!(
+            environment.env_overrides["HOMEBREW_GITHUB_API_TOKEN"],
+            "ghp_secret".to_string()
+        );
+    }
+}
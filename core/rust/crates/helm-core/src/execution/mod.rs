@@ -1,10 +1,32 @@
+pub mod command_policy;
+pub mod command_timing;
+pub mod detection_probe_store;
+pub mod environment_preview;
+pub mod process_qos;
 pub mod task_log_note_store;
 pub mod task_output_store;
+pub mod task_process_store;
+pub mod task_progress_store;
+pub mod task_temp_dir_store;
 pub mod timeout_prompt_store;
 #[cfg(unix)]
 pub mod tokio_process;
 
-pub use task_output_store::TaskOutputRecord;
+pub use command_policy::{
+    CommandPolicyMode, ScriptInstallerStep, command_policy_mode, set_command_policy_mode,
+};
+pub use command_timing::{
+    CommandTimingHistogram, CommandTimingSnapshot, command_timing_snapshot,
+    set_slow_command_tracing_enabled,
+};
+pub use environment_preview::{
+    EnvironmentPreviewExecutor, ManagerEnvironment, manager_environment,
+};
+pub use process_qos::{ProcessQos, default_process_qos, set_default_process_qos};
+pub use task_output_store::{
+    TaskOutputChunk, TaskOutputChunks, TaskOutputRecord, TaskOutputStream,
+};
+pub use task_process_store::TaskProcessRecord;
 #[cfg(unix)]
 pub use tokio_process::TokioProcessExecutor;
 
@@ -12,7 +34,7 @@ use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::{OnceLock, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::{Duration, SystemTime};
 
 use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskId, TaskType};
@@ -21,6 +43,29 @@ pub type ExecutionResult<T> = Result<T, CoreError>;
 
 pub type ProcessWaitFuture = Pin<Box<dyn Future<Output = ExecutionResult<ProcessOutput>> + Send>>;
 
+/// Reads a completion percentage from one line of a running command's output.
+#[derive(Clone, Copy)]
+pub struct ProgressParser(pub fn(&str) -> Option<u8>);
+
+impl PartialEq for ProgressParser {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for ProgressParser {}
+
+impl std::fmt::Debug for ProgressParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressParser")
+    }
+}
+
+/// Receives completion percentages parsed from a running task's output.
+pub trait ProgressSink: Send + Sync {
+    fn report_progress(&self, task_id: TaskId, percent: u8);
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CommandSpec {
     pub program: PathBuf,
@@ -114,6 +159,12 @@ pub struct ProcessSpawnRequest {
     pub requires_elevation: bool,
     pub timeout: Option<Duration>,
     pub idle_timeout: Option<Duration>,
+    pub script_installer: Option<ScriptInstallerStep>,
+    /// Filled from the manager's QoS preference by `spawn_validated` when
+    /// left at `ProcessQos::Default`.
+    pub qos: ProcessQos,
+    /// Parses progress out of the command's output lines as they stream.
+    pub progress_parser: Option<ProgressParser>,
     pub requested_at: SystemTime,
 }
 
@@ -133,6 +184,9 @@ impl ProcessSpawnRequest {
             requires_elevation: false,
             timeout: None,
             idle_timeout: None,
+            script_installer: None,
+            qos: ProcessQos::Default,
+            progress_parser: None,
             requested_at: SystemTime::now(),
         }
     }
@@ -157,6 +211,21 @@ impl ProcessSpawnRequest {
         self
     }
 
+    pub fn script_installer(mut self, step: ScriptInstallerStep) -> Self {
+        self.script_installer = Some(step);
+        self
+    }
+
+    pub fn qos(mut self, qos: ProcessQos) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn progress_parser(mut self, parser: fn(&str) -> Option<u8>) -> Self {
+        self.progress_parser = Some(ProgressParser(parser));
+        self
+    }
+
     pub fn validate(&self) -> ExecutionResult<()> {
         self.command
             .validate(self.manager, self.task_type, self.action)?;
@@ -232,6 +301,7 @@ static MANAGER_EXECUTABLE_OVERRIDES: OnceLock<RwLock<ManagerExecutionPreferences
 struct ManagerExecutionPreferences {
     executable_overrides: HashMap<ManagerId, PathBuf>,
     timeout_profiles: HashMap<ManagerId, ManagerTimeoutProfile>,
+    process_qos: HashMap<ManagerId, ProcessQos>,
 }
 
 fn manager_execution_preferences() -> &'static RwLock<ManagerExecutionPreferences> {
@@ -480,6 +550,12 @@ fn apply_manager_timeout_profile(request: &mut ProcessSpawnRequest) {
     request.idle_timeout = effective_idle_timeout;
 }
 
+fn apply_manager_process_qos(request: &mut ProcessSpawnRequest) {
+    if request.qos == ProcessQos::Default {
+        request.qos = effective_process_qos(request.manager);
+    }
+}
+
 pub fn set_manager_selected_executable(manager: ManagerId, path: Option<PathBuf>) {
     let Ok(mut guard) = manager_execution_preferences().write() else {
         return;
@@ -521,6 +597,30 @@ pub fn manager_timeout_profile(manager: ManagerId) -> Option<ManagerTimeoutProfi
         .and_then(|guard| guard.timeout_profiles.get(&manager).copied())
 }
 
+/// Override the QoS for one manager; `None` falls back to the global default.
+pub fn set_manager_process_qos(manager: ManagerId, qos: Option<ProcessQos>) {
+    let Ok(mut guard) = manager_execution_preferences().write() else {
+        return;
+    };
+    if let Some(qos) = qos {
+        guard.process_qos.insert(manager, qos);
+    } else {
+        guard.process_qos.remove(&manager);
+    }
+}
+
+pub fn manager_process_qos(manager: ManagerId) -> Option<ProcessQos> {
+    manager_execution_preferences()
+        .read()
+        .ok()
+        .and_then(|guard| guard.process_qos.get(&manager).copied())
+}
+
+/// The manager's override if set, otherwise the global default.
+pub fn effective_process_qos(manager: ManagerId) -> ProcessQos {
+    manager_process_qos(manager).unwrap_or_else(default_process_qos)
+}
+
 pub fn manager_selected_executable(manager: ManagerId) -> Option<PathBuf> {
     manager_execution_preferences()
         .read()
@@ -531,12 +631,14 @@ pub fn manager_selected_executable(manager: ManagerId) -> Option<PathBuf> {
 pub fn replace_manager_execution_preferences(
     executable_overrides: HashMap<ManagerId, PathBuf>,
     timeout_profiles: HashMap<ManagerId, ManagerTimeoutProfile>,
+    process_qos: HashMap<ManagerId, ProcessQos>,
 ) {
     let Ok(mut guard) = manager_execution_preferences().write() else {
         return;
     };
     guard.executable_overrides = executable_overrides;
     guard.timeout_profiles = timeout_profiles;
+    guard.process_qos = process_qos;
 }
 
 pub fn spawn_validated(
@@ -549,7 +651,9 @@ pub fn spawn_validated(
     apply_manager_executable_override(&mut request);
     resolve_program_from_path_env(&mut request.command);
     apply_manager_timeout_profile(&mut request);
+    apply_manager_process_qos(&mut request);
     request.validate()?;
+    command_policy::check_command_policy(&request)?;
     executor.spawn(request)
 }
 
@@ -557,6 +661,47 @@ pub fn task_output(task_id: TaskId) -> Option<TaskOutputRecord> {
     task_output_store::get(task_id)
 }
 
+pub fn task_output_since(task_id: TaskId, offset: u64) -> TaskOutputChunks {
+    task_output_store::get_since(task_id, offset)
+}
+
+pub fn task_outputs(task_ids: &[TaskId]) -> Vec<(TaskId, Option<TaskOutputRecord>)> {
+    task_output_store::get_many(task_ids)
+}
+
+pub fn task_process(task_id: TaskId) -> Option<TaskProcessRecord> {
+    task_process_store::get(task_id)
+}
+
+static PROGRESS_SINK: OnceLock<Arc<dyn ProgressSink>> = OnceLock::new();
+
+/// Route parsed task progress to `sink` instead of the built-in progress
+/// store. Returns false when a sink is already installed.
+pub fn install_progress_sink(sink: Arc<dyn ProgressSink>) -> bool {
+    PROGRESS_SINK.set(sink).is_ok()
+}
+
+/// Apply `parser` to each line of an output chunk and report the last
+/// percentage found. Progress bars redraw with `\r`, so both line endings split.
+pub(crate) fn report_chunk_progress(task_id: TaskId, chunk: &[u8], parser: ProgressParser) {
+    let text = String::from_utf8_lossy(chunk);
+    let Some(percent) = text
+        .split(['\r', '\n'])
+        .rev()
+        .find_map(|line| (parser.0)(line.trim()))
+    else {
+        return;
+    };
+    match PROGRESS_SINK.get() {
+        Some(sink) => sink.report_progress(task_id, percent.min(100)),
+        None => task_progress_store::record(task_id, percent),
+    }
+}
+
+pub fn task_progress(task_id: TaskId) -> Option<u8> {
+    task_progress_store::get(task_id)
+}
+
 pub fn record_task_log_note(note: &str) {
     if let Some(task_id) = crate::task_context::current_task_id() {
         task_log_note_store::append(task_id, note);
@@ -567,6 +712,18 @@ pub fn drain_task_log_notes(task_id: TaskId) -> Vec<String> {
     task_log_note_store::drain(task_id)
 }
 
+/// Flag the current task's detection as inconclusive: a version probe errored
+/// for a reason other than the executable being absent.
+pub fn record_detection_probe_failure(kind: CoreErrorKind) {
+    if let Some(task_id) = crate::task_context::current_task_id() {
+        detection_probe_store::record(task_id, kind);
+    }
+}
+
+pub fn take_detection_probe_failure(task_id: TaskId) -> Option<CoreErrorKind> {
+    detection_probe_store::take(task_id)
+}
+
 fn invalid_input(
     manager: ManagerId,
     task_type: TaskType,
@@ -1073,6 +1230,42 @@ mod tests {
         clear_manager_timeout_profiles();
     }
 
+    #[test]
+    fn spawn_validated_prefers_manager_qos_over_global_default() {
+        let _lock = execution_test_lock()
+            .lock()
+            .expect("execution test lock poisoned");
+        set_default_process_qos(ProcessQos::Utility);
+        set_manager_process_qos(ManagerId::HomebrewFormula, Some(ProcessQos::Background));
+
+        let spawn_qos = |manager| {
+            let executor = CapturingExecutor::default();
+            let request = ProcessSpawnRequest::new(
+                manager,
+                TaskType::Upgrade,
+                ManagerAction::Upgrade,
+                CommandSpec::new("/bin/echo"),
+            );
+            let _ = spawn_validated(&executor, request).expect("spawn should succeed");
+            executor
+                .request
+                .lock()
+                .expect("capture lock poisoned")
+                .as_ref()
+                .expect("expected captured request")
+                .qos
+        };
+        assert_eq!(
+            spawn_qos(ManagerId::HomebrewFormula),
+            ProcessQos::Background
+        );
+        assert_eq!(spawn_qos(ManagerId::Npm), ProcessQos::Utility);
+
+        set_manager_process_qos(ManagerId::HomebrewFormula, None);
+        assert_eq!(spawn_qos(ManagerId::HomebrewFormula), ProcessQos::Utility);
+        set_default_process_qos(ProcessQos::Default);
+    }
+
     #[test]
     fn replace_manager_execution_preferences_avoids_empty_read_window() {
         let _lock = execution_test_lock()
@@ -1109,7 +1302,11 @@ mod tests {
             },
         );
 
-        replace_manager_execution_preferences(executable_map_a.clone(), timeout_map_a.clone());
+        replace_manager_execution_preferences(
+            executable_map_a.clone(),
+            timeout_map_a.clone(),
+            HashMap::new(),
+        );
 
         let stop_reader = Arc::new(AtomicBool::new(false));
         let observed_empty = Arc::new(AtomicBool::new(false));
@@ -1128,8 +1325,16 @@ mod tests {
         });
 
         for _ in 0..500 {
-            replace_manager_execution_preferences(executable_map_b.clone(), timeout_map_b.clone());
-            replace_manager_execution_preferences(executable_map_a.clone(), timeout_map_a.clone());
+            replace_manager_execution_preferences(
+                executable_map_b.clone(),
+                timeout_map_b.clone(),
+                HashMap::new(),
+            );
+            replace_manager_execution_preferences(
+                executable_map_a.clone(),
+                timeout_map_a.clone(),
+                HashMap::new(),
+            );
         }
 
         stop_reader.store(true, Ordering::Relaxed);
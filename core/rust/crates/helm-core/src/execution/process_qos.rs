@@ -0,0 +1,167 @@
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, CoreErrorKind};
+
+static DEFAULT_PROCESS_QOS: OnceLock<RwLock<ProcessQos>> = OnceLock::new();
+
+/// Scheduling priority for spawned manager processes.
+///
+/// Lowered classes trade wall-clock time for a responsive Mac during long
+/// upgrades. Children inherit the class, so build steps spawned by a manager
+/// (compilers, `make`, `node-gyp`) run at the same priority.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessQos {
+    /// Inherit Helm's own priority.
+    #[default]
+    Default,
+    /// `nice` 5 with throttled disk I/O; macOS utility I/O policy.
+    Utility,
+    /// `nice` 15 with idle disk I/O; macOS background priority band.
+    Background,
+}
+
+impl ProcessQos {
+    pub const ALL: [ProcessQos; 3] = [
+        ProcessQos::Default,
+        ProcessQos::Utility,
+        ProcessQos::Background,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessQos::Default => "default",
+            ProcessQos::Utility => "utility",
+            ProcessQos::Background => "background",
+        }
+    }
+
+    /// Absolute nice value applied to the child, if any.
+    pub fn nice_value(self) -> Option<i32> {
+        match self {
+            ProcessQos::Default => None,
+            ProcessQos::Utility => Some(5),
+            ProcessQos::Background => Some(15),
+        }
+    }
+}
+
+impl FromStr for ProcessQos {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ProcessQos::ALL
+            .into_iter()
+            .find(|qos| qos.as_str() == value.trim())
+            .ok_or_else(|| CoreError {
+                manager: None,
+                task: None,
+                action: None,
+                kind: CoreErrorKind::InvalidInput,
+                message: format!(
+                    "unknown process QoS '{}' (expected: default, utility, background)",
+                    value.trim()
+                ),
+            })
+    }
+}
+
+/// Set the QoS used for managers without their own override.
+pub fn set_default_process_qos(qos: ProcessQos) {
+    let slot = DEFAULT_PROCESS_QOS.get_or_init(|| RwLock::new(ProcessQos::default()));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = qos;
+}
+
+pub fn default_process_qos() -> ProcessQos {
+    DEFAULT_PROCESS_QOS
+        .get()
+        .map(|slot| *slot.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+const PRIO_DARWIN_PROCESS: libc::c_int = 4;
+#[cfg(target_os = "macos")]
+const PRIO_DARWIN_BG: libc::c_int = 0x1000;
+#[cfg(target_os = "macos")]
+const IOPOL_TYPE_DISK: libc::c_int = 0;
+#[cfg(target_os = "macos")]
+const IOPOL_SCOPE_PROCESS: libc::c_int = 0;
+#[cfg(target_os = "macos")]
+const IOPOL_UTILITY: libc::c_int = 4;
+
+#[cfg(target_os = "macos")]
+unsafe extern "C" {
+    fn setiopolicy_np(iotype: libc::c_int, scope: libc::c_int, policy: libc::c_int) -> libc::c_int;
+}
+
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_BE: libc::c_long = 2;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+
+/// Lower the calling process to `qos`. Runs in the forked child before
+/// `exec`, so it only makes async-signal-safe calls. Failures are ignored:
+/// a child at normal priority is better than a failed spawn.
+#[cfg(unix)]
+pub(crate) fn apply_to_current_process(qos: ProcessQos) {
+    let Some(nice) = qos.nice_value() else {
+        return;
+    };
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe {
+        match qos {
+            // The background band also throttles disk and network I/O.
+            ProcessQos::Background => {
+                libc::setpriority(PRIO_DARWIN_PROCESS, 0, PRIO_DARWIN_BG);
+            }
+            ProcessQos::Utility => {
+                setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_PROCESS, IOPOL_UTILITY);
+            }
+            ProcessQos::Default => {}
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let ioprio = match qos {
+            ProcessQos::Background => IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+            ProcessQos::Utility => (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | 7,
+            ProcessQos::Default => return,
+        };
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_qos_roundtrips_through_wire_names() {
+        for qos in ProcessQos::ALL {
+            assert_eq!(qos.as_str().parse::<ProcessQos>().unwrap(), qos);
+            assert_eq!(
+                serde_json::to_value(qos).unwrap(),
+                serde_json::Value::String(qos.as_str().to_string())
+            );
+        }
+        assert!("realtime".parse::<ProcessQos>().is_err());
+        assert_eq!(ProcessQos::Default.nice_value(), None);
+        assert!(ProcessQos::Background.nice_value() > ProcessQos::Utility.nice_value());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -13,6 +13,11 @@ const MAX_PATH_SNIPPET_BYTES: usize = 8 * 1024;
 const MAX_ERROR_CODE_BYTES: usize = 256;
 const MAX_ERROR_MESSAGE_BYTES: usize = 16 * 1024;
 const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+/// Bytes of live chunks kept per task; older chunks are dropped first.
+const MAX_LIVE_CHUNK_BYTES: usize = MAX_STREAM_BYTES;
+/// A partial line is held back until its newline arrives so redaction sees
+/// whole lines, unless it grows past this many bytes.
+const MAX_PENDING_LINE_BYTES: usize = 16 * 1024;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct TaskOutputRecord {
@@ -20,6 +25,7 @@ pub struct TaskOutputRecord {
     pub cwd: Option<String>,
     pub program_path: Option<String>,
     pub path_snippet: Option<String>,
+    pub process_qos: Option<String>,
     pub started_at_unix_ms: Option<i64>,
     pub finished_at_unix_ms: Option<i64>,
     pub duration_ms: Option<u64>,
@@ -31,12 +37,63 @@ pub struct TaskOutputRecord {
     pub stderr: Option<String>,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TaskOutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl TaskOutputStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+        }
+    }
+}
+
+/// One piece of live process output. Sequence numbers start at 1 and
+/// increase by one per chunk across both streams of a task.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaskOutputChunk {
+    pub sequence: u64,
+    pub stream: TaskOutputStream,
+    pub text: String,
+}
+
+/// Live output chunks newer than a caller's offset.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TaskOutputChunks {
+    pub chunks: Vec<TaskOutputChunk>,
+    /// Offset to pass on the next call: the last sequence seen so far.
+    pub next_offset: u64,
+    /// Chunks after the requested offset were dropped to bound memory; the
+    /// full tail is still available from [`get`].
+    pub missed: bool,
+    /// The process has exited and no further chunks will arrive.
+    pub finished: bool,
+}
+
+#[derive(Debug, Default)]
+struct LiveOutput {
+    chunks: VecDeque<TaskOutputChunk>,
+    retained_bytes: usize,
+    last_sequence: u64,
+    pending_stdout: Vec<u8>,
+    pending_stderr: Vec<u8>,
+}
+
 static TASK_OUTPUTS: OnceLock<Mutex<BTreeMap<u64, TaskOutputRecord>>> = OnceLock::new();
+static TASK_OUTPUT_CHUNKS: OnceLock<Mutex<BTreeMap<u64, LiveOutput>>> = OnceLock::new();
 
 fn task_outputs() -> &'static Mutex<BTreeMap<u64, TaskOutputRecord>> {
     TASK_OUTPUTS.get_or_init(|| Mutex::new(BTreeMap::new()))
 }
 
+fn task_output_chunks() -> &'static Mutex<BTreeMap<u64, LiveOutput>> {
+    TASK_OUTPUT_CHUNKS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
 fn normalize_stream(bytes: &[u8]) -> Option<String> {
     if bytes.is_empty() {
         return None;
@@ -183,6 +240,15 @@ fn redact_sensitive_pair_token(token: &str) -> Option<String> {
     Some(format!("{key}{delimiter}{REDACTED_PLACEHOLDER}"))
 }
 
+/// Value of environment variable `key` with secrets replaced, judged by name
+/// the same way `KEY=value` pairs in command output are.
+pub(crate) fn redact_sensitive_env_value(key: &str, value: &str) -> String {
+    match redact_sensitive_pair_token(&format!("{key}={value}")) {
+        Some(_) => REDACTED_PLACEHOLDER.to_string(),
+        None => value.to_string(),
+    }
+}
+
 fn redact_auth_header_line(line: &str) -> Option<String> {
     let trimmed = line.trim_start();
     let lowercase = trimmed.to_ascii_lowercase();
@@ -198,7 +264,7 @@ fn redact_auth_header_line(line: &str) -> Option<String> {
     Some(format!("{indent}{header_name}: {REDACTED_PLACEHOLDER}"))
 }
 
-fn redact_sensitive_text(value: &str) -> String {
+pub(crate) fn redact_sensitive_text(value: &str) -> String {
     let line_redacted = value
         .lines()
         .map(|line| redact_auth_header_line(line).unwrap_or_else(|| line.to_string()))
@@ -346,6 +412,12 @@ pub fn record_process_context(
     }
 }
 
+pub fn record_process_qos(task_id: TaskId, qos: &str) {
+    if let Ok(mut outputs) = task_outputs().lock() {
+        ensure_entry(&mut outputs, task_id).process_qos = Some(qos.to_string());
+    }
+}
+
 pub fn record_started_at(task_id: TaskId, started_at: SystemTime) {
     if let Ok(mut outputs) = task_outputs().lock() {
         let entry = ensure_entry(&mut outputs, task_id);
@@ -396,11 +468,85 @@ pub fn record_error(
     }
 }
 
+impl LiveOutput {
+    fn pending(&mut self, stream: TaskOutputStream) -> &mut Vec<u8> {
+        match stream {
+            TaskOutputStream::Stdout => &mut self.pending_stdout,
+            TaskOutputStream::Stderr => &mut self.pending_stderr,
+        }
+    }
+
+    /// Buffer `bytes` and emit every complete line as a chunk. A partial line
+    /// longer than `MAX_PENDING_LINE_BYTES` is emitted up to its last whole
+    /// UTF-8 character.
+    fn append(&mut self, stream: TaskOutputStream, bytes: &[u8]) {
+        let pending = self.pending(stream);
+        pending.extend_from_slice(bytes);
+        let emit_len = match pending.iter().rposition(|byte| *byte == b'\n') {
+            Some(newline) => newline + 1,
+            None if pending.len() > MAX_PENDING_LINE_BYTES => utf8_prefix_len(pending),
+            None => return,
+        };
+        let emitted = pending.drain(..emit_len).collect::<Vec<_>>();
+        self.push_chunk(stream, &emitted);
+    }
+
+    fn flush(&mut self, stream: TaskOutputStream) {
+        let emitted = std::mem::take(self.pending(stream));
+        self.push_chunk(stream, &emitted);
+    }
+
+    fn push_chunk(&mut self, stream: TaskOutputStream, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let text = redact_sensitive_text(String::from_utf8_lossy(bytes).as_ref());
+        self.last_sequence += 1;
+        self.retained_bytes += text.len();
+        self.chunks.push_back(TaskOutputChunk {
+            sequence: self.last_sequence,
+            stream,
+            text,
+        });
+        while self.retained_bytes > MAX_LIVE_CHUNK_BYTES && self.chunks.len() > 1 {
+            if let Some(dropped) = self.chunks.pop_front() {
+                self.retained_bytes -= dropped.text.len();
+            }
+        }
+    }
+}
+
+/// Length of the longest prefix of `bytes` that does not end inside a
+/// multi-byte UTF-8 character.
+fn utf8_prefix_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(error) if error.error_len().is_none() => error.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+fn append_live_chunk(task_id: TaskId, stream: TaskOutputStream, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    if let Ok(mut live) = task_output_chunks().lock() {
+        if !live.contains_key(&task_id.0)
+            && live.len() >= MAX_TASK_OUTPUT_RECORDS
+            && let Some(oldest_task_id) = live.keys().next().copied()
+        {
+            live.remove(&oldest_task_id);
+        }
+        live.entry(task_id.0).or_default().append(stream, bytes);
+    }
+}
+
 pub fn append_stdout(task_id: TaskId, chunk: &[u8]) {
     if let Ok(mut outputs) = task_outputs().lock() {
         let entry = ensure_entry(&mut outputs, task_id);
         append_stream(&mut entry.stdout, chunk);
     }
+    append_live_chunk(task_id, TaskOutputStream::Stdout, chunk);
 }
 
 pub fn append_stderr(task_id: TaskId, chunk: &[u8]) {
@@ -408,12 +554,83 @@ pub fn append_stderr(task_id: TaskId, chunk: &[u8]) {
         let entry = ensure_entry(&mut outputs, task_id);
         append_stream(&mut entry.stderr, chunk);
     }
+    append_live_chunk(task_id, TaskOutputStream::Stderr, chunk);
+}
+
+/// Emit any partial trailing line of `stream` as a final chunk. Call once the
+/// stream has closed.
+pub fn finish_stream(task_id: TaskId, stream: TaskOutputStream) {
+    if let Ok(mut live) = task_output_chunks().lock()
+        && let Some(entry) = live.get_mut(&task_id.0)
+    {
+        entry.flush(stream);
+    }
+}
+
+/// Live output chunks with a sequence greater than `offset` (`0` returns
+/// every retained chunk).
+pub fn get_since(task_id: TaskId, offset: u64) -> TaskOutputChunks {
+    let finished = get(task_id).is_some_and(|record| record.finished_at_unix_ms.is_some());
+    let Ok(live) = task_output_chunks().lock() else {
+        return TaskOutputChunks {
+            next_offset: offset,
+            finished,
+            ..TaskOutputChunks::default()
+        };
+    };
+    let Some(entry) = live.get(&task_id.0) else {
+        return TaskOutputChunks {
+            next_offset: offset,
+            finished,
+            ..TaskOutputChunks::default()
+        };
+    };
+
+    let chunks = entry
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.sequence > offset)
+        .cloned()
+        .collect::<Vec<_>>();
+    let missed = entry
+        .chunks
+        .front()
+        .is_some_and(|oldest| oldest.sequence > offset.saturating_add(1));
+    TaskOutputChunks {
+        next_offset: entry.last_sequence.max(offset),
+        missed,
+        finished,
+        chunks,
+    }
 }
 
 pub fn get(task_id: TaskId) -> Option<TaskOutputRecord> {
     task_outputs().lock().ok()?.get(&task_id.0).cloned()
 }
 
+pub fn get_many(task_ids: &[TaskId]) -> Vec<(TaskId, Option<TaskOutputRecord>)> {
+    let Ok(outputs) = task_outputs().lock() else {
+        return task_ids.iter().map(|task_id| (*task_id, None)).collect();
+    };
+    task_ids
+        .iter()
+        .map(|task_id| (*task_id, outputs.get(&task_id.0).cloned()))
+        .collect()
+}
+
+/// Keep only the trailing `max_bytes` of a captured stream.
+///
+/// Returns the (possibly shortened) text and whether any bytes were dropped.
+pub fn tail_stream(value: Option<String>, max_bytes: usize) -> (Option<String>, bool) {
+    match value {
+        Some(text) if text.len() > max_bytes => (
+            Some(truncate_str_to_tail_bytes(text.as_str(), max_bytes).to_string()),
+            true,
+        ),
+        other => (other, false),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::{Duration, UNIX_EPOCH};
@@ -421,9 +638,10 @@ mod tests {
     use std::sync::{Mutex, OnceLock};
 
     use super::{
-        MAX_STREAM_BYTES, MAX_TASK_OUTPUT_RECORDS, append_stderr, append_stdout, get, record,
-        record_command, record_context, record_error, record_process_context, record_started_at,
-        record_terminal_metadata, task_outputs,
+        MAX_STREAM_BYTES, MAX_TASK_OUTPUT_RECORDS, TaskOutputStream, append_stderr, append_stdout,
+        finish_stream, get, get_many, get_since, record, record_command, record_context,
+        record_error, record_process_context, record_started_at, record_terminal_metadata,
+        tail_stream, task_output_chunks, task_outputs,
     };
     use crate::models::TaskId;
 
@@ -440,6 +658,9 @@ mod tests {
         if let Ok(mut outputs) = task_outputs().lock() {
             outputs.clear();
         }
+        if let Ok(mut live) = task_output_chunks().lock() {
+            live.clear();
+        }
     }
 
     #[test]
@@ -561,6 +782,67 @@ mod tests {
         assert_eq!(output.stdout.as_deref(), Some("first\nsecond\n"));
     }
 
+    #[test]
+    fn live_chunks_are_sequenced_by_line_across_streams() {
+        let _guard = acquire_test_lock();
+        clear_store();
+        let task_id = TaskId(9008);
+        append_stdout(task_id, b"==> Upgrading ri");
+        assert!(get_since(task_id, 0).chunks.is_empty());
+        append_stdout(task_id, b"pgrep\n==> Pouring");
+        append_stderr(task_id, b"Warning: password=hunter2\n");
+
+        let first = get_since(task_id, 0);
+        assert_eq!(first.chunks.len(), 2);
+        assert_eq!(first.chunks[0].sequence, 1);
+        assert_eq!(first.chunks[0].stream, TaskOutputStream::Stdout);
+        assert_eq!(first.chunks[0].text, "==> Upgrading ripgrep\n");
+        assert_eq!(first.chunks[1].stream, TaskOutputStream::Stderr);
+        assert_eq!(first.chunks[1].text, "Warning: password=[REDACTED]\n");
+        assert_eq!(first.next_offset, 2);
+        assert!(!first.missed);
+        assert!(!first.finished);
+
+        finish_stream(task_id, TaskOutputStream::Stdout);
+        record_terminal_metadata(task_id, UNIX_EPOCH, UNIX_EPOCH, Some(0), None);
+        let rest = get_since(task_id, first.next_offset);
+        assert_eq!(rest.chunks.len(), 1);
+        assert_eq!(rest.chunks[0].sequence, 3);
+        assert_eq!(rest.chunks[0].text, "==> Pouring");
+        assert!(rest.finished);
+        assert!(get_since(task_id, rest.next_offset).chunks.is_empty());
+    }
+
+    #[test]
+    fn live_chunks_report_dropped_history() {
+        let _guard = acquire_test_lock();
+        clear_store();
+        let task_id = TaskId(9009);
+        let line = format!("{}\n", "x".repeat(1023));
+        for _ in 0..(MAX_STREAM_BYTES / line.len() + 8) {
+            append_stdout(task_id, line.as_bytes());
+        }
+
+        let tail = get_since(task_id, 0);
+        assert!(tail.missed);
+        assert!(tail.chunks.len() <= MAX_STREAM_BYTES / line.len());
+        assert_eq!(
+            tail.chunks.last().map(|chunk| chunk.sequence),
+            Some(tail.next_offset)
+        );
+        assert!(!get_since(task_id, tail.next_offset - 1).missed);
+    }
+
+    #[test]
+    fn get_since_unknown_task_keeps_the_offset() {
+        let _guard = acquire_test_lock();
+        clear_store();
+        let since = get_since(TaskId(9010), 7);
+        assert!(since.chunks.is_empty());
+        assert_eq!(since.next_offset, 7);
+        assert!(!since.finished);
+    }
+
     #[test]
     fn append_stderr_is_truncated_to_tail_window() {
         let _guard = acquire_test_lock();
@@ -668,4 +950,44 @@ mod tests {
             "newest record should be retained"
         );
     }
+
+    #[test]
+    fn get_many_preserves_request_order_and_reports_missing_tasks() {
+        let _guard = acquire_test_lock();
+        clear_store();
+        record(TaskId(9101), None, b"first\n", b"");
+        record(TaskId(9103), None, b"third\n", b"");
+
+        let outputs = get_many(&[TaskId(9103), TaskId(9102), TaskId(9101)]);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[0].0, TaskId(9103));
+        assert_eq!(
+            outputs[0]
+                .1
+                .as_ref()
+                .and_then(|entry| entry.stdout.as_deref()),
+            Some("third\n")
+        );
+        assert_eq!(outputs[1], (TaskId(9102), None));
+        assert_eq!(
+            outputs[2]
+                .1
+                .as_ref()
+                .and_then(|entry| entry.stdout.as_deref()),
+            Some("first\n")
+        );
+    }
+
+    #[test]
+    fn tail_stream_keeps_trailing_bytes_on_char_boundary() {
+        let (short, short_truncated) = tail_stream(Some("abc".to_string()), 8);
+        assert_eq!(short.as_deref(), Some("abc"));
+        assert!(!short_truncated);
+
+        let (tail, truncated) = tail_stream(Some("xxé-end".to_string()), 5);
+        assert_eq!(tail.as_deref(), Some("-end"));
+        assert!(truncated);
+
+        assert_eq!(tail_stream(None, 4), (None, false));
+    }
 }
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::TaskId;
+
+const MAX_PROCESS_RECORDS: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskProcessRecord {
+    pub task_id: TaskId,
+    pub pid: u32,
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: Option<u64>,
+    pub sampled_at_unix_ms: Option<i64>,
+}
+
+static TASK_PROCESSES: OnceLock<Mutex<BTreeMap<u64, TaskProcessRecord>>> = OnceLock::new();
+
+fn task_processes() -> &'static Mutex<BTreeMap<u64, TaskProcessRecord>> {
+    TASK_PROCESSES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn system_time_to_unix_ms(value: SystemTime) -> i64 {
+    value.duration_since(UNIX_EPOCH).map_or(0_i64, |duration| {
+        i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+    })
+}
+
+/// Clears the process record for a task when dropped, so every exit path of a
+/// process wait (success, failure, timeout, cancellation) releases the entry.
+pub struct TaskProcessRegistration {
+    task_id: TaskId,
+}
+
+impl Drop for TaskProcessRegistration {
+    fn drop(&mut self) {
+        clear(self.task_id);
+    }
+}
+
+pub fn record_pid(task_id: TaskId, pid: u32) -> TaskProcessRegistration {
+    if let Ok(mut processes) = task_processes().lock() {
+        if !processes.contains_key(&task_id.0)
+            && processes.len() >= MAX_PROCESS_RECORDS
+            && let Some(oldest_task_id) = processes.keys().next().copied()
+        {
+            processes.remove(&oldest_task_id);
+        }
+        processes.insert(
+            task_id.0,
+            TaskProcessRecord {
+                task_id,
+                pid,
+                cpu_percent: None,
+                rss_bytes: None,
+                sampled_at_unix_ms: None,
+            },
+        );
+    }
+    TaskProcessRegistration { task_id }
+}
+
+pub fn record_usage(task_id: TaskId, cpu_percent: Option<f64>, rss_bytes: Option<u64>) {
+    if let Ok(mut processes) = task_processes().lock()
+        && let Some(record) = processes.get_mut(&task_id.0)
+    {
+        if cpu_percent.is_some() {
+            record.cpu_percent = cpu_percent;
+        }
+        if rss_bytes.is_some() {
+            record.rss_bytes = rss_bytes;
+        }
+        record.sampled_at_unix_ms = Some(system_time_to_unix_ms(SystemTime::now()));
+    }
+}
+
+pub fn get(task_id: TaskId) -> Option<TaskProcessRecord> {
+    task_processes()
+        .lock()
+        .ok()
+        .and_then(|processes| processes.get(&task_id.0).cloned())
+}
+
+pub fn clear(task_id: TaskId) {
+    if let Ok(mut processes) = task_processes().lock() {
+        processes.remove(&task_id.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, record_pid, record_usage};
+    use crate::models::TaskId;
+
+    #[test]
+    fn record_pid_and_usage_until_registration_drops() {
+        let task_id = TaskId(778_001);
+        let registration = record_pid(task_id, 4242);
+
+        let recorded = get(task_id).expect("process record should exist");
+        assert_eq!(recorded.pid, 4242);
+        assert_eq!(recorded.cpu_percent, None);
+        assert_eq!(recorded.sampled_at_unix_ms, None);
+
+        record_usage(task_id, None, Some(8 * 1024 * 1024));
+        record_usage(task_id, Some(37.5), None);
+        let sampled = get(task_id).expect("process record should exist");
+        assert_eq!(sampled.cpu_percent, Some(37.5));
+        assert_eq!(sampled.rss_bytes, Some(8 * 1024 * 1024));
+        assert!(sampled.sampled_at_unix_ms.is_some_and(|value| value > 0));
+
+        drop(registration);
+        assert!(get(task_id).is_none());
+    }
+
+    #[test]
+    fn record_usage_ignores_unknown_tasks() {
+        let task_id = TaskId(778_002);
+        record_usage(task_id, Some(10.0), Some(1024));
+        assert!(get(task_id).is_none());
+    }
+}
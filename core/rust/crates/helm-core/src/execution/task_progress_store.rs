@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::models::TaskId;
+
+const MAX_PROGRESS_RECORDS: usize = 256;
+
+static TASK_PROGRESS: OnceLock<Mutex<BTreeMap<u64, u8>>> = OnceLock::new();
+
+fn task_progress() -> &'static Mutex<BTreeMap<u64, u8>> {
+    TASK_PROGRESS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Remember the latest completion percentage for `task_id`, capped at 100.
+pub fn record(task_id: TaskId, percent: u8) {
+    if let Ok(mut progress) = task_progress().lock() {
+        if !progress.contains_key(&task_id.0)
+            && progress.len() >= MAX_PROGRESS_RECORDS
+            && let Some(oldest_task_id) = progress.keys().next().copied()
+        {
+            progress.remove(&oldest_task_id);
+        }
+        progress.insert(task_id.0, percent.min(100));
+    }
+}
+
+pub fn get(task_id: TaskId) -> Option<u8> {
+    task_progress()
+        .lock()
+        .ok()
+        .and_then(|progress| progress.get(&task_id.0).copied())
+}
+
+pub fn clear(task_id: TaskId) {
+    if let Ok(mut progress) = task_progress().lock() {
+        progress.remove(&task_id.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear, get, record};
+    use crate::models::TaskId;
+
+    #[test]
+    fn latest_progress_is_kept_until_cleared() {
+        let task_id = TaskId(93001);
+        record(task_id, 12);
+        record(task_id, 250);
+        assert_eq!(get(task_id), Some(100));
+        clear(task_id);
+        assert_eq!(get(task_id), None);
+    }
+}
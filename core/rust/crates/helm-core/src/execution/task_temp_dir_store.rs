@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use crate::models::TaskId;
+
+const TASK_DIR_PREFIX: &str = "task-";
+
+/// Leftover task directories younger than this are kept by the sweep: another
+/// Helm process (the CLI next to the service) may still be using them.
+pub const TASK_TEMP_DIR_SWEEP_MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+static TEMP_ROOT: OnceLock<RwLock<Option<PathBuf>>> = OnceLock::new();
+static TASK_TEMP_DIRS: OnceLock<Mutex<BTreeMap<u64, PathBuf>>> = OnceLock::new();
+
+fn temp_root_slot() -> &'static RwLock<Option<PathBuf>> {
+    TEMP_ROOT.get_or_init(|| RwLock::new(None))
+}
+
+fn task_temp_dirs() -> &'static Mutex<BTreeMap<u64, PathBuf>> {
+    TASK_TEMP_DIRS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Put task scratch directories under `root`, normally `tmp` in Helm's cache
+/// directory. Until a host sets one, the system temp directory is used.
+pub fn set_temp_root(root: PathBuf) {
+    if let Ok(mut slot) = temp_root_slot().write() {
+        *slot = Some(root);
+    }
+}
+
+/// `~/Library/Caches/Helm/tmp`, the root hosts pass to [`set_temp_root`].
+pub fn default_temp_root() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").filter(|home| !home.is_empty())?;
+    Some(
+        PathBuf::from(home)
+            .join("Library")
+            .join("Caches")
+            .join("Helm")
+            .join("tmp"),
+    )
+}
+
+pub fn temp_root() -> PathBuf {
+    temp_root_slot()
+        .read()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .unwrap_or_else(|| std::env::temp_dir().join("helm"))
+}
+
+/// The scratch directory for `task_id`, created (private to the user) on first
+/// use. It is removed when the task reaches a terminal state.
+pub fn get_or_create(task_id: TaskId) -> io::Result<PathBuf> {
+    let mut dirs = task_temp_dirs()
+        .lock()
+        .map_err(|_| io::Error::other("task temp dir registry poisoned"))?;
+    if let Some(path) = dirs.get(&task_id.0)
+        && path.is_dir()
+    {
+        return Ok(path.clone());
+    }
+    let path = create_in(&temp_root(), task_id)?;
+    dirs.insert(task_id.0, path.clone());
+    Ok(path)
+}
+
+/// Delete the scratch directory of `task_id`, if it made one.
+pub fn remove(task_id: TaskId) {
+    let path = task_temp_dirs()
+        .lock()
+        .ok()
+        .and_then(|mut dirs| dirs.remove(&task_id.0));
+    if let Some(path) = path {
+        remove_dir(&path);
+    }
+}
+
+/// Delete task directories under the temp root that no live task in this
+/// process owns and that are older than [`TASK_TEMP_DIR_SWEEP_MIN_AGE`], such
+/// as those left by a crash. Returns how many were removed.
+pub fn sweep() -> usize {
+    let owned = task_temp_dirs()
+        .lock()
+        .map(|dirs| dirs.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    sweep_in(
+        &temp_root(),
+        &owned,
+        TASK_TEMP_DIR_SWEEP_MIN_AGE,
+        SystemTime::now(),
+    )
+}
+
+fn create_in(root: &Path, task_id: TaskId) -> io::Result<PathBuf> {
+    let path = root.join(format!("{TASK_DIR_PREFIX}{}", task_id.0));
+    // A directory already here was left by an earlier run that reused the id.
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(root)?;
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(&path)?;
+    Ok(path)
+}
+
+fn sweep_in(root: &Path, owned: &[PathBuf], min_age: Duration, now: SystemTime) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_task_dir = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(TASK_DIR_PREFIX));
+        if !is_task_dir || owned.contains(&path) {
+            continue;
+        }
+        let old_enough = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map(|modified| now.duration_since(modified).unwrap_or_default() >= min_age)
+            .unwrap_or(false);
+        if old_enough && fs::remove_dir_all(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+fn remove_dir(path: &Path) {
+    if let Err(error) = fs::remove_dir_all(path)
+        && error.kind() != io::ErrorKind::NotFound
+    {
+        tracing::warn!(
+            path = %path.display(),
+            error = %error,
+            "failed to remove task temp dir"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{create_in, sweep_in};
+    use crate::models::TaskId;
+
+    fn scratch_root(name: &str) -> std::path::PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("helm-task-temp-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn create_replaces_leftovers_from_a_reused_task_id() {
+        let root = scratch_root("create");
+        let path = create_in(&root, TaskId(41)).unwrap();
+        std::fs::write(path.join("installer.sh"), "echo stale").unwrap();
+
+        let path = create_in(&root, TaskId(41)).unwrap();
+        assert!(path.ends_with("task-41"));
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn sweep_keeps_owned_and_recent_dirs() {
+        let root = scratch_root("sweep");
+        let owned = create_in(&root, TaskId(1)).unwrap();
+        let orphaned = create_in(&root, TaskId(2)).unwrap();
+        std::fs::create_dir_all(root.join("unrelated")).unwrap();
+
+        let now = SystemTime::now();
+        let min_age = Duration::from_secs(60);
+        assert_eq!(
+            sweep_in(&root, std::slice::from_ref(&owned), min_age, now),
+            0
+        );
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(
+            sweep_in(&root, std::slice::from_ref(&owned), min_age, later),
+            1
+        );
+        assert!(owned.is_dir());
+        assert!(!orphaned.exists());
+        assert!(root.join("unrelated").is_dir());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -9,8 +9,8 @@ use std::time::{Duration, SystemTime};
 use tokio::io::AsyncReadExt;
 
 use crate::execution::{
-    CommandSpec, ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput,
-    ProcessSpawnRequest, ProcessTerminationMode, ProcessWaitFuture, RunningProcess,
+    CommandSpec, ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessQos,
+    ProcessSpawnRequest, ProcessTerminationMode, ProcessWaitFuture, ProgressParser, RunningProcess,
 };
 use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskId, TaskType};
 
@@ -42,6 +42,24 @@ impl ProcessExecutor for TokioProcessExecutor {
                 Some(program_path.as_str()),
                 path_snippet.as_deref(),
             );
+            if request.qos != ProcessQos::Default {
+                crate::execution::task_output_store::record_process_qos(
+                    task_id,
+                    request.qos.as_str(),
+                );
+                crate::execution::task_log_note_store::append(
+                    task_id,
+                    format!(
+                        "Helm process QoS: {} (nice {}) for {}",
+                        request.qos.as_str(),
+                        request.qos.nice_value().unwrap_or_default(),
+                        crate::execution::task_output_store::redact_sensitive_text(
+                            command_display.as_str()
+                        )
+                    )
+                    .as_str(),
+                );
+            }
         }
 
         let mut cmd = tokio::process::Command::new(&prepared.command.program);
@@ -59,6 +77,17 @@ impl ProcessExecutor for TokioProcessExecutor {
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
         cmd.process_group(0);
+        let qos = request.qos;
+        if qos != ProcessQos::Default {
+            // SAFETY: the hook runs between fork and exec and only makes
+            // async-signal-safe priority syscalls.
+            unsafe {
+                cmd.pre_exec(move || {
+                    crate::execution::process_qos::apply_to_current_process(qos);
+                    Ok(())
+                });
+            }
+        }
 
         let child = cmd.spawn().map_err(|error| {
             let message = append_error_context(
@@ -84,6 +113,15 @@ impl ProcessExecutor for TokioProcessExecutor {
         if let Some(task_id) = task_id {
             crate::execution::task_output_store::record_started_at(task_id, started_at);
         }
+        let process_registration = task_id
+            .zip(pid)
+            .map(|(task_id, pid)| crate::execution::task_process_store::record_pid(task_id, pid));
+        if let Some(task_id) = task_id
+            && request.progress_parser.is_some()
+        {
+            // A previous command of the same task must not leave its percentage behind.
+            crate::execution::task_progress_store::clear(task_id);
+        }
 
         Ok(Box::new(TokioRunningProcess {
             child: Mutex::new(Some(child)),
@@ -98,6 +136,8 @@ impl ProcessExecutor for TokioProcessExecutor {
             command_display,
             program_path,
             path_snippet,
+            process_registration,
+            progress_parser: request.progress_parser,
         }))
     }
 }
@@ -168,6 +208,31 @@ fn prepare_command_for_spawn(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
+fn record_command_timing(
+    manager: ManagerId,
+    action: ManagerAction,
+    task_id: Option<TaskId>,
+    command_display: &str,
+    started_at: SystemTime,
+    finished_at: SystemTime,
+    exit_code: Option<i32>,
+    timed_out: bool,
+) {
+    crate::execution::command_timing::record_command_timing(
+        crate::execution::command_timing::CommandTimingSample {
+            manager,
+            action,
+            task_id,
+            command: crate::execution::task_output_store::redact_sensitive_text(command_display),
+            started_at,
+            duration: finished_at.duration_since(started_at).unwrap_or_default(),
+            exit_code,
+            timed_out,
+        },
+    );
+}
+
 fn process_context_details(command: &CommandSpec) -> (String, Option<String>) {
     let program_path = command.program.to_string_lossy().to_string();
     let path_snippet = command
@@ -425,14 +490,22 @@ struct TokioRunningProcess {
     command_display: String,
     program_path: String,
     path_snippet: Option<String>,
+    process_registration: Option<crate::execution::task_process_store::TaskProcessRegistration>,
+    progress_parser: Option<ProgressParser>,
 }
 
 struct ProcessCpuProgressProbe {
     pid: Option<u32>,
+    task_id: Option<TaskId>,
     last_sampled_total_cpu: Option<Duration>,
     last_sampled_at: Option<tokio::time::Instant>,
 }
 
+struct ProcessResourceSample {
+    total_cpu: Duration,
+    rss_bytes: Option<u64>,
+}
+
 struct HardTimeoutState {
     base_timeout: Duration,
     deadline: tokio::time::Instant,
@@ -559,10 +632,15 @@ fn read_task_hard_timeout_grace_extension(task_type: TaskType, hard_timeout: Dur
 }
 
 impl ProcessCpuProgressProbe {
-    fn new(pid: Option<u32>, now: tokio::time::Instant) -> Self {
+    fn new(pid: Option<u32>, task_id: Option<TaskId>, now: tokio::time::Instant) -> Self {
+        let sample = pid.and_then(sample_process_resources);
+        if let (Some(task_id), Some(sample)) = (task_id, sample.as_ref()) {
+            crate::execution::task_process_store::record_usage(task_id, None, sample.rss_bytes);
+        }
         Self {
             pid,
-            last_sampled_total_cpu: pid.and_then(sample_process_total_cpu_time),
+            task_id,
+            last_sampled_total_cpu: sample.map(|sample| sample.total_cpu),
             last_sampled_at: Some(now),
         }
     }
@@ -571,15 +649,30 @@ impl ProcessCpuProgressProbe {
         let Some(pid) = self.pid else {
             return false;
         };
-        if let Some(last_sampled_at) = self.last_sampled_at
+        let previous_sampled_at = self.last_sampled_at;
+        if let Some(last_sampled_at) = previous_sampled_at
             && now.duration_since(last_sampled_at) < CPU_PROGRESS_SAMPLE_INTERVAL
         {
             return false;
         }
         self.last_sampled_at = Some(now);
-        let Some(total_cpu) = sample_process_total_cpu_time(pid) else {
+        let Some(sample) = sample_process_resources(pid) else {
             return false;
         };
+        let total_cpu = sample.total_cpu;
+        if let Some(task_id) = self.task_id {
+            let cpu_percent = self
+                .last_sampled_total_cpu
+                .zip(previous_sampled_at)
+                .and_then(|(previous_cpu, previous_at)| {
+                    cpu_percent_between(previous_cpu, total_cpu, now.duration_since(previous_at))
+                });
+            crate::execution::task_process_store::record_usage(
+                task_id,
+                cpu_percent,
+                sample.rss_bytes,
+            );
+        }
         let has_progress = self
             .last_sampled_total_cpu
             .is_some_and(|previous| total_cpu > previous);
@@ -588,6 +681,18 @@ impl ProcessCpuProgressProbe {
     }
 }
 
+fn cpu_percent_between(
+    previous_cpu: Duration,
+    current_cpu: Duration,
+    elapsed: Duration,
+) -> Option<f64> {
+    if elapsed.is_zero() {
+        return None;
+    }
+    let cpu_delta = current_cpu.saturating_sub(previous_cpu);
+    Some(cpu_delta.as_secs_f64() / elapsed.as_secs_f64() * 100.0)
+}
+
 #[cfg(target_os = "macos")]
 #[repr(C)]
 struct RusageInfoV2 {
@@ -598,7 +703,7 @@ struct RusageInfoV2 {
     _ri_interrupt_wkups: u64,
     _ri_pageins: u64,
     _ri_wired_size: u64,
-    _ri_resident_size: u64,
+    ri_resident_size: u64,
     _ri_phys_footprint: u64,
     _ri_proc_start_abstime: u64,
     _ri_proc_exit_abstime: u64,
@@ -622,7 +727,7 @@ unsafe extern "C" {
 }
 
 #[cfg(target_os = "macos")]
-fn sample_process_total_cpu_time(pid: u32) -> Option<Duration> {
+fn sample_process_resources(pid: u32) -> Option<ProcessResourceSample> {
     let mut info = MaybeUninit::<RusageInfoV2>::zeroed();
     let result = unsafe {
         proc_pid_rusage(
@@ -640,11 +745,14 @@ fn sample_process_total_cpu_time(pid: u32) -> Option<Duration> {
         .saturating_add(info.ri_system_time)
         .saturating_add(info.ri_child_user_time)
         .saturating_add(info.ri_child_system_time);
-    Some(Duration::from_nanos(total_nanos))
+    Some(ProcessResourceSample {
+        total_cpu: Duration::from_nanos(total_nanos),
+        rss_bytes: Some(info.ri_resident_size),
+    })
 }
 
 #[cfg(target_os = "linux")]
-fn sample_process_total_cpu_time(pid: u32) -> Option<Duration> {
+fn sample_process_resources(pid: u32) -> Option<ProcessResourceSample> {
     let stat_path = format!("/proc/{pid}/stat");
     let stat_raw = fs::read_to_string(stat_path).ok()?;
     let close_paren_index = stat_raw.rfind(')')?;
@@ -680,11 +788,20 @@ fn sample_process_total_cpu_time(pid: u32) -> Option<Duration> {
     let total_nanos = ticks
         .saturating_mul(1_000_000_000)
         .checked_div(ticks_per_second)?;
-    Some(Duration::from_nanos(total_nanos))
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let rss_bytes = stat_fields
+        .get(21)
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|_| page_size > 0)
+        .map(|pages| pages.saturating_mul(page_size as u64));
+    Some(ProcessResourceSample {
+        total_cpu: Duration::from_nanos(total_nanos),
+        rss_bytes,
+    })
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn sample_process_total_cpu_time(_pid: u32) -> Option<Duration> {
+fn sample_process_resources(_pid: u32) -> Option<ProcessResourceSample> {
     None
 }
 
@@ -734,8 +851,11 @@ impl RunningProcess for TokioRunningProcess {
         let command_display = self.command_display;
         let program_path = self.program_path;
         let path_snippet = self.path_snippet;
+        let process_registration = self.process_registration;
+        let progress_parser = self.progress_parser;
 
         Box::pin(async move {
+            let _process_registration = process_registration;
             let mut child = child.ok_or_else(|| {
                 let message = "child process already consumed".to_string();
                 if let Some(task_id) = task_id {
@@ -778,12 +898,23 @@ impl RunningProcess for TokioRunningProcess {
                                         crate::execution::task_output_store::append_stdout(
                                             task_id, bytes,
                                         );
+                                        if let Some(parser) = progress_parser {
+                                            crate::execution::report_chunk_progress(
+                                                task_id, bytes, parser,
+                                            );
+                                        }
                                     }
                                 }
                                 Err(_) => break,
                             }
                         }
                     }
+                    if let Some(task_id) = stream_task_id {
+                        crate::execution::task_output_store::finish_stream(
+                            task_id,
+                            crate::execution::TaskOutputStream::Stdout,
+                        );
+                    }
                     buffer
                 })
             };
@@ -806,12 +937,23 @@ impl RunningProcess for TokioRunningProcess {
                                         crate::execution::task_output_store::append_stderr(
                                             task_id, bytes,
                                         );
+                                        if let Some(parser) = progress_parser {
+                                            crate::execution::report_chunk_progress(
+                                                task_id, bytes, parser,
+                                            );
+                                        }
                                     }
                                 }
                                 Err(_) => break,
                             }
                         }
                     }
+                    if let Some(task_id) = stream_task_id {
+                        crate::execution::task_output_store::finish_stream(
+                            task_id,
+                            crate::execution::TaskOutputStream::Stderr,
+                        );
+                    }
                     buffer
                 })
             };
@@ -841,7 +983,7 @@ impl RunningProcess for TokioRunningProcess {
             let started_instant = tokio::time::Instant::now();
             let mut last_activity_instant = started_instant;
             let mut last_output_activity_instant = started_instant;
-            let mut cpu_probe = ProcessCpuProgressProbe::new(pid, started_instant);
+            let mut cpu_probe = ProcessCpuProgressProbe::new(pid, task_id, started_instant);
             let mut hard_timeout_state = HardTimeoutState::new(timeout, task_type, started_instant);
             let mut hard_timeout_prompt_started_at: Option<tokio::time::Instant> = None;
             let mut hard_timeout_grace_applied = false;
@@ -1135,6 +1277,16 @@ impl RunningProcess for TokioRunningProcess {
                     reap_or_detach_timed_out_child(child, pid, manager, task_type, action, task_id)
                         .await;
                     let finished_at = SystemTime::now();
+                    record_command_timing(
+                        manager,
+                        action,
+                        task_id,
+                        command_display.as_str(),
+                        started_at,
+                        finished_at,
+                        None,
+                        true,
+                    );
                     let message = append_error_context(
                         timeout_reason.as_str(),
                         program_path.as_str(),
@@ -1142,6 +1294,12 @@ impl RunningProcess for TokioRunningProcess {
                     );
                     if let Some(task_id) = task_id {
                         crate::execution::timeout_prompt_store::clear_prompt(task_id);
+                        for stream in [
+                            crate::execution::TaskOutputStream::Stdout,
+                            crate::execution::TaskOutputStream::Stderr,
+                        ] {
+                            crate::execution::task_output_store::finish_stream(task_id, stream);
+                        }
                         crate::execution::task_output_store::record_terminal_metadata(
                             task_id,
                             started_at,
@@ -1262,6 +1420,17 @@ impl RunningProcess for TokioRunningProcess {
                 ),
             };
 
+            record_command_timing(
+                manager,
+                action,
+                task_id,
+                command_display.as_str(),
+                started_at,
+                finished_at,
+                exit_code,
+                false,
+            );
+
             if let Some(task_id) = task_id {
                 crate::execution::task_output_store::record_terminal_metadata(
                     task_id,
@@ -1449,10 +1618,10 @@ fn process_failure(
 #[cfg(test)]
 mod tests {
     use super::{
-        HELM_SUDO_ASKPASS_ALLOW_OVERRIDE_ENV, HELM_SUDO_ASKPASS_ENV, hard_timeout_activity_window,
-        hard_timeout_extension_budget, prepare_command_for_spawn,
+        HELM_SUDO_ASKPASS_ALLOW_OVERRIDE_ENV, HELM_SUDO_ASKPASS_ENV, cpu_percent_between,
+        hard_timeout_activity_window, hard_timeout_extension_budget, prepare_command_for_spawn,
         read_task_hard_timeout_grace_extension, resolve_effective_working_dir,
-        supports_read_task_timeout_grace,
+        sample_process_resources, supports_read_task_timeout_grace,
     };
     use crate::execution::{CommandSpec, ProcessSpawnRequest};
     use crate::models::{ManagerAction, ManagerId, TaskType};
@@ -1733,4 +1902,36 @@ mod tests {
             Duration::ZERO
         );
     }
+
+    #[test]
+    fn cpu_percent_between_scales_cpu_delta_by_wall_time() {
+        assert_eq!(
+            cpu_percent_between(
+                Duration::from_millis(500),
+                Duration::from_millis(1_000),
+                Duration::from_secs(1)
+            ),
+            Some(50.0)
+        );
+        assert_eq!(
+            cpu_percent_between(
+                Duration::ZERO,
+                Duration::from_secs(2),
+                Duration::from_secs(1)
+            ),
+            Some(200.0)
+        );
+        assert_eq!(
+            cpu_percent_between(Duration::ZERO, Duration::from_secs(1), Duration::ZERO),
+            None
+        );
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn sample_process_resources_reports_rss_for_current_process() {
+        let sample = sample_process_resources(std::process::id())
+            .expect("current process should be sampleable");
+        assert!(sample.rss_bytes.is_some_and(|bytes| bytes > 0));
+    }
 }
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::{HelmManifest, ManifestPackageEntry};
+use crate::models::{CoreError, CoreErrorKind, InstalledPackage, ManagerId};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Homebrew Bundle `Brewfile` with formulae, casks, and Mac App Store apps.
+    Brewfile,
+    /// pip `requirements.txt` pinned to installed versions.
+    Requirements,
+    /// `package.json` whose dependencies are the global npm packages.
+    PackageJson,
+    /// One `cargo install` command per installed crate.
+    CargoInstall,
+    /// One `pipx install` command per pipx-managed application.
+    PipxInstall,
+    /// Every installed package as a [`HelmManifest`].
+    HelmJson,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 6] = [
+        ExportFormat::Brewfile,
+        ExportFormat::Requirements,
+        ExportFormat::PackageJson,
+        ExportFormat::CargoInstall,
+        ExportFormat::PipxInstall,
+        ExportFormat::HelmJson,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::Brewfile => "brewfile",
+            ExportFormat::Requirements => "requirements",
+            ExportFormat::PackageJson => "package_json",
+            ExportFormat::CargoInstall => "cargo_install",
+            ExportFormat::PipxInstall => "pipx_install",
+            ExportFormat::HelmJson => "helm_json",
+        }
+    }
+
+    /// Conventional file name for the exported contents.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ExportFormat::Brewfile => "Brewfile",
+            ExportFormat::Requirements => "requirements.txt",
+            ExportFormat::PackageJson => "package.json",
+            ExportFormat::CargoInstall => "cargo-install.sh",
+            ExportFormat::PipxInstall => "pipx-install.sh",
+            ExportFormat::HelmJson => "helm-manifest.json",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == value.trim())
+            .ok_or_else(|| CoreError {
+                manager: None,
+                task: None,
+                action: None,
+                kind: CoreErrorKind::InvalidInput,
+                message: format!("unknown export format '{}'", value.trim()),
+            })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedManifest {
+    pub format: ExportFormat,
+    pub file_name: String,
+    pub contents: String,
+    pub package_count: usize,
+}
+
+/// Serialize the installed-package snapshot in `format`.
+///
+/// Each format only covers the managers it can describe; entries are sorted by
+/// name so repeated exports of the same machine diff cleanly.
+pub fn export_installed(format: ExportFormat, installed: &[InstalledPackage]) -> ExportedManifest {
+    let (contents, package_count) = match format {
+        ExportFormat::Brewfile => render_brewfile(installed),
+        ExportFormat::Requirements => render_requirements(installed),
+        ExportFormat::PackageJson => render_package_json(installed),
+        ExportFormat::CargoInstall => render_cargo_install(installed),
+        ExportFormat::PipxInstall => render_pipx_install(installed),
+        ExportFormat::HelmJson => render_helm_json(installed),
+    };
+    ExportedManifest {
+        format,
+        file_name: format.file_name().to_string(),
+        contents,
+        package_count,
+    }
+}
+
+fn packages_for(installed: &[InstalledPackage], manager: ManagerId) -> Vec<&InstalledPackage> {
+    let mut packages: Vec<&InstalledPackage> = installed
+        .iter()
+        .filter(|package| package.package.manager == manager)
+        .collect();
+    packages.sort_by(|left, right| left.package.name.cmp(&right.package.name));
+    packages.dedup_by(|left, right| left.package.name == right.package.name);
+    packages
+}
+
+fn ruby_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_brewfile(installed: &[InstalledPackage]) -> (String, usize) {
+    let formulae = packages_for(installed, ManagerId::HomebrewFormula);
+    let casks = packages_for(installed, ManagerId::HomebrewCask);
+    let apps: Vec<&InstalledPackage> = packages_for(installed, ManagerId::Mas)
+        .into_iter()
+        .filter(|package| package.package_identifier.is_some())
+        .collect();
+
+    // Tap-qualified formulae (`user/repo/name`) need their tap first.
+    let mut taps: Vec<&str> = formulae
+        .iter()
+        .chain(casks.iter())
+        .filter_map(|package| {
+            let name = package.package.name.as_str();
+            let (tap, _) = name.rsplit_once('/')?;
+            tap.contains('/').then_some(tap)
+        })
+        .collect();
+    taps.sort_unstable();
+    taps.dedup();
+
+    let mut lines: Vec<String> = Vec::new();
+    lines.extend(taps.iter().map(|tap| format!("tap {}", ruby_string(tap))));
+    lines.extend(
+        formulae
+            .iter()
+            .map(|package| format!("brew {}", ruby_string(&package.package.name))),
+    );
+    lines.extend(
+        casks
+            .iter()
+            .map(|package| format!("cask {}", ruby_string(&package.package.name))),
+    );
+    lines.extend(apps.iter().filter_map(|package| {
+        let app_id = package.package_identifier.as_deref()?;
+        Some(format!(
+            "mas {}, id: {app_id}",
+            ruby_string(&package.package.name)
+        ))
+    }));
+
+    let count = formulae.len() + casks.len() + apps.len();
+    (join_lines(lines), count)
+}
+
+fn render_requirements(installed: &[InstalledPackage]) -> (String, usize) {
+    let packages = packages_for(installed, ManagerId::Pip);
+    let lines: Vec<String> = packages
+        .iter()
+        .map(|package| match package.installed_version.as_deref() {
+            Some(version) => format!("{}=={version}", package.package.name),
+            None => package.package.name.clone(),
+        })
+        .collect();
+    (join_lines(lines), packages.len())
+}
+
+fn render_package_json(installed: &[InstalledPackage]) -> (String, usize) {
+    let packages = packages_for(installed, ManagerId::Npm);
+    let dependencies: BTreeMap<&str, &str> = packages
+        .iter()
+        .map(|package| {
+            (
+                package.package.name.as_str(),
+                package.installed_version.as_deref().unwrap_or("*"),
+            )
+        })
+        .collect();
+    let document = serde_json::json!({
+        "name": "helm-global-packages",
+        "private": true,
+        "dependencies": dependencies,
+    });
+    let contents = serde_json::to_string_pretty(&document).unwrap_or_default();
+    (format!("{contents}\n"), packages.len())
+}
+
+fn render_cargo_install(installed: &[InstalledPackage]) -> (String, usize) {
+    let packages = packages_for(installed, ManagerId::Cargo);
+    let lines: Vec<String> = packages
+        .iter()
+        .map(|package| match package.installed_version.as_deref() {
+            Some(version) => format!(
+                "cargo install --locked {} --version {version}",
+                package.package.name
+            ),
+            None => format!("cargo install --locked {}", package.package.name),
+        })
+        .collect();
+    (join_lines(lines), packages.len())
+}
+
+fn render_pipx_install(installed: &[InstalledPackage]) -> (String, usize) {
+    let packages = packages_for(installed, ManagerId::Pipx);
+    let lines: Vec<String> = packages
+        .iter()
+        .map(|package| match package.installed_version.as_deref() {
+            Some(version) => format!("pipx install {}=={version}", package.package.name),
+            None => format!("pipx install {}", package.package.name),
+        })
+        .collect();
+    (join_lines(lines), packages.len())
+}
+
+fn render_helm_json(installed: &[InstalledPackage]) -> (String, usize) {
+    let mut packages: Vec<ManifestPackageEntry> = installed
+        .iter()
+        .map(|package| ManifestPackageEntry {
+            manager: package.package.manager,
+            name: package.package.name.clone(),
+            target_name: package.package_identifier.clone(),
+            version: package.installed_version.clone(),
+        })
+        .collect();
+    packages.sort_by(|left, right| {
+        (left.manager.as_str(), left.name.as_str())
+            .cmp(&(right.manager.as_str(), right.name.as_str()))
+    });
+    packages.dedup_by(|left, right| left.manager == right.manager && left.name == right.name);
+    let count = packages.len();
+    let manifest = HelmManifest {
+        packages,
+        homebrew_taps: Vec::new(),
+    };
+    let contents = serde_json::to_string_pretty(&manifest).unwrap_or_default();
+    (format!("{contents}\n"), count)
+}
+
+fn join_lines(lines: Vec<String>) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::installed;
+
+    fn snapshot() -> Vec<InstalledPackage> {
+        vec![
+            installed(ManagerId::HomebrewFormula, "git", Some("2.47.0")),
+            installed(
+                ManagerId::HomebrewFormula,
+                "hashicorp/tap/terraform",
+                Some("1.9.8"),
+            ),
+            installed(ManagerId::HomebrewCask, "firefox", Some("131.0")),
+            InstalledPackage {
+                package_identifier: Some("497799835".to_string()),
+                ..installed(ManagerId::Mas, "Xcode", Some("16.0"))
+            },
+            installed(ManagerId::Pip, "requests", Some("2.32.3")),
+            installed(ManagerId::Pip, "black", None),
+            installed(ManagerId::Npm, "typescript", Some("5.6.3")),
+            installed(ManagerId::Npm, "@angular/cli", Some("18.2.0")),
+            installed(ManagerId::Cargo, "ripgrep", Some("14.1.1")),
+        ]
+    }
+
+    #[test]
+    fn brewfile_lists_taps_before_formulae_casks_and_apps() {
+        let exported = export_installed(ExportFormat::Brewfile, &snapshot());
+        assert_eq!(exported.file_name, "Brewfile");
+        assert_eq!(exported.package_count, 4);
+        assert_eq!(
+            exported.contents,
+            "tap \"hashicorp/tap\"\n\
+             brew \"git\"\n\
+             brew \"hashicorp/tap/terraform\"\n\
+             cask \"firefox\"\n\
+             mas \"Xcode\", id: 497799835\n"
+        );
+    }
+
+    #[test]
+    fn language_formats_pin_installed_versions() {
+        let packages = snapshot();
+
+        let requirements = export_installed(ExportFormat::Requirements, &packages);
+        assert_eq!(requirements.contents, "black\nrequests==2.32.3\n");
+
+        let package_json = export_installed(ExportFormat::PackageJson, &packages);
+        let document: serde_json::Value =
+            serde_json::from_str(&package_json.contents).expect("valid json");
+        assert_eq!(
+            document["dependencies"],
+            serde_json::json!({"@angular/cli": "18.2.0", "typescript": "5.6.3"})
+        );
+
+        let cargo = export_installed(ExportFormat::CargoInstall, &packages);
+        assert_eq!(
+            cargo.contents,
+            "cargo install --locked ripgrep --version 14.1.1\n"
+        );
+
+        let pipx = export_installed(
+            ExportFormat::PipxInstall,
+            &[
+                installed(ManagerId::Pipx, "poetry", Some("1.8.4")),
+                installed(ManagerId::Pipx, "black", None),
+            ],
+        );
+        assert_eq!(pipx.file_name, "pipx-install.sh");
+        assert_eq!(
+            pipx.contents,
+            "pipx install black\npipx install poetry==1.8.4\n"
+        );
+    }
+
+    #[test]
+    fn helm_json_round_trips_as_a_manifest() {
+        let exported = export_installed(ExportFormat::HelmJson, &snapshot());
+        let manifest: HelmManifest =
+            serde_json::from_str(&exported.contents).expect("manifest json");
+        assert_eq!(manifest.packages.len(), 9);
+        assert_eq!(exported.package_count, 9);
+        assert_eq!(manifest.packages[0].manager, ManagerId::Cargo);
+        let xcode = manifest
+            .packages
+            .iter()
+            .find(|entry| entry.name == "Xcode")
+            .expect("mas entry");
+        assert_eq!(xcode.target_name.as_deref(), Some("497799835"));
+    }
+
+    #[test]
+    fn formats_parse_from_their_names() {
+        for format in ExportFormat::ALL {
+            assert_eq!(format.as_str().parse::<ExportFormat>().unwrap(), format);
+        }
+        let error = "yaml".parse::<ExportFormat>().unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,153 @@
+use serde::Serialize;
+
+use crate::models::{ManagerId, OutdatedPackage, PinKind, PinRecord};
+use crate::versioning::VersionConstraint;
+
+/// Why an outdated package is left out of bulk upgrades.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeldBackReason {
+    /// Pinned by the manager itself (`brew pin`, cask version hold, ...).
+    NativePin,
+    /// Pinned only in Helm's own pin records.
+    VirtualPin,
+    /// The owning manager is disabled, so its updates are hidden entirely.
+    DisabledManager,
+}
+
+impl HeldBackReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NativePin => "native_pin",
+            Self::VirtualPin => "virtual_pin",
+            Self::DisabledManager => "disabled_manager",
+        }
+    }
+}
+
+/// An outdated package that upgrade listings and bulk upgrades skip.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeldBackPackage {
+    pub outdated: OutdatedPackage,
+    pub reason: HeldBackReason,
+}
+
+/// Reason `package` is held back, if any. A disabled manager outranks pins
+/// because re-enabling the manager is the first step to upgrading it.
+pub fn held_back_reason(
+    package: &OutdatedPackage,
+    pins: &[PinRecord],
+    manager_enabled: bool,
+) -> Option<HeldBackReason> {
+    if !manager_enabled {
+        return Some(HeldBackReason::DisabledManager);
+    }
+    // Mirrors the store's pin overlay: an unversioned pin holds every version,
+    // and a constrained pin only candidates outside its range.
+    let pin = pins.iter().find(|pin| {
+        pin.package == package.package
+            && (pin.pinned_version.is_none()
+                || pin.pinned_version.as_deref() == package.installed_version.as_deref())
+            && pin.version_constraint.as_deref().is_none_or(|constraint| {
+                !VersionConstraint::parse(constraint)
+                    .is_ok_and(|constraint| constraint.matches(&package.candidate_version))
+            })
+    });
+    match pin.map(|pin| pin.kind) {
+        Some(PinKind::Virtual) => Some(HeldBackReason::VirtualPin),
+        Some(PinKind::Native) => Some(HeldBackReason::NativePin),
+        None if package.pinned => Some(HeldBackReason::NativePin),
+        None => None,
+    }
+}
+
+/// Outdated packages excluded from upgrades, annotated with the reason, in
+/// the order they were given. Manager self-packages are never reported.
+pub fn held_back_packages(
+    outdated: Vec<OutdatedPackage>,
+    pins: &[PinRecord],
+    is_manager_enabled: impl Fn(ManagerId) -> bool,
+) -> Vec<HeldBackPackage> {
+    outdated
+        .into_iter()
+        .filter(|package| package.package.is_user_visible_package())
+        .filter_map(|package| {
+            let reason =
+                held_back_reason(&package, pins, is_manager_enabled(package.package.manager))?;
+            Some(HeldBackPackage {
+                outdated: package,
+                reason,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::{HeldBackReason, held_back_packages};
+    use crate::models::{ManagerId, OutdatedPackage, PackageRef, PinKind, PinRecord};
+    use crate::test_fixtures::outdated;
+
+    fn pin(manager: ManagerId, name: &str, kind: PinKind, version: Option<&str>) -> PinRecord {
+        PinRecord {
+            package: PackageRef {
+                manager,
+                name: name.to_string(),
+            },
+            kind,
+            pinned_version: version.map(str::to_string),
+            version_constraint: None,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn classifies_pins_and_disabled_managers() {
+        let packages = vec![
+            OutdatedPackage {
+                pinned: true,
+                ..outdated(ManagerId::HomebrewFormula, "git", Some("1.0.0"), "2.0.0")
+            },
+            outdated(ManagerId::Npm, "typescript", Some("1.0.0"), "2.0.0"),
+            outdated(ManagerId::Npm, "eslint", Some("1.0.0"), "2.0.0"),
+            outdated(ManagerId::Npm, "prettier", Some("1.0.0"), "2.0.0"),
+            outdated(ManagerId::Npm, "vite", Some("1.0.0"), "2.0.0"),
+            outdated(ManagerId::Npm, "react", Some("1.0.0"), "2.0.0"),
+            OutdatedPackage {
+                pinned: true,
+                ..outdated(ManagerId::Pip, "black", Some("1.0.0"), "2.0.0")
+            },
+            outdated(ManagerId::Pip, "__self__", Some("1.0.0"), "2.0.0"),
+        ];
+        let pins = vec![
+            pin(ManagerId::Npm, "typescript", PinKind::Virtual, None),
+            pin(ManagerId::Npm, "eslint", PinKind::Virtual, Some("0.9.0")),
+            PinRecord {
+                version_constraint: Some("^1.2".to_string()),
+                ..pin(ManagerId::Npm, "vite", PinKind::Virtual, None)
+            },
+            PinRecord {
+                version_constraint: Some(">=2.0 <3".to_string()),
+                ..pin(ManagerId::Npm, "react", PinKind::Virtual, None)
+            },
+        ];
+
+        let held = held_back_packages(packages, &pins, |manager| manager != ManagerId::Pip);
+        let summary = held
+            .iter()
+            .map(|held| (held.outdated.package.name.as_str(), held.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("git", HeldBackReason::NativePin),
+                ("typescript", HeldBackReason::VirtualPin),
+                ("vite", HeldBackReason::VirtualPin),
+                ("black", HeldBackReason::DisabledManager),
+            ]
+        );
+    }
+}
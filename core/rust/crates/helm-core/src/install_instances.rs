@@ -12,6 +12,7 @@ use tracing::{debug, warn};
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 
+use crate::base_dirs::ToolBaseDirs;
 use crate::models::{
     AutomationLevel, DetectionInfo, InstallInstanceIdentityKind, InstallProvenance, ManagerId,
     ManagerInstallInstance, StrategyKind,
@@ -33,27 +34,19 @@ struct ScoreFactor {
 }
 
 fn configured_asdf_root_paths() -> Vec<PathBuf> {
-    let mut roots = Vec::new();
-    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
-        roots.push(home.join(".asdf"));
-    }
-    if let Some(path) = std::env::var_os("ASDF_DIR").map(PathBuf::from)
-        && path.is_absolute()
-        && !path.as_os_str().is_empty()
-    {
-        roots.push(path);
-    }
-    if let Some(path) = std::env::var_os("ASDF_DATA_DIR").map(PathBuf::from)
-        && path.is_absolute()
-        && !path.as_os_str().is_empty()
-    {
-        roots.push(path);
-    }
+    let mut roots = ToolBaseDirs::from_env().asdf_roots();
     roots.sort();
-    roots.dedup();
     roots
 }
 
+fn configured_mise_root_paths() -> Vec<PathBuf> {
+    let base_dirs = ToolBaseDirs::from_env();
+    [base_dirs.mise_data_dir, base_dirs.rtx_data_dir]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
 fn path_contains_asdf_root_subpath(text: &str, suffix: &str) -> bool {
     text.contains("/.asdf/")
         || configured_asdf_root_paths().iter().any(|root| {
@@ -66,6 +59,19 @@ fn path_contains_asdf_root_subpath(text: &str, suffix: &str) -> bool {
         })
 }
 
+fn path_contains_mise_root_subpath(text: &str, suffix: &str) -> bool {
+    text.contains(format!("/.local/share/mise/{suffix}").as_str())
+        || text.contains(format!("/.local/share/rtx/{suffix}").as_str())
+        || configured_mise_root_paths().iter().any(|root| {
+            let needle = format!(
+                "{}/{}",
+                root.to_string_lossy().to_string().to_lowercase(),
+                suffix
+            );
+            text.contains(needle.as_str())
+        })
+}
+
 fn path_contains_asdf_shims(text: &str) -> bool {
     path_contains_asdf_root_subpath(text, "shims/")
 }
@@ -1287,18 +1293,11 @@ fn classify_runtime_manager_instance(
     add_score(
         &mut scores,
         &mut factors,
-        ((canonical.contains("/.local/share/mise/shims/")
-            || canonical.contains("/.local/share/rtx/shims/"))
-            && canonical_matches_exec)
-            || ((display.contains("/.local/share/mise/shims/")
-                || display.contains("/.local/share/rtx/shims/"))
-                && display_matches_exec)
-            || ((canonical.contains("/.local/share/mise/installs/")
-                || canonical.contains("/.local/share/rtx/installs/"))
+        (path_contains_mise_root_subpath(&canonical, "shims/") && canonical_matches_exec)
+            || (path_contains_mise_root_subpath(&display, "shims/") && display_matches_exec)
+            || (path_contains_mise_root_subpath(&canonical, "installs/")
                 && canonical_matches_bin_exec)
-            || ((display.contains("/.local/share/mise/installs/")
-                || display.contains("/.local/share/rtx/installs/"))
-                && display_matches_bin_exec),
+            || (path_contains_mise_root_subpath(&display, "installs/") && display_matches_bin_exec),
         InstallProvenance::Mise,
         0.92,
         format!(
@@ -1835,13 +1834,9 @@ fn classify_rustup_instance(
     add_score(
         &mut scores,
         &mut factors,
-        canonical_lower.contains("/.local/share/mise/shims/rustup")
-            || canonical_lower.contains("/.local/share/rtx/shims/rustup")
-            || display_lower.contains("/.local/share/mise/shims/rustup")
-            || display_lower.contains("/.local/share/rtx/shims/rustup")
-            || (canonical_lower.contains("/.local/share/mise/installs/")
-                && canonical_lower.ends_with("/bin/rustup"))
-            || (canonical_lower.contains("/.local/share/rtx/installs/")
+        path_contains_mise_root_subpath(&canonical_lower, "shims/rustup")
+            || path_contains_mise_root_subpath(&display_lower, "shims/rustup")
+            || (path_contains_mise_root_subpath(&canonical_lower, "installs/")
                 && canonical_lower.ends_with("/bin/rustup")),
         InstallProvenance::Mise,
         0.92,
@@ -2138,8 +2133,7 @@ fn is_custom_cargo_home_rustup_path(canonical_lower: &str, display_lower: &str)
         || canonical_lower.contains("/homebrew/")
         || canonical_lower.contains("/nix/store/")
         || path_contains_asdf_root_subpath(canonical_lower, "")
-        || canonical_lower.contains("/.local/share/mise/")
-        || canonical_lower.contains("/.local/share/rtx/");
+        || path_contains_mise_root_subpath(canonical_lower, "");
     looks_like_rustup_bin && cargo_home_style_path && !known_non_rustup_init_layout
 }
 
@@ -2428,18 +2422,11 @@ fn manager_additional_bin_roots() -> Vec<PathBuf> {
         PathBuf::from("/sbin"),
     ];
 
-    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
-        roots.push(home.join(".local/bin"));
-        roots.push(home.join(".cargo/bin"));
-        roots.push(home.join(".asdf/bin"));
-        roots.push(home.join(".asdf/shims"));
-        roots.push(home.join(".local/share/rtx/shims"));
+    let base_dirs = ToolBaseDirs::from_env();
+    roots.extend(base_dirs.tool_bin_roots());
+    if let Some(home) = &base_dirs.home {
         roots.push(home.join(".nix-profile/bin"));
     }
-    for root in configured_asdf_root_paths() {
-        roots.push(root.join("bin"));
-        roots.push(root.join("shims"));
-    }
 
     roots
 }
@@ -2485,14 +2472,8 @@ fn manager_versioned_install_roots(id: ManagerId) -> Vec<PathBuf> {
             | ManagerId::Bundler
             | ManagerId::Cargo
             | ManagerId::CargoBinstall
-    ) && let Some(home) = std::env::var_os("HOME").map(PathBuf::from)
-    {
-        roots.push(home.join(".asdf/installs"));
-        roots.push(home.join(".local/share/mise/installs"));
-        roots.push(home.join(".local/share/rtx/installs"));
-    }
-    for root in configured_asdf_root_paths() {
-        roots.push(root.join("installs"));
+    ) {
+        roots.extend(ToolBaseDirs::from_env().tool_version_install_roots());
     }
 
     roots
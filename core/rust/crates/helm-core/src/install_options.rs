@@ -0,0 +1,161 @@
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskType};
+
+pub const MAX_INSTALL_OPTIONS: usize = 8;
+
+const HOMEBREW_FORMULA_INSTALL_OPTIONS: &[&str] =
+    &["--HEAD", "--build-from-source", "--force-bottle"];
+const HOMEBREW_FORMULA_UPGRADE_OPTIONS: &[&str] = &["--build-from-source", "--force-bottle"];
+const HOMEBREW_CASK_INSTALL_OPTIONS: &[&str] = &["--no-quarantine", "--require-sha", "--adopt"];
+const HOMEBREW_CASK_UPGRADE_OPTIONS: &[&str] = &["--no-quarantine", "--require-sha"];
+
+/// Flags a manager accepts on install. Managers not listed accept none.
+pub fn allowed_install_options(manager: ManagerId) -> &'static [&'static str] {
+    match manager {
+        ManagerId::HomebrewFormula => HOMEBREW_FORMULA_INSTALL_OPTIONS,
+        ManagerId::HomebrewCask => HOMEBREW_CASK_INSTALL_OPTIONS,
+        _ => &[],
+    }
+}
+
+/// Subset of install flags that remain meaningful when the package is later upgraded.
+pub fn allowed_upgrade_options(manager: ManagerId) -> &'static [&'static str] {
+    match manager {
+        ManagerId::HomebrewFormula => HOMEBREW_FORMULA_UPGRADE_OPTIONS,
+        ManagerId::HomebrewCask => HOMEBREW_CASK_UPGRADE_OPTIONS,
+        _ => &[],
+    }
+}
+
+/// Validate install flags against the manager allowlist, returning them trimmed
+/// and de-duplicated in request order.
+pub fn validate_install_options(
+    manager: ManagerId,
+    options: &[String],
+) -> Result<Vec<String>, CoreError> {
+    validate_options(
+        manager,
+        options,
+        allowed_install_options(manager),
+        TaskType::Install,
+        ManagerAction::Install,
+    )
+}
+
+pub fn validate_upgrade_options(
+    manager: ManagerId,
+    options: &[String],
+) -> Result<Vec<String>, CoreError> {
+    validate_options(
+        manager,
+        options,
+        allowed_upgrade_options(manager),
+        TaskType::Upgrade,
+        ManagerAction::Upgrade,
+    )
+}
+
+/// Carry persisted install flags forward to an upgrade, dropping flags that only
+/// apply at install time (for example `--HEAD` or `--adopt`).
+pub fn upgrade_options_from_install(manager: ManagerId, install_options: &[String]) -> Vec<String> {
+    let allowed = allowed_upgrade_options(manager);
+    install_options
+        .iter()
+        .filter(|option| allowed.contains(&option.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn validate_options(
+    manager: ManagerId,
+    options: &[String],
+    allowed: &[&str],
+    task_type: TaskType,
+    action: ManagerAction,
+) -> Result<Vec<String>, CoreError> {
+    let invalid = |message: String| CoreError {
+        manager: Some(manager),
+        task: Some(task_type),
+        action: Some(action),
+        kind: CoreErrorKind::InvalidInput,
+        message,
+    };
+    if options.len() > MAX_INSTALL_OPTIONS {
+        return Err(invalid(format!(
+            "at most {MAX_INSTALL_OPTIONS} options may be supplied"
+        )));
+    }
+
+    let mut validated: Vec<String> = Vec::with_capacity(options.len());
+    for option in options {
+        let option = option.trim();
+        if !allowed.contains(&option) {
+            return Err(invalid(format!(
+                "option '{option}' is not supported for {}",
+                manager.as_str()
+            )));
+        }
+        if !validated.iter().any(|existing| existing == option) {
+            validated.push(option.to_string());
+        }
+    }
+    Ok(validated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_install_options_accepts_allowlisted_flags_and_dedupes() {
+        let validated = validate_install_options(
+            ManagerId::HomebrewFormula,
+            &strings(&[" --HEAD", "--build-from-source", "--HEAD"]),
+        )
+        .expect("allowlisted flags should validate");
+        assert_eq!(validated, strings(&["--HEAD", "--build-from-source"]));
+
+        let cask =
+            validate_install_options(ManagerId::HomebrewCask, &strings(&["--no-quarantine"]))
+                .expect("cask flag should validate");
+        assert_eq!(cask, strings(&["--no-quarantine"]));
+    }
+
+    #[test]
+    fn validate_install_options_rejects_unknown_or_unsupported_flags() {
+        let error =
+            validate_install_options(ManagerId::HomebrewFormula, &strings(&["--no-quarantine"]))
+                .expect_err("cask flag must be rejected for formulae");
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+
+        let error = validate_install_options(ManagerId::Npm, &strings(&["--HEAD"]))
+            .expect_err("npm has no install options");
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+
+        assert!(
+            validate_install_options(ManagerId::HomebrewFormula, &strings(&["--HEAD; rm -rf /"]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn upgrade_options_keep_only_upgrade_compatible_flags() {
+        assert_eq!(
+            upgrade_options_from_install(
+                ManagerId::HomebrewFormula,
+                &strings(&["--HEAD", "--build-from-source"])
+            ),
+            strings(&["--build-from-source"])
+        );
+        assert_eq!(
+            upgrade_options_from_install(
+                ManagerId::HomebrewCask,
+                &strings(&["--adopt", "--no-quarantine"])
+            ),
+            strings(&["--no-quarantine"])
+        );
+    }
+}
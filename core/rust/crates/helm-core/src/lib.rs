@@ -1,20 +1,56 @@
 pub mod adapters;
+pub mod base_dirs;
+pub mod completion;
+pub mod confirmation;
+pub mod diagnostics_redaction;
 pub mod doctor;
+pub mod duration_estimates;
 pub mod execution;
+pub mod export;
+pub mod held_back;
 pub(crate) mod install_instances;
+pub mod install_options;
 pub mod managed_automation_policy;
 pub mod manager_dependencies;
 pub mod manager_instances;
 pub mod manager_lifecycle;
 pub mod manager_policy;
+pub mod manager_self_update;
+pub mod manager_stats;
+pub mod manifest;
+pub mod manifest_plan;
+pub mod metrics_text;
 pub mod models;
+pub mod network_policy;
+pub mod npm_migration;
+pub mod onboarding;
 pub mod orchestration;
+pub mod package_aliases;
+pub mod package_dependencies;
+pub mod package_key;
 pub mod persistence;
+pub mod pin_enforcement;
 pub mod post_install_setup;
+pub mod prewarm;
+pub mod profiles;
 pub(crate) mod provenance_policy;
+pub mod reachability;
 pub mod registry;
 pub mod repair;
+pub mod scheduled_runs;
+pub mod search_popularity;
+pub mod settings;
+pub mod snapshot_cache;
+pub mod snapshot_reconciliation;
 pub mod sqlite;
 pub(crate) mod task_context;
+#[cfg(test)]
+mod test_fixtures;
 pub mod uninstall_preview;
+pub mod upgrade_simulation;
+pub mod upgrade_target;
 pub mod versioning;
+pub mod view_hint;
+
+/// Version of the `helm-core` crate this build was compiled from.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -24,9 +24,33 @@ pub fn provenance_requires_manager_dependency(
     provenance_dependency_manager(manager, provenance).is_some()
 }
 
+/// Managers whose packages run on a toolchain that `provider` installs and
+/// upgrades in place, e.g. npm globals living under a mise-managed node.
+pub fn toolchain_dependent_managers(provider: ManagerId) -> &'static [ManagerId] {
+    match provider {
+        ManagerId::Mise | ManagerId::Asdf => &[
+            ManagerId::Npm,
+            ManagerId::Pnpm,
+            ManagerId::Yarn,
+            ManagerId::Pip,
+            ManagerId::Pipx,
+            ManagerId::Poetry,
+            ManagerId::RubyGems,
+            ManagerId::Bundler,
+            ManagerId::Cargo,
+            ManagerId::CargoBinstall,
+        ],
+        ManagerId::Rustup => &[ManagerId::Cargo, ManagerId::CargoBinstall],
+        _ => &[],
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{provenance_dependency_manager, provenance_requires_manager_dependency};
+    use super::{
+        provenance_dependency_manager, provenance_requires_manager_dependency,
+        toolchain_dependent_managers,
+    };
     use crate::models::{InstallProvenance, ManagerId};
 
     #[test]
@@ -72,4 +96,16 @@ mod tests {
             InstallProvenance::RustupInit
         ));
     }
+
+    #[test]
+    fn toolchain_dependents_cover_language_managers_of_runtime_providers() {
+        assert!(toolchain_dependent_managers(ManagerId::Mise).contains(&ManagerId::Npm));
+        assert!(toolchain_dependent_managers(ManagerId::Asdf).contains(&ManagerId::Pip));
+        assert_eq!(
+            toolchain_dependent_managers(ManagerId::Rustup),
+            &[ManagerId::Cargo, ManagerId::CargoBinstall]
+        );
+        assert!(toolchain_dependent_managers(ManagerId::Npm).is_empty());
+        assert!(toolchain_dependent_managers(ManagerId::HomebrewFormula).is_empty());
+    }
 }
@@ -1,4 +1,6 @@
-use crate::adapters::{AdapterRequest, InstallRequest, UninstallRequest, UpgradeRequest};
+use crate::adapters::{
+    AdapterRequest, InstallRequest, UninstallRequest, UpgradeRequest, UpgradeTarget,
+};
 use crate::models::{
     InstallProvenance, ManagerId, ManagerInstallInstance, PackageRef, StrategyKind,
 };
@@ -109,6 +111,9 @@ pub struct ManagerUninstallOptions {
     pub mise_cleanup_mode: Option<MiseUninstallCleanupMode>,
     pub mise_config_removal: Option<MiseUninstallConfigRemoval>,
     pub remove_helm_managed_shell_setup: Option<bool>,
+    /// Explicit consent to remove manager data (tool versions, caches) when the
+    /// uninstall would also remove packages Helm still tracks for the manager.
+    pub confirm_manager_data_removal: bool,
 }
 
 const HOMEBREW_MANAGER_UNINSTALL_MARKER: &str = "@@helm.manager.uninstall::";
@@ -380,6 +385,7 @@ pub fn plan_manager_uninstall_route_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             MiseUninstallTarget::MacPortsPort => (
@@ -391,6 +397,7 @@ pub fn plan_manager_uninstall_route_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             MiseUninstallTarget::SelfManaged | MiseUninstallTarget::ReadOnly => {
@@ -405,6 +412,7 @@ pub fn plan_manager_uninstall_route_with_options(
                         },
                         target_name: None,
                         version: None,
+                        disposal: Default::default(),
                     }),
                 )
             }
@@ -433,6 +441,7 @@ pub fn plan_manager_uninstall_route_with_options(
                         },
                         target_name: None,
                         version: None,
+                        disposal: Default::default(),
                     }),
                     strategy: resolution.strategy,
                     unknown_override_required: resolution.unknown_override_required,
@@ -488,6 +497,7 @@ pub fn plan_manager_uninstall_route_with_options(
                         },
                         target_name: None,
                         version: None,
+                        disposal: Default::default(),
                     }),
                 )
             }
@@ -504,6 +514,7 @@ pub fn plan_manager_uninstall_route_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             _ => return Err(ManagerUninstallRouteError::AmbiguousProvenance),
@@ -551,6 +562,7 @@ pub fn plan_manager_uninstall_route_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             StrategyKind::AsdfSelf => (
@@ -566,6 +578,7 @@ pub fn plan_manager_uninstall_route_with_options(
                     },
                     target_name: None,
                     version: None,
+                    disposal: Default::default(),
                 }),
             ),
             StrategyKind::ReadOnly => {
@@ -625,6 +638,7 @@ pub fn plan_manager_uninstall_route_with_options(
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
             strategy: resolution.strategy,
             unknown_override_required: resolution.unknown_override_required,
@@ -665,6 +679,7 @@ pub fn plan_manager_uninstall_route_with_options(
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
             strategy: resolution.strategy,
             unknown_override_required: resolution.unknown_override_required,
@@ -689,6 +704,7 @@ fn read_only_uninstall_route_plan(
             },
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }),
         strategy: StrategyKind::ReadOnly,
         unknown_override_required,
@@ -706,6 +722,7 @@ fn homebrew_manager_install_plan(formula_name: &'static str) -> ManagerInstallPl
             },
             target_name: None,
             version: None,
+            options: Vec::new(),
         }),
         label_key: "service.task.label.install.homebrew_formula",
         label_args: vec![("package", formula_name.to_string())],
@@ -725,6 +742,7 @@ fn package_manager_install_plan(
             },
             target_name: None,
             version: None,
+            options: Vec::new(),
         }),
         label_key: "service.task.label.install.package",
         label_args: vec![
@@ -747,6 +765,7 @@ fn mise_manager_install_plan(
             },
             target_name: None,
             version,
+            options: Vec::new(),
         }),
         label_key: "service.task.label.install.package",
         label_args: vec![
@@ -769,6 +788,7 @@ fn rustup_manager_install_plan(
             },
             target_name: None,
             version,
+            options: Vec::new(),
         }),
         label_key: "service.task.label.install.package",
         label_args: vec![
@@ -788,6 +808,7 @@ fn asdf_manager_install_plan() -> ManagerInstallPlan {
             },
             target_name: None,
             version: Some("scriptInstaller:officialDownload".to_string()),
+            options: Vec::new(),
         }),
         label_key: "service.task.label.install.package",
         label_args: vec![
@@ -1064,47 +1085,28 @@ fn resolve_mise_uninstall_strategy(
 pub fn build_update_request(
     plan: &ManagerUpdatePlan,
     homebrew_package_name: Option<String>,
+    cleanup_old_kegs: bool,
 ) -> Option<AdapterRequest> {
     match (&plan.target, plan.target_manager) {
-        (ManagerUpdateTarget::ManagerSelf, ManagerId::HomebrewFormula) => {
-            Some(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::HomebrewFormula,
-                    name: "__self__".to_string(),
-                }),
-                target_name: None,
-                version: None,
-            }))
-        }
-        (ManagerUpdateTarget::ManagerSelf, ManagerId::Rustup) => {
-            Some(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Rustup,
-                    name: "__self__".to_string(),
-                }),
-                target_name: None,
-                version: None,
-            }))
-        }
-        (ManagerUpdateTarget::ManagerSelf, ManagerId::Mise) => {
-            Some(AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Mise,
-                    name: "__self__".to_string(),
-                }),
-                target_name: None,
-                version: None,
-            }))
-        }
+        // Every adapter that plans a self-update handles `UpgradeTarget::SelfUpdate`.
+        (ManagerUpdateTarget::ManagerSelf, _) => Some(AdapterRequest::Upgrade(UpgradeRequest {
+            target: UpgradeTarget::SelfUpdate,
+            target_name: None,
+            version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
+        })),
         (ManagerUpdateTarget::HomebrewFormula { .. }, ManagerId::HomebrewFormula) => {
             homebrew_package_name.map(|package_name| {
                 AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
+                    target: UpgradeTarget::Package(PackageRef {
                         manager: ManagerId::HomebrewFormula,
                         name: package_name,
                     }),
                     target_name: None,
                     version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs,
                 })
             })
         }
@@ -1112,6 +1114,24 @@ pub fn build_update_request(
     }
 }
 
+/// Extend a manager self-update plan into an update-then-upgrade-all flow.
+///
+/// Only Homebrew runs both steps as one task today; other plans return `None`.
+pub fn build_update_then_upgrade_all_request(plan: &ManagerUpdatePlan) -> Option<AdapterRequest> {
+    match (&plan.target, plan.target_manager) {
+        (ManagerUpdateTarget::ManagerSelf, ManagerId::HomebrewFormula) => {
+            Some(AdapterRequest::Upgrade(UpgradeRequest {
+                target: UpgradeTarget::SelfUpdateThenAll,
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            }))
+        }
+        _ => None,
+    }
+}
+
 pub fn manager_homebrew_formula_name(manager: ManagerId) -> Option<&'static str> {
     match manager {
         ManagerId::Asdf => Some("asdf"),
@@ -1410,19 +1430,22 @@ fn asdf_instance_path_looks_homebrew(instance: &ManagerInstallInstance) -> bool
 mod tests {
     use super::{
         HomebrewUninstallCleanupMode, ManagerInstallOptions, ManagerInstallPlanError,
-        ManagerUninstallOptions, ManagerUninstallRouteError, MiseInstallSource,
-        MiseUninstallCleanupMode, MiseUninstallConfigRemoval, RustupInstallSource,
-        UpdateStrategyResolutionError, encode_homebrew_manager_uninstall_package_name,
+        ManagerUninstallOptions, ManagerUninstallRouteError, ManagerUpdateTarget,
+        MiseInstallSource, MiseUninstallCleanupMode, MiseUninstallConfigRemoval,
+        RustupInstallSource, UpdateStrategyResolutionError, build_update_request,
+        build_update_then_upgrade_all_request, encode_homebrew_manager_uninstall_package_name,
         encode_homebrew_manager_uninstall_package_name_with_options, manager_homebrew_formula_name,
         manager_supported_install_methods, parse_homebrew_manager_uninstall_package_name,
-        plan_manager_install, plan_manager_uninstall_route_with_options,
+        plan_manager_install, plan_manager_uninstall_route_with_options, plan_manager_update,
         resolve_asdf_update_strategy, resolve_homebrew_manager_update_strategy,
         resolve_rustup_uninstall_strategy,
     };
+    use crate::adapters::AdapterRequest;
     use crate::models::{
         AutomationLevel, InstallInstanceIdentityKind, InstallProvenance, ManagerId,
         ManagerInstallInstance, StrategyKind,
     };
+    use crate::upgrade_target::UpgradeTarget;
     use std::path::PathBuf;
 
     fn sample_instance() -> ManagerInstallInstance {
@@ -1746,6 +1769,35 @@ mod tests {
         assert_eq!(strategy, StrategyKind::AsdfSelf);
     }
 
+    #[test]
+    fn manager_self_update_plans_build_self_upgrade_request_for_any_manager() {
+        let mut instance = sample_instance();
+        instance.manager = ManagerId::Asdf;
+        instance.update_strategy = StrategyKind::AsdfSelf;
+        let plan = plan_manager_update(ManagerId::Asdf, Some(&instance))
+            .expect("asdf self update should plan");
+        assert_eq!(plan.target, ManagerUpdateTarget::ManagerSelf);
+
+        let Some(AdapterRequest::Upgrade(request)) = build_update_request(&plan, None, false)
+        else {
+            panic!("asdf self update should build an upgrade request");
+        };
+        assert_eq!(request.target, UpgradeTarget::SelfUpdate);
+        assert_eq!(build_update_then_upgrade_all_request(&plan), None);
+    }
+
+    #[test]
+    fn homebrew_self_update_plan_extends_to_update_then_upgrade_all() {
+        let plan = plan_manager_update(ManagerId::HomebrewFormula, None)
+            .expect("homebrew self update should plan");
+
+        let Some(AdapterRequest::Upgrade(request)) = build_update_then_upgrade_all_request(&plan)
+        else {
+            panic!("homebrew should build an update-then-upgrade request");
+        };
+        assert_eq!(request.target, UpgradeTarget::SelfUpdateThenAll);
+    }
+
     #[test]
     fn mise_uninstall_full_cleanup_requires_explicit_config_choice() {
         let error = plan_manager_uninstall_route_with_options(
@@ -1758,6 +1810,7 @@ mod tests {
                 mise_cleanup_mode: Some(MiseUninstallCleanupMode::FullCleanup),
                 mise_config_removal: None,
                 remove_helm_managed_shell_setup: None,
+                confirm_manager_data_removal: false,
             },
         )
         .expect_err("full cleanup should require config selection");
@@ -1776,6 +1829,7 @@ mod tests {
                 mise_cleanup_mode: Some(MiseUninstallCleanupMode::ManagerOnly),
                 mise_config_removal: Some(MiseUninstallConfigRemoval::RemoveConfig),
                 remove_helm_managed_shell_setup: None,
+                confirm_manager_data_removal: false,
             },
         )
         .expect_err("manager-only uninstall should not accept config removal options");
@@ -1794,6 +1848,7 @@ mod tests {
                 mise_cleanup_mode: Some(MiseUninstallCleanupMode::FullCleanup),
                 mise_config_removal: Some(MiseUninstallConfigRemoval::KeepConfig),
                 remove_helm_managed_shell_setup: None,
+                confirm_manager_data_removal: false,
             },
         )
         .expect("full cleanup with config choice should route");
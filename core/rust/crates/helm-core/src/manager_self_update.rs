@@ -0,0 +1,154 @@
+use crate::manager_lifecycle::{ManagerUpdateTarget, plan_manager_update};
+use crate::models::{ManagerId, ManagerInstallInstance, ManagerSelfVersion, OutdatedPackage};
+
+/// Installed and latest version of a manager tool, plus whether Helm can run its update.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ManagerSelfUpdateStatus {
+    pub self_version: Option<ManagerSelfVersion>,
+    pub supports_manager_update: bool,
+}
+
+impl ManagerSelfUpdateStatus {
+    pub fn update_available(&self) -> bool {
+        self.supports_manager_update
+            && self
+                .self_version
+                .as_ref()
+                .is_some_and(ManagerSelfVersion::update_available)
+    }
+}
+
+/// Resolve a manager's self-update status from the route its update would take.
+///
+/// Homebrew-routed managers read the owning formula from the Homebrew outdated
+/// snapshot; self-updating managers use the result of their adapter's
+/// `SelfVersionCheck`. Homebrew itself has no latest-version source, so its
+/// status only reports the detected version.
+pub fn resolve_manager_self_update_status(
+    manager: ManagerId,
+    active_instance: Option<&ManagerInstallInstance>,
+    detected_version: Option<&str>,
+    checked: Option<&ManagerSelfVersion>,
+    outdated: &[OutdatedPackage],
+) -> ManagerSelfUpdateStatus {
+    let Ok(plan) = plan_manager_update(manager, active_instance) else {
+        return ManagerSelfUpdateStatus::default();
+    };
+
+    let detected_version = detected_version.map(str::to_owned);
+    let self_version = match &plan.target {
+        ManagerUpdateTarget::HomebrewFormula { formula_name } => {
+            let outdated_formula = outdated.iter().find(|package| {
+                package.package.manager == ManagerId::HomebrewFormula
+                    && package.package.name == *formula_name
+            });
+            Some(match outdated_formula {
+                Some(package) => ManagerSelfVersion {
+                    installed_version: package.installed_version.clone().or(detected_version),
+                    latest_version: Some(package.candidate_version.clone()),
+                },
+                None => ManagerSelfVersion {
+                    latest_version: detected_version.clone(),
+                    installed_version: detected_version,
+                },
+            })
+        }
+        ManagerUpdateTarget::ManagerSelf => match checked {
+            Some(checked) => Some(ManagerSelfVersion {
+                installed_version: checked.installed_version.clone().or(detected_version),
+                latest_version: checked.latest_version.clone(),
+            }),
+            None => detected_version.map(|version| ManagerSelfVersion {
+                installed_version: Some(version),
+                latest_version: None,
+            }),
+        },
+    };
+
+    ManagerSelfUpdateStatus {
+        self_version,
+        supports_manager_update: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PackageRef;
+
+    fn outdated_formula(name: &str, from: &str, to: &str) -> OutdatedPackage {
+        OutdatedPackage {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: name.to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some(from.to_string()),
+            candidate_version: to.to_string(),
+            pinned: false,
+            restart_required: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        }
+    }
+
+    #[test]
+    fn homebrew_routed_managers_read_owning_formula_from_outdated_snapshot() {
+        let outdated = [outdated_formula("pipx", "1.6.0", "1.7.1")];
+        let status = resolve_manager_self_update_status(
+            ManagerId::Pipx,
+            None,
+            Some("1.6.0"),
+            None,
+            &outdated,
+        );
+        assert!(status.supports_manager_update);
+        assert!(status.update_available());
+        assert_eq!(
+            status.self_version.unwrap().latest_version.as_deref(),
+            Some("1.7.1")
+        );
+
+        let current =
+            resolve_manager_self_update_status(ManagerId::Mas, None, Some("1.8.7"), None, &[]);
+        assert!(!current.update_available());
+        assert_eq!(
+            current.self_version.unwrap().installed_version.as_deref(),
+            Some("1.8.7")
+        );
+    }
+
+    #[test]
+    fn self_updating_managers_use_checked_versions() {
+        let checked = ManagerSelfVersion {
+            installed_version: Some("1.27.1".to_string()),
+            latest_version: Some("1.28.2".to_string()),
+        };
+        let status = resolve_manager_self_update_status(
+            ManagerId::Rustup,
+            None,
+            Some("1.27.1"),
+            Some(&checked),
+            &[],
+        );
+        assert!(status.update_available());
+
+        let unchecked =
+            resolve_manager_self_update_status(ManagerId::Rustup, None, Some("1.27.1"), None, &[]);
+        assert!(unchecked.supports_manager_update);
+        assert!(!unchecked.update_available());
+    }
+
+    #[test]
+    fn managers_without_update_route_report_no_support() {
+        let status = resolve_manager_self_update_status(
+            ManagerId::SoftwareUpdate,
+            None,
+            Some("14.5"),
+            None,
+            &[],
+        );
+        assert_eq!(status, ManagerSelfUpdateStatus::default());
+        assert!(!status.update_available());
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::models::{InstalledPackage, ManagerId, OutdatedPackage};
+
+/// Installed/outdated package counts for one manager, over user-visible packages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ManagerPackageCounts {
+    pub installed: usize,
+    pub outdated: usize,
+}
+
+pub fn count_packages_by_manager(
+    installed: &[InstalledPackage],
+    outdated: &[OutdatedPackage],
+) -> HashMap<ManagerId, ManagerPackageCounts> {
+    let mut counts: HashMap<ManagerId, ManagerPackageCounts> = HashMap::new();
+    for package in installed
+        .iter()
+        .map(|package| &package.package)
+        .filter(|package| package.is_user_visible_package())
+    {
+        counts.entry(package.manager).or_default().installed += 1;
+    }
+    for package in outdated
+        .iter()
+        .map(|package| &package.package)
+        .filter(|package| package.is_user_visible_package())
+    {
+        counts.entry(package.manager).or_default().outdated += 1;
+    }
+    counts
+}
+
+/// Directories whose combined size approximates what a manager's packages
+/// occupy on disk, derived from the manager's resolved executable.
+///
+/// Returns an empty list for managers whose footprint is not computable.
+pub fn manager_size_roots(manager: ManagerId, executable_path: &Path) -> Vec<PathBuf> {
+    let Some(bin_dir) = executable_path.parent() else {
+        return Vec::new();
+    };
+    let prefix = bin_dir.parent();
+    match manager {
+        ManagerId::HomebrewFormula => prefix
+            .map(|prefix| vec![prefix.join("Cellar")])
+            .unwrap_or_default(),
+        ManagerId::HomebrewCask => prefix
+            .map(|prefix| vec![prefix.join("Caskroom")])
+            .unwrap_or_default(),
+        ManagerId::Npm => prefix
+            .map(|prefix| vec![prefix.join("lib").join("node_modules")])
+            .unwrap_or_default(),
+        ManagerId::Cargo => vec![bin_dir.to_path_buf()],
+        _ => Vec::new(),
+    }
+}
+
+/// Total apparent size of regular files under `roots`, without following
+/// symlinks and counting hard-linked files once.
+///
+/// Returns `None` when none of the roots exist.
+pub fn directory_size_bytes(roots: &[PathBuf]) -> Option<u64> {
+    let mut total = 0_u64;
+    let mut any_root = false;
+    let mut seen_inodes: HashSet<(u64, u64)> = HashSet::new();
+    let mut pending: Vec<PathBuf> = Vec::new();
+
+    for root in roots {
+        if std::fs::symlink_metadata(root).is_ok_and(|metadata| metadata.is_dir()) {
+            any_root = true;
+            pending.push(root.clone());
+        }
+    }
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let file_type = metadata.file_type();
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() && first_link_to_inode(&metadata, &mut seen_inodes) {
+                total = total.saturating_add(metadata.len());
+            }
+        }
+    }
+
+    any_root.then_some(total)
+}
+
+#[cfg(unix)]
+fn first_link_to_inode(metadata: &std::fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() <= 1 || seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn first_link_to_inode(_metadata: &std::fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{installed, outdated};
+
+    #[test]
+    fn counts_group_installed_and_outdated_by_manager() {
+        let counts = count_packages_by_manager(
+            &[
+                installed(ManagerId::Npm, "eslint", Some("1.0.0")),
+                installed(ManagerId::Npm, "typescript", Some("1.0.0")),
+                installed(ManagerId::Cargo, "ripgrep", Some("1.0.0")),
+            ],
+            &[outdated(ManagerId::Npm, "eslint", Some("1.0.0"), "2.0.0")],
+        );
+        assert_eq!(
+            counts.get(&ManagerId::Npm),
+            Some(&ManagerPackageCounts {
+                installed: 2,
+                outdated: 1
+            })
+        );
+        assert_eq!(counts.get(&ManagerId::Cargo).map(|c| c.installed), Some(1));
+        assert!(!counts.contains_key(&ManagerId::Pip));
+    }
+
+    #[test]
+    fn size_roots_follow_manager_prefix_layout() {
+        assert_eq!(
+            manager_size_roots(
+                ManagerId::HomebrewFormula,
+                Path::new("/opt/homebrew/bin/brew")
+            ),
+            vec![PathBuf::from("/opt/homebrew/Cellar")]
+        );
+        assert_eq!(
+            manager_size_roots(ManagerId::HomebrewCask, Path::new("/usr/local/bin/brew")),
+            vec![PathBuf::from("/usr/local/Caskroom")]
+        );
+        assert_eq!(
+            manager_size_roots(ManagerId::Npm, Path::new("/usr/local/bin/npm")),
+            vec![PathBuf::from("/usr/local/lib/node_modules")]
+        );
+        assert_eq!(
+            manager_size_roots(ManagerId::Cargo, Path::new("/Users/me/.cargo/bin/cargo")),
+            vec![PathBuf::from("/Users/me/.cargo/bin")]
+        );
+        assert!(manager_size_roots(ManagerId::Pip, Path::new("/usr/bin/pip3")).is_empty());
+    }
+
+    #[test]
+    fn directory_size_sums_nested_files_and_skips_missing_roots() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock should be after epoch")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("helm-manager-stats-{nanos}"));
+        std::fs::create_dir_all(root.join("pkg/lib")).unwrap();
+        std::fs::write(root.join("pkg/a.bin"), vec![0_u8; 100]).unwrap();
+        std::fs::write(root.join("pkg/lib/b.bin"), vec![0_u8; 28]).unwrap();
+
+        assert_eq!(directory_size_bytes(std::slice::from_ref(&root)), Some(128));
+        assert_eq!(directory_size_bytes(&[root.join("missing")]), None);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
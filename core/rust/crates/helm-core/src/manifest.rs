@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+
+use crate::models::{InstalledPackage, ManagerId};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on manifest source files accepted for import.
+pub const MANIFEST_SOURCE_MAX_BYTES: usize = 1024 * 1024;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPackageEntry {
+    pub manager: ManagerId,
+    pub name: String,
+    #[serde(default)]
+    pub target_name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A Homebrew tap to add, optionally cloned from a non-GitHub remote.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestHomebrewTap {
+    pub name: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelmManifest {
+    #[serde(default)]
+    pub packages: Vec<ManifestPackageEntry>,
+    #[serde(default)]
+    pub homebrew_taps: Vec<ManifestHomebrewTap>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestParseWarning {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ManifestParseOutcome {
+    pub manifest: HelmManifest,
+    pub warnings: Vec<ManifestParseWarning>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestOperationStatus {
+    Install,
+    AlreadyInstalled,
+    ManagerUnavailable,
+    Unsupported,
+}
+
+impl ManifestOperationStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Install => "install",
+            Self::AlreadyInstalled => "already_installed",
+            Self::ManagerUnavailable => "manager_unavailable",
+            Self::Unsupported => "unsupported",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestOperationKind {
+    Package,
+    HomebrewTap,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestOperation {
+    pub kind: ManifestOperationKind,
+    pub manager_id: String,
+    pub package_name: String,
+    pub target_name: Option<String>,
+    pub version: Option<String>,
+    /// Remote a `homebrew_tap` operation clones from, when not the default.
+    pub tap_url: Option<String>,
+    pub status: ManifestOperationStatus,
+    pub installed_version: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestDiff {
+    pub operations: Vec<ManifestOperation>,
+    pub install_count: usize,
+    pub already_installed_count: usize,
+    pub skipped_count: usize,
+    pub warnings: Vec<ManifestParseWarning>,
+}
+
+/// Parse a Homebrew Bundle `Brewfile` into a Helm manifest.
+///
+/// Only the declarative subset Helm can act on is understood: `tap`, `brew`,
+/// `cask`, and `mas` entries. Other directives are reported as warnings and
+/// skipped; the Ruby DSL is never evaluated.
+pub fn parse_brewfile(contents: &str) -> ManifestParseOutcome {
+    let mut outcome = ManifestParseOutcome::default();
+    let mut seen: HashSet<(ManagerId, String)> = HashSet::new();
+
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_brewfile_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (directive, rest) =
+            match line.find(|character: char| character.is_whitespace() || character == '(') {
+                Some(split) => (&line[..split], line[split..].trim_start()),
+                None => (line, ""),
+            };
+        let rest = rest.strip_prefix('(').unwrap_or(rest);
+
+        let manager = match directive {
+            "tap" | "brew" => ManagerId::HomebrewFormula,
+            "cask" => ManagerId::HomebrewCask,
+            "mas" => ManagerId::Mas,
+            "cask_args" => continue,
+            other => {
+                outcome.warnings.push(ManifestParseWarning {
+                    line: line_number,
+                    message: format!("unsupported Brewfile directive '{other}'"),
+                });
+                continue;
+            }
+        };
+
+        let Some((name, options)) = leading_quoted_string(rest) else {
+            outcome.warnings.push(ManifestParseWarning {
+                line: line_number,
+                message: format!("expected a quoted name after '{directive}'"),
+            });
+            continue;
+        };
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            outcome.warnings.push(ManifestParseWarning {
+                line: line_number,
+                message: format!("empty name for '{directive}' entry"),
+            });
+            continue;
+        }
+
+        if directive == "tap" {
+            let url = options
+                .trim_start()
+                .strip_prefix(',')
+                .and_then(|rest| leading_quoted_string(rest.trim_start()))
+                .map(|(url, _)| url.trim().to_string())
+                .filter(|url| !url.is_empty());
+            if !outcome
+                .manifest
+                .homebrew_taps
+                .iter()
+                .any(|tap| tap.name == name)
+            {
+                outcome
+                    .manifest
+                    .homebrew_taps
+                    .push(ManifestHomebrewTap { name, url });
+            }
+            continue;
+        }
+
+        let target_name = if manager == ManagerId::Mas {
+            match brewfile_mas_app_id(options) {
+                Some(app_id) => Some(app_id),
+                None => {
+                    outcome.warnings.push(ManifestParseWarning {
+                        line: line_number,
+                        message: format!("mas entry '{name}' is missing a numeric id"),
+                    });
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        let dedupe_key = (manager, target_name.clone().unwrap_or_else(|| name.clone()));
+        if !seen.insert(dedupe_key) {
+            continue;
+        }
+
+        outcome.manifest.packages.push(ManifestPackageEntry {
+            manager,
+            name,
+            target_name,
+            version: None,
+        });
+    }
+
+    outcome
+}
+
+/// Compare a manifest against the current installed snapshot.
+///
+/// `available_managers` lists managers that are enabled and detected; entries
+/// for any other manager are reported instead of being queued. Taps are always
+/// planned while Homebrew is available, since adding an existing tap is a no-op.
+pub fn diff_manifest_against_installed(
+    manifest: &HelmManifest,
+    installed: &[InstalledPackage],
+    available_managers: &HashSet<ManagerId>,
+) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+
+    for tap in &manifest.homebrew_taps {
+        let (status, reason) = if available_managers.contains(&ManagerId::HomebrewFormula) {
+            (ManifestOperationStatus::Install, None)
+        } else {
+            (
+                ManifestOperationStatus::ManagerUnavailable,
+                Some(format!(
+                    "manager '{}' is not enabled or not detected",
+                    ManagerId::HomebrewFormula.as_str()
+                )),
+            )
+        };
+        diff.operations.push(ManifestOperation {
+            kind: ManifestOperationKind::HomebrewTap,
+            manager_id: ManagerId::HomebrewFormula.as_str().to_string(),
+            package_name: tap.name.clone(),
+            target_name: None,
+            version: None,
+            tap_url: tap.url.clone(),
+            status,
+            installed_version: None,
+            reason,
+        });
+    }
+
+    for entry in &manifest.packages {
+        let existing = installed
+            .iter()
+            .find(|package| installed_package_matches_entry(package, entry));
+        let (status, reason) = if existing.is_some() {
+            (ManifestOperationStatus::AlreadyInstalled, None)
+        } else if !available_managers.contains(&entry.manager) {
+            (
+                ManifestOperationStatus::ManagerUnavailable,
+                Some(format!(
+                    "manager '{}' is not enabled or not detected",
+                    entry.manager.as_str()
+                )),
+            )
+        } else {
+            (ManifestOperationStatus::Install, None)
+        };
+
+        diff.operations.push(ManifestOperation {
+            kind: ManifestOperationKind::Package,
+            manager_id: entry.manager.as_str().to_string(),
+            package_name: entry.name.clone(),
+            target_name: entry.target_name.clone(),
+            version: entry.version.clone(),
+            tap_url: None,
+            status,
+            installed_version: existing.and_then(|package| package.installed_version.clone()),
+            reason,
+        });
+    }
+
+    for operation in &diff.operations {
+        match operation.status {
+            ManifestOperationStatus::Install => diff.install_count += 1,
+            ManifestOperationStatus::AlreadyInstalled => diff.already_installed_count += 1,
+            ManifestOperationStatus::ManagerUnavailable | ManifestOperationStatus::Unsupported => {
+                diff.skipped_count += 1
+            }
+        }
+    }
+
+    diff
+}
+
+fn installed_package_matches_entry(
+    package: &InstalledPackage,
+    entry: &ManifestPackageEntry,
+) -> bool {
+    if package.package.manager != entry.manager {
+        return false;
+    }
+    if let Some(target) = entry.target_name.as_deref()
+        && package.package_identifier.as_deref() == Some(target)
+    {
+        return true;
+    }
+    let wanted = match entry.manager {
+        ManagerId::HomebrewFormula | ManagerId::HomebrewCask => {
+            homebrew_short_name(entry.name.as_str())
+        }
+        _ => entry.name.as_str(),
+    };
+    package.package.name == wanted
+}
+
+fn homebrew_short_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+fn strip_brewfile_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (index, character) in line.char_indices() {
+        match (quote, character) {
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if character == open => quote = None,
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn leading_quoted_string(input: &str) -> Option<(&str, &str)> {
+    let quote = input.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let body = &input[1..];
+    let end = body.find(quote)?;
+    Some((&body[..end], &body[end + 1..]))
+}
+
+fn brewfile_mas_app_id(options: &str) -> Option<String> {
+    let (_, after) = options.split_once("id:")?;
+    let digits: String = after
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    (!digits.is_empty()).then_some(digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::installed;
+
+    #[test]
+    fn parse_brewfile_extracts_supported_entries_and_warns_on_others() {
+        let outcome = parse_brewfile(
+            r#"
+# developer tools
+tap "homebrew/bundle"
+tap "hashicorp/tap"
+tap "acme/private", "https://git.example.com/acme/homebrew-private.git"
+cask_args appdir: "~/Applications"
+brew "git"
+brew "mysql@8.0", restart_service: true, link: true
+brew "hashicorp/tap/terraform" # pinned tap
+cask "firefox", args: { appdir: "/Applications" }
+mas "Xcode", id: 497799835
+mas "Broken"
+vscode "rust-lang.rust-analyzer"
+brew "git"
+"#,
+        );
+
+        let taps: Vec<(&str, Option<&str>)> = outcome
+            .manifest
+            .homebrew_taps
+            .iter()
+            .map(|tap| (tap.name.as_str(), tap.url.as_deref()))
+            .collect();
+        assert_eq!(
+            taps,
+            vec![
+                ("homebrew/bundle", None),
+                ("hashicorp/tap", None),
+                (
+                    "acme/private",
+                    Some("https://git.example.com/acme/homebrew-private.git")
+                ),
+            ]
+        );
+        let names: Vec<(ManagerId, &str, Option<&str>)> = outcome
+            .manifest
+            .packages
+            .iter()
+            .map(|entry| {
+                (
+                    entry.manager,
+                    entry.name.as_str(),
+                    entry.target_name.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (ManagerId::HomebrewFormula, "git", None),
+                (ManagerId::HomebrewFormula, "mysql@8.0", None),
+                (ManagerId::HomebrewFormula, "hashicorp/tap/terraform", None),
+                (ManagerId::HomebrewCask, "firefox", None),
+                (ManagerId::Mas, "Xcode", Some("497799835")),
+            ]
+        );
+        let warning_lines: Vec<usize> = outcome.warnings.iter().map(|w| w.line).collect();
+        assert_eq!(warning_lines, vec![12, 13]);
+    }
+
+    #[test]
+    fn parse_brewfile_accepts_parenthesized_and_single_quoted_names() {
+        let outcome = parse_brewfile("brew('wget')\ncask 'visual-studio-code'\n");
+        assert_eq!(outcome.manifest.packages.len(), 2);
+        assert_eq!(outcome.manifest.packages[0].name, "wget");
+        assert_eq!(outcome.manifest.packages[1].name, "visual-studio-code");
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn diff_marks_installed_missing_and_unavailable_entries() {
+        let outcome = parse_brewfile(
+            "tap \"acme/private\", \"https://git.example.com/acme/homebrew-private.git\"\nbrew \"git\"\nbrew \"hashicorp/tap/terraform\"\ncask \"firefox\"\nmas \"Xcode\", id: 497799835\n",
+        );
+        let installed = vec![
+            installed(ManagerId::HomebrewFormula, "terraform", Some("1.0.0")),
+            InstalledPackage {
+                package_identifier: Some("497799835".to_string()),
+                ..installed(ManagerId::Mas, "Xcode-beta", Some("1.0.0"))
+            },
+        ];
+        let available: HashSet<ManagerId> = [ManagerId::HomebrewFormula, ManagerId::Mas]
+            .into_iter()
+            .collect();
+
+        let diff = diff_manifest_against_installed(&outcome.manifest, &installed, &available);
+        let statuses: Vec<(&str, ManifestOperationStatus)> = diff
+            .operations
+            .iter()
+            .map(|operation| (operation.package_name.as_str(), operation.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("acme/private", ManifestOperationStatus::Install),
+                ("git", ManifestOperationStatus::Install),
+                (
+                    "hashicorp/tap/terraform",
+                    ManifestOperationStatus::AlreadyInstalled
+                ),
+                ("firefox", ManifestOperationStatus::ManagerUnavailable),
+                ("Xcode", ManifestOperationStatus::AlreadyInstalled),
+            ]
+        );
+        assert_eq!(diff.operations[0].kind, ManifestOperationKind::HomebrewTap);
+        assert_eq!(
+            diff.operations[0].tap_url.as_deref(),
+            Some("https://git.example.com/acme/homebrew-private.git")
+        );
+        assert_eq!(diff.install_count, 2);
+        assert_eq!(diff.already_installed_count, 2);
+        assert_eq!(diff.skipped_count, 1);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::{
+    HelmManifest, ManifestOperation, ManifestOperationKind, ManifestOperationStatus,
+    diff_manifest_against_installed,
+};
+use crate::models::{InstalledPackage, ManagerId};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestPlanAction {
+    /// Queue an install.
+    Add,
+    /// Nothing to do, or nothing Helm may do; `reason` says which.
+    Skip,
+    /// Installed, but at a different version than the manifest pins.
+    Conflict,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPlanEntry {
+    pub kind: ManifestOperationKind,
+    pub manager_id: String,
+    pub package_name: String,
+    pub target_name: Option<String>,
+    pub version: Option<String>,
+    pub tap_url: Option<String>,
+    pub installed_version: Option<String>,
+    pub action: ManifestPlanAction,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPlan {
+    pub entries: Vec<ManifestPlanEntry>,
+    pub add_count: usize,
+    pub skip_count: usize,
+    pub conflict_count: usize,
+}
+
+impl ManifestPlan {
+    pub fn adds(&self) -> impl Iterator<Item = &ManifestPlanEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.action == ManifestPlanAction::Add)
+    }
+}
+
+/// What the current machine allows when applying a manifest.
+pub struct ManifestPlanContext<'a> {
+    pub installed: &'a [InstalledPackage],
+    /// Managers that are enabled and detected.
+    pub available_managers: &'a HashSet<ManagerId>,
+    /// Managers whose adapter supports installs.
+    pub install_capable_managers: &'a HashSet<ManagerId>,
+    /// Safe mode turns every add into a skip, so applying only reports.
+    pub safe_mode: bool,
+}
+
+/// Plan applying `manifest` to this machine: which entries to install, which
+/// to skip, and which are installed at a different version than pinned.
+///
+/// Version conflicts are reported but never resolved automatically; Helm does
+/// not downgrade or upgrade packages as part of an import.
+pub fn plan_manifest_import(
+    manifest: &HelmManifest,
+    context: &ManifestPlanContext,
+) -> ManifestPlan {
+    let diff =
+        diff_manifest_against_installed(manifest, context.installed, context.available_managers);
+    let mut plan = ManifestPlan::default();
+
+    for operation in diff.operations {
+        let (action, reason) = plan_operation(&operation, context);
+        match action {
+            ManifestPlanAction::Add => plan.add_count += 1,
+            ManifestPlanAction::Skip => plan.skip_count += 1,
+            ManifestPlanAction::Conflict => plan.conflict_count += 1,
+        }
+        plan.entries.push(ManifestPlanEntry {
+            kind: operation.kind,
+            manager_id: operation.manager_id,
+            package_name: operation.package_name,
+            target_name: operation.target_name,
+            version: operation.version,
+            tap_url: operation.tap_url,
+            installed_version: operation.installed_version,
+            action,
+            reason,
+        });
+    }
+
+    plan
+}
+
+fn plan_operation(
+    operation: &ManifestOperation,
+    context: &ManifestPlanContext,
+) -> (ManifestPlanAction, Option<String>) {
+    match operation.status {
+        ManifestOperationStatus::AlreadyInstalled => {
+            match (
+                operation.version.as_deref(),
+                operation.installed_version.as_deref(),
+            ) {
+                (Some(wanted), Some(installed)) if wanted != installed => (
+                    ManifestPlanAction::Conflict,
+                    Some(format!(
+                        "installed version {installed} differs from manifest version {wanted}"
+                    )),
+                ),
+                _ => (
+                    ManifestPlanAction::Skip,
+                    Some("already installed".to_string()),
+                ),
+            }
+        }
+        ManifestOperationStatus::ManagerUnavailable | ManifestOperationStatus::Unsupported => {
+            (ManifestPlanAction::Skip, operation.reason.clone())
+        }
+        ManifestOperationStatus::Install => {
+            let manager = operation.manager_id.parse::<ManagerId>().ok();
+            if !manager.is_some_and(|manager| context.install_capable_managers.contains(&manager)) {
+                (
+                    ManifestPlanAction::Skip,
+                    Some(format!(
+                        "manager '{}' does not support installs",
+                        operation.manager_id
+                    )),
+                )
+            } else if context.safe_mode {
+                (
+                    ManifestPlanAction::Skip,
+                    Some("safe mode is on; installs are not queued".to_string()),
+                )
+            } else {
+                (ManifestPlanAction::Add, None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{ManifestHomebrewTap, ManifestPackageEntry};
+    use crate::test_fixtures::installed;
+
+    fn entry(manager: ManagerId, name: &str, version: Option<&str>) -> ManifestPackageEntry {
+        ManifestPackageEntry {
+            manager,
+            name: name.to_string(),
+            target_name: None,
+            version: version.map(str::to_string),
+        }
+    }
+
+    fn manifest() -> HelmManifest {
+        HelmManifest {
+            packages: vec![
+                entry(ManagerId::HomebrewFormula, "git", None),
+                entry(ManagerId::HomebrewFormula, "jq", None),
+                entry(ManagerId::Pip, "requests", Some("2.32.3")),
+                entry(ManagerId::Npm, "typescript", None),
+                entry(ManagerId::SoftwareUpdate, "macOS 15.1", None),
+            ],
+            homebrew_taps: vec![ManifestHomebrewTap {
+                name: "hashicorp/tap".to_string(),
+                url: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn plan_classifies_adds_skips_and_version_conflicts() {
+        let installed = vec![
+            installed(ManagerId::HomebrewFormula, "git", Some("2.47.0")),
+            installed(ManagerId::Pip, "requests", Some("2.31.0")),
+        ];
+        let available: HashSet<ManagerId> = [
+            ManagerId::HomebrewFormula,
+            ManagerId::Pip,
+            ManagerId::SoftwareUpdate,
+        ]
+        .into_iter()
+        .collect();
+        let install_capable: HashSet<ManagerId> = [ManagerId::HomebrewFormula, ManagerId::Pip]
+            .into_iter()
+            .collect();
+
+        let plan = plan_manifest_import(
+            &manifest(),
+            &ManifestPlanContext {
+                installed: &installed,
+                available_managers: &available,
+                install_capable_managers: &install_capable,
+                safe_mode: false,
+            },
+        );
+
+        let actions: Vec<(&str, ManifestPlanAction)> = plan
+            .entries
+            .iter()
+            .map(|entry| (entry.package_name.as_str(), entry.action))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("hashicorp/tap", ManifestPlanAction::Add),
+                ("git", ManifestPlanAction::Skip),
+                ("jq", ManifestPlanAction::Add),
+                ("requests", ManifestPlanAction::Conflict),
+                ("typescript", ManifestPlanAction::Skip),
+                ("macOS 15.1", ManifestPlanAction::Skip),
+            ]
+        );
+        assert_eq!(
+            (plan.add_count, plan.skip_count, plan.conflict_count),
+            (2, 3, 1)
+        );
+        assert_eq!(
+            plan.entries[3].reason.as_deref(),
+            Some("installed version 2.31.0 differs from manifest version 2.32.3")
+        );
+        assert_eq!(
+            plan.entries[5].reason.as_deref(),
+            Some("manager 'softwareupdate' does not support installs")
+        );
+        assert_eq!(
+            plan.adds()
+                .map(|entry| entry.package_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["hashicorp/tap", "jq"]
+        );
+    }
+
+    #[test]
+    fn safe_mode_turns_adds_into_skips() {
+        let available: HashSet<ManagerId> = [ManagerId::HomebrewFormula].into_iter().collect();
+        let plan = plan_manifest_import(
+            &HelmManifest {
+                packages: vec![entry(ManagerId::HomebrewFormula, "jq", None)],
+                homebrew_taps: Vec::new(),
+            },
+            &ManifestPlanContext {
+                installed: &[],
+                available_managers: &available,
+                install_capable_managers: &available,
+                safe_mode: true,
+            },
+        );
+
+        assert_eq!(plan.add_count, 0);
+        assert_eq!(plan.entries[0].action, ManifestPlanAction::Skip);
+        assert_eq!(
+            plan.entries[0].reason.as_deref(),
+            Some("safe mode is on; installs are not queued")
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::SystemTime;
+
+use crate::execution::CommandTimingSnapshot;
+use crate::manager_stats::count_packages_by_manager;
+use crate::models::{
+    InstalledPackage, OutdatedPackage, PackageSnapshotFreshness, TaskRecord, TaskStatus,
+};
+
+/// State rendered by [`render_metrics_text`].
+pub struct MetricsInput<'a> {
+    /// Task history as retained by the store; pruned tasks are not counted.
+    pub tasks: &'a [TaskRecord],
+    pub installed: &'a [InstalledPackage],
+    pub outdated: &'a [OutdatedPackage],
+    pub freshness: &'a [PackageSnapshotFreshness],
+    pub command_timings: &'a CommandTimingSnapshot,
+    pub now: SystemTime,
+}
+
+/// Render Helm state in the Prometheus text exposition format (version 0.0.4).
+///
+/// Series are ordered by manager id so consecutive scrapes diff cleanly.
+/// Command durations are reported as a histogram over each rolling sample
+/// window, so `_count` and `_sum` can drop as old samples roll off.
+pub fn render_metrics_text(input: &MetricsInput<'_>) -> String {
+    let mut out = String::new();
+
+    let mut task_counts: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+    for task in input.tasks {
+        *task_counts
+            .entry((task.manager.as_str(), task_status_label(task.status)))
+            .or_default() += 1;
+    }
+    family(
+        &mut out,
+        "helm_tasks",
+        "gauge",
+        "Tasks in Helm's retained task history by manager and status.",
+    );
+    for ((manager, status), count) in &task_counts {
+        let _ = writeln!(
+            out,
+            "helm_tasks{{manager=\"{manager}\",status=\"{status}\"}} {count}"
+        );
+    }
+
+    let package_counts = count_packages_by_manager(input.installed, input.outdated)
+        .into_iter()
+        .map(|(manager, counts)| (manager.as_str(), counts))
+        .collect::<BTreeMap<_, _>>();
+    family(
+        &mut out,
+        "helm_installed_packages",
+        "gauge",
+        "Installed packages per manager.",
+    );
+    for (manager, counts) in &package_counts {
+        let _ = writeln!(
+            out,
+            "helm_installed_packages{{manager=\"{manager}\"}} {}",
+            counts.installed
+        );
+    }
+    family(
+        &mut out,
+        "helm_outdated_packages",
+        "gauge",
+        "Outdated packages per manager.",
+    );
+    for (manager, counts) in &package_counts {
+        let _ = writeln!(
+            out,
+            "helm_outdated_packages{{manager=\"{manager}\"}} {}",
+            counts.outdated
+        );
+    }
+
+    let freshness = input
+        .freshness
+        .iter()
+        .map(|freshness| (freshness.manager.as_str(), freshness))
+        .collect::<BTreeMap<_, _>>();
+    family(
+        &mut out,
+        "helm_snapshot_age_seconds",
+        "gauge",
+        "Seconds since the manager's package snapshot was last refreshed.",
+    );
+    for (manager, freshness) in &freshness {
+        let age = input
+            .now
+            .duration_since(freshness.updated_at)
+            .unwrap_or_default()
+            .as_secs();
+        let _ = writeln!(
+            out,
+            "helm_snapshot_age_seconds{{manager=\"{manager}\"}} {age}"
+        );
+    }
+    family(
+        &mut out,
+        "helm_snapshot_partial",
+        "gauge",
+        "1 when the manager's last snapshot task was cancelled or failed part way.",
+    );
+    for (manager, freshness) in &freshness {
+        let _ = writeln!(
+            out,
+            "helm_snapshot_partial{{manager=\"{manager}\"}} {}",
+            u8::from(freshness.partial)
+        );
+    }
+
+    let bounds = &input.command_timings.bucket_upper_bounds_ms;
+    family(
+        &mut out,
+        "helm_command_duration_seconds",
+        "histogram",
+        "Durations of recent manager commands by manager and action.",
+    );
+    for histogram in &input.command_timings.histograms {
+        let labels = format!(
+            "manager=\"{}\",action=\"{}\"",
+            histogram.manager_id.as_str(),
+            histogram.action
+        );
+        let mut cumulative = 0_u64;
+        for (bound, count) in bounds.iter().zip(&histogram.bucket_counts) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "helm_command_duration_seconds_bucket{{{labels},le=\"{}\"}} {cumulative}",
+                millis_as_seconds(*bound)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "helm_command_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+            histogram.sample_count
+        );
+        let _ = writeln!(
+            out,
+            "helm_command_duration_seconds_sum{{{labels}}} {}",
+            millis_as_seconds(histogram.total_ms)
+        );
+        let _ = writeln!(
+            out,
+            "helm_command_duration_seconds_count{{{labels}}} {}",
+            histogram.sample_count
+        );
+    }
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn task_status_label(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Queued => "queued",
+        TaskStatus::Running => "running",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Cancelled => "cancelled",
+        TaskStatus::Failed => "failed",
+    }
+}
+
+fn millis_as_seconds(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let fraction = ms % 1000;
+    if fraction == 0 {
+        seconds.to_string()
+    } else {
+        format!("{seconds}.{fraction:03}")
+            .trim_end_matches('0')
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{MetricsInput, millis_as_seconds, render_metrics_text};
+    use crate::execution::{CommandTimingHistogram, CommandTimingSnapshot};
+    use crate::models::{
+        ManagerId, OutdatedPackage, PackageRef, PackageSnapshotFreshness, TaskId, TaskRecord,
+        TaskStatus, TaskType,
+    };
+
+    fn task(id: u64, manager: ManagerId, status: TaskStatus) -> TaskRecord {
+        TaskRecord {
+            id: TaskId(id),
+            manager,
+            task_type: TaskType::Refresh,
+            status,
+            created_at: UNIX_EPOCH,
+            attempt_of: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn seconds_are_rendered_without_trailing_zeros() {
+        assert_eq!(millis_as_seconds(100), "0.1");
+        assert_eq!(millis_as_seconds(2_500), "2.5");
+        assert_eq!(millis_as_seconds(1_234), "1.234");
+        assert_eq!(millis_as_seconds(60_000), "60");
+    }
+
+    #[test]
+    fn renders_task_package_freshness_and_duration_families() {
+        let tasks = vec![
+            task(1, ManagerId::Npm, TaskStatus::Queued),
+            task(2, ManagerId::Npm, TaskStatus::Failed),
+            task(3, ManagerId::Npm, TaskStatus::Failed),
+        ];
+        let outdated = vec![OutdatedPackage {
+            package: PackageRef {
+                manager: ManagerId::Pip,
+                name: "black".to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some("1.0.0".to_string()),
+            candidate_version: "2.0.0".to_string(),
+            pinned: false,
+            restart_required: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        }];
+        let freshness = vec![PackageSnapshotFreshness {
+            manager: ManagerId::Pip,
+            task_id: TaskId(1),
+            partial: true,
+            installed_partial: true,
+            outdated_partial: true,
+            updated_at: UNIX_EPOCH + Duration::from_secs(100),
+        }];
+        let command_timings = CommandTimingSnapshot {
+            bucket_upper_bounds_ms: vec![100, 1_000],
+            histograms: vec![CommandTimingHistogram {
+                manager_id: ManagerId::Npm,
+                action: "refresh",
+                sample_count: 3,
+                total_ms: 4_250,
+                max_ms: 4_000,
+                p50_ms: 200,
+                p95_ms: 4_000,
+                bucket_counts: vec![1, 1, 1],
+            }],
+            ..CommandTimingSnapshot::default()
+        };
+
+        let text = render_metrics_text(&MetricsInput {
+            tasks: &tasks,
+            installed: &[],
+            outdated: &outdated,
+            freshness: &freshness,
+            command_timings: &command_timings,
+            now: UNIX_EPOCH + Duration::from_secs(160),
+        });
+        let lines = text.lines().collect::<Vec<_>>();
+
+        for expected in [
+            "# TYPE helm_tasks gauge",
+            "helm_tasks{manager=\"npm\",status=\"failed\"} 2",
+            "helm_tasks{manager=\"npm\",status=\"queued\"} 1",
+            "helm_installed_packages{manager=\"pip\"} 0",
+            "helm_outdated_packages{manager=\"pip\"} 1",
+            "helm_snapshot_age_seconds{manager=\"pip\"} 60",
+            "helm_snapshot_partial{manager=\"pip\"} 1",
+            "# TYPE helm_command_duration_seconds histogram",
+            "helm_command_duration_seconds_bucket{manager=\"npm\",action=\"refresh\",le=\"0.1\"} 1",
+            "helm_command_duration_seconds_bucket{manager=\"npm\",action=\"refresh\",le=\"1\"} 2",
+            "helm_command_duration_seconds_bucket{manager=\"npm\",action=\"refresh\",le=\"+Inf\"} 3",
+            "helm_command_duration_seconds_sum{manager=\"npm\",action=\"refresh\"} 4.25",
+            "helm_command_duration_seconds_count{manager=\"npm\",action=\"refresh\"} 3",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "missing `{expected}` in:\n{text}"
+            );
+        }
+        assert!(text.ends_with('\n'));
+    }
+}
@@ -1,8 +1,13 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 
+use serde::Serialize;
+
 use crate::models::{ManagerAction, ManagerId, TaskType};
 
+/// Longest stderr excerpt carried by a [`CoreErrorPayload`], in characters.
+const STDERR_EXCERPT_MAX_CHARS: usize = 2_000;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum CoreErrorKind {
     NotInstalled,
@@ -13,9 +18,56 @@ pub enum CoreErrorKind {
     Cancelled,
     ProcessFailure,
     StorageFailure,
+    /// The manager's registry could not be reached; retry once connectivity returns.
+    NetworkUnreachable,
+    /// Offline mode is on and the task needs the network.
+    OfflineMode,
     Internal,
 }
 
+impl CoreErrorKind {
+    const ALL: [Self; 11] = [
+        Self::NotInstalled,
+        Self::UnsupportedCapability,
+        Self::InvalidInput,
+        Self::ParseFailure,
+        Self::Timeout,
+        Self::Cancelled,
+        Self::ProcessFailure,
+        Self::StorageFailure,
+        Self::NetworkUnreachable,
+        Self::OfflineMode,
+        Self::Internal,
+    ];
+
+    /// Stable snake_case code for task logs and persisted results.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::NotInstalled => "not_installed",
+            Self::UnsupportedCapability => "unsupported_capability",
+            Self::InvalidInput => "invalid_input",
+            Self::ParseFailure => "parse_failure",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::ProcessFailure => "process_failure",
+            Self::StorageFailure => "storage_failure",
+            Self::NetworkUnreachable => "network_unreachable",
+            Self::OfflineMode => "offline_mode",
+            Self::Internal => "internal",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    /// Whether running the same operation again may succeed without the user
+    /// changing anything first.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::Timeout | Self::NetworkUnreachable)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CoreError {
     pub manager: Option<ManagerId>,
@@ -32,3 +84,86 @@ impl Display for CoreError {
 }
 
 impl Error for CoreError {}
+
+/// Serializable form of a [`CoreError`] for clients outside the core. Process
+/// failures reported as `process exited with code N: <stderr>` are split into
+/// `exit_code` and `stderr_excerpt`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CoreErrorPayload {
+    pub kind: &'static str,
+    pub manager: Option<ManagerId>,
+    pub task: Option<TaskType>,
+    pub action: Option<&'static str>,
+    pub message: String,
+    pub exit_code: Option<i32>,
+    pub stderr_excerpt: Option<String>,
+    pub retryable: bool,
+}
+
+impl CoreError {
+    pub fn payload(&self) -> CoreErrorPayload {
+        let (exit_code, stderr) = self
+            .message
+            .strip_prefix("process exited with code ")
+            .and_then(|rest| {
+                let (code, stderr) = rest.split_once(':').unwrap_or((rest, ""));
+                Some((code.trim().parse::<i32>().ok()?, stderr.trim()))
+            })
+            .map_or((None, None), |(code, stderr)| (Some(code), Some(stderr)));
+        CoreErrorPayload {
+            kind: self.kind.code(),
+            manager: self.manager,
+            task: self.task,
+            action: self.action.map(ManagerAction::as_str),
+            message: self.message.clone(),
+            exit_code,
+            stderr_excerpt: stderr
+                .filter(|stderr| !stderr.is_empty())
+                .map(|stderr| stderr.chars().take(STDERR_EXCERPT_MAX_CHARS).collect()),
+            retryable: self.kind.is_retryable(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoreError, CoreErrorKind};
+    use crate::models::{ManagerAction, ManagerId, TaskType};
+
+    #[test]
+    fn payload_splits_process_failures_into_exit_code_and_stderr() {
+        let error = CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Upgrade),
+            action: Some(ManagerAction::Upgrade),
+            kind: CoreErrorKind::ProcessFailure,
+            message: "process exited with code 1: npm ERR! code E404\n".to_string(),
+        };
+        let json = serde_json::to_value(error.payload()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "process_failure",
+                "manager": "npm",
+                "task": "upgrade",
+                "action": "upgrade",
+                "message": "process exited with code 1: npm ERR! code E404\n",
+                "exit_code": 1,
+                "stderr_excerpt": "npm ERR! code E404",
+                "retryable": false,
+            })
+        );
+
+        let timeout = CoreError {
+            manager: None,
+            task: None,
+            action: None,
+            kind: CoreErrorKind::Timeout,
+            message: "process exited after deadline".to_string(),
+        }
+        .payload();
+        assert!(timeout.retryable);
+        assert_eq!(timeout.exit_code, None);
+        assert_eq!(timeout.stderr_excerpt, None);
+    }
+}
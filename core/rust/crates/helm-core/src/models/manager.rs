@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::models::CoreErrorKind;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ManagerId {
@@ -103,41 +105,47 @@ impl ManagerId {
     }
 }
 
+impl ManagerId {
+    /// Other spellings that parse to a manager: the tool's own command name
+    /// where it differs from the id, and ids from before the Homebrew
+    /// formula/cask split. [`ManagerId::as_str`] stays the only spelling
+    /// Helm writes.
+    pub const ALIASES: &'static [(&'static str, ManagerId)] = &[
+        ("homebrew", ManagerId::HomebrewFormula),
+        ("brew", ManagerId::HomebrewFormula),
+        ("brew_cask", ManagerId::HomebrewCask),
+        ("port", ManagerId::MacPorts),
+        ("gem", ManagerId::RubyGems),
+        ("bundle", ManagerId::Bundler),
+        ("software_update", ManagerId::SoftwareUpdate),
+        ("xcode_clt", ManagerId::XcodeCommandLineTools),
+    ];
+
+    fn from_canonical_str(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|manager| manager.as_str() == value)
+    }
+}
+
+/// Accepts canonical ids, then the same ids case-insensitively and with `-`
+/// for `_` (`cargo-binstall`), then [`ManagerId::ALIASES`].
 impl std::str::FromStr for ManagerId {
     type Err = ();
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "mise" => Ok(Self::Mise),
-            "asdf" => Ok(Self::Asdf),
-            "rustup" => Ok(Self::Rustup),
-            "homebrew_formula" => Ok(Self::HomebrewFormula),
-            "softwareupdate" => Ok(Self::SoftwareUpdate),
-            "macports" => Ok(Self::MacPorts),
-            "nix_darwin" => Ok(Self::NixDarwin),
-            "pnpm" => Ok(Self::Pnpm),
-            "npm" => Ok(Self::Npm),
-            "yarn" => Ok(Self::Yarn),
-            "pipx" => Ok(Self::Pipx),
-            "pip" => Ok(Self::Pip),
-            "poetry" => Ok(Self::Poetry),
-            "rubygems" => Ok(Self::RubyGems),
-            "bundler" => Ok(Self::Bundler),
-            "cargo" => Ok(Self::Cargo),
-            "cargo_binstall" => Ok(Self::CargoBinstall),
-            "mas" => Ok(Self::Mas),
-            "sparkle" => Ok(Self::Sparkle),
-            "setapp" => Ok(Self::Setapp),
-            "homebrew_cask" => Ok(Self::HomebrewCask),
-            "docker_desktop" => Ok(Self::DockerDesktop),
-            "podman" => Ok(Self::Podman),
-            "colima" => Ok(Self::Colima),
-            "parallels_desktop" => Ok(Self::ParallelsDesktop),
-            "xcode_command_line_tools" => Ok(Self::XcodeCommandLineTools),
-            "rosetta2" => Ok(Self::Rosetta2),
-            "firmware_updates" => Ok(Self::FirmwareUpdates),
-            _ => Err(()),
+        if let Some(manager) = Self::from_canonical_str(value) {
+            return Ok(manager);
         }
+        let normalized = value.trim().to_ascii_lowercase().replace('-', "_");
+        Self::from_canonical_str(&normalized)
+            .or_else(|| {
+                Self::ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == normalized)
+                    .map(|(_, manager)| *manager)
+            })
+            .ok_or(())
     }
 }
 
@@ -171,6 +179,54 @@ pub enum Capability {
     Upgrade,
     Pin,
     Unpin,
+    /// Report the manager tool's own installed and latest available version.
+    SelfVersionCheck,
+    /// List the files an installed package owns on disk.
+    ListFiles,
+    /// Report which installed packages depend on which.
+    ListDependencies,
+    /// Report which installed packages were installed on request rather than
+    /// as a dependency.
+    ListRequested,
+    /// Restore the version a package had before its last upgrade.
+    Rollback,
+    /// Report descriptive metadata for a package: description, homepage,
+    /// license, size, and direct dependencies.
+    PackageInfo,
+    /// List the background services installed packages provide and start,
+    /// stop, or restart them.
+    Services,
+    /// Report and reclaim disk space held by downloads and caches the
+    /// manager can fetch again.
+    Cleanup,
+    /// Measure how much disk each installed package occupies.
+    PackageSizes,
+}
+
+impl Capability {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Detect => "detect",
+            Self::Refresh => "refresh",
+            Self::Search => "search",
+            Self::ListInstalled => "list_installed",
+            Self::ListOutdated => "list_outdated",
+            Self::Install => "install",
+            Self::Uninstall => "uninstall",
+            Self::Upgrade => "upgrade",
+            Self::Pin => "pin",
+            Self::Unpin => "unpin",
+            Self::SelfVersionCheck => "self_version_check",
+            Self::ListFiles => "list_files",
+            Self::ListDependencies => "list_dependencies",
+            Self::ListRequested => "list_requested",
+            Self::Rollback => "rollback",
+            Self::PackageInfo => "package_info",
+            Self::Services => "services",
+            Self::Cleanup => "cleanup",
+            Self::PackageSizes => "package_sizes",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -186,9 +242,47 @@ pub enum ManagerAction {
     Configure,
     Pin,
     Unpin,
+    SelfVersionCheck,
+    ListFiles,
+    ListDependencies,
+    ListRequested,
+    Rollback,
+    PackageInfo,
+    ListServices,
+    SetServiceState,
+    PreviewCleanup,
+    Cleanup,
+    MeasurePackageSizes,
 }
 
 impl ManagerAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Detect => "detect",
+            Self::Refresh => "refresh",
+            Self::Search => "search",
+            Self::ListInstalled => "list_installed",
+            Self::ListOutdated => "list_outdated",
+            Self::Install => "install",
+            Self::Uninstall => "uninstall",
+            Self::Upgrade => "upgrade",
+            Self::Configure => "configure",
+            Self::Pin => "pin",
+            Self::Unpin => "unpin",
+            Self::SelfVersionCheck => "self_version_check",
+            Self::ListFiles => "list_files",
+            Self::ListDependencies => "list_dependencies",
+            Self::ListRequested => "list_requested",
+            Self::Rollback => "rollback",
+            Self::PackageInfo => "package_info",
+            Self::ListServices => "list_services",
+            Self::SetServiceState => "set_service_state",
+            Self::PreviewCleanup => "preview_cleanup",
+            Self::Cleanup => "cleanup",
+            Self::MeasurePackageSizes => "measure_package_sizes",
+        }
+    }
+
     pub fn required_capability(self) -> Capability {
         match self {
             Self::Detect => Capability::Detect,
@@ -202,6 +296,15 @@ impl ManagerAction {
             Self::Configure => Capability::Upgrade,
             Self::Pin => Capability::Pin,
             Self::Unpin => Capability::Unpin,
+            Self::SelfVersionCheck => Capability::SelfVersionCheck,
+            Self::ListFiles => Capability::ListFiles,
+            Self::ListDependencies => Capability::ListDependencies,
+            Self::ListRequested => Capability::ListRequested,
+            Self::Rollback => Capability::Rollback,
+            Self::PackageInfo => Capability::PackageInfo,
+            Self::ListServices | Self::SetServiceState => Capability::Services,
+            Self::PreviewCleanup | Self::Cleanup => Capability::Cleanup,
+            Self::MeasurePackageSizes => Capability::PackageSizes,
         }
     }
 
@@ -211,13 +314,24 @@ impl ManagerAction {
             | Self::Refresh
             | Self::Search
             | Self::ListInstalled
-            | Self::ListOutdated => ActionSafety::ReadOnly,
+            | Self::ListOutdated
+            | Self::SelfVersionCheck
+            | Self::ListFiles
+            | Self::ListDependencies
+            | Self::ListRequested
+            | Self::PackageInfo
+            | Self::ListServices
+            | Self::PreviewCleanup
+            | Self::MeasurePackageSizes => ActionSafety::ReadOnly,
             Self::Install
             | Self::Uninstall
             | Self::Upgrade
             | Self::Configure
             | Self::Pin
-            | Self::Unpin => ActionSafety::Mutating,
+            | Self::Unpin
+            | Self::Rollback
+            | Self::SetServiceState
+            | Self::Cleanup => ActionSafety::Mutating,
         }
     }
 }
@@ -226,6 +340,58 @@ impl ManagerAction {
 pub enum ActionSafety {
     ReadOnly,
     Mutating,
+    /// Removal the user can undo, such as moving an app bundle to the Trash.
+    Recoverable,
+    /// Removal that deletes files outright.
+    Destructive,
+}
+
+/// What an uninstall does with the app bundles it removes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UninstallDisposal {
+    /// Let the manager delete the package as it normally would.
+    #[default]
+    Delete,
+    /// Move app bundles to the Trash before the manager forgets the package.
+    Trash,
+}
+
+impl UninstallDisposal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Trash => "trash",
+        }
+    }
+
+    pub fn safety(self) -> ActionSafety {
+        match self {
+            Self::Delete => ActionSafety::Destructive,
+            Self::Trash => ActionSafety::Recoverable,
+        }
+    }
+
+    /// Whether `manager` can honor this disposal. Only Homebrew casks install
+    /// app bundles Helm can move to the Trash itself.
+    pub fn supported_by(self, manager: ManagerId) -> bool {
+        match self {
+            Self::Delete => true,
+            Self::Trash => manager == ManagerId::HomebrewCask,
+        }
+    }
+}
+
+impl std::str::FromStr for UninstallDisposal {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "delete" => Ok(Self::Delete),
+            "trash" => Ok(Self::Trash),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -243,6 +409,31 @@ impl ManagerDescriptor {
     }
 }
 
+/// Disk space held by a manager's caches and stale downloads: what a cleanup
+/// would free, or what it did free.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    pub bytes: u64,
+}
+
+/// Installed and latest available version of a manager tool itself (for
+/// example `rustup`), as opposed to the packages it manages.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagerSelfVersion {
+    pub installed_version: Option<String>,
+    pub latest_version: Option<String>,
+}
+
+impl ManagerSelfVersion {
+    pub fn update_available(&self) -> bool {
+        self.latest_version
+            .as_deref()
+            .is_some_and(|latest| Some(latest) != self.installed_version.as_deref())
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DetectionInfo {
     pub installed: bool,
@@ -250,6 +441,43 @@ pub struct DetectionInfo {
     pub version: Option<String>,
 }
 
+/// How the latest detection of a manager ended. `installed: false` alone cannot
+/// tell an absent tool from a probe that errored.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DetectionOutcome {
+    Installed,
+    NotFound,
+    /// The probe errored (timeout, spawn or wait failure); the previous
+    /// detection state is kept.
+    ProbeFailed {
+        error_key: String,
+    },
+}
+
+impl DetectionOutcome {
+    pub fn probe_failed(kind: CoreErrorKind) -> Self {
+        Self::ProbeFailed {
+            error_key: format!("service.error.{}", kind.code()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Installed => "installed",
+            Self::NotFound => "not_found",
+            Self::ProbeFailed { .. } => "probe_failed",
+        }
+    }
+
+    pub fn error_key(&self) -> Option<&str> {
+        match self {
+            Self::ProbeFailed { error_key } => Some(error_key.as_str()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum InstallInstanceIdentityKind {
@@ -438,6 +666,13 @@ pub struct UninstallImpactPath {
     pub exists: bool,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagerUninstallAffectedPackage {
+    pub package_name: String,
+    pub installed_version: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ManagerUninstallPreview {
@@ -455,6 +690,11 @@ pub struct ManagerUninstallPreview {
     pub competing_confidence: Option<f64>,
     pub files_removed: Vec<UninstallImpactPath>,
     pub directories_removed: Vec<UninstallImpactPath>,
+    /// Packages Helm tracks under the requested manager; they are orphaned by a
+    /// manager-only uninstall and removed with the data of a full cleanup.
+    pub affected_packages: Vec<ManagerUninstallAffectedPackage>,
+    /// Whether the uninstall removes manager-owned data directories (tool versions, caches).
+    pub removes_manager_data: bool,
     pub secondary_effects: Vec<String>,
     pub summary_lines: Vec<String>,
     pub blast_radius_score: u32,
@@ -487,3 +727,41 @@ pub struct PackageUninstallPreview {
     pub competing_provenance: Option<String>,
     pub competing_confidence: Option<f64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ManagerId;
+
+    #[test]
+    fn canonical_ids_round_trip() {
+        for manager in ManagerId::ALL {
+            assert_eq!(manager.as_str().parse::<ManagerId>(), Ok(manager));
+        }
+    }
+
+    #[test]
+    fn hyphenated_uppercase_and_aliased_ids_parse() {
+        assert_eq!(
+            "cargo-binstall".parse::<ManagerId>(),
+            Ok(ManagerId::CargoBinstall)
+        );
+        assert_eq!(
+            " Homebrew_Cask ".parse::<ManagerId>(),
+            Ok(ManagerId::HomebrewCask)
+        );
+        assert_eq!("brew".parse::<ManagerId>(), Ok(ManagerId::HomebrewFormula));
+        assert_eq!("gem".parse::<ManagerId>(), Ok(ManagerId::RubyGems));
+        assert_eq!("winget".parse::<ManagerId>(), Err(()));
+    }
+
+    #[test]
+    fn aliases_do_not_shadow_canonical_ids() {
+        for (alias, _) in ManagerId::ALIASES {
+            assert!(
+                ManagerId::ALL
+                    .into_iter()
+                    .all(|manager| manager.as_str() != *alias)
+            );
+        }
+    }
+}
@@ -1,24 +1,39 @@
 pub mod error;
 pub mod keg_policy;
 pub mod manager;
+pub mod onboarding;
 pub mod package;
 pub mod pin;
+pub mod refresh;
 pub mod search;
+pub mod service;
 pub mod task;
+pub mod task_history;
 pub mod task_log;
 
-pub use error::{CoreError, CoreErrorKind};
+pub use error::{CoreError, CoreErrorKind, CoreErrorPayload};
 pub use keg_policy::{HomebrewKegPolicy, PackageKegPolicy};
 pub use manager::{
-    ActionSafety, AutomationLevel, Capability, DetectionInfo, InstallInstanceIdentityKind,
-    InstallProvenance, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
-    ManagerId, ManagerInstallInstance, ManagerUninstallPreview, PackageUninstallPreview,
-    StrategyKind, UninstallImpactPath,
+    ActionSafety, AutomationLevel, Capability, CleanupReport, DetectionInfo, DetectionOutcome,
+    InstallInstanceIdentityKind, InstallProvenance, ManagerAction, ManagerAuthority,
+    ManagerCategory, ManagerDescriptor, ManagerId, ManagerInstallInstance, ManagerSelfVersion,
+    ManagerUninstallAffectedPackage, ManagerUninstallPreview, PackageUninstallPreview,
+    StrategyKind, UninstallDisposal, UninstallImpactPath,
 };
+pub use onboarding::{OnboardingChoices, OnboardingPhase};
 pub use package::{
-    InstalledPackage, OutdatedPackage, PackageCandidate, PackageRef, PackageRuntimeState,
+    InstalledPackage, InstalledPackageSize, OutdatedPackage, PackageAlias, PackageAliasKind,
+    PackageCandidate, PackageDependency, PackageFiles, PackageListSort, PackageMetadata,
+    PackageRef, PackageRuntimeState, PackageSizes, PackageSnapshotFreshness,
 };
 pub use pin::{PinKind, PinRecord};
-pub use search::{CachedSearchResult, SearchQuery};
-pub use task::{TaskId, TaskRecord, TaskStatus, TaskType};
+pub use refresh::{ManagerRefreshError, ManagerRefreshResult};
+pub use search::{
+    CachedSearchResult, RankedSearchResult, SearchCompletion, SearchPopularity, SearchQuery,
+    normalize_search_completion_query, rank_search_results, rank_search_results_with_aliases,
+    search_relevance, search_relevance_with_aliases,
+};
+pub use service::{ServiceAction, ServiceRecord};
+pub use task::{TaskId, TaskLabel, TaskRecord, TaskStatus, TaskType};
+pub use task_history::{TaskHistoryFilter, TaskHistoryRecord};
 pub use task_log::{NewTaskLogRecord, TaskLogLevel, TaskLogRecord};
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{HomebrewKegPolicy, ManagerId};
+
+/// Persisted first-run onboarding phase.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingPhase {
+    #[default]
+    NotStarted,
+    Detecting,
+    Review,
+    Complete,
+}
+
+impl OnboardingPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotStarted => "not_started",
+            Self::Detecting => "detecting",
+            Self::Review => "review",
+            Self::Complete => "complete",
+        }
+    }
+}
+
+impl std::str::FromStr for OnboardingPhase {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "not_started" => Ok(Self::NotStarted),
+            "detecting" => Ok(Self::Detecting),
+            "review" => Ok(Self::Review),
+            "complete" => Ok(Self::Complete),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Preferences chosen at the end of onboarding. Omitted fields keep their
+/// current stored value.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OnboardingChoices {
+    pub manager_enabled: HashMap<ManagerId, bool>,
+    pub safe_mode: Option<bool>,
+    pub homebrew_keg_policy: Option<HomebrewKegPolicy>,
+    pub auto_check_for_updates: Option<bool>,
+    pub auto_check_frequency_minutes: Option<u32>,
+}
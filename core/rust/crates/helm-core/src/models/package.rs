@@ -1,4 +1,6 @@
-use crate::models::ManagerId;
+use std::time::SystemTime;
+
+use crate::models::{ManagerId, TaskId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -32,6 +34,131 @@ impl PackageRuntimeState {
     }
 }
 
+/// Sizes a manager reported for a package; managers that do not report sizes
+/// leave both fields empty.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageSizes {
+    pub install_size_bytes: Option<u64>,
+    pub download_size_bytes: Option<u64>,
+}
+
+impl PackageSizes {
+    pub const fn is_empty(&self) -> bool {
+        self.install_size_bytes.is_none() && self.download_size_bytes.is_none()
+    }
+
+    fn sort_key(&self) -> Option<u64> {
+        self.download_size_bytes.or(self.install_size_bytes)
+    }
+}
+
+/// Disk space an installed package occupies: its keg, module, or virtualenv
+/// directory, or the binaries it installed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledPackageSize {
+    pub package: PackageRef,
+    pub bytes: u64,
+}
+
+/// Files an installed package owns, as reported by its manager.
+///
+/// `total` counts every owned file; `files` holds at most the requested limit
+/// of them, in the order the manager listed them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageFiles {
+    pub files: Vec<String>,
+    pub total: usize,
+}
+
+impl PackageFiles {
+    pub fn from_listing(files: impl IntoIterator<Item = String>, limit: Option<usize>) -> Self {
+        let mut kept = Vec::new();
+        let mut total = 0usize;
+        for file in files {
+            total += 1;
+            if limit.is_none_or(|limit| kept.len() < limit) {
+                kept.push(file);
+            }
+        }
+        Self { files: kept, total }
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.files.len() < self.total
+    }
+}
+
+/// One edge of a manager's installed dependency graph: `package` needs
+/// `dependency`, directly or through another dependency.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PackageDependency {
+    pub package: PackageRef,
+    pub dependency: String,
+}
+
+/// Descriptive metadata a manager reports for one package.
+///
+/// Every field is optional because managers expose different subsets;
+/// `dependencies` lists direct dependencies only.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageMetadata {
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+    pub installed_size_bytes: Option<u64>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Other names the package is known by, such as a cask's app name.
+    #[serde(default)]
+    pub aliases: Vec<PackageAlias>,
+}
+
+/// Where a package alias comes from.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageAliasKind {
+    /// Human-readable name, e.g. "Visual Studio Code" for `visual-studio-code`.
+    DisplayName,
+    /// File name of an app bundle the package installs.
+    AppBundle,
+    /// Bundle identifier of an app the package installs.
+    BundleId,
+}
+
+impl PackageAliasKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DisplayName => "display_name",
+            Self::AppBundle => "app_bundle",
+            Self::BundleId => "bundle_id",
+        }
+    }
+}
+
+impl std::str::FromStr for PackageAliasKind {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "display_name" => Ok(Self::DisplayName),
+            "app_bundle" => Ok(Self::AppBundle),
+            "bundle_id" => Ok(Self::BundleId),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageAlias {
+    pub kind: PackageAliasKind,
+    pub value: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct InstalledPackage {
     pub package: PackageRef,
@@ -41,6 +168,8 @@ pub struct InstalledPackage {
     pub pinned: bool,
     #[serde(default)]
     pub runtime_state: PackageRuntimeState,
+    #[serde(default)]
+    pub sizes: PackageSizes,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -54,6 +183,53 @@ pub struct OutdatedPackage {
     pub restart_required: bool,
     #[serde(default)]
     pub runtime_state: PackageRuntimeState,
+    #[serde(default)]
+    pub sizes: PackageSizes,
+}
+
+/// Ordering for package listings.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageListSort {
+    /// Store order: manager, then package name.
+    #[default]
+    Default,
+    /// Largest download (falling back to install) size first; packages without
+    /// a reported size keep store order after every sized package.
+    SizeDescending,
+}
+
+impl PackageListSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::SizeDescending => "size",
+        }
+    }
+
+    pub fn sort_installed(self, packages: &mut [InstalledPackage]) {
+        if self == Self::SizeDescending {
+            packages.sort_by_key(|package| std::cmp::Reverse(package.sizes.sort_key()));
+        }
+    }
+
+    pub fn sort_outdated(self, packages: &mut [OutdatedPackage]) {
+        if self == Self::SizeDescending {
+            packages.sort_by_key(|package| std::cmp::Reverse(package.sizes.sort_key()));
+        }
+    }
+}
+
+impl std::str::FromStr for PackageListSort {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "" | "default" => Ok(Self::Default),
+            "size" => Ok(Self::SizeDescending),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -64,3 +240,56 @@ pub struct PackageCandidate {
     pub version: Option<String>,
     pub summary: Option<String>,
 }
+
+/// Completeness of a manager's persisted package snapshot, tagged with the
+/// task that last wrote it or left it incomplete.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PackageSnapshotFreshness {
+    pub manager: ManagerId,
+    pub task_id: TaskId,
+    /// A snapshot task ended cancelled or failed, so installed and outdated
+    /// rows may come from different refreshes.
+    pub partial: bool,
+    /// The installed list was not rewritten by the task that last tried to.
+    #[serde(default)]
+    pub installed_partial: bool,
+    /// The outdated list was not rewritten by the task that last tried to.
+    #[serde(default)]
+    pub outdated_partial: bool,
+    pub updated_at: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::outdated;
+
+    fn sized_update(name: &str, download_size_bytes: Option<u64>) -> OutdatedPackage {
+        OutdatedPackage {
+            sizes: PackageSizes {
+                install_size_bytes: None,
+                download_size_bytes,
+            },
+            ..outdated(ManagerId::SoftwareUpdate, name, None, "1")
+        }
+    }
+
+    #[test]
+    fn size_sort_puts_largest_first_and_unsized_last_in_store_order() {
+        let mut packages = vec![
+            sized_update("a", None),
+            sized_update("b", Some(10)),
+            sized_update("c", None),
+            sized_update("d", Some(300)),
+        ];
+        PackageListSort::SizeDescending.sort_outdated(&mut packages);
+        let names: Vec<&str> = packages
+            .iter()
+            .map(|package| package.package.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["d", "b", "a", "c"]);
+        assert_eq!("size".parse(), Ok(PackageListSort::SizeDescending));
+        assert_eq!("".parse(), Ok(PackageListSort::Default));
+        assert!("name".parse::<PackageListSort>().is_err());
+    }
+}
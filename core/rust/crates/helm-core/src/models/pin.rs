@@ -13,5 +13,9 @@ pub struct PinRecord {
     pub package: PackageRef,
     pub kind: PinKind,
     pub pinned_version: Option<String>,
+    /// Version range, such as `^1.2`, that upgrades may move within. A
+    /// constrained pin holds a package only while its candidate version falls
+    /// outside the range; see [`crate::versioning::VersionConstraint`].
+    pub version_constraint: Option<String>,
     pub created_at: SystemTime,
 }
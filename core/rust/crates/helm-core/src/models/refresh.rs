@@ -0,0 +1,27 @@
+use std::time::{Duration, SystemTime};
+
+use crate::models::{CoreErrorKind, ManagerId};
+
+/// How one manager fared in an ordered refresh-all run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerRefreshResult {
+    pub manager: ManagerId,
+    /// `None` when the refresh succeeded.
+    pub error: Option<ManagerRefreshError>,
+    pub duration: Duration,
+    /// When the refresh-all run this result belongs to started.
+    pub run_started_at: SystemTime,
+    pub finished_at: SystemTime,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerRefreshError {
+    pub kind: CoreErrorKind,
+    pub message: String,
+}
+
+impl ManagerRefreshResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
@@ -1,8 +1,10 @@
-use std::time::SystemTime;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
 
 use crate::models::{ManagerId, PackageCandidate};
+use crate::package_aliases::{PackageAliasIndex, normalize_package_alias};
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SearchQuery {
@@ -16,4 +18,342 @@ pub struct CachedSearchResult {
     pub source_manager: ManagerId,
     pub originating_query: String,
     pub cached_at: SystemTime,
+    #[serde(default)]
+    pub popularity: SearchPopularity,
+}
+
+/// Popularity a registry reports for a search result, where it reports any.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SearchPopularity {
+    /// Downloads or installs over the registry's reporting window: the last
+    /// month on npm, 90 days on crates.io, 30 days on Homebrew.
+    pub downloads: Option<u64>,
+    /// 1-based position in the registry's own popularity ranking.
+    pub rank: Option<u32>,
+}
+
+impl SearchPopularity {
+    pub fn is_empty(&self) -> bool {
+        self.downloads.is_none() && self.rank.is_none()
+    }
+}
+
+/// Records that `manager` finished a remote search for `query`, so an empty
+/// result set can be told apart from a search that never ran.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SearchCompletion {
+    pub query: String,
+    pub manager: ManagerId,
+    pub result_count: usize,
+    pub completed_at: SystemTime,
+}
+
+impl SearchCompletion {
+    /// Whether the completion is recent enough to skip another remote search.
+    pub fn is_fresh(&self, now: SystemTime, ttl: Duration) -> bool {
+        match now.duration_since(self.completed_at) {
+            Ok(age) => age < ttl,
+            // Completed "in the future" (clock moved backwards): treat as fresh.
+            Err(_) => true,
+        }
+    }
+}
+
+/// Relevance of a name (or identifier) equal to the query.
+pub const SEARCH_RELEVANCE_EXACT: u32 = 1000;
+/// Relevance of a name starting with the query.
+pub const SEARCH_RELEVANCE_PREFIX: u32 = 600;
+/// Relevance of a name containing the query.
+pub const SEARCH_RELEVANCE_SUBSTRING: u32 = 300;
+/// Relevance of a result matched only through its summary.
+pub const SEARCH_RELEVANCE_SUMMARY: u32 = 100;
+/// Added for packages already installed through the result's manager. Smaller
+/// than the gap between match tiers, so it only reorders within a tier.
+pub const SEARCH_RELEVANCE_INSTALLED_BOOST: u32 = 150;
+
+/// A local search hit together with its relevance to the query.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RankedSearchResult {
+    pub result: CachedSearchResult,
+    pub installed: bool,
+    pub relevance: u32,
+}
+
+/// Score `result` against `query`: exact name matches outrank prefixes, which
+/// outrank substrings and summary-only matches; installed packages get a boost.
+/// An empty query scores only the installed boost.
+pub fn search_relevance(query: &str, result: &CachedSearchResult, installed: bool) -> u32 {
+    search_relevance_with_aliases(query, result, installed, &PackageAliasIndex::default())
+}
+
+/// [`search_relevance`], also matching the package's display names and app
+/// bundles from `aliases` in normalized form, so "Visual Studio Code" is an
+/// exact match for the `visual-studio-code` cask.
+pub fn search_relevance_with_aliases(
+    query: &str,
+    result: &CachedSearchResult,
+    installed: bool,
+    aliases: &PackageAliasIndex,
+) -> u32 {
+    let needle = query.trim().to_lowercase();
+    let installed_boost = if installed {
+        SEARCH_RELEVANCE_INSTALLED_BOOST
+    } else {
+        0
+    };
+    if needle.is_empty() {
+        return installed_boost;
+    }
+
+    let names = [
+        Some(result.result.package.name.as_str()),
+        result.result.package_identifier.as_deref(),
+    ];
+    let name_relevance = names
+        .into_iter()
+        .flatten()
+        .map(|name| {
+            let name = name.to_lowercase();
+            if name == needle {
+                SEARCH_RELEVANCE_EXACT
+            } else if name.starts_with(&needle) {
+                SEARCH_RELEVANCE_PREFIX
+            } else if name.contains(&needle) {
+                SEARCH_RELEVANCE_SUBSTRING
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    let normalized_needle = normalize_package_alias(&needle);
+    let alias_relevance = aliases
+        .display_aliases(&result.result.package)
+        .map(normalize_package_alias)
+        .filter(|_| !normalized_needle.is_empty())
+        .map(|alias| {
+            if alias == normalized_needle {
+                SEARCH_RELEVANCE_EXACT
+            } else if alias.starts_with(&normalized_needle) {
+                SEARCH_RELEVANCE_PREFIX
+            } else if alias.contains(&normalized_needle) {
+                SEARCH_RELEVANCE_SUBSTRING
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    let name_relevance = name_relevance.max(alias_relevance);
+    let match_relevance = if name_relevance > 0 {
+        name_relevance
+    } else if result
+        .result
+        .summary
+        .as_deref()
+        .is_some_and(|summary| summary.to_lowercase().contains(&needle))
+    {
+        SEARCH_RELEVANCE_SUMMARY
+    } else {
+        0
+    };
+    match_relevance + installed_boost
+}
+
+/// Rank search hits for `query`, most relevant first, keeping at most `limit`.
+///
+/// Identical packages (same manager, name, identifier, and version) reported
+/// by more than one source manager are kept once. `hits` must arrive newest
+/// first: the newest duplicate wins, and equally relevant results keep that
+/// recency order.
+pub fn rank_search_results(
+    query: &str,
+    hits: impl IntoIterator<Item = (CachedSearchResult, bool)>,
+    limit: usize,
+) -> Vec<RankedSearchResult> {
+    rank_search_results_with_aliases(query, hits, &PackageAliasIndex::default(), limit)
+}
+
+/// [`rank_search_results`] with package aliases: a hit named by a display
+/// name or app bundle counts as a duplicate of the package it resolves to,
+/// and aliases score as names.
+pub fn rank_search_results_with_aliases(
+    query: &str,
+    hits: impl IntoIterator<Item = (CachedSearchResult, bool)>,
+    aliases: &PackageAliasIndex,
+    limit: usize,
+) -> Vec<RankedSearchResult> {
+    let mut seen = HashSet::new();
+    let mut ranked = hits
+        .into_iter()
+        .filter(|(result, _)| {
+            let package = &result.result.package;
+            let name = aliases
+                .resolve(package.manager, &package.name)
+                .unwrap_or(&package.name);
+            seen.insert((
+                package.manager,
+                name.to_string(),
+                result.result.package_identifier.clone(),
+                result.result.version.clone(),
+            ))
+        })
+        .map(|(result, installed)| RankedSearchResult {
+            relevance: search_relevance_with_aliases(query, &result, installed, aliases),
+            result,
+            installed,
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|ranked| std::cmp::Reverse(ranked.relevance));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Key completion markers by the trimmed, case-folded query text.
+pub fn normalize_search_completion_query(query: &str) -> String {
+    query.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PackageRef;
+
+    fn hit(manager: ManagerId, name: &str, summary: &str, cached_secs: u64) -> CachedSearchResult {
+        CachedSearchResult {
+            result: PackageCandidate {
+                package: PackageRef {
+                    manager,
+                    name: name.to_string(),
+                },
+                package_identifier: None,
+                version: Some(format!("{cached_secs}.0")),
+                summary: Some(summary.to_string()),
+            },
+            source_manager: manager,
+            originating_query: "rip".to_string(),
+            cached_at: SystemTime::UNIX_EPOCH + Duration::from_secs(cached_secs),
+            popularity: SearchPopularity::default(),
+        }
+    }
+
+    #[test]
+    fn relevance_orders_exact_then_prefix_then_substring_then_summary() {
+        let score = |name: &str, summary: &str| {
+            search_relevance(" RipGrep ", &hit(ManagerId::Cargo, name, summary, 1), false)
+        };
+        assert_eq!(score("ripgrep", ""), SEARCH_RELEVANCE_EXACT);
+        assert_eq!(score("ripgrep-all", ""), SEARCH_RELEVANCE_PREFIX);
+        assert_eq!(score("cargo-ripgrep", ""), SEARCH_RELEVANCE_SUBSTRING);
+        assert_eq!(score("rga", "ripgrep wrapper"), SEARCH_RELEVANCE_SUMMARY);
+        assert_eq!(score("fd", "find"), 0);
+
+        let installed = search_relevance(
+            "ripgrep",
+            &hit(ManagerId::Cargo, "ripgrep-all", "", 1),
+            true,
+        );
+        assert_eq!(
+            installed,
+            SEARCH_RELEVANCE_PREFIX + SEARCH_RELEVANCE_INSTALLED_BOOST
+        );
+        assert!(installed < SEARCH_RELEVANCE_EXACT);
+    }
+
+    #[test]
+    fn ranking_deduplicates_identical_packages_and_keeps_recency_for_ties() {
+        let mut reported_elsewhere = hit(ManagerId::HomebrewFormula, "ripgrep", "", 20);
+        reported_elsewhere.source_manager = ManagerId::HomebrewCask;
+        reported_elsewhere.cached_at = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+        let hits = vec![
+            (hit(ManagerId::Cargo, "ripgrep-all", "", 30), false),
+            (hit(ManagerId::HomebrewFormula, "ripgrep", "", 20), false),
+            (reported_elsewhere, false),
+            (hit(ManagerId::Cargo, "ripgrep", "", 5), true),
+            (hit(ManagerId::Npm, "ripgrep-js", "", 4), false),
+        ];
+
+        let ranked = rank_search_results("ripgrep", hits, 3);
+        let order = ranked
+            .iter()
+            .map(|ranked| {
+                (
+                    ranked.result.result.package.manager,
+                    ranked.result.result.package.name.as_str(),
+                    ranked.result.result.version.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                (ManagerId::Cargo, "ripgrep", Some("5.0")),
+                (ManagerId::HomebrewFormula, "ripgrep", Some("20.0")),
+                (ManagerId::Cargo, "ripgrep-all", Some("30.0")),
+            ]
+        );
+        assert!(ranked[0].installed);
+        assert_eq!(ranked[1].result.source_manager, ManagerId::HomebrewFormula);
+    }
+
+    #[test]
+    fn ranking_matches_and_deduplicates_cask_aliases() {
+        use crate::models::{PackageAlias, PackageAliasKind};
+
+        let vscode = PackageRef {
+            manager: ManagerId::HomebrewCask,
+            name: "visual-studio-code".to_string(),
+        };
+        let aliases = PackageAliasIndex::from_aliases([(
+            vscode.clone(),
+            PackageAlias {
+                kind: PackageAliasKind::DisplayName,
+                value: "Microsoft Visual Studio Code".to_string(),
+            },
+        )]);
+        let token_hit = hit(ManagerId::HomebrewCask, "visual-studio-code", "", 10);
+        let ranked = rank_search_results_with_aliases(
+            "Microsoft Visual Studio Code",
+            vec![(token_hit.clone(), false)],
+            &aliases,
+            10,
+        );
+        assert_eq!(ranked[0].relevance, SEARCH_RELEVANCE_EXACT);
+
+        let mut display_name_hit = token_hit.clone();
+        display_name_hit.result.package.name = "Microsoft Visual Studio Code".to_string();
+        let hits = vec![
+            (display_name_hit, false),
+            (token_hit, false),
+            (hit(ManagerId::Npm, "vscode-langservers", "", 5), false),
+        ];
+        let ranked = rank_search_results_with_aliases("visual", hits, &aliases, 10);
+        let casks = ranked
+            .iter()
+            .filter(|ranked| ranked.result.result.package.manager == ManagerId::HomebrewCask)
+            .count();
+        assert_eq!(casks, 1);
+        assert!(
+            ranked
+                .iter()
+                .all(|ranked| ranked.result.result.package != vscode)
+        );
+    }
+
+    #[test]
+    fn completions_expire_after_ttl() {
+        let completed_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let completion = SearchCompletion {
+            query: normalize_search_completion_query("  RipGrep "),
+            manager: ManagerId::HomebrewFormula,
+            result_count: 0,
+            completed_at,
+        };
+        assert_eq!(completion.query, "ripgrep");
+
+        let ttl = Duration::from_secs(300);
+        assert!(completion.is_fresh(completed_at + Duration::from_secs(299), ttl));
+        assert!(!completion.is_fresh(completed_at + Duration::from_secs(300), ttl));
+        assert!(completion.is_fresh(completed_at - Duration::from_secs(5), ttl));
+    }
 }
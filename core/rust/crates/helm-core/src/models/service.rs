@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// State change requested for a background service run by a package.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl ServiceAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        }
+    }
+
+    /// Whether the action takes a running service down, even briefly. Safe
+    /// mode blocks these.
+    pub fn interrupts_service(self) -> bool {
+        matches!(self, Self::Stop | Self::Restart)
+    }
+}
+
+impl FromStr for ServiceAction {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "start" => Ok(Self::Start),
+            "stop" => Ok(Self::Stop),
+            "restart" => Ok(Self::Restart),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One background service as reported by its manager.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceRecord {
+    pub name: String,
+    /// Manager-reported state, passed through verbatim (Homebrew uses
+    /// `started`, `stopped`, `scheduled`, `error`, `none`, and `unknown`).
+    pub status: String,
+    /// Account the service runs as, when it is loaded.
+    pub user: Option<String>,
+    /// launchd plist backing the service, when it is loaded.
+    pub file: Option<PathBuf>,
+    pub exit_code: Option<i64>,
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::SystemTime;
 
 use crate::models::ManagerId;
@@ -21,6 +22,21 @@ pub enum TaskType {
     Unpin,
 }
 
+impl TaskType {
+    pub const ALL: [Self; 10] = [
+        Self::Detection,
+        Self::Refresh,
+        Self::Search,
+        Self::CatalogSync,
+        Self::Install,
+        Self::Uninstall,
+        Self::Upgrade,
+        Self::Configure,
+        Self::Pin,
+        Self::Unpin,
+    ];
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
@@ -38,4 +54,22 @@ pub struct TaskRecord {
     pub task_type: TaskType,
     pub status: TaskStatus,
     pub created_at: SystemTime,
+    /// First task of the retry chain this task re-attempts; `None` for a
+    /// first attempt. Set once through `TaskStore::record_task_attempt` and
+    /// left untouched by `update_task`.
+    #[serde(default)]
+    pub attempt_of: Option<TaskId>,
+    /// Localization key and arguments describing the task. Written once by
+    /// `create_task` and left untouched by `update_task`.
+    #[serde(default)]
+    pub label: Option<TaskLabel>,
+}
+
+/// Display label for a task; also part of the signature used to dedupe
+/// equivalent in-flight submissions.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TaskLabel {
+    pub key: String,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
 }
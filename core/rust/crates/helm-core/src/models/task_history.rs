@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+use crate::models::{ManagerId, TaskId, TaskStatus, TaskType};
+
+/// A terminal task kept after its live `TaskRecord` is pruned.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TaskHistoryRecord {
+    pub task_id: TaskId,
+    pub manager: ManagerId,
+    pub task_type: TaskType,
+    pub status: TaskStatus,
+    pub exit_code: Option<i32>,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub duration_ms: Option<u64>,
+    /// Tail of the captured stdout, truncated by the recorder.
+    pub stdout_tail: Option<String>,
+    /// Tail of the captured stderr, truncated by the recorder.
+    pub stderr_tail: Option<String>,
+    pub finished_at: SystemTime,
+}
+
+/// Narrows a task history listing; unset fields match every entry.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskHistoryFilter {
+    pub manager: Option<ManagerId>,
+    pub task_type: Option<TaskType>,
+    pub status: Option<TaskStatus>,
+}
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ManagerId;
+
+static NETWORK_HINT: RwLock<NetworkHint> = RwLock::new(NetworkHint {
+    expensive: false,
+    constrained: false,
+});
+static ALLOW_HEAVY_NETWORK_WHEN_METERED: AtomicBool = AtomicBool::new(false);
+static LAST_REFRESH_SUPPRESSIONS: Mutex<Vec<ManagerId>> = Mutex::new(Vec::new());
+
+/// The host's view of the current network path.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHint {
+    /// Cellular or a personal hotspot (`NWPath.isExpensive`).
+    #[serde(default)]
+    pub expensive: bool,
+    /// Low Data Mode is on (`NWPath.isConstrained`).
+    #[serde(default)]
+    pub constrained: bool,
+}
+
+impl NetworkHint {
+    pub fn is_metered(self) -> bool {
+        self.expensive || self.constrained
+    }
+}
+
+/// Work a manager's refresh does that is too heavy for a metered network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HeavyRefreshWork {
+    /// The whole refresh: `softwareupdate --list` scans Apple's catalog.
+    Refresh,
+    /// The `brew update` Homebrew runs on its own before listing outdated
+    /// packages. The listing itself still runs.
+    AutoUpdate,
+}
+
+/// Replace the network hint. It persists until the host sends another one.
+pub fn set_network_hint(hint: NetworkHint) {
+    *NETWORK_HINT
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = hint;
+}
+
+pub fn current_network_hint() -> NetworkHint {
+    *NETWORK_HINT
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The user's override: run bandwidth-heavy work even on metered networks.
+pub fn set_allow_heavy_network_when_metered(allow: bool) {
+    ALLOW_HEAVY_NETWORK_WHEN_METERED.store(allow, Ordering::SeqCst);
+}
+
+pub fn allow_heavy_network_when_metered() -> bool {
+    ALLOW_HEAVY_NETWORK_WHEN_METERED.load(Ordering::SeqCst)
+}
+
+/// Whether bandwidth-heavy work should be skipped right now.
+pub fn suppress_heavy_network() -> bool {
+    should_suppress(current_network_hint(), allow_heavy_network_when_metered())
+}
+
+fn should_suppress(hint: NetworkHint, allow_when_metered: bool) -> bool {
+    hint.is_metered() && !allow_when_metered
+}
+
+pub fn heavy_refresh_work(manager: ManagerId) -> Option<HeavyRefreshWork> {
+    match manager {
+        ManagerId::SoftwareUpdate => Some(HeavyRefreshWork::Refresh),
+        ManagerId::HomebrewFormula | ManagerId::HomebrewCask => Some(HeavyRefreshWork::AutoUpdate),
+        _ => None,
+    }
+}
+
+/// Record which managers the latest refresh-all run held back because the
+/// network was metered.
+pub fn record_refresh_suppressions(managers: Vec<ManagerId>) {
+    *LAST_REFRESH_SUPPRESSIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = managers;
+}
+
+pub fn last_refresh_suppressions() -> Vec<ManagerId> {
+    LAST_REFRESH_SUPPRESSIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeavyRefreshWork, NetworkHint, heavy_refresh_work, should_suppress};
+    use crate::models::ManagerId;
+
+    #[test]
+    fn expensive_or_constrained_paths_are_metered() {
+        assert!(!NetworkHint::default().is_metered());
+        let hotspot = NetworkHint {
+            expensive: true,
+            constrained: false,
+        };
+        let low_data = NetworkHint {
+            expensive: false,
+            constrained: true,
+        };
+        assert!(hotspot.is_metered());
+        assert!(low_data.is_metered());
+    }
+
+    #[test]
+    fn user_override_lifts_suppression() {
+        let hotspot = NetworkHint {
+            expensive: true,
+            constrained: false,
+        };
+        assert!(should_suppress(hotspot, false));
+        assert!(!should_suppress(hotspot, true));
+        assert!(!should_suppress(NetworkHint::default(), false));
+    }
+
+    #[test]
+    fn hint_json_defaults_missing_fields() {
+        let hint: NetworkHint = serde_json::from_str(r#"{"expensive":true}"#).unwrap();
+        assert_eq!(
+            hint,
+            NetworkHint {
+                expensive: true,
+                constrained: false,
+            }
+        );
+    }
+
+    #[test]
+    fn only_catalog_scans_and_homebrew_auto_update_are_heavy() {
+        assert_eq!(
+            heavy_refresh_work(ManagerId::SoftwareUpdate),
+            Some(HeavyRefreshWork::Refresh)
+        );
+        assert_eq!(
+            heavy_refresh_work(ManagerId::HomebrewFormula),
+            Some(HeavyRefreshWork::AutoUpdate)
+        );
+        assert_eq!(heavy_refresh_work(ManagerId::Npm), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashSet;
+
+use crate::models::{InstalledPackage, ManagerId};
+use serde::{Deserialize, Serialize};
+
+/// npm globals that ship with Node or the target manager itself and must not be moved.
+const NPM_MIGRATION_EXCLUDED_PACKAGES: &[&str] = &["npm", "corepack", "pnpm", "yarn"];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NpmMigrationPackageStatus {
+    Migrate,
+    AlreadyInTarget,
+    Excluded,
+    NotSelected,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpmMigrationPackagePlan {
+    pub package_name: String,
+    pub npm_version: Option<String>,
+    pub target_version: Option<String>,
+    pub status: NpmMigrationPackageStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpmMigrationPreview {
+    pub source_manager_id: String,
+    pub target_manager_id: String,
+    pub packages: Vec<NpmMigrationPackagePlan>,
+    pub migrate_count: usize,
+    pub already_in_target_count: usize,
+    pub excluded_count: usize,
+    pub summary_lines: Vec<String>,
+}
+
+impl NpmMigrationPreview {
+    pub fn packages_to_migrate(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|plan| plan.status == NpmMigrationPackageStatus::Migrate)
+            .map(|plan| plan.package_name.clone())
+            .collect()
+    }
+}
+
+pub fn is_npm_migration_target(manager: ManagerId) -> bool {
+    matches!(manager, ManagerId::Pnpm | ManagerId::Yarn)
+}
+
+/// Build a migration preview of npm globals into `target`.
+///
+/// Packages already present under the target are left in place (the npm copy
+/// is not removed either). When `selection` is provided, only the named
+/// packages are considered for migration.
+pub fn build_npm_migration_preview(
+    target: ManagerId,
+    npm_installed: &[InstalledPackage],
+    target_installed: &[InstalledPackage],
+    selection: Option<&[String]>,
+) -> NpmMigrationPreview {
+    let target_versions: std::collections::HashMap<&str, Option<&str>> = target_installed
+        .iter()
+        .filter(|package| package.package.manager == target)
+        .map(|package| {
+            (
+                package.package.name.as_str(),
+                package.installed_version.as_deref(),
+            )
+        })
+        .collect();
+    let selected: Option<HashSet<&str>> =
+        selection.map(|names| names.iter().map(String::as_str).collect());
+
+    let mut seen = HashSet::new();
+    let mut packages = Vec::new();
+    for package in npm_installed {
+        if package.package.manager != ManagerId::Npm
+            || !package.package.is_user_visible_package()
+            || !seen.insert(package.package.name.as_str())
+        {
+            continue;
+        }
+        let name = package.package.name.as_str();
+        let target_version = target_versions.get(name).copied();
+        let (status, reason) = if NPM_MIGRATION_EXCLUDED_PACKAGES.contains(&name) {
+            (
+                NpmMigrationPackageStatus::Excluded,
+                Some("bundled with Node.js or a package manager itself".to_string()),
+            )
+        } else if target_version.is_some() {
+            (
+                NpmMigrationPackageStatus::AlreadyInTarget,
+                Some(format!("already installed via {}", target.as_str())),
+            )
+        } else if selected.as_ref().is_some_and(|names| !names.contains(name)) {
+            (NpmMigrationPackageStatus::NotSelected, None)
+        } else {
+            (NpmMigrationPackageStatus::Migrate, None)
+        };
+        packages.push(NpmMigrationPackagePlan {
+            package_name: name.to_string(),
+            npm_version: package.installed_version.clone(),
+            target_version: target_version.flatten().map(str::to_string),
+            status,
+            reason,
+        });
+    }
+    packages.sort_by(|left, right| left.package_name.cmp(&right.package_name));
+
+    let count = |status: NpmMigrationPackageStatus| {
+        packages.iter().filter(|plan| plan.status == status).count()
+    };
+    let migrate_count = count(NpmMigrationPackageStatus::Migrate);
+    let already_in_target_count = count(NpmMigrationPackageStatus::AlreadyInTarget);
+    let excluded_count = count(NpmMigrationPackageStatus::Excluded);
+
+    let mut summary_lines = vec![format!(
+        "{migrate_count} npm global package(s) will be installed via {} and then removed from npm.",
+        target.as_str()
+    )];
+    if already_in_target_count > 0 {
+        summary_lines.push(format!(
+            "{already_in_target_count} package(s) are already installed via {} and will be left unchanged.",
+            target.as_str()
+        ));
+    }
+    if excluded_count > 0 {
+        summary_lines.push(format!(
+            "{excluded_count} package(s) are bundled tooling and will stay with npm."
+        ));
+    }
+    summary_lines.push(
+        "npm copies are removed only after every install succeeds; a failed install rolls back packages installed so far."
+            .to_string(),
+    );
+
+    NpmMigrationPreview {
+        source_manager_id: ManagerId::Npm.as_str().to_string(),
+        target_manager_id: target.as_str().to_string(),
+        packages,
+        migrate_count,
+        already_in_target_count,
+        excluded_count,
+        summary_lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::installed;
+
+    #[test]
+    fn preview_classifies_npm_globals_against_target() {
+        let npm = vec![
+            installed(ManagerId::Npm, "typescript", Some("5.4.0")),
+            installed(ManagerId::Npm, "npm", Some("10.5.0")),
+            installed(ManagerId::Npm, "eslint", Some("9.0.0")),
+            installed(ManagerId::Npm, "@scope/tool", Some("1.2.3")),
+            installed(ManagerId::Npm, "__self__", Some("10.5.0")),
+        ];
+        let pnpm = vec![installed(ManagerId::Pnpm, "eslint", Some("9.1.0"))];
+
+        let preview = build_npm_migration_preview(ManagerId::Pnpm, &npm, &pnpm, None);
+        let statuses: Vec<(&str, NpmMigrationPackageStatus)> = preview
+            .packages
+            .iter()
+            .map(|plan| (plan.package_name.as_str(), plan.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("@scope/tool", NpmMigrationPackageStatus::Migrate),
+                ("eslint", NpmMigrationPackageStatus::AlreadyInTarget),
+                ("npm", NpmMigrationPackageStatus::Excluded),
+                ("typescript", NpmMigrationPackageStatus::Migrate),
+            ]
+        );
+        assert_eq!(preview.migrate_count, 2);
+        assert_eq!(preview.already_in_target_count, 1);
+        assert_eq!(preview.excluded_count, 1);
+        assert_eq!(preview.target_manager_id, "pnpm");
+        assert_eq!(preview.packages[1].target_version.as_deref(), Some("9.1.0"));
+        assert_eq!(
+            preview.packages_to_migrate(),
+            vec!["@scope/tool".to_string(), "typescript".to_string()]
+        );
+    }
+
+    #[test]
+    fn preview_honors_explicit_selection() {
+        let npm = vec![
+            installed(ManagerId::Npm, "typescript", Some("5.4.0")),
+            installed(ManagerId::Npm, "prettier", Some("3.2.0")),
+        ];
+        let selection = vec!["prettier".to_string()];
+
+        let preview = build_npm_migration_preview(ManagerId::Yarn, &npm, &[], Some(&selection));
+        assert_eq!(preview.packages_to_migrate(), vec!["prettier".to_string()]);
+        assert_eq!(
+            preview.packages[1].status,
+            NpmMigrationPackageStatus::NotSelected
+        );
+    }
+
+    #[test]
+    fn only_pnpm_and_yarn_are_migration_targets() {
+        assert!(is_npm_migration_target(ManagerId::Pnpm));
+        assert!(is_npm_migration_target(ManagerId::Yarn));
+        assert!(!is_npm_migration_target(ManagerId::Npm));
+        assert!(!is_npm_migration_target(ManagerId::Cargo));
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, CoreErrorKind, OnboardingChoices, OnboardingPhase};
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+/// Inputs that move first-run onboarding between phases.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OnboardingEvent {
+    DetectionStarted,
+    DetectionFinished,
+    Completed,
+    Reset,
+}
+
+/// Onboarding phase plus the defaults suggested for the review step.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingState {
+    pub phase: OnboardingPhase,
+    pub suggested_choices: OnboardingChoices,
+}
+
+/// Phase reached by applying `event` to `phase`, or `None` when the event does
+/// not apply there. Detection may restart from review so a rescan returns to
+/// review when it finishes; once complete, only a reset leaves the phase.
+pub fn next_phase(phase: OnboardingPhase, event: OnboardingEvent) -> Option<OnboardingPhase> {
+    use OnboardingEvent as Event;
+    use OnboardingPhase as Phase;
+
+    match (phase, event) {
+        (_, Event::Reset) => Some(Phase::NotStarted),
+        (Phase::NotStarted | Phase::Detecting | Phase::Review, Event::DetectionStarted) => {
+            Some(Phase::Detecting)
+        }
+        (Phase::Detecting, Event::DetectionFinished) => Some(Phase::Review),
+        (Phase::NotStarted | Phase::Detecting | Phase::Review, Event::Completed) => {
+            Some(Phase::Complete)
+        }
+        _ => None,
+    }
+}
+
+/// Current onboarding phase with suggested choices: every manager detection
+/// has run for is enabled exactly when it was found installed.
+pub fn onboarding_state(store: &dyn DetectionStore) -> PersistenceResult<OnboardingState> {
+    let phase = store.onboarding_phase()?;
+    let manager_enabled = store
+        .list_detections()?
+        .into_iter()
+        .map(|(manager, info)| (manager, info.installed))
+        .collect();
+    Ok(OnboardingState {
+        phase,
+        suggested_choices: OnboardingChoices {
+            manager_enabled,
+            ..OnboardingChoices::default()
+        },
+    })
+}
+
+/// Apply a detection lifecycle event, leaving the phase untouched when the
+/// event does not apply (for example detection runs after onboarding).
+pub fn record_onboarding_event(
+    store: &dyn DetectionStore,
+    event: OnboardingEvent,
+) -> PersistenceResult<OnboardingPhase> {
+    let phase = store.onboarding_phase()?;
+    match next_phase(phase, event) {
+        Some(next) if next != phase => {
+            store.set_onboarding_phase(next)?;
+            Ok(next)
+        }
+        _ => Ok(phase),
+    }
+}
+
+/// Persist the user's choices and mark onboarding complete atomically.
+pub fn complete_onboarding(
+    store: &dyn DetectionStore,
+    choices: &OnboardingChoices,
+) -> PersistenceResult<()> {
+    let phase = store.onboarding_phase()?;
+    if next_phase(phase, OnboardingEvent::Completed).is_none() {
+        return Err(CoreError {
+            manager: None,
+            task: None,
+            action: None,
+            kind: CoreErrorKind::InvalidInput,
+            message: format!("onboarding cannot complete from phase '{}'", phase.as_str()),
+        });
+    }
+    store.complete_onboarding(choices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OnboardingEvent, next_phase};
+    use crate::models::OnboardingPhase;
+
+    #[test]
+    fn detection_moves_through_review_to_complete() {
+        let detecting = next_phase(
+            OnboardingPhase::NotStarted,
+            OnboardingEvent::DetectionStarted,
+        );
+        assert_eq!(detecting, Some(OnboardingPhase::Detecting));
+        let review = next_phase(
+            OnboardingPhase::Detecting,
+            OnboardingEvent::DetectionFinished,
+        );
+        assert_eq!(review, Some(OnboardingPhase::Review));
+        assert_eq!(
+            next_phase(OnboardingPhase::Review, OnboardingEvent::DetectionStarted),
+            Some(OnboardingPhase::Detecting)
+        );
+        assert_eq!(
+            next_phase(OnboardingPhase::Review, OnboardingEvent::Completed),
+            Some(OnboardingPhase::Complete)
+        );
+    }
+
+    #[test]
+    fn complete_phase_only_leaves_on_reset() {
+        for event in [
+            OnboardingEvent::DetectionStarted,
+            OnboardingEvent::DetectionFinished,
+            OnboardingEvent::Completed,
+        ] {
+            assert_eq!(next_phase(OnboardingPhase::Complete, event), None);
+        }
+        assert_eq!(
+            next_phase(OnboardingPhase::Complete, OnboardingEvent::Reset),
+            Some(OnboardingPhase::NotStarted)
+        );
+        assert_eq!(
+            next_phase(
+                OnboardingPhase::NotStarted,
+                OnboardingEvent::DetectionFinished
+            ),
+            None
+        );
+    }
+}
@@ -15,7 +15,7 @@ use crate::models::{
 };
 use crate::orchestration::{
     CancellationMode, InMemoryAsyncTaskQueue, OrchestrationResult, TaskCancellationToken,
-    TaskOperation, TaskRuntimeSnapshot, TaskSubmission,
+    TaskOperation, TaskPriority, TaskRuntimeSnapshot, TaskSubmission,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -29,6 +29,9 @@ pub enum AdapterTaskTerminalState {
 pub struct AdapterTaskSnapshot {
     pub runtime: TaskRuntimeSnapshot,
     pub terminal_state: Option<AdapterTaskTerminalState>,
+    /// Completion percentage parsed from the running command's output, for
+    /// adapters that report it; always `None` once the task is terminal.
+    pub progress: Option<u8>,
 }
 
 type OutcomeSlot = Arc<Mutex<Option<AdapterTaskTerminalState>>>;
@@ -51,11 +54,36 @@ impl AdapterExecutionRuntime {
         }
     }
 
-    #[instrument(skip(self, adapter, request), fields(manager = ?adapter.descriptor().id))]
     pub async fn submit(
         &self,
         adapter: Arc<dyn ManagerAdapter>,
         request: AdapterRequest,
+    ) -> OrchestrationResult<TaskId> {
+        let priority = TaskPriority::for_task_type(task_type_for_request(&request));
+        self.submit_with_priority(adapter, request, priority).await
+    }
+
+    /// Like [`Self::submit`], but with an explicit priority instead of the
+    /// default for the request's task type.
+    pub async fn submit_with_priority(
+        &self,
+        adapter: Arc<dyn ManagerAdapter>,
+        request: AdapterRequest,
+        priority: TaskPriority,
+    ) -> OrchestrationResult<TaskId> {
+        self.submit_with_dependencies(adapter, request, priority, Vec::new())
+            .await
+    }
+
+    /// Submit a task that starts only after every task in `depends_on`
+    /// completed, and fails if any of them fails or is cancelled.
+    #[instrument(skip(self, adapter, request), fields(manager = ?adapter.descriptor().id))]
+    pub async fn submit_with_dependencies(
+        &self,
+        adapter: Arc<dyn ManagerAdapter>,
+        request: AdapterRequest,
+        priority: TaskPriority,
+        depends_on: Vec<TaskId>,
     ) -> OrchestrationResult<TaskId> {
         let manager = adapter.descriptor().id;
         let action = request.action();
@@ -120,7 +148,9 @@ impl AdapterExecutionRuntime {
                 TaskSubmission {
                     manager,
                     task_type,
+                    priority,
                     requested_at: SystemTime::now(),
+                    depends_on,
                 },
                 operation,
             )
@@ -139,12 +169,23 @@ impl AdapterExecutionRuntime {
         self.queue.cancel(task_id, mode).await
     }
 
+    pub async fn pending_blockers(
+        &self,
+        task_id: TaskId,
+    ) -> OrchestrationResult<Vec<TaskRuntimeSnapshot>> {
+        self.queue.pending_blockers(task_id).await
+    }
+
     pub async fn snapshot(&self, task_id: TaskId) -> OrchestrationResult<AdapterTaskSnapshot> {
         let runtime = self.queue.snapshot(task_id).await?;
         let terminal_state = self.terminal_state_for(task_id, &runtime).await?;
+        let progress = (runtime.status == TaskStatus::Running)
+            .then(|| crate::execution::task_progress(task_id))
+            .flatten();
         Ok(AdapterTaskSnapshot {
             runtime,
             terminal_state,
+            progress,
         })
     }
 
@@ -161,6 +202,7 @@ impl AdapterExecutionRuntime {
         Ok(AdapterTaskSnapshot {
             runtime,
             terminal_state,
+            progress: None,
         })
     }
 
@@ -203,14 +245,24 @@ impl AdapterExecutionRuntime {
 fn task_type_for_action(action: ManagerAction) -> TaskType {
     match action {
         ManagerAction::Detect => TaskType::Detection,
-        ManagerAction::Refresh | ManagerAction::ListInstalled | ManagerAction::ListOutdated => {
-            TaskType::Refresh
-        }
+        ManagerAction::Refresh
+        | ManagerAction::ListInstalled
+        | ManagerAction::ListOutdated
+        | ManagerAction::SelfVersionCheck
+        | ManagerAction::ListFiles
+        | ManagerAction::ListDependencies
+        | ManagerAction::ListRequested
+        | ManagerAction::PackageInfo
+        | ManagerAction::ListServices
+        | ManagerAction::PreviewCleanup
+        | ManagerAction::MeasurePackageSizes => TaskType::Refresh,
         ManagerAction::Search => TaskType::Search,
         ManagerAction::Install => TaskType::Install,
         ManagerAction::Uninstall => TaskType::Uninstall,
-        ManagerAction::Upgrade => TaskType::Upgrade,
-        ManagerAction::Configure => TaskType::Configure,
+        ManagerAction::Upgrade | ManagerAction::Rollback => TaskType::Upgrade,
+        ManagerAction::Configure | ManagerAction::SetServiceState | ManagerAction::Cleanup => {
+            TaskType::Configure
+        }
         ManagerAction::Pin => TaskType::Pin,
         ManagerAction::Unpin => TaskType::Unpin,
     }
@@ -1,30 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use serde::Serialize;
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
 use tracing::instrument;
 
 use crate::adapters::{
-    AdapterRequest, AdapterResponse, DetectRequest, ListInstalledRequest, ListOutdatedRequest,
-    ManagerAdapter,
+    AdapterRequest, AdapterResponse, DetectRequest, ListDependenciesRequest, ListInstalledRequest,
+    ListOutdatedRequest, ListRequestedRequest, ManagerAdapter, SelfVersionCheckRequest,
 };
 use crate::install_instances::collect_manager_install_instances;
 use crate::manager_dependencies::provenance_requires_manager_dependency;
 use crate::manager_policy::manager_enablement_eligibility;
 use crate::models::{
-    Capability, CoreError, CoreErrorKind, DetectionInfo, ManagerAction, ManagerId,
-    NewTaskLogRecord, TaskId, TaskLogLevel, TaskRecord, TaskStatus, TaskType,
+    Capability, CoreError, CoreErrorKind, DetectionInfo, DetectionOutcome, ManagerAction,
+    ManagerId, ManagerRefreshError, ManagerRefreshResult, NewTaskLogRecord,
+    PackageSnapshotFreshness, SearchCompletion, TaskHistoryRecord, TaskId, TaskLabel, TaskLogLevel,
+    TaskRecord, TaskStatus, TaskType,
 };
+use crate::network_policy::HeavyRefreshWork;
 use crate::orchestration::{
     AdapterExecutionRuntime, AdapterTaskSnapshot, AdapterTaskTerminalState, CancellationMode,
-    OrchestrationResult,
+    OrchestrationResult, TaskPriority, TaskRuntimeSnapshot, quarantine, refresh_scheduler,
 };
+use crate::package_dependencies::{OrphanedPackage, orphaned_packages};
 use crate::persistence::{
-    DetectionStore, ManagerPreference, PackageStore, SearchCacheStore, TaskStore,
+    DependencyStore, DetectionStore, ManagerPreference, PackageStore, PinStore, SearchCacheStore,
+    TaskStore,
 };
 use crate::post_install_setup::evaluate_manager_post_install_setup;
+use crate::reachability::ReachabilityStatus;
+use crate::snapshot_cache::PersistenceListener;
+use crate::snapshot_reconciliation::reconcile_package_snapshots;
 
 const TASK_PERSIST_RETRY_ATTEMPTS: usize = 3;
 const TASK_PERSIST_RETRY_DELAY_MS: u64 = 15;
@@ -39,6 +48,8 @@ const FAILURE_DIAGNOSTIC_SCHEMA: &str = "helm.task.failure_diagnostic";
 const FAILURE_DIAGNOSTIC_SCHEMA_VERSION: u8 = 1;
 const FAILURE_DIAGNOSTIC_COMMAND_MAX_CHARS: usize = 240;
 const FAILURE_DIAGNOSTIC_EXCERPT_MAX_CHARS: usize = 320;
+/// Bytes of each output stream kept with a task history entry.
+const TASK_HISTORY_OUTPUT_TAIL_BYTES: usize = 8 * 1024;
 
 #[derive(Clone)]
 pub struct AdapterRuntime {
@@ -48,6 +59,20 @@ pub struct AdapterRuntime {
     package_store: Option<Arc<dyn PackageStore>>,
     search_cache_store: Option<Arc<dyn SearchCacheStore>>,
     detection_store: Option<Arc<dyn DetectionStore>>,
+    dependency_store: Option<Arc<dyn DependencyStore>>,
+    pin_store: Option<Arc<dyn PinStore>>,
+    persistence_listener: Option<Arc<dyn PersistenceListener>>,
+    persistence_gate: PersistenceGate,
+}
+
+/// Task persistence holds this shared; [`AdapterRuntime::quiesce`] holds it
+/// exclusively and bumps the generation, so watchers for tasks submitted
+/// before a quiesce never write into the rebuilt stores.
+type PersistenceGate = Arc<RwLock<u64>>;
+
+/// Exclusive hold on task persistence. Submissions wait until it is dropped.
+pub struct RuntimeQuiesceGuard {
+    _gate: OwnedRwLockWriteGuard<u64>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -68,6 +93,8 @@ impl ManagerEnablementSnapshot {
 struct RefreshCapabilityPlan {
     list_installed: bool,
     list_outdated: bool,
+    self_version_check: bool,
+    list_dependencies: bool,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -130,6 +157,7 @@ const HOMEBREW_API_CACHE_PERMISSION_PROBES: [&str; 3] = [
     "ls -ld ~/Library/Caches/Homebrew ~/Library/Caches/Homebrew/api",
     "brew update --debug",
 ];
+const MAS_NOT_SIGNED_IN_PROBES: [&str; 2] = ["mas account", "open -a \"App Store\""];
 
 impl AdapterRuntime {
     pub fn new(
@@ -216,9 +244,41 @@ impl AdapterRuntime {
             package_store,
             search_cache_store,
             detection_store,
+            dependency_store: None,
+            pin_store: None,
+            persistence_listener: None,
+            persistence_gate: PersistenceGate::default(),
         })
     }
 
+    /// Notify `listener` whenever a task's package or detection data is persisted.
+    pub fn with_persistence_listener(mut self, listener: Arc<dyn PersistenceListener>) -> Self {
+        self.persistence_listener = Some(listener);
+        self
+    }
+
+    /// Persist dependency graphs reported by `ListDependencies` tasks to `store`.
+    pub fn with_dependency_store(mut self, store: Arc<dyn DependencyStore>) -> Self {
+        self.dependency_store = Some(store);
+        self
+    }
+
+    /// Hold packages virtually pinned in `store` out of upgrades that the
+    /// manager would otherwise apply to them (see `pin_enforcement`).
+    pub fn with_pin_store(mut self, store: Arc<dyn PinStore>) -> Self {
+        self.pin_store = Some(store);
+        self
+    }
+
+    /// Pause submissions and wait for in-flight task persistence to finish.
+    /// Tasks already running keep running, but stop persisting, so stores can
+    /// be rebuilt while the guard is held without rows reappearing afterwards.
+    pub async fn quiesce(&self) -> RuntimeQuiesceGuard {
+        let mut gate = self.persistence_gate.clone().write_owned().await;
+        *gate += 1;
+        RuntimeQuiesceGuard { _gate: gate }
+    }
+
     pub fn has_manager(&self, manager: ManagerId) -> bool {
         self.adapters.contains_key(&manager)
     }
@@ -230,6 +290,18 @@ impl AdapterRuntime {
             .unwrap_or(false)
     }
 
+    /// The adapter-declared estimate for `action`; `None` when the manager is
+    /// not registered or does not estimate the action.
+    pub fn estimated_action_duration(
+        &self,
+        manager: ManagerId,
+        action: ManagerAction,
+    ) -> Option<Duration> {
+        self.adapters
+            .get(&manager)
+            .and_then(|adapter| adapter.estimated_duration(action))
+    }
+
     pub fn adapter_list(&self) -> Vec<Arc<dyn ManagerAdapter>> {
         self.adapters.values().cloned().collect()
     }
@@ -291,6 +363,56 @@ impl AdapterRuntime {
         }
     }
 
+    pub fn is_offline_mode(&self) -> bool {
+        if let Some(ds) = &self.detection_store {
+            ds.offline_mode().unwrap_or(false)
+        } else {
+            false
+        }
+    }
+
+    /// Quarantined managers that background refreshes skip at `now_unix`
+    /// because their next probe is not due yet.
+    fn managers_awaiting_probe(&self, now_unix: i64) -> HashSet<ManagerId> {
+        let Some(store) = self.detection_store.as_ref() else {
+            return HashSet::new();
+        };
+        quarantine::quarantined_managers(store.as_ref())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|quarantine| !quarantine.probe_due(now_unix))
+            .map(|quarantine| quarantine.manager)
+            .collect()
+    }
+
+    fn record_refresh_outcome(&self, manager: ManagerId, result: &OrchestrationResult<()>) {
+        let Some(store) = self.detection_store.as_ref() else {
+            return;
+        };
+        match quarantine::record_refresh_outcome(
+            store.as_ref(),
+            manager,
+            result.as_ref().map(|_| ()),
+            unix_now(),
+        ) {
+            Ok(Some(quarantine)) => tracing::warn!(
+                manager = manager.as_str(),
+                consecutive_failures = quarantine.consecutive_failures,
+                next_probe_unix = quarantine.next_probe_unix,
+                reason = %quarantine.reason,
+                "manager quarantined after repeated refresh failures"
+            ),
+            Ok(None) => {}
+            Err(error) => {
+                tracing::warn!(
+                    manager = manager.as_str(),
+                    error = %error,
+                    "failed to record refresh outcome"
+                );
+            }
+        }
+    }
+
     fn manager_enablement_snapshot(&self) -> Option<Arc<ManagerEnablementSnapshot>> {
         let detection_store = self.detection_store.as_ref()?;
         let preferences = detection_store.list_manager_preferences().ok()?;
@@ -310,8 +432,49 @@ impl AdapterRuntime {
             .unwrap_or_else(|| self.is_manager_enabled(manager))
     }
 
+    /// Apply the manager's [`PinEnforcement`](crate::pin_enforcement::PinEnforcement)
+    /// to upgrade requests. Store read failures leave the request unchanged,
+    /// as before pins were enforced here.
+    fn enforce_virtual_pins(&self, manager: ManagerId, request: AdapterRequest) -> AdapterRequest {
+        let AdapterRequest::Upgrade(upgrade) = request else {
+            return request;
+        };
+        let (Some(pin_store), Some(enforcement)) = (
+            &self.pin_store,
+            crate::pin_enforcement::pin_enforcement(manager),
+        ) else {
+            return AdapterRequest::Upgrade(upgrade);
+        };
+        let Ok(pins) = pin_store.list_pins() else {
+            return AdapterRequest::Upgrade(upgrade);
+        };
+        let outdated = self
+            .package_store
+            .as_ref()
+            .and_then(|store| store.list_outdated().ok())
+            .unwrap_or_default();
+        let held = crate::pin_enforcement::held_virtual_pins(manager, &pins, &outdated);
+        if held.is_empty() {
+            return AdapterRequest::Upgrade(upgrade);
+        }
+        AdapterRequest::Upgrade(enforcement.enforce_upgrade(upgrade, &held))
+    }
+
     #[instrument(skip(self))]
     pub async fn detect_all_ordered(&self) -> Vec<(ManagerId, OrchestrationResult<()>)> {
+        self.detect_all_ordered_skipping_absent(&HashSet::new())
+            .await
+    }
+
+    /// Detect every enabled manager in authority order, except that managers
+    /// in `absent` are recorded as not installed without spawning their
+    /// detection probe. Callers pass managers a filesystem pass already ruled
+    /// out, so cold detection only runs version probes for present tools.
+    #[instrument(skip(self, absent))]
+    pub async fn detect_all_ordered_skipping_absent(
+        &self,
+        absent: &HashSet<ManagerId>,
+    ) -> Vec<(ManagerId, OrchestrationResult<()>)> {
         let adapter_refs: Vec<&dyn ManagerAdapter> =
             self.adapters.values().map(|a| a.as_ref()).collect();
         let phases = crate::orchestration::authority_order::detection_phases(&adapter_refs);
@@ -340,6 +503,11 @@ impl AdapterRuntime {
                     continue;
                 }
 
+                if absent.contains(&manager) {
+                    all_results.push((manager, self.record_absent_detection(manager).await));
+                    continue;
+                }
+
                 let runtime = self.clone();
                 let enablement_snapshot = enablement_snapshot.clone();
                 handles.push(tokio::spawn(async move {
@@ -365,11 +533,53 @@ impl AdapterRuntime {
         all_results
     }
 
+    async fn record_absent_detection(&self, manager: ManagerId) -> OrchestrationResult<()> {
+        let Some(detection_store) = self.detection_store.clone() else {
+            return Ok(());
+        };
+        let response = AdapterResponse::Detection(DetectionInfo {
+            installed: false,
+            executable_path: None,
+            version: None,
+        });
+        let outcome_store = detection_store.clone();
+        tokio::task::spawn_blocking(move || {
+            outcome_store.set_detection_outcome(manager, &DetectionOutcome::NotFound)
+        })
+        .await
+        .map_err(|join_error| CoreError {
+            manager: Some(manager),
+            task: Some(TaskType::Detection),
+            action: Some(ManagerAction::Detect),
+            kind: CoreErrorKind::Internal,
+            message: format!("detection persistence join failure: {join_error}"),
+        })??;
+        persist_detection_response(
+            detection_store,
+            &response,
+            manager,
+            TaskType::Detection,
+            ManagerAction::Detect,
+        )
+        .await?;
+        if let Some(listener) = self.persistence_listener.clone() {
+            let _ = tokio::task::spawn_blocking(move || listener.snapshot_persisted(manager)).await;
+        }
+        Ok(())
+    }
+
+    /// Refresh every enabled, detected manager in authority order, and record
+    /// each refreshed manager's outcome and duration as the latest refresh
+    /// results.
     #[instrument(skip(self))]
     pub async fn refresh_all_ordered(&self) -> Vec<(ManagerId, OrchestrationResult<()>)> {
+        let run_started_at = SystemTime::now();
         let adapter_refs: Vec<&dyn ManagerAdapter> =
             self.adapters.values().map(|a| a.as_ref()).collect();
-        let phases = crate::orchestration::authority_order::authority_phases(&adapter_refs);
+        let phases = crate::view_hint::prioritize_phases(
+            crate::orchestration::authority_order::authority_phases(&adapter_refs),
+            crate::view_hint::focused_manager(),
+        );
         let detected_by_manager: HashMap<ManagerId, bool> = self
             .detection_store
             .as_ref()
@@ -379,7 +589,12 @@ impl AdapterRuntime {
             .map(|(manager, info)| (manager, info.installed))
             .collect();
 
+        let suppress_heavy_network = crate::network_policy::suppress_heavy_network();
+        let awaiting_probe = self.managers_awaiting_probe(unix_now());
         let mut all_results = Vec::new();
+        let mut refresh_results = Vec::new();
+        let mut metered_suppressions = Vec::new();
+        let mut quarantine_skips = Vec::new();
 
         for phase in phases {
             let enablement_snapshot = self.manager_enablement_snapshot();
@@ -404,44 +619,57 @@ impl AdapterRuntime {
                     all_results.push((manager, Ok(())));
                     continue;
                 }
+                if awaiting_probe.contains(&manager) {
+                    quarantine_skips.push(manager);
+                    all_results.push((manager, Ok(())));
+                    continue;
+                }
+                if suppress_heavy_network {
+                    match crate::network_policy::heavy_refresh_work(manager) {
+                        Some(HeavyRefreshWork::Refresh) => {
+                            metered_suppressions.push(manager);
+                            all_results.push((manager, Ok(())));
+                            continue;
+                        }
+                        Some(HeavyRefreshWork::AutoUpdate) => metered_suppressions.push(manager),
+                        None => {}
+                    }
+                }
                 let capability_plan = refresh_capability_plan(adapter.as_ref());
 
                 let runtime = self.clone();
                 let enablement_snapshot = enablement_snapshot.clone();
 
                 handles.push(tokio::spawn(async move {
-                    if capability_plan.list_installed
-                        && let Err(e) = runtime
-                            .submit_refresh_request_with_enablement(
-                                manager,
-                                AdapterRequest::ListInstalled(ListInstalledRequest),
-                                enablement_snapshot.as_deref(),
-                            )
-                            .await
-                    {
-                        return vec![(manager, Err(e))];
-                    }
-
-                    if capability_plan.list_outdated
-                        && let Err(e) = runtime
-                            .submit_refresh_request_with_enablement(
-                                manager,
-                                AdapterRequest::ListOutdated(ListOutdatedRequest),
-                                enablement_snapshot.as_deref(),
-                            )
-                            .await
-                    {
-                        return vec![(manager, Err(e))];
-                    }
-
-                    vec![(manager, Ok(()))]
+                    let started = Instant::now();
+                    let result = runtime
+                        .refresh_manager_with_plan(
+                            manager,
+                            capability_plan,
+                            enablement_snapshot.as_deref(),
+                        )
+                        .await;
+                    runtime.record_refresh_outcome(manager, &result);
+                    (manager, result, started.elapsed())
                 }));
             }
 
             // Wait for all managers in this phase to complete
             for handle in handles {
                 match handle.await {
-                    Ok(results) => all_results.extend(results),
+                    Ok((manager, result, duration)) => {
+                        refresh_results.push(ManagerRefreshResult {
+                            manager,
+                            error: result.as_ref().err().map(|error| ManagerRefreshError {
+                                kind: error.kind,
+                                message: error.message.clone(),
+                            }),
+                            duration,
+                            run_started_at,
+                            finished_at: SystemTime::now(),
+                        });
+                        all_results.push((manager, result));
+                    }
                     Err(_join_error) => {
                         // JoinError means the task panicked; we still continue with other phases
                     }
@@ -449,9 +677,259 @@ impl AdapterRuntime {
             }
         }
 
+        if let Some(store) = self.detection_store.as_ref()
+            && let Err(error) = store.replace_refresh_results(&refresh_results)
+        {
+            tracing::warn!(error = %error, "failed to persist refresh results");
+        }
+        crate::network_policy::record_refresh_suppressions(metered_suppressions);
+        quarantine::record_refresh_quarantine_skips(quarantine_skips);
+
         all_results
     }
 
+    /// Refresh one manager's installed and outdated snapshots.
+    #[instrument(skip(self))]
+    pub async fn refresh_manager(&self, manager: ManagerId) -> OrchestrationResult<()> {
+        let adapter = self.adapters.get(&manager).ok_or_else(|| CoreError {
+            manager: Some(manager),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::Refresh),
+            kind: CoreErrorKind::InvalidInput,
+            message: format!("no adapter is registered for manager '{manager:?}'"),
+        })?;
+        let capability_plan = refresh_capability_plan(adapter.as_ref());
+        let result = self
+            .refresh_manager_with_plan(manager, capability_plan, None)
+            .await;
+        self.record_refresh_outcome(manager, &result);
+        result
+    }
+
+    /// Refresh every enabled, detected manager whose background refresh
+    /// schedule is due at `now_unix`. Each run is recorded before it starts, so
+    /// a tick that overlaps a slow refresh does not start it again.
+    #[instrument(skip(self))]
+    pub async fn run_due_scheduled_refreshes(
+        &self,
+        now_unix: i64,
+    ) -> Vec<(ManagerId, OrchestrationResult<()>)> {
+        let Some(store) = self.detection_store.as_ref() else {
+            return Vec::new();
+        };
+        let schedules = match refresh_scheduler::refresh_schedules(store.as_ref()) {
+            Ok(schedules) => schedules,
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to load refresh schedules");
+                return Vec::new();
+            }
+        };
+        let due = refresh_scheduler::due_refreshes(&schedules, now_unix);
+        if due.is_empty() {
+            return Vec::new();
+        }
+
+        let detected_by_manager: HashMap<ManagerId, bool> = store
+            .list_detections()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(manager, info)| (manager, info.installed))
+            .collect();
+        let enablement_snapshot = self.manager_enablement_snapshot();
+        let suppress_heavy_network = crate::network_policy::suppress_heavy_network();
+        let awaiting_probe = self.managers_awaiting_probe(now_unix);
+        let mut handles = Vec::new();
+
+        for manager in due {
+            let Some(adapter) = self.adapters.get(&manager) else {
+                continue;
+            };
+            if !self.manager_is_enabled_from_snapshot(manager, enablement_snapshot.as_deref()) {
+                continue;
+            }
+            if adapter.descriptor().supports(Capability::Detect)
+                && !detected_by_manager.get(&manager).copied().unwrap_or(false)
+            {
+                continue;
+            }
+            // Left due, so it runs once the quarantine's next probe succeeds.
+            if awaiting_probe.contains(&manager) {
+                continue;
+            }
+            // Left due, so it runs on the first tick after the network is unmetered.
+            if suppress_heavy_network
+                && crate::network_policy::heavy_refresh_work(manager)
+                    == Some(HeavyRefreshWork::Refresh)
+            {
+                continue;
+            }
+            if let Err(error) =
+                refresh_scheduler::record_scheduled_refresh(store.as_ref(), manager, now_unix)
+            {
+                tracing::warn!(
+                    manager = manager.as_str(),
+                    error = %error,
+                    "failed to record scheduled refresh; skipping it"
+                );
+                continue;
+            }
+            let capability_plan = refresh_capability_plan(adapter.as_ref());
+
+            let runtime = self.clone();
+            let enablement_snapshot = enablement_snapshot.clone();
+            handles.push(tokio::spawn(async move {
+                let result = runtime
+                    .refresh_manager_with_plan(
+                        manager,
+                        capability_plan,
+                        enablement_snapshot.as_deref(),
+                    )
+                    .await;
+                runtime.record_refresh_outcome(manager, &result);
+                (manager, result)
+            }));
+        }
+
+        let mut results = Vec::new();
+        for handle in handles {
+            if let Ok(result) = handle.await {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    /// Refresh every enabled manager whose quarantine is due for a probe at
+    /// `now_unix`. A success lifts the quarantine; a failure pushes the next
+    /// probe further out.
+    #[instrument(skip(self))]
+    pub async fn run_due_quarantine_probes(
+        &self,
+        now_unix: i64,
+    ) -> Vec<(ManagerId, OrchestrationResult<()>)> {
+        let Some(store) = self.detection_store.as_ref() else {
+            return Vec::new();
+        };
+        let quarantines = match quarantine::quarantined_managers(store.as_ref()) {
+            Ok(quarantines) => quarantines,
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to load manager quarantines");
+                return Vec::new();
+            }
+        };
+        let enablement_snapshot = self.manager_enablement_snapshot();
+        let mut handles = Vec::new();
+
+        for quarantine in quarantines {
+            let manager = quarantine.manager;
+            if !quarantine.probe_due(now_unix)
+                || !self.manager_is_enabled_from_snapshot(manager, enablement_snapshot.as_deref())
+            {
+                continue;
+            }
+            let Some(adapter) = self.adapters.get(&manager) else {
+                continue;
+            };
+            let capability_plan = refresh_capability_plan(adapter.as_ref());
+
+            let runtime = self.clone();
+            let enablement_snapshot = enablement_snapshot.clone();
+            handles.push(tokio::spawn(async move {
+                let result = runtime
+                    .refresh_manager_with_plan(
+                        manager,
+                        capability_plan,
+                        enablement_snapshot.as_deref(),
+                    )
+                    .await;
+                runtime.record_refresh_outcome(manager, &result);
+                (manager, result)
+            }));
+        }
+
+        let mut results = Vec::new();
+        for handle in handles {
+            if let Ok(result) = handle.await {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    async fn refresh_manager_with_plan(
+        &self,
+        manager: ManagerId,
+        capability_plan: RefreshCapabilityPlan,
+        enablement_snapshot: Option<&ManagerEnablementSnapshot>,
+    ) -> OrchestrationResult<()> {
+        // Offline, only local listing runs; the cached outdated snapshot stays
+        // until a refresh with network access replaces it.
+        let offline = self.is_offline_mode();
+        let runnable = |action: ManagerAction| {
+            !offline || !crate::reachability::action_requires_network(manager, action)
+        };
+        let capability_plan = RefreshCapabilityPlan {
+            list_installed: capability_plan.list_installed
+                && runnable(ManagerAction::ListInstalled),
+            list_outdated: capability_plan.list_outdated && runnable(ManagerAction::ListOutdated),
+            self_version_check: capability_plan.self_version_check
+                && runnable(ManagerAction::SelfVersionCheck),
+            list_dependencies: capability_plan.list_dependencies
+                && runnable(ManagerAction::ListDependencies),
+        };
+        if capability_plan.list_installed {
+            self.submit_refresh_request_with_enablement(
+                manager,
+                AdapterRequest::ListInstalled(ListInstalledRequest),
+                enablement_snapshot,
+            )
+            .await?;
+        }
+        if capability_plan.list_outdated {
+            self.submit_refresh_request_with_enablement(
+                manager,
+                AdapterRequest::ListOutdated(ListOutdatedRequest),
+                enablement_snapshot,
+            )
+            .await?;
+        }
+        if capability_plan.self_version_check
+            && let Err(error) = self
+                .submit_refresh_request_with_enablement(
+                    manager,
+                    AdapterRequest::SelfVersionCheck(SelfVersionCheckRequest),
+                    enablement_snapshot,
+                )
+                .await
+        {
+            // The manager's own version is advisory; never fail the package refresh over it.
+            tracing::warn!(
+                manager = ?manager,
+                kind = ?error.kind,
+                message = %error.message,
+                "manager self-version check failed"
+            );
+        }
+        if capability_plan.list_dependencies
+            && let Err(error) = self
+                .submit_refresh_request_with_enablement(
+                    manager,
+                    AdapterRequest::ListDependencies(ListDependenciesRequest),
+                    enablement_snapshot,
+                )
+                .await
+        {
+            // The dependency graph only informs uninstall warnings; keep the refresh.
+            tracing::warn!(
+                manager = ?manager,
+                kind = ?error.kind,
+                message = %error.message,
+                "dependency graph refresh failed"
+            );
+        }
+        Ok(())
+    }
+
     #[instrument(skip(self, request), fields(manager = ?manager))]
     pub async fn submit_refresh_request(
         &self,
@@ -472,6 +950,89 @@ impl AdapterRuntime {
             .await
     }
 
+    /// Find `manager`'s orphaned packages: installed only as dependencies of
+    /// packages that are gone.
+    ///
+    /// Reconciles a fresh `ListRequested` answer with the stored dependency
+    /// graph (captured now if none is stored) and the installed snapshot.
+    /// Results are in uninstall order, dependents first.
+    #[instrument(skip(self), fields(manager = ?manager))]
+    pub async fn reconcile_orphaned_packages(
+        &self,
+        manager: ManagerId,
+    ) -> OrchestrationResult<Vec<OrphanedPackage>> {
+        let (Some(package_store), Some(dependency_store)) =
+            (self.package_store.clone(), self.dependency_store.clone())
+        else {
+            return Err(CoreError {
+                manager: Some(manager),
+                task: Some(TaskType::Refresh),
+                action: Some(ManagerAction::ListRequested),
+                kind: CoreErrorKind::Internal,
+                message: "orphan reconciliation requires package and dependency stores".to_string(),
+            });
+        };
+
+        let requested = match self
+            .submit_refresh_request_response(
+                manager,
+                AdapterRequest::ListRequested(ListRequestedRequest),
+            )
+            .await?
+        {
+            AdapterResponse::RequestedPackages(names) => names,
+            _ => {
+                return Err(unexpected_response_error(
+                    manager,
+                    ManagerAction::ListRequested,
+                ));
+            }
+        };
+
+        let stored = tokio::task::spawn_blocking(move || {
+            let edges = dependency_store.list_dependency_graph(manager)?;
+            let installed = package_store.list_installed()?;
+            Ok::<_, CoreError>((edges, installed))
+        })
+        .await
+        .map_err(|join_error| CoreError {
+            manager: Some(manager),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListRequested),
+            kind: CoreErrorKind::Internal,
+            message: format!("orphan reconciliation join failure: {join_error}"),
+        })?
+        .map_err(|error| {
+            attribute_error(
+                error,
+                manager,
+                TaskType::Refresh,
+                ManagerAction::ListRequested,
+            )
+        })?;
+        let (mut edges, installed) = stored;
+        if edges.is_empty() {
+            edges = match self
+                .submit_refresh_request_response(
+                    manager,
+                    AdapterRequest::ListDependencies(ListDependenciesRequest),
+                )
+                .await?
+            {
+                AdapterResponse::Dependencies(edges) => edges,
+                _ => {
+                    return Err(unexpected_response_error(
+                        manager,
+                        ManagerAction::ListDependencies,
+                    ));
+                }
+            };
+        }
+
+        let requested = requested.into_iter().collect();
+        Ok(orphaned_packages(manager, &edges, &installed, &requested))
+    }
+
     async fn submit_refresh_request_with_enablement(
         &self,
         manager: ManagerId,
@@ -512,7 +1073,13 @@ impl AdapterRuntime {
             );
 
             let task_id = self
-                .submit_with_enablement(manager, request.clone(), enablement_snapshot)
+                .submit_with_enablement(
+                    manager,
+                    request.clone(),
+                    enablement_snapshot,
+                    None,
+                    Vec::new(),
+                )
                 .await
                 .map_err(|error| attribute_error(error, manager, task_type, action))?;
 
@@ -645,7 +1212,7 @@ impl AdapterRuntime {
                     );
                     continue;
                 }
-                Err(error) => return Err(error),
+                Err(error) => return Err(reclassify_network_failure(manager, error).await),
             }
         }
     }
@@ -656,7 +1223,49 @@ impl AdapterRuntime {
         manager: ManagerId,
         request: AdapterRequest,
     ) -> OrchestrationResult<TaskId> {
-        self.submit_with_enablement(manager, request, None).await
+        self.submit_with_enablement(manager, request, None, None, Vec::new())
+            .await
+    }
+
+    /// Submit `request` with its display label written in the same insert as
+    /// the task row, so no listing ever sees the task unlabeled.
+    #[instrument(skip(self, request, label), fields(manager = ?manager))]
+    pub async fn submit_labeled(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+        label: TaskLabel,
+    ) -> OrchestrationResult<TaskId> {
+        self.submit_with_enablement(manager, request, None, Some(label), Vec::new())
+            .await
+    }
+
+    /// Submit `request` to run only after every task in `depends_on` has
+    /// completed, e.g. a tap-qualified Homebrew install after its tap task. If any
+    /// dependency fails or is cancelled, the task fails without running.
+    #[instrument(skip(self, request), fields(manager = ?manager))]
+    pub async fn submit_after(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+        depends_on: Vec<TaskId>,
+    ) -> OrchestrationResult<TaskId> {
+        self.submit_with_enablement(manager, request, None, None, depends_on)
+            .await
+    }
+
+    /// [`Self::submit_after`] with the task's display label, written as in
+    /// [`Self::submit_labeled`].
+    #[instrument(skip(self, request, label), fields(manager = ?manager))]
+    pub async fn submit_labeled_after(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+        label: TaskLabel,
+        depends_on: Vec<TaskId>,
+    ) -> OrchestrationResult<TaskId> {
+        self.submit_with_enablement(manager, request, None, Some(label), depends_on)
+            .await
     }
 
     async fn submit_with_enablement(
@@ -664,6 +1273,8 @@ impl AdapterRuntime {
         manager: ManagerId,
         request: AdapterRequest,
         enablement_snapshot: Option<&ManagerEnablementSnapshot>,
+        label: Option<TaskLabel>,
+        depends_on: Vec<TaskId>,
     ) -> OrchestrationResult<TaskId> {
         let action = request.action();
         let task_type = task_type_for_request(&request);
@@ -694,6 +1305,19 @@ impl AdapterRuntime {
             });
         }
 
+        if let AdapterRequest::SetServiceState(service_request) = &request
+            && service_request.action.interrupts_service()
+            && self.is_safe_mode()
+        {
+            return Err(CoreError {
+                manager: Some(manager),
+                task: Some(task_type),
+                action: Some(action),
+                kind: CoreErrorKind::InvalidInput,
+                message: "safe mode blocks stopping or restarting services".to_string(),
+            });
+        }
+
         let adapter = self
             .adapters
             .get(&manager)
@@ -706,7 +1330,37 @@ impl AdapterRuntime {
                 message: format!("no adapter is registered for manager '{manager:?}'"),
             })?;
 
-        let task_id = self.execution.submit(adapter, request).await?;
+        if self.is_offline_mode() && request.requires_network(manager) {
+            return Err(CoreError {
+                manager: Some(manager),
+                task: Some(task_type),
+                action: Some(action),
+                kind: CoreErrorKind::OfflineMode,
+                message: format!("offline mode blocks network-dependent {action:?} tasks"),
+            });
+        }
+
+        if let Some(error) = network_gate_error(manager, task_type, action).await {
+            return Err(error);
+        }
+
+        let request = self.enforce_virtual_pins(manager, request);
+
+        let search_query = match &request {
+            AdapterRequest::Search(search) => Some(search.query.text.clone()),
+            _ => None,
+        };
+        let persisting = self.persistence_gate.clone().read_owned().await;
+        let generation = *persisting;
+        let task_id = self
+            .execution
+            .submit_with_dependencies(
+                adapter,
+                request,
+                TaskPriority::for_task_type(task_type),
+                depends_on.clone(),
+            )
+            .await?;
 
         if let Some(task_store) = &self.task_store {
             let record = TaskRecord {
@@ -715,6 +1369,8 @@ impl AdapterRuntime {
                 task_type,
                 status: TaskStatus::Queued,
                 created_at: SystemTime::now(),
+                attempt_of: None,
+                label,
             };
 
             if let Err(error) =
@@ -755,16 +1411,76 @@ impl AdapterRuntime {
                 );
             }
 
+            if !depends_on.is_empty() {
+                let waiting_on = depends_on
+                    .iter()
+                    .map(|dependency| format!("#{}", dependency.0))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let _ = persist_append_task_log(
+                    task_store.clone(),
+                    NewTaskLogRecord {
+                        task_id,
+                        manager,
+                        task_type,
+                        status: Some(TaskStatus::Queued),
+                        level: TaskLogLevel::Info,
+                        message: format!("waiting for dependency tasks to complete: {waiting_on}"),
+                        created_at: SystemTime::now(),
+                    },
+                    manager,
+                    task_type,
+                    action,
+                )
+                .await;
+            }
+
+            let blockers = self
+                .execution
+                .pending_blockers(task_id)
+                .await
+                .unwrap_or_default();
+            if !blockers.is_empty() {
+                let waiting_on = blockers
+                    .iter()
+                    .map(|blocker| format!("#{} ({})", blocker.id.0, blocker.manager.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let _ = persist_append_task_log(
+                    task_store.clone(),
+                    NewTaskLogRecord {
+                        task_id,
+                        manager,
+                        task_type,
+                        status: Some(TaskStatus::Queued),
+                        level: TaskLogLevel::Info,
+                        message: format!(
+                            "waiting for conflicting toolchain tasks to finish: {waiting_on}"
+                        ),
+                        created_at: SystemTime::now(),
+                    },
+                    manager,
+                    task_type,
+                    action,
+                )
+                .await;
+            }
+
             spawn_terminal_persistence_watcher(PersistenceWatcherContext {
                 execution: self.execution.clone(),
                 task_store: task_store.clone(),
                 package_store: self.package_store.clone(),
                 search_cache_store: self.search_cache_store.clone(),
                 detection_store: self.detection_store.clone(),
+                dependency_store: self.dependency_store.clone(),
+                persistence_listener: self.persistence_listener.clone(),
+                persistence_gate: self.persistence_gate.clone(),
+                generation,
                 task_id,
                 manager,
                 task_type,
                 action,
+                search_query,
             });
         }
 
@@ -779,6 +1495,14 @@ impl AdapterRuntime {
         self.execution.cancel(task_id, mode).await
     }
 
+    /// Toolchain-conflicting tasks that must finish before `task_id` starts.
+    pub async fn pending_blockers(
+        &self,
+        task_id: TaskId,
+    ) -> OrchestrationResult<Vec<TaskRuntimeSnapshot>> {
+        self.execution.pending_blockers(task_id).await
+    }
+
     pub async fn snapshot(&self, task_id: TaskId) -> OrchestrationResult<AdapterTaskSnapshot> {
         self.execution.snapshot(task_id).await
     }
@@ -836,10 +1560,15 @@ struct PersistenceWatcherContext {
     package_store: Option<Arc<dyn PackageStore>>,
     search_cache_store: Option<Arc<dyn SearchCacheStore>>,
     detection_store: Option<Arc<dyn DetectionStore>>,
+    dependency_store: Option<Arc<dyn DependencyStore>>,
+    persistence_listener: Option<Arc<dyn PersistenceListener>>,
+    persistence_gate: PersistenceGate,
+    generation: u64,
     task_id: TaskId,
     manager: ManagerId,
     task_type: TaskType,
     action: ManagerAction,
+    search_query: Option<String>,
 }
 
 fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
@@ -849,10 +1578,15 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
         package_store,
         search_cache_store,
         detection_store,
+        dependency_store,
+        persistence_listener,
+        persistence_gate,
+        generation,
         task_id,
         manager,
         task_type,
         action,
+        search_query,
     } = ctx;
 
     tokio::spawn(async move {
@@ -860,12 +1594,18 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
         for _ in 0..50 {
             if let Ok(status) = execution.status(task_id).await {
                 if status == TaskStatus::Running {
+                    let Some(_persisting) = persistence_permit(&persistence_gate, generation).await
+                    else {
+                        break;
+                    };
                     let running_record = TaskRecord {
                         id: task_id,
                         manager,
                         task_type,
                         status: TaskStatus::Running,
                         created_at: SystemTime::now(),
+                        attempt_of: None,
+                        label: None,
                     };
                     let _ = persist_update_task(
                         task_store.clone(),
@@ -922,29 +1662,102 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
             }
         };
 
+        let Some(_persisting) = persistence_permit(&persistence_gate, generation).await else {
+            crate::execution::drain_task_log_notes(task_id);
+            return;
+        };
+
+        let mut snapshot_persisted = false;
+
         // Persist task result (domain data)
-        if let Some(package_store) = package_store
-            && let Some(AdapterTaskTerminalState::Succeeded(response)) = &snapshot.terminal_state
-            && let Err(error) =
-                persist_adapter_response(package_store, response, manager, task_type, action).await
-        {
-            tracing::error!(
-                manager = ?manager,
-                task_id = task_id.0,
-                task_type = ?task_type,
-                action = ?action,
-                kind = ?error.kind,
-                message = %error.message,
-                "failed to persist adapter response data"
-            );
+        if let Some(package_store) = package_store {
+            let stage_staleness = match &snapshot.terminal_state {
+                Some(AdapterTaskTerminalState::Succeeded(response)) => {
+                    let persisted = persist_adapter_response(
+                        package_store.clone(),
+                        response,
+                        manager,
+                        task_type,
+                        action,
+                    )
+                    .await;
+                    match &persisted {
+                        Ok(()) => snapshot_persisted |= response_changes_package_snapshot(response),
+                        Err(error) => tracing::error!(
+                            manager = ?manager,
+                            task_id = task_id.0,
+                            task_type = ?task_type,
+                            action = ?action,
+                            kind = ?error.kind,
+                            message = %error.message,
+                            "failed to persist adapter response data"
+                        ),
+                    }
+                    response_snapshot_staleness(response, persisted.is_err())
+                }
+                Some(
+                    AdapterTaskTerminalState::Failed(_) | AdapterTaskTerminalState::Cancelled(_),
+                ) => action_snapshot_staleness(action),
+                None => None,
+            };
+
+            if let Some((installed_partial, outdated_partial)) = stage_staleness
+                && let Err(error) = persist_package_snapshot_freshness(
+                    package_store.clone(),
+                    manager,
+                    task_id,
+                    installed_partial,
+                    outdated_partial,
+                    task_type,
+                    action,
+                )
+                .await
+            {
+                tracing::error!(
+                    manager = ?manager,
+                    task_id = task_id.0,
+                    task_type = ?task_type,
+                    action = ?action,
+                    kind = ?error.kind,
+                    message = %error.message,
+                    "failed to persist package snapshot freshness"
+                );
+            }
+
+            if snapshot_persisted
+                && let Err(error) = persist_snapshot_reconciliation(
+                    package_store,
+                    detection_store.clone(),
+                    manager,
+                    task_type,
+                    action,
+                )
+                .await
+            {
+                tracing::error!(
+                    manager = ?manager,
+                    task_id = task_id.0,
+                    task_type = ?task_type,
+                    action = ?action,
+                    kind = ?error.kind,
+                    message = %error.message,
+                    "failed to reconcile package snapshots across managers"
+                );
+            }
         }
 
         // Persist search results to cache
         if let Some(search_cache_store) = search_cache_store
             && let Some(AdapterTaskTerminalState::Succeeded(response)) = &snapshot.terminal_state
-            && let Err(error) =
-                persist_search_response(search_cache_store, response, manager, task_type, action)
-                    .await
+            && let Err(error) = persist_search_response(
+                search_cache_store,
+                response,
+                search_query,
+                manager,
+                task_type,
+                action,
+            )
+            .await
         {
             tracing::error!(
                 manager = ?manager,
@@ -979,11 +1792,62 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
             );
         }
 
-        // Persist detection results
-        if let Some(detection_store) = detection_store
+        // Persist detection results. A probe that errored keeps the last known
+        // detection state and only records the failed outcome.
+        let detection_outcome = detection_outcome_for_snapshot(&snapshot);
+        let probe_failed = matches!(
+            detection_outcome,
+            Some(DetectionOutcome::ProbeFailed { .. })
+        );
+        if let Some(detection_store) = detection_store {
+            if let Some(outcome) = detection_outcome {
+                let outcome_store = detection_store.clone();
+                if let Ok(Err(error)) = tokio::task::spawn_blocking(move || {
+                    outcome_store.set_detection_outcome(manager, &outcome)
+                })
+                .await
+                {
+                    tracing::error!(
+                        manager = ?manager,
+                        task_id = task_id.0,
+                        kind = ?error.kind,
+                        message = %error.message,
+                        "failed to persist detection outcome"
+                    );
+                }
+            }
+
+            if !probe_failed
+                && let Some(AdapterTaskTerminalState::Succeeded(response)) =
+                    &snapshot.terminal_state
+            {
+                match persist_detection_response(
+                    detection_store,
+                    response,
+                    manager,
+                    task_type,
+                    action,
+                )
+                .await
+                {
+                    Ok(()) => snapshot_persisted |= response_changes_package_snapshot(response),
+                    Err(error) => tracing::error!(
+                        manager = ?manager,
+                        task_id = task_id.0,
+                        task_type = ?task_type,
+                        action = ?action,
+                        kind = ?error.kind,
+                        message = %error.message,
+                        "failed to persist detection data"
+                    ),
+                }
+            }
+        }
+
+        if let Some(dependency_store) = dependency_store
             && let Some(AdapterTaskTerminalState::Succeeded(response)) = &snapshot.terminal_state
             && let Err(error) =
-                persist_detection_response(detection_store, response, manager, task_type, action)
+                persist_dependency_response(dependency_store, response, manager, task_type, action)
                     .await
         {
             tracing::error!(
@@ -993,10 +1857,15 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
                 action = ?action,
                 kind = ?error.kind,
                 message = %error.message,
-                "failed to persist detection data"
+                "failed to persist dependency graph"
             );
         }
 
+        if snapshot_persisted && let Some(listener) = persistence_listener {
+            // Listeners typically rebuild from the store, so keep them off the async workers.
+            let _ = tokio::task::spawn_blocking(move || listener.snapshot_persisted(manager)).await;
+        }
+
         let updated = TaskRecord {
             id: snapshot.runtime.id,
             manager: snapshot.runtime.manager,
@@ -1005,6 +1874,8 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
             // Use terminal timestamp so retention windows for completed/failed tasks
             // are measured from completion/failure, not from original queue time.
             created_at: SystemTime::now(),
+            attempt_of: None,
+            label: None,
         };
 
         if let Err(error) = persist_update_task(
@@ -1060,6 +1931,28 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
             );
         }
 
+        let history = build_task_history_record(&snapshot, terminal_error.as_ref());
+        let history_store = task_store.clone();
+        match tokio::task::spawn_blocking(move || history_store.record_task_history(&history)).await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => tracing::warn!(
+                manager = ?manager,
+                task_id = task_id.0,
+                task_type = ?task_type,
+                action = ?action,
+                kind = ?error.kind,
+                message = %error.message,
+                "failed to persist task history"
+            ),
+            Err(join_error) => tracing::warn!(
+                manager = ?manager,
+                task_id = task_id.0,
+                error = %join_error,
+                "task history persistence join failure"
+            ),
+        }
+
         let failure_diagnostics = build_failure_diagnostic_entries(&snapshot, terminal_error);
         for diagnostic in failure_diagnostics {
             if let Err(error) = persist_append_task_log(
@@ -1091,6 +1984,49 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
             }
         }
 
+        if let Some(AdapterTaskTerminalState::Succeeded(AdapterResponse::SnapshotSync {
+            stage_failures,
+            ..
+        })) = &snapshot.terminal_state
+        {
+            for failure in stage_failures {
+                let stage = failure
+                    .action
+                    .map_or("refresh stage", ManagerAction::as_str);
+                if let Err(error) = persist_append_task_log(
+                    task_store.clone(),
+                    NewTaskLogRecord {
+                        task_id: snapshot.runtime.id,
+                        manager: snapshot.runtime.manager,
+                        task_type: snapshot.runtime.task_type,
+                        status: Some(terminal_status),
+                        level: TaskLogLevel::Warn,
+                        message: format!(
+                            "{stage} failed ({}): {}; its snapshot was left unchanged",
+                            failure.kind.code(),
+                            failure.message
+                        ),
+                        created_at: SystemTime::now(),
+                    },
+                    snapshot.runtime.manager,
+                    snapshot.runtime.task_type,
+                    action,
+                )
+                .await
+                {
+                    tracing::warn!(
+                        manager = ?manager,
+                        task_id = task_id.0,
+                        task_type = ?task_type,
+                        action = ?action,
+                        kind = ?error.kind,
+                        message = %error.message,
+                        "failed to persist refresh stage failure task log"
+                    );
+                }
+            }
+        }
+
         let supplemental_notes = crate::execution::drain_task_log_notes(snapshot.runtime.id);
         for note in supplemental_notes {
             if let Err(error) = persist_append_task_log(
@@ -1124,6 +2060,16 @@ fn spawn_terminal_persistence_watcher(ctx: PersistenceWatcherContext) {
     });
 }
 
+/// Shared hold on the persistence gate, or `None` when the runtime was
+/// quiesced after the task was submitted.
+async fn persistence_permit(
+    gate: &PersistenceGate,
+    generation: u64,
+) -> Option<OwnedRwLockReadGuard<u64>> {
+    let permit = gate.clone().read_owned().await;
+    (*permit == generation).then_some(permit)
+}
+
 async fn persist_adapter_response(
     package_store: Arc<dyn PackageStore>,
     response: &AdapterResponse,
@@ -1146,6 +2092,7 @@ async fn persist_adapter_response(
             AdapterResponse::SnapshotSync {
                 installed,
                 outdated,
+                ..
             } => {
                 if let Some(packages) = installed.as_ref() {
                     package_store.replace_installed_snapshot(manager, packages)?;
@@ -1182,8 +2129,16 @@ async fn persist_adapter_response(
                     mutation.before_version.as_deref(),
                     mutation.after_version.as_deref(),
                 ),
+                ManagerAction::Rollback => package_store.apply_rollback_result(
+                    &mutation.package,
+                    mutation.package_identifier.as_deref(),
+                    mutation.after_version.as_deref(),
+                ),
                 _ => Ok(()),
             },
+            AdapterResponse::PackageSizes(sizes) => {
+                package_store.set_installed_package_sizes(manager, &sizes)
+            }
             _ => Ok(()), // Other responses not persisted yet
         }
     })
@@ -1270,6 +2225,7 @@ async fn persist_manager_uninstall_state_reset(
 async fn persist_search_response(
     search_cache_store: Arc<dyn SearchCacheStore>,
     response: &AdapterResponse,
+    search_query: Option<String>,
     manager: ManagerId,
     task_type: TaskType,
     action: ManagerAction,
@@ -1278,7 +2234,17 @@ async fn persist_search_response(
 
     tokio::task::spawn_blocking(move || match response {
         AdapterResponse::SearchResults(results) => {
-            search_cache_store.upsert_search_results(&results)
+            search_cache_store.upsert_search_results(&results)?;
+            // Catalog syncs (empty query) track freshness through cached_at instead.
+            match search_query.filter(|query| !query.trim().is_empty()) {
+                Some(query) => search_cache_store.record_search_completion(&SearchCompletion {
+                    query,
+                    manager,
+                    result_count: results.len(),
+                    completed_at: SystemTime::now(),
+                }),
+                None => Ok(()),
+            }
         }
         _ => Ok(()),
     })
@@ -1293,6 +2259,166 @@ async fn persist_search_response(
     .map_err(|error| attribute_error(error, manager, task_type, action))
 }
 
+async fn persist_dependency_response(
+    dependency_store: Arc<dyn DependencyStore>,
+    response: &AdapterResponse,
+    manager: ManagerId,
+    task_type: TaskType,
+    action: ManagerAction,
+) -> OrchestrationResult<()> {
+    let AdapterResponse::Dependencies(edges) = response else {
+        return Ok(());
+    };
+    let edges = edges.clone();
+
+    tokio::task::spawn_blocking(move || dependency_store.replace_dependency_graph(manager, &edges))
+        .await
+        .map_err(|join_error| CoreError {
+            manager: Some(manager),
+            task: Some(task_type),
+            action: Some(action),
+            kind: CoreErrorKind::Internal,
+            message: format!("dependency persistence join failure: {join_error}"),
+        })?
+        .map_err(|error| attribute_error(error, manager, task_type, action))
+}
+
+/// Record snapshot freshness for `manager`. A list the task did not touch
+/// keeps the staleness recorded for it earlier.
+async fn persist_package_snapshot_freshness(
+    package_store: Arc<dyn PackageStore>,
+    manager: ManagerId,
+    task_id: TaskId,
+    installed_partial: Option<bool>,
+    outdated_partial: Option<bool>,
+    task_type: TaskType,
+    action: ManagerAction,
+) -> OrchestrationResult<()> {
+    tokio::task::spawn_blocking(move || {
+        let previous = if installed_partial.is_none() || outdated_partial.is_none() {
+            package_store
+                .list_package_snapshot_freshness()?
+                .into_iter()
+                .find(|freshness| freshness.manager == manager)
+        } else {
+            None
+        };
+        let installed_partial = installed_partial.unwrap_or_else(|| {
+            previous
+                .as_ref()
+                .is_some_and(|freshness| freshness.installed_partial)
+        });
+        let outdated_partial = outdated_partial.unwrap_or_else(|| {
+            previous
+                .as_ref()
+                .is_some_and(|freshness| freshness.outdated_partial)
+        });
+        package_store.record_package_snapshot_freshness(&PackageSnapshotFreshness {
+            manager,
+            task_id,
+            partial: installed_partial || outdated_partial,
+            installed_partial,
+            outdated_partial,
+            updated_at: SystemTime::now(),
+        })
+    })
+    .await
+    .map_err(|join_error| CoreError {
+        manager: Some(manager),
+        task: Some(task_type),
+        action: Some(action),
+        kind: CoreErrorKind::Internal,
+        message: format!("snapshot freshness persistence join failure: {join_error}"),
+    })?
+    .map_err(|error| attribute_error(error, manager, task_type, action))
+}
+
+async fn persist_snapshot_reconciliation(
+    package_store: Arc<dyn PackageStore>,
+    detection_store: Option<Arc<dyn DetectionStore>>,
+    manager: ManagerId,
+    task_type: TaskType,
+    action: ManagerAction,
+) -> OrchestrationResult<()> {
+    let removed = tokio::task::spawn_blocking(move || {
+        reconcile_package_snapshots(package_store.as_ref(), detection_store.as_deref(), manager)
+    })
+    .await
+    .map_err(|join_error| CoreError {
+        manager: Some(manager),
+        task: Some(task_type),
+        action: Some(action),
+        kind: CoreErrorKind::Internal,
+        message: format!("snapshot reconciliation join failure: {join_error}"),
+    })?
+    .map_err(|error| attribute_error(error, manager, task_type, action))?;
+    if removed > 0 {
+        tracing::debug!(
+            manager = ?manager,
+            removed,
+            "hid package snapshot rows owned by another manager"
+        );
+    }
+    Ok(())
+}
+
+/// Whether `response` replaces a manager's installed or outdated snapshot.
+/// Which snapshot lists a successful `response` left stale, as
+/// `(installed, outdated)`; `None` marks a list the response did not touch.
+fn response_snapshot_staleness(
+    response: &AdapterResponse,
+    persist_failed: bool,
+) -> Option<(Option<bool>, Option<bool>)> {
+    match response {
+        AdapterResponse::InstalledPackages(_) => Some((Some(persist_failed), None)),
+        AdapterResponse::OutdatedPackages(_) => Some((None, Some(persist_failed))),
+        AdapterResponse::SnapshotSync {
+            installed,
+            outdated,
+            stage_failures,
+        } => {
+            let stage_failed = |stage| {
+                stage_failures
+                    .iter()
+                    .any(|failure| failure.action == Some(stage))
+            };
+            let installed_failed = stage_failed(ManagerAction::ListInstalled);
+            let outdated_failed = stage_failed(ManagerAction::ListOutdated);
+            Some((
+                (installed.is_some() || installed_failed)
+                    .then_some(persist_failed || installed_failed),
+                (outdated.is_some() || outdated_failed)
+                    .then_some(persist_failed || outdated_failed),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Which snapshot lists a task running `action` would have replaced had it
+/// succeeded; cancelling or failing it leaves those lists partial.
+fn action_snapshot_staleness(action: ManagerAction) -> Option<(Option<bool>, Option<bool>)> {
+    match action {
+        ManagerAction::ListInstalled => Some((Some(true), None)),
+        ManagerAction::ListOutdated => Some((None, Some(true))),
+        ManagerAction::Refresh => Some((Some(true), Some(true))),
+        _ => None,
+    }
+}
+
+/// Whether persisting `response` can change package lists or manager enablement.
+fn response_changes_package_snapshot(response: &AdapterResponse) -> bool {
+    matches!(
+        response,
+        AdapterResponse::Detection(_)
+            | AdapterResponse::InstalledPackages(_)
+            | AdapterResponse::OutdatedPackages(_)
+            | AdapterResponse::SnapshotSync { .. }
+            | AdapterResponse::Mutation(_)
+            | AdapterResponse::PackageSizes(_)
+    )
+}
+
 async fn persist_detection_response(
     detection_store: Arc<dyn DetectionStore>,
     response: &AdapterResponse,
@@ -1327,6 +2453,9 @@ async fn persist_detection_response(
                 }
             }
         }
+        AdapterResponse::SelfVersion(self_version) => {
+            detection_store.set_manager_self_version(manager, &self_version)
+        }
         _ => Ok(()),
     })
     .await
@@ -1340,6 +2469,30 @@ async fn persist_detection_response(
     .map_err(|error| attribute_error(error, manager, task_type, action))
 }
 
+fn detection_outcome_for_snapshot(snapshot: &AdapterTaskSnapshot) -> Option<DetectionOutcome> {
+    // Always drain so failures from probes outside detection do not linger.
+    let probe_failure = crate::execution::take_detection_probe_failure(snapshot.runtime.id);
+    match snapshot.terminal_state.as_ref()? {
+        AdapterTaskTerminalState::Succeeded(AdapterResponse::Detection(info)) => {
+            Some(if info.installed {
+                DetectionOutcome::Installed
+            } else {
+                probe_failure.map_or(DetectionOutcome::NotFound, DetectionOutcome::probe_failed)
+            })
+        }
+        AdapterTaskTerminalState::Failed(error)
+            if snapshot.runtime.task_type == TaskType::Detection =>
+        {
+            Some(if error.kind == CoreErrorKind::NotInstalled {
+                DetectionOutcome::NotFound
+            } else {
+                DetectionOutcome::probe_failed(error.kind)
+            })
+        }
+        _ => None,
+    }
+}
+
 async fn persist_create_task(
     task_store: Arc<dyn TaskStore>,
     task_record: TaskRecord,
@@ -1435,7 +2588,7 @@ fn terminal_error_details(snapshot: &AdapterTaskSnapshot) -> Option<TaskTerminal
 
     if let Some(error) = from_terminal_state {
         return Some(TaskTerminalErrorDetails {
-            code: core_error_kind_code(error.kind).to_string(),
+            code: error.kind.code().to_string(),
             message: error.message,
         });
     }
@@ -1450,20 +2603,6 @@ fn terminal_error_details(snapshot: &AdapterTaskSnapshot) -> Option<TaskTerminal
         })
 }
 
-fn core_error_kind_code(kind: CoreErrorKind) -> &'static str {
-    match kind {
-        CoreErrorKind::NotInstalled => "not_installed",
-        CoreErrorKind::UnsupportedCapability => "unsupported_capability",
-        CoreErrorKind::InvalidInput => "invalid_input",
-        CoreErrorKind::ParseFailure => "parse_failure",
-        CoreErrorKind::Timeout => "timeout",
-        CoreErrorKind::Cancelled => "cancelled",
-        CoreErrorKind::ProcessFailure => "process_failure",
-        CoreErrorKind::StorageFailure => "storage_failure",
-        CoreErrorKind::Internal => "internal",
-    }
-}
-
 fn task_log_message_for_status(
     status: TaskStatus,
     error: Option<TaskTerminalErrorDetails>,
@@ -1619,6 +2758,17 @@ fn classify_failure_issue(manager: ManagerId, combined_text: &str) -> FailureIss
         }
     }
 
+    if manager == ManagerId::Mas && crate::adapters::mas::mentions_mas_not_signed_in(combined_text)
+    {
+        return FailureIssueClassification {
+            key: "mas.apple_account_not_signed_in",
+            owner: "local_configuration",
+            confidence: "high",
+            summary: "The App Store has no Apple Account signed in, so mas cannot install or upgrade apps.",
+            recommended_probes: &MAS_NOT_SIGNED_IN_PROBES,
+        };
+    }
+
     FailureIssueClassification {
         key: "unclassified_process_failure",
         owner: "undetermined",
@@ -1691,6 +2841,38 @@ fn fnv1a64_hex(bytes: &[u8]) -> String {
     format!("{hash:016x}")
 }
 
+fn build_task_history_record(
+    snapshot: &AdapterTaskSnapshot,
+    terminal_error: Option<&TaskTerminalErrorDetails>,
+) -> TaskHistoryRecord {
+    let output = crate::execution::task_output(snapshot.runtime.id).unwrap_or_default();
+    TaskHistoryRecord {
+        task_id: snapshot.runtime.id,
+        manager: snapshot.runtime.manager,
+        task_type: snapshot.runtime.task_type,
+        status: snapshot.runtime.status,
+        exit_code: output.exit_code,
+        error_code: terminal_error.map(|details| details.code.clone()),
+        error_message: terminal_error.map(|details| details.message.clone()),
+        duration_ms: output.duration_ms,
+        stdout_tail: output.stdout.as_deref().map(task_history_output_tail),
+        stderr_tail: output.stderr.as_deref().map(task_history_output_tail),
+        finished_at: SystemTime::now(),
+    }
+}
+
+/// Keep the end of a stream, where failures usually report their cause.
+fn task_history_output_tail(text: &str) -> String {
+    if text.len() <= TASK_HISTORY_OUTPUT_TAIL_BYTES {
+        return text.to_string();
+    }
+    let mut start = text.len() - TASK_HISTORY_OUTPUT_TAIL_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
 fn truncate_for_diagnostic(value: &str, max_chars: usize) -> String {
     let trimmed = value.trim();
     if max_chars == 0 {
@@ -1768,6 +2950,16 @@ fn attribute_error(
     }
 }
 
+fn unexpected_response_error(manager: ManagerId, action: ManagerAction) -> CoreError {
+    CoreError {
+        manager: Some(manager),
+        task: Some(TaskType::Refresh),
+        action: Some(action),
+        kind: CoreErrorKind::Internal,
+        message: format!("unexpected adapter response for {}", action.as_str()),
+    }
+}
+
 fn missing_phase_adapter_error(manager: ManagerId) -> CoreError {
     CoreError {
         manager: Some(manager),
@@ -1780,6 +2972,80 @@ fn missing_phase_adapter_error(manager: ManagerId) -> CoreError {
     }
 }
 
+/// Refuse network-dependent work up front when the manager's registry is known
+/// to be unreachable, so captive portals surface as a calm, retryable status.
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+async fn network_gate_error(
+    manager: ManagerId,
+    task_type: TaskType,
+    action: ManagerAction,
+) -> Option<CoreError> {
+    if !crate::reachability::action_requires_network(manager, action) {
+        return None;
+    }
+    let checker = crate::reachability::reachability_checker()?;
+    let status = {
+        let checker = checker.clone();
+        tokio::task::spawn_blocking(move || checker.manager_status(manager))
+            .await
+            .ok()?
+    };
+    if status != ReachabilityStatus::NetworkUnreachable {
+        return None;
+    }
+    checker.mark_awaiting_connectivity(manager);
+    Some(network_unreachable_error(manager, task_type, action))
+}
+
+/// Turn a refresh/search failure that looks network-related into
+/// `NetworkUnreachable` when a fresh probe confirms the registry is down.
+async fn reclassify_network_failure(manager: ManagerId, error: CoreError) -> CoreError {
+    let reclassifiable = matches!(
+        error.kind,
+        CoreErrorKind::Timeout | CoreErrorKind::ProcessFailure
+    ) && is_network_failure_message(&error.message);
+    if !reclassifiable {
+        return error;
+    }
+    let Some(checker) = crate::reachability::reachability_checker() else {
+        return error;
+    };
+    let status = {
+        let checker = checker.clone();
+        tokio::task::spawn_blocking(move || checker.recheck_manager(manager)).await
+    };
+    if status.ok() != Some(ReachabilityStatus::NetworkUnreachable) {
+        return error;
+    }
+    checker.mark_awaiting_connectivity(manager);
+    let (Some(task_type), Some(action)) = (error.task, error.action) else {
+        return error;
+    };
+    network_unreachable_error(manager, task_type, action)
+}
+
+fn network_unreachable_error(
+    manager: ManagerId,
+    task_type: TaskType,
+    action: ManagerAction,
+) -> CoreError {
+    CoreError {
+        manager: Some(manager),
+        task: Some(task_type),
+        action: Some(action),
+        kind: CoreErrorKind::NetworkUnreachable,
+        message: format!(
+            "registry for manager '{manager:?}' is unreachable; will retry when connectivity returns"
+        ),
+    }
+}
+
 fn reduce_detect_request_result(
     result: OrchestrationResult<AdapterResponse>,
 ) -> OrchestrationResult<()> {
@@ -1789,10 +3055,14 @@ fn reduce_detect_request_result(
 fn build_refresh_capability_plan(
     supports_list_installed: bool,
     supports_list_outdated: bool,
+    supports_self_version_check: bool,
+    supports_list_dependencies: bool,
 ) -> RefreshCapabilityPlan {
     RefreshCapabilityPlan {
         list_installed: supports_list_installed,
         list_outdated: supports_list_outdated,
+        self_version_check: supports_self_version_check,
+        list_dependencies: supports_list_dependencies,
     }
 }
 
@@ -1800,6 +3070,8 @@ fn refresh_capability_plan(adapter: &dyn ManagerAdapter) -> RefreshCapabilityPla
     build_refresh_capability_plan(
         adapter.descriptor().supports(Capability::ListInstalled),
         adapter.descriptor().supports(Capability::ListOutdated),
+        adapter.descriptor().supports(Capability::SelfVersionCheck),
+        adapter.descriptor().supports(Capability::ListDependencies),
     )
 }
 
@@ -2096,6 +3368,8 @@ fn should_retry_transient_refresh_error(
             | CoreErrorKind::UnsupportedCapability
             | CoreErrorKind::InvalidInput
             | CoreErrorKind::ParseFailure
+            | CoreErrorKind::NetworkUnreachable
+            | CoreErrorKind::OfflineMode
     ) {
         return false;
     }
@@ -2103,7 +3377,11 @@ fn should_retry_transient_refresh_error(
         return true;
     }
 
-    let normalized = error.message.to_ascii_lowercase();
+    is_network_failure_message(&error.message)
+}
+
+fn is_network_failure_message(message: &str) -> bool {
+    let normalized = message.to_ascii_lowercase();
     normalized.contains("temporary failure in name resolution")
         || normalized.contains("name or service not known")
         || normalized.contains("failed to lookup address")
@@ -2251,14 +3529,24 @@ fn log_detection_timing(
 fn task_type_for_action(action: ManagerAction) -> TaskType {
     match action {
         ManagerAction::Detect => TaskType::Detection,
-        ManagerAction::Refresh | ManagerAction::ListInstalled | ManagerAction::ListOutdated => {
-            TaskType::Refresh
-        }
+        ManagerAction::Refresh
+        | ManagerAction::ListInstalled
+        | ManagerAction::ListOutdated
+        | ManagerAction::SelfVersionCheck
+        | ManagerAction::ListFiles
+        | ManagerAction::ListDependencies
+        | ManagerAction::ListRequested
+        | ManagerAction::PackageInfo
+        | ManagerAction::ListServices
+        | ManagerAction::PreviewCleanup
+        | ManagerAction::MeasurePackageSizes => TaskType::Refresh,
         ManagerAction::Search => TaskType::Search,
         ManagerAction::Install => TaskType::Install,
         ManagerAction::Uninstall => TaskType::Uninstall,
-        ManagerAction::Upgrade => TaskType::Upgrade,
-        ManagerAction::Configure => TaskType::Configure,
+        ManagerAction::Upgrade | ManagerAction::Rollback => TaskType::Upgrade,
+        ManagerAction::Configure | ManagerAction::SetServiceState | ManagerAction::Cleanup => {
+            TaskType::Configure
+        }
         ManagerAction::Pin => TaskType::Pin,
         ManagerAction::Unpin => TaskType::Unpin,
     }
@@ -2276,12 +3564,13 @@ fn task_type_for_request(request: &AdapterRequest) -> TaskType {
 #[cfg(test)]
 mod tests {
     use super::{
-        SelectedExecutablePathUpdate, TaskTerminalErrorDetails, TaskType,
-        build_failure_diagnostic_envelope, build_manager_enablement_map,
-        build_refresh_capability_plan, classify_failure_issue, failure_fingerprint,
-        manager_uninstall_reset_targets, persist_manager_uninstall_state_reset_sync,
-        reconcile_detected_install_instances, reduce_detect_request_result, refresh_wait_budget,
-        task_type_code, task_type_for_request, truncate_for_diagnostic,
+        SelectedExecutablePathUpdate, TASK_HISTORY_OUTPUT_TAIL_BYTES, TaskTerminalErrorDetails,
+        TaskType, build_failure_diagnostic_envelope, build_manager_enablement_map,
+        build_refresh_capability_plan, classify_failure_issue, detection_outcome_for_snapshot,
+        failure_fingerprint, manager_uninstall_reset_targets, network_unreachable_error,
+        persist_manager_uninstall_state_reset_sync, reconcile_detected_install_instances,
+        reduce_detect_request_result, refresh_wait_budget, should_retry_transient_refresh_error,
+        task_history_output_tail, task_type_code, task_type_for_request, truncate_for_diagnostic,
     };
     use crate::adapters::{AdapterRequest, AdapterResponse, MutationResult, SearchRequest};
     use crate::execution::{
@@ -2289,12 +3578,12 @@ mod tests {
         set_manager_timeout_profile,
     };
     use crate::models::{
-        AutomationLevel, CoreError, CoreErrorKind, DetectionInfo, InstallInstanceIdentityKind,
-        InstallProvenance, ManagerAction, ManagerId, ManagerInstallInstance, PackageRef,
-        SearchQuery, StrategyKind, TaskId, TaskStatus,
+        AutomationLevel, CoreError, CoreErrorKind, DetectionInfo, DetectionOutcome,
+        InstallInstanceIdentityKind, InstallProvenance, ManagerAction, ManagerId,
+        ManagerInstallInstance, PackageRef, SearchQuery, StrategyKind, TaskId, TaskStatus,
     };
     use crate::orchestration::{
-        AdapterTaskSnapshot, AdapterTaskTerminalState, TaskRuntimeSnapshot,
+        AdapterTaskSnapshot, AdapterTaskTerminalState, TaskPriority, TaskRuntimeSnapshot,
     };
     use crate::persistence::{DetectionStore, ManagerPreference};
     use crate::sqlite::SqliteStore;
@@ -2355,6 +3644,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn network_unreachable_errors_have_distinct_code_and_are_not_retried() {
+        let error = network_unreachable_error(
+            ManagerId::Npm,
+            TaskType::Refresh,
+            ManagerAction::ListOutdated,
+        );
+        assert_eq!(error.kind, CoreErrorKind::NetworkUnreachable);
+        assert_eq!(error.kind.code(), "network_unreachable");
+        assert!(!should_retry_transient_refresh_error(
+            TaskType::Refresh,
+            ManagerAction::ListOutdated,
+            &error
+        ));
+
+        let process_failure = CoreError {
+            kind: CoreErrorKind::ProcessFailure,
+            message: "npm ERR! network request failed".to_string(),
+            ..error
+        };
+        assert!(should_retry_transient_refresh_error(
+            TaskType::Refresh,
+            ManagerAction::ListOutdated,
+            &process_failure
+        ));
+    }
+
     #[test]
     fn refresh_wait_timeout_uses_default_policy_when_no_override_is_set() {
         let _guard = timeout_profile_test_guard();
@@ -2433,17 +3749,21 @@ mod tests {
     #[test]
     fn build_refresh_capability_plan_reflects_support_flags() {
         assert_eq!(
-            build_refresh_capability_plan(false, true),
+            build_refresh_capability_plan(false, true, false, false),
             super::RefreshCapabilityPlan {
                 list_installed: false,
                 list_outdated: true,
+                self_version_check: false,
+                list_dependencies: false,
             }
         );
         assert_eq!(
-            build_refresh_capability_plan(true, false),
+            build_refresh_capability_plan(true, false, true, true),
             super::RefreshCapabilityPlan {
                 list_installed: true,
                 list_outdated: false,
+                self_version_check: true,
+                list_dependencies: true,
             }
         );
     }
@@ -2484,6 +3804,7 @@ mod tests {
             selected_install_method: None,
             timeout_hard_seconds: None,
             timeout_idle_seconds: None,
+            process_qos: None,
         }];
         let detections = vec![(
             ManagerId::RubyGems,
@@ -2507,6 +3828,7 @@ mod tests {
             selected_install_method: None,
             timeout_hard_seconds: None,
             timeout_idle_seconds: None,
+            process_qos: None,
         }];
         let detections = vec![(
             ManagerId::Pip,
@@ -2628,6 +3950,7 @@ mod tests {
             action: ManagerAction::Uninstall,
             before_version: None,
             after_version: None,
+            disposal: None,
         });
         persist_manager_uninstall_state_reset_sync(&store, &response, ManagerId::Asdf)
             .expect("manager uninstall reset should succeed");
@@ -2678,6 +4001,7 @@ mod tests {
                 action: ManagerAction::Uninstall,
                 before_version: None,
                 after_version: None,
+                disposal: None,
             }),
         );
         assert_eq!(targets, vec![ManagerId::Asdf]);
@@ -2694,16 +4018,89 @@ mod tests {
                 id: task_id,
                 manager,
                 task_type,
+                priority: TaskPriority::for_task_type(task_type),
                 status: TaskStatus::Failed,
                 created_at: SystemTime::now(),
                 started_at: None,
                 finished_at: None,
                 error_message: Some(error.message.clone()),
+                blocked_by: Vec::new(),
+                depends_on: Vec::new(),
             },
             terminal_state: Some(AdapterTaskTerminalState::Failed(error)),
+            progress: None,
         }
     }
 
+    #[test]
+    fn detection_outcome_separates_absent_tools_from_failed_probes() {
+        let detected = |task_id: TaskId, installed: bool| {
+            let mut snapshot = failed_snapshot(
+                task_id,
+                ManagerId::Npm,
+                TaskType::Detection,
+                CoreError {
+                    manager: None,
+                    task: None,
+                    action: None,
+                    kind: CoreErrorKind::Internal,
+                    message: String::new(),
+                },
+            );
+            snapshot.runtime.status = TaskStatus::Completed;
+            snapshot.terminal_state = Some(AdapterTaskTerminalState::Succeeded(
+                AdapterResponse::Detection(DetectionInfo {
+                    installed,
+                    executable_path: None,
+                    version: None,
+                }),
+            ));
+            snapshot
+        };
+        let failed = |task_id: TaskId, kind: CoreErrorKind| {
+            failed_snapshot(
+                task_id,
+                ManagerId::Npm,
+                TaskType::Detection,
+                CoreError {
+                    manager: Some(ManagerId::Npm),
+                    task: Some(TaskType::Detection),
+                    action: Some(ManagerAction::Detect),
+                    kind,
+                    message: "probe".to_string(),
+                },
+            )
+        };
+
+        assert_eq!(
+            detection_outcome_for_snapshot(&detected(TaskId(93_001), true)),
+            Some(DetectionOutcome::Installed)
+        );
+        assert_eq!(
+            detection_outcome_for_snapshot(&detected(TaskId(93_002), false)),
+            Some(DetectionOutcome::NotFound)
+        );
+
+        crate::execution::detection_probe_store::record(TaskId(93_003), CoreErrorKind::Timeout);
+        assert_eq!(
+            detection_outcome_for_snapshot(&detected(TaskId(93_003), false)),
+            Some(DetectionOutcome::ProbeFailed {
+                error_key: "service.error.timeout".to_string()
+            })
+        );
+
+        assert_eq!(
+            detection_outcome_for_snapshot(&failed(TaskId(93_004), CoreErrorKind::NotInstalled)),
+            Some(DetectionOutcome::NotFound)
+        );
+        assert_eq!(
+            detection_outcome_for_snapshot(&failed(TaskId(93_005), CoreErrorKind::ProcessFailure)),
+            Some(DetectionOutcome::probe_failed(
+                CoreErrorKind::ProcessFailure
+            ))
+        );
+    }
+
     #[test]
     fn classify_failure_issue_detects_homebrew_manifest_formula_signature() {
         let issue = classify_failure_issue(
@@ -2715,6 +4112,17 @@ mod tests {
         assert_eq!(issue.confidence, "high");
     }
 
+    #[test]
+    fn classify_failure_issue_detects_mas_sign_in_signature() {
+        let issue = classify_failure_issue(ManagerId::Mas, "Error: Not signed in");
+        assert_eq!(issue.key, "mas.apple_account_not_signed_in");
+        assert_eq!(issue.owner, "local_configuration");
+        assert_eq!(
+            classify_failure_issue(ManagerId::Npm, "Error: Not signed in").key,
+            "unclassified_process_failure"
+        );
+    }
+
     #[test]
     fn failure_fingerprint_is_deterministic() {
         let first = failure_fingerprint(
@@ -2766,6 +4174,7 @@ mod tests {
                 cwd: Some("/Users/test".to_string()),
                 program_path: Some("/opt/homebrew/bin/brew".to_string()),
                 path_snippet: Some("/opt/homebrew/bin:/usr/bin".to_string()),
+                process_qos: None,
                 started_at_unix_ms: None,
                 finished_at_unix_ms: None,
                 duration_ms: None,
@@ -2798,6 +4207,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn task_history_output_tail_keeps_the_end_on_a_char_boundary() {
+        let text = format!("é{}", "x".repeat(TASK_HISTORY_OUTPUT_TAIL_BYTES - 1));
+        let tail = task_history_output_tail(&text);
+        assert_eq!(tail.len(), TASK_HISTORY_OUTPUT_TAIL_BYTES - 1);
+        assert!(tail.chars().all(|character| character == 'x'));
+        assert_eq!(task_history_output_tail("short"), "short");
+    }
+
     #[test]
     fn truncate_for_diagnostic_appends_ascii_ellipsis() {
         let value = truncate_for_diagnostic("abcdef", 3);
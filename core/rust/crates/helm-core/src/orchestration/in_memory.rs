@@ -43,6 +43,8 @@ impl TaskCoordinator for InMemoryTaskCoordinator {
             task_type: submission.task_type,
             status: TaskStatus::Queued,
             created_at: submission.requested_at,
+            attempt_of: None,
+            label: None,
         };
         state.tasks.insert(task_id, record.clone());
 
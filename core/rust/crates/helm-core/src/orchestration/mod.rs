@@ -2,28 +2,67 @@ pub mod adapter_execution;
 pub mod adapter_runtime;
 pub mod authority_order;
 pub mod in_memory;
+pub mod quarantine;
+pub mod refresh_scheduler;
 pub mod runtime_queue;
+pub mod task_view;
 
 pub use adapter_execution::{
     AdapterExecutionRuntime, AdapterTaskSnapshot, AdapterTaskTerminalState,
 };
-pub use adapter_runtime::AdapterRuntime;
+pub use adapter_runtime::{AdapterRuntime, RuntimeQuiesceGuard};
 pub use in_memory::InMemoryTaskCoordinator;
+pub use quarantine::{ManagerQuarantine, QUARANTINE_FAILURE_THRESHOLD};
+pub use refresh_scheduler::{MIN_REFRESH_INTERVAL_SECS, RefreshSchedule};
 pub use runtime_queue::{
     InMemoryAsyncTaskQueue, TaskCancellationToken, TaskOperation, TaskRuntimeSnapshot,
 };
+pub use task_view::{TaskViewGrouping, TaskViewOptions, build_visible_tasks};
 
 use std::time::{Duration, SystemTime};
 
+use crate::manager_dependencies::toolchain_dependent_managers;
 use crate::models::{CoreError, ManagerId, TaskId, TaskRecord, TaskStatus, TaskType};
 
 pub type OrchestrationResult<T> = Result<T, CoreError>;
 
+/// How urgently a queued task should run. When several tasks wait on the same
+/// manager, the highest priority starts first; ties keep submission order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum TaskPriority {
+    /// Detection, refresh, and catalog sync nobody is waiting on.
+    #[default]
+    Background,
+    /// Changes the user asked for, such as installs and upgrades.
+    UserInitiated,
+    /// Work whose result the user is looking at right now, such as search.
+    UserInteractive,
+}
+
+impl TaskPriority {
+    pub fn for_task_type(task_type: TaskType) -> Self {
+        match task_type {
+            TaskType::Detection | TaskType::Refresh | TaskType::CatalogSync => Self::Background,
+            TaskType::Search => Self::UserInteractive,
+            TaskType::Install
+            | TaskType::Uninstall
+            | TaskType::Upgrade
+            | TaskType::Configure
+            | TaskType::Pin
+            | TaskType::Unpin => Self::UserInitiated,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TaskSubmission {
     pub manager: ManagerId,
     pub task_type: TaskType,
+    pub priority: TaskPriority,
     pub requested_at: SystemTime,
+    /// Tasks that must complete successfully before this one starts. If any
+    /// of them fails or is cancelled, this task fails without running.
+    pub depends_on: Vec<TaskId>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -34,6 +73,12 @@ pub enum CancellationMode {
 
 pub trait ConcurrencyPolicy: Send + Sync {
     fn can_run_together(&self, first_manager: ManagerId, second_manager: ManagerId) -> bool;
+
+    /// Whether `later` must not start until `earlier` has finished, even
+    /// though the two tasks belong to different managers.
+    fn must_wait_for(&self, _earlier: &TaskSubmission, _later: &TaskSubmission) -> bool {
+        false
+    }
 }
 
 pub struct SerialPerManagerPolicy;
@@ -44,6 +89,28 @@ impl ConcurrencyPolicy for SerialPerManagerPolicy {
     }
 }
 
+/// Serializes tasks per manager and additionally orders runtime provider
+/// upgrades against every task of a manager running on that toolchain, in
+/// whichever order they were queued.
+pub struct ToolchainConflictPolicy;
+
+impl ToolchainConflictPolicy {
+    fn upgrade_blocks(provider: &TaskSubmission, dependent: &TaskSubmission) -> bool {
+        provider.task_type == TaskType::Upgrade
+            && toolchain_dependent_managers(provider.manager).contains(&dependent.manager)
+    }
+}
+
+impl ConcurrencyPolicy for ToolchainConflictPolicy {
+    fn can_run_together(&self, first_manager: ManagerId, second_manager: ManagerId) -> bool {
+        first_manager != second_manager
+    }
+
+    fn must_wait_for(&self, earlier: &TaskSubmission, later: &TaskSubmission) -> bool {
+        Self::upgrade_blocks(earlier, later) || Self::upgrade_blocks(later, earlier)
+    }
+}
+
 pub trait TaskCoordinator: Send + Sync {
     fn enqueue(&self, submission: TaskSubmission) -> OrchestrationResult<TaskRecord>;
 
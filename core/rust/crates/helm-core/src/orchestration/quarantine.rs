@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskType};
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+/// Consecutive failed refreshes after which a manager is quarantined.
+pub const QUARANTINE_FAILURE_THRESHOLD: u32 = 3;
+
+/// Wait before the first re-probe of a quarantined manager; each failed probe
+/// doubles it, up to [`MAX_PROBE_DELAY_SECS`].
+const FIRST_PROBE_DELAY_SECS: u64 = 3_600;
+const MAX_PROBE_DELAY_SECS: u64 = 86_400;
+
+static LAST_REFRESH_QUARANTINE_SKIPS: Mutex<Vec<ManagerId>> = Mutex::new(Vec::new());
+
+/// One manager's entry in the `manager_failures` app setting. Managers whose
+/// last refresh succeeded have no entry.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredManagerFailures {
+    consecutive_failures: u32,
+    last_error_code: String,
+    last_error_message: String,
+    #[serde(default)]
+    quarantined_since_unix: Option<i64>,
+    #[serde(default)]
+    next_probe_unix: Option<i64>,
+}
+
+/// A manager whose refreshes kept failing and that background refreshes now
+/// leave alone until its next probe.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerQuarantine {
+    pub manager: ManagerId,
+    pub consecutive_failures: u32,
+    pub reason_kind: CoreErrorKind,
+    pub reason: String,
+    pub quarantined_since_unix: i64,
+    /// When a refresh may next run to check whether the manager recovered.
+    pub next_probe_unix: i64,
+}
+
+impl ManagerQuarantine {
+    pub fn probe_due(&self, now_unix: i64) -> bool {
+        self.next_probe_unix <= now_unix
+    }
+}
+
+fn probe_delay_secs(consecutive_failures: u32) -> u64 {
+    let doublings = consecutive_failures.saturating_sub(QUARANTINE_FAILURE_THRESHOLD);
+    FIRST_PROBE_DELAY_SECS
+        .saturating_mul(1u64.checked_shl(doublings).unwrap_or(u64::MAX))
+        .min(MAX_PROBE_DELAY_SECS)
+}
+
+/// Entry after a refresh of the manager finished with `outcome`. Cancelled
+/// refreshes, and refreshes refused because the registry was unreachable or
+/// offline mode is on, say nothing about the manager's health and leave it
+/// unchanged.
+fn next_failures(
+    previous: Option<StoredManagerFailures>,
+    outcome: Result<(), &CoreError>,
+    now_unix: i64,
+) -> Option<StoredManagerFailures> {
+    let error = match outcome {
+        Ok(()) => return None,
+        Err(error)
+            if matches!(
+                error.kind,
+                CoreErrorKind::Cancelled
+                    | CoreErrorKind::NetworkUnreachable
+                    | CoreErrorKind::OfflineMode
+            ) =>
+        {
+            return previous;
+        }
+        Err(error) => error,
+    };
+    let consecutive_failures = previous
+        .as_ref()
+        .map_or(0, |entry| entry.consecutive_failures)
+        .saturating_add(1);
+    let quarantined = consecutive_failures >= QUARANTINE_FAILURE_THRESHOLD;
+    Some(StoredManagerFailures {
+        consecutive_failures,
+        last_error_code: error.kind.code().to_string(),
+        last_error_message: error.message.clone(),
+        quarantined_since_unix: quarantined.then(|| {
+            previous
+                .as_ref()
+                .and_then(|entry| entry.quarantined_since_unix)
+                .unwrap_or(now_unix)
+        }),
+        next_probe_unix: quarantined
+            .then(|| now_unix.saturating_add(probe_delay_secs(consecutive_failures) as i64)),
+    })
+}
+
+fn parse_stored_failures(
+    json: Option<&str>,
+) -> PersistenceResult<BTreeMap<String, StoredManagerFailures>> {
+    let Some(json) = json else {
+        return Ok(BTreeMap::new());
+    };
+    serde_json::from_str(json).map_err(|error| CoreError {
+        manager: None,
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::Refresh),
+        kind: CoreErrorKind::ParseFailure,
+        message: format!("stored manager failures are not valid JSON: {error}"),
+    })
+}
+
+fn encode_stored_failures(
+    failures: &BTreeMap<String, StoredManagerFailures>,
+) -> PersistenceResult<Option<String>> {
+    if failures.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(failures)
+        .map(Some)
+        .map_err(|error| CoreError {
+            manager: None,
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::Refresh),
+            kind: CoreErrorKind::Internal,
+            message: format!("failed to encode manager failures: {error}"),
+        })
+}
+
+/// Apply `update` to the stored failures in one store transaction and return
+/// its result.
+fn update_stored_failures<T>(
+    store: &dyn DetectionStore,
+    update: impl FnOnce(&mut BTreeMap<String, StoredManagerFailures>) -> T,
+) -> PersistenceResult<T> {
+    let mut update = Some(update);
+    let mut result = None;
+    store.update_manager_failures_json(&mut |json| {
+        let mut stored = parse_stored_failures(json.as_deref())?;
+        if let Some(update) = update.take() {
+            result = Some(update(&mut stored));
+        }
+        encode_stored_failures(&stored)
+    })?;
+    result.ok_or_else(|| CoreError {
+        manager: None,
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::Refresh),
+        kind: CoreErrorKind::Internal,
+        message: "manager failures update did not run".to_string(),
+    })
+}
+
+fn quarantine_from_stored(
+    manager: ManagerId,
+    entry: &StoredManagerFailures,
+) -> Option<ManagerQuarantine> {
+    Some(ManagerQuarantine {
+        manager,
+        consecutive_failures: entry.consecutive_failures,
+        reason_kind: CoreErrorKind::from_code(entry.last_error_code.as_str())
+            .unwrap_or(CoreErrorKind::Internal),
+        reason: entry.last_error_message.clone(),
+        quarantined_since_unix: entry.quarantined_since_unix?,
+        next_probe_unix: entry.next_probe_unix?,
+    })
+}
+
+/// Every quarantined manager, ordered by manager id.
+///
+/// Entries for manager ids this build does not know are skipped.
+pub fn quarantined_managers(
+    store: &dyn DetectionStore,
+) -> PersistenceResult<Vec<ManagerQuarantine>> {
+    Ok(
+        parse_stored_failures(store.manager_failures_json()?.as_deref())?
+            .iter()
+            .filter_map(|(manager_id, entry)| {
+                quarantine_from_stored(manager_id.parse::<ManagerId>().ok()?, entry)
+            })
+            .collect(),
+    )
+}
+
+/// Record how a refresh of `manager` finished. A success clears its failure
+/// streak and any quarantine; the failure that reaches
+/// [`QUARANTINE_FAILURE_THRESHOLD`] quarantines it, and each later failure
+/// pushes its next probe further out. Returns the quarantine in effect
+/// afterwards.
+pub fn record_refresh_outcome(
+    store: &dyn DetectionStore,
+    manager: ManagerId,
+    outcome: Result<(), &CoreError>,
+    now_unix: i64,
+) -> PersistenceResult<Option<ManagerQuarantine>> {
+    if outcome.is_ok() && store.manager_failures_json()?.is_none() {
+        return Ok(None);
+    }
+    update_stored_failures(store, |stored| {
+        let key = manager.as_str().to_string();
+        let next = next_failures(stored.remove(&key), outcome, now_unix);
+        let quarantine = next
+            .as_ref()
+            .and_then(|entry| quarantine_from_stored(manager, entry));
+        if let Some(entry) = next {
+            stored.insert(key, entry);
+        }
+        quarantine
+    })
+}
+
+/// Forget `manager`'s failure streak so background refreshes include it
+/// again. Returns whether it was quarantined.
+pub fn release_quarantine(
+    store: &dyn DetectionStore,
+    manager: ManagerId,
+) -> PersistenceResult<bool> {
+    update_stored_failures(store, |stored| {
+        stored
+            .remove(manager.as_str())
+            .is_some_and(|entry| entry.quarantined_since_unix.is_some())
+    })
+}
+
+/// Record which managers the latest refresh-all run left out because they
+/// were quarantined and not yet due for a probe.
+pub fn record_refresh_quarantine_skips(managers: Vec<ManagerId>) {
+    *LAST_REFRESH_QUARANTINE_SKIPS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = managers;
+}
+
+pub fn last_refresh_quarantine_skips() -> Vec<ManagerId> {
+    LAST_REFRESH_QUARANTINE_SKIPS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(kind: CoreErrorKind) -> CoreError {
+        CoreError {
+            manager: Some(ManagerId::Pip),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListInstalled),
+            kind,
+            message: "python3: bad interpreter".to_string(),
+        }
+    }
+
+    #[test]
+    fn repeated_failures_quarantine_and_back_off_until_a_success() {
+        let error = failure(CoreErrorKind::ProcessFailure);
+        let mut entry = None;
+        for now in [100, 200] {
+            entry = next_failures(entry, Err(&error), now);
+        }
+        let not_yet = entry.clone().unwrap();
+        assert_eq!(not_yet.consecutive_failures, 2);
+        assert_eq!(not_yet.next_probe_unix, None);
+
+        entry = next_failures(entry, Err(&error), 300);
+        let quarantine = quarantine_from_stored(ManagerId::Pip, entry.as_ref().unwrap()).unwrap();
+        assert_eq!(quarantine.reason_kind, CoreErrorKind::ProcessFailure);
+        assert_eq!(quarantine.quarantined_since_unix, 300);
+        assert_eq!(quarantine.next_probe_unix, 300 + 3_600);
+        assert!(!quarantine.probe_due(1_000));
+        assert!(quarantine.probe_due(3_900));
+
+        // A failed probe doubles the wait; cancellation changes nothing.
+        entry = next_failures(entry, Err(&error), 4_000);
+        entry = next_failures(entry, Err(&failure(CoreErrorKind::Cancelled)), 4_100);
+        let probed = entry.clone().unwrap();
+        assert_eq!(probed.quarantined_since_unix, Some(300));
+        assert_eq!(probed.next_probe_unix, Some(4_000 + 7_200));
+
+        assert_eq!(next_failures(entry, Ok(()), 12_000), None);
+    }
+
+    #[test]
+    fn unreachable_registries_do_not_count_towards_quarantine() {
+        let offline = failure(CoreErrorKind::NetworkUnreachable);
+        let mut entry = None;
+        for now in 1..=i64::from(QUARANTINE_FAILURE_THRESHOLD) * 2 {
+            entry = next_failures(entry, Err(&offline), now);
+        }
+        assert_eq!(entry, None);
+
+        let broken = failure(CoreErrorKind::ProcessFailure);
+        entry = next_failures(entry, Err(&broken), 100);
+        entry = next_failures(entry, Err(&offline), 200);
+        assert_eq!(entry.unwrap().consecutive_failures, 1);
+    }
+
+    #[test]
+    fn probe_delay_is_capped() {
+        assert_eq!(probe_delay_secs(QUARANTINE_FAILURE_THRESHOLD), 3_600);
+        assert_eq!(probe_delay_secs(QUARANTINE_FAILURE_THRESHOLD + 4), 57_600);
+        assert_eq!(probe_delay_secs(QUARANTINE_FAILURE_THRESHOLD + 5), 86_400);
+        assert_eq!(probe_delay_secs(u32::MAX), 86_400);
+    }
+}
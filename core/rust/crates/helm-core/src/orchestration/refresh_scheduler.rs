@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, CoreErrorKind, ManagerAction, ManagerId, TaskType};
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+/// Shortest background refresh cadence a manager can be scheduled at.
+pub const MIN_REFRESH_INTERVAL_SECS: u64 = 300;
+
+/// One manager's entry in the `refresh_schedules` app setting.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredRefreshSchedule {
+    interval_secs: u64,
+    #[serde(default)]
+    last_run_unix: Option<i64>,
+}
+
+/// A manager's background refresh cadence and when it last refreshed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RefreshSchedule {
+    pub manager: ManagerId,
+    pub interval_secs: u64,
+    /// Latest of the last scheduled refresh and the manager's result in the
+    /// last refresh-all run, so a UI-triggered refresh pushes the next run out.
+    pub last_refreshed_unix: Option<i64>,
+}
+
+impl RefreshSchedule {
+    /// Due time from wall-clock time only, matching the auto-check schedule:
+    /// a host that slept past it sees the run as overdue, and a last refresh
+    /// in the future (clock moved backwards) makes the run due immediately.
+    pub fn next_run_unix(&self, now_unix: i64) -> i64 {
+        self.last_refreshed_unix
+            .filter(|last_refreshed| *last_refreshed <= now_unix)
+            .map_or(now_unix, |last_refreshed| {
+                last_refreshed.saturating_add(self.interval_secs as i64)
+            })
+    }
+
+    pub fn is_due(&self, now_unix: i64) -> bool {
+        self.next_run_unix(now_unix) <= now_unix
+    }
+}
+
+fn invalid_schedule_error(manager: Option<ManagerId>, message: String) -> CoreError {
+    CoreError {
+        manager,
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::Refresh),
+        kind: CoreErrorKind::InvalidInput,
+        message,
+    }
+}
+
+fn load_stored_schedules(
+    store: &dyn DetectionStore,
+) -> PersistenceResult<BTreeMap<String, StoredRefreshSchedule>> {
+    let Some(json) = store.refresh_schedules_json()? else {
+        return Ok(BTreeMap::new());
+    };
+    serde_json::from_str(json.as_str()).map_err(|error| CoreError {
+        manager: None,
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::Refresh),
+        kind: CoreErrorKind::ParseFailure,
+        message: format!("stored refresh schedules are not valid JSON: {error}"),
+    })
+}
+
+fn save_stored_schedules(
+    store: &dyn DetectionStore,
+    schedules: &BTreeMap<String, StoredRefreshSchedule>,
+) -> PersistenceResult<()> {
+    if schedules.is_empty() {
+        return store.set_refresh_schedules_json(None);
+    }
+    let json = serde_json::to_string(schedules).map_err(|error| CoreError {
+        manager: None,
+        task: Some(TaskType::Refresh),
+        action: Some(ManagerAction::Refresh),
+        kind: CoreErrorKind::Internal,
+        message: format!("failed to encode refresh schedules: {error}"),
+    })?;
+    store.set_refresh_schedules_json(Some(json.as_str()))
+}
+
+/// Every configured refresh schedule, ordered by manager id.
+///
+/// Entries for manager ids this build does not know are skipped.
+pub fn refresh_schedules(store: &dyn DetectionStore) -> PersistenceResult<Vec<RefreshSchedule>> {
+    let stored = load_stored_schedules(store)?;
+    if stored.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut refresh_all_finished: HashMap<ManagerId, i64> = HashMap::new();
+    for result in store.list_refresh_results()? {
+        let Ok(finished) = result.finished_at.duration_since(UNIX_EPOCH) else {
+            continue;
+        };
+        refresh_all_finished.insert(result.manager, finished.as_secs() as i64);
+    }
+
+    Ok(stored
+        .into_iter()
+        .filter_map(|(manager_id, entry)| {
+            let manager = manager_id.parse::<ManagerId>().ok()?;
+            let last_refreshed_unix = entry
+                .last_run_unix
+                .max(refresh_all_finished.get(&manager).copied());
+            Some(RefreshSchedule {
+                manager,
+                interval_secs: entry.interval_secs,
+                last_refreshed_unix,
+            })
+        })
+        .collect())
+}
+
+/// Refresh `manager` in the background every `interval_secs`; `0` removes its
+/// schedule. Changing the interval keeps the last scheduled run, so the next
+/// run moves relative to it rather than firing immediately.
+pub fn set_refresh_schedule(
+    store: &dyn DetectionStore,
+    manager: ManagerId,
+    interval_secs: u64,
+) -> PersistenceResult<()> {
+    if interval_secs != 0 && interval_secs < MIN_REFRESH_INTERVAL_SECS {
+        return Err(invalid_schedule_error(
+            Some(manager),
+            format!(
+                "refresh interval must be at least {MIN_REFRESH_INTERVAL_SECS} seconds, got {interval_secs}"
+            ),
+        ));
+    }
+
+    let mut stored = load_stored_schedules(store)?;
+    let key = manager.as_str().to_string();
+    if interval_secs == 0 {
+        if stored.remove(&key).is_none() {
+            return Ok(());
+        }
+    } else {
+        let last_run_unix = stored.get(&key).and_then(|entry| entry.last_run_unix);
+        stored.insert(
+            key,
+            StoredRefreshSchedule {
+                interval_secs,
+                last_run_unix,
+            },
+        );
+    }
+    save_stored_schedules(store, &stored)
+}
+
+/// Record that a scheduled refresh of `manager` started at `ran_at_unix`.
+/// Managers without a schedule are left unscheduled.
+pub fn record_scheduled_refresh(
+    store: &dyn DetectionStore,
+    manager: ManagerId,
+    ran_at_unix: i64,
+) -> PersistenceResult<()> {
+    let mut stored = load_stored_schedules(store)?;
+    let Some(entry) = stored.get_mut(manager.as_str()) else {
+        return Ok(());
+    };
+    entry.last_run_unix = Some(ran_at_unix);
+    save_stored_schedules(store, &stored)
+}
+
+/// Managers whose schedule is due at `now_unix`, soonest-overdue first.
+pub fn due_refreshes(schedules: &[RefreshSchedule], now_unix: i64) -> Vec<ManagerId> {
+    let mut due: Vec<&RefreshSchedule> = schedules
+        .iter()
+        .filter(|schedule| schedule.is_due(now_unix))
+        .collect();
+    due.sort_by_key(|schedule| schedule.next_run_unix(now_unix));
+    due.into_iter().map(|schedule| schedule.manager).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(
+        manager: ManagerId,
+        interval_secs: u64,
+        last_refreshed_unix: Option<i64>,
+    ) -> RefreshSchedule {
+        RefreshSchedule {
+            manager,
+            interval_secs,
+            last_refreshed_unix,
+        }
+    }
+
+    #[test]
+    fn next_run_follows_the_last_refresh_on_the_wall_clock() {
+        let hourly = schedule(ManagerId::HomebrewFormula, 3_600, Some(1_000));
+        assert_eq!(hourly.next_run_unix(2_000), 4_600);
+        assert!(!hourly.is_due(2_000));
+        assert!(hourly.is_due(90_000));
+
+        let never = schedule(ManagerId::HomebrewFormula, 3_600, None);
+        assert_eq!(never.next_run_unix(2_000), 2_000);
+        assert!(never.is_due(2_000));
+
+        let clock_moved_back = schedule(ManagerId::HomebrewFormula, 3_600, Some(9_000));
+        assert!(clock_moved_back.is_due(5_000));
+    }
+
+    #[test]
+    fn due_refreshes_orders_the_most_overdue_first() {
+        let schedules = [
+            schedule(ManagerId::HomebrewFormula, 3_600, Some(10_000)),
+            schedule(ManagerId::SoftwareUpdate, 86_400, Some(0)),
+            schedule(ManagerId::Npm, 3_600, Some(5_000)),
+        ];
+        assert_eq!(due_refreshes(&schedules, 12_000), vec![ManagerId::Npm]);
+        assert_eq!(
+            due_refreshes(&schedules, 90_000),
+            vec![
+                ManagerId::Npm,
+                ManagerId::HomebrewFormula,
+                ManagerId::SoftwareUpdate
+            ]
+        );
+    }
+}
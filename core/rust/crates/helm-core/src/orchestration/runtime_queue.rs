@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -10,7 +11,10 @@ use tokio::task::AbortHandle;
 use tokio::time::timeout;
 
 use crate::models::{CoreError, CoreErrorKind, ManagerId, TaskId, TaskStatus, TaskType};
-use crate::orchestration::{CancellationMode, OrchestrationResult, TaskSubmission};
+use crate::orchestration::{
+    CancellationMode, ConcurrencyPolicy, OrchestrationResult, TaskPriority, TaskSubmission,
+    ToolchainConflictPolicy,
+};
 
 const WAIT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
 const WAIT_HEARTBEAT_LOG_EVERY: u64 = 5;
@@ -47,23 +51,122 @@ pub struct TaskRuntimeSnapshot {
     pub id: TaskId,
     pub manager: ManagerId,
     pub task_type: TaskType,
+    pub priority: TaskPriority,
     pub status: TaskStatus,
     pub created_at: SystemTime,
     pub started_at: Option<SystemTime>,
     pub finished_at: Option<SystemTime>,
     pub error_message: Option<String>,
+    /// Tasks of other managers that were still pending when this one was
+    /// queued and that the concurrency policy orders ahead of it.
+    pub blocked_by: Vec<TaskId>,
+    /// Tasks this one was submitted to run after; see
+    /// [`TaskSubmission::depends_on`].
+    pub depends_on: Vec<TaskId>,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct InMemoryAsyncTaskQueue {
     inner: Arc<Mutex<QueueState>>,
+    policy: Arc<dyn ConcurrencyPolicy>,
+}
+
+impl Default for InMemoryAsyncTaskQueue {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(QueueState::default())),
+            policy: Arc::new(ToolchainConflictPolicy),
+        }
+    }
+}
+
+/// Admits one task per manager at a time. Waiting tasks are admitted highest
+/// priority first, in arrival order within a priority, so a user's install
+/// does not sit behind every refresh queued for the same manager.
+#[derive(Default)]
+struct ManagerGate {
+    state: std::sync::Mutex<ManagerGateState>,
+    released: Notify,
+}
+
+#[derive(Default)]
+struct ManagerGateState {
+    held: bool,
+    next_ticket: u64,
+    waiting: BTreeSet<GateTicket>,
+}
+
+type GateTicket = (Reverse<TaskPriority>, u64);
+
+impl ManagerGate {
+    fn gate_state(&self) -> std::sync::MutexGuard<'_, ManagerGateState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn acquire(self: &Arc<Self>, priority: TaskPriority) -> ManagerGateGuard {
+        let ticket = {
+            let mut state = self.gate_state();
+            let ticket = (Reverse(priority), state.next_ticket);
+            state.next_ticket = state.next_ticket.wrapping_add(1);
+            state.waiting.insert(ticket);
+            ticket
+        };
+        // Withdraws the ticket if the task is aborted while waiting.
+        let mut waiter = GateWaiter {
+            gate: self.clone(),
+            ticket: Some(ticket),
+        };
+
+        loop {
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            {
+                let mut state = self.gate_state();
+                if !state.held && state.waiting.first() == Some(&ticket) {
+                    state.waiting.remove(&ticket);
+                    state.held = true;
+                    waiter.ticket = None;
+                    return ManagerGateGuard { gate: self.clone() };
+                }
+            }
+            released.await;
+        }
+    }
+}
+
+struct GateWaiter {
+    gate: Arc<ManagerGate>,
+    ticket: Option<GateTicket>,
+}
+
+impl Drop for GateWaiter {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket.take() {
+            self.gate.gate_state().waiting.remove(&ticket);
+            self.gate.released.notify_waiters();
+        }
+    }
+}
+
+struct ManagerGateGuard {
+    gate: Arc<ManagerGate>,
+}
+
+impl Drop for ManagerGateGuard {
+    fn drop(&mut self) {
+        self.gate.gate_state().held = false;
+        self.gate.released.notify_waiters();
+    }
 }
 
 #[derive(Default)]
 struct QueueState {
     next_task_id: u64,
     tasks: HashMap<TaskId, TaskRuntimeSnapshot>,
-    manager_locks: HashMap<ManagerId, Arc<Mutex<()>>>,
+    manager_gates: HashMap<ManagerId, Arc<ManagerGate>>,
     cancellation_flags: HashMap<TaskId, Arc<AtomicBool>>,
     abort_handles: HashMap<TaskId, AbortHandle>,
     completion_notifiers: HashMap<TaskId, Arc<Notify>>,
@@ -80,37 +183,71 @@ impl InMemoryAsyncTaskQueue {
                 next_task_id: start,
                 ..QueueState::default()
             })),
+            ..Self::default()
         }
     }
 
+    pub fn with_policy(mut self, policy: Arc<dyn ConcurrencyPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
     pub async fn spawn(
         &self,
         submission: TaskSubmission,
         operation: TaskOperation,
     ) -> OrchestrationResult<TaskId> {
-        let (task_id, manager_lock, cancel_flag, completion_notify) = {
+        let (task_id, manager_gate, cancel_flag, completion_notify, blocked_by) = {
             let mut state = self.inner.lock().await;
+            if let Some(unknown) = submission
+                .depends_on
+                .iter()
+                .find(|dependency| !state.tasks.contains_key(dependency))
+            {
+                return Err(task_lookup_error(*unknown));
+            }
             let task_id = TaskId(state.next_task_id);
             state.next_task_id = state.next_task_id.saturating_add(1);
 
+            let mut blocked_by: Vec<TaskId> = state
+                .tasks
+                .values()
+                .filter(|task| !is_terminal(task.status))
+                .filter(|task| {
+                    let earlier = TaskSubmission {
+                        manager: task.manager,
+                        task_type: task.task_type,
+                        priority: task.priority,
+                        requested_at: task.created_at,
+                        depends_on: Vec::new(),
+                    };
+                    self.policy.must_wait_for(&earlier, &submission)
+                })
+                .map(|task| task.id)
+                .collect();
+            blocked_by.sort_by_key(|id| id.0);
+
             state.tasks.insert(
                 task_id,
                 TaskRuntimeSnapshot {
                     id: task_id,
                     manager: submission.manager,
                     task_type: submission.task_type,
+                    priority: submission.priority,
                     status: TaskStatus::Queued,
                     created_at: submission.requested_at,
                     started_at: None,
                     finished_at: None,
                     error_message: None,
+                    blocked_by: blocked_by.clone(),
+                    depends_on: submission.depends_on.clone(),
                 },
             );
 
-            let manager_lock = state
-                .manager_locks
+            let manager_gate = state
+                .manager_gates
                 .entry(submission.manager)
-                .or_insert_with(|| Arc::new(Mutex::new(())))
+                .or_default()
                 .clone();
             let cancel_flag = Arc::new(AtomicBool::new(false));
             let completion_notify = Arc::new(Notify::new());
@@ -122,13 +259,47 @@ impl InMemoryAsyncTaskQueue {
                 .completion_notifiers
                 .insert(task_id, completion_notify.clone());
 
-            (task_id, manager_lock, cancel_flag, completion_notify)
+            (
+                task_id,
+                manager_gate,
+                cancel_flag,
+                completion_notify,
+                blocked_by,
+            )
         };
 
+        if !blocked_by.is_empty() {
+            tracing::debug!(
+                task_id = task_id.0,
+                manager = ?submission.manager,
+                task_type = ?submission.task_type,
+                blocked_by = ?blocked_by.iter().map(|id| id.0).collect::<Vec<_>>(),
+                "task ordered behind conflicting toolchain tasks"
+            );
+        }
+
         let inner = self.inner.clone();
+        let priority = submission.priority;
+        let depends_on = submission.depends_on;
         let token = TaskCancellationToken::new(cancel_flag);
         let join_handle = tokio::spawn(async move {
-            let _manager_guard = manager_lock.lock().await;
+            for dependency in depends_on {
+                wait_until_terminal(&inner, dependency).await;
+                if let Some(message) = failed_dependency_message(&inner, dependency).await {
+                    // A task cancelled while waiting stays cancelled.
+                    if !token.is_cancelled() {
+                        set_terminal(&inner, task_id, TaskStatus::Failed, Some(message)).await;
+                    }
+                    finalize_cleanup(&inner, task_id, &completion_notify).await;
+                    return;
+                }
+            }
+
+            for blocker in blocked_by {
+                wait_until_terminal(&inner, blocker).await;
+            }
+
+            let _manager_guard = manager_gate.acquire(priority).await;
 
             if !set_running_if_possible(&inner, task_id).await {
                 finalize_cleanup(&inner, task_id, &completion_notify).await;
@@ -179,6 +350,28 @@ impl InMemoryAsyncTaskQueue {
         Ok(self.snapshot(task_id).await?.status)
     }
 
+    /// Conflicting tasks `task_id` is still waiting on, oldest first.
+    pub async fn pending_blockers(
+        &self,
+        task_id: TaskId,
+    ) -> OrchestrationResult<Vec<TaskRuntimeSnapshot>> {
+        let state = self.inner.lock().await;
+        let task = state
+            .tasks
+            .get(&task_id)
+            .ok_or_else(|| task_lookup_error(task_id))?;
+        if is_terminal(task.status) {
+            return Ok(Vec::new());
+        }
+        Ok(task
+            .blocked_by
+            .iter()
+            .filter_map(|blocker| state.tasks.get(blocker))
+            .filter(|blocker| !is_terminal(blocker.status))
+            .cloned()
+            .collect())
+    }
+
     pub async fn cancel(&self, task_id: TaskId, mode: CancellationMode) -> OrchestrationResult<()> {
         let (abort_handle, notify, prior_status, manager, task_type) = {
             let mut state = self.inner.lock().await;
@@ -389,6 +582,7 @@ impl InMemoryAsyncTaskQueue {
             state.cancellation_flags.remove(&task_id);
             state.completion_notifiers.get(&task_id).cloned()
         };
+        release_task_temp_dir(task_id);
 
         if let Some(notify) = notify {
             notify.notify_waiters();
@@ -396,6 +590,36 @@ impl InMemoryAsyncTaskQueue {
     }
 }
 
+async fn wait_until_terminal(inner: &Arc<Mutex<QueueState>>, task_id: TaskId) {
+    loop {
+        let Some(notify) = inner
+            .lock()
+            .await
+            .completion_notifiers
+            .get(&task_id)
+            .cloned()
+        else {
+            return;
+        };
+        // Register before re-checking status so a completion landing in
+        // between still wakes this waiter.
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let pending = inner
+            .lock()
+            .await
+            .tasks
+            .get(&task_id)
+            .is_some_and(|task| !is_terminal(task.status));
+        if !pending {
+            return;
+        }
+        notified.await;
+    }
+}
+
 async fn set_running_if_possible(inner: &Arc<Mutex<QueueState>>, task_id: TaskId) -> bool {
     let mut state = inner.lock().await;
     let Some(task) = state.tasks.get_mut(&task_id) else {
@@ -409,6 +633,22 @@ async fn set_running_if_possible(inner: &Arc<Mutex<QueueState>>, task_id: TaskId
     true
 }
 
+/// Why `task_id` cannot be depended on, once it is terminal: `None` when it
+/// completed.
+async fn failed_dependency_message(
+    inner: &Arc<Mutex<QueueState>>,
+    dependency: TaskId,
+) -> Option<String> {
+    let state = inner.lock().await;
+    let task = state.tasks.get(&dependency)?;
+    let outcome = match task.status {
+        TaskStatus::Completed => return None,
+        TaskStatus::Cancelled => "was cancelled",
+        _ => "failed",
+    };
+    Some(format!("dependency task #{} {outcome}", dependency.0))
+}
+
 async fn set_terminal(
     inner: &Arc<Mutex<QueueState>>,
     task_id: TaskId,
@@ -437,9 +677,18 @@ async fn finalize_cleanup(inner: &Arc<Mutex<QueueState>>, task_id: TaskId, notif
         state.abort_handles.remove(&task_id);
         state.cancellation_flags.remove(&task_id);
     }
+    release_task_temp_dir(task_id);
     notify.notify_waiters();
 }
 
+/// Delete the task's scratch directory off the async workers; it may hold
+/// a large download.
+fn release_task_temp_dir(task_id: TaskId) {
+    tokio::task::spawn_blocking(move || {
+        crate::execution::task_temp_dir_store::remove(task_id);
+    });
+}
+
 fn is_terminal(status: TaskStatus) -> bool {
     matches!(
         status,
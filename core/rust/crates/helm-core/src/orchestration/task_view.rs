@@ -0,0 +1,300 @@
+//! Shaping of the task list shown to users: in-flight duplicates collapse to
+//! one row, failures already superseded by a retry are hidden, and terminal
+//! history is capped. The same policy backs the app, CLI, and engine task
+//! lists; its parameters are the `task_view_*` settings.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::models::{CoreError, CoreErrorKind, ManagerId, TaskRecord, TaskStatus};
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+pub const DEFAULT_TASK_VIEW_HISTORY_LIMIT: usize = 50;
+pub const MAX_TASK_VIEW_HISTORY_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TaskViewGrouping {
+    /// Newest first, as listed by the store.
+    #[default]
+    None,
+    /// Rows of the same manager kept together, managers ordered by their
+    /// newest task.
+    Manager,
+}
+
+impl TaskViewGrouping {
+    pub const ALL: [TaskViewGrouping; 2] = [TaskViewGrouping::None, TaskViewGrouping::Manager];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskViewGrouping::None => "none",
+            TaskViewGrouping::Manager => "manager",
+        }
+    }
+}
+
+impl FromStr for TaskViewGrouping {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        TaskViewGrouping::ALL
+            .into_iter()
+            .find(|grouping| grouping.as_str() == value.trim())
+            .ok_or_else(|| CoreError {
+                manager: None,
+                task: None,
+                action: None,
+                kind: CoreErrorKind::InvalidInput,
+                message: format!("unknown task view grouping '{}'", value.trim()),
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TaskViewOptions {
+    /// Terminal rows kept after in-flight ones.
+    pub history_limit: usize,
+    /// Collapse queued/running tasks with the same signature to one row and
+    /// hide failures of a signature that has a newer row.
+    pub dedupe_inflight: bool,
+    pub grouping: TaskViewGrouping,
+}
+
+impl Default for TaskViewOptions {
+    fn default() -> Self {
+        Self {
+            history_limit: DEFAULT_TASK_VIEW_HISTORY_LIMIT,
+            dedupe_inflight: true,
+            grouping: TaskViewGrouping::None,
+        }
+    }
+}
+
+impl TaskViewOptions {
+    pub fn from_store(store: &dyn DetectionStore) -> PersistenceResult<Self> {
+        Ok(Self {
+            history_limit: (store.task_view_history_limit()? as usize)
+                .min(MAX_TASK_VIEW_HISTORY_LIMIT),
+            dedupe_inflight: store.task_view_dedupe_inflight()?,
+            grouping: store.task_view_grouping()?.parse().unwrap_or_default(),
+        })
+    }
+}
+
+pub fn is_inflight_status(status: TaskStatus) -> bool {
+    matches!(status, TaskStatus::Queued | TaskStatus::Running)
+}
+
+/// Identity of what a task does: manager, type, and label with its args.
+pub fn task_signature_key(task: &TaskRecord) -> String {
+    task.label
+        .as_ref()
+        .map(|label| {
+            let mut encoded = format!("{:?}:{:?}:{}", task.manager, task.task_type, label.key);
+            for (arg_key, arg_value) in &label.args {
+                encoded.push('|');
+                encoded.push_str(arg_key);
+                encoded.push('=');
+                encoded.push_str(arg_value);
+            }
+            encoded
+        })
+        .unwrap_or_else(|| format!("{:?}:{:?}", task.manager, task.task_type))
+}
+
+fn should_replace_visible_inflight_task(current: &TaskRecord, candidate: &TaskRecord) -> bool {
+    let current_running = current.status == TaskStatus::Running;
+    let candidate_running = candidate.status == TaskStatus::Running;
+
+    if current_running != candidate_running {
+        return candidate_running;
+    }
+
+    candidate.id.0 > current.id.0
+}
+
+/// Shape `tasks`, listed newest first, into the rows a task list shows.
+pub fn build_visible_tasks(tasks: Vec<TaskRecord>, options: &TaskViewOptions) -> Vec<TaskRecord> {
+    let mut visible: Vec<TaskRecord> = Vec::with_capacity(tasks.len());
+    let mut seen_inflight: HashMap<String, usize> = HashMap::new();
+    let mut seen_signatures: HashSet<String> = HashSet::new();
+    let mut terminal_count = 0usize;
+
+    for task in tasks {
+        if is_inflight_status(task.status) {
+            if !options.dedupe_inflight {
+                visible.push(task);
+                continue;
+            }
+            let signature = task_signature_key(&task);
+            if let Some(existing_index) = seen_inflight.get(&signature).copied() {
+                if should_replace_visible_inflight_task(&visible[existing_index], &task) {
+                    visible[existing_index] = task;
+                }
+            } else {
+                seen_inflight.insert(signature.clone(), visible.len());
+                visible.push(task);
+            }
+            seen_signatures.insert(signature);
+            continue;
+        }
+
+        if options.dedupe_inflight {
+            let signature = task_signature_key(&task);
+            if task.status == TaskStatus::Failed && seen_signatures.contains(&signature) {
+                continue;
+            }
+            seen_signatures.insert(signature);
+        }
+
+        if terminal_count < options.history_limit {
+            visible.push(task);
+            terminal_count = terminal_count.saturating_add(1);
+        }
+    }
+
+    if options.grouping == TaskViewGrouping::Manager {
+        let mut manager_rank: HashMap<ManagerId, usize> = HashMap::new();
+        for task in &visible {
+            let next_rank = manager_rank.len();
+            manager_rank.entry(task.manager).or_insert(next_rank);
+        }
+        visible.sort_by_key(|task| manager_rank[&task.manager]);
+    }
+
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::{TaskViewGrouping, TaskViewOptions, build_visible_tasks};
+    use crate::models::{ManagerId, TaskId, TaskLabel, TaskRecord, TaskStatus, TaskType};
+
+    fn task(id: u64, manager: ManagerId, task_type: TaskType, status: TaskStatus) -> TaskRecord {
+        TaskRecord {
+            id: TaskId(id),
+            manager,
+            task_type,
+            status,
+            created_at: SystemTime::now(),
+            attempt_of: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn build_visible_tasks_deduplicates_inflight_rows_by_manager_and_type() {
+        let tasks = vec![
+            task(
+                10,
+                ManagerId::HomebrewFormula,
+                TaskType::Refresh,
+                TaskStatus::Running,
+            ),
+            task(
+                9,
+                ManagerId::HomebrewFormula,
+                TaskType::Refresh,
+                TaskStatus::Queued,
+            ),
+            task(8, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Queued),
+            task(7, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Running),
+        ];
+
+        let visible = build_visible_tasks(tasks, &TaskViewOptions::default());
+        assert_eq!(visible.len(), 2);
+        assert_eq!(visible[0].id, TaskId(10));
+        assert_eq!(visible[1].id, TaskId(7));
+    }
+
+    #[test]
+    fn build_visible_tasks_prefers_newer_inflight_row_when_status_matches() {
+        let tasks = vec![
+            task(21, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Queued),
+            task(22, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Queued),
+        ];
+
+        let visible = build_visible_tasks(tasks, &TaskViewOptions::default());
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].id, TaskId(22));
+    }
+
+    #[test]
+    fn build_visible_tasks_keeps_terminal_history_bounded() {
+        let tasks = (0..60)
+            .map(|idx| {
+                task(
+                    idx,
+                    ManagerId::HomebrewFormula,
+                    TaskType::Refresh,
+                    TaskStatus::Completed,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let visible = build_visible_tasks(tasks.clone(), &TaskViewOptions::default());
+        assert_eq!(visible.len(), 50);
+        assert_eq!(visible[0].id, TaskId(0));
+        assert_eq!(visible[49].id, TaskId(49));
+
+        let options = TaskViewOptions {
+            history_limit: 5,
+            ..TaskViewOptions::default()
+        };
+        assert_eq!(build_visible_tasks(tasks, &options).len(), 5);
+    }
+
+    #[test]
+    fn build_visible_tasks_replaces_older_identical_failed_task() {
+        let tasks = vec![
+            task(201, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Running),
+            task(200, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Failed),
+        ];
+        let visible = build_visible_tasks(tasks, &TaskViewOptions::default());
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].id, TaskId(201));
+        assert_eq!(visible[0].status, TaskStatus::Running);
+    }
+
+    #[test]
+    fn build_visible_tasks_keeps_distinct_labeled_inflight_rows() {
+        let upgrade = |id: u64, package: &str| TaskRecord {
+            label: Some(TaskLabel {
+                key: "service.task.label.upgrade.package".to_string(),
+                args: std::collections::BTreeMap::from([
+                    ("manager".to_string(), "npm".to_string()),
+                    ("package".to_string(), package.to_string()),
+                ]),
+            }),
+            ..task(id, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Queued)
+        };
+        let tasks = vec![upgrade(100, "typescript"), upgrade(99, "eslint")];
+
+        let visible = build_visible_tasks(tasks, &TaskViewOptions::default());
+        assert_eq!(visible.len(), 2);
+    }
+
+    #[test]
+    fn build_visible_tasks_can_keep_duplicates_and_group_by_manager() {
+        let tasks = vec![
+            task(5, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Queued),
+            task(4, ManagerId::Pip, TaskType::Refresh, TaskStatus::Running),
+            task(3, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Running),
+            task(2, ManagerId::Npm, TaskType::Upgrade, TaskStatus::Failed),
+            task(1, ManagerId::Pip, TaskType::Refresh, TaskStatus::Completed),
+        ];
+        let options = TaskViewOptions {
+            dedupe_inflight: false,
+            grouping: TaskViewGrouping::Manager,
+            ..TaskViewOptions::default()
+        };
+
+        let visible = build_visible_tasks(tasks, &options);
+        assert_eq!(
+            visible.iter().map(|task| task.id.0).collect::<Vec<_>>(),
+            vec![5, 3, 2, 4, 1]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::models::{ManagerId, PackageAlias, PackageAliasKind, PackageRef};
+
+/// Fold a package, app, or display name to the form aliases match on:
+/// lowercase ASCII words joined by `-`, without a trailing `.app`. Both
+/// "Visual Studio Code.app" and "visual-studio-code" fold to
+/// `visual-studio-code`.
+pub fn normalize_package_alias(value: &str) -> String {
+    let value = value.trim();
+    let value = match value.len().checked_sub(4) {
+        Some(split)
+            if value.is_char_boundary(split) && value[split..].eq_ignore_ascii_case(".app") =>
+        {
+            &value[..split]
+        }
+        _ => value,
+    };
+    let mut normalized = String::with_capacity(value.len());
+    for character in value.chars().flat_map(char::to_lowercase) {
+        if character.is_alphanumeric() {
+            normalized.push(character);
+        } else if !normalized.is_empty() && !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+    if normalized.ends_with('-') {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Package names and their aliases, keyed by normalized alias, for mapping
+/// an app name or bundle id back to the package that installs it.
+///
+/// A package's own name wins over another package's alias. An alias shared
+/// by several packages of one manager resolves to none of them, so a lookup
+/// never guesses between two casks.
+#[derive(Clone, Debug, Default)]
+pub struct PackageAliasIndex {
+    by_name: HashMap<(ManagerId, String), BTreeSet<String>>,
+    by_alias: HashMap<(ManagerId, String), BTreeSet<String>>,
+    aliases: HashMap<PackageRef, Vec<PackageAlias>>,
+}
+
+impl PackageAliasIndex {
+    pub fn from_aliases(entries: impl IntoIterator<Item = (PackageRef, PackageAlias)>) -> Self {
+        let mut index = Self::default();
+        for (package, alias) in entries {
+            index.insert(&package, alias);
+        }
+        index
+    }
+
+    /// Register `alias` for `package`. The package's own name always
+    /// resolves to it as well.
+    pub fn insert(&mut self, package: &PackageRef, alias: PackageAlias) {
+        for (keys, value) in [
+            (&mut self.by_name, &package.name),
+            (&mut self.by_alias, &alias.value),
+        ] {
+            let key = normalize_package_alias(value);
+            if !key.is_empty() {
+                keys.entry((package.manager, key))
+                    .or_default()
+                    .insert(package.name.clone());
+            }
+        }
+        let aliases = self.aliases.entry(package.clone()).or_default();
+        if !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// The package `name` refers to under `manager`: its own name, a
+    /// display name, an app bundle, or a bundle id.
+    pub fn resolve(&self, manager: ManagerId, name: &str) -> Option<&str> {
+        let key = (manager, normalize_package_alias(name));
+        let names = self.by_name.get(&key).or_else(|| self.by_alias.get(&key))?;
+        match names.len() {
+            1 => names.first().map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// Every package, across managers, that `name` unambiguously refers to.
+    pub fn lookup(&self, name: &str) -> Vec<PackageRef> {
+        let key = normalize_package_alias(name);
+        let mut managers = self
+            .by_name
+            .keys()
+            .chain(self.by_alias.keys())
+            .filter(|(_, alias)| *alias == key)
+            .map(|(manager, _)| *manager)
+            .collect::<Vec<_>>();
+        managers.sort_by_key(|manager| manager.as_str());
+        managers.dedup();
+        managers
+            .into_iter()
+            .filter_map(|manager| {
+                self.resolve(manager, name).map(|resolved| PackageRef {
+                    manager,
+                    name: resolved.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Names shown for `package` besides its own: display names and app
+    /// bundles, without bundle ids.
+    pub fn display_aliases(&self, package: &PackageRef) -> impl Iterator<Item = &str> {
+        self.aliases
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter(|alias| alias.kind != PackageAliasKind::BundleId)
+            .map(|alias| alias.value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackageAliasIndex, normalize_package_alias};
+    use crate::models::{ManagerId, PackageAlias, PackageAliasKind, PackageRef};
+
+    fn cask(name: &str) -> PackageRef {
+        PackageRef {
+            manager: ManagerId::HomebrewCask,
+            name: name.to_string(),
+        }
+    }
+
+    fn alias(kind: PackageAliasKind, value: &str) -> PackageAlias {
+        PackageAlias {
+            kind,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn normalization_folds_app_and_display_names_to_tokens() {
+        assert_eq!(
+            normalize_package_alias("Visual Studio Code.app"),
+            "visual-studio-code"
+        );
+        assert_eq!(
+            normalize_package_alias(" Visual Studio Code "),
+            "visual-studio-code"
+        );
+        assert_eq!(normalize_package_alias("iTerm2.APP"), "iterm2");
+        assert_eq!(
+            normalize_package_alias("com.microsoft.VSCode"),
+            "com-microsoft-vscode"
+        );
+        assert_eq!(normalize_package_alias("--.app"), "");
+    }
+
+    #[test]
+    fn index_resolves_aliases_and_refuses_ambiguous_ones() {
+        let index = PackageAliasIndex::from_aliases([
+            (
+                cask("visual-studio-code"),
+                alias(
+                    PackageAliasKind::DisplayName,
+                    "Microsoft Visual Studio Code",
+                ),
+            ),
+            (
+                cask("visual-studio-code"),
+                alias(PackageAliasKind::AppBundle, "Visual Studio Code.app"),
+            ),
+            (
+                cask("visual-studio-code"),
+                alias(PackageAliasKind::BundleId, "com.microsoft.VSCode"),
+            ),
+            (
+                cask("firefox"),
+                alias(PackageAliasKind::AppBundle, "Firefox.app"),
+            ),
+            (
+                cask("firefox"),
+                alias(PackageAliasKind::BundleId, "org.mozilla.firefox"),
+            ),
+            (
+                cask("firefox@esr"),
+                alias(PackageAliasKind::BundleId, "org.mozilla.firefox"),
+            ),
+        ]);
+
+        for name in [
+            "Visual Studio Code",
+            "visual-studio-code",
+            "com.microsoft.vscode",
+            "Microsoft Visual Studio Code",
+        ] {
+            assert_eq!(
+                index.resolve(ManagerId::HomebrewCask, name),
+                Some("visual-studio-code"),
+                "{name}"
+            );
+        }
+        assert_eq!(
+            index.resolve(ManagerId::HomebrewCask, "Firefox.app"),
+            Some("firefox")
+        );
+        assert_eq!(
+            index.resolve(ManagerId::HomebrewCask, "org.mozilla.firefox"),
+            None
+        );
+        assert_eq!(index.resolve(ManagerId::Mas, "Visual Studio Code"), None);
+        assert_eq!(
+            index.lookup("Visual Studio Code.app"),
+            vec![cask("visual-studio-code")]
+        );
+        assert_eq!(
+            index
+                .display_aliases(&cask("visual-studio-code"))
+                .collect::<Vec<_>>(),
+            vec!["Microsoft Visual Studio Code", "Visual Studio Code.app"]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{InstalledPackage, ManagerId, PackageDependency, PackageRef};
+
+/// What uninstalling a package would affect, read from its manager's
+/// installed dependency graph.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyReport {
+    pub manager_id: String,
+    pub package_name: String,
+    /// Installed packages the package needs.
+    pub dependencies: Vec<String>,
+    /// Installed packages that need the package; uninstalling it breaks them.
+    pub dependents: Vec<String>,
+    /// Dependencies no other installed package needs, left orphaned by an
+    /// uninstall. Some may also have been installed on request.
+    pub orphaned_dependencies: Vec<String>,
+}
+
+/// Build the report for `package` from `edges`.
+///
+/// Edges whose package or dependency is no longer in `installed` are ignored,
+/// so a graph captured before an uninstall does not report removed packages.
+pub fn dependency_report(
+    package: &PackageRef,
+    edges: &[PackageDependency],
+    installed: &HashSet<String>,
+) -> DependencyReport {
+    let live: Vec<&PackageDependency> = edges
+        .iter()
+        .filter(|edge| edge.package.manager == package.manager)
+        .filter(|edge| {
+            installed.contains(&edge.package.name) && installed.contains(&edge.dependency)
+        })
+        .collect();
+
+    let mut dependencies: Vec<String> = live
+        .iter()
+        .filter(|edge| edge.package.name == package.name)
+        .map(|edge| edge.dependency.clone())
+        .collect();
+    dependencies.sort();
+    dependencies.dedup();
+
+    let mut dependents: Vec<String> = live
+        .iter()
+        .filter(|edge| edge.dependency == package.name)
+        .map(|edge| edge.package.name.clone())
+        .collect();
+    dependents.sort();
+    dependents.dedup();
+
+    let still_needed: HashSet<&str> = live
+        .iter()
+        .filter(|edge| edge.package.name != package.name)
+        .map(|edge| edge.dependency.as_str())
+        .collect();
+    let orphaned_dependencies = dependencies
+        .iter()
+        .filter(|dependency| !still_needed.contains(dependency.as_str()))
+        .cloned()
+        .collect();
+
+    DependencyReport {
+        manager_id: package.manager.as_str().to_string(),
+        package_name: package.name.clone(),
+        dependencies,
+        dependents,
+        orphaned_dependencies,
+    }
+}
+
+/// An installed package nothing requested and nothing installed still needs.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedPackage {
+    pub manager_id: String,
+    pub package_name: String,
+    /// Pinned orphans are reported but left alone by cleanup.
+    pub pinned: bool,
+}
+
+/// Find `manager`'s orphans: installed packages not in `requested` that no
+/// requested package reaches through `edges`.
+///
+/// Results are in uninstall order, dependents before their dependencies, so
+/// removing them one by one never trips a manager's dependent check.
+pub fn orphaned_packages(
+    manager: ManagerId,
+    edges: &[PackageDependency],
+    installed: &[InstalledPackage],
+    requested: &HashSet<String>,
+) -> Vec<OrphanedPackage> {
+    let edges: Vec<&PackageDependency> = edges
+        .iter()
+        .filter(|edge| edge.package.manager == manager)
+        .collect();
+
+    let mut needed: HashSet<&str> = requested.iter().map(String::as_str).collect();
+    let mut frontier: Vec<&str> = needed.iter().copied().collect();
+    while let Some(name) = frontier.pop() {
+        for edge in edges.iter().filter(|edge| edge.package.name == name) {
+            if needed.insert(edge.dependency.as_str()) {
+                frontier.push(edge.dependency.as_str());
+            }
+        }
+    }
+
+    let orphans: Vec<&InstalledPackage> = installed
+        .iter()
+        .filter(|package| package.package.manager == manager)
+        .filter(|package| !needed.contains(package.package.name.as_str()))
+        .collect();
+    let orphan_names: HashSet<&str> = orphans
+        .iter()
+        .map(|package| package.package.name.as_str())
+        .collect();
+    let orphan_dependents = |name: &str| {
+        edges
+            .iter()
+            .filter(|edge| edge.dependency == name)
+            .filter(|edge| orphan_names.contains(edge.package.name.as_str()))
+            .count()
+    };
+
+    let mut ordered: Vec<(usize, &InstalledPackage)> = orphans
+        .into_iter()
+        .map(|package| (orphan_dependents(&package.package.name), package))
+        .collect();
+    ordered.sort_by(|(left_rank, left), (right_rank, right)| {
+        left_rank
+            .cmp(right_rank)
+            .then_with(|| left.package.name.cmp(&right.package.name))
+    });
+    ordered
+        .into_iter()
+        .map(|(_, package)| OrphanedPackage {
+            manager_id: manager.as_str().to_string(),
+            package_name: package.package.name.clone(),
+            pinned: package.pinned,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::installed;
+
+    fn edge(package: &str, dependency: &str) -> PackageDependency {
+        PackageDependency {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: package.to_string(),
+            },
+            dependency: dependency.to_string(),
+        }
+    }
+
+    #[test]
+    fn report_lists_dependents_and_dependencies_left_orphaned() {
+        let edges = vec![
+            edge("git", "gettext"),
+            edge("git", "pcre2"),
+            edge("wget", "gettext"),
+            edge("wget", "libidn2"),
+            edge("ffmpeg", "wget"),
+            edge("removed", "git"),
+        ];
+        let installed: HashSet<String> = ["git", "gettext", "pcre2", "wget", "libidn2", "ffmpeg"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        let git = dependency_report(
+            &PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "git".to_string(),
+            },
+            &edges,
+            &installed,
+        );
+        assert_eq!(git.dependencies, vec!["gettext", "pcre2"]);
+        assert!(git.dependents.is_empty());
+        assert_eq!(git.orphaned_dependencies, vec!["pcre2"]);
+
+        let wget = dependency_report(
+            &PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "wget".to_string(),
+            },
+            &edges,
+            &installed,
+        );
+        assert_eq!(wget.dependents, vec!["ffmpeg"]);
+        assert_eq!(wget.orphaned_dependencies, vec!["libidn2"]);
+    }
+
+    #[test]
+    fn orphans_are_unrequested_packages_no_requested_package_reaches() {
+        // `ffmpeg` was uninstalled, leaving `x264` and its dependency `nasm`.
+        let edges = vec![
+            edge("git", "gettext"),
+            edge("gettext", "libintl"),
+            edge("x264", "nasm"),
+            edge("x265", "cmake"),
+        ];
+        let packages = vec![
+            installed(ManagerId::HomebrewFormula, "git", Some("1.0")),
+            installed(ManagerId::HomebrewFormula, "gettext", Some("1.0")),
+            installed(ManagerId::HomebrewFormula, "libintl", Some("1.0")),
+            installed(ManagerId::HomebrewFormula, "nasm", Some("1.0")),
+            installed(ManagerId::HomebrewFormula, "x264", Some("1.0")),
+            InstalledPackage {
+                pinned: true,
+                ..installed(ManagerId::HomebrewFormula, "x265", Some("1.0"))
+            },
+            installed(ManagerId::HomebrewFormula, "cmake", Some("1.0")),
+        ];
+        let requested: HashSet<String> = ["git".to_string()].into_iter().collect();
+
+        let orphans = orphaned_packages(ManagerId::HomebrewFormula, &edges, &packages, &requested);
+        let names: Vec<(&str, bool)> = orphans
+            .iter()
+            .map(|orphan| (orphan.package_name.as_str(), orphan.pinned))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("x264", false),
+                ("x265", true),
+                ("cmake", false),
+                ("nasm", false)
+            ]
+        );
+    }
+}
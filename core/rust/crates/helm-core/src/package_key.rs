@@ -0,0 +1,188 @@
+//! Canonical string form of a manager/package pair: `<manager>:<encoded name>`.
+//!
+//! Package names are percent-encoded wherever they contain characters that
+//! carry meaning in coordinates or keys (`%`, `:`, `/`, `@`, whitespace, and
+//! control characters), so tap-qualified formulae (`homebrew/core/wget`), npm
+//! scopes (`@scope/pkg`), and `formula@version` aliases round-trip exactly.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoreError, CoreErrorKind, ManagerId, PackageRef};
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct PackageKey {
+    manager: ManagerId,
+    name: String,
+}
+
+impl PackageKey {
+    /// Key for `name` under `manager`. Surrounding whitespace is not part of a
+    /// package name and is dropped, so keys built from user input and from
+    /// manager output agree.
+    pub fn new(manager: ManagerId, name: &str) -> Self {
+        Self {
+            manager,
+            name: name.trim().to_string(),
+        }
+    }
+
+    pub fn manager(&self) -> ManagerId {
+        self.manager
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn to_package_ref(&self) -> PackageRef {
+        PackageRef {
+            manager: self.manager,
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl PackageRef {
+    pub fn key(&self) -> PackageKey {
+        PackageKey::new(self.manager, &self.name)
+    }
+}
+
+impl fmt::Display for PackageKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.manager.as_str())?;
+        formatter.write_str(":")?;
+        formatter.write_str(&encode_package_name(&self.name))
+    }
+}
+
+/// Parses the encoded form and legacy unencoded keys
+/// (`homebrew_formula:python@3.12`), which decode to the same package.
+impl FromStr for PackageKey {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CoreError {
+            manager: None,
+            task: None,
+            action: None,
+            kind: CoreErrorKind::InvalidInput,
+            message: format!("invalid package key '{value}'"),
+        };
+        let (manager_raw, encoded_name) = value.split_once(':').ok_or_else(invalid)?;
+        let manager = manager_raw.parse::<ManagerId>().map_err(|_| invalid())?;
+        let name = decode_package_name(encoded_name).ok_or_else(invalid)?;
+        if name.trim().is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new(manager, &name))
+    }
+}
+
+impl From<PackageKey> for String {
+    fn from(key: PackageKey) -> Self {
+        key.to_string()
+    }
+}
+
+impl TryFrom<String> for PackageKey {
+    type Error = CoreError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+pub fn encode_package_name(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for character in name.chars() {
+        if is_reserved_key_char(character) {
+            let mut buffer = [0_u8; 4];
+            for byte in character.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            encoded.push(character);
+        }
+    }
+    encoded
+}
+
+/// Reverse [`encode_package_name`]. A `%` not followed by two hex digits is
+/// kept literally, as legacy keys never encoded it.
+pub fn decode_package_name(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(hex) = value.get(index + 1..index + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            index += 3;
+            continue;
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn is_reserved_key_char(character: char) -> bool {
+    matches!(character, '%' | ':' | '/' | '@')
+        || character.is_whitespace()
+        || character.is_control()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackageKey;
+    use crate::models::{ManagerId, PackageRef};
+
+    #[test]
+    fn keys_round_trip_tap_qualified_and_scoped_names() {
+        for (manager, name) in [
+            (ManagerId::HomebrewFormula, "homebrew/core/wget"),
+            (ManagerId::HomebrewFormula, "python@3.12"),
+            (ManagerId::Npm, "@scope/pkg"),
+            (ManagerId::Npm, "a:b"),
+            (ManagerId::Pip, "100%-coverage"),
+        ] {
+            let key = PackageKey::new(manager, name);
+            let encoded = key.to_string();
+            assert_eq!(encoded.matches(':').count(), 1, "{encoded}");
+            assert_eq!(encoded.parse::<PackageKey>().unwrap(), key);
+            assert_eq!(
+                serde_json::from_value::<PackageKey>(serde_json::to_value(&key).unwrap()).unwrap(),
+                key
+            );
+        }
+        assert_eq!(
+            PackageKey::new(ManagerId::Npm, "@scope/pkg").to_string(),
+            "npm:%40scope%2Fpkg"
+        );
+    }
+
+    #[test]
+    fn keys_normalize_whitespace_and_accept_legacy_form() {
+        let package = PackageRef {
+            manager: ManagerId::HomebrewFormula,
+            name: " python@3.12 ".to_string(),
+        };
+        assert_eq!(
+            package.key(),
+            "homebrew_formula:python@3.12"
+                .parse::<PackageKey>()
+                .unwrap()
+        );
+        assert_eq!(package.key().name(), "python@3.12");
+        assert!("npm:".parse::<PackageKey>().is_err());
+        assert!("npm".parse::<PackageKey>().is_err());
+        assert!("not_a_manager:pkg".parse::<PackageKey>().is_err());
+    }
+}
@@ -1,8 +1,10 @@
 use crate::models::{
-    DetectionInfo, HomebrewKegPolicy, ManagerId, ManagerInstallInstance, PackageKegPolicy,
+    DetectionInfo, DetectionOutcome, HomebrewKegPolicy, ManagerId, ManagerInstallInstance,
+    ManagerRefreshResult, ManagerSelfVersion, OnboardingChoices, OnboardingPhase, PackageKegPolicy,
     PackageRef,
 };
 use crate::persistence::PersistenceResult;
+use std::time::SystemTime;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ManagerPreference {
@@ -12,6 +14,27 @@ pub struct ManagerPreference {
     pub selected_install_method: Option<String>,
     pub timeout_hard_seconds: Option<u64>,
     pub timeout_idle_seconds: Option<u64>,
+    pub process_qos: Option<String>,
+}
+
+/// A user override for one manager environment setting (e.g. Homebrew auto-update).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerEnvSetting {
+    pub manager: ManagerId,
+    pub key: String,
+    pub value: String,
+}
+
+/// An executable path discovery found for a manager, kept across restarts so
+/// status can be served without re-walking the search paths.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerExecutableCandidate {
+    pub manager: ManagerId,
+    pub path: String,
+    /// Version last probed from this path, when detection ran against it.
+    pub version: Option<String>,
+    /// When discovery last confirmed the path still resolves to a file.
+    pub last_validated_at: SystemTime,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -25,6 +48,21 @@ pub trait DetectionStore: Send + Sync {
 
     fn list_detections(&self) -> PersistenceResult<Vec<(ManagerId, DetectionInfo)>>;
 
+    /// Record how the latest detection of `manager` ended, without touching
+    /// the detected state itself.
+    fn set_detection_outcome(
+        &self,
+        _manager: ManagerId,
+        _outcome: &DetectionOutcome,
+    ) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// Recorded detection outcomes; managers never detected are absent.
+    fn list_detection_outcomes(&self) -> PersistenceResult<Vec<(ManagerId, DetectionOutcome)>> {
+        Ok(Vec::new())
+    }
+
     fn replace_install_instances(
         &self,
         manager: ManagerId,
@@ -73,6 +111,13 @@ pub trait DetectionStore: Send + Sync {
         seconds: Option<u64>,
     ) -> PersistenceResult<()>;
 
+    /// Override the spawned-process QoS for one manager; `None` clears it.
+    fn set_manager_process_qos(
+        &self,
+        manager: ManagerId,
+        qos: Option<&str>,
+    ) -> PersistenceResult<()>;
+
     fn list_manager_preferences(&self) -> PersistenceResult<Vec<ManagerPreference>>;
 
     fn set_safe_mode(&self, enabled: bool) -> PersistenceResult<()>;
@@ -83,6 +128,81 @@ pub trait DetectionStore: Send + Sync {
 
     fn homebrew_keg_policy(&self) -> PersistenceResult<HomebrewKegPolicy>;
 
+    fn set_homebrew_cask_exclude_auto_updates(&self, exclude: bool) -> PersistenceResult<()>;
+
+    /// Whether outdated cask listings skip `auto_updates true` casks; defaults to true.
+    fn homebrew_cask_exclude_auto_updates(&self) -> PersistenceResult<bool>;
+
+    fn set_slow_command_tracing(&self, enabled: bool) -> PersistenceResult<()>;
+
+    fn set_hide_pipx_packages_from_pip(&self, hide: bool) -> PersistenceResult<()>;
+
+    /// Whether pip snapshots drop packages pipx owns; defaults to true.
+    fn hide_pipx_packages_from_pip(&self) -> PersistenceResult<bool>;
+
+    /// Whether the daily slow-command trace is collected; defaults to false.
+    fn slow_command_tracing(&self) -> PersistenceResult<bool>;
+
+    fn set_npm_registry_outdated_checks(&self, enabled: bool) -> PersistenceResult<()>;
+
+    /// Whether npm outdated checks query the registry directly instead of
+    /// running `npm outdated -g`; defaults to false.
+    fn npm_registry_outdated_checks(&self) -> PersistenceResult<bool>;
+
+    fn set_process_qos(&self, qos: &str) -> PersistenceResult<()>;
+
+    /// Spawned-process QoS for managers without an override; defaults to
+    /// `default`.
+    fn process_qos(&self) -> PersistenceResult<String>;
+
+    fn set_prewarm_on_launch(&self, enabled: bool) -> PersistenceResult<()>;
+
+    /// Whether manager runtimes and registry connections are warmed in the
+    /// background after init; defaults to true.
+    fn prewarm_on_launch(&self) -> PersistenceResult<bool>;
+
+    fn set_allow_heavy_network_when_metered(&self, allow: bool) -> PersistenceResult<()>;
+
+    /// Whether bandwidth-heavy refresh work still runs on metered networks;
+    /// defaults to false.
+    fn allow_heavy_network_when_metered(&self) -> PersistenceResult<bool>;
+
+    fn set_diagnostics_redaction_patterns(&self, patterns: Option<&str>) -> PersistenceResult<()>;
+
+    /// Extra literal patterns, comma or newline separated, that diagnostics
+    /// exports redact; defaults to none.
+    fn diagnostics_redaction_patterns(&self) -> PersistenceResult<Option<String>>;
+
+    fn set_task_history_retention_days(&self, days: u32) -> PersistenceResult<()>;
+
+    /// Days a finished task stays in the task history; defaults to 30.
+    fn task_history_retention_days(&self) -> PersistenceResult<u32>;
+
+    fn set_task_history_max_entries(&self, entries: u32) -> PersistenceResult<()>;
+
+    /// Upper bound on retained task history entries; defaults to 5000.
+    fn task_history_max_entries(&self) -> PersistenceResult<u32>;
+
+    fn set_task_view_history_limit(&self, limit: u32) -> PersistenceResult<()>;
+
+    /// Terminal rows task lists show after in-flight ones; defaults to 50.
+    fn task_view_history_limit(&self) -> PersistenceResult<u32>;
+
+    fn set_task_view_dedupe_inflight(&self, enabled: bool) -> PersistenceResult<()>;
+
+    /// Whether task lists collapse duplicate in-flight tasks; defaults to true.
+    fn task_view_dedupe_inflight(&self) -> PersistenceResult<bool>;
+
+    fn set_task_view_grouping(&self, grouping: &str) -> PersistenceResult<()>;
+
+    /// How task lists order rows (`none` or `manager`); defaults to `none`.
+    fn task_view_grouping(&self) -> PersistenceResult<String>;
+
+    fn set_offline_mode(&self, enabled: bool) -> PersistenceResult<()>;
+
+    /// Whether network-dependent tasks are refused; defaults to false.
+    fn offline_mode(&self) -> PersistenceResult<bool>;
+
     fn set_auto_check_for_updates(&self, enabled: bool) -> PersistenceResult<()>;
 
     fn auto_check_for_updates(&self) -> PersistenceResult<bool>;
@@ -106,6 +226,15 @@ pub trait DetectionStore: Send + Sync {
 
     fn cli_accepted_license_terms_version(&self) -> PersistenceResult<Option<String>>;
 
+    fn set_onboarding_phase(&self, phase: OnboardingPhase) -> PersistenceResult<()>;
+
+    /// Stored onboarding phase. Completion follows the shared
+    /// `cli_onboarding_completed` flag so either surface can finish or reset it.
+    fn onboarding_phase(&self) -> PersistenceResult<OnboardingPhase>;
+
+    /// Write every onboarding choice and mark onboarding complete in one transaction.
+    fn complete_onboarding(&self, choices: &OnboardingChoices) -> PersistenceResult<()>;
+
     fn set_manager_priority_overrides_json(
         &self,
         overrides_json: Option<&str>,
@@ -113,6 +242,23 @@ pub trait DetectionStore: Send + Sync {
 
     fn manager_priority_overrides_json(&self) -> PersistenceResult<Option<String>>;
 
+    /// Store per-manager background refresh schedules; `None` removes them all.
+    fn set_refresh_schedules_json(&self, schedules_json: Option<&str>) -> PersistenceResult<()>;
+
+    fn refresh_schedules_json(&self) -> PersistenceResult<Option<String>>;
+
+    /// Rewrite the stored per-manager refresh failure streaks and quarantines
+    /// inside one write transaction, so refreshes finishing at the same time
+    /// cannot overwrite each other's changes. `update` receives the current
+    /// JSON and returns the replacement; `None` removes it. An error from
+    /// `update` leaves the stored value unchanged.
+    fn update_manager_failures_json(
+        &self,
+        update: &mut dyn FnMut(Option<String>) -> PersistenceResult<Option<String>>,
+    ) -> PersistenceResult<()>;
+
+    fn manager_failures_json(&self) -> PersistenceResult<Option<String>>;
+
     fn set_package_keg_policy(
         &self,
         package: &PackageRef,
@@ -126,6 +272,15 @@ pub trait DetectionStore: Send + Sync {
 
     fn list_package_keg_policies(&self) -> PersistenceResult<Vec<PackageKegPolicy>>;
 
+    /// Persist the install flags last used for a package; an empty list clears them.
+    fn set_package_install_options(
+        &self,
+        package: &PackageRef,
+        options: &[String],
+    ) -> PersistenceResult<()>;
+
+    fn package_install_options(&self, package: &PackageRef) -> PersistenceResult<Vec<String>>;
+
     fn set_package_manager_preference(
         &self,
         package_family_key: &str,
@@ -138,4 +293,43 @@ pub trait DetectionStore: Send + Sync {
     ) -> PersistenceResult<Option<ManagerId>>;
 
     fn list_package_manager_preferences(&self) -> PersistenceResult<Vec<PackageManagerPreference>>;
+
+    /// Persist the most recent self-version check result for a manager.
+    fn set_manager_self_version(
+        &self,
+        manager: ManagerId,
+        self_version: &ManagerSelfVersion,
+    ) -> PersistenceResult<()>;
+
+    fn list_manager_self_versions(&self)
+    -> PersistenceResult<Vec<(ManagerId, ManagerSelfVersion)>>;
+
+    /// Set or (with `None`) clear a manager environment setting override.
+    fn set_manager_env_setting(
+        &self,
+        manager: ManagerId,
+        key: &str,
+        value: Option<&str>,
+    ) -> PersistenceResult<()>;
+
+    fn list_manager_env_settings(&self) -> PersistenceResult<Vec<ManagerEnvSetting>>;
+
+    /// Replace the stored refresh outcomes with those of the latest
+    /// refresh-all run.
+    fn replace_refresh_results(&self, results: &[ManagerRefreshResult]) -> PersistenceResult<()>;
+
+    fn list_refresh_results(&self) -> PersistenceResult<Vec<ManagerRefreshResult>>;
+
+    /// Replace the discovered executable candidates for one manager,
+    /// preserving discovery order.
+    fn replace_manager_executable_candidates(
+        &self,
+        manager: ManagerId,
+        candidates: &[ManagerExecutableCandidate],
+    ) -> PersistenceResult<()>;
+
+    fn list_manager_executable_candidates(
+        &self,
+        manager: Option<ManagerId>,
+    ) -> PersistenceResult<Vec<ManagerExecutableCandidate>>;
 }
@@ -1,11 +1,18 @@
 pub mod detection_store;
 
+use std::time::SystemTime;
+
 use crate::models::{
-    CachedSearchResult, CoreError, InstalledPackage, ManagerId, OutdatedPackage, PackageRef,
-    PinRecord, TaskId, TaskLogRecord, TaskRecord,
+    CachedSearchResult, CoreError, InstalledPackage, InstalledPackageSize, ManagerId,
+    OutdatedPackage, PackageAlias, PackageDependency, PackageMetadata, PackageRef,
+    PackageSnapshotFreshness, PinRecord, RankedSearchResult, SearchCompletion, TaskHistoryFilter,
+    TaskHistoryRecord, TaskId, TaskLogRecord, TaskRecord,
 };
 
-pub use detection_store::{DetectionStore, ManagerPreference, PackageManagerPreference};
+pub use detection_store::{
+    DetectionStore, ManagerEnvSetting, ManagerExecutableCandidate, ManagerPreference,
+    PackageManagerPreference,
+};
 
 pub type PersistenceResult<T> = Result<T, CoreError>;
 
@@ -32,6 +39,17 @@ pub trait PackageStore: Send + Sync {
         packages: &[OutdatedPackage],
     ) -> PersistenceResult<()>;
 
+    /// Record measured install sizes on `manager`'s installed snapshot rows.
+    /// Later snapshot rewrites keep a size while the package stays at the
+    /// version that was measured.
+    fn set_installed_package_sizes(
+        &self,
+        _manager: ManagerId,
+        _sizes: &[InstalledPackageSize],
+    ) -> PersistenceResult<()> {
+        Ok(())
+    }
+
     fn list_installed(&self) -> PersistenceResult<Vec<InstalledPackage>>;
 
     fn list_outdated(&self) -> PersistenceResult<Vec<OutdatedPackage>>;
@@ -57,6 +75,8 @@ pub trait PackageStore: Send + Sync {
         removed_version: Option<&str>,
     ) -> PersistenceResult<()>;
 
+    /// Applies an upgrade to the snapshot and, when the version changed,
+    /// records the replaced version as the package's rollback target.
     fn apply_upgrade_result(
         &self,
         package: &PackageRef,
@@ -64,6 +84,84 @@ pub trait PackageStore: Send + Sync {
         before_version: Option<&str>,
         after_version: Option<&str>,
     ) -> PersistenceResult<()>;
+
+    /// The version `package` had before its last upgrade through Helm.
+    fn previous_installed_version(
+        &self,
+        _package: &PackageRef,
+    ) -> PersistenceResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Set the snapshot to the rolled-back version and consume the
+    /// recorded rollback target.
+    fn apply_rollback_result(
+        &self,
+        _package: &PackageRef,
+        _package_identifier: Option<&str>,
+        _restored_version: Option<&str>,
+    ) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// Record which task last wrote (or interrupted) a manager's snapshot.
+    /// Records from tasks older than the stored one are ignored, since task
+    /// watchers can finish out of order.
+    fn record_package_snapshot_freshness(
+        &self,
+        _freshness: &PackageSnapshotFreshness,
+    ) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    fn list_package_snapshot_freshness(&self) -> PersistenceResult<Vec<PackageSnapshotFreshness>> {
+        Ok(Vec::new())
+    }
+
+    /// Drop installed and outdated snapshot rows for `package_names` under
+    /// `manager`. Returns the number of rows removed.
+    fn remove_snapshot_packages(
+        &self,
+        _manager: ManagerId,
+        _package_names: &[String],
+    ) -> PersistenceResult<usize> {
+        Ok(0)
+    }
+}
+
+pub trait DependencyStore: Send + Sync {
+    /// Replace `manager`'s installed dependency graph with `edges`.
+    fn replace_dependency_graph(
+        &self,
+        manager: ManagerId,
+        edges: &[PackageDependency],
+    ) -> PersistenceResult<()>;
+
+    /// Every stored edge for `manager`, ordered by package then dependency.
+    fn list_dependency_graph(
+        &self,
+        manager: ManagerId,
+    ) -> PersistenceResult<Vec<PackageDependency>>;
+}
+
+pub trait PackageMetadataStore: Send + Sync {
+    /// Cache `metadata` for `package`, replacing any earlier entry.
+    fn upsert_package_metadata(
+        &self,
+        package: &PackageRef,
+        metadata: &PackageMetadata,
+        fetched_at: SystemTime,
+    ) -> PersistenceResult<()>;
+
+    /// The cached metadata for `package` and when it was fetched.
+    fn package_metadata(
+        &self,
+        package: &PackageRef,
+    ) -> PersistenceResult<Option<(PackageMetadata, SystemTime)>>;
+
+    /// Every cached alias, for building a
+    /// [`PackageAliasIndex`](crate::package_aliases::PackageAliasIndex).
+    fn list_package_aliases(&self) -> PersistenceResult<Vec<(PackageRef, PackageAlias)>>;
 }
 
 pub trait PinStore: Send + Sync {
@@ -81,7 +179,31 @@ pub trait PinStore: Send + Sync {
 pub trait SearchCacheStore: Send + Sync {
     fn upsert_search_results(&self, results: &[CachedSearchResult]) -> PersistenceResult<()>;
 
-    fn query_local(&self, query: &str, limit: usize) -> PersistenceResult<Vec<CachedSearchResult>>;
+    /// Cached results matching `query`, most relevant first (see
+    /// `rank_search_results`).
+    fn query_local_ranked(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> PersistenceResult<Vec<RankedSearchResult>>;
+
+    fn query_local(&self, query: &str, limit: usize) -> PersistenceResult<Vec<CachedSearchResult>> {
+        Ok(self
+            .query_local_ranked(query, limit)?
+            .into_iter()
+            .map(|ranked| ranked.result)
+            .collect())
+    }
+
+    /// Record (or refresh) the completion marker for a manager's remote search.
+    fn record_search_completion(&self, _completion: &SearchCompletion) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// Completion markers recorded for `query`, one per manager that searched it.
+    fn list_search_completions(&self, _query: &str) -> PersistenceResult<Vec<SearchCompletion>> {
+        Ok(Vec::new())
+    }
 }
 
 pub trait TaskStore: Send + Sync {
@@ -97,6 +219,30 @@ pub trait TaskStore: Send + Sync {
     /// Returns the number of rows deleted.
     fn prune_completed_tasks(&self, max_age_secs: i64) -> PersistenceResult<usize>;
 
+    /// Mark `task_id` as a retry of `attempt_of`. Links always point at the
+    /// first attempt, so retrying a retry joins the original chain.
+    fn record_task_attempt(&self, _task_id: TaskId, _attempt_of: TaskId) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// Every retained attempt in the chain `task_id` belongs to, oldest first.
+    /// A task that was never retried yields just itself.
+    fn list_task_attempts(&self, _task_id: TaskId) -> PersistenceResult<Vec<TaskRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// Tag `task_id` with a caller-supplied idempotency key. Each key belongs
+    /// to at most one retained task, and a task that already has a key keeps
+    /// it.
+    fn set_task_idempotency_key(&self, _task_id: TaskId, _key: &str) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// The retained task previously submitted under `key`, if any.
+    fn task_for_idempotency_key(&self, _key: &str) -> PersistenceResult<Option<TaskRecord>> {
+        Ok(None)
+    }
+
     /// Delete a task record and any associated log rows.
     fn delete_task(&self, _task_id: TaskId) -> PersistenceResult<()> {
         Ok(())
@@ -125,4 +271,33 @@ pub trait TaskStore: Send + Sync {
     fn prune_task_logs(&self, _max_age_secs: i64) -> PersistenceResult<usize> {
         Ok(0)
     }
+
+    /// Archive a terminal task. Re-recording the same task replaces its entry.
+    fn record_task_history(&self, _entry: &TaskHistoryRecord) -> PersistenceResult<()> {
+        Ok(())
+    }
+
+    /// Archived tasks matching `filter`, most recently finished first.
+    fn list_task_history(
+        &self,
+        _filter: &TaskHistoryFilter,
+        _offset: usize,
+        _limit: usize,
+    ) -> PersistenceResult<Vec<TaskHistoryRecord>> {
+        Ok(Vec::new())
+    }
+
+    fn count_task_history(&self, _filter: &TaskHistoryFilter) -> PersistenceResult<usize> {
+        Ok(0)
+    }
+
+    /// Drop history entries finished more than `max_age_secs` ago, then the
+    /// oldest entries beyond `max_entries`. Returns the number of rows deleted.
+    fn prune_task_history(
+        &self,
+        _max_age_secs: i64,
+        _max_entries: usize,
+    ) -> PersistenceResult<usize> {
+        Ok(0)
+    }
 }
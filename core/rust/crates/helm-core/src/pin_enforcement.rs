@@ -0,0 +1,211 @@
+use crate::adapters::UpgradeRequest;
+use crate::held_back::{HeldBackReason, held_back_reason};
+use crate::models::{ManagerId, OutdatedPackage, PinKind, PinRecord};
+use crate::upgrade_target::UpgradeTarget;
+use crate::versioning::VersionConstraint;
+
+/// Keeps a manager's own upgrade commands from moving packages that only
+/// Helm has pinned. Native pins (`brew pin`) need no help; virtual pins are
+/// otherwise only honored by Helm's per-package upgrade-all.
+pub trait PinEnforcement: Send + Sync {
+    /// Rewrite `request` so it leaves every package in `held` where it is.
+    /// `held` only lists virtual pins for the request's manager that still
+    /// hold a package back.
+    fn enforce_upgrade(&self, request: UpgradeRequest, held: &[PinRecord]) -> UpgradeRequest;
+}
+
+/// Manager-wide upgrades carry the held package names in `options`, and the
+/// adapter upgrades the remaining outdated packages one by one instead of
+/// running `npm update -g` or `pipx upgrade-all`. Targeted upgrades are an
+/// explicit request and pass through.
+pub struct HoldListPinEnforcement;
+
+impl PinEnforcement for HoldListPinEnforcement {
+    fn enforce_upgrade(&self, request: UpgradeRequest, held: &[PinRecord]) -> UpgradeRequest {
+        with_hold_list(request, held)
+    }
+}
+
+/// Cargo keeps the hold list for manager-wide upgrades and reinstalls a
+/// range-pinned crate with `cargo install --version <requirement>`, so a
+/// targeted upgrade takes the newest release inside the pin's range.
+pub struct CargoPinEnforcement;
+
+impl PinEnforcement for CargoPinEnforcement {
+    fn enforce_upgrade(&self, request: UpgradeRequest, held: &[PinRecord]) -> UpgradeRequest {
+        let UpgradeTarget::Package(package) = &request.target else {
+            return with_hold_list(request, held);
+        };
+        let requirement = held
+            .iter()
+            .find(|pin| pin.package == *package)
+            .and_then(|pin| pin.version_constraint.as_deref())
+            .and_then(|constraint| VersionConstraint::parse(constraint).ok())
+            .and_then(|constraint| constraint.to_cargo_requirement());
+        match requirement {
+            Some(requirement) => UpgradeRequest {
+                version: Some(requirement),
+                ..request
+            },
+            None => request,
+        }
+    }
+}
+
+fn with_hold_list(request: UpgradeRequest, held: &[PinRecord]) -> UpgradeRequest {
+    if !matches!(request.target, UpgradeTarget::All) {
+        return request;
+    }
+    let mut options = held
+        .iter()
+        .map(|pin| pin.package.name.clone())
+        .collect::<Vec<_>>();
+    options.sort();
+    options.dedup();
+    UpgradeRequest { options, ..request }
+}
+
+/// Enforcement for managers whose upgrade commands would override virtual
+/// pins. pip has no manager-wide package upgrade, so it needs none.
+pub fn pin_enforcement(manager: ManagerId) -> Option<&'static dyn PinEnforcement> {
+    match manager {
+        ManagerId::Npm | ManagerId::Pipx => Some(&HoldListPinEnforcement),
+        ManagerId::Cargo => Some(&CargoPinEnforcement),
+        _ => None,
+    }
+}
+
+/// Virtual pins for `manager` that hold a package back. A pin is released
+/// when the outdated snapshot shows its candidate inside the pin's range or
+/// a version other than the pinned one installed; pins without an outdated
+/// row are kept, since holding a current package costs nothing.
+pub fn held_virtual_pins(
+    manager: ManagerId,
+    pins: &[PinRecord],
+    outdated: &[OutdatedPackage],
+) -> Vec<PinRecord> {
+    pins.iter()
+        .filter(|pin| pin.kind == PinKind::Virtual && pin.package.manager == manager)
+        .filter(|pin| {
+            outdated
+                .iter()
+                .find(|package| package.package == pin.package)
+                .is_none_or(|package| {
+                    held_back_reason(package, std::slice::from_ref(pin), true)
+                        == Some(HeldBackReason::VirtualPin)
+                })
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::{held_virtual_pins, pin_enforcement};
+    use crate::adapters::UpgradeRequest;
+    use crate::models::{ManagerId, OutdatedPackage, PackageRef, PinKind, PinRecord};
+    use crate::test_fixtures::outdated;
+    use crate::upgrade_target::UpgradeTarget;
+
+    fn package(manager: ManagerId, name: &str) -> PackageRef {
+        PackageRef {
+            manager,
+            name: name.to_string(),
+        }
+    }
+
+    fn pin(manager: ManagerId, name: &str, kind: PinKind, constraint: Option<&str>) -> PinRecord {
+        PinRecord {
+            package: package(manager, name),
+            kind,
+            pinned_version: None,
+            version_constraint: constraint.map(str::to_string),
+            created_at: SystemTime::now(),
+        }
+    }
+
+    fn upgrade(target: UpgradeTarget) -> UpgradeRequest {
+        UpgradeRequest {
+            target,
+            target_name: None,
+            version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
+        }
+    }
+
+    #[test]
+    fn held_pins_skip_native_pins_and_candidates_inside_the_range() {
+        let pins = vec![
+            pin(ManagerId::Npm, "typescript", PinKind::Virtual, None),
+            pin(ManagerId::Npm, "vite", PinKind::Virtual, Some("^1.2")),
+            pin(ManagerId::Npm, "eslint", PinKind::Virtual, Some("^1.2")),
+            pin(ManagerId::Npm, "corepack", PinKind::Native, None),
+            pin(ManagerId::Pipx, "black", PinKind::Virtual, None),
+        ];
+        let outdated = vec![
+            OutdatedPackage {
+                pinned: true,
+                ..outdated(ManagerId::Npm, "vite", Some("1.2.0"), "1.4.0")
+            },
+            OutdatedPackage {
+                pinned: true,
+                ..outdated(ManagerId::Npm, "eslint", Some("1.2.0"), "2.0.0")
+            },
+        ];
+
+        let held = held_virtual_pins(ManagerId::Npm, &pins, &outdated)
+            .into_iter()
+            .map(|pin| pin.package.name)
+            .collect::<Vec<_>>();
+        assert_eq!(held, vec!["typescript", "eslint"]);
+    }
+
+    #[test]
+    fn manager_wide_upgrades_carry_a_hold_list() {
+        let held = vec![
+            pin(ManagerId::Npm, "typescript", PinKind::Virtual, None),
+            pin(ManagerId::Npm, "eslint", PinKind::Virtual, Some("^1.2")),
+        ];
+        let enforcement = pin_enforcement(ManagerId::Npm).expect("npm enforces pins");
+
+        let all = enforcement.enforce_upgrade(upgrade(UpgradeTarget::All), &held);
+        assert_eq!(all.options, vec!["eslint", "typescript"]);
+
+        let targeted = upgrade(UpgradeTarget::Package(package(
+            ManagerId::Npm,
+            "typescript",
+        )));
+        assert_eq!(
+            enforcement.enforce_upgrade(targeted.clone(), &held),
+            targeted
+        );
+        assert!(pin_enforcement(ManagerId::Pip).is_none());
+    }
+
+    #[test]
+    fn cargo_locks_range_pinned_reinstalls_to_the_requirement() {
+        let held = vec![
+            pin(ManagerId::Cargo, "ripgrep", PinKind::Virtual, Some("^13")),
+            pin(ManagerId::Cargo, "bat", PinKind::Virtual, None),
+        ];
+        let enforcement = pin_enforcement(ManagerId::Cargo).expect("cargo enforces pins");
+
+        let ripgrep = enforcement.enforce_upgrade(
+            upgrade(UpgradeTarget::Package(package(ManagerId::Cargo, "ripgrep"))),
+            &held,
+        );
+        assert_eq!(ripgrep.version.as_deref(), Some(">=13, <14"));
+
+        let bat = enforcement.enforce_upgrade(
+            upgrade(UpgradeTarget::Package(package(ManagerId::Cargo, "bat"))),
+            &held,
+        );
+        assert_eq!(bat.version, None);
+
+        let all = enforcement.enforce_upgrade(upgrade(UpgradeTarget::All), &held);
+        assert_eq!(all.options, vec!["bat", "ripgrep"]);
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::adapters::homebrew::homebrew_detect_request;
+use crate::adapters::npm::npm_ping_request;
+use crate::execution::{ProcessExecutor, ProcessSpawnRequest, spawn_validated};
+use crate::models::{DetectionInfo, ManagerId};
+use crate::reachability::{ReachabilityChecker, registry_hosts};
+
+/// Background work that hides cold-start latency from the first user-visible
+/// operation after launch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrewarmPlan {
+    /// Managers whose registry hosts are probed, warming DNS, TLS, and the
+    /// reachability cache that gates network tasks.
+    pub registry_managers: Vec<ManagerId>,
+    /// Trivial commands that load a manager's runtime from disk
+    /// (`brew --version`, `npm ping`).
+    pub commands: Vec<ProcessSpawnRequest>,
+}
+
+impl PrewarmPlan {
+    pub fn is_empty(&self) -> bool {
+        self.registry_managers.is_empty() && self.commands.is_empty()
+    }
+}
+
+/// Plan prewarming for enabled managers detected as installed. Managers that
+/// were never detected are skipped, so the first launch warms nothing. Offline
+/// mode warms nothing either: registry probes and `npm ping` touch the network.
+pub fn prewarm_plan(
+    detections: &[(ManagerId, DetectionInfo)],
+    is_enabled: impl Fn(ManagerId) -> bool,
+    offline: bool,
+) -> PrewarmPlan {
+    let mut plan = PrewarmPlan::default();
+    if offline {
+        return plan;
+    }
+    for (manager, info) in detections {
+        if !info.installed || !is_enabled(*manager) {
+            continue;
+        }
+        if !registry_hosts(*manager).is_empty()
+            && !crate::registry::manager_network_capabilities(*manager).is_empty()
+        {
+            plan.registry_managers.push(*manager);
+        }
+        if let Some(request) = prewarm_command_request(*manager, info.executable_path.as_deref()) {
+            plan.commands.push(request);
+        }
+    }
+    plan
+}
+
+fn prewarm_command_request(
+    manager: ManagerId,
+    executable_path: Option<&Path>,
+) -> Option<ProcessSpawnRequest> {
+    let mut request = match manager {
+        ManagerId::HomebrewFormula => homebrew_detect_request(None),
+        ManagerId::Npm => npm_ping_request(None),
+        _ => return None,
+    };
+    // The service's PATH is minimal; npm also needs `node` next to it.
+    let mut search_path = String::new();
+    if let Some(directory) = executable_path.and_then(Path::parent) {
+        search_path.push_str(&directory.to_string_lossy());
+        search_path.push(':');
+    }
+    search_path.push_str("/opt/homebrew/bin:/usr/local/bin:");
+    search_path.push_str(&std::env::var("PATH").unwrap_or_default());
+    request.command = request.command.env("PATH", search_path);
+    Some(request)
+}
+
+/// Run `plan`, probing registries alongside the commands. Prewarming is best
+/// effort: failures are logged and never surfaced.
+pub async fn run_prewarm(
+    plan: PrewarmPlan,
+    checker: Option<Arc<ReachabilityChecker>>,
+    executor: Arc<dyn ProcessExecutor>,
+) {
+    let registry_managers = plan.registry_managers;
+    let probes = async move {
+        let Some(checker) = checker else {
+            return;
+        };
+        // Probes block on the network.
+        let _ = tokio::task::spawn_blocking(move || {
+            for manager in registry_managers {
+                checker.manager_status(manager);
+            }
+        })
+        .await;
+    };
+    let commands = async move {
+        for request in plan.commands {
+            let manager = request.manager;
+            let result = match spawn_validated(executor.as_ref(), request) {
+                Ok(process) => process.wait().await.map(|_| ()),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                tracing::debug!(
+                    manager = manager.as_str(),
+                    error = %error,
+                    "prewarm command failed"
+                );
+            }
+        }
+    };
+    tokio::join!(probes, commands);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn detected(manager: ManagerId, executable: &str) -> (ManagerId, DetectionInfo) {
+        (
+            manager,
+            DetectionInfo {
+                installed: true,
+                executable_path: Some(PathBuf::from(executable)),
+                version: None,
+            },
+        )
+    }
+
+    #[test]
+    fn plan_warms_enabled_installed_managers_only() {
+        let detections = vec![
+            detected(ManagerId::HomebrewFormula, "/opt/homebrew/bin/brew"),
+            detected(ManagerId::Npm, "/Users/dev/.volta/bin/npm"),
+            detected(ManagerId::Cargo, "/Users/dev/.cargo/bin/cargo"),
+            detected(ManagerId::Pip, "/usr/bin/python3"),
+            (
+                ManagerId::RubyGems,
+                DetectionInfo {
+                    installed: false,
+                    executable_path: None,
+                    version: None,
+                },
+            ),
+        ];
+
+        let plan = prewarm_plan(&detections, |manager| manager != ManagerId::Pip, false);
+
+        assert_eq!(
+            plan.registry_managers,
+            vec![ManagerId::HomebrewFormula, ManagerId::Npm, ManagerId::Cargo]
+        );
+        let commands: Vec<_> = plan
+            .commands
+            .iter()
+            .map(|request| (request.manager, request.command.args.clone()))
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                (ManagerId::HomebrewFormula, vec!["--version".to_string()]),
+                (ManagerId::Npm, vec!["ping".to_string()]),
+            ]
+        );
+        let npm_path = &plan.commands[1].command.env["PATH"];
+        assert!(npm_path.starts_with("/Users/dev/.volta/bin:/opt/homebrew/bin:"));
+    }
+
+    #[test]
+    fn plan_is_empty_before_first_detection() {
+        assert!(prewarm_plan(&[], |_| true, false).is_empty());
+    }
+
+    #[test]
+    fn plan_is_empty_in_offline_mode() {
+        let detections = vec![
+            detected(ManagerId::HomebrewFormula, "/opt/homebrew/bin/brew"),
+            detected(ManagerId::Npm, "/Users/dev/.volta/bin/npm"),
+        ];
+        assert!(prewarm_plan(&detections, |_| true, true).is_empty());
+    }
+}
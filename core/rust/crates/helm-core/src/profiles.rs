@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::models::{CoreError, CoreErrorKind};
+
+/// The profile backed by the database Helm was initialized with.
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active";
+const PROFILE_DATABASE_EXTENSION: &str = "sqlite3";
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub database_path: PathBuf,
+    pub active: bool,
+}
+
+/// Named Helm inventories, each in its own SQLite database.
+///
+/// `default` keeps using the database Helm was initialized with, so existing
+/// installs keep their data. Other profiles live at
+/// `profiles/<name>.sqlite3` next to it, and `profiles/active` records which
+/// profile the next launch opens.
+#[derive(Clone, Debug)]
+pub struct ProfileDirectory {
+    default_database: PathBuf,
+}
+
+impl ProfileDirectory {
+    pub fn new(default_database: impl Into<PathBuf>) -> Self {
+        Self {
+            default_database: default_database.into(),
+        }
+    }
+
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.default_database
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(PROFILES_DIR)
+    }
+
+    pub fn database_path(&self, name: &str) -> Result<PathBuf, CoreError> {
+        validate_profile_name(name)?;
+        if name == DEFAULT_PROFILE {
+            return Ok(self.default_database.clone());
+        }
+        Ok(self
+            .profiles_dir()
+            .join(format!("{name}.{PROFILE_DATABASE_EXTENSION}")))
+    }
+
+    /// The recorded active profile, or `default` when none is recorded or the
+    /// record is unreadable.
+    pub fn active_profile(&self) -> String {
+        std::fs::read_to_string(self.profiles_dir().join(ACTIVE_PROFILE_FILE))
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|name| validate_profile_name(name).is_ok())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    pub fn set_active_profile(&self, name: &str) -> Result<(), CoreError> {
+        validate_profile_name(name)?;
+        let profiles_dir = self.profiles_dir();
+        std::fs::create_dir_all(&profiles_dir)
+            .and_then(|()| std::fs::write(profiles_dir.join(ACTIVE_PROFILE_FILE), name))
+            .map_err(|error| profile_error(CoreErrorKind::StorageFailure, error.to_string()))
+    }
+
+    /// `default` first, then every profile with a database, by name. The
+    /// active profile is listed even before its database is created.
+    pub fn list(&self) -> Vec<ProfileInfo> {
+        let active = self.active_profile();
+        let mut names = std::fs::read_dir(self.profiles_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != PROFILE_DATABASE_EXTENSION {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_string();
+                validate_profile_name(&name).ok().map(|()| name)
+            })
+            .filter(|name| name != DEFAULT_PROFILE)
+            .collect::<Vec<_>>();
+        if active != DEFAULT_PROFILE && !names.contains(&active) {
+            names.push(active.clone());
+        }
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                Some(ProfileInfo {
+                    database_path: self.database_path(&name).ok()?,
+                    active: name == active,
+                    name,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Profile names become file names: 1–64 ASCII letters, digits, `-`, or `_`.
+pub fn validate_profile_name(name: &str) -> Result<(), CoreError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(profile_error(
+            CoreErrorKind::InvalidInput,
+            format!("invalid profile name '{name}'"),
+        ))
+    }
+}
+
+fn profile_error(kind: CoreErrorKind, message: String) -> CoreError {
+    CoreError {
+        manager: None,
+        task: None,
+        action: None,
+        kind,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{DEFAULT_PROFILE, ProfileDirectory, validate_profile_name};
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("helm-profiles-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).expect("temp root");
+        root
+    }
+
+    #[test]
+    fn profile_names_must_be_file_safe() {
+        for valid in ["default", "work", "personal_2", "client-a"] {
+            assert!(validate_profile_name(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["", "../work", "work profile", "wörk", &"x".repeat(65)] {
+            assert!(validate_profile_name(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn default_profile_keeps_the_initial_database() {
+        let root = temp_root("paths");
+        let profiles = ProfileDirectory::new(root.join("helm.db"));
+
+        assert_eq!(profiles.active_profile(), DEFAULT_PROFILE);
+        assert_eq!(
+            profiles.database_path(DEFAULT_PROFILE).unwrap(),
+            root.join("helm.db")
+        );
+        assert_eq!(
+            profiles.database_path("work").unwrap(),
+            root.join("profiles/work.sqlite3")
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn list_reports_databases_and_the_active_profile() {
+        let root = temp_root("list");
+        let profiles = ProfileDirectory::new(root.join("helm.db"));
+        std::fs::create_dir_all(profiles.profiles_dir()).unwrap();
+        std::fs::write(profiles.profiles_dir().join("work.sqlite3"), "").unwrap();
+        std::fs::write(profiles.profiles_dir().join("notes.txt"), "").unwrap();
+        profiles.set_active_profile("personal").unwrap();
+
+        let listed = profiles
+            .list()
+            .into_iter()
+            .map(|profile| (profile.name, profile.active))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            listed,
+            vec![
+                ("default".to_string(), false),
+                ("personal".to_string(), true),
+                ("work".to_string(), false),
+            ]
+        );
+        assert_eq!(profiles.active_profile(), "personal");
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ManagerAction, ManagerId};
+
+const REACHABLE_CACHE_TTL: Duration = Duration::from_secs(120);
+const UNREACHABLE_CACHE_TTL: Duration = Duration::from_secs(15);
+
+static REACHABILITY_CHECKER: OnceLock<RwLock<Option<Arc<ReachabilityChecker>>>> = OnceLock::new();
+
+/// Host-provided connectivity probe. Implementations issue a lightweight HTTPS
+/// `HEAD` and treat any HTTP response (including error statuses) as reachable.
+pub trait ReachabilityProbe: Send + Sync {
+    fn probe(&self, host: &str) -> bool;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReachabilityStatus {
+    Reachable,
+    NetworkUnreachable,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostReachability {
+    pub host: String,
+    pub status: ReachabilityStatus,
+    pub checked_at_unix: i64,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReachabilitySnapshot {
+    pub hosts: Vec<HostReachability>,
+    /// Managers whose network work was deferred and will refresh on recovery.
+    pub awaiting_connectivity: Vec<ManagerId>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct HostProbeResult {
+    reachable: bool,
+    checked_at: Instant,
+    checked_at_unix: i64,
+}
+
+/// Per-host reachability cache. Unreachable results expire quickly so recovery
+/// is noticed soon after connectivity returns.
+pub struct ReachabilityChecker {
+    probe: Arc<dyn ReachabilityProbe>,
+    reachable_ttl: Duration,
+    unreachable_ttl: Duration,
+    hosts: Mutex<HashMap<&'static str, HostProbeResult>>,
+    awaiting_connectivity: Mutex<Vec<ManagerId>>,
+}
+
+impl ReachabilityChecker {
+    pub fn new(probe: Arc<dyn ReachabilityProbe>) -> Self {
+        Self::with_ttls(probe, REACHABLE_CACHE_TTL, UNREACHABLE_CACHE_TTL)
+    }
+
+    pub fn with_ttls(
+        probe: Arc<dyn ReachabilityProbe>,
+        reachable_ttl: Duration,
+        unreachable_ttl: Duration,
+    ) -> Self {
+        Self {
+            probe,
+            reachable_ttl,
+            unreachable_ttl,
+            hosts: Mutex::new(HashMap::new()),
+            awaiting_connectivity: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Reachability of a manager's registries, probing only hosts whose cached
+    /// result expired. Managers without known registry hosts are never gated.
+    pub fn manager_status(&self, manager: ManagerId) -> ReachabilityStatus {
+        self.evaluate_manager(manager, false)
+    }
+
+    /// Like [`Self::manager_status`], but ignores cached results.
+    pub fn recheck_manager(&self, manager: ManagerId) -> ReachabilityStatus {
+        self.evaluate_manager(manager, true)
+    }
+
+    pub fn mark_awaiting_connectivity(&self, manager: ManagerId) {
+        let mut awaiting = lock(&self.awaiting_connectivity);
+        if !awaiting.contains(&manager) {
+            awaiting.push(manager);
+        }
+    }
+
+    /// Re-probe managers awaiting connectivity and return (and forget) the ones
+    /// whose registries answer again.
+    pub fn take_recovered_managers(&self) -> Vec<ManagerId> {
+        let awaiting = lock(&self.awaiting_connectivity).clone();
+        let recovered: Vec<ManagerId> = awaiting
+            .into_iter()
+            .filter(|manager| self.recheck_manager(*manager) == ReachabilityStatus::Reachable)
+            .collect();
+        lock(&self.awaiting_connectivity).retain(|manager| !recovered.contains(manager));
+        recovered
+    }
+
+    pub fn snapshot(&self) -> ReachabilitySnapshot {
+        let mut hosts: Vec<HostReachability> = lock(&self.hosts)
+            .iter()
+            .map(|(host, result)| HostReachability {
+                host: (*host).to_string(),
+                status: if result.reachable {
+                    ReachabilityStatus::Reachable
+                } else {
+                    ReachabilityStatus::NetworkUnreachable
+                },
+                checked_at_unix: result.checked_at_unix,
+            })
+            .collect();
+        hosts.sort_by(|left, right| left.host.cmp(&right.host));
+        ReachabilitySnapshot {
+            hosts,
+            awaiting_connectivity: lock(&self.awaiting_connectivity).clone(),
+        }
+    }
+
+    fn evaluate_manager(&self, manager: ManagerId, force: bool) -> ReachabilityStatus {
+        let hosts = registry_hosts(manager);
+        if hosts.is_empty() {
+            return ReachabilityStatus::Reachable;
+        }
+        if hosts.iter().any(|host| self.host_reachable(host, force)) {
+            ReachabilityStatus::Reachable
+        } else {
+            ReachabilityStatus::NetworkUnreachable
+        }
+    }
+
+    fn host_reachable(&self, host: &'static str, force: bool) -> bool {
+        if !force && let Some(cached) = lock(&self.hosts).get(host).copied() {
+            let ttl = if cached.reachable {
+                self.reachable_ttl
+            } else {
+                self.unreachable_ttl
+            };
+            if cached.checked_at.elapsed() < ttl {
+                return cached.reachable;
+            }
+        }
+
+        // Probe without holding the cache lock; probes may block on the network.
+        let reachable = self.probe.probe(host);
+        lock(&self.hosts).insert(
+            host,
+            HostProbeResult {
+                reachable,
+                checked_at: Instant::now(),
+                checked_at_unix: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs() as i64)
+                    .unwrap_or(0),
+            },
+        );
+        reachable
+    }
+}
+
+/// Registry endpoints a manager contacts for refresh, search, install, and upgrade.
+pub fn registry_hosts(manager: ManagerId) -> &'static [&'static str] {
+    match manager {
+        ManagerId::HomebrewFormula | ManagerId::HomebrewCask => &["formulae.brew.sh"],
+        ManagerId::Npm | ManagerId::Pnpm => &["registry.npmjs.org"],
+        ManagerId::Yarn => &["registry.yarnpkg.com", "registry.npmjs.org"],
+        ManagerId::Pip | ManagerId::Pipx | ManagerId::Poetry => &["pypi.org"],
+        ManagerId::RubyGems | ManagerId::Bundler => &["rubygems.org"],
+        ManagerId::Cargo | ManagerId::CargoBinstall => &["index.crates.io"],
+        ManagerId::Rustup => &["static.rust-lang.org"],
+        ManagerId::Mise | ManagerId::Asdf => &["github.com"],
+        ManagerId::MacPorts => &["ports.macports.org"],
+        _ => &[],
+    }
+}
+
+/// Whether an action needs a manager's registry to succeed, per the registry's
+/// declared network capabilities. `Configure` only edits local settings.
+pub fn action_requires_network(manager: ManagerId, action: ManagerAction) -> bool {
+    action != ManagerAction::Configure
+        && crate::registry::capability_requires_network(manager, action.required_capability())
+}
+
+/// Install the process-wide checker consulted by the adapter runtime.
+/// Without one, network-dependent tasks run ungated.
+pub fn install_reachability_probe(probe: Arc<dyn ReachabilityProbe>) {
+    let slot = REACHABILITY_CHECKER.get_or_init(|| RwLock::new(None));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = Some(Arc::new(ReachabilityChecker::new(probe)));
+}
+
+pub fn reachability_checker() -> Option<Arc<ReachabilityChecker>> {
+    let slot = REACHABILITY_CHECKER.get()?;
+    slot.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct SwitchProbe {
+        online: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    impl ReachabilityProbe for SwitchProbe {
+        fn probe(&self, _host: &str) -> bool {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.online.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn manager_status_caches_probe_results_per_host() {
+        let probe = Arc::new(SwitchProbe::default());
+        probe.online.store(true, Ordering::SeqCst);
+        let checker = ReachabilityChecker::with_ttls(
+            probe.clone(),
+            Duration::from_secs(3_600),
+            Duration::from_secs(3_600),
+        );
+
+        assert_eq!(
+            checker.manager_status(ManagerId::Npm),
+            ReachabilityStatus::Reachable
+        );
+        assert_eq!(
+            checker.manager_status(ManagerId::Pnpm),
+            ReachabilityStatus::Reachable
+        );
+        assert_eq!(probe.calls.load(Ordering::SeqCst), 1);
+
+        probe.online.store(false, Ordering::SeqCst);
+        assert_eq!(
+            checker.manager_status(ManagerId::Npm),
+            ReachabilityStatus::Reachable
+        );
+        assert_eq!(
+            checker.recheck_manager(ManagerId::Npm),
+            ReachabilityStatus::NetworkUnreachable
+        );
+    }
+
+    #[test]
+    fn managers_without_registry_hosts_are_never_gated() {
+        let probe = Arc::new(SwitchProbe::default());
+        let checker = ReachabilityChecker::new(probe.clone());
+        assert_eq!(
+            checker.manager_status(ManagerId::SoftwareUpdate),
+            ReachabilityStatus::Reachable
+        );
+        assert_eq!(probe.calls.load(Ordering::SeqCst), 0);
+        assert!(!action_requires_network(
+            ManagerId::Npm,
+            ManagerAction::ListInstalled
+        ));
+        assert!(action_requires_network(
+            ManagerId::Npm,
+            ManagerAction::ListOutdated
+        ));
+        assert!(!action_requires_network(
+            ManagerId::Npm,
+            ManagerAction::Configure
+        ));
+    }
+
+    #[test]
+    fn recovered_managers_are_returned_once_connectivity_returns() {
+        let probe = Arc::new(SwitchProbe::default());
+        let checker = ReachabilityChecker::with_ttls(
+            probe.clone(),
+            Duration::from_secs(3_600),
+            Duration::from_secs(3_600),
+        );
+        assert_eq!(
+            checker.manager_status(ManagerId::Cargo),
+            ReachabilityStatus::NetworkUnreachable
+        );
+        checker.mark_awaiting_connectivity(ManagerId::Cargo);
+        checker.mark_awaiting_connectivity(ManagerId::Cargo);
+        assert!(checker.take_recovered_managers().is_empty());
+
+        probe.online.store(true, Ordering::SeqCst);
+        assert_eq!(checker.take_recovered_managers(), vec![ManagerId::Cargo]);
+        assert!(checker.take_recovered_managers().is_empty());
+
+        let snapshot = checker.snapshot();
+        assert!(snapshot.awaiting_connectivity.is_empty());
+        assert_eq!(snapshot.hosts.len(), 1);
+        assert_eq!(snapshot.hosts[0].host, "index.crates.io");
+        assert_eq!(snapshot.hosts[0].status, ReachabilityStatus::Reachable);
+    }
+}
@@ -48,6 +48,9 @@ pub struct ManagerLifecycleMetadata {
     pub install_methods: &'static [ManagerInstallMethodSpec],
     pub participates_in_package_search: bool,
     pub participates_in_catalog_sync: bool,
+    /// Capabilities that contact the manager's registry or vendor servers and
+    /// therefore cannot succeed offline.
+    pub network_capabilities: &'static [Capability],
 }
 
 const fn method_spec(
@@ -133,6 +136,15 @@ const MAS_CAPABILITIES: &[Capability] = &[
     Capability::Upgrade,
 ];
 
+const REGISTRY_NETWORK_CAPABILITIES: &[Capability] = &[
+    Capability::Refresh,
+    Capability::Search,
+    Capability::ListOutdated,
+    Capability::Install,
+    Capability::Upgrade,
+    Capability::SelfVersionCheck,
+];
+
 const ALL_MANAGERS: [ManagerDescriptor; 28] = [
     ManagerDescriptor {
         id: ManagerId::Mise,
@@ -825,168 +837,196 @@ const MISE_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetada
     install_methods: MISE_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: true,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const ASDF_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: ASDF_INSTALL_METHOD_IDS,
     install_methods: ASDF_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: true,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const RUSTUP_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: RUSTUP_INSTALL_METHOD_IDS,
     install_methods: RUSTUP_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: true,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const HOMEBREW_FORMULA_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: HOMEBREW_FORMULA_INSTALL_METHOD_IDS,
     install_methods: HOMEBREW_FORMULA_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: true,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const SOFTWAREUPDATE_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: SOFTWAREUPDATE_INSTALL_METHOD_IDS,
     install_methods: SOFTWAREUPDATE_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const MACPORTS_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: MACPORTS_INSTALL_METHOD_IDS,
     install_methods: MACPORTS_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const NIX_DARWIN_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: NIX_DARWIN_INSTALL_METHOD_IDS,
     install_methods: NIX_DARWIN_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const NPM_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: NPM_INSTALL_METHOD_IDS,
     install_methods: NPM_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const PNPM_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: PNPM_INSTALL_METHOD_IDS,
     install_methods: PNPM_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const YARN_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: YARN_INSTALL_METHOD_IDS,
     install_methods: YARN_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const PIPX_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: PIPX_INSTALL_METHOD_IDS,
     install_methods: PIPX_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const PIP_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: PIP_INSTALL_METHOD_IDS,
     install_methods: PIP_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const POETRY_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: POETRY_INSTALL_METHOD_IDS,
     install_methods: POETRY_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const RUBYGEMS_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: RUBYGEMS_INSTALL_METHOD_IDS,
     install_methods: RUBYGEMS_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const BUNDLER_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: BUNDLER_INSTALL_METHOD_IDS,
     install_methods: BUNDLER_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const CARGO_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: CARGO_INSTALL_METHOD_IDS,
     install_methods: CARGO_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const CARGO_BINSTALL_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: CARGO_BINSTALL_INSTALL_METHOD_IDS,
     install_methods: CARGO_BINSTALL_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const MAS_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: MAS_INSTALL_METHOD_IDS,
     install_methods: MAS_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const SPARKLE_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: SPARKLE_INSTALL_METHOD_IDS,
     install_methods: SPARKLE_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const SETAPP_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: SETAPP_INSTALL_METHOD_IDS,
     install_methods: SETAPP_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const HOMEBREW_CASK_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: HOMEBREW_CASK_INSTALL_METHOD_IDS,
     install_methods: HOMEBREW_CASK_INSTALL_METHODS,
     participates_in_package_search: true,
     participates_in_catalog_sync: true,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const DOCKER_DESKTOP_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: DOCKER_DESKTOP_INSTALL_METHOD_IDS,
     install_methods: DOCKER_DESKTOP_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const PODMAN_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: PODMAN_INSTALL_METHOD_IDS,
     install_methods: PODMAN_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const COLIMA_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: COLIMA_INSTALL_METHOD_IDS,
     install_methods: COLIMA_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const PARALLELS_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: PARALLELS_INSTALL_METHOD_IDS,
     install_methods: PARALLELS_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const XCODE_CLT_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: XCODE_CLT_INSTALL_METHOD_IDS,
     install_methods: XCODE_CLT_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const ROSETTA_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: ROSETTA_INSTALL_METHOD_IDS,
     install_methods: ROSETTA_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 const FIRMWARE_LIFECYCLE_METADATA: ManagerLifecycleMetadata = ManagerLifecycleMetadata {
     install_method_ids: FIRMWARE_INSTALL_METHOD_IDS,
     install_methods: FIRMWARE_INSTALL_METHODS,
     participates_in_package_search: false,
     participates_in_catalog_sync: false,
+    network_capabilities: REGISTRY_NETWORK_CAPABILITIES,
 };
 pub fn managers() -> &'static [ManagerDescriptor] {
     &ALL_MANAGERS
@@ -1056,11 +1096,34 @@ pub fn manager_participates_in_catalog_sync(id: ManagerId) -> bool {
         && manager_lifecycle_metadata(id).participates_in_catalog_sync
 }
 
+/// Whether `capability` needs network access for this manager. Offline
+/// handling and the reachability gate consult this instead of special-casing
+/// actions.
+pub fn capability_requires_network(id: ManagerId, capability: Capability) -> bool {
+    manager_lifecycle_metadata(id)
+        .network_capabilities
+        .contains(&capability)
+}
+
+/// Supported capabilities of a manager that need network access.
+pub fn manager_network_capabilities(id: ManagerId) -> Vec<Capability> {
+    let Some(descriptor) = manager(id) else {
+        return Vec::new();
+    };
+    descriptor
+        .capabilities
+        .iter()
+        .copied()
+        .filter(|capability| capability_requires_network(id, *capability))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        InstallMethodRecommendationReason, manager, manager_install_method_candidates,
-        manager_install_method_specs, manager_participates_in_catalog_sync,
+        InstallMethodRecommendationReason, capability_requires_network, manager,
+        manager_install_method_candidates, manager_install_method_specs,
+        manager_network_capabilities, manager_participates_in_catalog_sync,
         manager_participates_in_package_search,
     };
     use crate::models::{Capability, ManagerId};
@@ -1133,4 +1196,24 @@ mod tests {
         assert!(!manager_participates_in_catalog_sync(ManagerId::Cargo));
         assert!(!manager_participates_in_catalog_sync(ManagerId::Pipx));
     }
+
+    #[test]
+    fn listing_installed_packages_never_requires_network() {
+        for id in ManagerId::ALL {
+            assert!(!capability_requires_network(id, Capability::ListInstalled));
+            assert!(!capability_requires_network(id, Capability::Uninstall));
+        }
+        assert!(capability_requires_network(
+            ManagerId::Npm,
+            Capability::ListOutdated
+        ));
+        assert_eq!(
+            manager_network_capabilities(ManagerId::SoftwareUpdate),
+            vec![
+                Capability::Refresh,
+                Capability::ListOutdated,
+                Capability::Upgrade
+            ]
+        );
+    }
 }
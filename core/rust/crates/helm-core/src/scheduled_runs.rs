@@ -0,0 +1,155 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::orchestration::refresh_scheduler::{self, RefreshSchedule};
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+static WAKE_HINT_PENDING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledRunKind {
+    AutoCheckForUpdates,
+    ManagerRefresh,
+}
+
+/// Background work the core intends to run, so the host can keep the service
+/// alive around the next due time.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRun {
+    pub kind: ScheduledRunKind,
+    pub manager_id: Option<String>,
+    pub eta_unix: i64,
+    pub interval_seconds: u64,
+    pub overdue: bool,
+}
+
+/// Compute the auto-check schedule from wall-clock time only, so a host that
+/// slept past the due time sees the run as overdue instead of shifted.
+///
+/// A last-run timestamp in the future (clock moved backwards) is treated as
+/// never run, which makes the check due immediately.
+pub fn auto_check_scheduled_run(
+    enabled: bool,
+    frequency_minutes: u32,
+    last_checked_unix: Option<i64>,
+    now_unix: i64,
+) -> Option<ScheduledRun> {
+    if !enabled {
+        return None;
+    }
+    let interval_seconds = u64::from(frequency_minutes.max(1)) * 60;
+    let eta_unix = last_checked_unix
+        .filter(|last_checked| *last_checked <= now_unix)
+        .map_or(now_unix, |last_checked| {
+            last_checked.saturating_add(interval_seconds as i64)
+        });
+    Some(ScheduledRun {
+        kind: ScheduledRunKind::AutoCheckForUpdates,
+        manager_id: None,
+        eta_unix,
+        interval_seconds,
+        overdue: eta_unix <= now_unix,
+    })
+}
+
+/// A manager's background refresh as a scheduled run.
+pub fn manager_refresh_scheduled_run(schedule: &RefreshSchedule, now_unix: i64) -> ScheduledRun {
+    let eta_unix = schedule.next_run_unix(now_unix);
+    ScheduledRun {
+        kind: ScheduledRunKind::ManagerRefresh,
+        manager_id: Some(schedule.manager.as_str().to_string()),
+        eta_unix,
+        interval_seconds: schedule.interval_secs,
+        overdue: eta_unix <= now_unix,
+    }
+}
+
+/// Upcoming scheduled runs ordered by due time.
+pub fn next_scheduled_runs(
+    store: &dyn DetectionStore,
+    now_unix: i64,
+) -> PersistenceResult<Vec<ScheduledRun>> {
+    let mut runs: Vec<ScheduledRun> = auto_check_scheduled_run(
+        store.auto_check_for_updates()?,
+        store.auto_check_frequency_minutes()?,
+        store.auto_check_last_checked_unix()?,
+        now_unix,
+    )
+    .into_iter()
+    .collect();
+    runs.extend(
+        refresh_scheduler::refresh_schedules(store)?
+            .into_iter()
+            .map(|schedule| manager_refresh_scheduled_run(&schedule, now_unix)),
+    );
+    runs.sort_by_key(|run| run.eta_unix);
+    Ok(runs)
+}
+
+/// Record that the host woke from sleep; schedulers re-evaluate on their next poll.
+pub fn notify_wake() {
+    WAKE_HINT_PENDING.store(true, Ordering::SeqCst);
+}
+
+/// Consume a pending wake hint. Returns true at most once per `notify_wake`.
+pub fn take_wake_hint() -> bool {
+    WAKE_HINT_PENDING.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_check_schedule_uses_wall_clock_from_last_run() {
+        assert_eq!(
+            auto_check_scheduled_run(false, 60, Some(1_000), 2_000),
+            None
+        );
+
+        let run = auto_check_scheduled_run(true, 60, Some(1_000), 2_000).unwrap();
+        assert_eq!(run.kind, ScheduledRunKind::AutoCheckForUpdates);
+        assert_eq!(run.eta_unix, 4_600);
+        assert_eq!(run.interval_seconds, 3_600);
+        assert!(!run.overdue);
+
+        let after_sleep = auto_check_scheduled_run(true, 60, Some(1_000), 90_000).unwrap();
+        assert_eq!(after_sleep.eta_unix, 4_600);
+        assert!(after_sleep.overdue);
+    }
+
+    #[test]
+    fn auto_check_schedule_is_due_when_never_run_or_clock_moved_back() {
+        let never = auto_check_scheduled_run(true, 1_440, None, 5_000).unwrap();
+        assert_eq!(never.eta_unix, 5_000);
+        assert!(never.overdue);
+
+        let future = auto_check_scheduled_run(true, 1_440, Some(9_000), 5_000).unwrap();
+        assert_eq!(future.eta_unix, 5_000);
+        assert!(future.overdue);
+    }
+
+    #[test]
+    fn manager_refresh_run_names_the_manager() {
+        let schedule = RefreshSchedule {
+            manager: crate::models::ManagerId::SoftwareUpdate,
+            interval_secs: 86_400,
+            last_refreshed_unix: Some(1_000),
+        };
+        let run = manager_refresh_scheduled_run(&schedule, 2_000);
+        assert_eq!(run.kind, ScheduledRunKind::ManagerRefresh);
+        assert_eq!(run.manager_id.as_deref(), Some("softwareupdate"));
+        assert_eq!(run.eta_unix, 87_400);
+        assert!(!run.overdue);
+    }
+
+    #[test]
+    fn wake_hint_is_consumed_once() {
+        notify_wake();
+        assert!(take_wake_hint());
+        assert!(!take_wake_hint());
+    }
+}
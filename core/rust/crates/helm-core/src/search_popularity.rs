@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::models::{CachedSearchResult, ManagerId, SearchPopularity};
+
+/// Homebrew publishes analytics daily; the ranking files are large, so one
+/// download serves every search until it is this old.
+const HOMEBREW_RANKING_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+/// The npm downloads API answers at most this many unscoped names at once.
+const NPM_DOWNLOADS_BULK_LIMIT: usize = 128;
+
+static SEARCH_POPULARITY_CLIENT: OnceLock<RwLock<Option<Arc<dyn SearchPopularityClient>>>> =
+    OnceLock::new();
+static HOMEBREW_RANKINGS: OnceLock<Mutex<HashMap<ManagerId, HomebrewRanking>>> = OnceLock::new();
+
+/// Host-provided HTTPS `GET` for registry popularity endpoints.
+pub trait SearchPopularityClient: Send + Sync {
+    /// Body of `url`, or `Ok(None)` when the registry has no such resource.
+    fn fetch(&self, url: &str) -> Result<Option<String>, String>;
+}
+
+/// Install the process-wide client. Without one, search results carry no
+/// popularity data.
+pub fn install_search_popularity_client(client: Arc<dyn SearchPopularityClient>) {
+    let slot = SEARCH_POPULARITY_CLIENT.get_or_init(|| RwLock::new(None));
+    let mut guard = slot
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *guard = Some(client);
+}
+
+pub fn search_popularity_client() -> Option<Arc<dyn SearchPopularityClient>> {
+    let slot = SEARCH_POPULARITY_CLIENT.get()?;
+    slot.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Fill in popularity for `results` of a `manager` search for `query`, where
+/// the manager's registry publishes it. Lookups are best-effort: a failure
+/// leaves the results unannotated.
+pub fn annotate_search_popularity(
+    manager: ManagerId,
+    query: &str,
+    results: &mut [CachedSearchResult],
+) {
+    if results.is_empty() {
+        return;
+    }
+    let Some(client) = search_popularity_client() else {
+        return;
+    };
+    let names = results
+        .iter()
+        .map(|result| result.result.package.name.clone())
+        .collect::<Vec<_>>();
+    let popularity = match manager {
+        ManagerId::Npm | ManagerId::Pnpm | ManagerId::Yarn => {
+            npm_popularity(client.as_ref(), &names)
+        }
+        ManagerId::Cargo | ManagerId::CargoBinstall => crates_io_popularity(client.as_ref(), query),
+        ManagerId::HomebrewFormula | ManagerId::HomebrewCask => {
+            homebrew_popularity(client.as_ref(), manager, &names, Instant::now())
+        }
+        _ => return,
+    };
+    let popularity = match popularity {
+        Ok(popularity) => popularity,
+        Err(error) => {
+            tracing::debug!(manager = ?manager, %error, "search popularity lookup failed");
+            return;
+        }
+    };
+    for result in results {
+        if let Some(entry) = popularity.get(&result.result.package.name) {
+            result.popularity = entry.clone();
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NpmDownloadsPoint {
+    downloads: u64,
+    package: String,
+}
+
+/// Last-month downloads from `api.npmjs.org`. Unscoped names are fetched in
+/// bulk; scoped names are not supported by the bulk endpoint.
+fn npm_popularity(
+    client: &dyn SearchPopularityClient,
+    names: &[String],
+) -> Result<HashMap<String, SearchPopularity>, String> {
+    let (scoped, unscoped): (Vec<&String>, Vec<&String>) =
+        names.iter().partition(|name| name.starts_with('@'));
+    let mut points = Vec::new();
+    for batch in unscoped.chunks(NPM_DOWNLOADS_BULK_LIMIT) {
+        let joined = batch
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        let Some(body) = client.fetch(&format!(
+            "https://api.npmjs.org/downloads/point/last-month/{joined}"
+        ))?
+        else {
+            continue;
+        };
+        if batch.len() == 1 {
+            points.extend(serde_json::from_str::<NpmDownloadsPoint>(&body).ok());
+        } else {
+            let bulk = serde_json::from_str::<HashMap<String, Option<NpmDownloadsPoint>>>(&body)
+                .map_err(|error| format!("invalid npm downloads JSON: {error}"))?;
+            points.extend(bulk.into_values().flatten());
+        }
+    }
+    for name in scoped {
+        if let Some(body) = client.fetch(&format!(
+            "https://api.npmjs.org/downloads/point/last-month/{name}"
+        ))? {
+            points.extend(serde_json::from_str::<NpmDownloadsPoint>(&body).ok());
+        }
+    }
+    Ok(points
+        .into_iter()
+        .map(|point| {
+            (
+                point.package,
+                SearchPopularity {
+                    downloads: Some(point.downloads),
+                    rank: None,
+                },
+            )
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct CratesIoSearch {
+    crates: Vec<CratesIoCrate>,
+}
+
+#[derive(Deserialize)]
+struct CratesIoCrate {
+    name: String,
+    #[serde(default)]
+    recent_downloads: Option<u64>,
+}
+
+/// 90-day downloads from the crates.io search API, one request per query.
+/// Ranks follow the API's default relevance order, not popularity.
+fn crates_io_popularity(
+    client: &dyn SearchPopularityClient,
+    query: &str,
+) -> Result<HashMap<String, SearchPopularity>, String> {
+    let Some(body) = client.fetch(&format!(
+        "https://crates.io/api/v1/crates?per_page=100&q={}",
+        percent_encode_query(query)
+    ))?
+    else {
+        return Ok(HashMap::new());
+    };
+    let search = serde_json::from_str::<CratesIoSearch>(&body)
+        .map_err(|error| format!("invalid crates.io search JSON: {error}"))?;
+    Ok(search
+        .crates
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.name,
+                SearchPopularity {
+                    downloads: entry.recent_downloads,
+                    rank: None,
+                },
+            )
+        })
+        .collect())
+}
+
+struct HomebrewRanking {
+    fetched_at: Instant,
+    entries: HashMap<String, SearchPopularity>,
+}
+
+#[derive(Deserialize)]
+struct HomebrewAnalytics {
+    items: Vec<HomebrewAnalyticsItem>,
+}
+
+#[derive(Deserialize)]
+struct HomebrewAnalyticsItem {
+    number: u32,
+    #[serde(alias = "cask")]
+    formula: String,
+    count: String,
+}
+
+/// 30-day install rank and count from Homebrew's published analytics.
+fn homebrew_popularity(
+    client: &dyn SearchPopularityClient,
+    manager: ManagerId,
+    names: &[String],
+    now: Instant,
+) -> Result<HashMap<String, SearchPopularity>, String> {
+    let rankings = HOMEBREW_RANKINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let cached = {
+        let rankings = rankings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        rankings
+            .get(&manager)
+            .filter(|ranking| now.duration_since(ranking.fetched_at) < HOMEBREW_RANKING_TTL)
+            .map(|ranking| select(&ranking.entries, names))
+    };
+    if let Some(cached) = cached {
+        return Ok(cached);
+    }
+
+    let url = if manager == ManagerId::HomebrewCask {
+        "https://formulae.brew.sh/api/analytics/cask-install/30d.json"
+    } else {
+        "https://formulae.brew.sh/api/analytics/install-on-request/30d.json"
+    };
+    let Some(body) = client.fetch(url)? else {
+        return Ok(HashMap::new());
+    };
+    let entries = parse_homebrew_analytics(&body)?;
+    let selected = select(&entries, names);
+    rankings
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(
+            manager,
+            HomebrewRanking {
+                fetched_at: now,
+                entries,
+            },
+        );
+    Ok(selected)
+}
+
+fn parse_homebrew_analytics(body: &str) -> Result<HashMap<String, SearchPopularity>, String> {
+    let analytics = serde_json::from_str::<HomebrewAnalytics>(body)
+        .map_err(|error| format!("invalid Homebrew analytics JSON: {error}"))?;
+    let mut entries = HashMap::new();
+    for item in analytics.items {
+        // Tap formulae are listed as `user/tap/name`; search results use the short name.
+        let name = item
+            .formula
+            .rsplit('/')
+            .next()
+            .unwrap_or(&item.formula)
+            .to_string();
+        entries.entry(name).or_insert(SearchPopularity {
+            downloads: item.count.replace(',', "").parse().ok(),
+            rank: Some(item.number),
+        });
+    }
+    Ok(entries)
+}
+
+fn select(
+    entries: &HashMap<String, SearchPopularity>,
+    names: &[String],
+) -> HashMap<String, SearchPopularity> {
+    names
+        .iter()
+        .filter_map(|name| Some((name.clone(), entries.get(name)?.clone())))
+        .collect()
+}
+
+fn percent_encode_query(query: &str) -> String {
+    let mut encoded = String::with_capacity(query.len());
+    for byte in query.trim().bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use super::{
+        SearchPopularityClient, crates_io_popularity, npm_popularity, parse_homebrew_analytics,
+        percent_encode_query,
+    };
+    use crate::models::SearchPopularity;
+
+    #[derive(Default)]
+    struct FakeClient {
+        bodies: HashMap<String, String>,
+        requested: Mutex<Vec<String>>,
+    }
+
+    impl SearchPopularityClient for FakeClient {
+        fn fetch(&self, url: &str) -> Result<Option<String>, String> {
+            self.requested.lock().unwrap().push(url.to_string());
+            Ok(self.bodies.get(url).cloned())
+        }
+    }
+
+    fn downloads(value: u64) -> SearchPopularity {
+        SearchPopularity {
+            downloads: Some(value),
+            rank: None,
+        }
+    }
+
+    #[test]
+    fn npm_downloads_are_fetched_in_bulk_with_scoped_names_separately() {
+        let client = FakeClient {
+            bodies: HashMap::from([
+                (
+                    "https://api.npmjs.org/downloads/point/last-month/left-pad,typescript"
+                        .to_string(),
+                    r#"{"left-pad":{"downloads":10,"package":"left-pad"},"typescript":{"downloads":2000,"package":"typescript"}}"#.to_string(),
+                ),
+                (
+                    "https://api.npmjs.org/downloads/point/last-month/@angular/cli".to_string(),
+                    r#"{"downloads":300,"package":"@angular/cli"}"#.to_string(),
+                ),
+            ]),
+            ..FakeClient::default()
+        };
+        let names = ["left-pad", "@angular/cli", "typescript"].map(str::to_string);
+
+        let popularity = npm_popularity(&client, &names).expect("lookup");
+        assert_eq!(popularity["typescript"], downloads(2_000));
+        assert_eq!(popularity["@angular/cli"], downloads(300));
+        assert_eq!(client.requested.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn crates_io_search_reports_recent_downloads() {
+        let client = FakeClient {
+            bodies: HashMap::from([(
+                "https://crates.io/api/v1/crates?per_page=100&q=serde%20json".to_string(),
+                r#"{"crates":[{"name":"serde_json","recent_downloads":5000},{"name":"json5"}]}"#
+                    .to_string(),
+            )]),
+            ..FakeClient::default()
+        };
+
+        let popularity = crates_io_popularity(&client, "serde json").expect("lookup");
+        assert_eq!(popularity["serde_json"], downloads(5_000));
+        assert_eq!(popularity["json5"], SearchPopularity::default());
+    }
+
+    #[test]
+    fn homebrew_analytics_rank_short_names() {
+        let entries = parse_homebrew_analytics(
+            r#"{"items":[
+                {"number":1,"formula":"node","count":"512,004","percent":"3.1"},
+                {"number":2,"formula":"hashicorp/tap/terraform","count":"1,200","percent":"0.1"}
+            ]}"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            entries["node"],
+            SearchPopularity {
+                downloads: Some(512_004),
+                rank: Some(1),
+            }
+        );
+        assert_eq!(entries["terraform"].rank, Some(2));
+        assert_eq!(percent_encode_query(" a+b "), "a%2Bb");
+    }
+}
@@ -0,0 +1,616 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::execution::ProcessQos;
+use crate::models::{CoreError, CoreErrorKind, HomebrewKegPolicy};
+use crate::orchestration::task_view;
+use crate::persistence::{DetectionStore, PersistenceResult};
+
+const MAX_SETTING_CHANGES: usize = 128;
+const MAX_AUTO_CHECK_FREQUENCY_MINUTES: u64 = 43_200;
+const MAX_TASK_HISTORY_RETENTION_DAYS: u64 = 3_650;
+const MAX_TASK_HISTORY_ENTRIES: u64 = 100_000;
+const MAX_TASK_VIEW_HISTORY_LIMIT: u64 = task_view::MAX_TASK_VIEW_HISTORY_LIMIT as u64;
+const MAX_SETTING_STRING_LEN: usize = 256;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingKey {
+    SafeMode,
+    HomebrewKegAutoCleanup,
+    AutoCheckForUpdates,
+    AutoCheckFrequencyMinutes,
+    CliOnboardingCompleted,
+    CliAcceptedLicenseTermsVersion,
+    HomebrewCaskExcludeAutoUpdates,
+    SlowCommandTracing,
+    HidePipxPackagesFromPip,
+    NpmRegistryOutdatedChecks,
+    PrewarmOnLaunch,
+    ProcessQos,
+    TaskHistoryRetentionDays,
+    TaskHistoryMaxEntries,
+    AllowHeavyNetworkWhenMetered,
+    DiagnosticsRedactionPatterns,
+    TaskViewHistoryLimit,
+    TaskViewDedupeInflight,
+    TaskViewGrouping,
+    OfflineMode,
+}
+
+impl SettingKey {
+    pub const ALL: [SettingKey; 20] = [
+        SettingKey::SafeMode,
+        SettingKey::HomebrewKegAutoCleanup,
+        SettingKey::AutoCheckForUpdates,
+        SettingKey::AutoCheckFrequencyMinutes,
+        SettingKey::CliOnboardingCompleted,
+        SettingKey::CliAcceptedLicenseTermsVersion,
+        SettingKey::HomebrewCaskExcludeAutoUpdates,
+        SettingKey::SlowCommandTracing,
+        SettingKey::HidePipxPackagesFromPip,
+        SettingKey::NpmRegistryOutdatedChecks,
+        SettingKey::PrewarmOnLaunch,
+        SettingKey::ProcessQos,
+        SettingKey::TaskHistoryRetentionDays,
+        SettingKey::TaskHistoryMaxEntries,
+        SettingKey::AllowHeavyNetworkWhenMetered,
+        SettingKey::DiagnosticsRedactionPatterns,
+        SettingKey::TaskViewHistoryLimit,
+        SettingKey::TaskViewDedupeInflight,
+        SettingKey::TaskViewGrouping,
+        SettingKey::OfflineMode,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SettingKey::SafeMode => "safe_mode",
+            SettingKey::HomebrewKegAutoCleanup => "homebrew_keg_auto_cleanup",
+            SettingKey::AutoCheckForUpdates => "auto_check_for_updates",
+            SettingKey::AutoCheckFrequencyMinutes => "auto_check_frequency_minutes",
+            SettingKey::CliOnboardingCompleted => "cli_onboarding_completed",
+            SettingKey::CliAcceptedLicenseTermsVersion => "cli_accepted_license_terms_version",
+            SettingKey::HomebrewCaskExcludeAutoUpdates => "homebrew_cask_exclude_auto_updates",
+            SettingKey::SlowCommandTracing => "slow_command_tracing",
+            SettingKey::HidePipxPackagesFromPip => "hide_pipx_packages_from_pip",
+            SettingKey::NpmRegistryOutdatedChecks => "npm_registry_outdated_checks",
+            SettingKey::PrewarmOnLaunch => "prewarm_on_launch",
+            SettingKey::ProcessQos => "process_qos",
+            SettingKey::TaskHistoryRetentionDays => "task_history_retention_days",
+            SettingKey::TaskHistoryMaxEntries => "task_history_max_entries",
+            SettingKey::AllowHeavyNetworkWhenMetered => "allow_heavy_network_when_metered",
+            SettingKey::DiagnosticsRedactionPatterns => "diagnostics_redaction_patterns",
+            SettingKey::TaskViewHistoryLimit => "task_view_history_limit",
+            SettingKey::TaskViewDedupeInflight => "task_view_dedupe_inflight",
+            SettingKey::TaskViewGrouping => "task_view_grouping",
+            SettingKey::OfflineMode => "offline_mode",
+        }
+    }
+
+    pub fn kind(self) -> SettingKind {
+        match self {
+            SettingKey::SafeMode
+            | SettingKey::HomebrewKegAutoCleanup
+            | SettingKey::AutoCheckForUpdates
+            | SettingKey::CliOnboardingCompleted
+            | SettingKey::HomebrewCaskExcludeAutoUpdates
+            | SettingKey::SlowCommandTracing
+            | SettingKey::HidePipxPackagesFromPip
+            | SettingKey::NpmRegistryOutdatedChecks
+            | SettingKey::PrewarmOnLaunch
+            | SettingKey::AllowHeavyNetworkWhenMetered
+            | SettingKey::TaskViewDedupeInflight
+            | SettingKey::OfflineMode => SettingKind::Bool,
+            SettingKey::AutoCheckFrequencyMinutes
+            | SettingKey::TaskHistoryRetentionDays
+            | SettingKey::TaskHistoryMaxEntries
+            | SettingKey::TaskViewHistoryLimit => SettingKind::Integer,
+            SettingKey::CliAcceptedLicenseTermsVersion
+            | SettingKey::DiagnosticsRedactionPatterns => SettingKind::OptionalString,
+            SettingKey::ProcessQos | SettingKey::TaskViewGrouping => SettingKind::Choice,
+        }
+    }
+
+    /// Accepted values for `SettingKind::Choice` keys; empty otherwise.
+    pub fn choices(self) -> &'static [&'static str] {
+        match self {
+            SettingKey::ProcessQos => &["default", "utility", "background"],
+            SettingKey::TaskViewGrouping => &["none", "manager"],
+            _ => &[],
+        }
+    }
+
+    /// Largest accepted value for `SettingKind::Integer` keys; the smallest is 1.
+    pub fn max_integer(self) -> u64 {
+        match self {
+            SettingKey::TaskHistoryRetentionDays => MAX_TASK_HISTORY_RETENTION_DAYS,
+            SettingKey::TaskHistoryMaxEntries => MAX_TASK_HISTORY_ENTRIES,
+            SettingKey::TaskViewHistoryLimit => MAX_TASK_VIEW_HISTORY_LIMIT,
+            _ => MAX_AUTO_CHECK_FREQUENCY_MINUTES,
+        }
+    }
+
+    pub fn default_value(self) -> Value {
+        match self {
+            SettingKey::SafeMode
+            | SettingKey::HomebrewKegAutoCleanup
+            | SettingKey::AutoCheckForUpdates
+            | SettingKey::CliOnboardingCompleted
+            | SettingKey::SlowCommandTracing
+            | SettingKey::NpmRegistryOutdatedChecks
+            | SettingKey::AllowHeavyNetworkWhenMetered
+            | SettingKey::OfflineMode => Value::Bool(false),
+            SettingKey::AutoCheckFrequencyMinutes => Value::from(1_440_u32),
+            SettingKey::CliAcceptedLicenseTermsVersion
+            | SettingKey::DiagnosticsRedactionPatterns => Value::Null,
+            SettingKey::HomebrewCaskExcludeAutoUpdates
+            | SettingKey::HidePipxPackagesFromPip
+            | SettingKey::PrewarmOnLaunch
+            | SettingKey::TaskViewDedupeInflight => Value::Bool(true),
+            SettingKey::ProcessQos => Value::String(ProcessQos::Default.as_str().to_string()),
+            SettingKey::TaskHistoryRetentionDays => Value::from(30_u32),
+            SettingKey::TaskHistoryMaxEntries => Value::from(5_000_u32),
+            SettingKey::TaskViewHistoryLimit => {
+                Value::from(task_view::DEFAULT_TASK_VIEW_HISTORY_LIMIT as u32)
+            }
+            SettingKey::TaskViewGrouping => {
+                Value::String(task_view::TaskViewGrouping::None.as_str().to_string())
+            }
+        }
+    }
+}
+
+impl FromStr for SettingKey {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        SettingKey::ALL
+            .into_iter()
+            .find(|key| key.as_str() == value.trim())
+            .ok_or_else(|| invalid_setting(format!("unknown setting '{}'", value.trim())))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingKind {
+    Bool,
+    Integer,
+    OptionalString,
+    /// One of `SettingKey::choices`.
+    Choice,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingSnapshot {
+    pub key: SettingKey,
+    pub kind: SettingKind,
+    pub value: Value,
+    pub default_value: Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChange {
+    pub sequence: u64,
+    pub key: SettingKey,
+    pub value: Value,
+    pub changed_at_unix_ms: i64,
+}
+
+struct SettingChangeFeed {
+    next_sequence: u64,
+    changes: VecDeque<SettingChange>,
+}
+
+static SETTING_CHANGES: OnceLock<Mutex<SettingChangeFeed>> = OnceLock::new();
+
+fn setting_changes() -> &'static Mutex<SettingChangeFeed> {
+    SETTING_CHANGES.get_or_init(|| {
+        Mutex::new(SettingChangeFeed {
+            next_sequence: 1,
+            changes: VecDeque::new(),
+        })
+    })
+}
+
+fn invalid_setting(message: String) -> CoreError {
+    CoreError {
+        manager: None,
+        task: None,
+        action: None,
+        kind: CoreErrorKind::InvalidInput,
+        message,
+    }
+}
+
+pub fn get_setting(store: &dyn DetectionStore, key: SettingKey) -> PersistenceResult<Value> {
+    Ok(match key {
+        SettingKey::SafeMode => Value::Bool(store.safe_mode()?),
+        SettingKey::HomebrewKegAutoCleanup => Value::Bool(matches!(
+            store.homebrew_keg_policy()?,
+            HomebrewKegPolicy::Cleanup
+        )),
+        SettingKey::AutoCheckForUpdates => Value::Bool(store.auto_check_for_updates()?),
+        SettingKey::AutoCheckFrequencyMinutes => Value::from(store.auto_check_frequency_minutes()?),
+        SettingKey::CliOnboardingCompleted => Value::Bool(store.cli_onboarding_completed()?),
+        SettingKey::CliAcceptedLicenseTermsVersion => store
+            .cli_accepted_license_terms_version()?
+            .map_or(Value::Null, Value::String),
+        SettingKey::HomebrewCaskExcludeAutoUpdates => {
+            Value::Bool(store.homebrew_cask_exclude_auto_updates()?)
+        }
+        SettingKey::SlowCommandTracing => Value::Bool(store.slow_command_tracing()?),
+        SettingKey::HidePipxPackagesFromPip => Value::Bool(store.hide_pipx_packages_from_pip()?),
+        SettingKey::NpmRegistryOutdatedChecks => Value::Bool(store.npm_registry_outdated_checks()?),
+        SettingKey::PrewarmOnLaunch => Value::Bool(store.prewarm_on_launch()?),
+        SettingKey::ProcessQos => Value::String(store.process_qos()?),
+        SettingKey::TaskHistoryRetentionDays => Value::from(store.task_history_retention_days()?),
+        SettingKey::TaskHistoryMaxEntries => Value::from(store.task_history_max_entries()?),
+        SettingKey::AllowHeavyNetworkWhenMetered => {
+            Value::Bool(store.allow_heavy_network_when_metered()?)
+        }
+        SettingKey::DiagnosticsRedactionPatterns => store
+            .diagnostics_redaction_patterns()?
+            .map_or(Value::Null, Value::String),
+        SettingKey::TaskViewHistoryLimit => Value::from(store.task_view_history_limit()?),
+        SettingKey::TaskViewDedupeInflight => Value::Bool(store.task_view_dedupe_inflight()?),
+        SettingKey::TaskViewGrouping => Value::String(store.task_view_grouping()?),
+        SettingKey::OfflineMode => Value::Bool(store.offline_mode()?),
+    })
+}
+
+pub fn list_settings(store: &dyn DetectionStore) -> PersistenceResult<Vec<SettingSnapshot>> {
+    SettingKey::ALL
+        .into_iter()
+        .map(|key| {
+            Ok(SettingSnapshot {
+                key,
+                kind: key.kind(),
+                value: get_setting(store, key)?,
+                default_value: key.default_value(),
+                choices: key
+                    .choices()
+                    .iter()
+                    .map(|choice| choice.to_string())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Validate `value` against the registry, persist it, and publish a change
+/// event when the stored value actually changed. Returns the normalized value.
+pub fn set_setting(
+    store: &dyn DetectionStore,
+    key: SettingKey,
+    value: &Value,
+) -> PersistenceResult<Value> {
+    let normalized = validate_setting_value(key, value)?;
+    let previous = get_setting(store, key)?;
+
+    match key {
+        SettingKey::SafeMode => store.set_safe_mode(normalized == Value::Bool(true))?,
+        SettingKey::HomebrewKegAutoCleanup => {
+            store.set_homebrew_keg_policy(if normalized == Value::Bool(true) {
+                HomebrewKegPolicy::Cleanup
+            } else {
+                HomebrewKegPolicy::Keep
+            })?
+        }
+        SettingKey::AutoCheckForUpdates => {
+            store.set_auto_check_for_updates(normalized == Value::Bool(true))?
+        }
+        SettingKey::AutoCheckFrequencyMinutes => store.set_auto_check_frequency_minutes(
+            normalized
+                .as_u64()
+                .and_then(|minutes| u32::try_from(minutes).ok())
+                .unwrap_or(1_440),
+        )?,
+        SettingKey::CliOnboardingCompleted => {
+            store.set_cli_onboarding_completed(normalized == Value::Bool(true))?
+        }
+        SettingKey::CliAcceptedLicenseTermsVersion => {
+            store.set_cli_accepted_license_terms_version(normalized.as_str())?
+        }
+        SettingKey::HomebrewCaskExcludeAutoUpdates => {
+            store.set_homebrew_cask_exclude_auto_updates(normalized == Value::Bool(true))?
+        }
+        SettingKey::SlowCommandTracing => {
+            store.set_slow_command_tracing(normalized == Value::Bool(true))?
+        }
+        SettingKey::HidePipxPackagesFromPip => {
+            store.set_hide_pipx_packages_from_pip(normalized == Value::Bool(true))?
+        }
+        SettingKey::NpmRegistryOutdatedChecks => {
+            store.set_npm_registry_outdated_checks(normalized == Value::Bool(true))?
+        }
+        SettingKey::PrewarmOnLaunch => {
+            store.set_prewarm_on_launch(normalized == Value::Bool(true))?
+        }
+        SettingKey::ProcessQos => {
+            store.set_process_qos(normalized.as_str().unwrap_or("default"))?
+        }
+        SettingKey::TaskHistoryRetentionDays => store.set_task_history_retention_days(
+            normalized
+                .as_u64()
+                .and_then(|days| u32::try_from(days).ok())
+                .unwrap_or(30),
+        )?,
+        SettingKey::TaskHistoryMaxEntries => store.set_task_history_max_entries(
+            normalized
+                .as_u64()
+                .and_then(|entries| u32::try_from(entries).ok())
+                .unwrap_or(5_000),
+        )?,
+        SettingKey::AllowHeavyNetworkWhenMetered => {
+            store.set_allow_heavy_network_when_metered(normalized == Value::Bool(true))?
+        }
+        SettingKey::DiagnosticsRedactionPatterns => {
+            store.set_diagnostics_redaction_patterns(normalized.as_str())?
+        }
+        SettingKey::TaskViewHistoryLimit => store.set_task_view_history_limit(
+            normalized
+                .as_u64()
+                .and_then(|limit| u32::try_from(limit).ok())
+                .unwrap_or(50),
+        )?,
+        SettingKey::TaskViewDedupeInflight => {
+            store.set_task_view_dedupe_inflight(normalized == Value::Bool(true))?
+        }
+        SettingKey::TaskViewGrouping => {
+            store.set_task_view_grouping(normalized.as_str().unwrap_or("none"))?
+        }
+        SettingKey::OfflineMode => store.set_offline_mode(normalized == Value::Bool(true))?,
+    }
+
+    if previous != normalized {
+        publish_setting_change(key, normalized.clone());
+    }
+    Ok(normalized)
+}
+
+pub fn validate_setting_value(key: SettingKey, value: &Value) -> Result<Value, CoreError> {
+    let type_error = || {
+        invalid_setting(format!(
+            "setting '{}' expects a {} value",
+            key.as_str(),
+            match key.kind() {
+                SettingKind::Bool => "boolean",
+                SettingKind::Integer => "positive integer",
+                SettingKind::OptionalString => "string or null",
+                SettingKind::Choice => "string",
+            }
+        ))
+    };
+
+    match key.kind() {
+        SettingKind::Bool => value.as_bool().map(Value::Bool).ok_or_else(type_error),
+        SettingKind::Integer => {
+            let number = value.as_u64().ok_or_else(type_error)?;
+            let max = key.max_integer();
+            if !(1..=max).contains(&number) {
+                return Err(invalid_setting(format!(
+                    "setting '{}' must be between 1 and {max}",
+                    key.as_str()
+                )));
+            }
+            Ok(Value::from(number))
+        }
+        SettingKind::OptionalString => match value {
+            Value::Null => Ok(Value::Null),
+            Value::String(raw) => {
+                let trimmed = raw.trim();
+                if trimmed.is_empty() {
+                    return Ok(Value::Null);
+                }
+                if trimmed.len() > MAX_SETTING_STRING_LEN {
+                    return Err(invalid_setting(format!(
+                        "setting '{}' exceeds {MAX_SETTING_STRING_LEN} characters",
+                        key.as_str()
+                    )));
+                }
+                Ok(Value::String(trimmed.to_string()))
+            }
+            _ => Err(type_error()),
+        },
+        SettingKind::Choice => {
+            let raw = value.as_str().ok_or_else(type_error)?.trim();
+            if !key.choices().contains(&raw) {
+                return Err(invalid_setting(format!(
+                    "setting '{}' must be one of: {}",
+                    key.as_str(),
+                    key.choices().join(", ")
+                )));
+            }
+            Ok(Value::String(raw.to_string()))
+        }
+    }
+}
+
+fn publish_setting_change(key: SettingKey, value: Value) {
+    let changed_at_unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0_i64, |duration| {
+            i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+        });
+    if let Ok(mut feed) = setting_changes().lock() {
+        let sequence = feed.next_sequence;
+        feed.next_sequence += 1;
+        if feed.changes.len() >= MAX_SETTING_CHANGES {
+            feed.changes.pop_front();
+        }
+        feed.changes.push_back(SettingChange {
+            sequence,
+            key,
+            value,
+            changed_at_unix_ms,
+        });
+    }
+}
+
+/// Setting changes published after `after_sequence`, oldest first. Only the most
+/// recent changes are retained, so pollers that fall far behind should re-read
+/// the full settings list.
+pub fn setting_changes_since(after_sequence: u64) -> Vec<SettingChange> {
+    setting_changes()
+        .lock()
+        .map(|feed| {
+            feed.changes
+                .iter()
+                .filter(|change| change.sequence > after_sequence)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqlite::SqliteStore;
+
+    fn test_store(test_name: &str) -> SqliteStore {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock before unix epoch")
+            .as_nanos();
+        let store = SqliteStore::new(
+            std::env::temp_dir().join(format!("helm-{test_name}-{nanos}.sqlite3")),
+        );
+        store.migrate_to_latest().expect("migrations should apply");
+        store
+    }
+
+    #[test]
+    fn setting_keys_roundtrip_through_wire_names() {
+        for key in SettingKey::ALL {
+            assert_eq!(key.as_str().parse::<SettingKey>().unwrap(), key);
+            assert_eq!(
+                serde_json::to_value(key).unwrap(),
+                Value::String(key.as_str().to_string())
+            );
+        }
+        assert!("unknown_setting".parse::<SettingKey>().is_err());
+    }
+
+    #[test]
+    fn validate_setting_value_enforces_kind_and_range() {
+        assert_eq!(
+            validate_setting_value(SettingKey::SafeMode, &Value::Bool(true)).unwrap(),
+            Value::Bool(true)
+        );
+        assert!(validate_setting_value(SettingKey::SafeMode, &Value::from(1)).is_err());
+        assert!(
+            validate_setting_value(SettingKey::AutoCheckFrequencyMinutes, &Value::from(0)).is_err()
+        );
+        assert!(
+            validate_setting_value(SettingKey::AutoCheckFrequencyMinutes, &Value::from(-5))
+                .is_err()
+        );
+        assert!(
+            validate_setting_value(SettingKey::TaskHistoryRetentionDays, &Value::from(3_651))
+                .is_err()
+        );
+        assert_eq!(
+            validate_setting_value(SettingKey::TaskHistoryMaxEntries, &Value::from(50_000))
+                .unwrap(),
+            Value::from(50_000)
+        );
+        assert_eq!(
+            validate_setting_value(
+                SettingKey::CliAcceptedLicenseTermsVersion,
+                &Value::String("  ".to_string())
+            )
+            .unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            validate_setting_value(
+                SettingKey::ProcessQos,
+                &Value::String(" background ".to_string())
+            )
+            .unwrap(),
+            Value::String("background".to_string())
+        );
+        assert!(
+            validate_setting_value(SettingKey::ProcessQos, &Value::String("high".to_string()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn set_setting_persists_and_publishes_only_actual_changes() {
+        let store = test_store("settings-registry");
+        let before = setting_changes_since(0)
+            .last()
+            .map_or(0, |change| change.sequence);
+
+        let defaults = list_settings(&store).unwrap();
+        assert!(
+            defaults
+                .iter()
+                .all(|setting| setting.value == setting.default_value)
+        );
+
+        set_setting(
+            &store,
+            SettingKey::HomebrewKegAutoCleanup,
+            &Value::Bool(true),
+        )
+        .unwrap();
+        set_setting(
+            &store,
+            SettingKey::HomebrewKegAutoCleanup,
+            &Value::Bool(true),
+        )
+        .unwrap();
+        set_setting(
+            &store,
+            SettingKey::AutoCheckFrequencyMinutes,
+            &Value::from(60),
+        )
+        .unwrap();
+        assert_eq!(
+            store.homebrew_keg_policy().unwrap(),
+            HomebrewKegPolicy::Cleanup
+        );
+        assert_eq!(
+            get_setting(&store, SettingKey::AutoCheckFrequencyMinutes).unwrap(),
+            Value::from(60)
+        );
+
+        let changes: Vec<SettingKey> = setting_changes_since(before)
+            .into_iter()
+            .map(|change| change.key)
+            .filter(|key| {
+                matches!(
+                    key,
+                    SettingKey::HomebrewKegAutoCleanup | SettingKey::AutoCheckFrequencyMinutes
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                SettingKey::HomebrewKegAutoCleanup,
+                SettingKey::AutoCheckFrequencyMinutes
+            ]
+        );
+
+        let error =
+            set_setting(&store, SettingKey::SafeMode, &Value::String("yes".into())).unwrap_err();
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert!(!store.safe_mode().unwrap());
+
+        let _ = std::fs::remove_file(store.database_path());
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwapOption;
+
+use crate::models::ManagerId;
+
+/// Notified by the adapter runtime after a task's package or detection data
+/// has been persisted, so hosts can rebuild read caches off the hot path.
+pub trait PersistenceListener: Send + Sync {
+    fn snapshot_persisted(&self, manager: ManagerId);
+}
+
+/// Latest published copy of a read-mostly snapshot.
+///
+/// Readers load an `Arc` without locking; writers publish whole replacements.
+/// Every rebuild and invalidation bumps a generation, so a rebuild can only
+/// publish if nothing newer started after it read its data.
+pub struct SnapshotCache<T> {
+    current: ArcSwapOption<T>,
+    generation: AtomicU64,
+    write_lock: Mutex<()>,
+}
+
+impl<T> SnapshotCache<T> {
+    pub const fn new() -> Self {
+        Self {
+            current: ArcSwapOption::const_empty(),
+            generation: AtomicU64::new(0),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn load(&self) -> Option<Arc<T>> {
+        self.current.load_full()
+    }
+
+    /// Start a rebuild; pass the returned generation to [`Self::publish_if_current`].
+    pub fn begin_rebuild(&self) -> u64 {
+        let _guard = self.lock_writers();
+        self.generation.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    pub fn publish(&self, value: Arc<T>) {
+        let _guard = self.lock_writers();
+        self.current.store(Some(value));
+    }
+
+    /// Publish `value` unless an invalidation or newer rebuild started after `generation`.
+    pub fn publish_if_current(&self, generation: u64, value: Arc<T>) -> bool {
+        let _guard = self.lock_writers();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+        self.current.store(Some(value));
+        true
+    }
+
+    pub fn invalidate(&self) {
+        let _guard = self.lock_writers();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.current.store(None);
+    }
+
+    fn lock_writers(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> Default for SnapshotCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_snapshots_are_served_until_invalidated() {
+        let cache: SnapshotCache<Vec<&str>> = SnapshotCache::new();
+        assert!(cache.load().is_none());
+
+        cache.publish(Arc::new(vec!["git"]));
+        assert_eq!(cache.load().as_deref(), Some(&vec!["git"]));
+
+        cache.invalidate();
+        assert!(cache.load().is_none());
+    }
+
+    #[test]
+    fn rebuilds_superseded_by_invalidation_or_newer_rebuild_are_discarded() {
+        let cache: SnapshotCache<u32> = SnapshotCache::new();
+        let stale = cache.begin_rebuild();
+        cache.invalidate();
+        assert!(!cache.publish_if_current(stale, Arc::new(1)));
+        assert!(cache.load().is_none());
+
+        let older = cache.begin_rebuild();
+        let newer = cache.begin_rebuild();
+        assert!(cache.publish_if_current(newer, Arc::new(3)));
+        assert!(!cache.publish_if_current(older, Arc::new(2)));
+        assert_eq!(cache.load().as_deref(), Some(&3));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use crate::models::{ManagerId, PackageRef};
+use crate::persistence::{DetectionStore, PackageStore, PersistenceResult};
+use crate::settings::SettingKey;
+
+/// Cross-manager rule that hides one manager's snapshot rows for packages
+/// another manager owns. Each rule has its own opt-out setting.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReconciliationRule {
+    /// Tools pipx installs into their own venvs also show up in the pip
+    /// inventory of interpreters that can see those venvs; pipx owns them.
+    PipxOwnedPipPackages,
+}
+
+impl SnapshotReconciliationRule {
+    pub const ALL: [SnapshotReconciliationRule; 1] =
+        [SnapshotReconciliationRule::PipxOwnedPipPackages];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PipxOwnedPipPackages => "pipx_owned_pip_packages",
+        }
+    }
+
+    pub fn setting_key(self) -> SettingKey {
+        match self {
+            Self::PipxOwnedPipPackages => SettingKey::HidePipxPackagesFromPip,
+        }
+    }
+
+    /// Manager whose rows are hidden.
+    pub fn hidden_manager(self) -> ManagerId {
+        match self {
+            Self::PipxOwnedPipPackages => ManagerId::Pip,
+        }
+    }
+
+    /// Manager that owns the hidden packages.
+    pub fn owner_manager(self) -> ManagerId {
+        match self {
+            Self::PipxOwnedPipPackages => ManagerId::Pipx,
+        }
+    }
+
+    /// A refresh of either side can introduce duplicates, so both trigger the rule.
+    pub fn is_triggered_by(self, manager: ManagerId) -> bool {
+        manager == self.hidden_manager() || manager == self.owner_manager()
+    }
+
+    /// Rules are on unless opted out; a store read failure keeps the default.
+    pub fn is_enabled(self, store: &dyn DetectionStore) -> bool {
+        match self {
+            Self::PipxOwnedPipPackages => store.hide_pipx_packages_from_pip().unwrap_or(true),
+        }
+    }
+
+    fn owns(self, owner_names: &HashSet<String>, package: &PackageRef) -> bool {
+        package.manager == self.hidden_manager()
+            && owner_names.contains(&normalize_package_name(self, &package.name))
+    }
+}
+
+/// Apply every enabled rule that `refreshed` triggers, after its snapshot was
+/// persisted. Returns the number of snapshot rows removed.
+///
+/// Hidden rows come back on the next refresh of their manager once a rule is
+/// turned off.
+pub fn reconcile_package_snapshots(
+    package_store: &dyn PackageStore,
+    detection_store: Option<&dyn DetectionStore>,
+    refreshed: ManagerId,
+) -> PersistenceResult<usize> {
+    let mut removed = 0;
+    for rule in SnapshotReconciliationRule::ALL {
+        if !rule.is_triggered_by(refreshed)
+            || detection_store.is_some_and(|store| !rule.is_enabled(store))
+        {
+            continue;
+        }
+        removed += apply_rule(package_store, rule)?;
+    }
+    Ok(removed)
+}
+
+fn apply_rule(
+    package_store: &dyn PackageStore,
+    rule: SnapshotReconciliationRule,
+) -> PersistenceResult<usize> {
+    let installed = package_store.list_installed()?;
+    let owner_names = installed
+        .iter()
+        .filter(|package| package.package.manager == rule.owner_manager())
+        .map(|package| normalize_package_name(rule, &package.package.name))
+        .collect::<HashSet<_>>();
+    if owner_names.is_empty() {
+        return Ok(0);
+    }
+
+    let outdated = package_store.list_outdated()?;
+    let hidden = installed
+        .iter()
+        .map(|package| &package.package)
+        .chain(outdated.iter().map(|package| &package.package))
+        .filter(|package| rule.owns(&owner_names, package))
+        .map(|package| package.name.clone())
+        .collect::<BTreeSet<_>>();
+    if hidden.is_empty() {
+        return Ok(0);
+    }
+    let hidden = hidden.into_iter().collect::<Vec<_>>();
+    package_store.remove_snapshot_packages(rule.hidden_manager(), &hidden)
+}
+
+fn normalize_package_name(rule: SnapshotReconciliationRule, name: &str) -> String {
+    match rule {
+        // PEP 503: case-insensitive, with runs of `-`, `_`, and `.` equivalent.
+        SnapshotReconciliationRule::PipxOwnedPipPackages => {
+            let mut normalized = String::with_capacity(name.len());
+            for character in name.trim().chars() {
+                if matches!(character, '-' | '_' | '.') {
+                    if !normalized.ends_with('-') {
+                        normalized.push('-');
+                    }
+                } else {
+                    normalized.extend(character.to_lowercase());
+                }
+            }
+            normalized
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotReconciliationRule, normalize_package_name};
+    use crate::models::ManagerId;
+
+    #[test]
+    fn pip_names_normalize_per_pep_503() {
+        let rule = SnapshotReconciliationRule::PipxOwnedPipPackages;
+        assert_eq!(normalize_package_name(rule, "Poetry_Core"), "poetry-core");
+        assert_eq!(
+            normalize_package_name(rule, "zope.interface"),
+            "zope-interface"
+        );
+        assert_eq!(normalize_package_name(rule, "a-_.b"), "a-b");
+    }
+
+    #[test]
+    fn pipx_rule_is_triggered_by_either_side() {
+        let rule = SnapshotReconciliationRule::PipxOwnedPipPackages;
+        assert!(rule.is_triggered_by(ManagerId::Pip));
+        assert!(rule.is_triggered_by(ManagerId::Pipx));
+        assert!(!rule.is_triggered_by(ManagerId::Npm));
+    }
+}
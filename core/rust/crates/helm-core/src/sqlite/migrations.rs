@@ -766,7 +766,637 @@ CREATE INDEX IF NOT EXISTS idx_search_cache_query_time
 "#,
 };
 
-const MIGRATIONS: [SqliteMigration; 16] = [
+const MIGRATION_0017: SqliteMigration = SqliteMigration {
+    version: 17,
+    name: "add_package_install_options",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_install_options (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    options_json TEXT NOT NULL,
+    updated_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS package_install_options;
+"#,
+};
+
+const MIGRATION_0018: SqliteMigration = SqliteMigration {
+    version: 18,
+    name: "add_manager_self_versions",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS manager_self_versions (
+    manager_id TEXT PRIMARY KEY,
+    installed_version TEXT,
+    latest_version TEXT,
+    checked_at_unix INTEGER NOT NULL
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS manager_self_versions;
+"#,
+};
+
+const MIGRATION_0019: SqliteMigration = SqliteMigration {
+    version: 19,
+    name: "add_manager_env_settings",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS manager_env_settings (
+    manager_id TEXT NOT NULL,
+    setting_key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (manager_id, setting_key)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS manager_env_settings;
+"#,
+};
+
+const MIGRATION_0020: SqliteMigration = SqliteMigration {
+    version: 20,
+    name: "add_search_completions",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS search_completions (
+    query TEXT NOT NULL,
+    manager_id TEXT NOT NULL,
+    result_count INTEGER NOT NULL,
+    completed_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (query, manager_id)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS search_completions;
+"#,
+};
+
+const MIGRATION_0021: SqliteMigration = SqliteMigration {
+    version: 21,
+    name: "add_package_snapshot_freshness",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_snapshot_freshness (
+    manager_id TEXT PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL,
+    partial INTEGER NOT NULL DEFAULT 0,
+    updated_at_unix INTEGER NOT NULL
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS package_snapshot_freshness;
+"#,
+};
+
+const MIGRATION_0022: SqliteMigration = SqliteMigration {
+    version: 22,
+    name: "add_package_sizes",
+    up_sql: r#"
+ALTER TABLE installed_package_versions ADD COLUMN install_size_bytes INTEGER;
+ALTER TABLE installed_package_versions ADD COLUMN download_size_bytes INTEGER;
+ALTER TABLE outdated_packages ADD COLUMN install_size_bytes INTEGER;
+ALTER TABLE outdated_packages ADD COLUMN download_size_bytes INTEGER;
+"#,
+    down_sql: r#"
+CREATE TABLE installed_package_versions_without_sizes (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    package_identifier TEXT NOT NULL DEFAULT '',
+    installed_version TEXT NOT NULL DEFAULT '',
+    pinned INTEGER NOT NULL DEFAULT 0,
+    is_active INTEGER NOT NULL DEFAULT 0,
+    is_default INTEGER NOT NULL DEFAULT 0,
+    has_override INTEGER NOT NULL DEFAULT 0,
+    updated_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name, package_identifier, installed_version)
+);
+
+INSERT INTO installed_package_versions_without_sizes (
+    manager_id,
+    package_name,
+    package_identifier,
+    installed_version,
+    pinned,
+    is_active,
+    is_default,
+    has_override,
+    updated_at_unix
+)
+SELECT
+    manager_id,
+    package_name,
+    package_identifier,
+    installed_version,
+    pinned,
+    is_active,
+    is_default,
+    has_override,
+    updated_at_unix
+FROM installed_package_versions;
+
+DROP INDEX IF EXISTS idx_installed_package_versions_manager_package;
+DROP TABLE installed_package_versions;
+ALTER TABLE installed_package_versions_without_sizes RENAME TO installed_package_versions;
+
+CREATE INDEX IF NOT EXISTS idx_installed_package_versions_manager_package
+    ON installed_package_versions (manager_id, package_name, package_identifier);
+
+CREATE TABLE outdated_packages_without_sizes (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    package_identifier TEXT NOT NULL DEFAULT '',
+    installed_version TEXT,
+    candidate_version TEXT NOT NULL,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    restart_required INTEGER NOT NULL DEFAULT 0,
+    is_active INTEGER NOT NULL DEFAULT 0,
+    is_default INTEGER NOT NULL DEFAULT 0,
+    has_override INTEGER NOT NULL DEFAULT 0,
+    updated_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name, package_identifier)
+);
+
+INSERT INTO outdated_packages_without_sizes (
+    manager_id,
+    package_name,
+    package_identifier,
+    installed_version,
+    candidate_version,
+    pinned,
+    restart_required,
+    is_active,
+    is_default,
+    has_override,
+    updated_at_unix
+)
+SELECT
+    manager_id,
+    package_name,
+    package_identifier,
+    installed_version,
+    candidate_version,
+    pinned,
+    restart_required,
+    is_active,
+    is_default,
+    has_override,
+    updated_at_unix
+FROM outdated_packages;
+
+DROP TABLE outdated_packages;
+ALTER TABLE outdated_packages_without_sizes RENAME TO outdated_packages;
+"#,
+};
+
+const MIGRATION_0023: SqliteMigration = SqliteMigration {
+    version: 23,
+    name: "add_task_attempt_of",
+    up_sql: r#"
+ALTER TABLE task_records ADD COLUMN attempt_of INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_task_records_attempt_of
+    ON task_records (attempt_of);
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_task_records_attempt_of;
+
+CREATE TABLE task_records_without_attempts (
+    task_id INTEGER PRIMARY KEY,
+    manager_id TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at_unix INTEGER NOT NULL
+);
+
+INSERT INTO task_records_without_attempts (
+    task_id,
+    manager_id,
+    task_type,
+    status,
+    created_at_unix
+)
+SELECT task_id, manager_id, task_type, status, created_at_unix
+FROM task_records;
+
+DROP TABLE task_records;
+ALTER TABLE task_records_without_attempts RENAME TO task_records;
+"#,
+};
+
+const MIGRATION_0024: SqliteMigration = SqliteMigration {
+    version: 24,
+    name: "add_search_cache_popularity",
+    up_sql: r#"
+ALTER TABLE search_cache ADD COLUMN downloads INTEGER;
+ALTER TABLE search_cache ADD COLUMN popularity_rank INTEGER;
+"#,
+    down_sql: r#"
+CREATE TABLE search_cache_without_popularity (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    version TEXT,
+    summary TEXT,
+    originating_query TEXT NOT NULL,
+    cached_at_unix INTEGER NOT NULL,
+    package_identifier TEXT NOT NULL DEFAULT ''
+);
+
+INSERT INTO search_cache_without_popularity (
+    manager_id,
+    package_name,
+    version,
+    summary,
+    originating_query,
+    cached_at_unix,
+    package_identifier
+)
+SELECT
+    manager_id,
+    package_name,
+    version,
+    summary,
+    originating_query,
+    cached_at_unix,
+    package_identifier
+FROM search_cache;
+
+DROP INDEX IF EXISTS idx_search_cache_query_time;
+DROP TABLE search_cache;
+ALTER TABLE search_cache_without_popularity RENAME TO search_cache;
+
+CREATE INDEX IF NOT EXISTS idx_search_cache_query_time
+    ON search_cache (originating_query, cached_at_unix DESC);
+"#,
+};
+
+const MIGRATION_0025: SqliteMigration = SqliteMigration {
+    version: 25,
+    name: "add_refresh_results",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS refresh_results (
+    manager_id TEXT PRIMARY KEY,
+    error_kind TEXT,
+    error_message TEXT,
+    duration_ms INTEGER NOT NULL,
+    run_started_at_unix INTEGER NOT NULL,
+    finished_at_unix INTEGER NOT NULL
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS refresh_results;
+"#,
+};
+
+const MIGRATION_0026: SqliteMigration = SqliteMigration {
+    version: 26,
+    name: "add_package_dependencies",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_dependencies (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    dependency_name TEXT NOT NULL,
+    PRIMARY KEY (manager_id, package_name, dependency_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_package_dependencies_dependency
+    ON package_dependencies (manager_id, dependency_name);
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_package_dependencies_dependency;
+DROP TABLE IF EXISTS package_dependencies;
+"#,
+};
+
+const MIGRATION_0027: SqliteMigration = SqliteMigration {
+    version: 27,
+    name: "add_manager_process_qos",
+    up_sql: r#"
+ALTER TABLE manager_preferences ADD COLUMN process_qos TEXT;
+"#,
+    down_sql: r#"
+CREATE TABLE manager_preferences_backup (
+    manager_id TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    selected_executable_path TEXT,
+    selected_install_method TEXT,
+    timeout_hard_seconds INTEGER,
+    timeout_idle_seconds INTEGER
+);
+INSERT INTO manager_preferences_backup
+    SELECT manager_id, enabled, selected_executable_path, selected_install_method,
+           timeout_hard_seconds, timeout_idle_seconds
+    FROM manager_preferences;
+DROP TABLE manager_preferences;
+ALTER TABLE manager_preferences_backup RENAME TO manager_preferences;
+"#,
+};
+
+const MIGRATION_0028: SqliteMigration = SqliteMigration {
+    version: 28,
+    name: "add_manager_executable_candidates",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS manager_executable_candidates (
+    manager_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    version TEXT,
+    last_validated_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, path)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS manager_executable_candidates;
+"#,
+};
+
+const MIGRATION_0029: SqliteMigration = SqliteMigration {
+    version: 29,
+    name: "add_package_version_history",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_version_history (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    previous_version TEXT NOT NULL,
+    replaced_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS package_version_history;
+"#,
+};
+
+const MIGRATION_0030: SqliteMigration = SqliteMigration {
+    version: 30,
+    name: "add_task_history",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS task_history (
+    task_id INTEGER PRIMARY KEY,
+    manager_id TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    exit_code INTEGER,
+    error_code TEXT,
+    error_message TEXT,
+    duration_ms INTEGER,
+    stdout_tail TEXT,
+    stderr_tail TEXT,
+    finished_at_unix INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_history_finished_at
+    ON task_history (finished_at_unix DESC, task_id DESC);
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_task_history_finished_at;
+DROP TABLE IF EXISTS task_history;
+"#,
+};
+
+const MIGRATION_0031: SqliteMigration = SqliteMigration {
+    version: 31,
+    name: "add_manager_detection_outcome",
+    up_sql: r#"
+ALTER TABLE manager_detection ADD COLUMN outcome TEXT;
+ALTER TABLE manager_detection ADD COLUMN probe_error_key TEXT;
+"#,
+    down_sql: r#"
+CREATE TABLE manager_detection_backup (
+    manager_id TEXT PRIMARY KEY,
+    detected INTEGER NOT NULL DEFAULT 0,
+    executable_path TEXT,
+    version TEXT,
+    detected_at_unix INTEGER NOT NULL
+);
+INSERT INTO manager_detection_backup
+    SELECT manager_id, detected, executable_path, version, detected_at_unix
+    FROM manager_detection;
+DROP TABLE manager_detection;
+ALTER TABLE manager_detection_backup RENAME TO manager_detection;
+"#,
+};
+
+const MIGRATION_0032: SqliteMigration = SqliteMigration {
+    version: 32,
+    name: "add_task_labels",
+    up_sql: r#"
+ALTER TABLE task_records ADD COLUMN label_key TEXT;
+ALTER TABLE task_records ADD COLUMN label_args_json TEXT;
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_task_records_attempt_of;
+
+CREATE TABLE task_records_without_labels (
+    task_id INTEGER PRIMARY KEY,
+    manager_id TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at_unix INTEGER NOT NULL,
+    attempt_of INTEGER
+);
+
+INSERT INTO task_records_without_labels (
+    task_id,
+    manager_id,
+    task_type,
+    status,
+    created_at_unix,
+    attempt_of
+)
+SELECT task_id, manager_id, task_type, status, created_at_unix, attempt_of
+FROM task_records;
+
+DROP TABLE task_records;
+ALTER TABLE task_records_without_labels RENAME TO task_records;
+
+CREATE INDEX IF NOT EXISTS idx_task_records_attempt_of
+    ON task_records (attempt_of);
+"#,
+};
+
+const MIGRATION_0033: SqliteMigration = SqliteMigration {
+    version: 33,
+    name: "add_package_snapshot_stage_freshness",
+    up_sql: r#"
+ALTER TABLE package_snapshot_freshness ADD COLUMN installed_partial INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE package_snapshot_freshness ADD COLUMN outdated_partial INTEGER NOT NULL DEFAULT 0;
+UPDATE package_snapshot_freshness
+SET installed_partial = partial,
+    outdated_partial = partial;
+"#,
+    down_sql: r#"
+CREATE TABLE package_snapshot_freshness_without_stages (
+    manager_id TEXT PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL,
+    partial INTEGER NOT NULL DEFAULT 0,
+    updated_at_unix INTEGER NOT NULL
+);
+
+INSERT INTO package_snapshot_freshness_without_stages (
+    manager_id,
+    task_id,
+    partial,
+    updated_at_unix
+)
+SELECT manager_id, task_id, partial, updated_at_unix
+FROM package_snapshot_freshness;
+
+DROP TABLE package_snapshot_freshness;
+ALTER TABLE package_snapshot_freshness_without_stages RENAME TO package_snapshot_freshness;
+"#,
+};
+
+const MIGRATION_0034: SqliteMigration = SqliteMigration {
+    version: 34,
+    name: "add_package_metadata",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_metadata (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    description TEXT,
+    homepage TEXT,
+    license TEXT,
+    installed_size_bytes INTEGER,
+    dependencies_json TEXT NOT NULL DEFAULT '[]',
+    fetched_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name)
+);
+"#,
+    down_sql: r#"
+DROP TABLE IF EXISTS package_metadata;
+"#,
+};
+
+const MIGRATION_0035: SqliteMigration = SqliteMigration {
+    version: 35,
+    name: "add_task_idempotency_keys",
+    up_sql: r#"
+ALTER TABLE task_records ADD COLUMN idempotency_key TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_task_records_idempotency_key
+    ON task_records (idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_task_records_idempotency_key;
+DROP INDEX IF EXISTS idx_task_records_attempt_of;
+
+CREATE TABLE task_records_without_idempotency_keys (
+    task_id INTEGER PRIMARY KEY,
+    manager_id TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at_unix INTEGER NOT NULL,
+    attempt_of INTEGER,
+    label_key TEXT,
+    label_args_json TEXT
+);
+
+INSERT INTO task_records_without_idempotency_keys (
+    task_id,
+    manager_id,
+    task_type,
+    status,
+    created_at_unix,
+    attempt_of,
+    label_key,
+    label_args_json
+)
+SELECT task_id, manager_id, task_type, status, created_at_unix, attempt_of,
+    label_key, label_args_json
+FROM task_records;
+
+DROP TABLE task_records;
+ALTER TABLE task_records_without_idempotency_keys RENAME TO task_records;
+
+CREATE INDEX IF NOT EXISTS idx_task_records_attempt_of
+    ON task_records (attempt_of);
+"#,
+};
+
+const MIGRATION_0036: SqliteMigration = SqliteMigration {
+    version: 36,
+    name: "add_pin_version_constraints",
+    up_sql: r#"
+ALTER TABLE pin_records ADD COLUMN version_constraint TEXT NOT NULL DEFAULT '';
+"#,
+    down_sql: r#"
+CREATE TABLE pin_records_without_constraints (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    pin_kind TEXT NOT NULL,
+    pinned_version TEXT NOT NULL DEFAULT '',
+    created_at_unix INTEGER NOT NULL,
+    PRIMARY KEY (manager_id, package_name, pinned_version)
+);
+
+INSERT INTO pin_records_without_constraints (
+    manager_id,
+    package_name,
+    pin_kind,
+    pinned_version,
+    created_at_unix
+)
+SELECT manager_id, package_name, pin_kind, pinned_version, created_at_unix
+FROM pin_records;
+
+DROP TABLE pin_records;
+ALTER TABLE pin_records_without_constraints RENAME TO pin_records;
+"#,
+};
+
+const MIGRATION_0037: SqliteMigration = SqliteMigration {
+    version: 37,
+    name: "add_package_aliases",
+    up_sql: r#"
+CREATE TABLE IF NOT EXISTS package_aliases (
+    manager_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    alias_kind TEXT NOT NULL,
+    alias TEXT NOT NULL,
+    normalized_alias TEXT NOT NULL,
+    PRIMARY KEY (manager_id, package_name, alias_kind, alias)
+);
+
+CREATE INDEX IF NOT EXISTS idx_package_aliases_normalized_alias
+    ON package_aliases (normalized_alias);
+"#,
+    down_sql: r#"
+DROP INDEX IF EXISTS idx_package_aliases_normalized_alias;
+DROP TABLE IF EXISTS package_aliases;
+"#,
+};
+
+const MIGRATION_0038: SqliteMigration = SqliteMigration {
+    version: 38,
+    name: "normalize_package_key_names",
+    // Rows keyed by package name now store the canonical `PackageKey` name:
+    // trimmed of surrounding spaces, tabs, and line breaks, and without the
+    // Homebrew cleanup suffix older clients appended.
+    // Where the normalized row already exists, it wins over the legacy one.
+    up_sql: r#"
+UPDATE OR IGNORE pin_records
+SET package_name = TRIM(package_name, ' ' || char(9) || char(10) || char(13))
+WHERE package_name <> TRIM(package_name, ' ' || char(9) || char(10) || char(13));
+DELETE FROM pin_records WHERE package_name <> TRIM(package_name, ' ' || char(9) || char(10) || char(13));
+
+UPDATE OR IGNORE package_keg_policies
+SET package_name = TRIM(package_name, ' ' || char(9) || char(10) || char(13))
+WHERE package_name <> TRIM(package_name, ' ' || char(9) || char(10) || char(13));
+DELETE FROM package_keg_policies WHERE package_name <> TRIM(package_name, ' ' || char(9) || char(10) || char(13));
+
+UPDATE OR IGNORE package_keg_policies
+SET package_name = SUBSTR(package_name, 1, LENGTH(package_name) - LENGTH('@@helm.cleanup'))
+WHERE manager_id = 'homebrew_formula' AND package_name LIKE '%@@helm.cleanup';
+DELETE FROM package_keg_policies
+WHERE manager_id = 'homebrew_formula' AND package_name LIKE '%@@helm.cleanup';
+"#,
+    down_sql: r#"
+-- Normalized names are valid under the previous schema; nothing to undo.
+"#,
+};
+
+const MIGRATIONS: [SqliteMigration; 38] = [
     MIGRATION_0001,
     MIGRATION_0002,
     MIGRATION_0003,
@@ -783,6 +1413,28 @@ const MIGRATIONS: [SqliteMigration; 16] = [
     MIGRATION_0014,
     MIGRATION_0015,
     MIGRATION_0016,
+    MIGRATION_0017,
+    MIGRATION_0018,
+    MIGRATION_0019,
+    MIGRATION_0020,
+    MIGRATION_0021,
+    MIGRATION_0022,
+    MIGRATION_0023,
+    MIGRATION_0024,
+    MIGRATION_0025,
+    MIGRATION_0026,
+    MIGRATION_0027,
+    MIGRATION_0028,
+    MIGRATION_0029,
+    MIGRATION_0030,
+    MIGRATION_0031,
+    MIGRATION_0032,
+    MIGRATION_0033,
+    MIGRATION_0034,
+    MIGRATION_0035,
+    MIGRATION_0036,
+    MIGRATION_0037,
+    MIGRATION_0038,
 ];
 
 pub fn migrations() -> &'static [SqliteMigration] {
@@ -1,33 +1,75 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use rusqlite::{Connection, OptionalExtension, params};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
 
 use crate::models::{
-    AutomationLevel, CachedSearchResult, CoreError, CoreErrorKind, DetectionInfo,
-    HomebrewKegPolicy, InstallInstanceIdentityKind, InstallProvenance, InstalledPackage, ManagerId,
-    ManagerInstallInstance, NewTaskLogRecord, OutdatedPackage, PackageCandidate, PackageKegPolicy,
-    PackageRef, PinKind, PinRecord, StrategyKind, TaskId, TaskLogLevel, TaskLogRecord, TaskRecord,
-    TaskStatus, TaskType,
+    AutomationLevel, CachedSearchResult, CoreError, CoreErrorKind, DetectionInfo, DetectionOutcome,
+    HomebrewKegPolicy, InstallInstanceIdentityKind, InstallProvenance, InstalledPackage,
+    InstalledPackageSize, ManagerId, ManagerInstallInstance, ManagerRefreshError,
+    ManagerRefreshResult, ManagerSelfVersion, NewTaskLogRecord, OnboardingChoices, OnboardingPhase,
+    OutdatedPackage, PackageAlias, PackageAliasKind, PackageCandidate, PackageDependency,
+    PackageKegPolicy, PackageMetadata, PackageRef, PackageSnapshotFreshness, PinKind, PinRecord,
+    RankedSearchResult, SearchCompletion, SearchPopularity, StrategyKind, TaskHistoryFilter,
+    TaskHistoryRecord, TaskId, TaskLabel, TaskLogLevel, TaskLogRecord, TaskRecord, TaskStatus,
+    TaskType, normalize_search_completion_query, rank_search_results_with_aliases,
 };
+use crate::package_aliases::{PackageAliasIndex, normalize_package_alias};
 use crate::persistence::{
-    DetectionStore, ManagerPreference, MigrationStore, PackageManagerPreference, PackageStore,
-    PersistenceResult, PinStore, SearchCacheStore, TaskStore,
+    DependencyStore, DetectionStore, ManagerEnvSetting, ManagerExecutableCandidate,
+    ManagerPreference, MigrationStore, PackageManagerPreference, PackageMetadataStore,
+    PackageStore, PersistenceResult, PinStore, SearchCacheStore, TaskStore,
 };
 use crate::sqlite::migrations::{SqliteMigration, current_schema_version, migration, migrations};
+use crate::versioning::VersionConstraint;
 use crate::versioning::normalize_package_family_key;
 
 const MIGRATIONS_TABLE: &str = "helm_schema_migrations";
+const READ_STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Version, summary, downloads, and rank of a cached search entry.
+type ExistingSearchCacheRow = (Option<String>, Option<String>, Option<i64>, Option<i64>);
+
+struct ReadSnapshot {
+    database_path: PathBuf,
+    connection: Connection,
+}
+
+thread_local! {
+    static READ_SNAPSHOT: RefCell<Option<ReadSnapshot>> = const { RefCell::new(None) };
+}
+
+/// Ends the snapshot transaction even if the read scope unwinds.
+struct ReadSnapshotGuard;
+
+impl Drop for ReadSnapshotGuard {
+    fn drop(&mut self) {
+        let snapshot = READ_SNAPSHOT.with(|slot| slot.borrow_mut().take());
+        if let Some(snapshot) = snapshot {
+            let _ = snapshot.connection.execute_batch("COMMIT");
+        }
+    }
+}
 
 pub struct SqliteStore {
     database_path: PathBuf,
+    /// Long-lived `query_only` connection reused by listing operations so their
+    /// prepared statements stay cached between polls.
+    read_connection: Mutex<Option<Connection>>,
+    /// Manager ids found in listed rows that this build does not recognize.
+    unknown_manager_ids: Mutex<BTreeSet<String>>,
 }
 
 impl SqliteStore {
     pub fn new(database_path: impl Into<PathBuf>) -> Self {
         Self {
             database_path: database_path.into(),
+            read_connection: Mutex::new(None),
+            unknown_manager_ids: Mutex::new(BTreeSet::new()),
         }
     }
 
@@ -35,6 +77,55 @@ impl SqliteStore {
         &self.database_path
     }
 
+    /// Manager ids this build does not recognize that listings have skipped,
+    /// typically rows written by a newer Helm before a downgrade.
+    pub fn unknown_manager_ids(&self) -> Vec<String> {
+        self.unknown_manager_ids
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Collect listing rows, leaving out rows whose manager id this build does
+    /// not recognize instead of failing the whole listing. Other row errors
+    /// still fail it.
+    fn collect_known_manager_rows<T>(
+        &self,
+        operation_name: &str,
+        rows: impl Iterator<Item = rusqlite::Result<T>>,
+    ) -> rusqlite::Result<Vec<T>> {
+        let mut collected = Vec::new();
+        for row in rows {
+            match row {
+                Ok(value) => collected.push(value),
+                Err(error) => match unknown_manager_id(&error) {
+                    Some(raw) => self.note_unknown_manager_id(operation_name, raw),
+                    None => return Err(error),
+                },
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Records `raw`, warning only the first time each id is seen so polling
+    /// listings do not flood the log.
+    fn note_unknown_manager_id(&self, operation_name: &str, raw: &str) {
+        let newly_seen = self
+            .unknown_manager_ids
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(raw.to_string());
+        if newly_seen {
+            tracing::warn!(
+                operation = operation_name,
+                manager_id = raw,
+                "skipping sqlite rows with an unknown manager id"
+            );
+        }
+    }
+
     pub fn planned_migrations(&self, from_version: i64) -> Vec<&'static SqliteMigration> {
         migrations()
             .iter()
@@ -46,16 +137,115 @@ impl SqliteStore {
         self.apply_migration(current_schema_version())
     }
 
+    /// Copy every committed write-ahead-log page into the database file and
+    /// truncate the log, so a shutdown leaves nothing pending in the WAL.
+    pub fn checkpoint(&self) -> PersistenceResult<()> {
+        self.with_connection("checkpoint", |connection| {
+            connection.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+        })
+    }
+
+    /// Run `read` with every store call on this thread sharing one connection inside a
+    /// single deferred transaction, so multi-table listings observe one consistent
+    /// snapshot even while a refresh commits concurrently.
+    ///
+    /// The snapshot connection is `query_only`, so a write attempted inside the
+    /// scope fails instead of joining the snapshot transaction. Nested scopes reuse
+    /// the outer snapshot.
+    pub fn read_snapshot<T>(&self, read: impl FnOnce() -> T) -> PersistenceResult<T> {
+        let already_active = READ_SNAPSHOT.with(|slot| {
+            slot.try_borrow()
+                .map(|snapshot| snapshot.is_some())
+                .unwrap_or(true)
+        });
+        if already_active {
+            return Ok(read());
+        }
+
+        let connection = open_read_connection(&self.database_path)
+            .map_err(|error| storage_error("read_snapshot", error))?;
+        connection
+            .execute_batch("BEGIN DEFERRED")
+            .map_err(|error| storage_error("read_snapshot", error))?;
+        READ_SNAPSHOT.with(|slot| {
+            *slot.borrow_mut() = Some(ReadSnapshot {
+                database_path: self.database_path.clone(),
+                connection,
+            });
+        });
+        let _guard = ReadSnapshotGuard;
+        Ok(read())
+    }
+
     fn with_connection<T>(
         &self,
         operation_name: &str,
         operation: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
     ) -> PersistenceResult<T> {
+        let mut operation = Some(operation);
+        let snapshot_result = READ_SNAPSHOT.with(|slot| {
+            let mut slot = slot.try_borrow_mut().ok()?;
+            let snapshot = slot
+                .as_mut()
+                .filter(|snapshot| snapshot.database_path == self.database_path)?;
+            let operation = operation.take()?;
+            Some(
+                operation(&mut snapshot.connection)
+                    .map_err(|error| storage_error(operation_name, error)),
+            )
+        });
+        if let Some(result) = snapshot_result {
+            return result;
+        }
+        let Some(operation) = operation else {
+            return Err(storage_error_text(
+                operation_name,
+                "snapshot connection consumed the operation",
+            ));
+        };
+
         let mut connection = open_connection(&self.database_path)
             .map_err(|error| storage_error(operation_name, error))?;
         operation(&mut connection).map_err(|error| storage_error(operation_name, error))
     }
 
+    /// Run a listing on a `query_only` connection, separate from the write path.
+    ///
+    /// The store keeps one such connection (and its prepared statement cache)
+    /// between calls; when another thread is already using it, the read opens a
+    /// short-lived `query_only` connection instead of waiting. Inside
+    /// [`Self::read_snapshot`] the snapshot connection is used as usual.
+    fn with_read_connection<T>(
+        &self,
+        operation_name: &str,
+        operation: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> PersistenceResult<T> {
+        let snapshot_active = READ_SNAPSHOT.with(|slot| {
+            slot.try_borrow().map_or(true, |snapshot| {
+                snapshot
+                    .as_ref()
+                    .is_some_and(|snapshot| snapshot.database_path == self.database_path)
+            })
+        });
+        if snapshot_active {
+            return self.with_connection(operation_name, |connection| operation(connection));
+        }
+
+        let Ok(mut cached) = self.read_connection.try_lock() else {
+            let connection = open_read_connection(&self.database_path)
+                .map_err(|error| storage_error(operation_name, error))?;
+            return operation(&connection).map_err(|error| storage_error(operation_name, error));
+        };
+        if cached.is_none() {
+            *cached = Some(
+                open_read_connection(&self.database_path)
+                    .map_err(|error| storage_error(operation_name, error))?,
+            );
+        }
+        let connection = cached.as_ref().expect("read connection was just opened");
+        operation(connection).map_err(|error| storage_error(operation_name, error))
+    }
+
     pub fn latest_search_cached_at_unix(
         &self,
         manager: ManagerId,
@@ -139,14 +329,16 @@ impl PackageStore for SqliteStore {
                 let mut statement = transaction.prepare(
                     "
 INSERT INTO installed_package_versions (
-    manager_id, package_name, package_identifier, installed_version, pinned, is_active, is_default, has_override, updated_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, strftime('%s', 'now'))
+    manager_id, package_name, package_identifier, installed_version, pinned, is_active, is_default, has_override, install_size_bytes, download_size_bytes, updated_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%s', 'now'))
 ON CONFLICT(manager_id, package_name, package_identifier, installed_version) DO UPDATE SET
     installed_version = excluded.installed_version,
     pinned = excluded.pinned,
     is_active = excluded.is_active,
     is_default = excluded.is_default,
     has_override = excluded.has_override,
+    install_size_bytes = COALESCE(excluded.install_size_bytes, installed_package_versions.install_size_bytes),
+    download_size_bytes = excluded.download_size_bytes,
     updated_at_unix = excluded.updated_at_unix
 ",
                 )?;
@@ -165,6 +357,8 @@ ON CONFLICT(manager_id, package_name, package_identifier, installed_version) DO
                         bool_to_sqlite(package.runtime_state.is_active),
                         bool_to_sqlite(package.runtime_state.is_default),
                         bool_to_sqlite(package.runtime_state.has_override),
+                        size_to_sqlite(package.sizes.install_size_bytes),
+                        size_to_sqlite(package.sizes.download_size_bytes),
                     ))?;
                 }
             }
@@ -182,6 +376,23 @@ ON CONFLICT(manager_id, package_name, package_identifier, installed_version) DO
             ensure_schema_ready(connection)?;
             let transaction = connection.transaction()?;
 
+            // A measured install size stays valid while the package remains
+            // at the version that was measured.
+            let measured_sizes: HashMap<(String, String), i64> = {
+                let mut statement = transaction.prepare(
+                    "
+SELECT package_name, installed_version, install_size_bytes
+FROM installed_package_versions
+WHERE manager_id = ?1 AND install_size_bytes IS NOT NULL
+",
+                )?;
+                statement
+                    .query_map([manager.as_str()], |row| {
+                        Ok(((row.get(0)?, row.get(1)?), row.get(2)?))
+                    })?
+                    .collect::<Result<_, _>>()?
+            };
+
             transaction.execute(
                 "DELETE FROM installed_package_versions WHERE manager_id = ?1",
                 [manager.as_str()],
@@ -191,8 +402,8 @@ ON CONFLICT(manager_id, package_name, package_identifier, installed_version) DO
                 let mut statement = transaction.prepare(
                     "
 INSERT INTO installed_package_versions (
-    manager_id, package_name, package_identifier, installed_version, pinned, is_active, is_default, has_override, updated_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, strftime('%s', 'now'))
+    manager_id, package_name, package_identifier, installed_version, pinned, is_active, is_default, has_override, install_size_bytes, download_size_bytes, updated_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%s', 'now'))
 ",
                 )?;
 
@@ -210,6 +421,12 @@ INSERT INTO installed_package_versions (
                         bool_to_sqlite(package.runtime_state.is_active),
                         bool_to_sqlite(package.runtime_state.is_default),
                         bool_to_sqlite(package.runtime_state.has_override),
+                        size_to_sqlite(package.sizes.install_size_bytes).or_else(|| {
+                            measured_sizes
+                                .get(&(package.package.name.clone(), installed_version.clone()))
+                                .copied()
+                        }),
+                        size_to_sqlite(package.sizes.download_size_bytes),
                     ))?;
                 }
             }
@@ -219,6 +436,35 @@ INSERT INTO installed_package_versions (
         })
     }
 
+    fn set_installed_package_sizes(
+        &self,
+        manager: ManagerId,
+        sizes: &[InstalledPackageSize],
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_installed_package_sizes", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            {
+                let mut statement = transaction.prepare(
+                    "
+UPDATE installed_package_versions
+SET install_size_bytes = ?3
+WHERE manager_id = ?1 AND package_name = ?2
+",
+                )?;
+                for size in sizes.iter().filter(|size| size.package.manager == manager) {
+                    statement.execute(params![
+                        manager.as_str(),
+                        size.package.name,
+                        size_to_sqlite(Some(size.bytes)),
+                    ])?;
+                }
+            }
+            transaction.commit()?;
+            Ok(())
+        })
+    }
+
     fn upsert_outdated(&self, packages: &[OutdatedPackage]) -> PersistenceResult<()> {
         self.with_connection("upsert_outdated", |connection| {
             ensure_schema_ready(connection)?;
@@ -227,8 +473,8 @@ INSERT INTO installed_package_versions (
                 let mut statement = transaction.prepare(
                     "
 INSERT INTO outdated_packages (
-    manager_id, package_name, package_identifier, installed_version, candidate_version, pinned, restart_required, is_active, is_default, has_override, updated_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%s', 'now'))
+    manager_id, package_name, package_identifier, installed_version, candidate_version, pinned, restart_required, is_active, is_default, has_override, install_size_bytes, download_size_bytes, updated_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, strftime('%s', 'now'))
 ON CONFLICT(manager_id, package_name, package_identifier) DO UPDATE SET
     installed_version = excluded.installed_version,
     candidate_version = excluded.candidate_version,
@@ -237,6 +483,8 @@ ON CONFLICT(manager_id, package_name, package_identifier) DO UPDATE SET
     is_active = excluded.is_active,
     is_default = excluded.is_default,
     has_override = excluded.has_override,
+    install_size_bytes = excluded.install_size_bytes,
+    download_size_bytes = excluded.download_size_bytes,
     updated_at_unix = excluded.updated_at_unix
 ",
                 )?;
@@ -255,6 +503,8 @@ ON CONFLICT(manager_id, package_name, package_identifier) DO UPDATE SET
                         bool_to_sqlite(package.runtime_state.is_active),
                         bool_to_sqlite(package.runtime_state.is_default),
                         bool_to_sqlite(package.runtime_state.has_override),
+                        size_to_sqlite(package.sizes.install_size_bytes),
+                        size_to_sqlite(package.sizes.download_size_bytes),
                     ))?;
                 }
             }
@@ -281,8 +531,8 @@ ON CONFLICT(manager_id, package_name, package_identifier) DO UPDATE SET
                 let mut statement = transaction.prepare(
                     "
 INSERT INTO outdated_packages (
-    manager_id, package_name, package_identifier, installed_version, candidate_version, pinned, restart_required, is_active, is_default, has_override, updated_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%s', 'now'))
+    manager_id, package_name, package_identifier, installed_version, candidate_version, pinned, restart_required, is_active, is_default, has_override, install_size_bytes, download_size_bytes, updated_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, strftime('%s', 'now'))
 ",
                 )?;
 
@@ -300,6 +550,8 @@ INSERT INTO outdated_packages (
                         bool_to_sqlite(package.runtime_state.is_active),
                         bool_to_sqlite(package.runtime_state.is_default),
                         bool_to_sqlite(package.runtime_state.has_override),
+                        size_to_sqlite(package.sizes.install_size_bytes),
+                        size_to_sqlite(package.sizes.download_size_bytes),
                     ))?;
                 }
             }
@@ -310,9 +562,9 @@ INSERT INTO outdated_packages (
     }
 
     fn list_installed(&self) -> PersistenceResult<Vec<InstalledPackage>> {
-        self.with_connection("list_installed", |connection| {
+        self.with_read_connection("list_installed", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT
     ipv.manager_id,
@@ -331,7 +583,9 @@ SELECT
     END AS pinned,
     ipv.is_active,
     ipv.is_default,
-    ipv.has_override
+    ipv.has_override,
+    ipv.install_size_bytes,
+    ipv.download_size_bytes
 FROM installed_package_versions ipv
 ORDER BY ipv.manager_id, ipv.package_name, ipv.package_identifier, ipv.installed_version
 ",
@@ -346,6 +600,8 @@ ORDER BY ipv.manager_id, ipv.package_name, ipv.package_identifier, ipv.installed
                 let is_active_int: i64 = row.get(5)?;
                 let is_default_int: i64 = row.get(6)?;
                 let has_override_int: i64 = row.get(7)?;
+                let install_size_bytes: Option<i64> = row.get(8)?;
+                let download_size_bytes: Option<i64> = row.get(9)?;
 
                 let manager = parse_manager_id(&manager_id)?;
                 Ok(InstalledPackage {
@@ -361,17 +617,21 @@ ORDER BY ipv.manager_id, ipv.package_name, ipv.package_identifier, ipv.installed
                         is_default: sqlite_to_bool(is_default_int),
                         has_override: sqlite_to_bool(has_override_int),
                     },
+                    sizes: crate::models::PackageSizes {
+                        install_size_bytes: sqlite_to_size(install_size_bytes),
+                        download_size_bytes: sqlite_to_size(download_size_bytes),
+                    },
                 })
             })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_installed", rows)
         })
     }
 
     fn list_outdated(&self) -> PersistenceResult<Vec<OutdatedPackage>> {
-        self.with_connection("list_outdated", |connection| {
+        self.with_read_connection("list_outdated", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT
     op.manager_id,
@@ -385,6 +645,7 @@ SELECT
             FROM pin_records pr
             WHERE pr.manager_id = op.manager_id
               AND pr.package_name = op.package_name
+              AND pr.version_constraint = ''
               AND (
                     pr.pinned_version = ''
                     OR pr.pinned_version = COALESCE(op.installed_version, '')
@@ -395,7 +656,9 @@ SELECT
     op.restart_required,
     op.is_active,
     op.is_default,
-    op.has_override
+    op.has_override,
+    op.install_size_bytes,
+    op.download_size_bytes
 FROM outdated_packages op
 ORDER BY op.manager_id, op.package_name, op.package_identifier
 ",
@@ -412,6 +675,8 @@ ORDER BY op.manager_id, op.package_name, op.package_identifier
                 let is_active_int: i64 = row.get(7)?;
                 let is_default_int: i64 = row.get(8)?;
                 let has_override_int: i64 = row.get(9)?;
+                let install_size_bytes: Option<i64> = row.get(10)?;
+                let download_size_bytes: Option<i64> = row.get(11)?;
 
                 let manager = parse_manager_id(&manager_id)?;
                 Ok(OutdatedPackage {
@@ -429,10 +694,16 @@ ORDER BY op.manager_id, op.package_name, op.package_identifier
                         is_default: sqlite_to_bool(is_default_int),
                         has_override: sqlite_to_bool(has_override_int),
                     },
+                    sizes: crate::models::PackageSizes {
+                        install_size_bytes: sqlite_to_size(install_size_bytes),
+                        download_size_bytes: sqlite_to_size(download_size_bytes),
+                    },
                 })
             })?;
 
-            rows.collect()
+            let mut outdated = self.collect_known_manager_rows("list_outdated", rows)?;
+            apply_pin_constraints(connection, &mut outdated)?;
+            Ok(outdated)
         })
     }
 
@@ -655,6 +926,13 @@ WHERE manager_id = ?1
                 .optional()?;
 
             let mut clear_outdated = package.manager != ManagerId::Asdf;
+            let mut replaced_version = match (before_version, after_version) {
+                (Some(before), Some(after)) => Some((
+                    to_installed_version_token(Some(before)),
+                    to_installed_version_token(Some(after)),
+                )),
+                _ => None,
+            };
             if let Some((
                 installed_version,
                 candidate_version,
@@ -694,6 +972,10 @@ ON CONFLICT(manager_id, package_name, package_identifier, installed_version) DO
                 let prior_version_token = to_installed_version_token(
                     before_version.or(installed_version.as_deref()),
                 );
+                replaced_version = Some((
+                    prior_version_token.clone(),
+                    promoted_version_token.clone(),
+                ));
                 if prior_version_token != promoted_version_token {
                     transaction.execute(
                         "
@@ -716,6 +998,23 @@ WHERE manager_id = ?1
                 }
             }
 
+            if let Some((prior, promoted)) = replaced_version
+                && !prior.is_empty()
+                && prior != promoted
+            {
+                transaction.execute(
+                    "
+INSERT INTO package_version_history (
+    manager_id, package_name, previous_version, replaced_at_unix
+) VALUES (?1, ?2, ?3, strftime('%s', 'now'))
+ON CONFLICT(manager_id, package_name) DO UPDATE SET
+    previous_version = excluded.previous_version,
+    replaced_at_unix = excluded.replaced_at_unix
+",
+                    params![package.manager.as_str(), package.name.as_str(), prior],
+                )?;
+            }
+
             if clear_outdated {
                 transaction.execute(
                     "
@@ -736,151 +1035,548 @@ WHERE manager_id = ?1
             Ok(())
         })
     }
-}
 
-impl PinStore for SqliteStore {
-    fn upsert_pin(&self, pin: &PinRecord) -> PersistenceResult<()> {
-        self.with_connection("upsert_pin", |connection| {
+    fn previous_installed_version(
+        &self,
+        package: &PackageRef,
+    ) -> PersistenceResult<Option<String>> {
+        self.with_connection("previous_installed_version", |connection| {
             ensure_schema_ready(connection)?;
-            connection.execute(
+            connection
+                .query_row(
+                    "
+SELECT previous_version
+FROM package_version_history
+WHERE manager_id = ?1
+  AND package_name = ?2
+",
+                    params![package.manager.as_str(), package.name.as_str()],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+    }
+
+    fn apply_rollback_result(
+        &self,
+        package: &PackageRef,
+        package_identifier: Option<&str>,
+        restored_version: Option<&str>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("apply_rollback_result", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            let restored_version_token = to_installed_version_token(restored_version);
+
+            if !restored_version_token.is_empty() {
+                transaction.execute(
+                    "
+UPDATE installed_package_versions
+SET installed_version = ?4,
+    updated_at_unix = strftime('%s', 'now')
+WHERE manager_id = ?1
+  AND package_name = ?2
+  AND package_identifier = ?3
+",
+                    params![
+                        package.manager.as_str(),
+                        package.name.as_str(),
+                        package_identifier.unwrap_or_default(),
+                        restored_version_token.as_str(),
+                    ],
+                )?;
+            }
+            transaction.execute(
                 "
-INSERT INTO pin_records (
-    manager_id, package_name, pin_kind, pinned_version, created_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5)
-ON CONFLICT(manager_id, package_name, pinned_version) DO UPDATE SET
-    pin_kind = excluded.pin_kind,
-    created_at_unix = excluded.created_at_unix
+DELETE FROM package_version_history
+WHERE manager_id = ?1
+  AND package_name = ?2
 ",
-                params![
-                    pin.package.manager.as_str(),
-                    pin.package.name.as_str(),
-                    pin_kind_to_str(pin.kind),
-                    to_installed_version_token(pin.pinned_version.as_deref()),
-                    to_unix_seconds(pin.created_at)?,
-                ],
+                params![package.manager.as_str(), package.name.as_str()],
             )?;
+
+            transaction.commit()?;
             Ok(())
         })
     }
 
-    fn remove_pin(
+    fn record_package_snapshot_freshness(
         &self,
-        package: &PackageRef,
-        pinned_version: Option<&str>,
+        freshness: &PackageSnapshotFreshness,
     ) -> PersistenceResult<()> {
-        self.with_connection("remove_pin", |connection| {
+        self.with_connection("record_package_snapshot_freshness", |connection| {
             ensure_schema_ready(connection)?;
-            let version_token = to_installed_version_token(pinned_version);
             connection.execute(
                 "
-DELETE FROM pin_records
-WHERE manager_id = ?1
-  AND package_name = ?2
-  AND pinned_version = ?3
+INSERT INTO package_snapshot_freshness (
+    manager_id,
+    task_id,
+    partial,
+    updated_at_unix,
+    installed_partial,
+    outdated_partial
+)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+ON CONFLICT(manager_id) DO UPDATE SET
+    task_id = excluded.task_id,
+    partial = excluded.partial,
+    updated_at_unix = excluded.updated_at_unix,
+    installed_partial = excluded.installed_partial,
+    outdated_partial = excluded.outdated_partial
+WHERE excluded.task_id >= package_snapshot_freshness.task_id
 ",
                 params![
-                    package.manager.as_str(),
-                    package.name.as_str(),
-                    version_token.as_str(),
+                    freshness.manager.as_str(),
+                    task_id_to_i64(freshness.task_id)?,
+                    freshness.partial,
+                    to_unix_seconds(freshness.updated_at)?,
+                    freshness.installed_partial,
+                    freshness.outdated_partial,
                 ],
             )?;
             Ok(())
         })
     }
 
-    fn list_pins(&self) -> PersistenceResult<Vec<PinRecord>> {
-        self.with_connection("list_pins", |connection| {
+    fn list_package_snapshot_freshness(&self) -> PersistenceResult<Vec<PackageSnapshotFreshness>> {
+        self.with_read_connection("list_package_snapshot_freshness", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
-SELECT manager_id, package_name, pin_kind, pinned_version, created_at_unix
-FROM pin_records
-ORDER BY manager_id, package_name, pinned_version
+SELECT manager_id, task_id, partial, updated_at_unix, installed_partial, outdated_partial
+FROM package_snapshot_freshness
+ORDER BY manager_id ASC
 ",
             )?;
             let rows = statement.query_map([], |row| {
                 let manager_raw: String = row.get(0)?;
-                let package_name: String = row.get(1)?;
-                let pin_kind_raw: String = row.get(2)?;
-                let pinned_version_raw: String = row.get(3)?;
-                let created_at_unix: i64 = row.get(4)?;
-
-                Ok(PinRecord {
-                    package: PackageRef {
-                        manager: parse_manager_id(&manager_raw)?,
-                        name: package_name,
-                    },
-                    kind: parse_pin_kind(&pin_kind_raw)?,
-                    pinned_version: from_installed_version_token(pinned_version_raw),
-                    created_at: from_unix_seconds(created_at_unix)?,
+                let task_id_raw: i64 = row.get(1)?;
+                let updated_at_unix: i64 = row.get(3)?;
+                Ok(PackageSnapshotFreshness {
+                    manager: parse_manager_id(&manager_raw)?,
+                    task_id: TaskId(i64_to_u64(task_id_raw)?),
+                    partial: row.get(2)?,
+                    installed_partial: row.get(4)?,
+                    outdated_partial: row.get(5)?,
+                    updated_at: from_unix_seconds(updated_at_unix)?,
                 })
             })?;
+            self.collect_known_manager_rows("list_package_snapshot_freshness", rows)
+        })
+    }
 
-            rows.collect()
+    fn remove_snapshot_packages(
+        &self,
+        manager: ManagerId,
+        package_names: &[String],
+    ) -> PersistenceResult<usize> {
+        self.with_connection("remove_snapshot_packages", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            let mut removed = 0;
+            for package_name in package_names {
+                removed += transaction.execute(
+                    "DELETE FROM installed_package_versions WHERE manager_id = ?1 AND package_name = ?2",
+                    params![manager.as_str(), package_name],
+                )?;
+                removed += transaction.execute(
+                    "DELETE FROM outdated_packages WHERE manager_id = ?1 AND package_name = ?2",
+                    params![manager.as_str(), package_name],
+                )?;
+            }
+            transaction.commit()?;
+            Ok(removed)
         })
     }
 }
 
-impl SearchCacheStore for SqliteStore {
-    fn upsert_search_results(&self, results: &[CachedSearchResult]) -> PersistenceResult<()> {
-        self.with_connection("upsert_search_results", |connection| {
+impl DependencyStore for SqliteStore {
+    fn replace_dependency_graph(
+        &self,
+        manager: ManagerId,
+        edges: &[PackageDependency],
+    ) -> PersistenceResult<()> {
+        self.with_connection("replace_dependency_graph", |connection| {
             ensure_schema_ready(connection)?;
             let transaction = connection.transaction()?;
+            transaction.execute(
+                "DELETE FROM package_dependencies WHERE manager_id = ?1",
+                params![manager.as_str()],
+            )?;
             {
-                let mut select_statement = transaction.prepare(
+                let mut statement = transaction.prepare(
                     "
-SELECT version, summary
-FROM search_cache
-WHERE manager_id = ?1
-  AND package_name = ?2
-  AND package_identifier = ?3
-  AND COALESCE(version, '') = COALESCE(?4, '')
-ORDER BY cached_at_unix DESC
-LIMIT 1
+INSERT OR IGNORE INTO package_dependencies (manager_id, package_name, dependency_name)
+VALUES (?1, ?2, ?3)
 ",
                 )?;
-                let mut delete_statement = transaction.prepare(
-                    "
-DELETE FROM search_cache
+                for edge in edges.iter().filter(|edge| edge.package.manager == manager) {
+                    statement.execute(params![
+                        manager.as_str(),
+                        edge.package.name.as_str(),
+                        edge.dependency.as_str()
+                    ])?;
+                }
+            }
+            transaction.commit()
+        })
+    }
+
+    fn list_dependency_graph(
+        &self,
+        manager: ManagerId,
+    ) -> PersistenceResult<Vec<PackageDependency>> {
+        self.with_read_connection("list_dependency_graph", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT package_name, dependency_name
+FROM package_dependencies
 WHERE manager_id = ?1
-  AND package_name = ?2
-  AND package_identifier = ?3
-  AND COALESCE(version, '') = COALESCE(?4, '')
-",
-                )?;
-                let mut insert_statement = transaction.prepare(
-                    "
-INSERT INTO search_cache (
-    manager_id, package_name, package_identifier, version, summary, originating_query, cached_at_unix
-) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+ORDER BY package_name, dependency_name
 ",
-                )?;
+            )?;
+            let rows = statement.query_map(params![manager.as_str()], |row| {
+                Ok(PackageDependency {
+                    package: PackageRef {
+                        manager,
+                        name: row.get(0)?,
+                    },
+                    dependency: row.get(1)?,
+                })
+            })?;
+            rows.collect()
+        })
+    }
+}
 
-                for result in results {
-                    let incoming_version = normalize_optional_text(result.result.version.clone());
-                    let package_identifier = result
-                        .result
-                        .package_identifier
-                        .as_deref()
-                        .unwrap_or_default();
-                    let existing_entry: Option<(Option<String>, Option<String>)> = select_statement
-                        .query_row(
-                            params![
-                                result.source_manager.as_str(),
-                                result.result.package.name.as_str(),
-                                package_identifier,
-                                incoming_version.as_deref(),
+impl PackageMetadataStore for SqliteStore {
+    fn upsert_package_metadata(
+        &self,
+        package: &PackageRef,
+        metadata: &PackageMetadata,
+        fetched_at: SystemTime,
+    ) -> PersistenceResult<()> {
+        self.with_connection("upsert_package_metadata", |connection| {
+            ensure_schema_ready(connection)?;
+            let dependencies_json = serde_json::to_string(&metadata.dependencies)
+                .map_err(|error| rusqlite::Error::ToSqlConversionFailure(Box::new(error)))?;
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "
+INSERT INTO package_metadata (
+    manager_id, package_name, description, homepage, license, installed_size_bytes, dependencies_json, fetched_at_unix
+)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+ON CONFLICT(manager_id, package_name) DO UPDATE SET
+    description = excluded.description,
+    homepage = excluded.homepage,
+    license = excluded.license,
+    installed_size_bytes = excluded.installed_size_bytes,
+    dependencies_json = excluded.dependencies_json,
+    fetched_at_unix = excluded.fetched_at_unix
+",
+                params![
+                    package.manager.as_str(),
+                    package.name.as_str(),
+                    metadata.description.as_deref(),
+                    metadata.homepage.as_deref(),
+                    metadata.license.as_deref(),
+                    size_to_sqlite(metadata.installed_size_bytes),
+                    dependencies_json,
+                    to_unix_seconds(fetched_at)?
+                ],
+            )?;
+            transaction.execute(
+                "DELETE FROM package_aliases WHERE manager_id = ?1 AND package_name = ?2",
+                params![package.manager.as_str(), package.name.as_str()],
+            )?;
+            {
+                let mut insert = transaction.prepare_cached(
+                    "
+INSERT OR IGNORE INTO package_aliases (
+    manager_id, package_name, alias_kind, alias, normalized_alias
+)
+VALUES (?1, ?2, ?3, ?4, ?5)
+",
+                )?;
+                for alias in &metadata.aliases {
+                    let value = alias.value.trim();
+                    let normalized = normalize_package_alias(value);
+                    if normalized.is_empty() {
+                        continue;
+                    }
+                    insert.execute(params![
+                        package.manager.as_str(),
+                        package.name.as_str(),
+                        alias.kind.as_str(),
+                        value,
+                        normalized,
+                    ])?;
+                }
+            }
+            transaction.commit()?;
+            Ok(())
+        })
+    }
+
+    fn package_metadata(
+        &self,
+        package: &PackageRef,
+    ) -> PersistenceResult<Option<(PackageMetadata, SystemTime)>> {
+        self.with_read_connection("package_metadata", |connection| {
+            ensure_schema_ready(connection)?;
+            let row = connection
+                .query_row(
+                    "
+SELECT description, homepage, license, installed_size_bytes, dependencies_json, fetched_at_unix
+FROM package_metadata
+WHERE manager_id = ?1 AND package_name = ?2
+",
+                    params![package.manager.as_str(), package.name.as_str()],
+                    |row| {
+                        Ok((
+                            row.get::<_, Option<String>>(0)?,
+                            row.get::<_, Option<String>>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                            row.get::<_, Option<i64>>(3)?,
+                            row.get::<_, String>(4)?,
+                            row.get::<_, i64>(5)?,
+                        ))
+                    },
+                )
+                .optional()?;
+            let Some((description, homepage, license, size, dependencies_json, fetched_at)) = row
+            else {
+                return Ok(None);
+            };
+            let dependencies = serde_json::from_str::<Vec<String>>(dependencies_json.as_str())
+                .map_err(|_| storage_error_sqlite("invalid package metadata dependencies value"))?;
+            let aliases = load_package_aliases(connection, Some(package))?
+                .into_iter()
+                .map(|(_, alias)| alias)
+                .collect();
+            Ok(Some((
+                PackageMetadata {
+                    description,
+                    homepage,
+                    license,
+                    installed_size_bytes: sqlite_to_size(size),
+                    dependencies,
+                    aliases,
+                },
+                from_unix_seconds(fetched_at)?,
+            )))
+        })
+    }
+
+    fn list_package_aliases(&self) -> PersistenceResult<Vec<(PackageRef, PackageAlias)>> {
+        self.with_read_connection("list_package_aliases", |connection| {
+            ensure_schema_ready(connection)?;
+            load_package_aliases(connection, None)
+        })
+    }
+}
+
+/// Cached aliases for `package`, or for every package. Rows naming a manager
+/// or alias kind this build does not know are skipped.
+fn load_package_aliases(
+    connection: &Connection,
+    package: Option<&PackageRef>,
+) -> rusqlite::Result<Vec<(PackageRef, PackageAlias)>> {
+    let mut statement = connection.prepare_cached(
+        "
+SELECT manager_id, package_name, alias_kind, alias
+FROM package_aliases
+WHERE ?1 IS NULL OR (manager_id = ?1 AND package_name = ?2)
+ORDER BY manager_id, package_name, alias_kind, alias
+",
+    )?;
+    let rows = statement.query_map(
+        params![
+            package.map(|package| package.manager.as_str()),
+            package.map(|package| package.name.as_str())
+        ],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        },
+    )?;
+    let mut aliases = Vec::new();
+    for row in rows {
+        let (manager_raw, name, kind_raw, value) = row?;
+        let (Ok(manager), Ok(kind)) = (
+            manager_raw.parse::<ManagerId>(),
+            kind_raw.parse::<PackageAliasKind>(),
+        ) else {
+            continue;
+        };
+        aliases.push((PackageRef { manager, name }, PackageAlias { kind, value }));
+    }
+    Ok(aliases)
+}
+
+impl PinStore for SqliteStore {
+    fn upsert_pin(&self, pin: &PinRecord) -> PersistenceResult<()> {
+        let key = pin.package.key();
+        self.with_connection("upsert_pin", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO pin_records (
+    manager_id, package_name, pin_kind, pinned_version, version_constraint, created_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+ON CONFLICT(manager_id, package_name, pinned_version) DO UPDATE SET
+    pin_kind = excluded.pin_kind,
+    version_constraint = excluded.version_constraint,
+    created_at_unix = excluded.created_at_unix
+",
+                params![
+                    key.manager().as_str(),
+                    key.name(),
+                    pin_kind_to_str(pin.kind),
+                    to_installed_version_token(pin.pinned_version.as_deref()),
+                    pin.version_constraint.as_deref().unwrap_or_default(),
+                    to_unix_seconds(pin.created_at)?,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn remove_pin(
+        &self,
+        package: &PackageRef,
+        pinned_version: Option<&str>,
+    ) -> PersistenceResult<()> {
+        let key = package.key();
+        self.with_connection("remove_pin", |connection| {
+            ensure_schema_ready(connection)?;
+            let version_token = to_installed_version_token(pinned_version);
+            connection.execute(
+                "
+DELETE FROM pin_records
+WHERE manager_id = ?1
+  AND package_name = ?2
+  AND pinned_version = ?3
+",
+                params![key.manager().as_str(), key.name(), version_token.as_str()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_pins(&self) -> PersistenceResult<Vec<PinRecord>> {
+        self.with_read_connection("list_pins", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, package_name, pin_kind, pinned_version, version_constraint, created_at_unix
+FROM pin_records
+ORDER BY manager_id, package_name, pinned_version
+",
+            )?;
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                let package_name: String = row.get(1)?;
+                let pin_kind_raw: String = row.get(2)?;
+                let pinned_version_raw: String = row.get(3)?;
+                let version_constraint_raw: String = row.get(4)?;
+                let created_at_unix: i64 = row.get(5)?;
+
+                Ok(PinRecord {
+                    package: PackageRef {
+                        manager: parse_manager_id(&manager_raw)?,
+                        name: package_name,
+                    },
+                    kind: parse_pin_kind(&pin_kind_raw)?,
+                    pinned_version: from_installed_version_token(pinned_version_raw),
+                    version_constraint: from_installed_version_token(version_constraint_raw),
+                    created_at: from_unix_seconds(created_at_unix)?,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_pins", rows)
+        })
+    }
+}
+
+impl SearchCacheStore for SqliteStore {
+    fn upsert_search_results(&self, results: &[CachedSearchResult]) -> PersistenceResult<()> {
+        self.with_connection("upsert_search_results", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            {
+                let mut select_statement = transaction.prepare(
+                    "
+SELECT version, summary, downloads, popularity_rank
+FROM search_cache
+WHERE manager_id = ?1
+  AND package_name = ?2
+  AND package_identifier = ?3
+  AND COALESCE(version, '') = COALESCE(?4, '')
+ORDER BY cached_at_unix DESC
+LIMIT 1
+",
+                )?;
+                let mut delete_statement = transaction.prepare(
+                    "
+DELETE FROM search_cache
+WHERE manager_id = ?1
+  AND package_name = ?2
+  AND package_identifier = ?3
+  AND COALESCE(version, '') = COALESCE(?4, '')
+",
+                )?;
+                let mut insert_statement = transaction.prepare(
+                    "
+INSERT INTO search_cache (
+    manager_id, package_name, package_identifier, version, summary, originating_query, cached_at_unix,
+    downloads, popularity_rank
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+",
+                )?;
+
+                for result in results {
+                    let incoming_version = normalize_optional_text(result.result.version.clone());
+                    let package_identifier = result
+                        .result
+                        .package_identifier
+                        .as_deref()
+                        .unwrap_or_default();
+                    let existing_entry: Option<ExistingSearchCacheRow> = select_statement
+                        .query_row(
+                            params![
+                                result.source_manager.as_str(),
+                                result.result.package.name.as_str(),
+                                package_identifier,
+                                incoming_version.as_deref(),
                             ],
-                            |row| Ok((row.get(0)?, row.get(1)?)),
+                            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
                         )
                         .optional()?;
-                    let (existing_version, existing_summary) =
-                        existing_entry.unwrap_or((None, None));
+                    let (existing_version, existing_summary, existing_downloads, existing_rank) =
+                        existing_entry.unwrap_or((None, None, None, None));
                     let merged_version = incoming_version
                         .clone()
                         .or_else(|| normalize_optional_text(existing_version));
                     let merged_summary = normalize_optional_text(result.result.summary.clone())
                         .or_else(|| normalize_optional_text(existing_summary));
+                    // Popularity lookups are best-effort; keep the last known values.
+                    let merged_downloads = result
+                        .popularity
+                        .downloads
+                        .and_then(|downloads| i64::try_from(downloads).ok())
+                        .or(existing_downloads);
+                    let merged_rank = result
+                        .popularity
+                        .rank
+                        .map(i64::from)
+                        .or(existing_rank);
 
                     delete_statement.execute(params![
                         result.source_manager.as_str(),
@@ -897,6 +1593,8 @@ INSERT INTO search_cache (
                         merged_summary.as_deref(),
                         result.originating_query.as_str(),
                         to_unix_seconds(result.cached_at)?,
+                        merged_downloads,
+                        merged_rank,
                     ])?;
                 }
             }
@@ -905,26 +1603,42 @@ INSERT INTO search_cache (
         })
     }
 
-    fn query_local(&self, query: &str, limit: usize) -> PersistenceResult<Vec<CachedSearchResult>> {
+    fn query_local_ranked(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> PersistenceResult<Vec<RankedSearchResult>> {
         if limit == 0 {
             return Ok(Vec::new());
         }
 
-        self.with_connection("query_local", |connection| {
+        self.with_read_connection("query_local", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            // Every match is ranked before the limit applies, so an exact
+            // match cached long ago still beats recent substring matches.
+            let mut statement = connection.prepare_cached(
                 "
-SELECT manager_id, package_name, package_identifier, version, summary, originating_query, cached_at_unix
-FROM search_cache
-WHERE (?1 = '' OR package_name LIKE ?2 OR package_identifier LIKE ?2 OR COALESCE(summary, '') LIKE ?2)
-ORDER BY cached_at_unix DESC, package_name ASC
-LIMIT ?3
+SELECT sc.manager_id, sc.package_name, sc.package_identifier, sc.version, sc.summary,
+    sc.originating_query, sc.cached_at_unix, sc.downloads, sc.popularity_rank,
+    EXISTS (
+        SELECT 1 FROM installed_package_versions ipv
+        WHERE ipv.manager_id = sc.manager_id AND ipv.package_name = sc.package_name
+    ) AS installed
+FROM search_cache sc
+WHERE (?1 = '' OR sc.package_name LIKE ?2 OR sc.package_identifier LIKE ?2 OR COALESCE(sc.summary, '') LIKE ?2
+    OR EXISTS (
+        SELECT 1 FROM package_aliases pa
+        WHERE pa.manager_id = sc.manager_id AND pa.package_name = sc.package_name
+            AND pa.alias_kind != 'bundle_id'
+            AND (pa.alias LIKE ?2 OR (?3 != '' AND pa.normalized_alias LIKE '%' || ?3 || '%'))
+    ))
+ORDER BY sc.cached_at_unix DESC, sc.package_name ASC
 ",
             )?;
 
             let pattern = format!("%{}%", query.trim());
-            let rows =
-                statement.query_map(params![query.trim(), pattern, to_i64(limit)?], |row| {
+            let normalized_query = normalize_package_alias(query);
+            let rows = statement.query_map(params![query.trim(), pattern, normalized_query], |row| {
                     let manager_raw: String = row.get(0)?;
                     let package_name: String = row.get(1)?;
                     let package_identifier_raw: String = row.get(2)?;
@@ -932,9 +1646,12 @@ LIMIT ?3
                     let summary: Option<String> = row.get(4)?;
                     let originating_query: String = row.get(5)?;
                     let cached_at_unix: i64 = row.get(6)?;
+                    let downloads: Option<i64> = row.get(7)?;
+                    let popularity_rank: Option<i64> = row.get(8)?;
+                    let installed: i64 = row.get(9)?;
 
                     let manager = parse_manager_id(&manager_raw)?;
-                    Ok(CachedSearchResult {
+                    let result = CachedSearchResult {
                         result: PackageCandidate {
                             package: PackageRef {
                                 manager,
@@ -949,10 +1666,70 @@ LIMIT ?3
                         source_manager: manager,
                         originating_query,
                         cached_at: from_unix_seconds(cached_at_unix)?,
-                    })
+                        popularity: SearchPopularity {
+                            downloads: downloads.and_then(|value| u64::try_from(value).ok()),
+                            rank: popularity_rank.and_then(|value| u32::try_from(value).ok()),
+                        },
+                    };
+                    Ok((result, sqlite_to_bool(installed)))
                 })?;
 
-            rows.collect()
+            let hits = self.collect_known_manager_rows("query_local", rows)?;
+            let aliases = PackageAliasIndex::from_aliases(load_package_aliases(connection, None)?);
+            Ok(rank_search_results_with_aliases(
+                query, hits, &aliases, limit,
+            ))
+        })
+    }
+
+    fn record_search_completion(&self, completion: &SearchCompletion) -> PersistenceResult<()> {
+        self.with_connection("record_search_completion", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO search_completions (query, manager_id, result_count, completed_at_unix)
+VALUES (?1, ?2, ?3, ?4)
+ON CONFLICT(query, manager_id) DO UPDATE SET
+    result_count = excluded.result_count,
+    completed_at_unix = excluded.completed_at_unix
+",
+                params![
+                    normalize_search_completion_query(&completion.query),
+                    completion.manager.as_str(),
+                    to_i64(completion.result_count)?,
+                    to_unix_seconds(completion.completed_at)?,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_search_completions(&self, query: &str) -> PersistenceResult<Vec<SearchCompletion>> {
+        self.with_read_connection("list_search_completions", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT query, manager_id, result_count, completed_at_unix
+FROM search_completions
+WHERE query = ?1
+ORDER BY manager_id ASC
+",
+            )?;
+
+            let rows = statement.query_map([normalize_search_completion_query(query)], |row| {
+                let query: String = row.get(0)?;
+                let manager_raw: String = row.get(1)?;
+                let result_count: i64 = row.get(2)?;
+                let completed_at_unix: i64 = row.get(3)?;
+                Ok(SearchCompletion {
+                    query,
+                    manager: parse_manager_id(&manager_raw)?,
+                    result_count: usize::try_from(result_count).unwrap_or_default(),
+                    completed_at: from_unix_seconds(completed_at_unix)?,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_search_completions", rows)
         })
     }
 }
@@ -963,8 +1740,10 @@ impl TaskStore for SqliteStore {
             ensure_schema_ready(connection)?;
             connection.execute(
                 "
-INSERT INTO task_records (task_id, manager_id, task_type, status, created_at_unix)
-VALUES (?1, ?2, ?3, ?4, ?5)
+INSERT INTO task_records (
+    task_id, manager_id, task_type, status, created_at_unix, attempt_of,
+    label_key, label_args_json
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
 ",
                 params![
                     task_id_to_i64(task.id)?,
@@ -972,6 +1751,12 @@ VALUES (?1, ?2, ?3, ?4, ?5)
                     task_type_to_str(task.task_type),
                     task_status_to_str(task.status),
                     to_unix_seconds(task.created_at)?,
+                    task.attempt_of.map(task_id_to_i64).transpose()?,
+                    task.label.as_ref().map(|label| label.key.as_str()),
+                    task.label
+                        .as_ref()
+                        .map(|label| task_label_args_json(&label.args))
+                        .transpose()?,
                 ],
             )?;
             Ok(())
@@ -1008,68 +1793,168 @@ WHERE task_id = ?1
             return Ok(Vec::new());
         }
 
-        self.with_connection("list_recent_tasks", |connection| {
+        self.with_read_connection("list_recent_tasks", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
-SELECT task_id, manager_id, task_type, status, created_at_unix
+SELECT task_id, manager_id, task_type, status, created_at_unix, attempt_of,
+    label_key, label_args_json
 FROM task_records
 ORDER BY created_at_unix DESC, task_id DESC
 LIMIT ?1
 ",
             )?;
-            let rows = statement.query_map(params![to_i64(limit)?], |row| {
-                let task_id_raw: i64 = row.get(0)?;
-                let manager_raw: String = row.get(1)?;
-                let task_type_raw: String = row.get(2)?;
-                let status_raw: String = row.get(3)?;
-                let created_at_unix: i64 = row.get(4)?;
-
-                Ok(TaskRecord {
-                    id: TaskId(i64_to_u64(task_id_raw)?),
-                    manager: parse_manager_id(&manager_raw)?,
-                    task_type: parse_task_type(&task_type_raw)?,
-                    status: parse_task_status(&status_raw)?,
-                    created_at: from_unix_seconds(created_at_unix)?,
-                })
-            })?;
+            let rows = statement.query_map(params![to_i64(limit)?], task_record_from_row)?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_recent_tasks", rows)
         })
     }
 
-    fn next_task_id(&self) -> PersistenceResult<u64> {
-        self.with_connection("next_task_id", |connection| {
+    fn record_task_attempt(&self, task_id: TaskId, attempt_of: TaskId) -> PersistenceResult<()> {
+        self.with_connection("record_task_attempt", |connection| {
             ensure_schema_ready(connection)?;
-            let max_id: Option<i64> =
-                connection.query_row("SELECT MAX(task_id) FROM task_records", [], |row| {
-                    row.get(0)
-                })?;
-            match max_id {
-                Some(id) => Ok(i64_to_u64(id)?.saturating_add(1)),
-                None => Ok(0),
+            let task_id = task_id_to_i64(task_id)?;
+            let attempt_of = task_id_to_i64(attempt_of)?;
+            let transaction = connection.transaction()?;
+            // The previous attempt may already be pruned; it then roots the chain.
+            let root_id = transaction
+                .query_row(
+                    "SELECT COALESCE(attempt_of, task_id) FROM task_records WHERE task_id = ?1",
+                    params![attempt_of],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()?
+                .unwrap_or(attempt_of);
+            if root_id == task_id {
+                return Err(storage_error_sqlite(
+                    "a task cannot be an attempt of itself",
+                ));
+            }
+
+            let updated = transaction.execute(
+                "UPDATE task_records SET attempt_of = ?2 WHERE task_id = ?1",
+                params![task_id, root_id],
+            )?;
+            if updated == 0 {
+                return Err(storage_error_sqlite(
+                    "task id was not found for attempt link",
+                ));
             }
+            // Attempts already chained to `task_id` follow it into the new chain.
+            transaction.execute(
+                "UPDATE task_records SET attempt_of = ?2 WHERE attempt_of = ?1",
+                params![task_id, root_id],
+            )?;
+            transaction.commit()?;
+            Ok(())
         })
     }
 
-    fn prune_completed_tasks(&self, max_age_secs: i64) -> PersistenceResult<usize> {
-        self.with_connection("prune_completed_tasks", |connection| {
+    fn set_task_idempotency_key(&self, task_id: TaskId, key: &str) -> PersistenceResult<()> {
+        self.with_connection("set_task_idempotency_key", |connection| {
             ensure_schema_ready(connection)?;
-            let cutoff = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or(Duration::ZERO)
-                .as_secs() as i64
-                - max_age_secs;
-            let transaction = connection.transaction()?;
-            transaction.execute(
+            // A task deduplicated onto by a later call keeps its first key, so
+            // retries carrying that key still find it.
+            let updated = connection.execute(
                 "
-DELETE FROM task_log_records
-WHERE task_id IN (
-    SELECT task_id
-    FROM task_records
-    WHERE status IN ('completed', 'cancelled')
-      AND created_at_unix < ?1
-)
+UPDATE task_records
+SET idempotency_key = COALESCE(idempotency_key, ?2)
+WHERE task_id = ?1
+",
+                params![task_id_to_i64(task_id)?, key],
+            )?;
+            if updated == 0 {
+                return Err(storage_error_sqlite(
+                    "task id was not found for idempotency key",
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn task_for_idempotency_key(&self, key: &str) -> PersistenceResult<Option<TaskRecord>> {
+        self.with_read_connection("task_for_idempotency_key", |connection| {
+            ensure_schema_ready(connection)?;
+            connection
+                .query_row(
+                    "
+SELECT task_id, manager_id, task_type, status, created_at_unix, attempt_of,
+    label_key, label_args_json
+FROM task_records
+WHERE idempotency_key = ?1
+",
+                    params![key],
+                    task_record_from_row,
+                )
+                .optional()
+        })
+    }
+
+    fn list_task_attempts(&self, task_id: TaskId) -> PersistenceResult<Vec<TaskRecord>> {
+        self.with_read_connection("list_task_attempts", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+WITH chain(root_id) AS (
+    SELECT COALESCE(
+        (SELECT attempt_of FROM task_records WHERE task_id = ?1),
+        ?1
+    )
+)
+SELECT task_id, manager_id, task_type, status, created_at_unix, attempt_of,
+    label_key, label_args_json
+FROM task_records, chain
+WHERE task_id = chain.root_id OR attempt_of = chain.root_id
+ORDER BY task_id ASC
+",
+            )?;
+            let rows =
+                statement.query_map(params![task_id_to_i64(task_id)?], task_record_from_row)?;
+
+            self.collect_known_manager_rows("list_task_attempts", rows)
+        })
+    }
+
+    fn next_task_id(&self) -> PersistenceResult<u64> {
+        self.with_connection("next_task_id", |connection| {
+            ensure_schema_ready(connection)?;
+            // History outlives pruned task records, so its ids stay reserved.
+            let max_id: Option<i64> = connection.query_row(
+                "
+SELECT MAX(task_id) FROM (
+    SELECT task_id FROM task_records
+    UNION ALL
+    SELECT task_id FROM task_history
+)
+",
+                [],
+                |row| row.get(0),
+            )?;
+            match max_id {
+                Some(id) => Ok(i64_to_u64(id)?.saturating_add(1)),
+                None => Ok(0),
+            }
+        })
+    }
+
+    fn prune_completed_tasks(&self, max_age_secs: i64) -> PersistenceResult<usize> {
+        self.with_connection("prune_completed_tasks", |connection| {
+            ensure_schema_ready(connection)?;
+            let cutoff = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs() as i64
+                - max_age_secs;
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "
+DELETE FROM task_log_records
+WHERE task_id IN (
+    SELECT task_id
+    FROM task_records
+    WHERE status IN ('completed', 'cancelled')
+      AND created_at_unix < ?1
+)
 ",
                 params![cutoff],
             )?;
@@ -1086,6 +1971,140 @@ WHERE status IN ('completed', 'cancelled')
         })
     }
 
+    fn record_task_history(&self, entry: &TaskHistoryRecord) -> PersistenceResult<()> {
+        self.with_connection("record_task_history", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO task_history (
+    task_id, manager_id, task_type, status, exit_code, error_code, error_message,
+    duration_ms, stdout_tail, stderr_tail, finished_at_unix
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+ON CONFLICT(task_id) DO UPDATE SET
+    manager_id = excluded.manager_id,
+    task_type = excluded.task_type,
+    status = excluded.status,
+    exit_code = excluded.exit_code,
+    error_code = excluded.error_code,
+    error_message = excluded.error_message,
+    duration_ms = excluded.duration_ms,
+    stdout_tail = excluded.stdout_tail,
+    stderr_tail = excluded.stderr_tail,
+    finished_at_unix = excluded.finished_at_unix
+",
+                params![
+                    task_id_to_i64(entry.task_id)?,
+                    entry.manager.as_str(),
+                    task_type_to_str(entry.task_type),
+                    task_status_to_str(entry.status),
+                    entry.exit_code,
+                    entry.error_code.as_deref(),
+                    entry.error_message.as_deref(),
+                    size_to_sqlite(entry.duration_ms),
+                    entry.stdout_tail.as_deref(),
+                    entry.stderr_tail.as_deref(),
+                    to_unix_seconds(entry.finished_at)?,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_task_history(
+        &self,
+        filter: &TaskHistoryFilter,
+        offset: usize,
+        limit: usize,
+    ) -> PersistenceResult<Vec<TaskHistoryRecord>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        self.with_read_connection("list_task_history", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT task_id, manager_id, task_type, status, exit_code, error_code, error_message,
+       duration_ms, stdout_tail, stderr_tail, finished_at_unix
+FROM task_history
+WHERE (?1 IS NULL OR manager_id = ?1)
+  AND (?2 IS NULL OR task_type = ?2)
+  AND (?3 IS NULL OR status = ?3)
+ORDER BY finished_at_unix DESC, task_id DESC
+LIMIT ?4 OFFSET ?5
+",
+            )?;
+            let rows = statement.query_map(
+                params![
+                    filter.manager.map(ManagerId::as_str),
+                    filter.task_type.map(task_type_to_str),
+                    filter.status.map(task_status_to_str),
+                    to_i64(limit)?,
+                    to_i64(offset)?,
+                ],
+                task_history_record_from_row,
+            )?;
+
+            self.collect_known_manager_rows("list_task_history", rows)
+        })
+    }
+
+    fn count_task_history(&self, filter: &TaskHistoryFilter) -> PersistenceResult<usize> {
+        self.with_connection("count_task_history", |connection| {
+            ensure_schema_ready(connection)?;
+            let count: i64 = connection.query_row(
+                "
+SELECT COUNT(*)
+FROM task_history
+WHERE (?1 IS NULL OR manager_id = ?1)
+  AND (?2 IS NULL OR task_type = ?2)
+  AND (?3 IS NULL OR status = ?3)
+",
+                params![
+                    filter.manager.map(ManagerId::as_str),
+                    filter.task_type.map(task_type_to_str),
+                    filter.status.map(task_status_to_str),
+                ],
+                |row| row.get(0),
+            )?;
+            Ok(usize::try_from(count).unwrap_or(0))
+        })
+    }
+
+    fn prune_task_history(
+        &self,
+        max_age_secs: i64,
+        max_entries: usize,
+    ) -> PersistenceResult<usize> {
+        self.with_connection("prune_task_history", |connection| {
+            ensure_schema_ready(connection)?;
+            let cutoff = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs() as i64
+                - max_age_secs;
+            let transaction = connection.transaction()?;
+            let expired = transaction.execute(
+                "DELETE FROM task_history WHERE finished_at_unix < ?1",
+                params![cutoff],
+            )?;
+            let overflow = transaction.execute(
+                "
+DELETE FROM task_history
+WHERE task_id NOT IN (
+    SELECT task_id
+    FROM task_history
+    ORDER BY finished_at_unix DESC, task_id DESC
+    LIMIT ?1
+)
+",
+                params![to_i64(max_entries)?],
+            )?;
+            transaction.commit()?;
+            Ok(expired + overflow)
+        })
+    }
+
     fn delete_task(&self, task_id: TaskId) -> PersistenceResult<()> {
         self.with_connection("delete_task", |connection| {
             ensure_schema_ready(connection)?;
@@ -1170,9 +2189,9 @@ INSERT INTO task_log_records (
             return Ok(Vec::new());
         }
 
-        self.with_connection("list_task_logs", |connection| {
+        self.with_read_connection("list_task_logs", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT
     log_id,
@@ -1212,7 +2231,7 @@ LIMIT ?2
                     })
                 })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_task_logs", rows)
         })
     }
 
@@ -1275,10 +2294,67 @@ ON CONFLICT(manager_id) DO UPDATE SET
         })
     }
 
+    fn set_detection_outcome(
+        &self,
+        manager: ManagerId,
+        outcome: &DetectionOutcome,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_detection_outcome", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO manager_detection (manager_id, detected, detected_at_unix, outcome, probe_error_key)
+VALUES (?1, 0, strftime('%s', 'now'), ?2, ?3)
+ON CONFLICT(manager_id) DO UPDATE SET
+    outcome = excluded.outcome,
+    probe_error_key = excluded.probe_error_key
+",
+                params![manager.as_str(), outcome.as_str(), outcome.error_key()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_detection_outcomes(&self) -> PersistenceResult<Vec<(ManagerId, DetectionOutcome)>> {
+        self.with_read_connection("list_detection_outcomes", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, outcome, probe_error_key
+FROM manager_detection
+WHERE outcome IS NOT NULL
+ORDER BY manager_id
+",
+            )?;
+
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                let outcome_raw: String = row.get(1)?;
+                let error_key: Option<String> = row.get(2)?;
+                let outcome = match outcome_raw.as_str() {
+                    "installed" => DetectionOutcome::Installed,
+                    "not_found" => DetectionOutcome::NotFound,
+                    "probe_failed" => DetectionOutcome::ProbeFailed {
+                        error_key: error_key
+                            .unwrap_or_else(|| "service.error.process_failure".to_string()),
+                    },
+                    _ => {
+                        return Err(storage_error_sqlite(&format!(
+                            "unknown detection outcome '{outcome_raw}' in sqlite record"
+                        )));
+                    }
+                };
+                Ok((parse_manager_id(&manager_raw)?, outcome))
+            })?;
+
+            self.collect_known_manager_rows("list_detection_outcomes", rows)
+        })
+    }
+
     fn list_detections(&self) -> PersistenceResult<Vec<(ManagerId, DetectionInfo)>> {
-        self.with_connection("list_detections", |connection| {
+        self.with_read_connection("list_detections", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT manager_id, detected, executable_path, version
 FROM manager_detection
@@ -1303,7 +2379,7 @@ ORDER BY manager_id
                 ))
             })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_detections", rows)
         })
     }
 
@@ -1403,9 +2479,9 @@ VALUES (
         &self,
         manager: Option<ManagerId>,
     ) -> PersistenceResult<Vec<ManagerInstallInstance>> {
-        self.with_connection("list_install_instances", |connection| {
+        self.with_read_connection("list_install_instances", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT manager_id,
        instance_id,
@@ -1494,7 +2570,7 @@ ORDER BY manager_id, is_active DESC, instance_id
                 })
             })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_install_instances", rows)
         })
     }
 
@@ -1612,159 +2688,530 @@ ON CONFLICT(manager_id) DO UPDATE SET
         })
     }
 
-    fn set_manager_timeout_hard_seconds(
-        &self,
-        manager: ManagerId,
-        seconds: Option<u64>,
-    ) -> PersistenceResult<()> {
-        self.with_connection("set_manager_timeout_hard_seconds", |connection| {
+    fn set_manager_timeout_hard_seconds(
+        &self,
+        manager: ManagerId,
+        seconds: Option<u64>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_timeout_hard_seconds", |connection| {
+            ensure_schema_ready(connection)?;
+            let seconds = seconds.and_then(|value| i64::try_from(value).ok());
+            connection.execute(
+                "
+INSERT INTO manager_preferences (manager_id, enabled, timeout_hard_seconds)
+VALUES (
+    ?1,
+    COALESCE((SELECT enabled FROM manager_preferences WHERE manager_id = ?1), 1),
+    ?2
+)
+ON CONFLICT(manager_id) DO UPDATE SET
+    timeout_hard_seconds = excluded.timeout_hard_seconds
+",
+                params![manager.as_str(), seconds],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn set_manager_timeout_idle_seconds(
+        &self,
+        manager: ManagerId,
+        seconds: Option<u64>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_timeout_idle_seconds", |connection| {
+            ensure_schema_ready(connection)?;
+            let seconds = seconds.and_then(|value| i64::try_from(value).ok());
+            connection.execute(
+                "
+INSERT INTO manager_preferences (manager_id, enabled, timeout_idle_seconds)
+VALUES (
+    ?1,
+    COALESCE((SELECT enabled FROM manager_preferences WHERE manager_id = ?1), 1),
+    ?2
+)
+ON CONFLICT(manager_id) DO UPDATE SET
+    timeout_idle_seconds = excluded.timeout_idle_seconds
+",
+                params![manager.as_str(), seconds],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn set_manager_process_qos(
+        &self,
+        manager: ManagerId,
+        qos: Option<&str>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_process_qos", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO manager_preferences (manager_id, enabled, process_qos)
+VALUES (
+    ?1,
+    COALESCE((SELECT enabled FROM manager_preferences WHERE manager_id = ?1), 1),
+    ?2
+)
+ON CONFLICT(manager_id) DO UPDATE SET
+    process_qos = excluded.process_qos
+",
+                params![manager.as_str(), qos],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_manager_preferences(&self) -> PersistenceResult<Vec<ManagerPreference>> {
+        self.with_read_connection("list_manager_preferences", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id,
+       enabled,
+       selected_executable_path,
+       selected_install_method,
+       timeout_hard_seconds,
+       timeout_idle_seconds,
+       process_qos
+FROM manager_preferences
+ORDER BY manager_id
+",
+            )?;
+
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                let enabled_int: i64 = row.get(1)?;
+                let selected_executable_path: Option<String> = row.get(2)?;
+                let selected_install_method: Option<String> = row.get(3)?;
+                let timeout_hard_seconds_raw: Option<i64> = row.get(4)?;
+                let timeout_idle_seconds_raw: Option<i64> = row.get(5)?;
+                let process_qos: Option<String> = row.get(6)?;
+
+                let manager = parse_manager_id(&manager_raw)?;
+                Ok(ManagerPreference {
+                    manager,
+                    enabled: sqlite_to_bool(enabled_int),
+                    selected_executable_path,
+                    selected_install_method,
+                    timeout_hard_seconds: timeout_hard_seconds_raw
+                        .and_then(|value| u64::try_from(value).ok())
+                        .filter(|value| *value > 0),
+                    timeout_idle_seconds: timeout_idle_seconds_raw
+                        .and_then(|value| u64::try_from(value).ok())
+                        .filter(|value| *value > 0),
+                    process_qos,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_manager_preferences", rows)
+        })
+    }
+
+    fn set_safe_mode(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_safe_mode", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO app_settings (key, value)
+VALUES ('safe_mode', ?1)
+ON CONFLICT(key) DO UPDATE SET
+    value = excluded.value
+",
+                params![if enabled { "1" } else { "0" }],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn safe_mode(&self) -> PersistenceResult<bool> {
+        self.with_connection("safe_mode", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement =
+                connection.prepare("SELECT value FROM app_settings WHERE key = 'safe_mode'")?;
+            let mut rows = statement.query([])?;
+            let Some(row) = rows.next()? else {
+                return Ok(false);
+            };
+            let value: String = row.get(0)?;
+            Ok(value.trim() == "1")
+        })
+    }
+
+    fn set_homebrew_keg_policy(&self, policy: HomebrewKegPolicy) -> PersistenceResult<()> {
+        self.with_connection("set_homebrew_keg_policy", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO app_settings (key, value)
+VALUES ('homebrew_keg_policy', ?1)
+ON CONFLICT(key) DO UPDATE SET
+    value = excluded.value
+",
+                params![policy.as_str()],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn homebrew_keg_policy(&self) -> PersistenceResult<HomebrewKegPolicy> {
+        self.with_connection("homebrew_keg_policy", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection
+                .prepare("SELECT value FROM app_settings WHERE key = 'homebrew_keg_policy'")?;
+            let mut rows = statement.query([])?;
+            let Some(row) = rows.next()? else {
+                return Ok(HomebrewKegPolicy::Keep);
+            };
+            let value: String = row.get(0)?;
+            Ok(value
+                .trim()
+                .parse::<HomebrewKegPolicy>()
+                .unwrap_or(HomebrewKegPolicy::Keep))
+        })
+    }
+
+    fn set_homebrew_cask_exclude_auto_updates(&self, exclude: bool) -> PersistenceResult<()> {
+        self.with_connection("set_homebrew_cask_exclude_auto_updates", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(
+                connection,
+                "homebrew_cask_exclude_auto_updates",
+                if exclude { "1" } else { "0" },
+            )
+        })
+    }
+
+    fn homebrew_cask_exclude_auto_updates(&self) -> PersistenceResult<bool> {
+        self.with_connection("homebrew_cask_exclude_auto_updates", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'homebrew_cask_exclude_auto_updates'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_none_or(|value| value.trim() != "0"))
+        })
+    }
+
+    fn set_slow_command_tracing(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_slow_command_tracing", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(
+                connection,
+                "slow_command_tracing",
+                if enabled { "1" } else { "0" },
+            )
+        })
+    }
+
+    fn slow_command_tracing(&self) -> PersistenceResult<bool> {
+        self.with_connection("slow_command_tracing", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'slow_command_tracing'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_some_and(|value| value.trim() == "1"))
+        })
+    }
+
+    fn set_npm_registry_outdated_checks(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_npm_registry_outdated_checks", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(
+                connection,
+                "npm_registry_outdated_checks",
+                if enabled { "1" } else { "0" },
+            )
+        })
+    }
+
+    fn npm_registry_outdated_checks(&self) -> PersistenceResult<bool> {
+        self.with_connection("npm_registry_outdated_checks", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'npm_registry_outdated_checks'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_some_and(|value| value.trim() == "1"))
+        })
+    }
+
+    fn set_process_qos(&self, qos: &str) -> PersistenceResult<()> {
+        self.with_connection("set_process_qos", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(connection, "process_qos", qos)
+        })
+    }
+
+    fn process_qos(&self) -> PersistenceResult<String> {
+        self.with_connection("process_qos", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'process_qos'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.unwrap_or_else(|| "default".to_string()))
+        })
+    }
+
+    fn set_prewarm_on_launch(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_prewarm_on_launch", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(
+                connection,
+                "prewarm_on_launch",
+                if enabled { "1" } else { "0" },
+            )
+        })
+    }
+
+    fn prewarm_on_launch(&self) -> PersistenceResult<bool> {
+        self.with_connection("prewarm_on_launch", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'prewarm_on_launch'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_none_or(|value| value.trim() != "0"))
+        })
+    }
+
+    fn set_allow_heavy_network_when_metered(&self, allow: bool) -> PersistenceResult<()> {
+        self.with_connection("set_allow_heavy_network_when_metered", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(
+                connection,
+                "allow_heavy_network_when_metered",
+                if allow { "1" } else { "0" },
+            )
+        })
+    }
+
+    fn allow_heavy_network_when_metered(&self) -> PersistenceResult<bool> {
+        self.with_connection("allow_heavy_network_when_metered", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'allow_heavy_network_when_metered'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_some_and(|value| value.trim() == "1"))
+        })
+    }
+
+    fn set_diagnostics_redaction_patterns(&self, patterns: Option<&str>) -> PersistenceResult<()> {
+        self.with_connection("set_diagnostics_redaction_patterns", |connection| {
+            ensure_schema_ready(connection)?;
+            match patterns {
+                Some(value) => {
+                    upsert_app_setting(connection, "diagnostics_redaction_patterns", value)
+                }
+                None => {
+                    connection.execute(
+                        "DELETE FROM app_settings WHERE key = 'diagnostics_redaction_patterns'",
+                        [],
+                    )?;
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn diagnostics_redaction_patterns(&self) -> PersistenceResult<Option<String>> {
+        self.with_connection("diagnostics_redaction_patterns", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'diagnostics_redaction_patterns'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()))
+        })
+    }
+
+    fn set_task_history_retention_days(&self, days: u32) -> PersistenceResult<()> {
+        self.with_connection("set_task_history_retention_days", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(connection, "task_history_retention_days", &days.to_string())
+        })
+    }
+
+    fn task_history_retention_days(&self) -> PersistenceResult<u32> {
+        self.with_connection("task_history_retention_days", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'task_history_retention_days'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .filter(|days| *days > 0)
+                .unwrap_or(30))
+        })
+    }
+
+    fn set_task_history_max_entries(&self, entries: u32) -> PersistenceResult<()> {
+        self.with_connection("set_task_history_max_entries", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(connection, "task_history_max_entries", &entries.to_string())
+        })
+    }
+
+    fn task_history_max_entries(&self) -> PersistenceResult<u32> {
+        self.with_connection("task_history_max_entries", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'task_history_max_entries'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .filter(|entries| *entries > 0)
+                .unwrap_or(5_000))
+        })
+    }
+
+    fn set_task_view_history_limit(&self, limit: u32) -> PersistenceResult<()> {
+        self.with_connection("set_task_view_history_limit", |connection| {
+            ensure_schema_ready(connection)?;
+            upsert_app_setting(connection, "task_view_history_limit", &limit.to_string())
+        })
+    }
+
+    fn task_view_history_limit(&self) -> PersistenceResult<u32> {
+        self.with_connection("task_view_history_limit", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'task_view_history_limit'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .filter(|limit| *limit > 0)
+                .unwrap_or(50))
+        })
+    }
+
+    fn set_offline_mode(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_offline_mode", |connection| {
             ensure_schema_ready(connection)?;
-            let seconds = seconds.and_then(|value| i64::try_from(value).ok());
-            connection.execute(
-                "
-INSERT INTO manager_preferences (manager_id, enabled, timeout_hard_seconds)
-VALUES (
-    ?1,
-    COALESCE((SELECT enabled FROM manager_preferences WHERE manager_id = ?1), 1),
-    ?2
-)
-ON CONFLICT(manager_id) DO UPDATE SET
-    timeout_hard_seconds = excluded.timeout_hard_seconds
-",
-                params![manager.as_str(), seconds],
-            )?;
-            Ok(())
+            upsert_app_setting(connection, "offline_mode", if enabled { "1" } else { "0" })
         })
     }
 
-    fn set_manager_timeout_idle_seconds(
-        &self,
-        manager: ManagerId,
-        seconds: Option<u64>,
-    ) -> PersistenceResult<()> {
-        self.with_connection("set_manager_timeout_idle_seconds", |connection| {
+    fn offline_mode(&self) -> PersistenceResult<bool> {
+        self.with_connection("offline_mode", |connection| {
             ensure_schema_ready(connection)?;
-            let seconds = seconds.and_then(|value| i64::try_from(value).ok());
-            connection.execute(
-                "
-INSERT INTO manager_preferences (manager_id, enabled, timeout_idle_seconds)
-VALUES (
-    ?1,
-    COALESCE((SELECT enabled FROM manager_preferences WHERE manager_id = ?1), 1),
-    ?2
-)
-ON CONFLICT(manager_id) DO UPDATE SET
-    timeout_idle_seconds = excluded.timeout_idle_seconds
-",
-                params![manager.as_str(), seconds],
-            )?;
-            Ok(())
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'offline_mode'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_some_and(|value| value.trim() == "1"))
         })
     }
 
-    fn list_manager_preferences(&self) -> PersistenceResult<Vec<ManagerPreference>> {
-        self.with_connection("list_manager_preferences", |connection| {
+    fn set_task_view_dedupe_inflight(&self, enabled: bool) -> PersistenceResult<()> {
+        self.with_connection("set_task_view_dedupe_inflight", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
-                "
-SELECT manager_id,
-       enabled,
-       selected_executable_path,
-       selected_install_method,
-       timeout_hard_seconds,
-       timeout_idle_seconds
-FROM manager_preferences
-ORDER BY manager_id
-",
-            )?;
-
-            let rows = statement.query_map([], |row| {
-                let manager_raw: String = row.get(0)?;
-                let enabled_int: i64 = row.get(1)?;
-                let selected_executable_path: Option<String> = row.get(2)?;
-                let selected_install_method: Option<String> = row.get(3)?;
-                let timeout_hard_seconds_raw: Option<i64> = row.get(4)?;
-                let timeout_idle_seconds_raw: Option<i64> = row.get(5)?;
-
-                let manager = parse_manager_id(&manager_raw)?;
-                Ok(ManagerPreference {
-                    manager,
-                    enabled: sqlite_to_bool(enabled_int),
-                    selected_executable_path,
-                    selected_install_method,
-                    timeout_hard_seconds: timeout_hard_seconds_raw
-                        .and_then(|value| u64::try_from(value).ok())
-                        .filter(|value| *value > 0),
-                    timeout_idle_seconds: timeout_idle_seconds_raw
-                        .and_then(|value| u64::try_from(value).ok())
-                        .filter(|value| *value > 0),
-                })
-            })?;
+            upsert_app_setting(
+                connection,
+                "task_view_dedupe_inflight",
+                if enabled { "1" } else { "0" },
+            )
+        })
+    }
 
-            rows.collect()
+    fn task_view_dedupe_inflight(&self) -> PersistenceResult<bool> {
+        self.with_connection("task_view_dedupe_inflight", |connection| {
+            ensure_schema_ready(connection)?;
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'task_view_dedupe_inflight'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_none_or(|value| value.trim() != "0"))
         })
     }
 
-    fn set_safe_mode(&self, enabled: bool) -> PersistenceResult<()> {
-        self.with_connection("set_safe_mode", |connection| {
+    fn set_task_view_grouping(&self, grouping: &str) -> PersistenceResult<()> {
+        self.with_connection("set_task_view_grouping", |connection| {
             ensure_schema_ready(connection)?;
-            connection.execute(
-                "
-INSERT INTO app_settings (key, value)
-VALUES ('safe_mode', ?1)
-ON CONFLICT(key) DO UPDATE SET
-    value = excluded.value
-",
-                params![if enabled { "1" } else { "0" }],
-            )?;
-            Ok(())
+            upsert_app_setting(connection, "task_view_grouping", grouping)
         })
     }
 
-    fn safe_mode(&self) -> PersistenceResult<bool> {
-        self.with_connection("safe_mode", |connection| {
+    fn task_view_grouping(&self) -> PersistenceResult<String> {
+        self.with_connection("task_view_grouping", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement =
-                connection.prepare("SELECT value FROM app_settings WHERE key = 'safe_mode'")?;
-            let mut rows = statement.query([])?;
-            let Some(row) = rows.next()? else {
-                return Ok(false);
-            };
-            let value: String = row.get(0)?;
-            Ok(value.trim() == "1")
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'task_view_grouping'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.unwrap_or_else(|| "none".to_string()))
         })
     }
 
-    fn set_homebrew_keg_policy(&self, policy: HomebrewKegPolicy) -> PersistenceResult<()> {
-        self.with_connection("set_homebrew_keg_policy", |connection| {
+    fn set_hide_pipx_packages_from_pip(&self, hide: bool) -> PersistenceResult<()> {
+        self.with_connection("set_hide_pipx_packages_from_pip", |connection| {
             ensure_schema_ready(connection)?;
-            connection.execute(
-                "
-INSERT INTO app_settings (key, value)
-VALUES ('homebrew_keg_policy', ?1)
-ON CONFLICT(key) DO UPDATE SET
-    value = excluded.value
-",
-                params![policy.as_str()],
-            )?;
-            Ok(())
+            upsert_app_setting(
+                connection,
+                "hide_pipx_packages_from_pip",
+                if hide { "1" } else { "0" },
+            )
         })
     }
 
-    fn homebrew_keg_policy(&self) -> PersistenceResult<HomebrewKegPolicy> {
-        self.with_connection("homebrew_keg_policy", |connection| {
+    fn hide_pipx_packages_from_pip(&self) -> PersistenceResult<bool> {
+        self.with_connection("hide_pipx_packages_from_pip", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection
-                .prepare("SELECT value FROM app_settings WHERE key = 'homebrew_keg_policy'")?;
-            let mut rows = statement.query([])?;
-            let Some(row) = rows.next()? else {
-                return Ok(HomebrewKegPolicy::Keep);
-            };
-            let value: String = row.get(0)?;
-            Ok(value
-                .trim()
-                .parse::<HomebrewKegPolicy>()
-                .unwrap_or(HomebrewKegPolicy::Keep))
+            let value: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'hide_pipx_packages_from_pip'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(value.is_none_or(|value| value.trim() != "0"))
         })
     }
 
@@ -1936,45 +3383,227 @@ ON CONFLICT(key) DO UPDATE SET
             if trimmed.is_empty() {
                 return Ok(None);
             }
-            Ok(Some(trimmed.to_string()))
+            Ok(Some(trimmed.to_string()))
+        })
+    }
+
+    fn set_onboarding_phase(&self, phase: OnboardingPhase) -> PersistenceResult<()> {
+        self.with_connection("set_onboarding_phase", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            upsert_app_setting(&transaction, "onboarding_phase", phase.as_str())?;
+            upsert_app_setting(
+                &transaction,
+                "cli_onboarding_completed",
+                if phase == OnboardingPhase::Complete {
+                    "1"
+                } else {
+                    "0"
+                },
+            )?;
+            transaction.commit()
+        })
+    }
+
+    fn onboarding_phase(&self) -> PersistenceResult<OnboardingPhase> {
+        self.with_connection("onboarding_phase", |connection| {
+            ensure_schema_ready(connection)?;
+            let completed: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'cli_onboarding_completed'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if completed.as_deref().map(str::trim) == Some("1") {
+                return Ok(OnboardingPhase::Complete);
+            }
+            let stored: Option<String> = connection
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'onboarding_phase'",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(stored
+                .and_then(|value| value.parse().ok())
+                .filter(|phase| *phase != OnboardingPhase::Complete)
+                .unwrap_or_default())
+        })
+    }
+
+    fn complete_onboarding(&self, choices: &OnboardingChoices) -> PersistenceResult<()> {
+        self.with_connection("complete_onboarding", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            {
+                let mut statement = transaction.prepare(
+                    "
+INSERT INTO manager_preferences (manager_id, enabled)
+VALUES (?1, ?2)
+ON CONFLICT(manager_id) DO UPDATE SET
+    enabled = excluded.enabled
+",
+                )?;
+                for (manager, enabled) in &choices.manager_enabled {
+                    statement.execute(params![manager.as_str(), bool_to_sqlite(*enabled)])?;
+                }
+            }
+            if let Some(enabled) = choices.safe_mode {
+                upsert_app_setting(&transaction, "safe_mode", if enabled { "1" } else { "0" })?;
+            }
+            if let Some(policy) = choices.homebrew_keg_policy {
+                upsert_app_setting(&transaction, "homebrew_keg_policy", policy.as_str())?;
+            }
+            if let Some(enabled) = choices.auto_check_for_updates {
+                upsert_app_setting(
+                    &transaction,
+                    "auto_check_for_updates",
+                    if enabled { "1" } else { "0" },
+                )?;
+            }
+            if let Some(minutes) = choices.auto_check_frequency_minutes {
+                upsert_app_setting(
+                    &transaction,
+                    "auto_check_frequency_minutes",
+                    &minutes.max(1).to_string(),
+                )?;
+            }
+            upsert_app_setting(
+                &transaction,
+                "onboarding_phase",
+                OnboardingPhase::Complete.as_str(),
+            )?;
+            upsert_app_setting(&transaction, "cli_onboarding_completed", "1")?;
+            transaction.commit()
+        })
+    }
+
+    fn set_manager_priority_overrides_json(
+        &self,
+        overrides_json: Option<&str>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_priority_overrides_json", |connection| {
+            ensure_schema_ready(connection)?;
+            match overrides_json {
+                Some(json) => {
+                    connection.execute(
+                        "
+INSERT INTO app_settings (key, value)
+VALUES ('manager_priority_overrides', ?1)
+ON CONFLICT(key) DO UPDATE SET
+    value = excluded.value
+",
+                        params![json],
+                    )?;
+                }
+                None => {
+                    connection.execute(
+                        "DELETE FROM app_settings WHERE key = 'manager_priority_overrides'",
+                        [],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn manager_priority_overrides_json(&self) -> PersistenceResult<Option<String>> {
+        self.with_connection("manager_priority_overrides_json", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare(
+                "SELECT value FROM app_settings WHERE key = 'manager_priority_overrides'",
+            )?;
+            let mut rows = statement.query([])?;
+            let Some(row) = rows.next()? else {
+                return Ok(None);
+            };
+            let value: String = row.get(0)?;
+            let trimmed = value.trim().to_string();
+            if trimmed.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(trimmed))
+            }
+        })
+    }
+
+    fn set_refresh_schedules_json(&self, schedules_json: Option<&str>) -> PersistenceResult<()> {
+        self.with_connection("set_refresh_schedules_json", |connection| {
+            ensure_schema_ready(connection)?;
+            match schedules_json {
+                Some(json) => upsert_app_setting(connection, "refresh_schedules", json)?,
+                None => {
+                    connection.execute(
+                        "DELETE FROM app_settings WHERE key = 'refresh_schedules'",
+                        [],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn refresh_schedules_json(&self) -> PersistenceResult<Option<String>> {
+        self.with_connection("refresh_schedules_json", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection
+                .prepare("SELECT value FROM app_settings WHERE key = 'refresh_schedules'")?;
+            let mut rows = statement.query([])?;
+            let Some(row) = rows.next()? else {
+                return Ok(None);
+            };
+            let value: String = row.get(0)?;
+            let trimmed = value.trim().to_string();
+            if trimmed.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(trimmed))
+            }
         })
     }
 
-    fn set_manager_priority_overrides_json(
+    fn update_manager_failures_json(
         &self,
-        overrides_json: Option<&str>,
+        update: &mut dyn FnMut(Option<String>) -> PersistenceResult<Option<String>>,
     ) -> PersistenceResult<()> {
-        self.with_connection("set_manager_priority_overrides_json", |connection| {
+        let mut update_error = None;
+        self.with_connection("update_manager_failures_json", |connection| {
             ensure_schema_ready(connection)?;
-            match overrides_json {
-                Some(json) => {
-                    connection.execute(
-                        "
-INSERT INTO app_settings (key, value)
-VALUES ('manager_priority_overrides', ?1)
-ON CONFLICT(key) DO UPDATE SET
-    value = excluded.value
-",
-                        params![json],
-                    )?;
-                }
-                None => {
-                    connection.execute(
-                        "DELETE FROM app_settings WHERE key = 'manager_priority_overrides'",
+            let transaction =
+                connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let current = transaction
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = 'manager_failures'",
+                    [],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+            match update(current) {
+                Ok(Some(json)) => upsert_app_setting(&transaction, "manager_failures", &json)?,
+                Ok(None) => {
+                    transaction.execute(
+                        "DELETE FROM app_settings WHERE key = 'manager_failures'",
                         [],
                     )?;
                 }
+                Err(error) => {
+                    update_error = Some(error);
+                    return Ok(());
+                }
             }
-            Ok(())
-        })
+            transaction.commit()
+        })?;
+        update_error.map_or(Ok(()), Err)
     }
 
-    fn manager_priority_overrides_json(&self) -> PersistenceResult<Option<String>> {
-        self.with_connection("manager_priority_overrides_json", |connection| {
+    fn manager_failures_json(&self) -> PersistenceResult<Option<String>> {
+        self.with_connection("manager_failures_json", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
-                "SELECT value FROM app_settings WHERE key = 'manager_priority_overrides'",
-            )?;
+            let mut statement = connection
+                .prepare("SELECT value FROM app_settings WHERE key = 'manager_failures'")?;
             let mut rows = statement.query([])?;
             let Some(row) = rows.next()? else {
                 return Ok(None);
@@ -1994,6 +3623,7 @@ ON CONFLICT(key) DO UPDATE SET
         package: &PackageRef,
         policy: Option<HomebrewKegPolicy>,
     ) -> PersistenceResult<()> {
+        let key = package.key();
         self.with_connection("set_package_keg_policy", |connection| {
             ensure_schema_ready(connection)?;
 
@@ -2007,13 +3637,13 @@ ON CONFLICT(manager_id, package_name) DO UPDATE SET
     policy = excluded.policy,
     updated_at_unix = excluded.updated_at_unix
 ",
-                        params![package.manager.as_str(), package.name.as_str(), policy.as_str()],
+                        params![key.manager().as_str(), key.name(), policy.as_str()],
                     )?;
                 }
                 None => {
                     connection.execute(
                         "DELETE FROM package_keg_policies WHERE manager_id = ?1 AND package_name = ?2",
-                        params![package.manager.as_str(), package.name.as_str()],
+                        params![key.manager().as_str(), key.name()],
                     )?;
                 }
             }
@@ -2026,6 +3656,7 @@ ON CONFLICT(manager_id, package_name) DO UPDATE SET
         &self,
         package: &PackageRef,
     ) -> PersistenceResult<Option<HomebrewKegPolicy>> {
+        let key = package.key();
         self.with_connection("package_keg_policy", |connection| {
             ensure_schema_ready(connection)?;
             let mut statement = connection.prepare(
@@ -2035,8 +3666,7 @@ FROM package_keg_policies
 WHERE manager_id = ?1 AND package_name = ?2
 ",
             )?;
-            let mut rows =
-                statement.query(params![package.manager.as_str(), package.name.as_str()])?;
+            let mut rows = statement.query(params![key.manager().as_str(), key.name()])?;
             let Some(row) = rows.next()? else {
                 return Ok(None);
             };
@@ -2046,9 +3676,9 @@ WHERE manager_id = ?1 AND package_name = ?2
     }
 
     fn list_package_keg_policies(&self) -> PersistenceResult<Vec<PackageKegPolicy>> {
-        self.with_connection("list_package_keg_policies", |connection| {
+        self.with_read_connection("list_package_keg_policies", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT manager_id, package_name, policy
 FROM package_keg_policies
@@ -2074,7 +3704,65 @@ ORDER BY manager_id, package_name
                 })
             })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_package_keg_policies", rows)
+        })
+    }
+
+    fn set_package_install_options(
+        &self,
+        package: &PackageRef,
+        options: &[String],
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_package_install_options", |connection| {
+            ensure_schema_ready(connection)?;
+
+            if options.is_empty() {
+                connection.execute(
+                    "DELETE FROM package_install_options WHERE manager_id = ?1 AND package_name = ?2",
+                    params![package.manager.as_str(), package.name.as_str()],
+                )?;
+                return Ok(());
+            }
+
+            let options_json = serde_json::to_string(options)
+                .map_err(|error| rusqlite::Error::ToSqlConversionFailure(Box::new(error)))?;
+            connection.execute(
+                "
+INSERT INTO package_install_options (manager_id, package_name, options_json, updated_at_unix)
+VALUES (?1, ?2, ?3, strftime('%s', 'now'))
+ON CONFLICT(manager_id, package_name) DO UPDATE SET
+    options_json = excluded.options_json,
+    updated_at_unix = excluded.updated_at_unix
+",
+                params![
+                    package.manager.as_str(),
+                    package.name.as_str(),
+                    options_json
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn package_install_options(&self, package: &PackageRef) -> PersistenceResult<Vec<String>> {
+        self.with_connection("package_install_options", |connection| {
+            ensure_schema_ready(connection)?;
+            let options_json: Option<String> = connection
+                .query_row(
+                    "
+SELECT options_json
+FROM package_install_options
+WHERE manager_id = ?1 AND package_name = ?2
+",
+                    params![package.manager.as_str(), package.name.as_str()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(options_json) = options_json else {
+                return Ok(Vec::new());
+            };
+            serde_json::from_str::<Vec<String>>(options_json.as_str())
+                .map_err(|_| storage_error_sqlite("invalid package install options value"))
         })
     }
 
@@ -2140,14 +3828,20 @@ WHERE package_name = ?1
                 return Ok(None);
             };
             let manager_raw: String = row.get(0)?;
-            Ok(Some(parse_manager_id(manager_raw.as_str())?))
+            match parse_manager_id(manager_raw.as_str()) {
+                Err(error) if unknown_manager_id(&error).is_some() => {
+                    self.note_unknown_manager_id("package_manager_preference", &manager_raw);
+                    Ok(None)
+                }
+                parsed => parsed.map(Some),
+            }
         })
     }
 
     fn list_package_manager_preferences(&self) -> PersistenceResult<Vec<PackageManagerPreference>> {
-        self.with_connection("list_package_manager_preferences", |connection| {
+        self.with_read_connection("list_package_manager_preferences", |connection| {
             ensure_schema_ready(connection)?;
-            let mut statement = connection.prepare(
+            let mut statement = connection.prepare_cached(
                 "
 SELECT package_name, manager_id
 FROM package_manager_preferences
@@ -2164,7 +3858,243 @@ ORDER BY package_name
                 })
             })?;
 
-            rows.collect()
+            self.collect_known_manager_rows("list_package_manager_preferences", rows)
+        })
+    }
+
+    fn set_manager_self_version(
+        &self,
+        manager: ManagerId,
+        self_version: &ManagerSelfVersion,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_self_version", |connection| {
+            ensure_schema_ready(connection)?;
+            connection.execute(
+                "
+INSERT INTO manager_self_versions (manager_id, installed_version, latest_version, checked_at_unix)
+VALUES (?1, ?2, ?3, strftime('%s', 'now'))
+ON CONFLICT(manager_id) DO UPDATE SET
+    installed_version = excluded.installed_version,
+    latest_version = excluded.latest_version,
+    checked_at_unix = excluded.checked_at_unix
+",
+                params![
+                    manager.as_str(),
+                    self_version.installed_version.as_deref(),
+                    self_version.latest_version.as_deref()
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_manager_self_versions(
+        &self,
+    ) -> PersistenceResult<Vec<(ManagerId, ManagerSelfVersion)>> {
+        self.with_read_connection("list_manager_self_versions", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, installed_version, latest_version
+FROM manager_self_versions
+ORDER BY manager_id
+",
+            )?;
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                let manager = parse_manager_id(manager_raw.as_str())?;
+                Ok((
+                    manager,
+                    ManagerSelfVersion {
+                        installed_version: row.get(1)?,
+                        latest_version: row.get(2)?,
+                    },
+                ))
+            })?;
+
+            self.collect_known_manager_rows("list_manager_self_versions", rows)
+        })
+    }
+
+    fn set_manager_env_setting(
+        &self,
+        manager: ManagerId,
+        key: &str,
+        value: Option<&str>,
+    ) -> PersistenceResult<()> {
+        self.with_connection("set_manager_env_setting", |connection| {
+            ensure_schema_ready(connection)?;
+            match value {
+                Some(value) => {
+                    connection.execute(
+                        "
+INSERT INTO manager_env_settings (manager_id, setting_key, value)
+VALUES (?1, ?2, ?3)
+ON CONFLICT(manager_id, setting_key) DO UPDATE SET
+    value = excluded.value
+",
+                        params![manager.as_str(), key, value],
+                    )?;
+                }
+                None => {
+                    connection.execute(
+                        "DELETE FROM manager_env_settings WHERE manager_id = ?1 AND setting_key = ?2",
+                        params![manager.as_str(), key],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn list_manager_env_settings(&self) -> PersistenceResult<Vec<ManagerEnvSetting>> {
+        self.with_read_connection("list_manager_env_settings", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, setting_key, value
+FROM manager_env_settings
+ORDER BY manager_id, setting_key
+",
+            )?;
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                Ok(ManagerEnvSetting {
+                    manager: parse_manager_id(manager_raw.as_str())?,
+                    key: row.get(1)?,
+                    value: row.get(2)?,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_manager_env_settings", rows)
+        })
+    }
+
+    fn replace_refresh_results(&self, results: &[ManagerRefreshResult]) -> PersistenceResult<()> {
+        self.with_connection("replace_refresh_results", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            transaction.execute("DELETE FROM refresh_results", [])?;
+            for result in results {
+                transaction.execute(
+                    "
+INSERT INTO refresh_results (
+    manager_id,
+    error_kind,
+    error_message,
+    duration_ms,
+    run_started_at_unix,
+    finished_at_unix
+)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+",
+                    params![
+                        result.manager.as_str(),
+                        result.error.as_ref().map(|error| error.kind.code()),
+                        result.error.as_ref().map(|error| error.message.as_str()),
+                        i64::try_from(result.duration.as_millis()).unwrap_or(i64::MAX),
+                        to_unix_seconds(result.run_started_at)?,
+                        to_unix_seconds(result.finished_at)?
+                    ],
+                )?;
+            }
+            transaction.commit()
+        })
+    }
+
+    fn list_refresh_results(&self) -> PersistenceResult<Vec<ManagerRefreshResult>> {
+        self.with_read_connection("list_refresh_results", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, error_kind, error_message, duration_ms, run_started_at_unix, finished_at_unix
+FROM refresh_results
+ORDER BY finished_at_unix, manager_id
+",
+            )?;
+            let rows = statement.query_map([], |row| {
+                let manager_raw: String = row.get(0)?;
+                let error_kind: Option<String> = row.get(1)?;
+                let error_message: Option<String> = row.get(2)?;
+                let error = error_kind.map(|kind| ManagerRefreshError {
+                    kind: CoreErrorKind::from_code(&kind).unwrap_or(CoreErrorKind::Internal),
+                    message: error_message.unwrap_or_default(),
+                });
+                Ok(ManagerRefreshResult {
+                    manager: parse_manager_id(manager_raw.as_str())?,
+                    error,
+                    duration: Duration::from_millis(row.get::<_, i64>(3)?.max(0) as u64),
+                    run_started_at: from_unix_seconds(row.get(4)?)?,
+                    finished_at: from_unix_seconds(row.get(5)?)?,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_refresh_results", rows)
+        })
+    }
+
+    fn replace_manager_executable_candidates(
+        &self,
+        manager: ManagerId,
+        candidates: &[ManagerExecutableCandidate],
+    ) -> PersistenceResult<()> {
+        self.with_connection("replace_manager_executable_candidates", |connection| {
+            ensure_schema_ready(connection)?;
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "DELETE FROM manager_executable_candidates WHERE manager_id = ?1",
+                params![manager.as_str()],
+            )?;
+            for (position, candidate) in candidates.iter().enumerate() {
+                transaction.execute(
+                    "
+INSERT OR REPLACE INTO manager_executable_candidates (
+    manager_id,
+    path,
+    position,
+    version,
+    last_validated_at_unix
+)
+VALUES (?1, ?2, ?3, ?4, ?5)
+",
+                    params![
+                        manager.as_str(),
+                        candidate.path.as_str(),
+                        i64::try_from(position).unwrap_or(i64::MAX),
+                        candidate.version.as_deref(),
+                        to_unix_seconds(candidate.last_validated_at)?
+                    ],
+                )?;
+            }
+            transaction.commit()
+        })
+    }
+
+    fn list_manager_executable_candidates(
+        &self,
+        manager: Option<ManagerId>,
+    ) -> PersistenceResult<Vec<ManagerExecutableCandidate>> {
+        self.with_read_connection("list_manager_executable_candidates", |connection| {
+            ensure_schema_ready(connection)?;
+            let mut statement = connection.prepare_cached(
+                "
+SELECT manager_id, path, version, last_validated_at_unix
+FROM manager_executable_candidates
+WHERE ?1 IS NULL OR manager_id = ?1
+ORDER BY manager_id, position
+",
+            )?;
+            let rows = statement.query_map(params![manager.map(|id| id.as_str())], |row| {
+                let manager_raw: String = row.get(0)?;
+                Ok(ManagerExecutableCandidate {
+                    manager: parse_manager_id(manager_raw.as_str())?,
+                    path: row.get(1)?,
+                    version: row.get(2)?,
+                    last_validated_at: from_unix_seconds(row.get(3)?)?,
+                })
+            })?;
+
+            self.collect_known_manager_rows("list_manager_executable_candidates", rows)
         })
     }
 }
@@ -2186,6 +4116,13 @@ PRAGMA busy_timeout = 5000;
     Ok(connection)
 }
 
+fn open_read_connection(database_path: &Path) -> rusqlite::Result<Connection> {
+    let connection = open_connection(database_path)?;
+    connection.execute_batch("PRAGMA query_only = ON;")?;
+    connection.set_prepared_statement_cache_capacity(READ_STATEMENT_CACHE_CAPACITY);
+    Ok(connection)
+}
+
 fn ensure_migrations_table(connection: &Connection) -> rusqlite::Result<()> {
     connection.execute_batch(
         "
@@ -2200,8 +4137,17 @@ CREATE TABLE IF NOT EXISTS helm_schema_migrations (
 }
 
 fn ensure_schema_ready(connection: &Connection) -> rusqlite::Result<()> {
-    ensure_migrations_table(connection)?;
-    let version = read_current_version(connection)?;
+    // Only reads here: listing paths run on `query_only` connections.
+    let migrations_table_exists = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [MIGRATIONS_TABLE],
+        |row| row.get::<_, bool>(0),
+    )?;
+    let version = if migrations_table_exists {
+        read_current_version(connection)?
+    } else {
+        0
+    };
     if version <= 0 {
         return Err(storage_error_sqlite(
             "database schema is not initialized; apply migrations before package operations",
@@ -2284,14 +4230,37 @@ fn storage_error_sqlite(message: &str) -> rusqlite::Error {
     rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(message.to_string())))
 }
 
+/// A persisted manager id this build does not recognize.
+#[derive(Debug)]
+struct UnknownManagerId(String);
+
+impl std::fmt::Display for UnknownManagerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown manager id '{}' found in persisted sqlite record",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownManagerId {}
+
 fn parse_manager_id(raw: &str) -> rusqlite::Result<ManagerId> {
     raw.parse::<ManagerId>().map_err(|_| {
-        storage_error_sqlite(&format!(
-            "unknown manager id '{raw}' found in persisted sqlite record"
-        ))
+        rusqlite::Error::ToSqlConversionFailure(Box::new(UnknownManagerId(raw.to_string())))
     })
 }
 
+fn unknown_manager_id(error: &rusqlite::Error) -> Option<&str> {
+    match error {
+        rusqlite::Error::ToSqlConversionFailure(source) => source
+            .downcast_ref::<UnknownManagerId>()
+            .map(|unknown| unknown.0.as_str()),
+        _ => None,
+    }
+}
+
 fn parse_install_instance_identity_kind(
     raw: &str,
 ) -> rusqlite::Result<InstallInstanceIdentityKind> {
@@ -2324,6 +4293,50 @@ fn parse_strategy_kind(raw: &str) -> rusqlite::Result<StrategyKind> {
     })
 }
 
+/// Hold outdated packages whose constrained pin's range excludes the
+/// candidate version. A constraint that no longer parses holds the package.
+fn apply_pin_constraints(
+    connection: &Connection,
+    outdated: &mut [OutdatedPackage],
+) -> rusqlite::Result<()> {
+    let mut statement = connection.prepare_cached(
+        "
+SELECT manager_id, package_name, pinned_version, version_constraint
+FROM pin_records
+WHERE version_constraint != ''
+",
+    )?;
+    let constrained = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if constrained.is_empty() {
+        return Ok(());
+    }
+
+    for package in outdated.iter_mut().filter(|package| !package.pinned) {
+        let installed_version = package.installed_version.as_deref().unwrap_or_default();
+        package.pinned = constrained
+            .iter()
+            .filter(|(manager_id, package_name, pinned_version, _)| {
+                manager_id == package.package.manager.as_str()
+                    && *package_name == package.package.name
+                    && (pinned_version.is_empty() || pinned_version == installed_version)
+            })
+            .any(|(_, _, _, constraint)| {
+                !VersionConstraint::parse(constraint)
+                    .is_ok_and(|constraint| constraint.matches(&package.candidate_version))
+            });
+    }
+    Ok(())
+}
+
 fn pin_kind_to_str(kind: PinKind) -> &'static str {
     match kind {
         PinKind::Native => "native",
@@ -2420,10 +4433,31 @@ fn bool_to_sqlite(value: bool) -> i64 {
     if value { 1 } else { 0 }
 }
 
+fn upsert_app_setting(connection: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
+    connection.execute(
+        "
+INSERT INTO app_settings (key, value)
+VALUES (?1, ?2)
+ON CONFLICT(key) DO UPDATE SET
+    value = excluded.value
+",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
 fn sqlite_to_bool(value: i64) -> bool {
     value != 0
 }
 
+fn size_to_sqlite(value: Option<u64>) -> Option<i64> {
+    value.and_then(|bytes| i64::try_from(bytes).ok())
+}
+
+fn sqlite_to_size(value: Option<i64>) -> Option<u64> {
+    value.and_then(|bytes| u64::try_from(bytes).ok())
+}
+
 fn normalize_optional_text(value: Option<String>) -> Option<String> {
     value.and_then(|text| {
         let trimmed = text.trim();
@@ -2484,6 +4518,62 @@ fn from_unix_seconds(value: i64) -> rusqlite::Result<SystemTime> {
     Ok(UNIX_EPOCH + Duration::from_secs(seconds))
 }
 
+fn task_record_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TaskRecord> {
+    let task_id_raw: i64 = row.get(0)?;
+    let manager_raw: String = row.get(1)?;
+    let task_type_raw: String = row.get(2)?;
+    let status_raw: String = row.get(3)?;
+    let created_at_unix: i64 = row.get(4)?;
+    let attempt_of_raw: Option<i64> = row.get(5)?;
+    let label_key: Option<String> = row.get(6)?;
+    let label_args_json: Option<String> = row.get(7)?;
+    let label = label_key.map(|key| TaskLabel {
+        key,
+        args: label_args_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    });
+
+    Ok(TaskRecord {
+        id: TaskId(i64_to_u64(task_id_raw)?),
+        manager: parse_manager_id(&manager_raw)?,
+        task_type: parse_task_type(&task_type_raw)?,
+        status: parse_task_status(&status_raw)?,
+        created_at: from_unix_seconds(created_at_unix)?,
+        attempt_of: attempt_of_raw.map(i64_to_u64).transpose()?.map(TaskId),
+        label,
+    })
+}
+
+fn task_label_args_json(
+    args: &std::collections::BTreeMap<String, String>,
+) -> rusqlite::Result<String> {
+    serde_json::to_string(args)
+        .map_err(|error| storage_error_sqlite(&format!("failed to encode task label: {error}")))
+}
+
+fn task_history_record_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TaskHistoryRecord> {
+    let task_id_raw: i64 = row.get(0)?;
+    let manager_raw: String = row.get(1)?;
+    let task_type_raw: String = row.get(2)?;
+    let status_raw: String = row.get(3)?;
+    let finished_at_unix: i64 = row.get(10)?;
+
+    Ok(TaskHistoryRecord {
+        task_id: TaskId(i64_to_u64(task_id_raw)?),
+        manager: parse_manager_id(&manager_raw)?,
+        task_type: parse_task_type(&task_type_raw)?,
+        status: parse_task_status(&status_raw)?,
+        exit_code: row.get(4)?,
+        error_code: row.get(5)?,
+        error_message: row.get(6)?,
+        duration_ms: sqlite_to_size(row.get(7)?),
+        stdout_tail: row.get(8)?,
+        stderr_tail: row.get(9)?,
+        finished_at: from_unix_seconds(finished_at_unix)?,
+    })
+}
+
 fn task_id_to_i64(value: TaskId) -> rusqlite::Result<i64> {
     i64::try_from(value.0).map_err(|_| storage_error_sqlite("task id exceeds i64 range"))
 }
@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::path::PathBuf;
 
 use crate::models::TaskId;
 
@@ -18,3 +19,16 @@ pub fn with_task_id<R>(task_id: TaskId, operation: impl FnOnce() -> R) -> R {
         result
     })
 }
+
+/// Scratch directory for the running task, created on first use and removed
+/// once the task completes, fails, or is cancelled. Script installers and
+/// downloads should stage files here instead of the shared temp directory.
+pub fn current_task_temp_dir() -> std::io::Result<PathBuf> {
+    let task_id = current_task_id().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no task is running on this thread",
+        )
+    })?;
+    crate::execution::task_temp_dir_store::get_or_create(task_id)
+}
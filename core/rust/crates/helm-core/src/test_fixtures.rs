@@ -0,0 +1,40 @@
+//! Package rows shared by the crate's unit tests.
+
+use crate::models::{InstalledPackage, ManagerId, OutdatedPackage, PackageRef};
+
+/// An unpinned installed row with no identifier, runtime state, or sizes.
+pub(crate) fn installed(manager: ManagerId, name: &str, version: Option<&str>) -> InstalledPackage {
+    InstalledPackage {
+        package: PackageRef {
+            manager,
+            name: name.to_string(),
+        },
+        package_identifier: None,
+        installed_version: version.map(str::to_string),
+        pinned: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    }
+}
+
+/// An unpinned outdated row that does not require a restart.
+pub(crate) fn outdated(
+    manager: ManagerId,
+    name: &str,
+    installed_version: Option<&str>,
+    candidate_version: &str,
+) -> OutdatedPackage {
+    OutdatedPackage {
+        package: PackageRef {
+            manager,
+            name: name.to_string(),
+        },
+        package_identifier: None,
+        installed_version: installed_version.map(str::to_string),
+        candidate_version: candidate_version.to_string(),
+        pinned: false,
+        restart_required: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    }
+}
@@ -1,11 +1,13 @@
 use crate::adapters::AdapterRequest;
+use crate::base_dirs::ToolBaseDirs;
 use crate::manager_lifecycle::{
     HomebrewUninstallCleanupMode, parse_homebrew_manager_uninstall_package_name,
     strip_shell_setup_cleanup_suffix,
 };
 use crate::models::{
-    AutomationLevel, InstallProvenance, ManagerId, ManagerInstallInstance, ManagerUninstallPreview,
-    PackageRef, PackageRuntimeState, PackageUninstallPreview, StrategyKind, UninstallImpactPath,
+    AutomationLevel, InstallProvenance, ManagerId, ManagerInstallInstance,
+    ManagerUninstallAffectedPackage, ManagerUninstallPreview, PackageRef, PackageRuntimeState,
+    PackageUninstallPreview, StrategyKind, UninstallImpactPath,
 };
 use crate::persistence::PackageStore;
 use crate::sqlite::SqliteStore;
@@ -45,6 +47,7 @@ struct UninstallSummaryContext<'a> {
     files_count: usize,
     directories_count: usize,
     secondary_effect_count: usize,
+    affected_package_count: usize,
 }
 
 struct PackageUninstallSummaryContext<'a> {
@@ -165,6 +168,24 @@ pub fn build_manager_uninstall_preview(
         ));
     }
 
+    let affected_packages = collect_manager_affected_packages(store, context.requested_manager);
+    let removes_manager_data = !directories_removed.is_empty();
+    if !affected_packages.is_empty() {
+        secondary_effects.push(if removes_manager_data {
+            format!(
+                "{} package(s) installed via {} will be removed with the manager's data.",
+                affected_packages.len(),
+                context.requested_manager.as_str()
+            )
+        } else {
+            format!(
+                "{} package(s) installed via {} will remain installed without a manager.",
+                affected_packages.len(),
+                context.requested_manager.as_str()
+            )
+        });
+    }
+
     if files_removed.is_empty() && directories_removed.is_empty() && secondary_effects.is_empty() {
         secondary_effects.push("No blast-radius details were discovered for this strategy.".into());
     }
@@ -194,7 +215,8 @@ pub fn build_manager_uninstall_preview(
         || confidence_requires_confirmation
         || context.unknown_override_required
         || context.used_unknown_override
-        || read_only_blocked;
+        || read_only_blocked
+        || (removes_manager_data && !affected_packages.is_empty());
     let summary_lines = build_manager_uninstall_summary_lines(UninstallSummaryContext {
         target_manager: context.target_manager,
         strategy: context.strategy,
@@ -206,6 +228,7 @@ pub fn build_manager_uninstall_preview(
         files_count: files_removed.len(),
         directories_count: directories_removed.len(),
         secondary_effect_count: secondary_effects.len(),
+        affected_package_count: affected_packages.len(),
     });
 
     ManagerUninstallPreview {
@@ -241,6 +264,8 @@ pub fn build_manager_uninstall_preview(
             .and_then(|instance| instance.competing_confidence),
         files_removed,
         directories_removed,
+        affected_packages,
+        removes_manager_data,
         secondary_effects,
         summary_lines,
         blast_radius_score,
@@ -463,10 +488,9 @@ fn append_asdf_uninstall_impact(
         }
     }
 
-    let home = std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("~"));
-    let asdf_root = home.join(".asdf");
+    let asdf_root = ToolBaseDirs::from_env()
+        .asdf_data_dir
+        .unwrap_or_else(|| PathBuf::from("~/.asdf"));
     push_impact_path(directories_removed, seen_directories, asdf_root.clone());
 
     secondary_effects.push(format!(
@@ -525,12 +549,16 @@ fn append_mise_uninstall_impact(
         }
     }
 
-    let home = std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("~"));
-    let mise_state_dir = home.join(".local/share/mise");
-    let mise_cache_dir = home.join(".cache/mise");
-    let mise_config_dir = home.join(".config/mise");
+    let base_dirs = ToolBaseDirs::from_env();
+    let mise_state_dir = base_dirs
+        .mise_data_dir
+        .unwrap_or_else(|| PathBuf::from("~/.local/share/mise"));
+    let mise_cache_dir = base_dirs
+        .mise_cache_dir
+        .unwrap_or_else(|| PathBuf::from("~/.cache/mise"));
+    let mise_config_dir = base_dirs
+        .mise_config_dir
+        .unwrap_or_else(|| PathBuf::from("~/.config/mise"));
 
     let (mode, remove_shell_setup) = parse_mise_uninstall_preview_mode(package_name);
     match mode {
@@ -551,8 +579,9 @@ fn append_mise_uninstall_impact(
                 mise_cache_dir.clone(),
             );
             secondary_effects.push(format!(
-                "Mise state and cache directories under '{}' may be removed.",
-                home.display()
+                "Mise state and cache directories '{}' and '{}' may be removed.",
+                mise_state_dir.display(),
+                mise_cache_dir.display()
             ));
             secondary_effects
                 .push("Mise config files are preserved in this uninstall mode.".to_string());
@@ -574,8 +603,10 @@ fn append_mise_uninstall_impact(
                 mise_config_dir.clone(),
             );
             secondary_effects.push(format!(
-                "Mise state, cache, and config directories under '{}' may be removed.",
-                home.display()
+                "Mise state, cache, and config directories '{}', '{}', and '{}' may be removed.",
+                mise_state_dir.display(),
+                mise_cache_dir.display(),
+                mise_config_dir.display()
             ));
         }
     }
@@ -692,6 +723,27 @@ fn append_shell_setup_cleanup_impact(
     ));
 }
 
+fn collect_manager_affected_packages(
+    store: &SqliteStore,
+    manager: ManagerId,
+) -> Vec<ManagerUninstallAffectedPackage> {
+    let mut packages: Vec<ManagerUninstallAffectedPackage> = store
+        .list_installed()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|package| {
+            package.package.manager == manager && package.package.is_user_visible_package()
+        })
+        .map(|package| ManagerUninstallAffectedPackage {
+            package_name: package.package.name,
+            installed_version: package.installed_version,
+        })
+        .collect();
+    packages.sort_by(|left, right| left.package_name.cmp(&right.package_name));
+    packages.dedup_by(|left, right| left.package_name == right.package_name);
+    packages
+}
+
 fn append_homebrew_full_cleanup_impact(
     files_removed: &mut Vec<UninstallImpactPath>,
     directories_removed: &mut Vec<UninstallImpactPath>,
@@ -728,11 +780,13 @@ fn append_homebrew_full_cleanup_impact(
             ));
         }
         ManagerId::Mise => {
-            let home = std::env::var_os("HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("~"));
-            let mise_state = home.join(".local/share/mise");
-            let mise_cache = home.join(".cache/mise");
+            let base_dirs = ToolBaseDirs::from_env();
+            let mise_state = base_dirs
+                .mise_data_dir
+                .unwrap_or_else(|| PathBuf::from("~/.local/share/mise"));
+            let mise_cache = base_dirs
+                .mise_cache_dir
+                .unwrap_or_else(|| PathBuf::from("~/.cache/mise"));
             push_impact_path(directories_removed, seen_directories, mise_state.clone());
             push_impact_path(directories_removed, seen_directories, mise_cache.clone());
             secondary_effects.push(format!(
@@ -751,22 +805,14 @@ fn append_homebrew_full_cleanup_impact(
 }
 
 fn resolve_cargo_home() -> PathBuf {
-    if let Some(raw) = std::env::var_os("CARGO_HOME").filter(|value| !value.is_empty()) {
-        return PathBuf::from(raw);
-    }
-    std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .map(|home| home.join(".cargo"))
+    ToolBaseDirs::from_env()
+        .cargo_home
         .unwrap_or_else(|| PathBuf::from("~/.cargo"))
 }
 
 fn resolve_rustup_home() -> PathBuf {
-    if let Some(raw) = std::env::var_os("RUSTUP_HOME").filter(|value| !value.is_empty()) {
-        return PathBuf::from(raw);
-    }
-    std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .map(|home| home.join(".rustup"))
+    ToolBaseDirs::from_env()
+        .rustup_home
         .unwrap_or_else(|| PathBuf::from("~/.rustup"))
 }
 
@@ -850,6 +896,12 @@ fn build_manager_uninstall_summary_lines(context: UninstallSummaryContext<'_>) -
         "Impacts: {} files, {} directories, {} secondary effects",
         context.files_count, context.directories_count, context.secondary_effect_count
     ));
+    if context.affected_package_count > 0 {
+        lines.push(format!(
+            "Affected packages: {}",
+            context.affected_package_count
+        ));
+    }
 
     if let Some(instance) = context.active_instance {
         lines.push(format!(
@@ -1080,4 +1132,114 @@ mod tests {
                 .any(|line| line.contains("directory override"))
         );
     }
+
+    fn mise_preview_store(test_name: &str) -> SqliteStore {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock before unix epoch")
+            .as_nanos();
+        let store = SqliteStore::new(
+            std::env::temp_dir().join(format!("helm-{test_name}-{nanos}.sqlite3")),
+        );
+        store.migrate_to_latest().expect("migrations should apply");
+        let installed =
+            |manager: ManagerId, name: &str, version: &str| crate::models::InstalledPackage {
+                package: PackageRef {
+                    manager,
+                    name: name.to_string(),
+                },
+                package_identifier: None,
+                installed_version: Some(version.to_string()),
+                pinned: false,
+                runtime_state: PackageRuntimeState::default(),
+                sizes: Default::default(),
+            };
+        store
+            .upsert_installed(&[
+                installed(ManagerId::Mise, "python", "3.12.2"),
+                installed(ManagerId::Mise, "node", "22.1.0"),
+                installed(ManagerId::Mise, "__self__", "2024.5.0"),
+                installed(ManagerId::Npm, "eslint", "9.0.0"),
+            ])
+            .expect("installed rows should persist");
+        store
+    }
+
+    fn mise_uninstall_request(package_name: &str) -> AdapterRequest {
+        AdapterRequest::Uninstall(crate::adapters::UninstallRequest {
+            package: PackageRef {
+                manager: ManagerId::Mise,
+                name: package_name.to_string(),
+            },
+            target_name: None,
+            version: None,
+            disposal: Default::default(),
+        })
+    }
+
+    fn mise_preview(store: &SqliteStore, request: &AdapterRequest) -> ManagerUninstallPreview {
+        build_manager_uninstall_preview(
+            store,
+            ManagerUninstallPreviewContext {
+                requested_manager: ManagerId::Mise,
+                target_manager: ManagerId::Mise,
+                request,
+                strategy: StrategyKind::InteractivePrompt,
+                active_instance: None,
+                unknown_override_required: false,
+                used_unknown_override: false,
+                legacy_fallback_used: false,
+            },
+            DEFAULT_MANAGER_UNINSTALL_SAFE_BLAST_RADIUS_THRESHOLD,
+        )
+    }
+
+    #[test]
+    fn manager_uninstall_preview_lists_orphaned_packages_for_manager_only_uninstall() {
+        let store = mise_preview_store("uninstall-preview-mise-orphans");
+        let preview = mise_preview(&store, &mise_uninstall_request("__self__"));
+
+        let names: Vec<&str> = preview
+            .affected_packages
+            .iter()
+            .map(|package| package.package_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["node", "python"]);
+        assert_eq!(
+            preview.affected_packages[1].installed_version.as_deref(),
+            Some("3.12.2")
+        );
+        assert!(!preview.removes_manager_data);
+        assert!(
+            preview
+                .secondary_effects
+                .iter()
+                .any(|line| line.contains("2 package(s) installed via mise will remain installed"))
+        );
+        assert!(
+            preview
+                .summary_lines
+                .iter()
+                .any(|line| line == "Affected packages: 2")
+        );
+    }
+
+    #[test]
+    fn manager_uninstall_preview_requires_confirmation_when_cascade_removes_data() {
+        let store = mise_preview_store("uninstall-preview-mise-cascade");
+        let preview = mise_preview(
+            &store,
+            &mise_uninstall_request("__self__:fullCleanup:keepConfig"),
+        );
+
+        assert!(preview.removes_manager_data);
+        assert_eq!(preview.affected_packages.len(), 2);
+        assert!(preview.requires_yes);
+        assert!(
+            preview
+                .secondary_effects
+                .iter()
+                .any(|line| line.contains("will be removed with the manager's data"))
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{InstalledPackage, ManagerId, OutdatedPackage};
+use crate::versioning::normalize_package_family_key;
+
+/// One package the simulation predicts will move from `old_version` to `new_version`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedUpgrade {
+    pub manager: ManagerId,
+    pub package_name: String,
+    pub old_version: Option<String>,
+    pub new_version: String,
+    pub pinned: bool,
+    pub restart_required: bool,
+    pub download_size_bytes: Option<u64>,
+}
+
+/// Predicted post-upgrade state of one installed package.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedInstalledPackage {
+    pub manager: ManagerId,
+    pub package_name: String,
+    pub version: Option<String>,
+    pub upgraded: bool,
+}
+
+/// The same package family upgraded by more than one manager in one run, which
+/// usually means two copies of one binary compete for the same name on PATH.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedUpgradeConflict {
+    pub package_family: String,
+    pub managers: Vec<ManagerId>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeSimulation {
+    pub upgrades: Vec<SimulatedUpgrade>,
+    pub predicted_installed: Vec<SimulatedInstalledPackage>,
+    pub conflicts: Vec<SimulatedUpgradeConflict>,
+    /// Sum of reported download sizes; `None` when no upgrade reports one.
+    pub estimated_download_bytes: Option<u64>,
+    pub download_size_reported_count: usize,
+    pub restart_required: bool,
+}
+
+/// Predict the result of an upgrade-all run without executing anything.
+///
+/// `is_target` decides which outdated entries the run would upgrade (pin,
+/// enablement, and manager support filtering belong to the caller);
+/// `download_size` supplies a size for upgrades whose adapter can report one.
+pub fn simulate_upgrade_all(
+    installed: &[InstalledPackage],
+    outdated: &[OutdatedPackage],
+    is_target: impl Fn(&OutdatedPackage) -> bool,
+    download_size: impl Fn(&OutdatedPackage) -> Option<u64>,
+) -> UpgradeSimulation {
+    let mut upgrades_by_key: BTreeMap<(&'static str, String), SimulatedUpgrade> = BTreeMap::new();
+    for package in outdated.iter().filter(|package| is_target(package)) {
+        upgrades_by_key
+            .entry((
+                package.package.manager.as_str(),
+                package.package.name.clone(),
+            ))
+            .or_insert_with(|| SimulatedUpgrade {
+                manager: package.package.manager,
+                package_name: package.package.name.clone(),
+                old_version: package.installed_version.clone(),
+                new_version: package.candidate_version.clone(),
+                pinned: package.pinned,
+                restart_required: package.restart_required,
+                download_size_bytes: download_size(package),
+            });
+    }
+
+    let mut predicted: BTreeMap<(&'static str, String), SimulatedInstalledPackage> = installed
+        .iter()
+        .filter(|package| package.package.is_user_visible_package())
+        .map(|package| {
+            (
+                (
+                    package.package.manager.as_str(),
+                    package.package.name.clone(),
+                ),
+                SimulatedInstalledPackage {
+                    manager: package.package.manager,
+                    package_name: package.package.name.clone(),
+                    version: package.installed_version.clone(),
+                    upgraded: false,
+                },
+            )
+        })
+        .collect();
+    for (key, upgrade) in &upgrades_by_key {
+        let entry = predicted
+            .entry(key.clone())
+            .or_insert_with(|| SimulatedInstalledPackage {
+                manager: upgrade.manager,
+                package_name: upgrade.package_name.clone(),
+                version: None,
+                upgraded: false,
+            });
+        entry.version = Some(upgrade.new_version.clone());
+        entry.upgraded = true;
+    }
+
+    let mut managers_by_family: HashMap<String, Vec<ManagerId>> = HashMap::new();
+    for upgrade in upgrades_by_key.values() {
+        if let Some(family) = normalize_package_family_key(&upgrade.package_name) {
+            let managers = managers_by_family.entry(family).or_default();
+            if !managers.contains(&upgrade.manager) {
+                managers.push(upgrade.manager);
+            }
+        }
+    }
+    let mut conflicts: Vec<SimulatedUpgradeConflict> = managers_by_family
+        .into_iter()
+        .filter(|(_, managers)| managers.len() > 1)
+        .map(|(package_family, mut managers)| {
+            managers.sort_by_key(|manager| manager.as_str());
+            SimulatedUpgradeConflict {
+                package_family,
+                managers,
+            }
+        })
+        .collect();
+    conflicts.sort_by(|left, right| left.package_family.cmp(&right.package_family));
+
+    let upgrades: Vec<SimulatedUpgrade> = upgrades_by_key.into_values().collect();
+    let reported_sizes: Vec<u64> = upgrades
+        .iter()
+        .filter_map(|upgrade| upgrade.download_size_bytes)
+        .collect();
+
+    UpgradeSimulation {
+        restart_required: upgrades.iter().any(|upgrade| upgrade.restart_required),
+        estimated_download_bytes: (!reported_sizes.is_empty()).then(|| {
+            reported_sizes
+                .iter()
+                .copied()
+                .fold(0_u64, u64::saturating_add)
+        }),
+        download_size_reported_count: reported_sizes.len(),
+        predicted_installed: predicted.into_values().collect(),
+        conflicts,
+        upgrades,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{installed, outdated};
+
+    #[test]
+    fn simulation_predicts_final_versions_for_targets_only() {
+        let mut pinned = outdated(ManagerId::Npm, "eslint", Some("8.0.0"), "9.0.0");
+        pinned.pinned = true;
+        let simulation = simulate_upgrade_all(
+            &[
+                installed(ManagerId::Npm, "eslint", Some("8.0.0")),
+                installed(ManagerId::HomebrewFormula, "git", Some("2.40.0")),
+                installed(ManagerId::HomebrewFormula, "wget", Some("1.21")),
+            ],
+            &[
+                outdated(ManagerId::HomebrewFormula, "git", Some("2.40.0"), "2.45.0"),
+                pinned,
+            ],
+            |package| !package.pinned,
+            |_| None,
+        );
+
+        assert_eq!(simulation.upgrades.len(), 1);
+        assert_eq!(simulation.upgrades[0].package_name, "git");
+        assert_eq!(
+            simulation.upgrades[0].old_version.as_deref(),
+            Some("2.40.0")
+        );
+        assert_eq!(simulation.upgrades[0].new_version, "2.45.0");
+
+        let git = simulation
+            .predicted_installed
+            .iter()
+            .find(|package| package.package_name == "git")
+            .unwrap();
+        assert_eq!(git.version.as_deref(), Some("2.45.0"));
+        assert!(git.upgraded);
+        let eslint = simulation
+            .predicted_installed
+            .iter()
+            .find(|package| package.package_name == "eslint")
+            .unwrap();
+        assert_eq!(eslint.version.as_deref(), Some("8.0.0"));
+        assert!(!eslint.upgraded);
+        assert_eq!(simulation.predicted_installed.len(), 3);
+        assert_eq!(simulation.estimated_download_bytes, None);
+        assert!(!simulation.restart_required);
+    }
+
+    #[test]
+    fn simulation_reports_cross_manager_conflicts_sizes_and_restarts() {
+        let mut os_update = outdated(
+            ManagerId::SoftwareUpdate,
+            "macOS Sonoma",
+            Some("14.4"),
+            "14.5",
+        );
+        os_update.restart_required = true;
+        let simulation = simulate_upgrade_all(
+            &[],
+            &[
+                outdated(ManagerId::HomebrewFormula, "node", Some("20.0.0"), "22.0.0"),
+                outdated(ManagerId::Mise, "Node", Some("20.0.0"), "22.1.0"),
+                outdated(ManagerId::Cargo, "ripgrep", Some("13.0.0"), "14.0.0"),
+                os_update,
+            ],
+            |_| true,
+            |package| (package.package.manager == ManagerId::HomebrewFormula).then_some(40),
+        );
+
+        assert_eq!(
+            simulation.conflicts,
+            vec![SimulatedUpgradeConflict {
+                package_family: "node".to_string(),
+                managers: vec![ManagerId::HomebrewFormula, ManagerId::Mise],
+            }]
+        );
+        assert_eq!(simulation.estimated_download_bytes, Some(40));
+        assert_eq!(simulation.download_size_reported_count, 1);
+        assert!(simulation.restart_required);
+    }
+}
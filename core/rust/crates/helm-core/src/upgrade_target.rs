@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ManagerId, PackageRef};
+use crate::package_key::PackageKey;
+
+/// Name suffix older clients appended to Homebrew upgrade targets to request
+/// keg cleanup, before `UpgradeRequest::cleanup_old_kegs` existed.
+pub const LEGACY_HOMEBREW_CLEANUP_SUFFIX: &str = "@@helm.cleanup";
+
+/// Package name standing in for the manager itself in mutation results and
+/// legacy upgrade requests.
+pub const SELF_PACKAGE_NAME: &str = "__self__";
+/// Package name standing in for "every outdated package" in mutation results
+/// and legacy upgrade requests.
+pub const ALL_PACKAGES_NAME: &str = "__all__";
+/// Package name standing in for confirmed OS updates in mutation results,
+/// upgrade plan steps, and legacy upgrade requests.
+pub const CONFIRMED_OS_UPDATES_NAME: &str = "__confirm_os_updates__";
+
+/// What an upgrade request upgrades.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UpgradeTarget {
+    /// One installed package.
+    Package(PackageRef),
+    /// Every outdated package the manager tracks.
+    All,
+    /// The manager tool itself (`brew update`, `rustup self update`, ...).
+    SelfUpdate,
+    /// Update the manager, then upgrade every outdated package with the
+    /// refreshed metadata (`brew update` followed by `brew upgrade`).
+    SelfUpdateThenAll,
+    /// Install every pending OS update; only valid once the user confirmed
+    /// the plan (`softwareupdate`).
+    OsUpdatesConfirmed,
+}
+
+/// Wire name of an [`UpgradeTarget`] variant, used by coordinator requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeTargetKind {
+    Package,
+    All,
+    SelfUpdate,
+    SelfUpdateThenAll,
+    OsUpdatesConfirmed,
+}
+
+impl UpgradeTargetKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Package => "package",
+            Self::All => "all",
+            Self::SelfUpdate => "self_update",
+            Self::SelfUpdateThenAll => "self_update_then_all",
+            Self::OsUpdatesConfirmed => "os_updates_confirmed",
+        }
+    }
+}
+
+impl UpgradeTarget {
+    pub fn package(&self) -> Option<&PackageRef> {
+        match self {
+            Self::Package(package) => Some(package),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> UpgradeTargetKind {
+        match self {
+            Self::Package(_) => UpgradeTargetKind::Package,
+            Self::All => UpgradeTargetKind::All,
+            Self::SelfUpdate => UpgradeTargetKind::SelfUpdate,
+            Self::SelfUpdateThenAll => UpgradeTargetKind::SelfUpdateThenAll,
+            Self::OsUpdatesConfirmed => UpgradeTargetKind::OsUpdatesConfirmed,
+        }
+    }
+
+    /// Whether the target updates the manager tool itself.
+    pub fn updates_manager(&self) -> bool {
+        matches!(self, Self::SelfUpdate | Self::SelfUpdateThenAll)
+    }
+
+    /// Package reported in the mutation result: the package itself, or the
+    /// reserved name standing in for a manager-wide target.
+    pub fn result_package(&self, manager: ManagerId) -> PackageRef {
+        let name = match self {
+            Self::Package(package) => return package.clone(),
+            Self::All => ALL_PACKAGES_NAME,
+            Self::SelfUpdate | Self::SelfUpdateThenAll => SELF_PACKAGE_NAME,
+            Self::OsUpdatesConfirmed => CONFIRMED_OS_UPDATES_NAME,
+        };
+        PackageRef {
+            manager,
+            name: name.to_string(),
+        }
+    }
+
+    /// Rebuild a target from its wire form.
+    ///
+    /// Requests written before `kind` existed carry the reserved names in
+    /// `package_name` instead; those decode to the matching variant, and a
+    /// missing name means every package. A package target without a name
+    /// decodes to an empty name, which adapters reject as invalid input
+    /// rather than widening it into an upgrade-all.
+    pub fn from_wire(
+        manager: ManagerId,
+        kind: Option<UpgradeTargetKind>,
+        package_name: Option<String>,
+    ) -> Self {
+        match kind {
+            Some(UpgradeTargetKind::Package) => Self::Package(PackageRef {
+                manager,
+                name: package_name.unwrap_or_default(),
+            }),
+            Some(UpgradeTargetKind::All) => Self::All,
+            Some(UpgradeTargetKind::SelfUpdate) => Self::SelfUpdate,
+            Some(UpgradeTargetKind::SelfUpdateThenAll) => Self::SelfUpdateThenAll,
+            Some(UpgradeTargetKind::OsUpdatesConfirmed) => Self::OsUpdatesConfirmed,
+            None => match package_name.as_deref().map(str::trim) {
+                None | Some(ALL_PACKAGES_NAME) => Self::All,
+                Some(SELF_PACKAGE_NAME) => Self::SelfUpdate,
+                Some(CONFIRMED_OS_UPDATES_NAME) => Self::OsUpdatesConfirmed,
+                Some(_) => Self::Package(PackageRef {
+                    manager,
+                    name: package_name.unwrap_or_default(),
+                }),
+            },
+        }
+    }
+
+    /// Wire form of the target: its kind plus a package name.
+    ///
+    /// Manager-wide targets other than [`UpgradeTarget::All`] keep their
+    /// reserved name in `package_name`, so a coordinator that predates `kind`
+    /// never widens them into an upgrade-all.
+    pub fn to_wire(&self) -> (UpgradeTargetKind, Option<String>) {
+        let package_name = match self {
+            Self::All => None,
+            target => Some(target.result_package(ManagerId::HomebrewFormula).name),
+        };
+        (self.kind(), package_name)
+    }
+}
+
+/// Split a legacy `name@@helm.cleanup` target into the package name and cleanup flag.
+///
+/// Names without the suffix are returned unchanged, so versioned formulae like
+/// `python@3.12` are never misread as encoded targets.
+pub fn decode_legacy_upgrade_target(name: &str) -> (&str, bool) {
+    match name.strip_suffix(LEGACY_HOMEBREW_CLEANUP_SUFFIX) {
+        Some(stripped) => (stripped, true),
+        None => (name, false),
+    }
+}
+
+/// Stable upgrade plan step ID: the step's [`PackageKey`], whose encoding keeps
+/// tap-qualified names and `formula@version` aliases distinct and delimiter-safe.
+pub fn upgrade_plan_step_id(manager: ManagerId, package_name: &str) -> String {
+    PackageKey::new(manager, package_name).to_string()
+}
+
+/// Parse a step ID back into its manager and package name.
+///
+/// Accepts both the encoded form and legacy unencoded IDs (`homebrew_formula:python@3.12`),
+/// which decode to the same package name.
+pub fn parse_upgrade_plan_step_id(step_id: &str) -> Option<(ManagerId, String)> {
+    let key = step_id.parse::<PackageKey>().ok()?;
+    Some((key.manager(), key.name().to_string()))
+}
+
+/// Rewrite a persisted legacy step ID into the current encoding.
+///
+/// Returns `None` for IDs that do not parse; already-current IDs round-trip unchanged.
+pub fn migrate_upgrade_plan_step_id(step_id: &str) -> Option<String> {
+    let (manager, package_name) = parse_upgrade_plan_step_id(step_id)?;
+    let (package_name, _) = decode_legacy_upgrade_target(&package_name);
+    Some(upgrade_plan_step_id(manager, package_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_cleanup_suffix_decodes_without_touching_versioned_names() {
+        assert_eq!(
+            decode_legacy_upgrade_target("python@3.12@@helm.cleanup"),
+            ("python@3.12", true)
+        );
+        assert_eq!(
+            decode_legacy_upgrade_target("python@3.12"),
+            ("python@3.12", false)
+        );
+        assert_eq!(decode_legacy_upgrade_target(""), ("", false));
+    }
+
+    #[test]
+    fn upgrade_targets_round_trip_and_decode_legacy_reserved_names() {
+        let wget = UpgradeTarget::Package(PackageRef {
+            manager: ManagerId::HomebrewFormula,
+            name: "wget".to_string(),
+        });
+        for target in [
+            wget.clone(),
+            UpgradeTarget::All,
+            UpgradeTarget::SelfUpdate,
+            UpgradeTarget::SelfUpdateThenAll,
+            UpgradeTarget::OsUpdatesConfirmed,
+        ] {
+            let (kind, package_name) = target.to_wire();
+            assert_eq!(
+                UpgradeTarget::from_wire(ManagerId::HomebrewFormula, Some(kind), package_name),
+                target
+            );
+        }
+
+        let legacy = |name: Option<&str>| {
+            UpgradeTarget::from_wire(ManagerId::HomebrewFormula, None, name.map(str::to_string))
+        };
+        assert_eq!(legacy(None), UpgradeTarget::All);
+        assert_eq!(legacy(Some("__all__")), UpgradeTarget::All);
+        assert_eq!(legacy(Some("__self__")), UpgradeTarget::SelfUpdate);
+        assert_eq!(
+            legacy(Some("__confirm_os_updates__")),
+            UpgradeTarget::OsUpdatesConfirmed
+        );
+        assert_eq!(legacy(Some("wget")), wget);
+        assert_eq!(
+            UpgradeTarget::from_wire(
+                ManagerId::HomebrewFormula,
+                Some(UpgradeTargetKind::Package),
+                None
+            ),
+            UpgradeTarget::Package(PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn step_ids_encode_aliases_and_tap_qualified_names() {
+        assert_eq!(
+            upgrade_plan_step_id(ManagerId::Npm, "typescript"),
+            "npm:typescript"
+        );
+        assert_eq!(
+            upgrade_plan_step_id(ManagerId::HomebrewFormula, "python@3.12"),
+            "homebrew_formula:python%403.12"
+        );
+        assert_eq!(
+            upgrade_plan_step_id(ManagerId::HomebrewCask, "homebrew/cask-fonts/font-x"),
+            "homebrew_cask:homebrew%2Fcask-fonts%2Ffont-x"
+        );
+        assert_ne!(
+            upgrade_plan_step_id(ManagerId::Npm, "a:b"),
+            upgrade_plan_step_id(ManagerId::Npm, "a%3Ab")
+        );
+    }
+
+    #[test]
+    fn step_ids_round_trip_and_accept_legacy_form() {
+        for name in [
+            "python@3.12",
+            "homebrew/cask-fonts/font-x",
+            "@scope/pkg",
+            "a%3Ab",
+            "ümlaut pkg",
+        ] {
+            let step_id = upgrade_plan_step_id(ManagerId::HomebrewFormula, name);
+            assert_eq!(
+                parse_upgrade_plan_step_id(&step_id),
+                Some((ManagerId::HomebrewFormula, name.to_string()))
+            );
+        }
+        assert_eq!(
+            parse_upgrade_plan_step_id("homebrew_formula:python@3.12"),
+            Some((ManagerId::HomebrewFormula, "python@3.12".to_string()))
+        );
+        assert_eq!(parse_upgrade_plan_step_id("not_a_manager:pkg"), None);
+        assert_eq!(parse_upgrade_plan_step_id("npm:"), None);
+    }
+
+    #[test]
+    fn legacy_step_ids_migrate_to_current_encoding() {
+        assert_eq!(
+            migrate_upgrade_plan_step_id("homebrew_formula:python@3.12@@helm.cleanup").as_deref(),
+            Some("homebrew_formula:python%403.12")
+        );
+        assert_eq!(
+            migrate_upgrade_plan_step_id("npm:typescript").as_deref(),
+            Some("npm:typescript")
+        );
+        assert_eq!(migrate_upgrade_plan_step_id("garbage"), None);
+    }
+}
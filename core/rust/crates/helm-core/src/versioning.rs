@@ -124,9 +124,318 @@ fn atom_starts_release_token(atom: &str) -> bool {
     }
 }
 
+/// Semver-style range a version must satisfy, such as `^1.2`, `~1.4.2`,
+/// `>=1.0 <2.0`, or `1.x || 2.1`.
+///
+/// Comparators separated by whitespace or commas must all match; `||`
+/// separates alternatives. A bare or `=` version with fewer than three
+/// components matches every version it prefixes, as does `x`/`*`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionConstraint {
+    raw: String,
+    alternatives: Vec<Vec<VersionBound>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct VersionBound {
+    lower: Option<(ParsedVersion, bool)>,
+    upper: Option<(ParsedVersion, bool)>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ParsedVersion {
+    release: Vec<u64>,
+    pre_release: Option<String>,
+}
+
+impl ParsedVersion {
+    /// Leading `v` and build metadata are ignored; anything after the first
+    /// `-` is a pre-release tag.
+    fn parse(raw: &str) -> Option<Self> {
+        let trimmed = raw.trim();
+        let trimmed = trimmed
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(trimmed)
+            .split('+')
+            .next()
+            .unwrap_or_default();
+        let (release_raw, pre_release) = match trimmed.split_once('-') {
+            Some((release, pre)) => (release, Some(pre.to_string())),
+            None => (trimmed, None),
+        };
+        let release = release_raw
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            release,
+            pre_release,
+        })
+    }
+
+    fn release_only(release: Vec<u64>) -> Self {
+        Self {
+            release,
+            pre_release: None,
+        }
+    }
+
+    /// Cargo only takes a pre-release tag on a full `major.minor.patch`.
+    fn to_cargo_version(&self) -> Option<String> {
+        if self.release.len() > 3 {
+            return None;
+        }
+        let mut release = self.release.clone();
+        let Some(pre_release) = &self.pre_release else {
+            return Some(join_release(&release));
+        };
+        release.resize(3, 0);
+        Some(format!("{}-{pre_release}", join_release(&release)))
+    }
+
+    fn component(&self, index: usize) -> u64 {
+        self.release.get(index).copied().unwrap_or(0)
+    }
+}
+
+fn join_release(release: &[u64]) -> String {
+    release
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+impl Ord for ParsedVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let width = self.release.len().max(other.release.len());
+        (0..width)
+            .map(|index| self.component(index).cmp(&other.component(index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => std::cmp::Ordering::Equal,
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some(left), Some(right)) => left.cmp(right),
+            })
+    }
+}
+
+impl PartialOrd for ParsedVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl VersionBound {
+    fn matches(&self, version: &ParsedVersion) -> bool {
+        let above = self.lower.as_ref().is_none_or(|(bound, inclusive)| {
+            if *inclusive {
+                version >= bound
+            } else {
+                version > bound
+            }
+        });
+        let below = self.upper.as_ref().is_none_or(|(bound, inclusive)| {
+            if *inclusive {
+                version <= bound
+            } else {
+                version < bound
+            }
+        });
+        above && below
+    }
+
+    /// Versions starting with `prefix`; everything for an empty prefix.
+    fn prefix(prefix: Vec<u64>) -> Self {
+        let Some((last, head)) = prefix.split_last() else {
+            return Self {
+                lower: None,
+                upper: None,
+            };
+        };
+        let mut upper = head.to_vec();
+        upper.push(last + 1);
+        Self {
+            lower: Some((ParsedVersion::release_only(prefix.clone()), true)),
+            upper: Some((ParsedVersion::release_only(upper), false)),
+        }
+    }
+
+    fn parse(raw: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid version comparator '{raw}'");
+        let (operator, version_raw) = ["<=", ">=", "<", ">", "=", "^", "~"]
+            .into_iter()
+            .find_map(|operator| raw.strip_prefix(operator).map(|rest| (operator, rest)))
+            .unwrap_or(("", raw));
+        let version_raw = version_raw.trim();
+        if version_raw.is_empty() {
+            return Err(invalid());
+        }
+
+        // Partial versions: `1.2`, `1.2.x`, `*`.
+        let mut parts = Vec::new();
+        let mut wildcard = false;
+        for part in version_raw
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(version_raw)
+            .split('.')
+        {
+            if matches!(part, "x" | "X" | "*") {
+                wildcard = true;
+                continue;
+            }
+            if wildcard {
+                return Err(invalid());
+            }
+            match part.parse::<u64>() {
+                Ok(number) => parts.push(number),
+                Err(_) => break,
+            }
+        }
+        let exact = ParsedVersion::parse(version_raw).filter(|_| !wildcard);
+        if parts.is_empty() {
+            // `*` alone matches everything; `>*` and friends are meaningless.
+            return match operator {
+                "" | "=" | "^" | "~" if wildcard => Ok(Self::prefix(parts)),
+                _ => Err(invalid()),
+            };
+        }
+
+        Ok(match operator {
+            "" | "=" => match exact {
+                Some(version) if version.release.len() >= 3 || version.pre_release.is_some() => {
+                    Self {
+                        lower: Some((version.clone(), true)),
+                        upper: Some((version, true)),
+                    }
+                }
+                _ => Self::prefix(parts),
+            },
+            "^" => {
+                let lower = exact.unwrap_or_else(|| ParsedVersion::release_only(parts.clone()));
+                let significant = parts
+                    .iter()
+                    .position(|part| *part != 0)
+                    .unwrap_or(parts.len().saturating_sub(1));
+                Self {
+                    upper: Self::prefix(parts[..=significant].to_vec()).upper,
+                    lower: Some((lower, true)),
+                }
+            }
+            "~" => {
+                let lower = exact.unwrap_or_else(|| ParsedVersion::release_only(parts.clone()));
+                let kept = if parts.len() >= 2 { 2 } else { 1 };
+                Self {
+                    upper: Self::prefix(parts[..kept].to_vec()).upper,
+                    lower: Some((lower, true)),
+                }
+            }
+            ">" | ">=" | "<" | "<=" => {
+                let version = exact.unwrap_or_else(|| ParsedVersion::release_only(parts));
+                let inclusive = operator.ends_with('=');
+                if operator.starts_with('>') {
+                    Self {
+                        lower: Some((version, inclusive)),
+                        upper: None,
+                    }
+                } else {
+                    Self {
+                        lower: None,
+                        upper: Some((version, inclusive)),
+                    }
+                }
+            }
+            _ => return Err(invalid()),
+        })
+    }
+}
+
+impl VersionConstraint {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let normalized = raw.trim();
+        if normalized.is_empty() {
+            return Err("version constraint is empty".to_string());
+        }
+        let alternatives = normalized
+            .split("||")
+            .map(|alternative| {
+                let mut comparators = Vec::new();
+                let mut pending_operator = String::new();
+                for token in alternative
+                    .split([' ', ','])
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                {
+                    // Allow `>= 1.2` with the operator split from its version.
+                    if token.chars().all(|c| "<>=^~".contains(c)) {
+                        pending_operator.push_str(token);
+                        continue;
+                    }
+                    let comparator = format!("{pending_operator}{token}");
+                    pending_operator.clear();
+                    comparators.push(VersionBound::parse(&comparator)?);
+                }
+                if !pending_operator.is_empty() || comparators.is_empty() {
+                    return Err(format!("invalid version constraint '{normalized}'"));
+                }
+                Ok(comparators)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self {
+            raw: normalized.to_string(),
+            alternatives,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// The constraint as a Cargo version requirement such as
+    /// `>=1.2, <2`, for `cargo install --version`. Cargo has no `||` and
+    /// no versions with more than three components, so those yield `None`.
+    pub fn to_cargo_requirement(&self) -> Option<String> {
+        let [comparators] = self.alternatives.as_slice() else {
+            return None;
+        };
+        let mut parts = Vec::new();
+        for comparator in comparators {
+            if let Some((version, inclusive)) = &comparator.lower {
+                let operator = if *inclusive { ">=" } else { ">" };
+                parts.push(format!("{operator}{}", version.to_cargo_version()?));
+            }
+            if let Some((version, inclusive)) = &comparator.upper {
+                let operator = if *inclusive { "<=" } else { "<" };
+                parts.push(format!("{operator}{}", version.to_cargo_version()?));
+            }
+        }
+        if parts.is_empty() {
+            return Some("*".to_string());
+        }
+        Some(parts.join(", "))
+    }
+
+    /// Whether `version` satisfies the constraint. Versions that do not
+    /// parse as dotted numbers never do.
+    pub fn matches(&self, version: &str) -> bool {
+        let Some(version) = ParsedVersion::parse(version) else {
+            return false;
+        };
+        self.alternatives.iter().any(|comparators| {
+            comparators
+                .iter()
+                .all(|comparator| comparator.matches(&version))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{PackageCoordinate, normalize_package_family_key, package_family_preference_key};
+    use super::{
+        PackageCoordinate, VersionConstraint, normalize_package_family_key,
+        package_family_preference_key,
+    };
 
     #[test]
     fn parses_package_coordinate_without_selector() {
@@ -213,4 +522,56 @@ mod tests {
         );
         assert_eq!(package_family_preference_key(" rust ", None), "rust");
     }
+
+    #[test]
+    fn version_constraints_follow_semver_range_rules() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("^1.2", &["1.2.0", "1.9.4"], &["1.1.9", "2.0.0"]),
+            ("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2"]),
+            ("~1.4.2", &["1.4.2", "1.4.10"], &["1.5.0", "1.4.1"]),
+            (">=1.0 <2.0", &["1.0", "1.99.1"], &["2.0.0", "0.9"]),
+            ("<2.0", &["1.9.9", "2.0.0-rc1"], &["2.0.0", "2.1"]),
+            ("1.x || 3.1", &["1.0.5", "3.1.7"], &["2.0.0", "3.2.0"]),
+            ("= 1.2.3", &["1.2.3", "v1.2.3"], &["1.2.4"]),
+        ];
+        for (raw, matching, rejected) in cases {
+            let constraint = VersionConstraint::parse(raw).expect("constraint should parse");
+            for version in *matching {
+                assert!(constraint.matches(version), "{raw} should match {version}");
+            }
+            for version in *rejected {
+                assert!(
+                    !constraint.matches(version),
+                    "{raw} should reject {version}"
+                );
+            }
+        }
+        assert!(!VersionConstraint::parse("^1.2").unwrap().matches("latest"));
+        assert!(VersionConstraint::parse("").is_err());
+        assert!(VersionConstraint::parse(">=").is_err());
+        assert!(VersionConstraint::parse("^banana").is_err());
+        assert!(VersionConstraint::parse("*").unwrap().matches("0.0.1"));
+    }
+
+    #[test]
+    fn version_constraints_render_as_cargo_requirements() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("^1.2", Some(">=1.2, <2")),
+            ("~1.4.2", Some(">=1.4.2, <1.5")),
+            (">=1.0 <2.0", Some(">=1.0, <2.0")),
+            ("1.x", Some(">=1, <2")),
+            ("=2.0.0-beta.1", Some(">=2.0.0-beta.1, <=2.0.0-beta.1")),
+            ("*", Some("*")),
+            ("1.x || 2.1", None),
+            ("^1.2.3.4", None),
+        ];
+        for (raw, expected) in cases {
+            let constraint = VersionConstraint::parse(raw).expect("constraint should parse");
+            assert_eq!(
+                constraint.to_cargo_requirement().as_deref(),
+                *expected,
+                "{raw}"
+            );
+        }
+    }
 }
@@ -0,0 +1,109 @@
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ManagerId;
+
+static VIEW_HINT: RwLock<Option<ViewHint>> = RwLock::new(None);
+
+/// What the host is currently showing, so refreshes can favor it.
+///
+/// `manager` is the manager whose packages or details are on screen; `None`
+/// means the view spans all managers and nothing is favored.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewHint {
+    #[serde(default)]
+    pub manager: Option<ManagerId>,
+}
+
+/// Replace the current view hint. The hint persists until the host sends
+/// another one.
+pub fn set_view_hint(hint: ViewHint) {
+    *VIEW_HINT
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(hint);
+}
+
+pub fn current_view_hint() -> Option<ViewHint> {
+    VIEW_HINT
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// The manager the user is looking at, if the view is about one manager.
+pub fn focused_manager() -> Option<ManagerId> {
+    current_view_hint().and_then(|hint| hint.manager)
+}
+
+/// Move `focus` into a phase of its own ahead of every other phase, so the
+/// manager on screen refreshes first and the rest wait for it. Phases left
+/// empty are dropped; an unknown focus leaves the order unchanged.
+pub fn prioritize_phases(
+    mut phases: Vec<Vec<ManagerId>>,
+    focus: Option<ManagerId>,
+) -> Vec<Vec<ManagerId>> {
+    let Some(focus) = focus else {
+        return phases;
+    };
+    let Some(phase_index) = phases.iter().position(|phase| phase.contains(&focus)) else {
+        return phases;
+    };
+
+    phases[phase_index].retain(|manager| *manager != focus);
+    if phases[phase_index].is_empty() {
+        phases.remove(phase_index);
+    }
+    phases.insert(0, vec![focus]);
+    phases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ViewHint, prioritize_phases};
+    use crate::models::ManagerId;
+
+    fn phases() -> Vec<Vec<ManagerId>> {
+        vec![
+            vec![ManagerId::Mise],
+            vec![ManagerId::HomebrewFormula, ManagerId::Npm, ManagerId::Cargo],
+            vec![ManagerId::SoftwareUpdate],
+        ]
+    }
+
+    #[test]
+    fn focused_manager_refreshes_before_its_phase() {
+        assert_eq!(
+            prioritize_phases(phases(), Some(ManagerId::Npm)),
+            vec![
+                vec![ManagerId::Npm],
+                vec![ManagerId::Mise],
+                vec![ManagerId::HomebrewFormula, ManagerId::Cargo],
+                vec![ManagerId::SoftwareUpdate],
+            ]
+        );
+        assert_eq!(
+            prioritize_phases(phases(), Some(ManagerId::SoftwareUpdate)),
+            vec![
+                vec![ManagerId::SoftwareUpdate],
+                vec![ManagerId::Mise],
+                vec![ManagerId::HomebrewFormula, ManagerId::Npm, ManagerId::Cargo],
+            ]
+        );
+    }
+
+    #[test]
+    fn no_or_unknown_focus_keeps_authority_order() {
+        assert_eq!(prioritize_phases(phases(), None), phases());
+        assert_eq!(prioritize_phases(phases(), Some(ManagerId::Pnpm)), phases());
+    }
+
+    #[test]
+    fn hint_json_names_the_manager() {
+        let hint: ViewHint = serde_json::from_str(r#"{"manager":"npm"}"#).unwrap();
+        assert_eq!(hint.manager, Some(ManagerId::Npm));
+        let hint: ViewHint = serde_json::from_str("{}").unwrap();
+        assert_eq!(hint.manager, None);
+    }
+}
@@ -57,6 +57,7 @@ fn ensure_request_supported_returns_structured_error_for_missing_capability() {
         },
         target_name: None,
         version: None,
+        options: Vec::new(),
     });
 
     let error = ensure_request_supported(adapter.descriptor(), &request).unwrap_err();
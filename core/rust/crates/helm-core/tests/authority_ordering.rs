@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -454,3 +455,66 @@ async fn detect_all_ordered_includes_detection_only_authority() {
     assert_eq!(detect_calls.load(Ordering::SeqCst), 1);
     assert_eq!(list_outdated_calls.load(Ordering::SeqCst), 0);
 }
+
+#[tokio::test]
+async fn detect_all_ordered_skipping_absent_records_absence_without_probe() {
+    const DETECT_CAPS: &[Capability] = &[Capability::Detect];
+    let npm_detect_calls = Arc::new(AtomicU64::new(0));
+    let pnpm_detect_calls = Arc::new(AtomicU64::new(0));
+    let list_outdated_calls = Arc::new(AtomicU64::new(0));
+    let npm: Arc<dyn ManagerAdapter> = Arc::new(CapabilitySparseAdapter::new(
+        ManagerId::Npm,
+        ManagerAuthority::Standard,
+        DETECT_CAPS,
+        true,
+        npm_detect_calls.clone(),
+        list_outdated_calls.clone(),
+    ));
+    let pnpm: Arc<dyn ManagerAdapter> = Arc::new(CapabilitySparseAdapter::new(
+        ManagerId::Pnpm,
+        ManagerAuthority::Standard,
+        DETECT_CAPS,
+        true,
+        pnpm_detect_calls.clone(),
+        list_outdated_calls.clone(),
+    ));
+
+    let path = test_db_path("detect-skip-absent");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    store
+        .upsert_detection(
+            ManagerId::Npm,
+            &DetectionInfo {
+                installed: true,
+                executable_path: None,
+                version: Some("10.0.0".to_string()),
+            },
+        )
+        .unwrap();
+    let runtime = AdapterRuntime::with_all_stores(
+        [npm, pnpm],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap();
+
+    let absent = HashSet::from([ManagerId::Npm]);
+    let results = runtime.detect_all_ordered_skipping_absent(&absent).await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    assert_eq!(npm_detect_calls.load(Ordering::SeqCst), 0);
+    assert_eq!(pnpm_detect_calls.load(Ordering::SeqCst), 1);
+
+    let detections = store.list_detections().unwrap();
+    let npm_detection = detections
+        .iter()
+        .find(|(manager, _)| *manager == ManagerId::Npm)
+        .map(|(_, info)| info)
+        .expect("npm detection should be persisted");
+    assert!(!npm_detection.installed);
+    assert!(npm_detection.version.is_none());
+}
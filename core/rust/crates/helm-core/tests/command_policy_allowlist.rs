@@ -0,0 +1,289 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use helm_core::adapters::{
+    asdf, bundler, cargo, cargo_binstall, colima, docker_desktop, firmware_updates, homebrew,
+    homebrew_cask, homebrew_cleanup, homebrew_services, macports, mas, mise, nix_darwin, npm,
+    parallels_desktop, pip, pipx, pnpm, podman, poetry, rosetta2, rubygems, rustup, setapp,
+    softwareupdate, sparkle, xcode_command_line_tools, yarn,
+};
+use helm_core::execution::command_policy::check_command_policy;
+use helm_core::execution::{
+    CommandPolicyMode, ProcessSpawnRequest, ScriptInstallerStep, set_command_policy_mode,
+};
+use helm_core::models::{SearchQuery, ServiceAction};
+use sha2::{Digest, Sha256};
+
+/// Every public `*_request` builder under `helm_core::adapters`, called with
+/// placeholder arguments. Script-installer runs are covered separately because
+/// the process sources attach their policy step at spawn time.
+fn every_adapter_request() -> Vec<ProcessSpawnRequest> {
+    let query = SearchQuery {
+        text: "example".to_string(),
+        issued_at: SystemTime::now(),
+    };
+    let names = vec!["example".to_string()];
+    vec![
+        asdf::asdf_detect_request(None),
+        asdf::asdf_list_current_request(None),
+        asdf::asdf_list_plugins_request(None),
+        asdf::asdf_list_installed_versions_request(None, "example"),
+        asdf::asdf_search_plugins_request(None, &query),
+        asdf::asdf_latest_request(None, "example"),
+        asdf::asdf_add_plugin_request(None, "example"),
+        asdf::asdf_install_request(None, "example", Some("1.0.0")),
+        asdf::asdf_uninstall_request(None, "example", "example"),
+        asdf::asdf_set_home_version_request(None, "example", "example"),
+        asdf::asdf_self_update_request(None, "example"),
+        asdf::asdf_clone_install_request(None, "example"),
+        bundler::bundler_detect_request(None),
+        bundler::bundler_list_installed_request(None),
+        bundler::bundler_list_outdated_request(None),
+        bundler::bundler_install_request(None, Some("1.0.0")),
+        bundler::bundler_uninstall_request(None, Some("1.0.0")),
+        bundler::bundler_upgrade_request(None),
+        cargo::cargo_detect_request(None),
+        cargo::cargo_list_installed_request(None),
+        cargo::cargo_search_request(None, &query),
+        cargo::cargo_search_single_request(None, "example"),
+        cargo::cargo_install_request(None, "example", Some("1.0.0")),
+        cargo::cargo_uninstall_request(None, "example"),
+        cargo::cargo_upgrade_request(None, "example"),
+        cargo::cargo_info_request(None, "example"),
+        cargo_binstall::cargo_binstall_detect_request(None),
+        cargo_binstall::cargo_binstall_list_installed_request(None),
+        cargo_binstall::cargo_binstall_search_request(None, &query),
+        cargo_binstall::cargo_binstall_search_single_request(None, "example"),
+        cargo_binstall::cargo_binstall_install_request(None, "example", Some("1.0.0")),
+        cargo_binstall::cargo_binstall_uninstall_request(None, "example"),
+        cargo_binstall::cargo_binstall_upgrade_request(None, "example"),
+        colima::colima_detect_request(None),
+        colima::colima_list_outdated_request(None),
+        colima::colima_homebrew_info_request(None),
+        docker_desktop::docker_desktop_detect_request(None),
+        docker_desktop::docker_desktop_list_outdated_request(None),
+        docker_desktop::docker_desktop_homebrew_info_request(None),
+        firmware_updates::firmware_updates_history_request(None),
+        firmware_updates::firmware_updates_list_request(None),
+        homebrew::homebrew_detect_request(None),
+        homebrew::homebrew_config_request(None),
+        homebrew::homebrew_list_installed_request(None),
+        homebrew::homebrew_list_outdated_request(None),
+        homebrew::homebrew_refresh_outdated_request(None),
+        homebrew::homebrew_catalog_formulae_request(None),
+        homebrew::homebrew_search_formulae_request(None, &query),
+        homebrew::homebrew_search_local_request(None, &query),
+        homebrew::homebrew_install_request(None, "example", &names),
+        homebrew::homebrew_list_taps_request(None),
+        homebrew::homebrew_tap_request(None, "example", None),
+        homebrew::homebrew_uninstall_request(None, "example"),
+        homebrew::homebrew_update_request(None),
+        homebrew::homebrew_upgrade_request(None, Some("1.0.0"), &names),
+        homebrew::homebrew_cleanup_request(None, "example"),
+        homebrew::homebrew_pin_request(None, "example"),
+        homebrew::homebrew_unpin_request(None, "example"),
+        homebrew::homebrew_location_request(None, "example"),
+        homebrew::homebrew_cellar_request(None),
+        homebrew::homebrew_unlink_request(None, "example"),
+        homebrew::homebrew_link_request(None, "example"),
+        homebrew::homebrew_list_files_request(None, "example"),
+        homebrew::homebrew_list_dependencies_request(None),
+        homebrew::homebrew_list_requested_request(None),
+        homebrew::homebrew_info_request(None, "example"),
+        homebrew_cask::homebrew_cask_catalog_request(None),
+        homebrew_cask::homebrew_cask_search_request(None, &query),
+        homebrew_cask::homebrew_cask_info_request(None, "example"),
+        homebrew_cask::homebrew_cask_detect_request(None),
+        homebrew_cask::homebrew_cask_config_request(None),
+        homebrew_cask::homebrew_cask_list_installed_request(None),
+        homebrew_cask::homebrew_cask_list_outdated_request(None, false),
+        homebrew_cask::homebrew_cask_refresh_outdated_request(None, false),
+        homebrew_cask::homebrew_cask_install_request(None, "example", &names),
+        homebrew_cask::homebrew_cask_uninstall_request(None, "example"),
+        homebrew_cask::homebrew_cask_trash_app_request(None, Path::new("/tmp/helm-example")),
+        homebrew_cask::homebrew_cask_upgrade_request(None, Some("1.0.0"), &names),
+        homebrew_cleanup::homebrew_disk_cleanup_request(None, false),
+        homebrew_services::homebrew_services_list_request(None),
+        homebrew_services::homebrew_services_action_request(
+            None,
+            "example",
+            ServiceAction::Restart,
+        ),
+        macports::macports_detect_request(None),
+        macports::macports_list_installed_request(None),
+        macports::macports_list_outdated_request(None),
+        macports::macports_search_request(None, &query),
+        macports::macports_install_request(None, "example", Some("1.0.0"), &names),
+        macports::macports_uninstall_request(None, "example", Some("1.0.0"), &names),
+        macports::macports_upgrade_request(None, Some("1.0.0"), Some("1.0.0"), &names),
+        macports::macports_contents_request(None, "example"),
+        mas::mas_detect_request(None),
+        mas::mas_list_installed_request(None),
+        mas::mas_list_outdated_request(None),
+        mas::mas_search_request(None, &query),
+        mas::mas_install_request(None, "example"),
+        mas::mas_get_request(None, "example"),
+        mas::mas_uninstall_request(None, "example"),
+        mas::mas_upgrade_request(None, Some("1.0.0")),
+        mise::mise_detect_request(None),
+        mise::mise_list_installed_request(None),
+        mise::mise_list_outdated_request(None),
+        mise::mise_list_remote_request(None),
+        mise::mise_registry_request(None),
+        mise::mise_upgrade_request(None, Some("1.0.0")),
+        mise::mise_download_install_script_request(None, "example"),
+        mise::mise_install_tool_request(None, "example", Some("1.0.0")),
+        mise::mise_implode_request(None, false),
+        mise::mise_uninstall_tool_request(None, "example", Some("1.0.0")),
+        nix_darwin::nix_darwin_detect_request(None),
+        nix_darwin::nix_darwin_list_installed_request(None),
+        nix_darwin::nix_darwin_list_outdated_request(None),
+        nix_darwin::nix_darwin_search_request(None, &query),
+        nix_darwin::nix_darwin_install_request(None, "example"),
+        nix_darwin::nix_darwin_uninstall_request(None, "example"),
+        nix_darwin::nix_darwin_upgrade_request(None, Some("1.0.0")),
+        npm::npm_detect_request(None),
+        npm::npm_list_installed_request(None),
+        npm::npm_list_outdated_request(None),
+        npm::npm_search_request(None, &query),
+        npm::npm_install_request(None, "example", Some("1.0.0")),
+        npm::npm_uninstall_request(None, "example"),
+        npm::npm_upgrade_request(None, Some("1.0.0")),
+        npm::npm_global_root_request(None),
+        npm::npm_view_request(None, "example"),
+        npm::npm_cache_dir_request(None),
+        npm::npm_cache_clean_request(None),
+        npm::npm_ping_request(None),
+        parallels_desktop::parallels_desktop_detect_request(None, "example"),
+        pip::pip_detect_request(None),
+        pip::pip_list_request(None),
+        pip::pip_list_outdated_request(None),
+        pip::pip_search_request(None, &query),
+        pip::pip_install_request(None, "example", Some("1.0.0")),
+        pip::pip_uninstall_request(None, "example"),
+        pip::pip_upgrade_request(None, Some("1.0.0")),
+        pip::pip_show_files_request(None, "example"),
+        pip::pip_show_request(None, "example"),
+        pip::pip_cache_dir_request(None),
+        pip::pip_cache_purge_request(None),
+        pipx::pipx_detect_request(None),
+        pipx::pipx_list_request(None),
+        pipx::pipx_list_outdated_request(None),
+        pipx::pipx_search_request(None, &query),
+        pipx::pipx_install_request(None, "example", Some("1.0.0")),
+        pipx::pipx_uninstall_request(None, "example"),
+        pipx::pipx_upgrade_request(None, Some("1.0.0")),
+        pipx::pipx_venvs_dir_request(None),
+        pnpm::pnpm_detect_request(None),
+        pnpm::pnpm_list_installed_request(None),
+        pnpm::pnpm_list_outdated_request(None),
+        pnpm::pnpm_search_request(None, &query),
+        pnpm::pnpm_install_request(None, "example", Some("1.0.0")),
+        pnpm::pnpm_uninstall_request(None, "example"),
+        pnpm::pnpm_upgrade_request(None, Some("1.0.0")),
+        podman::podman_detect_request(None),
+        podman::podman_list_outdated_request(None),
+        podman::podman_homebrew_info_request(None),
+        poetry::poetry_detect_request(None),
+        poetry::poetry_list_plugins_request(None),
+        poetry::poetry_list_installed_request(None),
+        poetry::poetry_list_outdated_plugins_request(None),
+        poetry::poetry_install_plugin_request(None, "example", Some("1.0.0")),
+        poetry::poetry_uninstall_plugin_request(None, "example"),
+        poetry::poetry_upgrade_plugins_request(None, Some("1.0.0")),
+        rosetta2::rosetta2_detect_request(None),
+        rosetta2::rosetta2_install_request(None),
+        rubygems::rubygems_detect_request(None),
+        rubygems::rubygems_list_installed_request(None),
+        rubygems::rubygems_list_outdated_request(None),
+        rubygems::rubygems_search_request(None, &query),
+        rubygems::rubygems_install_request(None, "example", Some("1.0.0")),
+        rubygems::rubygems_uninstall_request(None, "example", Some("1.0.0")),
+        rubygems::rubygems_upgrade_request(None, Some("1.0.0")),
+        rustup::rustup_detect_request(None),
+        rustup::rustup_show_request(None),
+        rustup::rustup_toolchain_list_request(None),
+        rustup::rustup_component_list_request(None, "example"),
+        rustup::rustup_target_list_request(None, "example"),
+        rustup::rustup_override_list_request(None),
+        rustup::rustup_show_profile_request(None),
+        rustup::rustup_toolchain_version_request(None, "example"),
+        rustup::rustup_check_request(None),
+        rustup::rustup_toolchain_install_request(None, "example"),
+        rustup::rustup_self_uninstall_request(None),
+        rustup::rustup_toolchain_uninstall_request(None, "example"),
+        rustup::rustup_download_install_script_request(None, "example"),
+        rustup::rustup_self_update_request(None),
+        rustup::rustup_toolchain_update_request(None, "example"),
+        rustup::rustup_add_component_request(None, "example", "example"),
+        rustup::rustup_remove_component_request(None, "example", "example"),
+        rustup::rustup_add_target_request(None, "example", "example"),
+        rustup::rustup_remove_target_request(None, "example", "example"),
+        rustup::rustup_set_default_request(None, "example"),
+        rustup::rustup_set_override_request(None, "example", Path::new("/tmp/helm-example")),
+        rustup::rustup_unset_override_request(None, Path::new("/tmp/helm-example")),
+        rustup::rustup_set_profile_request(None, "example"),
+        setapp::setapp_detect_request(None, "example"),
+        softwareupdate::softwareupdate_detect_request(None),
+        softwareupdate::softwareupdate_list_request(None),
+        softwareupdate::softwareupdate_install_labels_request(None, &names),
+        sparkle::sparkle_detect_request(None, "example"),
+        xcode_command_line_tools::xcode_command_line_tools_detect_request(None),
+        xcode_command_line_tools::xcode_command_line_tools_list_outdated_request(None),
+        xcode_command_line_tools::xcode_command_line_tools_upgrade_request(None, "example"),
+        yarn::yarn_detect_request(None),
+        yarn::yarn_list_installed_request(None),
+        yarn::yarn_list_outdated_request(None),
+        yarn::yarn_search_request(None, &query),
+        yarn::yarn_install_request(None, "example", Some("1.0.0")),
+        yarn::yarn_uninstall_request(None, "example"),
+        yarn::yarn_upgrade_request(None, Some("1.0.0")),
+    ]
+}
+
+/// The script-installer runs with the step their process source attaches,
+/// against a stand-in script pinned to its own digest.
+fn script_installer_runs() -> Vec<ProcessSpawnRequest> {
+    let contents = b"#!/bin/sh\necho install\n";
+    let script_path = std::env::temp_dir().join(format!(
+        "helm-command-policy-allowlist-{}.sh",
+        std::process::id()
+    ));
+    std::fs::write(&script_path, contents).expect("failed to write stand-in script");
+    let sha256 = format!("{:x}", Sha256::digest(contents));
+    let script = script_path.to_string_lossy().to_string();
+    vec![
+        mise::mise_run_downloaded_install_script_request(None, &script),
+        rustup::rustup_run_downloaded_install_script_request(None, &script),
+        rustup::rustup_init_install_request(None, "/tmp/rustup-init"),
+    ]
+    .into_iter()
+    .map(|request| {
+        let step = if request.command.program.ends_with("rustup-init") {
+            ScriptInstallerStep::RunExisting {
+                program: request.command.program.clone(),
+            }
+        } else {
+            ScriptInstallerStep::Run {
+                script_path: script_path.clone(),
+                sha256: sha256.clone(),
+            }
+        };
+        request.script_installer(step)
+    })
+    .collect()
+}
+
+#[test]
+fn every_adapter_request_builder_passes_the_enforced_command_policy() {
+    set_command_policy_mode(CommandPolicyMode::Enforce);
+
+    let violations = every_adapter_request()
+        .iter()
+        .chain(script_installer_runs().iter())
+        .filter_map(|request| check_command_policy(request).err())
+        .map(|error| error.message)
+        .collect::<Vec<_>>();
+
+    assert!(violations.is_empty(), "{violations:#?}");
+}
@@ -7,6 +7,7 @@ use helm_core::adapters::asdf::{AsdfAdapter, AsdfDetectOutput, AsdfInstallSource
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::models::{ManagerId, PackageRef, SearchQuery};
 use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
@@ -306,6 +307,7 @@ async fn asdf_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("1.9.8".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -333,6 +335,7 @@ async fn asdf_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("3.12.2".to_string()),
+                disposal: Default::default(),
             }),
         )
         .await
@@ -354,12 +357,14 @@ async fn asdf_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Asdf,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Asdf,
                     name: "nodejs".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -6,7 +6,7 @@ use helm_core::adapters::bundler::BundlerAdapter;
 use helm_core::adapters::bundler_process::ProcessBundlerSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
-    ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -215,16 +215,20 @@ async fn search_install_uninstall_and_upgrade_bundler_through_full_orchestration
             package: package.clone(),
             target_name: None,
             version: Some("2.5.23".to_string()),
+            options: Vec::new(),
         }),
         AdapterRequest::Uninstall(UninstallRequest {
             package: package.clone(),
             target_name: None,
             version: Some("2.5.22".to_string()),
+            disposal: Default::default(),
         }),
         AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(package.clone()),
+            target: UpgradeTarget::Package(package.clone()),
             target_name: None,
             version: Some("2.5.22".to_string()),
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }),
     ] {
         let task_id = runtime
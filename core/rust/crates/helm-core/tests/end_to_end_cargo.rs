@@ -8,6 +8,7 @@ use helm_core::adapters::cargo_process::ProcessCargoSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -227,6 +228,7 @@ async fn cargo_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -253,6 +255,7 @@ async fn cargo_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -273,12 +276,14 @@ async fn cargo_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Cargo,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Cargo,
                     name: "bat".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -7,7 +7,7 @@ use helm_core::adapters::cargo_binstall::CargoBinstallAdapter;
 use helm_core::adapters::cargo_binstall_process::ProcessCargoBinstallSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
-    ListOutdatedRequest, ManagerAdapter, UninstallRequest, UpgradeRequest,
+    ListOutdatedRequest, ManagerAdapter, UninstallRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -241,6 +241,7 @@ async fn cargo_binstall_tracks_only_helm_managed_packages() {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -302,12 +303,14 @@ async fn cargo_binstall_tracks_only_helm_managed_packages() {
         .submit(
             ManagerId::CargoBinstall,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::CargoBinstall,
                     name: "bat".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -335,6 +338,7 @@ async fn cargo_binstall_tracks_only_helm_managed_packages() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -392,6 +396,7 @@ async fn cargo_binstall_rejects_untracked_uninstall() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
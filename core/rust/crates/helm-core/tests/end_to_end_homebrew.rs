@@ -1,22 +1,29 @@
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, HomebrewAdapter, InstallRequest,
-    ListInstalledRequest, ListOutdatedRequest, ManagerAdapter, ProcessHomebrewSource,
-    SearchRequest, UninstallRequest, UpgradeRequest,
+    ListInstalledRequest, ListOutdatedRequest, ListServicesRequest, ManagerAdapter,
+    ProcessHomebrewSource, SearchRequest, SetServiceStateRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
     ProcessTerminationMode, ProcessWaitFuture, RunningProcess,
 };
-use helm_core::models::{CoreErrorKind, ManagerId, PackageRef, SearchQuery, TaskStatus};
+use helm_core::models::{
+    CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, ServiceAction, TaskStatus,
+    TaskType,
+};
 use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
+use helm_core::persistence::DetectionStore;
+use helm_core::sqlite::SqliteStore;
 
 const INSTALLED_FIXTURE: &str = include_str!("fixtures/homebrew/installed.json");
 const OUTDATED_FIXTURE: &str = include_str!("fixtures/homebrew/outdated.json");
 const SEARCH_FIXTURE: &str = include_str!("fixtures/homebrew/search_local.txt");
+const SERVICES_FIXTURE: &str = include_str!("fixtures/homebrew/services_list.json");
 
 struct RoutingFakeExecutor {
     fail_all: bool,
@@ -78,6 +85,9 @@ impl ProcessExecutor for RoutingFakeExecutor {
                 Some("info") => INSTALLED_FIXTURE.as_bytes().to_vec(),
                 Some("outdated") => OUTDATED_FIXTURE.as_bytes().to_vec(),
                 Some("search") => SEARCH_FIXTURE.as_bytes().to_vec(),
+                Some("services") if args.get(1).map(String::as_str) == Some("list") => {
+                    SERVICES_FIXTURE.as_bytes().to_vec()
+                }
                 _ => Vec::new(),
             }
         } else {
@@ -102,6 +112,30 @@ fn build_runtime(executor: Arc<dyn ProcessExecutor>) -> AdapterRuntime {
     AdapterRuntime::new([adapter]).expect("runtime creation should succeed")
 }
 
+fn build_runtime_with_store(
+    executor: Arc<dyn ProcessExecutor>,
+    store: Arc<SqliteStore>,
+) -> AdapterRuntime {
+    let source = ProcessHomebrewSource::new(executor);
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(HomebrewAdapter::new(source));
+    AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store,
+    )
+    .expect("runtime creation with store should succeed")
+}
+
+fn test_db_path(test_name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("helm-{test_name}-{nanos}.sqlite3"))
+}
+
 #[tokio::test]
 async fn detect_through_full_orchestration_path() {
     let executor = Arc::new(RoutingFakeExecutor::normal());
@@ -247,6 +281,7 @@ async fn install_uninstall_and_upgrade_homebrew_formula_through_full_orchestrati
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -273,6 +308,7 @@ async fn install_uninstall_and_upgrade_homebrew_formula_through_full_orchestrati
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -293,12 +329,14 @@ async fn install_uninstall_and_upgrade_homebrew_formula_through_full_orchestrati
         .submit(
             ManagerId::HomebrewFormula,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::HomebrewFormula,
                     name: "openssl@3".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -316,6 +354,100 @@ async fn install_uninstall_and_upgrade_homebrew_formula_through_full_orchestrati
     ));
 }
 
+#[tokio::test]
+async fn list_and_restart_services_through_full_orchestration_path() {
+    let executor = Arc::new(RoutingFakeExecutor::normal());
+    let runtime = build_runtime(executor);
+
+    let list_task = runtime
+        .submit(
+            ManagerId::HomebrewFormula,
+            AdapterRequest::ListServices(ListServicesRequest),
+        )
+        .await
+        .unwrap();
+    let list_snapshot = runtime
+        .wait_for_terminal(list_task, Some(Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(list_snapshot.runtime.status, TaskStatus::Completed);
+    match list_snapshot.terminal_state {
+        Some(AdapterTaskTerminalState::Succeeded(AdapterResponse::Services(services))) => {
+            assert_eq!(services.len(), 3);
+            assert_eq!(services[0].name, "postgresql@16");
+        }
+        other => panic!("expected Services response, got {other:?}"),
+    }
+
+    let restart_task = runtime
+        .submit(
+            ManagerId::HomebrewFormula,
+            AdapterRequest::SetServiceState(SetServiceStateRequest {
+                service: "redis".to_string(),
+                action: ServiceAction::Restart,
+            }),
+        )
+        .await
+        .unwrap();
+    let restart_snapshot = runtime
+        .wait_for_terminal(restart_task, Some(Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(restart_snapshot.runtime.status, TaskStatus::Completed);
+    assert_eq!(restart_snapshot.runtime.task_type, TaskType::Configure);
+    assert!(matches!(
+        restart_snapshot.terminal_state,
+        Some(AdapterTaskTerminalState::Succeeded(
+            AdapterResponse::Services(_)
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn safe_mode_blocks_stopping_services_but_not_starting_them() {
+    let path = test_db_path("homebrew-services-safe-mode");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    store.set_safe_mode(true).unwrap();
+
+    let executor = Arc::new(RoutingFakeExecutor::normal());
+    let runtime = build_runtime_with_store(executor, store);
+
+    for action in [ServiceAction::Stop, ServiceAction::Restart] {
+        let error = runtime
+            .submit(
+                ManagerId::HomebrewFormula,
+                AdapterRequest::SetServiceState(SetServiceStateRequest {
+                    service: "postgresql@16".to_string(),
+                    action,
+                }),
+            )
+            .await
+            .expect_err("safe mode should block interrupting a service");
+        assert_eq!(error.kind, CoreErrorKind::InvalidInput);
+        assert_eq!(error.action, Some(ManagerAction::SetServiceState));
+        assert!(error.message.contains("safe mode"));
+    }
+
+    let start_task = runtime
+        .submit(
+            ManagerId::HomebrewFormula,
+            AdapterRequest::SetServiceState(SetServiceStateRequest {
+                service: "redis".to_string(),
+                action: ServiceAction::Start,
+            }),
+        )
+        .await
+        .expect("safe mode should still allow starting a service");
+    let start_snapshot = runtime
+        .wait_for_terminal(start_task, Some(Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(start_snapshot.runtime.status, TaskStatus::Completed);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[tokio::test]
 async fn process_failure_propagates_as_structured_error() {
     let executor = Arc::new(RoutingFakeExecutor::failing());
@@ -5,7 +5,7 @@ use std::time::{Duration, SystemTime};
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, HomebrewCaskAdapter, InstallRequest,
     ListInstalledRequest, ListOutdatedRequest, ManagerAdapter, ProcessHomebrewCaskSource,
-    SearchRequest, UninstallRequest, UpgradeRequest,
+    SearchRequest, UninstallRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -258,19 +258,23 @@ async fn install_uninstall_and_upgrade_homebrew_cask_through_full_orchestration_
             package: install_package.clone(),
             target_name: None,
             version: None,
+            options: Vec::new(),
         }),
         AdapterRequest::Uninstall(UninstallRequest {
             package: install_package.clone(),
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }),
         AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(PackageRef {
+            target: UpgradeTarget::Package(PackageRef {
                 manager: ManagerId::HomebrewCask,
                 name: "raycast".to_string(),
             }),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }),
     ] {
         let task_id = runtime
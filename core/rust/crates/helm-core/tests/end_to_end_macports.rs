@@ -7,6 +7,7 @@ use helm_core::adapters::macports_process::ProcessMacPortsSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -247,6 +248,7 @@ async fn macports_search_and_mutation_paths_use_exact_targets_through_orchestrat
                 package: package.clone(),
                 target_name: Some(package.name.clone()),
                 version: Some("2.49.0_0".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -271,6 +273,7 @@ async fn macports_search_and_mutation_paths_use_exact_targets_through_orchestrat
                 package: package.clone(),
                 target_name: Some(package.name.clone()),
                 version: Some("2.49.0_0".to_string()),
+                disposal: Default::default(),
             }),
         )
         .await
@@ -292,9 +295,11 @@ async fn macports_search_and_mutation_paths_use_exact_targets_through_orchestrat
         .submit(
             ManagerId::MacPorts,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(package),
+                target: UpgradeTarget::Package(package),
                 target_name: Some("git+credential_osxkeychain".to_string()),
                 version: Some("2.49.0_0".to_string()),
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
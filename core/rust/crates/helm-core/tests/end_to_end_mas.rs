@@ -8,6 +8,7 @@ use helm_core::adapters::mas_process::ProcessMasSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -268,21 +269,27 @@ async fn search_install_uninstall_and_upgrade_mas_through_full_orchestration_pat
             package: package.clone(),
             target_name: None,
             version: None,
+            options: Vec::new(),
         }),
         AdapterRequest::Uninstall(UninstallRequest {
             package: package.clone(),
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }),
         AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(package.clone()),
+            target: UpgradeTarget::Package(package.clone()),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }),
         AdapterRequest::Upgrade(UpgradeRequest {
-            package: None,
+            target: UpgradeTarget::All,
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }),
     ] {
         let task_id = runtime.submit(ManagerId::Mas, request).await.unwrap();
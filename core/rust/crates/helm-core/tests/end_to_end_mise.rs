@@ -6,6 +6,7 @@ use helm_core::adapters::mise_process::ProcessMiseSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -315,6 +316,7 @@ async fn install_mise_tool_through_full_orchestration_path() {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -343,6 +345,7 @@ async fn uninstall_mise_tool_through_full_orchestration_path() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -365,12 +368,14 @@ async fn upgrade_mise_tool_through_full_orchestration_path() {
         .submit(
             ManagerId::Mise,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Mise,
                     name: "python@3.12.3".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -8,6 +8,7 @@ use helm_core::adapters::npm_process::ProcessNpmSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -239,6 +240,7 @@ async fn npm_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("9.0.0".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -266,6 +268,7 @@ async fn npm_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -287,12 +290,14 @@ async fn npm_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Npm,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Npm,
                     name: "typescript".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
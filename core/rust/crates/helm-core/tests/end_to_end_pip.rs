@@ -8,6 +8,7 @@ use helm_core::adapters::pip_process::ProcessPipSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -251,6 +252,7 @@ async fn pip_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("0.28.1".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -278,6 +280,7 @@ async fn pip_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -299,12 +302,14 @@ async fn pip_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Pip,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Pip,
                     name: "black".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
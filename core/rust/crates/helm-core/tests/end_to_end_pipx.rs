@@ -5,9 +5,11 @@ use std::time::{Duration, SystemTime};
 
 use helm_core::adapters::pipx::PipxAdapter;
 use helm_core::adapters::pipx_process::ProcessPipxSource;
+use helm_core::adapters::pypi::{PypiClient, install_pypi_client};
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -19,6 +21,7 @@ use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
 const VERSION_FIXTURE: &str = include_str!("fixtures/pipx/version.txt");
 const INSTALLED_FIXTURE: &str = include_str!("fixtures/pipx/list_global.json");
 const OUTDATED_FIXTURE: &str = include_str!("fixtures/pipx/list_outdated.json");
+const PYPI_PROJECT_FIXTURE: &str = include_str!("fixtures/pipx/pypi_project.json");
 
 struct PipxFakeExecutor {
     httpie_upgraded: AtomicBool,
@@ -124,6 +127,14 @@ impl ProcessExecutor for PipxFakeExecutor {
     }
 }
 
+struct FakePypiClient;
+
+impl PypiClient for FakePypiClient {
+    fn fetch_project_json(&self, project: &str) -> Result<Option<String>, String> {
+        Ok((project == "httpie").then(|| PYPI_PROJECT_FIXTURE.to_string()))
+    }
+}
+
 fn build_runtime(executor: Arc<dyn ProcessExecutor>) -> AdapterRuntime {
     let source = ProcessPipxSource::new(executor);
     let adapter: Arc<dyn ManagerAdapter> = Arc::new(PipxAdapter::new(source));
@@ -132,6 +143,7 @@ fn build_runtime(executor: Arc<dyn ProcessExecutor>) -> AdapterRuntime {
 
 #[tokio::test]
 async fn pipx_detect_list_search_and_mutate_through_orchestration() {
+    install_pypi_client(Arc::new(FakePypiClient));
     let runtime = build_runtime(Arc::new(PipxFakeExecutor::new()));
 
     let detect_task = runtime
@@ -214,6 +226,9 @@ async fn pipx_detect_list_search_and_mutate_through_orchestration() {
         Some(AdapterTaskTerminalState::Succeeded(AdapterResponse::SearchResults(results))) => {
             assert_eq!(results.len(), 1);
             assert_eq!(results[0].result.package.name, "httpie");
+            assert_eq!(results[0].result.version.as_deref(), Some("3.2.4"));
+            // Only the PyPI lookup carries a summary.
+            assert!(results[0].result.summary.is_some());
         }
         other => panic!("expected SearchResults response, got {other:?}"),
     }
@@ -228,6 +243,7 @@ async fn pipx_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("1.8.4".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -255,6 +271,7 @@ async fn pipx_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -276,12 +293,14 @@ async fn pipx_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Pipx,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Pipx,
                     name: "httpie".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -8,6 +8,7 @@ use helm_core::adapters::poetry_process::ProcessPoetrySource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -220,6 +221,7 @@ async fn poetry_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("1.9.0".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -247,6 +249,7 @@ async fn poetry_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -267,12 +270,14 @@ async fn poetry_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Poetry,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Poetry,
                     name: "poetry-plugin-export".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -6,7 +6,7 @@ use helm_core::adapters::rubygems::RubyGemsAdapter;
 use helm_core::adapters::rubygems_process::ProcessRubyGemsSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
-    ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -222,16 +222,20 @@ async fn search_install_uninstall_and_upgrade_rubygems_through_full_orchestratio
             package: install_package,
             target_name: None,
             version: Some("1.72.0".to_string()),
+            options: Vec::new(),
         }),
         AdapterRequest::Uninstall(UninstallRequest {
             package: uninstall_package,
             target_name: None,
             version: Some("1.72.0".to_string()),
+            disposal: Default::default(),
         }),
         AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(upgrade_package),
+            target: UpgradeTarget::Package(upgrade_package),
             target_name: None,
             version: Some("13.1.0".to_string()),
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }),
     ] {
         let task_id = runtime
@@ -254,9 +258,11 @@ async fn search_install_uninstall_and_upgrade_rubygems_through_full_orchestratio
         .submit(
             ManagerId::RubyGems,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: None,
+                target: UpgradeTarget::All,
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -289,6 +289,7 @@ async fn install_toolchain_through_full_orchestration_path() {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -317,6 +318,7 @@ async fn uninstall_toolchain_through_full_orchestration_path() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -6,15 +6,13 @@ use helm_core::adapters::softwareupdate::SoftwareUpdateAdapter;
 use helm_core::adapters::softwareupdate_process::ProcessSoftwareUpdateSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, ListOutdatedRequest, ManagerAdapter,
-    UpgradeRequest,
+    UpgradeRequest, UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
     ProcessTerminationMode, ProcessWaitFuture, RunningProcess,
 };
-use helm_core::models::{
-    CoreErrorKind, ManagerAction, ManagerId, PackageRef, TaskStatus, TaskType,
-};
+use helm_core::models::{CoreErrorKind, ManagerAction, ManagerId, TaskStatus, TaskType};
 use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
 use helm_core::persistence::DetectionStore;
 use helm_core::sqlite::SqliteStore;
@@ -231,12 +229,11 @@ async fn safe_mode_blocks_softwareupdate_upgrade_submission() {
         .submit(
             ManagerId::SoftwareUpdate,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::SoftwareUpdate,
-                    name: "__confirm_os_updates__".to_string(),
-                }),
+                target: UpgradeTarget::OsUpdatesConfirmed,
                 target_name: None,
                 version: None,
+                options: vec!["Safari 18.3.1-18.3.1".to_string()],
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -264,12 +261,11 @@ async fn safe_mode_off_allows_confirmed_softwareupdate_upgrade() {
         .submit(
             ManagerId::SoftwareUpdate,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::SoftwareUpdate,
-                    name: "__confirm_os_updates__".to_string(),
-                }),
+                target: UpgradeTarget::OsUpdatesConfirmed,
                 target_name: None,
                 version: None,
+                options: vec!["Safari 18.3.1-18.3.1".to_string()],
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -290,3 +286,40 @@ async fn safe_mode_off_allows_confirmed_softwareupdate_upgrade() {
 
     let _ = std::fs::remove_file(path);
 }
+
+#[tokio::test]
+async fn offline_mode_blocks_network_tasks_but_allows_detection() {
+    let path = test_db_path("softwareupdate-offline-mode");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    store.set_offline_mode(true).unwrap();
+
+    let executor = Arc::new(SoftwareUpdateFakeExecutor::normal());
+    let runtime = build_runtime_with_store(executor, store);
+    assert!(runtime.is_offline_mode());
+
+    let error = runtime
+        .submit(
+            ManagerId::SoftwareUpdate,
+            AdapterRequest::ListOutdated(ListOutdatedRequest),
+        )
+        .await
+        .expect_err("offline mode should block outdated checks");
+    assert_eq!(error.kind, CoreErrorKind::OfflineMode);
+    assert_eq!(error.action, Some(ManagerAction::ListOutdated));
+
+    let task_id = runtime
+        .submit(
+            ManagerId::SoftwareUpdate,
+            AdapterRequest::Detect(DetectRequest),
+        )
+        .await
+        .unwrap();
+    let snapshot = runtime
+        .wait_for_terminal(task_id, Some(Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.runtime.status, TaskStatus::Completed);
+
+    let _ = std::fs::remove_file(path);
+}
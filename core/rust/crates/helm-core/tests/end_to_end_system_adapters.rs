@@ -23,7 +23,7 @@ use helm_core::adapters::xcode_command_line_tools::{
 };
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
-    ListOutdatedRequest, ManagerAdapter, RefreshRequest, UpgradeRequest,
+    ListOutdatedRequest, ManagerAdapter, RefreshRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::models::{CoreErrorKind, ManagerId, PackageRef};
 use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
@@ -528,12 +528,14 @@ async fn xcode_command_line_tools_detect_list_and_upgrade_through_orchestration(
         .submit(
             ManagerId::XcodeCommandLineTools,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::XcodeCommandLineTools,
                     name: "Command Line Tools for Xcode-16.3".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -628,6 +630,7 @@ async fn rosetta2_detect_and_install_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                options: Vec::new(),
             }),
         )
         .await
@@ -725,6 +728,7 @@ async fn nix_darwin_detect_and_refresh_through_orchestration() {
         Some(AdapterTaskTerminalState::Succeeded(AdapterResponse::SnapshotSync {
             installed,
             outdated,
+            ..
         })) => {
             assert!(
                 installed
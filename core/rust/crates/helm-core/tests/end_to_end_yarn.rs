@@ -8,6 +8,7 @@ use helm_core::adapters::yarn_process::ProcessYarnSource;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, DetectRequest, InstallRequest, ListInstalledRequest,
     ListOutdatedRequest, ManagerAdapter, SearchRequest, UninstallRequest, UpgradeRequest,
+    UpgradeTarget,
 };
 use helm_core::execution::{
     ExecutionResult, ProcessExecutor, ProcessExitStatus, ProcessOutput, ProcessSpawnRequest,
@@ -243,6 +244,7 @@ async fn yarn_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: Some("9.0.0".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -270,6 +272,7 @@ async fn yarn_detect_list_search_and_mutate_through_orchestration() {
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -291,12 +294,14 @@ async fn yarn_detect_list_search_and_mutate_through_orchestration() {
         .submit(
             ManagerId::Yarn,
             AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Yarn,
                     name: "typescript".to_string(),
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             }),
         )
         .await
@@ -7,10 +7,10 @@ use helm_core::adapters::npm::NpmDetectOutput;
 use helm_core::adapters::{
     AdapterRequest, AdapterResponse, AdapterResult, AsdfAdapter, AsdfSource, HomebrewAdapter,
     HomebrewSource, InstallRequest, ManagerAdapter, NpmAdapter, NpmSource, PinRequest,
-    UninstallRequest, UnpinRequest, UpgradeRequest,
+    UninstallRequest, UnpinRequest, UpgradeRequest, UpgradeTarget,
 };
 use helm_core::models::{
-    CoreError, CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery,
+    CoreError, CoreErrorKind, ManagerAction, ManagerId, PackageRef, SearchQuery, ServiceAction,
 };
 
 fn package(manager: ManagerId, name: &str) -> PackageRef {
@@ -152,6 +152,22 @@ impl NpmSource for NpmLifecycleSource {
     fn upgrade_global(&self, _name: Option<&str>) -> AdapterResult<String> {
         Ok("updated".to_string())
     }
+
+    fn global_root(&self) -> AdapterResult<String> {
+        Ok("/opt/homebrew/lib/node_modules\n".to_string())
+    }
+
+    fn view(&self, _name: &str) -> AdapterResult<String> {
+        Ok("{}".to_string())
+    }
+
+    fn cache_dir(&self) -> AdapterResult<String> {
+        Ok("/Users/dev/.npm\n".to_string())
+    }
+
+    fn clean_cache(&self) -> AdapterResult<String> {
+        Ok(String::new())
+    }
 }
 
 struct HomebrewIdempotentSource;
@@ -176,7 +192,7 @@ impl HomebrewSource for HomebrewIdempotentSource {
         Ok(String::new())
     }
 
-    fn install_formula(&self, _name: &str) -> AdapterResult<String> {
+    fn install_formula(&self, _name: &str, _options: &[String]) -> AdapterResult<String> {
         Err(process_failure(
             ManagerId::HomebrewFormula,
             ManagerAction::Install,
@@ -192,7 +208,11 @@ impl HomebrewSource for HomebrewIdempotentSource {
         ))
     }
 
-    fn upgrade_formula(&self, _name: Option<&str>) -> AdapterResult<String> {
+    fn update_homebrew(&self) -> AdapterResult<String> {
+        Ok("updated".to_string())
+    }
+
+    fn upgrade_formula(&self, _name: Option<&str>, _options: &[String]) -> AdapterResult<String> {
         Ok("upgraded".to_string())
     }
 
@@ -207,6 +227,50 @@ impl HomebrewSource for HomebrewIdempotentSource {
     fn unpin_formula(&self, _name: &str) -> AdapterResult<String> {
         Ok("unpinned".to_string())
     }
+
+    fn list_formula_files(&self, _name: &str) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn list_formula_dependencies(&self) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn list_requested_formulae(&self) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn formula_info(&self, _name: &str) -> AdapterResult<String> {
+        Ok(r#"{"formulae":[],"casks":[]}"#.to_string())
+    }
+
+    fn switch_formula_keg(&self, _name: &str, _version: &str) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn list_taps(&self) -> AdapterResult<String> {
+        Ok("homebrew/core\n".to_string())
+    }
+
+    fn add_tap(&self, _tap: &str, _url: Option<&str>) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn list_services(&self) -> AdapterResult<String> {
+        Ok("[]".to_string())
+    }
+
+    fn set_service_state(&self, _name: &str, _action: ServiceAction) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn cleanup(&self, _dry_run: bool) -> AdapterResult<String> {
+        Ok(String::new())
+    }
+
+    fn cellar(&self) -> AdapterResult<String> {
+        Ok("/opt/homebrew/Cellar\n".to_string())
+    }
 }
 
 #[test]
@@ -218,6 +282,7 @@ fn authoritative_asdf_lifecycle_mutations_are_emitted() {
             package: package(ManagerId::Asdf, "nodejs"),
             target_name: None,
             version: Some("20.12.2".to_string()),
+            options: Vec::new(),
         }))
         .expect("authoritative install should succeed");
     match install {
@@ -232,9 +297,11 @@ fn authoritative_asdf_lifecycle_mutations_are_emitted() {
 
     let upgrade = adapter
         .execute(AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(package(ManagerId::Asdf, "nodejs")),
+            target: UpgradeTarget::Package(package(ManagerId::Asdf, "nodejs")),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }))
         .expect("authoritative upgrade should succeed");
     match upgrade {
@@ -250,6 +317,7 @@ fn authoritative_asdf_lifecycle_mutations_are_emitted() {
             package: package(ManagerId::Asdf, "nodejs"),
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }))
         .expect("authoritative uninstall should succeed");
     match uninstall {
@@ -272,6 +340,7 @@ fn standard_npm_lifecycle_mutations_are_emitted() {
             package: package(ManagerId::Npm, "eslint"),
             target_name: None,
             version: Some("9.0.0".to_string()),
+            options: Vec::new(),
         }))
         .expect("standard install should succeed");
     match install {
@@ -286,9 +355,11 @@ fn standard_npm_lifecycle_mutations_are_emitted() {
 
     let upgrade = adapter
         .execute(AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(package(ManagerId::Npm, "eslint")),
+            target: UpgradeTarget::Package(package(ManagerId::Npm, "eslint")),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }))
         .expect("standard upgrade should succeed");
     match upgrade {
@@ -304,6 +375,7 @@ fn standard_npm_lifecycle_mutations_are_emitted() {
             package: package(ManagerId::Npm, "eslint"),
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }))
         .expect("standard uninstall should succeed");
     match uninstall {
@@ -325,6 +397,7 @@ fn guarded_homebrew_lifecycle_is_idempotent_for_already_installed_or_absent_form
             package: package(ManagerId::HomebrewFormula, "ripgrep"),
             target_name: None,
             version: None,
+            options: Vec::new(),
         }))
         .expect("guarded install should be idempotent for already-installed formula");
     match install {
@@ -340,6 +413,7 @@ fn guarded_homebrew_lifecycle_is_idempotent_for_already_installed_or_absent_form
             package: package(ManagerId::HomebrewFormula, "ripgrep"),
             target_name: None,
             version: None,
+            disposal: Default::default(),
         }))
         .expect("guarded uninstall should be idempotent for already-absent formula");
     match uninstall {
@@ -352,9 +426,11 @@ fn guarded_homebrew_lifecycle_is_idempotent_for_already_installed_or_absent_form
 
     let upgrade = adapter
         .execute(AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(package(ManagerId::HomebrewFormula, "ripgrep")),
+            target: UpgradeTarget::Package(package(ManagerId::HomebrewFormula, "ripgrep")),
             target_name: None,
             version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: false,
         }))
         .expect("guarded upgrade should succeed");
     match upgrade {
@@ -12,10 +12,11 @@ use helm_core::adapters::{
 use helm_core::models::{
     ActionSafety, Capability, CoreError, CoreErrorKind, DetectionInfo, ManagerAction,
     ManagerAuthority, ManagerCategory, ManagerDescriptor, ManagerId, OutdatedPackage, PackageRef,
-    SearchQuery, TaskId, TaskRecord, TaskStatus, TaskType,
+    SearchQuery, TaskId, TaskLabel, TaskRecord, TaskStatus, TaskType,
 };
 use helm_core::orchestration::{AdapterRuntime, AdapterTaskTerminalState};
 use helm_core::persistence::{DetectionStore, PackageStore, PersistenceResult, TaskStore};
+use helm_core::snapshot_cache::PersistenceListener;
 use helm_core::sqlite::SqliteStore;
 
 const TEST_CAPABILITIES: &[Capability] = &[Capability::Refresh, Capability::Search];
@@ -32,6 +33,7 @@ fn test_db_path(test_name: &str) -> PathBuf {
 #[derive(Clone)]
 enum AdapterBehavior {
     Succeeds(AdapterResponse),
+    SucceedsAfter(Duration, AdapterResponse),
     Fails(CoreError),
 }
 
@@ -200,6 +202,10 @@ impl ManagerAdapter for TestAdapter {
     fn execute(&self, _request: AdapterRequest) -> AdapterResult<AdapterResponse> {
         match &self.behavior {
             AdapterBehavior::Succeeds(response) => Ok(response.clone()),
+            AdapterBehavior::SucceedsAfter(delay, response) => {
+                std::thread::sleep(*delay);
+                Ok(response.clone())
+            }
             AdapterBehavior::Fails(error) => Err(error.clone()),
         }
     }
@@ -505,6 +511,95 @@ async fn submit_with_task_store_persists_queued_then_terminal_status() {
     assert_eq!(record.status, TaskStatus::Completed);
 }
 
+#[tokio::test]
+async fn submit_labeled_persists_the_label_with_the_task_row() {
+    let path = test_db_path("submit-labeled");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(TestAdapter::new(
+        ManagerId::Npm,
+        AdapterBehavior::Succeeds(AdapterResponse::Refreshed),
+    ));
+    let runtime = AdapterRuntime::with_task_store([adapter], store.clone()).unwrap();
+    let label = TaskLabel {
+        key: "service.task.label.refresh.manager".to_string(),
+        args: std::collections::BTreeMap::from([("manager".to_string(), "npm".to_string())]),
+    };
+
+    let task_id = runtime
+        .submit_labeled(
+            ManagerId::Npm,
+            AdapterRequest::Refresh(RefreshRequest),
+            label.clone(),
+        )
+        .await
+        .unwrap();
+    let queued = store.list_recent_tasks(10).unwrap();
+    assert_eq!(queued[0].id, task_id);
+    assert_eq!(queued[0].label.as_ref(), Some(&label));
+
+    runtime
+        .wait_for_terminal(task_id, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    let listed = store.list_recent_tasks(10).unwrap();
+    assert_eq!(listed[0].label.as_ref(), Some(&label));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn quiesce_pauses_submissions_and_stops_stale_task_persistence() {
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(TestAdapter::new(
+        ManagerId::Npm,
+        AdapterBehavior::SucceedsAfter(Duration::from_millis(100), AdapterResponse::Refreshed),
+    ));
+    let task_store = Arc::new(RecordingTaskStore::default());
+    let runtime = AdapterRuntime::with_task_store([adapter], task_store.clone()).unwrap();
+
+    let stale_task = runtime
+        .submit(ManagerId::Npm, AdapterRequest::Refresh(RefreshRequest))
+        .await
+        .unwrap();
+    let quiesced = runtime.quiesce().await;
+
+    let pending_runtime = runtime.clone();
+    let pending_submit = tokio::spawn(async move {
+        pending_runtime
+            .submit(ManagerId::Npm, AdapterRequest::Refresh(RefreshRequest))
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!pending_submit.is_finished());
+
+    let snapshot = runtime
+        .wait_for_terminal(stale_task, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.runtime.status, TaskStatus::Completed);
+    drop(quiesced);
+
+    let resumed_task = pending_submit.await.unwrap().unwrap();
+    runtime
+        .wait_for_terminal(resumed_task, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    let mut resumed_status = None;
+    for _ in 0..20 {
+        resumed_status = task_store.get(resumed_task).map(|record| record.status);
+        if resumed_status == Some(TaskStatus::Completed) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(resumed_status, Some(TaskStatus::Completed));
+    assert_ne!(
+        task_store.get(stale_task).map(|record| record.status),
+        Some(TaskStatus::Completed)
+    );
+}
+
 #[tokio::test]
 async fn submit_returns_error_when_initial_task_persistence_fails() {
     let adapter: Arc<dyn ManagerAdapter> = Arc::new(TestAdapter::new(
@@ -699,6 +794,118 @@ async fn refresh_all_ordered_recomputes_enablement_after_preference_update() {
     );
 }
 
+#[tokio::test]
+async fn scheduled_refreshes_run_once_per_due_interval() {
+    use helm_core::orchestration::refresh_scheduler::{refresh_schedules, set_refresh_schedule};
+
+    let path = test_db_path("orchestration-runtime-scheduled-refresh");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    store.set_manager_enabled(ManagerId::Npm, true).unwrap();
+    set_refresh_schedule(store.as_ref(), ManagerId::Npm, 3_600).unwrap();
+
+    let call_count = Arc::new(AtomicUsize::new(0));
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(SequencedAdapter::with_capabilities(
+        ManagerId::Npm,
+        &[Capability::ListInstalled],
+        vec![
+            Ok(AdapterResponse::InstalledPackages(vec![])),
+            Ok(AdapterResponse::InstalledPackages(vec![])),
+        ],
+        call_count.clone(),
+    ));
+    let runtime = AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap();
+
+    let first = runtime.run_due_scheduled_refreshes(10_000).await;
+    assert_eq!(first.len(), 1);
+    assert!(first[0].1.is_ok());
+    assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        refresh_schedules(store.as_ref()).unwrap()[0].last_refreshed_unix,
+        Some(10_000)
+    );
+
+    assert!(runtime.run_due_scheduled_refreshes(12_000).await.is_empty());
+    assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+    assert_eq!(runtime.run_due_scheduled_refreshes(13_600).await.len(), 1);
+    assert_eq!(call_count.load(Ordering::SeqCst), 2);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn repeated_refresh_failures_quarantine_until_a_probe_succeeds() {
+    use helm_core::orchestration::QUARANTINE_FAILURE_THRESHOLD;
+    use helm_core::orchestration::quarantine::quarantined_managers;
+
+    let path = test_db_path("orchestration-runtime-quarantine");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+    store.set_manager_enabled(ManagerId::Npm, true).unwrap();
+
+    let failure = || {
+        Err(CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::ListInstalled),
+            kind: CoreErrorKind::ParseFailure,
+            message: "unexpected npm output".to_string(),
+        })
+    };
+    let call_count = Arc::new(AtomicUsize::new(0));
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(SequencedAdapter::with_capabilities(
+        ManagerId::Npm,
+        &[Capability::ListInstalled],
+        vec![
+            failure(),
+            failure(),
+            failure(),
+            Ok(AdapterResponse::InstalledPackages(vec![])),
+        ],
+        call_count.clone(),
+    ));
+    let runtime = AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap();
+
+    for _ in 0..QUARANTINE_FAILURE_THRESHOLD {
+        assert!(runtime.refresh_all_ordered().await[0].1.is_err());
+    }
+    let quarantines = quarantined_managers(store.as_ref()).unwrap();
+    assert_eq!(quarantines.len(), 1);
+    assert_eq!(quarantines[0].manager, ManagerId::Npm);
+    assert_eq!(quarantines[0].reason_kind, CoreErrorKind::ParseFailure);
+
+    // Quarantined managers sit out refresh-all runs until their probe is due.
+    assert!(runtime.refresh_all_ordered().await[0].1.is_ok());
+    assert_eq!(call_count.load(Ordering::SeqCst), 3);
+    let now_unix = quarantines[0].next_probe_unix - 1;
+    assert!(runtime.run_due_quarantine_probes(now_unix).await.is_empty());
+
+    let probes = runtime
+        .run_due_quarantine_probes(quarantines[0].next_probe_unix)
+        .await;
+    assert_eq!(probes.len(), 1);
+    assert!(probes[0].1.is_ok());
+    assert_eq!(call_count.load(Ordering::SeqCst), 4);
+    assert!(quarantined_managers(store.as_ref()).unwrap().is_empty());
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[tokio::test]
 async fn detect_persists_install_instances_alongside_detection_rows() {
     let path = test_db_path("orchestration-runtime-detect-install-instances");
@@ -781,6 +988,7 @@ async fn install_mutation_updates_cached_snapshots_without_manual_refresh() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -793,6 +1001,7 @@ async fn install_mutation_updates_cached_snapshots_without_manual_refresh() {
             action: ManagerAction::Install,
             before_version: Some("9.24.0".to_string()),
             after_version: Some("9.25.0".to_string()),
+            disposal: None,
         })),
     ));
     let runtime = AdapterRuntime::with_all_stores(
@@ -811,6 +1020,7 @@ async fn install_mutation_updates_cached_snapshots_without_manual_refresh() {
                 package: package.clone(),
                 target_name: None,
                 version: Some("9.25.0".to_string()),
+                options: Vec::new(),
             }),
         )
         .await
@@ -853,6 +1063,7 @@ async fn uninstall_mutation_removes_cached_snapshots_without_manual_refresh() {
             installed_version: Some("5.8.3".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
     store
@@ -864,6 +1075,7 @@ async fn uninstall_mutation_removes_cached_snapshots_without_manual_refresh() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -876,6 +1088,7 @@ async fn uninstall_mutation_removes_cached_snapshots_without_manual_refresh() {
             action: ManagerAction::Uninstall,
             before_version: Some("5.8.3".to_string()),
             after_version: None,
+            disposal: None,
         })),
     ));
     let runtime = AdapterRuntime::with_all_stores(
@@ -894,6 +1107,7 @@ async fn uninstall_mutation_removes_cached_snapshots_without_manual_refresh() {
                 package: package.clone(),
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
         )
         .await
@@ -934,6 +1148,7 @@ async fn installed_snapshot_refresh_replaces_stale_rows_for_manager() {
             installed_version: Some("5.8.3".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -977,3 +1192,332 @@ async fn installed_snapshot_refresh_replaces_stale_rows_for_manager() {
         "expected list-installed refresh to replace stale installed rows for manager"
     );
 }
+
+#[tokio::test]
+async fn interrupted_refresh_marks_manager_snapshot_partial() {
+    let path = test_db_path("orchestration-runtime-partial-snapshot");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(SequencedAdapter::with_capabilities(
+        ManagerId::Npm,
+        &[Capability::ListInstalled, Capability::ListOutdated],
+        vec![
+            Ok(AdapterResponse::InstalledPackages(vec![])),
+            Err(CoreError {
+                manager: None,
+                task: None,
+                action: None,
+                kind: CoreErrorKind::ParseFailure,
+                message: "invalid outdated payload".to_string(),
+            }),
+            Ok(AdapterResponse::InstalledPackages(vec![])),
+            Ok(AdapterResponse::OutdatedPackages(vec![])),
+        ],
+        Arc::new(AtomicUsize::new(0)),
+    ));
+    let runtime = AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap();
+
+    async fn wait_for_freshness(
+        store: &SqliteStore,
+        partial: bool,
+    ) -> Vec<helm_core::models::PackageSnapshotFreshness> {
+        let mut freshness = Vec::new();
+        for _ in 0..50 {
+            freshness = store.list_package_snapshot_freshness().unwrap();
+            if freshness
+                .first()
+                .is_some_and(|entry| entry.partial == partial)
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        freshness
+    }
+
+    runtime
+        .refresh_manager(ManagerId::Npm)
+        .await
+        .expect_err("outdated listing should fail");
+    let partial = wait_for_freshness(&store, true).await;
+    assert_eq!(partial.len(), 1);
+    assert_eq!(partial[0].manager, ManagerId::Npm);
+    assert!(
+        partial[0].partial,
+        "failed outdated listing should mark snapshot partial"
+    );
+    let failed_task = store
+        .list_recent_tasks(10)
+        .unwrap()
+        .into_iter()
+        .find(|task| task.status == TaskStatus::Failed)
+        .expect("failed outdated task should be recorded");
+    assert_eq!(partial[0].task_id, failed_task.id);
+    assert!(!partial[0].installed_partial);
+    assert!(partial[0].outdated_partial);
+
+    runtime.refresh_manager(ManagerId::Npm).await.unwrap();
+    let complete = wait_for_freshness(&store, false).await;
+    assert!(
+        !complete[0].partial,
+        "completed refresh should clear partial marker"
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn refresh_stage_failure_is_logged_and_marks_only_that_list_partial() {
+    let path = test_db_path("orchestration-runtime-stage-failure");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(TestAdapter::new(
+        ManagerId::Npm,
+        AdapterBehavior::Succeeds(AdapterResponse::SnapshotSync {
+            installed: Some(Vec::new()),
+            outdated: None,
+            stage_failures: vec![CoreError {
+                manager: Some(ManagerId::Npm),
+                task: None,
+                action: Some(ManagerAction::ListOutdated),
+                kind: CoreErrorKind::Timeout,
+                message: "npm outdated timed out".to_string(),
+            }],
+        }),
+    ));
+    let runtime = AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap();
+
+    let task_id = runtime
+        .submit(ManagerId::Npm, AdapterRequest::Refresh(RefreshRequest))
+        .await
+        .unwrap();
+    let snapshot = runtime
+        .wait_for_terminal(task_id, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert!(matches!(
+        snapshot.terminal_state,
+        Some(AdapterTaskTerminalState::Succeeded(_))
+    ));
+
+    let mut freshness = Vec::new();
+    let mut logs = Vec::new();
+    for _ in 0..50 {
+        freshness = store.list_package_snapshot_freshness().unwrap();
+        logs = store.list_task_logs(task_id, 20).unwrap();
+        if !freshness.is_empty()
+            && logs
+                .iter()
+                .any(|entry| entry.level == helm_core::models::TaskLogLevel::Warn)
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(freshness.len(), 1);
+    assert!(freshness[0].partial);
+    assert!(!freshness[0].installed_partial);
+    assert!(freshness[0].outdated_partial);
+    assert!(logs.iter().any(|entry| {
+        entry.level == helm_core::models::TaskLogLevel::Warn
+            && entry.message.contains("list_outdated failed (timeout)")
+    }));
+
+    let _ = std::fs::remove_file(path);
+}
+
+struct RecordingPersistenceListener {
+    store: Arc<SqliteStore>,
+    observed: Mutex<Vec<(ManagerId, usize)>>,
+}
+
+impl PersistenceListener for RecordingPersistenceListener {
+    fn snapshot_persisted(&self, manager: ManagerId) {
+        let installed = self.store.list_installed().unwrap().len();
+        self.observed.lock().unwrap().push((manager, installed));
+    }
+}
+
+#[tokio::test]
+async fn persistence_listener_runs_after_snapshot_is_persisted() {
+    let path = test_db_path("orchestration-runtime-persistence-listener");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(TestAdapter::with_capabilities(
+        ManagerId::Npm,
+        &[Capability::ListInstalled, Capability::Search],
+        AdapterBehavior::Succeeds(AdapterResponse::InstalledPackages(vec![
+            helm_core::models::InstalledPackage {
+                package: PackageRef {
+                    manager: ManagerId::Npm,
+                    name: "typescript".to_string(),
+                },
+                package_identifier: None,
+                installed_version: Some("5.8.3".to_string()),
+                pinned: false,
+                runtime_state: Default::default(),
+                sizes: Default::default(),
+            },
+        ])),
+    ));
+    let listener = Arc::new(RecordingPersistenceListener {
+        store: store.clone(),
+        observed: Mutex::new(Vec::new()),
+    });
+    let runtime = AdapterRuntime::with_all_stores(
+        [adapter],
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )
+    .unwrap()
+    .with_persistence_listener(listener.clone());
+
+    runtime
+        .submit_refresh_request_response(
+            ManagerId::Npm,
+            AdapterRequest::ListInstalled(ListInstalledRequest),
+        )
+        .await
+        .unwrap();
+
+    let mut observed = Vec::new();
+    for _ in 0..50 {
+        observed = listener.observed.lock().unwrap().clone();
+        if !observed.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(observed, vec![(ManagerId::Npm, 1)]);
+}
+
+/// Records which managers ran, in order, before behaving like [`TestAdapter`].
+struct OrderRecordingAdapter {
+    inner: TestAdapter,
+    ran: Arc<Mutex<Vec<ManagerId>>>,
+}
+
+impl ManagerAdapter for OrderRecordingAdapter {
+    fn descriptor(&self) -> &ManagerDescriptor {
+        self.inner.descriptor()
+    }
+
+    fn action_safety(&self, action: ManagerAction) -> ActionSafety {
+        self.inner.action_safety(action)
+    }
+
+    fn execute(&self, request: AdapterRequest) -> AdapterResult<AdapterResponse> {
+        self.ran.lock().unwrap().push(self.inner.descriptor.id);
+        self.inner.execute(request)
+    }
+}
+
+fn order_recording_runtime(
+    first: AdapterBehavior,
+    ran: &Arc<Mutex<Vec<ManagerId>>>,
+) -> AdapterRuntime {
+    let adapters: [Arc<dyn ManagerAdapter>; 2] = [
+        Arc::new(OrderRecordingAdapter {
+            inner: TestAdapter::new(ManagerId::Npm, first),
+            ran: ran.clone(),
+        }),
+        Arc::new(OrderRecordingAdapter {
+            inner: TestAdapter::new(
+                ManagerId::Pnpm,
+                AdapterBehavior::Succeeds(AdapterResponse::Refreshed),
+            ),
+            ran: ran.clone(),
+        }),
+    ];
+    AdapterRuntime::new(adapters).unwrap()
+}
+
+#[tokio::test]
+async fn submit_after_runs_only_once_its_dependency_succeeds() {
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let runtime = order_recording_runtime(
+        AdapterBehavior::SucceedsAfter(Duration::from_millis(150), AdapterResponse::Refreshed),
+        &ran,
+    );
+
+    let first = runtime
+        .submit(ManagerId::Npm, AdapterRequest::Refresh(RefreshRequest))
+        .await
+        .unwrap();
+    let second = runtime
+        .submit_after(
+            ManagerId::Pnpm,
+            AdapterRequest::Refresh(RefreshRequest),
+            vec![first],
+        )
+        .await
+        .unwrap();
+
+    let snapshot = runtime
+        .wait_for_terminal(second, Some(Duration::from_secs(2)))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.runtime.status, TaskStatus::Completed);
+    assert_eq!(snapshot.runtime.depends_on, vec![first]);
+    assert_eq!(*ran.lock().unwrap(), vec![ManagerId::Npm, ManagerId::Pnpm]);
+}
+
+#[tokio::test]
+async fn submit_after_fails_without_running_when_a_dependency_fails() {
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let runtime = order_recording_runtime(
+        AdapterBehavior::Fails(CoreError {
+            manager: Some(ManagerId::Npm),
+            task: Some(TaskType::Refresh),
+            action: Some(ManagerAction::Refresh),
+            kind: CoreErrorKind::ProcessFailure,
+            message: "dependency failed".to_string(),
+        }),
+        &ran,
+    );
+
+    let first = runtime
+        .submit(ManagerId::Npm, AdapterRequest::Refresh(RefreshRequest))
+        .await
+        .unwrap();
+    let second = runtime
+        .submit_after(
+            ManagerId::Pnpm,
+            AdapterRequest::Refresh(RefreshRequest),
+            vec![first],
+        )
+        .await
+        .unwrap();
+
+    let snapshot = runtime
+        .wait_for_terminal(second, Some(Duration::from_secs(2)))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.runtime.status, TaskStatus::Failed);
+    assert!(matches!(
+        snapshot.terminal_state,
+        Some(AdapterTaskTerminalState::Failed(_))
+    ));
+    assert_eq!(*ran.lock().unwrap(), vec![ManagerId::Npm]);
+}
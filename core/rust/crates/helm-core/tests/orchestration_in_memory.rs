@@ -2,14 +2,16 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use helm_core::models::{CoreErrorKind, ManagerId, TaskStatus, TaskType};
 use helm_core::orchestration::{
-    CancellationMode, InMemoryTaskCoordinator, TaskCoordinator, TaskSubmission,
+    CancellationMode, InMemoryTaskCoordinator, TaskCoordinator, TaskPriority, TaskSubmission,
 };
 
 fn submission(manager: ManagerId, task_type: TaskType, seconds: u64) -> TaskSubmission {
     TaskSubmission {
         manager,
         task_type,
+        priority: TaskPriority::for_task_type(task_type),
         requested_at: UNIX_EPOCH + Duration::from_secs(seconds),
+        depends_on: Vec::new(),
     }
 }
 
@@ -92,7 +94,9 @@ fn enqueue_uses_submission_timestamp_as_creation_time() {
         .enqueue(TaskSubmission {
             manager: ManagerId::Npm,
             task_type: TaskType::Detection,
+            priority: TaskPriority::Background,
             requested_at: requested,
+            depends_on: Vec::new(),
         })
         .unwrap();
 
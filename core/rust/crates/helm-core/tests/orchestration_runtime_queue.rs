@@ -7,14 +7,16 @@ use std::time::{Duration, SystemTime};
 use helm_core::models::{CoreError, CoreErrorKind, ManagerId, TaskStatus, TaskType};
 use helm_core::orchestration::{
     CancellationMode, InMemoryAsyncTaskQueue, OrchestrationResult, TaskCancellationToken,
-    TaskOperation, TaskSubmission,
+    TaskOperation, TaskPriority, TaskSubmission,
 };
 
 fn submission(manager: ManagerId, task_type: TaskType) -> TaskSubmission {
     TaskSubmission {
         manager,
         task_type,
+        priority: TaskPriority::for_task_type(task_type),
         requested_at: SystemTime::now(),
+        depends_on: Vec::new(),
     }
 }
 
@@ -136,6 +138,99 @@ async fn different_managers_can_run_in_parallel() {
     assert!(peak.load(Ordering::SeqCst) >= 2);
 }
 
+#[tokio::test]
+async fn runtime_provider_upgrade_is_ordered_against_dependent_manager_tasks() {
+    let queue = InMemoryAsyncTaskQueue::new();
+    let npm_refresh_done = Arc::new(AtomicBool::new(false));
+    let mise_upgrade_done = Arc::new(AtomicBool::new(false));
+
+    let npm_refresh = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Refresh),
+            operation({
+                let npm_refresh_done = npm_refresh_done.clone();
+                move |_| async move {
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    npm_refresh_done.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+            }),
+        )
+        .await
+        .unwrap();
+
+    let mise_upgrade = queue
+        .spawn(
+            submission(ManagerId::Mise, TaskType::Upgrade),
+            operation({
+                let npm_refresh_done = npm_refresh_done.clone();
+                let mise_upgrade_done = mise_upgrade_done.clone();
+                move |_| async move {
+                    assert!(npm_refresh_done.load(Ordering::SeqCst));
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    mise_upgrade_done.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+            }),
+        )
+        .await
+        .unwrap();
+
+    let npm_upgrade = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Upgrade),
+            operation({
+                let mise_upgrade_done = mise_upgrade_done.clone();
+                move |_| async move {
+                    assert!(mise_upgrade_done.load(Ordering::SeqCst));
+                    Ok(())
+                }
+            }),
+        )
+        .await
+        .unwrap();
+
+    let homebrew_refresh = queue
+        .spawn(
+            submission(ManagerId::HomebrewFormula, TaskType::Refresh),
+            operation(|_| async { Ok(()) }),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        queue.snapshot(mise_upgrade).await.unwrap().blocked_by,
+        vec![npm_refresh]
+    );
+    assert_eq!(
+        queue.snapshot(npm_upgrade).await.unwrap().blocked_by,
+        vec![mise_upgrade]
+    );
+    assert!(
+        queue
+            .snapshot(homebrew_refresh)
+            .await
+            .unwrap()
+            .blocked_by
+            .is_empty()
+    );
+
+    for task_id in [npm_refresh, mise_upgrade, npm_upgrade, homebrew_refresh] {
+        let snapshot = queue
+            .wait_for_terminal(task_id, Some(Duration::from_secs(2)))
+            .await
+            .unwrap();
+        assert_eq!(snapshot.status, TaskStatus::Completed);
+    }
+    assert!(
+        queue
+            .pending_blockers(npm_upgrade)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[tokio::test]
 async fn immediate_cancellation_terminates_running_task_and_unblocks_queue() {
     let queue = InMemoryAsyncTaskQueue::new();
@@ -331,3 +426,255 @@ async fn wait_for_terminal_is_stable_for_fast_completing_tasks() {
         assert_eq!(snapshot.status, TaskStatus::Completed);
     }
 }
+
+#[tokio::test]
+async fn task_temp_dirs_are_removed_on_completion_and_cancellation() {
+    use helm_core::execution::task_temp_dir_store;
+
+    let queue = InMemoryAsyncTaskQueue::with_initial_id(91_000);
+    let scratch = |fail_after_create: bool| -> TaskOperation {
+        Box::new(move |task_id, token| {
+            Box::pin(async move {
+                let dir = task_temp_dir_store::get_or_create(task_id).unwrap();
+                std::fs::write(dir.join("install.sh"), "echo hi").unwrap();
+                while fail_after_create && !token.is_cancelled() {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                Ok(())
+            }) as Pin<Box<dyn Future<Output = OrchestrationResult<()>> + Send>>
+        })
+    };
+
+    let completed = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Install),
+            scratch(false),
+        )
+        .await
+        .unwrap();
+    let cancelled = queue
+        .spawn(submission(ManagerId::Pip, TaskType::Install), scratch(true))
+        .await
+        .unwrap();
+    queue
+        .wait_for_terminal(completed, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let cancelled_dir = task_temp_dir_store::temp_root().join(format!("task-{}", cancelled.0));
+    assert!(cancelled_dir.is_dir());
+    queue
+        .cancel(cancelled, CancellationMode::Immediate)
+        .await
+        .unwrap();
+    queue
+        .wait_for_terminal(cancelled, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+
+    // Removal runs on the blocking pool after the terminal transition.
+    let completed_dir = task_temp_dir_store::temp_root().join(format!("task-{}", completed.0));
+    let deadline = std::time::Instant::now() + Duration::from_secs(2);
+    while (completed_dir.exists() || cancelled_dir.exists()) && std::time::Instant::now() < deadline
+    {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!completed_dir.exists());
+    assert!(!cancelled_dir.exists());
+}
+
+#[tokio::test]
+async fn waiting_tasks_start_in_priority_order() {
+    let queue = InMemoryAsyncTaskQueue::new();
+    let started = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let record = |label: &'static str, hold: Duration| {
+        let started = started.clone();
+        operation(move |_| async move {
+            started.lock().unwrap().push(label);
+            tokio::time::sleep(hold).await;
+            Ok(())
+        })
+    };
+
+    let running = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Refresh),
+            record("running refresh", Duration::from_millis(100)),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let mut waiting = Vec::new();
+    for (task_type, label) in [
+        (TaskType::Refresh, "queued refresh"),
+        (TaskType::Install, "install"),
+        (TaskType::Detection, "detection"),
+        (TaskType::Search, "search"),
+    ] {
+        let task_id = queue
+            .spawn(
+                submission(ManagerId::Npm, task_type),
+                record(label, Duration::ZERO),
+            )
+            .await
+            .unwrap();
+        waiting.push(task_id);
+    }
+
+    for task_id in std::iter::once(running).chain(waiting) {
+        queue
+            .wait_for_terminal(task_id, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        *started.lock().unwrap(),
+        vec![
+            "running refresh",
+            "search",
+            "install",
+            "queued refresh",
+            "detection",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn aborted_waiter_does_not_hold_up_lower_priority_tasks() {
+    let queue = InMemoryAsyncTaskQueue::new();
+    let blocker = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Refresh),
+            operation(|_| async {
+                tokio::time::sleep(Duration::from_millis(60)).await;
+                Ok(())
+            }),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let install = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Install),
+            operation(|_| async { Ok(()) }),
+        )
+        .await
+        .unwrap();
+    let refresh = queue
+        .spawn(
+            submission(ManagerId::Npm, TaskType::Refresh),
+            operation(|_| async { Ok(()) }),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    queue
+        .cancel(install, CancellationMode::Immediate)
+        .await
+        .unwrap();
+
+    let blocker = queue
+        .wait_for_terminal(blocker, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    let refresh = queue
+        .wait_for_terminal(refresh, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert_eq!(blocker.status, TaskStatus::Completed);
+    assert_eq!(refresh.status, TaskStatus::Completed);
+    assert_eq!(queue.status(install).await.unwrap(), TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn dependent_tasks_wait_for_and_fail_with_their_dependencies() {
+    let queue = InMemoryAsyncTaskQueue::new();
+    let started = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let record = |label: &'static str, result: OrchestrationResult<()>| {
+        let started = started.clone();
+        operation(move |_| async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            started.lock().unwrap().push(label);
+            result
+        })
+    };
+    let failure = || CoreError {
+        manager: Some(ManagerId::Rustup),
+        task: Some(TaskType::Install),
+        action: None,
+        kind: CoreErrorKind::ProcessFailure,
+        message: "install failed".to_string(),
+    };
+
+    let rustup = queue
+        .spawn(
+            submission(ManagerId::Rustup, TaskType::Install),
+            record("rustup", Ok(())),
+        )
+        .await
+        .unwrap();
+    // A different manager, so only the dependency orders it.
+    let toolchain = queue
+        .spawn(
+            TaskSubmission {
+                depends_on: vec![rustup],
+                ..submission(ManagerId::Cargo, TaskType::Install)
+            },
+            record("toolchain", Ok(())),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        queue.snapshot(toolchain).await.unwrap().depends_on,
+        vec![rustup]
+    );
+    let toolchain = queue
+        .wait_for_terminal(toolchain, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert_eq!(toolchain.status, TaskStatus::Completed);
+    assert_eq!(*started.lock().unwrap(), vec!["rustup", "toolchain"]);
+
+    let failing = queue
+        .spawn(
+            submission(ManagerId::Pip, TaskType::Install),
+            record("failing", Err(failure())),
+        )
+        .await
+        .unwrap();
+    let dependent = queue
+        .spawn(
+            TaskSubmission {
+                depends_on: vec![failing],
+                ..submission(ManagerId::Pipx, TaskType::Install)
+            },
+            record("dependent", Ok(())),
+        )
+        .await
+        .unwrap();
+    let dependent = queue
+        .wait_for_terminal(dependent, Some(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert_eq!(dependent.status, TaskStatus::Failed);
+    assert_eq!(
+        dependent.error_message.as_deref(),
+        Some(format!("dependency task #{} failed", failing.0).as_str())
+    );
+    assert!(!started.lock().unwrap().contains(&"dependent"));
+
+    let unknown = queue
+        .spawn(
+            TaskSubmission {
+                depends_on: vec![helm_core::models::TaskId(9_999)],
+                ..submission(ManagerId::Pipx, TaskType::Install)
+            },
+            record("unknown", Ok(())),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(unknown.kind, CoreErrorKind::InvalidInput);
+}
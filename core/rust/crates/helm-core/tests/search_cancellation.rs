@@ -58,6 +58,7 @@ impl ManagerAdapter for SlowSearchAdapter {
             source_manager: ManagerId::Npm,
             originating_query: "slow".to_string(),
             cached_at: SystemTime::now(),
+            popularity: Default::default(),
         }];
         Ok(AdapterResponse::SearchResults(results))
     }
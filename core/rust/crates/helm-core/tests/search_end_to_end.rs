@@ -56,6 +56,7 @@ impl SearchAndRefreshAdapter {
                     source_manager: ManagerId::HomebrewFormula,
                     originating_query: "wget".to_string(),
                     cached_at: SystemTime::now(),
+                    popularity: Default::default(),
                 },
                 CachedSearchResult {
                     result: PackageCandidate {
@@ -70,6 +71,7 @@ impl SearchAndRefreshAdapter {
                     source_manager: ManagerId::HomebrewFormula,
                     originating_query: "wget".to_string(),
                     cached_at: SystemTime::now(),
+                    popularity: Default::default(),
                 },
             ],
             "rip" => vec![CachedSearchResult {
@@ -85,6 +87,7 @@ impl SearchAndRefreshAdapter {
                 source_manager: ManagerId::HomebrewFormula,
                 originating_query: "rip".to_string(),
                 cached_at: SystemTime::now(),
+                popularity: Default::default(),
             }],
             _ => vec![],
         }
@@ -119,6 +122,7 @@ impl ManagerAdapter for SearchAndRefreshAdapter {
                     installed_version: Some("1.24.5".to_string()),
                     pinned: false,
                     runtime_state: Default::default(),
+                    sizes: Default::default(),
                 }]))
             }
             _ => Ok(AdapterResponse::Refreshed),
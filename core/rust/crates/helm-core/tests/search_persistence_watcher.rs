@@ -57,6 +57,7 @@ impl ManagerAdapter for SearchTestAdapter {
                 source_manager: ManagerId::Npm,
                 originating_query: "rip".to_string(),
                 cached_at: SystemTime::now(),
+                popularity: Default::default(),
             },
             CachedSearchResult {
                 result: PackageCandidate {
@@ -71,6 +72,7 @@ impl ManagerAdapter for SearchTestAdapter {
                 source_manager: ManagerId::Npm,
                 originating_query: "rip".to_string(),
                 cached_at: SystemTime::now(),
+                popularity: Default::default(),
             },
         ];
         Ok(AdapterResponse::SearchResults(results))
@@ -134,5 +136,17 @@ async fn search_results_are_persisted_to_cache_via_watcher() {
     assert!(names.contains(&"ripgrep"));
     assert!(names.contains(&"rip-csv"));
 
+    let mut completions = Vec::new();
+    for _ in 0..30 {
+        completions = store.list_search_completions("rip").unwrap();
+        if !completions.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(completions.len(), 1, "expected a completion marker for npm");
+    assert_eq!(completions[0].manager, ManagerId::Npm);
+    assert_eq!(completions[0].result_count, 2);
+
     let _ = std::fs::remove_file(path);
 }
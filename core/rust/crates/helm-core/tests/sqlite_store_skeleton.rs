@@ -2,14 +2,20 @@ use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use helm_core::models::{
-    AutomationLevel, CachedSearchResult, CoreErrorKind, HomebrewKegPolicy,
-    InstallInstanceIdentityKind, InstallProvenance, InstalledPackage, ManagerId,
-    ManagerInstallInstance, NewTaskLogRecord, OutdatedPackage, PackageCandidate, PackageRef,
-    PinKind, PinRecord, StrategyKind, TaskId, TaskLogLevel, TaskRecord, TaskStatus, TaskType,
+    AutomationLevel, CachedSearchResult, CoreErrorKind, DetectionInfo, DetectionOutcome,
+    HomebrewKegPolicy, InstallInstanceIdentityKind, InstallProvenance, InstalledPackage,
+    InstalledPackageSize, ManagerId, ManagerInstallInstance, ManagerRefreshError,
+    ManagerRefreshResult, ManagerSelfVersion, NewTaskLogRecord, OnboardingChoices, OnboardingPhase,
+    OutdatedPackage, PackageAlias, PackageAliasKind, PackageCandidate, PackageDependency,
+    PackageMetadata, PackageRef, PackageSnapshotFreshness, PinKind, PinRecord, SearchCompletion,
+    SearchPopularity, StrategyKind, TaskHistoryFilter, TaskHistoryRecord, TaskId, TaskLabel,
+    TaskLogLevel, TaskRecord, TaskStatus, TaskType,
 };
 use helm_core::persistence::{
-    DetectionStore, MigrationStore, PackageStore, PinStore, SearchCacheStore, TaskStore,
+    DependencyStore, DetectionStore, ManagerExecutableCandidate, MigrationStore,
+    PackageMetadataStore, PackageStore, PinStore, SearchCacheStore, TaskStore,
 };
+use helm_core::snapshot_reconciliation::reconcile_package_snapshots;
 use helm_core::sqlite::{SqliteStore, current_schema_version};
 use rusqlite::params;
 
@@ -33,6 +39,413 @@ fn planned_migrations_include_versions_after_requested_version() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn checkpoint_empties_the_write_ahead_log() {
+    let path = test_db_path("checkpoint");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+    store.set_safe_mode(true).unwrap();
+
+    store.checkpoint().unwrap();
+
+    let wal_path = PathBuf::from(format!("{}-wal", path.display()));
+    let wal_len = std::fs::metadata(&wal_path)
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+    assert_eq!(wal_len, 0);
+    assert!(store.safe_mode().unwrap());
+
+    let _ = std::fs::remove_file(&wal_path);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn refresh_results_keep_only_the_latest_run() {
+    let path = test_db_path("refresh-results");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let result = |manager, run_started_unix: u64, error| ManagerRefreshResult {
+        manager,
+        error,
+        duration: Duration::from_millis(1_500),
+        run_started_at: UNIX_EPOCH + Duration::from_secs(run_started_unix),
+        finished_at: UNIX_EPOCH + Duration::from_secs(run_started_unix + 2),
+    };
+    store
+        .replace_refresh_results(&[
+            result(ManagerId::Npm, 1_000, None),
+            result(ManagerId::Cargo, 1_000, None),
+        ])
+        .unwrap();
+
+    let failure = ManagerRefreshError {
+        kind: CoreErrorKind::Timeout,
+        message: "npm outdated timed out".to_string(),
+    };
+    let latest = vec![result(ManagerId::Npm, 2_000, Some(failure))];
+    store.replace_refresh_results(&latest).unwrap();
+
+    assert_eq!(store.list_refresh_results().unwrap(), latest);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn repeated_listings_observe_writes_made_between_them() {
+    let path = test_db_path("read-connection-reuse");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+    assert!(store.list_refresh_results().unwrap().is_empty());
+
+    let results = vec![ManagerRefreshResult {
+        manager: ManagerId::Npm,
+        error: None,
+        duration: Duration::from_millis(200),
+        run_started_at: UNIX_EPOCH + Duration::from_secs(1_000),
+        finished_at: UNIX_EPOCH + Duration::from_secs(1_001),
+    }];
+    store.replace_refresh_results(&results).unwrap();
+
+    assert_eq!(store.list_refresh_results().unwrap(), results);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn listings_on_an_unmigrated_store_fail_without_writing() {
+    let path = test_db_path("read-connection-unmigrated");
+    let store = SqliteStore::new(&path);
+
+    assert!(store.list_installed().is_err());
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let table_count: i64 = connection
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(table_count, 0);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn detection_outcome_survives_detection_upserts() {
+    let path = test_db_path("detection-outcome");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    store
+        .upsert_detection(
+            ManagerId::Npm,
+            &DetectionInfo {
+                installed: true,
+                executable_path: Some(PathBuf::from("/opt/homebrew/bin/npm")),
+                version: Some("10.8.0".to_string()),
+            },
+        )
+        .unwrap();
+    let probe_failed = DetectionOutcome::probe_failed(CoreErrorKind::Timeout);
+    store
+        .set_detection_outcome(ManagerId::Npm, &probe_failed)
+        .unwrap();
+    // A manager that was never detected still gets a row for its outcome.
+    store
+        .set_detection_outcome(ManagerId::Pnpm, &DetectionOutcome::NotFound)
+        .unwrap();
+
+    assert_eq!(
+        store.list_detection_outcomes().unwrap(),
+        vec![
+            (ManagerId::Npm, probe_failed),
+            (ManagerId::Pnpm, DetectionOutcome::NotFound),
+        ]
+    );
+    let npm = store
+        .list_detections()
+        .unwrap()
+        .into_iter()
+        .find(|(manager, _)| *manager == ManagerId::Npm)
+        .map(|(_, info)| info)
+        .unwrap();
+    assert!(npm.installed);
+    assert_eq!(npm.version.as_deref(), Some("10.8.0"));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn executable_candidates_keep_discovery_order_per_manager() {
+    let path = test_db_path("executable-candidates");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let candidate = |manager, path: &str, version: Option<&str>| ManagerExecutableCandidate {
+        manager,
+        path: path.to_string(),
+        version: version.map(str::to_string),
+        last_validated_at: UNIX_EPOCH + Duration::from_secs(1_000),
+    };
+    let rustup = vec![
+        candidate(
+            ManagerId::Rustup,
+            "/Users/me/.cargo/bin/rustup",
+            Some("1.28.1"),
+        ),
+        candidate(ManagerId::Rustup, "/opt/homebrew/bin/rustup", None),
+    ];
+    store
+        .replace_manager_executable_candidates(ManagerId::Rustup, &rustup)
+        .unwrap();
+    store
+        .replace_manager_executable_candidates(
+            ManagerId::Npm,
+            &[candidate(ManagerId::Npm, "/opt/homebrew/bin/npm", None)],
+        )
+        .unwrap();
+
+    assert_eq!(
+        store
+            .list_manager_executable_candidates(Some(ManagerId::Rustup))
+            .unwrap(),
+        rustup
+    );
+
+    store
+        .replace_manager_executable_candidates(ManagerId::Rustup, &[])
+        .unwrap();
+    let remaining = store.list_manager_executable_candidates(None).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].manager, ManagerId::Npm);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn dependency_graph_replaces_only_the_reporting_manager() {
+    let path = test_db_path("dependency-graph");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let edge = |manager, package: &str, dependency: &str| PackageDependency {
+        package: PackageRef {
+            manager,
+            name: package.to_string(),
+        },
+        dependency: dependency.to_string(),
+    };
+    store
+        .replace_dependency_graph(
+            ManagerId::HomebrewFormula,
+            &[
+                edge(ManagerId::HomebrewFormula, "wget", "openssl@3"),
+                edge(ManagerId::HomebrewFormula, "git", "pcre2"),
+            ],
+        )
+        .unwrap();
+    store
+        .replace_dependency_graph(
+            ManagerId::MacPorts,
+            &[edge(ManagerId::MacPorts, "git", "curl")],
+        )
+        .unwrap();
+    store
+        .replace_dependency_graph(
+            ManagerId::HomebrewFormula,
+            &[
+                edge(ManagerId::HomebrewFormula, "wget", "openssl@3"),
+                edge(ManagerId::HomebrewFormula, "git", "gettext"),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(
+        store
+            .list_dependency_graph(ManagerId::HomebrewFormula)
+            .unwrap(),
+        vec![
+            edge(ManagerId::HomebrewFormula, "git", "gettext"),
+            edge(ManagerId::HomebrewFormula, "wget", "openssl@3"),
+        ]
+    );
+    assert_eq!(
+        store.list_dependency_graph(ManagerId::MacPorts).unwrap(),
+        vec![edge(ManagerId::MacPorts, "git", "curl")]
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn package_metadata_round_trips_and_replaces_earlier_fetches() {
+    let path = test_db_path("package-metadata");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let wget = PackageRef {
+        manager: ManagerId::HomebrewFormula,
+        name: "wget".to_string(),
+    };
+    assert_eq!(store.package_metadata(&wget).unwrap(), None);
+
+    let first = PackageMetadata {
+        description: Some("Internet file retriever".to_string()),
+        homepage: None,
+        license: Some("GPL-3.0-or-later".to_string()),
+        installed_size_bytes: None,
+        dependencies: vec!["libidn2".to_string()],
+        aliases: Vec::new(),
+    };
+    store
+        .upsert_package_metadata(&wget, &first, UNIX_EPOCH + Duration::from_secs(1_000))
+        .unwrap();
+    let second = PackageMetadata {
+        homepage: Some("https://www.gnu.org/software/wget/".to_string()),
+        installed_size_bytes: Some(4_096),
+        dependencies: vec!["libidn2".to_string(), "openssl@3".to_string()],
+        ..first
+    };
+    let fetched_at = UNIX_EPOCH + Duration::from_secs(2_000);
+    store
+        .upsert_package_metadata(&wget, &second, fetched_at)
+        .unwrap();
+
+    assert_eq!(
+        store.package_metadata(&wget).unwrap(),
+        Some((second, fetched_at))
+    );
+    assert_eq!(
+        store
+            .package_metadata(&PackageRef {
+                manager: ManagerId::MacPorts,
+                name: "wget".to_string(),
+            })
+            .unwrap(),
+        None
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn refresh_schedules_persist_and_follow_the_latest_refresh() {
+    use helm_core::orchestration::refresh_scheduler::{
+        record_scheduled_refresh, refresh_schedules, set_refresh_schedule,
+    };
+
+    let path = test_db_path("refresh-schedules");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+    assert!(refresh_schedules(&store).unwrap().is_empty());
+
+    let too_short = set_refresh_schedule(&store, ManagerId::Npm, 60).unwrap_err();
+    assert_eq!(too_short.kind, CoreErrorKind::InvalidInput);
+
+    set_refresh_schedule(&store, ManagerId::HomebrewFormula, 3_600).unwrap();
+    set_refresh_schedule(&store, ManagerId::SoftwareUpdate, 86_400).unwrap();
+    record_scheduled_refresh(&store, ManagerId::HomebrewFormula, 1_000).unwrap();
+    record_scheduled_refresh(&store, ManagerId::Npm, 1_000).unwrap();
+    store
+        .replace_refresh_results(&[ManagerRefreshResult {
+            manager: ManagerId::SoftwareUpdate,
+            error: None,
+            duration: Duration::from_secs(4),
+            run_started_at: UNIX_EPOCH + Duration::from_secs(5_000),
+            finished_at: UNIX_EPOCH + Duration::from_secs(5_004),
+        }])
+        .unwrap();
+
+    // Changing the interval keeps the last scheduled run.
+    set_refresh_schedule(&store, ManagerId::HomebrewFormula, 7_200).unwrap();
+
+    let schedules = refresh_schedules(&store).unwrap();
+    let summary: Vec<_> = schedules
+        .iter()
+        .map(|schedule| {
+            (
+                schedule.manager,
+                schedule.interval_secs,
+                schedule.last_refreshed_unix,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (ManagerId::HomebrewFormula, 7_200, Some(1_000)),
+            (ManagerId::SoftwareUpdate, 86_400, Some(5_004)),
+        ]
+    );
+
+    set_refresh_schedule(&store, ManagerId::HomebrewFormula, 0).unwrap();
+    set_refresh_schedule(&store, ManagerId::SoftwareUpdate, 0).unwrap();
+    assert!(refresh_schedules(&store).unwrap().is_empty());
+    assert_eq!(store.refresh_schedules_json().unwrap(), None);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn concurrent_refresh_outcomes_keep_every_managers_failure_streak() {
+    use std::sync::{Arc, Barrier};
+
+    use helm_core::models::{CoreError, ManagerAction};
+    use helm_core::orchestration::QUARANTINE_FAILURE_THRESHOLD;
+    use helm_core::orchestration::quarantine::{quarantined_managers, record_refresh_outcome};
+
+    let path = test_db_path("manager-failures-concurrent");
+    let store = Arc::new(SqliteStore::new(&path));
+    store.migrate_to_latest().unwrap();
+
+    let managers = [
+        ManagerId::Npm,
+        ManagerId::Pip,
+        ManagerId::Cargo,
+        ManagerId::RubyGems,
+    ];
+    for round in 1..=i64::from(QUARANTINE_FAILURE_THRESHOLD) {
+        let barrier = Arc::new(Barrier::new(managers.len()));
+        let threads: Vec<_> = managers
+            .into_iter()
+            .map(|manager| {
+                let store = store.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    let error = CoreError {
+                        manager: Some(manager),
+                        task: Some(TaskType::Refresh),
+                        action: Some(ManagerAction::ListInstalled),
+                        kind: CoreErrorKind::ProcessFailure,
+                        message: "exit status 1".to_string(),
+                    };
+                    barrier.wait();
+                    record_refresh_outcome(store.as_ref(), manager, Err(&error), round).unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    let quarantined: Vec<_> = quarantined_managers(store.as_ref())
+        .unwrap()
+        .into_iter()
+        .map(|quarantine| (quarantine.manager, quarantine.consecutive_failures))
+        .collect();
+    let mut expected: Vec<_> = managers
+        .into_iter()
+        .map(|manager| (manager, QUARANTINE_FAILURE_THRESHOLD))
+        .collect();
+    expected.sort_by_key(|(manager, _)| manager.as_str());
+    assert_eq!(quarantined, expected);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn applying_defined_migration_updates_persisted_schema_version() {
     let path = test_db_path("apply-and-reopen");
@@ -120,6 +533,79 @@ INSERT INTO installed_packages (
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn migration_normalizes_package_names_in_keyed_rows() {
+    let path = test_db_path("migrate-package-key-names");
+    let store = SqliteStore::new(&path);
+    store.apply_migration(37).unwrap();
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    for name in [" ripgrep ", "jq", "jq ", "\tfd\r\n", "jq\t"] {
+        connection
+            .execute(
+                "
+INSERT INTO pin_records (manager_id, package_name, pin_kind, pinned_version, created_at_unix)
+VALUES ('homebrew_formula', ?1, 'native', '', 1)
+",
+                params![name],
+            )
+            .unwrap();
+    }
+    for (name, policy) in [
+        ("python@3.12@@helm.cleanup", "cleanup"),
+        ("wget", "keep"),
+        ("wget@@helm.cleanup", "cleanup"),
+        ("\tcurl\n", "keep"),
+    ] {
+        connection
+            .execute(
+                "
+INSERT INTO package_keg_policies (manager_id, package_name, policy, updated_at_unix)
+VALUES ('homebrew_formula', ?1, ?2, 1)
+",
+                params![name, policy],
+            )
+            .unwrap();
+    }
+    drop(connection);
+
+    store.apply_migration(current_schema_version()).unwrap();
+
+    let mut pinned = store
+        .list_pins()
+        .unwrap()
+        .into_iter()
+        .map(|pin| pin.package.name)
+        .collect::<Vec<_>>();
+    pinned.sort();
+    assert_eq!(pinned, vec!["fd", "jq", "ripgrep"]);
+
+    let policies = store
+        .list_package_keg_policies()
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.package.name, entry.policy))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        policies,
+        vec![
+            ("curl".to_string(), HomebrewKegPolicy::Keep),
+            ("python@3.12".to_string(), HomebrewKegPolicy::Cleanup),
+            ("wget".to_string(), HomebrewKegPolicy::Keep),
+        ]
+    );
+
+    // Lookups normalize the same way, so untrimmed input still matches.
+    let ripgrep = PackageRef {
+        manager: ManagerId::HomebrewFormula,
+        name: "ripgrep\n".to_string(),
+    };
+    store.remove_pin(&ripgrep, None).unwrap();
+    assert_eq!(store.list_pins().unwrap().len(), 2);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn upsert_and_list_installed_roundtrip() {
     let path = test_db_path("installed-roundtrip");
@@ -136,6 +622,7 @@ fn upsert_and_list_installed_roundtrip() {
             installed_version: Some("2.45.1".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         },
         InstalledPackage {
             package: PackageRef {
@@ -146,6 +633,7 @@ fn upsert_and_list_installed_roundtrip() {
             installed_version: Some("5.5.2".to_string()),
             pinned: true,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         },
     ];
 
@@ -161,6 +649,72 @@ fn upsert_and_list_installed_roundtrip() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn listings_skip_rows_with_unknown_manager_ids() {
+    let path = test_db_path("unknown-manager-ids");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    store
+        .upsert_installed(&[InstalledPackage {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "git".to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some("2.45.0".to_string()),
+            pinned: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        }])
+        .unwrap();
+    store
+        .create_task(&TaskRecord {
+            id: TaskId(1),
+            manager: ManagerId::HomebrewFormula,
+            task_type: TaskType::Refresh,
+            status: TaskStatus::Completed,
+            created_at: UNIX_EPOCH + Duration::from_secs(100),
+            attempt_of: None,
+            label: None,
+        })
+        .unwrap();
+
+    // Rows a newer Helm wrote for a manager this build has never heard of.
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    connection
+        .execute(
+            "
+INSERT INTO installed_package_versions (
+    manager_id, package_name, package_identifier, installed_version, pinned, is_active,
+    is_default, has_override, updated_at_unix
+) VALUES ('winget', 'git', '', '2.45.0', 0, 0, 0, 0, 0)
+",
+            [],
+        )
+        .unwrap();
+    connection
+        .execute(
+            "
+INSERT INTO task_records (task_id, manager_id, task_type, status, created_at_unix)
+VALUES (2, 'winget', 'refresh', 'completed', 200)
+",
+            [],
+        )
+        .unwrap();
+
+    assert!(store.unknown_manager_ids().is_empty());
+    let installed = store.list_installed().unwrap();
+    assert_eq!(installed.len(), 1);
+    assert_eq!(installed[0].package.manager, ManagerId::HomebrewFormula);
+    let tasks = store.list_recent_tasks(10).unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, TaskId(1));
+    assert_eq!(store.unknown_manager_ids(), vec!["winget".to_string()]);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn upsert_and_list_installed_preserves_multiple_versions_per_package() {
     let path = test_db_path("installed-multi-version-roundtrip");
@@ -178,6 +732,7 @@ fn upsert_and_list_installed_preserves_multiple_versions_per_package() {
                 installed_version: Some("3.11.9".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
             InstalledPackage {
                 package: PackageRef {
@@ -188,6 +743,7 @@ fn upsert_and_list_installed_preserves_multiple_versions_per_package() {
                 installed_version: Some("3.12.3".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
         ])
         .unwrap();
@@ -201,7 +757,84 @@ fn upsert_and_list_installed_preserves_multiple_versions_per_package() {
         .filter_map(|entry| entry.installed_version.clone())
         .collect();
 
-    assert_eq!(versions, vec!["3.11.9".to_string(), "3.12.3".to_string()]);
+    assert_eq!(versions, vec!["3.11.9".to_string(), "3.12.3".to_string()]);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn read_snapshot_isolates_multi_table_reads_from_concurrent_writes() {
+    let path = test_db_path("read-snapshot-consistency");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+    store.set_manager_enabled(ManagerId::Npm, true).unwrap();
+
+    let (installed_count, npm_enabled) = store
+        .read_snapshot(|| {
+            let before = store.list_installed().unwrap().len();
+            let writer_path = path.clone();
+            std::thread::spawn(move || {
+                let writer = SqliteStore::new(writer_path);
+                writer.set_manager_enabled(ManagerId::Npm, false).unwrap();
+                writer
+                    .upsert_installed(&[InstalledPackage {
+                        package: PackageRef {
+                            manager: ManagerId::Npm,
+                            name: "typescript".to_string(),
+                        },
+                        package_identifier: None,
+                        installed_version: Some("5.8.3".to_string()),
+                        pinned: false,
+                        runtime_state: Default::default(),
+                        sizes: Default::default(),
+                    }])
+                    .unwrap();
+            })
+            .join()
+            .unwrap();
+            let npm_enabled = store
+                .list_manager_preferences()
+                .unwrap()
+                .into_iter()
+                .any(|preference| preference.manager == ManagerId::Npm && preference.enabled);
+            assert_eq!(store.list_installed().unwrap().len(), before);
+            (before, npm_enabled)
+        })
+        .unwrap();
+    assert_eq!(installed_count, 0);
+    assert!(npm_enabled);
+
+    assert_eq!(store.list_installed().unwrap().len(), 1);
+    assert!(
+        !store
+            .list_manager_preferences()
+            .unwrap()
+            .into_iter()
+            .any(|preference| preference.manager == ManagerId::Npm && preference.enabled)
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn read_snapshot_rejects_writes_inside_the_scope() {
+    let path = test_db_path("read-snapshot-query-only");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let write_result = store
+        .read_snapshot(|| store.set_manager_enabled(ManagerId::Npm, false))
+        .unwrap();
+    assert!(write_result.is_err());
+
+    store.set_manager_enabled(ManagerId::Npm, false).unwrap();
+    assert!(
+        store
+            .list_manager_preferences()
+            .unwrap()
+            .into_iter()
+            .any(|preference| preference.manager == ManagerId::Npm && !preference.enabled)
+    );
 
     let _ = std::fs::remove_file(path);
 }
@@ -222,6 +855,7 @@ fn replace_installed_snapshot_clears_stale_rows_for_manager() {
             installed_version: Some("5.8.3".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -240,6 +874,59 @@ fn replace_installed_snapshot_clears_stale_rows_for_manager() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn measured_sizes_survive_snapshot_rewrites_until_the_version_changes() {
+    let path = test_db_path("installed-measured-sizes");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let typescript = |version: &str| InstalledPackage {
+        package: PackageRef {
+            manager: ManagerId::Npm,
+            name: "typescript".to_string(),
+        },
+        package_identifier: None,
+        installed_version: Some(version.to_string()),
+        pinned: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    };
+    let installed_size = |store: &SqliteStore| {
+        store
+            .list_installed()
+            .unwrap()
+            .into_iter()
+            .find(|package| package.package.name == "typescript")
+            .and_then(|package| package.sizes.install_size_bytes)
+    };
+
+    store
+        .replace_installed_snapshot(ManagerId::Npm, &[typescript("5.8.3")])
+        .unwrap();
+    store
+        .set_installed_package_sizes(
+            ManagerId::Npm,
+            &[InstalledPackageSize {
+                package: typescript("5.8.3").package,
+                bytes: 23_068_672,
+            }],
+        )
+        .unwrap();
+    assert_eq!(installed_size(&store), Some(23_068_672));
+
+    store
+        .replace_installed_snapshot(ManagerId::Npm, &[typescript("5.8.3")])
+        .unwrap();
+    assert_eq!(installed_size(&store), Some(23_068_672));
+
+    store
+        .replace_installed_snapshot(ManagerId::Npm, &[typescript("5.9.2")])
+        .unwrap();
+    assert_eq!(installed_size(&store), None);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn upsert_and_list_outdated_roundtrip() {
     let path = test_db_path("outdated-roundtrip");
@@ -257,6 +944,7 @@ fn upsert_and_list_outdated_roundtrip() {
         pinned: false,
         restart_required: false,
         runtime_state: Default::default(),
+        sizes: Default::default(),
     }];
 
     store.upsert_outdated(&packages).unwrap();
@@ -287,6 +975,7 @@ fn replace_outdated_snapshot_clears_stale_rows_for_manager() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -313,6 +1002,7 @@ fn upsert_and_remove_pins_roundtrip() {
         },
         kind: PinKind::Native,
         pinned_version: Some("2.45.1".to_string()),
+        version_constraint: None,
         created_at: UNIX_EPOCH + Duration::from_secs(123),
     };
 
@@ -353,6 +1043,7 @@ fn remove_pin_is_version_scoped() {
                 package: package.clone(),
                 kind: PinKind::Virtual,
                 pinned_version: Some(version.to_string()),
+                version_constraint: None,
                 created_at: UNIX_EPOCH + Duration::from_secs(123),
             })
             .unwrap();
@@ -382,6 +1073,21 @@ fn safe_mode_defaults_false_and_roundtrips() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn offline_mode_defaults_false_and_roundtrips() {
+    let path = test_db_path("offline-mode-roundtrip");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    assert!(!store.offline_mode().unwrap());
+    store.set_offline_mode(true).unwrap();
+    assert!(store.offline_mode().unwrap());
+    store.set_offline_mode(false).unwrap();
+    assert!(!store.offline_mode().unwrap());
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn auto_check_settings_roundtrip() {
     let path = test_db_path("auto-check-settings-roundtrip");
@@ -440,6 +1146,58 @@ fn cli_onboarding_settings_roundtrip() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn complete_onboarding_writes_choices_and_shared_completion_flag() {
+    let path = test_db_path("complete-onboarding");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    assert_eq!(
+        store.onboarding_phase().unwrap(),
+        OnboardingPhase::NotStarted
+    );
+    store.set_onboarding_phase(OnboardingPhase::Review).unwrap();
+    assert_eq!(store.onboarding_phase().unwrap(), OnboardingPhase::Review);
+
+    let choices = OnboardingChoices {
+        manager_enabled: [(ManagerId::Npm, false), (ManagerId::Mise, true)]
+            .into_iter()
+            .collect(),
+        safe_mode: Some(true),
+        homebrew_keg_policy: Some(HomebrewKegPolicy::Cleanup),
+        auto_check_for_updates: None,
+        auto_check_frequency_minutes: Some(60),
+    };
+    store.complete_onboarding(&choices).unwrap();
+
+    assert_eq!(store.onboarding_phase().unwrap(), OnboardingPhase::Complete);
+    assert!(store.cli_onboarding_completed().unwrap());
+    assert!(store.safe_mode().unwrap());
+    assert_eq!(
+        store.homebrew_keg_policy().unwrap(),
+        HomebrewKegPolicy::Cleanup
+    );
+    assert!(!store.auto_check_for_updates().unwrap());
+    assert_eq!(store.auto_check_frequency_minutes().unwrap(), 60);
+    let preferences = store.list_manager_preferences().unwrap();
+    let enabled = |manager| {
+        preferences
+            .iter()
+            .find(|preference| preference.manager == manager)
+            .map(|preference| preference.enabled)
+    };
+    assert_eq!(enabled(ManagerId::Npm), Some(false));
+    assert_eq!(enabled(ManagerId::Mise), Some(true));
+
+    store.set_cli_onboarding_completed(false).unwrap();
+    assert_eq!(
+        store.onboarding_phase().unwrap(),
+        OnboardingPhase::NotStarted
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn homebrew_keg_policy_defaults_keep_and_roundtrips() {
     let path = test_db_path("keg-policy-roundtrip");
@@ -468,6 +1226,114 @@ fn homebrew_keg_policy_defaults_keep_and_roundtrips() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn homebrew_cask_exclude_auto_updates_defaults_true_and_roundtrips() {
+    let path = test_db_path("cask-auto-updates-roundtrip");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    assert!(store.homebrew_cask_exclude_auto_updates().unwrap());
+    store.set_homebrew_cask_exclude_auto_updates(false).unwrap();
+    assert!(!store.homebrew_cask_exclude_auto_updates().unwrap());
+    store.set_homebrew_cask_exclude_auto_updates(true).unwrap();
+    assert!(store.homebrew_cask_exclude_auto_updates().unwrap());
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn pipx_owned_pip_packages_are_hidden_unless_opted_out() {
+    let path = test_db_path("pipx-owned-pip-packages");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let installed = |manager: ManagerId, name: &str| InstalledPackage {
+        package: PackageRef {
+            manager,
+            name: name.to_string(),
+        },
+        package_identifier: None,
+        installed_version: Some("1.0.0".to_string()),
+        pinned: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    };
+    let outdated = |manager: ManagerId, name: &str| OutdatedPackage {
+        package: PackageRef {
+            manager,
+            name: name.to_string(),
+        },
+        package_identifier: None,
+        installed_version: Some("1.0.0".to_string()),
+        candidate_version: "2.0.0".to_string(),
+        pinned: false,
+        restart_required: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    };
+    let seed = || {
+        store
+            .replace_installed_snapshot(
+                ManagerId::Pip,
+                &[
+                    installed(ManagerId::Pip, "Black"),
+                    installed(ManagerId::Pip, "requests"),
+                ],
+            )
+            .unwrap();
+        store
+            .replace_outdated_snapshot(ManagerId::Pip, &[outdated(ManagerId::Pip, "Black")])
+            .unwrap();
+    };
+    let pip_names = || {
+        let installed = store
+            .list_installed()
+            .unwrap()
+            .into_iter()
+            .map(|package| package.package);
+        let outdated = store
+            .list_outdated()
+            .unwrap()
+            .into_iter()
+            .map(|package| package.package);
+        installed
+            .chain(outdated)
+            .filter(|package| package.manager == ManagerId::Pip)
+            .map(|package| package.name)
+            .collect::<Vec<_>>()
+    };
+
+    store
+        .replace_installed_snapshot(ManagerId::Pipx, &[installed(ManagerId::Pipx, "black")])
+        .unwrap();
+
+    store.set_hide_pipx_packages_from_pip(false).unwrap();
+    seed();
+    assert_eq!(
+        reconcile_package_snapshots(&store, Some(&store), ManagerId::Pip).unwrap(),
+        0
+    );
+    assert_eq!(pip_names(), vec!["Black", "requests", "Black"]);
+
+    store.set_hide_pipx_packages_from_pip(true).unwrap();
+    assert_eq!(
+        reconcile_package_snapshots(&store, Some(&store), ManagerId::Pipx).unwrap(),
+        2
+    );
+    assert_eq!(pip_names(), vec!["requests"]);
+    assert_eq!(
+        store
+            .list_installed()
+            .unwrap()
+            .iter()
+            .filter(|package| package.package.manager == ManagerId::Pipx)
+            .count(),
+        1
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn package_keg_policy_roundtrip_and_clear() {
     let path = test_db_path("package-keg-policy-roundtrip");
@@ -501,6 +1367,108 @@ fn package_keg_policy_roundtrip_and_clear() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn package_install_options_roundtrip_and_clear() {
+    let path = test_db_path("package-install-options-roundtrip");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let package = PackageRef {
+        manager: ManagerId::HomebrewFormula,
+        name: "neovim".to_string(),
+    };
+
+    assert!(store.package_install_options(&package).unwrap().is_empty());
+
+    let options = vec!["--HEAD".to_string(), "--build-from-source".to_string()];
+    store
+        .set_package_install_options(&package, &options)
+        .unwrap();
+    assert_eq!(store.package_install_options(&package).unwrap(), options);
+
+    store
+        .set_package_install_options(&package, &["--force-bottle".to_string()])
+        .unwrap();
+    assert_eq!(
+        store.package_install_options(&package).unwrap(),
+        vec!["--force-bottle".to_string()]
+    );
+
+    store.set_package_install_options(&package, &[]).unwrap();
+    assert!(store.package_install_options(&package).unwrap().is_empty());
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn manager_self_versions_roundtrip_and_overwrite() {
+    let path = test_db_path("manager-self-versions-roundtrip");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    assert!(store.list_manager_self_versions().unwrap().is_empty());
+
+    store
+        .set_manager_self_version(
+            ManagerId::Rustup,
+            &ManagerSelfVersion {
+                installed_version: Some("1.27.0".to_string()),
+                latest_version: Some("1.27.1".to_string()),
+            },
+        )
+        .unwrap();
+    store
+        .set_manager_self_version(
+            ManagerId::Rustup,
+            &ManagerSelfVersion {
+                installed_version: Some("1.27.1".to_string()),
+                latest_version: Some("1.27.1".to_string()),
+            },
+        )
+        .unwrap();
+
+    let versions = store.list_manager_self_versions().unwrap();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].0, ManagerId::Rustup);
+    assert_eq!(versions[0].1.installed_version.as_deref(), Some("1.27.1"));
+    assert!(!versions[0].1.update_available());
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn manager_env_settings_roundtrip_and_clear() {
+    let path = test_db_path("manager-env-settings-roundtrip");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    assert!(store.list_manager_env_settings().unwrap().is_empty());
+
+    store
+        .set_manager_env_setting(ManagerId::HomebrewFormula, "auto_update", Some("false"))
+        .unwrap();
+    store
+        .set_manager_env_setting(ManagerId::HomebrewFormula, "auto_update", Some("true"))
+        .unwrap();
+    store
+        .set_manager_env_setting(ManagerId::HomebrewCask, "color", Some("true"))
+        .unwrap();
+
+    let settings = store.list_manager_env_settings().unwrap();
+    assert_eq!(settings.len(), 2);
+    assert_eq!(settings[0].manager, ManagerId::HomebrewCask);
+    assert_eq!(settings[1].manager, ManagerId::HomebrewFormula);
+    assert_eq!(settings[1].key, "auto_update");
+    assert_eq!(settings[1].value, "true");
+
+    store
+        .set_manager_env_setting(ManagerId::HomebrewCask, "color", None)
+        .unwrap();
+    assert_eq!(store.list_manager_env_settings().unwrap().len(), 1);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn package_manager_preference_roundtrip_and_clear() {
     let path = test_db_path("package-manager-preference-roundtrip");
@@ -580,6 +1548,7 @@ fn list_installed_marks_package_pinned_when_pin_record_exists() {
             installed_version: Some("2.45.1".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -591,6 +1560,7 @@ fn list_installed_marks_package_pinned_when_pin_record_exists() {
             },
             kind: PinKind::Virtual,
             pinned_version: None,
+            version_constraint: None,
             created_at: UNIX_EPOCH + Duration::from_secs(500),
         })
         .unwrap();
@@ -620,6 +1590,7 @@ fn list_outdated_marks_package_pinned_when_pin_record_exists() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -631,6 +1602,7 @@ fn list_outdated_marks_package_pinned_when_pin_record_exists() {
             },
             kind: PinKind::Virtual,
             pinned_version: Some("16.1".to_string()),
+            version_constraint: None,
             created_at: UNIX_EPOCH + Duration::from_secs(501),
         })
         .unwrap();
@@ -642,6 +1614,63 @@ fn list_outdated_marks_package_pinned_when_pin_record_exists() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn list_outdated_holds_only_candidates_outside_pin_constraint() {
+    let path = test_db_path("outdated-pin-constraint-overlay");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let outdated = |name: &str, candidate: &str| OutdatedPackage {
+        package: PackageRef {
+            manager: ManagerId::Npm,
+            name: name.to_string(),
+        },
+        package_identifier: None,
+        installed_version: Some("1.2.0".to_string()),
+        candidate_version: candidate.to_string(),
+        pinned: false,
+        restart_required: false,
+        runtime_state: Default::default(),
+        sizes: Default::default(),
+    };
+    store
+        .upsert_outdated(&[outdated("vite", "1.4.0"), outdated("eslint", "2.0.0")])
+        .unwrap();
+
+    for name in ["vite", "eslint"] {
+        store
+            .upsert_pin(&PinRecord {
+                package: PackageRef {
+                    manager: ManagerId::Npm,
+                    name: name.to_string(),
+                },
+                kind: PinKind::Virtual,
+                pinned_version: None,
+                version_constraint: Some("^1.2".to_string()),
+                created_at: UNIX_EPOCH + Duration::from_secs(502),
+            })
+            .unwrap();
+    }
+
+    let pins = store.list_pins().unwrap();
+    assert!(
+        pins.iter()
+            .all(|pin| pin.version_constraint.as_deref() == Some("^1.2"))
+    );
+
+    let outdated = store.list_outdated().unwrap();
+    let pinned = |name: &str| {
+        outdated
+            .iter()
+            .find(|package| package.package.name == name)
+            .map(|package| package.pinned)
+    };
+    assert_eq!(pinned("vite"), Some(false));
+    assert_eq!(pinned("eslint"), Some(true));
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn list_installed_marks_only_matching_version_pinned() {
     let path = test_db_path("installed-version-pin-overlay");
@@ -660,6 +1689,7 @@ fn list_installed_marks_only_matching_version_pinned() {
                 installed_version: Some("3.12.3".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
             InstalledPackage {
                 package: package.clone(),
@@ -667,6 +1697,7 @@ fn list_installed_marks_only_matching_version_pinned() {
                 installed_version: Some("3.13.0".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
         ])
         .unwrap();
@@ -675,6 +1706,7 @@ fn list_installed_marks_only_matching_version_pinned() {
             package,
             kind: PinKind::Virtual,
             pinned_version: Some("3.12.3".to_string()),
+            version_constraint: None,
             created_at: UNIX_EPOCH + Duration::from_secs(777),
         })
         .unwrap();
@@ -709,6 +1741,7 @@ fn set_snapshot_pinned_updates_cached_rows_immediately() {
             installed_version: Some("1.11.4".to_string()),
             pinned: true,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
     store
@@ -720,6 +1753,7 @@ fn set_snapshot_pinned_updates_cached_rows_immediately() {
             pinned: true,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -755,6 +1789,7 @@ fn set_snapshot_pinned_only_updates_matching_version() {
                 installed_version: Some("3.12.3".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
             InstalledPackage {
                 package: package.clone(),
@@ -762,6 +1797,7 @@ fn set_snapshot_pinned_only_updates_matching_version() {
                 installed_version: Some("3.13.0".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
         ])
         .unwrap();
@@ -801,6 +1837,7 @@ fn apply_install_result_updates_installed_snapshot_and_clears_outdated_entry() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -839,6 +1876,7 @@ fn apply_uninstall_result_removes_package_from_cached_snapshots() {
             installed_version: Some("5.8.3".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
     store
@@ -850,6 +1888,7 @@ fn apply_uninstall_result_removes_package_from_cached_snapshots() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -881,6 +1920,7 @@ fn apply_uninstall_result_falls_back_to_package_wide_delete_for_single_version_m
             installed_version: Some("0.24.0".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -912,6 +1952,7 @@ fn apply_upgrade_result_promotes_package_to_installed_snapshot() {
             installed_version: Some("20250127.0".to_string()),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
     store
@@ -923,6 +1964,7 @@ fn apply_upgrade_result_promotes_package_to_installed_snapshot() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -933,12 +1975,60 @@ fn apply_upgrade_result_promotes_package_to_installed_snapshot() {
     let installed = store.list_installed().unwrap();
     let outdated = store.list_outdated().unwrap();
 
-    let upgraded = installed
-        .iter()
+    let upgraded = installed
+        .iter()
+        .find(|entry| entry.package == package)
+        .expect("upgraded package should remain installed");
+    assert_eq!(upgraded.installed_version.as_deref(), Some("20250814.0"));
+    assert!(outdated.iter().all(|entry| entry.package != package));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn apply_rollback_result_restores_the_version_replaced_by_the_last_upgrade() {
+    let path = test_db_path("apply-rollback-result");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let package = PackageRef {
+        manager: ManagerId::HomebrewFormula,
+        name: "git".to_string(),
+    };
+    store
+        .upsert_installed(&[InstalledPackage {
+            package: package.clone(),
+            package_identifier: None,
+            installed_version: Some("2.44.0".to_string()),
+            pinned: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        }])
+        .unwrap();
+    assert_eq!(store.previous_installed_version(&package).unwrap(), None);
+
+    store
+        .apply_upgrade_result(&package, None, Some("2.44.0"), Some("2.45.1"))
+        .unwrap();
+    assert_eq!(
+        store
+            .previous_installed_version(&package)
+            .unwrap()
+            .as_deref(),
+        Some("2.44.0")
+    );
+
+    store
+        .apply_rollback_result(&package, None, Some("2.44.0"))
+        .unwrap();
+    let restored = store
+        .list_installed()
+        .unwrap()
+        .into_iter()
         .find(|entry| entry.package == package)
-        .expect("upgraded package should remain installed");
-    assert_eq!(upgraded.installed_version.as_deref(), Some("20250814.0"));
-    assert!(outdated.iter().all(|entry| entry.package != package));
+        .expect("rolled back package should remain installed");
+    assert_eq!(restored.installed_version.as_deref(), Some("2.44.0"));
+    assert_eq!(store.previous_installed_version(&package).unwrap(), None);
 
     let _ = std::fs::remove_file(path);
 }
@@ -962,6 +2052,7 @@ fn apply_upgrade_result_replaces_only_matching_installed_version() {
                 installed_version: Some("3.11.9".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
             InstalledPackage {
                 package: package.clone(),
@@ -969,6 +2060,7 @@ fn apply_upgrade_result_replaces_only_matching_installed_version() {
                 installed_version: Some("3.12.3".to_string()),
                 pinned: false,
                 runtime_state: Default::default(),
+                sizes: Default::default(),
             },
         ])
         .unwrap();
@@ -981,6 +2073,7 @@ fn apply_upgrade_result_replaces_only_matching_installed_version() {
             pinned: false,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }])
         .unwrap();
 
@@ -1283,6 +2376,9 @@ fn manager_preferences_selection_fields_roundtrip() {
     store
         .set_manager_timeout_idle_seconds(ManagerId::Pip, Some(180))
         .unwrap();
+    store
+        .set_manager_process_qos(ManagerId::Pip, Some("background"))
+        .unwrap();
 
     let prefs = store.list_manager_preferences().unwrap();
     let pip_pref = prefs
@@ -1300,6 +2396,7 @@ fn manager_preferences_selection_fields_roundtrip() {
     );
     assert_eq!(pip_pref.timeout_hard_seconds, Some(600));
     assert_eq!(pip_pref.timeout_idle_seconds, Some(180));
+    assert_eq!(pip_pref.process_qos.as_deref(), Some("background"));
 
     store
         .set_manager_selected_executable_path(ManagerId::Pip, None)
@@ -1349,6 +2446,7 @@ fn upsert_and_query_search_cache_roundtrip() {
             source_manager: ManagerId::HomebrewFormula,
             originating_query: "rip".to_string(),
             cached_at: now,
+            popularity: Default::default(),
         },
         CachedSearchResult {
             result: PackageCandidate {
@@ -1363,6 +2461,7 @@ fn upsert_and_query_search_cache_roundtrip() {
             source_manager: ManagerId::Pnpm,
             originating_query: "type".to_string(),
             cached_at: now + Duration::from_secs(1),
+            popularity: Default::default(),
         },
     ];
 
@@ -1398,6 +2497,7 @@ fn search_cache_deduplicates_same_version_and_preserves_summary() {
         source_manager: ManagerId::HomebrewFormula,
         originating_query: "rip".to_string(),
         cached_at: now,
+        popularity: Default::default(),
     };
     store.upsert_search_results(&[first]).unwrap();
 
@@ -1414,6 +2514,7 @@ fn search_cache_deduplicates_same_version_and_preserves_summary() {
         source_manager: ManagerId::HomebrewFormula,
         originating_query: "rg".to_string(),
         cached_at: now + Duration::from_secs(5),
+        popularity: Default::default(),
     };
     store.upsert_search_results(&[second]).unwrap();
 
@@ -1455,6 +2556,7 @@ fn search_cache_retains_distinct_versions_for_same_package() {
         source_manager: ManagerId::HomebrewFormula,
         originating_query: "rip".to_string(),
         cached_at: now,
+        popularity: Default::default(),
     };
     let second = CachedSearchResult {
         result: PackageCandidate {
@@ -1469,6 +2571,7 @@ fn search_cache_retains_distinct_versions_for_same_package() {
         source_manager: ManagerId::HomebrewFormula,
         originating_query: "rip".to_string(),
         cached_at: now + Duration::from_secs(1),
+        popularity: Default::default(),
     };
 
     store.upsert_search_results(&[first, second]).unwrap();
@@ -1488,6 +2591,139 @@ fn search_cache_retains_distinct_versions_for_same_package() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn search_cache_keeps_last_known_popularity() {
+    let path = test_db_path("search-popularity");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let result = |popularity: SearchPopularity, offset_secs: u64| CachedSearchResult {
+        result: PackageCandidate {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "ripgrep".to_string(),
+            },
+            package_identifier: None,
+            version: Some("14.1.0".to_string()),
+            summary: None,
+        },
+        source_manager: ManagerId::HomebrewFormula,
+        originating_query: "rip".to_string(),
+        cached_at: UNIX_EPOCH + Duration::from_secs(1_000 + offset_secs),
+        popularity,
+    };
+
+    let ranked = SearchPopularity {
+        downloads: Some(12_345),
+        rank: Some(42),
+    };
+    store
+        .upsert_search_results(&[result(ranked.clone(), 0)])
+        .unwrap();
+    assert_eq!(store.query_local("rip", 10).unwrap()[0].popularity, ranked);
+
+    // A later search whose popularity lookup failed must not erase the counts.
+    store
+        .upsert_search_results(&[result(SearchPopularity::default(), 5)])
+        .unwrap();
+    assert_eq!(store.query_local("rip", 10).unwrap()[0].popularity, ranked);
+
+    let refreshed = SearchPopularity {
+        downloads: Some(20_000),
+        rank: None,
+    };
+    store
+        .upsert_search_results(&[result(refreshed, 10)])
+        .unwrap();
+    assert_eq!(
+        store.query_local("rip", 10).unwrap()[0].popularity,
+        SearchPopularity {
+            downloads: Some(20_000),
+            rank: Some(42),
+        }
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn package_snapshot_freshness_keeps_latest_task_per_manager() {
+    let path = test_db_path("package-snapshot-freshness");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let freshness = |manager, task_id, partial| PackageSnapshotFreshness {
+        manager,
+        task_id: TaskId(task_id),
+        partial,
+        installed_partial: false,
+        outdated_partial: partial,
+        updated_at: UNIX_EPOCH + Duration::from_secs(500 + task_id),
+    };
+    store
+        .record_package_snapshot_freshness(&freshness(ManagerId::Npm, 1, false))
+        .unwrap();
+    store
+        .record_package_snapshot_freshness(&freshness(ManagerId::Npm, 2, true))
+        .unwrap();
+    store
+        .record_package_snapshot_freshness(&freshness(ManagerId::Cargo, 3, false))
+        .unwrap();
+    store
+        .record_package_snapshot_freshness(&freshness(ManagerId::Npm, 1, false))
+        .unwrap();
+
+    assert_eq!(
+        store.list_package_snapshot_freshness().unwrap(),
+        vec![
+            freshness(ManagerId::Cargo, 3, false),
+            freshness(ManagerId::Npm, 2, true),
+        ]
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn search_completions_are_keyed_by_normalized_query_and_manager() {
+    let path = test_db_path("search-completions");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let completion = |query: &str, manager, result_count, secs| SearchCompletion {
+        query: query.to_string(),
+        manager,
+        result_count,
+        completed_at: UNIX_EPOCH + Duration::from_secs(secs),
+    };
+    store
+        .record_search_completion(&completion("RipGrep", ManagerId::Npm, 3, 100))
+        .unwrap();
+    store
+        .record_search_completion(&completion(" ripgrep ", ManagerId::Npm, 0, 200))
+        .unwrap();
+    store
+        .record_search_completion(&completion("ripgrep", ManagerId::Cargo, 0, 150))
+        .unwrap();
+    store
+        .record_search_completion(&completion("fd", ManagerId::Cargo, 1, 150))
+        .unwrap();
+
+    let completions = store.list_search_completions("RIPGREP").unwrap();
+    assert_eq!(completions.len(), 2);
+    assert_eq!(completions[0].manager, ManagerId::Cargo);
+    assert_eq!(completions[1].manager, ManagerId::Npm);
+    assert_eq!(completions[1].query, "ripgrep");
+    assert_eq!(completions[1].result_count, 0);
+    assert_eq!(
+        completions[1].completed_at,
+        UNIX_EPOCH + Duration::from_secs(200)
+    );
+    assert!(store.list_search_completions("rip").unwrap().is_empty());
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn create_update_and_list_recent_tasks_roundtrip() {
     let path = test_db_path("tasks-roundtrip");
@@ -1500,6 +2736,8 @@ fn create_update_and_list_recent_tasks_roundtrip() {
         task_type: TaskType::Refresh,
         status: TaskStatus::Queued,
         created_at: UNIX_EPOCH + Duration::from_secs(777),
+        attempt_of: None,
+        label: None,
     };
 
     store.create_task(&task).unwrap();
@@ -1516,6 +2754,155 @@ fn create_update_and_list_recent_tasks_roundtrip() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn task_labels_persist_across_store_handles_and_updates() {
+    let path = test_db_path("task-labels");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let label = TaskLabel {
+        key: "service.task.label.upgrade.package".to_string(),
+        args: std::collections::BTreeMap::from([
+            ("manager".to_string(), "npm".to_string()),
+            ("package".to_string(), "typescript".to_string()),
+        ]),
+    };
+    let unlabeled = TaskRecord {
+        id: TaskId(7),
+        manager: ManagerId::Npm,
+        task_type: TaskType::Upgrade,
+        status: TaskStatus::Queued,
+        created_at: UNIX_EPOCH + Duration::from_secs(100),
+        attempt_of: None,
+        label: None,
+    };
+    let mut labeled = TaskRecord {
+        id: TaskId(8),
+        label: Some(label.clone()),
+        ..unlabeled.clone()
+    };
+    store.create_task(&unlabeled).unwrap();
+    store.create_task(&labeled).unwrap();
+
+    labeled.status = TaskStatus::Running;
+    labeled.label = None;
+    store.update_task(&labeled).unwrap();
+
+    let reopened = SqliteStore::new(&path);
+    let listed = reopened.list_recent_tasks(10).unwrap();
+    assert_eq!(listed.len(), 2);
+    let label_of = |id: TaskId| {
+        listed
+            .iter()
+            .find(|task| task.id == id)
+            .and_then(|task| task.label.clone())
+    };
+    assert_eq!(label_of(TaskId(8)), Some(label));
+    assert_eq!(label_of(TaskId(7)), None);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn idempotency_keys_resolve_to_their_task_until_it_is_pruned() {
+    let path = test_db_path("task-idempotency-keys");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let task = |id: u64| TaskRecord {
+        id: TaskId(id),
+        manager: ManagerId::Npm,
+        task_type: TaskType::Upgrade,
+        status: TaskStatus::Queued,
+        created_at: UNIX_EPOCH + Duration::from_secs(id),
+        attempt_of: None,
+        label: None,
+    };
+    store.create_task(&task(1)).unwrap();
+    store.create_task(&task(2)).unwrap();
+
+    store
+        .set_task_idempotency_key(TaskId(1), "retry-key")
+        .unwrap();
+    assert!(
+        store
+            .set_task_idempotency_key(TaskId(2), "retry-key")
+            .is_err()
+    );
+    assert!(store.set_task_idempotency_key(TaskId(9), "other").is_err());
+    store
+        .set_task_idempotency_key(TaskId(1), "deduplicated-key")
+        .unwrap();
+    assert_eq!(
+        store.task_for_idempotency_key("deduplicated-key").unwrap(),
+        None
+    );
+
+    let mut running = task(1);
+    running.status = TaskStatus::Running;
+    store.update_task(&running).unwrap();
+    assert_eq!(
+        store.task_for_idempotency_key("retry-key").unwrap(),
+        Some(running)
+    );
+    assert_eq!(store.task_for_idempotency_key("unknown").unwrap(), None);
+
+    store.delete_task(TaskId(1)).unwrap();
+    assert_eq!(store.task_for_idempotency_key("retry-key").unwrap(), None);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn task_attempts_flatten_onto_first_attempt_and_survive_updates() {
+    let path = test_db_path("task-attempts");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let task = |id: u64| TaskRecord {
+        id: TaskId(id),
+        manager: ManagerId::Npm,
+        task_type: TaskType::Upgrade,
+        status: TaskStatus::Failed,
+        created_at: UNIX_EPOCH + Duration::from_secs(id),
+        attempt_of: None,
+        label: None,
+    };
+    for id in [1, 2, 3, 4] {
+        store.create_task(&task(id)).unwrap();
+    }
+
+    store.record_task_attempt(TaskId(2), TaskId(1)).unwrap();
+    store.record_task_attempt(TaskId(3), TaskId(2)).unwrap();
+    assert!(store.record_task_attempt(TaskId(1), TaskId(3)).is_err());
+    assert!(store.record_task_attempt(TaskId(9), TaskId(1)).is_err());
+
+    let mut retried = task(3);
+    retried.status = TaskStatus::Completed;
+    store.update_task(&retried).unwrap();
+
+    let attempts = store.list_task_attempts(TaskId(2)).unwrap();
+    assert_eq!(
+        attempts
+            .iter()
+            .map(|attempt| (attempt.id, attempt.attempt_of))
+            .collect::<Vec<_>>(),
+        vec![
+            (TaskId(1), None),
+            (TaskId(2), Some(TaskId(1))),
+            (TaskId(3), Some(TaskId(1))),
+        ]
+    );
+    assert_eq!(attempts[2].status, TaskStatus::Completed);
+    assert_eq!(store.list_task_attempts(TaskId(4)).unwrap(), vec![task(4)]);
+
+    // A chain retried under another task moves over as a whole.
+    store.record_task_attempt(TaskId(1), TaskId(4)).unwrap();
+    assert_eq!(store.list_task_attempts(TaskId(3)).unwrap().len(), 4);
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
     let path = test_db_path("tasks-prune-filter");
@@ -1530,6 +2917,8 @@ fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
             task_type: TaskType::Refresh,
             status: TaskStatus::Completed,
             created_at: old_created_at,
+            attempt_of: None,
+            label: None,
         },
         TaskRecord {
             id: TaskId(2),
@@ -1537,6 +2926,8 @@ fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
             task_type: TaskType::Refresh,
             status: TaskStatus::Failed,
             created_at: old_created_at,
+            attempt_of: None,
+            label: None,
         },
         TaskRecord {
             id: TaskId(3),
@@ -1544,6 +2935,8 @@ fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
             task_type: TaskType::Refresh,
             status: TaskStatus::Cancelled,
             created_at: old_created_at,
+            attempt_of: None,
+            label: None,
         },
         TaskRecord {
             id: TaskId(4),
@@ -1551,6 +2944,8 @@ fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
             task_type: TaskType::Refresh,
             status: TaskStatus::Running,
             created_at: old_created_at,
+            attempt_of: None,
+            label: None,
         },
     ];
 
@@ -1573,6 +2968,95 @@ fn prune_completed_tasks_removes_cancelled_and_keeps_running_records() {
     let _ = std::fs::remove_file(path);
 }
 
+#[test]
+fn task_history_outlives_pruned_tasks_and_pages_with_filters() {
+    let path = test_db_path("task-history");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let now = SystemTime::now();
+    let history = |id: u64, manager: ManagerId, status: TaskStatus, finished_at: SystemTime| {
+        TaskHistoryRecord {
+            task_id: TaskId(id),
+            manager,
+            task_type: TaskType::Upgrade,
+            status,
+            exit_code: Some(if status == TaskStatus::Failed { 1 } else { 0 }),
+            error_code: None,
+            error_message: None,
+            duration_ms: Some(1_250),
+            stdout_tail: Some(format!("task {id} output")),
+            stderr_tail: None,
+            finished_at,
+        }
+    };
+    store
+        .record_task_history(&history(
+            1,
+            ManagerId::HomebrewFormula,
+            TaskStatus::Completed,
+            UNIX_EPOCH + Duration::from_secs(5),
+        ))
+        .unwrap();
+    for id in 2..=5 {
+        let status = if id % 2 == 0 {
+            TaskStatus::Failed
+        } else {
+            TaskStatus::Completed
+        };
+        store
+            .record_task_history(&history(
+                id,
+                ManagerId::Npm,
+                status,
+                now - Duration::from_secs(60 * (10 - id)),
+            ))
+            .unwrap();
+    }
+
+    // Ids in history stay reserved even though no live task record exists.
+    assert_eq!(store.next_task_id().unwrap(), 6);
+
+    let all = TaskHistoryFilter::default();
+    assert_eq!(store.count_task_history(&all).unwrap(), 5);
+    let page = store.list_task_history(&all, 1, 2).unwrap();
+    assert_eq!(
+        page.iter().map(|entry| entry.task_id).collect::<Vec<_>>(),
+        vec![TaskId(4), TaskId(3)]
+    );
+    assert_eq!(page[0].duration_ms, Some(1_250));
+    assert_eq!(page[0].stdout_tail.as_deref(), Some("task 4 output"));
+
+    let failed_npm = TaskHistoryFilter {
+        manager: Some(ManagerId::Npm),
+        status: Some(TaskStatus::Failed),
+        ..TaskHistoryFilter::default()
+    };
+    assert_eq!(store.count_task_history(&failed_npm).unwrap(), 2);
+    assert!(
+        store
+            .list_task_history(&failed_npm, 0, 10)
+            .unwrap()
+            .iter()
+            .all(|entry| entry.exit_code == Some(1))
+    );
+
+    // The ancient entry expires by age, then the oldest survivor by count.
+    let deleted = store.prune_task_history(24 * 60 * 60, 3).unwrap();
+    assert_eq!(deleted, 2);
+    assert_eq!(
+        store
+            .list_task_history(&all, 0, 10)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.task_id)
+            .collect::<Vec<_>>(),
+        vec![TaskId(5), TaskId(4), TaskId(3)]
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
 #[test]
 fn delete_task_removes_record_and_logs() {
     let path = test_db_path("delete-task-with-logs");
@@ -1585,6 +3069,8 @@ fn delete_task_removes_record_and_logs() {
         task_type: TaskType::Upgrade,
         status: TaskStatus::Failed,
         created_at: UNIX_EPOCH + Duration::from_secs(1_000),
+        attempt_of: None,
+        label: None,
     };
     store.create_task(&task).unwrap();
     store
@@ -1618,6 +3104,8 @@ fn delete_tasks_for_manager_removes_only_matching_manager_rows() {
         task_type: TaskType::Upgrade,
         status: TaskStatus::Failed,
         created_at: UNIX_EPOCH + Duration::from_secs(1_100),
+        attempt_of: None,
+        label: None,
     };
     let npm_task = TaskRecord {
         id: TaskId(401),
@@ -1625,6 +3113,8 @@ fn delete_tasks_for_manager_removes_only_matching_manager_rows() {
         task_type: TaskType::Upgrade,
         status: TaskStatus::Failed,
         created_at: UNIX_EPOCH + Duration::from_secs(1_101),
+        attempt_of: None,
+        label: None,
     };
     store.create_task(&homebrew_task).unwrap();
     store.create_task(&npm_task).unwrap();
@@ -1682,6 +3172,8 @@ fn append_and_list_task_logs_roundtrip() {
         task_type: TaskType::Refresh,
         status: TaskStatus::Queued,
         created_at: UNIX_EPOCH + Duration::from_secs(900),
+        attempt_of: None,
+        label: None,
     };
     store.create_task(&task).unwrap();
 
@@ -1731,6 +3223,8 @@ fn prune_completed_tasks_removes_associated_task_logs() {
         task_type: TaskType::Refresh,
         status: TaskStatus::Completed,
         created_at: old_created_at,
+        attempt_of: None,
+        label: None,
     };
 
     store.create_task(&completed_task).unwrap();
@@ -1758,3 +3252,160 @@ fn prune_completed_tasks_removes_associated_task_logs() {
 
     let _ = std::fs::remove_file(path);
 }
+
+#[test]
+fn search_cache_ranks_exact_and_installed_matches_before_recent_substrings() {
+    let path = test_db_path("search-ranking");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let result = |manager: ManagerId, name: &str, offset_secs: u64| CachedSearchResult {
+        result: PackageCandidate {
+            package: PackageRef {
+                manager,
+                name: name.to_string(),
+            },
+            package_identifier: None,
+            version: Some("1.0.0".to_string()),
+            summary: None,
+        },
+        source_manager: manager,
+        originating_query: "rip".to_string(),
+        cached_at: UNIX_EPOCH + Duration::from_secs(1_000 + offset_secs),
+        popularity: SearchPopularity::default(),
+    };
+    store
+        .upsert_search_results(&[
+            result(ManagerId::HomebrewFormula, "ripgrep", 0),
+            result(ManagerId::Cargo, "ripgrep", 5),
+            result(ManagerId::Npm, "ripgrep-all", 10),
+            result(ManagerId::Npm, "vscode-ripgrep", 20),
+        ])
+        .unwrap();
+    store
+        .upsert_installed(&[InstalledPackage {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "ripgrep".to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some("1.0.0".to_string()),
+            pinned: false,
+            runtime_state: Default::default(),
+            sizes: Default::default(),
+        }])
+        .unwrap();
+
+    let ranked = store.query_local_ranked("ripgrep", 3).unwrap();
+    let order = ranked
+        .iter()
+        .map(|ranked| {
+            (
+                ranked.result.result.package.manager,
+                ranked.result.result.package.name.as_str(),
+                ranked.installed,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        order,
+        vec![
+            (ManagerId::HomebrewFormula, "ripgrep", true),
+            (ManagerId::Cargo, "ripgrep", false),
+            (ManagerId::Npm, "ripgrep-all", false),
+        ]
+    );
+    assert!(ranked[0].relevance > ranked[1].relevance);
+    assert!(ranked[1].relevance > ranked[2].relevance);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn cached_cask_aliases_resolve_app_names_and_match_search() {
+    let path = test_db_path("package-aliases");
+    let store = SqliteStore::new(&path);
+    store.migrate_to_latest().unwrap();
+
+    let vscode = PackageRef {
+        manager: ManagerId::HomebrewCask,
+        name: "visual-studio-code".to_string(),
+    };
+    let alias = |kind: PackageAliasKind, value: &str| PackageAlias {
+        kind,
+        value: value.to_string(),
+    };
+    let metadata = PackageMetadata {
+        description: Some("Open-source code editor".to_string()),
+        aliases: vec![
+            alias(
+                PackageAliasKind::DisplayName,
+                "Microsoft Visual Studio Code",
+            ),
+            alias(PackageAliasKind::AppBundle, "Visual Studio Code.app"),
+            alias(PackageAliasKind::BundleId, "com.microsoft.VSCode"),
+        ],
+        ..PackageMetadata::default()
+    };
+    store
+        .upsert_package_metadata(&vscode, &metadata, UNIX_EPOCH + Duration::from_secs(1_000))
+        .unwrap();
+    let (cached, _) = store.package_metadata(&vscode).unwrap().unwrap();
+    assert_eq!(cached.aliases.len(), 3);
+
+    let index = helm_core::package_aliases::PackageAliasIndex::from_aliases(
+        store.list_package_aliases().unwrap(),
+    );
+    assert_eq!(
+        index.resolve(ManagerId::HomebrewCask, "Visual Studio Code.app"),
+        Some("visual-studio-code")
+    );
+    assert_eq!(index.lookup("com.microsoft.vscode"), vec![vscode.clone()]);
+
+    store
+        .upsert_search_results(&[CachedSearchResult {
+            result: PackageCandidate {
+                package: vscode.clone(),
+                package_identifier: None,
+                version: Some("1.97.2".to_string()),
+                summary: Some("Open-source code editor".to_string()),
+            },
+            source_manager: ManagerId::HomebrewCask,
+            originating_query: "code".to_string(),
+            cached_at: UNIX_EPOCH + Duration::from_secs(1_000),
+            popularity: SearchPopularity::default(),
+        }])
+        .unwrap();
+    let ranked = store
+        .query_local_ranked("Microsoft Visual Studio Code", 5)
+        .unwrap();
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].result.result.package, vscode);
+    assert_eq!(
+        ranked[0].relevance,
+        helm_core::models::search::SEARCH_RELEVANCE_EXACT
+    );
+    assert!(
+        store
+            .query_local_ranked("com.microsoft", 5)
+            .unwrap()
+            .is_empty()
+    );
+
+    let refreshed = PackageMetadata {
+        aliases: vec![alias(PackageAliasKind::DisplayName, "Visual Studio Code")],
+        ..metadata
+    };
+    store
+        .upsert_package_metadata(&vscode, &refreshed, UNIX_EPOCH + Duration::from_secs(2_000))
+        .unwrap();
+    assert_eq!(
+        store.list_package_aliases().unwrap(),
+        vec![(
+            vscode,
+            alias(PackageAliasKind::DisplayName, "Visual Studio Code")
+        )]
+    );
+
+    let _ = std::fs::remove_file(path);
+}
@@ -6,8 +6,8 @@ use std::time::Duration;
 
 use helm_core::execution::timeout_prompt_store::{self, TimeoutPromptDecision};
 use helm_core::execution::{
-    CommandSpec, ProcessExitStatus, ProcessSpawnRequest, ProcessTerminationMode,
-    TokioProcessExecutor, spawn_validated,
+    CommandSpec, ProcessExitStatus, ProcessQos, ProcessSpawnRequest, ProcessTerminationMode,
+    TokioProcessExecutor, spawn_validated, task_output, task_progress,
 };
 use helm_core::models::{CoreErrorKind, ManagerAction, ManagerId, TaskId, TaskType};
 
@@ -54,6 +54,66 @@ async fn spawns_echo_and_captures_stdout() {
     assert!(output.started_at <= output.finished_at);
 }
 
+#[tokio::test]
+async fn lowered_qos_renices_the_child_and_records_it() {
+    let executor = TokioProcessExecutor;
+    let task_id = TaskId(9_880_001);
+    let request = ProcessSpawnRequest::new(
+        ManagerId::HomebrewFormula,
+        TaskType::Upgrade,
+        ManagerAction::Upgrade,
+        CommandSpec::new("/bin/sh").args(["-c", "ps -o ni= -p $$"]),
+    )
+    .task_id(task_id)
+    .qos(ProcessQos::Background);
+
+    let output = spawn_validated(&executor, request)
+        .expect("spawn should succeed")
+        .wait()
+        .await
+        .expect("wait should succeed");
+
+    assert_eq!(output.status, ProcessExitStatus::ExitCode(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "15");
+    assert_eq!(
+        task_output(task_id)
+            .and_then(|record| record.process_qos)
+            .as_deref(),
+        Some("background")
+    );
+}
+
+#[tokio::test]
+async fn progress_parser_reports_the_latest_streamed_percentage() {
+    let executor = TokioProcessExecutor;
+    let task_id = TaskId(9_880_002);
+    let request = ProcessSpawnRequest::new(
+        ManagerId::HomebrewFormula,
+        TaskType::Upgrade,
+        ManagerAction::Upgrade,
+        CommandSpec::new("/bin/sh").args(["-c", "printf '## 40.0%%\\r#### 80.5%%\\n==> done\\n'"]),
+    )
+    .task_id(task_id)
+    .progress_parser(|line| {
+        line.strip_prefix('#')?
+            .trim_start_matches('#')
+            .trim()
+            .strip_suffix('%')?
+            .parse::<f64>()
+            .ok()
+            .map(|value| value as u8)
+    });
+
+    let output = spawn_validated(&executor, request)
+        .expect("spawn should succeed")
+        .wait()
+        .await
+        .expect("wait should succeed");
+
+    assert_eq!(output.status, ProcessExitStatus::ExitCode(0));
+    assert_eq!(task_progress(task_id), Some(80));
+}
+
 #[tokio::test]
 async fn captures_nonzero_exit_code() {
     let executor = TokioProcessExecutor;
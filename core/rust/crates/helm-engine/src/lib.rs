@@ -0,0 +1,517 @@
+//! # Helm Engine
+//!
+//! Safe Rust facade over the Helm core for direct embedding. [`Engine`] owns the
+//! SQLite store and the adapter runtime, so Rust applications and integration
+//! tests can drive Helm without crossing the C boundary. The C FFI layer
+//! (`helm-ffi`) is a thin wrapper that routes its package list, search, task,
+//! mutation, cancellation, and refresh exports through these methods.
+//!
+//! ## Lifecycle
+//!
+//! - [`Engine::open`] migrates the database and registers every implemented
+//!   manager adapter (see [`default_adapters`]).
+//! - [`Engine::with_adapters`] accepts an explicit adapter list, which is how
+//!   tests substitute fake adapters.
+//! - [`Engine::with_runtime`] wraps a store and runtime the host also keeps,
+//!   built with [`adapter_runtime`].
+//!
+//! Snapshot reads (`list_*`, `search_local`) are synchronous local SQLite
+//! queries. Operations that run manager processes are `async` and must be
+//! awaited on a Tokio runtime; mutations return a [`TaskId`] that can be
+//! awaited with [`Engine::wait_for_task`] or cancelled with [`Engine::cancel`].
+//! [`Engine::info`] reports crate versions, schema version, and uptime for support.
+
+pub mod registry_clients;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use helm_core::adapters::asdf::AsdfAdapter;
+use helm_core::adapters::asdf_process::ProcessAsdfSource;
+use helm_core::adapters::bundler::BundlerAdapter;
+use helm_core::adapters::bundler_process::ProcessBundlerSource;
+use helm_core::adapters::cargo::CargoAdapter;
+use helm_core::adapters::cargo_binstall::CargoBinstallAdapter;
+use helm_core::adapters::cargo_binstall_process::ProcessCargoBinstallSource;
+use helm_core::adapters::cargo_process::ProcessCargoSource;
+use helm_core::adapters::colima::ColimaAdapter;
+use helm_core::adapters::colima_process::ProcessColimaSource;
+use helm_core::adapters::docker_desktop::DockerDesktopAdapter;
+use helm_core::adapters::docker_desktop_process::ProcessDockerDesktopSource;
+use helm_core::adapters::firmware_updates::FirmwareUpdatesAdapter;
+use helm_core::adapters::firmware_updates_process::ProcessFirmwareUpdatesSource;
+use helm_core::adapters::homebrew::HomebrewAdapter;
+use helm_core::adapters::homebrew_cask::HomebrewCaskAdapter;
+use helm_core::adapters::homebrew_cask_process::ProcessHomebrewCaskSource;
+use helm_core::adapters::homebrew_process::ProcessHomebrewSource;
+use helm_core::adapters::macports::MacPortsAdapter;
+use helm_core::adapters::macports_process::ProcessMacPortsSource;
+use helm_core::adapters::mas::MasAdapter;
+use helm_core::adapters::mas_process::ProcessMasSource;
+use helm_core::adapters::mise::MiseAdapter;
+use helm_core::adapters::mise_process::ProcessMiseSource;
+use helm_core::adapters::nix_darwin::NixDarwinAdapter;
+use helm_core::adapters::nix_darwin_process::ProcessNixDarwinSource;
+use helm_core::adapters::npm::NpmAdapter;
+use helm_core::adapters::npm_process::ProcessNpmSource;
+use helm_core::adapters::parallels_desktop::ParallelsDesktopAdapter;
+use helm_core::adapters::parallels_desktop_process::ProcessParallelsDesktopSource;
+use helm_core::adapters::pip::PipAdapter;
+use helm_core::adapters::pip_process::ProcessPipSource;
+use helm_core::adapters::pipx::PipxAdapter;
+use helm_core::adapters::pipx_process::ProcessPipxSource;
+use helm_core::adapters::pnpm::PnpmAdapter;
+use helm_core::adapters::pnpm_process::ProcessPnpmSource;
+use helm_core::adapters::podman::PodmanAdapter;
+use helm_core::adapters::podman_process::ProcessPodmanSource;
+use helm_core::adapters::poetry::PoetryAdapter;
+use helm_core::adapters::poetry_process::ProcessPoetrySource;
+use helm_core::adapters::rosetta2::Rosetta2Adapter;
+use helm_core::adapters::rosetta2_process::ProcessRosetta2Source;
+use helm_core::adapters::rubygems::RubyGemsAdapter;
+use helm_core::adapters::rubygems_process::ProcessRubyGemsSource;
+use helm_core::adapters::rustup::RustupAdapter;
+use helm_core::adapters::rustup_process::ProcessRustupSource;
+use helm_core::adapters::setapp::SetappAdapter;
+use helm_core::adapters::setapp_process::ProcessSetappSource;
+use helm_core::adapters::softwareupdate::SoftwareUpdateAdapter;
+use helm_core::adapters::softwareupdate_process::ProcessSoftwareUpdateSource;
+use helm_core::adapters::sparkle::SparkleAdapter;
+use helm_core::adapters::sparkle_process::ProcessSparkleSource;
+use helm_core::adapters::xcode_command_line_tools::XcodeCommandLineToolsAdapter;
+use helm_core::adapters::xcode_command_line_tools_process::ProcessXcodeCommandLineToolsSource;
+use helm_core::adapters::yarn::YarnAdapter;
+use helm_core::adapters::yarn_process::ProcessYarnSource;
+use helm_core::adapters::{
+    AdapterRequest, DetectRequest, InstallRequest, ManagerAdapter, UninstallRequest,
+    UpgradeRequest, UpgradeTarget,
+};
+use helm_core::execution::tokio_process::TokioProcessExecutor;
+use helm_core::execution::{
+    EnvironmentPreviewExecutor, ManagerEnvironment, ProcessExecutor, manager_environment,
+};
+use helm_core::models::{
+    CachedSearchResult, CoreError, InstalledPackage, ManagerId, OutdatedPackage, PackageRef,
+    RankedSearchResult, SearchCompletion, TaskId, TaskLabel, TaskLogRecord, TaskRecord,
+};
+use helm_core::orchestration::adapter_runtime::AdapterRuntime;
+use helm_core::orchestration::{
+    AdapterTaskSnapshot, CancellationMode, TaskViewOptions, build_visible_tasks,
+};
+use helm_core::persistence::{MigrationStore, PackageStore, SearchCacheStore, TaskStore};
+use helm_core::snapshot_cache::PersistenceListener;
+use helm_core::sqlite::SqliteStore;
+
+pub type EngineResult<T> = Result<T, CoreError>;
+
+/// Recent tasks read before shaping, so long-running in-flight tasks are not
+/// pushed out by newer terminal ones.
+const VISIBLE_TASK_FETCH_LIMIT: usize = 1_000;
+
+/// Every implemented manager adapter, backed by real process execution.
+///
+/// This is the registry used by [`Engine::open`] and by the FFI `helm_init`.
+pub fn default_adapters(
+    executor: Arc<dyn ProcessExecutor>,
+    store: Arc<SqliteStore>,
+) -> Vec<Arc<dyn ManagerAdapter>> {
+    vec![
+        Arc::new(HomebrewAdapter::new(ProcessHomebrewSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(HomebrewCaskAdapter::new(ProcessHomebrewCaskSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(MiseAdapter::new(ProcessMiseSource::new(executor.clone()))),
+        Arc::new(AsdfAdapter::new(ProcessAsdfSource::new(executor.clone()))),
+        Arc::new(NpmAdapter::new(ProcessNpmSource::new(executor.clone()))),
+        Arc::new(PnpmAdapter::new(ProcessPnpmSource::new(executor.clone()))),
+        Arc::new(YarnAdapter::new(ProcessYarnSource::new(executor.clone()))),
+        Arc::new(CargoAdapter::new(ProcessCargoSource::new(executor.clone()))),
+        Arc::new(CargoBinstallAdapter::new(ProcessCargoBinstallSource::new(
+            executor.clone(),
+            store,
+        ))),
+        Arc::new(PipAdapter::new(ProcessPipSource::new(executor.clone()))),
+        Arc::new(PipxAdapter::new(ProcessPipxSource::new(executor.clone()))),
+        Arc::new(PoetryAdapter::new(ProcessPoetrySource::new(
+            executor.clone(),
+        ))),
+        Arc::new(RubyGemsAdapter::new(ProcessRubyGemsSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(BundlerAdapter::new(ProcessBundlerSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(RustupAdapter::new(ProcessRustupSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(SoftwareUpdateAdapter::new(
+            ProcessSoftwareUpdateSource::new(executor.clone()),
+        )),
+        Arc::new(MacPortsAdapter::new(ProcessMacPortsSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(NixDarwinAdapter::new(ProcessNixDarwinSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(MasAdapter::new(ProcessMasSource::new(executor.clone()))),
+        Arc::new(DockerDesktopAdapter::new(ProcessDockerDesktopSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(PodmanAdapter::new(ProcessPodmanSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(ColimaAdapter::new(ProcessColimaSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(SparkleAdapter::new(ProcessSparkleSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(SetappAdapter::new(ProcessSetappSource::new(
+            executor.clone(),
+        ))),
+        Arc::new(ParallelsDesktopAdapter::new(
+            ProcessParallelsDesktopSource::new(executor.clone()),
+        )),
+        Arc::new(XcodeCommandLineToolsAdapter::new(
+            ProcessXcodeCommandLineToolsSource::new(executor.clone()),
+        )),
+        Arc::new(Rosetta2Adapter::new(ProcessRosetta2Source::new(
+            executor.clone(),
+        ))),
+        Arc::new(FirmwareUpdatesAdapter::new(
+            ProcessFirmwareUpdatesSource::new(executor),
+        )),
+    ]
+}
+
+/// Version of the `helm-engine` crate this build was compiled from.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Build and runtime facts about an [`Engine`], for About screens and support.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EngineInfo {
+    pub core_version: &'static str,
+    pub engine_version: &'static str,
+    /// Schema version recorded in the open database.
+    pub schema_version: i64,
+    /// Newest schema version this build knows how to migrate to.
+    pub latest_schema_version: i64,
+    pub database_path: PathBuf,
+    pub uptime: Duration,
+    /// Managers with a registered adapter, in registration order.
+    pub adapters: Vec<ManagerId>,
+    /// Manager ids in the database this build does not recognize, usually
+    /// written by a newer Helm. Their rows are left out of listings.
+    pub unknown_manager_ids: Vec<String>,
+}
+
+/// Adapters wired to a command-capturing executor, built once per engine for
+/// [`Engine::manager_environment`]. Previews hold the lock so concurrent
+/// callers never read each other's captured command.
+struct EnvironmentPreview {
+    executor: Arc<EnvironmentPreviewExecutor>,
+    adapters: Mutex<Vec<Arc<dyn ManagerAdapter>>>,
+}
+
+/// An embedded Helm instance: one SQLite store plus the adapter runtime that
+/// persists into it.
+#[derive(Clone)]
+pub struct Engine {
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+    environment_preview: Arc<OnceLock<EnvironmentPreview>>,
+    started_at: Instant,
+}
+
+impl Engine {
+    /// Open (and migrate) the database at `database_path` with all default adapters.
+    pub fn open(database_path: impl Into<PathBuf>) -> EngineResult<Self> {
+        let store = Arc::new(SqliteStore::new(database_path));
+        store.migrate_to_latest()?;
+        let adapters = default_adapters(Arc::new(TokioProcessExecutor), store.clone());
+        Self::from_parts(store, adapters)
+    }
+
+    /// Open (and migrate) the database at `database_path` with an explicit adapter list.
+    pub fn with_adapters(
+        database_path: impl Into<PathBuf>,
+        adapters: impl IntoIterator<Item = Arc<dyn ManagerAdapter>>,
+    ) -> EngineResult<Self> {
+        let store = Arc::new(SqliteStore::new(database_path));
+        store.migrate_to_latest()?;
+        Self::from_parts(store, adapters)
+    }
+
+    fn from_parts(
+        store: Arc<SqliteStore>,
+        adapters: impl IntoIterator<Item = Arc<dyn ManagerAdapter>>,
+    ) -> EngineResult<Self> {
+        let runtime = adapter_runtime(store.clone(), adapters)?;
+        Ok(Self::with_runtime(store, Arc::new(runtime)))
+    }
+
+    /// Wrap an already migrated store and a runtime built over it with [`adapter_runtime`].
+    ///
+    /// For hosts that share the store and runtime with their own code, such as the FFI.
+    pub fn with_runtime(store: Arc<SqliteStore>, runtime: Arc<AdapterRuntime>) -> Self {
+        Self {
+            store,
+            runtime,
+            environment_preview: Arc::new(OnceLock::new()),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Notify `listener` after the runtime persists package or detection data.
+    ///
+    /// Call before sharing the runtime; the engine swaps in a runtime carrying the listener.
+    pub fn with_persistence_listener(mut self, listener: Arc<dyn PersistenceListener>) -> Self {
+        self.runtime = Arc::new(
+            self.runtime
+                .as_ref()
+                .clone()
+                .with_persistence_listener(listener),
+        );
+        self
+    }
+
+    /// Versions, schema state, database location, uptime, and registered adapters.
+    pub fn info(&self) -> EngineResult<EngineInfo> {
+        Ok(EngineInfo {
+            core_version: helm_core::VERSION,
+            engine_version: VERSION,
+            schema_version: self.store.current_version()?,
+            latest_schema_version: helm_core::sqlite::current_schema_version(),
+            database_path: self.store.database_path().to_path_buf(),
+            uptime: self.started_at.elapsed(),
+            adapters: self
+                .runtime
+                .adapter_list()
+                .iter()
+                .map(|adapter| adapter.descriptor().id)
+                .collect(),
+            unknown_manager_ids: self.store.unknown_manager_ids(),
+        })
+    }
+
+    /// PATH, environment overrides, and resolved program Helm would use for
+    /// `manager`'s commands, taken from its detection probe without running it.
+    ///
+    /// Blocks while the manager's executable is located on disk; call off async workers.
+    pub fn manager_environment(&self, manager: ManagerId) -> ManagerEnvironment {
+        let preview = self.environment_preview.get_or_init(|| {
+            let executor = Arc::new(EnvironmentPreviewExecutor::default());
+            let adapters = default_adapters(executor.clone(), self.store.clone());
+            EnvironmentPreview {
+                executor,
+                adapters: Mutex::new(adapters),
+            }
+        });
+        let adapters = preview
+            .adapters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = preview.executor.take_captured();
+        if let Some(adapter) = adapters
+            .iter()
+            .find(|adapter| adapter.descriptor().id == manager)
+        {
+            let _ = adapter.execute(AdapterRequest::Detect(DetectRequest));
+        }
+        manager_environment(manager, preview.executor.take_captured())
+    }
+
+    /// Run `read` against one consistent view of the database, so the listings
+    /// it takes agree with each other.
+    pub fn read_snapshot<T>(&self, read: impl FnOnce() -> T) -> EngineResult<T> {
+        self.store.read_snapshot(read)
+    }
+
+    pub fn list_installed(&self) -> EngineResult<Vec<InstalledPackage>> {
+        self.store.list_installed()
+    }
+
+    pub fn list_outdated(&self) -> EngineResult<Vec<OutdatedPackage>> {
+        self.store.list_outdated()
+    }
+
+    /// Query the local search cache without contacting any manager.
+    pub fn search_local(&self, query: &str, limit: usize) -> EngineResult<Vec<CachedSearchResult>> {
+        self.store.query_local(query, limit)
+    }
+
+    /// [`Engine::search_local`] with each result's relevance ranking.
+    pub fn search_local_ranked(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> EngineResult<Vec<RankedSearchResult>> {
+        self.store.query_local_ranked(query, limit)
+    }
+
+    /// Managers that finished a remote search for `query`, including ones with no results.
+    pub fn search_completions(&self, query: &str) -> EngineResult<Vec<SearchCompletion>> {
+        self.store.list_search_completions(query)
+    }
+
+    pub fn list_tasks(&self, limit: usize) -> EngineResult<Vec<TaskRecord>> {
+        self.store.list_recent_tasks(limit)
+    }
+
+    /// Recent tasks shaped like the app's task list, per the `task_view_*` settings.
+    pub fn list_visible_tasks(&self) -> EngineResult<Vec<TaskRecord>> {
+        let options = TaskViewOptions::from_store(self.store.as_ref())?;
+        let tasks = self.store.list_recent_tasks(VISIBLE_TASK_FETCH_LIMIT)?;
+        Ok(build_visible_tasks(tasks, &options))
+    }
+
+    pub fn task_logs(&self, task_id: TaskId, limit: usize) -> EngineResult<Vec<TaskLogRecord>> {
+        self.store.list_task_logs(task_id, limit)
+    }
+
+    /// Run detection for every enabled manager in authority order.
+    pub async fn detect_all(&self) -> Vec<(ManagerId, EngineResult<()>)> {
+        self.runtime.detect_all_ordered().await
+    }
+
+    /// Refresh installed and outdated snapshots for every detected manager in authority order.
+    pub async fn refresh_all(&self) -> Vec<(ManagerId, EngineResult<()>)> {
+        self.runtime.refresh_all_ordered().await
+    }
+
+    /// Submit an arbitrary adapter request, returning the queued task id.
+    pub async fn submit(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+    ) -> EngineResult<TaskId> {
+        self.runtime.submit(manager, request).await
+    }
+
+    /// Submit a request whose task shows `label` instead of the generic task-type label.
+    pub async fn submit_labeled(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+        label: TaskLabel,
+    ) -> EngineResult<TaskId> {
+        self.runtime.submit_labeled(manager, request, label).await
+    }
+
+    /// [`Engine::submit_labeled`], starting only after every task in `depends_on` succeeds.
+    pub async fn submit_labeled_after(
+        &self,
+        manager: ManagerId,
+        request: AdapterRequest,
+        label: TaskLabel,
+        depends_on: Vec<TaskId>,
+    ) -> EngineResult<TaskId> {
+        self.runtime
+            .submit_labeled_after(manager, request, label, depends_on)
+            .await
+    }
+
+    pub async fn install(
+        &self,
+        manager: ManagerId,
+        package_name: &str,
+        version: Option<String>,
+        options: Vec<String>,
+    ) -> EngineResult<TaskId> {
+        self.submit(
+            manager,
+            AdapterRequest::Install(InstallRequest {
+                package: package_ref(manager, package_name),
+                target_name: None,
+                version,
+                options,
+            }),
+        )
+        .await
+    }
+
+    pub async fn uninstall(&self, manager: ManagerId, package_name: &str) -> EngineResult<TaskId> {
+        self.submit(
+            manager,
+            AdapterRequest::Uninstall(UninstallRequest {
+                package: package_ref(manager, package_name),
+                target_name: None,
+                version: None,
+                disposal: Default::default(),
+            }),
+        )
+        .await
+    }
+
+    /// Upgrade one package, or every outdated package of `manager` when `package_name` is `None`.
+    pub async fn upgrade(
+        &self,
+        manager: ManagerId,
+        package_name: Option<&str>,
+    ) -> EngineResult<TaskId> {
+        self.submit(
+            manager,
+            AdapterRequest::Upgrade(UpgradeRequest {
+                target: match package_name {
+                    Some(name) => UpgradeTarget::Package(package_ref(manager, name)),
+                    None => UpgradeTarget::All,
+                },
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            }),
+        )
+        .await
+    }
+
+    pub async fn cancel(&self, task_id: TaskId) -> EngineResult<()> {
+        self.cancel_with_mode(task_id, CancellationMode::Immediate)
+            .await
+    }
+
+    /// Cancel a task, giving a graceful `mode` time to let the process exit on its own.
+    pub async fn cancel_with_mode(
+        &self,
+        task_id: TaskId,
+        mode: CancellationMode,
+    ) -> EngineResult<()> {
+        self.runtime.cancel(task_id, mode).await
+    }
+
+    /// Wait until a task succeeds, fails, or is cancelled. `None` waits indefinitely.
+    pub async fn wait_for_task(
+        &self,
+        task_id: TaskId,
+        timeout: Option<Duration>,
+    ) -> EngineResult<AdapterTaskSnapshot> {
+        self.runtime.wait_for_terminal(task_id, timeout).await
+    }
+}
+
+/// The adapter runtime the engine builds over `store`, persisting into every
+/// store trait it implements.
+pub fn adapter_runtime(
+    store: Arc<SqliteStore>,
+    adapters: impl IntoIterator<Item = Arc<dyn ManagerAdapter>>,
+) -> EngineResult<AdapterRuntime> {
+    Ok(AdapterRuntime::with_all_stores(
+        adapters,
+        store.clone(),
+        store.clone(),
+        store.clone(),
+        store.clone(),
+    )?
+    .with_dependency_store(store.clone())
+    .with_pin_store(store))
+}
+
+fn package_ref(manager: ManagerId, package_name: &str) -> PackageRef {
+    PackageRef {
+        manager,
+        name: package_name.to_string(),
+    }
+}
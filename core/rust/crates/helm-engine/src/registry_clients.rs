@@ -0,0 +1,192 @@
+//! HTTPS clients for the package registries the core reads directly instead of
+//! spawning a manager process. The core only defines the client traits; every
+//! front end installs these implementations with [`install_registry_clients`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use helm_core::adapters::cargo_index::CratesIndexClient;
+use helm_core::adapters::npm_registry::{NpmRegistryClient, NpmRegistryResponse};
+use helm_core::adapters::pypi::PypiClient;
+use helm_core::search_popularity::SearchPopularityClient;
+
+const REGISTRY_CONNECT_TIMEOUT_SECS: u64 = 5;
+const CRATES_INDEX_TIMEOUT_SECS: u64 = 15;
+const NPM_REGISTRY_TIMEOUT_SECS: u64 = 15;
+const SEARCH_POPULARITY_TIMEOUT_SECS: u64 = 15;
+const PYPI_TIMEOUT_SECS: u64 = 15;
+
+fn registry_agent(timeout_secs: u64) -> ureq::AgentBuilder {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(REGISTRY_CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(timeout_secs))
+}
+
+/// crates.io sparse index reader for cargo outdated checks. The agent keeps
+/// connections alive across the per-crate lookups of one refresh.
+pub struct HttpsCratesIndexClient {
+    agent: ureq::Agent,
+}
+
+impl HttpsCratesIndexClient {
+    pub fn new() -> Self {
+        Self {
+            agent: registry_agent(CRATES_INDEX_TIMEOUT_SECS).build(),
+        }
+    }
+}
+
+impl Default for HttpsCratesIndexClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CratesIndexClient for HttpsCratesIndexClient {
+    fn fetch_index_file(&self, path: &str) -> Result<Option<String>, String> {
+        match self
+            .agent
+            .get(&format!("https://index.crates.io/{path}"))
+            .call()
+        {
+            Ok(response) => response
+                .into_string()
+                .map(Some)
+                .map_err(|error| error.to_string()),
+            Err(ureq::Error::Status(404 | 410 | 451, _)) => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+/// npm registry dist-tags reader for registry-based npm outdated checks.
+pub struct HttpsNpmRegistryClient {
+    agent: ureq::Agent,
+}
+
+impl HttpsNpmRegistryClient {
+    pub fn new() -> Self {
+        Self {
+            agent: registry_agent(NPM_REGISTRY_TIMEOUT_SECS).build(),
+        }
+    }
+}
+
+impl Default for HttpsNpmRegistryClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NpmRegistryClient for HttpsNpmRegistryClient {
+    fn fetch_dist_tags(
+        &self,
+        package_name: &str,
+        etag: Option<&str>,
+    ) -> Result<NpmRegistryResponse, String> {
+        let url = format!(
+            "https://registry.npmjs.org/-/package/{}/dist-tags",
+            package_name.replace('/', "%2f")
+        );
+        let mut request = self.agent.get(&url);
+        if let Some(etag) = etag {
+            request = request.set("If-None-Match", etag);
+        }
+        match request.call() {
+            Ok(response) if response.status() == 304 => Ok(NpmRegistryResponse::NotModified),
+            Ok(response) => {
+                let etag = response.header("ETag").map(str::to_string);
+                let body = response.into_string().map_err(|error| error.to_string())?;
+                Ok(NpmRegistryResponse::Modified { body, etag })
+            }
+            Err(ureq::Error::Status(304, _)) => Ok(NpmRegistryResponse::NotModified),
+            Err(ureq::Error::Status(404, _)) => Ok(NpmRegistryResponse::NotFound),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+/// PyPI JSON API reader for pipx remote search.
+pub struct HttpsPypiClient {
+    agent: ureq::Agent,
+}
+
+impl HttpsPypiClient {
+    pub fn new() -> Self {
+        Self {
+            agent: registry_agent(PYPI_TIMEOUT_SECS).build(),
+        }
+    }
+}
+
+impl Default for HttpsPypiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PypiClient for HttpsPypiClient {
+    fn fetch_project_json(&self, project: &str) -> Result<Option<String>, String> {
+        match self
+            .agent
+            .get(&format!("https://pypi.org/pypi/{project}/json"))
+            .call()
+        {
+            Ok(response) => response
+                .into_string()
+                .map(Some)
+                .map_err(|error| error.to_string()),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+/// Registry popularity reader used to annotate search results.
+pub struct HttpsSearchPopularityClient {
+    agent: ureq::Agent,
+}
+
+impl HttpsSearchPopularityClient {
+    pub fn new() -> Self {
+        Self {
+            // crates.io rejects API requests without an identifying user agent.
+            agent: registry_agent(SEARCH_POPULARITY_TIMEOUT_SECS)
+                .user_agent(concat!("Helm/", env!("CARGO_PKG_VERSION")))
+                .build(),
+        }
+    }
+}
+
+impl Default for HttpsSearchPopularityClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchPopularityClient for HttpsSearchPopularityClient {
+    fn fetch(&self, url: &str) -> Result<Option<String>, String> {
+        match self.agent.get(url).call() {
+            Ok(response) => response
+                .into_string()
+                .map(Some)
+                .map_err(|error| error.to_string()),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+/// Install the process-wide crates.io, npm, PyPI, and popularity clients.
+pub fn install_registry_clients() {
+    helm_core::adapters::cargo_index::install_crates_index_client(Arc::new(
+        HttpsCratesIndexClient::new(),
+    ));
+    helm_core::adapters::npm_registry::install_npm_registry_client(Arc::new(
+        HttpsNpmRegistryClient::new(),
+    ));
+    helm_core::adapters::pypi::install_pypi_client(Arc::new(HttpsPypiClient::new()));
+    helm_core::search_popularity::install_search_popularity_client(Arc::new(
+        HttpsSearchPopularityClient::new(),
+    ));
+}
@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use helm_core::adapters::{
+    AdapterRequest, AdapterResponse, AdapterResult, InstallRequest, ManagerAdapter, MutationResult,
+};
+use helm_core::models::{
+    ActionSafety, Capability, CoreErrorKind, ManagerAction, ManagerAuthority, ManagerCategory,
+    ManagerDescriptor, ManagerId, PackageRef, TaskLabel,
+};
+use helm_core::orchestration::AdapterTaskTerminalState;
+use helm_engine::Engine;
+
+const INSTALL_CAPABILITIES: &[Capability] = &[Capability::Install];
+
+fn test_db_path(test_name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("helm-{test_name}-{nanos}.sqlite3"))
+}
+
+struct InstallingAdapter {
+    descriptor: ManagerDescriptor,
+}
+
+impl InstallingAdapter {
+    fn new(manager: ManagerId) -> Self {
+        Self {
+            descriptor: ManagerDescriptor {
+                id: manager,
+                display_name: "installing-adapter",
+                category: ManagerCategory::Language,
+                authority: ManagerAuthority::Standard,
+                capabilities: INSTALL_CAPABILITIES,
+            },
+        }
+    }
+}
+
+impl ManagerAdapter for InstallingAdapter {
+    fn descriptor(&self) -> &ManagerDescriptor {
+        &self.descriptor
+    }
+
+    fn action_safety(&self, action: ManagerAction) -> ActionSafety {
+        action.safety()
+    }
+
+    fn execute(&self, request: AdapterRequest) -> AdapterResult<AdapterResponse> {
+        let AdapterRequest::Install(install) = request else {
+            unreachable!("only install is advertised");
+        };
+        Ok(AdapterResponse::Mutation(MutationResult {
+            package: install.package,
+            package_identifier: None,
+            action: ManagerAction::Install,
+            before_version: None,
+            after_version: install.version,
+            disposal: None,
+        }))
+    }
+}
+
+#[tokio::test]
+async fn engine_install_runs_task_and_persists_installed_snapshot() {
+    let path = test_db_path("engine-install");
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(InstallingAdapter::new(ManagerId::Npm));
+    let engine = Engine::with_adapters(&path, [adapter]).expect("engine should open");
+
+    let task_id = engine
+        .install(
+            ManagerId::Npm,
+            "eslint",
+            Some("9.25.0".to_string()),
+            Vec::new(),
+        )
+        .await
+        .expect("install should be queued");
+    let snapshot = engine
+        .wait_for_task(task_id, Some(Duration::from_secs(5)))
+        .await
+        .expect("install task should finish");
+    assert!(matches!(
+        snapshot.terminal_state,
+        Some(AdapterTaskTerminalState::Succeeded(_))
+    ));
+
+    let package = PackageRef {
+        manager: ManagerId::Npm,
+        name: "eslint".to_string(),
+    };
+    let mut installed_version = None;
+    for _ in 0..50 {
+        installed_version = engine
+            .list_installed()
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.package == package)
+            .and_then(|entry| entry.installed_version);
+        if installed_version.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(installed_version.as_deref(), Some("9.25.0"));
+    assert!(
+        engine
+            .list_tasks(10)
+            .unwrap()
+            .iter()
+            .any(|task| task.id == task_id)
+    );
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn engine_labeled_submit_records_the_label_on_the_task() {
+    let path = test_db_path("engine-labeled-submit");
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(InstallingAdapter::new(ManagerId::Npm));
+    let engine = Engine::with_adapters(&path, [adapter]).expect("engine should open");
+    let label = TaskLabel {
+        key: "service.task.label.install.package".to_string(),
+        args: [("package".to_string(), "eslint".to_string())]
+            .into_iter()
+            .collect(),
+    };
+
+    let task_id = engine
+        .submit_labeled(
+            ManagerId::Npm,
+            AdapterRequest::Install(InstallRequest {
+                package: PackageRef {
+                    manager: ManagerId::Npm,
+                    name: "eslint".to_string(),
+                },
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+            }),
+            label.clone(),
+        )
+        .await
+        .expect("install should be queued");
+    engine
+        .wait_for_task(task_id, Some(Duration::from_secs(5)))
+        .await
+        .expect("install task should finish");
+
+    let task = engine
+        .list_tasks(10)
+        .unwrap()
+        .into_iter()
+        .find(|task| task.id == task_id)
+        .expect("task should be listed");
+    assert_eq!(task.label, Some(label));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn engine_surfaces_errors_for_unregistered_or_unsupported_managers() {
+    let path = test_db_path("engine-unsupported");
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(InstallingAdapter::new(ManagerId::Npm));
+    let engine = Engine::with_adapters(&path, [adapter]).expect("engine should open");
+
+    let error = engine
+        .install(ManagerId::Cargo, "ripgrep", None, Vec::new())
+        .await
+        .expect_err("unregistered manager must be rejected");
+    assert_eq!(error.manager, Some(ManagerId::Cargo));
+
+    let task_id = engine
+        .uninstall(ManagerId::Npm, "eslint")
+        .await
+        .expect("uninstall should be queued");
+    let snapshot = engine
+        .wait_for_task(task_id, Some(Duration::from_secs(5)))
+        .await
+        .expect("uninstall task should finish");
+    let Some(AdapterTaskTerminalState::Failed(error)) = snapshot.terminal_state else {
+        panic!("uninstall capability is not advertised");
+    };
+    assert_eq!(error.kind, CoreErrorKind::UnsupportedCapability);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn engine_info_reports_versions_schema_and_adapters() {
+    let path = test_db_path("engine-info");
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(InstallingAdapter::new(ManagerId::Npm));
+    let engine = Engine::with_adapters(&path, [adapter]).expect("engine should open");
+
+    let info = engine.info().expect("info should load");
+    assert_eq!(info.core_version, helm_core::VERSION);
+    assert_eq!(info.engine_version, helm_engine::VERSION);
+    assert_eq!(info.schema_version, info.latest_schema_version);
+    assert_eq!(info.database_path, path);
+    assert_eq!(info.adapters, vec![ManagerId::Npm]);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn manager_environment_previews_stay_per_manager_across_calls() {
+    let path = test_db_path("manager-environment");
+    let adapter: Arc<dyn ManagerAdapter> = Arc::new(InstallingAdapter::new(ManagerId::Npm));
+    let engine = Engine::with_adapters(&path, [adapter]).expect("engine should open");
+
+    let npm = engine.manager_environment(ManagerId::Npm);
+    let pip = engine.manager_environment(ManagerId::Pip);
+    assert_eq!(npm.manager_id, ManagerId::Npm);
+    assert_eq!(pip.manager_id, ManagerId::Pip);
+    assert_eq!(engine.manager_environment(ManagerId::Npm), npm);
+    assert_eq!(engine.clone().manager_environment(ManagerId::Pip), pip);
+
+    let _ = std::fs::remove_file(path);
+}
@@ -15,10 +15,59 @@
  */
 bool helm_init(const char *db_path);
 
+/**
+ * Tear down the engine so the service can call `helm_init` again, for example
+ * after a memory-pressure restart. Returns true once the state is released,
+ * including when Helm was not initialized.
+ *
+ * In-flight adapter tasks are cancelled and given a few seconds to reach a
+ * terminal state, task persistence is quiesced while the SQLite write-ahead
+ * log is checkpointed, and the Tokio runtime is shut down before returning.
+ * Must not be called from a Helm runtime thread.
+ */
+bool helm_shutdown(void);
+
 char *helm_list_installed_packages(void);
 
 char *helm_list_outdated_packages(void);
 
+/**
+ * Word lists for shell completion as JSON: enabled manager ids, installed
+ * package names per manager, and task types. Served from the package
+ * snapshot cache, so it is cheap enough to call on every completion.
+ */
+char *helm_get_completion_data(void);
+
+/**
+ * Return installed packages as JSON, ordered by `sort`.
+ *
+ * `sort` is `"default"` (manager, then name) or `"size"` (largest reported
+ * size first, unsized packages last). Null selects the default order.
+ *
+ * # Safety
+ *
+ * `sort` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_list_installed_packages_sorted(const char *sort);
+
+/**
+ * Return outdated packages as JSON, ordered by `sort`.
+ *
+ * `sort` is `"default"` (manager, then name) or `"size"` (largest reported
+ * size first, unsized packages last). Null selects the default order.
+ *
+ * # Safety
+ *
+ * `sort` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_list_outdated_packages_sorted(const char *sort);
+
+/**
+ * Return outdated packages that upgrades skip as JSON, each annotated with
+ * the reason it is held back (native pin, virtual pin, disabled manager).
+ */
+char *helm_list_held_back_packages(void);
+
 /**
  * Return rustup toolchain-scoped component and target detail as JSON.
  *
@@ -28,6 +77,92 @@ char *helm_list_outdated_packages(void);
  */
 char *helm_get_rustup_toolchain_detail(const char *toolchain);
 
+/**
+ * List the files an installed package owns as JSON, for managers with the
+ * `ListFiles` capability (Homebrew formulae, MacPorts, pip, npm).
+ *
+ * At most `limit` paths are returned (`0` returns all of them); `total`
+ * always reports the full count.
+ *
+ * # Safety
+ *
+ * `manager_id` and `package_name` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+char *helm_list_package_files(const char *manager_id, const char *package_name, int64_t limit);
+
+/**
+ * Report a package's installed dependencies and dependents as JSON
+ * `{managerId, packageName, dependencies, dependents, orphanedDependencies}`,
+ * for managers with the `ListDependencies` capability (Homebrew formulae).
+ *
+ * The graph is captured during refresh; if none is stored yet it is captured
+ * now. `dependents` break if the package is uninstalled, and
+ * `orphanedDependencies` are no longer needed by anything else.
+ *
+ * # Safety
+ *
+ * `manager_id` and `package_name` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+char *helm_list_package_dependencies(const char *manager_id, const char *package_name);
+
+/**
+ * Report a package's metadata as JSON `{managerId, packageName,
+ * description, homepage, license, installedSizeBytes, dependencies,
+ * aliases, fetchedAtUnix, stale}`, for managers with the `PackageInfo`
+ * capability (Homebrew formulae and casks, npm, pip, Cargo). `aliases` lists
+ * `{kind, value}` pairs such as a cask's display names, app bundles, and
+ * bundle ids.
+ *
+ * Results are cached in the `package_metadata` table for a day. If the
+ * manager fails and an expired entry exists, it is returned with `stale`.
+ *
+ * # Safety
+ *
+ * `manager_id` and `package_name` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+char *helm_get_package_info(const char *manager_id, const char *package_name);
+
+/**
+ * Map an app name, display name, or bundle id to the packages it refers
+ * to, as a JSON array of `{managerId, packageName}`.
+ *
+ * Matches package names and the aliases cached by `helm_get_package_info`
+ * after folding case, spacing, punctuation, and a trailing `.app`, so
+ * `Visual Studio Code.app` finds the `visual-studio-code` cask. An alias
+ * shared by two packages of one manager matches neither.
+ *
+ * # Safety
+ *
+ * `name` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_resolve_package_alias(const char *name);
+
+/**
+ * List installed packages that were only ever installed as dependencies and
+ * that no requested package still needs, as a JSON array of
+ * `{managerId, packageName, pinned}` in uninstall order.
+ *
+ * Covers managers with the `ListRequested` capability (Homebrew formulae).
+ */
+char *helm_list_orphaned_packages(void);
+
+/**
+ * Queue uninstall tasks for every unpinned orphan reported by
+ * [`helm_list_orphaned_packages`]. Returns a JSON array of
+ * `{managerId, packageName, taskId}` for the queued tasks; pinned orphans
+ * and managers that cannot uninstall individual packages are skipped.
+ *
+ * Tasks are queued dependents first, so each uninstall finds nothing left
+ * that needs its package.
+ */
+char *helm_cleanup_orphans(void);
+
+/**
+ * Tasks for the task list, shaped by the `task_view_*` settings.
+ */
 char *helm_list_tasks(void);
 
 /**
@@ -37,6 +172,30 @@ char *helm_list_tasks(void);
  */
 char *helm_get_task_output(int64_t task_id);
 
+/**
+ * Return live output chunks for a task newer than `offset` as JSON.
+ *
+ * Pass `0` first, then the returned `nextOffset`, to tail a running task.
+ * `missedChunks` reports that older chunks were dropped (the full tail stays
+ * available from `helm_get_task_output`); `finished` means no more chunks
+ * will arrive.
+ */
+char *helm_get_task_output_since(int64_t task_id, int64_t offset);
+
+/**
+ * Return captured stdout/stderr for several task IDs as a JSON array.
+ *
+ * `task_ids_json` is a JSON array of task IDs. Results follow request order
+ * (duplicates removed) and each stream is cut to its trailing
+ * `TASK_OUTPUT_BATCH_MAX_STREAM_BYTES`, flagged via `stdoutTruncated` /
+ * `stderrTruncated`.
+ *
+ * # Safety
+ *
+ * `task_ids_json` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_get_task_outputs(const char *task_ids_json);
+
 /**
  * Return persisted lifecycle task logs for a task ID as JSON.
  *
@@ -44,6 +203,19 @@ char *helm_get_task_output(int64_t task_id);
  */
 char *helm_list_task_logs(int64_t task_id, int64_t limit);
 
+/**
+ * Return one page of archived terminal tasks as `{"total", "entries"}` JSON,
+ * most recently finished first.
+ *
+ * `filter_json` may be null or an object with optional `manager`, `task_type`,
+ * and `status` fields. `limit` is capped at `TASK_HISTORY_PAGE_MAX_LIMIT`.
+ *
+ * # Safety
+ *
+ * `filter_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_list_task_history(int64_t offset, int64_t limit, const char *filter_json);
+
 /**
  * List pending hard-timeout prompts for running tasks as JSON.
  */
@@ -62,16 +234,20 @@ bool helm_trigger_refresh(void);
 bool helm_trigger_detection(void);
 
 /**
- * Trigger detection for a single manager.
+ * Trigger detection for a single manager. Returns the detection task ID, or
+ * -1 on error. When a detection for the manager is already in flight, its
+ * task ID is returned instead of queuing another probe.
  *
  * # Safety
  *
  * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
  */
-bool helm_trigger_detection_for_manager(const char *manager_id);
+int64_t helm_trigger_detection_for_manager(const char *manager_id);
 
 /**
- * Query the local search cache synchronously and return JSON results.
+ * Query the local search cache synchronously and return JSON
+ * `{results, completions}`, where `completions` lists the managers that
+ * finished a remote search for this query (including ones with no results).
  *
  * # Safety
  *
@@ -80,7 +256,11 @@ bool helm_trigger_detection_for_manager(const char *manager_id);
 char *helm_search_local(const char *query);
 
 /**
- * Submit a remote search request for the given query. Returns the task ID, or -1 on error.
+ * Submit a remote search request for the given query. Returns the first task ID,
+ * -2 when every target manager already completed this query recently, or -1 on error.
+ *
+ * Deprecated: the other managers' task IDs are dropped. Use
+ * `helm_trigger_remote_search_v2` to track every manager's search.
  *
  * # Safety
  *
@@ -89,7 +269,19 @@ char *helm_search_local(const char *query);
 int64_t helm_trigger_remote_search(const char *query);
 
 /**
- * Submit a remote search request for a specific manager. Returns the task ID, or -1 on error.
+ * Submit a remote search request for the given query on every targeted manager.
+ * Returns a JSON array of `{managerId, taskId, errorKey, skippedFresh}` entries,
+ * one per manager, or null on invalid input.
+ *
+ * # Safety
+ *
+ * `query` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_trigger_remote_search_v2(const char *query);
+
+/**
+ * Submit a remote search request for a specific manager. Returns the task ID,
+ * -2 when the manager already completed this query recently, or -1 on error.
  *
  * # Safety
  *
@@ -102,11 +294,36 @@ int64_t helm_trigger_remote_search_for_manager(const char *manager_id, const cha
  */
 bool helm_cancel_task(int64_t task_id);
 
+/**
+ * Block until a task is terminal, then return its outcome as JSON: status,
+ * core error detail when it failed, duration, exit code, and the trailing
+ * `TASK_OUTPUT_BATCH_MAX_STREAM_BYTES` of each output stream.
+ *
+ * - `timeout_ms`: longest wait; `0` waits without limit. When it elapses the
+ *   result has `timedOut: true` and the task's current status.
+ *
+ * Returns null for a negative argument or a task unknown to this service.
+ */
+char *helm_wait_for_task(int64_t task_id, int64_t timeout_ms);
+
 /**
  * Dismiss a terminal task by ID. Returns true on success.
  */
 bool helm_dismiss_task(int64_t task_id);
 
+/**
+ * Record that `task_id` retries `attempt_of`. Chains are flattened onto the
+ * first attempt, so linking a retry of a retry keeps one history. Returns
+ * true on success.
+ */
+bool helm_record_task_attempt(int64_t task_id, int64_t attempt_of);
+
+/**
+ * List every retained attempt in `task_id`'s retry chain as JSON, oldest
+ * first, each numbered so the UI can show "attempt 2 of 3".
+ */
+char *helm_list_task_attempts(int64_t task_id);
+
 /**
  * List manager status: detection info + preferences + implementation status as JSON.
  */
@@ -124,6 +341,174 @@ char *helm_list_manager_status(void);
  */
 char *helm_doctor_scan(void);
 
+/**
+ * Read one setting from the typed settings registry.
+ *
+ * Returns the JSON-encoded value (`true`, `1440`, `"v1"`, or `null` for unset optional
+ * settings), or null on error.
+ *
+ * # Safety
+ *
+ * `key` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_get_setting(const char *key);
+
+/**
+ * Validate and persist one setting from a JSON value. Returns true on success.
+ *
+ * Unknown keys and values of the wrong kind fail with `service.error.invalid_input`.
+ * Successful changes are published to `helm_list_setting_changes`.
+ *
+ * # Safety
+ *
+ * `key` and `json_value` must be valid, non-null pointers to NUL-terminated UTF-8 C strings.
+ */
+bool helm_set_setting(const char *key, const char *json_value);
+
+/**
+ * List every registry setting with its kind, current value, and default as JSON.
+ */
+char *helm_list_settings(void);
+
+/**
+ * List setting changes published after `after_sequence` as JSON, oldest first.
+ *
+ * Pass 0 to read all retained changes; callers poll with the last `sequence` they saw.
+ */
+char *helm_list_setting_changes(int64_t after_sequence);
+
+/**
+ * List upcoming scheduled background runs (kind, manager, ETA) as JSON, soonest first.
+ *
+ * Hosts use this to keep the service alive until the next due run. ETAs are
+ * wall-clock unix seconds; runs whose due time already passed report `overdue`.
+ */
+char *helm_get_next_scheduled_runs(void);
+
+/**
+ * Refresh a manager in the background every `interval_secs` seconds (at least
+ * 300); `0` removes its schedule. Returns true on success.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_set_refresh_schedule(const char *manager_id, int64_t interval_secs);
+
+/**
+ * List per-manager background refresh schedules as JSON, ordered by manager id.
+ *
+ * `nextRunUnix` is wall-clock unix seconds; schedules whose run is already
+ * due report `overdue`.
+ */
+char *helm_get_refresh_schedules(void);
+
+/**
+ * Lift a manager's refresh-failure quarantine and refresh it now in the
+ * background. Managers that are not quarantined are refreshed as well.
+ * Returns true once the refresh is queued.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_retry_quarantined_manager(const char *manager_id);
+
+/**
+ * Hint that the host just woke from system sleep.
+ *
+ * Background tickers re-evaluate due schedules on their next poll instead of
+ * waiting out a tick interval that may have stalled during sleep. Always returns true.
+ */
+bool helm_notify_wake(void);
+
+/**
+ * Tell the core which manager the host is showing, so refreshes start with
+ * it and defer the rest until it finishes. Returns true on success.
+ *
+ * `json_hint` is an object with an optional `manager` id; `{}` clears the
+ * focus.
+ *
+ * # Safety
+ *
+ * `json_hint` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_set_view_hint(const char *json_hint);
+
+/**
+ * Tell the core whether the network is metered, so refreshes skip
+ * bandwidth-heavy work (Homebrew's auto-update, softwareupdate scans, and
+ * search catalog prefetch) unless the `allow_heavy_network_when_metered`
+ * setting is on. Returns true on success.
+ *
+ * `json_hint` is an object with optional `expensive` (cellular or personal
+ * hotspot) and `constrained` (Low Data Mode) booleans; `{}` clears both.
+ *
+ * # Safety
+ *
+ * `json_hint` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_set_network_hint(const char *json_hint);
+
+/**
+ * Report build and runtime facts for the About screen and support as JSON:
+ * crate versions, database schema version, database path, uptime, the
+ * compiled-in adapter list, and any unrecognized manager ids (written by a
+ * newer Helm) whose rows listings are skipping.
+ */
+char *helm_get_engine_info(void);
+
+/**
+ * Report the outcome of the latest refresh-all run as JSON: success and
+ * failure counts, plus per-manager results with the failure's service error
+ * key, code, and message and the refresh duration. Managers that were skipped
+ * as disabled or undetected are absent. `suppressedOnMeteredNetwork` names
+ * managers whose bandwidth-heavy work was skipped on a metered network, and
+ * `skippedWhileQuarantined` names quarantined managers the run left out.
+ */
+char *helm_get_refresh_summary(void);
+
+/**
+ * Report progress of the most recent upgrade-all batch as JSON: step counts
+ * by status, the fraction of steps finished, labels of the running steps,
+ * and an ETA estimated from archived upgrade durations. All counts are zero
+ * before the first batch.
+ */
+char *helm_get_bulk_operation_progress(void);
+
+/**
+ * Report cached registry reachability as JSON: per-host status with the last
+ * probe time, plus managers whose network work is deferred until connectivity returns.
+ *
+ * Reads the cache only; it never probes the network.
+ */
+char *helm_get_network_reachability(void);
+
+/**
+ * Report command timing metrics as JSON: rolling duration histograms per
+ * (manager, action) for every spawned command since launch, plus today's
+ * slowest commands when the `slow_command_tracing` setting is on.
+ */
+char *helm_get_command_metrics(void);
+
+/**
+ * Report task counts, installed and outdated totals, snapshot ages, and
+ * command duration histograms per manager as Prometheus exposition text,
+ * for scraping through a small local shim. Disabled managers are omitted.
+ */
+char *helm_get_metrics_text(void);
+
+/**
+ * Report the environment a manager's next command would run with as JSON:
+ * effective and inherited PATH, environment overrides (secrets redacted), the
+ * selected executable, and the resolved program path. Nothing is executed.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_get_manager_environment(const char *manager_id);
+
 /**
  * Return whether shared onboarding has been completed.
  */
@@ -134,6 +519,27 @@ bool helm_get_cli_onboarding_completed(void);
  */
 bool helm_set_cli_onboarding_completed(bool completed);
 
+/**
+ * Return the first-run onboarding state as JSON
+ * `{phase, suggestedChoices}`, where `phase` is one of `not_started`,
+ * `detecting`, `review`, or `complete`.
+ */
+char *helm_get_onboarding_state(void);
+
+/**
+ * Apply onboarding choices and mark onboarding complete in one transaction,
+ * then start the initial refresh. Returns true on success.
+ *
+ * `json_choices` is an object with optional `managerEnabled` (manager id to
+ * bool), `safeMode`, `homebrewKegPolicy`, `autoCheckForUpdates`, and
+ * `autoCheckFrequencyMinutes`; omitted fields keep their stored value.
+ *
+ * # Safety
+ *
+ * `json_choices` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_complete_onboarding(const char *json_choices);
+
 /**
  * Return accepted shared license terms version.
  *
@@ -162,6 +568,19 @@ bool helm_get_safe_mode(void);
  */
 bool helm_set_safe_mode(bool enabled);
 
+/**
+ * Return whether offline mode is enabled.
+ */
+bool helm_get_offline_mode(void);
+
+/**
+ * Set offline mode state. While enabled, tasks that need a manager's
+ * registry (remote search, index refresh, outdated checks, installs,
+ * upgrades) are refused and refreshes only list installed packages.
+ * Returns true on success.
+ */
+bool helm_set_offline_mode(bool enabled);
+
 /**
  * Return whether Homebrew upgrades should auto-clean old kegs by default.
  */
@@ -219,11 +638,53 @@ bool helm_set_package_manager_preference(const char *package_family_key, const c
  */
 char *helm_preview_upgrade_plan(bool include_pinned, bool allow_os_updates);
 
+/**
+ * Predict the outcome of `helm_upgrade_all` without executing anything, as JSON.
+ *
+ * Returns the upgrades that would be queued (old → new version), the predicted
+ * post-upgrade installed snapshot, cross-manager conflicts for the same package
+ * family, restart requirements, and an estimated download size when adapters
+ * report one. OS updates are included so their restart requirement is visible,
+ * even though executing them needs a `helm_upgrade_all_confirmed` token.
+ *
+ * - `include_pinned`: if false, pinned packages are excluded.
+ */
+char *helm_simulate_upgrade_all(bool include_pinned);
+
+/**
+ * Issue a confirmation token for the upgrade-all plan Helm would run now, as JSON.
+ *
+ * Returns `{token, expiresAtUnix, plan}`, where `plan` lists every upgrade
+ * (OS updates included unless safe mode is on), `osUpdateCount`, and
+ * `restartRequired`. Present the token to `helm_upgrade_all_confirmed` to run
+ * exactly that plan.
+ *
+ * - `include_pinned`: if false, pinned packages are excluded.
+ */
+char *helm_request_upgrade_all_confirmation(bool include_pinned);
+
+/**
+ * Run the upgrade-all plan a token from `helm_request_upgrade_all_confirmation`
+ * vouches for. OS updates run only if the confirmed plan contained them.
+ *
+ * Tokens are single-use. Fails with `service.error.confirmation_invalid` for
+ * unknown or spent tokens, `service.error.confirmation_expired` once the token
+ * lapses, and `service.error.confirmation_plan_changed` when the plan Helm
+ * would run now differs from the confirmed one; request a new token to retry.
+ *
+ * # Safety
+ *
+ * `token` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_upgrade_all_confirmed(const char *token);
+
 /**
  * Queue upgrade tasks for supported managers using cached outdated snapshot.
  *
  * - `include_pinned`: if false, pinned packages are excluded.
- * - `allow_os_updates`: explicit confirmation gate for `softwareupdate` upgrades.
+ * - `allow_os_updates`: must be false. OS updates run only through
+ *   `helm_upgrade_all_confirmed`, so a caller flag alone can never start them;
+ *   passing true fails with `service.error.confirmation_required`.
  */
 bool helm_upgrade_all(bool include_pinned, bool allow_os_updates);
 
@@ -244,7 +705,14 @@ bool helm_upgrade_all(bool include_pinned, bool allow_os_updates);
  * - "rubygems"
  * - "bundler"
  * - "rustup"
- * - "softwareupdate" (requires package_name "__confirm_os_updates__")
+ *
+ * "softwareupdate" fails with `service.error.invalid_input`, including the
+ * legacy package_name "__confirm_os_updates__": OS updates are queued only
+ * through `helm_request_upgrade_all_confirmation` and
+ * `helm_upgrade_all_confirmed`.
+ *
+ * `idempotency_key` may be null. When set, a retried call carrying the same key
+ * returns the task first queued under it instead of queueing a duplicate.
  *
  * # Safety
  *
@@ -254,11 +722,85 @@ bool helm_upgrade_all(bool include_pinned, bool allow_os_updates);
 int64_t helm_upgrade_package(const char *manager_id,
                              const char *package_name,
                              const char *package_target_name,
-                             const char *version);
+                             const char *version,
+                             const char *idempotency_key);
+
+/**
+ * Queue a task that switches a package back to the version it had before its
+ * last upgrade through Helm. Returns the task ID, or -1 on error.
+ *
+ * Requires the `Rollback` capability (Homebrew formulae, while the prior keg
+ * is still in the Cellar). Fails with `service.error.invalid_input` when no
+ * prior version is recorded for the package.
+ *
+ * # Safety
+ *
+ * `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
+ * strings.
+ */
+int64_t helm_rollback_package(const char *manager_id, const char *package_name);
+
+/**
+ * List Homebrew background services as a JSON array of
+ * `{name, status, user, file, exitCode}`, read live from
+ * `brew services list --json`.
+ */
+char *helm_list_services(void);
+
+/**
+ * Queue a task that starts, stops or restarts a Homebrew service. `action`
+ * is `start`, `stop` or `restart`. Returns the task ID, or -1 on error.
+ *
+ * Stopping or restarting interrupts whatever depends on the service, so safe
+ * mode refuses both with `service.error.safe_mode_blocked`.
+ *
+ * # Safety
+ *
+ * `service_name` and `action` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+int64_t helm_set_service_state(const char *service_name, const char *action);
+
+/**
+ * Report the disk space each manager's cleanup would free, as a JSON array
+ * of `{managerId, reclaimableBytes}`, for enabled managers with the
+ * `Cleanup` capability (Homebrew formulae, npm, pip, Cargo).
+ *
+ * Nothing is removed; managers whose measurement fails are left out.
+ */
+char *helm_get_reclaimable_space(void);
+
+/**
+ * Queue a task that reclaims a manager's cache space (`brew cleanup`,
+ * `npm cache clean`, `pip cache purge`, or removing Cargo's registry
+ * downloads). Returns the task ID, or -1 on error.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C
+ * string.
+ */
+int64_t helm_run_cleanup(const char *manager_id);
+
+/**
+ * Queue a task that measures the disk space each of a manager's installed
+ * packages occupies (Homebrew kegs, npm global modules, pipx venvs, Cargo
+ * binaries). The sizes land in the `installed_size_bytes` field of
+ * `helm_list_installed_packages`. Returns the task ID, or -1 on error.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C
+ * string.
+ */
+int64_t helm_measure_package_sizes(const char *manager_id);
 
 /**
  * Queue an install task for a single package. Returns the task ID, or -1 on error.
  *
+ * `idempotency_key` may be null. When set, a retried call carrying the same key
+ * returns the task first queued under it instead of queueing a duplicate.
+ *
  * # Safety
  *
  * `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
@@ -267,11 +809,130 @@ int64_t helm_upgrade_package(const char *manager_id,
 int64_t helm_install_package(const char *manager_id,
                              const char *package_name,
                              const char *package_target_name,
-                             const char *version);
+                             const char *version,
+                             const char *idempotency_key);
+
+/**
+ * Queue an install task for a single package with manager-specific install flags.
+ * Returns the task ID, or -1 on error.
+ *
+ * `options_json` is a JSON array of flags, validated against the manager allowlist
+ * (for example `["--HEAD"]` for Homebrew formulae or `["--no-quarantine"]` for casks).
+ * Flags used by a successful install are persisted and carried forward to later
+ * upgrades where Homebrew supports them.
+ *
+ * `idempotency_key` may be null. When set, a retried call carrying the same key
+ * returns the task first queued under it instead of queueing a duplicate.
+ *
+ * # Safety
+ *
+ * `manager_id`, `package_name`, and `options_json` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+int64_t helm_install_package_with_options(const char *manager_id,
+                                          const char *package_name,
+                                          const char *package_target_name,
+                                          const char *version,
+                                          const char *options_json,
+                                          const char *idempotency_key);
+
+/**
+ * Import a Homebrew Bundle `Brewfile` and return the diff against current state as JSON.
+ *
+ * `brew`, `cask`, and `mas` entries missing from the installed snapshot are
+ * reported with status `install`, as are `tap` entries while Homebrew is
+ * available; when `dry_run` is false those are queued (taps as Homebrew tap
+ * tasks, keeping any custom remote URL) and their task IDs returned in
+ * `queuedTasks`.
+ *
+ * # Safety
+ *
+ * `path` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string
+ * holding an absolute file path.
+ */
+char *helm_import_brewfile(const char *path, bool dry_run);
+
+/**
+ * Plan applying a Helm manifest (the `helm_json` export) and return the plan as JSON.
+ *
+ * Each entry is classified `add`, `skip`, or `conflict` (installed at a
+ * different version than the manifest pins). When `dry_run` is false the
+ * `add` entries are queued as install tasks and returned in `queuedTasks`;
+ * safe mode and managers without individual installs turn adds into skips.
+ *
+ * # Safety
+ *
+ * `manifest_json` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_import_manifest(const char *manifest_json, bool dry_run);
+
+/**
+ * Export the installed-package snapshot as JSON `{format, fileName, contents, packageCount}`.
+ *
+ * `format` is one of `brewfile`, `requirements`, `package_json`,
+ * `cargo_install`, `pipx_install`, or `helm_json`; the host writes `contents`
+ * wherever the user chooses.
+ *
+ * # Safety
+ *
+ * `format` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_export_manifest(const char *format);
+
+/**
+ * Export one Python interpreter's packages so the environment can be rebuilt elsewhere.
+ *
+ * Runs `<interpreter_path> -m pip list` and writes a `requirements.txt` pinned to the
+ * installed versions into the `dest` directory, plus `pipx-install.sh` for the pipx
+ * applications in the installed snapshot when there are any. Existing files are
+ * replaced. Returns JSON `{interpreterPath, files: [{format, path, packageCount}]}`.
+ *
+ * # Safety
+ *
+ * `interpreter_path` and `dest` must be valid, non-null pointers to NUL-terminated
+ * UTF-8 C strings holding absolute paths to an interpreter executable and an
+ * existing directory.
+ */
+char *helm_export_python_requirements(const char *interpreter_path, const char *dest);
+
+/**
+ * Preview migrating global npm packages to pnpm or yarn as JSON.
+ *
+ * `package_names_json` is an optional JSON array restricting the migration to
+ * the named packages; `null` considers every eligible npm global.
+ *
+ * # Safety
+ *
+ * `target_manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ * `package_names_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_preview_npm_migration(const char *target_manager_id, const char *package_names_json);
+
+/**
+ * Migrate global npm packages to pnpm or yarn as a single composite task.
+ *
+ * Every selected package is installed under the target first; if any install
+ * fails, packages installed so far are removed again and npm is left
+ * untouched. npm copies are uninstalled only after all installs succeed.
+ * Cancelling the task stops it before its next step.
+ * Returns the composite task ID, or -1 on error.
+ *
+ * # Safety
+ *
+ * `target_manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ * `package_names_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+int64_t helm_migrate_npm_globals(const char *target_manager_id, const char *package_names_json);
 
 /**
  * Queue an uninstall task for a single package. Returns the task ID, or -1 on error.
  *
+ * With `move_to_trash`, app bundles go to the Trash instead of being deleted; managers
+ * that cannot honor this fail with the unsupported-capability error.
+ *
+ * `idempotency_key` may be null. When set, a retried call carrying the same key
+ * returns the task first queued under it instead of queueing a duplicate.
+ *
  * # Safety
  *
  * `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
@@ -280,7 +941,9 @@ int64_t helm_install_package(const char *manager_id,
 int64_t helm_uninstall_package(const char *manager_id,
                                const char *package_name,
                                const char *package_target_name,
-                               const char *version);
+                               const char *version,
+                               bool move_to_trash,
+                               const char *idempotency_key);
 
 /**
  * Queue a rustup component-add task. Returns the task ID, or -1 on error.
@@ -383,6 +1046,23 @@ char *helm_list_pins(void);
  */
 bool helm_pin_package(const char *manager_id, const char *package_name, const char *pinned_version);
 
+/**
+ * Persist a virtual pin that lets upgrades move within a version range,
+ * such as `^1.2` or `>=1.0 <2.0`. The package is held back only while its
+ * candidate version falls outside the range. Returns true on success.
+ *
+ * Constrained pins are always virtual: a native pin (for example `brew pin`)
+ * still holds the package regardless of the range.
+ *
+ * # Safety
+ *
+ * `manager_id`, `package_name`, and `constraint` must be valid, non-null pointers to
+ * NUL-terminated UTF-8 C strings.
+ */
+bool helm_pin_package_with_constraint(const char *manager_id,
+                                      const char *package_name,
+                                      const char *constraint);
+
 /**
  * Remove a pin for a package. Returns true on success.
  *
@@ -470,6 +1150,46 @@ bool helm_set_manager_timeout_profile(const char *manager_id,
                                       int64_t hard_timeout_seconds,
                                       int64_t idle_timeout_seconds);
 
+/**
+ * Set the spawned-process QoS override for a manager.
+ *
+ * `qos` is `default`, `utility`, or `background`; null clears the override so
+ * the manager follows the global `process_qos` setting. An explicit `default`
+ * keeps the manager at normal priority even when the global setting is lower.
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ * `qos` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_set_manager_process_qos(const char *manager_id, const char *qos);
+
+/**
+ * List Homebrew environment settings for a Homebrew manager as JSON.
+ *
+ * Each entry carries the setting `key` and the user `override_value`
+ * (`null` when Helm's per-action default applies).
+ *
+ * # Safety
+ *
+ * `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+char *helm_list_manager_env_settings(const char *manager_id);
+
+/**
+ * Set or clear a Homebrew environment setting override.
+ *
+ * `value` values:
+ * - `-1`: clear override (use Helm's per-action default)
+ * - `0`: force off
+ * - `1`: force on
+ *
+ * # Safety
+ *
+ * `manager_id` and `key` must be valid, non-null pointers to NUL-terminated UTF-8 C strings.
+ */
+bool helm_set_manager_env_setting(const char *manager_id, const char *key, int32_t value);
+
 /**
  * Apply a manager package-state repair option and queue the corresponding task.
  *
@@ -530,6 +1250,7 @@ int64_t helm_install_manager_with_options(const char *manager_id,
  * Supported manager IDs:
  * - "homebrew_formula" -> `brew update`
  * - "rustup" -> provenance-driven (`brew upgrade rustup` or `rustup self update`)
+ * - Self-updating managers (for example `asdf`) -> the adapter's `__self__` upgrade
  * - Homebrew one-to-one managers -> provenance-driven (`asdf`, `mise`, `mas`, `pnpm`,
  *   `yarn`, `pipx`, `poetry`, `cargo-binstall`, `podman`, `colima`)
  * - Homebrew parent-formula managers -> provenance-driven (`npm`, `pip`, `rubygems`,
@@ -559,6 +1280,9 @@ int64_t helm_uninstall_manager(const char *manager_id);
 /**
  * Preview manager uninstall blast radius and strategy as JSON.
  *
+ * The preview lists `affectedPackages` tracked under the manager and whether the uninstall
+ * `removesManagerData`; together they describe the cascade of a full-cleanup uninstall.
+ *
  * `allow_unknown_provenance` controls whether unknown-provenance routing uses override mode.
  * For preview-only UI flows, callers typically pass `false` and rely on `unknown_override_required`
  * in the JSON response to gate destructive execution.
@@ -608,6 +1332,8 @@ int64_t helm_uninstall_manager_with_options(const char *manager_id, bool allow_u
  * - `homebrewCleanupMode` (`managerOnly` | `fullCleanup`)
  * - `miseCleanupMode` (`managerOnly` | `fullCleanup`)
  * - `miseConfigRemoval` (`keepConfig` | `removeConfig`)
+ * - `confirmManagerDataRemoval` (bool) — required when the preview reports
+ *   `removesManagerData` with non-empty `affectedPackages`
  *
  * # Safety
  *
@@ -623,11 +1349,40 @@ int64_t helm_uninstall_manager_with_uninstall_options(const char *manager_id,
  */
 bool helm_reset_database(void);
 
+/**
+ * List Helm profiles as JSON (`[{name, databasePath, active}]`), `default`
+ * first.
+ */
+char *helm_list_profiles(void);
+
+/**
+ * Switch to the named profile, creating its database on first use, and
+ * remember it for the next launch. Returns true on success.
+ *
+ * The current runtime is quiesced before its store is swapped out. Refused
+ * with `service.error.profile_switch_busy` while tasks are queued or running,
+ * and with `service.error.unsupported_capability` while the legacy file-IPC
+ * coordinator is serving the current database.
+ *
+ * # Safety
+ *
+ * `name` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+ */
+bool helm_switch_profile(const char *name);
+
 /**
  * Return and clear the most recent service error localization key.
  */
 char *helm_take_last_error_key(void);
 
+/**
+ * Return and clear the most recent service error as JSON: the localization
+ * `key`, and when a core error caused it, its `kind`, `manager`, `task`,
+ * `action`, `message`, `exit_code`, `stderr_excerpt`, and `retryable` flag.
+ * Shares its slot with [`helm_take_last_error_key`]; taking either clears both.
+ */
+char *helm_take_last_error_json(void);
+
 /**
  * Free a string previously returned by a `helm_*` function.
  *
@@ -6,38 +6,73 @@
 //! ## Lifecycle
 //!
 //! - **Initialization**: [`helm_init`] must be called once with a valid SQLite
-//!   database path. It creates a Tokio runtime, initializes the SQLite store with
-//!   migrations, registers all implemented manager adapters, and stores the engine state in
-//!   a process-global `Mutex<Option<HelmState>>`.
+//!   database path. It creates a Tokio runtime, opens a `helm_engine::Engine` (SQLite
+//!   store with migrations plus all implemented manager adapters), and stores the engine
+//!   state in a process-global `Mutex<Option<HelmState>>`. Package lists, search,
+//!   the task list, installs, uninstalls, upgrades, cancellation, and refresh go
+//!   through the engine's facade methods. Rust embedders should use `helm-engine`
+//!   directly instead of this C boundary.
 //!
-//! - **No explicit shutdown**: There is no `helm_shutdown()` function. The Tokio
-//!   runtime, SQLite connections, and adapter state live for the entire process
-//!   lifetime. Cleanup occurs when the XPC service process exits.
+//! - **Shutdown**: [`helm_shutdown`] cancels in-flight adapter tasks, checkpoints
+//!   the SQLite write-ahead log, shuts the Tokio runtime down, and clears the
+//!   global state so [`helm_init`] can run again in the same process. Without it,
+//!   state lives until the XPC service process exits.
 //!
 //! - **Thread safety**: All FFI functions acquire the global state mutex before
 //!   accessing the engine. Poisoned-lock recovery is implemented via
 //!   [`lock_or_recover`] to prevent lock-poison panics at the FFI boundary.
 //!
+//! - **Panic isolation**: Every export runs its body through [`ffi_boundary`],
+//!   which catches panics instead of letting them unwind across the C ABI. The
+//!   panic message goes to stderr (the service log), the call returns its usual
+//!   failure value, and [`helm_take_last_error_key`] reports
+//!   `service.error.internal_panic`.
+//!
 //! ## FFI Exports (service surface)
 //!
 //! | Function | Category |
 //! |----------|----------|
 //! | `helm_init` | Lifecycle |
+//! | `helm_shutdown` | Lifecycle |
 //! | `helm_list_installed_packages` | Package queries |
 //! | `helm_list_outdated_packages` | Package queries |
+//! | `helm_list_installed_packages_sorted` | Package queries |
+//! | `helm_list_outdated_packages_sorted` | Package queries |
+//! | `helm_list_held_back_packages` | Package queries |
 //! | `helm_get_rustup_toolchain_detail` | Package queries |
+//! | `helm_list_package_files` | Package queries |
+//! | `helm_list_package_dependencies` | Package queries |
+//! | `helm_measure_package_sizes` | Package queries |
+//! | `helm_get_package_info` | Package queries |
+//! | `helm_resolve_package_alias` | Package queries |
+//! | `helm_get_completion_data` | Package queries |
+//! | `helm_list_orphaned_packages` | Package queries |
 //! | `helm_list_tasks` | Task management |
 //! | `helm_get_task_output` | Task management |
+//! | `helm_get_task_output_since` | Task management |
+//! | `helm_get_task_outputs` | Task management |
 //! | `helm_list_task_logs` | Task management |
+//! | `helm_list_task_history` | Task management |
 //! | `helm_trigger_refresh` | Task management |
+//! | `helm_get_refresh_summary` | Task management |
+//! | `helm_get_bulk_operation_progress` | Task management |
 //! | `helm_trigger_detection` | Task management |
 //! | `helm_trigger_detection_for_manager` | Task management |
 //! | `helm_cancel_task` | Task management |
+//! | `helm_wait_for_task` | Task management |
 //! | `helm_dismiss_task` | Task management |
+//! | `helm_record_task_attempt` | Task management |
+//! | `helm_list_task_attempts` | Task management |
 //! | `helm_search_local` | Search |
 //! | `helm_trigger_remote_search` | Search |
+//! | `helm_trigger_remote_search_v2` | Search |
 //! | `helm_list_manager_status` | Manager control |
 //! | `helm_doctor_scan` | Diagnostics |
+//! | `helm_get_engine_info` | Diagnostics |
+//! | `helm_get_network_reachability` | Diagnostics |
+//! | `helm_get_command_metrics` | Diagnostics |
+//! | `helm_get_metrics_text` | Diagnostics |
+//! | `helm_get_manager_environment` | Diagnostics |
 //! | `helm_set_manager_enabled` | Manager control |
 //! | `helm_install_manager` | Manager control |
 //! | `helm_update_manager` | Manager control |
@@ -47,24 +82,63 @@
 //! | `helm_apply_manager_package_state_issue_repair` | Manager control |
 //! | `helm_get_safe_mode` | Settings |
 //! | `helm_set_safe_mode` | Settings |
+//! | `helm_get_offline_mode` | Settings |
+//! | `helm_set_offline_mode` | Settings |
 //! | `helm_get_homebrew_keg_auto_cleanup` | Settings |
 //! | `helm_set_homebrew_keg_auto_cleanup` | Settings |
+//! | `helm_get_setting` | Settings |
+//! | `helm_set_setting` | Settings |
+//! | `helm_list_settings` | Settings |
+//! | `helm_list_setting_changes` | Settings |
+//! | `helm_list_manager_env_settings` | Settings |
+//! | `helm_set_manager_env_setting` | Settings |
+//! | `helm_set_manager_process_qos` | Settings |
+//! | `helm_get_onboarding_state` | Onboarding |
+//! | `helm_complete_onboarding` | Onboarding |
+//! | `helm_get_next_scheduled_runs` | Scheduling |
+//! | `helm_notify_wake` | Scheduling |
+//! | `helm_set_view_hint` | Scheduling |
+//! | `helm_set_network_hint` | Scheduling |
+//! | `helm_set_refresh_schedule` | Scheduling |
+//! | `helm_get_refresh_schedules` | Scheduling |
+//! | `helm_retry_quarantined_manager` | Scheduling |
 //! | `helm_list_package_keg_policies` | Keg policies |
 //! | `helm_set_package_keg_policy` | Keg policies |
 //! | `helm_list_package_manager_preferences` | Package manager preferences |
 //! | `helm_set_package_manager_preference` | Package manager preferences |
+//! | `helm_install_package_with_options` | Package actions |
+//! | `helm_cleanup_orphans` | Package actions |
+//! | `helm_import_brewfile` | Manifest |
+//! | `helm_import_manifest` | Manifest |
+//! | `helm_export_manifest` | Manifest |
+//! | `helm_export_python_requirements` | Manifest |
+//! | `helm_preview_npm_migration` | Migration |
+//! | `helm_migrate_npm_globals` | Migration |
 //! | `helm_preview_upgrade_plan` | Upgrade |
+//! | `helm_simulate_upgrade_all` | Upgrade |
 //! | `helm_upgrade_all` | Upgrade |
+//! | `helm_request_upgrade_all_confirmation` | Upgrade |
+//! | `helm_upgrade_all_confirmed` | Upgrade |
 //! | `helm_upgrade_package` | Upgrade |
+//! | `helm_rollback_package` | Upgrade |
 //! | `helm_list_pins` | Pinning |
 //! | `helm_pin_package` | Pinning |
+//! | `helm_pin_package_with_constraint` | Pinning |
 //! | `helm_unpin_package` | Pinning |
+//! | `helm_list_services` | Services |
+//! | `helm_set_service_state` | Services |
+//! | `helm_get_reclaimable_space` | Cleanup |
+//! | `helm_run_cleanup` | Cleanup |
 //! | `helm_reset_database` | Database |
+//! | `helm_list_profiles` | Profiles |
+//! | `helm_switch_profile` | Profiles |
 //! | `helm_take_last_error_key` | Error |
+//! | `helm_take_last_error_json` | Error |
 //! | `helm_free_string` | Memory management |
 //!
-//! All data exchange uses JSON-encoded UTF-8 `*mut c_char` strings. The caller
-//! must free returned strings via [`helm_free_string`].
+//! All data exchange uses JSON-encoded UTF-8 `*mut c_char` strings, except
+//! `helm_get_metrics_text`, which returns Prometheus exposition text. The
+//! caller must free returned strings via [`helm_free_string`].
 
 use std::ffi::{CStr, CString};
 use std::fs::OpenOptions;
@@ -73,142 +147,130 @@ use std::io::Write;
 use std::os::raw::c_char;
 #[cfg(unix)]
 use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use helm_core::adapters::asdf::AsdfAdapter;
-use helm_core::adapters::asdf_process::ProcessAsdfSource;
-use helm_core::adapters::bundler::BundlerAdapter;
-use helm_core::adapters::bundler_process::ProcessBundlerSource;
-use helm_core::adapters::cargo::CargoAdapter;
-use helm_core::adapters::cargo_binstall::CargoBinstallAdapter;
-use helm_core::adapters::cargo_binstall_process::ProcessCargoBinstallSource;
-use helm_core::adapters::cargo_process::ProcessCargoSource;
-use helm_core::adapters::colima::ColimaAdapter;
-use helm_core::adapters::colima_process::ProcessColimaSource;
-use helm_core::adapters::docker_desktop::DockerDesktopAdapter;
-use helm_core::adapters::docker_desktop_process::ProcessDockerDesktopSource;
-use helm_core::adapters::firmware_updates::FirmwareUpdatesAdapter;
-use helm_core::adapters::firmware_updates_process::ProcessFirmwareUpdatesSource;
-use helm_core::adapters::homebrew::HomebrewAdapter;
-use helm_core::adapters::homebrew_cask::HomebrewCaskAdapter;
-use helm_core::adapters::homebrew_cask_process::ProcessHomebrewCaskSource;
-use helm_core::adapters::homebrew_process::ProcessHomebrewSource;
+use helm_core::adapters::homebrew_cask::set_exclude_auto_updating_casks;
+use helm_core::adapters::homebrew_env::{
+    HomebrewEnvOverrides, HomebrewEnvSetting, homebrew_env_overrides, homebrew_env_vars,
+    set_homebrew_env_overrides,
+};
 use helm_core::adapters::load_rustup_toolchain_detail_with_runtime;
-use helm_core::adapters::macports::MacPortsAdapter;
-use helm_core::adapters::macports_process::ProcessMacPortsSource;
 use helm_core::adapters::manager::{
     PackageDetailChildKind, PackageDetailOperation, PackageDetailRequest,
 };
-use helm_core::adapters::mas::MasAdapter;
-use helm_core::adapters::mas_process::ProcessMasSource;
-use helm_core::adapters::mise::MiseAdapter;
-use helm_core::adapters::mise_process::ProcessMiseSource;
-use helm_core::adapters::nix_darwin::NixDarwinAdapter;
-use helm_core::adapters::nix_darwin_process::ProcessNixDarwinSource;
-use helm_core::adapters::npm::NpmAdapter;
-use helm_core::adapters::npm_process::ProcessNpmSource;
-use helm_core::adapters::parallels_desktop::ParallelsDesktopAdapter;
-use helm_core::adapters::parallels_desktop_process::ProcessParallelsDesktopSource;
-use helm_core::adapters::pip::PipAdapter;
-use helm_core::adapters::pip_process::ProcessPipSource;
-use helm_core::adapters::pipx::PipxAdapter;
-use helm_core::adapters::pipx_process::ProcessPipxSource;
-use helm_core::adapters::pnpm::PnpmAdapter;
-use helm_core::adapters::pnpm_process::ProcessPnpmSource;
-use helm_core::adapters::podman::PodmanAdapter;
-use helm_core::adapters::podman_process::ProcessPodmanSource;
-use helm_core::adapters::poetry::PoetryAdapter;
-use helm_core::adapters::poetry_process::ProcessPoetrySource;
-use helm_core::adapters::rosetta2::Rosetta2Adapter;
-use helm_core::adapters::rosetta2_process::ProcessRosetta2Source;
-use helm_core::adapters::rubygems::RubyGemsAdapter;
-use helm_core::adapters::rubygems_process::ProcessRubyGemsSource;
-use helm_core::adapters::rustup::{RustupAdapter, RustupToolchainDetail};
-use helm_core::adapters::rustup_process::ProcessRustupSource;
-use helm_core::adapters::setapp::SetappAdapter;
-use helm_core::adapters::setapp_process::ProcessSetappSource;
-use helm_core::adapters::softwareupdate::SoftwareUpdateAdapter;
-use helm_core::adapters::softwareupdate_process::ProcessSoftwareUpdateSource;
-use helm_core::adapters::sparkle::SparkleAdapter;
-use helm_core::adapters::sparkle_process::ProcessSparkleSource;
-use helm_core::adapters::xcode_command_line_tools::XcodeCommandLineToolsAdapter;
-use helm_core::adapters::xcode_command_line_tools_process::ProcessXcodeCommandLineToolsSource;
-use helm_core::adapters::yarn::YarnAdapter;
-use helm_core::adapters::yarn_process::ProcessYarnSource;
+use helm_core::adapters::rustup::RustupToolchainDetail;
 use helm_core::adapters::{
-    AdapterRequest, InstallRequest, PinRequest, SearchRequest, UninstallRequest, UnpinRequest,
-    UpgradeRequest,
+    AdapterRequest, CleanupRequest, InstallRequest, MeasurePackageSizesRequest, PinRequest,
+    RollbackRequest, SearchRequest, SetServiceStateRequest, UninstallRequest, UnpinRequest,
+    UpgradeRequest, UpgradeTarget, UpgradeTargetKind,
 };
-use helm_core::execution::tokio_process::TokioProcessExecutor;
+use helm_core::base_dirs::ToolBaseDirs;
+use helm_core::confirmation::{ConfirmationError, ConfirmationPlan, ConfirmationTokens};
 use helm_core::execution::{
-    ManagerTimeoutProfile, clear_manager_selected_executables,
-    replace_manager_execution_preferences,
+    CommandPolicyMode, ManagerTimeoutProfile, ProcessQos, clear_manager_selected_executables,
+    replace_manager_execution_preferences, set_command_policy_mode, set_default_process_qos,
 };
+use helm_core::held_back::held_back_packages;
 use helm_core::managed_automation_policy::{
     ManagedAutomationPolicyMode, apply_managed_automation_policy,
 };
 use helm_core::manager_dependencies::provenance_dependency_manager;
 use helm_core::manager_instances::{install_instance_fingerprint, resolve_multi_instance_state};
 use helm_core::manager_policy::manager_enablement_eligibility;
+use helm_core::metrics_text::{MetricsInput, render_metrics_text};
 use helm_core::models::{
-    Capability, DetectionInfo, HomebrewKegPolicy, ManagerAction, ManagerAuthority, ManagerId,
-    ManagerInstallInstance, ManagerUninstallPreview, OutdatedPackage, PackageRef,
-    PackageRuntimeState, PinKind, PinRecord, SearchQuery, StrategyKind, TaskId, TaskLogLevel,
-    TaskLogRecord, TaskRecord, TaskStatus, TaskType,
+    Capability, DetectionInfo, DetectionOutcome, HomebrewKegPolicy, InstalledPackage,
+    ManagerAction, ManagerAuthority, ManagerId, ManagerInstallInstance, ManagerRefreshResult,
+    ManagerUninstallPreview, OnboardingChoices, OutdatedPackage, PackageListSort, PackageRef,
+    PackageRuntimeState, PinKind, PinRecord, SearchQuery, ServiceAction, StrategyKind,
+    TaskHistoryFilter, TaskHistoryRecord, TaskId, TaskLabel, TaskLogLevel, TaskLogRecord,
+    TaskRecord, TaskStatus, TaskType, UninstallDisposal,
 };
+use helm_core::onboarding::{OnboardingEvent, record_onboarding_event};
 use helm_core::orchestration::adapter_runtime::AdapterRuntime;
-use helm_core::orchestration::{AdapterTaskTerminalState, CancellationMode};
+use helm_core::orchestration::task_view::is_inflight_status;
+use helm_core::orchestration::{
+    AdapterTaskSnapshot, AdapterTaskTerminalState, CancellationMode, TaskViewOptions,
+    build_visible_tasks,
+};
+use helm_core::package_dependencies::OrphanedPackage;
 use helm_core::persistence::{
-    DetectionStore, ManagerPreference, MigrationStore, PackageStore, PinStore, SearchCacheStore,
-    TaskStore,
+    DependencyStore, DetectionStore, ManagerExecutableCandidate, ManagerPreference, MigrationStore,
+    PackageMetadataStore, PackageStore, PersistenceResult, PinStore, SearchCacheStore, TaskStore,
 };
+use helm_core::profiles::{DEFAULT_PROFILE, ProfileDirectory};
+use helm_core::settings::SettingKey;
+use helm_core::snapshot_cache::{PersistenceListener, SnapshotCache};
+use helm_core::snapshot_reconciliation::reconcile_package_snapshots;
 use helm_core::sqlite::SqliteStore;
 use helm_core::uninstall_preview::{
     DEFAULT_MANAGER_UNINSTALL_SAFE_BLAST_RADIUS_THRESHOLD, ManagerUninstallPreviewContext,
     PackageUninstallPreviewContext, build_manager_uninstall_preview,
     build_package_uninstall_preview,
 };
-use helm_core::versioning::PackageCoordinate;
+use helm_core::upgrade_target::CONFIRMED_OS_UPDATES_NAME;
+use helm_core::versioning::{PackageCoordinate, VersionConstraint};
 use lazy_static::lazy_static;
 
 struct HelmState {
+    engine: Arc<helm_engine::Engine>,
     store: Arc<SqliteStore>,
     runtime: Arc<AdapterRuntime>,
+    profiles: ProfileDirectory,
+    profile: String,
     rt_handle: tokio::runtime::Handle,
-    _tokio_rt: tokio::runtime::Runtime,
-}
-
-#[derive(Clone, Debug, Default)]
-struct TaskLabel {
-    key: String,
-    args: std::collections::BTreeMap<String, String>,
+    tokio_rt: tokio::runtime::Runtime,
 }
 
 lazy_static! {
     static ref STATE: Mutex<Option<HelmState>> = Mutex::new(None);
-    static ref TASK_LABELS: Mutex<std::collections::HashMap<u64, TaskLabel>> =
-        Mutex::new(std::collections::HashMap::new());
-    static ref LAST_ERROR_KEY: Mutex<Option<String>> = Mutex::new(None);
+    static ref LAST_ERROR: Mutex<Option<LastServiceError>> = Mutex::new(None);
+    static ref CONFIRMATION_TOKENS: ConfirmationTokens = ConfirmationTokens::default();
+    static ref BULK_OPERATION: Mutex<Option<BulkOperationSession>> = Mutex::new(None);
 }
 
 const LOCK_POISONED_ERROR_KEY: &str = "error.ffi.lock_poisoned";
 const SERVICE_ERROR_INVALID_INPUT: &str = "service.error.invalid_input";
 const SERVICE_ERROR_INTERNAL: &str = "service.error.internal";
+const SERVICE_ERROR_INTERNAL_PANIC: &str = "service.error.internal_panic";
 const SERVICE_ERROR_PROCESS_FAILURE: &str = "service.error.process_failure";
 const SERVICE_ERROR_STORAGE_FAILURE: &str = "service.error.storage_failure";
+const SERVICE_ERROR_NETWORK_UNREACHABLE: &str = "service.error.network_unreachable";
+const SERVICE_ERROR_OFFLINE_MODE: &str = "service.error.offline_mode";
 const SERVICE_ERROR_UNSUPPORTED_CAPABILITY: &str = "service.error.unsupported_capability";
 const SERVICE_ERROR_MANAGER_DEPENDENCY_BLOCKED: &str = "service.error.manager_dependency_blocked";
 const SERVICE_ERROR_MANAGER_SETUP_REQUIRED: &str = "service.error.manager_setup_required";
+const SERVICE_ERROR_MANAGER_DATA_REMOVAL_CONFIRMATION_REQUIRED: &str =
+    "service.error.manager_data_removal_confirmation_required";
+const SERVICE_ERROR_CONFIRMATION_REQUIRED: &str = "service.error.confirmation_required";
+const SERVICE_ERROR_CONFIRMATION_INVALID: &str = "service.error.confirmation_invalid";
+const SERVICE_ERROR_CONFIRMATION_EXPIRED: &str = "service.error.confirmation_expired";
+const SERVICE_ERROR_CONFIRMATION_PLAN_CHANGED: &str = "service.error.confirmation_plan_changed";
+const SERVICE_ERROR_PROFILE_SWITCH_BUSY: &str = "service.error.profile_switch_busy";
+const SERVICE_ERROR_SAFE_MODE_BLOCKED: &str = "service.error.safe_mode_blocked";
+
+/// The most recent service error: its localization key, plus the core error
+/// behind it when the failure came from an adapter or the store.
+#[derive(serde::Serialize, Clone, Debug)]
+struct LastServiceError {
+    key: String,
+    #[serde(flatten)]
+    detail: Option<helm_core::models::CoreErrorPayload>,
+}
 
 fn note_lock_poisoned(context: &str) {
     eprintln!("helm-ffi: recovering from poisoned mutex: {context}");
-    if let Ok(mut key) = LAST_ERROR_KEY.try_lock() {
-        *key = Some(LOCK_POISONED_ERROR_KEY.to_string());
+    if let Ok(mut last_error) = LAST_ERROR.try_lock() {
+        *last_error = Some(LastServiceError {
+            key: LOCK_POISONED_ERROR_KEY.to_string(),
+            detail: None,
+        });
     }
 }
 
@@ -223,11 +285,21 @@ fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, context: &str) -> MutexGuard<'a,
 }
 
 fn clear_last_error_key() {
-    lock_or_recover(&LAST_ERROR_KEY, "last_error_key").take();
+    lock_or_recover(&LAST_ERROR, "last_error").take();
 }
 
 fn set_last_error_key(error_key: &str) {
-    *lock_or_recover(&LAST_ERROR_KEY, "last_error_key") = Some(error_key.to_string());
+    *lock_or_recover(&LAST_ERROR, "last_error") = Some(LastServiceError {
+        key: error_key.to_string(),
+        detail: None,
+    });
+}
+
+fn set_last_core_error(error: &helm_core::models::CoreError) {
+    *lock_or_recover(&LAST_ERROR, "last_error") = Some(LastServiceError {
+        key: core_error_service_key(error).to_string(),
+        detail: Some(error.payload()),
+    });
 }
 
 fn return_error_bool(error_key: &str) -> bool {
@@ -245,13 +317,96 @@ fn return_error_ptr(error_key: &str) -> *mut c_char {
     std::ptr::null_mut()
 }
 
+fn return_core_error_bool(error: &helm_core::models::CoreError) -> bool {
+    set_last_core_error(error);
+    false
+}
+
+fn return_core_error_i64(error: &helm_core::models::CoreError) -> i64 {
+    set_last_core_error(error);
+    -1
+}
+
+fn return_core_error_ptr(error: &helm_core::models::CoreError) -> *mut c_char {
+    set_last_core_error(error);
+    std::ptr::null_mut()
+}
+
+/// What an export returns when its body panics: the same value it uses for
+/// any other failure.
+trait FfiPanicValue {
+    fn panic_value() -> Self;
+}
+
+impl FfiPanicValue for *mut c_char {
+    fn panic_value() -> Self {
+        std::ptr::null_mut()
+    }
+}
+
+impl FfiPanicValue for bool {
+    fn panic_value() -> Self {
+        false
+    }
+}
+
+impl FfiPanicValue for i64 {
+    fn panic_value() -> Self {
+        -1
+    }
+}
+
+impl FfiPanicValue for () {
+    fn panic_value() -> Self {}
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Export name that [`ffi_boundary`] panics in on this thread, for tests.
+    static PANIC_ON_ENTRY: std::cell::Cell<Option<&'static str>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Run the body of the export `function`, catching any panic so it never
+/// unwinds across the C ABI and aborts the service. A caught panic is logged
+/// with its message and reported as `service.error.internal_panic`; mutexes it
+/// poisoned are recovered by [`lock_or_recover`] on the next call.
+fn ffi_boundary<T: FfiPanicValue>(function: &'static str, body: impl FnOnce() -> T) -> T {
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        #[cfg(test)]
+        if PANIC_ON_ENTRY.with(|hook| hook.get()) == Some(function) {
+            panic!("test hook panic in {function}");
+        }
+        body()
+    }));
+    match result {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("non-string panic payload");
+            eprintln!("helm-ffi: panic in {function}: {message}");
+            set_last_error_key(SERVICE_ERROR_INTERNAL_PANIC);
+            T::panic_value()
+        }
+    }
+}
+
 fn core_error_service_key(error: &helm_core::models::CoreError) -> &'static str {
-    match error.kind {
+    core_error_kind_service_key(error.kind)
+}
+
+fn core_error_kind_service_key(kind: helm_core::models::CoreErrorKind) -> &'static str {
+    match kind {
         helm_core::models::CoreErrorKind::InvalidInput => SERVICE_ERROR_INVALID_INPUT,
         helm_core::models::CoreErrorKind::UnsupportedCapability => {
             SERVICE_ERROR_UNSUPPORTED_CAPABILITY
         }
         helm_core::models::CoreErrorKind::StorageFailure => SERVICE_ERROR_STORAGE_FAILURE,
+        helm_core::models::CoreErrorKind::NetworkUnreachable => SERVICE_ERROR_NETWORK_UNREACHABLE,
+        helm_core::models::CoreErrorKind::OfflineMode => SERVICE_ERROR_OFFLINE_MODE,
         helm_core::models::CoreErrorKind::Internal => SERVICE_ERROR_INTERNAL,
         helm_core::models::CoreErrorKind::NotInstalled
         | helm_core::models::CoreErrorKind::ParseFailure
@@ -341,6 +496,7 @@ struct ManagerUninstallOptionsPayload {
     mise_cleanup_mode: Option<MiseUninstallCleanupModePayload>,
     mise_config_removal: Option<MiseUninstallConfigRemovalPayload>,
     remove_helm_managed_shell_setup: Option<bool>,
+    confirm_manager_data_removal: Option<bool>,
 }
 
 fn parse_install_options_payload(
@@ -443,6 +599,7 @@ fn parse_uninstall_options_payload(
             mise_cleanup_mode,
             mise_config_removal,
             remove_helm_managed_shell_setup: payload.remove_helm_managed_shell_setup,
+            confirm_manager_data_removal: payload.confirm_manager_data_removal.unwrap_or(false),
         },
     ))
 }
@@ -522,6 +679,7 @@ fn manager_display_name(id: ManagerId) -> &'static str {
         ManagerId::Rustup => "rustup",
         ManagerId::SoftwareUpdate => "Software Update",
         ManagerId::Mas => "App Store",
+        ManagerId::MacPorts => "MacPorts",
         _ => id.as_str(),
     }
 }
@@ -583,6 +741,8 @@ fn is_implemented_manager(id: ManagerId) -> bool {
 struct FfiManagerStatus {
     manager_id: String,
     detected: bool,
+    detection_outcome: &'static str,
+    detection_error_key: Option<String>,
     version: Option<String>,
     executable_path: Option<String>,
     executable_paths: Vec<String>,
@@ -594,6 +754,7 @@ struct FfiManagerStatus {
     install_method_options: Vec<FfiManagerInstallMethodOption>,
     timeout_hard_seconds: Option<u64>,
     timeout_idle_seconds: Option<u64>,
+    process_qos: Option<String>,
     enabled: bool,
     is_implemented: bool,
     is_optional: bool,
@@ -602,6 +763,7 @@ struct FfiManagerStatus {
     supports_package_install: bool,
     supports_package_uninstall: bool,
     supports_package_upgrade: bool,
+    supports_package_rollback: bool,
     package_state_issues: Vec<FfiManagerPackageStateIssue>,
     is_eligible: bool,
     ineligible_reason_code: Option<String>,
@@ -623,6 +785,42 @@ struct FfiManagerStatus {
     active_explanation_secondary: Option<String>,
     competing_provenance: Option<String>,
     competing_confidence: Option<f64>,
+    installed_package_count: usize,
+    outdated_package_count: usize,
+    size_on_disk_bytes: Option<u64>,
+    self_latest_version: Option<String>,
+    self_update_available: bool,
+    supports_manager_update: bool,
+    /// A refresh task for this manager was cancelled or failed after its
+    /// snapshot was last written, so package data may be incomplete.
+    snapshot_partial: bool,
+    /// The installed list was left stale by the last task that tried to
+    /// rewrite it.
+    installed_snapshot_partial: bool,
+    /// The outdated list was left stale by the last task that tried to
+    /// rewrite it.
+    outdated_snapshot_partial: bool,
+    /// Supported capabilities that need network access; the rest work offline.
+    network_capabilities: Vec<&'static str>,
+    /// Set while repeated refresh failures keep the manager out of background
+    /// refreshes.
+    quarantine: Option<FfiManagerQuarantine>,
+    /// Manager-specific detection facts, such as yarn's `flavor` and
+    /// `provider`.
+    detection_extras: std::collections::BTreeMap<String, String>,
+    /// A background version probe is running; `version` is the last known
+    /// value and a later status call picks up the result.
+    probing: bool,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiManagerQuarantine {
+    consecutive_failures: u32,
+    reason_kind: &'static str,
+    reason: String,
+    quarantined_since_unix: i64,
+    next_probe_unix: i64,
 }
 
 #[derive(serde::Serialize, Clone, Debug, PartialEq)]
@@ -691,6 +889,301 @@ struct FfiManagerInstallInstanceSummary {
 
 type FfiManagerUninstallPreview = ManagerUninstallPreview;
 
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiEngineInfo {
+    core_version: String,
+    engine_version: String,
+    ffi_version: String,
+    schema_version: i64,
+    latest_schema_version: i64,
+    database_path: String,
+    uptime_seconds: u64,
+    adapters: Vec<String>,
+    /// Tool data and bin directories discovery resolved from the environment.
+    base_dirs: ToolBaseDirs,
+    /// Manager ids from a newer Helm whose rows listings are skipping.
+    unknown_manager_ids: Vec<String>,
+}
+
+fn build_ffi_engine_info(info: helm_engine::EngineInfo) -> FfiEngineInfo {
+    FfiEngineInfo {
+        core_version: info.core_version.to_string(),
+        engine_version: info.engine_version.to_string(),
+        ffi_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: info.schema_version,
+        latest_schema_version: info.latest_schema_version,
+        database_path: info.database_path.to_string_lossy().into_owned(),
+        uptime_seconds: info.uptime.as_secs(),
+        adapters: info
+            .adapters
+            .into_iter()
+            .map(|manager| manager.as_str().to_string())
+            .collect(),
+        base_dirs: ToolBaseDirs::from_env(),
+        unknown_manager_ids: info.unknown_manager_ids,
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiRefreshSummary {
+    run_started_at_unix: Option<i64>,
+    succeeded: usize,
+    failed: usize,
+    managers: Vec<FfiManagerRefreshResult>,
+    /// Whether the host currently reports a metered network.
+    metered_network: bool,
+    /// Managers whose bandwidth-heavy work the run skipped because the network
+    /// was metered: Homebrew's auto-update, or the whole softwareupdate scan.
+    suppressed_on_metered_network: Vec<String>,
+    /// Managers the run left out because they are quarantined and their next
+    /// probe is not yet due.
+    skipped_while_quarantined: Vec<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiManagerRefreshResult {
+    manager_id: String,
+    succeeded: bool,
+    /// Service localization key for the failure.
+    error_key: Option<String>,
+    error_code: Option<String>,
+    error_message: Option<String>,
+    duration_ms: u64,
+    finished_at_unix: i64,
+}
+
+fn build_ffi_refresh_summary(
+    results: Vec<ManagerRefreshResult>,
+    metered_network: bool,
+    metered_suppressions: Vec<ManagerId>,
+    quarantine_skips: Vec<ManagerId>,
+) -> FfiRefreshSummary {
+    let unix = |time: SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0)
+    };
+    let failed = results.iter().filter(|result| !result.succeeded()).count();
+    FfiRefreshSummary {
+        run_started_at_unix: results.first().map(|result| unix(result.run_started_at)),
+        succeeded: results.len() - failed,
+        failed,
+        managers: results
+            .into_iter()
+            .map(|result| FfiManagerRefreshResult {
+                manager_id: result.manager.as_str().to_string(),
+                succeeded: result.error.is_none(),
+                error_key: result
+                    .error
+                    .as_ref()
+                    .map(|error| core_error_kind_service_key(error.kind).to_string()),
+                error_code: result
+                    .error
+                    .as_ref()
+                    .map(|error| error.kind.code().to_string()),
+                duration_ms: result.duration.as_millis() as u64,
+                finished_at_unix: unix(result.finished_at),
+                error_message: result.error.map(|error| error.message),
+            })
+            .collect(),
+        metered_network,
+        suppressed_on_metered_network: metered_suppressions
+            .into_iter()
+            .map(|manager| manager.as_str().to_string())
+            .collect(),
+        skipped_while_quarantined: quarantine_skips
+            .into_iter()
+            .map(|manager| manager.as_str().to_string())
+            .collect(),
+    }
+}
+
+/// Tasks queued by the most recent upgrade-all run. Each step keeps the last
+/// record seen for it, since terminal rows are pruned from `task_records`
+/// while a long batch is still running.
+#[derive(Debug)]
+struct BulkOperationSession {
+    started_at: SystemTime,
+    steps: std::collections::BTreeMap<u64, Option<TaskRecord>>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiBulkOperationProgress {
+    started_at_unix: Option<i64>,
+    total_steps: usize,
+    completed: usize,
+    failed: usize,
+    cancelled: usize,
+    running: usize,
+    queued: usize,
+    /// Finished steps over total steps; 0 when no batch has run.
+    fraction_complete: f64,
+    running_steps: Vec<FfiBulkOperationRunningStep>,
+    /// Estimated seconds until the batch finishes, from archived upgrade
+    /// durations. `None` when no upgrade has been archived yet.
+    eta_seconds: Option<u64>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiBulkOperationRunningStep {
+    task_id: u64,
+    manager_id: String,
+    label_key: Option<String>,
+    label_args: std::collections::BTreeMap<String, String>,
+}
+
+fn begin_bulk_operation() {
+    *lock_or_recover(&BULK_OPERATION, "bulk_operation") = Some(BulkOperationSession {
+        started_at: SystemTime::now(),
+        steps: std::collections::BTreeMap::new(),
+    });
+}
+
+fn track_bulk_operation_task(task_id: TaskId) {
+    if let Some(session) = lock_or_recover(&BULK_OPERATION, "bulk_operation").as_mut() {
+        session.steps.insert(task_id.0, None);
+    }
+}
+
+/// Resolve every step of `session` to its current record, preferring the live
+/// row, then the archived outcome, then the last record seen. A step with
+/// neither was dismissed and counts as cancelled; one never seen is left out.
+fn resolve_bulk_operation_steps(
+    session: &mut BulkOperationSession,
+    recent: &[TaskRecord],
+    history: &[TaskHistoryRecord],
+) -> Vec<TaskRecord> {
+    let mut resolved = Vec::with_capacity(session.steps.len());
+    for (task_id, last_seen) in session.steps.iter_mut() {
+        if let Some(record) = recent.iter().find(|task| task.id.0 == *task_id) {
+            *last_seen = Some(record.clone());
+        } else if let Some(entry) = history.iter().find(|entry| entry.task_id.0 == *task_id) {
+            let mut record = last_seen.clone().unwrap_or(TaskRecord {
+                id: entry.task_id,
+                manager: entry.manager,
+                task_type: entry.task_type,
+                status: entry.status,
+                created_at: entry.finished_at,
+                attempt_of: None,
+                label: None,
+            });
+            record.status = entry.status;
+            *last_seen = Some(record);
+        } else if let Some(record) = last_seen.as_mut()
+            && is_inflight_status(record.status)
+        {
+            record.status = TaskStatus::Cancelled;
+        }
+        if let Some(record) = last_seen {
+            resolved.push(record.clone());
+        }
+    }
+    resolved
+}
+
+fn build_ffi_bulk_operation_progress(
+    started_at: Option<SystemTime>,
+    steps: &[TaskRecord],
+    history: &[TaskHistoryRecord],
+    now: SystemTime,
+) -> FfiBulkOperationProgress {
+    let count = |status: TaskStatus| steps.iter().filter(|task| task.status == status).count();
+    let (completed, failed, cancelled) = (
+        count(TaskStatus::Completed),
+        count(TaskStatus::Failed),
+        count(TaskStatus::Cancelled),
+    );
+    let finished = completed + failed + cancelled;
+
+    let mut durations: std::collections::HashMap<ManagerId, (u64, u64)> =
+        std::collections::HashMap::new();
+    let mut overall = (0u64, 0u64);
+    for entry in history {
+        if entry.status != TaskStatus::Completed {
+            continue;
+        }
+        let Some(duration_ms) = entry.duration_ms else {
+            continue;
+        };
+        let totals = durations.entry(entry.manager).or_default();
+        totals.0 = totals.0.saturating_add(duration_ms);
+        totals.1 += 1;
+        overall.0 = overall.0.saturating_add(duration_ms);
+        overall.1 += 1;
+    }
+    let average_ms = |manager: ManagerId| {
+        let (total, samples) = durations.get(&manager).copied().unwrap_or(overall);
+        (samples > 0).then(|| total / samples)
+    };
+
+    // Managers run their tasks one at a time but in parallel with each other,
+    // so the batch ends when the most loaded manager drains.
+    let mut remaining_ms: std::collections::HashMap<ManagerId, u64> =
+        std::collections::HashMap::new();
+    let mut eta_known = overall.1 > 0;
+    for task in steps.iter().filter(|task| is_inflight_status(task.status)) {
+        let Some(average) = average_ms(task.manager) else {
+            eta_known = false;
+            continue;
+        };
+        let elapsed_ms = if task.status == TaskStatus::Running {
+            now.duration_since(task.created_at)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let remaining = remaining_ms.entry(task.manager).or_default();
+        *remaining = remaining.saturating_add(average.saturating_sub(elapsed_ms));
+    }
+
+    FfiBulkOperationProgress {
+        started_at_unix: started_at.map(|time| {
+            time.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0)
+        }),
+        total_steps: steps.len(),
+        completed,
+        failed,
+        cancelled,
+        running: count(TaskStatus::Running),
+        queued: count(TaskStatus::Queued),
+        fraction_complete: if steps.is_empty() {
+            0.0
+        } else {
+            finished as f64 / steps.len() as f64
+        },
+        running_steps: steps
+            .iter()
+            .filter(|task| task.status == TaskStatus::Running)
+            .map(|task| FfiBulkOperationRunningStep {
+                task_id: task.id.0,
+                manager_id: task.manager.as_str().to_string(),
+                label_key: task.label.as_ref().map(|label| label.key.clone()),
+                label_args: task
+                    .label
+                    .as_ref()
+                    .map(|label| label.args.clone())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        eta_seconds: eta_known.then(|| {
+            remaining_ms
+                .values()
+                .copied()
+                .max()
+                .unwrap_or(0)
+                .div_ceil(1000)
+        }),
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ManagerUninstallPlan {
     target_manager: ManagerId,
@@ -715,26 +1208,110 @@ type ManagerUninstallLegacyRequest = (
     Vec<(&'static str, String)>,
 );
 
+#[derive(Clone, Debug, Default)]
+struct ManagerSizeCacheEntry {
+    roots: Vec<std::path::PathBuf>,
+    size_bytes: Option<u64>,
+    computed_at: Option<Instant>,
+    refreshing: bool,
+}
+
+#[derive(Default)]
+struct ManagerVersionProbeEntry {
+    executable_path: Option<std::path::PathBuf>,
+    version: Option<String>,
+    probed_at: Option<Instant>,
+    probing: bool,
+}
+
+/// User-visible installed and outdated rows for enabled managers, served to the
+/// package list calls without touching SQLite or `STATE`.
+///
+/// Only the package lists are cached. Tasks, manager status, and local search
+/// mix in live runtime state or per-query results, so those calls still read
+/// SQLite under `STATE`.
+#[derive(Debug, Default)]
+struct FfiPackageSnapshot {
+    installed: Vec<InstalledPackage>,
+    outdated: Vec<OutdatedPackage>,
+}
+
+/// Rebuilds the package snapshot cache whenever the runtime persists new data.
+///
+/// The engine owns the runtime that owns this listener, so it holds the engine
+/// weakly; it is set once the engine is built.
+struct PackageSnapshotCacheListener {
+    store: Arc<SqliteStore>,
+    engine: OnceLock<Weak<helm_engine::Engine>>,
+}
+
+impl PersistenceListener for PackageSnapshotCacheListener {
+    fn snapshot_persisted(&self, _manager: ManagerId) {
+        let Some(engine) = self.engine.get().and_then(Weak::upgrade) else {
+            PACKAGE_SNAPSHOT_CACHE.invalidate();
+            return;
+        };
+        if let Err(error) = rebuild_package_snapshot_cache(&engine, self.store.as_ref()) {
+            eprintln!("helm-ffi: failed to rebuild package snapshot cache: {error}");
+            PACKAGE_SNAPSHOT_CACHE.invalidate();
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct ManagerAutomationPolicyContext {
     mode: ManagedAutomationPolicyMode,
 }
 
+/// Discovered executable paths for one manager and when they were last
+/// confirmed against the filesystem.
+#[derive(Clone, Debug)]
+struct ExecutableDiscoveryCacheEntry {
+    paths: Vec<String>,
+    validated_at: SystemTime,
+}
+
 static EXECUTABLE_DISCOVERY_CACHE: OnceLock<
-    Mutex<std::collections::HashMap<ManagerId, Vec<String>>>,
+    Mutex<std::collections::HashMap<ManagerId, ExecutableDiscoveryCacheEntry>>,
 > = OnceLock::new();
+/// Store the discovery cache was hydrated from; discoveries are written back
+/// to it so they survive a restart.
+static EXECUTABLE_DISCOVERY_STORE: Mutex<Option<Weak<SqliteStore>>> = Mutex::new(None);
+static EXECUTABLE_DISCOVERY_REVALIDATING: OnceLock<Mutex<std::collections::HashSet<ManagerId>>> =
+    OnceLock::new();
 static EXECUTABLE_DISCOVERY_INVALIDATED_TASKS: OnceLock<Mutex<std::collections::HashSet<u64>>> =
     OnceLock::new();
 static MANAGER_AUTOMATION_POLICY_CONTEXT: OnceLock<ManagerAutomationPolicyContext> =
     OnceLock::new();
+static MANAGER_SIZE_CACHE: OnceLock<
+    Mutex<std::collections::HashMap<ManagerId, ManagerSizeCacheEntry>>,
+> = OnceLock::new();
+static MANAGER_VERSION_PROBE_CACHE: OnceLock<
+    Mutex<std::collections::HashMap<ManagerId, ManagerVersionProbeEntry>>,
+> = OnceLock::new();
 static COORDINATOR_REQUEST_COUNTER: AtomicU64 = AtomicU64::new(1);
 static COORDINATOR_SERVER_STARTED: AtomicBool = AtomicBool::new(false);
 static AUTO_CHECK_TICKER_STARTED: AtomicBool = AtomicBool::new(false);
+static NETWORK_RECOVERY_TICKER_STARTED: AtomicBool = AtomicBool::new(false);
+static REFRESH_SCHEDULE_TICKER_STARTED: AtomicBool = AtomicBool::new(false);
+/// Bumped by `helm_shutdown`; threads bound to one engine exit when it changes.
+static ENGINE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static PACKAGE_SNAPSHOT_CACHE: SnapshotCache<FfiPackageSnapshot> = SnapshotCache::new();
 
 const COORDINATOR_REQUEST_TIMEOUT_SECS: u64 = 30;
 const COORDINATOR_POLL_SLEEP_MS: u64 = 25;
 const AUTO_CHECK_TICK_SECS: u64 = 30;
+const NETWORK_RECOVERY_POLL_SECS: u64 = 20;
+const REFRESH_SCHEDULE_TICK_SECS: u64 = 30;
+const SHUTDOWN_TASK_GRACE_MS: u64 = 500;
+const SHUTDOWN_TASK_WAIT_SECS: u64 = 5;
+const SHUTDOWN_RUNTIME_TIMEOUT_SECS: u64 = 5;
+const REACHABILITY_PROBE_TIMEOUT_SECS: u64 = 5;
 const EXECUTABLE_CACHE_INVALIDATION_MAX_AGE_SECS: u64 = 600;
+const EXECUTABLE_DISCOVERY_REVALIDATE_AFTER_SECS: u64 = 3_600;
+const MANAGER_SIZE_CACHE_TTL_SECS: u64 = 900;
+const MANAGER_VERSION_PROBE_TTL_SECS: u64 = 60;
+const PACKAGE_METADATA_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
 #[cfg(any(test, target_os = "macos"))]
 const LEGACY_FILE_COORDINATOR_IPC_ENV: &str = "HELM_LEGACY_FILE_COORDINATOR_IPC";
 const DEFAULT_CLI_UPDATE_ENDPOINT: &str = "https://helmapp.dev/updates/cli/latest.json";
@@ -859,16 +1436,26 @@ enum CoordinatorSubmitRequest {
         package_name: String,
         target_name: Option<String>,
         version: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        options: Vec<String>,
     },
     Uninstall {
         package_name: String,
         target_name: Option<String>,
         version: Option<String>,
+        #[serde(default)]
+        disposal: UninstallDisposal,
     },
     Upgrade {
         package_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<UpgradeTargetKind>,
         target_name: Option<String>,
         version: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        options: Vec<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cleanup_old_kegs: bool,
     },
     Pin {
         package_name: String,
@@ -878,6 +1465,16 @@ enum CoordinatorSubmitRequest {
         package_name: String,
         version: Option<String>,
     },
+    Rollback {
+        package_name: String,
+        version: String,
+    },
+    SetServiceState {
+        service: String,
+        action: ServiceAction,
+    },
+    Cleanup,
+    MeasurePackageSizes,
     RustupAddComponent {
         toolchain: String,
         component: String,
@@ -908,6 +1505,10 @@ enum CoordinatorSubmitRequest {
     RustupSetProfile {
         profile: String,
     },
+    HomebrewAddTap {
+        tap: String,
+        url: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -950,6 +1551,8 @@ enum CoordinatorPayload {
         action: String,
         before_version: Option<String>,
         after_version: Option<String>,
+        #[serde(default)]
+        disposal: Option<UninstallDisposal>,
     },
     Refreshed,
     InstalledPackages {
@@ -1198,27 +1801,7 @@ fn manager_additional_bin_roots() -> Vec<std::path::PathBuf> {
         std::path::PathBuf::from("/usr/local/bin"),
         std::path::PathBuf::from("/opt/local/bin"),
     ];
-
-    if let Some(cargo_home) = absolute_env_path("CARGO_HOME") {
-        roots.push(cargo_home.join("bin"));
-    }
-    if let Some(home) = std::env::var_os("HOME").map(std::path::PathBuf::from) {
-        roots.push(home.join(".local/bin"));
-        roots.push(home.join(".cargo/bin"));
-        roots.push(home.join(".asdf/bin"));
-        roots.push(home.join(".asdf/shims"));
-        roots.push(home.join(".local/share/mise/shims"));
-        roots.push(home.join(".local/share/rtx/shims"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("bin"));
-        roots.push(path.join("shims"));
-    }
-
+    roots.extend(ToolBaseDirs::from_env().tool_bin_roots());
     roots
 }
 
@@ -1263,20 +1846,57 @@ fn manager_versioned_install_roots(id: ManagerId) -> Vec<std::path::PathBuf> {
             | ManagerId::Bundler
             | ManagerId::Cargo
             | ManagerId::CargoBinstall
-    ) && let Some(home) = std::env::var_os("HOME").map(std::path::PathBuf::from)
-    {
-        roots.push(home.join(".asdf/installs"));
-        roots.push(home.join(".local/share/mise/installs"));
-        roots.push(home.join(".local/share/rtx/installs"));
+    ) {
+        roots.extend(ToolBaseDirs::from_env().tool_version_install_roots());
+    }
+
+    roots
+}
+
+/// Directories scanned for Python interpreters: the search path, the shared
+/// tool bin roots, versioned tool installs, and pyenv's `versions/*/bin`.
+fn python_interpreter_dirs() -> Vec<std::path::PathBuf> {
+    let mut dirs: Vec<std::path::PathBuf> = std::env::var_os("PATH")
+        .as_deref()
+        .map(std::env::split_paths)
+        .map(|iter| iter.collect())
+        .unwrap_or_default();
+    dirs.extend(manager_additional_bin_roots());
+
+    let mut version_parents: Vec<std::path::PathBuf> = Vec::new();
+    for root in manager_versioned_install_roots(ManagerId::Pip) {
+        let Ok(tool_dirs) = std::fs::read_dir(root) else {
+            continue;
+        };
+        version_parents.extend(tool_dirs.flatten().map(|entry| entry.path()));
     }
-    if let Some(path) = absolute_env_path("ASDF_DIR") {
-        roots.push(path.join("installs"));
+    let pyenv_root = std::env::var_os("PYENV_ROOT")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".pyenv"))
+        });
+    if let Some(pyenv_root) = pyenv_root {
+        version_parents.push(pyenv_root.join("versions"));
     }
-    if let Some(path) = absolute_env_path("ASDF_DATA_DIR") {
-        roots.push(path.join("installs"));
+    for parent in version_parents {
+        let Ok(version_dirs) = std::fs::read_dir(parent) else {
+            continue;
+        };
+        dirs.extend(version_dirs.flatten().map(|entry| entry.path().join("bin")));
     }
+    dirs
+}
 
-    roots
+/// Whether `interpreter` is one of the Python interpreters discovered on this
+/// machine, either by path or by the file it resolves to.
+fn is_discovered_python_interpreter(interpreter: &std::path::Path) -> bool {
+    let resolved = std::fs::canonicalize(interpreter).ok();
+    helm_core::adapters::pip::discover_python_interpreters(&python_interpreter_dirs())
+        .into_iter()
+        .any(|candidate| {
+            candidate == interpreter
+                || (resolved.is_some() && std::fs::canonicalize(&candidate).ok() == resolved)
+        })
 }
 
 fn push_discovered_path(
@@ -1349,25 +1969,149 @@ fn discover_executable_paths(id: ManagerId, candidates: &[&str]) -> Vec<String>
     discovered
 }
 
-fn cached_discovered_executable_paths(id: ManagerId, candidates: &[&str]) -> Vec<String> {
+fn cached_discovered_executable_paths(
+    id: ManagerId,
+    candidates: &'static [&'static str],
+) -> Vec<String> {
     let cache =
         EXECUTABLE_DISCOVERY_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
 
-    if let Ok(mut guard) = cache.lock() {
-        if let Some(cached) = guard.get(&id).cloned()
-            && cached
-                .iter()
-                .all(|path| std::path::Path::new(path).is_file())
-        {
-            return cached;
+    let Ok(mut guard) = cache.lock() else {
+        return discover_executable_paths(id, candidates);
+    };
+    if let Some(cached) = guard.get(&id).cloned()
+        && cached
+            .paths
+            .iter()
+            .all(|path| std::path::Path::new(path).is_file())
+    {
+        drop(guard);
+        if executable_discovery_is_stale(cached.validated_at) {
+            spawn_executable_discovery_revalidation(id, candidates);
         }
+        return cached.paths;
+    }
+
+    let discovered = discover_executable_paths(id, candidates);
+    guard.insert(
+        id,
+        ExecutableDiscoveryCacheEntry {
+            paths: discovered.clone(),
+            validated_at: SystemTime::now(),
+        },
+    );
+    drop(guard);
+    persist_discovered_executable_paths(id, discovered.clone());
+    discovered
+}
+
+fn executable_discovery_is_stale(validated_at: SystemTime) -> bool {
+    SystemTime::now()
+        .duration_since(validated_at)
+        .map(|elapsed| elapsed.as_secs() >= EXECUTABLE_DISCOVERY_REVALIDATE_AFTER_SECS)
+        .unwrap_or(false)
+}
+
+/// Serve persisted discovery results until they are revalidated, so status
+/// does not re-walk the search paths after every restart.
+fn hydrate_executable_discovery_cache(store: &Arc<SqliteStore>) {
+    *lock_or_recover(&EXECUTABLE_DISCOVERY_STORE, "executable_discovery_store") =
+        Some(Arc::downgrade(store));
 
+    let mut entries: std::collections::HashMap<ManagerId, ExecutableDiscoveryCacheEntry> =
+        std::collections::HashMap::new();
+    for candidate in store
+        .list_manager_executable_candidates(None)
+        .unwrap_or_default()
+    {
+        let entry =
+            entries
+                .entry(candidate.manager)
+                .or_insert_with(|| ExecutableDiscoveryCacheEntry {
+                    paths: Vec::new(),
+                    validated_at: candidate.last_validated_at,
+                });
+        entry.validated_at = entry.validated_at.min(candidate.last_validated_at);
+        entry.paths.push(candidate.path);
+    }
+
+    let cache =
+        EXECUTABLE_DISCOVERY_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+    let mut guard = lock_or_recover(cache, "executable_discovery_cache");
+    guard.clear();
+    guard.extend(entries);
+}
+
+/// Rediscover a manager's executables off the calling thread, replacing the
+/// cached entry only once the walk finishes.
+fn spawn_executable_discovery_revalidation(id: ManagerId, candidates: &'static [&'static str]) {
+    let revalidating = EXECUTABLE_DISCOVERY_REVALIDATING
+        .get_or_init(|| Mutex::new(std::collections::HashSet::new()));
+    if !lock_or_recover(revalidating, "executable_discovery_revalidating").insert(id) {
+        return;
+    }
+
+    thread::spawn(move || {
         let discovered = discover_executable_paths(id, candidates);
-        guard.insert(id, discovered.clone());
-        return discovered;
+        let cache =
+            EXECUTABLE_DISCOVERY_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+        lock_or_recover(cache, "executable_discovery_cache").insert(
+            id,
+            ExecutableDiscoveryCacheEntry {
+                paths: discovered.clone(),
+                validated_at: SystemTime::now(),
+            },
+        );
+        write_discovered_executable_paths(id, &discovered);
+        lock_or_recover(revalidating, "executable_discovery_revalidating").remove(&id);
+    });
+}
+
+/// Callers often hold the FFI state lock, so the store write happens on its
+/// own thread.
+fn persist_discovered_executable_paths(id: ManagerId, discovered: Vec<String>) {
+    if lock_or_recover(&EXECUTABLE_DISCOVERY_STORE, "executable_discovery_store").is_none() {
+        return;
     }
+    thread::spawn(move || write_discovered_executable_paths(id, &discovered));
+}
+
+fn write_discovered_executable_paths(id: ManagerId, discovered: &[String]) {
+    let Some(store) = lock_or_recover(&EXECUTABLE_DISCOVERY_STORE, "executable_discovery_store")
+        .as_ref()
+        .and_then(Weak::upgrade)
+    else {
+        return;
+    };
 
-    discover_executable_paths(id, candidates)
+    let detected = store
+        .list_detections()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|(manager, _)| *manager == id)
+        .map(|(_, info)| info);
+    let validated_at = SystemTime::now();
+    let candidates = discovered
+        .iter()
+        .map(|path| ManagerExecutableCandidate {
+            manager: id,
+            path: path.clone(),
+            version: detected
+                .as_ref()
+                .filter(|info| {
+                    info.executable_path
+                        .as_deref()
+                        .and_then(normalize_path_string)
+                        .as_deref()
+                        == Some(path.as_str())
+                })
+                .and_then(|info| info.version.clone()),
+            last_validated_at: validated_at,
+        })
+        .collect::<Vec<_>>();
+    if let Err(error) = store.replace_manager_executable_candidates(id, &candidates) {
+        eprintln!("helm-ffi: failed to persist executable discovery for {id:?}: {error}");
+    }
 }
 
 fn build_manager_executable_doctor_states(
@@ -1510,6 +2254,8 @@ fn sync_manager_executable_overrides(
         std::collections::HashMap::new();
     let mut timeout_profiles: std::collections::HashMap<ManagerId, ManagerTimeoutProfile> =
         std::collections::HashMap::new();
+    let mut process_qos: std::collections::HashMap<ManagerId, ProcessQos> =
+        std::collections::HashMap::new();
     for manager in ManagerId::ALL {
         let selected = resolved_manager_selected_executable_path(manager, detection_map, pref_map);
         if let Some(path) = selected {
@@ -1532,21 +2278,72 @@ fn sync_manager_executable_overrides(
         if profile.hard_timeout.is_some() || profile.idle_timeout.is_some() {
             timeout_profiles.insert(manager, profile);
         }
+        if let Some(qos) = pref_map
+            .get(&manager)
+            .and_then(|preference| preference.process_qos.as_deref())
+            .and_then(|value| value.parse::<ProcessQos>().ok())
+        {
+            process_qos.insert(manager, qos);
+        }
     }
-    replace_manager_execution_preferences(executable_overrides, timeout_profiles);
+    replace_manager_execution_preferences(executable_overrides, timeout_profiles, process_qos);
 }
 
-fn build_manager_statuses(
-    runtime: Option<&AdapterRuntime>,
-    store: Option<&SqliteStore>,
-    detection_map: &std::collections::HashMap<ManagerId, DetectionInfo>,
-    pref_map: &std::collections::HashMap<ManagerId, ManagerPreference>,
-) -> Vec<FfiManagerStatus> {
-    if let Some(store) = store {
-        invalidate_executable_cache_for_recent_manager_lifecycle_tasks(store);
-    }
+const HOMEBREW_ENV_MANAGERS: [ManagerId; 2] = [ManagerId::HomebrewFormula, ManagerId::HomebrewCask];
 
-    let mut install_instances_by_manager: std::collections::HashMap<
+fn sync_homebrew_env_overrides(store: &SqliteStore) {
+    let settings = store.list_manager_env_settings().unwrap_or_default();
+    for manager in HOMEBREW_ENV_MANAGERS {
+        set_homebrew_env_overrides(
+            manager,
+            HomebrewEnvOverrides::from_settings(manager, &settings),
+        );
+    }
+}
+
+fn sync_homebrew_cask_settings(store: &SqliteStore) {
+    set_exclude_auto_updating_casks(store.homebrew_cask_exclude_auto_updates().unwrap_or(true));
+}
+
+fn sync_npm_settings(store: &SqliteStore) {
+    helm_core::adapters::npm_registry::set_npm_registry_outdated_checks(
+        store.npm_registry_outdated_checks().unwrap_or(false),
+    );
+}
+
+fn sync_network_policy_settings(store: &SqliteStore) {
+    helm_core::network_policy::set_allow_heavy_network_when_metered(
+        store.allow_heavy_network_when_metered().unwrap_or(false),
+    );
+}
+
+fn sync_command_timing_settings(store: &SqliteStore) {
+    helm_core::execution::set_slow_command_tracing_enabled(
+        store.slow_command_tracing().unwrap_or(false),
+    );
+}
+
+fn sync_process_qos_settings(store: &SqliteStore) {
+    set_default_process_qos(
+        store
+            .process_qos()
+            .ok()
+            .and_then(|value| value.parse::<ProcessQos>().ok())
+            .unwrap_or_default(),
+    );
+}
+
+fn build_manager_statuses(
+    runtime: Option<&AdapterRuntime>,
+    store: Option<&SqliteStore>,
+    detection_map: &std::collections::HashMap<ManagerId, DetectionInfo>,
+    pref_map: &std::collections::HashMap<ManagerId, ManagerPreference>,
+) -> Vec<FfiManagerStatus> {
+    if let Some(store) = store {
+        invalidate_executable_cache_for_recent_manager_lifecycle_tasks(store);
+    }
+
+    let mut install_instances_by_manager: std::collections::HashMap<
         ManagerId,
         Vec<ManagerInstallInstance>,
     > = std::collections::HashMap::new();
@@ -1579,21 +2376,63 @@ fn build_manager_statuses(
         }
     }
 
-    let homebrew_installed_formulas: std::collections::HashSet<String> = store
+    let installed_packages = store
         .and_then(|store| store.list_installed().ok())
-        .map(|packages| {
-            packages
-                .into_iter()
-                .filter(|package| package.package.manager == ManagerId::HomebrewFormula)
-                .filter_map(|package| {
-                    let name = package.package.name.trim().to_ascii_lowercase();
-                    (!name.is_empty()).then_some(name)
-                })
-                .collect()
-        })
         .unwrap_or_default();
+    let outdated_packages = store
+        .and_then(|store| store.list_outdated().ok())
+        .unwrap_or_default();
+    let package_counts = helm_core::manager_stats::count_packages_by_manager(
+        &installed_packages,
+        &outdated_packages,
+    );
+    let self_versions: std::collections::HashMap<ManagerId, helm_core::models::ManagerSelfVersion> =
+        store
+            .and_then(|store| store.list_manager_self_versions().ok())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+    let snapshot_freshness: std::collections::HashMap<
+        ManagerId,
+        helm_core::models::PackageSnapshotFreshness,
+    > = store
+        .and_then(|store| store.list_package_snapshot_freshness().ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|freshness| (freshness.manager, freshness))
+        .collect();
+    let quarantines: std::collections::HashMap<ManagerId, FfiManagerQuarantine> = store
+        .and_then(|store| helm_core::orchestration::quarantine::quarantined_managers(store).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|quarantine| {
+            (
+                quarantine.manager,
+                FfiManagerQuarantine {
+                    consecutive_failures: quarantine.consecutive_failures,
+                    reason_kind: quarantine.reason_kind.code(),
+                    reason: quarantine.reason,
+                    quarantined_since_unix: quarantine.quarantined_since_unix,
+                    next_probe_unix: quarantine.next_probe_unix,
+                },
+            )
+        })
+        .collect();
+    let homebrew_installed_formulas: std::collections::HashSet<String> = installed_packages
+        .iter()
+        .filter(|package| package.package.manager == ManagerId::HomebrewFormula)
+        .filter_map(|package| {
+            let name = package.package.name.trim().to_ascii_lowercase();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect();
     let manager_executable_doctor_states =
         build_manager_executable_doctor_states(detection_map, pref_map);
+    let detection_outcomes: std::collections::HashMap<ManagerId, DetectionOutcome> = store
+        .and_then(|store| store.list_detection_outcomes().ok())
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
 
     ManagerId::ALL
         .iter()
@@ -1618,10 +2457,16 @@ fn build_manager_statuses(
                 .get(&id)
                 .and_then(|pref| pref.timeout_idle_seconds)
                 .filter(|value| *value > 0);
+            let process_qos = pref_map.get(&id).and_then(|pref| pref.process_qos.clone());
             let is_implemented = is_implemented_manager(id);
             let is_optional = is_optional_manager(id);
             let is_detection_only = is_detection_only_manager(id);
             let detected = detection.map(|d| d.installed).unwrap_or(false);
+            let detection_outcome = detection_outcomes.get(&id).cloned().unwrap_or(if detected {
+                DetectionOutcome::Installed
+            } else {
+                DetectionOutcome::NotFound
+            });
             let executable_path = detection.and_then(|d| {
                 normalize_nonempty(
                     d.executable_path
@@ -1668,6 +2513,12 @@ fn build_manager_statuses(
             let supports_package_upgrade = runtime
                 .map(|runtime| supports_individual_package_upgrade(runtime, id))
                 .unwrap_or(false);
+            let supports_package_rollback = runtime
+                .map(|runtime| {
+                    runtime.is_manager_enabled(id)
+                        && runtime.supports_capability(id, Capability::Rollback)
+                })
+                .unwrap_or(false);
             let manager_install_instances = install_instances_by_manager.get(&id);
             let install_instance_count = manager_install_instances.map_or(0, Vec::len);
             let install_instances = manager_install_instances
@@ -1703,10 +2554,32 @@ fn build_manager_statuses(
                 issue.issue_code == helm_core::doctor::ISSUE_CODE_POST_INSTALL_SETUP_REQUIRED
             });
             let enabled = configured_enabled && eligibility.is_eligible && !setup_required;
+            let counts = package_counts.get(&id).copied().unwrap_or_default();
+            let size_on_disk_bytes = if detected {
+                selected_executable_path
+                    .as_deref()
+                    .or(executable_path.as_deref())
+                    .and_then(|path| cached_manager_size_on_disk(id, std::path::Path::new(path)))
+            } else {
+                None
+            };
+            let self_update = if detected {
+                helm_core::manager_self_update::resolve_manager_self_update_status(
+                    id,
+                    active_instance,
+                    version.as_deref(),
+                    self_versions.get(&id),
+                    &outdated_packages,
+                )
+            } else {
+                Default::default()
+            };
 
             FfiManagerStatus {
                 manager_id: id.as_str().to_string(),
                 detected,
+                detection_outcome: detection_outcome.as_str(),
+                detection_error_key: detection_outcome.error_key().map(str::to_string),
                 version,
                 executable_path,
                 executable_paths,
@@ -1718,6 +2591,7 @@ fn build_manager_statuses(
                 install_method_options,
                 timeout_hard_seconds,
                 timeout_idle_seconds,
+                process_qos,
                 enabled,
                 is_implemented,
                 is_optional,
@@ -1726,6 +2600,7 @@ fn build_manager_statuses(
                 supports_package_install,
                 supports_package_uninstall,
                 supports_package_upgrade,
+                supports_package_rollback,
                 package_state_issues,
                 is_eligible: eligibility.is_eligible,
                 ineligible_reason_code: eligibility.reason_code.map(str::to_string),
@@ -1761,11 +2636,145 @@ fn build_manager_statuses(
                 }),
                 competing_confidence: active_instance
                     .and_then(|instance| instance.competing_confidence),
+                installed_package_count: counts.installed,
+                outdated_package_count: counts.outdated,
+                size_on_disk_bytes,
+                self_latest_version: self_update
+                    .self_version
+                    .as_ref()
+                    .and_then(|self_version| self_version.latest_version.clone()),
+                self_update_available: self_update.update_available(),
+                supports_manager_update: self_update.supports_manager_update,
+                snapshot_partial: snapshot_freshness
+                    .get(&id)
+                    .is_some_and(|freshness| freshness.partial),
+                installed_snapshot_partial: snapshot_freshness
+                    .get(&id)
+                    .is_some_and(|freshness| freshness.installed_partial),
+                outdated_snapshot_partial: snapshot_freshness
+                    .get(&id)
+                    .is_some_and(|freshness| freshness.outdated_partial),
+                network_capabilities: helm_core::registry::manager_network_capabilities(id)
+                    .into_iter()
+                    .map(|capability| capability.as_str())
+                    .collect(),
+                quarantine: quarantines.get(&id).cloned(),
+                detection_extras: detection
+                    .filter(|info| info.installed)
+                    .map(|info| helm_core::adapters::detection_extras(id, info))
+                    .unwrap_or_default(),
+                probing: false,
             }
         })
         .collect()
 }
 
+/// Return the last computed on-disk size for a manager's package roots.
+///
+/// Directory walks can take seconds for large Cellars, so a stale or missing
+/// entry is refreshed on a background thread and the previous value (or none)
+/// is returned immediately.
+fn cached_manager_size_on_disk(
+    manager: ManagerId,
+    executable_path: &std::path::Path,
+) -> Option<u64> {
+    let roots = helm_core::manager_stats::manager_size_roots(manager, executable_path);
+    if roots.is_empty() {
+        return None;
+    }
+
+    let cache = MANAGER_SIZE_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+    let mut guard = cache.lock().ok()?;
+    let entry = guard.entry(manager).or_default();
+    if entry.roots != roots {
+        *entry = ManagerSizeCacheEntry {
+            roots: roots.clone(),
+            ..ManagerSizeCacheEntry::default()
+        };
+    }
+    let stale = entry.computed_at.is_none_or(|computed_at| {
+        computed_at.elapsed() >= Duration::from_secs(MANAGER_SIZE_CACHE_TTL_SECS)
+    });
+    if stale && !entry.refreshing {
+        entry.refreshing = true;
+        thread::spawn(move || {
+            let size_bytes = helm_core::manager_stats::directory_size_bytes(&roots);
+            let cache =
+                MANAGER_SIZE_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+            if let Ok(mut guard) = cache.lock()
+                && let Some(entry) = guard.get_mut(&manager)
+                && entry.roots == roots
+            {
+                entry.size_bytes = size_bytes;
+                entry.computed_at = Some(Instant::now());
+                entry.refreshing = false;
+            }
+        });
+    }
+    entry.size_bytes
+}
+
+/// Return the last probed version for a manager whose detection is incomplete,
+/// and whether a probe is still running.
+///
+/// `brew --version` and `brew config` can take seconds on a cold Homebrew, so
+/// concurrent status calls share one background probe per manager and reuse
+/// its result for `MANAGER_VERSION_PROBE_TTL_SECS`. A successful probe is
+/// persisted as the manager's detection so later calls skip probing entirely.
+fn cached_manager_version_probe(
+    store: &Arc<SqliteStore>,
+    manager: ManagerId,
+    executable_path: Option<std::path::PathBuf>,
+    probe: fn(Option<&std::path::Path>) -> Option<String>,
+) -> (Option<String>, bool) {
+    let cache =
+        MANAGER_VERSION_PROBE_CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+    let Ok(mut guard) = cache.lock() else {
+        return (None, false);
+    };
+    let entry = guard.entry(manager).or_default();
+    if entry.executable_path != executable_path {
+        *entry = ManagerVersionProbeEntry {
+            executable_path: executable_path.clone(),
+            ..ManagerVersionProbeEntry::default()
+        };
+    }
+    let stale = entry.probed_at.is_none_or(|probed_at| {
+        probed_at.elapsed() >= Duration::from_secs(MANAGER_VERSION_PROBE_TTL_SECS)
+    });
+    if stale && !entry.probing {
+        entry.probing = true;
+        let store = Arc::clone(store);
+        thread::spawn(move || {
+            let version = probe(executable_path.as_deref());
+            if let Some(version) = version.clone() {
+                let _ = store.upsert_detection(
+                    manager,
+                    &DetectionInfo {
+                        installed: true,
+                        executable_path: executable_path.clone(),
+                        version: Some(version),
+                    },
+                );
+                invalidate_package_snapshot_cache();
+            }
+            let cache = MANAGER_VERSION_PROBE_CACHE
+                .get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+            if let Ok(mut guard) = cache.lock()
+                && let Some(entry) = guard.get_mut(&manager)
+                && entry.executable_path == executable_path
+            {
+                if version.is_some() {
+                    entry.version = version;
+                }
+                entry.probed_at = Some(Instant::now());
+                entry.probing = false;
+            }
+        });
+    }
+    (entry.version.clone(), entry.probing)
+}
+
 fn manager_package_state_issues(
     manager: ManagerId,
     manager_install_instances: Option<&Vec<ManagerInstallInstance>>,
@@ -1892,18 +2901,77 @@ fn build_install_instance_summary(
     }
 }
 
-fn set_task_label(task_id: helm_core::models::TaskId, key: &str, args: &[(&str, String)]) {
-    let mut args_map = std::collections::BTreeMap::new();
-    for (arg_key, arg_value) in args {
-        args_map.insert((*arg_key).to_string(), arg_value.clone());
+fn task_label(key: &str, args: &[(&str, String)]) -> TaskLabel {
+    TaskLabel {
+        key: key.to_string(),
+        args: args
+            .iter()
+            .map(|(arg_key, arg_value)| ((*arg_key).to_string(), arg_value.clone()))
+            .collect(),
+    }
+}
+
+/// Idempotency keys held by a submission that has not yet bound its key to a
+/// task.
+static IDEMPOTENCY_KEYS_IN_FLIGHT: Mutex<std::collections::BTreeSet<String>> =
+    Mutex::new(std::collections::BTreeSet::new());
+static IDEMPOTENCY_KEY_RELEASED: Condvar = Condvar::new();
+
+/// A submission's hold on its caller-supplied idempotency key, released on
+/// drop. Holds nothing when the caller passed no key.
+struct IdempotencyClaim(Option<String>);
+
+impl Drop for IdempotencyClaim {
+    fn drop(&mut self) {
+        if let Some(key) = self.0.take() {
+            lock_or_recover(&IDEMPOTENCY_KEYS_IN_FLIGHT, "idempotency_keys").remove(&key);
+            IDEMPOTENCY_KEY_RELEASED.notify_all();
+        }
+    }
+}
+
+/// Claim a caller-supplied idempotency key for one submission, or return the
+/// task already queued under it. Hosts reuse the key when retrying a call
+/// whose reply was lost, so the retry resolves to the original task instead of
+/// queueing a duplicate. A call racing another with the same key waits until
+/// the first has bound the key to its task, then resolves to that task.
+fn claim_idempotency_key(
+    store: &SqliteStore,
+    idempotency_key: Option<&str>,
+) -> Result<IdempotencyClaim, TaskId> {
+    let Some(key) = idempotency_key else {
+        return Ok(IdempotencyClaim(None));
+    };
+    {
+        let mut in_flight = lock_or_recover(&IDEMPOTENCY_KEYS_IN_FLIGHT, "idempotency_keys");
+        while in_flight.contains(key) {
+            in_flight = IDEMPOTENCY_KEY_RELEASED
+                .wait(in_flight)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        in_flight.insert(key.to_string());
+    }
+    let claim = IdempotencyClaim(Some(key.to_string()));
+    match store.task_for_idempotency_key(key) {
+        Ok(Some(task)) => Err(task.id),
+        Ok(None) => Ok(claim),
+        Err(error) => {
+            eprintln!("Failed to look up idempotency key: {}", error);
+            Ok(claim)
+        }
+    }
+}
+
+fn set_task_idempotency_key(store: &SqliteStore, task_id: TaskId, claim: &IdempotencyClaim) {
+    let Some(key) = claim.0.as_deref() else {
+        return;
+    };
+    if let Err(error) = store.set_task_idempotency_key(task_id, key) {
+        eprintln!(
+            "Failed to persist idempotency key for task {}: {}",
+            task_id.0, error
+        );
     }
-    lock_or_recover(&TASK_LABELS, "task_labels").insert(
-        task_id.0,
-        TaskLabel {
-            key: key.to_string(),
-            args: args_map,
-        },
-    );
 }
 
 fn append_local_task_log(
@@ -1930,6 +2998,7 @@ fn create_local_task(
     store: &SqliteStore,
     manager: ManagerId,
     task_type: TaskType,
+    label: TaskLabel,
 ) -> Result<TaskId, &'static str> {
     let task_id = store
         .next_task_id()
@@ -1941,6 +3010,8 @@ fn create_local_task(
         task_type,
         status: TaskStatus::Queued,
         created_at: std::time::SystemTime::now(),
+        attempt_of: None,
+        label: Some(label),
     };
     store
         .create_task(&record)
@@ -1972,38 +3043,168 @@ fn update_local_task_status(
         task_type,
         status,
         created_at: std::time::SystemTime::now(),
+        attempt_of: None,
+        label: None,
     };
     let _ = store.update_task(&record);
+    let message = message.into();
+    if matches!(
+        status,
+        TaskStatus::Completed | TaskStatus::Cancelled | TaskStatus::Failed
+    ) {
+        let _ = store.record_task_history(&TaskHistoryRecord {
+            task_id,
+            manager,
+            task_type,
+            status,
+            exit_code: None,
+            error_code: None,
+            error_message: (status != TaskStatus::Completed).then(|| message.clone()),
+            duration_ms: None,
+            stdout_tail: None,
+            stderr_tail: None,
+            finished_at: std::time::SystemTime::now(),
+        });
+    }
     append_local_task_log(store, task_id, manager, task_type, status, level, message);
 }
 
-const TASK_PRUNE_MAX_AGE_SECS: i64 = 300;
-const TASK_RECENT_FETCH_LIMIT: usize = 1000;
-const TASK_TERMINAL_HISTORY_LIMIT: usize = 50;
-const TASK_INFLIGHT_DEDUP_MAX_AGE_SECS: u64 = 1800;
-const CATALOG_SYNC_STALE_AFTER_SECS: i64 = 6 * 60 * 60;
-const STALE_INFLIGHT_TASK_LOG_CONTEXT_STARTUP: &str = "startup_reconciliation";
-const STALE_INFLIGHT_TASK_LOG_CONTEXT_DEDUPE: &str = "inflight_dedupe_check";
-const STALE_INFLIGHT_TASK_LOG_CONTEXT_TRIGGER_GUARD: &str = "trigger_guard";
+lazy_static! {
+    static ref LOCAL_TASK_CANCELLATIONS: Mutex<std::collections::HashMap<u64, Arc<LocalTaskCancellation>>> =
+        Mutex::new(std::collections::HashMap::new());
+}
+
+/// Cancellation state for a composite task that runs on a local thread
+/// rather than in the adapter runtime, so `helm_cancel_task` can reach it.
+#[derive(Default)]
+struct LocalTaskCancellation {
+    requested: AtomicBool,
+    /// Runtime task of the step in flight, cancelled along with the parent.
+    current_step: Mutex<Option<TaskId>>,
+}
+
+impl LocalTaskCancellation {
+    fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Submit one step of the composite task and wait for it, unless the
+    /// task was cancelled first.
+    fn run_step(
+        &self,
+        runtime: &AdapterRuntime,
+        rt_handle: &tokio::runtime::Handle,
+        manager: ManagerId,
+        request: AdapterRequest,
+    ) -> Result<helm_core::adapters::AdapterResponse, String> {
+        let task_id = {
+            let mut current_step = lock_or_recover(&self.current_step, "local_task_step");
+            if self.is_requested() {
+                return Err("cancelled before the step started".to_string());
+            }
+            let task_id = rt_handle
+                .block_on(runtime.submit(manager, request))
+                .map_err(format_core_error)?;
+            *current_step = Some(task_id);
+            task_id
+        };
+        let outcome = wait_for_submitted_request(runtime, rt_handle, task_id);
+        *lock_or_recover(&self.current_step, "local_task_step") = None;
+        outcome
+    }
+}
+
+fn register_local_task_cancellation(task_id: TaskId) -> Arc<LocalTaskCancellation> {
+    let cancellation = Arc::new(LocalTaskCancellation::default());
+    lock_or_recover(&LOCAL_TASK_CANCELLATIONS, "local_task_cancellations")
+        .insert(task_id.0, cancellation.clone());
+    cancellation
+}
 
-fn is_inflight_status(status: TaskStatus) -> bool {
-    matches!(status, TaskStatus::Queued | TaskStatus::Running)
+fn unregister_local_task_cancellation(task_id: TaskId) {
+    lock_or_recover(&LOCAL_TASK_CANCELLATIONS, "local_task_cancellations").remove(&task_id.0);
 }
 
-fn should_replace_visible_inflight_task(
-    current: &helm_core::models::TaskRecord,
-    candidate: &helm_core::models::TaskRecord,
+/// Request cancellation of a local composite task and of its step in flight.
+/// Returns false when `task_id` is not a running local task.
+fn cancel_local_task(
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    task_id: TaskId,
+    mode: CancellationMode,
 ) -> bool {
-    let current_running = current.status == TaskStatus::Running;
-    let candidate_running = candidate.status == TaskStatus::Running;
+    let Some(cancellation) = lock_or_recover(&LOCAL_TASK_CANCELLATIONS, "local_task_cancellations")
+        .get(&task_id.0)
+        .cloned()
+    else {
+        return false;
+    };
+    cancellation.requested.store(true, Ordering::SeqCst);
+    let current_step = *lock_or_recover(&cancellation.current_step, "local_task_step");
+    if let Some(step_id) = current_step
+        && let Err(error) = rt_handle.block_on(runtime.cancel(step_id, mode))
+    {
+        eprintln!(
+            "Failed to cancel step {} of task {}: {}",
+            step_id.0, task_id.0, error
+        );
+    }
+    true
+}
 
-    if current_running != candidate_running {
-        return candidate_running;
+/// Remove task scratch directories orphaned by a crash, at most once per
+/// `TASK_TEMP_DIR_SWEEP_INTERVAL_SECS`; called alongside live-task pruning.
+fn sweep_task_temp_dirs() {
+    static LAST_SWEEP_UNIX: AtomicU64 = AtomicU64::new(0);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let last = LAST_SWEEP_UNIX.load(Ordering::Relaxed);
+    if last != 0 && now.saturating_sub(last) < TASK_TEMP_DIR_SWEEP_INTERVAL_SECS {
+        return;
+    }
+    if LAST_SWEEP_UNIX
+        .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+        .is_err()
+    {
+        return;
     }
+    std::thread::spawn(|| {
+        let removed = helm_core::execution::task_temp_dir_store::sweep();
+        if removed > 0 {
+            eprintln!("helm: removed {removed} orphaned task temp dir(s)");
+        }
+    });
+}
 
-    candidate.id.0 > current.id.0
+/// Apply the task history retention settings; called alongside live-task pruning.
+fn prune_task_history(store: &SqliteStore) {
+    let retention_days = store.task_history_retention_days().unwrap_or(30);
+    let max_entries = store.task_history_max_entries().unwrap_or(5_000);
+    let _ = store.prune_task_history(
+        i64::from(retention_days) * 24 * 60 * 60,
+        max_entries as usize,
+    );
 }
 
+const TASK_PRUNE_MAX_AGE_SECS: i64 = 300;
+const TASK_TEMP_DIR_SWEEP_INTERVAL_SECS: u64 = 60 * 60;
+const TASK_RECENT_FETCH_LIMIT: usize = 1000;
+const TASK_HISTORY_PAGE_MAX_LIMIT: usize = 500;
+const TASK_INFLIGHT_DEDUP_MAX_AGE_SECS: u64 = 1800;
+const TASK_OUTPUT_BATCH_MAX_TASKS: usize = 64;
+const TASK_OUTPUT_BATCH_MAX_STREAM_BYTES: usize = 16 * 1024;
+const CATALOG_SYNC_STALE_AFTER_SECS: i64 = 6 * 60 * 60;
+const REMOTE_SEARCH_COMPLETION_TTL_SECS: u64 = 5 * 60;
+/// Remote search trigger result when every target manager already completed
+/// the query within `REMOTE_SEARCH_COMPLETION_TTL_SECS`; the local cache is complete.
+const REMOTE_SEARCH_SKIPPED_FRESH: i64 = -2;
+const STALE_INFLIGHT_TASK_LOG_CONTEXT_STARTUP: &str = "startup_reconciliation";
+const STALE_INFLIGHT_TASK_LOG_CONTEXT_PROFILE_SWITCH: &str = "profile_switch_reconciliation";
+const STALE_INFLIGHT_TASK_LOG_CONTEXT_DEDUPE: &str = "inflight_dedupe_check";
+const STALE_INFLIGHT_TASK_LOG_CONTEXT_TRIGGER_GUARD: &str = "trigger_guard";
+
 fn is_recent_inflight_task(task: &helm_core::models::TaskRecord) -> bool {
     std::time::SystemTime::now()
         .duration_since(task.created_at)
@@ -2022,6 +3223,42 @@ fn runtime_task_is_inflight(
         .unwrap_or(false)
 }
 
+/// Tasks `task_id` was submitted to run after, while the runtime still
+/// tracks it.
+fn task_dependencies(
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    task_id: TaskId,
+) -> Vec<TaskId> {
+    rt_handle
+        .block_on(runtime.snapshot(task_id))
+        .map(|snapshot| snapshot.runtime.depends_on)
+        .unwrap_or_default()
+}
+
+/// Label args naming the conflicting toolchain task a queued task is ordered
+/// behind, so the task list can explain why it has not started yet.
+fn conflict_wait_label_args(
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    task_id: TaskId,
+) -> Vec<(String, String)> {
+    let Some(blocker) = rt_handle
+        .block_on(runtime.pending_blockers(task_id))
+        .ok()
+        .and_then(|blockers| blockers.into_iter().next())
+    else {
+        return Vec::new();
+    };
+    vec![
+        ("waits_for_task".to_string(), blocker.id.0.to_string()),
+        (
+            "waits_for_manager".to_string(),
+            manager_display_name(blocker.manager).to_string(),
+        ),
+    ]
+}
+
 fn mark_stale_inflight_task_terminal(
     store: &SqliteStore,
     task: &helm_core::models::TaskRecord,
@@ -2068,64 +3305,6 @@ fn reconcile_stale_local_inflight_tasks(
     reconciled
 }
 
-fn task_signature_key(
-    task: &helm_core::models::TaskRecord,
-    labels: &std::collections::HashMap<u64, TaskLabel>,
-) -> String {
-    labels
-        .get(&task.id.0)
-        .map(|label| {
-            let mut encoded = format!("{:?}:{:?}:{}", task.manager, task.task_type, label.key);
-            for (arg_key, arg_value) in &label.args {
-                encoded.push('|');
-                encoded.push_str(arg_key);
-                encoded.push('=');
-                encoded.push_str(arg_value);
-            }
-            encoded
-        })
-        .unwrap_or_else(|| format!("{:?}:{:?}", task.manager, task.task_type))
-}
-
-fn build_visible_tasks(
-    tasks: Vec<helm_core::models::TaskRecord>,
-    labels: &std::collections::HashMap<u64, TaskLabel>,
-) -> Vec<helm_core::models::TaskRecord> {
-    let mut visible = Vec::with_capacity(tasks.len());
-    let mut seen_inflight: std::collections::HashMap<String, usize> =
-        std::collections::HashMap::new();
-    let mut seen_signatures: std::collections::HashSet<String> = std::collections::HashSet::new();
-    let mut terminal_count = 0usize;
-
-    for task in tasks {
-        let signature = task_signature_key(&task, labels);
-        if is_inflight_status(task.status) {
-            if let Some(existing_index) = seen_inflight.get(&signature).copied() {
-                if should_replace_visible_inflight_task(&visible[existing_index], &task) {
-                    visible[existing_index] = task;
-                }
-            } else {
-                seen_inflight.insert(signature.clone(), visible.len());
-                visible.push(task);
-            }
-            seen_signatures.insert(signature);
-            continue;
-        }
-
-        if task.status == TaskStatus::Failed && seen_signatures.contains(&signature) {
-            continue;
-        }
-
-        if terminal_count < TASK_TERMINAL_HISTORY_LIMIT {
-            visible.push(task);
-            terminal_count = terminal_count.saturating_add(1);
-        }
-        seen_signatures.insert(signature);
-    }
-
-    visible
-}
-
 fn find_matching_inflight_task(
     store: &SqliteStore,
     runtime: &AdapterRuntime,
@@ -2136,7 +3315,6 @@ fn find_matching_inflight_task(
     label_args: &[(&str, String)],
 ) -> Option<helm_core::models::TaskId> {
     let tasks = store.list_recent_tasks(TASK_RECENT_FETCH_LIMIT).ok()?;
-    let labels = lock_or_recover(&TASK_LABELS, "task_labels");
 
     tasks.into_iter().find_map(|task| {
         if task.manager != manager
@@ -2155,7 +3333,7 @@ fn find_matching_inflight_task(
             return Some(task.id);
         };
 
-        let label = labels.get(&task.id.0)?;
+        let label = task.label.as_ref()?;
         if label.key != expected_label_key || label.args.len() != label_args.len() {
             return None;
         }
@@ -2201,10 +3379,6 @@ fn cancel_inflight_tasks_for_manager(
                 );
             }
         }
-        let mut labels = lock_or_recover(&TASK_LABELS, "task_labels");
-        for task_id in task_ids {
-            labels.remove(&task_id.0);
-        }
         return;
     }
 
@@ -2220,11 +3394,6 @@ fn cancel_inflight_tasks_for_manager(
             }
         }
     });
-
-    let mut labels = lock_or_recover(&TASK_LABELS, "task_labels");
-    for task_id in task_ids {
-        labels.remove(&task_id.0);
-    }
 }
 
 fn purge_tasks_for_manager(
@@ -2257,8 +3426,6 @@ fn purge_tasks_for_manager(
         return false;
     }
 
-    let mut labels = lock_or_recover(&TASK_LABELS, "task_labels");
-    labels.retain(|task_id, _| !task_ids_for_manager.contains(task_id));
     true
 }
 
@@ -2299,6 +3466,7 @@ fn apply_manager_enablement_self_heal(
             );
             continue;
         }
+        invalidate_package_snapshot_cache();
 
         if let Some(preference) = pref_map.get_mut(&manager) {
             preference.enabled = false;
@@ -2312,6 +3480,7 @@ fn apply_manager_enablement_self_heal(
                     selected_install_method: None,
                     timeout_hard_seconds: None,
                     timeout_idle_seconds: None,
+                    process_qos: None,
                 },
             );
         }
@@ -2512,11 +3681,23 @@ fn has_recent_refresh_or_detection(
     false
 }
 
+/// Filesystem pass ahead of bulk detection. Returns the managers it proved
+/// absent, whose version probes detection can skip.
 fn preseed_presence_detections(
     store: &SqliteStore,
     runtime: &AdapterRuntime,
     enabled_by_manager: &std::collections::HashMap<ManagerId, bool>,
-) {
+) -> std::collections::HashSet<ManagerId> {
+    let selected_paths: std::collections::HashSet<ManagerId> = store
+        .list_manager_preferences()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|preference| {
+            normalize_nonempty(preference.selected_executable_path.clone()).is_some()
+        })
+        .map(|preference| preference.manager)
+        .collect();
+    let mut absent = std::collections::HashSet::new();
     for manager in ManagerId::ALL {
         if !manager_is_enabled(enabled_by_manager, manager) {
             continue;
@@ -2524,25 +3705,56 @@ fn preseed_presence_detections(
         if !is_implemented_manager(manager) || !runtime.has_manager(manager) {
             continue;
         }
-        preseed_presence_detection(store, runtime, manager);
+        let present = preseed_presence_detection(store, runtime, manager);
+        if !present
+            && manager_detection_is_executable_probe(manager)
+            && !selected_paths.contains(&manager)
+        {
+            absent.insert(manager);
+        }
     }
+    absent
 }
 
-fn preseed_presence_detection(store: &SqliteStore, runtime: &AdapterRuntime, manager: ManagerId) {
-    if !is_implemented_manager(manager) || !runtime.has_manager(manager) {
-        return;
-    }
+/// Whether a manager's detection only looks for its executable, so missing
+/// every `manager_executable_candidates` path proves it is not installed.
+/// Managers detected through app bundles, system paths, or profiles outside
+/// the candidate search keep their probe.
+fn manager_detection_is_executable_probe(manager: ManagerId) -> bool {
+    !manager_executable_candidates(manager).is_empty()
+        && !matches!(
+            manager,
+            ManagerId::DockerDesktop
+                | ManagerId::NixDarwin
+                | ManagerId::SoftwareUpdate
+                | ManagerId::XcodeCommandLineTools
+        )
+}
+
+/// Record a filesystem hit as an installed detection; returns whether the
+/// manager's executable was found.
+fn preseed_presence_detection(
+    store: &SqliteStore,
+    runtime: &AdapterRuntime,
+    manager: ManagerId,
+) -> bool {
+    if !is_implemented_manager(manager) || !runtime.has_manager(manager) {
+        return false;
+    }
 
     invalidate_executable_discovery_cache(Some(manager));
     let discovered_paths = collect_manager_executable_paths(manager, None);
-    if let Some(executable_path) = discovered_paths.first().map(std::path::PathBuf::from) {
-        let info = DetectionInfo {
-            installed: true,
-            executable_path: Some(executable_path),
-            version: None,
-        };
-        let _ = store.upsert_detection(manager, &info);
-    }
+    let Some(executable_path) = discovered_paths.first().map(std::path::PathBuf::from) else {
+        return false;
+    };
+    let info = DetectionInfo {
+        installed: true,
+        executable_path: Some(executable_path),
+        version: None,
+    };
+    let _ = store.upsert_detection(manager, &info);
+    invalidate_package_snapshot_cache();
+    true
 }
 
 fn sync_manager_execution_preferences_from_store(store: &SqliteStore) {
@@ -2621,6 +3833,23 @@ fn search_cache_is_stale_for_manager(
     }
 }
 
+/// Managers whose remote search for `query` completed recently enough that
+/// re-running it would only return what the local cache already holds.
+fn fresh_search_completion_managers(
+    store: &SqliteStore,
+    query: &str,
+) -> std::collections::HashSet<ManagerId> {
+    let now = SystemTime::now();
+    let ttl = Duration::from_secs(REMOTE_SEARCH_COMPLETION_TTL_SECS);
+    store
+        .list_search_completions(query)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|completion| completion.is_fresh(now, ttl))
+        .map(|completion| completion.manager)
+        .collect()
+}
+
 fn manager_can_catalog_sync(
     store: &SqliteStore,
     runtime: &AdapterRuntime,
@@ -2655,6 +3884,10 @@ fn queue_catalog_sync_task_if_needed(
     if !force && !search_cache_is_stale_for_manager(store, manager, now_unix) {
         return None;
     }
+    // A full catalog download is the largest prefetch; leave it for later.
+    if !force && helm_core::network_policy::suppress_heavy_network() {
+        return None;
+    }
 
     queue_remote_search_task(store, runtime, rt_handle, manager, "").ok()
 }
@@ -2728,11 +3961,12 @@ fn queue_remote_search_task(
         },
     });
 
-    match rt_handle.block_on(runtime.submit(manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, &label_args);
-            Ok(task_id)
-        }
+    match rt_handle.block_on(runtime.submit_labeled(
+        manager,
+        request,
+        task_label(label_key, &label_args),
+    )) {
+        Ok(task_id) => Ok(task_id),
         Err(error) => {
             eprintln!(
                 "Failed to queue remote search for manager {} with query '{}': {}",
@@ -2850,6 +4084,7 @@ fn supports_individual_package_upgrade(runtime: &AdapterRuntime, manager: Manage
         ManagerId::Asdf
             | ManagerId::HomebrewFormula
             | ManagerId::HomebrewCask
+            | ManagerId::MacPorts
             | ManagerId::Mas
             | ManagerId::Mise
             | ManagerId::Npm
@@ -2862,14 +4097,6 @@ fn supports_individual_package_upgrade(runtime: &AdapterRuntime, manager: Manage
     )
 }
 
-fn encode_homebrew_upgrade_target(package_name: &str, cleanup_old_kegs: bool) -> String {
-    if cleanup_old_kegs {
-        format!("{package_name}@@helm.cleanup")
-    } else {
-        package_name.to_string()
-    }
-}
-
 fn effective_homebrew_keg_policy(store: &SqliteStore, package_name: &str) -> HomebrewKegPolicy {
     let package_ref = PackageRef {
         manager: ManagerId::HomebrewFormula,
@@ -2885,6 +4112,41 @@ fn effective_homebrew_keg_policy(store: &SqliteStore, package_name: &str) -> Hom
         .unwrap_or(HomebrewKegPolicy::Keep)
 }
 
+fn persisted_upgrade_options(
+    store: &SqliteStore,
+    manager: ManagerId,
+    package_name: &str,
+) -> Vec<String> {
+    let package_ref = PackageRef {
+        manager,
+        name: package_name.to_string(),
+    };
+    store
+        .package_install_options(&package_ref)
+        .map(|options| helm_core::install_options::upgrade_options_from_install(manager, &options))
+        .unwrap_or_default()
+}
+
+/// The request upgrade-all queues for one package, replaying the install
+/// options saved for it.
+fn upgrade_all_package_request(
+    store: &SqliteStore,
+    manager: ManagerId,
+    package_name: &str,
+    cleanup_old_kegs: bool,
+) -> AdapterRequest {
+    AdapterRequest::Upgrade(UpgradeRequest {
+        target: UpgradeTarget::Package(PackageRef {
+            manager,
+            name: package_name.to_string(),
+        }),
+        target_name: None,
+        version: None,
+        options: persisted_upgrade_options(store, manager, package_name),
+        cleanup_old_kegs,
+    })
+}
+
 fn normalize_nonempty(value: Option<String>) -> Option<String> {
     value
         .map(|v| v.trim().to_string())
@@ -2916,8 +4178,10 @@ fn run_homebrew_probe_output(program: &std::ffi::OsStr, args: &[&str]) -> Option
             "PATH",
             "/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin",
         )
-        .env("HOMEBREW_NO_AUTO_UPDATE", "1")
-        .env("HOMEBREW_NO_ENV_HINTS", "1")
+        .envs(homebrew_env_vars(
+            ManagerAction::Detect,
+            &homebrew_env_overrides(ManagerId::HomebrewFormula),
+        ))
         .output()
         .ok()?;
 
@@ -3078,6 +4342,7 @@ struct UpgradeAllTargets {
     asdf: Vec<String>,
     homebrew: Vec<String>,
     homebrew_cask: Vec<String>,
+    macports: Vec<String>,
     mas: Vec<String>,
     mise: Vec<String>,
     npm: Vec<String>,
@@ -3091,7 +4356,8 @@ struct UpgradeAllTargets {
     rubygems: Vec<String>,
     bundler: Vec<String>,
     rustup: Vec<String>,
-    softwareupdate_outdated: bool,
+    /// `softwareupdate` labels; a confirmed OS update installs exactly these.
+    softwareupdate: Vec<String>,
 }
 
 #[derive(serde::Serialize, Clone, Debug, Eq, PartialEq)]
@@ -3106,6 +4372,23 @@ struct FfiUpgradePlanStep {
     reason_label_key: String,
     reason_label_args: std::collections::HashMap<String, String>,
     status: String,
+    /// Download size reported by the manager; the OS update confirmation step
+    /// carries the total of every reported `softwareupdate` download.
+    download_size_bytes: Option<u64>,
+    /// True when the step runs under `sudo` and will prompt for administrator
+    /// authentication (MacPorts `port upgrade`).
+    requires_elevation: bool,
+    /// Adapter-declared duration of the step, refined by completed upgrades
+    /// archived on this machine. `None` when the manager offers no estimate.
+    estimated_duration_ms: Option<u64>,
+    /// When the step should finish, counted from the start of the plan.
+    /// Managers drain in parallel, so the largest value is the plan total.
+    /// `None` when an earlier step of the same manager has no estimate.
+    estimated_finish_ms: Option<u64>,
+}
+
+fn upgrade_requires_elevation(manager: ManagerId) -> bool {
+    manager == ManagerId::MacPorts
 }
 
 fn manager_authority_key(id: ManagerId) -> &'static str {
@@ -3145,6 +4428,10 @@ fn upgrade_reason_label_for(
             "service.task.label.upgrade.mise",
             vec![("package", package_name.to_string())],
         ),
+        ManagerId::Asdf => (
+            "service.task.label.upgrade.asdf",
+            vec![("plugin", package_name.to_string())],
+        ),
         ManagerId::Rustup => (
             "service.task.label.upgrade.rustup_toolchain",
             vec![("toolchain", package_name.to_string())],
@@ -3161,7 +4448,7 @@ fn upgrade_reason_label_for(
 }
 
 fn upgrade_plan_step_id(manager: ManagerId, package_name: &str) -> String {
-    format!("{}:{}", manager.as_str(), package_name)
+    helm_core::upgrade_target::upgrade_plan_step_id(manager, package_name)
 }
 
 fn upgrade_task_label_for(
@@ -3199,10 +4486,95 @@ fn push_upgrade_plan_step(
         reason_label_key: reason_label_key.to_string(),
         reason_label_args,
         status: "queued".to_string(),
+        download_size_bytes: None,
+        requires_elevation: upgrade_requires_elevation(manager),
+        estimated_duration_ms: None,
+        estimated_finish_ms: None,
     });
     *next_order_index += 1;
 }
 
+fn annotate_upgrade_plan_download_sizes(
+    steps: &mut [FfiUpgradePlanStep],
+    outdated: &[OutdatedPackage],
+    include_pinned: bool,
+) {
+    let eligible = || {
+        outdated
+            .iter()
+            .filter(move |package| include_pinned || !package.pinned)
+    };
+    let softwareupdate_sizes: Vec<u64> = eligible()
+        .filter(|package| package.package.manager == ManagerId::SoftwareUpdate)
+        .filter_map(|package| package.sizes.download_size_bytes)
+        .collect();
+    let softwareupdate_total =
+        (!softwareupdate_sizes.is_empty()).then(|| softwareupdate_sizes.iter().sum::<u64>());
+
+    for step in steps {
+        step.download_size_bytes = if step.manager_id == ManagerId::SoftwareUpdate.as_str() {
+            softwareupdate_total
+        } else {
+            eligible()
+                .find(|package| {
+                    package.package.manager.as_str() == step.manager_id
+                        && package.package.name == step.package_name
+                })
+                .and_then(|package| package.sizes.download_size_bytes)
+        };
+    }
+}
+
+fn annotate_upgrade_plan_durations(
+    steps: &mut [FfiUpgradePlanStep],
+    runtime: &AdapterRuntime,
+    history: &[TaskHistoryRecord],
+) {
+    let estimator = helm_core::duration_estimates::DurationEstimator::from_history(history);
+    let estimates: Vec<(ManagerId, Option<Duration>)> = steps
+        .iter()
+        .filter_map(|step| {
+            let manager = step.manager_id.parse::<ManagerId>().ok()?;
+            let declared = runtime.estimated_action_duration(manager, ManagerAction::Upgrade);
+            Some((
+                manager,
+                estimator.estimate(manager, ManagerAction::Upgrade, declared),
+            ))
+        })
+        .collect();
+    let finish_offsets = helm_core::duration_estimates::plan_finish_offsets(&estimates);
+
+    for ((step, (_, duration)), finish) in steps.iter_mut().zip(&estimates).zip(finish_offsets) {
+        step.estimated_duration_ms = duration.map(|duration| duration.as_millis() as u64);
+        step.estimated_finish_ms = finish.map(|finish| finish.as_millis() as u64);
+    }
+}
+
+/// Managers whose outdated packages `helm_upgrade_all` can queue.
+fn is_upgrade_all_manager(manager: ManagerId) -> bool {
+    matches!(
+        manager,
+        ManagerId::Asdf
+            | ManagerId::HomebrewFormula
+            | ManagerId::HomebrewCask
+            | ManagerId::MacPorts
+            | ManagerId::Mas
+            | ManagerId::Mise
+            | ManagerId::Npm
+            | ManagerId::Pnpm
+            | ManagerId::Yarn
+            | ManagerId::Cargo
+            | ManagerId::CargoBinstall
+            | ManagerId::Pip
+            | ManagerId::Pipx
+            | ManagerId::Poetry
+            | ManagerId::RubyGems
+            | ManagerId::Bundler
+            | ManagerId::Rustup
+            | ManagerId::SoftwareUpdate
+    )
+}
+
 fn collect_upgrade_all_targets(
     outdated: &[OutdatedPackage],
     include_pinned: bool,
@@ -3211,6 +4583,7 @@ fn collect_upgrade_all_targets(
     let mut seen_asdf = std::collections::HashSet::new();
     let mut seen_homebrew = std::collections::HashSet::new();
     let mut seen_homebrew_cask = std::collections::HashSet::new();
+    let mut seen_macports = std::collections::HashSet::new();
     let mut seen_mas = std::collections::HashSet::new();
     let mut seen_mise = std::collections::HashSet::new();
     let mut seen_npm = std::collections::HashSet::new();
@@ -3224,6 +4597,7 @@ fn collect_upgrade_all_targets(
     let mut seen_rubygems = std::collections::HashSet::new();
     let mut seen_bundler = std::collections::HashSet::new();
     let mut seen_rustup = std::collections::HashSet::new();
+    let mut seen_softwareupdate = std::collections::HashSet::new();
 
     for package in outdated {
         if !include_pinned && package.pinned {
@@ -3246,6 +4620,11 @@ fn collect_upgrade_all_targets(
                     targets.homebrew_cask.push(package.package.name.clone());
                 }
             }
+            ManagerId::MacPorts => {
+                if seen_macports.insert(package.package.name.clone()) {
+                    targets.macports.push(package.package.name.clone());
+                }
+            }
             ManagerId::Mas => {
                 if seen_mas.insert(package.package.name.clone()) {
                     targets.mas.push(package.package.name.clone());
@@ -3311,7 +4690,11 @@ fn collect_upgrade_all_targets(
                     targets.rustup.push(package.package.name.clone());
                 }
             }
-            ManagerId::SoftwareUpdate => targets.softwareupdate_outdated = true,
+            ManagerId::SoftwareUpdate => {
+                if seen_softwareupdate.insert(package.package.name.clone()) {
+                    targets.softwareupdate.push(package.package.name.clone());
+                }
+            }
             _ => {}
         }
     }
@@ -3561,6 +4944,7 @@ fn build_manager_uninstall_request_legacy(
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
             "service.task.label.uninstall.homebrew_formula",
             vec![("package", "mise".to_string())],
@@ -3574,6 +4958,7 @@ fn build_manager_uninstall_request_legacy(
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
             "service.task.label.uninstall.homebrew_formula",
             vec![("package", "mas".to_string())],
@@ -3587,6 +4972,7 @@ fn build_manager_uninstall_request_legacy(
                 },
                 target_name: None,
                 version: None,
+                disposal: Default::default(),
             }),
             "service.task.label.uninstall.rustup_self",
             Vec::new(),
@@ -3621,7 +5007,7 @@ fn build_manager_uninstall_plan_with_options(
 ) -> Result<ManagerUninstallPlan, &'static str> {
     let active_instance = active_manager_install_instance(store, manager)?;
 
-    match helm_core::manager_lifecycle::plan_manager_uninstall_route_with_options(
+    let plan = match helm_core::manager_lifecycle::plan_manager_uninstall_route_with_options(
         manager,
         active_instance.as_ref(),
         allow_unknown_provenance,
@@ -3678,7 +5064,52 @@ fn build_manager_uninstall_plan_with_options(
             })
         }
         Err(error) => Err(manager_uninstall_route_error_key(error)),
+    }?;
+
+    if !preview_only
+        && manager_uninstall_cascades_package_data(&plan.preview)
+        && !uninstall_options.confirm_manager_data_removal
+    {
+        return Err(SERVICE_ERROR_MANAGER_DATA_REMOVAL_CONFIRMATION_REQUIRED);
     }
+    Ok(plan)
+}
+
+/// A cascade uninstall removes manager data while Helm still tracks packages under it.
+fn manager_uninstall_cascades_package_data(preview: &FfiManagerUninstallPreview) -> bool {
+    preview.removes_manager_data && !preview.affected_packages.is_empty()
+}
+
+/// Drop cached package rows for a manager once a cascade uninstall removed its data.
+fn spawn_manager_cascade_snapshot_cleanup(
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+    rt_handle: tokio::runtime::Handle,
+    task_id: TaskId,
+    manager: ManagerId,
+) {
+    thread::spawn(move || {
+        let Ok(snapshot) = rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) else {
+            return;
+        };
+        if !matches!(
+            snapshot.terminal_state,
+            Some(AdapterTaskTerminalState::Succeeded(_))
+        ) {
+            return;
+        }
+        let cleared = store
+            .replace_installed_snapshot(manager, &[])
+            .and_then(|()| store.replace_outdated_snapshot(manager, &[]));
+        invalidate_package_snapshot_cache();
+        if let Err(error) = cleared {
+            eprintln!(
+                "uninstall_manager: failed to clear package snapshot for {}: {}",
+                manager.as_str(),
+                error.message
+            );
+        }
+    });
 }
 
 fn build_provenance_manager_uninstall_plan(
@@ -3915,7 +5346,21 @@ fn initialize_coordinator_bridge(
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
         .is_ok()
     {
-        start_local_auto_check_ticker(store.clone());
+        start_local_auto_check_ticker();
+    }
+
+    if NETWORK_RECOVERY_TICKER_STARTED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+    {
+        start_network_recovery_ticker();
+    }
+
+    if REFRESH_SCHEDULE_TICKER_STARTED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+    {
+        start_refresh_schedule_ticker();
     }
 
     *lock_or_recover(&COORDINATOR_BRIDGE, "coordinator_bridge") = CoordinatorBridge::Local;
@@ -4066,6 +5511,34 @@ fn auto_check_http_agent() -> ureq::Agent {
         .build()
 }
 
+/// Registry reachability probe: an HTTPS `HEAD` to the host root with short
+/// timeouts. Any HTTP status counts as reachable; only transport errors
+/// (DNS, TLS, connect, captive-portal interception) count as unreachable.
+struct HttpsHeadReachabilityProbe {
+    agent: ureq::Agent,
+}
+
+impl HttpsHeadReachabilityProbe {
+    fn new() -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(REACHABILITY_PROBE_TIMEOUT_SECS))
+                .timeout(Duration::from_secs(REACHABILITY_PROBE_TIMEOUT_SECS))
+                .redirects(0)
+                .build(),
+        }
+    }
+}
+
+impl helm_core::reachability::ReachabilityProbe for HttpsHeadReachabilityProbe {
+    fn probe(&self, host: &str) -> bool {
+        match self.agent.head(&format!("https://{host}/")).call() {
+            Ok(_) | Err(ureq::Error::Status(_, _)) => true,
+            Err(ureq::Error::Transport(_)) => false,
+        }
+    }
+}
+
 fn run_due_auto_check_tick(store: &SqliteStore) {
     let enabled = match store.auto_check_for_updates() {
         Ok(enabled) => enabled,
@@ -4137,12 +5610,16 @@ fn run_due_auto_check_tick(store: &SqliteStore) {
     }
 }
 
-fn start_local_auto_check_ticker(store: Arc<SqliteStore>) {
+/// Run due auto-checks against whichever profile is active at each tick.
+fn start_local_auto_check_ticker() {
     thread::spawn(move || {
         let mut next_auto_check_tick = Instant::now();
         loop {
-            if Instant::now() >= next_auto_check_tick {
-                run_due_auto_check_tick(store.as_ref());
+            if helm_core::scheduled_runs::take_wake_hint() || Instant::now() >= next_auto_check_tick
+            {
+                if let Some(store) = active_state_store() {
+                    run_due_auto_check_tick(store.as_ref());
+                }
                 next_auto_check_tick = Instant::now() + Duration::from_secs(AUTO_CHECK_TICK_SECS);
             }
             thread::sleep(Duration::from_millis(COORDINATOR_POLL_SLEEP_MS));
@@ -4150,12 +5627,114 @@ fn start_local_auto_check_ticker(store: Arc<SqliteStore>) {
     });
 }
 
+/// Refresh managers whose network work was deferred once their registries answer
+/// again, using whichever engine and profile are active at the time.
+fn start_network_recovery_ticker() {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_secs(NETWORK_RECOVERY_POLL_SECS));
+            let Some(checker) = helm_core::reachability::reachability_checker() else {
+                continue;
+            };
+            let Some((runtime, rt_handle)) = active_state_runtime() else {
+                continue;
+            };
+            for manager in checker.take_recovered_managers() {
+                let runtime = runtime.clone();
+                rt_handle.spawn(async move {
+                    if let Err(error) = runtime.refresh_manager(manager).await {
+                        log_manager_operation_failure("network recovery refresh", manager, &error);
+                    }
+                });
+            }
+        }
+    });
+}
+
+/// Run per-manager background refreshes as their schedules come due, and
+/// re-probe quarantined managers on their backoff, using whichever engine and
+/// profile are active at the time.
+fn start_refresh_schedule_ticker() {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_secs(REFRESH_SCHEDULE_TICK_SECS));
+            let Some((runtime, rt_handle)) = active_state_runtime() else {
+                continue;
+            };
+            let now_unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0);
+            rt_handle.spawn(async move {
+                for (manager, result) in runtime.run_due_scheduled_refreshes(now_unix).await {
+                    if let Err(error) = result {
+                        log_manager_operation_failure("scheduled refresh", manager, &error);
+                    }
+                }
+                for (manager, result) in runtime.run_due_quarantine_probes(now_unix).await {
+                    if let Err(error) = result {
+                        log_manager_operation_failure("quarantine probe", manager, &error);
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Warm manager runtimes and registry connections in the background when the
+/// `prewarm_on_launch` setting is on, so the first search after launch is fast.
+fn start_launch_prewarm(
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+    rt_handle: &tokio::runtime::Handle,
+) {
+    if !store.prewarm_on_launch().unwrap_or(true) {
+        return;
+    }
+    rt_handle.spawn(async move {
+        let detections = store.list_detections().unwrap_or_default();
+        let plan = helm_core::prewarm::prewarm_plan(
+            &detections,
+            |manager| runtime.is_manager_enabled(manager),
+            runtime.is_offline_mode(),
+        );
+        if plan.is_empty() {
+            return;
+        }
+        helm_core::prewarm::run_prewarm(
+            plan,
+            helm_core::reachability::reachability_checker(),
+            Arc::new(helm_core::execution::tokio_process::TokioProcessExecutor),
+        )
+        .await;
+    });
+}
+
+fn active_state_store() -> Option<Arc<SqliteStore>> {
+    lock_or_recover(&STATE, "state")
+        .as_ref()
+        .map(|state| state.store.clone())
+}
+
+fn active_state_engine() -> Option<(Arc<helm_engine::Engine>, tokio::runtime::Handle)> {
+    lock_or_recover(&STATE, "state")
+        .as_ref()
+        .map(|state| (state.engine.clone(), state.rt_handle.clone()))
+}
+
+fn active_state_runtime() -> Option<(Arc<AdapterRuntime>, tokio::runtime::Handle)> {
+    lock_or_recover(&STATE, "state")
+        .as_ref()
+        .map(|state| (state.runtime.clone(), state.rt_handle.clone()))
+}
+
 fn start_local_coordinator_server(
     state_dir: PathBuf,
     store: Arc<SqliteStore>,
     runtime: Arc<AdapterRuntime>,
     rt_handle: tokio::runtime::Handle,
 ) {
+    let generation = ENGINE_GENERATION.load(Ordering::SeqCst);
     thread::spawn(move || {
         if reset_coordinator_state_dir(state_dir.as_path()).is_err() {
             return;
@@ -4179,7 +5758,12 @@ fn start_local_coordinator_server(
         let requests_dir = coordinator_requests_dir(state_dir.as_path());
         let mut next_auto_check_tick = Instant::now();
         loop {
-            if Instant::now() >= next_auto_check_tick {
+            // The engine this server was started for has been shut down.
+            if ENGINE_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if helm_core::scheduled_runs::take_wake_hint() || Instant::now() >= next_auto_check_tick
+            {
                 run_due_auto_check_tick(store.as_ref());
                 next_auto_check_tick = Instant::now() + Duration::from_secs(AUTO_CHECK_TICK_SECS);
             }
@@ -4407,6 +5991,55 @@ fn handle_local_coordinator_request(
                 };
             }
             let rt_handle = rt_handle.clone();
+            if let CoordinatorWorkflowRequest::DetectManager { manager_id } = &workflow {
+                let Ok(manager) = manager_id.parse::<ManagerId>() else {
+                    return CoordinatorResponse {
+                        ok: false,
+                        task_id: None,
+                        job_id: Some(job_id),
+                        payload: None,
+                        error: Some(format!("unknown manager id '{}'", manager_id)),
+                    };
+                };
+                let was_detected = manager_is_detected(store.as_ref(), manager);
+                let task_id = match queue_manager_detection_task(
+                    store.as_ref(),
+                    workflow_runtime.as_ref(),
+                    &rt_handle,
+                    manager,
+                ) {
+                    Ok(task_id) => task_id,
+                    Err(error_key) => {
+                        return CoordinatorResponse {
+                            ok: false,
+                            task_id: None,
+                            job_id: Some(job_id),
+                            payload: None,
+                            error: Some(format!(
+                                "failed to queue detection for manager '{}' ({error_key})",
+                                manager.as_str()
+                            )),
+                        };
+                    }
+                };
+                thread::spawn(move || {
+                    let _ = finish_manager_detection_task(
+                        store.as_ref(),
+                        workflow_runtime.as_ref(),
+                        &rt_handle,
+                        manager,
+                        task_id,
+                        was_detected,
+                    );
+                });
+                return CoordinatorResponse {
+                    ok: true,
+                    task_id: Some(task_id.0),
+                    job_id: Some(job_id),
+                    payload: None,
+                    error: None,
+                };
+            }
             thread::spawn(move || {
                 let _ = run_coordinator_workflow(
                     workflow_runtime.as_ref(),
@@ -4465,7 +6098,11 @@ fn run_coordinator_workflow(
         }
         CoordinatorWorkflowRequest::DetectAll => {
             let detected_before = detected_installed_map(store);
-            let results = rt_handle.block_on(runtime.detect_all_ordered());
+            let enabled_by_manager = manager_enabled_map(store);
+            let absent = preseed_presence_detections(store, runtime, &enabled_by_manager);
+            let _ = record_onboarding_event(store, OnboardingEvent::DetectionStarted);
+            let results = rt_handle.block_on(runtime.detect_all_ordered_skipping_absent(&absent));
+            let _ = record_onboarding_event(store, OnboardingEvent::DetectionFinished);
             let failures = results
                 .into_iter()
                 .filter(|(_, result)| result.is_err())
@@ -4573,26 +6210,71 @@ fn detect_single_manager(
     if !runtime.is_manager_enabled(manager) {
         return Ok(());
     }
-    if !runtime.supports_capability(manager, Capability::Detect) {
-        return Err(format!(
-            "manager '{}' has no detection capability",
-            manager.as_str()
-        ));
-    }
-
-    preseed_presence_detection(store, runtime, manager);
-    sync_manager_execution_preferences_from_store(store);
 
     let was_detected = manager_is_detected(store, manager);
-    let response = submit_request_wait(
+    let task_id = queue_manager_detection_task(store, runtime, rt_handle, manager)
+        .map_err(|error_key| format!("failed to queue detection ({error_key})"))?;
+    finish_manager_detection_task(store, runtime, rt_handle, manager, task_id, was_detected)
+}
+
+/// Queue detection for one manager, reusing an in-flight detection task for
+/// that manager when one exists so repeated triggers stay idempotent.
+fn queue_manager_detection_task(
+    store: &SqliteStore,
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    manager: ManagerId,
+) -> Result<helm_core::models::TaskId, &'static str> {
+    if !runtime.has_manager(manager)
+        || !runtime.is_manager_enabled(manager)
+        || !runtime.supports_capability(manager, Capability::Detect)
+    {
+        return Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+    }
+
+    if let Some(existing) = find_matching_inflight_task(
+        store,
         runtime,
         rt_handle,
         manager,
-        AdapterRequest::Detect(helm_core::adapters::DetectRequest),
-    )?;
+        TaskType::Detection,
+        None,
+        &[],
+    ) {
+        return Ok(existing);
+    }
 
-    match response {
-        helm_core::adapters::AdapterResponse::Detection(_) => {
+    preseed_presence_detection(store, runtime, manager);
+    sync_manager_execution_preferences_from_store(store);
+
+    rt_handle
+        .block_on(runtime.submit(
+            manager,
+            AdapterRequest::Detect(helm_core::adapters::DetectRequest),
+        ))
+        .map_err(|error| {
+            log_manager_operation_failure("detection", manager, &format_core_error(error));
+            SERVICE_ERROR_PROCESS_FAILURE
+        })
+}
+
+/// Wait for a queued single-manager detection and schedule a catalog sync,
+/// forcing it when the manager became detected during this run.
+fn finish_manager_detection_task(
+    store: &SqliteStore,
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    manager: ManagerId,
+    task_id: helm_core::models::TaskId,
+    was_detected: bool,
+) -> Result<(), String> {
+    let snapshot = rt_handle
+        .block_on(runtime.wait_for_terminal(task_id, None))
+        .map_err(format_core_error)?;
+    match snapshot.terminal_state {
+        Some(AdapterTaskTerminalState::Succeeded(
+            helm_core::adapters::AdapterResponse::Detection(_),
+        )) => {
             let mut force_managers = std::collections::HashSet::new();
             if manager_is_detected(store, manager) && !was_detected {
                 force_managers.insert(manager);
@@ -4606,10 +6288,19 @@ fn detect_single_manager(
             );
             Ok(())
         }
-        _ => Err(format!(
+        Some(AdapterTaskTerminalState::Succeeded(_)) => Err(format!(
             "manager '{}' detect action returned unexpected payload",
             manager.as_str()
         )),
+        Some(AdapterTaskTerminalState::Failed(error)) => Err(format_core_error(error)),
+        Some(AdapterTaskTerminalState::Cancelled(Some(error))) => Err(format_core_error(error)),
+        Some(AdapterTaskTerminalState::Cancelled(None)) => {
+            Err(format!("task {} was cancelled", task_id.0))
+        }
+        None => Err(format!(
+            "task {} reached terminal state without outcome payload",
+            task_id.0
+        )),
     }
 }
 
@@ -4641,12 +6332,14 @@ fn run_updates_workflow(
     if runtime.is_manager_enabled(ManagerId::Asdf) {
         for package_name in targets.asdf {
             let request = AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Asdf,
                     name: package_name,
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             });
             let _ = submit_request_wait(runtime, rt_handle, ManagerId::Asdf, request)?;
         }
@@ -4656,42 +6349,51 @@ fn run_updates_workflow(
         for package_name in targets.homebrew {
             let policy = effective_homebrew_keg_policy(store, &package_name);
             let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
-            let target_name = encode_homebrew_upgrade_target(&package_name, cleanup_old_kegs);
-            let request = AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::HomebrewFormula,
-                    name: target_name,
-                }),
-                target_name: None,
-                version: None,
-            });
+            let request = upgrade_all_package_request(
+                store,
+                ManagerId::HomebrewFormula,
+                &package_name,
+                cleanup_old_kegs,
+            );
             let _ = submit_request_wait(runtime, rt_handle, ManagerId::HomebrewFormula, request)?;
         }
     }
 
     if runtime.is_manager_enabled(ManagerId::HomebrewCask) {
         for package_name in targets.homebrew_cask {
+            let request =
+                upgrade_all_package_request(store, ManagerId::HomebrewCask, &package_name, false);
+            let _ = submit_request_wait(runtime, rt_handle, ManagerId::HomebrewCask, request)?;
+        }
+    }
+
+    if runtime.is_manager_enabled(ManagerId::MacPorts) {
+        for package_name in targets.macports {
             let request = AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::HomebrewCask,
+                target: UpgradeTarget::Package(PackageRef {
+                    manager: ManagerId::MacPorts,
                     name: package_name,
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             });
-            let _ = submit_request_wait(runtime, rt_handle, ManagerId::HomebrewCask, request)?;
+            let _ = submit_request_wait(runtime, rt_handle, ManagerId::MacPorts, request)?;
         }
     }
 
     if runtime.is_manager_enabled(ManagerId::Mas) {
         for package_name in targets.mas {
             let request = AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager: ManagerId::Mas,
                     name: package_name,
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             });
             let _ = submit_request_wait(runtime, rt_handle, ManagerId::Mas, request)?;
         }
@@ -4716,29 +6418,30 @@ fn run_updates_workflow(
         }
         for package_name in packages {
             let request = AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
+                target: UpgradeTarget::Package(PackageRef {
                     manager,
                     name: package_name,
                 }),
                 target_name: None,
                 version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
             });
             let _ = submit_request_wait(runtime, rt_handle, manager, request)?;
         }
     }
 
     if allow_os_updates
-        && targets.softwareupdate_outdated
+        && !targets.softwareupdate.is_empty()
         && runtime.is_manager_enabled(ManagerId::SoftwareUpdate)
         && !runtime.is_safe_mode()
     {
         let request = AdapterRequest::Upgrade(UpgradeRequest {
-            package: Some(PackageRef {
-                manager: ManagerId::SoftwareUpdate,
-                name: "__confirm_os_updates__".to_string(),
-            }),
+            target: UpgradeTarget::OsUpdatesConfirmed,
             target_name: None,
             version: None,
+            options: targets.softwareupdate,
+            cleanup_old_kegs: false,
         });
         let _ = submit_request_wait(runtime, rt_handle, ManagerId::SoftwareUpdate, request)?;
     }
@@ -4755,6 +6458,14 @@ fn submit_request_wait(
     let task_id = rt_handle
         .block_on(runtime.submit(manager, request))
         .map_err(format_core_error)?;
+    wait_for_submitted_request(runtime, rt_handle, task_id)
+}
+
+fn wait_for_submitted_request(
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    task_id: TaskId,
+) -> Result<helm_core::adapters::AdapterResponse, String> {
     let snapshot = rt_handle
         .block_on(runtime.wait_for_terminal(task_id, None))
         .map_err(format_core_error)?;
@@ -4795,6 +6506,7 @@ fn coordinator_submit_to_adapter(
             package_name,
             target_name,
             version,
+            options,
         } => AdapterRequest::Install(InstallRequest {
             package: PackageRef {
                 manager,
@@ -4802,11 +6514,13 @@ fn coordinator_submit_to_adapter(
             },
             target_name,
             version,
+            options,
         }),
         CoordinatorSubmitRequest::Uninstall {
             package_name,
             target_name,
             version,
+            disposal,
         } => AdapterRequest::Uninstall(UninstallRequest {
             package: PackageRef {
                 manager,
@@ -4814,15 +6528,21 @@ fn coordinator_submit_to_adapter(
             },
             target_name,
             version,
+            disposal,
         }),
         CoordinatorSubmitRequest::Upgrade {
             package_name,
+            target,
             target_name,
             version,
+            options,
+            cleanup_old_kegs,
         } => AdapterRequest::Upgrade(UpgradeRequest {
-            package: package_name.map(|name| PackageRef { manager, name }),
+            target: UpgradeTarget::from_wire(manager, target, package_name),
             target_name,
             version,
+            options,
+            cleanup_old_kegs,
         }),
         CoordinatorSubmitRequest::Pin {
             package_name,
@@ -4843,6 +6563,23 @@ fn coordinator_submit_to_adapter(
                 name: package_name,
             },
         }),
+        CoordinatorSubmitRequest::Rollback {
+            package_name,
+            version,
+        } => AdapterRequest::Rollback(RollbackRequest {
+            package: PackageRef {
+                manager,
+                name: package_name,
+            },
+            version,
+        }),
+        CoordinatorSubmitRequest::SetServiceState { service, action } => {
+            AdapterRequest::SetServiceState(SetServiceStateRequest { service, action })
+        }
+        CoordinatorSubmitRequest::Cleanup => AdapterRequest::Cleanup(CleanupRequest),
+        CoordinatorSubmitRequest::MeasurePackageSizes => {
+            AdapterRequest::MeasurePackageSizes(MeasurePackageSizesRequest)
+        }
         CoordinatorSubmitRequest::RustupAddComponent {
             toolchain,
             component,
@@ -4938,6 +6675,13 @@ fn coordinator_submit_to_adapter(
                 operation: PackageDetailOperation::SetProfile { profile },
             })
         }
+        CoordinatorSubmitRequest::HomebrewAddTap { tap, url } => {
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+                manager,
+                package: None,
+                operation: PackageDetailOperation::AddTap { tap, url },
+            })
+        }
     }
 }
 
@@ -4950,17 +6694,25 @@ fn adapter_request_to_coordinator_submit(
             package_name: install.package.name,
             target_name: install.target_name,
             version: install.version,
+            options: install.options,
         }),
         AdapterRequest::Uninstall(uninstall) => Ok(CoordinatorSubmitRequest::Uninstall {
             package_name: uninstall.package.name,
             target_name: uninstall.target_name,
             version: uninstall.version,
+            disposal: uninstall.disposal,
         }),
-        AdapterRequest::Upgrade(upgrade) => Ok(CoordinatorSubmitRequest::Upgrade {
-            package_name: upgrade.package.map(|package| package.name),
-            target_name: upgrade.target_name,
-            version: upgrade.version,
-        }),
+        AdapterRequest::Upgrade(upgrade) => {
+            let (target, package_name) = upgrade.target.to_wire();
+            Ok(CoordinatorSubmitRequest::Upgrade {
+                package_name,
+                target: Some(target),
+                target_name: upgrade.target_name,
+                version: upgrade.version,
+                options: upgrade.options,
+                cleanup_old_kegs: upgrade.cleanup_old_kegs,
+            })
+        }
         AdapterRequest::Pin(pin) => Ok(CoordinatorSubmitRequest::Pin {
             package_name: pin.package.name,
             version: pin.version,
@@ -4969,6 +6721,16 @@ fn adapter_request_to_coordinator_submit(
             package_name: unpin.package.name,
             version: None,
         }),
+        AdapterRequest::Rollback(rollback) => Ok(CoordinatorSubmitRequest::Rollback {
+            package_name: rollback.package.name,
+            version: rollback.version,
+        }),
+        AdapterRequest::SetServiceState(request) => Ok(CoordinatorSubmitRequest::SetServiceState {
+            service: request.service,
+            action: request.action,
+        }),
+        AdapterRequest::Cleanup(_) => Ok(CoordinatorSubmitRequest::Cleanup),
+        AdapterRequest::MeasurePackageSizes(_) => Ok(CoordinatorSubmitRequest::MeasurePackageSizes),
         AdapterRequest::ConfigurePackageDetail(request) => match request.operation {
             PackageDetailOperation::AddChild {
                 kind: PackageDetailChildKind::Component,
@@ -5039,6 +6801,20 @@ fn adapter_request_to_coordinator_submit(
             PackageDetailOperation::SetProfile { profile } => {
                 Ok(CoordinatorSubmitRequest::RustupSetProfile { profile })
             }
+            PackageDetailOperation::AddChild {
+                kind: PackageDetailChildKind::Tap,
+                value,
+            } => Ok(CoordinatorSubmitRequest::HomebrewAddTap {
+                tap: value,
+                url: None,
+            }),
+            PackageDetailOperation::AddTap { tap, url } => {
+                Ok(CoordinatorSubmitRequest::HomebrewAddTap { tap, url })
+            }
+            PackageDetailOperation::RemoveChild {
+                kind: PackageDetailChildKind::Tap,
+                ..
+            } => Err("coordinator submit request does not support removing taps".to_string()),
         },
         unsupported => Err(format!(
             "coordinator submit request does not support adapter action '{:?}'",
@@ -5073,7 +6849,16 @@ fn adapter_response_to_coordinator_payload(
         helm_core::adapters::AdapterResponse::SnapshotSync {
             installed: _,
             outdated: _,
+            stage_failures: _,
         } => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::SelfVersion(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::PackageFiles(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::Dependencies(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::RequestedPackages(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::PackageInfo(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::Services(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::Cleanup(_) => CoordinatorPayload::Refreshed,
+        helm_core::adapters::AdapterResponse::PackageSizes(_) => CoordinatorPayload::Refreshed,
         helm_core::adapters::AdapterResponse::SearchResults(results) => {
             CoordinatorPayload::SearchResults {
                 count: results.len(),
@@ -5085,6 +6870,7 @@ fn adapter_response_to_coordinator_payload(
             action: format!("{:?}", mutation.action).to_lowercase(),
             before_version: mutation.before_version,
             after_version: mutation.after_version,
+            disposal: mutation.disposal,
         },
     }
 }
@@ -5096,160 +6882,235 @@ fn adapter_response_to_coordinator_payload(
 /// `db_path` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_init(db_path: *const c_char) -> bool {
-    if db_path.is_null() {
-        return false;
-    }
+    ffi_boundary("helm_init", || {
+        if db_path.is_null() {
+            return false;
+        }
 
-    // If already initialized, return true
-    if lock_or_recover(&STATE, "state").is_some() {
-        return true;
-    }
+        // If already initialized, return true
+        if lock_or_recover(&STATE, "state").is_some() {
+            return true;
+        }
 
-    let c_str = unsafe { CStr::from_ptr(db_path) };
-    let path_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return false,
-    };
+        let c_str = unsafe { CStr::from_ptr(db_path) };
+        let path_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
 
-    // Initialize logging
-    let _ = tracing_subscriber::fmt::try_init();
+        // Initialize logging
+        let _ = tracing_subscriber::fmt::try_init();
 
-    // Create Tokio Runtime
-    let rt = match tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-    {
-        Ok(rt) => rt,
-        Err(e) => {
-            eprintln!("Failed to create Tokio runtime: {}", e);
-            return false;
+        // The service runs adapters with the user's privileges; only spawn
+        // allowlisted manager binaries and the installer scripts they download.
+        set_command_policy_mode(CommandPolicyMode::Enforce);
+
+        // Task scratch directories live in Helm's cache; clear any a crash left behind.
+        if let Some(root) = helm_core::execution::task_temp_dir_store::default_temp_root() {
+            helm_core::execution::task_temp_dir_store::set_temp_root(root);
         }
-    };
+        sweep_task_temp_dirs();
 
-    // Initialize Store
-    let store = Arc::new(SqliteStore::new(path_str));
-    if let Err(e) = store.migrate_to_latest() {
-        eprintln!("Failed to migrate DB: {}", e);
-        return false;
-    }
+        // Create Tokio Runtime
+        let rt = match tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                eprintln!("Failed to create Tokio runtime: {}", e);
+                return false;
+            }
+        };
 
-    // Initialize Adapters
-    let executor = Arc::new(TokioProcessExecutor);
-
-    let homebrew_adapter = Arc::new(HomebrewAdapter::new(ProcessHomebrewSource::new(
-        executor.clone(),
-    )));
-    let homebrew_cask_adapter = Arc::new(HomebrewCaskAdapter::new(ProcessHomebrewCaskSource::new(
-        executor.clone(),
-    )));
-    let mise_adapter = Arc::new(MiseAdapter::new(ProcessMiseSource::new(executor.clone())));
-    let asdf_adapter = Arc::new(AsdfAdapter::new(ProcessAsdfSource::new(executor.clone())));
-    let npm_adapter = Arc::new(NpmAdapter::new(ProcessNpmSource::new(executor.clone())));
-    let pnpm_adapter = Arc::new(PnpmAdapter::new(ProcessPnpmSource::new(executor.clone())));
-    let yarn_adapter = Arc::new(YarnAdapter::new(ProcessYarnSource::new(executor.clone())));
-    let cargo_adapter = Arc::new(CargoAdapter::new(ProcessCargoSource::new(executor.clone())));
-    let cargo_binstall_adapter = Arc::new(CargoBinstallAdapter::new(
-        ProcessCargoBinstallSource::new(executor.clone(), store.clone()),
-    ));
-    let pip_adapter = Arc::new(PipAdapter::new(ProcessPipSource::new(executor.clone())));
-    let pipx_adapter = Arc::new(PipxAdapter::new(ProcessPipxSource::new(executor.clone())));
-    let poetry_adapter = Arc::new(PoetryAdapter::new(ProcessPoetrySource::new(
-        executor.clone(),
-    )));
-    let rubygems_adapter = Arc::new(RubyGemsAdapter::new(ProcessRubyGemsSource::new(
-        executor.clone(),
-    )));
-    let bundler_adapter = Arc::new(BundlerAdapter::new(ProcessBundlerSource::new(
-        executor.clone(),
-    )));
-    let rustup_adapter = Arc::new(RustupAdapter::new(ProcessRustupSource::new(
-        executor.clone(),
-    )));
-    let softwareupdate_adapter = Arc::new(SoftwareUpdateAdapter::new(
-        ProcessSoftwareUpdateSource::new(executor.clone()),
-    ));
-    let macports_adapter = Arc::new(MacPortsAdapter::new(ProcessMacPortsSource::new(
-        executor.clone(),
-    )));
-    let nix_darwin_adapter = Arc::new(NixDarwinAdapter::new(ProcessNixDarwinSource::new(
-        executor.clone(),
-    )));
-    let mas_adapter = Arc::new(MasAdapter::new(ProcessMasSource::new(executor.clone())));
-    let docker_desktop_adapter = Arc::new(DockerDesktopAdapter::new(
-        ProcessDockerDesktopSource::new(executor.clone()),
-    ));
-    let podman_adapter = Arc::new(PodmanAdapter::new(ProcessPodmanSource::new(
-        executor.clone(),
-    )));
-    let colima_adapter = Arc::new(ColimaAdapter::new(ProcessColimaSource::new(
-        executor.clone(),
-    )));
-    let sparkle_adapter = Arc::new(SparkleAdapter::new(ProcessSparkleSource::new(
-        executor.clone(),
-    )));
-    let setapp_adapter = Arc::new(SetappAdapter::new(ProcessSetappSource::new(
-        executor.clone(),
-    )));
-    let parallels_desktop_adapter = Arc::new(ParallelsDesktopAdapter::new(
-        ProcessParallelsDesktopSource::new(executor.clone()),
-    ));
-    let xcode_command_line_tools_adapter = Arc::new(XcodeCommandLineToolsAdapter::new(
-        ProcessXcodeCommandLineToolsSource::new(executor.clone()),
-    ));
-    let rosetta2_adapter = Arc::new(Rosetta2Adapter::new(ProcessRosetta2Source::new(
-        executor.clone(),
-    )));
-    let firmware_updates_adapter = Arc::new(FirmwareUpdatesAdapter::new(
-        ProcessFirmwareUpdatesSource::new(executor.clone()),
-    ));
+        // Initialize Store, Adapters, and Orchestration for the active profile
+        let profiles = ProfileDirectory::new(path_str);
+        let mut profile = profiles.active_profile();
+        let engine = match profiles
+            .database_path(&profile)
+            .map_err(|error| error.to_string())
+            .and_then(|path| open_profile_engine(&path))
+        {
+            Ok(engine) => engine,
+            Err(e) if profile != DEFAULT_PROFILE => {
+                eprintln!("Failed to open Helm profile '{profile}', falling back to default: {e}");
+                profile = DEFAULT_PROFILE.to_string();
+                match open_profile_engine(Path::new(path_str)) {
+                    Ok(engine) => engine,
+                    Err(e) => {
+                        eprintln!("Failed to open Helm engine: {}", e);
+                        return false;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to open Helm engine: {}", e);
+                return false;
+            }
+        };
+        let ProfileEngine {
+            engine,
+            store,
+            runtime,
+        } = engine;
 
-    let adapters: Vec<Arc<dyn helm_core::adapters::ManagerAdapter>> = vec![
-        homebrew_adapter,
-        homebrew_cask_adapter,
-        mise_adapter,
-        asdf_adapter,
-        npm_adapter,
-        pnpm_adapter,
-        yarn_adapter,
-        cargo_adapter,
-        cargo_binstall_adapter,
-        pip_adapter,
-        pipx_adapter,
-        poetry_adapter,
-        rubygems_adapter,
-        bundler_adapter,
-        rustup_adapter,
-        softwareupdate_adapter,
-        macports_adapter,
-        nix_darwin_adapter,
-        mas_adapter,
-        docker_desktop_adapter,
-        podman_adapter,
-        colima_adapter,
-        sparkle_adapter,
-        setapp_adapter,
-        parallels_desktop_adapter,
-        xcode_command_line_tools_adapter,
-        rosetta2_adapter,
-        firmware_updates_adapter,
-    ];
+        let rt_handle = rt.handle().clone();
 
-    // Initialize Orchestration
-    let runtime = match AdapterRuntime::with_all_stores(
-        adapters,
-        store.clone(),
-        store.clone(),
+        let reconciled_stale_tasks = reconcile_stale_local_inflight_tasks(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            STALE_INFLIGHT_TASK_LOG_CONTEXT_STARTUP,
+        );
+        if reconciled_stale_tasks > 0 {
+            eprintln!(
+                "helm_init: reconciled {} stale queued/running task record(s)",
+                reconciled_stale_tasks
+            );
+        }
+
+        let coordinator_rt_handle = rt_handle.clone();
+
+        let state = HelmState {
+            engine,
+            store: store.clone(),
+            runtime: runtime.clone(),
+            profiles,
+            profile,
+            rt_handle,
+            tokio_rt: rt,
+        };
+
+        *lock_or_recover(&STATE, "state") = Some(state);
+        helm_core::reachability::install_reachability_probe(Arc::new(
+            HttpsHeadReachabilityProbe::new(),
+        ));
+        helm_engine::registry_clients::install_registry_clients();
+        start_launch_prewarm(store.clone(), runtime.clone(), &coordinator_rt_handle);
+        initialize_coordinator_bridge(store, runtime, coordinator_rt_handle);
+
+        true
+    })
+}
+
+/// Tear down the engine so the service can call `helm_init` again, for example
+/// after a memory-pressure restart. Returns true once the state is released,
+/// including when Helm was not initialized.
+///
+/// In-flight adapter tasks are cancelled and given a few seconds to reach a
+/// terminal state, task persistence is quiesced while the SQLite write-ahead
+/// log is checkpointed, and the Tokio runtime is shut down before returning.
+/// Must not be called from a Helm runtime thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_shutdown() -> bool {
+    ffi_boundary("helm_shutdown", || {
+        clear_last_error_key();
+        // Taking the state first turns away new calls while teardown runs.
+        let Some(state) = lock_or_recover(&STATE, "state").take() else {
+            return true;
+        };
+        ENGINE_GENERATION.fetch_add(1, Ordering::SeqCst);
+        COORDINATOR_SERVER_STARTED.store(false, Ordering::SeqCst);
+
+        let HelmState {
+            engine,
+            store,
+            runtime,
+            rt_handle,
+            tokio_rt,
+            ..
+        } = state;
+
+        let inflight_task_ids = store
+            .list_recent_tasks(TASK_RECENT_FETCH_LIMIT)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|task| {
+                is_inflight_status(task.status)
+                    && runtime_task_is_inflight(runtime.as_ref(), &rt_handle, task.id)
+            })
+            .map(|task| task.id)
+            .collect::<Vec<_>>();
+        let mode = CancellationMode::Graceful {
+            grace_period: Duration::from_millis(SHUTDOWN_TASK_GRACE_MS),
+        };
+        for task_id in &inflight_task_ids {
+            if let Err(error) = rt_handle.block_on(runtime.cancel(*task_id, mode)) {
+                eprintln!(
+                    "helm_shutdown: failed to cancel task {}: {error}",
+                    task_id.0
+                );
+            }
+        }
+        for task_id in &inflight_task_ids {
+            let _ =
+                rt_handle.block_on(runtime.wait_for_terminal(
+                    *task_id,
+                    Some(Duration::from_secs(SHUTDOWN_TASK_WAIT_SECS)),
+                ));
+        }
+
+        {
+            let _quiesced = rt_handle.block_on(runtime.quiesce());
+            if let Err(error) = store.checkpoint() {
+                eprintln!("helm_shutdown: failed to checkpoint database: {error}");
+            }
+        }
+
+        drop(engine);
+        drop(runtime);
+        drop(store);
+        tokio_rt.shutdown_timeout(Duration::from_secs(SHUTDOWN_RUNTIME_TIMEOUT_SECS));
+
+        invalidate_package_snapshot_cache();
+        true
+    })
+}
+
+/// A profile's engine, plus the store and runtime it wraps for the exports
+/// the facade does not cover.
+struct ProfileEngine {
+    engine: Arc<helm_engine::Engine>,
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+}
+
+/// Open the engine for one profile's database and load that database's
+/// settings into the process-wide adapter state.
+fn open_profile_engine(database_path: &Path) -> Result<ProfileEngine, String> {
+    let store = Arc::new(SqliteStore::new(database_path));
+    store
+        .migrate_to_latest()
+        .map_err(|error| error.to_string())?;
+    let adapters = helm_engine::default_adapters(
+        Arc::new(helm_core::execution::tokio_process::TokioProcessExecutor),
         store.clone(),
+    );
+    let listener = Arc::new(PackageSnapshotCacheListener {
+        store: store.clone(),
+        engine: OnceLock::new(),
+    });
+    let runtime = Arc::new(
+        helm_engine::adapter_runtime(store.clone(), adapters)
+            .map_err(|error| error.to_string())?
+            .with_persistence_listener(listener.clone()),
+    );
+    let engine = Arc::new(helm_engine::Engine::with_runtime(
         store.clone(),
-    ) {
-        Ok(rt) => Arc::new(rt),
-        Err(e) => {
-            eprintln!("Failed to create adapter runtime: {}", e);
-            return false;
-        }
-    };
+        runtime.clone(),
+    ));
+    let _ = listener.engine.set(Arc::downgrade(&engine));
+    load_profile_settings(&store);
+    Ok(ProfileEngine {
+        engine,
+        store,
+        runtime,
+    })
+}
 
-    let rt_handle = rt.handle().clone();
+fn load_profile_settings(store: &Arc<SqliteStore>) {
+    invalidate_package_snapshot_cache();
+    hydrate_executable_discovery_cache(store);
 
     let detection_map: std::collections::HashMap<_, _> = store
         .list_detections()
@@ -5263,385 +7124,1061 @@ pub unsafe extern "C" fn helm_init(db_path: *const c_char) -> bool {
         .map(|pref| (pref.manager, pref))
         .collect();
     sync_manager_executable_overrides(&detection_map, &pref_map);
-    let reconciled_stale_tasks = reconcile_stale_local_inflight_tasks(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        STALE_INFLIGHT_TASK_LOG_CONTEXT_STARTUP,
-    );
-    if reconciled_stale_tasks > 0 {
-        eprintln!(
-            "helm_init: reconciled {} stale queued/running task record(s)",
-            reconciled_stale_tasks
-        );
-    }
-
-    let coordinator_rt_handle = rt_handle.clone();
+    sync_homebrew_env_overrides(store);
+    sync_homebrew_cask_settings(store);
+    sync_command_timing_settings(store);
+    sync_process_qos_settings(store);
+    sync_npm_settings(store);
+    sync_network_policy_settings(store);
+}
 
-    let state = HelmState {
-        store: store.clone(),
-        runtime: runtime.clone(),
-        rt_handle,
-        _tokio_rt: rt,
+fn load_package_snapshot(
+    engine: &helm_engine::Engine,
+    store: &SqliteStore,
+) -> PersistenceResult<FfiPackageSnapshot> {
+    let (enabled_by_manager, installed, outdated) = engine.read_snapshot(|| {
+        let enabled_by_manager = manager_enabled_map(store);
+        let installed = engine.list_installed()?;
+        let outdated = engine.list_outdated()?;
+        PersistenceResult::Ok((enabled_by_manager, installed, outdated))
+    })??;
+    let is_listed = |package: &PackageRef| {
+        package.is_user_visible_package()
+            && manager_is_enabled(&enabled_by_manager, package.manager)
     };
+    Ok(FfiPackageSnapshot {
+        installed: installed
+            .into_iter()
+            .filter(|package| is_listed(&package.package))
+            .collect(),
+        outdated: outdated
+            .into_iter()
+            .filter(|package| is_listed(&package.package))
+            .collect(),
+    })
+}
 
-    *lock_or_recover(&STATE, "state") = Some(state);
-    initialize_coordinator_bridge(store, runtime, coordinator_rt_handle);
-
-    true
+fn rebuild_package_snapshot_cache(
+    engine: &helm_engine::Engine,
+    store: &SqliteStore,
+) -> PersistenceResult<Arc<FfiPackageSnapshot>> {
+    let generation = PACKAGE_SNAPSHOT_CACHE.begin_rebuild();
+    let snapshot = Arc::new(load_package_snapshot(engine, store)?);
+    PACKAGE_SNAPSHOT_CACHE.publish_if_current(generation, snapshot.clone());
+    Ok(snapshot)
 }
 
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_list_installed_packages() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+/// Drop cached package lists after a write that bypasses the adapter runtime
+/// (manager enablement, executable selection, pins, detection, database reset).
+fn invalidate_package_snapshot_cache() {
+    PACKAGE_SNAPSHOT_CACHE.invalidate();
+}
 
-    let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+/// Cached package lists, rebuilt from SQLite only after an invalidation.
+///
+/// An external coordinator persists from another process and cannot notify this
+/// cache, so that mode always reads through to SQLite.
+fn package_snapshot(operation: &str) -> Option<Arc<FfiPackageSnapshot>> {
+    let external_coordinator = external_coordinator_state_dir().is_some();
+    if !external_coordinator && let Some(snapshot) = PACKAGE_SNAPSHOT_CACHE.load() {
+        return Some(snapshot);
+    }
 
-    let packages = match state.store.list_installed() {
-        Ok(pkgs) => pkgs,
+    let (engine, store) = {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = guard.as_ref()?;
+        (state.engine.clone(), state.store.clone())
+    };
+    let result = if external_coordinator {
+        load_package_snapshot(&engine, store.as_ref()).map(Arc::new)
+    } else {
+        rebuild_package_snapshot_cache(&engine, store.as_ref())
+    };
+    match result {
+        Ok(snapshot) => Some(snapshot),
         Err(e) => {
-            eprintln!("Failed to list installed packages: {}", e);
-            return std::ptr::null_mut();
+            eprintln!("Failed to {operation}: {e}");
+            None
         }
     }
-    .into_iter()
-    .filter(|package| {
-        package.package.is_user_visible_package()
-            && manager_is_enabled(&enabled_by_manager, package.package.manager)
-    })
-    .collect::<Vec<_>>();
+}
 
-    let json = match serde_json::to_string(&packages) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+/// An installed row as the list calls return it: the package plus its
+/// measured install size, if any, as a top-level `installed_size_bytes`.
+#[derive(serde::Serialize)]
+struct FfiInstalledPackage<'a> {
+    #[serde(flatten)]
+    package: &'a InstalledPackage,
+    installed_size_bytes: Option<u64>,
+}
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+fn installed_packages_json(packages: &[InstalledPackage]) -> serde_json::Result<String> {
+    let rows: Vec<FfiInstalledPackage<'_>> = packages
+        .iter()
+        .map(|package| FfiInstalledPackage {
+            package,
+            installed_size_bytes: package.sizes.install_size_bytes,
+        })
+        .collect();
+    serde_json::to_string(&rows)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_list_outdated_packages() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+pub extern "C" fn helm_list_installed_packages() -> *mut c_char {
+    ffi_boundary("helm_list_installed_packages", || {
+        let Some(snapshot) = package_snapshot("list installed packages") else {
+            return std::ptr::null_mut();
+        };
 
-    let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+        let json = match installed_packages_json(&snapshot.installed) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
 
-    let packages = match state.store.list_outdated() {
-        Ok(pkgs) => pkgs,
-        Err(e) => {
-            eprintln!("Failed to list outdated packages: {}", e);
-            return std::ptr::null_mut();
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
         }
-    }
-    .into_iter()
-    .filter(|package| {
-        package.package.is_user_visible_package()
-            && manager_is_enabled(&enabled_by_manager, package.package.manager)
     })
-    .collect::<Vec<_>>();
-
-    let json = match serde_json::to_string(&packages) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+}
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_outdated_packages() -> *mut c_char {
+    ffi_boundary("helm_list_outdated_packages", || {
+        let Some(snapshot) = package_snapshot("list outdated packages") else {
+            return std::ptr::null_mut();
+        };
+
+        let json = match serde_json::to_string(&snapshot.outdated) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Word lists for shell completion as JSON: enabled manager ids, installed
+/// package names per manager, and task types. Served from the package
+/// snapshot cache, so it is cheap enough to call on every completion.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_completion_data() -> *mut c_char {
+    ffi_boundary("helm_get_completion_data", || {
+        clear_last_error_key();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
+        };
+        let Some(snapshot) = package_snapshot("build completion data") else {
+            return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+        };
+        let enabled_by_manager = manager_enabled_map(store.as_ref());
+        let data = helm_core::completion::build_completion_data(&snapshot.installed, |manager| {
+            manager_is_enabled(&enabled_by_manager, manager)
+        });
+
+        let json = match serde_json::to_string(&data) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+unsafe fn parse_package_list_sort(sort: *const c_char) -> Option<PackageListSort> {
+    if sort.is_null() {
+        return Some(PackageListSort::Default);
     }
+    unsafe { CStr::from_ptr(sort) }.to_str().ok()?.parse().ok()
 }
 
-/// Return rustup toolchain-scoped component and target detail as JSON.
+/// Return installed packages as JSON, ordered by `sort`.
+///
+/// `sort` is `"default"` (manager, then name) or `"size"` (largest reported
+/// size first, unsized packages last). Null selects the default order.
 ///
 /// # Safety
 ///
-/// `toolchain` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `sort` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_get_rustup_toolchain_detail(toolchain: *const c_char) -> *mut c_char {
-    clear_last_error_key();
-    if toolchain.is_null() {
-        return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
-    }
+pub unsafe extern "C" fn helm_list_installed_packages_sorted(sort: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_list_installed_packages_sorted", || {
+        clear_last_error_key();
+        let Some(sort) = (unsafe { parse_package_list_sort(sort) }) else {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Some(snapshot) = package_snapshot("list installed packages") else {
+            return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+        };
 
-    let toolchain_cstr = unsafe { CStr::from_ptr(toolchain) };
-    let toolchain_name = match toolchain_cstr.to_str() {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let mut packages = snapshot.installed.clone();
+        sort.sort_installed(&mut packages);
+        match installed_packages_json(&packages)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
 
-    let detail = match load_rustup_toolchain_detail_from_state(
-        toolchain_name.as_str(),
-        "helm_get_rustup_toolchain_detail",
-    ) {
-        Ok(detail) => detail,
-        Err(error_key) => return return_error_ptr(error_key),
-    };
+/// Return outdated packages as JSON, ordered by `sort`.
+///
+/// `sort` is `"default"` (manager, then name) or `"size"` (largest reported
+/// size first, unsized packages last). Null selects the default order.
+///
+/// # Safety
+///
+/// `sort` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_list_outdated_packages_sorted(sort: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_list_outdated_packages_sorted", || {
+        clear_last_error_key();
+        let Some(sort) = (unsafe { parse_package_list_sort(sort) }) else {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Some(snapshot) = package_snapshot("list outdated packages") else {
+            return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+        };
 
-    let json = match serde_json::to_string(&detail) {
-        Ok(json) => json,
-        Err(error) => {
-            eprintln!("helm_get_rustup_toolchain_detail: failed to encode JSON: {error}");
-            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        let mut packages = snapshot.outdated.clone();
+        sort.sort_outdated(&mut packages);
+        match serde_json::to_string(&packages)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    };
-
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
-    }
+    })
 }
 
+/// Return outdated packages that upgrades skip as JSON, each annotated with
+/// the reason it is held back (native pin, virtual pin, disabled manager).
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_list_tasks() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+pub extern "C" fn helm_list_held_back_packages() -> *mut c_char {
+    ffi_boundary("helm_list_held_back_packages", || {
+        clear_last_error_key();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
+        };
 
-    // Auto-prune completed/cancelled tasks older than 5 minutes.
-    let _ = state.store.prune_completed_tasks(TASK_PRUNE_MAX_AGE_SECS);
+        let loaded = store.read_snapshot(|| {
+            let enabled_by_manager = manager_enabled_map(store.as_ref());
+            let outdated = store.list_outdated()?;
+            let pins = store.list_pins()?;
+            PersistenceResult::Ok((enabled_by_manager, outdated, pins))
+        });
+        let (enabled_by_manager, outdated, pins) = match loaded {
+            Ok(Ok(loaded)) => loaded,
+            Ok(Err(error)) | Err(error) => {
+                eprintln!("helm_list_held_back_packages: failed to read store: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+        let held_back = held_back_packages(outdated, &pins, |manager| {
+            manager_is_enabled(&enabled_by_manager, manager)
+        });
 
-    let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+        match serde_json::to_string(&held_back)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
 
-    // Fetch a wider snapshot so long-running queued/running tasks do not disappear
-    // behind a tight recent-task limit.
-    let raw_tasks = match state.store.list_recent_tasks(TASK_RECENT_FETCH_LIMIT) {
-        Ok(tasks) => tasks,
-        Err(e) => {
-            eprintln!("Failed to list tasks: {}", e);
-            return std::ptr::null_mut();
+/// Return rustup toolchain-scoped component and target detail as JSON.
+///
+/// # Safety
+///
+/// `toolchain` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_get_rustup_toolchain_detail(toolchain: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_get_rustup_toolchain_detail", || {
+        clear_last_error_key();
+        if toolchain.is_null() {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
         }
-    }
-    .into_iter()
-    .filter(|task| manager_is_enabled(&enabled_by_manager, task.manager))
-    .collect::<Vec<_>>();
-    #[derive(serde::Serialize)]
-    struct FfiTaskRecord {
-        id: helm_core::models::TaskId,
-        manager: ManagerId,
-        task_type: helm_core::models::TaskType,
-        status: helm_core::models::TaskStatus,
-        label_key: Option<String>,
-        label_args: Option<std::collections::BTreeMap<String, String>>,
-    }
 
-    let mut labels = lock_or_recover(&TASK_LABELS, "task_labels");
-    let fetched_ids: std::collections::HashSet<u64> =
-        raw_tasks.iter().map(|task| task.id.0).collect();
-    let visible_tasks = build_visible_tasks(raw_tasks, &labels);
-    labels.retain(|task_id, _| fetched_ids.contains(task_id));
+        let toolchain_cstr = unsafe { CStr::from_ptr(toolchain) };
+        let toolchain_name = match toolchain_cstr.to_str() {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let ffi_tasks: Vec<FfiTaskRecord> = visible_tasks
-        .iter()
-        .map(|task| FfiTaskRecord {
-            id: task.id,
-            manager: task.manager,
-            task_type: task.task_type,
-            status: task.status,
-            label_key: labels.get(&task.id.0).map(|label| label.key.clone()),
-            label_args: labels.get(&task.id.0).and_then(|label| {
-                if label.args.is_empty() {
-                    None
-                } else {
-                    Some(label.args.clone())
-                }
-            }),
-        })
-        .collect();
-    drop(labels);
+        let detail = match load_rustup_toolchain_detail_from_state(
+            toolchain_name.as_str(),
+            "helm_get_rustup_toolchain_detail",
+        ) {
+            Ok(detail) => detail,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
 
-    let json = match serde_json::to_string(&ffi_tasks) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let json = match serde_json::to_string(&detail) {
+            Ok(json) => json,
+            Err(error) => {
+                eprintln!("helm_get_rustup_toolchain_detail: failed to encode JSON: {error}");
+                return return_error_ptr(SERVICE_ERROR_INTERNAL);
+            }
+        };
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct FfiTaskOutputRecord {
-    task_id: TaskId,
-    command: Option<String>,
-    cwd: Option<String>,
-    program_path: Option<String>,
-    path_snippet: Option<String>,
-    started_at_unix_ms: Option<i64>,
-    finished_at_unix_ms: Option<i64>,
-    duration_ms: Option<u64>,
-    exit_code: Option<i32>,
-    termination_reason: Option<String>,
-    error_code: Option<String>,
-    error_message: Option<String>,
-    stdout: Option<String>,
-    stderr: Option<String>,
+struct FfiPackageFiles {
+    manager_id: String,
+    package_name: String,
+    files: Vec<String>,
+    total: usize,
+    truncated: bool,
 }
 
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct FfiTaskLogRecord {
-    id: u64,
-    task_id: TaskId,
-    manager: ManagerId,
-    task_type: TaskType,
-    status: Option<&'static str>,
-    level: &'static str,
-    message: String,
-    created_at_unix: i64,
+fn build_ffi_package_files(
+    package: &PackageRef,
+    files: helm_core::models::PackageFiles,
+) -> FfiPackageFiles {
+    FfiPackageFiles {
+        manager_id: package.manager.as_str().to_string(),
+        package_name: package.name.clone(),
+        truncated: files.truncated(),
+        total: files.total,
+        files: files.files,
+    }
 }
 
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct FfiTaskTimeoutPromptRecord {
-    task_id: TaskId,
-    manager: ManagerId,
-    task_type: TaskType,
-    action: &'static str,
-    requested_at_unix_ms: i64,
-    grace_seconds: u64,
-    suggested_extension_seconds: u64,
-}
+/// List the files an installed package owns as JSON, for managers with the
+/// `ListFiles` capability (Homebrew formulae, MacPorts, pip, npm).
+///
+/// At most `limit` paths are returned (`0` returns all of them); `total`
+/// always reports the full count.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_list_package_files(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    limit: i64,
+) -> *mut c_char {
+    ffi_boundary("helm_list_package_files", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let package_name = match unsafe { parse_required_cstr_arg(package_name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        if limit < 0 {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        }
+        let Some((runtime, rt_handle)) = active_state_runtime() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
 
-const DIAGNOSTICS_REDACTION_PLACEHOLDER: &str = "[REDACTED]";
-const DIAGNOSTICS_ALLOWED_ENV_KEYS: &[&str] = &[
-    "PATH", "PWD", "SHELL", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "TMPDIR", "TMP", "TEMP",
-];
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let request = AdapterRequest::ListFiles(helm_core::adapters::ListFilesRequest {
+            package: package.clone(),
+            limit: (limit > 0).then_some(limit as usize),
+        });
+        let files =
+            match rt_handle.block_on(runtime.submit_refresh_request_response(manager, request)) {
+                Ok(helm_core::adapters::AdapterResponse::PackageFiles(files)) => files,
+                Ok(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+                Err(error) => {
+                    eprintln!(
+                        "helm_list_package_files: failed to list files for {}:{}: {}",
+                        manager.as_str(),
+                        package.name,
+                        error.message
+                    );
+                    return return_core_error_ptr(&error);
+                }
+            };
 
-fn diagnostics_env_key_allowed(key: &str) -> bool {
-    DIAGNOSTICS_ALLOWED_ENV_KEYS
-        .iter()
-        .any(|allowed| key.eq_ignore_ascii_case(allowed))
+        let json = match serde_json::to_string(&build_ffi_package_files(&package, files)) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-fn looks_like_env_key(key: &str) -> bool {
-    let mut chars = key.chars();
-    let Some(first) = chars.next() else {
-        return false;
-    };
-    if !(first.is_ascii_alphabetic() || first == '_') {
-        return false;
-    }
-    chars.all(|character| character.is_ascii_alphanumeric() || character == '_')
-}
+/// Report a package's installed dependencies and dependents as JSON
+/// `{managerId, packageName, dependencies, dependents, orphanedDependencies}`,
+/// for managers with the `ListDependencies` capability (Homebrew formulae).
+///
+/// The graph is captured during refresh; if none is stored yet it is captured
+/// now. `dependents` break if the package is uninstalled, and
+/// `orphanedDependencies` are no longer needed by anything else.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_list_package_dependencies(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+) -> *mut c_char {
+    ffi_boundary("helm_list_package_dependencies", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let package_name = match unsafe { parse_required_cstr_arg(package_name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let (Some(store), Some((runtime, rt_handle))) =
+            (active_state_store(), active_state_runtime())
+        else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        if !runtime.supports_capability(manager, Capability::ListDependencies) {
+            return return_error_ptr(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
 
-fn normalize_sensitive_key(raw: &str) -> Option<String> {
-    let trimmed = raw.trim_matches(|character: char| {
-        !(character.is_ascii_alphanumeric() || character == '_' || character == '-')
-    });
-    if trimmed.is_empty() {
-        return None;
-    }
-    Some(trimmed.to_ascii_lowercase().replace('-', "_"))
-}
+        let mut edges = match store.list_dependency_graph(manager) {
+            Ok(edges) => edges,
+            Err(error) => {
+                eprintln!(
+                    "helm_list_package_dependencies: failed to read dependency graph: {error}"
+                );
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+        if edges.is_empty() {
+            let request =
+                AdapterRequest::ListDependencies(helm_core::adapters::ListDependenciesRequest);
+            edges = match rt_handle
+                .block_on(runtime.submit_refresh_request_response(manager, request))
+            {
+                Ok(helm_core::adapters::AdapterResponse::Dependencies(edges)) => edges,
+                Ok(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+                Err(error) => {
+                    eprintln!(
+                        "helm_list_package_dependencies: failed to capture dependency graph for {}: {}",
+                        manager.as_str(),
+                        error.message
+                    );
+                    return return_core_error_ptr(&error);
+                }
+            };
+        }
+        let installed: std::collections::HashSet<String> = match store.list_installed() {
+            Ok(packages) => packages
+                .into_iter()
+                .filter(|package| package.package.manager == manager)
+                .map(|package| package.package.name)
+                .collect(),
+            Err(error) => {
+                eprintln!(
+                    "helm_list_package_dependencies: failed to list installed packages: {error}"
+                );
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
 
-fn is_sensitive_key_name(key: &str) -> bool {
-    key.contains("token")
-        || key.contains("secret")
-        || key.contains("password")
-        || key.contains("passwd")
-        || key.contains("api_key")
-        || key.contains("apikey")
-        || key.contains("auth")
-        || key.contains("cookie")
-        || key.contains("session")
-        || key.contains("license_key")
-        || key.contains("private_key")
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let report =
+            helm_core::package_dependencies::dependency_report(&package, &edges, &installed);
+        let json = match serde_json::to_string(&report) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-fn redact_env_assignment_token(token: &str) -> Option<String> {
-    let (key, value) = token.split_once('=')?;
-    if value.is_empty() || !looks_like_env_key(key) {
-        return None;
-    }
-    if diagnostics_env_key_allowed(key) {
-        return Some(token.to_string());
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiPackageInfo {
+    manager_id: String,
+    package_name: String,
+    description: Option<String>,
+    homepage: Option<String>,
+    license: Option<String>,
+    installed_size_bytes: Option<u64>,
+    dependencies: Vec<String>,
+    aliases: Vec<helm_core::models::PackageAlias>,
+    fetched_at_unix: i64,
+    /// The manager could not be reached, so this is an expired cache entry.
+    stale: bool,
+}
+
+fn build_ffi_package_info(
+    package: &PackageRef,
+    metadata: helm_core::models::PackageMetadata,
+    fetched_at: SystemTime,
+    stale: bool,
+) -> FfiPackageInfo {
+    FfiPackageInfo {
+        manager_id: package.manager.as_str().to_string(),
+        package_name: package.name.clone(),
+        description: metadata.description,
+        homepage: metadata.homepage,
+        license: metadata.license,
+        installed_size_bytes: metadata.installed_size_bytes,
+        dependencies: metadata.dependencies,
+        aliases: metadata.aliases,
+        fetched_at_unix: fetched_at
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0),
+        stale,
     }
-    Some(format!("{key}={DIAGNOSTICS_REDACTION_PLACEHOLDER}"))
 }
 
-fn redact_sensitive_pair_token(token: &str) -> Option<String> {
-    let (key, delimiter, value) = if let Some((key, value)) = token.split_once('=') {
-        (key, '=', value)
-    } else if let Some((key, value)) = token.split_once(':') {
-        (key, ':', value)
-    } else {
-        return None;
-    };
-    if value.is_empty() {
-        return None;
-    }
-    let normalized = normalize_sensitive_key(key)?;
-    if !is_sensitive_key_name(normalized.as_str()) {
-        return None;
-    }
-    Some(format!(
-        "{key}{delimiter}{DIAGNOSTICS_REDACTION_PLACEHOLDER}"
-    ))
+fn package_metadata_is_fresh(fetched_at: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(fetched_at)
+        .map(|age| age < Duration::from_secs(PACKAGE_METADATA_CACHE_TTL_SECS))
+        .unwrap_or(true)
 }
 
-fn redact_auth_header_line(line: &str) -> Option<String> {
-    let trimmed = line.trim_start();
-    let lowercase = trimmed.to_ascii_lowercase();
-    if !(lowercase.starts_with("authorization:") || lowercase.starts_with("proxy-authorization:")) {
-        return None;
-    }
-    let indent_len = line.len().saturating_sub(trimmed.len());
-    let indent = &line[..indent_len];
-    let header_name = trimmed
-        .split_once(':')
-        .map(|(name, _)| name)
-        .unwrap_or("Authorization");
-    Some(format!(
-        "{indent}{header_name}: {DIAGNOSTICS_REDACTION_PLACEHOLDER}"
-    ))
-}
+/// Report a package's metadata as JSON `{managerId, packageName,
+/// description, homepage, license, installedSizeBytes, dependencies,
+/// aliases, fetchedAtUnix, stale}`, for managers with the `PackageInfo`
+/// capability (Homebrew formulae and casks, npm, pip, Cargo). `aliases` lists
+/// `{kind, value}` pairs such as a cask's display names, app bundles, and
+/// bundle ids.
+///
+/// Results are cached in the `package_metadata` table for a day. If the
+/// manager fails and an expired entry exists, it is returned with `stale`.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_get_package_info(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+) -> *mut c_char {
+    ffi_boundary("helm_get_package_info", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let package_name = match unsafe { parse_required_cstr_arg(package_name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let (Some(store), Some((runtime, rt_handle))) =
+            (active_state_store(), active_state_runtime())
+        else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        if !runtime.supports_capability(manager, Capability::PackageInfo) {
+            return return_error_ptr(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
 
-fn redact_diagnostics_text(value: &str) -> String {
-    let line_redacted = value
-        .lines()
-        .map(|line| redact_auth_header_line(line).unwrap_or_else(|| line.to_string()))
-        .collect::<Vec<_>>()
-        .join("\n");
-    let mut rendered = String::with_capacity(line_redacted.len());
-    let mut token = String::new();
-    for character in line_redacted.chars() {
-        if character.is_whitespace() {
-            if !token.is_empty() {
-                let redacted = redact_env_assignment_token(token.as_str())
-                    .or_else(|| redact_sensitive_pair_token(token.as_str()))
-                    .unwrap_or_else(|| token.clone());
-                rendered.push_str(redacted.as_str());
-                token.clear();
-            }
-            rendered.push(character);
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let cached = match store.package_metadata(&package) {
+            Ok(cached) => cached,
+            Err(error) => {
+                eprintln!("helm_get_package_info: failed to read cached metadata: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+        let now = SystemTime::now();
+        let info = match cached {
+            Some((metadata, fetched_at)) if package_metadata_is_fresh(fetched_at, now) => {
+                build_ffi_package_info(&package, metadata, fetched_at, false)
+            }
+            cached => {
+                let request =
+                    AdapterRequest::PackageInfo(helm_core::adapters::PackageInfoRequest {
+                        package: package.clone(),
+                    });
+                match rt_handle.block_on(runtime.submit_refresh_request_response(manager, request))
+                {
+                    Ok(helm_core::adapters::AdapterResponse::PackageInfo(metadata)) => {
+                        if let Err(error) = store.upsert_package_metadata(&package, &metadata, now)
+                        {
+                            eprintln!(
+                                "helm_get_package_info: failed to cache metadata for {}:{}: {error}",
+                                manager.as_str(),
+                                package.name
+                            );
+                        }
+                        build_ffi_package_info(&package, metadata, now, false)
+                    }
+                    Ok(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+                    Err(error) => {
+                        eprintln!(
+                            "helm_get_package_info: failed to read metadata for {}:{}: {}",
+                            manager.as_str(),
+                            package.name,
+                            error.message
+                        );
+                        let Some((metadata, fetched_at)) = cached else {
+                            return return_core_error_ptr(&error);
+                        };
+                        build_ffi_package_info(&package, metadata, fetched_at, true)
+                    }
+                }
+            }
+        };
+
+        let json = match serde_json::to_string(&info) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiPackageAliasMatch {
+    manager_id: String,
+    package_name: String,
+}
+
+/// Map an app name, display name, or bundle id to the packages it refers
+/// to, as a JSON array of `{managerId, packageName}`.
+///
+/// Matches package names and the aliases cached by `helm_get_package_info`
+/// after folding case, spacing, punctuation, and a trailing `.app`, so
+/// `Visual Studio Code.app` finds the `visual-studio-code` cask. An alias
+/// shared by two packages of one manager matches neither.
+///
+/// # Safety
+///
+/// `name` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_resolve_package_alias(name: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_resolve_package_alias", || {
+        clear_last_error_key();
+        let name = match unsafe { parse_required_cstr_arg(name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let Some(store) = active_state_store() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let aliases = match store.list_package_aliases() {
+            Ok(aliases) => aliases,
+            Err(error) => {
+                eprintln!("helm_resolve_package_alias: failed to read package aliases: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+
+        let matches = helm_core::package_aliases::PackageAliasIndex::from_aliases(aliases)
+            .lookup(&name)
+            .into_iter()
+            .map(|package| FfiPackageAliasMatch {
+                manager_id: package.manager.as_str().to_string(),
+                package_name: package.name,
+            })
+            .collect::<Vec<_>>();
+        let json = match serde_json::to_string(&matches) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiQueuedOrphanUninstall {
+    manager_id: String,
+    package_name: String,
+    task_id: i64,
+}
+
+/// Orphans of every enabled manager with the `ListRequested` capability, in
+/// uninstall order.
+fn reconcile_all_orphaned_packages(
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+) -> Result<Vec<OrphanedPackage>, &'static str> {
+    let mut orphans = Vec::new();
+    for manager in ManagerId::ALL {
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::ListRequested)
+        {
             continue;
         }
-        token.push(character);
-    }
-    if !token.is_empty() {
-        let redacted = redact_env_assignment_token(token.as_str())
-            .or_else(|| redact_sensitive_pair_token(token.as_str()))
-            .unwrap_or(token);
-        rendered.push_str(redacted.as_str());
-    }
-    if value.ends_with('\n') && !rendered.ends_with('\n') {
-        rendered.push('\n');
+        match rt_handle.block_on(runtime.reconcile_orphaned_packages(manager)) {
+            Ok(found) => orphans.extend(found),
+            Err(error) => {
+                eprintln!(
+                    "orphaned packages: reconciliation failed for {}: {}",
+                    manager.as_str(),
+                    error.message
+                );
+                return Err(core_error_service_key(&error));
+            }
+        }
     }
-    rendered
+    Ok(orphans)
+}
+
+/// List installed packages that were only ever installed as dependencies and
+/// that no requested package still needs, as a JSON array of
+/// `{managerId, packageName, pinned}` in uninstall order.
+///
+/// Covers managers with the `ListRequested` capability (Homebrew formulae).
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_orphaned_packages() -> *mut c_char {
+    ffi_boundary("helm_list_orphaned_packages", || {
+        clear_last_error_key();
+        let Some((runtime, rt_handle)) = active_state_runtime() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let orphans = match reconcile_all_orphaned_packages(runtime.as_ref(), &rt_handle) {
+            Ok(orphans) => orphans,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let json = match serde_json::to_string(&orphans) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+/// Queue uninstall tasks for every unpinned orphan reported by
+/// [`helm_list_orphaned_packages`]. Returns a JSON array of
+/// `{managerId, packageName, taskId}` for the queued tasks; pinned orphans
+/// and managers that cannot uninstall individual packages are skipped.
+///
+/// Tasks are queued dependents first, so each uninstall finds nothing left
+/// that needs its package.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_cleanup_orphans() -> *mut c_char {
+    ffi_boundary("helm_cleanup_orphans", || {
+        clear_last_error_key();
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(state) => state,
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+        let orphans = match reconcile_all_orphaned_packages(runtime.as_ref(), &rt_handle) {
+            Ok(orphans) => orphans,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+
+        let mut queued = Vec::new();
+        for orphan in orphans.into_iter().filter(|orphan| !orphan.pinned) {
+            let Ok(manager) = orphan.manager_id.parse::<ManagerId>() else {
+                continue;
+            };
+            if !supports_individual_package_uninstall(runtime.as_ref(), manager) {
+                continue;
+            }
+            let (label_key, label_args) = uninstall_task_label_for(manager, &orphan.package_name);
+            if let Some(existing) = find_matching_inflight_task(
+                store.as_ref(),
+                runtime.as_ref(),
+                &rt_handle,
+                manager,
+                TaskType::Uninstall,
+                Some(label_key),
+                &label_args,
+            ) {
+                queued.push(FfiQueuedOrphanUninstall {
+                    manager_id: orphan.manager_id,
+                    package_name: orphan.package_name,
+                    task_id: existing.0 as i64,
+                });
+                continue;
+            }
+
+            let request = AdapterRequest::Uninstall(UninstallRequest {
+                package: PackageRef {
+                    manager,
+                    name: orphan.package_name.clone(),
+                },
+                target_name: None,
+                version: None,
+                disposal: UninstallDisposal::Delete,
+            });
+            match rt_handle.block_on(runtime.submit_labeled(
+                manager,
+                request,
+                task_label(label_key, &label_args),
+            )) {
+                Ok(task_id) => {
+                    queued.push(FfiQueuedOrphanUninstall {
+                        manager_id: orphan.manager_id,
+                        package_name: orphan.package_name,
+                        task_id: task_id.0 as i64,
+                    });
+                }
+                Err(error) => {
+                    eprintln!(
+                        "cleanup_orphans: failed to queue uninstall of {}: {error}",
+                        orphan.package_name
+                    );
+                }
+            }
+        }
+
+        let json = match serde_json::to_string(&queued) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+/// Tasks for the task list, shaped by the `task_view_*` settings.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_tasks() -> *mut c_char {
+    ffi_boundary("helm_list_tasks", || {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        // Auto-prune completed/cancelled tasks older than 5 minutes; the task
+        // history keeps them under its own retention policy.
+        let _ = state.store.prune_completed_tasks(TASK_PRUNE_MAX_AGE_SECS);
+        prune_task_history(state.store.as_ref());
+        sweep_task_temp_dirs();
+
+        // Fetch a wider snapshot so long-running queued/running tasks do not disappear
+        // behind a tight recent-task limit.
+        let snapshot = state.engine.read_snapshot(|| {
+            let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+            state
+                .engine
+                .list_tasks(TASK_RECENT_FETCH_LIMIT)
+                .map(|tasks| (enabled_by_manager, tasks))
+        });
+        let (enabled_by_manager, raw_tasks) = match snapshot.and_then(|result| result) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("Failed to list tasks: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
+        let raw_tasks = raw_tasks
+            .into_iter()
+            .filter(|task| manager_is_enabled(&enabled_by_manager, task.manager))
+            .collect::<Vec<_>>();
+        #[derive(serde::Serialize)]
+        struct FfiTaskRecord {
+            id: helm_core::models::TaskId,
+            manager: ManagerId,
+            task_type: helm_core::models::TaskType,
+            status: helm_core::models::TaskStatus,
+            label_key: Option<String>,
+            label_args: Option<std::collections::BTreeMap<String, String>>,
+            pid: Option<u32>,
+            cpu_percent: Option<f64>,
+            rss_bytes: Option<u64>,
+            progress: Option<u8>,
+            attempt_of: Option<helm_core::models::TaskId>,
+            depends_on: Vec<helm_core::models::TaskId>,
+        }
+
+        let view_options = TaskViewOptions::from_store(state.store.as_ref()).unwrap_or_default();
+        let visible_tasks = build_visible_tasks(raw_tasks, &view_options);
+
+        let ffi_tasks: Vec<FfiTaskRecord> = visible_tasks
+            .iter()
+            .map(|task| {
+                // Process metrics and progress are only meaningful while the child is alive.
+                let running = task.status == helm_core::models::TaskStatus::Running;
+                let process = running
+                    .then(|| helm_core::execution::task_process(task.id))
+                    .flatten();
+                let progress = running
+                    .then(|| helm_core::execution::task_progress(task.id))
+                    .flatten();
+                let mut label_args = task
+                    .label
+                    .as_ref()
+                    .map(|label| label.args.clone())
+                    .unwrap_or_default();
+                if task.status == helm_core::models::TaskStatus::Queued {
+                    label_args.extend(conflict_wait_label_args(
+                        state.runtime.as_ref(),
+                        &state.rt_handle,
+                        task.id,
+                    ));
+                }
+                FfiTaskRecord {
+                    id: task.id,
+                    manager: task.manager,
+                    task_type: task.task_type,
+                    status: task.status,
+                    label_key: task.label.as_ref().map(|label| label.key.clone()),
+                    label_args: (!label_args.is_empty()).then_some(label_args),
+                    pid: process.as_ref().map(|process| process.pid),
+                    cpu_percent: process.as_ref().and_then(|process| process.cpu_percent),
+                    rss_bytes: process.as_ref().and_then(|process| process.rss_bytes),
+                    progress,
+                    attempt_of: task.attempt_of,
+                    depends_on: task_dependencies(
+                        state.runtime.as_ref(),
+                        &state.rt_handle,
+                        task.id,
+                    ),
+                }
+            })
+            .collect();
+
+        let json = match serde_json::to_string(&ffi_tasks) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskOutputRecord {
+    task_id: TaskId,
+    command: Option<String>,
+    cwd: Option<String>,
+    program_path: Option<String>,
+    path_snippet: Option<String>,
+    process_qos: Option<String>,
+    started_at_unix_ms: Option<i64>,
+    finished_at_unix_ms: Option<i64>,
+    duration_ms: Option<u64>,
+    exit_code: Option<i32>,
+    termination_reason: Option<String>,
+    error_code: Option<String>,
+    error_message: Option<String>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskOutputBatchRecord {
+    #[serde(flatten)]
+    output: FfiTaskOutputRecord,
+    stdout_truncated: bool,
+    stderr_truncated: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskLogRecord {
+    id: u64,
+    task_id: TaskId,
+    manager: ManagerId,
+    task_type: TaskType,
+    status: Option<&'static str>,
+    level: &'static str,
+    message: String,
+    created_at_unix: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskHistoryRecord {
+    task_id: TaskId,
+    manager: ManagerId,
+    task_type: TaskType,
+    status: &'static str,
+    exit_code: Option<i32>,
+    error_code: Option<String>,
+    error_message: Option<String>,
+    duration_ms: Option<u64>,
+    stdout_tail: Option<String>,
+    stderr_tail: Option<String>,
+    finished_at_unix: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskHistoryPage {
+    total: usize,
+    entries: Vec<FfiTaskHistoryRecord>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskTimeoutPromptRecord {
+    task_id: TaskId,
+    manager: ManagerId,
+    task_type: TaskType,
+    action: &'static str,
+    requested_at_unix_ms: i64,
+    grace_seconds: u64,
+    suggested_extension_seconds: u64,
+}
+
+fn redact_diagnostics_text(value: &str) -> String {
+    helm_core::diagnostics_redaction::redact_secrets(value)
 }
 
 fn redact_diagnostics_optional(value: Option<String>) -> Option<String> {
@@ -5649,7 +8186,13 @@ fn redact_diagnostics_optional(value: Option<String>) -> Option<String> {
 }
 
 fn build_ffi_task_output_record(task_id: TaskId) -> FfiTaskOutputRecord {
-    let output = helm_core::execution::task_output(task_id);
+    map_task_output_record(task_id, helm_core::execution::task_output(task_id))
+}
+
+fn map_task_output_record(
+    task_id: TaskId,
+    output: Option<helm_core::execution::TaskOutputRecord>,
+) -> FfiTaskOutputRecord {
     FfiTaskOutputRecord {
         task_id,
         command: redact_diagnostics_optional(
@@ -5662,6 +8205,7 @@ fn build_ffi_task_output_record(task_id: TaskId) -> FfiTaskOutputRecord {
         path_snippet: redact_diagnostics_optional(
             output.as_ref().and_then(|entry| entry.path_snippet.clone()),
         ),
+        process_qos: output.as_ref().and_then(|entry| entry.process_qos.clone()),
         started_at_unix_ms: output.as_ref().and_then(|entry| entry.started_at_unix_ms),
         finished_at_unix_ms: output.as_ref().and_then(|entry| entry.finished_at_unix_ms),
         duration_ms: output.as_ref().and_then(|entry| entry.duration_ms),
@@ -5697,6 +8241,33 @@ fn map_task_log_record(entry: TaskLogRecord) -> FfiTaskLogRecord {
     }
 }
 
+fn map_task_history_record(entry: TaskHistoryRecord) -> FfiTaskHistoryRecord {
+    FfiTaskHistoryRecord {
+        task_id: entry.task_id,
+        manager: entry.manager,
+        task_type: entry.task_type,
+        status: task_status_str(entry.status),
+        exit_code: entry.exit_code,
+        error_code: entry.error_code,
+        error_message: redact_diagnostics_optional(entry.error_message),
+        duration_ms: entry.duration_ms,
+        stdout_tail: redact_diagnostics_optional(entry.stdout_tail),
+        stderr_tail: redact_diagnostics_optional(entry.stderr_tail),
+        finished_at_unix: entry
+            .finished_at
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0),
+    }
+}
+
+fn parse_task_history_filter(ptr: *const c_char) -> Result<TaskHistoryFilter, &'static str> {
+    match parse_optional_nonempty_string_arg(ptr)? {
+        Some(raw) => serde_json::from_str(&raw).map_err(|_| SERVICE_ERROR_INVALID_INPUT),
+        None => Ok(TaskHistoryFilter::default()),
+    }
+}
+
 fn map_timeout_prompt_record(
     entry: helm_core::execution::timeout_prompt_store::TaskTimeoutPromptRecord,
 ) -> FfiTaskTimeoutPromptRecord {
@@ -5716,80 +8287,283 @@ fn map_timeout_prompt_record(
 /// Returns `null` only on serialization/allocation failure.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_get_task_output(task_id: i64) -> *mut c_char {
-    if task_id < 0 {
-        return std::ptr::null_mut();
-    }
+    ffi_boundary("helm_get_task_output", || {
+        if task_id < 0 {
+            return std::ptr::null_mut();
+        }
 
-    let task_id = TaskId(task_id as u64);
-    let record = build_ffi_task_output_record(task_id);
+        let task_id = TaskId(task_id as u64);
+        let record = build_ffi_task_output_record(task_id);
 
-    let json = match serde_json::to_string(&record) {
-        Ok(value) => value,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let json = match serde_json::to_string(&record) {
+            Ok(value) => value,
+            Err(_) => return std::ptr::null_mut(),
+        };
 
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
-/// Return persisted lifecycle task logs for a task ID as JSON.
-///
-/// Returns `null` only on invalid input or serialization/allocation failure.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_list_task_logs(task_id: i64, limit: i64) -> *mut c_char {
-    if task_id < 0 || limit < 0 {
-        return std::ptr::null_mut();
-    }
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskOutputChunk {
+    sequence: u64,
+    stream: &'static str,
+    text: String,
+}
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskOutputChunks {
+    task_id: u64,
+    chunks: Vec<FfiTaskOutputChunk>,
+    next_offset: u64,
+    missed_chunks: bool,
+    finished: bool,
+}
 
-    let entries = match state
-        .store
-        .list_task_logs(TaskId(task_id as u64), limit as usize)
-    {
-        Ok(entries) => entries,
-        Err(error) => {
-            eprintln!("Failed to list task logs for task {}: {}", task_id, error);
+fn build_ffi_task_output_chunks(
+    task_id: TaskId,
+    since: helm_core::execution::TaskOutputChunks,
+) -> FfiTaskOutputChunks {
+    FfiTaskOutputChunks {
+        task_id: task_id.0,
+        chunks: since
+            .chunks
+            .into_iter()
+            .map(|chunk| FfiTaskOutputChunk {
+                sequence: chunk.sequence,
+                stream: chunk.stream.as_str(),
+                text: chunk.text,
+            })
+            .collect(),
+        next_offset: since.next_offset,
+        missed_chunks: since.missed,
+        finished: since.finished,
+    }
+}
+
+/// Return live output chunks for a task newer than `offset` as JSON.
+///
+/// Pass `0` first, then the returned `nextOffset`, to tail a running task.
+/// `missedChunks` reports that older chunks were dropped (the full tail stays
+/// available from `helm_get_task_output`); `finished` means no more chunks
+/// will arrive.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_task_output_since(task_id: i64, offset: i64) -> *mut c_char {
+    ffi_boundary("helm_get_task_output_since", || {
+        clear_last_error_key();
+        if task_id < 0 || offset < 0 {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let task_id = TaskId(task_id as u64);
+        let since = helm_core::execution::task_output_since(task_id, offset as u64);
+        let json = match serde_json::to_string(&build_ffi_task_output_chunks(task_id, since)) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+fn parse_task_output_batch_ids(raw: &str) -> Result<Vec<TaskId>, &'static str> {
+    let requested: Vec<i64> = serde_json::from_str(raw).map_err(|_| SERVICE_ERROR_INVALID_INPUT)?;
+    if requested.len() > TASK_OUTPUT_BATCH_MAX_TASKS || requested.iter().any(|id| *id < 0) {
+        return Err(SERVICE_ERROR_INVALID_INPUT);
+    }
+    let mut seen = std::collections::HashSet::new();
+    Ok(requested
+        .into_iter()
+        .filter(|id| seen.insert(*id))
+        .map(|id| TaskId(id as u64))
+        .collect())
+}
+
+fn build_task_output_batch(task_ids: &[TaskId]) -> Vec<FfiTaskOutputBatchRecord> {
+    helm_core::execution::task_outputs(task_ids)
+        .into_iter()
+        .map(|(task_id, output)| {
+            let mut record = map_task_output_record(task_id, output);
+            let (stdout, stdout_truncated) = helm_core::execution::task_output_store::tail_stream(
+                record.stdout.take(),
+                TASK_OUTPUT_BATCH_MAX_STREAM_BYTES,
+            );
+            let (stderr, stderr_truncated) = helm_core::execution::task_output_store::tail_stream(
+                record.stderr.take(),
+                TASK_OUTPUT_BATCH_MAX_STREAM_BYTES,
+            );
+            record.stdout = stdout;
+            record.stderr = stderr;
+            FfiTaskOutputBatchRecord {
+                output: record,
+                stdout_truncated,
+                stderr_truncated,
+            }
+        })
+        .collect()
+}
+
+/// Return captured stdout/stderr for several task IDs as a JSON array.
+///
+/// `task_ids_json` is a JSON array of task IDs. Results follow request order
+/// (duplicates removed) and each stream is cut to its trailing
+/// `TASK_OUTPUT_BATCH_MAX_STREAM_BYTES`, flagged via `stdoutTruncated` /
+/// `stderrTruncated`.
+///
+/// # Safety
+///
+/// `task_ids_json` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_get_task_outputs(task_ids_json: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_get_task_outputs", || {
+        clear_last_error_key();
+        let raw = match unsafe { parse_required_cstr_arg(task_ids_json) } {
+            Ok(raw) => raw,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let task_ids = match parse_task_output_batch_ids(raw.as_str()) {
+            Ok(task_ids) => task_ids,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+
+        let records = build_task_output_batch(&task_ids);
+        let json = match serde_json::to_string(&records) {
+            Ok(value) => value,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+/// Return persisted lifecycle task logs for a task ID as JSON.
+///
+/// Returns `null` only on invalid input or serialization/allocation failure.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_task_logs(task_id: i64, limit: i64) -> *mut c_char {
+    ffi_boundary("helm_list_task_logs", || {
+        if task_id < 0 || limit < 0 {
             return std::ptr::null_mut();
         }
-    };
 
-    let payload: Vec<FfiTaskLogRecord> = entries.into_iter().map(map_task_log_record).collect();
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
 
-    let json = match serde_json::to_string(&payload) {
-        Ok(value) => value,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let entries = match state
+            .store
+            .list_task_logs(TaskId(task_id as u64), limit as usize)
+        {
+            Ok(entries) => entries,
+            Err(error) => {
+                eprintln!("Failed to list task logs for task {}: {}", task_id, error);
+                return std::ptr::null_mut();
+            }
+        };
 
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        let payload: Vec<FfiTaskLogRecord> = entries.into_iter().map(map_task_log_record).collect();
+
+        let json = match serde_json::to_string(&payload) {
+            Ok(value) => value,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Return one page of archived terminal tasks as `{"total", "entries"}` JSON,
+/// most recently finished first.
+///
+/// `filter_json` may be null or an object with optional `manager`, `task_type`,
+/// and `status` fields. `limit` is capped at `TASK_HISTORY_PAGE_MAX_LIMIT`.
+///
+/// # Safety
+///
+/// `filter_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_list_task_history(
+    offset: i64,
+    limit: i64,
+    filter_json: *const c_char,
+) -> *mut c_char {
+    ffi_boundary("helm_list_task_history", || {
+        clear_last_error_key();
+        if offset < 0 || limit <= 0 {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        }
+        let filter = match parse_task_history_filter(filter_json) {
+            Ok(filter) => filter,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let limit = (limit as usize).min(TASK_HISTORY_PAGE_MAX_LIMIT);
+
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let page = state.store.read_snapshot(|| {
+            let total = state.store.count_task_history(&filter)?;
+            let entries = state
+                .store
+                .list_task_history(&filter, offset as usize, limit)?;
+            Ok::<_, helm_core::models::CoreError>((total, entries))
+        });
+        let (total, entries) = match page.and_then(|result| result) {
+            Ok(page) => page,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
+        drop(guard);
+
+        let page = FfiTaskHistoryPage {
+            total,
+            entries: entries.into_iter().map(map_task_history_record).collect(),
+        };
+        let json = match serde_json::to_string(&page) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
 /// List pending hard-timeout prompts for running tasks as JSON.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_list_task_timeout_prompts() -> *mut c_char {
-    let entries: Vec<FfiTaskTimeoutPromptRecord> =
-        helm_core::execution::timeout_prompt_store::list_prompts()
-            .into_iter()
-            .map(map_timeout_prompt_record)
-            .collect();
+    ffi_boundary("helm_list_task_timeout_prompts", || {
+        let entries: Vec<FfiTaskTimeoutPromptRecord> =
+            helm_core::execution::timeout_prompt_store::list_prompts()
+                .into_iter()
+                .map(map_timeout_prompt_record)
+                .collect();
 
-    let json = match serde_json::to_string(&entries) {
-        Ok(value) => value,
-        Err(_) => return std::ptr::null_mut(),
-    };
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        let json = match serde_json::to_string(&entries) {
+            Ok(value) => value,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
 /// Respond to a pending task hard-timeout prompt by task ID.
@@ -5801,15 +8575,17 @@ pub extern "C" fn helm_respond_task_timeout_prompt(
     task_id: i64,
     wait_for_completion: bool,
 ) -> bool {
-    if task_id < 0 {
-        return false;
-    }
-    let decision = if wait_for_completion {
-        helm_core::execution::timeout_prompt_store::TimeoutPromptDecision::Wait
-    } else {
-        helm_core::execution::timeout_prompt_store::TimeoutPromptDecision::Stop
-    };
-    helm_core::execution::timeout_prompt_store::respond(TaskId(task_id as u64), decision)
+    ffi_boundary("helm_respond_task_timeout_prompt", || {
+        if task_id < 0 {
+            return false;
+        }
+        let decision = if wait_for_completion {
+            helm_core::execution::timeout_prompt_store::TimeoutPromptDecision::Wait
+        } else {
+            helm_core::execution::timeout_prompt_store::TimeoutPromptDecision::Stop
+        };
+        helm_core::execution::timeout_prompt_store::respond(TaskId(task_id as u64), decision)
+    })
 }
 
 fn task_status_str(status: TaskStatus) -> &'static str {
@@ -5835,6 +8611,17 @@ fn manager_action_str(action: ManagerAction) -> &'static str {
         ManagerAction::Configure => "configure",
         ManagerAction::Pin => "pin",
         ManagerAction::Unpin => "unpin",
+        ManagerAction::SelfVersionCheck => "self_version_check",
+        ManagerAction::ListFiles => "list_files",
+        ManagerAction::ListDependencies => "list_dependencies",
+        ManagerAction::ListRequested => "list_requested",
+        ManagerAction::Rollback => "rollback",
+        ManagerAction::PackageInfo => "package_info",
+        ManagerAction::ListServices => "list_services",
+        ManagerAction::SetServiceState => "set_service_state",
+        ManagerAction::PreviewCleanup => "preview_cleanup",
+        ManagerAction::Cleanup => "cleanup",
+        ManagerAction::MeasurePackageSizes => "measure_package_sizes",
     }
 }
 
@@ -5848,293 +8635,395 @@ fn task_log_level_str(level: TaskLogLevel) -> &'static str {
 
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_trigger_refresh() -> bool {
-    clear_last_error_key();
-    if external_coordinator_state_dir().is_some() {
-        return coordinator_start_workflow_external(CoordinatorWorkflowRequest::RefreshAll).is_ok();
-    }
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+    ffi_boundary("helm_trigger_refresh", || {
+        clear_last_error_key();
+        if external_coordinator_state_dir().is_some() {
+            return coordinator_start_workflow_external(CoordinatorWorkflowRequest::RefreshAll)
+                .is_ok();
+        }
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    let runtime = state.runtime.clone();
-    let store = state.store.clone();
-    let rt_handle = state.rt_handle.clone();
-    let enabled_by_manager = manager_enabled_map(store.as_ref());
+        let engine = state.engine.clone();
+        let runtime = state.runtime.clone();
+        let store = state.store.clone();
+        let rt_handle = state.rt_handle.clone();
+        let enabled_by_manager = manager_enabled_map(store.as_ref());
 
-    let has_refresh_or_detection = has_recent_refresh_or_detection(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        &enabled_by_manager,
-    );
-    if has_refresh_or_detection {
-        return true;
-    }
+        let has_refresh_or_detection = has_recent_refresh_or_detection(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            &enabled_by_manager,
+        );
+        if has_refresh_or_detection {
+            return true;
+        }
 
-    {
-        let catalog_store = store.clone();
-        let catalog_runtime = runtime.clone();
-        let catalog_rt_handle = rt_handle.clone();
-        thread::spawn(move || {
-            let _ = schedule_catalog_sync_for_managers(
-                catalog_store.as_ref(),
-                catalog_runtime.as_ref(),
-                &catalog_rt_handle,
-                remote_catalog_sync_target_managers(
-                    catalog_runtime.as_ref(),
+        {
+            let catalog_store = store.clone();
+            let catalog_runtime = runtime.clone();
+            let catalog_rt_handle = rt_handle.clone();
+            thread::spawn(move || {
+                let _ = schedule_catalog_sync_for_managers(
                     catalog_store.as_ref(),
-                ),
-                &std::collections::HashSet::new(),
-            );
-        });
-    }
+                    catalog_runtime.as_ref(),
+                    &catalog_rt_handle,
+                    remote_catalog_sync_target_managers(
+                        catalog_runtime.as_ref(),
+                        catalog_store.as_ref(),
+                    ),
+                    &std::collections::HashSet::new(),
+                );
+            });
+        }
 
-    state._tokio_rt.spawn(async move {
-        let results = runtime.refresh_all_ordered().await;
-        for (manager, result) in results {
-            if let Err(e) = result {
-                log_manager_operation_failure("refresh", manager, &e);
+        state.tokio_rt.spawn(async move {
+            let results = engine.refresh_all().await;
+            for (manager, result) in results {
+                if let Err(e) = result {
+                    log_manager_operation_failure("refresh", manager, &e);
+                }
             }
-        }
-    });
+        });
 
-    true
+        true
+    })
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_trigger_detection() -> bool {
-    clear_last_error_key();
-    if external_coordinator_state_dir().is_some() {
-        return coordinator_start_workflow_external(CoordinatorWorkflowRequest::DetectAll).is_ok();
-    }
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+    ffi_boundary("helm_trigger_detection", || {
+        clear_last_error_key();
+        if external_coordinator_state_dir().is_some() {
+            return coordinator_start_workflow_external(CoordinatorWorkflowRequest::DetectAll)
+                .is_ok();
+        }
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    let runtime = state.runtime.clone();
-    let store = state.store.clone();
-    let rt_handle = state.rt_handle.clone();
-    let enabled_by_manager = manager_enabled_map(store.as_ref());
-    let detected_before = detected_installed_map(store.as_ref());
+        let runtime = state.runtime.clone();
+        let store = state.store.clone();
+        let rt_handle = state.rt_handle.clone();
+        let enabled_by_manager = manager_enabled_map(store.as_ref());
+        let detected_before = detected_installed_map(store.as_ref());
 
-    let has_refresh_or_detection = has_recent_refresh_or_detection(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        &enabled_by_manager,
-    );
-    if has_refresh_or_detection {
-        return true;
-    }
+        let has_refresh_or_detection = has_recent_refresh_or_detection(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            &enabled_by_manager,
+        );
+        if has_refresh_or_detection {
+            return true;
+        }
 
-    preseed_presence_detections(store.as_ref(), runtime.as_ref(), &enabled_by_manager);
-    sync_manager_execution_preferences_from_store(store.as_ref());
+        let absent =
+            preseed_presence_detections(store.as_ref(), runtime.as_ref(), &enabled_by_manager);
+        sync_manager_execution_preferences_from_store(store.as_ref());
+        let _ = record_onboarding_event(store.as_ref(), OnboardingEvent::DetectionStarted);
 
-    state._tokio_rt.spawn(async move {
-        let results = runtime.detect_all_ordered().await;
-        for (manager, result) in results {
-            if let Err(e) = result {
-                log_manager_operation_failure("detection", manager, &e);
+        state.tokio_rt.spawn(async move {
+            let results = runtime.detect_all_ordered_skipping_absent(&absent).await;
+            for (manager, result) in results {
+                if let Err(e) = result {
+                    log_manager_operation_failure("detection", manager, &e);
+                }
             }
-        }
+            let _ = record_onboarding_event(store.as_ref(), OnboardingEvent::DetectionFinished);
 
-        let catalog_store = store.clone();
-        let catalog_runtime = runtime.clone();
-        let catalog_rt_handle = rt_handle.clone();
-        thread::spawn(move || {
-            let force_managers: std::collections::HashSet<ManagerId> =
-                remote_catalog_sync_target_managers(
-                    catalog_runtime.as_ref(),
+            let catalog_store = store.clone();
+            let catalog_runtime = runtime.clone();
+            let catalog_rt_handle = rt_handle.clone();
+            thread::spawn(move || {
+                let force_managers: std::collections::HashSet<ManagerId> =
+                    remote_catalog_sync_target_managers(
+                        catalog_runtime.as_ref(),
+                        catalog_store.as_ref(),
+                    )
+                    .into_iter()
+                    .filter(|manager| {
+                        manager_is_detected(catalog_store.as_ref(), *manager)
+                            && !detected_before.get(manager).copied().unwrap_or(false)
+                    })
+                    .collect();
+                let _ = schedule_catalog_sync_for_managers(
                     catalog_store.as_ref(),
-                )
-                .into_iter()
-                .filter(|manager| {
-                    manager_is_detected(catalog_store.as_ref(), *manager)
-                        && !detected_before.get(manager).copied().unwrap_or(false)
-                })
-                .collect();
-            let _ = schedule_catalog_sync_for_managers(
-                catalog_store.as_ref(),
-                catalog_runtime.as_ref(),
-                &catalog_rt_handle,
-                remote_catalog_sync_target_managers(
                     catalog_runtime.as_ref(),
-                    catalog_store.as_ref(),
-                ),
-                &force_managers,
-            );
+                    &catalog_rt_handle,
+                    remote_catalog_sync_target_managers(
+                        catalog_runtime.as_ref(),
+                        catalog_store.as_ref(),
+                    ),
+                    &force_managers,
+                );
+            });
         });
-    });
 
-    true
+        true
+    })
 }
 
-/// Trigger detection for a single manager.
+/// Trigger detection for a single manager. Returns the detection task ID, or
+/// -1 on error. When a detection for the manager is already in flight, its
+/// task ID is returned instead of queuing another probe.
 ///
 /// # Safety
 ///
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_trigger_detection_for_manager(manager_id: *const c_char) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
+pub unsafe extern "C" fn helm_trigger_detection_for_manager(manager_id: *const c_char) -> i64 {
+    ffi_boundary("helm_trigger_detection_for_manager", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(manager) => manager,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(manager) => manager,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    if external_coordinator_state_dir().is_some() {
-        return coordinator_start_workflow_external(CoordinatorWorkflowRequest::DetectManager {
-            manager_id: manager.as_str().to_string(),
-        })
-        .is_ok();
-    }
+        if external_coordinator_state_dir().is_some() {
+            return match coordinator_start_workflow_external(
+                CoordinatorWorkflowRequest::DetectManager {
+                    manager_id: manager.as_str().to_string(),
+                },
+            ) {
+                Ok(CoordinatorResponse {
+                    task_id: Some(task_id),
+                    ..
+                }) => task_id as i64,
+                Ok(_) => return_error_i64(SERVICE_ERROR_INTERNAL),
+                Err(error) => {
+                    log_manager_operation_failure("detection", manager, &error);
+                    return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+                }
+            };
+        }
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    if !runtime.has_manager(manager) || !runtime.is_manager_enabled(manager) {
-        return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-    }
-
-    if !runtime.supports_capability(manager, Capability::Detect) {
-        return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-    }
+        let was_detected = manager_is_detected(store.as_ref(), manager);
+        let task_id = match queue_manager_detection_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+        ) {
+            Ok(task_id) => task_id,
+            Err(error_key) => return return_error_i64(error_key),
+        };
 
-    thread::spawn(move || {
-        if let Err(error) =
-            detect_single_manager(runtime.as_ref(), store.as_ref(), &rt_handle, manager)
-        {
-            log_manager_operation_failure("detection", manager, &error);
-        }
-    });
+        thread::spawn(move || {
+            if let Err(error) = finish_manager_detection_task(
+                store.as_ref(),
+                runtime.as_ref(),
+                &rt_handle,
+                manager,
+                task_id,
+                was_detected,
+            ) {
+                log_manager_operation_failure("detection", manager, &error);
+            }
+        });
 
-    true
+        task_id.0 as i64
+    })
 }
 
-/// Query the local search cache synchronously and return JSON results.
+/// Query the local search cache synchronously and return JSON
+/// `{results, completions}`, where `completions` lists the managers that
+/// finished a remote search for this query (including ones with no results).
 ///
 /// # Safety
 ///
 /// `query` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_search_local(query: *const c_char) -> *mut c_char {
-    if query.is_null() {
-        return std::ptr::null_mut();
-    }
+    ffi_boundary("helm_search_local", || {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
 
-    let c_str = unsafe { CStr::from_ptr(query) };
-    let query_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let c_str = unsafe { CStr::from_ptr(query) };
+        let query_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return std::ptr::null_mut(),
+        };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
 
-    let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+        let snapshot = state.engine.read_snapshot(|| {
+            let enabled_by_manager = manager_enabled_map(state.store.as_ref());
+            let results = state.engine.search_local_ranked(query_str, 500)?;
+            let completions = state.engine.search_completions(query_str)?;
+            Ok((enabled_by_manager, results, completions))
+        });
+        let (enabled_by_manager, results, completions) = match snapshot.and_then(|result| result) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("Failed to query local search cache: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
+        let results = results
+            .into_iter()
+            .filter(|ranked| {
+                let result = &ranked.result;
+                result.result.package.is_user_visible_package()
+                    && manager_participates_in_package_search(result.result.package.manager)
+                    && manager_participates_in_package_search(result.source_manager)
+                    && manager_is_enabled(&enabled_by_manager, result.result.package.manager)
+                    && manager_is_enabled(&enabled_by_manager, result.source_manager)
+            })
+            .collect::<Vec<_>>();
+        let completions = completions
+            .into_iter()
+            .filter(|completion| {
+                manager_participates_in_package_search(completion.manager)
+                    && manager_is_enabled(&enabled_by_manager, completion.manager)
+            })
+            .collect::<Vec<_>>();
 
-    let results = match state.store.query_local(query_str, 500) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Failed to query local search cache: {}", e);
-            return std::ptr::null_mut();
+        #[derive(serde::Serialize)]
+        struct FfiSearchResult {
+            manager: String,
+            name: String,
+            package_identifier: Option<String>,
+            version: Option<String>,
+            summary: Option<String>,
+            source_manager: String,
+            downloads: Option<u64>,
+            popularity_rank: Option<u32>,
+            installed: bool,
+            relevance: u32,
         }
-    }
-    .into_iter()
-    .filter(|result| {
-        result.result.package.is_user_visible_package()
-            && manager_participates_in_package_search(result.result.package.manager)
-            && manager_participates_in_package_search(result.source_manager)
-            && manager_is_enabled(&enabled_by_manager, result.result.package.manager)
-            && manager_is_enabled(&enabled_by_manager, result.source_manager)
-    })
-    .collect::<Vec<_>>();
 
-    #[derive(serde::Serialize)]
-    struct FfiSearchResult {
-        manager: String,
-        name: String,
-        package_identifier: Option<String>,
-        version: Option<String>,
-        summary: Option<String>,
-        source_manager: String,
-    }
+        #[derive(serde::Serialize)]
+        struct FfiSearchCompletion {
+            manager: String,
+            result_count: usize,
+            completed_at_unix: i64,
+        }
 
-    let ffi_results: Vec<FfiSearchResult> = results
-        .into_iter()
-        .map(|r| FfiSearchResult {
-            manager: r.result.package.manager.as_str().to_string(),
-            name: r.result.package.name,
-            package_identifier: r.result.package_identifier,
-            version: r.result.version,
-            summary: r.result.summary,
-            source_manager: r.source_manager.as_str().to_string(),
-        })
-        .collect();
+        #[derive(serde::Serialize)]
+        struct FfiSearchPayload {
+            results: Vec<FfiSearchResult>,
+            completions: Vec<FfiSearchCompletion>,
+        }
 
-    let json = match serde_json::to_string(&ffi_results) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let ffi_results: Vec<FfiSearchResult> = results
+            .into_iter()
+            .map(|ranked| {
+                let r = ranked.result;
+                FfiSearchResult {
+                    manager: r.result.package.manager.as_str().to_string(),
+                    name: r.result.package.name,
+                    package_identifier: r.result.package_identifier,
+                    version: r.result.version,
+                    summary: r.result.summary,
+                    source_manager: r.source_manager.as_str().to_string(),
+                    downloads: r.popularity.downloads,
+                    popularity_rank: r.popularity.rank,
+                    installed: ranked.installed,
+                    relevance: ranked.relevance,
+                }
+            })
+            .collect();
+        let ffi_completions: Vec<FfiSearchCompletion> = completions
+            .into_iter()
+            .map(|completion| FfiSearchCompletion {
+                manager: completion.manager.as_str().to_string(),
+                result_count: completion.result_count,
+                completed_at_unix: completion
+                    .completed_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs() as i64)
+                    .unwrap_or(0),
+            })
+            .collect();
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        let json = match serde_json::to_string(&FfiSearchPayload {
+            results: ffi_results,
+            completions: ffi_completions,
+        }) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Outcome of queueing one manager's remote search.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiRemoteSearchTrigger {
+    manager_id: String,
+    task_id: Option<i64>,
+    error_key: Option<&'static str>,
+    /// True when the manager already completed this query recently, so no task
+    /// was queued.
+    skipped_fresh: bool,
 }
 
-/// Submit a remote search request for the given query. Returns the task ID, or -1 on error.
+/// Queue a remote search for every targeted manager, reporting each manager's
+/// task ID, skip, or error. Errors cover only invalid input and missing state.
 ///
 /// # Safety
 ///
-/// `query` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_trigger_remote_search(query: *const c_char) -> i64 {
-    clear_last_error_key();
+/// `query` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+unsafe fn trigger_remote_search_for_targets(
+    query: *const c_char,
+) -> Result<Vec<FfiRemoteSearchTrigger>, &'static str> {
     if query.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        return Err(SERVICE_ERROR_INVALID_INPUT);
     }
 
     let c_str = unsafe { CStr::from_ptr(query) };
     let query_str = match c_str.to_str() {
         Ok(s) => s.trim(),
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        Err(_) => return Err(SERVICE_ERROR_INVALID_INPUT),
     };
     if query_str.is_empty() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        return Err(SERVICE_ERROR_INVALID_INPUT);
     }
 
     let (store, runtime, rt_handle) = {
         let guard = lock_or_recover(&STATE, "state");
         let state = match guard.as_ref() {
             Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            None => return Err(SERVICE_ERROR_INTERNAL),
         };
         (
             state.store.clone(),
@@ -6143,8 +9032,7 @@ pub unsafe extern "C" fn helm_trigger_remote_search(query: *const c_char) -> i64
         )
     };
 
-    let mut first_task_id: Option<i64> = None;
-    let mut last_error_key: Option<&'static str> = None;
+    let fresh_managers = fresh_search_completion_managers(store.as_ref(), query_str);
 
     let _ = schedule_catalog_sync_for_managers(
         store.as_ref(),
@@ -6154,32 +9042,100 @@ pub unsafe extern "C" fn helm_trigger_remote_search(query: *const c_char) -> i64
         &std::collections::HashSet::new(),
     );
 
+    let mut triggers = Vec::new();
     for manager in remote_search_target_managers(runtime.as_ref(), store.as_ref()) {
-        match queue_remote_search_task(
-            store.as_ref(),
-            runtime.as_ref(),
-            &rt_handle,
-            manager,
-            query_str,
-        ) {
-            Ok(task_id) => {
-                if first_task_id.is_none() {
-                    first_task_id = Some(task_id.0 as i64);
-                }
-            }
-            Err(error_key) => {
-                last_error_key = Some(error_key);
+        let mut trigger = FfiRemoteSearchTrigger {
+            manager_id: manager.as_str().to_string(),
+            task_id: None,
+            error_key: None,
+            skipped_fresh: false,
+        };
+        if fresh_managers.contains(&manager) {
+            trigger.skipped_fresh = true;
+        } else {
+            match queue_remote_search_task(
+                store.as_ref(),
+                runtime.as_ref(),
+                &rt_handle,
+                manager,
+                query_str,
+            ) {
+                Ok(task_id) => trigger.task_id = Some(task_id.0 as i64),
+                Err(error_key) => trigger.error_key = Some(error_key),
             }
         }
+        triggers.push(trigger);
     }
+    Ok(triggers)
+}
 
-    match first_task_id {
-        Some(task_id) => task_id,
-        None => return_error_i64(last_error_key.unwrap_or(SERVICE_ERROR_UNSUPPORTED_CAPABILITY)),
+/// Collapse per-manager triggers into the single-value contract of
+/// `helm_trigger_remote_search`: the first task ID, `REMOTE_SEARCH_SKIPPED_FRESH`
+/// when nothing was queued only because results are fresh, or the last error key.
+fn remote_search_first_task_id(triggers: &[FfiRemoteSearchTrigger]) -> Result<i64, &'static str> {
+    if let Some(task_id) = triggers.iter().find_map(|trigger| trigger.task_id) {
+        return Ok(task_id);
     }
+    let last_error_key = triggers.iter().rev().find_map(|trigger| trigger.error_key);
+    let skipped_fresh = triggers.iter().any(|trigger| trigger.skipped_fresh);
+    match last_error_key {
+        None if skipped_fresh => Ok(REMOTE_SEARCH_SKIPPED_FRESH),
+        error_key => Err(error_key.unwrap_or(SERVICE_ERROR_UNSUPPORTED_CAPABILITY)),
+    }
+}
+
+/// Submit a remote search request for the given query. Returns the first task ID,
+/// -2 when every target manager already completed this query recently, or -1 on error.
+///
+/// Deprecated: the other managers' task IDs are dropped. Use
+/// `helm_trigger_remote_search_v2` to track every manager's search.
+///
+/// # Safety
+///
+/// `query` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_trigger_remote_search(query: *const c_char) -> i64 {
+    ffi_boundary("helm_trigger_remote_search", || {
+        clear_last_error_key();
+        let result = unsafe { trigger_remote_search_for_targets(query) }
+            .and_then(|triggers| remote_search_first_task_id(&triggers));
+        match result {
+            Ok(task_id) => task_id,
+            Err(error_key) => return_error_i64(error_key),
+        }
+    })
+}
+
+/// Submit a remote search request for the given query on every targeted manager.
+/// Returns a JSON array of `{managerId, taskId, errorKey, skippedFresh}` entries,
+/// one per manager, or null on invalid input.
+///
+/// # Safety
+///
+/// `query` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_trigger_remote_search_v2(query: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_trigger_remote_search_v2", || {
+        clear_last_error_key();
+        let triggers = match unsafe { trigger_remote_search_for_targets(query) } {
+            Ok(triggers) => triggers,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+
+        let json = match serde_json::to_string(&triggers) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
-/// Submit a remote search request for a specific manager. Returns the task ID, or -1 on error.
+/// Submit a remote search request for a specific manager. Returns the task ID,
+/// -2 when the manager already completed this query recently, or -1 on error.
 ///
 /// # Safety
 ///
@@ -6189,224 +9145,426 @@ pub unsafe extern "C" fn helm_trigger_remote_search_for_manager(
     manager_id: *const c_char,
     query: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() || query.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
+    ffi_boundary("helm_trigger_remote_search_for_manager", || {
+        clear_last_error_key();
+        if manager_id.is_null() || query.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
-    let manager = match manager_cstr
-        .to_str()
-        .ok()
-        .and_then(|s| s.parse::<ManagerId>().ok())
-    {
-        Some(manager) => manager,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
+        let manager = match manager_cstr
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let query_cstr = unsafe { CStr::from_ptr(query) };
-    let query_str = match query_cstr.to_str() {
-        Ok(query_text) => query_text.trim(),
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-    if query_str.is_empty() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
+        let query_cstr = unsafe { CStr::from_ptr(query) };
+        let query_str = match query_cstr.to_str() {
+            Ok(query_text) => query_text.trim(),
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        if query_str.is_empty() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    let _ = schedule_catalog_sync_for_managers(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        [manager],
-        &std::collections::HashSet::new(),
-    );
+        let _ = schedule_catalog_sync_for_managers(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            [manager],
+            &std::collections::HashSet::new(),
+        );
 
-    match queue_remote_search_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        manager,
-        query_str,
-    ) {
-        Ok(task_id) => task_id.0 as i64,
-        Err(error_key) => return_error_i64(error_key),
-    }
+        if fresh_search_completion_managers(store.as_ref(), query_str).contains(&manager) {
+            return REMOTE_SEARCH_SKIPPED_FRESH;
+        }
+
+        match queue_remote_search_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            query_str,
+        ) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error_key) => return_error_i64(error_key),
+        }
+    })
 }
 
 /// Cancel a running task by ID. Returns true on success.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_cancel_task(task_id: i64) -> bool {
-    if task_id < 0 {
-        return false;
-    }
+    ffi_boundary("helm_cancel_task", || {
+        if task_id < 0 {
+            return false;
+        }
 
-    if external_coordinator_state_dir().is_some() {
-        return coordinator_cancel_external(task_id as u64).is_ok();
-    }
+        if external_coordinator_state_dir().is_some() {
+            return coordinator_cancel_external(task_id as u64).is_ok();
+        }
 
-    let (runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return false,
+        let (engine, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return false,
+            };
+            (
+                state.engine.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
         };
-        (state.runtime.clone(), state.rt_handle.clone())
-    };
 
-    let mode = CancellationMode::Graceful {
-        grace_period: Duration::from_millis(500),
-    };
+        let mode = CancellationMode::Graceful {
+            grace_period: Duration::from_millis(500),
+        };
 
-    match rt_handle.block_on(runtime.cancel(helm_core::models::TaskId(task_id as u64), mode)) {
-        Ok(()) => true,
-        Err(e) => {
-            eprintln!("Failed to cancel task {}: {}", task_id, e);
-            false
+        let task_id = helm_core::models::TaskId(task_id as u64);
+        if cancel_local_task(runtime.as_ref(), &rt_handle, task_id, mode) {
+            return true;
+        }
+
+        match rt_handle.block_on(engine.cancel_with_mode(task_id, mode)) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Failed to cancel task {}: {}", task_id.0, e);
+                false
+            }
         }
+    })
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiTaskWaitResult {
+    task_id: u64,
+    /// True when the wait gave up first; `status` is then the last live status.
+    timed_out: bool,
+    status: &'static str,
+    error: Option<helm_core::models::CoreErrorPayload>,
+    duration_ms: Option<u64>,
+    exit_code: Option<i32>,
+    stdout_tail: Option<String>,
+    stderr_tail: Option<String>,
+}
+
+fn build_ffi_task_wait_result(
+    snapshot: &AdapterTaskSnapshot,
+    timed_out: bool,
+    output: FfiTaskOutputRecord,
+) -> FfiTaskWaitResult {
+    let error = match &snapshot.terminal_state {
+        Some(AdapterTaskTerminalState::Failed(error))
+        | Some(AdapterTaskTerminalState::Cancelled(Some(error))) => Some(error.payload()),
+        _ => None,
+    };
+    let duration_ms = output.duration_ms.or_else(|| {
+        let started_at = snapshot.runtime.started_at?;
+        let finished_at = snapshot.runtime.finished_at?;
+        finished_at
+            .duration_since(started_at)
+            .ok()
+            .map(|duration| duration.as_millis() as u64)
+    });
+    let (stdout_tail, _) = helm_core::execution::task_output_store::tail_stream(
+        output.stdout,
+        TASK_OUTPUT_BATCH_MAX_STREAM_BYTES,
+    );
+    let (stderr_tail, _) = helm_core::execution::task_output_store::tail_stream(
+        output.stderr,
+        TASK_OUTPUT_BATCH_MAX_STREAM_BYTES,
+    );
+    FfiTaskWaitResult {
+        task_id: snapshot.runtime.id.0,
+        timed_out,
+        status: task_status_str(snapshot.runtime.status),
+        error,
+        duration_ms: (!timed_out).then_some(duration_ms).flatten(),
+        exit_code: output.exit_code,
+        stdout_tail,
+        stderr_tail,
     }
 }
 
-/// Dismiss a terminal task by ID. Returns true on success.
+/// Block until a task is terminal, then return its outcome as JSON: status,
+/// core error detail when it failed, duration, exit code, and the trailing
+/// `TASK_OUTPUT_BATCH_MAX_STREAM_BYTES` of each output stream.
+///
+/// - `timeout_ms`: longest wait; `0` waits without limit. When it elapses the
+///   result has `timedOut: true` and the task's current status.
+///
+/// Returns null for a negative argument or a task unknown to this service.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_dismiss_task(task_id: i64) -> bool {
-    clear_last_error_key();
-    if task_id < 0 {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
+pub extern "C" fn helm_wait_for_task(task_id: i64, timeout_ms: i64) -> *mut c_char {
+    ffi_boundary("helm_wait_for_task", || {
+        clear_last_error_key();
+        if task_id < 0 || timeout_ms < 0 {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        }
+        let task_id = TaskId(task_id as u64);
+        let timeout = (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms as u64));
 
-    let store = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        let (runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let Some(state) = guard.as_ref() else {
+                return return_error_ptr(SERVICE_ERROR_INTERNAL);
+            };
+            (state.runtime.clone(), state.rt_handle.clone())
         };
-        state.store.clone()
-    };
 
-    if store
-        .delete_task(TaskId(task_id as u64))
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_err()
-    {
-        return false;
-    }
+        let (snapshot, timed_out) =
+            match rt_handle.block_on(runtime.wait_for_terminal(task_id, timeout)) {
+                Ok(snapshot) => (snapshot, false),
+                Err(error) if error.kind == helm_core::models::CoreErrorKind::Timeout => {
+                    match rt_handle.block_on(runtime.snapshot(task_id)) {
+                        Ok(snapshot) => (snapshot, true),
+                        Err(error) => return return_core_error_ptr(&error),
+                    }
+                }
+                Err(error) => return return_core_error_ptr(&error),
+            };
+        let result =
+            build_ffi_task_wait_result(&snapshot, timed_out, build_ffi_task_output_record(task_id));
 
-    lock_or_recover(&TASK_LABELS, "task_labels").remove(&(task_id as u64));
-    true
+        let json = match serde_json::to_string(&result) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// List manager status: detection info + preferences + implementation status as JSON.
+/// Dismiss a terminal task by ID. Returns true on success.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_list_manager_status() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+pub extern "C" fn helm_dismiss_task(task_id: i64) -> bool {
+    ffi_boundary("helm_dismiss_task", || {
+        clear_last_error_key();
+        if task_id < 0 {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let detections = state.store.list_detections().unwrap_or_default();
-    let preferences = state.store.list_manager_preferences().unwrap_or_default();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+            };
+            state.store.clone()
+        };
 
-    let mut detection_map: std::collections::HashMap<_, _> = detections.into_iter().collect();
-    let mut pref_map: std::collections::HashMap<_, _> = preferences
-        .into_iter()
-        .map(|pref| (pref.manager, pref))
-        .collect();
+        if store
+            .delete_task(TaskId(task_id as u64))
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_err()
+        {
+            return false;
+        }
 
-    // Homebrew detection/version probing is occasionally flaky during first detection.
-    // If status is missing or incomplete, probe directly from brew.
-    if detection_map
-        .get(&ManagerId::HomebrewFormula)
-        .is_none_or(|detection| detection.version.is_none() || !detection.installed)
-        && let Some(probed) = probe_homebrew_version(
-            detection_map
-                .get(&ManagerId::HomebrewFormula)
-                .and_then(|d| d.executable_path.as_deref()),
-        )
-    {
-        let refreshed = if let Some(existing) = detection_map.get(&ManagerId::HomebrewFormula) {
-            DetectionInfo {
-                installed: true,
-                executable_path: existing.executable_path.clone(),
-                version: Some(probed),
+        true
+    })
+}
+
+/// Record that `task_id` retries `attempt_of`. Chains are flattened onto the
+/// first attempt, so linking a retry of a retry keeps one history. Returns
+/// true on success.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_record_task_attempt(task_id: i64, attempt_of: i64) -> bool {
+    ffi_boundary("helm_record_task_attempt", || {
+        clear_last_error_key();
+        if task_id < 0 || attempt_of < 0 || task_id == attempt_of {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+            };
+            state.store.clone()
+        };
+
+        match store.record_task_attempt(TaskId(task_id as u64), TaskId(attempt_of as u64)) {
+            Ok(()) => true,
+            Err(error) => {
+                eprintln!("Failed to link task {task_id} as an attempt of {attempt_of}: {error}");
+                return_error_bool(SERVICE_ERROR_STORAGE_FAILURE)
             }
-        } else {
-            DetectionInfo {
-                installed: true,
-                executable_path: None,
-                version: Some(probed),
+        }
+    })
+}
+
+/// List every retained attempt in `task_id`'s retry chain as JSON, oldest
+/// first, each numbered so the UI can show "attempt 2 of 3".
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_task_attempts(task_id: i64) -> *mut c_char {
+    ffi_boundary("helm_list_task_attempts", || {
+        clear_last_error_key();
+        if task_id < 0 {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            };
+            state.store.clone()
+        };
+
+        let attempts = match store.list_task_attempts(TaskId(task_id as u64)) {
+            Ok(attempts) => attempts,
+            Err(error) => {
+                eprintln!("Failed to list attempts for task {task_id}: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
             }
         };
-        let _ = state
-            .store
-            .upsert_detection(ManagerId::HomebrewFormula, &refreshed);
-        detection_map.insert(ManagerId::HomebrewFormula, refreshed);
-    }
 
-    if detection_map
-        .get(&ManagerId::Rustup)
-        .is_none_or(|detection| detection.version.is_none() || !detection.installed)
-        && let Some(probed) = probe_rustup_version(
-            detection_map
-                .get(&ManagerId::Rustup)
-                .and_then(|d| d.executable_path.as_deref()),
-        )
-    {
-        let refreshed = if let Some(existing) = detection_map.get(&ManagerId::Rustup) {
-            DetectionInfo {
-                installed: true,
-                executable_path: existing.executable_path.clone(),
-                version: Some(probed),
+        #[derive(serde::Serialize)]
+        struct FfiTaskAttemptRecord {
+            id: TaskId,
+            manager: ManagerId,
+            task_type: TaskType,
+            status: TaskStatus,
+            attempt_of: Option<TaskId>,
+            attempt: usize,
+            attempt_count: usize,
+            label_key: Option<String>,
+        }
+
+        let attempt_count = attempts.len();
+        let payload = attempts
+            .into_iter()
+            .enumerate()
+            .map(|(index, task)| FfiTaskAttemptRecord {
+                id: task.id,
+                manager: task.manager,
+                task_type: task.task_type,
+                status: task.status,
+                attempt_of: task.attempt_of,
+                attempt: index + 1,
+                attempt_count,
+                label_key: task.label.map(|label| label.key),
+            })
+            .collect::<Vec<_>>();
+
+        match serde_json::to_string(&payload)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+/// List manager status: detection info + preferences + implementation status as JSON.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_manager_status() -> *mut c_char {
+    ffi_boundary("helm_list_manager_status", || {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        let detections = state.store.list_detections().unwrap_or_default();
+        let preferences = state.store.list_manager_preferences().unwrap_or_default();
+
+        let mut detection_map: std::collections::HashMap<_, _> = detections.into_iter().collect();
+        let mut pref_map: std::collections::HashMap<_, _> = preferences
+            .into_iter()
+            .map(|pref| (pref.manager, pref))
+            .collect();
+
+        // Homebrew and rustup detection/version probing is occasionally flaky during first
+        // detection. If status is missing or incomplete, probe directly from the tool in the
+        // background and report the last known version in the meantime.
+        let mut probing_managers = std::collections::HashSet::new();
+        for (manager, probe) in [
+            (
+                ManagerId::HomebrewFormula,
+                probe_homebrew_version as fn(Option<&std::path::Path>) -> Option<String>,
+            ),
+            (ManagerId::Rustup, probe_rustup_version),
+        ] {
+            let existing = detection_map.get(&manager);
+            if existing.is_some_and(|detection| detection.version.is_some() && detection.installed)
+            {
+                continue;
             }
-        } else {
-            DetectionInfo {
-                installed: true,
-                executable_path: None,
-                version: Some(probed),
+            let executable_path = existing.and_then(|d| d.executable_path.clone());
+            let (version, probing) =
+                cached_manager_version_probe(&state.store, manager, executable_path.clone(), probe);
+            if probing {
+                probing_managers.insert(manager);
             }
-        };
-        let _ = state.store.upsert_detection(ManagerId::Rustup, &refreshed);
-        detection_map.insert(ManagerId::Rustup, refreshed);
-    }
+            if let Some(version) = version {
+                detection_map.insert(
+                    manager,
+                    DetectionInfo {
+                        installed: true,
+                        executable_path,
+                        version: Some(version),
+                    },
+                );
+            }
+        }
 
-    apply_manager_enablement_self_heal(
-        state.store.as_ref(),
-        state.runtime.as_ref(),
-        &state.rt_handle,
-        &detection_map,
-        &mut pref_map,
-    );
-    sync_manager_executable_overrides(&detection_map, &pref_map);
+        apply_manager_enablement_self_heal(
+            state.store.as_ref(),
+            state.runtime.as_ref(),
+            &state.rt_handle,
+            &detection_map,
+            &mut pref_map,
+        );
+        sync_manager_executable_overrides(&detection_map, &pref_map);
 
-    let statuses = build_manager_statuses(
-        Some(state.runtime.as_ref()),
-        Some(state.store.as_ref()),
-        &detection_map,
-        &pref_map,
-    );
+        let mut statuses = build_manager_statuses(
+            Some(state.runtime.as_ref()),
+            Some(state.store.as_ref()),
+            &detection_map,
+            &pref_map,
+        );
+        for status in &mut statuses {
+            status.probing = status
+                .manager_id
+                .parse::<ManagerId>()
+                .is_ok_and(|manager| probing_managers.contains(&manager));
+        }
 
-    let json = match serde_json::to_string(&statuses) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let json = match serde_json::to_string(&statuses) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
 /// Run a local doctor scan and return a health report JSON payload.
@@ -6419,1776 +9577,4495 @@ pub extern "C" fn helm_list_manager_status() -> *mut c_char {
 /// TODO(doctor-repair): wire additional detectors and remote fingerprint lookups.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_doctor_scan() -> *mut c_char {
-    clear_last_error_key();
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => {
-            set_last_error_key(SERVICE_ERROR_INTERNAL);
-            return std::ptr::null_mut();
-        }
-    };
+    ffi_boundary("helm_doctor_scan", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => {
+                set_last_error_key(SERVICE_ERROR_INTERNAL);
+                return std::ptr::null_mut();
+            }
+        };
 
-    let installed_packages = match state.store.list_installed() {
-        Ok(packages) => packages,
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
-            return std::ptr::null_mut();
-        }
-    };
-    let instances = match state.store.list_install_instances(None) {
-        Ok(instances) => instances,
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
-            return std::ptr::null_mut();
-        }
-    };
-    let mut instances_by_manager: std::collections::HashMap<
-        ManagerId,
-        Vec<ManagerInstallInstance>,
-    > = std::collections::HashMap::new();
-    for instance in instances {
-        instances_by_manager
-            .entry(instance.manager)
-            .or_default()
-            .push(instance);
-    }
-    let detection_map: std::collections::HashMap<_, _> = match state.store.list_detections() {
-        Ok(entries) => entries.into_iter().collect(),
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
-            return std::ptr::null_mut();
-        }
-    };
-    let pref_map: std::collections::HashMap<_, _> = match state.store.list_manager_preferences() {
-        Ok(entries) => entries
-            .into_iter()
-            .map(|pref| (pref.manager, pref))
-            .collect(),
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
-            return std::ptr::null_mut();
+        let installed_packages = match state.store.list_installed() {
+            Ok(packages) => packages,
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
+                return std::ptr::null_mut();
+            }
+        };
+        let instances = match state.store.list_install_instances(None) {
+            Ok(instances) => instances,
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
+                return std::ptr::null_mut();
+            }
+        };
+        let mut instances_by_manager: std::collections::HashMap<
+            ManagerId,
+            Vec<ManagerInstallInstance>,
+        > = std::collections::HashMap::new();
+        for instance in instances {
+            instances_by_manager
+                .entry(instance.manager)
+                .or_default()
+                .push(instance);
         }
-    };
-    let executable_states = build_manager_executable_doctor_states(&detection_map, &pref_map);
+        let detection_map: std::collections::HashMap<_, _> = match state.store.list_detections() {
+            Ok(entries) => entries.into_iter().collect(),
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
+                return std::ptr::null_mut();
+            }
+        };
+        let pref_map: std::collections::HashMap<_, _> = match state.store.list_manager_preferences()
+        {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|pref| (pref.manager, pref))
+                .collect(),
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE);
+                return std::ptr::null_mut();
+            }
+        };
+        let executable_states = build_manager_executable_doctor_states(&detection_map, &pref_map);
 
-    let report = helm_core::doctor::scan_package_state_report(
-        ManagerId::ALL,
-        &instances_by_manager,
-        installed_packages.as_slice(),
-        &executable_states,
-    );
-    let json = match serde_json::to_string(&report) {
-        Ok(json) => json,
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_INTERNAL);
-            return std::ptr::null_mut();
-        }
-    };
+        let report = helm_core::doctor::scan_package_state_report(
+            ManagerId::ALL,
+            &instances_by_manager,
+            installed_packages.as_slice(),
+            &executable_states,
+        );
+        let json = match serde_json::to_string(&report) {
+            Ok(json) => json,
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_INTERNAL);
+                return std::ptr::null_mut();
+            }
+        };
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
-/// Return whether shared onboarding has been completed.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_get_cli_onboarding_completed() -> bool {
+fn read_bool_setting(key: SettingKey) -> bool {
     let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
+    let Some(state) = guard.as_ref() else {
+        return false;
     };
-    state.store.cli_onboarding_completed().unwrap_or(false)
+    helm_core::settings::get_setting(state.store.as_ref(), key)
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
 }
 
-/// Set shared onboarding completion state. Returns true on success.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_set_cli_onboarding_completed(completed: bool) -> bool {
+fn write_setting(key: SettingKey, value: serde_json::Value) -> bool {
     let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
+    let Some(state) = guard.as_ref() else {
+        return false;
     };
-    state.store.set_cli_onboarding_completed(completed).is_ok()
+    helm_core::settings::set_setting(state.store.as_ref(), key, &value).is_ok()
 }
 
-/// Return accepted shared license terms version.
+/// Read one setting from the typed settings registry.
 ///
-/// Returns null when unset or unavailable.
+/// Returns the JSON-encoded value (`true`, `1440`, `"v1"`, or `null` for unset optional
+/// settings), or null on error.
+///
+/// # Safety
+///
+/// `key` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_get_cli_accepted_license_terms_version() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+pub unsafe extern "C" fn helm_get_setting(key: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_get_setting", || {
+        clear_last_error_key();
+        let key = match unsafe { parse_required_cstr_arg(key) } {
+            Ok(key) => key,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let Ok(key) = key.parse::<SettingKey>() else {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        };
 
-    let version = match state.store.cli_accepted_license_terms_version() {
-        Ok(version) => version,
-        Err(_) => return std::ptr::null_mut(),
-    };
-    let Some(version) = version else {
-        return std::ptr::null_mut();
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let value = match helm_core::settings::get_setting(state.store.as_ref(), key) {
+            Ok(value) => value,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    match CString::new(version) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(value.to_string()) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// Set accepted shared license terms version.
+/// Validate and persist one setting from a JSON value. Returns true on success.
 ///
-/// Pass null to clear. Returns true on success.
+/// Unknown keys and values of the wrong kind fail with `service.error.invalid_input`.
+/// Successful changes are published to `helm_list_setting_changes`.
 ///
 /// # Safety
 ///
-/// `version` may be null; when non-null, it must point to a valid NUL-terminated UTF-8 string.
+/// `key` and `json_value` must be valid, non-null pointers to NUL-terminated UTF-8 C strings.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_set_cli_accepted_license_terms_version(
-    version: *const c_char,
-) -> bool {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
-
-    let value = if version.is_null() {
-        None
-    } else {
-        let version_cstr = unsafe { CStr::from_ptr(version) };
-        let version_str = match version_cstr.to_str() {
-            Ok(s) => s,
-            Err(_) => return false,
+pub unsafe extern "C" fn helm_set_setting(key: *const c_char, json_value: *const c_char) -> bool {
+    ffi_boundary("helm_set_setting", || {
+        clear_last_error_key();
+        let key = match unsafe { parse_required_cstr_arg(key) } {
+            Ok(key) => key,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(key) = key.parse::<SettingKey>() else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let raw_value = match unsafe { parse_required_cstr_arg(json_value) } {
+            Ok(raw_value) => raw_value,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&raw_value) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
         };
-        Some(version_str)
-    };
-
-    state
-        .store
-        .set_cli_accepted_license_terms_version(value)
-        .is_ok()
-}
 
-/// Return whether safe mode is enabled.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_get_safe_mode() -> bool {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
-    state.store.safe_mode().unwrap_or(false)
-}
-
-/// Set safe mode state. Returns true on success.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_set_safe_mode(enabled: bool) -> bool {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
-    state.store.set_safe_mode(enabled).is_ok()
-}
-
-/// Return whether Homebrew upgrades should auto-clean old kegs by default.
-#[unsafe(no_mangle)]
-pub extern "C" fn helm_get_homebrew_keg_auto_cleanup() -> bool {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
-
-    matches!(
-        state.store.homebrew_keg_policy(),
-        Ok(HomebrewKegPolicy::Cleanup)
-    )
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_bool(SERVICE_ERROR_INTERNAL);
+        };
+        match helm_core::settings::set_setting(state.store.as_ref(), key, &value) {
+            Ok(_) => {
+                match key {
+                    SettingKey::HomebrewCaskExcludeAutoUpdates => {
+                        sync_homebrew_cask_settings(state.store.as_ref())
+                    }
+                    SettingKey::SlowCommandTracing => {
+                        sync_command_timing_settings(state.store.as_ref())
+                    }
+                    SettingKey::NpmRegistryOutdatedChecks => {
+                        sync_npm_settings(state.store.as_ref())
+                    }
+                    SettingKey::ProcessQos => sync_process_qos_settings(state.store.as_ref()),
+                    SettingKey::AllowHeavyNetworkWhenMetered => {
+                        sync_network_policy_settings(state.store.as_ref())
+                    }
+                    SettingKey::HidePipxPackagesFromPip => {
+                        // Turning the rule on hides existing duplicates right away;
+                        // turning it off restores them on pip's next refresh.
+                        if let Err(error) = reconcile_package_snapshots(
+                            state.store.as_ref(),
+                            Some(state.store.as_ref()),
+                            ManagerId::Pip,
+                        ) {
+                            eprintln!("helm_set_setting: failed to reconcile snapshots: {error}");
+                        }
+                        invalidate_package_snapshot_cache();
+                    }
+                    _ => {}
+                }
+                true
+            }
+            Err(error) if error.kind == helm_core::models::CoreErrorKind::InvalidInput => {
+                return_error_bool(SERVICE_ERROR_INVALID_INPUT)
+            }
+            Err(_) => return_error_bool(SERVICE_ERROR_STORAGE_FAILURE),
+        }
+    })
 }
 
-/// Set the global Homebrew keg policy.
+/// List every registry setting with its kind, current value, and default as JSON.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_set_homebrew_keg_auto_cleanup(enabled: bool) -> bool {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
+pub extern "C" fn helm_list_settings() -> *mut c_char {
+    ffi_boundary("helm_list_settings", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let settings = match helm_core::settings::list_settings(state.store.as_ref()) {
+            Ok(settings) => settings,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    let policy = if enabled {
-        HomebrewKegPolicy::Cleanup
-    } else {
-        HomebrewKegPolicy::Keep
-    };
-    state.store.set_homebrew_keg_policy(policy).is_ok()
+        let json = match serde_json::to_string(&settings) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// List per-package Homebrew keg policy overrides as JSON.
+/// List setting changes published after `after_sequence` as JSON, oldest first.
+///
+/// Pass 0 to read all retained changes; callers poll with the last `sequence` they saw.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_list_package_keg_policies() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
-
-    #[derive(serde::Serialize)]
-    struct FfiPackageKegPolicy {
-        manager_id: String,
-        package_name: String,
-        policy: String,
-    }
-
-    let policies = match state.store.list_package_keg_policies() {
-        Ok(entries) => entries
-            .into_iter()
-            .map(|entry| FfiPackageKegPolicy {
-                manager_id: entry.package.manager.as_str().to_string(),
-                package_name: entry.package.name,
-                policy: entry.policy.as_str().to_string(),
-            })
-            .collect::<Vec<_>>(),
-        Err(error) => {
-            eprintln!("Failed to list package keg policies: {error}");
-            return std::ptr::null_mut();
+pub extern "C" fn helm_list_setting_changes(after_sequence: i64) -> *mut c_char {
+    ffi_boundary("helm_list_setting_changes", || {
+        clear_last_error_key();
+        let changes = helm_core::settings::setting_changes_since(after_sequence.max(0) as u64);
+        let json = match serde_json::to_string(&changes) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    };
-
-    let json = match serde_json::to_string(&policies) {
-        Ok(json) => json,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+    })
 }
 
-/// List per-package manager preferences as JSON.
+/// List upcoming scheduled background runs (kind, manager, ETA) as JSON, soonest first.
+///
+/// Hosts use this to keep the service alive until the next due run. ETAs are
+/// wall-clock unix seconds; runs whose due time already passed report `overdue`.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_list_package_manager_preferences() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
-
-    #[derive(serde::Serialize)]
-    struct FfiPackageManagerPreference {
-        package_family_key: String,
-        manager_id: String,
-    }
+pub extern "C" fn helm_get_next_scheduled_runs() -> *mut c_char {
+    ffi_boundary("helm_get_next_scheduled_runs", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let now_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+        let runs =
+            match helm_core::scheduled_runs::next_scheduled_runs(state.store.as_ref(), now_unix) {
+                Ok(runs) => runs,
+                Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+            };
 
-    let preferences = match state.store.list_package_manager_preferences() {
-        Ok(entries) => entries
-            .into_iter()
-            .map(|entry| FfiPackageManagerPreference {
-                package_family_key: entry.package_family_key,
-                manager_id: entry.manager.as_str().to_string(),
-            })
-            .collect::<Vec<_>>(),
-        Err(error) => {
-            eprintln!("Failed to list package manager preferences: {error}");
-            return std::ptr::null_mut();
+        let json = match serde_json::to_string(&runs) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    };
+    })
+}
 
-    let json = match serde_json::to_string(&preferences) {
-        Ok(json) => json,
-        Err(_) => return std::ptr::null_mut(),
-    };
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FfiRefreshSchedule {
+    manager_id: String,
+    interval_secs: u64,
+    last_refreshed_unix: Option<i64>,
+    next_run_unix: i64,
+    overdue: bool,
+}
 
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+fn build_ffi_refresh_schedules(
+    schedules: &[helm_core::orchestration::RefreshSchedule],
+    now_unix: i64,
+) -> Vec<FfiRefreshSchedule> {
+    schedules
+        .iter()
+        .map(|schedule| {
+            let next_run_unix = schedule.next_run_unix(now_unix);
+            FfiRefreshSchedule {
+                manager_id: schedule.manager.as_str().to_string(),
+                interval_secs: schedule.interval_secs,
+                last_refreshed_unix: schedule.last_refreshed_unix,
+                next_run_unix,
+                overdue: next_run_unix <= now_unix,
+            }
+        })
+        .collect()
 }
 
-/// Set per-package Homebrew keg policy override.
-///
-/// `policy_mode` values:
-/// - `-1`: clear override (use global)
-/// - `0`: keep old kegs
-/// - `1`: cleanup old kegs
+/// Refresh a manager in the background every `interval_secs` seconds (at least
+/// 300); `0` removes its schedule. Returns true on success.
 ///
 /// # Safety
 ///
-/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
-/// strings.
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_set_package_keg_policy(
+pub unsafe extern "C" fn helm_set_refresh_schedule(
     manager_id: *const c_char,
-    package_name: *const c_char,
-    policy_mode: i32,
+    interval_secs: i64,
 ) -> bool {
-    if manager_id.is_null() || package_name.is_null() {
-        return false;
-    }
+    ffi_boundary("helm_set_refresh_schedule", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(interval_secs) = u64::try_from(interval_secs) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Some(store) = active_state_store() else {
+            return return_error_bool(SERVICE_ERROR_INTERNAL);
+        };
 
-    let manager = {
-        let c_str = unsafe { CStr::from_ptr(manager_id) };
-        match c_str
-            .to_str()
-            .ok()
-            .and_then(|value| value.parse::<ManagerId>().ok())
-        {
-            Some(manager) => manager,
-            None => return false,
+        match helm_core::orchestration::refresh_scheduler::set_refresh_schedule(
+            store.as_ref(),
+            manager,
+            interval_secs,
+        ) {
+            Ok(()) => true,
+            Err(error) => return_core_error_bool(&error),
         }
-    };
+    })
+}
 
-    if manager != ManagerId::HomebrewFormula {
-        return false;
-    }
+/// List per-manager background refresh schedules as JSON, ordered by manager id.
+///
+/// `nextRunUnix` is wall-clock unix seconds; schedules whose run is already
+/// due report `overdue`.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_refresh_schedules() -> *mut c_char {
+    ffi_boundary("helm_get_refresh_schedules", || {
+        clear_last_error_key();
+        let Some(store) = active_state_store() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let schedules =
+            match helm_core::orchestration::refresh_scheduler::refresh_schedules(store.as_ref()) {
+                Ok(schedules) => schedules,
+                Err(error) => return return_core_error_ptr(&error),
+            };
+        let now_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
 
-    let package_name = {
-        let c_str = unsafe { CStr::from_ptr(package_name) };
-        match c_str.to_str() {
-            Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
-            _ => return false,
+        let json = match serde_json::to_string(&build_ffi_refresh_schedules(&schedules, now_unix)) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    };
+    })
+}
 
-    let policy = match policy_mode {
-        -1 => None,
-        0 => Some(HomebrewKegPolicy::Keep),
-        1 => Some(HomebrewKegPolicy::Cleanup),
-        _ => return false,
-    };
+/// Lift a manager's refresh-failure quarantine and refresh it now in the
+/// background. Managers that are not quarantined are refreshed as well.
+/// Returns true once the refresh is queued.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_retry_quarantined_manager(manager_id: *const c_char) -> bool {
+    ffi_boundary("helm_retry_quarantined_manager", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let (Some(store), Some((runtime, rt_handle))) =
+            (active_state_store(), active_state_runtime())
+        else {
+            return return_error_bool(SERVICE_ERROR_INTERNAL);
+        };
 
-    let package = PackageRef {
-        manager,
-        name: package_name,
-    };
+        if let Err(error) =
+            helm_core::orchestration::quarantine::release_quarantine(store.as_ref(), manager)
+        {
+            return return_core_error_bool(&error);
+        }
+        rt_handle.spawn(async move {
+            if let Err(error) = runtime.refresh_manager(manager).await {
+                log_manager_operation_failure("quarantine retry", manager, &error);
+            }
+        });
+        true
+    })
+}
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
+/// Hint that the host just woke from system sleep.
+///
+/// Background tickers re-evaluate due schedules on their next poll instead of
+/// waiting out a tick interval that may have stalled during sleep. Always returns true.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_notify_wake() -> bool {
+    ffi_boundary("helm_notify_wake", || {
+        clear_last_error_key();
+        helm_core::scheduled_runs::notify_wake();
+        true
+    })
+}
 
-    state.store.set_package_keg_policy(&package, policy).is_ok()
+/// Tell the core which manager the host is showing, so refreshes start with
+/// it and defer the rest until it finishes. Returns true on success.
+///
+/// `json_hint` is an object with an optional `manager` id; `{}` clears the
+/// focus.
+///
+/// # Safety
+///
+/// `json_hint` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_view_hint(json_hint: *const c_char) -> bool {
+    ffi_boundary("helm_set_view_hint", || {
+        clear_last_error_key();
+        let raw_hint = match unsafe { parse_required_cstr_arg(json_hint) } {
+            Ok(raw_hint) => raw_hint,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(hint) = serde_json::from_str::<helm_core::view_hint::ViewHint>(&raw_hint) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        helm_core::view_hint::set_view_hint(hint);
+        true
+    })
 }
 
-/// Set or clear per-package manager preference.
+/// Tell the core whether the network is metered, so refreshes skip
+/// bandwidth-heavy work (Homebrew's auto-update, softwareupdate scans, and
+/// search catalog prefetch) unless the `allow_heavy_network_when_metered`
+/// setting is on. Returns true on success.
 ///
-/// Pass null for `manager_id` to clear preference.
+/// `json_hint` is an object with optional `expensive` (cellular or personal
+/// hotspot) and `constrained` (Low Data Mode) booleans; `{}` clears both.
 ///
 /// # Safety
 ///
-/// `package_family_key` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
-/// `manager_id` may be null; when non-null it must point to a NUL-terminated UTF-8 C string.
+/// `json_hint` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_set_package_manager_preference(
-    package_family_key: *const c_char,
-    manager_id: *const c_char,
-) -> bool {
-    if package_family_key.is_null() {
-        return false;
-    }
+pub unsafe extern "C" fn helm_set_network_hint(json_hint: *const c_char) -> bool {
+    ffi_boundary("helm_set_network_hint", || {
+        clear_last_error_key();
+        let raw_hint = match unsafe { parse_required_cstr_arg(json_hint) } {
+            Ok(raw_hint) => raw_hint,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(hint) = serde_json::from_str::<helm_core::network_policy::NetworkHint>(&raw_hint)
+        else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        helm_core::network_policy::set_network_hint(hint);
+        true
+    })
+}
 
-    let package_family_key = {
-        let c_str = unsafe { CStr::from_ptr(package_family_key) };
-        match c_str.to_str() {
-            Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
-            _ => return false,
-        }
-    };
+/// Report build and runtime facts for the About screen and support as JSON:
+/// crate versions, database schema version, database path, uptime, the
+/// compiled-in adapter list, and any unrecognized manager ids (written by a
+/// newer Helm) whose rows listings are skipping.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_engine_info() -> *mut c_char {
+    ffi_boundary("helm_get_engine_info", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let info = match state.engine.info() {
+            Ok(info) => build_ffi_engine_info(info),
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    let manager = if manager_id.is_null() {
-        None
-    } else {
-        let c_str = unsafe { CStr::from_ptr(manager_id) };
-        match c_str
-            .to_str()
-            .ok()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-        {
-            Some(value) => match value.parse::<ManagerId>() {
-                Ok(manager) => Some(manager),
-                Err(_) => return false,
-            },
-            None => None,
+        let json = match serde_json::to_string(&info) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    };
+    })
+}
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return false,
-    };
+/// Report the outcome of the latest refresh-all run as JSON: success and
+/// failure counts, plus per-manager results with the failure's service error
+/// key, code, and message and the refresh duration. Managers that were skipped
+/// as disabled or undetected are absent. `suppressedOnMeteredNetwork` names
+/// managers whose bandwidth-heavy work was skipped on a metered network, and
+/// `skippedWhileQuarantined` names quarantined managers the run left out.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_refresh_summary() -> *mut c_char {
+    ffi_boundary("helm_get_refresh_summary", || {
+        clear_last_error_key();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            let Some(state) = guard.as_ref() else {
+                return return_error_ptr(SERVICE_ERROR_INTERNAL);
+            };
+            state.store.clone()
+        };
+        let summary = match store.list_refresh_results() {
+            Ok(results) => build_ffi_refresh_summary(
+                results,
+                helm_core::network_policy::current_network_hint().is_metered(),
+                helm_core::network_policy::last_refresh_suppressions(),
+                helm_core::orchestration::quarantine::last_refresh_quarantine_skips(),
+            ),
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    state
-        .store
-        .set_package_manager_preference(package_family_key.as_str(), manager)
-        .is_ok()
+        let json = match serde_json::to_string(&summary) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// Build an ordered upgrade execution plan from cached outdated snapshot as JSON.
-///
-/// - `include_pinned`: if false, pinned packages are excluded.
-/// - `allow_os_updates`: explicit confirmation gate for `softwareupdate` steps.
+/// Report progress of the most recent upgrade-all batch as JSON: step counts
+/// by status, the fraction of steps finished, labels of the running steps,
+/// and an ETA estimated from archived upgrade durations. All counts are zero
+/// before the first batch.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_preview_upgrade_plan(
-    include_pinned: bool,
-    allow_os_updates: bool,
-) -> *mut c_char {
-    clear_last_error_key();
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
+pub extern "C" fn helm_get_bulk_operation_progress() -> *mut c_char {
+    ffi_boundary("helm_get_bulk_operation_progress", || {
+        clear_last_error_key();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            let Some(state) = guard.as_ref() else {
+                return return_error_ptr(SERVICE_ERROR_INTERNAL);
+            };
+            state.store.clone()
+        };
+        let history_filter = TaskHistoryFilter {
+            task_type: Some(TaskType::Upgrade),
+            ..TaskHistoryFilter::default()
+        };
+        let records = store.read_snapshot(|| {
+            let recent = store.list_recent_tasks(TASK_RECENT_FETCH_LIMIT)?;
+            let history =
+                store.list_task_history(&history_filter, 0, TASK_HISTORY_PAGE_MAX_LIMIT)?;
+            Ok::<_, helm_core::models::CoreError>((recent, history))
+        });
+        let (recent, history) = match records.and_then(|result| result) {
+            Ok(records) => records,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    let outdated = match state.store.list_outdated() {
-        Ok(packages) => packages,
-        Err(error) => {
-            eprintln!("preview_upgrade_plan: failed to list outdated packages: {error}");
-            return std::ptr::null_mut();
-        }
-    };
+        let (started_at, steps) = {
+            let mut session = lock_or_recover(&BULK_OPERATION, "bulk_operation");
+            match session.as_mut() {
+                Some(session) => (
+                    Some(session.started_at),
+                    resolve_bulk_operation_steps(session, &recent, &history),
+                ),
+                None => (None, Vec::new()),
+            }
+        };
+        let progress =
+            build_ffi_bulk_operation_progress(started_at, &steps, &history, SystemTime::now());
 
-    let targets = collect_upgrade_all_targets(&outdated, include_pinned);
-    let mut steps: Vec<FfiUpgradePlanStep> = Vec::new();
-    let mut order_index = 0_u64;
+        let json = match serde_json::to_string(&progress) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Asdf) {
-        for package_name in targets.asdf {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Asdf,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
-
-    if state.runtime.is_manager_enabled(ManagerId::HomebrewFormula) {
-        for package_name in targets.homebrew {
-            let cleanup_old_kegs = effective_homebrew_keg_policy(&state.store, &package_name)
-                == HomebrewKegPolicy::Cleanup;
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::HomebrewFormula,
-                package_name,
-                cleanup_old_kegs,
-                &mut order_index,
-            );
-        }
-    }
-
-    if state.runtime.is_manager_enabled(ManagerId::HomebrewCask) {
-        for package_name in targets.homebrew_cask {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::HomebrewCask,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
-
-    if state.runtime.is_manager_enabled(ManagerId::Mas) {
-        for package_name in targets.mas {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Mas,
-                package_name,
-                false,
-                &mut order_index,
-            );
+/// Report cached registry reachability as JSON: per-host status with the last
+/// probe time, plus managers whose network work is deferred until connectivity returns.
+///
+/// Reads the cache only; it never probes the network.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_network_reachability() -> *mut c_char {
+    ffi_boundary("helm_get_network_reachability", || {
+        clear_last_error_key();
+        let snapshot = helm_core::reachability::reachability_checker()
+            .map(|checker| checker.snapshot())
+            .unwrap_or_default();
+
+        let json = match serde_json::to_string(&snapshot) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Mise) {
-        for package_name in targets.mise {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Mise,
-                package_name,
-                false,
-                &mut order_index,
-            );
+/// Report command timing metrics as JSON: rolling duration histograms per
+/// (manager, action) for every spawned command since launch, plus today's
+/// slowest commands when the `slow_command_tracing` setting is on.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_command_metrics() -> *mut c_char {
+    ffi_boundary("helm_get_command_metrics", || {
+        clear_last_error_key();
+        let snapshot = helm_core::execution::command_timing_snapshot();
+
+        let json = match serde_json::to_string(&snapshot) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Npm) {
-        for package_name in targets.npm {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Npm,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Report task counts, installed and outdated totals, snapshot ages, and
+/// command duration histograms per manager as Prometheus exposition text,
+/// for scraping through a small local shim. Disabled managers are omitted.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_metrics_text() -> *mut c_char {
+    ffi_boundary("helm_get_metrics_text", || {
+        clear_last_error_key();
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
+        };
+        let Some(packages) = package_snapshot("render metrics") else {
+            return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+        };
 
-    if state.runtime.is_manager_enabled(ManagerId::Pnpm) {
-        for package_name in targets.pnpm {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Pnpm,
-                package_name,
-                false,
-                &mut order_index,
-            );
+        let loaded = store.read_snapshot(|| {
+            let enabled_by_manager = manager_enabled_map(store.as_ref());
+            let tasks = store.list_recent_tasks(TASK_RECENT_FETCH_LIMIT)?;
+            let freshness = store.list_package_snapshot_freshness()?;
+            PersistenceResult::Ok((enabled_by_manager, tasks, freshness))
+        });
+        let (enabled_by_manager, mut tasks, mut freshness) = match loaded {
+            Ok(Ok(loaded)) => loaded,
+            Ok(Err(error)) | Err(error) => {
+                eprintln!("helm_get_metrics_text: failed to read store: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+        tasks.retain(|task| manager_is_enabled(&enabled_by_manager, task.manager));
+        freshness.retain(|freshness| manager_is_enabled(&enabled_by_manager, freshness.manager));
+        let mut command_timings = helm_core::execution::command_timing_snapshot();
+        command_timings
+            .histograms
+            .retain(|histogram| manager_is_enabled(&enabled_by_manager, histogram.manager_id));
+
+        let text = render_metrics_text(&MetricsInput {
+            tasks: &tasks,
+            installed: &packages.installed,
+            outdated: &packages.outdated,
+            freshness: &freshness,
+            command_timings: &command_timings,
+            now: SystemTime::now(),
+        });
+        match CString::new(text) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Yarn) {
-        for package_name in targets.yarn {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Yarn,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Report the environment a manager's next command would run with as JSON:
+/// effective and inherited PATH, environment overrides (secrets redacted), the
+/// selected executable, and the resolved program path. Nothing is executed.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_get_manager_environment(manager_id: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_get_manager_environment", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let engine = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.engine.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
+        };
 
-    if state.runtime.is_manager_enabled(ManagerId::Cargo) {
-        for package_name in targets.cargo {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Cargo,
-                package_name,
-                false,
-                &mut order_index,
-            );
+        let environment = engine.manager_environment(manager);
+        match serde_json::to_string(&environment)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::CargoBinstall) {
-        for package_name in targets.cargo_binstall {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::CargoBinstall,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Return whether shared onboarding has been completed.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_cli_onboarding_completed() -> bool {
+    ffi_boundary("helm_get_cli_onboarding_completed", || {
+        read_bool_setting(SettingKey::CliOnboardingCompleted)
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Pip) {
-        for package_name in targets.pip {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Pip,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Set shared onboarding completion state. Returns true on success.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_set_cli_onboarding_completed(completed: bool) -> bool {
+    ffi_boundary("helm_set_cli_onboarding_completed", || {
+        write_setting(
+            SettingKey::CliOnboardingCompleted,
+            serde_json::Value::Bool(completed),
+        )
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::Pipx) {
-        for package_name in targets.pipx {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Pipx,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Return the first-run onboarding state as JSON
+/// `{phase, suggestedChoices}`, where `phase` is one of `not_started`,
+/// `detecting`, `review`, or `complete`.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_onboarding_state() -> *mut c_char {
+    ffi_boundary("helm_get_onboarding_state", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let onboarding = match helm_core::onboarding::onboarding_state(state.store.as_ref()) {
+            Ok(onboarding) => onboarding,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE),
+        };
 
-    if state.runtime.is_manager_enabled(ManagerId::Poetry) {
-        for package_name in targets.poetry {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Poetry,
-                package_name,
-                false,
-                &mut order_index,
-            );
+        match serde_json::to_string(&onboarding)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+        {
+            Some(c) => c.into_raw(),
+            None => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
+}
 
-    if state.runtime.is_manager_enabled(ManagerId::RubyGems) {
-        for package_name in targets.rubygems {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::RubyGems,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+/// Apply onboarding choices and mark onboarding complete in one transaction,
+/// then start the initial refresh. Returns true on success.
+///
+/// `json_choices` is an object with optional `managerEnabled` (manager id to
+/// bool), `safeMode`, `homebrewKegPolicy`, `autoCheckForUpdates`, and
+/// `autoCheckFrequencyMinutes`; omitted fields keep their stored value.
+///
+/// # Safety
+///
+/// `json_choices` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_complete_onboarding(json_choices: *const c_char) -> bool {
+    ffi_boundary("helm_complete_onboarding", || {
+        clear_last_error_key();
+        let raw_choices = match unsafe { parse_required_cstr_arg(json_choices) } {
+            Ok(raw_choices) => raw_choices,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Ok(choices) = serde_json::from_str::<OnboardingChoices>(&raw_choices) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
 
-    if state.runtime.is_manager_enabled(ManagerId::Bundler) {
-        for package_name in targets.bundler {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Bundler,
-                package_name,
-                false,
-                &mut order_index,
-            );
-        }
-    }
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let Some(state) = guard.as_ref() else {
+                return return_error_bool(SERVICE_ERROR_INTERNAL);
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
 
-    if state.runtime.is_manager_enabled(ManagerId::Rustup) {
-        for package_name in targets.rustup {
-            push_upgrade_plan_step(
-                &mut steps,
-                ManagerId::Rustup,
-                package_name,
-                false,
-                &mut order_index,
-            );
+        match helm_core::onboarding::complete_onboarding(store.as_ref(), &choices) {
+            Ok(()) => {}
+            Err(error) if error.kind == helm_core::models::CoreErrorKind::InvalidInput => {
+                return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+            }
+            Err(_) => return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE),
         }
-    }
-
-    if allow_os_updates
-        && targets.softwareupdate_outdated
-        && state.runtime.is_manager_enabled(ManagerId::SoftwareUpdate)
-        && !state.runtime.is_safe_mode()
-    {
-        push_upgrade_plan_step(
-            &mut steps,
-            ManagerId::SoftwareUpdate,
-            "__confirm_os_updates__".to_string(),
-            false,
-            &mut order_index,
-        );
-    }
+        invalidate_package_snapshot_cache();
 
-    let json = match serde_json::to_string(&steps) {
-        Ok(json) => json,
-        Err(error) => {
-            eprintln!("preview_upgrade_plan: failed to encode JSON: {error}");
-            return std::ptr::null_mut();
+        for (manager, enabled) in &choices.manager_enabled {
+            if !enabled
+                && !purge_tasks_for_manager(
+                    store.as_ref(),
+                    runtime.as_ref(),
+                    &rt_handle,
+                    *manager,
+                    "complete_onboarding",
+                )
+            {
+                return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+            }
         }
-    };
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        helm_trigger_refresh()
+    })
 }
 
-/// Queue upgrade tasks for supported managers using cached outdated snapshot.
+/// Return accepted shared license terms version.
 ///
-/// - `include_pinned`: if false, pinned packages are excluded.
-/// - `allow_os_updates`: explicit confirmation gate for `softwareupdate` upgrades.
+/// Returns null when unset or unavailable.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_upgrade_all(include_pinned: bool, allow_os_updates: bool) -> bool {
-    clear_last_error_key();
-    if external_coordinator_state_dir().is_some() {
-        return coordinator_start_workflow_external(CoordinatorWorkflowRequest::UpdatesRun {
-            include_pinned,
-            allow_os_updates,
-        })
-        .is_ok();
-    }
-    let (store, runtime, tokio_rt) = {
+pub extern "C" fn helm_get_cli_accepted_license_terms_version() -> *mut c_char {
+    ffi_boundary("helm_get_cli_accepted_license_terms_version", || {
         let guard = lock_or_recover(&STATE, "state");
         let state = match guard.as_ref() {
             Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+            None => return std::ptr::null_mut(),
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state._tokio_rt.handle().clone(),
-        )
-    };
 
-    tokio_rt.spawn(async move {
-        let outdated = match store.list_outdated() {
-            Ok(packages) => packages,
-            Err(error) => {
-                eprintln!("upgrade_all: failed to list outdated packages: {error}");
-                return;
-            }
+        let version = match helm_core::settings::get_setting(
+            state.store.as_ref(),
+            SettingKey::CliAcceptedLicenseTermsVersion,
+        ) {
+            Ok(serde_json::Value::String(version)) => version,
+            _ => return std::ptr::null_mut(),
         };
 
-        let targets = collect_upgrade_all_targets(&outdated, include_pinned);
-
-        if runtime.is_manager_enabled(ManagerId::Asdf) {
-            for package_name in targets.asdf {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Asdf,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Asdf, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Asdf, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue asdf upgrade task: {error}");
-                    }
-                }
-            }
+        match CString::new(version) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
         }
+    })
+}
 
-        if runtime.is_manager_enabled(ManagerId::HomebrewFormula) {
-            for package_name in targets.homebrew {
-                let policy = effective_homebrew_keg_policy(&store, &package_name);
-                let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
-                let target_name = encode_homebrew_upgrade_target(&package_name, cleanup_old_kegs);
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::HomebrewFormula,
-                        name: target_name,
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::HomebrewFormula, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) = upgrade_task_label_for(
-                            ManagerId::HomebrewFormula,
-                            &package_name,
-                            cleanup_old_kegs,
-                        );
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue homebrew upgrade task: {error}");
-                    }
-                }
-            }
+/// Set accepted shared license terms version.
+///
+/// Pass null to clear. Returns true on success.
+///
+/// # Safety
+///
+/// `version` may be null; when non-null, it must point to a valid NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_cli_accepted_license_terms_version(
+    version: *const c_char,
+) -> bool {
+    ffi_boundary("helm_set_cli_accepted_license_terms_version", || {
+        let value = if version.is_null() {
+            serde_json::Value::Null
+        } else {
+            let version_cstr = unsafe { CStr::from_ptr(version) };
+            let version_str = match version_cstr.to_str() {
+                Ok(s) => s,
+                Err(_) => return false,
+            };
+            serde_json::Value::String(version_str.to_string())
+        };
+
+        write_setting(SettingKey::CliAcceptedLicenseTermsVersion, value)
+    })
+}
+
+/// Return whether safe mode is enabled.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_safe_mode() -> bool {
+    ffi_boundary("helm_get_safe_mode", || {
+        read_bool_setting(SettingKey::SafeMode)
+    })
+}
+
+/// Set safe mode state. Returns true on success.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_set_safe_mode(enabled: bool) -> bool {
+    ffi_boundary("helm_set_safe_mode", || {
+        write_setting(SettingKey::SafeMode, serde_json::Value::Bool(enabled))
+    })
+}
+
+/// Return whether offline mode is enabled.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_offline_mode() -> bool {
+    ffi_boundary("helm_get_offline_mode", || {
+        read_bool_setting(SettingKey::OfflineMode)
+    })
+}
+
+/// Set offline mode state. While enabled, tasks that need a manager's
+/// registry (remote search, index refresh, outdated checks, installs,
+/// upgrades) are refused and refreshes only list installed packages.
+/// Returns true on success.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_set_offline_mode(enabled: bool) -> bool {
+    ffi_boundary("helm_set_offline_mode", || {
+        write_setting(SettingKey::OfflineMode, serde_json::Value::Bool(enabled))
+    })
+}
+
+/// Return whether Homebrew upgrades should auto-clean old kegs by default.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_homebrew_keg_auto_cleanup() -> bool {
+    ffi_boundary("helm_get_homebrew_keg_auto_cleanup", || {
+        read_bool_setting(SettingKey::HomebrewKegAutoCleanup)
+    })
+}
+
+/// Set the global Homebrew keg policy.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_set_homebrew_keg_auto_cleanup(enabled: bool) -> bool {
+    ffi_boundary("helm_set_homebrew_keg_auto_cleanup", || {
+        write_setting(
+            SettingKey::HomebrewKegAutoCleanup,
+            serde_json::Value::Bool(enabled),
+        )
+    })
+}
+
+/// List per-package Homebrew keg policy overrides as JSON.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_package_keg_policies() -> *mut c_char {
+    ffi_boundary("helm_list_package_keg_policies", || {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        #[derive(serde::Serialize)]
+        struct FfiPackageKegPolicy {
+            manager_id: String,
+            package_name: String,
+            package_key: String,
+            policy: String,
         }
 
-        if runtime.is_manager_enabled(ManagerId::HomebrewCask) {
-            for package_name in targets.homebrew_cask {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::HomebrewCask,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::HomebrewCask, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::HomebrewCask, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!(
-                            "upgrade_all: failed to queue homebrew cask upgrade task: {error}"
-                        );
-                    }
-                }
+        let policies = match state.store.list_package_keg_policies() {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|entry| FfiPackageKegPolicy {
+                    manager_id: entry.package.manager.as_str().to_string(),
+                    package_key: entry.package.key().to_string(),
+                    package_name: entry.package.name,
+                    policy: entry.policy.as_str().to_string(),
+                })
+                .collect::<Vec<_>>(),
+            Err(error) => {
+                eprintln!("Failed to list package keg policies: {error}");
+                return std::ptr::null_mut();
             }
+        };
+
+        let json = match serde_json::to_string(&policies) {
+            Ok(json) => json,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => std::ptr::null_mut(),
         }
+    })
+}
 
-        if runtime.is_manager_enabled(ManagerId::Mas) {
-            for package_name in targets.mas {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Mas,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Mas, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Mas, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue mas upgrade task: {error}");
-                    }
-                }
-            }
+/// List per-package manager preferences as JSON.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_package_manager_preferences() -> *mut c_char {
+    ffi_boundary("helm_list_package_manager_preferences", || {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        #[derive(serde::Serialize)]
+        struct FfiPackageManagerPreference {
+            package_family_key: String,
+            manager_id: String,
         }
 
-        if runtime.is_manager_enabled(ManagerId::Mise) {
-            for package_name in targets.mise {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Mise,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Mise, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Mise, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue mise upgrade task: {error}");
-                    }
-                }
+        let preferences = match state.store.list_package_manager_preferences() {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|entry| FfiPackageManagerPreference {
+                    package_family_key: entry.package_family_key,
+                    manager_id: entry.manager.as_str().to_string(),
+                })
+                .collect::<Vec<_>>(),
+            Err(error) => {
+                eprintln!("Failed to list package manager preferences: {error}");
+                return std::ptr::null_mut();
             }
+        };
+
+        let json = match serde_json::to_string(&preferences) {
+            Ok(json) => json,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => std::ptr::null_mut(),
         }
+    })
+}
 
-        if runtime.is_manager_enabled(ManagerId::Npm) {
-            for package_name in targets.npm {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Npm,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Npm, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Npm, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue npm upgrade task: {error}");
-                    }
-                }
-            }
+/// Set per-package Homebrew keg policy override.
+///
+/// `policy_mode` values:
+/// - `-1`: clear override (use global)
+/// - `0`: keep old kegs
+/// - `1`: cleanup old kegs
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
+/// strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_package_keg_policy(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    policy_mode: i32,
+) -> bool {
+    ffi_boundary("helm_set_package_keg_policy", || {
+        if manager_id.is_null() || package_name.is_null() {
+            return false;
         }
 
-        if runtime.is_manager_enabled(ManagerId::Pnpm) {
-            for package_name in targets.pnpm {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Pnpm,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Pnpm, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Pnpm, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue pnpm upgrade task: {error}");
-                    }
-                }
+        let manager = {
+            let c_str = unsafe { CStr::from_ptr(manager_id) };
+            match c_str
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<ManagerId>().ok())
+            {
+                Some(manager) => manager,
+                None => return false,
             }
+        };
+
+        if manager != ManagerId::HomebrewFormula {
+            return false;
         }
 
-        if runtime.is_manager_enabled(ManagerId::Yarn) {
-            for package_name in targets.yarn {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Yarn,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Yarn, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Yarn, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue yarn upgrade task: {error}");
-                    }
-                }
+        let package_name = {
+            let c_str = unsafe { CStr::from_ptr(package_name) };
+            match c_str.to_str() {
+                Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
+                _ => return false,
             }
-        }
+        };
 
-        if runtime.is_manager_enabled(ManagerId::Cargo) {
-            for package_name in targets.cargo {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Cargo,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Cargo, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Cargo, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue cargo upgrade task: {error}");
-                    }
-                }
+        let policy = match policy_mode {
+            -1 => None,
+            0 => Some(HomebrewKegPolicy::Keep),
+            1 => Some(HomebrewKegPolicy::Cleanup),
+            _ => return false,
+        };
+
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return false,
+        };
+
+        state.store.set_package_keg_policy(&package, policy).is_ok()
+    })
+}
+
+/// Set or clear per-package manager preference.
+///
+/// Pass null for `manager_id` to clear preference.
+///
+/// # Safety
+///
+/// `package_family_key` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `manager_id` may be null; when non-null it must point to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_package_manager_preference(
+    package_family_key: *const c_char,
+    manager_id: *const c_char,
+) -> bool {
+    ffi_boundary("helm_set_package_manager_preference", || {
+        if package_family_key.is_null() {
+            return false;
+        }
+
+        let package_family_key = {
+            let c_str = unsafe { CStr::from_ptr(package_family_key) };
+            match c_str.to_str() {
+                Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
+                _ => return false,
+            }
+        };
+
+        let manager = if manager_id.is_null() {
+            None
+        } else {
+            let c_str = unsafe { CStr::from_ptr(manager_id) };
+            match c_str
+                .to_str()
+                .ok()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+            {
+                Some(value) => match value.parse::<ManagerId>() {
+                    Ok(manager) => Some(manager),
+                    Err(_) => return false,
+                },
+                None => None,
+            }
+        };
+
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return false,
+        };
+
+        state
+            .store
+            .set_package_manager_preference(package_family_key.as_str(), manager)
+            .is_ok()
+    })
+}
+
+/// Build an ordered upgrade execution plan from cached outdated snapshot as JSON.
+///
+/// - `include_pinned`: if false, pinned packages are excluded.
+/// - `allow_os_updates`: explicit confirmation gate for `softwareupdate` steps.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_preview_upgrade_plan(
+    include_pinned: bool,
+    allow_os_updates: bool,
+) -> *mut c_char {
+    ffi_boundary("helm_preview_upgrade_plan", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        let outdated = match state.store.list_outdated() {
+            Ok(packages) => packages,
+            Err(error) => {
+                eprintln!("preview_upgrade_plan: failed to list outdated packages: {error}");
+                return std::ptr::null_mut();
+            }
+        };
+
+        let targets = collect_upgrade_all_targets(&outdated, include_pinned);
+        let mut steps: Vec<FfiUpgradePlanStep> = Vec::new();
+        let mut order_index = 0_u64;
+
+        if state.runtime.is_manager_enabled(ManagerId::Asdf) {
+            for package_name in targets.asdf {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Asdf,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::CargoBinstall) {
+        if state.runtime.is_manager_enabled(ManagerId::HomebrewFormula) {
+            for package_name in targets.homebrew {
+                let cleanup_old_kegs = effective_homebrew_keg_policy(&state.store, &package_name)
+                    == HomebrewKegPolicy::Cleanup;
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::HomebrewFormula,
+                    package_name,
+                    cleanup_old_kegs,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::HomebrewCask) {
+            for package_name in targets.homebrew_cask {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::HomebrewCask,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::MacPorts) {
+            for package_name in targets.macports {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::MacPorts,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Mas) {
+            for package_name in targets.mas {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Mas,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Mise) {
+            for package_name in targets.mise {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Mise,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Npm) {
+            for package_name in targets.npm {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Npm,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Pnpm) {
+            for package_name in targets.pnpm {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Pnpm,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Yarn) {
+            for package_name in targets.yarn {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Yarn,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::Cargo) {
+            for package_name in targets.cargo {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Cargo,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
+            }
+        }
+
+        if state.runtime.is_manager_enabled(ManagerId::CargoBinstall) {
             for package_name in targets.cargo_binstall {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::CargoBinstall,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::CargoBinstall, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::CargoBinstall, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!(
-                            "upgrade_all: failed to queue cargo-binstall upgrade task: {error}"
-                        );
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::CargoBinstall,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::Pip) {
+        if state.runtime.is_manager_enabled(ManagerId::Pip) {
             for package_name in targets.pip {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Pip,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Pip, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Pip, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue pip upgrade task: {error}");
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Pip,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::Pipx) {
+        if state.runtime.is_manager_enabled(ManagerId::Pipx) {
             for package_name in targets.pipx {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Pipx,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Pipx, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Pipx, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue pipx upgrade task: {error}");
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Pipx,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::Poetry) {
+        if state.runtime.is_manager_enabled(ManagerId::Poetry) {
             for package_name in targets.poetry {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Poetry,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Poetry, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Poetry, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue poetry upgrade task: {error}");
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Poetry,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::RubyGems) {
+        if state.runtime.is_manager_enabled(ManagerId::RubyGems) {
             for package_name in targets.rubygems {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::RubyGems,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::RubyGems, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::RubyGems, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue rubygems upgrade task: {error}");
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::RubyGems,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::Bundler) {
+        if state.runtime.is_manager_enabled(ManagerId::Bundler) {
             for package_name in targets.bundler {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Bundler,
-                        name: package_name.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Bundler, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Bundler, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue bundler upgrade task: {error}");
-                    }
-                }
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Bundler,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
-        if runtime.is_manager_enabled(ManagerId::Rustup) {
-            for toolchain in targets.rustup {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Rustup,
-                        name: toolchain.clone(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::Rustup, request).await {
-                    Ok(task_id) => {
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::Rustup, &toolchain, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!(
-                            "upgrade_all: failed to queue rustup toolchain upgrade task: {error}"
-                        );
-                    }
-                }
+        if state.runtime.is_manager_enabled(ManagerId::Rustup) {
+            for package_name in targets.rustup {
+                push_upgrade_plan_step(
+                    &mut steps,
+                    ManagerId::Rustup,
+                    package_name,
+                    false,
+                    &mut order_index,
+                );
             }
         }
 
         if allow_os_updates
-            && targets.softwareupdate_outdated
-            && runtime.is_manager_enabled(ManagerId::SoftwareUpdate)
+            && !targets.softwareupdate.is_empty()
+            && state.runtime.is_manager_enabled(ManagerId::SoftwareUpdate)
+            && !state.runtime.is_safe_mode()
         {
-            if runtime.is_safe_mode() {
-                eprintln!("upgrade_all: safe mode enabled; skipping softwareupdate upgrade");
-            } else {
-                let request = AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::SoftwareUpdate,
-                        name: "__confirm_os_updates__".to_string(),
-                    }),
-                    target_name: None,
-                    version: None,
-                });
-                match runtime.submit(ManagerId::SoftwareUpdate, request).await {
-                    Ok(task_id) => {
-                        let package_name = "__confirm_os_updates__".to_string();
-                        let (label_key, label_args) =
-                            upgrade_task_label_for(ManagerId::SoftwareUpdate, &package_name, false);
-                        set_task_label(task_id, label_key, &label_args);
-                    }
-                    Err(error) => {
-                        eprintln!("upgrade_all: failed to queue softwareupdate task: {error}");
-                    }
-                }
+            push_upgrade_plan_step(
+                &mut steps,
+                ManagerId::SoftwareUpdate,
+                CONFIRMED_OS_UPDATES_NAME.to_string(),
+                false,
+                &mut order_index,
+            );
+        }
+        annotate_upgrade_plan_download_sizes(&mut steps, &outdated, include_pinned);
+        let history_filter = TaskHistoryFilter {
+            task_type: Some(TaskType::Upgrade),
+            status: Some(TaskStatus::Completed),
+            ..TaskHistoryFilter::default()
+        };
+        let history = state
+            .store
+            .list_task_history(&history_filter, 0, TASK_HISTORY_PAGE_MAX_LIMIT)
+            .unwrap_or_else(|error| {
+                eprintln!("preview_upgrade_plan: failed to read task history: {error}");
+                Vec::new()
+            });
+        annotate_upgrade_plan_durations(&mut steps, &state.runtime, &history);
+
+        let json = match serde_json::to_string(&steps) {
+            Ok(json) => json,
+            Err(error) => {
+                eprintln!("preview_upgrade_plan: failed to encode JSON: {error}");
+                return std::ptr::null_mut();
             }
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Predict the outcome of `helm_upgrade_all` without executing anything, as JSON.
+///
+/// Returns the upgrades that would be queued (old → new version), the predicted
+/// post-upgrade installed snapshot, cross-manager conflicts for the same package
+/// family, restart requirements, and an estimated download size when adapters
+/// report one. OS updates are included so their restart requirement is visible,
+/// even though executing them needs a `helm_upgrade_all_confirmed` token.
+///
+/// - `include_pinned`: if false, pinned packages are excluded.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_simulate_upgrade_all(include_pinned: bool) -> *mut c_char {
+    ffi_boundary("helm_simulate_upgrade_all", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let simulation = match simulate_upgrade_all_for_state(state, include_pinned) {
+            Ok(simulation) => simulation,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+
+        let json = match serde_json::to_string(&simulation) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
+    })
+}
+
+/// Upgrade-all simulation over the cached snapshot, with OS updates included
+/// unless safe mode is on.
+fn simulate_upgrade_all_for_state(
+    state: &HelmState,
+    include_pinned: bool,
+) -> Result<helm_core::upgrade_simulation::UpgradeSimulation, &'static str> {
+    let snapshot = state.store.read_snapshot(|| {
+        let installed = state.store.list_installed()?;
+        let outdated = state.store.list_outdated()?;
+        Ok::<_, helm_core::models::CoreError>((installed, outdated))
     });
+    let (installed, outdated) = snapshot
+        .and_then(|result| result)
+        .map_err(|_| SERVICE_ERROR_STORAGE_FAILURE)?;
+    let installed: Vec<InstalledPackage> = installed
+        .into_iter()
+        .filter(|package| state.runtime.is_manager_enabled(package.package.manager))
+        .collect();
+    let os_updates_allowed = !state.runtime.is_safe_mode();
+    Ok(helm_core::upgrade_simulation::simulate_upgrade_all(
+        &installed,
+        &outdated,
+        |package| {
+            let manager = package.package.manager;
+            (include_pinned || !package.pinned)
+                && is_upgrade_all_manager(manager)
+                && state.runtime.is_manager_enabled(manager)
+                && (manager != ManagerId::SoftwareUpdate || os_updates_allowed)
+        },
+        |package| package.sizes.download_size_bytes,
+    ))
+}
 
-    true
+/// Issue a confirmation token for the upgrade-all plan Helm would run now, as JSON.
+///
+/// Returns `{token, expiresAtUnix, plan}`, where `plan` lists every upgrade
+/// (OS updates included unless safe mode is on), `osUpdateCount`, and
+/// `restartRequired`. Present the token to `helm_upgrade_all_confirmed` to run
+/// exactly that plan.
+///
+/// - `include_pinned`: if false, pinned packages are excluded.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_request_upgrade_all_confirmation(include_pinned: bool) -> *mut c_char {
+    ffi_boundary("helm_request_upgrade_all_confirmation", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let simulation = match simulate_upgrade_all_for_state(state, include_pinned) {
+            Ok(simulation) => simulation,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let confirmation = CONFIRMATION_TOKENS.issue(
+            ConfirmationPlan::upgrade_all(include_pinned, &simulation),
+            SystemTime::now(),
+        );
+
+        let json = match serde_json::to_string(&confirmation) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// Queue an upgrade task for a single package. Returns the task ID, or -1 on error.
+/// Run the upgrade-all plan a token from `helm_request_upgrade_all_confirmation`
+/// vouches for. OS updates run only if the confirmed plan contained them.
 ///
-/// Currently supported manager IDs:
-/// - "homebrew_formula"
-/// - "mise"
-/// - "npm"
-/// - "pnpm"
-/// - "yarn"
-/// - "cargo"
-/// - "cargo_binstall"
-/// - "pip"
-/// - "pipx"
-/// - "poetry"
-/// - "rubygems"
-/// - "bundler"
-/// - "rustup"
-/// - "softwareupdate" (requires package_name "__confirm_os_updates__")
+/// Tokens are single-use. Fails with `service.error.confirmation_invalid` for
+/// unknown or spent tokens, `service.error.confirmation_expired` once the token
+/// lapses, and `service.error.confirmation_plan_changed` when the plan Helm
+/// would run now differs from the confirmed one; request a new token to retry.
 ///
 /// # Safety
 ///
-/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
-/// strings.
+/// `token` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_upgrade_package(
-    manager_id: *const c_char,
-    package_name: *const c_char,
-    package_target_name: *const c_char,
-    version: *const c_char,
-) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+pub unsafe extern "C" fn helm_upgrade_all_confirmed(token: *const c_char) -> bool {
+    ffi_boundary("helm_upgrade_all_confirmed", || {
+        clear_last_error_key();
+        let token = match unsafe { parse_required_cstr_arg(token) } {
+            Ok(token) => token,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+
+        let plan = {
+            let guard = lock_or_recover(&STATE, "state");
+            let Some(state) = guard.as_ref() else {
+                return return_error_bool(SERVICE_ERROR_INTERNAL);
+            };
+            let redeemed = match CONFIRMATION_TOKENS.redeem(&token, SystemTime::now()) {
+                Ok(redeemed) => redeemed,
+                Err(error) => return return_error_bool(confirmation_error_key(error)),
+            };
+            let include_pinned = redeemed.plan().include_pinned;
+            let current = match simulate_upgrade_all_for_state(state, include_pinned) {
+                Ok(simulation) => ConfirmationPlan::upgrade_all(include_pinned, &simulation),
+                Err(error_key) => return return_error_bool(error_key),
+            };
+            match redeemed.verify(&current) {
+                Ok(plan) => plan,
+                Err(error) => return return_error_bool(confirmation_error_key(error)),
+            }
+        };
+
+        let os_update_labels = plan
+            .upgrades
+            .iter()
+            .filter(|upgrade| upgrade.manager == ManagerId::SoftwareUpdate)
+            .map(|upgrade| upgrade.package_name.clone())
+            .collect();
+        queue_upgrade_all(plan.include_pinned, os_update_labels)
+    })
+}
+
+fn confirmation_error_key(error: ConfirmationError) -> &'static str {
+    match error {
+        ConfirmationError::Unknown => SERVICE_ERROR_CONFIRMATION_INVALID,
+        ConfirmationError::Expired => SERVICE_ERROR_CONFIRMATION_EXPIRED,
+        ConfirmationError::PlanChanged => SERVICE_ERROR_CONFIRMATION_PLAN_CHANGED,
     }
+}
 
-    let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
-    let manager = match manager_cstr
-        .to_str()
-        .ok()
-        .and_then(|s| s.parse::<ManagerId>().ok())
-    {
-        Some(manager) => manager,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
+/// Queue upgrade tasks for supported managers using cached outdated snapshot.
+///
+/// - `include_pinned`: if false, pinned packages are excluded.
+/// - `allow_os_updates`: must be false. OS updates run only through
+///   `helm_upgrade_all_confirmed`, so a caller flag alone can never start them;
+///   passing true fails with `service.error.confirmation_required`.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_upgrade_all(include_pinned: bool, allow_os_updates: bool) -> bool {
+    ffi_boundary("helm_upgrade_all", || {
+        clear_last_error_key();
+        if allow_os_updates {
+            return return_error_bool(SERVICE_ERROR_CONFIRMATION_REQUIRED);
+        }
+        queue_upgrade_all(include_pinned, Vec::new())
+    })
+}
 
-    let package_cstr = unsafe { CStr::from_ptr(package_name) };
-    let package_name = match package_cstr.to_str() {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-    let version = match parse_optional_nonempty_string_arg(version) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
+/// Queue upgrade-all from the cached outdated snapshot. OS updates run only for
+/// `os_update_labels`, the labels of a confirmed plan.
+fn queue_upgrade_all(include_pinned: bool, os_update_labels: Vec<String>) -> bool {
+    if external_coordinator_state_dir().is_some() {
+        return coordinator_start_workflow_external(CoordinatorWorkflowRequest::UpdatesRun {
+            include_pinned,
+            allow_os_updates: !os_update_labels.is_empty(),
+        })
+        .is_ok();
+    }
+    let (store, runtime, tokio_rt) = {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
+        (
+            state.store.clone(),
+            state.runtime.clone(),
+            state.tokio_rt.handle().clone(),
+        )
     };
-    let package_label_target = format_package_task_label_target(&package_name, version.as_deref());
 
-    let (target_manager, request, label_key, label_args): (
-        ManagerId,
-        AdapterRequest,
-        Option<&str>,
-        Vec<(&str, String)>,
-    ) = match manager {
-        ManagerId::HomebrewFormula => {
-            let policy = {
-                let guard = lock_or_recover(&STATE, "state");
-                let state = match guard.as_ref() {
-                    Some(s) => s,
-                    None => return return_error_i64(SERVICE_ERROR_INTERNAL),
-                };
-                effective_homebrew_keg_policy(&state.store, &package_name)
-            };
-            let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
-            let target_name = encode_homebrew_upgrade_target(&package_name, cleanup_old_kegs);
-            (
-                ManagerId::HomebrewFormula,
-                AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::HomebrewFormula,
+    begin_bulk_operation();
+    tokio_rt.spawn(async move {
+        let outdated = match store.list_outdated() {
+            Ok(packages) => packages,
+            Err(error) => {
+                eprintln!("upgrade_all: failed to list outdated packages: {error}");
+                return;
+            }
+        };
+
+        let targets = collect_upgrade_all_targets(&outdated, include_pinned);
+
+        if runtime.is_manager_enabled(ManagerId::Asdf) {
+            for package_name in targets.asdf {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Asdf,
                         name: package_name.clone(),
                     }),
-                    target_name: Some(target_name),
+                    target_name: None,
                     version: None,
-                }),
-                Some(if cleanup_old_kegs {
-                    "service.task.label.upgrade.homebrew_cleanup"
-                } else {
-                    "service.task.label.upgrade.homebrew"
-                }),
-                vec![("package", package_label_target.clone())],
-            )
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Asdf, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Asdf, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue asdf upgrade task: {error}");
+                    }
+                }
+            }
         }
-        ManagerId::HomebrewCask => (
-            ManagerId::HomebrewCask,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::HomebrewCask,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.homebrew_cask"),
-            vec![("package", package_label_target.clone())],
-        ),
-        ManagerId::Mise => (
-            ManagerId::Mise,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Mise,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: version.clone(),
-            }),
-            Some("service.task.label.upgrade.mise"),
-            vec![("package", package_label_target.clone())],
-        ),
-        ManagerId::Npm => (
-            ManagerId::Npm,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Npm,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Npm).to_string()),
-            ],
-        ),
-        ManagerId::Pnpm => (
-            ManagerId::Pnpm,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Pnpm,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Pnpm).to_string()),
-            ],
-        ),
-        ManagerId::Yarn => (
-            ManagerId::Yarn,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Yarn,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Yarn).to_string()),
-            ],
-        ),
-        ManagerId::Cargo => (
-            ManagerId::Cargo,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Cargo,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                (
-                    "manager",
-                    manager_display_name(ManagerId::Cargo).to_string(),
-                ),
-            ],
-        ),
-        ManagerId::CargoBinstall => (
-            ManagerId::CargoBinstall,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::CargoBinstall,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                (
-                    "manager",
-                    manager_display_name(ManagerId::CargoBinstall).to_string(),
-                ),
-            ],
-        ),
-        ManagerId::Pip => (
-            ManagerId::Pip,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Pip,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Pip).to_string()),
-            ],
-        ),
-        ManagerId::Pipx => (
-            ManagerId::Pipx,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Pipx,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Pipx).to_string()),
-            ],
-        ),
-        ManagerId::Poetry => (
-            ManagerId::Poetry,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Poetry,
-                    name: package_name.clone(),
+
+        if runtime.is_manager_enabled(ManagerId::HomebrewFormula) {
+            for package_name in targets.homebrew {
+                let policy = effective_homebrew_keg_policy(&store, &package_name);
+                let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
+                let request = upgrade_all_package_request(
+                    &store,
+                    ManagerId::HomebrewFormula,
+                    &package_name,
+                    cleanup_old_kegs,
+                );
+                let (label_key, label_args) = upgrade_task_label_for(
+                    ManagerId::HomebrewFormula,
+                    &package_name,
+                    cleanup_old_kegs,
+                );
+                match runtime
+                    .submit_labeled(
+                        ManagerId::HomebrewFormula,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue homebrew upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::HomebrewCask) {
+            for package_name in targets.homebrew_cask {
+                let request = upgrade_all_package_request(
+                    &store,
+                    ManagerId::HomebrewCask,
+                    &package_name,
+                    false,
+                );
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::HomebrewCask, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::HomebrewCask,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!(
+                            "upgrade_all: failed to queue homebrew cask upgrade task: {error}"
+                        );
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::MacPorts) {
+            for package_name in targets.macports {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::MacPorts,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::MacPorts, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::MacPorts,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue macports upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Mas) {
+            for package_name in targets.mas {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Mas,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Mas, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Mas, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue mas upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Mise) {
+            for package_name in targets.mise {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Mise,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Mise, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Mise, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue mise upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Npm) {
+            for package_name in targets.npm {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Npm,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Npm, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Npm, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue npm upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Pnpm) {
+            for package_name in targets.pnpm {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Pnpm,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Pnpm, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Pnpm, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue pnpm upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Yarn) {
+            for package_name in targets.yarn {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Yarn,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Yarn, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Yarn, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue yarn upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Cargo) {
+            for package_name in targets.cargo {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Cargo,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Cargo, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::Cargo,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue cargo upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::CargoBinstall) {
+            for package_name in targets.cargo_binstall {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::CargoBinstall,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::CargoBinstall, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::CargoBinstall,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!(
+                            "upgrade_all: failed to queue cargo-binstall upgrade task: {error}"
+                        );
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Pip) {
+            for package_name in targets.pip {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Pip,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Pip, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Pip, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue pip upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Pipx) {
+            for package_name in targets.pipx {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Pipx,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Pipx, &package_name, false);
+                match runtime
+                    .submit_labeled(ManagerId::Pipx, request, task_label(label_key, &label_args))
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue pipx upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Poetry) {
+            for package_name in targets.poetry {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Poetry,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Poetry, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::Poetry,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue poetry upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::RubyGems) {
+            for package_name in targets.rubygems {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::RubyGems,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::RubyGems, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::RubyGems,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue rubygems upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Bundler) {
+            for package_name in targets.bundler {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Bundler,
+                        name: package_name.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Bundler, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::Bundler,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue bundler upgrade task: {error}");
+                    }
+                }
+            }
+        }
+
+        if runtime.is_manager_enabled(ManagerId::Rustup) {
+            for toolchain in targets.rustup {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::Package(PackageRef {
+                        manager: ManagerId::Rustup,
+                        name: toolchain.clone(),
+                    }),
+                    target_name: None,
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                });
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::Rustup, &toolchain, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::Rustup,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!(
+                            "upgrade_all: failed to queue rustup toolchain upgrade task: {error}"
+                        );
+                    }
+                }
+            }
+        }
+
+        if !os_update_labels.is_empty() && runtime.is_manager_enabled(ManagerId::SoftwareUpdate) {
+            if runtime.is_safe_mode() {
+                eprintln!("upgrade_all: safe mode enabled; skipping softwareupdate upgrade");
+            } else {
+                let request = AdapterRequest::Upgrade(UpgradeRequest {
+                    target: UpgradeTarget::OsUpdatesConfirmed,
+                    target_name: None,
+                    version: None,
+                    options: os_update_labels,
+                    cleanup_old_kegs: false,
+                });
+                let package_name = CONFIRMED_OS_UPDATES_NAME.to_string();
+                let (label_key, label_args) =
+                    upgrade_task_label_for(ManagerId::SoftwareUpdate, &package_name, false);
+                match runtime
+                    .submit_labeled(
+                        ManagerId::SoftwareUpdate,
+                        request,
+                        task_label(label_key, &label_args),
+                    )
+                    .await
+                {
+                    Ok(task_id) => {
+                        track_bulk_operation_task(task_id);
+                    }
+                    Err(error) => {
+                        eprintln!("upgrade_all: failed to queue softwareupdate task: {error}");
+                    }
+                }
+            }
+        }
+    });
+
+    true
+}
+
+/// Queue an upgrade task for a single package. Returns the task ID, or -1 on error.
+///
+/// Currently supported manager IDs:
+/// - "homebrew_formula"
+/// - "mise"
+/// - "npm"
+/// - "pnpm"
+/// - "yarn"
+/// - "cargo"
+/// - "cargo_binstall"
+/// - "pip"
+/// - "pipx"
+/// - "poetry"
+/// - "rubygems"
+/// - "bundler"
+/// - "rustup"
+///
+/// "softwareupdate" fails with `service.error.invalid_input`, including the
+/// legacy package_name "__confirm_os_updates__": OS updates are queued only
+/// through `helm_request_upgrade_all_confirmation` and
+/// `helm_upgrade_all_confirmed`.
+///
+/// `idempotency_key` may be null. When set, a retried call carrying the same key
+/// returns the task first queued under it instead of queueing a duplicate.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
+/// strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_upgrade_package(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    package_target_name: *const c_char,
+    version: *const c_char,
+    idempotency_key: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_upgrade_package", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
+        let manager = match manager_cstr
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let package_cstr = unsafe { CStr::from_ptr(package_name) };
+        let package_name = match package_cstr.to_str() {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let version = match parse_optional_nonempty_string_arg(version) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let idempotency_key = match parse_optional_nonempty_string_arg(idempotency_key) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_label_target =
+            format_package_task_label_target(&package_name, version.as_deref());
+        // The C API still carries manager-wide targets as reserved package names.
+        let upgrade_target = UpgradeTarget::from_wire(manager, None, Some(package_name.clone()));
+
+        let (target_manager, request, label_key, label_args): (
+            ManagerId,
+            AdapterRequest,
+            Option<&str>,
+            Vec<(&str, String)>,
+        ) = match manager {
+            ManagerId::HomebrewFormula => {
+                let (policy, options) = {
+                    let guard = lock_or_recover(&STATE, "state");
+                    let state = match guard.as_ref() {
+                        Some(s) => s,
+                        None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+                    };
+                    (
+                        effective_homebrew_keg_policy(&state.store, &package_name),
+                        persisted_upgrade_options(
+                            &state.store,
+                            ManagerId::HomebrewFormula,
+                            &package_name,
+                        ),
+                    )
+                };
+                let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
+                (
+                    ManagerId::HomebrewFormula,
+                    AdapterRequest::Upgrade(UpgradeRequest {
+                        target: upgrade_target.clone(),
+                        target_name: None,
+                        version: None,
+                        options,
+                        cleanup_old_kegs,
+                    }),
+                    Some(if cleanup_old_kegs {
+                        "service.task.label.upgrade.homebrew_cleanup"
+                    } else {
+                        "service.task.label.upgrade.homebrew"
+                    }),
+                    vec![("package", package_label_target.clone())],
+                )
+            }
+            ManagerId::HomebrewCask => {
+                let options = {
+                    let guard = lock_or_recover(&STATE, "state");
+                    let state = match guard.as_ref() {
+                        Some(s) => s,
+                        None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+                    };
+                    persisted_upgrade_options(&state.store, ManagerId::HomebrewCask, &package_name)
+                };
+                (
+                    ManagerId::HomebrewCask,
+                    AdapterRequest::Upgrade(UpgradeRequest {
+                        target: upgrade_target.clone(),
+                        target_name: package_target_name.clone(),
+                        version: None,
+                        options,
+                        cleanup_old_kegs: false,
+                    }),
+                    Some("service.task.label.upgrade.homebrew_cask"),
+                    vec![("package", package_label_target.clone())],
+                )
+            }
+            ManagerId::Asdf => {
+                // `asdf install <plugin> latest`, then repoint the global version
+                // unless a local or environment override pins the plugin.
+                let (label_key, label_args) = if upgrade_target == UpgradeTarget::SelfUpdate {
+                    (
+                        "service.task.label.update.manager_self",
+                        vec![("manager", ManagerId::Asdf.as_str().to_string())],
+                    )
+                } else {
+                    (
+                        "service.task.label.upgrade.asdf",
+                        vec![("plugin", package_label_target.clone())],
+                    )
+                };
+                (
+                    ManagerId::Asdf,
+                    AdapterRequest::Upgrade(UpgradeRequest {
+                        target: upgrade_target.clone(),
+                        target_name: package_target_name.clone(),
+                        version: version.clone(),
+                        options: Vec::new(),
+                        cleanup_old_kegs: false,
+                    }),
+                    Some(label_key),
+                    label_args,
+                )
+            }
+            ManagerId::Mise => (
+                ManagerId::Mise,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: version.clone(),
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.mise"),
+                vec![("package", package_label_target.clone())],
+            ),
+            ManagerId::Npm => (
+                ManagerId::Npm,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Npm).to_string()),
+                ],
+            ),
+            ManagerId::Pnpm => (
+                ManagerId::Pnpm,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
                 }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                (
-                    "manager",
-                    manager_display_name(ManagerId::Poetry).to_string(),
-                ),
-            ],
-        ),
-        ManagerId::RubyGems => (
-            ManagerId::RubyGems,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::RubyGems,
-                    name: package_name.clone(),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Pnpm).to_string()),
+                ],
+            ),
+            ManagerId::Yarn => (
+                ManagerId::Yarn,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
                 }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Yarn).to_string()),
+                ],
+            ),
+            ManagerId::Cargo => (
+                ManagerId::Cargo,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::Cargo).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::CargoBinstall => (
+                ManagerId::CargoBinstall,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::CargoBinstall).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::Pip => (
+                ManagerId::Pip,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Pip).to_string()),
+                ],
+            ),
+            ManagerId::Pipx => (
+                ManagerId::Pipx,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Pipx).to_string()),
+                ],
+            ),
+            ManagerId::Poetry => (
+                ManagerId::Poetry,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::Poetry).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::RubyGems => (
+                ManagerId::RubyGems,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::RubyGems).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::Bundler => (
+                ManagerId::Bundler,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::Bundler).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::MacPorts => (
+                ManagerId::MacPorts,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: version.clone(),
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    (
+                        "manager",
+                        manager_display_name(ManagerId::MacPorts).to_string(),
+                    ),
+                ],
+            ),
+            ManagerId::Mas => (
+                ManagerId::Mas,
+                AdapterRequest::Upgrade(UpgradeRequest {
+                    target: upgrade_target.clone(),
+                    target_name: package_target_name.clone(),
+                    version: None,
+                    options: Vec::new(),
+                    cleanup_old_kegs: false,
+                }),
+                Some("service.task.label.upgrade.package"),
+                vec![
+                    ("package", package_label_target.clone()),
+                    ("manager", manager_display_name(ManagerId::Mas).to_string()),
+                ],
+            ),
+            ManagerId::Rustup => {
+                let label_key = if upgrade_target == UpgradeTarget::SelfUpdate {
+                    "service.task.label.update.rustup_self"
+                } else {
+                    "service.task.label.upgrade.rustup_toolchain"
+                };
                 (
-                    "manager",
-                    manager_display_name(ManagerId::RubyGems).to_string(),
+                    ManagerId::Rustup,
+                    AdapterRequest::Upgrade(UpgradeRequest {
+                        target: upgrade_target.clone(),
+                        target_name: package_target_name.clone(),
+                        version: None,
+                        options: Vec::new(),
+                        cleanup_old_kegs: false,
+                    }),
+                    Some(label_key),
+                    if upgrade_target == UpgradeTarget::SelfUpdate {
+                        Vec::new()
+                    } else {
+                        vec![("toolchain", package_name.clone())]
+                    },
+                )
+            }
+            // OS updates run only through a redeemed confirmation token, so
+            // the legacy reserved name cannot start them from here.
+            ManagerId::SoftwareUpdate => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            _ => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        };
+        let mut label_args = label_args;
+        if label_key.is_some() {
+            label_args.push((
+                "plan_step_id",
+                upgrade_plan_step_id(target_manager, &package_name),
+            ));
+        }
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(target_manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
+
+        let (engine, store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.engine.clone(),
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        let idempotency_claim =
+            match claim_idempotency_key(store.as_ref(), idempotency_key.as_deref()) {
+                Ok(claim) => claim,
+                Err(existing) => return existing.0 as i64,
+            };
+
+        if !runtime.is_manager_enabled(target_manager)
+            || !runtime.supports_capability(target_manager, Capability::Upgrade)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            target_manager,
+            TaskType::Upgrade,
+            label_key,
+            &label_args,
+        ) {
+            set_task_idempotency_key(store.as_ref(), existing, &idempotency_claim);
+            return existing.0 as i64;
+        }
+
+        let submitted = match label_key {
+            Some(label_key) => rt_handle.block_on(engine.submit_labeled(
+                target_manager,
+                request,
+                task_label(label_key, &label_args),
+            )),
+            None => rt_handle.block_on(engine.submit(target_manager, request)),
+        };
+        match submitted {
+            Ok(task_id) => {
+                set_task_idempotency_key(store.as_ref(), task_id, &idempotency_claim);
+                task_id.0 as i64
+            }
+            Err(error) => {
+                eprintln!("upgrade_package: failed to queue task: {error}");
+                return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+            }
+        }
+    })
+}
+
+/// Queue a task that switches a package back to the version it had before its
+/// last upgrade through Helm. Returns the task ID, or -1 on error.
+///
+/// Requires the `Rollback` capability (Homebrew formulae, while the prior keg
+/// is still in the Cellar). Fails with `service.error.invalid_input` when no
+/// prior version is recorded for the package.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
+/// strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_rollback_package(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_rollback_package", || {
+        clear_last_error_key();
+        let manager = match parse_nonempty_string_arg(manager_id)
+            .ok()
+            .and_then(|raw| raw.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let package_name = match parse_nonempty_string_arg(package_name) {
+            Ok(name) => name,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::Rollback)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let version = match store.previous_installed_version(&package) {
+            Ok(Some(version)) => version,
+            Ok(None) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            Err(error) => {
+                eprintln!("rollback_package: failed to read version history: {error}");
+                return return_error_i64(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+        let label_key = "service.task.label.rollback.package";
+        let label_args = vec![
+            ("package", package.name.clone()),
+            ("version", version.clone()),
+            ("manager", manager_display_name(manager).to_string()),
+        ];
+        let request = AdapterRequest::Rollback(RollbackRequest { package, version });
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            TaskType::Upgrade,
+            Some(label_key),
+            &label_args,
+        ) {
+            return existing.0 as i64;
+        }
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error) => {
+                eprintln!("rollback_package: failed to queue task: {error}");
+                return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+            }
+        }
+    })
+}
+
+/// List Homebrew background services as a JSON array of
+/// `{name, status, user, file, exitCode}`, read live from
+/// `brew services list --json`.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_services() -> *mut c_char {
+    ffi_boundary("helm_list_services", || {
+        clear_last_error_key();
+        let Some((runtime, rt_handle)) = active_state_runtime() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let manager = ManagerId::HomebrewFormula;
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::Services)
+        {
+            return return_error_ptr(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+
+        let request = AdapterRequest::ListServices(helm_core::adapters::ListServicesRequest);
+        let services =
+            match rt_handle.block_on(runtime.submit_refresh_request_response(manager, request)) {
+                Ok(helm_core::adapters::AdapterResponse::Services(services)) => services,
+                Ok(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+                Err(error) => {
+                    eprintln!("list_services: failed to list services: {}", error.message);
+                    return return_core_error_ptr(&error);
+                }
+            };
+
+        let json = match serde_json::to_string(&services) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+fn service_task_label_for(
+    service: &str,
+    action: ServiceAction,
+) -> (&'static str, Vec<(&'static str, String)>) {
+    let label_key = match action {
+        ServiceAction::Start => "service.task.label.service.start",
+        ServiceAction::Stop => "service.task.label.service.stop",
+        ServiceAction::Restart => "service.task.label.service.restart",
+    };
+    (
+        label_key,
+        vec![
+            ("service", service.to_string()),
+            ("action", action.as_str().to_string()),
+        ],
+    )
+}
+
+/// Queue a task that starts, stops or restarts a Homebrew service. `action`
+/// is `start`, `stop` or `restart`. Returns the task ID, or -1 on error.
+///
+/// Stopping or restarting interrupts whatever depends on the service, so safe
+/// mode refuses both with `service.error.safe_mode_blocked`.
+///
+/// # Safety
+///
+/// `service_name` and `action` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_service_state(
+    service_name: *const c_char,
+    action: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_set_service_state", || {
+        clear_last_error_key();
+        let service = match parse_nonempty_string_arg(service_name) {
+            Ok(name) => name,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let action = match parse_nonempty_string_arg(action)
+            .ok()
+            .and_then(|raw| raw.parse::<ServiceAction>().ok())
+        {
+            Some(action) => action,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        let manager = ManagerId::HomebrewFormula;
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::Services)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+        if action.interrupts_service() && runtime.is_safe_mode() {
+            return return_error_i64(SERVICE_ERROR_SAFE_MODE_BLOCKED);
+        }
+
+        let (label_key, label_args) = service_task_label_for(&service, action);
+        let request = AdapterRequest::SetServiceState(SetServiceStateRequest { service, action });
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            TaskType::Configure,
+            Some(label_key),
+            &label_args,
+        ) {
+            return existing.0 as i64;
+        }
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error) => {
+                eprintln!("set_service_state: failed to queue task: {error}");
+                return_core_error_i64(&error)
+            }
+        }
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiReclaimableSpace {
+    manager_id: String,
+    reclaimable_bytes: u64,
+}
+
+/// Report the disk space each manager's cleanup would free, as a JSON array
+/// of `{managerId, reclaimableBytes}`, for enabled managers with the
+/// `Cleanup` capability (Homebrew formulae, npm, pip, Cargo).
+///
+/// Nothing is removed; managers whose measurement fails are left out.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_get_reclaimable_space() -> *mut c_char {
+    ffi_boundary("helm_get_reclaimable_space", || {
+        clear_last_error_key();
+        let Some((runtime, rt_handle)) = active_state_runtime() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+
+        let mut reclaimable = Vec::new();
+        for manager in ManagerId::ALL {
+            if !runtime.is_manager_enabled(manager)
+                || !runtime.supports_capability(manager, Capability::Cleanup)
+            {
+                continue;
+            }
+            let request =
+                AdapterRequest::PreviewCleanup(helm_core::adapters::PreviewCleanupRequest);
+            match rt_handle.block_on(runtime.submit_refresh_request_response(manager, request)) {
+                Ok(helm_core::adapters::AdapterResponse::Cleanup(report)) => {
+                    reclaimable.push(FfiReclaimableSpace {
+                        manager_id: manager.as_str().to_string(),
+                        reclaimable_bytes: report.bytes,
+                    });
+                }
+                Ok(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+                Err(error) => eprintln!(
+                    "get_reclaimable_space: failed to measure {}: {}",
+                    manager.as_str(),
+                    error.message
                 ),
-            ],
+            }
+        }
+
+        let json = match serde_json::to_string(&reclaimable) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+/// Queue a task that reclaims a manager's cache space (`brew cleanup`,
+/// `npm cache clean`, `pip cache purge`, or removing Cargo's registry
+/// downloads). Returns the task ID, or -1 on error.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_run_cleanup(manager_id: *const c_char) -> i64 {
+    ffi_boundary("helm_run_cleanup", || {
+        clear_last_error_key();
+        let manager = match parse_nonempty_string_arg(manager_id)
+            .ok()
+            .and_then(|raw| raw.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::Cleanup)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+
+        let label_key = "service.task.label.cleanup";
+        let label_args = vec![("manager", manager_display_name(manager).to_string())];
+        let request = AdapterRequest::Cleanup(CleanupRequest);
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            TaskType::Configure,
+            Some(label_key),
+            &label_args,
+        ) {
+            return existing.0 as i64;
+        }
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error) => {
+                eprintln!("run_cleanup: failed to queue task: {error}");
+                return_core_error_i64(&error)
+            }
+        }
+    })
+}
+
+/// Queue a task that measures the disk space each of a manager's installed
+/// packages occupies (Homebrew kegs, npm global modules, pipx venvs, Cargo
+/// binaries). The sizes land in the `installed_size_bytes` field of
+/// `helm_list_installed_packages`. Returns the task ID, or -1 on error.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_measure_package_sizes(manager_id: *const c_char) -> i64 {
+    ffi_boundary("helm_measure_package_sizes", || {
+        clear_last_error_key();
+        let manager = match parse_nonempty_string_arg(manager_id)
+            .ok()
+            .and_then(|raw| raw.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        if !runtime.is_manager_enabled(manager)
+            || !runtime.supports_capability(manager, Capability::PackageSizes)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
+
+        let label_key = "service.task.label.measure_package_sizes";
+        let label_args = vec![("manager", manager_display_name(manager).to_string())];
+        let request = AdapterRequest::MeasurePackageSizes(MeasurePackageSizesRequest);
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            TaskType::Refresh,
+            Some(label_key),
+            &label_args,
+        ) {
+            return existing.0 as i64;
+        }
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error) => {
+                eprintln!("measure_package_sizes: failed to queue task: {error}");
+                return_core_error_i64(&error)
+            }
+        }
+    })
+}
+
+fn parse_nonempty_string_arg(ptr: *const c_char) -> Result<String, &'static str> {
+    if ptr.is_null() {
+        return Err(SERVICE_ERROR_INVALID_INPUT);
+    }
+    let value = unsafe { CStr::from_ptr(ptr) };
+    match value.to_str() {
+        Ok(raw) => {
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                Err(SERVICE_ERROR_INVALID_INPUT)
+            } else {
+                Ok(trimmed.to_string())
+            }
+        }
+        Err(_) => Err(SERVICE_ERROR_INVALID_INPUT),
+    }
+}
+
+fn parse_optional_nonempty_string_arg(ptr: *const c_char) -> Result<Option<String>, &'static str> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let value = unsafe { CStr::from_ptr(ptr) };
+    match value.to_str() {
+        Ok(raw) => {
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(trimmed.to_string()))
+            }
+        }
+        Err(_) => Err(SERVICE_ERROR_INVALID_INPUT),
+    }
+}
+
+fn parse_absolute_path_arg(ptr: *const c_char) -> Result<PathBuf, &'static str> {
+    let path = PathBuf::from(parse_nonempty_string_arg(ptr)?);
+    if !path.is_absolute() {
+        return Err(SERVICE_ERROR_INVALID_INPUT);
+    }
+    Ok(path)
+}
+
+fn format_package_task_label_target(package_name: &str, version: Option<&str>) -> String {
+    match version.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(version) => format!("{}@{}", package_name.trim(), version),
+        None => package_name.trim().to_string(),
+    }
+}
+
+fn queue_rustup_config_task(
+    request: AdapterRequest,
+    label_key: &'static str,
+    label_args: Vec<(&'static str, String)>,
+) -> i64 {
+    let manager = ManagerId::Rustup;
+
+    if external_coordinator_state_dir().is_some() {
+        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+            Ok(request) => request,
+            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        };
+        return match coordinator_submit_external(manager, submit_request, false) {
+            Ok(response) => response
+                .task_id
+                .map(|task_id| task_id as i64)
+                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+            Err(_) => return return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+        };
+    }
+
+    let (store, runtime, rt_handle) = {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        };
+        (
+            state.store.clone(),
+            state.runtime.clone(),
+            state.rt_handle.clone(),
+        )
+    };
+
+    if let Some(existing) = find_matching_inflight_task(
+        store.as_ref(),
+        runtime.as_ref(),
+        &rt_handle,
+        manager,
+        TaskType::Configure,
+        Some(label_key),
+        &label_args,
+    ) {
+        return existing.0 as i64;
+    }
+
+    match rt_handle.block_on(runtime.submit_labeled(
+        manager,
+        request,
+        task_label(label_key, &label_args),
+    )) {
+        Ok(task_id) => task_id.0 as i64,
+        Err(error) => {
+            eprintln!("queue_rustup_config_task: failed to queue task: {error}");
+            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+        }
+    }
+}
+
+/// Queue an install task for a single package. Returns the task ID, or -1 on error.
+///
+/// `idempotency_key` may be null. When set, a retried call carrying the same key
+/// returns the task first queued under it instead of queueing a duplicate.
+///
+/// # Safety
+///
+/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
+/// strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_install_package(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    package_target_name: *const c_char,
+    version: *const c_char,
+    idempotency_key: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_install_package", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
+        let manager = match manager_cstr
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let package_cstr = unsafe { CStr::from_ptr(package_name) };
+        let package_name = match package_cstr.to_str() {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let version = match parse_optional_nonempty_string_arg(version) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let idempotency_key = match parse_optional_nonempty_string_arg(idempotency_key) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+
+        match queue_package_install(
+            manager,
+            package_name,
+            package_target_name,
+            version,
+            Vec::new(),
+            idempotency_key,
+        ) {
+            Ok(task_id) => task_id,
+            Err(error_key) => return_error_i64(error_key),
+        }
+    })
+}
+
+/// Queue an install task for a single package with manager-specific install flags.
+/// Returns the task ID, or -1 on error.
+///
+/// `options_json` is a JSON array of flags, validated against the manager allowlist
+/// (for example `["--HEAD"]` for Homebrew formulae or `["--no-quarantine"]` for casks).
+/// Flags used by a successful install are persisted and carried forward to later
+/// upgrades where Homebrew supports them.
+///
+/// `idempotency_key` may be null. When set, a retried call carrying the same key
+/// returns the task first queued under it instead of queueing a duplicate.
+///
+/// # Safety
+///
+/// `manager_id`, `package_name`, and `options_json` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_install_package_with_options(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    package_target_name: *const c_char,
+    version: *const c_char,
+    options_json: *const c_char,
+    idempotency_key: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_install_package_with_options", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_name = match unsafe { parse_required_cstr_arg(package_name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let version = match parse_optional_nonempty_string_arg(version) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let idempotency_key = match parse_optional_nonempty_string_arg(idempotency_key) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let options = match unsafe { parse_install_options_arg(manager, options_json) } {
+            Ok(options) => options,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+
+        match queue_package_install(
+            manager,
+            package_name,
+            package_target_name,
+            version,
+            options,
+            idempotency_key,
+        ) {
+            Ok(task_id) => task_id,
+            Err(error_key) => return_error_i64(error_key),
+        }
+    })
+}
+
+unsafe fn parse_install_options_arg(
+    manager: ManagerId,
+    options_json: *const c_char,
+) -> Result<Vec<String>, &'static str> {
+    let raw = unsafe { parse_required_cstr_arg(options_json) }?;
+    let options: Vec<String> =
+        serde_json::from_str(raw.as_str()).map_err(|_| SERVICE_ERROR_INVALID_INPUT)?;
+    helm_core::install_options::validate_install_options(manager, &options).map_err(|error| {
+        eprintln!("install_package_with_options: {}", error.message);
+        SERVICE_ERROR_INVALID_INPUT
+    })
+}
+
+/// Persist the install flags once the install task succeeds so later upgrades reuse them.
+fn spawn_install_options_recorder(
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+    rt_handle: tokio::runtime::Handle,
+    task_id: TaskId,
+    package: PackageRef,
+    options: Vec<String>,
+) {
+    thread::spawn(move || {
+        let Ok(snapshot) = rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) else {
+            return;
+        };
+        if !matches!(
+            snapshot.terminal_state,
+            Some(AdapterTaskTerminalState::Succeeded(_))
+        ) {
+            return;
+        }
+        if let Err(error) = store.set_package_install_options(&package, &options) {
+            eprintln!(
+                "install_package: failed to persist install options for {}: {}",
+                package.name, error.message
+            );
+        }
+    });
+}
+
+fn queue_package_install(
+    manager: ManagerId,
+    package_name: String,
+    package_target_name: Option<String>,
+    version: Option<String>,
+    options: Vec<String>,
+    idempotency_key: Option<String>,
+) -> Result<i64, &'static str> {
+    let formula_target = if manager == ManagerId::HomebrewFormula {
+        match helm_core::adapters::homebrew::parse_homebrew_formula_install_target(&package_name) {
+            Ok(target) => Some(target),
+            Err(error) => {
+                eprintln!("install_package: {}", error.message);
+                return Err(SERVICE_ERROR_INVALID_INPUT);
+            }
+        }
+    } else {
+        None
+    };
+
+    let package_label_target = format_package_task_label_target(&package_name, version.as_deref());
+
+    let (label_key, label_args) = match manager {
+        ManagerId::HomebrewFormula => (
+            "service.task.label.install.homebrew_formula",
+            vec![("package", package_label_target.clone())],
         ),
-        ManagerId::Bundler => (
-            ManagerId::Bundler,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Bundler,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
-            vec![
-                ("package", package_label_target.clone()),
-                (
-                    "manager",
-                    manager_display_name(ManagerId::Bundler).to_string(),
-                ),
-            ],
+        ManagerId::HomebrewCask => (
+            "service.task.label.install.homebrew_cask",
+            vec![("package", package_label_target.clone())],
         ),
-        ManagerId::Mas => (
-            ManagerId::Mas,
-            AdapterRequest::Upgrade(UpgradeRequest {
-                package: Some(PackageRef {
-                    manager: ManagerId::Mas,
-                    name: package_name.clone(),
-                }),
-                target_name: package_target_name.clone(),
-                version: None,
-            }),
-            Some("service.task.label.upgrade.package"),
+        _ => (
+            "service.task.label.install.package",
             vec![
                 ("package", package_label_target.clone()),
-                ("manager", manager_display_name(ManagerId::Mas).to_string()),
+                ("manager", manager_display_name(manager).to_string()),
             ],
         ),
-        ManagerId::Rustup => {
-            let label_key = if package_name == "__self__" {
-                "service.task.label.update.rustup_self"
-            } else {
-                "service.task.label.upgrade.rustup_toolchain"
-            };
-            (
-                ManagerId::Rustup,
-                AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::Rustup,
-                        name: package_name.clone(),
-                    }),
-                    target_name: package_target_name.clone(),
-                    version: None,
-                }),
-                Some(label_key),
-                if package_name == "__self__" {
-                    Vec::new()
-                } else {
-                    vec![("toolchain", package_name.clone())]
-                },
+    };
+
+    // Options are looked up by the name the package is listed under once
+    // installed, which for tap-qualified or URL formulae is the bare formula.
+    let options_package = PackageRef {
+        manager,
+        name: formula_target
+            .as_ref()
+            .map(|target| target.installed_name().to_string())
+            .unwrap_or_else(|| package_name.clone()),
+    };
+    let tap = match &formula_target {
+        Some(helm_core::adapters::homebrew::HomebrewFormulaInstallTarget::TapQualified {
+            tap,
+            ..
+        }) => Some(tap.clone()),
+        _ => None,
+    };
+    let request = AdapterRequest::Install(InstallRequest {
+        package: PackageRef {
+            manager,
+            name: package_name,
+        },
+        target_name: package_target_name,
+        version,
+        options: options.clone(),
+    });
+
+    if external_coordinator_state_dir().is_some() {
+        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+            Ok(request) => request,
+            Err(_) => return Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        };
+        return match coordinator_submit_external(manager, submit_request, false) {
+            Ok(response) => response
+                .task_id
+                .map(|task_id| task_id as i64)
+                .ok_or(SERVICE_ERROR_PROCESS_FAILURE),
+            Err(_) => Err(SERVICE_ERROR_PROCESS_FAILURE),
+        };
+    }
+
+    let (engine, store, runtime, rt_handle) = {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return Err(SERVICE_ERROR_INTERNAL),
+        };
+        (
+            state.engine.clone(),
+            state.store.clone(),
+            state.runtime.clone(),
+            state.rt_handle.clone(),
+        )
+    };
+
+    let idempotency_claim = match claim_idempotency_key(store.as_ref(), idempotency_key.as_deref())
+    {
+        Ok(claim) => claim,
+        Err(existing) => return Ok(existing.0 as i64),
+    };
+
+    if !supports_individual_package_install(runtime.as_ref(), manager) {
+        return Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+    }
+
+    if let Some(existing) = find_matching_inflight_task(
+        store.as_ref(),
+        runtime.as_ref(),
+        &rt_handle,
+        manager,
+        TaskType::Install,
+        Some(label_key),
+        &label_args,
+    ) {
+        set_task_idempotency_key(store.as_ref(), existing, &idempotency_claim);
+        return Ok(existing.0 as i64);
+    }
+
+    // A plain reinstall also records (clears) previously persisted flags.
+    let record_options = !options.is_empty()
+        || store
+            .package_install_options(&options_package)
+            .is_ok_and(|existing| !existing.is_empty());
+
+    // Adding the tap runs as its own task so it shows up (and fails) on its
+    // own; the install waits for it.
+    let depends_on = match tap {
+        Some(tap) => {
+            let (tap_request, tap_label) = homebrew_tap_task(tap, None);
+            match rt_handle.block_on(engine.submit_labeled(manager, tap_request, tap_label)) {
+                Ok(tap_task_id) => {
+                    vec![tap_task_id]
+                }
+                Err(error) => {
+                    eprintln!("install_package: failed to queue tap task: {error}");
+                    return Err(SERVICE_ERROR_PROCESS_FAILURE);
+                }
+            }
+        }
+        None => Vec::new(),
+    };
+
+    match rt_handle.block_on(engine.submit_labeled_after(
+        manager,
+        request,
+        task_label(label_key, &label_args),
+        depends_on,
+    )) {
+        Ok(task_id) => {
+            set_task_idempotency_key(store.as_ref(), task_id, &idempotency_claim);
+            if record_options {
+                spawn_install_options_recorder(
+                    store,
+                    runtime,
+                    rt_handle,
+                    task_id,
+                    options_package,
+                    options,
+                );
+            }
+            Ok(task_id.0 as i64)
+        }
+        Err(error) => {
+            eprintln!("install_package: failed to queue task: {error}");
+            Err(SERVICE_ERROR_PROCESS_FAILURE)
+        }
+    }
+}
+
+/// The Homebrew tap task: adds `tap` (from `url` when given) and does nothing
+/// if it is already tapped.
+fn homebrew_tap_task(tap: String, url: Option<String>) -> (AdapterRequest, TaskLabel) {
+    let label = task_label("service.task.label.tap.homebrew", &[("tap", tap.clone())]);
+    let request = AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+        manager: ManagerId::HomebrewFormula,
+        package: None,
+        operation: PackageDetailOperation::AddTap { tap, url },
+    });
+    (request, label)
+}
+
+fn queue_homebrew_tap(tap: String, url: Option<String>) -> Result<i64, &'static str> {
+    let (request, label) = homebrew_tap_task(tap, url);
+
+    if external_coordinator_state_dir().is_some() {
+        let submit_request = match adapter_request_to_coordinator_submit(request) {
+            Ok(request) => request,
+            Err(_) => return Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        };
+        return match coordinator_submit_external(ManagerId::HomebrewFormula, submit_request, false)
+        {
+            Ok(response) => response
+                .task_id
+                .map(|task_id| task_id as i64)
+                .ok_or(SERVICE_ERROR_PROCESS_FAILURE),
+            Err(_) => Err(SERVICE_ERROR_PROCESS_FAILURE),
+        };
+    }
+
+    let Some((engine, rt_handle)) = active_state_engine() else {
+        return Err(SERVICE_ERROR_INTERNAL);
+    };
+    match rt_handle.block_on(engine.submit_labeled(ManagerId::HomebrewFormula, request, label)) {
+        Ok(task_id) => Ok(task_id.0 as i64),
+        Err(error) => {
+            eprintln!("queue_homebrew_tap: failed to queue task: {error}");
+            Err(SERVICE_ERROR_PROCESS_FAILURE)
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiManifestQueuedTask {
+    manager_id: String,
+    package_name: String,
+    task_id: Option<i64>,
+    error_key: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiManifestImportResult {
+    dry_run: bool,
+    #[serde(flatten)]
+    diff: helm_core::manifest::ManifestDiff,
+    queued_tasks: Vec<FfiManifestQueuedTask>,
+}
+
+fn read_manifest_source(path: &Path) -> Result<String, &'static str> {
+    let metadata = std::fs::metadata(path).map_err(|_| SERVICE_ERROR_INVALID_INPUT)?;
+    if !metadata.is_file() || metadata.len() > helm_core::manifest::MANIFEST_SOURCE_MAX_BYTES as u64
+    {
+        return Err(SERVICE_ERROR_INVALID_INPUT);
+    }
+    std::fs::read_to_string(path).map_err(|_| SERVICE_ERROR_INVALID_INPUT)
+}
+
+fn manifest_available_managers(store: &SqliteStore) -> std::collections::HashSet<ManagerId> {
+    let enabled_by_manager = manager_enabled_map(store);
+    detected_installed_map(store)
+        .into_iter()
+        .filter(|(manager, installed)| {
+            *installed && manager_is_enabled(&enabled_by_manager, *manager)
+        })
+        .map(|(manager, _)| manager)
+        .collect()
+}
+
+fn queue_manifest_installs(diff: &helm_core::manifest::ManifestDiff) -> Vec<FfiManifestQueuedTask> {
+    diff.operations
+        .iter()
+        .filter(|operation| {
+            operation.status == helm_core::manifest::ManifestOperationStatus::Install
+        })
+        .filter_map(|operation| {
+            queue_manifest_operation(
+                operation.kind,
+                &operation.manager_id,
+                &operation.package_name,
+                operation.target_name.clone(),
+                operation.version.clone(),
+                operation.tap_url.clone(),
             )
+        })
+        .collect()
+}
+
+/// Queue one manifest entry: taps go through the Homebrew tap task, packages
+/// through a regular install.
+fn queue_manifest_operation(
+    kind: helm_core::manifest::ManifestOperationKind,
+    manager_id: &str,
+    package_name: &str,
+    target_name: Option<String>,
+    version: Option<String>,
+    tap_url: Option<String>,
+) -> Option<FfiManifestQueuedTask> {
+    let manager = manager_id.parse::<ManagerId>().ok()?;
+    let outcome = match kind {
+        helm_core::manifest::ManifestOperationKind::HomebrewTap => {
+            queue_homebrew_tap(package_name.to_string(), tap_url)
+        }
+        helm_core::manifest::ManifestOperationKind::Package => queue_package_install(
+            manager,
+            package_name.to_string(),
+            target_name,
+            version,
+            Vec::new(),
+            None,
+        ),
+    };
+    Some(FfiManifestQueuedTask {
+        manager_id: manager_id.to_string(),
+        package_name: package_name.to_string(),
+        task_id: outcome.ok(),
+        error_key: outcome.err().map(str::to_string),
+    })
+}
+
+/// Import a Homebrew Bundle `Brewfile` and return the diff against current state as JSON.
+///
+/// `brew`, `cask`, and `mas` entries missing from the installed snapshot are
+/// reported with status `install`, as are `tap` entries while Homebrew is
+/// available; when `dry_run` is false those are queued (taps as Homebrew tap
+/// tasks, keeping any custom remote URL) and their task IDs returned in
+/// `queuedTasks`.
+///
+/// # Safety
+///
+/// `path` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string
+/// holding an absolute file path.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_import_brewfile(path: *const c_char, dry_run: bool) -> *mut c_char {
+    ffi_boundary("helm_import_brewfile", || {
+        clear_last_error_key();
+        let path = match parse_absolute_path_arg(path) {
+            Ok(path) => path,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let contents = match read_manifest_source(path.as_path()) {
+            Ok(contents) => contents,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
+        };
+        let installed = match store.list_installed() {
+            Ok(packages) => packages,
+            Err(error) => {
+                eprintln!("helm_import_brewfile: failed to list installed packages: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+
+        let parsed = helm_core::manifest::parse_brewfile(contents.as_str());
+        let mut diff = helm_core::manifest::diff_manifest_against_installed(
+            &parsed.manifest,
+            &installed,
+            &manifest_available_managers(store.as_ref()),
+        );
+        diff.warnings = parsed.warnings;
+
+        let queued_tasks = if dry_run {
+            Vec::new()
+        } else {
+            queue_manifest_installs(&diff)
+        };
+        let result = FfiManifestImportResult {
+            dry_run,
+            diff,
+            queued_tasks,
+        };
+
+        let json = match serde_json::to_string(&result) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiManifestApplyResult {
+    dry_run: bool,
+    #[serde(flatten)]
+    plan: helm_core::manifest_plan::ManifestPlan,
+    queued_tasks: Vec<FfiManifestQueuedTask>,
+}
+
+/// Plan applying a Helm manifest (the `helm_json` export) and return the plan as JSON.
+///
+/// Each entry is classified `add`, `skip`, or `conflict` (installed at a
+/// different version than the manifest pins). When `dry_run` is false the
+/// `add` entries are queued as install tasks and returned in `queuedTasks`;
+/// safe mode and managers without individual installs turn adds into skips.
+///
+/// # Safety
+///
+/// `manifest_json` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_import_manifest(
+    manifest_json: *const c_char,
+    dry_run: bool,
+) -> *mut c_char {
+    ffi_boundary("helm_import_manifest", || {
+        clear_last_error_key();
+        let manifest_json = match unsafe { parse_required_cstr_arg(manifest_json) } {
+            Ok(manifest_json) => manifest_json,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        if manifest_json.len() > helm_core::manifest::MANIFEST_SOURCE_MAX_BYTES {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
         }
-        ManagerId::SoftwareUpdate => {
-            if package_name != "__confirm_os_updates__" {
-                return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        let manifest =
+            match serde_json::from_str::<helm_core::manifest::HelmManifest>(manifest_json.as_str())
+            {
+                Ok(manifest) => manifest,
+                Err(_) => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
+            };
+
+        let (Some(store), Some((runtime, _))) = (active_state_store(), active_state_runtime())
+        else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let installed = match store.list_installed() {
+            Ok(packages) => packages,
+            Err(error) => {
+                eprintln!("helm_import_manifest: failed to list installed packages: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
             }
-            (
-                ManagerId::SoftwareUpdate,
-                AdapterRequest::Upgrade(UpgradeRequest {
-                    package: Some(PackageRef {
-                        manager: ManagerId::SoftwareUpdate,
-                        name: "__confirm_os_updates__".to_string(),
-                    }),
-                    target_name: None,
-                    version: None,
-                }),
-                Some("service.task.label.upgrade.softwareupdate_all"),
-                Vec::new(),
-            )
+        };
+        let install_capable_managers: std::collections::HashSet<ManagerId> = ManagerId::ALL
+            .into_iter()
+            .filter(|manager| supports_individual_package_install(runtime.as_ref(), *manager))
+            .collect();
+
+        let plan = helm_core::manifest_plan::plan_manifest_import(
+            &manifest,
+            &helm_core::manifest_plan::ManifestPlanContext {
+                installed: &installed,
+                available_managers: &manifest_available_managers(store.as_ref()),
+                install_capable_managers: &install_capable_managers,
+                safe_mode: runtime.is_safe_mode(),
+            },
+        );
+
+        let queued_tasks = if dry_run {
+            Vec::new()
+        } else {
+            plan.adds()
+                .filter_map(|entry| {
+                    queue_manifest_operation(
+                        entry.kind,
+                        &entry.manager_id,
+                        &entry.package_name,
+                        entry.target_name.clone(),
+                        entry.version.clone(),
+                        entry.tap_url.clone(),
+                    )
+                })
+                .collect()
+        };
+        let result = FfiManifestApplyResult {
+            dry_run,
+            plan,
+            queued_tasks,
+        };
+
+        let json = match serde_json::to_string(&result) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-        _ => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
-    };
-    let mut label_args = label_args;
-    if label_key.is_some() {
-        label_args.push((
-            "plan_step_id",
-            upgrade_plan_step_id(target_manager, &package_name),
-        ));
-    }
+    })
+}
 
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+/// Export the installed-package snapshot as JSON `{format, fileName, contents, packageCount}`.
+///
+/// `format` is one of `brewfile`, `requirements`, `package_json`,
+/// `cargo_install`, `pipx_install`, or `helm_json`; the host writes `contents`
+/// wherever the user chooses.
+///
+/// # Safety
+///
+/// `format` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_export_manifest(format: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_export_manifest", || {
+        clear_last_error_key();
+        let format = match unsafe { parse_required_cstr_arg(format) } {
+            Ok(format) => format,
+            Err(error_key) => return return_error_ptr(error_key),
         };
-        return match coordinator_submit_external(target_manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+        let format = match format.parse::<helm_core::export::ExportFormat>() {
+            Ok(format) => format,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let Some(store) = active_state_store() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let installed = match store.list_installed() {
+            Ok(packages) => packages,
+            Err(error) => {
+                eprintln!("helm_export_manifest: failed to list installed packages: {error}");
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
         };
-    }
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        let exported = helm_core::export::export_installed(format, &installed);
+        let json = match serde_json::to_string(&exported) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
+}
 
-    if !runtime.is_manager_enabled(target_manager)
-        || !runtime.supports_capability(target_manager, Capability::Upgrade)
-    {
-        return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-    }
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiPythonExportFile {
+    format: helm_core::export::ExportFormat,
+    path: String,
+    package_count: usize,
+}
 
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        target_manager,
-        TaskType::Upgrade,
-        label_key,
-        &label_args,
-    ) {
-        return existing.0 as i64;
-    }
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiPythonRequirementsExport {
+    interpreter_path: String,
+    files: Vec<FfiPythonExportFile>,
+}
 
-    match rt_handle.block_on(runtime.submit(target_manager, request)) {
-        Ok(task_id) => {
-            if let Some(label_key) = label_key {
-                set_task_label(task_id, label_key, &label_args);
+/// Export one Python interpreter's packages so the environment can be rebuilt elsewhere.
+///
+/// Runs `<interpreter_path> -m pip list` and writes a `requirements.txt` pinned to the
+/// installed versions into the `dest` directory, plus `pipx-install.sh` for the pipx
+/// applications in the installed snapshot when there are any. Existing files are
+/// replaced. Returns JSON `{interpreterPath, files: [{format, path, packageCount}]}`.
+///
+/// # Safety
+///
+/// `interpreter_path` and `dest` must be valid, non-null pointers to NUL-terminated
+/// UTF-8 C strings holding absolute paths to an interpreter executable and an
+/// existing directory.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_export_python_requirements(
+    interpreter_path: *const c_char,
+    dest: *const c_char,
+) -> *mut c_char {
+    ffi_boundary("helm_export_python_requirements", || {
+        clear_last_error_key();
+        let interpreter = match parse_absolute_path_arg(interpreter_path) {
+            Ok(path) if path.is_file() && is_discovered_python_interpreter(&path) => path,
+            Ok(_) => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let dest = match parse_absolute_path_arg(dest) {
+            Ok(path) if path.is_dir() => path,
+            Ok(_) => return return_error_ptr(SERVICE_ERROR_INVALID_INPUT),
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let (Some(store), Ok(rt_handle)) = (active_state_store(), current_runtime_handle()) else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+
+        let source = helm_core::adapters::pip_process::ProcessPipSource::new(Arc::new(
+            helm_core::execution::tokio_process::TokioProcessExecutor,
+        ))
+        .with_interpreter(interpreter.clone());
+        let adapter = helm_core::adapters::pip::PipAdapter::new(source);
+        let listed = rt_handle.block_on(tokio::task::spawn_blocking(move || {
+            helm_core::adapters::ManagerAdapter::execute(
+                &adapter,
+                AdapterRequest::ListInstalled(helm_core::adapters::ListInstalledRequest),
+            )
+        }));
+        let python_packages = match listed {
+            Ok(Ok(helm_core::adapters::AdapterResponse::InstalledPackages(packages))) => packages,
+            Ok(Ok(_)) | Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            Ok(Err(error)) => {
+                eprintln!(
+                    "helm_export_python_requirements: failed to list packages for '{}': {error}",
+                    interpreter.display()
+                );
+                return return_core_error_ptr(&error);
             }
-            task_id.0 as i64
+        };
+        let pipx_packages = match store.list_installed() {
+            Ok(packages) => packages
+                .into_iter()
+                .filter(|package| package.package.manager == ManagerId::Pipx)
+                .collect::<Vec<_>>(),
+            Err(error) => {
+                eprintln!(
+                    "helm_export_python_requirements: failed to list installed packages: {error}"
+                );
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+
+        let exports = [
+            Some(helm_core::export::export_installed(
+                helm_core::export::ExportFormat::Requirements,
+                &python_packages,
+            )),
+            Some(helm_core::export::export_installed(
+                helm_core::export::ExportFormat::PipxInstall,
+                &pipx_packages,
+            ))
+            .filter(|exported| exported.package_count > 0),
+        ];
+        let mut files = Vec::new();
+        for exported in exports.into_iter().flatten() {
+            let path = dest.join(&exported.file_name);
+            if let Err(error) = std::fs::write(&path, exported.contents.as_bytes()) {
+                eprintln!(
+                    "helm_export_python_requirements: failed to write '{}': {error}",
+                    path.display()
+                );
+                return return_error_ptr(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+            files.push(FfiPythonExportFile {
+                format: exported.format,
+                path: path.to_string_lossy().into_owned(),
+                package_count: exported.package_count,
+            });
         }
-        Err(error) => {
-            eprintln!("upgrade_package: failed to queue task: {error}");
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+
+        let result = FfiPythonRequirementsExport {
+            interpreter_path: interpreter.to_string_lossy().into_owned(),
+            files,
+        };
+        let json = match serde_json::to_string(&result) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
 }
 
-fn parse_nonempty_string_arg(ptr: *const c_char) -> Result<String, &'static str> {
-    if ptr.is_null() {
-        return Err(SERVICE_ERROR_INVALID_INPUT);
-    }
-    let value = unsafe { CStr::from_ptr(ptr) };
-    match value.to_str() {
-        Ok(raw) => {
-            let trimmed = raw.trim();
-            if trimmed.is_empty() {
-                Err(SERVICE_ERROR_INVALID_INPUT)
-            } else {
-                Ok(trimmed.to_string())
-            }
-        }
-        Err(_) => Err(SERVICE_ERROR_INVALID_INPUT),
+fn load_npm_migration_preview(
+    store: &SqliteStore,
+    target: ManagerId,
+    selection: Option<&[String]>,
+) -> Result<helm_core::npm_migration::NpmMigrationPreview, &'static str> {
+    let installed = store
+        .list_installed()
+        .map_err(|_| SERVICE_ERROR_STORAGE_FAILURE)?;
+    let (npm_installed, target_installed): (Vec<_>, Vec<_>) = installed
+        .into_iter()
+        .filter(|package| {
+            package.package.manager == ManagerId::Npm || package.package.manager == target
+        })
+        .partition(|package| package.package.manager == ManagerId::Npm);
+    Ok(helm_core::npm_migration::build_npm_migration_preview(
+        target,
+        &npm_installed,
+        &target_installed,
+        selection,
+    ))
+}
+
+unsafe fn parse_npm_migration_target_arg(
+    target_manager_id: *const c_char,
+) -> Result<ManagerId, &'static str> {
+    let target = unsafe { parse_manager_id_arg(target_manager_id) }?;
+    if !helm_core::npm_migration::is_npm_migration_target(target) {
+        return Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
     }
+    Ok(target)
 }
 
-fn parse_optional_nonempty_string_arg(ptr: *const c_char) -> Result<Option<String>, &'static str> {
-    if ptr.is_null() {
+fn parse_npm_migration_selection(
+    package_names_json: *const c_char,
+) -> Result<Option<Vec<String>>, &'static str> {
+    let Some(raw) = parse_optional_nonempty_string_arg(package_names_json)? else {
         return Ok(None);
-    }
-    let value = unsafe { CStr::from_ptr(ptr) };
-    match value.to_str() {
-        Ok(raw) => {
-            let trimmed = raw.trim();
-            if trimmed.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(trimmed.to_string()))
+    };
+    let names: Vec<String> =
+        serde_json::from_str(raw.as_str()).map_err(|_| SERVICE_ERROR_INVALID_INPUT)?;
+    Ok(Some(
+        names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+    ))
+}
+
+fn npm_migration_install_request(target: ManagerId, package_name: &str) -> AdapterRequest {
+    AdapterRequest::Install(InstallRequest {
+        package: PackageRef {
+            manager: target,
+            name: package_name.to_string(),
+        },
+        target_name: None,
+        version: None,
+        options: Vec::new(),
+    })
+}
+
+fn npm_migration_uninstall_request(manager: ManagerId, package_name: &str) -> AdapterRequest {
+    AdapterRequest::Uninstall(UninstallRequest {
+        package: PackageRef {
+            manager,
+            name: package_name.to_string(),
+        },
+        target_name: None,
+        version: None,
+        disposal: Default::default(),
+    })
+}
+
+fn spawn_npm_migration_task(
+    store: Arc<SqliteStore>,
+    runtime: Arc<AdapterRuntime>,
+    rt_handle: tokio::runtime::Handle,
+    target: ManagerId,
+    packages: Vec<String>,
+) -> Result<TaskId, &'static str> {
+    let task_type = TaskType::Install;
+    let task_id = create_local_task(
+        store.as_ref(),
+        target,
+        task_type,
+        task_label(
+            "service.task.label.migrate.npm_globals",
+            &[
+                ("manager", manager_display_name(target).to_string()),
+                ("count", packages.len().to_string()),
+            ],
+        ),
+    )?;
+
+    let cancellation = register_local_task_cancellation(task_id);
+    thread::spawn(move || {
+        run_npm_migration_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            task_id,
+            target,
+            &packages,
+            cancellation.as_ref(),
+        );
+        unregister_local_task_cancellation(task_id);
+    });
+
+    Ok(task_id)
+}
+
+/// Run the migration steps for `task_id`, checking `cancellation` before each
+/// one. A cancelled migration stops where it is and never removes npm copies
+/// that were not already removed.
+fn run_npm_migration_task(
+    store: &SqliteStore,
+    runtime: &AdapterRuntime,
+    rt_handle: &tokio::runtime::Handle,
+    task_id: TaskId,
+    target: ManagerId,
+    packages: &[String],
+    cancellation: &LocalTaskCancellation,
+) {
+    let task_type = TaskType::Install;
+    let log = |status: TaskStatus, level: TaskLogLevel, message: String| {
+        append_local_task_log(store, task_id, target, task_type, status, level, message);
+    };
+    let settle = |status: TaskStatus, level: TaskLogLevel, message: String| {
+        update_local_task_status(store, task_id, target, task_type, status, level, message);
+    };
+    update_local_task_status(
+        store,
+        task_id,
+        target,
+        task_type,
+        TaskStatus::Running,
+        TaskLogLevel::Info,
+        "task started",
+    );
+
+    let mut installed_in_target: Vec<&str> = Vec::new();
+    for package_name in packages {
+        if cancellation.is_requested() {
+            settle(
+                TaskStatus::Cancelled,
+                TaskLogLevel::Warn,
+                format!(
+                    "npm migration cancelled; {} package(s) already installed via {} were kept and npm packages were left unchanged",
+                    installed_in_target.len(),
+                    target.as_str()
+                ),
+            );
+            return;
+        }
+        log(
+            TaskStatus::Running,
+            TaskLogLevel::Info,
+            format!("installing '{package_name}' via {}", target.as_str()),
+        );
+        match cancellation.run_step(
+            runtime,
+            rt_handle,
+            target,
+            npm_migration_install_request(target, package_name),
+        ) {
+            Ok(_) => installed_in_target.push(package_name.as_str()),
+            Err(error) if cancellation.is_requested() => {
+                settle(
+                    TaskStatus::Cancelled,
+                    TaskLogLevel::Warn,
+                    format!(
+                        "npm migration cancelled while installing '{package_name}': {error}; npm packages were left unchanged"
+                    ),
+                );
+                return;
+            }
+            Err(error) => {
+                log(
+                    TaskStatus::Running,
+                    TaskLogLevel::Error,
+                    format!("install of '{package_name}' failed: {error}"),
+                );
+                for rollback_name in installed_in_target.iter().rev() {
+                    let outcome = submit_request_wait(
+                        runtime,
+                        rt_handle,
+                        target,
+                        npm_migration_uninstall_request(target, rollback_name),
+                    );
+                    let (level, message) = match outcome {
+                        Ok(_) => (
+                            TaskLogLevel::Info,
+                            format!("rolled back '{rollback_name}' from {}", target.as_str()),
+                        ),
+                        Err(error) => (
+                            TaskLogLevel::Warn,
+                            format!("rollback of '{rollback_name}' failed: {error}"),
+                        ),
+                    };
+                    log(TaskStatus::Running, level, message);
+                }
+                settle(
+                    TaskStatus::Failed,
+                    TaskLogLevel::Error,
+                    "npm migration rolled back; npm packages were left unchanged".to_string(),
+                );
+                return;
             }
         }
-        Err(_) => Err(SERVICE_ERROR_INVALID_INPUT),
     }
-}
 
-fn parse_absolute_path_arg(ptr: *const c_char) -> Result<PathBuf, &'static str> {
-    let path = PathBuf::from(parse_nonempty_string_arg(ptr)?);
-    if !path.is_absolute() {
-        return Err(SERVICE_ERROR_INVALID_INPUT);
+    let mut npm_removed = 0usize;
+    let mut npm_removal_failures = 0usize;
+    for package_name in packages {
+        if cancellation.is_requested() {
+            settle(
+                TaskStatus::Cancelled,
+                TaskLogLevel::Warn,
+                format!(
+                    "npm migration cancelled after installing {} package(s) via {}; {npm_removed} npm copy(ies) removed",
+                    packages.len(),
+                    target.as_str()
+                ),
+            );
+            return;
+        }
+        match cancellation.run_step(
+            runtime,
+            rt_handle,
+            ManagerId::Npm,
+            npm_migration_uninstall_request(ManagerId::Npm, package_name),
+        ) {
+            Ok(_) => npm_removed += 1,
+            Err(error) => {
+                npm_removal_failures += 1;
+                log(
+                    TaskStatus::Running,
+                    TaskLogLevel::Warn,
+                    format!("removing npm copy of '{package_name}' failed: {error}"),
+                );
+            }
+        }
     }
-    Ok(path)
-}
 
-fn format_package_task_label_target(package_name: &str, version: Option<&str>) -> String {
-    match version.map(str::trim).filter(|value| !value.is_empty()) {
-        Some(version) => format!("{}@{}", package_name.trim(), version),
-        None => package_name.trim().to_string(),
-    }
+    let (level, message) = if npm_removal_failures == 0 {
+        (
+            TaskLogLevel::Info,
+            format!(
+                "migrated {} package(s) from npm to {}",
+                packages.len(),
+                target.as_str()
+            ),
+        )
+    } else {
+        (
+            TaskLogLevel::Warn,
+            format!(
+                "migrated {} package(s) to {}; {npm_removal_failures} npm copy removal(s) failed",
+                packages.len(),
+                target.as_str()
+            ),
+        )
+    };
+    settle(TaskStatus::Completed, level, message);
 }
 
-fn queue_rustup_config_task(
-    request: AdapterRequest,
-    label_key: &'static str,
-    label_args: Vec<(&'static str, String)>,
-) -> i64 {
-    let manager = ManagerId::Rustup;
-
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+/// Preview migrating global npm packages to pnpm or yarn as JSON.
+///
+/// `package_names_json` is an optional JSON array restricting the migration to
+/// the named packages; `null` considers every eligible npm global.
+///
+/// # Safety
+///
+/// `target_manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `package_names_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_preview_npm_migration(
+    target_manager_id: *const c_char,
+    package_names_json: *const c_char,
+) -> *mut c_char {
+    ffi_boundary("helm_preview_npm_migration", || {
+        clear_last_error_key();
+        let target = match unsafe { parse_npm_migration_target_arg(target_manager_id) } {
+            Ok(target) => target,
+            Err(error_key) => return return_error_ptr(error_key),
         };
-        return match coordinator_submit_external(manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+        let selection = match parse_npm_migration_selection(package_names_json) {
+            Ok(selection) => selection,
+            Err(error_key) => return return_error_ptr(error_key),
         };
-    }
-
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+            }
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
-
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        manager,
-        TaskType::Configure,
-        Some(label_key),
-        &label_args,
-    ) {
-        return existing.0 as i64;
-    }
 
-    match rt_handle.block_on(runtime.submit(manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, &label_args);
-            task_id.0 as i64
-        }
-        Err(error) => {
-            eprintln!("queue_rustup_config_task: failed to queue task: {error}");
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+        let preview = match load_npm_migration_preview(store.as_ref(), target, selection.as_deref())
+        {
+            Ok(preview) => preview,
+            Err(error_key) => return return_error_ptr(error_key),
+        };
+        let json = match serde_json::to_string(&preview) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
 }
 
-/// Queue an install task for a single package. Returns the task ID, or -1 on error.
+/// Migrate global npm packages to pnpm or yarn as a single composite task.
+///
+/// Every selected package is installed under the target first; if any install
+/// fails, packages installed so far are removed again and npm is left
+/// untouched. npm copies are uninstalled only after all installs succeed.
+/// Cancelling the task stops it before its next step.
+/// Returns the composite task ID, or -1 on error.
 ///
 /// # Safety
 ///
-/// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
-/// strings.
+/// `target_manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `package_names_json` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_install_package(
-    manager_id: *const c_char,
-    package_name: *const c_char,
-    package_target_name: *const c_char,
-    version: *const c_char,
+pub unsafe extern "C" fn helm_migrate_npm_globals(
+    target_manager_id: *const c_char,
+    package_names_json: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
+    ffi_boundary("helm_migrate_npm_globals", || {
+        clear_last_error_key();
+        let target = match unsafe { parse_npm_migration_target_arg(target_manager_id) } {
+            Ok(target) => target,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let selection = match parse_npm_migration_selection(package_names_json) {
+            Ok(selection) => selection,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(state) => state,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
 
-    let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
-    let manager = match manager_cstr
-        .to_str()
-        .ok()
-        .and_then(|s| s.parse::<ManagerId>().ok())
-    {
-        Some(manager) => manager,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
+        if !supports_individual_package_install(runtime.as_ref(), target)
+            || !supports_individual_package_uninstall(runtime.as_ref(), ManagerId::Npm)
+        {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
 
-    let package_cstr = unsafe { CStr::from_ptr(package_name) };
-    let package_name = match package_cstr.to_str() {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-    let version = match parse_optional_nonempty_string_arg(version) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let package_label_target = format_package_task_label_target(&package_name, version.as_deref());
+        let preview = match load_npm_migration_preview(store.as_ref(), target, selection.as_deref())
+        {
+            Ok(preview) => preview,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let packages = preview.packages_to_migrate();
+        if packages.is_empty() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let (label_key, label_args) = match manager {
+        match spawn_npm_migration_task(store, runtime, rt_handle, target, packages) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(error_key) => return_error_i64(error_key),
+        }
+    })
+}
+
+fn uninstall_task_label_for(
+    manager: ManagerId,
+    package_label_target: &str,
+) -> (&'static str, Vec<(&'static str, String)>) {
+    match manager {
         ManagerId::HomebrewFormula => (
-            "service.task.label.install.homebrew_formula",
-            vec![("package", package_label_target.clone())],
+            "service.task.label.uninstall.homebrew_formula",
+            vec![("package", package_label_target.to_string())],
         ),
         ManagerId::HomebrewCask => (
-            "service.task.label.install.homebrew_cask",
-            vec![("package", package_label_target.clone())],
+            "service.task.label.uninstall.homebrew_cask",
+            vec![("package", package_label_target.to_string())],
         ),
         _ => (
-            "service.task.label.install.package",
+            "service.task.label.uninstall.package",
             vec![
-                ("package", package_label_target.clone()),
+                ("package", package_label_target.to_string()),
                 ("manager", manager_display_name(manager).to_string()),
             ],
         ),
-    };
-
-    let request = AdapterRequest::Install(InstallRequest {
-        package: PackageRef {
-            manager,
-            name: package_name,
-        },
-        target_name: package_target_name,
-        version,
-    });
-
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
-        };
-        return match coordinator_submit_external(manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
-        };
-    }
-
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
-        };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
-
-    if !supports_individual_package_install(runtime.as_ref(), manager) {
-        return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-    }
-
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        manager,
-        TaskType::Install,
-        Some(label_key),
-        &label_args,
-    ) {
-        return existing.0 as i64;
-    }
-
-    match rt_handle.block_on(runtime.submit(manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, &label_args);
-            task_id.0 as i64
-        }
-        Err(error) => {
-            eprintln!("install_package: failed to queue task: {error}");
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
-        }
     }
 }
 
 /// Queue an uninstall task for a single package. Returns the task ID, or -1 on error.
 ///
+/// With `move_to_trash`, app bundles go to the Trash instead of being deleted; managers
+/// that cannot honor this fail with the unsupported-capability error.
+///
+/// `idempotency_key` may be null. When set, a retried call carrying the same key
+/// returns the task first queued under it instead of queueing a duplicate.
+///
 /// # Safety
 ///
 /// `manager_id` and `package_name` must be valid, non-null pointers to NUL-terminated UTF-8 C
@@ -8199,117 +14076,132 @@ pub unsafe extern "C" fn helm_uninstall_package(
     package_name: *const c_char,
     package_target_name: *const c_char,
     version: *const c_char,
+    move_to_trash: bool,
+    idempotency_key: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
-    let manager = match manager_cstr
-        .to_str()
-        .ok()
-        .and_then(|s| s.parse::<ManagerId>().ok())
-    {
-        Some(manager) => manager,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-
-    let package_cstr = unsafe { CStr::from_ptr(package_name) };
-    let package_name = match package_cstr.to_str() {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-    let version = match parse_optional_nonempty_string_arg(version) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let package_label_target = format_package_task_label_target(&package_name, version.as_deref());
-
-    let (label_key, label_args) = match manager {
-        ManagerId::HomebrewFormula => (
-            "service.task.label.uninstall.homebrew_formula",
-            vec![("package", package_label_target.clone())],
-        ),
-        ManagerId::HomebrewCask => (
-            "service.task.label.uninstall.homebrew_cask",
-            vec![("package", package_label_target.clone())],
-        ),
-        _ => (
-            "service.task.label.uninstall.package",
-            vec![
-                ("package", package_label_target.clone()),
-                ("manager", manager_display_name(manager).to_string()),
-            ],
-        ),
-    };
+    ffi_boundary("helm_uninstall_package", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let request = AdapterRequest::Uninstall(UninstallRequest {
-        package: PackageRef {
-            manager,
-            name: package_name,
-        },
-        target_name: package_target_name,
-        version,
-    });
+        let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
+        let manager = match manager_cstr
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        let package_cstr = unsafe { CStr::from_ptr(package_name) };
+        let package_name = match package_cstr.to_str() {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
         };
-        return match coordinator_submit_external(manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+        let version = match parse_optional_nonempty_string_arg(version) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
         };
-    }
+        let package_target_name = match parse_optional_nonempty_string_arg(package_target_name) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let idempotency_key = match parse_optional_nonempty_string_arg(idempotency_key) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let package_label_target =
+            format_package_task_label_target(&package_name, version.as_deref());
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        let (label_key, label_args) = uninstall_task_label_for(manager, &package_label_target);
+
+        let disposal = if move_to_trash {
+            UninstallDisposal::Trash
+        } else {
+            UninstallDisposal::Delete
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
+        if !disposal.supported_by(manager) {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
 
-    if !supports_individual_package_uninstall(runtime.as_ref(), manager) {
-        return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-    }
+        let request = AdapterRequest::Uninstall(UninstallRequest {
+            package: PackageRef {
+                manager,
+                name: package_name,
+            },
+            target_name: package_target_name,
+            version,
+            disposal,
+        });
 
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        manager,
-        TaskType::Uninstall,
-        Some(label_key),
-        &label_args,
-    ) {
-        return existing.0 as i64;
-    }
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
+        }
 
-    match rt_handle.block_on(runtime.submit(manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, &label_args);
-            task_id.0 as i64
+        let (engine, store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.engine.clone(),
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        let idempotency_claim =
+            match claim_idempotency_key(store.as_ref(), idempotency_key.as_deref()) {
+                Ok(claim) => claim,
+                Err(existing) => return existing.0 as i64,
+            };
+
+        if !supports_individual_package_uninstall(runtime.as_ref(), manager) {
+            return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
         }
-        Err(error) => {
-            eprintln!("uninstall_package: failed to queue task: {error}");
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            manager,
+            TaskType::Uninstall,
+            Some(label_key),
+            &label_args,
+        ) {
+            set_task_idempotency_key(store.as_ref(), existing, &idempotency_claim);
+            return existing.0 as i64;
         }
-    }
+
+        match rt_handle.block_on(engine.submit_labeled(
+            manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => {
+                set_task_idempotency_key(store.as_ref(), task_id, &idempotency_claim);
+                task_id.0 as i64
+            }
+            Err(error) => {
+                eprintln!("uninstall_package: failed to queue task: {error}");
+                return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+            }
+        }
+    })
 }
 
 /// Queue a rustup component-add task. Returns the task ID, or -1 on error.
@@ -8323,30 +14215,32 @@ pub unsafe extern "C" fn helm_rustup_add_component(
     toolchain: *const c_char,
     component: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let component = match parse_nonempty_string_arg(component) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_add_component", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let component = match parse_nonempty_string_arg(component) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::AddChild {
+                    kind: PackageDetailChildKind::Component,
+                    value: component.clone(),
+                },
             }),
-            operation: PackageDetailOperation::AddChild {
-                kind: PackageDetailChildKind::Component,
-                value: component.clone(),
-            },
-        }),
-        "service.task.label.configure.rustup_component_add",
-        vec![("toolchain", toolchain), ("component", component)],
-    )
+            "service.task.label.configure.rustup_component_add",
+            vec![("toolchain", toolchain), ("component", component)],
+        )
+    })
 }
 
 /// Queue a rustup component-remove task. Returns the task ID, or -1 on error.
@@ -8360,30 +14254,32 @@ pub unsafe extern "C" fn helm_rustup_remove_component(
     toolchain: *const c_char,
     component: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let component = match parse_nonempty_string_arg(component) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_remove_component", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let component = match parse_nonempty_string_arg(component) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::RemoveChild {
+                    kind: PackageDetailChildKind::Component,
+                    value: component.clone(),
+                },
             }),
-            operation: PackageDetailOperation::RemoveChild {
-                kind: PackageDetailChildKind::Component,
-                value: component.clone(),
-            },
-        }),
-        "service.task.label.configure.rustup_component_remove",
-        vec![("toolchain", toolchain), ("component", component)],
-    )
+            "service.task.label.configure.rustup_component_remove",
+            vec![("toolchain", toolchain), ("component", component)],
+        )
+    })
 }
 
 /// Queue a rustup target-add task. Returns the task ID, or -1 on error.
@@ -8396,30 +14292,32 @@ pub unsafe extern "C" fn helm_rustup_add_target(
     toolchain: *const c_char,
     target: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let target = match parse_nonempty_string_arg(target) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_add_target", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let target = match parse_nonempty_string_arg(target) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::AddChild {
+                    kind: PackageDetailChildKind::Target,
+                    value: target.clone(),
+                },
             }),
-            operation: PackageDetailOperation::AddChild {
-                kind: PackageDetailChildKind::Target,
-                value: target.clone(),
-            },
-        }),
-        "service.task.label.configure.rustup_target_add",
-        vec![("toolchain", toolchain), ("target", target)],
-    )
+            "service.task.label.configure.rustup_target_add",
+            vec![("toolchain", toolchain), ("target", target)],
+        )
+    })
 }
 
 /// Queue a rustup target-remove task. Returns the task ID, or -1 on error.
@@ -8432,30 +14330,32 @@ pub unsafe extern "C" fn helm_rustup_remove_target(
     toolchain: *const c_char,
     target: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let target = match parse_nonempty_string_arg(target) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_remove_target", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let target = match parse_nonempty_string_arg(target) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::RemoveChild {
+                    kind: PackageDetailChildKind::Target,
+                    value: target.clone(),
+                },
             }),
-            operation: PackageDetailOperation::RemoveChild {
-                kind: PackageDetailChildKind::Target,
-                value: target.clone(),
-            },
-        }),
-        "service.task.label.configure.rustup_target_remove",
-        vec![("toolchain", toolchain), ("target", target)],
-    )
+            "service.task.label.configure.rustup_target_remove",
+            vec![("toolchain", toolchain), ("target", target)],
+        )
+    })
 }
 
 /// Queue a rustup set-default-toolchain task. Returns the task ID, or -1 on error.
@@ -8465,23 +14365,25 @@ pub unsafe extern "C" fn helm_rustup_remove_target(
 /// `toolchain` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_rustup_set_default_toolchain(toolchain: *const c_char) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_set_default_toolchain", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::SetDefault,
             }),
-            operation: PackageDetailOperation::SetDefault,
-        }),
-        "service.task.label.configure.rustup_default_toolchain",
-        vec![("toolchain", toolchain)],
-    )
+            "service.task.label.configure.rustup_default_toolchain",
+            vec![("toolchain", toolchain)],
+        )
+    })
 }
 
 /// Queue a rustup directory-override task. Returns the task ID, or -1 on error.
@@ -8494,30 +14396,32 @@ pub unsafe extern "C" fn helm_rustup_set_override(
     toolchain: *const c_char,
     path: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let path = match parse_absolute_path_arg(path) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+    ffi_boundary("helm_rustup_set_override", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let path = match parse_absolute_path_arg(path) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::SetPathOverride { path: path.clone() },
             }),
-            operation: PackageDetailOperation::SetPathOverride { path: path.clone() },
-        }),
-        "service.task.label.configure.rustup_override_set",
-        vec![
-            ("toolchain", toolchain),
-            ("path", path.to_string_lossy().to_string()),
-        ],
-    )
+            "service.task.label.configure.rustup_override_set",
+            vec![
+                ("toolchain", toolchain),
+                ("path", path.to_string_lossy().to_string()),
+            ],
+        )
+    })
 }
 
 /// Queue a rustup directory-override clear task. Returns the task ID, or -1 on error.
@@ -8529,31 +14433,33 @@ pub unsafe extern "C" fn helm_rustup_set_override(
 pub unsafe extern "C" fn helm_rustup_unset_override(
     toolchain: *const c_char,
     path: *const c_char,
-) -> i64 {
-    clear_last_error_key();
-    let toolchain = match parse_nonempty_string_arg(toolchain) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let path = match parse_absolute_path_arg(path) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: Some(PackageRef {
+) -> i64 {
+    ffi_boundary("helm_rustup_unset_override", || {
+        clear_last_error_key();
+        let toolchain = match parse_nonempty_string_arg(toolchain) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let path = match parse_absolute_path_arg(path) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
                 manager: ManagerId::Rustup,
-                name: toolchain.clone(),
+                package: Some(PackageRef {
+                    manager: ManagerId::Rustup,
+                    name: toolchain.clone(),
+                }),
+                operation: PackageDetailOperation::ClearPathOverride { path: path.clone() },
             }),
-            operation: PackageDetailOperation::ClearPathOverride { path: path.clone() },
-        }),
-        "service.task.label.configure.rustup_override_unset",
-        vec![
-            ("toolchain", toolchain),
-            ("path", path.to_string_lossy().to_string()),
-        ],
-    )
+            "service.task.label.configure.rustup_override_unset",
+            vec![
+                ("toolchain", toolchain),
+                ("path", path.to_string_lossy().to_string()),
+            ],
+        )
+    })
 }
 
 /// Queue a rustup profile-change task. Returns the task ID, or -1 on error.
@@ -8563,22 +14469,24 @@ pub unsafe extern "C" fn helm_rustup_unset_override(
 /// `profile` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_rustup_set_profile(profile: *const c_char) -> i64 {
-    clear_last_error_key();
-    let profile = match parse_nonempty_string_arg(profile) {
-        Ok(value) => value,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    queue_rustup_config_task(
-        AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
-            manager: ManagerId::Rustup,
-            package: None,
-            operation: PackageDetailOperation::SetProfile {
-                profile: profile.clone(),
-            },
-        }),
-        "service.task.label.configure.rustup_profile_set",
-        vec![("profile", profile)],
-    )
+    ffi_boundary("helm_rustup_set_profile", || {
+        clear_last_error_key();
+        let profile = match parse_nonempty_string_arg(profile) {
+            Ok(value) => value,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        queue_rustup_config_task(
+            AdapterRequest::ConfigurePackageDetail(PackageDetailRequest {
+                manager: ManagerId::Rustup,
+                package: None,
+                operation: PackageDetailOperation::SetProfile {
+                    profile: profile.clone(),
+                },
+            }),
+            "service.task.label.configure.rustup_profile_set",
+            vec![("profile", profile)],
+        )
+    })
 }
 
 /// Preview package uninstall blast radius as JSON.
@@ -8593,160 +14501,169 @@ pub unsafe extern "C" fn helm_preview_package_uninstall(
     package_name: *const c_char,
     version: *const c_char,
 ) -> *mut c_char {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        set_last_error_key(SERVICE_ERROR_INVALID_INPUT);
-        return std::ptr::null_mut();
-    }
-
-    let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
-    let manager = match manager_cstr
-        .to_str()
-        .ok()
-        .and_then(|s| s.parse::<ManagerId>().ok())
-    {
-        Some(manager) => manager,
-        None => {
-            set_last_error_key(SERVICE_ERROR_INVALID_INPUT);
-            return std::ptr::null_mut();
-        }
-    };
-
-    let package_cstr = unsafe { CStr::from_ptr(package_name) };
-    let package_name = match package_cstr.to_str() {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => {
+    ffi_boundary("helm_preview_package_uninstall", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
             set_last_error_key(SERVICE_ERROR_INVALID_INPUT);
             return std::ptr::null_mut();
         }
-    };
-    let requested_version = match parse_optional_nonempty_string_arg(version) {
-        Ok(value) => value,
-        Err(error_key) => {
-            set_last_error_key(error_key);
-            return std::ptr::null_mut();
-        }
-    };
 
-    let (store, runtime) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
+        let manager_cstr = unsafe { CStr::from_ptr(manager_id) };
+        let manager = match manager_cstr
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<ManagerId>().ok())
+        {
+            Some(manager) => manager,
             None => {
-                set_last_error_key(SERVICE_ERROR_INTERNAL);
+                set_last_error_key(SERVICE_ERROR_INVALID_INPUT);
                 return std::ptr::null_mut();
             }
         };
-        (state.store.clone(), state.runtime.clone())
-    };
 
-    if !supports_individual_package_uninstall(runtime.as_ref(), manager) {
-        set_last_error_key(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
-        return std::ptr::null_mut();
-    }
+        let package_cstr = unsafe { CStr::from_ptr(package_name) };
+        let package_name = match package_cstr.to_str() {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => {
+                set_last_error_key(SERVICE_ERROR_INVALID_INPUT);
+                return std::ptr::null_mut();
+            }
+        };
+        let requested_version = match parse_optional_nonempty_string_arg(version) {
+            Ok(value) => value,
+            Err(error_key) => {
+                set_last_error_key(error_key);
+                return std::ptr::null_mut();
+            }
+        };
 
-    let active_instance = match active_manager_install_instance(store.as_ref(), manager) {
-        Ok(instance) => instance,
-        Err(error_key) => {
-            set_last_error_key(error_key);
-            return std::ptr::null_mut();
-        }
-    };
-    let package = PackageRef {
-        manager,
-        name: package_name,
-    };
-    let runtime_state = match package_runtime_state_from_snapshot(
-        store.as_ref(),
-        &package,
-        requested_version.as_deref(),
-    ) {
-        Ok(state) => state,
-        Err(error_key) => {
-            set_last_error_key(error_key);
-            return std::ptr::null_mut();
-        }
-    };
-    let rustup_override_paths = rustup_override_paths_for_preview(&package, runtime_state.as_ref());
-    let preview = build_package_uninstall_preview(
-        PackageUninstallPreviewContext {
-            package: &package,
-            active_instance: active_instance.as_ref(),
-            package_runtime_state: runtime_state.as_ref(),
-            rustup_override_paths: rustup_override_paths.as_slice(),
-        },
-        DEFAULT_MANAGER_UNINSTALL_SAFE_BLAST_RADIUS_THRESHOLD,
-    );
+        let (store, runtime) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => {
+                    set_last_error_key(SERVICE_ERROR_INTERNAL);
+                    return std::ptr::null_mut();
+                }
+            };
+            (state.store.clone(), state.runtime.clone())
+        };
 
-    let json = match serde_json::to_string(&preview) {
-        Ok(json) => json,
-        Err(error) => {
-            eprintln!("preview_package_uninstall: failed to encode JSON: {error}");
-            set_last_error_key(SERVICE_ERROR_INTERNAL);
+        if !supports_individual_package_uninstall(runtime.as_ref(), manager) {
+            set_last_error_key(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
             return std::ptr::null_mut();
         }
-    };
 
-    match CString::new(json) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => {
-            set_last_error_key(SERVICE_ERROR_INTERNAL);
-            std::ptr::null_mut()
+        let active_instance = match active_manager_install_instance(store.as_ref(), manager) {
+            Ok(instance) => instance,
+            Err(error_key) => {
+                set_last_error_key(error_key);
+                return std::ptr::null_mut();
+            }
+        };
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let runtime_state = match package_runtime_state_from_snapshot(
+            store.as_ref(),
+            &package,
+            requested_version.as_deref(),
+        ) {
+            Ok(state) => state,
+            Err(error_key) => {
+                set_last_error_key(error_key);
+                return std::ptr::null_mut();
+            }
+        };
+        let rustup_override_paths =
+            rustup_override_paths_for_preview(&package, runtime_state.as_ref());
+        let preview = build_package_uninstall_preview(
+            PackageUninstallPreviewContext {
+                package: &package,
+                active_instance: active_instance.as_ref(),
+                package_runtime_state: runtime_state.as_ref(),
+                rustup_override_paths: rustup_override_paths.as_slice(),
+            },
+            DEFAULT_MANAGER_UNINSTALL_SAFE_BLAST_RADIUS_THRESHOLD,
+        );
+
+        let json = match serde_json::to_string(&preview) {
+            Ok(json) => json,
+            Err(error) => {
+                eprintln!("preview_package_uninstall: failed to encode JSON: {error}");
+                set_last_error_key(SERVICE_ERROR_INTERNAL);
+                return std::ptr::null_mut();
+            }
+        };
+
+        match CString::new(json) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => {
+                set_last_error_key(SERVICE_ERROR_INTERNAL);
+                std::ptr::null_mut()
+            }
         }
-    }
+    })
 }
 
 /// List pin records as JSON.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_list_pins() -> *mut c_char {
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return std::ptr::null_mut(),
-    };
-
-    #[derive(serde::Serialize)]
-    struct FfiPinRecord {
-        manager_id: String,
-        package_name: String,
-        pin_kind: String,
-        pinned_version: Option<String>,
-        created_at_unix: i64,
-    }
+    ffi_boundary("helm_list_pins", || {
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
 
-    let pins = match state.store.list_pins() {
-        Ok(records) => records
-            .into_iter()
-            .map(|record| FfiPinRecord {
-                manager_id: record.package.manager.as_str().to_string(),
-                package_name: record.package.name,
-                pin_kind: match record.kind {
-                    PinKind::Native => "native".to_string(),
-                    PinKind::Virtual => "virtual".to_string(),
-                },
-                pinned_version: record.pinned_version,
-                created_at_unix: record
-                    .created_at
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_secs() as i64)
-                    .unwrap_or(0),
-            })
-            .collect::<Vec<_>>(),
-        Err(e) => {
-            eprintln!("Failed to list pins: {}", e);
-            return std::ptr::null_mut();
+        #[derive(serde::Serialize)]
+        struct FfiPinRecord {
+            manager_id: String,
+            package_name: String,
+            package_key: String,
+            pin_kind: String,
+            pinned_version: Option<String>,
+            version_constraint: Option<String>,
+            created_at_unix: i64,
         }
-    };
 
-    let json = match serde_json::to_string(&pins) {
-        Ok(j) => j,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let pins = match state.store.list_pins() {
+            Ok(records) => records
+                .into_iter()
+                .map(|record| FfiPinRecord {
+                    manager_id: record.package.manager.as_str().to_string(),
+                    package_key: record.package.key().to_string(),
+                    package_name: record.package.name,
+                    pin_kind: match record.kind {
+                        PinKind::Native => "native".to_string(),
+                        PinKind::Virtual => "virtual".to_string(),
+                    },
+                    pinned_version: record.pinned_version,
+                    version_constraint: record.version_constraint,
+                    created_at_unix: record
+                        .created_at
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0),
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                eprintln!("Failed to list pins: {}", e);
+                return std::ptr::null_mut();
+            }
+        };
 
-    match CString::new(json) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        let json = match serde_json::to_string(&pins) {
+            Ok(j) => j,
+            Err(_) => return std::ptr::null_mut(),
+        };
+
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
 /// Persist a virtual pin for a package. Returns true on success.
@@ -8761,120 +14678,204 @@ pub unsafe extern "C" fn helm_pin_package(
     package_name: *const c_char,
     pinned_version: *const c_char,
 ) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let manager = {
-        let c_str = unsafe { CStr::from_ptr(manager_id) };
-        match c_str
-            .to_str()
-            .ok()
-            .and_then(|s| s.parse::<ManagerId>().ok())
-        {
-            Some(id) => id,
-            None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+    ffi_boundary("helm_pin_package", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
         }
-    };
 
-    let package_name = {
-        let c_str = unsafe { CStr::from_ptr(package_name) };
-        match c_str.to_str() {
-            Ok(value) if !value.trim().is_empty() => value.to_string(),
-            _ => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
+        let manager = {
+            let c_str = unsafe { CStr::from_ptr(manager_id) };
+            match c_str
+                .to_str()
+                .ok()
+                .and_then(|s| s.parse::<ManagerId>().ok())
+            {
+                Some(id) => id,
+                None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
 
-    let pinned_version = if pinned_version.is_null() {
-        None
-    } else {
-        let c_str = unsafe { CStr::from_ptr(pinned_version) };
-        match c_str.to_str() {
-            Ok(value) => {
-                let trimmed = value.trim();
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed.to_string())
-                }
+        let package_name = {
+            let c_str = unsafe { CStr::from_ptr(package_name) };
+            match c_str.to_str() {
+                Ok(value) if !value.trim().is_empty() => value.to_string(),
+                _ => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
             }
-            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
+        };
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        let pinned_version = if pinned_version.is_null() {
+            None
+        } else {
+            let c_str = unsafe { CStr::from_ptr(pinned_version) };
+            match c_str.to_str() {
+                Ok(value) => {
+                    let trimmed = value.trim();
+                    if trimmed.is_empty() {
+                        None
+                    } else {
+                        Some(trimmed.to_string())
+                    }
+                }
+                Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            }
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    let package = PackageRef {
-        manager,
-        name: package_name,
-    };
-    let pin_kind = if manager == ManagerId::HomebrewFormula {
-        let request = AdapterRequest::Pin(PinRequest {
-            package: package.clone(),
-            version: pinned_version.clone(),
-        });
-        if external_coordinator_state_dir().is_some() {
-            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-                Ok(request) => request,
-                Err(_) => return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
             };
-            if coordinator_submit_external(manager, submit_request, true).is_err() {
-                return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE);
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
+
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let pin_kind = if manager == ManagerId::HomebrewFormula {
+            let request = AdapterRequest::Pin(PinRequest {
+                package: package.clone(),
+                version: pinned_version.clone(),
+            });
+            if external_coordinator_state_dir().is_some() {
+                let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                    Ok(request) => request,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+                };
+                if coordinator_submit_external(manager, submit_request, true).is_err() {
+                    return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE);
+                }
+                PinKind::Native
+            } else {
+                let task_id = match rt_handle.block_on(runtime.submit_labeled(
+                    manager,
+                    request,
+                    task_label(
+                        "service.task.label.pin.homebrew",
+                        &[("package", package.name.clone())],
+                    ),
+                )) {
+                    Ok(task_id) => task_id,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                };
+
+                let snapshot = match rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) {
+                    Ok(snapshot) => snapshot,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                };
+
+                match snapshot.terminal_state {
+                    Some(AdapterTaskTerminalState::Succeeded(_)) => {}
+                    _ => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                }
+                PinKind::Native
             }
-            PinKind::Native
         } else {
-            let task_id = match rt_handle.block_on(runtime.submit(manager, request)) {
-                Ok(task_id) => task_id,
-                Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
-            };
+            PinKind::Virtual
+        };
+        let persisted_pinned_version = if pin_kind == PinKind::Native {
+            None
+        } else if manager == ManagerId::SoftwareUpdate {
+            // Update labels already name one version, and OS updates have no
+            // installed version to hold against.
+            None
+        } else if pinned_version.is_none() && manager == ManagerId::HomebrewCask {
+            // Casks have no native pin; hold the installed version so the pin
+            // lapses once the cask updates itself (for example auto_updates casks).
+            installed_package_version(store.as_ref(), &package)
+        } else {
+            pinned_version
+        };
 
-            set_task_label(
-                task_id,
-                "service.task.label.pin.homebrew",
-                &[("package", package.name.clone())],
-            );
+        let persisted = store
+            .upsert_pin(&PinRecord {
+                package,
+                kind: pin_kind,
+                pinned_version: persisted_pinned_version,
+                version_constraint: None,
+                created_at: std::time::SystemTime::now(),
+            })
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_ok();
+        invalidate_package_snapshot_cache();
+        persisted
+    })
+}
 
-            let snapshot = match rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) {
-                Ok(snapshot) => snapshot,
-                Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
-            };
+/// Persist a virtual pin that lets upgrades move within a version range,
+/// such as `^1.2` or `>=1.0 <2.0`. The package is held back only while its
+/// candidate version falls outside the range. Returns true on success.
+///
+/// Constrained pins are always virtual: a native pin (for example `brew pin`)
+/// still holds the package regardless of the range.
+///
+/// # Safety
+///
+/// `manager_id`, `package_name`, and `constraint` must be valid, non-null pointers to
+/// NUL-terminated UTF-8 C strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_pin_package_with_constraint(
+    manager_id: *const c_char,
+    package_name: *const c_char,
+    constraint: *const c_char,
+) -> bool {
+    ffi_boundary("helm_pin_package_with_constraint", || {
+        clear_last_error_key();
+        let Ok(manager_raw) = parse_nonempty_string_arg(manager_id) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Ok(manager) = manager_raw.parse::<ManagerId>() else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Ok(package_name) = parse_nonempty_string_arg(package_name) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Ok(constraint_raw) = parse_nonempty_string_arg(constraint) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        let Ok(constraint) = VersionConstraint::parse(&constraint_raw) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
 
-            match snapshot.terminal_state {
-                Some(AdapterTaskTerminalState::Succeeded(_)) => {}
-                _ => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+        let store = {
+            let guard = lock_or_recover(&STATE, "state");
+            match guard.as_ref() {
+                Some(state) => state.store.clone(),
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
             }
-            PinKind::Native
-        }
-    } else {
-        PinKind::Virtual
-    };
-    let persisted_pinned_version = if pin_kind == PinKind::Native {
-        None
-    } else {
-        pinned_version
-    };
+        };
+
+        let persisted = store
+            .upsert_pin(&PinRecord {
+                package: PackageRef {
+                    manager,
+                    name: package_name,
+                },
+                kind: PinKind::Virtual,
+                pinned_version: None,
+                version_constraint: Some(constraint.as_str().to_string()),
+                created_at: std::time::SystemTime::now(),
+            })
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_ok();
+        invalidate_package_snapshot_cache();
+        persisted
+    })
+}
 
+fn installed_package_version(store: &SqliteStore, package: &PackageRef) -> Option<String> {
     store
-        .upsert_pin(&PinRecord {
-            package,
-            kind: pin_kind,
-            pinned_version: persisted_pinned_version,
-            created_at: std::time::SystemTime::now(),
-        })
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_ok()
+        .list_installed()
+        .ok()?
+        .into_iter()
+        .find(|installed| installed.package == *package)
+        .and_then(|installed| installed.installed_version)
 }
 
 /// Remove a pin for a package. Returns true on success.
@@ -8889,107 +14890,112 @@ pub unsafe extern "C" fn helm_unpin_package(
     package_name: *const c_char,
     pinned_version: *const c_char,
 ) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() || package_name.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let manager = {
-        let c_str = unsafe { CStr::from_ptr(manager_id) };
-        match c_str
-            .to_str()
-            .ok()
-            .and_then(|s| s.parse::<ManagerId>().ok())
-        {
-            Some(id) => id,
-            None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+    ffi_boundary("helm_unpin_package", || {
+        clear_last_error_key();
+        if manager_id.is_null() || package_name.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
         }
-    };
 
-    let package_name = {
-        let c_str = unsafe { CStr::from_ptr(package_name) };
-        match c_str.to_str() {
-            Ok(value) if !value.trim().is_empty() => value.to_string(),
-            _ => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-    let pinned_version = if pinned_version.is_null() {
-        None
-    } else {
-        let c_str = unsafe { CStr::from_ptr(pinned_version) };
-        match c_str.to_str() {
-            Ok(value) => {
-                let trimmed = value.trim();
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed.to_string())
-                }
+        let manager = {
+            let c_str = unsafe { CStr::from_ptr(manager_id) };
+            match c_str
+                .to_str()
+                .ok()
+                .and_then(|s| s.parse::<ManagerId>().ok())
+            {
+                Some(id) => id,
+                None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
             }
-            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    if manager == ManagerId::HomebrewFormula {
-        let request = AdapterRequest::Unpin(UnpinRequest {
-            package: PackageRef {
-                manager,
-                name: package_name.clone(),
-            },
-        });
-        if external_coordinator_state_dir().is_some() {
-            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-                Ok(request) => request,
-                Err(_) => return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
-            };
-            if coordinator_submit_external(manager, submit_request, true).is_err() {
-                return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE);
+        let package_name = {
+            let c_str = unsafe { CStr::from_ptr(package_name) };
+            match c_str.to_str() {
+                Ok(value) if !value.trim().is_empty() => value.to_string(),
+                _ => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
             }
+        };
+        let pinned_version = if pinned_version.is_null() {
+            None
         } else {
-            let task_id = match rt_handle.block_on(runtime.submit(manager, request)) {
-                Ok(task_id) => task_id,
-                Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+            let c_str = unsafe { CStr::from_ptr(pinned_version) };
+            match c_str.to_str() {
+                Ok(value) => {
+                    let trimmed = value.trim();
+                    if trimmed.is_empty() {
+                        None
+                    } else {
+                        Some(trimmed.to_string())
+                    }
+                }
+                Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
+
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
             };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
 
-            set_task_label(
-                task_id,
-                "service.task.label.unpin.homebrew",
-                &[("package", package_name.clone())],
-            );
+        if manager == ManagerId::HomebrewFormula {
+            let request = AdapterRequest::Unpin(UnpinRequest {
+                package: PackageRef {
+                    manager,
+                    name: package_name.clone(),
+                },
+            });
+            if external_coordinator_state_dir().is_some() {
+                let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                    Ok(request) => request,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+                };
+                if coordinator_submit_external(manager, submit_request, true).is_err() {
+                    return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE);
+                }
+            } else {
+                let task_id = match rt_handle.block_on(runtime.submit_labeled(
+                    manager,
+                    request,
+                    task_label(
+                        "service.task.label.unpin.homebrew",
+                        &[("package", package_name.clone())],
+                    ),
+                )) {
+                    Ok(task_id) => task_id,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                };
 
-            let snapshot = match rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) {
-                Ok(snapshot) => snapshot,
-                Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
-            };
+                let snapshot = match rt_handle.block_on(runtime.wait_for_terminal(task_id, None)) {
+                    Ok(snapshot) => snapshot,
+                    Err(_) => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                };
 
-            match snapshot.terminal_state {
-                Some(AdapterTaskTerminalState::Succeeded(_)) => {}
-                _ => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                match snapshot.terminal_state {
+                    Some(AdapterTaskTerminalState::Succeeded(_)) => {}
+                    _ => return return_error_bool(SERVICE_ERROR_PROCESS_FAILURE),
+                }
             }
         }
-    }
 
-    let package = PackageRef {
-        manager,
-        name: package_name,
-    };
-    store
-        .remove_pin(&package, pinned_version.as_deref())
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_ok()
+        let package = PackageRef {
+            manager,
+            name: package_name,
+        };
+        let removed = store
+            .remove_pin(&package, pinned_version.as_deref())
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_ok();
+        invalidate_package_snapshot_cache();
+        removed
+    })
 }
 
 /// Set a manager as enabled or disabled.
@@ -9002,111 +15008,114 @@ pub unsafe extern "C" fn helm_set_manager_enabled(
     manager_id: *const c_char,
     enabled: bool,
 ) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
+    ffi_boundary("helm_set_manager_enabled", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(m) => m,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(m) => m,
+            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    if enabled {
-        let detection_map: std::collections::HashMap<ManagerId, DetectionInfo> = store
-            .list_detections()
-            .unwrap_or_default()
-            .into_iter()
-            .collect();
-        let pref_map: std::collections::HashMap<ManagerId, ManagerPreference> = store
-            .list_manager_preferences()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|preference| (preference.manager, preference))
-            .collect();
+        if enabled {
+            let detection_map: std::collections::HashMap<ManagerId, DetectionInfo> = store
+                .list_detections()
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            let pref_map: std::collections::HashMap<ManagerId, ManagerPreference> = store
+                .list_manager_preferences()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|preference| (preference.manager, preference))
+                .collect();
 
-        let selected_executable =
-            resolved_manager_selected_executable_path(manager, &detection_map, &pref_map);
-        let eligibility = manager_enablement_eligibility(
-            manager,
-            selected_executable.as_deref().map(std::path::Path::new),
-        );
-        if !eligibility.is_eligible {
-            return return_error_bool(
-                eligibility
-                    .service_error_key
-                    .unwrap_or(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            let selected_executable =
+                resolved_manager_selected_executable_path(manager, &detection_map, &pref_map);
+            let eligibility = manager_enablement_eligibility(
+                manager,
+                selected_executable.as_deref().map(std::path::Path::new),
             );
-        }
+            if !eligibility.is_eligible {
+                return return_error_bool(
+                    eligibility
+                        .service_error_key
+                        .unwrap_or(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+                );
+            }
 
-        let install_instances_by_manager = grouped_install_instances_by_manager(store.as_ref());
-        let homebrew_installed_formulas = homebrew_installed_formula_set(store.as_ref());
-        if manager_has_setup_required_issue(
-            manager,
-            &install_instances_by_manager,
-            &homebrew_installed_formulas,
-        ) {
-            return return_error_bool(SERVICE_ERROR_MANAGER_SETUP_REQUIRED);
-        }
-    } else {
-        let enabled_by_manager = manager_enabled_map(store.as_ref());
-        let dependents =
-            enabled_dependents_for_manager(store.as_ref(), &enabled_by_manager, manager);
-        if !dependents.is_empty() {
-            let dependent_ids = dependents
-                .iter()
-                .map(|id| id.as_str())
-                .collect::<Vec<_>>()
-                .join(", ");
-            eprintln!(
-                "helm-ffi: blocked disabling '{}' because enabled dependents rely on it: {}",
-                manager.as_str(),
-                dependent_ids
-            );
-            return return_error_bool(SERVICE_ERROR_MANAGER_DEPENDENCY_BLOCKED);
+            let install_instances_by_manager = grouped_install_instances_by_manager(store.as_ref());
+            let homebrew_installed_formulas = homebrew_installed_formula_set(store.as_ref());
+            if manager_has_setup_required_issue(
+                manager,
+                &install_instances_by_manager,
+                &homebrew_installed_formulas,
+            ) {
+                return return_error_bool(SERVICE_ERROR_MANAGER_SETUP_REQUIRED);
+            }
+        } else {
+            let enabled_by_manager = manager_enabled_map(store.as_ref());
+            let dependents =
+                enabled_dependents_for_manager(store.as_ref(), &enabled_by_manager, manager);
+            if !dependents.is_empty() {
+                let dependent_ids = dependents
+                    .iter()
+                    .map(|id| id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eprintln!(
+                    "helm-ffi: blocked disabling '{}' because enabled dependents rely on it: {}",
+                    manager.as_str(),
+                    dependent_ids
+                );
+                return return_error_bool(SERVICE_ERROR_MANAGER_DEPENDENCY_BLOCKED);
+            }
         }
-    }
 
-    if store
-        .set_manager_enabled(manager, enabled)
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_err()
-    {
-        return false;
-    }
+        if store
+            .set_manager_enabled(manager, enabled)
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_err()
+        {
+            return false;
+        }
+        invalidate_package_snapshot_cache();
 
-    if !enabled
-        && !purge_tasks_for_manager(
-            store.as_ref(),
-            runtime.as_ref(),
-            &rt_handle,
-            manager,
-            "set_manager_enabled",
-        )
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        if !enabled
+            && !purge_tasks_for_manager(
+                store.as_ref(),
+                runtime.as_ref(),
+                &rt_handle,
+                manager,
+                "set_manager_enabled",
+            )
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
 
-    true
+        true
+    })
 }
 
 /// Set (or clear) the selected executable path for a manager.
@@ -9120,63 +15129,66 @@ pub unsafe extern "C" fn helm_set_manager_selected_executable_path(
     manager_id: *const c_char,
     selected_path: *const c_char,
 ) -> bool {
-    clear_last_error_key();
-    let manager = match unsafe { parse_manager_id_arg(manager_id) } {
-        Ok(manager) => manager,
-        Err(error_key) => return return_error_bool(error_key),
-    };
-
-    let selected_path = if selected_path.is_null() {
-        None
-    } else {
-        let selected_cstr = unsafe { CStr::from_ptr(selected_path) };
-        let selected = match selected_cstr.to_str() {
-            Ok(s) => s.trim(),
-            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+    ffi_boundary("helm_set_manager_selected_executable_path", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
         };
-        if selected.is_empty() {
+
+        let selected_path = if selected_path.is_null() {
             None
         } else {
-            if !std::path::Path::new(selected).is_absolute() {
-                return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-            }
-            if !std::path::Path::new(selected).is_file() {
-                return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+            let selected_cstr = unsafe { CStr::from_ptr(selected_path) };
+            let selected = match selected_cstr.to_str() {
+                Ok(s) => s.trim(),
+                Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            };
+            if selected.is_empty() {
+                None
+            } else {
+                if !std::path::Path::new(selected).is_absolute() {
+                    return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+                }
+                if !std::path::Path::new(selected).is_file() {
+                    return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+                }
+                Some(selected.to_string())
             }
-            Some(selected.to_string())
-        }
-    };
+        };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    if let Err(_error) = state
-        .store
-        .set_manager_selected_executable_path(manager, selected_path.as_deref())
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        if let Err(_error) = state
+            .store
+            .set_manager_selected_executable_path(manager, selected_path.as_deref())
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
 
-    invalidate_executable_discovery_cache(Some(manager));
-    let detection_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_detections()
-        .unwrap_or_default()
-        .into_iter()
-        .collect();
-    let pref_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_manager_preferences()
-        .unwrap_or_default()
-        .into_iter()
-        .map(|pref| (pref.manager, pref))
-        .collect();
-    sync_manager_executable_overrides(&detection_map, &pref_map);
+        invalidate_executable_discovery_cache(Some(manager));
+        invalidate_package_snapshot_cache();
+        let detection_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_detections()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let pref_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_manager_preferences()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pref| (pref.manager, pref))
+            .collect();
+        sync_manager_executable_overrides(&detection_map, &pref_map);
 
-    true
+        true
+    })
 }
 
 /// Set the managed install instance for a manager by stable `instance_id`.
@@ -9193,80 +15205,83 @@ pub unsafe extern "C" fn helm_set_manager_active_install_instance(
     manager_id: *const c_char,
     instance_id: *const c_char,
 ) -> bool {
-    clear_last_error_key();
-    let manager = match unsafe { parse_manager_id_arg(manager_id) } {
-        Ok(manager) => manager,
-        Err(error_key) => return return_error_bool(error_key),
-    };
-    let instance_id = match unsafe { parse_required_cstr_arg(instance_id) } {
-        Ok(value) => value,
-        Err(error_key) => return return_error_bool(error_key),
-    };
+    ffi_boundary("helm_set_manager_active_install_instance", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let instance_id = match unsafe { parse_required_cstr_arg(instance_id) } {
+            Ok(value) => value,
+            Err(error_key) => return return_error_bool(error_key),
+        };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(state) => state,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(state) => state,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    let mut instances = match manager_install_instances_for(state.store.as_ref(), manager) {
-        Ok(instances) => instances,
-        Err(error_key) => return return_error_bool(error_key),
-    };
-    if instances.is_empty() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
+        let mut instances = match manager_install_instances_for(state.store.as_ref(), manager) {
+            Ok(instances) => instances,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        if instances.is_empty() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let selected_path = match instances
-        .iter()
-        .find(|instance| instance.instance_id == instance_id)
-    {
-        Some(instance) => instance.display_path.to_string_lossy().to_string(),
-        None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let selected_path = match instances
+            .iter()
+            .find(|instance| instance.instance_id == instance_id)
+        {
+            Some(instance) => instance.display_path.to_string_lossy().to_string(),
+            None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    for instance in &mut instances {
-        instance.is_active = instance.instance_id == instance_id;
-    }
+        for instance in &mut instances {
+            instance.is_active = instance.instance_id == instance_id;
+        }
 
-    if state
-        .store
-        .replace_install_instances(manager, &instances)
-        .is_err()
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
-    if state
-        .store
-        .set_manager_selected_executable_path(manager, Some(selected_path.as_str()))
-        .is_err()
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
-    if state
-        .store
-        .set_manager_multi_instance_ack_fingerprint(manager, None)
-        .is_err()
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        invalidate_package_snapshot_cache();
+        if state
+            .store
+            .replace_install_instances(manager, &instances)
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+        if state
+            .store
+            .set_manager_selected_executable_path(manager, Some(selected_path.as_str()))
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+        if state
+            .store
+            .set_manager_multi_instance_ack_fingerprint(manager, None)
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
 
-    let detection_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_detections()
-        .unwrap_or_default()
-        .into_iter()
-        .collect();
-    let pref_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_manager_preferences()
-        .unwrap_or_default()
-        .into_iter()
-        .map(|pref| (pref.manager, pref))
-        .collect();
-    sync_manager_executable_overrides(&detection_map, &pref_map);
+        let detection_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_detections()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let pref_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_manager_preferences()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pref| (pref.manager, pref))
+            .collect();
+        sync_manager_executable_overrides(&detection_map, &pref_map);
 
-    true
+        true
+    })
 }
 
 /// Acknowledge current multi-instance install set for a manager.
@@ -9278,35 +15293,37 @@ pub unsafe extern "C" fn helm_set_manager_active_install_instance(
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_ack_manager_multi_instance_state(manager_id: *const c_char) -> bool {
-    clear_last_error_key();
-    let manager = match unsafe { parse_manager_id_arg(manager_id) } {
-        Ok(manager) => manager,
-        Err(error_key) => return return_error_bool(error_key),
-    };
+    ffi_boundary("helm_ack_manager_multi_instance_state", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(state) => state,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(state) => state,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    let instances = match manager_install_instances_for(state.store.as_ref(), manager) {
-        Ok(instances) => instances,
-        Err(error_key) => return return_error_bool(error_key),
-    };
-    let Some(fingerprint) = install_instance_fingerprint(&instances) else {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    };
+        let instances = match manager_install_instances_for(state.store.as_ref(), manager) {
+            Ok(instances) => instances,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let Some(fingerprint) = install_instance_fingerprint(&instances) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
 
-    if state
-        .store
-        .set_manager_multi_instance_ack_fingerprint(manager, Some(fingerprint.as_str()))
-        .is_err()
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        if state
+            .store
+            .set_manager_multi_instance_ack_fingerprint(manager, Some(fingerprint.as_str()))
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
 
-    true
+        true
+    })
 }
 
 /// Clear multi-instance acknowledgement state for a manager.
@@ -9316,27 +15333,29 @@ pub unsafe extern "C" fn helm_ack_manager_multi_instance_state(manager_id: *cons
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_clear_manager_multi_instance_ack(manager_id: *const c_char) -> bool {
-    clear_last_error_key();
-    let manager = match unsafe { parse_manager_id_arg(manager_id) } {
-        Ok(manager) => manager,
-        Err(error_key) => return return_error_bool(error_key),
-    };
+    ffi_boundary("helm_clear_manager_multi_instance_ack", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(state) => state,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(state) => state,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    if state
-        .store
-        .set_manager_multi_instance_ack_fingerprint(manager, None)
-        .is_err()
-    {
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        if state
+            .store
+            .set_manager_multi_instance_ack_fingerprint(manager, None)
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
 
-    true
+        true
+    })
 }
 
 /// Set (or clear) the selected install method for a manager.
@@ -9350,131 +15369,301 @@ pub unsafe extern "C" fn helm_set_manager_install_method(
     manager_id: *const c_char,
     install_method: *const c_char,
 ) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
+    ffi_boundary("helm_set_manager_install_method", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(m) => m,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(m) => m,
+            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let install_method = if install_method.is_null() {
-        None
-    } else {
-        let method_cstr = unsafe { CStr::from_ptr(install_method) };
-        let method = match method_cstr.to_str() {
-            Ok(s) => s.trim(),
+        let install_method = if install_method.is_null() {
+            None
+        } else {
+            let method_cstr = unsafe { CStr::from_ptr(install_method) };
+            let method = match method_cstr.to_str() {
+                Ok(s) => s.trim(),
+                Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            };
+            if method.is_empty() {
+                None
+            } else if manager_supported_install_methods(manager).contains(&method) {
+                Some(method.to_string())
+            } else {
+                return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+            }
+        };
+
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
+
+        state
+            .store
+            .set_manager_selected_install_method(manager, install_method.as_deref())
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_ok()
+    })
+}
+
+/// Set manager timeout profile overrides in seconds.
+///
+/// Positive values set an override; zero/negative values clear the override.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_manager_timeout_profile(
+    manager_id: *const c_char,
+    hard_timeout_seconds: i64,
+    idle_timeout_seconds: i64,
+) -> bool {
+    ffi_boundary("helm_set_manager_timeout_profile", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
+
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(m) => m,
             Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
         };
-        if method.is_empty() {
-            None
-        } else if manager_supported_install_methods(manager).contains(&method) {
-            Some(method.to_string())
-        } else {
-            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+
+        let hard_timeout = if hard_timeout_seconds > 0 {
+            Some(hard_timeout_seconds as u64)
+        } else {
+            None
+        };
+        let idle_timeout = if idle_timeout_seconds > 0 {
+            Some(idle_timeout_seconds as u64)
+        } else {
+            None
+        };
+
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
+
+        if state
+            .store
+            .set_manager_timeout_hard_seconds(manager, hard_timeout)
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_err()
+        {
+            return false;
+        }
+        if state
+            .store
+            .set_manager_timeout_idle_seconds(manager, idle_timeout)
+            .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
+            .is_err()
+        {
+            return false;
+        }
+
+        let detection_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_detections()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let pref_map: std::collections::HashMap<_, _> = state
+            .store
+            .list_manager_preferences()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pref| (pref.manager, pref))
+            .collect();
+        sync_manager_executable_overrides(&detection_map, &pref_map);
+
+        true
+    })
+}
+
+/// Set the spawned-process QoS override for a manager.
+///
+/// `qos` is `default`, `utility`, or `background`; null clears the override so
+/// the manager follows the global `process_qos` setting. An explicit `default`
+/// keeps the manager at normal priority even when the global setting is lower.
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `qos` must be null or a valid pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_set_manager_process_qos(
+    manager_id: *const c_char,
+    qos: *const c_char,
+) -> bool {
+    ffi_boundary("helm_set_manager_process_qos", || {
+        clear_last_error_key();
+        let manager = match unsafe { parse_manager_id_arg(manager_id) } {
+            Ok(manager) => manager,
+            Err(error_key) => return return_error_bool(error_key),
+        };
+        let qos = if qos.is_null() {
+            None
+        } else {
+            match unsafe { parse_required_cstr_arg(qos) }
+                .ok()
+                .and_then(|value| value.parse::<ProcessQos>().ok())
+            {
+                Some(qos) => Some(qos),
+                None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
+
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_bool(SERVICE_ERROR_INTERNAL);
+        };
+        if state
+            .store
+            .set_manager_process_qos(manager, qos.map(ProcessQos::as_str))
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+        helm_core::execution::set_manager_process_qos(manager, qos);
+        true
+    })
+}
+
+fn parse_homebrew_env_manager(manager_id: *const c_char) -> Option<ManagerId> {
+    if manager_id.is_null() {
+        return None;
+    }
+    let c_str = unsafe { CStr::from_ptr(manager_id) };
+    c_str
+        .to_str()
+        .ok()
+        .and_then(|value| value.parse::<ManagerId>().ok())
+        .filter(|manager| HOMEBREW_ENV_MANAGERS.contains(manager))
+}
+
+/// List Homebrew environment settings for a Homebrew manager as JSON.
+///
+/// Each entry carries the setting `key` and the user `override_value`
+/// (`null` when Helm's per-action default applies).
+///
+/// # Safety
+///
+/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn helm_list_manager_env_settings(manager_id: *const c_char) -> *mut c_char {
+    ffi_boundary("helm_list_manager_env_settings", || {
+        clear_last_error_key();
+        let Some(manager) = parse_homebrew_env_manager(manager_id) else {
+            return return_error_ptr(SERVICE_ERROR_INVALID_INPUT);
+        };
+
+        #[derive(serde::Serialize)]
+        struct FfiManagerEnvSetting {
+            key: &'static str,
+            override_value: Option<bool>,
         }
-    };
 
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+        let overrides = homebrew_env_overrides(manager);
+        let settings = HomebrewEnvSetting::ALL
+            .into_iter()
+            .map(|setting| FfiManagerEnvSetting {
+                key: setting.as_str(),
+                override_value: overrides.get(setting),
+            })
+            .collect::<Vec<_>>();
 
-    state
-        .store
-        .set_manager_selected_install_method(manager, install_method.as_deref())
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_ok()
+        let json = match serde_json::to_string(&settings) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
+        }
+    })
 }
 
-/// Set manager timeout profile overrides in seconds.
+/// Set or clear a Homebrew environment setting override.
 ///
-/// Positive values set an override; zero/negative values clear the override.
+/// `value` values:
+/// - `-1`: clear override (use Helm's per-action default)
+/// - `0`: force off
+/// - `1`: force on
 ///
 /// # Safety
 ///
-/// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
+/// `manager_id` and `key` must be valid, non-null pointers to NUL-terminated UTF-8 C strings.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_set_manager_timeout_profile(
+pub unsafe extern "C" fn helm_set_manager_env_setting(
     manager_id: *const c_char,
-    hard_timeout_seconds: i64,
-    idle_timeout_seconds: i64,
+    key: *const c_char,
+    value: i32,
 ) -> bool {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
-
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(m) => m,
-        Err(_) => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
-    };
-
-    let hard_timeout = if hard_timeout_seconds > 0 {
-        Some(hard_timeout_seconds as u64)
-    } else {
-        None
-    };
-    let idle_timeout = if idle_timeout_seconds > 0 {
-        Some(idle_timeout_seconds as u64)
-    } else {
-        None
-    };
-
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
-
-    if state
-        .store
-        .set_manager_timeout_hard_seconds(manager, hard_timeout)
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_err()
-    {
-        return false;
-    }
-    if state
-        .store
-        .set_manager_timeout_idle_seconds(manager, idle_timeout)
-        .map_err(|_| set_last_error_key(SERVICE_ERROR_STORAGE_FAILURE))
-        .is_err()
-    {
-        return false;
-    }
+    ffi_boundary("helm_set_manager_env_setting", || {
+        clear_last_error_key();
+        let Some(manager) = parse_homebrew_env_manager(manager_id) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        if key.is_null() {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        }
+        let setting = match unsafe { CStr::from_ptr(key) }
+            .to_str()
+            .ok()
+            .and_then(|value| value.parse::<HomebrewEnvSetting>().ok())
+        {
+            Some(setting) => setting,
+            None => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let stored_value = match value {
+            -1 => None,
+            0 => Some("false"),
+            1 => Some("true"),
+            _ => return return_error_bool(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let detection_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_detections()
-        .unwrap_or_default()
-        .into_iter()
-        .collect();
-    let pref_map: std::collections::HashMap<_, _> = state
-        .store
-        .list_manager_preferences()
-        .unwrap_or_default()
-        .into_iter()
-        .map(|pref| (pref.manager, pref))
-        .collect();
-    sync_manager_executable_overrides(&detection_map, &pref_map);
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
 
-    true
+        if state
+            .store
+            .set_manager_env_setting(manager, setting.as_str(), stored_value)
+            .is_err()
+        {
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+        sync_homebrew_env_overrides(state.store.as_ref());
+        sync_homebrew_cask_settings(state.store.as_ref());
+        sync_command_timing_settings(state.store.as_ref());
+        sync_process_qos_settings(state.store.as_ref());
+        sync_npm_settings(state.store.as_ref());
+        sync_network_policy_settings(state.store.as_ref());
+
+        true
+    })
 }
 
 fn manager_supports_post_install_setup(manager: ManagerId) -> bool {
@@ -9492,12 +15681,15 @@ fn spawn_post_install_setup_task(
     wait_for_install_task: Option<TaskId>,
 ) -> Result<TaskId, &'static str> {
     let task_type = TaskType::Install;
-    let task_id = create_local_task(store.as_ref(), manager, task_type)?;
-    set_task_label(
-        task_id,
-        "service.task.label.setup.manager",
-        &[("manager", manager_display_name(manager).to_string())],
-    );
+    let task_id = create_local_task(
+        store.as_ref(),
+        manager,
+        task_type,
+        task_label(
+            "service.task.label.setup.manager",
+            &[("manager", manager_display_name(manager).to_string())],
+        ),
+    )?;
 
     thread::spawn(move || {
         update_local_task_status(
@@ -9686,191 +15878,195 @@ pub unsafe extern "C" fn helm_apply_manager_package_state_issue_repair(
     issue_code: *const c_char,
     option_id: *const c_char,
 ) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null()
-        || source_manager_id.is_null()
-        || package_name.is_null()
-        || issue_code.is_null()
-        || option_id.is_null()
-    {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let manager = {
-        let value = unsafe { CStr::from_ptr(manager_id) };
-        match value
-            .to_str()
-            .ok()
-            .and_then(|raw| raw.parse::<ManagerId>().ok())
-        {
-            Some(manager) => manager,
-            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-    let source_manager = {
-        let value = unsafe { CStr::from_ptr(source_manager_id) };
-        match value
-            .to_str()
-            .ok()
-            .and_then(|raw| raw.parse::<ManagerId>().ok())
+    ffi_boundary("helm_apply_manager_package_state_issue_repair", || {
+        clear_last_error_key();
+        if manager_id.is_null()
+            || source_manager_id.is_null()
+            || package_name.is_null()
+            || issue_code.is_null()
+            || option_id.is_null()
         {
-            Some(manager) => manager,
-            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-    let package_name = {
-        let value = unsafe { CStr::from_ptr(package_name) };
-        match value.to_str() {
-            Ok(raw) => raw.trim().to_string(),
-            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-    let issue_code = {
-        let value = unsafe { CStr::from_ptr(issue_code) };
-        match value.to_str() {
-            Ok(raw) if !raw.trim().is_empty() => raw.trim().to_string(),
-            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-        }
-    };
-    let option_id = {
-        let value = unsafe { CStr::from_ptr(option_id) };
-        match value.to_str() {
-            Ok(raw) if !raw.trim().is_empty() => raw.trim().to_string(),
-            _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
         }
-    };
-
-    let plan = match helm_core::repair::plan_for_issue(
-        manager,
-        source_manager,
-        package_name.as_str(),
-        issue_code.as_str(),
-    ) {
-        Some(plan) => plan,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-    let option = match helm_core::repair::resolve_option(&plan, option_id.as_str()) {
-        Some(option) => option,
-        None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
 
-    match option.action {
-        helm_core::repair::RepairAction::ReinstallManagerViaHomebrew => {
-            let manager_c = match CString::new(manager.as_str()) {
-                Ok(value) => value,
-                Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-            };
-            let options_c = match CString::new("{\"installMethodOverride\":\"homebrew\"}") {
-                Ok(value) => value,
-                Err(_) => return return_error_i64(SERVICE_ERROR_INTERNAL),
-            };
-            unsafe { helm_install_manager_with_options(manager_c.as_ptr(), options_c.as_ptr()) }
-        }
-        helm_core::repair::RepairAction::RemoveStalePackageEntry => {
-            if package_name.trim().is_empty() {
-                return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        let manager = {
+            let value = unsafe { CStr::from_ptr(manager_id) };
+            match value
+                .to_str()
+                .ok()
+                .and_then(|raw| raw.parse::<ManagerId>().ok())
+            {
+                Some(manager) => manager,
+                None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
             }
-            let source_c = match CString::new(source_manager.as_str()) {
-                Ok(value) => value,
-                Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-            };
-            let package_c = match CString::new(package_name) {
-                Ok(value) => value,
-                Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-            };
-            unsafe {
-                helm_uninstall_package(
-                    source_c.as_ptr(),
-                    package_c.as_ptr(),
-                    std::ptr::null(),
-                    std::ptr::null(),
-                )
+        };
+        let source_manager = {
+            let value = unsafe { CStr::from_ptr(source_manager_id) };
+            match value
+                .to_str()
+                .ok()
+                .and_then(|raw| raw.parse::<ManagerId>().ok())
+            {
+                Some(manager) => manager,
+                None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
             }
-        }
-        helm_core::repair::RepairAction::ApplyPostInstallSetupDefaults => {
-            let (store, runtime, rt_handle) = {
-                let guard = lock_or_recover(&STATE, "state");
-                let state = match guard.as_ref() {
-                    Some(s) => s,
-                    None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        };
+        let package_name = {
+            let value = unsafe { CStr::from_ptr(package_name) };
+            match value.to_str() {
+                Ok(raw) => raw.trim().to_string(),
+                _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
+        let issue_code = {
+            let value = unsafe { CStr::from_ptr(issue_code) };
+            match value.to_str() {
+                Ok(raw) if !raw.trim().is_empty() => raw.trim().to_string(),
+                _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
+        let option_id = {
+            let value = unsafe { CStr::from_ptr(option_id) };
+            match value.to_str() {
+                Ok(raw) if !raw.trim().is_empty() => raw.trim().to_string(),
+                _ => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+            }
+        };
+
+        let plan = match helm_core::repair::plan_for_issue(
+            manager,
+            source_manager,
+            package_name.as_str(),
+            issue_code.as_str(),
+        ) {
+            Some(plan) => plan,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+        let option = match helm_core::repair::resolve_option(&plan, option_id.as_str()) {
+            Some(option) => option,
+            None => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
+
+        match option.action {
+            helm_core::repair::RepairAction::ReinstallManagerViaHomebrew => {
+                let manager_c = match CString::new(manager.as_str()) {
+                    Ok(value) => value,
+                    Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
                 };
-                (
-                    state.store.clone(),
-                    state.runtime.clone(),
-                    state.rt_handle.clone(),
-                )
-            };
-            if !manager_supports_post_install_setup(manager) {
-                return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+                let options_c = match CString::new("{\"installMethodOverride\":\"homebrew\"}") {
+                    Ok(value) => value,
+                    Err(_) => return return_error_i64(SERVICE_ERROR_INTERNAL),
+                };
+                unsafe { helm_install_manager_with_options(manager_c.as_ptr(), options_c.as_ptr()) }
             }
-            match spawn_post_install_setup_task(store, runtime, rt_handle, manager, None) {
-                Ok(task_id) => task_id.0 as i64,
-                Err(error_key) => return_error_i64(error_key),
+            helm_core::repair::RepairAction::RemoveStalePackageEntry => {
+                if package_name.trim().is_empty() {
+                    return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+                }
+                let source_c = match CString::new(source_manager.as_str()) {
+                    Ok(value) => value,
+                    Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+                };
+                let package_c = match CString::new(package_name) {
+                    Ok(value) => value,
+                    Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+                };
+                unsafe {
+                    helm_uninstall_package(
+                        source_c.as_ptr(),
+                        package_c.as_ptr(),
+                        std::ptr::null(),
+                        std::ptr::null(),
+                        false,
+                        std::ptr::null(),
+                    )
+                }
             }
-        }
-        helm_core::repair::RepairAction::ClearSelectedExecutableOverride => {
-            let store = {
-                let guard = lock_or_recover(&STATE, "state");
-                let state = match guard.as_ref() {
-                    Some(s) => s,
-                    None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            helm_core::repair::RepairAction::ApplyPostInstallSetupDefaults => {
+                let (store, runtime, rt_handle) = {
+                    let guard = lock_or_recover(&STATE, "state");
+                    let state = match guard.as_ref() {
+                        Some(s) => s,
+                        None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+                    };
+                    (
+                        state.store.clone(),
+                        state.runtime.clone(),
+                        state.rt_handle.clone(),
+                    )
+                };
+                if !manager_supports_post_install_setup(manager) {
+                    return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+                }
+                match spawn_post_install_setup_task(store, runtime, rt_handle, manager, None) {
+                    Ok(task_id) => task_id.0 as i64,
+                    Err(error_key) => return_error_i64(error_key),
+                }
+            }
+            helm_core::repair::RepairAction::ClearSelectedExecutableOverride => {
+                let store = {
+                    let guard = lock_or_recover(&STATE, "state");
+                    let state = match guard.as_ref() {
+                        Some(s) => s,
+                        None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+                    };
+                    state.store.clone()
                 };
-                state.store.clone()
-            };
-
-            let task_id = match create_local_task(
-                store.as_ref(),
-                manager,
-                helm_core::models::TaskType::Configure,
-            ) {
-                Ok(task_id) => task_id,
-                Err(error_key) => return return_error_i64(error_key),
-            };
-            set_task_label(
-                task_id,
-                "service.task.label.repair.manager",
-                &[("manager", manager.as_str().to_string())],
-            );
-            update_local_task_status(
-                store.as_ref(),
-                task_id,
-                manager,
-                helm_core::models::TaskType::Configure,
-                helm_core::models::TaskStatus::Running,
-                helm_core::models::TaskLogLevel::Info,
-                "clearing selected executable override",
-            );
 
-            if store
-                .set_manager_selected_executable_path(manager, None)
-                .is_ok()
-            {
-                invalidate_executable_discovery_cache(Some(manager));
-                sync_manager_execution_preferences_from_store(store.as_ref());
-                update_local_task_status(
+                let task_id = match create_local_task(
                     store.as_ref(),
-                    task_id,
                     manager,
                     helm_core::models::TaskType::Configure,
-                    helm_core::models::TaskStatus::Completed,
-                    helm_core::models::TaskLogLevel::Info,
-                    "selected executable override cleared",
-                );
-            } else {
+                    task_label(
+                        "service.task.label.repair.manager",
+                        &[("manager", manager.as_str().to_string())],
+                    ),
+                ) {
+                    Ok(task_id) => task_id,
+                    Err(error_key) => return return_error_i64(error_key),
+                };
                 update_local_task_status(
                     store.as_ref(),
                     task_id,
                     manager,
                     helm_core::models::TaskType::Configure,
-                    helm_core::models::TaskStatus::Failed,
-                    helm_core::models::TaskLogLevel::Error,
-                    "failed to clear selected executable override",
+                    helm_core::models::TaskStatus::Running,
+                    helm_core::models::TaskLogLevel::Info,
+                    "clearing selected executable override",
                 );
-            }
 
-            task_id.0 as i64
+                if store
+                    .set_manager_selected_executable_path(manager, None)
+                    .is_ok()
+                {
+                    invalidate_executable_discovery_cache(Some(manager));
+                    invalidate_package_snapshot_cache();
+                    sync_manager_execution_preferences_from_store(store.as_ref());
+                    update_local_task_status(
+                        store.as_ref(),
+                        task_id,
+                        manager,
+                        helm_core::models::TaskType::Configure,
+                        helm_core::models::TaskStatus::Completed,
+                        helm_core::models::TaskLogLevel::Info,
+                        "selected executable override cleared",
+                    );
+                } else {
+                    update_local_task_status(
+                        store.as_ref(),
+                        task_id,
+                        manager,
+                        helm_core::models::TaskType::Configure,
+                        helm_core::models::TaskStatus::Failed,
+                        helm_core::models::TaskLogLevel::Error,
+                        "failed to clear selected executable override",
+                    );
+                }
+
+                task_id.0 as i64
+            }
         }
-    }
+    })
 }
 
 /// Install a manager tool. Returns the task ID, or -1 on error.
@@ -9883,7 +16079,9 @@ pub unsafe extern "C" fn helm_apply_manager_package_state_issue_repair(
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_install_manager(manager_id: *const c_char) -> i64 {
-    unsafe { helm_install_manager_with_options(manager_id, std::ptr::null()) }
+    ffi_boundary("helm_install_manager", || unsafe {
+        helm_install_manager_with_options(manager_id, std::ptr::null())
+    })
 }
 
 /// Install a manager tool with optional JSON options. Returns the task ID, or -1 on error.
@@ -9906,130 +16104,135 @@ pub unsafe extern "C" fn helm_install_manager(manager_id: *const c_char) -> i64
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 /// `options_json` may be null.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn helm_install_manager_with_options(
-    manager_id: *const c_char,
-    options_json: *const c_char,
-) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
-
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(manager) => manager,
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
-
-    let install_options = match parse_install_options_payload(options_json) {
-        Ok(options) => options,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
-        };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
-
-    let selected_method = manager_selected_install_method(store.as_ref(), manager);
-    let install_plan = match helm_core::manager_lifecycle::plan_manager_install(
-        manager,
-        selected_method.as_deref(),
-        &install_options,
-    ) {
-        Ok(plan) => plan,
-        Err(helm_core::manager_lifecycle::ManagerInstallPlanError::UnsupportedMethod)
-            if manager == ManagerId::Rustup && selected_method.is_some() =>
-        {
-            eprintln!(
-                "helm-ffi: unrecognized rustup install method '{}' - falling back to rustupInstaller",
-                selected_method.as_deref().unwrap_or("unknown")
-            );
-            match helm_core::manager_lifecycle::plan_manager_install(
-                manager,
-                Some("rustupInstaller"),
-                &install_options,
-            ) {
-                Ok(plan) => plan,
-                Err(error) => return return_error_i64(manager_install_plan_error_key(error)),
-            }
+pub unsafe extern "C" fn helm_install_manager_with_options(
+    manager_id: *const c_char,
+    options_json: *const c_char,
+) -> i64 {
+    ffi_boundary("helm_install_manager_with_options", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
         }
-        Err(error) => return return_error_i64(manager_install_plan_error_key(error)),
-    };
-    let target_manager = install_plan.target_manager;
-    let request = install_plan.request;
-    let label_key = install_plan.label_key;
-    let label_args = install_plan.label_args;
 
-    if target_manager == ManagerId::HomebrewFormula && !homebrew_dependency_available(&store) {
-        return return_error_i64("service.error.homebrew_required");
-    }
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(manager) => manager,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
         };
-        return match coordinator_submit_external(target_manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+
+        let install_options = match parse_install_options_payload(options_json) {
+            Ok(options) => options,
+            Err(error_key) => return return_error_i64(error_key),
         };
-    }
 
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        target_manager,
-        TaskType::Install,
-        Some(label_key),
-        label_args.as_slice(),
-    ) {
-        return existing.0 as i64;
-    }
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
+        };
 
-    match rt_handle.block_on(runtime.submit(target_manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, label_args.as_slice());
-            if install_options.complete_post_install_setup_automatically
-                && manager_supports_post_install_setup(manager)
-                && let Err(error_key) = spawn_post_install_setup_task(
-                    store.clone(),
-                    runtime.clone(),
-                    rt_handle.clone(),
-                    manager,
-                    Some(task_id),
-                )
+        let selected_method = manager_selected_install_method(store.as_ref(), manager);
+        let install_plan = match helm_core::manager_lifecycle::plan_manager_install(
+            manager,
+            selected_method.as_deref(),
+            &install_options,
+        ) {
+            Ok(plan) => plan,
+            Err(helm_core::manager_lifecycle::ManagerInstallPlanError::UnsupportedMethod)
+                if manager == ManagerId::Rustup && selected_method.is_some() =>
             {
                 eprintln!(
-                    "helm-ffi: failed to queue post-install setup task for '{}': {}",
-                    manager.as_str(),
-                    error_key
+                    "helm-ffi: unrecognized rustup install method '{}' - falling back to rustupInstaller",
+                    selected_method.as_deref().unwrap_or("unknown")
                 );
+                match helm_core::manager_lifecycle::plan_manager_install(
+                    manager,
+                    Some("rustupInstaller"),
+                    &install_options,
+                ) {
+                    Ok(plan) => plan,
+                    Err(error) => return return_error_i64(manager_install_plan_error_key(error)),
+                }
             }
-            task_id.0 as i64
+            Err(error) => return return_error_i64(manager_install_plan_error_key(error)),
+        };
+        let target_manager = install_plan.target_manager;
+        let request = install_plan.request;
+        let label_key = install_plan.label_key;
+        let label_args = install_plan.label_args;
+
+        if target_manager == ManagerId::HomebrewFormula && !homebrew_dependency_available(&store) {
+            return return_error_i64("service.error.homebrew_required");
         }
-        Err(e) => {
-            eprintln!("Failed to install manager {}: {}", id_str, e);
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            };
+            return match coordinator_submit_external(target_manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
+            };
         }
-    }
+
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            target_manager,
+            TaskType::Install,
+            Some(label_key),
+            label_args.as_slice(),
+        ) {
+            return existing.0 as i64;
+        }
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            target_manager,
+            request,
+            task_label(label_key, label_args.as_slice()),
+        )) {
+            Ok(task_id) => {
+                if install_options.complete_post_install_setup_automatically
+                    && manager_supports_post_install_setup(manager)
+                    && let Err(error_key) = spawn_post_install_setup_task(
+                        store.clone(),
+                        runtime.clone(),
+                        rt_handle.clone(),
+                        manager,
+                        Some(task_id),
+                    )
+                {
+                    eprintln!(
+                        "helm-ffi: failed to queue post-install setup task for '{}': {}",
+                        manager.as_str(),
+                        error_key
+                    );
+                }
+                task_id.0 as i64
+            }
+            Err(e) => {
+                eprintln!("Failed to install manager {}: {}", id_str, e);
+                return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+            }
+        }
+    })
 }
 
 /// Update a manager tool. Returns the task ID, or -1 on error.
@@ -10037,6 +16240,7 @@ pub unsafe extern "C" fn helm_install_manager_with_options(
 /// Supported manager IDs:
 /// - "homebrew_formula" -> `brew update`
 /// - "rustup" -> provenance-driven (`brew upgrade rustup` or `rustup self update`)
+/// - Self-updating managers (for example `asdf`) -> the adapter's `__self__` upgrade
 /// - Homebrew one-to-one managers -> provenance-driven (`asdf`, `mise`, `mas`, `pnpm`,
 ///   `yarn`, `pipx`, `poetry`, `cargo-binstall`, `podman`, `colima`)
 /// - Homebrew parent-formula managers -> provenance-driven (`npm`, `pip`, `rubygems`,
@@ -10047,132 +16251,143 @@ pub unsafe extern "C" fn helm_install_manager_with_options(
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_update_manager(manager_id: *const c_char) -> i64 {
-    clear_last_error_key();
-    if manager_id.is_null() {
-        return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
-    }
+    ffi_boundary("helm_update_manager", || {
+        clear_last_error_key();
+        if manager_id.is_null() {
+            return return_error_i64(SERVICE_ERROR_INVALID_INPUT);
+        }
 
-    let c_str = unsafe { CStr::from_ptr(manager_id) };
-    let id_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let c_str = unsafe { CStr::from_ptr(manager_id) };
+        let id_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let manager = match id_str.parse::<ManagerId>() {
-        Ok(manager) => manager,
-        Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
-    };
+        let manager = match id_str.parse::<ManagerId>() {
+            Ok(manager) => manager,
+            Err(_) => return return_error_i64(SERVICE_ERROR_INVALID_INPUT),
+        };
 
-    let (store, runtime, rt_handle) = {
-        let guard = lock_or_recover(&STATE, "state");
-        let state = match guard.as_ref() {
-            Some(s) => s,
-            None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+        let (store, runtime, rt_handle) = {
+            let guard = lock_or_recover(&STATE, "state");
+            let state = match guard.as_ref() {
+                Some(s) => s,
+                None => return return_error_i64(SERVICE_ERROR_INTERNAL),
+            };
+            (
+                state.store.clone(),
+                state.runtime.clone(),
+                state.rt_handle.clone(),
+            )
         };
-        (
-            state.store.clone(),
-            state.runtime.clone(),
-            state.rt_handle.clone(),
-        )
-    };
 
-    let homebrew_upgrade_target = |package_name: &str| {
-        let policy = effective_homebrew_keg_policy(store.as_ref(), package_name);
-        let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
-        let target_name = encode_homebrew_upgrade_target(package_name, cleanup_old_kegs);
-        let label_key = if cleanup_old_kegs {
-            "service.task.label.update.homebrew_formula_cleanup"
-        } else {
-            "service.task.label.update.homebrew_formula"
+        let homebrew_upgrade_target = |package_name: &str| {
+            let policy = effective_homebrew_keg_policy(store.as_ref(), package_name);
+            let cleanup_old_kegs = policy == HomebrewKegPolicy::Cleanup;
+            let label_key = if cleanup_old_kegs {
+                "service.task.label.update.homebrew_formula_cleanup"
+            } else {
+                "service.task.label.update.homebrew_formula"
+            };
+            (cleanup_old_kegs, label_key)
         };
-        (target_name, label_key)
-    };
 
-    let active_instance = match active_manager_install_instance(store.as_ref(), manager) {
-        Ok(instance) => instance,
-        Err(error_key) => return return_error_i64(error_key),
-    };
-    let update_plan = match helm_core::manager_lifecycle::plan_manager_update(
-        manager,
-        active_instance.as_ref(),
-    ) {
-        Ok(plan) => plan,
-        Err(error) => return return_error_i64(manager_update_plan_error_key(error)),
-    };
+        let active_instance = match active_manager_install_instance(store.as_ref(), manager) {
+            Ok(instance) => instance,
+            Err(error_key) => return return_error_i64(error_key),
+        };
+        let update_plan = match helm_core::manager_lifecycle::plan_manager_update(
+            manager,
+            active_instance.as_ref(),
+        ) {
+            Ok(plan) => plan,
+            Err(error) => return return_error_i64(manager_update_plan_error_key(error)),
+        };
 
-    let (target_manager, request, label_key, label_args): (
-        ManagerId,
-        AdapterRequest,
-        &str,
-        Vec<(&str, String)>,
-    ) = match &update_plan.target {
-        helm_core::manager_lifecycle::ManagerUpdateTarget::ManagerSelf => {
-            let request =
-                match helm_core::manager_lifecycle::build_update_request(&update_plan, None) {
+        let (target_manager, request, label_key, label_args): (
+            ManagerId,
+            AdapterRequest,
+            &str,
+            Vec<(&str, String)>,
+        ) = match &update_plan.target {
+            helm_core::manager_lifecycle::ManagerUpdateTarget::ManagerSelf => {
+                let request = match helm_core::manager_lifecycle::build_update_request(
+                    &update_plan,
+                    None,
+                    false,
+                ) {
                     Some(request) => request,
                     None => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
                 };
-            let label_key = if update_plan.target_manager == ManagerId::HomebrewFormula {
-                "service.task.label.update.homebrew_self"
-            } else {
-                "service.task.label.update.rustup_self"
+                let (label_key, label_args) = match update_plan.target_manager {
+                    ManagerId::HomebrewFormula => {
+                        ("service.task.label.update.homebrew_self", Vec::new())
+                    }
+                    ManagerId::Rustup => ("service.task.label.update.rustup_self", Vec::new()),
+                    target_manager => (
+                        "service.task.label.update.manager_self",
+                        vec![("manager", manager_display_name(target_manager).to_string())],
+                    ),
+                };
+                (update_plan.target_manager, request, label_key, label_args)
+            }
+            helm_core::manager_lifecycle::ManagerUpdateTarget::HomebrewFormula { formula_name } => {
+                let (cleanup_old_kegs, label_key) = homebrew_upgrade_target(formula_name.as_str());
+                let request = match helm_core::manager_lifecycle::build_update_request(
+                    &update_plan,
+                    Some(formula_name.clone()),
+                    cleanup_old_kegs,
+                ) {
+                    Some(request) => request,
+                    None => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+                };
+                (
+                    update_plan.target_manager,
+                    request,
+                    label_key,
+                    vec![("package", formula_name.clone())],
+                )
+            }
+        };
+
+        if external_coordinator_state_dir().is_some() {
+            let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
+                Ok(request) => request,
+                Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
             };
-            (update_plan.target_manager, request, label_key, Vec::new())
-        }
-        helm_core::manager_lifecycle::ManagerUpdateTarget::HomebrewFormula { formula_name } => {
-            let (target_name, label_key) = homebrew_upgrade_target(formula_name.as_str());
-            let request = match helm_core::manager_lifecycle::build_update_request(
-                &update_plan,
-                Some(target_name),
-            ) {
-                Some(request) => request,
-                None => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
+            return match coordinator_submit_external(target_manager, submit_request, false) {
+                Ok(response) => response
+                    .task_id
+                    .map(|task_id| task_id as i64)
+                    .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
+                Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
             };
-            (
-                update_plan.target_manager,
-                request,
-                label_key,
-                vec![("package", formula_name.clone())],
-            )
         }
-    };
-
-    if external_coordinator_state_dir().is_some() {
-        let submit_request = match adapter_request_to_coordinator_submit(request.clone()) {
-            Ok(request) => request,
-            Err(_) => return return_error_i64(SERVICE_ERROR_UNSUPPORTED_CAPABILITY),
-        };
-        return match coordinator_submit_external(target_manager, submit_request, false) {
-            Ok(response) => response
-                .task_id
-                .map(|task_id| task_id as i64)
-                .unwrap_or_else(|| return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)),
-            Err(_) => return_error_i64(SERVICE_ERROR_PROCESS_FAILURE),
-        };
-    }
-
-    if let Some(existing) = find_matching_inflight_task(
-        store.as_ref(),
-        runtime.as_ref(),
-        &rt_handle,
-        target_manager,
-        TaskType::Upgrade,
-        Some(label_key),
-        &label_args,
-    ) {
-        return existing.0 as i64;
-    }
 
-    match rt_handle.block_on(runtime.submit(target_manager, request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, label_key, &label_args);
-            task_id.0 as i64
+        if let Some(existing) = find_matching_inflight_task(
+            store.as_ref(),
+            runtime.as_ref(),
+            &rt_handle,
+            target_manager,
+            TaskType::Upgrade,
+            Some(label_key),
+            &label_args,
+        ) {
+            return existing.0 as i64;
         }
-        Err(e) => {
-            eprintln!("Failed to update manager {}: {}", id_str, e);
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+
+        match rt_handle.block_on(runtime.submit_labeled(
+            target_manager,
+            request,
+            task_label(label_key, &label_args),
+        )) {
+            Ok(task_id) => task_id.0 as i64,
+            Err(e) => {
+                eprintln!("Failed to update manager {}: {}", id_str, e);
+                return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+            }
         }
-    }
+    })
 }
 
 /// Uninstall a manager tool. Returns the task ID, or -1 on error.
@@ -10188,11 +16403,16 @@ pub unsafe extern "C" fn helm_update_manager(manager_id: *const c_char) -> i64 {
 /// `manager_id` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_uninstall_manager(manager_id: *const c_char) -> i64 {
-    unsafe { helm_uninstall_manager_with_options(manager_id, false) }
+    ffi_boundary("helm_uninstall_manager", || unsafe {
+        helm_uninstall_manager_with_options(manager_id, false)
+    })
 }
 
 /// Preview manager uninstall blast radius and strategy as JSON.
 ///
+/// The preview lists `affectedPackages` tracked under the manager and whether the uninstall
+/// `removesManagerData`; together they describe the cascade of a full-cleanup uninstall.
+///
 /// `allow_unknown_provenance` controls whether unknown-provenance routing uses override mode.
 /// For preview-only UI flows, callers typically pass `false` and rely on `unknown_override_required`
 /// in the JSON response to gate destructive execution.
@@ -10205,14 +16425,16 @@ pub unsafe extern "C" fn helm_preview_manager_uninstall(
     manager_id: *const c_char,
     allow_unknown_provenance: bool,
 ) -> *mut c_char {
-    let uninstall_options = helm_core::manager_lifecycle::ManagerUninstallOptions::default();
-    unsafe {
-        helm_preview_manager_uninstall_internal(
-            manager_id,
-            allow_unknown_provenance,
-            &uninstall_options,
-        )
-    }
+    ffi_boundary("helm_preview_manager_uninstall", || {
+        let uninstall_options = helm_core::manager_lifecycle::ManagerUninstallOptions::default();
+        unsafe {
+            helm_preview_manager_uninstall_internal(
+                manager_id,
+                allow_unknown_provenance,
+                &uninstall_options,
+            )
+        }
+    })
 }
 
 /// Preview manager uninstall blast radius and strategy as JSON with structured options.
@@ -10232,21 +16454,23 @@ pub unsafe extern "C" fn helm_preview_manager_uninstall_with_options(
     manager_id: *const c_char,
     options_json: *const c_char,
 ) -> *mut c_char {
-    let (allow_unknown_provenance, uninstall_options) =
-        match parse_uninstall_options_payload(options_json, false) {
-            Ok(parsed) => parsed,
-            Err(error_key) => {
-                set_last_error_key(error_key);
-                return std::ptr::null_mut();
-            }
-        };
-    unsafe {
-        helm_preview_manager_uninstall_internal(
-            manager_id,
-            allow_unknown_provenance,
-            &uninstall_options,
-        )
-    }
+    ffi_boundary("helm_preview_manager_uninstall_with_options", || {
+        let (allow_unknown_provenance, uninstall_options) =
+            match parse_uninstall_options_payload(options_json, false) {
+                Ok(parsed) => parsed,
+                Err(error_key) => {
+                    set_last_error_key(error_key);
+                    return std::ptr::null_mut();
+                }
+            };
+        unsafe {
+            helm_preview_manager_uninstall_internal(
+                manager_id,
+                allow_unknown_provenance,
+                &uninstall_options,
+            )
+        }
+    })
 }
 
 unsafe fn helm_preview_manager_uninstall_internal(
@@ -10332,14 +16556,16 @@ pub unsafe extern "C" fn helm_uninstall_manager_with_options(
     manager_id: *const c_char,
     allow_unknown_provenance: bool,
 ) -> i64 {
-    let uninstall_options = helm_core::manager_lifecycle::ManagerUninstallOptions::default();
-    unsafe {
-        helm_uninstall_manager_with_options_internal(
-            manager_id,
-            allow_unknown_provenance,
-            &uninstall_options,
-        )
-    }
+    ffi_boundary("helm_uninstall_manager_with_options", || {
+        let uninstall_options = helm_core::manager_lifecycle::ManagerUninstallOptions::default();
+        unsafe {
+            helm_uninstall_manager_with_options_internal(
+                manager_id,
+                allow_unknown_provenance,
+                &uninstall_options,
+            )
+        }
+    })
 }
 
 /// Uninstall a manager tool with structured options. Returns the task ID, or -1 on error.
@@ -10349,6 +16575,8 @@ pub unsafe extern "C" fn helm_uninstall_manager_with_options(
 /// - `homebrewCleanupMode` (`managerOnly` | `fullCleanup`)
 /// - `miseCleanupMode` (`managerOnly` | `fullCleanup`)
 /// - `miseConfigRemoval` (`keepConfig` | `removeConfig`)
+/// - `confirmManagerDataRemoval` (bool) — required when the preview reports
+///   `removesManagerData` with non-empty `affectedPackages`
 ///
 /// # Safety
 ///
@@ -10359,18 +16587,20 @@ pub unsafe extern "C" fn helm_uninstall_manager_with_uninstall_options(
     manager_id: *const c_char,
     options_json: *const c_char,
 ) -> i64 {
-    let (allow_unknown_provenance, uninstall_options) =
-        match parse_uninstall_options_payload(options_json, false) {
-            Ok(parsed) => parsed,
-            Err(error_key) => return return_error_i64(error_key),
-        };
-    unsafe {
-        helm_uninstall_manager_with_options_internal(
-            manager_id,
-            allow_unknown_provenance,
-            &uninstall_options,
-        )
-    }
+    ffi_boundary("helm_uninstall_manager_with_uninstall_options", || {
+        let (allow_unknown_provenance, uninstall_options) =
+            match parse_uninstall_options_payload(options_json, false) {
+                Ok(parsed) => parsed,
+                Err(error_key) => return return_error_i64(error_key),
+            };
+        unsafe {
+            helm_uninstall_manager_with_options_internal(
+                manager_id,
+                allow_unknown_provenance,
+                &uninstall_options,
+            )
+        }
+    })
 }
 
 unsafe fn helm_uninstall_manager_with_options_internal(
@@ -10444,61 +16674,218 @@ unsafe fn helm_uninstall_manager_with_options_internal(
         return existing.0 as i64;
     }
 
-    match rt_handle.block_on(runtime.submit(plan.target_manager, plan.request)) {
-        Ok(task_id) => {
-            set_task_label(task_id, plan.label_key, &plan.label_args);
-            task_id.0 as i64
+    let cascades_package_data = manager_uninstall_cascades_package_data(&plan.preview);
+    match rt_handle.block_on(runtime.submit_labeled(
+        plan.target_manager,
+        plan.request,
+        task_label(plan.label_key, &plan.label_args),
+    )) {
+        Ok(task_id) => {
+            if cascades_package_data {
+                spawn_manager_cascade_snapshot_cleanup(store, runtime, rt_handle, task_id, manager);
+            }
+            task_id.0 as i64
+        }
+        Err(e) => {
+            eprintln!("Failed to uninstall manager {}: {}", id_str, e);
+            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+        }
+    }
+}
+
+/// Reset the database by rolling back all migrations and re-applying them.
+/// Returns true on success.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_reset_database() -> bool {
+    ffi_boundary("helm_reset_database", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let state = match guard.as_ref() {
+            Some(s) => s,
+            None => return return_error_bool(SERVICE_ERROR_INTERNAL),
+        };
+
+        // Hold task persistence exclusively so in-flight watchers cannot write
+        // into the tables while they are dropped and recreated.
+        let _quiesced = state.rt_handle.block_on(state.runtime.quiesce());
+
+        // Roll back to version 0 (drops all data tables)
+        if let Err(e) = state.store.apply_migration(0) {
+            eprintln!("Failed to roll back migrations: {}", e);
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+
+        // Re-apply all migrations (recreates empty tables)
+        if let Err(e) = state.store.migrate_to_latest() {
+            eprintln!("Failed to re-apply migrations: {}", e);
+            return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+        }
+
+        clear_manager_selected_executables();
+        sync_homebrew_env_overrides(state.store.as_ref());
+        sync_homebrew_cask_settings(state.store.as_ref());
+        sync_command_timing_settings(state.store.as_ref());
+        sync_process_qos_settings(state.store.as_ref());
+        sync_npm_settings(state.store.as_ref());
+        sync_network_policy_settings(state.store.as_ref());
+        invalidate_package_snapshot_cache();
+
+        true
+    })
+}
+
+/// List Helm profiles as JSON (`[{name, databasePath, active}]`), `default`
+/// first.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_list_profiles() -> *mut c_char {
+    ffi_boundary("helm_list_profiles", || {
+        clear_last_error_key();
+        let guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_ref() else {
+            return return_error_ptr(SERVICE_ERROR_INTERNAL);
+        };
+        let mut profiles = state.profiles.list();
+        // The open profile can differ from the recorded one when it failed to open
+        // at launch.
+        for profile in &mut profiles {
+            profile.active = profile.name == state.profile;
         }
-        Err(e) => {
-            eprintln!("Failed to uninstall manager {}: {}", id_str, e);
-            return_error_i64(SERVICE_ERROR_PROCESS_FAILURE)
+
+        let json = match serde_json::to_string(&profiles) {
+            Ok(json) => json,
+            Err(_) => return return_error_ptr(SERVICE_ERROR_INTERNAL),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => return_error_ptr(SERVICE_ERROR_INTERNAL),
         }
-    }
+    })
 }
 
-/// Reset the database by rolling back all migrations and re-applying them.
-/// Returns true on success.
+/// Switch to the named profile, creating its database on first use, and
+/// remember it for the next launch. Returns true on success.
+///
+/// The current runtime is quiesced before its store is swapped out. Refused
+/// with `service.error.profile_switch_busy` while tasks are queued or running,
+/// and with `service.error.unsupported_capability` while the legacy file-IPC
+/// coordinator is serving the current database.
+///
+/// # Safety
+///
+/// `name` must be a valid, non-null pointer to a NUL-terminated UTF-8 C string.
 #[unsafe(no_mangle)]
-pub extern "C" fn helm_reset_database() -> bool {
-    clear_last_error_key();
-    let guard = lock_or_recover(&STATE, "state");
-    let state = match guard.as_ref() {
-        Some(s) => s,
-        None => return return_error_bool(SERVICE_ERROR_INTERNAL),
-    };
+pub unsafe extern "C" fn helm_switch_profile(name: *const c_char) -> bool {
+    ffi_boundary("helm_switch_profile", || {
+        clear_last_error_key();
+        let name = match unsafe { parse_required_cstr_arg(name) } {
+            Ok(name) => name,
+            Err(error_key) => return return_error_bool(error_key),
+        };
 
-    // Roll back to version 0 (drops all data tables)
-    if let Err(e) = state.store.apply_migration(0) {
-        eprintln!("Failed to roll back migrations: {}", e);
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        let mut guard = lock_or_recover(&STATE, "state");
+        let Some(state) = guard.as_mut() else {
+            return return_error_bool(SERVICE_ERROR_INTERNAL);
+        };
+        let Ok(database_path) = state.profiles.database_path(&name) else {
+            return return_error_bool(SERVICE_ERROR_INVALID_INPUT);
+        };
+        if name == state.profile {
+            return true;
+        }
+        if COORDINATOR_SERVER_STARTED.load(Ordering::SeqCst)
+            || external_coordinator_state_dir().is_some()
+        {
+            return return_error_bool(SERVICE_ERROR_UNSUPPORTED_CAPABILITY);
+        }
 
-    // Re-apply all migrations (recreates empty tables)
-    if let Err(e) = state.store.migrate_to_latest() {
-        eprintln!("Failed to re-apply migrations: {}", e);
-        return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
-    }
+        let has_inflight_tasks = state
+            .store
+            .list_recent_tasks(TASK_RECENT_FETCH_LIMIT)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|task| {
+                is_inflight_status(task.status)
+                    && runtime_task_is_inflight(state.runtime.as_ref(), &state.rt_handle, task.id)
+            });
+        if has_inflight_tasks {
+            return return_error_bool(SERVICE_ERROR_PROFILE_SWITCH_BUSY);
+        }
 
-    // Final cleanup: delete any task records that in-flight persistence
-    // watchers may have re-inserted during the brief reset window.
-    let _ = state.store.delete_all_tasks();
-    clear_manager_selected_executables();
+        // Keep the outgoing runtime from persisting while its store is replaced.
+        let _quiesced = state.rt_handle.block_on(state.runtime.quiesce());
 
-    true
+        let engine = match state
+            .profiles
+            .set_active_profile(&name)
+            .map_err(|error| error.to_string())
+            .and_then(|()| open_profile_engine(&database_path))
+        {
+            Ok(engine) => engine,
+            Err(error) => {
+                eprintln!("Failed to switch to Helm profile '{name}': {error}");
+                let _ = state.profiles.set_active_profile(&state.profile);
+                load_profile_settings(&state.store);
+                return return_error_bool(SERVICE_ERROR_STORAGE_FAILURE);
+            }
+        };
+
+        let ProfileEngine {
+            engine,
+            store,
+            runtime,
+        } = engine;
+        state.store = store;
+        state.runtime = runtime;
+        state.engine = engine;
+        state.profile = name;
+        reconcile_stale_local_inflight_tasks(
+            state.store.as_ref(),
+            state.runtime.as_ref(),
+            &state.rt_handle,
+            STALE_INFLIGHT_TASK_LOG_CONTEXT_PROFILE_SWITCH,
+        );
+
+        true
+    })
 }
 
 /// Return and clear the most recent service error localization key.
 #[unsafe(no_mangle)]
 pub extern "C" fn helm_take_last_error_key() -> *mut c_char {
-    let key = lock_or_recover(&LAST_ERROR_KEY, "last_error_key").take();
-    let Some(key) = key else {
-        return std::ptr::null_mut();
-    };
+    ffi_boundary("helm_take_last_error_key", || {
+        let last_error = lock_or_recover(&LAST_ERROR, "last_error").take();
+        let Some(last_error) = last_error else {
+            return std::ptr::null_mut();
+        };
 
-    match CString::new(key) {
-        Ok(c) => c.into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+        match CString::new(last_error.key) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Return and clear the most recent service error as JSON: the localization
+/// `key`, and when a core error caused it, its `kind`, `manager`, `task`,
+/// `action`, `message`, `exit_code`, `stderr_excerpt`, and `retryable` flag.
+/// Shares its slot with [`helm_take_last_error_key`]; taking either clears both.
+#[unsafe(no_mangle)]
+pub extern "C" fn helm_take_last_error_json() -> *mut c_char {
+    ffi_boundary("helm_take_last_error_json", || {
+        let last_error = lock_or_recover(&LAST_ERROR, "last_error").take();
+        let Some(last_error) = last_error else {
+            return std::ptr::null_mut();
+        };
+
+        let json = match serde_json::to_string(&last_error) {
+            Ok(json) => json,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        match CString::new(json) {
+            Ok(c) => c.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
 }
 
 /// Free a string previously returned by a `helm_*` function.
@@ -10508,40 +16895,51 @@ pub extern "C" fn helm_take_last_error_key() -> *mut c_char {
 /// `s` must be a pointer previously returned by a `helm_*` function, or null.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn helm_free_string(s: *mut c_char) {
-    if s.is_null() {
-        return;
-    }
-    unsafe {
-        let _ = CString::from_raw(s);
-    }
+    ffi_boundary("helm_free_string", || {
+        if s.is_null() {
+            return;
+        }
+        unsafe {
+            let _ = CString::from_raw(s);
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        FfiUpgradePlanStep, SERVICE_ERROR_UNSUPPORTED_CAPABILITY, build_manager_statuses,
-        build_manager_uninstall_plan, build_manager_uninstall_preview, build_visible_tasks,
-        collect_upgrade_all_targets, homebrew_probe_candidates,
+        FfiUpgradePlanStep, REMOTE_SEARCH_COMPLETION_TTL_SECS,
+        SERVICE_ERROR_MANAGER_DATA_REMOVAL_CONFIRMATION_REQUIRED,
+        SERVICE_ERROR_UNSUPPORTED_CAPABILITY, build_manager_statuses, build_manager_uninstall_plan,
+        build_manager_uninstall_plan_with_options, build_manager_uninstall_preview,
+        build_task_output_batch, collect_upgrade_all_targets, homebrew_probe_candidates,
+        installed_package_version, is_upgrade_all_manager, load_package_snapshot,
         manager_allows_individual_package_install, manager_allows_individual_package_uninstall,
         manager_authority_key, manager_participates_in_catalog_sync,
         manager_participates_in_package_search, manager_uninstall_label_for_route,
-        parse_homebrew_config_version, push_upgrade_plan_step,
-        resolve_homebrew_manager_update_strategy, resolve_rustup_uninstall_strategy,
-        rustup_probe_candidates, search_label_args, search_label_key_for_query,
-        search_task_type_for_query, upgrade_plan_step_id, upgrade_reason_label_for,
-        upgrade_task_label_for,
+        parse_homebrew_config_version, parse_task_output_batch_ids, push_upgrade_plan_step,
+        rebuild_package_snapshot_cache, resolve_homebrew_manager_update_strategy,
+        resolve_rustup_uninstall_strategy, rustup_probe_candidates, search_label_args,
+        search_label_key_for_query, search_task_type_for_query, upgrade_all_package_request,
+        upgrade_plan_step_id, upgrade_reason_label_for, upgrade_task_label_for,
+    };
+    use helm_core::adapters::{
+        AdapterRequest, AdapterResponse, AdapterResult, ManagerAdapter, MutationResult,
+        UninstallRequest, UpgradeRequest, UpgradeTarget,
     };
-    use helm_core::adapters::{AdapterRequest, ManagerAdapter, UninstallRequest};
     use helm_core::manager_policy::{
         PIP_SYSTEM_UNMANAGED_REASON_CODE, RUBYGEMS_SYSTEM_UNMANAGED_REASON_CODE,
     };
     use helm_core::models::{
-        AutomationLevel, DetectionInfo, InstallProvenance, InstalledPackage, ManagerId,
-        ManagerInstallInstance, OutdatedPackage, PackageRef, StrategyKind, TaskId, TaskLogRecord,
-        TaskRecord, TaskStatus, TaskType,
+        ActionSafety, AutomationLevel, Capability, DetectionInfo, InstallProvenance,
+        InstalledPackage, ManagerAction, ManagerAuthority, ManagerCategory, ManagerDescriptor,
+        ManagerId, ManagerInstallInstance, OutdatedPackage, PackageRef, SearchCompletion,
+        StrategyKind, TaskId, TaskLogRecord, TaskRecord, TaskStatus, TaskType,
     };
     use helm_core::orchestration::adapter_runtime::AdapterRuntime;
-    use helm_core::persistence::{DetectionStore, ManagerPreference, PackageStore, TaskStore};
+    use helm_core::persistence::{
+        DetectionStore, ManagerPreference, PackageStore, SearchCacheStore, TaskStore,
+    };
     use helm_core::sqlite::SqliteStore;
     use helm_core::uninstall_preview::{
         DEFAULT_MANAGER_UNINSTALL_SAFE_BLAST_RADIUS_THRESHOLD, ManagerUninstallPreviewContext,
@@ -10553,7 +16951,7 @@ mod tests {
     use std::os::unix::fs::{MetadataExt, PermissionsExt};
     use std::path::Path;
     use std::sync::{Arc, Mutex, OnceLock};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     #[cfg(unix)]
     fn unix_mode(path: &Path) -> u32 {
@@ -10632,6 +17030,7 @@ mod tests {
             installed_version: installed_version.map(str::to_string),
             pinned: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }
     }
 
@@ -10701,6 +17100,91 @@ mod tests {
         std::env::temp_dir().join(format!("helm-ffi-{name}-{nanos}"))
     }
 
+    #[test]
+    fn task_output_batch_ids_reject_invalid_input_and_dedupe() {
+        assert_eq!(
+            parse_task_output_batch_ids("[3, 1, 3, 2]").expect("valid ids"),
+            vec![TaskId(3), TaskId(1), TaskId(2)]
+        );
+        assert!(parse_task_output_batch_ids("[-1]").is_err());
+        assert!(parse_task_output_batch_ids("{\"taskIds\": [1]}").is_err());
+        let oversized = format!(
+            "[{}]",
+            (0..=super::TASK_OUTPUT_BATCH_MAX_TASKS)
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        assert!(parse_task_output_batch_ids(oversized.as_str()).is_err());
+    }
+
+    #[test]
+    fn task_history_filter_accepts_null_and_rejects_unknown_fields() {
+        let parse = |raw: &str| {
+            let raw = std::ffi::CString::new(raw).expect("valid C string");
+            super::parse_task_history_filter(raw.as_ptr())
+        };
+        assert_eq!(
+            super::parse_task_history_filter(std::ptr::null()).expect("null filter"),
+            super::TaskHistoryFilter::default()
+        );
+        assert_eq!(
+            parse(r#"{"manager": "npm", "status": "failed"}"#).expect("valid filter"),
+            super::TaskHistoryFilter {
+                manager: Some(ManagerId::Npm),
+                status: Some(TaskStatus::Failed),
+                ..super::TaskHistoryFilter::default()
+            }
+        );
+        assert!(parse(r#"{"package": "git"}"#).is_err());
+        assert!(parse(r#"{"status": "exploded"}"#).is_err());
+    }
+
+    #[test]
+    fn install_options_arg_validates_against_manager_allowlist() {
+        let parse = |manager: ManagerId, raw: &str| {
+            let raw = std::ffi::CString::new(raw).expect("valid C string");
+            unsafe { super::parse_install_options_arg(manager, raw.as_ptr()) }
+        };
+        assert_eq!(
+            parse(ManagerId::HomebrewFormula, r#"["--HEAD", "--HEAD"]"#).expect("valid options"),
+            vec!["--HEAD".to_string()]
+        );
+        assert_eq!(
+            parse(ManagerId::HomebrewCask, "[]").expect("empty list is valid"),
+            Vec::<String>::new()
+        );
+        assert!(parse(ManagerId::HomebrewCask, r#"["--HEAD"]"#).is_err());
+        assert!(parse(ManagerId::Npm, r#"["--force"]"#).is_err());
+        assert!(parse(ManagerId::HomebrewFormula, "--HEAD").is_err());
+    }
+
+    #[test]
+    fn task_output_batch_truncates_each_stream_independently() {
+        let long_task = TaskId(880_001);
+        let short_task = TaskId(880_002);
+        let long_stdout = "x".repeat(super::TASK_OUTPUT_BATCH_MAX_STREAM_BYTES + 10);
+        helm_core::execution::task_output_store::record(
+            long_task,
+            None,
+            long_stdout.as_bytes(),
+            b"warn\n",
+        );
+        helm_core::execution::task_output_store::record(short_task, None, b"ok\n", b"");
+
+        let batch = build_task_output_batch(&[long_task, short_task]);
+        assert_eq!(batch.len(), 2);
+        assert!(batch[0].stdout_truncated);
+        assert!(!batch[0].stderr_truncated);
+        assert_eq!(
+            batch[0].output.stdout.as_ref().map(String::len),
+            Some(super::TASK_OUTPUT_BATCH_MAX_STREAM_BYTES)
+        );
+        assert_eq!(batch[1].output.task_id, short_task);
+        assert_eq!(batch[1].output.stdout.as_deref(), Some("ok\n"));
+        assert!(!batch[1].stdout_truncated);
+    }
+
     #[test]
     fn package_search_policy_matches_shared_registry() {
         assert!(manager_participates_in_package_search(ManagerId::Rustup));
@@ -10791,6 +17275,62 @@ mod tests {
         assert!(candidates.contains(&"/usr/local/opt/rustup/bin/rustup".to_string()));
     }
 
+    #[test]
+    fn manager_version_probes_are_coalesced_and_cached() {
+        static PROBE_CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        fn slow_probe(_: Option<&std::path::Path>) -> Option<String> {
+            PROBE_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(200));
+            Some("1.2.3".to_string())
+        }
+
+        let store = Arc::new(temp_sqlite_store("version-probe-cache"));
+        store.migrate_to_latest().unwrap();
+        let executable = Some(std::path::PathBuf::from("/opt/test/bin/mise"));
+
+        let first = super::cached_manager_version_probe(
+            &store,
+            ManagerId::Mise,
+            executable.clone(),
+            slow_probe,
+        );
+        let second = super::cached_manager_version_probe(
+            &store,
+            ManagerId::Mise,
+            executable.clone(),
+            slow_probe,
+        );
+        assert_eq!(first, (None, true));
+        assert_eq!(second, (None, true));
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let settled = loop {
+            let result = super::cached_manager_version_probe(
+                &store,
+                ManagerId::Mise,
+                executable.clone(),
+                slow_probe,
+            );
+            if !result.1 || std::time::Instant::now() >= deadline {
+                break result;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(settled, (Some("1.2.3".to_string()), false));
+        assert_eq!(PROBE_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let detection = store
+            .list_detections()
+            .unwrap()
+            .into_iter()
+            .find(|(manager, _)| *manager == ManagerId::Mise)
+            .map(|(_, detection)| detection)
+            .expect("probe result should be persisted");
+        assert!(detection.installed);
+        assert_eq!(detection.version.as_deref(), Some("1.2.3"));
+        assert_eq!(detection.executable_path, executable);
+    }
+
     #[test]
     fn cached_discovery_recomputes_when_cached_path_disappears() {
         let _guard = ENV_LOCK
@@ -10855,6 +17395,78 @@ mod tests {
         let _ = fs::remove_dir_all(root);
     }
 
+    #[test]
+    fn persisted_discovery_is_served_then_revalidated_in_background() {
+        let _guard = ENV_LOCK
+            .get_or_init(|| Mutex::new(()))
+            .lock()
+            .expect("env lock should be available");
+        let _snapshot = EnvSnapshot::capture();
+
+        let root = unique_temp_dir("discovery-persisted");
+        let persisted = root.join("persisted/bin/rustup");
+        let current = root.join("current/bin/rustup");
+        for binary in [&persisted, &current] {
+            fs::create_dir_all(binary.parent().expect("binary parent"))
+                .expect("create binary parent");
+            fs::write(binary, b"#!/bin/sh\nexit 0\n").expect("write binary");
+        }
+        unsafe {
+            std::env::set_var("PATH", current.parent().expect("current parent"));
+        }
+
+        let store = Arc::new(temp_sqlite_store("discovery-persisted"));
+        store.migrate_to_latest().expect("migrate store");
+        store
+            .replace_manager_executable_candidates(
+                ManagerId::Rustup,
+                &[super::ManagerExecutableCandidate {
+                    manager: ManagerId::Rustup,
+                    path: persisted.to_string_lossy().to_string(),
+                    version: None,
+                    last_validated_at: SystemTime::now() - Duration::from_secs(7_200),
+                }],
+            )
+            .expect("seed candidates");
+        super::hydrate_executable_discovery_cache(&store);
+
+        let served = super::cached_discovered_executable_paths(ManagerId::Rustup, &["rustup"]);
+        assert_eq!(served, vec![persisted.to_string_lossy().to_string()]);
+
+        let current_rendered = current.to_string_lossy().to_string();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let revalidated = loop {
+            let stored = store
+                .list_manager_executable_candidates(Some(ManagerId::Rustup))
+                .expect("list candidates");
+            if stored
+                .iter()
+                .any(|candidate| candidate.path == current_rendered)
+                || std::time::Instant::now() >= deadline
+            {
+                break stored;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert!(
+            revalidated
+                .iter()
+                .all(|candidate| candidate.path != persisted.to_string_lossy()),
+            "revalidation should replace the stale candidate: {revalidated:?}"
+        );
+        assert!(
+            revalidated
+                .iter()
+                .any(|candidate| candidate.path == current_rendered),
+            "revalidation should persist the rediscovered binary: {revalidated:?}"
+        );
+
+        *super::lock_or_recover(&super::EXECUTABLE_DISCOVERY_STORE, "test") = None;
+        super::invalidate_executable_discovery_cache(None);
+        let _ = fs::remove_file(store.database_path());
+        let _ = fs::remove_dir_all(root);
+    }
+
     #[test]
     fn homebrew_uninstall_label_decodes_internal_cleanup_marker() {
         let encoded = helm_core::manager_lifecycle::encode_homebrew_manager_uninstall_package_name(
@@ -10869,6 +17481,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            disposal: Default::default(),
         });
 
         let (label_key, label_args) = manager_uninstall_label_for_route(
@@ -10882,6 +17495,59 @@ mod tests {
         assert_eq!(label_args, vec![("package", "rustup".to_string())]);
     }
 
+    #[test]
+    fn concurrent_submissions_with_one_idempotency_key_queue_one_task() {
+        let store = Arc::new(temp_sqlite_store("idempotency-claim-race"));
+        store
+            .migrate_to_latest()
+            .expect("sqlite migrations should apply");
+        let next_task_id = Arc::new(std::sync::atomic::AtomicU64::new(500));
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+
+        let handles = (0..2)
+            .map(|_| {
+                let store = store.clone();
+                let next_task_id = next_task_id.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    let claim = match super::claim_idempotency_key(&store, Some("retry-race-1")) {
+                        Ok(claim) => claim,
+                        Err(existing) => return existing,
+                    };
+                    // Stands in for `runtime.submit`, slow enough for the calls to overlap.
+                    let task_id =
+                        TaskId(next_task_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst));
+                    std::thread::sleep(Duration::from_millis(50));
+                    store
+                        .create_task(&TaskRecord {
+                            id: task_id,
+                            manager: ManagerId::Npm,
+                            task_type: TaskType::Install,
+                            status: TaskStatus::Queued,
+                            created_at: SystemTime::now(),
+                            attempt_of: None,
+                            label: None,
+                        })
+                        .expect("task insert should succeed");
+                    super::set_task_idempotency_key(&store, task_id, &claim);
+                    task_id
+                })
+            })
+            .collect::<Vec<_>>();
+        let task_ids = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("submission thread should not panic"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(task_ids[0], task_ids[1]);
+        assert_eq!(
+            next_task_id.load(std::sync::atomic::Ordering::SeqCst),
+            501,
+            "only one submission should have queued a task"
+        );
+    }
+
     #[test]
     fn manager_self_heal_does_not_cancel_tasks_for_not_installed_detection() {
         let store = temp_sqlite_store("self-heal-no-task-purge");
@@ -10905,6 +17571,8 @@ mod tests {
                 task_type: TaskType::Install,
                 status: TaskStatus::Queued,
                 created_at: SystemTime::now(),
+                attempt_of: None,
+                label: None,
             })
             .expect("task insert should succeed");
 
@@ -10955,6 +17623,8 @@ mod tests {
             task_type: TaskType::Uninstall,
             status: TaskStatus::Queued,
             created_at: SystemTime::now(),
+            attempt_of: None,
+            label: None,
         };
         let stale_running = TaskRecord {
             id: TaskId(202),
@@ -10962,6 +17632,8 @@ mod tests {
             task_type: TaskType::Install,
             status: TaskStatus::Running,
             created_at: SystemTime::now(),
+            attempt_of: None,
+            label: None,
         };
         let completed = TaskRecord {
             id: TaskId(203),
@@ -10969,6 +17641,8 @@ mod tests {
             task_type: TaskType::Detection,
             status: TaskStatus::Completed,
             created_at: SystemTime::now(),
+            attempt_of: None,
+            label: None,
         };
 
         store
@@ -11040,6 +17714,8 @@ mod tests {
                 task_type: TaskType::Install,
                 status: TaskStatus::Queued,
                 created_at: SystemTime::now(),
+                attempt_of: None,
+                label: None,
             })
             .expect("queued task insert should succeed");
 
@@ -11048,48 +17724,175 @@ mod tests {
         let tokio_runtime =
             tokio::runtime::Runtime::new().expect("tokio runtime should initialize");
 
-        let match_result = super::find_matching_inflight_task(
+        let match_result = super::find_matching_inflight_task(
+            &store,
+            &runtime,
+            tokio_runtime.handle(),
+            ManagerId::Rustup,
+            TaskType::Install,
+            None,
+            &[],
+        );
+        assert!(match_result.is_none());
+
+        let task = store
+            .list_recent_tasks(10)
+            .expect("task listing should succeed")
+            .into_iter()
+            .find(|entry| entry.id == TaskId(301))
+            .expect("task should still exist after reconciliation");
+        assert_eq!(task.status, TaskStatus::Cancelled);
+
+        let _ = fs::remove_file(store.database_path());
+    }
+
+    #[test]
+    fn queue_remote_search_task_empty_query_is_routed_as_catalog_sync() {
+        let store = temp_sqlite_store("queue-remote-search-empty-query");
+        store
+            .migrate_to_latest()
+            .expect("sqlite migrations should apply");
+
+        let runtime = AdapterRuntime::new(Vec::<Arc<dyn ManagerAdapter>>::new())
+            .expect("empty adapter runtime should initialize");
+        let tokio_runtime =
+            tokio::runtime::Runtime::new().expect("tokio runtime should initialize");
+
+        let result = super::queue_remote_search_task(
+            &store,
+            &runtime,
+            tokio_runtime.handle(),
+            ManagerId::Npm,
+            "   ",
+        );
+        assert_eq!(result, Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY));
+
+        let _ = fs::remove_file(store.database_path());
+    }
+
+    #[test]
+    fn remote_search_first_task_id_keeps_the_legacy_contract() {
+        let trigger = |manager: ManagerId,
+                       task_id: Option<i64>,
+                       error_key: Option<&'static str>,
+                       skipped_fresh: bool| super::FfiRemoteSearchTrigger {
+            manager_id: manager.as_str().to_string(),
+            task_id,
+            error_key,
+            skipped_fresh,
+        };
+
+        let mixed = [
+            trigger(ManagerId::HomebrewFormula, None, None, true),
+            trigger(
+                ManagerId::Npm,
+                None,
+                Some(super::SERVICE_ERROR_PROCESS_FAILURE),
+                false,
+            ),
+            trigger(ManagerId::Pip, Some(41), None, false),
+            trigger(ManagerId::Cargo, Some(42), None, false),
+        ];
+        assert_eq!(super::remote_search_first_task_id(&mixed), Ok(41));
+
+        let all_fresh = [
+            trigger(ManagerId::Npm, None, None, true),
+            trigger(ManagerId::Pip, None, None, true),
+        ];
+        assert_eq!(
+            super::remote_search_first_task_id(&all_fresh),
+            Ok(super::REMOTE_SEARCH_SKIPPED_FRESH)
+        );
+
+        let fresh_and_failed = [
+            trigger(ManagerId::Npm, None, None, true),
+            trigger(
+                ManagerId::Pip,
+                None,
+                Some(super::SERVICE_ERROR_PROCESS_FAILURE),
+                false,
+            ),
+        ];
+        assert_eq!(
+            super::remote_search_first_task_id(&fresh_and_failed),
+            Err(super::SERVICE_ERROR_PROCESS_FAILURE)
+        );
+        assert_eq!(
+            super::remote_search_first_task_id(&[]),
+            Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY)
+        );
+    }
+
+    #[test]
+    fn remote_search_trigger_serializes_per_manager_outcome() {
+        let trigger = super::FfiRemoteSearchTrigger {
+            manager_id: ManagerId::Npm.as_str().to_string(),
+            task_id: Some(7),
+            error_key: None,
+            skipped_fresh: false,
+        };
+        let json = serde_json::to_value(&trigger).expect("trigger should serialize");
+        assert_eq!(json["managerId"], "npm");
+        assert_eq!(json["taskId"], 7);
+        assert!(json["errorKey"].is_null());
+        assert_eq!(json["skippedFresh"], false);
+    }
+
+    #[test]
+    fn queue_manager_detection_task_rejects_unregistered_manager() {
+        let store = temp_sqlite_store("queue-manager-detection-unregistered");
+        store
+            .migrate_to_latest()
+            .expect("sqlite migrations should apply");
+
+        let runtime = AdapterRuntime::new(Vec::<Arc<dyn ManagerAdapter>>::new())
+            .expect("empty adapter runtime should initialize");
+        let tokio_runtime =
+            tokio::runtime::Runtime::new().expect("tokio runtime should initialize");
+
+        let result = super::queue_manager_detection_task(
             &store,
             &runtime,
             tokio_runtime.handle(),
-            ManagerId::Rustup,
-            TaskType::Install,
-            None,
-            &[],
+            ManagerId::Mise,
+        );
+        assert_eq!(result, Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY));
+        assert!(
+            store
+                .list_recent_tasks(10)
+                .expect("task listing should succeed")
+                .is_empty()
         );
-        assert!(match_result.is_none());
-
-        let task = store
-            .list_recent_tasks(10)
-            .expect("task listing should succeed")
-            .into_iter()
-            .find(|entry| entry.id == TaskId(301))
-            .expect("task should still exist after reconciliation");
-        assert_eq!(task.status, TaskStatus::Cancelled);
 
         let _ = fs::remove_file(store.database_path());
     }
 
     #[test]
-    fn queue_remote_search_task_empty_query_is_routed_as_catalog_sync() {
-        let store = temp_sqlite_store("queue-remote-search-empty-query");
+    fn fresh_search_completion_managers_respects_ttl() {
+        let store = temp_sqlite_store("fresh-search-completions");
         store
             .migrate_to_latest()
             .expect("sqlite migrations should apply");
 
-        let runtime = AdapterRuntime::new(Vec::<Arc<dyn ManagerAdapter>>::new())
-            .expect("empty adapter runtime should initialize");
-        let tokio_runtime =
-            tokio::runtime::Runtime::new().expect("tokio runtime should initialize");
+        let now = SystemTime::now();
+        for (manager, age_secs) in [
+            (ManagerId::Npm, 30),
+            (ManagerId::Cargo, REMOTE_SEARCH_COMPLETION_TTL_SECS + 30),
+        ] {
+            store
+                .record_search_completion(&SearchCompletion {
+                    query: "ripgrep".to_string(),
+                    manager,
+                    result_count: 0,
+                    completed_at: now - Duration::from_secs(age_secs),
+                })
+                .expect("search completion insert should succeed");
+        }
 
-        let result = super::queue_remote_search_task(
-            &store,
-            &runtime,
-            tokio_runtime.handle(),
-            ManagerId::Npm,
-            "   ",
-        );
-        assert_eq!(result, Err(SERVICE_ERROR_UNSUPPORTED_CAPABILITY));
+        let fresh = super::fresh_search_completion_managers(&store, " RipGrep ");
+        assert!(fresh.contains(&ManagerId::Npm));
+        assert!(!fresh.contains(&ManagerId::Cargo));
+        assert!(super::fresh_search_completion_managers(&store, "fd").is_empty());
 
         let _ = fs::remove_file(store.database_path());
     }
@@ -11108,6 +17911,8 @@ mod tests {
                 task_type: TaskType::Refresh,
                 status: TaskStatus::Queued,
                 created_at: SystemTime::now(),
+                attempt_of: None,
+                label: None,
             })
             .expect("queued refresh task insert should succeed");
 
@@ -11562,6 +18367,85 @@ mod tests {
         let _ = fs::remove_file(store.database_path());
     }
 
+    #[test]
+    fn homebrew_mise_full_cleanup_uninstall_requires_confirmation_for_tracked_packages() {
+        let store = temp_sqlite_store("ffi-uninstall-mise-cascade");
+        store
+            .migrate_to_latest()
+            .expect("store migration should succeed");
+        store
+            .upsert_detection(
+                ManagerId::HomebrewFormula,
+                &DetectionInfo {
+                    installed: true,
+                    executable_path: None,
+                    version: None,
+                },
+            )
+            .expect("homebrew detection upsert should succeed");
+        let instance = sample_manager_install_instance(
+            ManagerId::Mise,
+            StrategyKind::HomebrewFormula,
+            StrategyKind::HomebrewFormula,
+            InstallProvenance::Homebrew,
+            "/opt/homebrew/bin/mise",
+            "/opt/homebrew/Cellar/mise/2024.11.6/bin/mise",
+        );
+        store
+            .replace_install_instances(ManagerId::Mise, &[instance])
+            .expect("install instances should persist");
+        store
+            .upsert_installed(&[InstalledPackage {
+                package: PackageRef {
+                    manager: ManagerId::Mise,
+                    name: "python".to_string(),
+                },
+                package_identifier: None,
+                installed_version: Some("3.12.2".to_string()),
+                pinned: false,
+                runtime_state: Default::default(),
+                sizes: Default::default(),
+            }])
+            .expect("installed rows should persist");
+
+        let mut options = helm_core::manager_lifecycle::ManagerUninstallOptions {
+            homebrew_cleanup_mode: Some(
+                helm_core::manager_lifecycle::HomebrewUninstallCleanupMode::FullCleanup,
+            ),
+            ..Default::default()
+        };
+        let preview = build_manager_uninstall_plan_with_options(
+            &store,
+            ManagerId::Mise,
+            false,
+            true,
+            &options,
+        )
+        .expect("cascade preview should resolve");
+        assert!(preview.preview.removes_manager_data);
+        assert_eq!(preview.preview.affected_packages.len(), 1);
+        assert_eq!(preview.preview.affected_packages[0].package_name, "python");
+
+        let error = build_manager_uninstall_plan_with_options(
+            &store,
+            ManagerId::Mise,
+            false,
+            false,
+            &options,
+        )
+        .expect_err("cascade uninstall must require explicit confirmation");
+        assert_eq!(
+            error,
+            SERVICE_ERROR_MANAGER_DATA_REMOVAL_CONFIRMATION_REQUIRED
+        );
+
+        options.confirm_manager_data_removal = true;
+        build_manager_uninstall_plan_with_options(&store, ManagerId::Mise, false, false, &options)
+            .expect("confirmed cascade uninstall should resolve");
+
+        let _ = fs::remove_file(store.database_path());
+    }
+
     #[test]
     fn manager_uninstall_preview_marks_read_only_blocked() {
         let store = temp_sqlite_store("preview-read-only");
@@ -11577,6 +18461,7 @@ mod tests {
             },
             target_name: None,
             version: None,
+            disposal: Default::default(),
         });
         let preview = build_manager_uninstall_preview(
             &store,
@@ -11613,91 +18498,391 @@ mod tests {
             .map(|candidate| candidate.to_string_lossy().to_string())
             .collect();
 
-        assert!(as_strings.contains(&"/usr/local/bin/brew".to_string()));
-        assert!(as_strings.contains(&"/opt/homebrew/bin/brew".to_string()));
-        assert!(as_strings.contains(&"brew".to_string()));
+        assert!(as_strings.contains(&"/usr/local/bin/brew".to_string()));
+        assert!(as_strings.contains(&"/opt/homebrew/bin/brew".to_string()));
+        assert!(as_strings.contains(&"brew".to_string()));
+        assert_eq!(
+            as_strings
+                .iter()
+                .filter(|candidate| *candidate == "/usr/local/bin/brew")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn collect_upgrade_all_targets_routes_supported_managers() {
+        let outdated = vec![
+            outdated_pkg(ManagerId::Asdf, "python", false),
+            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
+            outdated_pkg(ManagerId::MacPorts, "wget", false),
+            outdated_pkg(ManagerId::Mas, "Keynote", false),
+            outdated_pkg(ManagerId::Mise, "node", false),
+            outdated_pkg(ManagerId::Rustup, "stable-x86_64-apple-darwin", false),
+            outdated_pkg(ManagerId::SoftwareUpdate, "macos", false),
+        ];
+        let targets = collect_upgrade_all_targets(&outdated, true);
+        assert_eq!(targets.asdf, vec!["python".to_string()]);
+        assert_eq!(targets.homebrew, vec!["git".to_string()]);
+        assert_eq!(targets.macports, vec!["wget".to_string()]);
+        assert_eq!(targets.mas, vec!["Keynote".to_string()]);
+        assert_eq!(targets.mise, vec!["node".to_string()]);
+        assert_eq!(
+            targets.rustup,
+            vec!["stable-x86_64-apple-darwin".to_string()]
+        );
+        assert_eq!(targets.softwareupdate, vec!["macos".to_string()]);
+    }
+
+    #[test]
+    fn upgrade_all_requests_replay_saved_install_options() {
+        let store = temp_sqlite_store("upgrade-all-options");
+        store.migrate_to_latest().expect("migrations should apply");
+        let wget = PackageRef {
+            manager: ManagerId::HomebrewFormula,
+            name: "wget".to_string(),
+        };
+        store
+            .set_package_install_options(
+                &wget,
+                &["--HEAD".to_string(), "--build-from-source".to_string()],
+            )
+            .expect("install options should persist");
+
+        let AdapterRequest::Upgrade(request) =
+            upgrade_all_package_request(&store, ManagerId::HomebrewFormula, "wget", true)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert_eq!(request.target, UpgradeTarget::Package(wget));
+        assert_eq!(request.options, vec!["--build-from-source".to_string()]);
+        assert!(request.cleanup_old_kegs);
+
+        let AdapterRequest::Upgrade(request) =
+            upgrade_all_package_request(&store, ManagerId::HomebrewCask, "firefox", false)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert!(request.options.is_empty());
+    }
+
+    #[test]
+    fn installed_package_version_resolves_cask_hold_version() {
+        let store = temp_sqlite_store("cask-pin-version");
+        store.migrate_to_latest().expect("migrations should apply");
+        store
+            .upsert_installed(&[sample_installed_package(
+                ManagerId::HomebrewCask,
+                "firefox",
+                Some("131.0"),
+            )])
+            .expect("installed rows should persist");
+
+        let firefox = PackageRef {
+            manager: ManagerId::HomebrewCask,
+            name: "firefox".to_string(),
+        };
+        assert_eq!(
+            installed_package_version(&store, &firefox).as_deref(),
+            Some("131.0")
+        );
+        let missing = PackageRef {
+            manager: ManagerId::HomebrewCask,
+            name: "zoom".to_string(),
+        };
+        assert_eq!(installed_package_version(&store, &missing), None);
+    }
+
+    #[test]
+    fn collect_upgrade_all_targets_excludes_pinned_and_deduplicates() {
+        let outdated = vec![
+            outdated_pkg(ManagerId::Asdf, "python", true),
+            outdated_pkg(ManagerId::Asdf, "python", true),
+            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
+            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
+            outdated_pkg(ManagerId::Mas, "Keynote", true),
+            outdated_pkg(ManagerId::Mise, "node", true),
+            outdated_pkg(ManagerId::Rustup, "stable-x86_64-apple-darwin", true),
+        ];
+        let targets = collect_upgrade_all_targets(&outdated, false);
+        assert!(targets.asdf.is_empty());
+        assert_eq!(targets.homebrew, vec!["git".to_string()]);
+        assert!(targets.mas.is_empty());
+        assert!(targets.mise.is_empty());
+        assert!(targets.rustup.is_empty());
+        assert!(targets.softwareupdate.is_empty());
+    }
+
+    #[test]
+    fn collect_upgrade_all_targets_holds_back_pinned_os_update_labels() {
+        let outdated = vec![
+            outdated_pkg(
+                ManagerId::SoftwareUpdate,
+                "macOS Sequoia 15.3.2-15.3.2",
+                true,
+            ),
+            outdated_pkg(ManagerId::SoftwareUpdate, "Safari 18.3.1-18.3.1", false),
+        ];
+
+        let targets = collect_upgrade_all_targets(&outdated, false);
+        assert_eq!(
+            targets.softwareupdate,
+            vec!["Safari 18.3.1-18.3.1".to_string()]
+        );
+
+        let targets = collect_upgrade_all_targets(&outdated, true);
+        assert_eq!(
+            targets.softwareupdate,
+            vec![
+                "macOS Sequoia 15.3.2-15.3.2".to_string(),
+                "Safari 18.3.1-18.3.1".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn upgrade_reason_label_uses_manager_specific_keys() {
+        let (homebrew_key, homebrew_args) =
+            upgrade_reason_label_for(ManagerId::HomebrewFormula, "git", true);
+        assert_eq!(homebrew_key, "service.task.label.upgrade.homebrew_cleanup");
+        assert_eq!(homebrew_args, vec![("package", "git".to_string())]);
+
+        let (homebrew_cask_key, homebrew_cask_args) =
+            upgrade_reason_label_for(ManagerId::HomebrewCask, "iterm2", false);
+        assert_eq!(
+            homebrew_cask_key,
+            "service.task.label.upgrade.homebrew_cask"
+        );
+        assert_eq!(homebrew_cask_args, vec![("package", "iterm2".to_string())]);
+
+        let (asdf_key, asdf_args) = upgrade_reason_label_for(ManagerId::Asdf, "python", false);
+        assert_eq!(asdf_key, "service.task.label.upgrade.asdf");
+        assert_eq!(asdf_args, vec![("plugin", "python".to_string())]);
+
+        let (rustup_key, rustup_args) =
+            upgrade_reason_label_for(ManagerId::Rustup, "stable", false);
+        assert_eq!(rustup_key, "service.task.label.upgrade.rustup_toolchain");
+        assert_eq!(rustup_args, vec![("toolchain", "stable".to_string())]);
+    }
+
+    #[test]
+    fn upgrade_task_label_includes_plan_step_id_arg() {
+        let (label_key, label_args) = upgrade_task_label_for(ManagerId::Npm, "typescript", false);
+        assert_eq!(label_key, "service.task.label.upgrade.package");
+        assert!(label_args.contains(&("package", "typescript".to_string())));
+        assert!(label_args.contains(&("manager", "npm".to_string())));
+        assert!(label_args.contains(&(
+            "plan_step_id",
+            upgrade_plan_step_id(ManagerId::Npm, "typescript"),
+        )));
+    }
+
+    #[test]
+    fn macports_upgrade_plan_steps_require_elevation() {
+        let mut steps = Vec::new();
+        let mut order_index = 0;
+        push_upgrade_plan_step(
+            &mut steps,
+            ManagerId::MacPorts,
+            "wget".to_string(),
+            false,
+            &mut order_index,
+        );
+        push_upgrade_plan_step(
+            &mut steps,
+            ManagerId::HomebrewFormula,
+            "git".to_string(),
+            false,
+            &mut order_index,
+        );
+
+        assert!(steps[0].requires_elevation);
+        assert_eq!(
+            steps[0]
+                .reason_label_args
+                .get("manager")
+                .map(String::as_str),
+            Some("MacPorts")
+        );
+        assert!(!steps[1].requires_elevation);
+    }
+
+    #[test]
+    fn upgrade_all_manager_set_matches_collected_targets() {
+        for manager in ManagerId::ALL {
+            let targets = collect_upgrade_all_targets(&[outdated_pkg(manager, "pkg", false)], true);
+            let collected = [
+                &targets.asdf,
+                &targets.homebrew,
+                &targets.homebrew_cask,
+                &targets.macports,
+                &targets.mas,
+                &targets.mise,
+                &targets.npm,
+                &targets.pnpm,
+                &targets.yarn,
+                &targets.cargo,
+                &targets.cargo_binstall,
+                &targets.pip,
+                &targets.pipx,
+                &targets.poetry,
+                &targets.rubygems,
+                &targets.bundler,
+                &targets.rustup,
+                &targets.softwareupdate,
+            ]
+            .iter()
+            .any(|names| !names.is_empty());
+            assert_eq!(is_upgrade_all_manager(manager), collected, "{manager:?}");
+        }
+    }
+
+    #[test]
+    fn coordinator_upgrade_payload_carries_structured_homebrew_cleanup() {
+        let request = AdapterRequest::Upgrade(UpgradeRequest {
+            target: UpgradeTarget::Package(PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "python@3.12".to_string(),
+            }),
+            target_name: None,
+            version: None,
+            options: Vec::new(),
+            cleanup_old_kegs: true,
+        });
+        let submit = super::adapter_request_to_coordinator_submit(request.clone()).unwrap();
+        let json = serde_json::to_string(&submit).unwrap();
+        assert!(!json.contains("@@helm"));
+        let decoded: super::CoordinatorSubmitRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, decoded),
+            request
+        );
+
+        let legacy: super::CoordinatorSubmitRequest = serde_json::from_str(
+            r#"{"kind":"upgrade","package_name":"wget@@helm.cleanup","target_name":null,"version":null}"#,
+        )
+        .unwrap();
+        let AdapterRequest::Upgrade(legacy) =
+            super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, legacy)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert!(!legacy.cleanup_old_kegs);
+        assert_eq!(
+            legacy.target.package().map(|package| package.name.as_str()),
+            Some("wget@@helm.cleanup")
+        );
+    }
+
+    #[test]
+    fn coordinator_upgrade_payload_carries_typed_manager_wide_targets() {
+        for target in [
+            UpgradeTarget::All,
+            UpgradeTarget::SelfUpdate,
+            UpgradeTarget::SelfUpdateThenAll,
+            UpgradeTarget::OsUpdatesConfirmed,
+        ] {
+            let request = AdapterRequest::Upgrade(UpgradeRequest {
+                target,
+                target_name: None,
+                version: None,
+                options: Vec::new(),
+                cleanup_old_kegs: false,
+            });
+            let submit = super::adapter_request_to_coordinator_submit(request.clone()).unwrap();
+            let json = serde_json::to_string(&submit).unwrap();
+            let decoded: super::CoordinatorSubmitRequest = serde_json::from_str(&json).unwrap();
+            assert_eq!(
+                super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, decoded),
+                request
+            );
+        }
+
+        let legacy: super::CoordinatorSubmitRequest = serde_json::from_str(
+            r#"{"kind":"upgrade","package_name":"__self__","target_name":null,"version":null}"#,
+        )
+        .unwrap();
+        let AdapterRequest::Upgrade(legacy) =
+            super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, legacy)
+        else {
+            panic!("expected upgrade request");
+        };
+        assert_eq!(legacy.target, UpgradeTarget::SelfUpdate);
+    }
+
+    #[test]
+    fn coordinator_rollback_payload_round_trips() {
+        let request = AdapterRequest::Rollback(super::RollbackRequest {
+            package: PackageRef {
+                manager: ManagerId::HomebrewFormula,
+                name: "git".to_string(),
+            },
+            version: "2.44.0".to_string(),
+        });
+        let submit = super::adapter_request_to_coordinator_submit(request.clone()).unwrap();
+        let json = serde_json::to_string(&submit).unwrap();
+        assert!(json.contains(r#""kind":"rollback""#));
+        let decoded: super::CoordinatorSubmitRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(
-            as_strings
-                .iter()
-                .filter(|candidate| *candidate == "/usr/local/bin/brew")
-                .count(),
-            1
+            super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, decoded),
+            request
         );
     }
 
     #[test]
-    fn collect_upgrade_all_targets_routes_supported_managers() {
-        let outdated = vec![
-            outdated_pkg(ManagerId::Asdf, "python", false),
-            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
-            outdated_pkg(ManagerId::Mas, "Keynote", false),
-            outdated_pkg(ManagerId::Mise, "node", false),
-            outdated_pkg(ManagerId::Rustup, "stable-x86_64-apple-darwin", false),
-            outdated_pkg(ManagerId::SoftwareUpdate, "macos", false),
-        ];
-        let targets = collect_upgrade_all_targets(&outdated, true);
-        assert_eq!(targets.asdf, vec!["python".to_string()]);
-        assert_eq!(targets.homebrew, vec!["git".to_string()]);
-        assert_eq!(targets.mas, vec!["Keynote".to_string()]);
-        assert_eq!(targets.mise, vec!["node".to_string()]);
+    fn coordinator_set_service_state_payload_round_trips() {
+        let request = AdapterRequest::SetServiceState(super::SetServiceStateRequest {
+            service: "postgresql@16".to_string(),
+            action: super::ServiceAction::Restart,
+        });
+        let submit = super::adapter_request_to_coordinator_submit(request.clone()).unwrap();
+        let json = serde_json::to_string(&submit).unwrap();
+        assert!(json.contains(r#""kind":"set_service_state""#));
+        assert!(json.contains(r#""action":"restart""#));
+        let decoded: super::CoordinatorSubmitRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(
-            targets.rustup,
-            vec!["stable-x86_64-apple-darwin".to_string()]
+            super::coordinator_submit_to_adapter(ManagerId::HomebrewFormula, decoded),
+            request
         );
-        assert!(targets.softwareupdate_outdated);
     }
 
     #[test]
-    fn collect_upgrade_all_targets_excludes_pinned_and_deduplicates() {
-        let outdated = vec![
-            outdated_pkg(ManagerId::Asdf, "python", true),
-            outdated_pkg(ManagerId::Asdf, "python", true),
-            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
-            outdated_pkg(ManagerId::HomebrewFormula, "git", false),
-            outdated_pkg(ManagerId::Mas, "Keynote", true),
-            outdated_pkg(ManagerId::Mise, "node", true),
-            outdated_pkg(ManagerId::Rustup, "stable-x86_64-apple-darwin", true),
-        ];
-        let targets = collect_upgrade_all_targets(&outdated, false);
-        assert!(targets.asdf.is_empty());
-        assert_eq!(targets.homebrew, vec!["git".to_string()]);
-        assert!(targets.mas.is_empty());
-        assert!(targets.mise.is_empty());
-        assert!(targets.rustup.is_empty());
-        assert!(!targets.softwareupdate_outdated);
+    fn coordinator_cleanup_payload_round_trips() {
+        let request = AdapterRequest::Cleanup(super::CleanupRequest);
+        let submit = super::adapter_request_to_coordinator_submit(request.clone()).unwrap();
+        let json = serde_json::to_string(&submit).unwrap();
+        assert_eq!(json, r#"{"kind":"cleanup"}"#);
+        let decoded: super::CoordinatorSubmitRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            super::coordinator_submit_to_adapter(ManagerId::Npm, decoded),
+            request
+        );
     }
 
     #[test]
-    fn upgrade_reason_label_uses_manager_specific_keys() {
-        let (homebrew_key, homebrew_args) =
-            upgrade_reason_label_for(ManagerId::HomebrewFormula, "git", true);
-        assert_eq!(homebrew_key, "service.task.label.upgrade.homebrew_cleanup");
-        assert_eq!(homebrew_args, vec![("package", "git".to_string())]);
-
-        let (homebrew_cask_key, homebrew_cask_args) =
-            upgrade_reason_label_for(ManagerId::HomebrewCask, "iterm2", false);
+    fn service_task_labels_name_the_service_and_action() {
+        let (label_key, label_args) =
+            super::service_task_label_for("redis", super::ServiceAction::Stop);
+        assert_eq!(label_key, "service.task.label.service.stop");
         assert_eq!(
-            homebrew_cask_key,
-            "service.task.label.upgrade.homebrew_cask"
+            label_args,
+            vec![
+                ("service", "redis".to_string()),
+                ("action", "stop".to_string())
+            ]
         );
-        assert_eq!(homebrew_cask_args, vec![("package", "iterm2".to_string())]);
-
-        let (rustup_key, rustup_args) =
-            upgrade_reason_label_for(ManagerId::Rustup, "stable", false);
-        assert_eq!(rustup_key, "service.task.label.upgrade.rustup_toolchain");
-        assert_eq!(rustup_args, vec![("toolchain", "stable".to_string())]);
     }
 
     #[test]
-    fn upgrade_task_label_includes_plan_step_id_arg() {
-        let (label_key, label_args) = upgrade_task_label_for(ManagerId::Npm, "typescript", false);
-        assert_eq!(label_key, "service.task.label.upgrade.package");
-        assert!(label_args.contains(&("package", "typescript".to_string())));
-        assert!(label_args.contains(&("manager", "npm".to_string())));
-        assert!(label_args.contains(&(
-            "plan_step_id",
-            upgrade_plan_step_id(ManagerId::Npm, "typescript"),
-        )));
+    fn upgrade_plan_step_ids_encode_versioned_and_tap_qualified_names() {
+        assert_eq!(
+            upgrade_plan_step_id(ManagerId::HomebrewFormula, "python@3.12"),
+            "homebrew_formula:python%403.12"
+        );
+        assert_eq!(
+            upgrade_plan_step_id(ManagerId::HomebrewCask, "homebrew/cask-fonts/font-x"),
+            "homebrew_cask:homebrew%2Fcask-fonts%2Ffont-x"
+        );
     }
 
     #[test]
@@ -11739,6 +18924,199 @@ mod tests {
         assert!(status_for(&statuses, ManagerId::Mise).enabled);
     }
 
+    #[test]
+    fn manager_status_reports_installed_and_outdated_package_counts() {
+        let store = temp_sqlite_store("manager-status-counts");
+        store.migrate_to_latest().expect("migrate");
+        store
+            .upsert_installed(&[
+                sample_installed_package(ManagerId::Npm, "eslint", Some("8.0.0")),
+                sample_installed_package(ManagerId::Npm, "typescript", Some("5.0.0")),
+            ])
+            .expect("installed should persist");
+        store
+            .upsert_outdated(&[OutdatedPackage {
+                package: PackageRef {
+                    manager: ManagerId::Npm,
+                    name: "eslint".to_string(),
+                },
+                package_identifier: None,
+                installed_version: Some("8.0.0".to_string()),
+                candidate_version: "9.0.0".to_string(),
+                pinned: false,
+                restart_required: false,
+                runtime_state: Default::default(),
+                sizes: Default::default(),
+            }])
+            .expect("outdated should persist");
+
+        let statuses = build_manager_statuses(None, Some(&store), &HashMap::new(), &HashMap::new());
+        let npm = status_for(&statuses, ManagerId::Npm);
+        assert_eq!(npm.installed_package_count, 2);
+        assert_eq!(npm.outdated_package_count, 1);
+        assert_eq!(npm.size_on_disk_bytes, None);
+        assert_eq!(
+            status_for(&statuses, ManagerId::Cargo).installed_package_count,
+            0
+        );
+    }
+
+    #[test]
+    fn package_snapshot_lists_only_enabled_managers_and_rebuilds_from_store() {
+        let store = Arc::new(temp_sqlite_store("package-snapshot-cache"));
+        store.migrate_to_latest().expect("migrate");
+        let runtime = helm_engine::adapter_runtime(store.clone(), Vec::new()).expect("runtime");
+        let engine = helm_engine::Engine::with_runtime(store.clone(), Arc::new(runtime));
+        store
+            .upsert_installed(&[
+                sample_installed_package(ManagerId::Npm, "eslint", Some("8.0.0")),
+                sample_installed_package(ManagerId::Cargo, "ripgrep", Some("14.0.0")),
+            ])
+            .expect("installed should persist");
+        store
+            .set_manager_enabled(ManagerId::Cargo, false)
+            .expect("preference should persist");
+
+        let snapshot = load_package_snapshot(&engine, &store).expect("snapshot should load");
+        let names = snapshot
+            .installed
+            .iter()
+            .map(|package| package.package.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["eslint"]);
+        assert!(snapshot.outdated.is_empty());
+
+        store
+            .set_manager_enabled(ManagerId::Cargo, true)
+            .expect("preference should persist");
+        let rebuilt =
+            rebuild_package_snapshot_cache(&engine, &store).expect("rebuild should succeed");
+        assert_eq!(rebuilt.installed.len(), 2);
+    }
+
+    #[test]
+    fn manager_status_reports_manager_self_update_availability() {
+        let store = temp_sqlite_store("manager-status-self-update");
+        store.migrate_to_latest().expect("migrate");
+        store
+            .set_manager_self_version(
+                ManagerId::Rustup,
+                &helm_core::models::ManagerSelfVersion {
+                    installed_version: Some("1.27.1".to_string()),
+                    latest_version: Some("1.28.2".to_string()),
+                },
+            )
+            .expect("self version should persist");
+        let detections = HashMap::from([(
+            ManagerId::Rustup,
+            DetectionInfo {
+                installed: true,
+                executable_path: None,
+                version: Some("1.27.1".to_string()),
+            },
+        )]);
+
+        let statuses = build_manager_statuses(None, Some(&store), &detections, &HashMap::new());
+        let rustup = status_for(&statuses, ManagerId::Rustup);
+        assert!(rustup.supports_manager_update);
+        assert!(rustup.self_update_available);
+        assert_eq!(rustup.self_latest_version.as_deref(), Some("1.28.2"));
+
+        let npm = status_for(&statuses, ManagerId::Npm);
+        assert!(!npm.self_update_available);
+        assert_eq!(npm.self_latest_version, None);
+    }
+
+    #[test]
+    fn manager_status_reports_quarantined_managers() {
+        let store = temp_sqlite_store("manager-status-quarantine");
+        store.migrate_to_latest().expect("migrate");
+        let error = helm_core::models::CoreError {
+            manager: Some(ManagerId::Pip),
+            task: Some(TaskType::Refresh),
+            action: Some(super::ManagerAction::ListInstalled),
+            kind: helm_core::models::CoreErrorKind::ProcessFailure,
+            message: "python3: bad interpreter".to_string(),
+        };
+        for now_unix in 1..=i64::from(helm_core::orchestration::QUARANTINE_FAILURE_THRESHOLD) {
+            helm_core::orchestration::quarantine::record_refresh_outcome(
+                &store,
+                ManagerId::Pip,
+                Err(&error),
+                now_unix,
+            )
+            .expect("refresh outcome should persist");
+        }
+
+        let statuses = build_manager_statuses(None, Some(&store), &HashMap::new(), &HashMap::new());
+        let quarantine = status_for(&statuses, ManagerId::Pip)
+            .quarantine
+            .as_ref()
+            .expect("pip should be quarantined");
+        assert_eq!(quarantine.reason_kind, "process_failure");
+        assert_eq!(quarantine.reason, "python3: bad interpreter");
+        assert!(status_for(&statuses, ManagerId::Npm).quarantine.is_none());
+
+        helm_core::orchestration::quarantine::release_quarantine(&store, ManagerId::Pip)
+            .expect("release should persist");
+        let statuses = build_manager_statuses(None, Some(&store), &HashMap::new(), &HashMap::new());
+        assert!(status_for(&statuses, ManagerId::Pip).quarantine.is_none());
+    }
+
+    #[test]
+    fn manager_status_reports_partial_package_snapshots() {
+        let store = temp_sqlite_store("manager-status-partial-snapshot");
+        store.migrate_to_latest().expect("migrate");
+        store
+            .record_package_snapshot_freshness(&helm_core::models::PackageSnapshotFreshness {
+                manager: ManagerId::Npm,
+                task_id: TaskId(7),
+                partial: true,
+                installed_partial: false,
+                outdated_partial: true,
+                updated_at: SystemTime::now(),
+            })
+            .expect("snapshot freshness should persist");
+
+        let statuses = build_manager_statuses(None, Some(&store), &HashMap::new(), &HashMap::new());
+        let npm = status_for(&statuses, ManagerId::Npm);
+        assert!(npm.snapshot_partial);
+        assert!(!npm.installed_snapshot_partial);
+        assert!(npm.outdated_snapshot_partial);
+        assert!(!status_for(&statuses, ManagerId::Cargo).snapshot_partial);
+    }
+
+    #[test]
+    fn manager_status_lists_network_capabilities() {
+        let statuses = build_manager_statuses(None, None, &HashMap::new(), &HashMap::new());
+        let npm = status_for(&statuses, ManagerId::Npm);
+        assert!(npm.network_capabilities.contains(&"list_outdated"));
+        assert!(!npm.network_capabilities.contains(&"list_installed"));
+    }
+
+    #[test]
+    fn manager_status_reports_yarn_flavor_in_detection_extras() {
+        let detection_map = HashMap::from([(
+            ManagerId::Yarn,
+            DetectionInfo {
+                installed: true,
+                executable_path: Some(std::path::PathBuf::from("/opt/homebrew/bin/yarn")),
+                version: Some("4.6.1".to_string()),
+            },
+        )]);
+        let statuses = build_manager_statuses(None, None, &detection_map, &HashMap::new());
+        let yarn = status_for(&statuses, ManagerId::Yarn);
+        assert_eq!(
+            yarn.detection_extras.get("flavor").map(String::as_str),
+            Some("berry")
+        );
+        assert!(
+            status_for(&statuses, ManagerId::Npm)
+                .detection_extras
+                .is_empty()
+        );
+    }
+
     #[test]
     fn manager_status_includes_core_install_method_metadata() {
         let statuses = build_manager_statuses(None, None, &HashMap::new(), &HashMap::new());
@@ -11783,6 +19161,7 @@ mod tests {
                     selected_install_method: None,
                     timeout_hard_seconds: None,
                     timeout_idle_seconds: None,
+                    process_qos: None,
                 },
             ),
             (
@@ -11794,6 +19173,7 @@ mod tests {
                     selected_install_method: None,
                     timeout_hard_seconds: None,
                     timeout_idle_seconds: None,
+                    process_qos: None,
                 },
             ),
             (
@@ -11805,6 +19185,7 @@ mod tests {
                     selected_install_method: None,
                     timeout_hard_seconds: None,
                     timeout_idle_seconds: None,
+                    process_qos: None,
                 },
             ),
         ]);
@@ -11905,6 +19286,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -11933,6 +19315,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -12222,6 +19605,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -12359,6 +19743,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -12391,6 +19776,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -12420,6 +19806,7 @@ mod tests {
                 selected_install_method: None,
                 timeout_hard_seconds: None,
                 timeout_idle_seconds: None,
+                process_qos: None,
             },
         )]);
 
@@ -12433,162 +19820,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn build_visible_tasks_deduplicates_inflight_rows_by_manager_and_type() {
-        let tasks = vec![
-            TaskRecord {
-                id: TaskId(10),
-                manager: ManagerId::HomebrewFormula,
-                task_type: TaskType::Refresh,
-                status: TaskStatus::Running,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(9),
-                manager: ManagerId::HomebrewFormula,
-                task_type: TaskType::Refresh,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(8),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(7),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Running,
-                created_at: std::time::SystemTime::now(),
-            },
-        ];
-
-        let labels = std::collections::HashMap::new();
-        let visible = build_visible_tasks(tasks, &labels);
-        assert_eq!(visible.len(), 2);
-        assert_eq!(visible[0].id, TaskId(10));
-        assert_eq!(visible[1].id, TaskId(7));
-    }
-
-    #[test]
-    fn build_visible_tasks_prefers_newer_inflight_row_when_status_matches() {
-        let tasks = vec![
-            TaskRecord {
-                id: TaskId(21),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(22),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-        ];
-
-        let labels = std::collections::HashMap::new();
-        let visible = build_visible_tasks(tasks, &labels);
-        assert_eq!(visible.len(), 1);
-        assert_eq!(visible[0].id, TaskId(22));
-    }
-
-    #[test]
-    fn build_visible_tasks_keeps_terminal_history_bounded() {
-        let mut tasks = Vec::new();
-        for idx in 0..60 {
-            tasks.push(TaskRecord {
-                id: TaskId(idx),
-                manager: ManagerId::HomebrewFormula,
-                task_type: TaskType::Refresh,
-                status: TaskStatus::Completed,
-                created_at: std::time::SystemTime::now(),
-            });
-        }
-
-        let labels = std::collections::HashMap::new();
-        let visible = build_visible_tasks(tasks, &labels);
-        assert_eq!(visible.len(), 50);
-        assert_eq!(visible[0].id, TaskId(0));
-        assert_eq!(visible[49].id, TaskId(49));
-    }
-
-    #[test]
-    fn build_visible_tasks_replaces_older_identical_failed_task() {
-        let tasks = vec![
-            TaskRecord {
-                id: TaskId(201),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Running,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(200),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Failed,
-                created_at: std::time::SystemTime::now(),
-            },
-        ];
-        let labels = std::collections::HashMap::new();
-        let visible = build_visible_tasks(tasks, &labels);
-        assert_eq!(visible.len(), 1);
-        assert_eq!(visible[0].id, TaskId(201));
-        assert_eq!(visible[0].status, TaskStatus::Running);
-    }
-
-    #[test]
-    fn build_visible_tasks_keeps_distinct_labeled_inflight_rows() {
-        let tasks = vec![
-            TaskRecord {
-                id: TaskId(100),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-            TaskRecord {
-                id: TaskId(99),
-                manager: ManagerId::Npm,
-                task_type: TaskType::Upgrade,
-                status: TaskStatus::Queued,
-                created_at: std::time::SystemTime::now(),
-            },
-        ];
-
-        let labels = std::collections::HashMap::from([
-            (
-                100_u64,
-                super::TaskLabel {
-                    key: "service.task.label.upgrade.package".to_string(),
-                    args: std::collections::BTreeMap::from([
-                        ("manager".to_string(), "npm".to_string()),
-                        ("package".to_string(), "typescript".to_string()),
-                    ]),
-                },
-            ),
-            (
-                99_u64,
-                super::TaskLabel {
-                    key: "service.task.label.upgrade.package".to_string(),
-                    args: std::collections::BTreeMap::from([
-                        ("manager".to_string(), "npm".to_string()),
-                        ("package".to_string(), "eslint".to_string()),
-                    ]),
-                },
-            ),
-        ]);
-
-        let visible = build_visible_tasks(tasks, &labels);
-        assert_eq!(visible.len(), 2);
-    }
-
     #[test]
     fn diagnostics_redaction_masks_non_allowlisted_env_assignments() {
         let redacted = super::redact_diagnostics_text(
@@ -12607,12 +19838,72 @@ mod tests {
         let redacted = super::redact_diagnostics_text(
             "authorization: Bearer token-value\npassword=hunter2 cookie:abc123",
         );
-        assert!(redacted.contains("authorization: [REDACTED]"));
-        assert!(redacted.contains("password=[REDACTED]"));
-        assert!(redacted.contains("cookie:[REDACTED]"));
-        assert!(!redacted.contains("token-value"));
-        assert!(!redacted.contains("hunter2"));
-        assert!(!redacted.contains("abc123"));
+        assert!(redacted.contains("authorization: [REDACTED]"));
+        assert!(redacted.contains("password=[REDACTED]"));
+        assert!(redacted.contains("cookie:[REDACTED]"));
+        assert!(!redacted.contains("token-value"));
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc123"));
+    }
+
+    #[test]
+    fn task_wait_result_reports_failure_detail_or_timeout() {
+        let runtime = helm_core::orchestration::TaskRuntimeSnapshot {
+            id: TaskId(41),
+            manager: ManagerId::Npm,
+            task_type: TaskType::Upgrade,
+            priority: helm_core::orchestration::TaskPriority::UserInitiated,
+            status: TaskStatus::Failed,
+            created_at: UNIX_EPOCH,
+            started_at: Some(UNIX_EPOCH + Duration::from_secs(2)),
+            finished_at: Some(UNIX_EPOCH + Duration::from_secs(5)),
+            error_message: None,
+            blocked_by: Vec::new(),
+            depends_on: Vec::new(),
+        };
+        let failed = super::AdapterTaskSnapshot {
+            runtime: runtime.clone(),
+            terminal_state: Some(super::AdapterTaskTerminalState::Failed(
+                helm_core::models::CoreError {
+                    manager: Some(ManagerId::Npm),
+                    task: Some(TaskType::Upgrade),
+                    action: None,
+                    kind: helm_core::models::CoreErrorKind::ProcessFailure,
+                    message: "process exited with code 1: npm ERR! 404".to_string(),
+                },
+            )),
+            progress: None,
+        };
+        let mut output = super::map_task_output_record(TaskId(41), None);
+        output.stderr = Some("npm ERR! 404".to_string());
+
+        let result = super::build_ffi_task_wait_result(&failed, false, output);
+        assert!(!result.timed_out);
+        assert_eq!(result.status, "failed");
+        assert_eq!(result.duration_ms, Some(3_000));
+        assert_eq!(result.stderr_tail.as_deref(), Some("npm ERR! 404"));
+        let error = result.error.expect("failure detail");
+        assert_eq!(error.kind, "process_failure");
+        assert_eq!(error.exit_code, Some(1));
+
+        let running = super::AdapterTaskSnapshot {
+            runtime: helm_core::orchestration::TaskRuntimeSnapshot {
+                status: TaskStatus::Running,
+                finished_at: None,
+                ..runtime
+            },
+            terminal_state: None,
+            progress: Some(40),
+        };
+        let result = super::build_ffi_task_wait_result(
+            &running,
+            true,
+            super::map_task_output_record(TaskId(41), None),
+        );
+        assert!(result.timed_out);
+        assert_eq!(result.status, "running");
+        assert_eq!(result.error, None);
+        assert_eq!(result.duration_ms, None);
     }
 
     #[test]
@@ -12708,6 +19999,599 @@ mod tests {
         assert!(!mapped.message.contains("abc123"));
     }
 
+    /// Serializes tests that read the process-global last-error key.
+    static LAST_ERROR_LOCK: Mutex<()> = Mutex::new(());
+
+    fn take_last_error_key() -> Option<String> {
+        let key = super::helm_take_last_error_key();
+        (!key.is_null()).then(|| {
+            unsafe { std::ffi::CString::from_raw(key) }
+                .into_string()
+                .unwrap()
+        })
+    }
+
+    fn with_panic_on_entry<T>(function: &'static str, call: impl FnOnce() -> T) -> T {
+        super::PANIC_ON_ENTRY.with(|hook| hook.set(Some(function)));
+        let result = call();
+        super::PANIC_ON_ENTRY.with(|hook| hook.set(None));
+        result
+    }
+
+    #[test]
+    fn panicking_exports_return_failure_values_and_report_internal_panic() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let info = with_panic_on_entry("helm_get_engine_info", || super::helm_get_engine_info());
+        assert!(info.is_null());
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INTERNAL_PANIC)
+        );
+
+        assert!(!with_panic_on_entry("helm_set_safe_mode", || {
+            super::helm_set_safe_mode(true)
+        }));
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INTERNAL_PANIC)
+        );
+
+        let query = std::ffi::CString::new("ripgrep").unwrap();
+        let task_id = with_panic_on_entry("helm_trigger_remote_search", || unsafe {
+            super::helm_trigger_remote_search(query.as_ptr())
+        });
+        assert_eq!(task_id, -1);
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INTERNAL_PANIC)
+        );
+
+        with_panic_on_entry("helm_free_string", || unsafe {
+            super::helm_free_string(std::ptr::null_mut())
+        });
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INTERNAL_PANIC)
+        );
+    }
+
+    #[test]
+    fn last_error_json_carries_core_error_detail() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let take_json = || {
+            let json = super::helm_take_last_error_json();
+            (!json.is_null()).then(|| {
+                let json = unsafe { std::ffi::CString::from_raw(json) }
+                    .into_string()
+                    .unwrap();
+                serde_json::from_str::<serde_json::Value>(&json).unwrap()
+            })
+        };
+
+        assert_eq!(
+            super::return_core_error_i64(&helm_core::models::CoreError {
+                manager: Some(ManagerId::HomebrewFormula),
+                task: Some(TaskType::Upgrade),
+                action: Some(helm_core::models::ManagerAction::Upgrade),
+                kind: helm_core::models::CoreErrorKind::ProcessFailure,
+                message: "process exited with code 1: Error: No such keg".to_string(),
+            }),
+            -1
+        );
+        let json = take_json().unwrap();
+        assert_eq!(json["key"], super::SERVICE_ERROR_PROCESS_FAILURE);
+        assert_eq!(json["kind"], "process_failure");
+        assert_eq!(json["manager"], "homebrew_formula");
+        assert_eq!(json["action"], "upgrade");
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(json["stderr_excerpt"], "Error: No such keg");
+        assert_eq!(json["retryable"], false);
+        assert_eq!(take_last_error_key(), None);
+
+        super::set_last_error_key(super::SERVICE_ERROR_INVALID_INPUT);
+        assert_eq!(
+            take_json(),
+            Some(serde_json::json!({ "key": super::SERVICE_ERROR_INVALID_INPUT }))
+        );
+        assert_eq!(take_json(), None);
+    }
+
+    #[test]
+    fn caught_panic_leaves_the_boundary_usable() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let key = std::ffi::CString::new("not_a_setting").unwrap();
+        let value = std::ffi::CString::new("true").unwrap();
+        assert!(!with_panic_on_entry("helm_set_setting", || unsafe {
+            super::helm_set_setting(key.as_ptr(), value.as_ptr())
+        }));
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INTERNAL_PANIC)
+        );
+
+        assert!(!unsafe { super::helm_set_setting(key.as_ptr(), value.as_ptr()) });
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+        assert_eq!(take_last_error_key(), None);
+    }
+
+    #[test]
+    fn set_service_state_rejects_unknown_actions() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let service = std::ffi::CString::new("redis").unwrap();
+        let action = std::ffi::CString::new("reload").unwrap();
+        let task_id = unsafe { super::helm_set_service_state(service.as_ptr(), action.as_ptr()) };
+        assert_eq!(task_id, -1);
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn upgrade_package_refuses_os_updates_without_a_confirmation_token() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let manager = std::ffi::CString::new("softwareupdate").unwrap();
+        let package = std::ffi::CString::new(super::CONFIRMED_OS_UPDATES_NAME).unwrap();
+        let task_id = unsafe {
+            super::helm_upgrade_package(
+                manager.as_ptr(),
+                package.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        assert_eq!(task_id, -1);
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn pin_package_with_constraint_rejects_invalid_ranges() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let manager = std::ffi::CString::new("npm").unwrap();
+        let package = std::ffi::CString::new("vite").unwrap();
+        let constraint = std::ffi::CString::new(">=1.0 <<2").unwrap();
+        assert!(!unsafe {
+            super::helm_pin_package_with_constraint(
+                manager.as_ptr(),
+                package.as_ptr(),
+                constraint.as_ptr(),
+            )
+        });
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn export_python_requirements_requires_an_interpreter_and_directory() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dest = std::env::temp_dir();
+        let dest = std::ffi::CString::new(dest.to_string_lossy().as_ref()).unwrap();
+        let relative = std::ffi::CString::new("bin/python3").unwrap();
+        assert!(
+            unsafe { super::helm_export_python_requirements(relative.as_ptr(), dest.as_ptr()) }
+                .is_null()
+        );
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+
+        let missing = std::ffi::CString::new("/nonexistent/helm/python3").unwrap();
+        assert!(
+            unsafe { super::helm_export_python_requirements(missing.as_ptr(), dest.as_ptr()) }
+                .is_null()
+        );
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+
+        let not_python = std::ffi::CString::new("/bin/sh").unwrap();
+        assert!(
+            unsafe { super::helm_export_python_requirements(not_python.as_ptr(), dest.as_ptr()) }
+                .is_null()
+        );
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn set_network_hint_rejects_malformed_json() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let hint = std::ffi::CString::new(r#"{"expensive":"yes"}"#).unwrap();
+        assert!(!unsafe { super::helm_set_network_hint(hint.as_ptr()) });
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn installed_listing_lifts_measured_size_to_the_top_level() {
+        let package = InstalledPackage {
+            package: PackageRef {
+                manager: ManagerId::Npm,
+                name: "typescript".to_string(),
+            },
+            package_identifier: None,
+            installed_version: Some("5.8.3".to_string()),
+            pinned: false,
+            runtime_state: Default::default(),
+            sizes: helm_core::models::PackageSizes {
+                install_size_bytes: Some(23_068_672),
+                download_size_bytes: None,
+            },
+        };
+        let json = super::installed_packages_json(&[package]).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0]["installed_size_bytes"], 23_068_672);
+        assert_eq!(rows[0]["package"]["name"], "typescript");
+        assert_eq!(rows[0]["sizes"]["installSizeBytes"], 23_068_672);
+    }
+
+    #[test]
+    fn measure_package_sizes_rejects_unknown_managers() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let manager = std::ffi::CString::new("not_a_manager").unwrap();
+        let task_id = unsafe { super::helm_measure_package_sizes(manager.as_ptr()) };
+        assert_eq!(task_id, -1);
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn run_cleanup_rejects_unknown_managers() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let manager = std::ffi::CString::new("not_a_manager").unwrap();
+        let task_id = unsafe { super::helm_run_cleanup(manager.as_ptr()) };
+        assert_eq!(task_id, -1);
+        assert_eq!(
+            take_last_error_key().as_deref(),
+            Some(super::SERVICE_ERROR_INVALID_INPUT)
+        );
+    }
+
+    #[test]
+    fn set_setting_rejects_unknown_keys_before_touching_state() {
+        let _lock = LAST_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let key = std::ffi::CString::new("not_a_setting").unwrap();
+        let value = std::ffi::CString::new("true").unwrap();
+        assert!(!unsafe { super::helm_set_setting(key.as_ptr(), value.as_ptr()) });
+
+        let changes = super::helm_list_setting_changes(i64::MAX);
+        assert!(!changes.is_null());
+        let json = unsafe { std::ffi::CString::from_raw(changes) };
+        assert_eq!(json.to_str().unwrap(), "[]");
+    }
+
+    #[test]
+    fn next_scheduled_runs_serialize_auto_check_eta_for_host() {
+        let store = temp_sqlite_store("scheduled-runs");
+        store.migrate_to_latest().expect("migrate");
+        store.set_auto_check_for_updates(true).expect("enable");
+        store
+            .set_auto_check_frequency_minutes(60)
+            .expect("frequency");
+        store
+            .set_auto_check_last_checked_unix(1_000)
+            .expect("last checked");
+
+        let runs = helm_core::scheduled_runs::next_scheduled_runs(&store, 2_000).expect("runs");
+        let json = serde_json::to_value(&runs).expect("json");
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "kind": "auto_check_for_updates",
+                "managerId": null,
+                "etaUnix": 4_600,
+                "intervalSeconds": 3_600,
+                "overdue": false
+            }])
+        );
+    }
+
+    #[test]
+    fn engine_info_serializes_versions_and_adapter_ids() {
+        let info = super::build_ffi_engine_info(helm_engine::EngineInfo {
+            core_version: helm_core::VERSION,
+            engine_version: helm_engine::VERSION,
+            schema_version: 7,
+            latest_schema_version: 9,
+            database_path: std::path::PathBuf::from("/tmp/helm.sqlite3"),
+            uptime: std::time::Duration::from_millis(90_500),
+            adapters: vec![ManagerId::HomebrewFormula, ManagerId::Npm],
+            unknown_manager_ids: vec!["winget".to_string()],
+        });
+        let json = serde_json::to_value(&info).expect("json");
+        assert_eq!(json["ffiVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["coreVersion"], helm_core::VERSION);
+        assert_eq!(json["schemaVersion"], 7);
+        assert_eq!(json["latestSchemaVersion"], 9);
+        assert_eq!(json["databasePath"], "/tmp/helm.sqlite3");
+        assert_eq!(json["uptimeSeconds"], 90);
+        assert_eq!(
+            json["adapters"],
+            serde_json::json!(["homebrew_formula", "npm"])
+        );
+        assert_eq!(json["unknownManagerIds"], serde_json::json!(["winget"]));
+    }
+
+    #[test]
+    fn refresh_summary_reports_failed_managers_with_error_keys() {
+        let run_started_at = UNIX_EPOCH + Duration::from_secs(1_000);
+        let result = |manager, error| helm_core::models::ManagerRefreshResult {
+            manager,
+            error,
+            duration: Duration::from_millis(1_250),
+            run_started_at,
+            finished_at: run_started_at + Duration::from_secs(2),
+        };
+        let summary = super::build_ffi_refresh_summary(
+            vec![
+                result(ManagerId::HomebrewFormula, None),
+                result(
+                    ManagerId::Npm,
+                    Some(helm_core::models::ManagerRefreshError {
+                        kind: helm_core::models::CoreErrorKind::NetworkUnreachable,
+                        message: "registry.npmjs.org unreachable".to_string(),
+                    }),
+                ),
+            ],
+            false,
+            Vec::new(),
+            Vec::new(),
+        );
+
+        let json = serde_json::to_value(&summary).expect("json");
+        assert_eq!(json["runStartedAtUnix"], 1_000);
+        assert_eq!(json["succeeded"], 1);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["managers"][0]["errorKey"], serde_json::Value::Null);
+        assert_eq!(json["managers"][1]["managerId"], "npm");
+        assert_eq!(json["managers"][1]["succeeded"], false);
+        assert_eq!(
+            json["managers"][1]["errorKey"],
+            "service.error.network_unreachable"
+        );
+        assert_eq!(json["managers"][1]["errorCode"], "network_unreachable");
+        assert_eq!(json["managers"][1]["durationMs"], 1_250);
+        assert_eq!(json["managers"][1]["finishedAtUnix"], 1_002);
+        assert_eq!(json["meteredNetwork"], false);
+        assert_eq!(json["suppressedOnMeteredNetwork"], serde_json::json!([]));
+        assert_eq!(json["skippedWhileQuarantined"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn refresh_summary_lists_managers_held_back_on_metered_networks() {
+        let summary = super::build_ffi_refresh_summary(
+            Vec::new(),
+            true,
+            vec![ManagerId::HomebrewFormula, ManagerId::SoftwareUpdate],
+            Vec::new(),
+        );
+        let json = serde_json::to_value(&summary).expect("json");
+        assert_eq!(json["meteredNetwork"], true);
+        assert_eq!(
+            json["suppressedOnMeteredNetwork"],
+            serde_json::json!(["homebrew_formula", "softwareupdate"])
+        );
+    }
+
+    #[test]
+    fn refresh_summary_lists_managers_skipped_while_quarantined() {
+        let summary =
+            super::build_ffi_refresh_summary(Vec::new(), false, Vec::new(), vec![ManagerId::Pip]);
+        let json = serde_json::to_value(&summary).expect("json");
+        assert_eq!(json["suppressedOnMeteredNetwork"], serde_json::json!([]));
+        assert_eq!(json["skippedWhileQuarantined"], serde_json::json!(["pip"]));
+    }
+
+    #[test]
+    fn bulk_operation_progress_latches_pruned_steps_and_estimates_eta() {
+        let now = UNIX_EPOCH + Duration::from_secs(10_000);
+        let task = |id: u64, manager, status| TaskRecord {
+            id: TaskId(id),
+            manager,
+            task_type: TaskType::Upgrade,
+            status,
+            created_at: now - Duration::from_secs(20),
+            attempt_of: None,
+            label: None,
+        };
+        let archived = |id: u64, manager, status, duration_ms| super::TaskHistoryRecord {
+            task_id: TaskId(id),
+            manager,
+            task_type: TaskType::Upgrade,
+            status,
+            exit_code: None,
+            error_code: None,
+            error_message: None,
+            duration_ms: Some(duration_ms),
+            stdout_tail: None,
+            stderr_tail: None,
+            finished_at: now - Duration::from_secs(60),
+        };
+        let mut session = super::BulkOperationSession {
+            started_at: now - Duration::from_secs(120),
+            steps: [1, 2, 3, 4, 5].into_iter().map(|id| (id, None)).collect(),
+        };
+        let history = vec![
+            archived(1, ManagerId::Npm, TaskStatus::Completed, 30_000),
+            archived(
+                50,
+                ManagerId::HomebrewFormula,
+                TaskStatus::Completed,
+                90_000,
+            ),
+        ];
+        let recent = vec![
+            task(2, ManagerId::HomebrewFormula, TaskStatus::Failed),
+            task(3, ManagerId::HomebrewFormula, TaskStatus::Running),
+            task(4, ManagerId::HomebrewFormula, TaskStatus::Queued),
+            task(5, ManagerId::Npm, TaskStatus::Queued),
+        ];
+        super::resolve_bulk_operation_steps(&mut session, &recent, &history);
+
+        // Step 1 was pruned before it was ever listed; step 5 is dismissed.
+        let steps = super::resolve_bulk_operation_steps(&mut session, &recent[..3], &history);
+        let progress = super::build_ffi_bulk_operation_progress(
+            Some(session.started_at),
+            &steps,
+            &history,
+            now,
+        );
+
+        assert_eq!(progress.total_steps, 5);
+        assert_eq!(progress.completed, 1);
+        assert_eq!(progress.failed, 1);
+        assert_eq!(progress.cancelled, 1);
+        assert_eq!(progress.running, 1);
+        assert_eq!(progress.queued, 1);
+        assert_eq!(progress.fraction_complete, 0.6);
+        assert_eq!(progress.running_steps[0].task_id, 3);
+        // Homebrew: 70s left on the running step plus one queued 90s step.
+        assert_eq!(progress.eta_seconds, Some(160));
+    }
+
+    #[test]
+    fn task_output_chunks_payload_names_streams() {
+        let payload = super::build_ffi_task_output_chunks(
+            TaskId(42),
+            helm_core::execution::TaskOutputChunks {
+                chunks: vec![helm_core::execution::TaskOutputChunk {
+                    sequence: 3,
+                    stream: helm_core::execution::TaskOutputStream::Stderr,
+                    text: "==> Pouring ripgrep\n".to_string(),
+                }],
+                next_offset: 3,
+                missed: true,
+                finished: false,
+            },
+        );
+
+        let json = serde_json::to_value(payload).expect("json");
+        assert_eq!(json["taskId"], 42);
+        assert_eq!(json["chunks"][0]["sequence"], 3);
+        assert_eq!(json["chunks"][0]["stream"], "stderr");
+        assert_eq!(json["chunks"][0]["text"], "==> Pouring ripgrep\n");
+        assert_eq!(json["nextOffset"], 3);
+        assert_eq!(json["missedChunks"], true);
+        assert_eq!(json["finished"], false);
+    }
+
+    #[test]
+    fn package_files_payload_flags_truncated_listings() {
+        let package = PackageRef {
+            manager: ManagerId::HomebrewFormula,
+            name: "git".to_string(),
+        };
+        let files = helm_core::models::PackageFiles::from_listing(
+            ["/opt/homebrew/bin/git", "/opt/homebrew/bin/git-shell"].map(str::to_string),
+            Some(1),
+        );
+
+        let json =
+            serde_json::to_value(super::build_ffi_package_files(&package, files)).expect("json");
+        assert_eq!(json["managerId"], "homebrew_formula");
+        assert_eq!(json["packageName"], "git");
+        assert_eq!(json["files"], serde_json::json!(["/opt/homebrew/bin/git"]));
+        assert_eq!(json["total"], 2);
+        assert_eq!(json["truncated"], true);
+    }
+
+    #[test]
+    fn package_info_payload_reports_metadata_and_cache_age() {
+        let package = PackageRef {
+            manager: ManagerId::Npm,
+            name: "typescript".to_string(),
+        };
+        let metadata = helm_core::models::PackageMetadata {
+            description: Some("TypeScript".to_string()),
+            homepage: Some("https://www.typescriptlang.org/".to_string()),
+            license: Some("Apache-2.0".to_string()),
+            installed_size_bytes: Some(22_946_702),
+            dependencies: Vec::new(),
+            aliases: Vec::new(),
+        };
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let json = serde_json::to_value(super::build_ffi_package_info(
+            &package, metadata, fetched_at, true,
+        ))
+        .expect("json");
+        assert_eq!(json["managerId"], "npm");
+        assert_eq!(json["packageName"], "typescript");
+        assert_eq!(json["license"], "Apache-2.0");
+        assert_eq!(json["installedSizeBytes"], 22_946_702);
+        assert_eq!(json["dependencies"], serde_json::json!([]));
+        assert_eq!(json["aliases"], serde_json::json!([]));
+        assert_eq!(json["fetchedAtUnix"], 1_700_000_000);
+        assert_eq!(json["stale"], true);
+
+        let ttl = Duration::from_secs(super::PACKAGE_METADATA_CACHE_TTL_SECS);
+        assert!(super::package_metadata_is_fresh(
+            fetched_at,
+            fetched_at + ttl - Duration::from_secs(1)
+        ));
+        assert!(!super::package_metadata_is_fresh(
+            fetched_at,
+            fetched_at + ttl
+        ));
+    }
+
+    #[test]
+    fn refresh_schedules_payload_reports_next_run() {
+        let schedules = [
+            helm_core::orchestration::RefreshSchedule {
+                manager: ManagerId::HomebrewFormula,
+                interval_secs: 3_600,
+                last_refreshed_unix: Some(1_000),
+            },
+            helm_core::orchestration::RefreshSchedule {
+                manager: ManagerId::SoftwareUpdate,
+                interval_secs: 86_400,
+                last_refreshed_unix: None,
+            },
+        ];
+
+        let json = serde_json::to_value(super::build_ffi_refresh_schedules(&schedules, 2_000))
+            .expect("json");
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "managerId": "homebrew_formula",
+                    "intervalSecs": 3_600,
+                    "lastRefreshedUnix": 1_000,
+                    "nextRunUnix": 4_600,
+                    "overdue": false
+                },
+                {
+                    "managerId": "softwareupdate",
+                    "intervalSecs": 86_400,
+                    "lastRefreshedUnix": null,
+                    "nextRunUnix": 2_000,
+                    "overdue": true
+                }
+            ])
+        );
+    }
+
     #[test]
     fn search_label_key_uses_query_variant_when_query_is_present() {
         assert_eq!(
@@ -12768,6 +20652,122 @@ mod tests {
         ));
     }
 
+    struct MigrationStepAdapter {
+        descriptor: ManagerDescriptor,
+        executed: Arc<Mutex<Vec<(ManagerId, ManagerAction)>>>,
+        cancel_after_install: Option<Arc<super::LocalTaskCancellation>>,
+    }
+
+    impl MigrationStepAdapter {
+        fn new(
+            manager: ManagerId,
+            executed: Arc<Mutex<Vec<(ManagerId, ManagerAction)>>>,
+            cancel_after_install: Option<Arc<super::LocalTaskCancellation>>,
+        ) -> Self {
+            Self {
+                descriptor: ManagerDescriptor {
+                    id: manager,
+                    display_name: "migration-step-adapter",
+                    category: ManagerCategory::Language,
+                    authority: ManagerAuthority::Standard,
+                    capabilities: &[Capability::Install, Capability::Uninstall],
+                },
+                executed,
+                cancel_after_install,
+            }
+        }
+    }
+
+    impl ManagerAdapter for MigrationStepAdapter {
+        fn descriptor(&self) -> &ManagerDescriptor {
+            &self.descriptor
+        }
+
+        fn action_safety(&self, action: ManagerAction) -> ActionSafety {
+            action.safety()
+        }
+
+        fn execute(&self, request: AdapterRequest) -> AdapterResult<AdapterResponse> {
+            let (package, action) = match request {
+                AdapterRequest::Install(request) => (request.package, ManagerAction::Install),
+                AdapterRequest::Uninstall(request) => (request.package, ManagerAction::Uninstall),
+                other => panic!("unexpected migration request: {other:?}"),
+            };
+            self.executed
+                .lock()
+                .expect("executed steps lock")
+                .push((self.descriptor.id, action));
+            if action == ManagerAction::Install
+                && let Some(cancellation) = &self.cancel_after_install
+            {
+                cancellation
+                    .requested
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            Ok(AdapterResponse::Mutation(MutationResult {
+                package,
+                package_identifier: None,
+                action,
+                before_version: None,
+                after_version: None,
+                disposal: None,
+            }))
+        }
+    }
+
+    #[test]
+    fn npm_migration_cancelled_between_steps_never_uninstalls_npm_packages() {
+        let store = temp_sqlite_store("npm-migration-cancel");
+        store.migrate_to_latest().expect("migrate store");
+        let task_id = super::create_local_task(
+            &store,
+            ManagerId::Pnpm,
+            TaskType::Install,
+            super::task_label("service.task.label.migrate.npm_globals", &[]),
+        )
+        .expect("local task should be created");
+
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        let cancellation = Arc::new(super::LocalTaskCancellation::default());
+        let runtime = AdapterRuntime::new(vec![
+            Arc::new(MigrationStepAdapter::new(
+                ManagerId::Pnpm,
+                executed.clone(),
+                Some(cancellation.clone()),
+            )) as Arc<dyn ManagerAdapter>,
+            Arc::new(MigrationStepAdapter::new(
+                ManagerId::Npm,
+                executed.clone(),
+                None,
+            )),
+        ])
+        .expect("adapter runtime should initialize");
+        let tokio_runtime =
+            tokio::runtime::Runtime::new().expect("tokio runtime should initialize");
+
+        super::run_npm_migration_task(
+            &store,
+            &runtime,
+            tokio_runtime.handle(),
+            task_id,
+            ManagerId::Pnpm,
+            &["typescript".to_string(), "eslint".to_string()],
+            cancellation.as_ref(),
+        );
+
+        assert_eq!(
+            *executed.lock().expect("executed steps lock"),
+            vec![(ManagerId::Pnpm, ManagerAction::Install)]
+        );
+        let task = store
+            .list_recent_tasks(10)
+            .expect("task listing should succeed")
+            .into_iter()
+            .find(|task| task.id == task_id)
+            .expect("migration task should exist");
+        assert_eq!(task.status, TaskStatus::Cancelled);
+    }
+
     fn status_for(
         statuses: &[super::FfiManagerStatus],
         manager_id: ManagerId,
@@ -12790,6 +20790,7 @@ mod tests {
             pinned,
             restart_required: false,
             runtime_state: Default::default(),
+            sizes: Default::default(),
         }
     }
 }